@@ -7,7 +7,7 @@
 set -e
 
 # Configuration
-BASE_URL="${NETGATE_URL:-http://localhost:8080}"
+BASE_URL="${NETGATE_URL:-http://localhost:8080/v1}"
 TENANT_COUNT="${TENANT_COUNT:-3}"
 ORDERS_PER_TENANT="${ORDERS_PER_TENANT:-5}"
 DELAY_BETWEEN_REQUESTS="${DELAY_BETWEEN_REQUESTS:-0.5}"
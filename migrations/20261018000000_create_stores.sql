@@ -0,0 +1,17 @@
+-- Order workflows, stored whole as their JSON form
+CREATE TABLE order_workflows (
+    order_id TEXT PRIMARY KEY,
+    workflow JSONB NOT NULL
+);
+
+-- Portal tenants mapped to NetBox tenants and endpoints
+CREATE TABLE tenant_mappings (
+    tenant_id TEXT PRIMARY KEY,
+    netbox_tenant_id INTEGER NOT NULL,
+    endpoint TEXT,
+    -- Encrypted with TENANT_TOKEN_KEY
+    netbox_token TEXT,
+    disabled_at TIMESTAMPTZ,
+    disabled_by TEXT,
+    CHECK ((disabled_at IS NULL) = (disabled_by IS NULL))
+);
@@ -0,0 +1,415 @@
+use poem::Request;
+use poem_openapi::{param::Path, param::Query, payload::Json, ApiResponse, OpenApi};
+use std::sync::Arc;
+
+use crate::business::import::{ImportError, InventoryImporter};
+use crate::business::onboarding::{OnboardingError, TenantOnboardingService};
+use crate::domain::chaos::{ChaosExperiment, ChaosExperimentRequest, ChaosStatus};
+use crate::domain::import::ImportJob;
+use crate::domain::onboarding::{OnboardTenantRequest, OnboardingStatus, TenantOnboarding};
+use crate::resilience::ChaosInjector;
+use crate::security::{require_role, ADMIN_ROLE};
+
+/// Operator-only maintenance endpoints
+pub struct AdminApi {
+    importer: Option<Arc<InventoryImporter>>,
+    chaos: Option<Arc<ChaosInjector>>,
+    onboarding: Option<Arc<TenantOnboardingService>>,
+}
+
+impl AdminApi {
+    pub fn new() -> Self {
+        Self {
+            importer: None,
+            chaos: None,
+            onboarding: None,
+        }
+    }
+
+    /// Allow importing tenants' existing NetBox inventory
+    pub fn with_importer(mut self, importer: Arc<InventoryImporter>) -> Self {
+        self.importer = Some(importer);
+        self
+    }
+
+    /// Allow running fault injection experiments against NetBox calls
+    pub fn with_chaos(mut self, chaos: Arc<ChaosInjector>) -> Self {
+        self.chaos = Some(chaos);
+        self
+    }
+
+    /// Allow onboarding tenants in one operation
+    pub fn with_onboarding(mut self, onboarding: Arc<TenantOnboardingService>) -> Self {
+        self.onboarding = Some(onboarding);
+        self
+    }
+}
+
+impl Default for AdminApi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(ApiResponse)]
+pub enum StartImportResponse {
+    #[oai(status = 202)]
+    Accepted(Json<ImportJob>),
+
+    #[oai(status = 404)]
+    NotFound(Json<serde_json::Value>),
+
+    #[oai(status = 409)]
+    Conflict(Json<serde_json::Value>),
+
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+pub enum ImportJobResponse {
+    #[oai(status = 200)]
+    Ok(Json<ImportJob>),
+
+    #[oai(status = 404)]
+    NotFound,
+}
+
+#[derive(ApiResponse)]
+pub enum ChaosStatusResponse {
+    #[oai(status = 200)]
+    Ok(Json<ChaosStatus>),
+
+    #[oai(status = 404)]
+    NotFound(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+pub enum StartChaosResponse {
+    #[oai(status = 201)]
+    Created(Json<ChaosExperiment>),
+
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+
+    #[oai(status = 404)]
+    NotFound(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+pub enum StopChaosResponse {
+    #[oai(status = 204)]
+    Stopped,
+
+    #[oai(status = 404)]
+    NotFound(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+pub enum OnboardTenantResponse {
+    #[oai(status = 201)]
+    Created(Json<TenantOnboarding>),
+
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+
+    #[oai(status = 409)]
+    Conflict(Json<serde_json::Value>),
+
+    /// A step failed; the record shows what was rolled back
+    #[oai(status = 500)]
+    RolledBack(Json<TenantOnboarding>),
+
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<serde_json::Value>),
+}
+
+const CHAOS_DISABLED: &str = "Fault injection is not enabled";
+
+fn error_json(message: &str) -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "error": message }))
+}
+
+#[OpenApi]
+impl AdminApi {
+    /// Import a tenant's existing NetBox sites and devices (requires the admin role)
+    ///
+    /// Runs in the background; poll the returned job. Each site and device becomes a
+    /// Completed order, and `virtual_resources=true` also maps a virtual resource to it.
+    /// Resources already known are skipped, and a failed job is resumed where it stopped.
+    #[oai(path = "/admin/import", method = "post")]
+    async fn start_import(
+        &self,
+        req: &Request,
+        tenant: Query<String>,
+        virtual_resources: Query<Option<bool>>,
+    ) -> Result<StartImportResponse, poem::Error> {
+        require_role(req, ADMIN_ROLE)?;
+
+        let Some(ref importer) = self.importer else {
+            return Ok(StartImportResponse::ServiceUnavailable(error_json("NetBox is not configured")));
+        };
+        Ok(match importer.start(&tenant.0, virtual_resources.0.unwrap_or(false)) {
+            Ok(job) => StartImportResponse::Accepted(Json(job)),
+            Err(e @ ImportError::UnknownTenant(_)) => StartImportResponse::NotFound(error_json(&e.to_string())),
+            Err(e @ ImportError::AlreadyRunning(_)) => StartImportResponse::Conflict(error_json(&e.to_string())),
+        })
+    }
+
+    /// Get the progress of an import job (requires the admin role)
+    #[oai(path = "/admin/import/:job_id", method = "get")]
+    async fn get_import_job(&self, req: &Request, job_id: Path<String>) -> Result<ImportJobResponse, poem::Error> {
+        require_role(req, ADMIN_ROLE)?;
+
+        Ok(match self.importer.as_ref().and_then(|importer| importer.get_job(&job_id.0)) {
+            Some(job) => ImportJobResponse::Ok(Json(job)),
+            None => ImportJobResponse::NotFound,
+        })
+    }
+
+    /// Onboard a tenant (requires the admin role)
+    ///
+    /// Creates the NetBox tenant, copies the transformation profile from
+    /// `profile_template`, creates `default_virtual_site`, and registers the
+    /// tenant mapping, in that order. If a step fails, the ones before it are
+    /// undone and the record is returned with status `rolled_back`.
+    #[oai(path = "/admin/tenants/onboard", method = "post")]
+    async fn onboard_tenant(&self, req: &Request, body: Json<OnboardTenantRequest>) -> Result<OnboardTenantResponse, poem::Error> {
+        let user = require_role(req, ADMIN_ROLE)?;
+
+        let Some(ref onboarding) = self.onboarding else {
+            return Ok(OnboardTenantResponse::ServiceUnavailable(error_json("NetBox is not configured")));
+        };
+        Ok(match onboarding.onboard(body.0, user).await {
+            Ok(record) if record.status == OnboardingStatus::Completed => OnboardTenantResponse::Created(Json(record)),
+            Ok(record) => OnboardTenantResponse::RolledBack(Json(record)),
+            Err(e @ OnboardingError::Invalid(_)) => OnboardTenantResponse::BadRequest(error_json(&e.to_string())),
+            Err(e @ OnboardingError::AlreadyExists(_)) => OnboardTenantResponse::Conflict(error_json(&e.to_string())),
+        })
+    }
+
+    /// List tenant onboardings, oldest first (requires the admin role)
+    #[oai(path = "/admin/tenants/onboardings", method = "get")]
+    async fn list_onboardings(&self, req: &Request) -> Result<Json<Vec<TenantOnboarding>>, poem::Error> {
+        require_role(req, ADMIN_ROLE)?;
+
+        Ok(Json(self.onboarding.as_ref().map(|onboarding| onboarding.onboardings()).unwrap_or_default()))
+    }
+
+    /// Get the running fault injection experiment (requires the admin role)
+    ///
+    /// 404 unless fault injection is enabled with CHAOS_ENABLED and NETGATE_ALLOW_FAULT_INJECTION.
+    #[oai(path = "/admin/chaos", method = "get")]
+    async fn get_chaos(&self, req: &Request) -> Result<ChaosStatusResponse, poem::Error> {
+        require_role(req, ADMIN_ROLE)?;
+
+        Ok(match self.chaos {
+            Some(ref chaos) => ChaosStatusResponse::Ok(Json(ChaosStatus {
+                experiment: chaos.current(),
+            })),
+            None => ChaosStatusResponse::NotFound(error_json(CHAOS_DISABLED)),
+        })
+    }
+
+    /// Inject failures or latency into NetBox calls for a while (requires the admin role)
+    ///
+    /// Replaces any running experiment. Faults apply to each attempt of the listed
+    /// client operations, inside retries and the circuit breaker, and are counted
+    /// in the `injected_*` NetBox metrics. The experiment stops itself after
+    /// `duration_secs`, at most an hour.
+    #[oai(path = "/admin/chaos", method = "post")]
+    async fn start_chaos(
+        &self,
+        req: &Request,
+        body: Json<ChaosExperimentRequest>,
+    ) -> Result<StartChaosResponse, poem::Error> {
+        let user = require_role(req, ADMIN_ROLE)?;
+
+        let Some(ref chaos) = self.chaos else {
+            return Ok(StartChaosResponse::NotFound(error_json(CHAOS_DISABLED)));
+        };
+        Ok(match chaos.start(body.0) {
+            Ok(experiment) => {
+                tracing::warn!("{} started fault injection experiment {}", user, experiment.experiment_id);
+                StartChaosResponse::Created(Json(experiment))
+            }
+            Err(e) => StartChaosResponse::BadRequest(error_json(&e.to_string())),
+        })
+    }
+
+    /// Stop the running fault injection experiment (requires the admin role)
+    #[oai(path = "/admin/chaos", method = "delete")]
+    async fn stop_chaos(&self, req: &Request) -> Result<StopChaosResponse, poem::Error> {
+        require_role(req, ADMIN_ROLE)?;
+
+        Ok(match self.chaos {
+            Some(ref chaos) => {
+                chaos.stop();
+                StopChaosResponse::Stopped
+            }
+            None => StopChaosResponse::NotFound(error_json(CHAOS_DISABLED)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::business::WorkflowManager;
+    use crate::config::Config;
+    use crate::domain::import::ImportJobStatus;
+    use crate::netbox::{NetBoxClient, ResilientNetBoxClient};
+    use crate::security::TenantMappingService;
+    use serde_json::json;
+    use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+    fn admin_request() -> Request {
+        Request::builder()
+            .header("X-User-Id", "ops")
+            .header("X-Roles", "admin")
+            .finish()
+    }
+
+    #[tokio::test]
+    async fn test_start_import_and_poll_job() {
+        let server = MockServer::start().await;
+        for endpoint in ["/api/dcim/sites/", "/api/dcim/devices/"] {
+            Mock::given(method("GET"))
+                .and(path(endpoint))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "count": 1,
+                    "next": null,
+                    "previous": null,
+                    "results": [{ "id": 1, "name": "existing" }]
+                })))
+                .mount(&server)
+                .await;
+        }
+        let config = Config {
+            netbox_url: server.uri(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let client = Arc::new(ResilientNetBoxClient::new(Arc::new(NetBoxClient::new(config).unwrap())));
+        let mappings = Arc::new(TenantMappingService::new());
+        mappings.register_mapping("acme".to_string(), 5);
+        let manager = Arc::new(WorkflowManager::new());
+        let api = AdminApi::new().with_importer(Arc::new(InventoryImporter::new(manager.clone(), client, mappings)));
+
+        let unknown = api
+            .start_import(&admin_request(), Query("other".to_string()), Query(None))
+            .await
+            .unwrap();
+        assert!(matches!(unknown, StartImportResponse::NotFound(_)));
+
+        let job = match api
+            .start_import(&admin_request(), Query("acme".to_string()), Query(None))
+            .await
+            .unwrap()
+        {
+            StartImportResponse::Accepted(Json(job)) => job,
+            _ => panic!("Expected Accepted response"),
+        };
+
+        let finished = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            loop {
+                if let ImportJobResponse::Ok(Json(job)) =
+                    api.get_import_job(&admin_request(), Path(job.job_id.clone())).await.unwrap()
+                {
+                    if job.status != ImportJobStatus::Running {
+                        return job;
+                    }
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("import job did not finish");
+        assert_eq!(finished.status, ImportJobStatus::Completed);
+        assert_eq!((finished.sites_imported, finished.devices_imported), (1, 1));
+        assert_eq!(manager.get_tenant_orders("acme").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_import_requires_admin_role() {
+        let api = AdminApi::new();
+        let not_admin = Request::builder().header("X-User-Id", "alice").header("X-Roles", "approver").finish();
+
+        let result = api.start_import(&not_admin, Query("acme".to_string()), Query(None)).await;
+        assert_eq!(result.err().unwrap().status(), poem::http::StatusCode::FORBIDDEN);
+        let missing = api.get_import_job(&admin_request(), Path("nope".to_string())).await.unwrap();
+        assert!(matches!(missing, ImportJobResponse::NotFound));
+    }
+
+    #[tokio::test]
+    async fn test_chaos_experiment_lifecycle() {
+        let disabled = AdminApi::new();
+        assert!(matches!(
+            disabled.get_chaos(&admin_request()).await.unwrap(),
+            ChaosStatusResponse::NotFound(_)
+        ));
+
+        let api = AdminApi::new().with_chaos(Arc::new(ChaosInjector::new()));
+        let request = |failure_rate| ChaosExperimentRequest {
+            operations: vec!["create_site".to_string()],
+            failure_rate: Some(failure_rate),
+            latency_ms: None,
+            error: None,
+            duration_secs: 60,
+        };
+        let invalid = api.start_chaos(&admin_request(), Json(request(2.0))).await.unwrap();
+        assert!(matches!(invalid, StartChaosResponse::BadRequest(_)));
+        let started = match api.start_chaos(&admin_request(), Json(request(0.5))).await.unwrap() {
+            StartChaosResponse::Created(Json(experiment)) => experiment,
+            _ => panic!("Expected Created response"),
+        };
+
+        match api.get_chaos(&admin_request()).await.unwrap() {
+            ChaosStatusResponse::Ok(Json(status)) => assert_eq!(status.experiment, Some(started)),
+            _ => panic!("Expected Ok response"),
+        }
+        assert!(matches!(api.stop_chaos(&admin_request()).await.unwrap(), StopChaosResponse::Stopped));
+        match api.get_chaos(&admin_request()).await.unwrap() {
+            ChaosStatusResponse::Ok(Json(status)) => assert!(status.experiment.is_none()),
+            _ => panic!("Expected Ok response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_onboard_tenant() {
+        use crate::domain::tenant::NewNetBoxTenant;
+        use crate::netbox::fake::FakeNetBox;
+        use crate::netbox::{NetBoxEndpoint, NetBoxRouter};
+        use crate::security::tenant::DEFAULT_NETBOX_ENDPOINT;
+
+        let request = || OnboardTenantRequest {
+            tenant_id: "acme".to_string(),
+            netbox_tenant: NewNetBoxTenant { name: "Acme".to_string(), slug: "acme".to_string(), description: None },
+            netbox_endpoint: None,
+            profile_template: None,
+            default_virtual_site: None,
+        };
+        let disabled = AdminApi::new().onboard_tenant(&admin_request(), Json(request())).await.unwrap();
+        assert!(matches!(disabled, OnboardTenantResponse::ServiceUnavailable(_)));
+
+        let fake = FakeNetBox::start().await;
+        let mappings = Arc::new(TenantMappingService::new());
+        let endpoint = NetBoxEndpoint::new(DEFAULT_NETBOX_ENDPOINT, &fake.config(), fake.client());
+        let router = Arc::new(NetBoxRouter::new(endpoint, mappings.clone()));
+        let api = AdminApi::new().with_onboarding(Arc::new(TenantOnboardingService::new(
+            router,
+            mappings.clone(),
+            Default::default(),
+        )));
+
+        let record = match api.onboard_tenant(&admin_request(), Json(request())).await.unwrap() {
+            OnboardTenantResponse::Created(Json(record)) => record,
+            _ => panic!("Expected Created response"),
+        };
+        assert_eq!(record.requested_by, "ops");
+        assert!(mappings.has_mapping(&"acme".to_string()));
+        let again = api.onboard_tenant(&admin_request(), Json(request())).await.unwrap();
+        assert!(matches!(again, OnboardTenantResponse::Conflict(_)));
+        assert_eq!(api.list_onboardings(&admin_request()).await.unwrap().0, vec![record]);
+    }
+}
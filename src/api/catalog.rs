@@ -0,0 +1,216 @@
+use poem::Request;
+use poem_openapi::{payload::Json, ApiResponse, Object, OpenApi};
+use std::sync::Arc;
+
+use crate::business::catalog::DeviceCatalog;
+use crate::error::AppError;
+use crate::netbox::{NetBoxDeviceRole, NetBoxDeviceType};
+use crate::security::extract_tenant_id;
+
+/// NetBox's device types and roles, for building order forms
+pub struct CatalogApi {
+    catalog: Option<Arc<DeviceCatalog>>,
+}
+
+impl CatalogApi {
+    pub fn new() -> Self {
+        Self { catalog: None }
+    }
+
+    pub fn with_catalog(mut self, catalog: Arc<DeviceCatalog>) -> Self {
+        self.catalog = Some(catalog);
+        self
+    }
+
+    fn catalog(&self) -> Result<&DeviceCatalog, AppError> {
+        self.catalog
+            .as_deref()
+            .ok_or_else(|| AppError::ServiceUnavailable("NetBox integration is not configured".to_string()))
+    }
+}
+
+impl Default for CatalogApi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Manufacturer of a device type
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Object)]
+pub struct ManufacturerSummary {
+    pub id: i32,
+    pub name: String,
+    pub slug: String,
+}
+
+/// A device type that device orders may name
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Object)]
+pub struct DeviceTypeSummary {
+    pub id: i32,
+    pub model: String,
+    pub slug: String,
+    pub manufacturer: Option<ManufacturerSummary>,
+    pub part_number: Option<String>,
+    pub u_height: Option<f64>,
+}
+
+impl DeviceTypeSummary {
+    fn from_netbox(device_type: &NetBoxDeviceType) -> Option<Self> {
+        Some(Self {
+            id: device_type.id?,
+            model: device_type.model.clone(),
+            slug: device_type.slug.clone(),
+            manufacturer: device_type.manufacturer.as_ref().map(|manufacturer| ManufacturerSummary {
+                id: manufacturer.id,
+                name: manufacturer.name.clone(),
+                slug: manufacturer.slug.clone(),
+            }),
+            part_number: device_type.part_number.clone(),
+            u_height: device_type.u_height,
+        })
+    }
+}
+
+/// A device role that device orders may name
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Object)]
+pub struct DeviceRoleSummary {
+    pub id: i32,
+    pub name: String,
+    pub slug: String,
+    pub color: Option<String>,
+    pub description: Option<String>,
+}
+
+impl DeviceRoleSummary {
+    fn from_netbox(role: &NetBoxDeviceRole) -> Option<Self> {
+        Some(Self {
+            id: role.id?,
+            name: role.name.clone(),
+            slug: role.slug.clone(),
+            color: role.color.clone(),
+            description: role.description.clone(),
+        })
+    }
+}
+
+#[derive(ApiResponse)]
+pub enum DeviceTypesResponse {
+    #[oai(status = 200)]
+    Ok(Json<Vec<DeviceTypeSummary>>),
+
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+pub enum DeviceRolesResponse {
+    #[oai(status = 200)]
+    Ok(Json<Vec<DeviceRoleSummary>>),
+
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<serde_json::Value>),
+}
+
+fn error_body(error: &AppError) -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "error": error.to_string() }))
+}
+
+#[OpenApi]
+impl CatalogApi {
+    /// List the device types that device orders accept, by id, slug or model
+    #[oai(path = "/catalog/device-types", method = "get")]
+    async fn device_types(&self, req: &Request) -> Result<DeviceTypesResponse, poem::Error> {
+        extract_tenant_id(req)?;
+        let snapshot = match self.catalog() {
+            Ok(catalog) => catalog.snapshot().await,
+            Err(e) => Err(e),
+        };
+        match snapshot {
+            Ok(snapshot) => Ok(DeviceTypesResponse::Ok(Json(
+                snapshot.device_types.iter().filter_map(DeviceTypeSummary::from_netbox).collect(),
+            ))),
+            Err(e) if e.is_unavailable() => {
+                Ok(DeviceTypesResponse::ServiceUnavailable(error_body(&e)))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// List the device roles that device orders accept, by id, slug or name
+    #[oai(path = "/catalog/device-roles", method = "get")]
+    async fn device_roles(&self, req: &Request) -> Result<DeviceRolesResponse, poem::Error> {
+        extract_tenant_id(req)?;
+        let snapshot = match self.catalog() {
+            Ok(catalog) => catalog.snapshot().await,
+            Err(e) => Err(e),
+        };
+        match snapshot {
+            Ok(snapshot) => Ok(DeviceRolesResponse::Ok(Json(
+                snapshot.device_roles.iter().filter_map(DeviceRoleSummary::from_netbox).collect(),
+            ))),
+            Err(e) if e.is_unavailable() => {
+                Ok(DeviceRolesResponse::ServiceUnavailable(error_body(&e)))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::netbox::{NetBoxClient, ResilientNetBoxClient};
+    use serde_json::json;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_device_types_include_manufacturer() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/device-types/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "results": [{
+                "id": 4,
+                "model": "C9300-48P",
+                "slug": "cisco-c9300-48p",
+                "u_height": 1.0,
+                "manufacturer": { "id": 1, "name": "Cisco", "slug": "cisco" }
+            }]})))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/device-roles/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "results": [
+                { "id": 1, "name": "Leaf Switch", "slug": "leaf-switch", "color": "2196f3" }
+            ]})))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let config = Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let client = Arc::new(ResilientNetBoxClient::new(Arc::new(NetBoxClient::new(config).unwrap())));
+        let api = CatalogApi::new().with_catalog(Arc::new(DeviceCatalog::new(client)));
+        let req = Request::builder().header("X-Tenant-Id", "tenant-1").finish();
+
+        let DeviceTypesResponse::Ok(Json(types)) = api.device_types(&req).await.unwrap() else {
+            panic!("Expected device types");
+        };
+        assert_eq!(types.len(), 1);
+        assert_eq!(types[0].slug, "cisco-c9300-48p");
+        assert_eq!(types[0].manufacturer.as_ref().unwrap().name, "Cisco");
+
+        // Served from the catalog loaded for the device types
+        let DeviceRolesResponse::Ok(Json(roles)) = api.device_roles(&req).await.unwrap() else {
+            panic!("Expected device roles");
+        };
+        assert_eq!(roles[0].name, "Leaf Switch");
+
+        let anonymous = Request::builder().finish();
+        assert!(api.device_roles(&anonymous).await.is_err());
+    }
+}
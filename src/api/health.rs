@@ -3,25 +3,89 @@ use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::timeout;
 
-use crate::netbox::ResilientNetBoxClient;
-use crate::resilience::CircuitState;
+use crate::business::{MaintenanceWindows, OrderState, WorkflowManager};
+#[cfg(feature = "postgres")]
+use crate::database::Database;
+use crate::netbox::transport::PoolConfig;
+use crate::netbox::{NetBoxEndpoint, NetBoxRouter, ResilientNetBoxClient, TokenScope};
+use crate::resilience::{CircuitState, RecoveryProbeStatus};
+use crate::sync::{SyncService, SyncStatus};
+use crate::warmup::{Readiness, ReadinessGate};
 
 pub struct HealthApi {
     netbox_client: Option<Arc<ResilientNetBoxClient>>,
+    workflow_manager: Option<(Arc<WorkflowManager>, Duration)>,
+    netbox_router: Option<Arc<NetBoxRouter>>,
+    readiness: Option<Arc<ReadinessGate>>,
+    sync: Option<Arc<SyncService>>,
+    maintenance: Option<Arc<MaintenanceWindows>>,
+    #[cfg(feature = "postgres")]
+    database: Option<Database>,
 }
 
 impl HealthApi {
     pub fn new() -> Self {
         Self {
             netbox_client: None,
+            workflow_manager: None,
+            netbox_router: None,
+            readiness: None,
+            sync: None,
+            maintenance: None,
+            #[cfg(feature = "postgres")]
+            database: None,
         }
     }
 
     pub fn with_netbox_client(netbox_client: Arc<ResilientNetBoxClient>) -> Self {
         Self {
             netbox_client: Some(netbox_client),
+            workflow_manager: None,
+            netbox_router: None,
+            readiness: None,
+            sync: None,
+            maintenance: None,
+            #[cfg(feature = "postgres")]
+            database: None,
         }
     }
+
+    /// Report orders that have been processing for longer than `stuck_after`
+    pub fn with_workflow_manager(mut self, workflow_manager: Arc<WorkflowManager>, stuck_after: Duration) -> Self {
+        self.workflow_manager = Some((workflow_manager, stuck_after));
+        self
+    }
+
+    /// Report warming on /health/ready until `readiness` opens
+    pub fn with_readiness(mut self, readiness: Arc<ReadinessGate>) -> Self {
+        self.readiness = Some(readiness);
+        self
+    }
+
+    /// Report how the read model sync is doing
+    pub fn with_sync(mut self, sync: Arc<SyncService>) -> Self {
+        self.sync = Some(sync);
+        self
+    }
+
+    /// Report whether a maintenance freeze is on
+    pub fn with_maintenance_windows(mut self, windows: Arc<MaintenanceWindows>) -> Self {
+        self.maintenance = Some(windows);
+        self
+    }
+
+    /// Also check the named NetBox endpoints tenants are routed to
+    pub fn with_netbox_router(mut self, router: Arc<NetBoxRouter>) -> Self {
+        self.netbox_router = Some(router);
+        self
+    }
+
+    /// Check the database workflows and tenant mappings are kept in
+    #[cfg(feature = "postgres")]
+    pub fn with_database(mut self, database: Database) -> Self {
+        self.database = Some(database);
+        self
+    }
 }
 
 impl Default for HealthApi {
@@ -38,12 +102,105 @@ pub struct HealthStatus {
     pub version: String,
     pub timestamp: String,
     pub netbox: Option<NetBoxHealth>,
+    /// NetBox release detected from `/api/status/`
+    pub netbox_version: Option<String>,
+    /// `valid` or `invalid`: whether NetBox accepts the configured token
+    pub netbox_auth: Option<String>,
+    /// When NetBox first rejected the token, while it keeps failing
+    pub netbox_auth_failed_at: Option<String>,
+    /// `NetBox maintenance suspected` while most NetBox calls failing since the
+    /// last success got an HTML 502/503/504, such as a proxy's maintenance page
+    pub netbox_maintenance: Option<String>,
+    /// `read-only`, `read-write` or `undetermined`, once the token's scope was probed
+    pub netbox_token_scope: Option<String>,
     pub circuit_breaker: Option<CircuitBreakerHealth>,
+    /// Background probing of NetBox while the circuit is open, when enabled
+    pub recovery_probe: Option<RecoveryProbeHealth>,
+    /// Connection pool settings for NetBox, for debugging connection churn
+    pub netbox_pool: Option<NetBoxPoolHealth>,
+    /// Named NetBox endpoints besides the default one described above
+    pub netbox_endpoints: Option<Vec<NetBoxEndpointHealth>>,
+    pub stuck_orders: Option<StuckOrdersHealth>,
+    /// Runs mirroring sites and devices into the read model, when enabled
+    pub sync: Option<SyncStatus>,
+    /// Only with maintenance windows configured
+    pub maintenance_freeze: Option<MaintenanceFreezeHealth>,
+    /// Only with DATABASE_URL set
+    pub database: Option<DatabaseHealth>,
+}
+
+/// Whether the database workflows and tenant mappings are kept in answers
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct DatabaseHealth {
+    pub connected: bool,
+    pub response_time_ms: Option<u64>,
+    pub error: Option<String>,
+    /// Most connections kept open, from DATABASE_POOL_SIZE
+    pub pool_size: u32,
+    pub idle_connections: u32,
+}
+
+/// Connectivity, token and circuit breaker state of one named NetBox endpoint
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct NetBoxEndpointHealth {
+    pub name: String,
+    pub url: String,
+    pub netbox: NetBoxHealth,
+    /// `valid` or `invalid`, as for the default endpoint
+    pub netbox_auth: String,
+    pub circuit_breaker: CircuitBreakerHealth,
+}
+
+impl NetBoxEndpointHealth {
+    fn is_healthy(&self) -> bool {
+        self.netbox.connected && self.netbox_auth == "valid" && self.circuit_breaker.state != "Open"
+    }
+}
+
+/// Configured NetBox connection pool; durations are absent when disabled
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct NetBoxPoolHealth {
+    pub max_idle_per_host: u64,
+    pub idle_timeout_secs: Option<u64>,
+    pub tcp_keepalive_secs: Option<u64>,
+    /// `auto`, `off` or `prior_knowledge`
+    pub http2: String,
+}
+
+impl From<&PoolConfig> for NetBoxPoolHealth {
+    fn from(pool: &PoolConfig) -> Self {
+        Self {
+            max_idle_per_host: pool.max_idle_per_host as u64,
+            idle_timeout_secs: pool.idle_timeout.map(|d| d.as_secs()),
+            tcp_keepalive_secs: pool.tcp_keepalive.map(|d| d.as_secs()),
+            http2: pool.http2.as_str().to_string(),
+        }
+    }
+}
+
+/// Whether orders are frozen for maintenance right now
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct MaintenanceFreezeHealth {
+    pub active: bool,
+    /// When the freeze ends
+    pub until: Option<String>,
+    /// `park` or `reject`
+    pub mode: String,
+}
+
+/// Orders that have sat in Processing longer than the threshold
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct StuckOrdersHealth {
+    pub threshold_secs: u64,
+    pub count: u64,
+    pub order_ids: Vec<String>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
 pub struct NetBoxHealth {
     pub connected: bool,
+    /// True when NetBox could not be reached and fallback data was served instead
+    pub degraded: bool,
     pub response_time_ms: Option<u64>,
     pub error: Option<String>,
 }
@@ -54,6 +211,44 @@ pub struct CircuitBreakerHealth {
     pub failure_count: u32,
 }
 
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct RecoveryProbeHealth {
+    /// Whether probes are running because the circuit isn't closed
+    pub active: bool,
+    pub consecutive_successes: u32,
+    pub consecutive_failures: u32,
+    pub last_probe_at: Option<String>,
+}
+
+impl From<RecoveryProbeStatus> for RecoveryProbeHealth {
+    fn from(status: RecoveryProbeStatus) -> Self {
+        Self {
+            active: status.active,
+            consecutive_successes: status.consecutive_successes,
+            consecutive_failures: status.consecutive_failures,
+            last_probe_at: status.last_probe_at.map(|at| at.to_rfc3339()),
+        }
+    }
+}
+
+/// Whether the replica has finished warming up
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct ReadinessStatus {
+    /// `warming` or `ready`
+    pub status: String,
+    /// What warm-up couldn't do, e.g. tenants it couldn't reach or a passed deadline
+    pub warnings: Vec<String>,
+}
+
+#[derive(ApiResponse)]
+pub enum ReadinessResponse {
+    #[oai(status = 200)]
+    Ready(Json<ReadinessStatus>),
+
+    #[oai(status = 503)]
+    Warming(Json<ReadinessStatus>),
+}
+
 #[derive(ApiResponse)]
 pub enum HealthResponse {
     #[oai(status = 200)]
@@ -79,7 +274,19 @@ impl HealthApi {
             version: "1.0.0".to_string(),
             timestamp: chrono::Utc::now().to_rfc3339(),
             netbox: None,
+            netbox_version: None,
+            netbox_auth: None,
+            netbox_auth_failed_at: None,
+            netbox_maintenance: None,
+            netbox_token_scope: None,
             circuit_breaker: None,
+            recovery_probe: None,
+            netbox_pool: None,
+            netbox_endpoints: None,
+            stuck_orders: None,
+            sync: None,
+            maintenance_freeze: None,
+            database: None,
         };
 
         // Check NetBox connectivity if client is available
@@ -91,6 +298,33 @@ impl HealthApi {
                 health.status = "degraded".to_string();
             }
 
+            health.netbox_version = timeout(Duration::from_secs(2), client.netbox_version())
+                .await
+                .ok()
+                .flatten()
+                .map(|version| version.to_string());
+
+            // Read after the connectivity check, which may have just hit an auth failure
+            match client.auth_failed_at() {
+                Some(failed_at) => {
+                    health.netbox_auth = Some("invalid".to_string());
+                    health.netbox_auth_failed_at = Some(failed_at.to_rfc3339());
+                    health.status = "degraded".to_string();
+                }
+                None => health.netbox_auth = Some("valid".to_string()),
+            }
+            if client.maintenance_suspected() {
+                health.netbox_maintenance = Some("NetBox maintenance suspected".to_string());
+                health.status = "degraded".to_string();
+            }
+            if let Some(scope) = client.token_scope() {
+                // Orders are refused with a read-only token
+                if scope == TokenScope::ReadOnly {
+                    health.status = "degraded".to_string();
+                }
+                health.netbox_token_scope = Some(scope.to_string());
+            }
+
             // Get circuit breaker state
             let cb_state = client.circuit_breaker_state();
             let cb_health = CircuitBreakerHealth {
@@ -98,12 +332,60 @@ impl HealthApi {
                 failure_count: client.circuit_breaker_failure_count(),
             };
             health.circuit_breaker = Some(cb_health);
+            health.recovery_probe = client.recovery_probe_status().map(Into::into);
+            health.netbox_pool = Some(client.pool_config().into());
 
             if cb_state == CircuitState::Open {
                 health.status = "degraded".to_string();
             }
         }
 
+        if let Some(ref router) = self.netbox_router {
+            let mut endpoints = Vec::new();
+            for endpoint in router.endpoints().iter().skip(1) {
+                endpoints.push(check_endpoint_health(endpoint).await);
+            }
+            if endpoints.iter().any(|endpoint| !endpoint.is_healthy()) {
+                health.status = "degraded".to_string();
+            }
+            health.netbox_endpoints = Some(endpoints);
+        }
+
+        if let Some((ref manager, stuck_after)) = self.workflow_manager {
+            let stuck = manager.get_orders_stuck_in_state(OrderState::Processing, stuck_after);
+            if !stuck.is_empty() {
+                health.status = "degraded".to_string();
+            }
+            health.stuck_orders = Some(StuckOrdersHealth {
+                threshold_secs: stuck_after.as_secs(),
+                count: stuck.len() as u64,
+                order_ids: stuck.into_iter().map(|w| w.order_id).collect(),
+            });
+        }
+
+        // Workflows and tenant mappings can't be stored without the database
+        #[cfg(feature = "postgres")]
+        if let Some(ref database) = self.database {
+            let database_health = check_database_health(database).await;
+            if !database_health.connected {
+                health.status = "degraded".to_string();
+            }
+            health.database = Some(database_health);
+        }
+
+        // A stale read model doesn't make the service unhealthy; reads fall back to NetBox
+        health.sync = self.sync.as_ref().map(|sync| sync.status());
+
+        // A freeze is planned, so it doesn't degrade the status either
+        health.maintenance_freeze = self.maintenance.as_ref().map(|windows| {
+            let until = windows.freeze_until(chrono::Utc::now());
+            MaintenanceFreezeHealth {
+                active: until.is_some(),
+                until: until.map(|until| until.to_rfc3339()),
+                mode: windows.mode.as_str().to_string(),
+            }
+        });
+
         // Determine response status
         if health.status == "healthy" {
             HealthResponse::Ok(Json(health))
@@ -111,6 +393,44 @@ impl HealthApi {
             HealthResponse::ServiceUnavailable(Json(health))
         }
     }
+
+    /// Readiness for load balancers
+    ///
+    /// 503 while the cache warm-up runs; ready once it is done or its
+    /// deadline passed, listing anything it couldn't do as warnings.
+    #[oai(path = "/health/ready", method = "get")]
+    async fn ready(&self) -> ReadinessResponse {
+        match self.readiness.as_ref().map(|gate| gate.state()) {
+            Some(Readiness::Warming) => ReadinessResponse::Warming(Json(ReadinessStatus {
+                status: "warming".to_string(),
+                warnings: Vec::new(),
+            })),
+            Some(Readiness::Ready { warnings }) => ReadinessResponse::Ready(Json(ReadinessStatus {
+                status: "ready".to_string(),
+                warnings,
+            })),
+            None => ReadinessResponse::Ready(Json(ReadinessStatus {
+                status: "ready".to_string(),
+                warnings: Vec::new(),
+            })),
+        }
+    }
+}
+
+/// Check a named NetBox endpoint the same way as the default one
+async fn check_endpoint_health(endpoint: &NetBoxEndpoint) -> NetBoxEndpointHealth {
+    let netbox = check_netbox_health(&endpoint.client).await;
+    let client = &endpoint.client;
+    NetBoxEndpointHealth {
+        name: endpoint.name.clone(),
+        url: endpoint.url.clone(),
+        netbox,
+        netbox_auth: if client.auth_failed_at().is_some() { "invalid" } else { "valid" }.to_string(),
+        circuit_breaker: CircuitBreakerHealth {
+            state: format!("{:?}", client.circuit_breaker_state()),
+            failure_count: client.circuit_breaker_failure_count(),
+        },
+    }
 }
 
 /// Check NetBox connectivity
@@ -119,10 +439,19 @@ async fn check_netbox_health(client: &ResilientNetBoxClient) -> NetBoxHealth {
     
     // Try to list sites with a very small limit to test connectivity
     match timeout(Duration::from_secs(2), client.list_sites(None, Some(1), None)).await {
+        Ok(Ok(response)) if response.degraded => {
+            NetBoxHealth {
+                connected: false,
+                degraded: true,
+                response_time_ms: None,
+                error: Some("NetBox unavailable, serving fallback data".to_string()),
+            }
+        }
         Ok(Ok(_)) => {
             let response_time = start.elapsed().as_millis() as u64;
             NetBoxHealth {
                 connected: true,
+                degraded: false,
                 response_time_ms: Some(response_time),
                 error: None,
             }
@@ -130,6 +459,7 @@ async fn check_netbox_health(client: &ResilientNetBoxClient) -> NetBoxHealth {
         Ok(Err(e)) => {
             NetBoxHealth {
                 connected: false,
+                degraded: false,
                 response_time_ms: None,
                 error: Some(e.to_string()),
             }
@@ -137,6 +467,7 @@ async fn check_netbox_health(client: &ResilientNetBoxClient) -> NetBoxHealth {
         Err(_) => {
             NetBoxHealth {
                 connected: false,
+                degraded: false,
                 response_time_ms: None,
                 error: Some("Timeout".to_string()),
             }
@@ -144,6 +475,24 @@ async fn check_netbox_health(client: &ResilientNetBoxClient) -> NetBoxHealth {
     }
 }
 
+/// Check the database answers a query
+#[cfg(feature = "postgres")]
+async fn check_database_health(database: &Database) -> DatabaseHealth {
+    let start = std::time::Instant::now();
+    let (connected, response_time_ms, error) = match timeout(Duration::from_secs(2), database.ping()).await {
+        Ok(Ok(())) => (true, Some(start.elapsed().as_millis() as u64), None),
+        Ok(Err(e)) => (false, None, Some(e.to_string())),
+        Err(_) => (false, None, Some("Timeout".to_string())),
+    };
+    DatabaseHealth {
+        connected,
+        response_time_ms,
+        error,
+        pool_size: database.pool_size(),
+        idle_connections: database.idle_connections(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -152,6 +501,33 @@ mod tests {
     use serde_json::json;
     use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
 
+    #[tokio::test]
+    async fn test_health_check_flags_stuck_processing_orders() {
+        let manager = Arc::new(WorkflowManager::new());
+        let stuck = manager.create_order("t1".to_string());
+        manager.update_order_state(&stuck, OrderState::Validated).unwrap();
+        manager.update_order_state(&stuck, OrderState::Processing).unwrap();
+        manager.create_order("t1".to_string());
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        let api = HealthApi::new().with_workflow_manager(manager.clone(), Duration::from_millis(1));
+        match api.health().await {
+            HealthResponse::ServiceUnavailable(Json(health)) => {
+                assert_eq!(health.status, "degraded");
+                let stuck_orders = health.stuck_orders.unwrap();
+                assert_eq!(stuck_orders.count, 1);
+                assert_eq!(stuck_orders.order_ids, vec![stuck]);
+            }
+            HealthResponse::Ok(_) => panic!("stuck orders should degrade health"),
+        }
+
+        let api = HealthApi::new().with_workflow_manager(manager, Duration::from_secs(600));
+        match api.health().await {
+            HealthResponse::Ok(Json(health)) => assert_eq!(health.stuck_orders.unwrap().count, 0),
+            HealthResponse::ServiceUnavailable(_) => panic!("no order is stuck yet"),
+        }
+    }
+
     #[test]
     fn test_health_api_creation() {
         let api = HealthApi::new();
@@ -159,12 +535,28 @@ mod tests {
         assert!(true);
     }
 
+    #[tokio::test]
+    async fn test_ready_reports_warming_until_gate_opens() {
+        let gate = Arc::new(ReadinessGate::warming());
+        let api = HealthApi::new().with_readiness(gate.clone());
+        assert!(matches!(api.ready().await, ReadinessResponse::Warming(_)));
+
+        gate.open(vec!["Tenant 'acme': listing sites failed".to_string()]);
+        let ReadinessResponse::Ready(Json(status)) = api.ready().await else {
+            panic!("Expected ready");
+        };
+        assert_eq!(status.status, "ready");
+        assert_eq!(status.warnings.len(), 1);
+        assert!(matches!(HealthApi::new().ready().await, ReadinessResponse::Ready(_)));
+    }
+
     #[test]
     fn test_health_api_with_client() {
         let config = Config {
             port: 8080,
             netbox_url: "http://localhost:8000".to_string(),
             netbox_token: "test-token".to_string(),
+            ..Default::default()
         };
         let netbox_client = Arc::new(NetBoxClient::new(config).unwrap());
         let resilient_client = Arc::new(ResilientNetBoxClient::new(netbox_client));
@@ -180,6 +572,7 @@ mod tests {
             port: 8080,
             netbox_url: mock_server.uri(),
             netbox_token: "test-token".to_string(),
+            ..Default::default()
         };
         let netbox_client = Arc::new(NetBoxClient::new(config).unwrap());
         let resilient_client = Arc::new(ResilientNetBoxClient::new(netbox_client));
@@ -204,6 +597,10 @@ mod tests {
                 assert_eq!(health.service, "NetGate");
                 assert!(health.netbox.is_some());
                 assert_eq!(health.netbox.unwrap().connected, true);
+                let pool = health.netbox_pool.unwrap();
+                assert_eq!(pool.max_idle_per_host, 32);
+                assert_eq!(pool.tcp_keepalive_secs, Some(60));
+                assert_eq!(pool.http2, "auto");
             }
             _ => panic!("Expected Ok response"),
         }
@@ -215,6 +612,7 @@ mod tests {
             port: 8080,
             netbox_url: "http://localhost:9999".to_string(), // Non-existent server
             netbox_token: "test-token".to_string(),
+            ..Default::default()
         };
         let netbox_client = Arc::new(NetBoxClient::new(config).unwrap());
         let resilient_client = Arc::new(ResilientNetBoxClient::new(netbox_client));
@@ -238,6 +636,7 @@ mod tests {
             port: 8080,
             netbox_url: mock_server.uri(),
             netbox_token: "test-token".to_string(),
+            ..Default::default()
         };
         let netbox_client = Arc::new(NetBoxClient::new(config).unwrap());
         let resilient_client = Arc::new(ResilientNetBoxClient::new(netbox_client));
@@ -274,5 +673,230 @@ mod tests {
             }
         }
     }
-}
 
+    #[tokio::test]
+    async fn test_health_check_reports_degraded_fallback() {
+        use crate::resilience::{
+            CircuitBreakerConfig, DegradationConfig, DegradationStrategy, RetryConfig,
+        };
+
+        let mock_server = MockServer::start().await;
+        let config = Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let netbox_client = Arc::new(NetBoxClient::new(config).unwrap());
+        let resilient_client = Arc::new(ResilientNetBoxClient::with_config(
+            netbox_client,
+            CircuitBreakerConfig::default(),
+            RetryConfig::new(1),
+            Duration::from_secs(60),
+            DegradationConfig {
+                list_strategy: DegradationStrategy::ReturnEmpty,
+                ..Default::default()
+            },
+        ));
+        let api = HealthApi::with_netbox_client(resilient_client);
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&mock_server)
+            .await;
+
+        match api.health().await {
+            HealthResponse::ServiceUnavailable(Json(health)) => {
+                let netbox = health.netbox.unwrap();
+                assert!(!netbox.connected);
+                assert!(netbox.degraded);
+            }
+            _ => panic!("Expected ServiceUnavailable response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_health_check_reports_invalid_netbox_token() {
+        let mock_server = MockServer::start().await;
+        let config = Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "expired-token".to_string(),
+            ..Default::default()
+        };
+        let netbox_client = Arc::new(NetBoxClient::new(config).unwrap());
+        let resilient_client = Arc::new(ResilientNetBoxClient::new(netbox_client));
+        let api = HealthApi::with_netbox_client(resilient_client);
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(json!({"detail": "Invalid token"})))
+            .mount(&mock_server)
+            .await;
+
+        match api.health().await {
+            HealthResponse::ServiceUnavailable(Json(health)) => {
+                assert_eq!(health.status, "degraded");
+                assert_eq!(health.netbox_auth.as_deref(), Some("invalid"));
+                assert!(health.netbox_auth_failed_at.is_some());
+                assert_eq!(health.circuit_breaker.unwrap().failure_count, 0);
+            }
+            _ => panic!("Expected ServiceUnavailable response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_health_check_suspects_maintenance_behind_html_503s() {
+        use crate::resilience::{CircuitBreakerConfig, DegradationConfig, RetryConfig};
+
+        let mock_server = MockServer::start().await;
+        let config = Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let netbox_client = Arc::new(NetBoxClient::new(config).unwrap());
+        let resilient_client = Arc::new(ResilientNetBoxClient::with_config(
+            netbox_client,
+            CircuitBreakerConfig::default(),
+            RetryConfig::new(1),
+            Duration::from_secs(60),
+            DegradationConfig::default(),
+        ));
+        let api = HealthApi::with_netbox_client(resilient_client.clone());
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(
+                ResponseTemplate::new(503)
+                    .insert_header("Content-Type", "text/html")
+                    .set_body_string("<html><body><h1>Down for maintenance</h1></body></html>"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        match api.health().await {
+            HealthResponse::ServiceUnavailable(Json(health)) => {
+                assert_eq!(health.status, "degraded");
+                assert_eq!(health.netbox_maintenance.as_deref(), Some("NetBox maintenance suspected"));
+                // One maintenance page is enough to open the circuit
+                assert_eq!(health.circuit_breaker.unwrap().state, "Open");
+            }
+            _ => panic!("Expected ServiceUnavailable response"),
+        }
+        assert_eq!(resilient_client.metrics().maintenance_responses, 1);
+    }
+
+    #[cfg(feature = "postgres")]
+    #[tokio::test]
+    async fn test_health_check_reports_database_connectivity() {
+        let Some(database) = crate::database::test_database().await else {
+            return;
+        };
+        let api = HealthApi::new().with_database(database.clone());
+
+        match api.health().await {
+            HealthResponse::Ok(Json(health)) => {
+                let database = health.database.unwrap();
+                assert!(database.connected);
+                assert_eq!(database.pool_size, 2);
+            }
+            _ => panic!("Expected Ok response"),
+        }
+
+        database.pool().close().await;
+        match api.health().await {
+            HealthResponse::ServiceUnavailable(Json(health)) => {
+                assert_eq!(health.status, "degraded");
+                let database = health.database.unwrap();
+                assert!(!database.connected);
+                assert!(database.error.is_some());
+            }
+            _ => panic!("Expected ServiceUnavailable response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_health_check_reports_a_freeze_without_degrading() {
+        let windows = MaintenanceWindows::new(MaintenanceWindows::parse_windows("* 00:00-24:00"), chrono_tz::Tz::UTC);
+        let api = HealthApi::new().with_maintenance_windows(Arc::new(windows));
+
+        match api.health().await {
+            HealthResponse::Ok(Json(health)) => {
+                let freeze = health.maintenance_freeze.unwrap();
+                assert!(freeze.active);
+                assert!(freeze.until.is_some());
+                assert_eq!(freeze.mode, "park");
+            }
+            _ => panic!("Expected Ok response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_health_check_reports_a_read_only_token() {
+        let mock_server = MockServer::start().await;
+        let config = Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let netbox_client = Arc::new(NetBoxClient::new(config).unwrap());
+        let resilient_client = Arc::new(ResilientNetBoxClient::new(netbox_client));
+        let api = HealthApi::with_netbox_client(resilient_client.clone());
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"count": 0, "results": []})))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("OPTIONS"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"name": "Site List"})))
+            .mount(&mock_server)
+            .await;
+
+        match api.health().await {
+            HealthResponse::Ok(Json(health)) => assert_eq!(health.netbox_token_scope, None),
+            _ => panic!("Expected Ok response before the probe"),
+        }
+        resilient_client.probe_token_scope().await;
+        match api.health().await {
+            HealthResponse::ServiceUnavailable(Json(health)) => {
+                assert_eq!(health.status, "degraded");
+                assert_eq!(health.netbox_token_scope.as_deref(), Some("read-only"));
+                assert_eq!(health.netbox_auth.as_deref(), Some("valid"));
+            }
+            _ => panic!("Expected ServiceUnavailable response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_health_check_reports_netbox_version() {
+        let mock_server = MockServer::start().await;
+        let config = Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let netbox_client = Arc::new(NetBoxClient::new(config).unwrap());
+        let api = HealthApi::with_netbox_client(Arc::new(ResilientNetBoxClient::new(netbox_client)));
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"count": 0, "results": []})))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/status/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"netbox-version": "4.0.3", "plugins": {}})))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let HealthResponse::Ok(Json(health)) = api.health().await else {
+            panic!("Expected Ok response");
+        };
+        assert_eq!(health.netbox_version.as_deref(), Some("4.0.3"));
+        // The version is cached after the first probe
+        let _ = api.health().await;
+    }
+}
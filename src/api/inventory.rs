@@ -0,0 +1,1269 @@
+use poem::Request;
+use poem_openapi::{param::{Path, Query}, payload::{Json, PlainText}, ApiResponse, Enum, Object, OpenApi};
+use std::sync::Arc;
+
+use crate::api::projection::ListView;
+use crate::business::site_report::{CapacityReport, SiteReport, SiteReportService};
+use crate::error::AppError;
+use crate::netbox::tenant_client::{SearchFilter, TenantAwareNetBoxClient};
+use crate::netbox::{
+    DeviceStatus, NetBoxDevice, NetBoxObjectChange, NetBoxSite, NetBoxVirtualMachine, NetBoxVlan, SiteStatus,
+};
+use crate::security::{extract_tenant_id, require_role, READER_ROLE};
+use crate::sync::SyncService;
+
+/// Page size used when the caller doesn't pass `limit`
+const DEFAULT_LIMIT: u32 = 50;
+/// Largest page a caller may request
+const MAX_LIMIT: u32 = 1000;
+
+/// Tenant-scoped search over the tenant's NetBox sites, devices and VLANs, and capacity reports
+pub struct InventoryApi {
+    netbox_client: Option<Arc<TenantAwareNetBoxClient>>,
+    reports: Option<Arc<SiteReportService>>,
+    sync: Option<Arc<SyncService>>,
+}
+
+impl InventoryApi {
+    pub fn new() -> Self {
+        Self {
+            netbox_client: None,
+            reports: None,
+            sync: None,
+        }
+    }
+
+    /// Serve searches and reports from `netbox_client`; reports count every racked device as 1U
+    /// until [`Self::with_site_reports`] sets a service with a catalog
+    pub fn with_netbox_client(netbox_client: Arc<TenantAwareNetBoxClient>) -> Self {
+        Self {
+            reports: Some(Arc::new(SiteReportService::new(netbox_client.clone()))),
+            netbox_client: Some(netbox_client),
+            sync: None,
+        }
+    }
+
+    pub fn with_site_reports(mut self, reports: Arc<SiteReportService>) -> Self {
+        self.reports = Some(reports);
+        self
+    }
+
+    /// Let searches pass `source=local` to read the sync read model instead of NetBox
+    pub fn with_sync(mut self, sync: Arc<SyncService>) -> Self {
+        self.sync = Some(sync);
+        self
+    }
+
+    fn client(&self) -> Result<&TenantAwareNetBoxClient, AppError> {
+        self.netbox_client
+            .as_deref()
+            .ok_or_else(|| AppError::ServiceUnavailable("NetBox integration is not configured".to_string()))
+    }
+
+    fn sync(&self) -> Result<&SyncService, AppError> {
+        self.sync
+            .as_deref()
+            .ok_or_else(|| AppError::ValidationError("source=local needs the read model sync enabled".to_string()))
+    }
+
+    fn reports(&self) -> Result<&SiteReportService, AppError> {
+        self.reports
+            .as_deref()
+            .ok_or_else(|| AppError::ServiceUnavailable("NetBox integration is not configured".to_string()))
+    }
+}
+
+impl Default for InventoryApi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where searches read from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Enum)]
+#[oai(rename_all = "lowercase")]
+pub enum ReadSource {
+    /// NetBox itself, through the response cache
+    #[default]
+    Netbox,
+    /// The read model kept by the sync, as of its last run
+    Local,
+}
+
+/// Site fields exposed to tenants
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Object)]
+pub struct SiteSummary {
+    pub id: Option<i32>,
+    pub name: String,
+    pub slug: Option<String>,
+    pub status: Option<String>,
+    pub facility: Option<String>,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    /// Send with update orders to refuse them if the site changed in the meantime
+    pub last_updated: Option<String>,
+}
+
+impl From<NetBoxSite> for SiteSummary {
+    fn from(site: NetBoxSite) -> Self {
+        Self {
+            id: site.id,
+            name: site.name,
+            slug: site.slug,
+            status: site.status.and_then(|status| status_value(&status)),
+            facility: site.facility,
+            description: site.description,
+            tags: site.tags.unwrap_or_default(),
+            last_updated: site.last_updated.map(|at| at.to_rfc3339()),
+        }
+    }
+}
+
+/// Device fields exposed to tenants
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Object)]
+pub struct DeviceSummary {
+    pub id: Option<i32>,
+    pub name: Option<String>,
+    pub site: Option<i32>,
+    pub status: Option<String>,
+    pub serial: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl From<NetBoxDevice> for DeviceSummary {
+    fn from(device: NetBoxDevice) -> Self {
+        Self {
+            id: device.id,
+            name: device.name,
+            site: device.site,
+            status: device.status.and_then(|status| status_value(&status)),
+            serial: device.serial,
+            tags: device.tags.unwrap_or_default(),
+        }
+    }
+}
+
+/// Virtual machine fields exposed to tenants
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Object)]
+pub struct VirtualMachineSummary {
+    pub id: Option<i32>,
+    pub name: String,
+    pub cluster: Option<i32>,
+    pub site: Option<i32>,
+    pub status: Option<String>,
+    pub vcpus: Option<f64>,
+    /// Memory in MB
+    pub memory: Option<i32>,
+    /// Disk in GB
+    pub disk: Option<i32>,
+    pub tags: Vec<String>,
+}
+
+impl From<NetBoxVirtualMachine> for VirtualMachineSummary {
+    fn from(vm: NetBoxVirtualMachine) -> Self {
+        Self {
+            id: vm.id,
+            name: vm.name,
+            cluster: vm.cluster,
+            site: vm.site,
+            status: vm.status.and_then(|status| status_value(&status)),
+            vcpus: vm.vcpus,
+            memory: vm.memory,
+            disk: vm.disk,
+            tags: vm.tags.unwrap_or_default(),
+        }
+    }
+}
+
+/// VLAN fields exposed to tenants
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Object)]
+pub struct VlanSummary {
+    pub id: Option<i32>,
+    pub vid: u16,
+    pub name: String,
+    pub site: Option<i32>,
+    pub group: Option<i32>,
+    pub status: Option<String>,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl From<NetBoxVlan> for VlanSummary {
+    fn from(vlan: NetBoxVlan) -> Self {
+        Self {
+            id: vlan.id,
+            vid: vlan.vid,
+            name: vlan.name,
+            site: vlan.site,
+            group: vlan.group,
+            status: vlan.status.and_then(|status| status_value(&status)),
+            description: vlan.description,
+            tags: vlan.tags.unwrap_or_default(),
+        }
+    }
+}
+
+/// One entry of a NetBox object's changelog
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Object)]
+pub struct ChangeEntry {
+    pub id: i32,
+    /// RFC 3339 timestamp of the change
+    pub time: String,
+    pub user: Option<String>,
+    /// `create`, `update` or `delete`
+    pub action: String,
+    /// NetBox's id of the request that made the change
+    pub request_id: Option<String>,
+    /// The object's fields before the change, as NetBox recorded them
+    pub prechange_data: Option<serde_json::Value>,
+    /// The object's fields after the change, as NetBox recorded them
+    pub postchange_data: Option<serde_json::Value>,
+}
+
+impl From<NetBoxObjectChange> for ChangeEntry {
+    fn from(change: NetBoxObjectChange) -> Self {
+        Self {
+            id: change.id,
+            time: change.time.to_rfc3339(),
+            user: change.user_name,
+            action: change.action.value,
+            request_id: change.request_id,
+            prechange_data: change.prechange_data,
+            postchange_data: change.postchange_data,
+        }
+    }
+}
+
+/// Lowercase wire value of a NetBox status enum
+fn status_value<T: serde::Serialize>(status: &T) -> Option<String> {
+    serde_json::to_value(status).ok()?.as_str().map(str::to_string)
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Object)]
+pub struct SiteSearchResponse {
+    pub results: Vec<SiteSummary>,
+    /// Total matches reported by NetBox
+    pub total: Option<i32>,
+    /// Whether a further page exists at `offset + limit`
+    pub has_more: bool,
+    pub limit: u32,
+    pub offset: u32,
+    /// NetBox couldn't be reached and the results come from fallback data, possibly stale or empty
+    pub degraded: bool,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Object)]
+pub struct DeviceSearchResponse {
+    pub results: Vec<DeviceSummary>,
+    /// Total matches reported by NetBox
+    pub total: Option<i32>,
+    /// Whether a further page exists at `offset + limit`
+    pub has_more: bool,
+    pub limit: u32,
+    pub offset: u32,
+    /// NetBox couldn't be reached and the results come from fallback data, possibly stale or empty
+    pub degraded: bool,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Object)]
+pub struct VlanListResponse {
+    pub results: Vec<VlanSummary>,
+    /// Total VLANs reported by NetBox
+    pub total: Option<i32>,
+    /// Whether a further page exists at `offset + limit`
+    pub has_more: bool,
+    pub limit: u32,
+    pub offset: u32,
+    /// NetBox couldn't be reached and the results come from fallback data, possibly stale or empty
+    pub degraded: bool,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Object)]
+pub struct ChangeHistoryResponse {
+    pub results: Vec<ChangeEntry>,
+    /// Total changes reported by NetBox
+    pub total: Option<i32>,
+    /// Whether a further page exists at `offset + limit`
+    pub has_more: bool,
+    pub limit: u32,
+    pub offset: u32,
+    /// NetBox couldn't be reached and the results come from fallback data, possibly stale or empty
+    pub degraded: bool,
+}
+
+#[derive(ApiResponse)]
+pub enum SearchSitesResponse {
+    #[oai(status = 200)]
+    Ok(Json<SiteSearchResponse>),
+
+    /// Results reduced to the fields selected with `fields`
+    #[oai(status = 200)]
+    Projected(Json<serde_json::Value>),
+
+    #[oai(status = 200, content_type = "text/csv")]
+    Csv(PlainText<String>),
+
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+
+    #[oai(status = 401)]
+    Unauthorized,
+
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+pub enum SearchDevicesResponse {
+    #[oai(status = 200)]
+    Ok(Json<DeviceSearchResponse>),
+
+    /// Results reduced to the fields selected with `fields`
+    #[oai(status = 200)]
+    Projected(Json<serde_json::Value>),
+
+    #[oai(status = 200, content_type = "text/csv")]
+    Csv(PlainText<String>),
+
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+
+    #[oai(status = 401)]
+    Unauthorized,
+
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+pub enum ListVlansResponse {
+    #[oai(status = 200)]
+    Ok(Json<VlanListResponse>),
+
+    #[oai(status = 401)]
+    Unauthorized,
+
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+pub enum SiteReportResponse {
+    #[oai(status = 200)]
+    Ok(Json<SiteReport>),
+
+    #[oai(status = 401)]
+    Unauthorized,
+
+    #[oai(status = 404)]
+    NotFound(Json<serde_json::Value>),
+
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+pub enum CapacityReportResponse {
+    #[oai(status = 200)]
+    Ok(Json<CapacityReport>),
+
+    #[oai(status = 401)]
+    Unauthorized,
+
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+pub enum SiteChangesResponse {
+    #[oai(status = 200)]
+    Ok(Json<ChangeHistoryResponse>),
+
+    #[oai(status = 401)]
+    Unauthorized,
+
+    #[oai(status = 404)]
+    NotFound(Json<serde_json::Value>),
+
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<serde_json::Value>),
+}
+
+/// Build the NetBox filter, rejecting statuses that `T` doesn't know
+fn search_filter<T: serde::de::DeserializeOwned>(
+    q: Option<String>,
+    tag: Option<String>,
+    status: Option<String>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+) -> Result<SearchFilter, AppError> {
+    if let Some(ref status) = status {
+        serde_json::from_value::<T>(serde_json::Value::String(status.clone()))
+            .map_err(|_| AppError::ValidationError(format!("Unknown status '{}'", status)))?;
+    }
+    Ok(SearchFilter {
+        q: q.filter(|q| !q.trim().is_empty()),
+        tag,
+        status,
+        updated_since: None,
+        limit: Some(limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)),
+        offset: Some(offset.unwrap_or(0)),
+    })
+}
+
+fn error_body(error: &AppError) -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "error": error.to_string() }))
+}
+
+#[OpenApi]
+impl InventoryApi {
+    /// Search the tenant's sites
+    ///
+    /// `q` is NetBox's free-text search; `tag` takes a tag slug. `fields` (comma-separated)
+    /// reduces results to those fields, and `Accept: text/csv` returns the results as CSV.
+    /// `source=local` reads the sync read model, where `q` matches names only.
+    #[oai(path = "/sites", method = "get")]
+    #[allow(clippy::too_many_arguments)]
+    async fn search_sites(
+        &self,
+        req: &Request,
+        q: Query<Option<String>>,
+        tag: Query<Option<String>>,
+        status: Query<Option<String>>,
+        limit: Query<Option<u32>>,
+        offset: Query<Option<u32>>,
+        fields: Query<Option<String>>,
+        source: Query<Option<ReadSource>>,
+    ) -> Result<SearchSitesResponse, poem::Error> {
+        let tenant_id = extract_tenant_id(req)?;
+        let view = match ListView::for_items::<SiteSummary>(req, fields.0.as_deref()) {
+            Ok(view) => view,
+            Err(e) => return Ok(SearchSitesResponse::BadRequest(error_body(&e))),
+        };
+        let filter = match search_filter::<SiteStatus>(q.0, tag.0, status.0, limit.0, offset.0) {
+            Ok(filter) => filter,
+            Err(e) => return Ok(SearchSitesResponse::BadRequest(error_body(&e))),
+        };
+        let page = match source.0.unwrap_or_default() {
+            ReadSource::Netbox => match self.client() {
+                Ok(client) => client.search_sites(&tenant_id, &filter).await,
+                Err(e) => return Ok(SearchSitesResponse::ServiceUnavailable(error_body(&e))),
+            },
+            ReadSource::Local => match self.sync() {
+                Ok(sync) => sync.search_sites(&tenant_id, &filter),
+                Err(e) => return Ok(SearchSitesResponse::BadRequest(error_body(&e))),
+            },
+        };
+
+        match page {
+            Ok(page) => {
+                let response = SiteSearchResponse {
+                    results: page.results.into_iter().map(SiteSummary::from).collect(),
+                    total: page.total,
+                    has_more: page.has_more,
+                    limit: filter.limit.unwrap_or(DEFAULT_LIMIT),
+                    offset: filter.offset.unwrap_or(0),
+                    degraded: page.degraded,
+                };
+                Ok(if view.csv {
+                    SearchSitesResponse::Csv(PlainText(view.to_csv(&response.results)))
+                } else if view.is_default() {
+                    SearchSitesResponse::Ok(Json(response))
+                } else {
+                    SearchSitesResponse::Projected(Json(view.project_list(&response, "results")))
+                })
+            }
+            Err(AppError::Unauthorized) => Ok(SearchSitesResponse::Unauthorized),
+            Err(e) if e.is_unavailable() => {
+                Ok(SearchSitesResponse::ServiceUnavailable(error_body(&e)))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Who changed one of the tenant's sites, and when (requires the reader role)
+    ///
+    /// Newest first, from NetBox's changelog. Snapshots are returned as NetBox recorded them.
+    #[oai(path = "/sites/:id/changes", method = "get")]
+    async fn site_changes(
+        &self,
+        req: &Request,
+        id: Path<i32>,
+        limit: Query<Option<u32>>,
+        offset: Query<Option<u32>>,
+    ) -> Result<SiteChangesResponse, poem::Error> {
+        let tenant_id = extract_tenant_id(req)?;
+        require_role(req, READER_ROLE)?;
+        let client = match self.client() {
+            Ok(client) => client,
+            Err(e) => return Ok(SiteChangesResponse::ServiceUnavailable(error_body(&e))),
+        };
+        let limit = limit.0.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let offset = offset.0.unwrap_or(0);
+
+        match client.site_changes(&tenant_id, id.0, Some(limit), Some(offset)).await {
+            Ok(page) => Ok(SiteChangesResponse::Ok(Json(ChangeHistoryResponse {
+                has_more: page.next.is_some(),
+                total: page.count,
+                results: page.results.unwrap_or_default().into_iter().map(ChangeEntry::from).collect(),
+                limit,
+                offset,
+                degraded: page.degraded,
+            }))),
+            Err(AppError::Unauthorized) => Ok(SiteChangesResponse::Unauthorized),
+            Err(e @ AppError::NotFound(_)) => Ok(SiteChangesResponse::NotFound(error_body(&e))),
+            Err(e) if e.is_unavailable() => {
+                Ok(SiteChangesResponse::ServiceUnavailable(error_body(&e)))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Capacity and utilization of one of the tenant's sites
+    ///
+    /// Device and rack counts, rack units in use, and prefix utilization. A part
+    /// that NetBox fails to return is marked with an `error` instead of failing the report.
+    #[oai(path = "/sites/:id/report", method = "get")]
+    async fn site_report(&self, req: &Request, id: Path<i32>) -> Result<SiteReportResponse, poem::Error> {
+        let tenant_id = extract_tenant_id(req)?;
+        let reports = match self.reports() {
+            Ok(reports) => reports,
+            Err(e) => return Ok(SiteReportResponse::ServiceUnavailable(error_body(&e))),
+        };
+
+        match reports.site_report(&tenant_id, id.0).await {
+            Ok(report) => Ok(SiteReportResponse::Ok(Json(report))),
+            Err(AppError::Unauthorized) => Ok(SiteReportResponse::Unauthorized),
+            Err(e @ AppError::NotFound(_)) => Ok(SiteReportResponse::NotFound(error_body(&e))),
+            Err(e) if e.is_unavailable() => {
+                Ok(SiteReportResponse::ServiceUnavailable(error_body(&e)))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Capacity and utilization of all the tenant's sites, with totals
+    ///
+    /// Sites are read a few at a time; as with the site report, failed parts are marked, not fatal.
+    #[oai(path = "/reports/capacity", method = "get")]
+    async fn capacity_report(&self, req: &Request) -> Result<CapacityReportResponse, poem::Error> {
+        let tenant_id = extract_tenant_id(req)?;
+        let reports = match self.reports() {
+            Ok(reports) => reports,
+            Err(e) => return Ok(CapacityReportResponse::ServiceUnavailable(error_body(&e))),
+        };
+
+        match reports.capacity_report(&tenant_id).await {
+            Ok(report) => Ok(CapacityReportResponse::Ok(Json(report))),
+            Err(AppError::Unauthorized) => Ok(CapacityReportResponse::Unauthorized),
+            Err(e) if e.is_unavailable() => {
+                Ok(CapacityReportResponse::ServiceUnavailable(error_body(&e)))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Search the tenant's devices, optionally within one site
+    ///
+    /// Takes `fields`, `Accept: text/csv` and `source` like the site search.
+    #[oai(path = "/devices", method = "get")]
+    #[allow(clippy::too_many_arguments)]
+    async fn search_devices(
+        &self,
+        req: &Request,
+        q: Query<Option<String>>,
+        tag: Query<Option<String>>,
+        status: Query<Option<String>>,
+        site: Query<Option<i32>>,
+        limit: Query<Option<u32>>,
+        offset: Query<Option<u32>>,
+        fields: Query<Option<String>>,
+        source: Query<Option<ReadSource>>,
+    ) -> Result<SearchDevicesResponse, poem::Error> {
+        let tenant_id = extract_tenant_id(req)?;
+        let view = match ListView::for_items::<DeviceSummary>(req, fields.0.as_deref()) {
+            Ok(view) => view,
+            Err(e) => return Ok(SearchDevicesResponse::BadRequest(error_body(&e))),
+        };
+        let filter = match search_filter::<DeviceStatus>(q.0, tag.0, status.0, limit.0, offset.0) {
+            Ok(filter) => filter,
+            Err(e) => return Ok(SearchDevicesResponse::BadRequest(error_body(&e))),
+        };
+        let page = match source.0.unwrap_or_default() {
+            ReadSource::Netbox => match self.client() {
+                Ok(client) => client.search_devices(&tenant_id, site.0, &filter).await,
+                Err(e) => return Ok(SearchDevicesResponse::ServiceUnavailable(error_body(&e))),
+            },
+            ReadSource::Local => match self.sync() {
+                Ok(sync) => sync.search_devices(&tenant_id, site.0, &filter),
+                Err(e) => return Ok(SearchDevicesResponse::BadRequest(error_body(&e))),
+            },
+        };
+
+        match page {
+            Ok(page) => {
+                let response = DeviceSearchResponse {
+                    results: page.results.into_iter().map(DeviceSummary::from).collect(),
+                    total: page.total,
+                    has_more: page.has_more,
+                    limit: filter.limit.unwrap_or(DEFAULT_LIMIT),
+                    offset: filter.offset.unwrap_or(0),
+                    degraded: page.degraded,
+                };
+                Ok(if view.csv {
+                    SearchDevicesResponse::Csv(PlainText(view.to_csv(&response.results)))
+                } else if view.is_default() {
+                    SearchDevicesResponse::Ok(Json(response))
+                } else {
+                    SearchDevicesResponse::Projected(Json(view.project_list(&response, "results")))
+                })
+            }
+            Err(AppError::Unauthorized) => Ok(SearchDevicesResponse::Unauthorized),
+            Err(e) if e.is_unavailable() => {
+                Ok(SearchDevicesResponse::ServiceUnavailable(error_body(&e)))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// List the tenant's VLANs, optionally within one site or VLAN group
+    #[oai(path = "/vlans", method = "get")]
+    async fn list_vlans(
+        &self,
+        req: &Request,
+        site: Query<Option<i32>>,
+        group: Query<Option<i32>>,
+        limit: Query<Option<u32>>,
+        offset: Query<Option<u32>>,
+    ) -> Result<ListVlansResponse, poem::Error> {
+        let tenant_id = extract_tenant_id(req)?;
+        let client = match self.client() {
+            Ok(client) => client,
+            Err(e) => return Ok(ListVlansResponse::ServiceUnavailable(error_body(&e))),
+        };
+        let limit = limit.0.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let offset = offset.0.unwrap_or(0);
+
+        match client.list_vlans(&tenant_id, site.0, group.0, Some(limit), Some(offset)).await {
+            Ok(page) => Ok(ListVlansResponse::Ok(Json(VlanListResponse {
+                results: page.results.into_iter().map(VlanSummary::from).collect(),
+                total: page.total,
+                has_more: page.has_more,
+                limit,
+                offset,
+                degraded: page.degraded,
+            }))),
+            Err(AppError::Unauthorized) => Ok(ListVlansResponse::Unauthorized),
+            Err(e) if e.is_unavailable() => {
+                Ok(ListVlansResponse::ServiceUnavailable(error_body(&e)))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::netbox::{NetBoxClient, ResilientNetBoxClient};
+    use crate::resilience::{CircuitBreakerConfig, DegradationConfig, DegradationStrategy, RetryConfig};
+    use crate::security::tenant::{TenantAccessControl, TenantMappingService};
+    use crate::sync::ReadModel;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn create_api(mock_server: &MockServer) -> InventoryApi {
+        let config = Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let client = Arc::new(NetBoxClient::new(config).unwrap());
+        let mappings = HashMap::from([("tenant-1".to_string(), 10), ("tenant-2".to_string(), 20)]);
+        let access_control = Arc::new(TenantAccessControl::new(TenantMappingService::from(mappings)));
+        InventoryApi::with_netbox_client(Arc::new(TenantAwareNetBoxClient::new(client, access_control)))
+    }
+
+    fn tenant_request(tenant_id: &str) -> Request {
+        Request::builder().header("X-Tenant-Id", tenant_id).finish()
+    }
+
+    #[tokio::test]
+    async fn test_search_sites_passes_filters_to_netbox() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .and(query_param("tenant_id", "10"))
+            .and(query_param("q", "berlin dc"))
+            .and(query_param("tag", "cost-center-cc-123"))
+            .and(query_param("status", "active"))
+            .and(query_param("limit", "50"))
+            .and(query_param("offset", "0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 1,
+                "results": [{
+                    "id": 1,
+                    "name": "Berlin DC1",
+                    "slug": "berlin-dc1",
+                    "tenant": 10,
+                    "status": "active",
+                    "tags": ["cost-center-cc-123"],
+                    "comments": "internal notes"
+                }]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let api = create_api(&mock_server);
+
+        let response = api
+            .search_sites(
+                &tenant_request("tenant-1"),
+                Query(Some("berlin dc".to_string())),
+                Query(Some("cost-center-cc-123".to_string())),
+                Query(Some("active".to_string())),
+                Query(None),
+                Query(None),
+                Query(None),
+                Query(None),
+            )
+            .await
+            .unwrap();
+
+        let SearchSitesResponse::Ok(Json(page)) = response else {
+            panic!("Expected Ok response");
+        };
+        assert_eq!(page.results.len(), 1);
+        assert_eq!(page.results[0].slug.as_deref(), Some("berlin-dc1"));
+        assert_eq!(page.results[0].status.as_deref(), Some("active"));
+        assert_eq!(page.results[0].tags, vec!["cost-center-cc-123"]);
+        assert_eq!(page.limit, 50);
+    }
+
+    #[tokio::test]
+    async fn test_search_sites_projects_fields_and_renders_csv() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 1,
+                "results": [{"id": 1, "name": "Berlin, DC1", "slug": "berlin-dc1", "tenant": 10, "status": "active"}]
+            })))
+            .mount(&mock_server)
+            .await;
+        let api = create_api(&mock_server);
+        let fields = || Query(Some("id,name".to_string()));
+
+        let response = api
+            .search_sites(&tenant_request("tenant-1"), Query(None), Query(None), Query(None), Query(None), Query(None), fields(), Query(None))
+            .await
+            .unwrap();
+        let SearchSitesResponse::Projected(Json(page)) = response else {
+            panic!("Expected a projected response");
+        };
+        assert_eq!(page["results"], json!([{"id": 1, "name": "Berlin, DC1"}]));
+        assert_eq!(page["total"], 1);
+
+        let csv_request = Request::builder()
+            .header("X-Tenant-Id", "tenant-1")
+            .header("Accept", "text/csv")
+            .finish();
+        let response = api
+            .search_sites(&csv_request, Query(None), Query(None), Query(None), Query(None), Query(None), fields(), Query(None))
+            .await
+            .unwrap();
+        let SearchSitesResponse::Csv(PlainText(csv)) = response else {
+            panic!("Expected a CSV response");
+        };
+        assert_eq!(csv, "id,name\r\n1,\"Berlin, DC1\"\r\n");
+
+        let unknown = api
+            .search_sites(
+                &tenant_request("tenant-1"),
+                Query(None),
+                Query(None),
+                Query(None),
+                Query(None),
+                Query(None),
+                Query(Some("name,comments".to_string())),
+                Query(None),
+            )
+            .await
+            .unwrap();
+        let SearchSitesResponse::BadRequest(Json(body)) = unknown else {
+            panic!("Expected BadRequest response");
+        };
+        assert!(body["error"].as_str().unwrap().contains("comments"));
+    }
+
+    #[tokio::test]
+    async fn test_search_sites_strips_other_tenants() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 3,
+                "results": [
+                    {"id": 1, "name": "Mine", "tenant": 10},
+                    {"id": 2, "name": "Theirs", "tenant": 20},
+                    {"id": 3, "name": "Unassigned"}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+        let api = create_api(&mock_server);
+
+        let response = api
+            .search_sites(&tenant_request("tenant-1"), Query(None), Query(None), Query(None), Query(None), Query(None), Query(None), Query(None))
+            .await
+            .unwrap();
+
+        let SearchSitesResponse::Ok(Json(page)) = response else {
+            panic!("Expected Ok response");
+        };
+        let names: Vec<_> = page.results.iter().map(|site| site.name.as_str()).collect();
+        assert_eq!(names, vec!["Mine"]);
+    }
+
+    #[tokio::test]
+    async fn test_search_devices_strips_other_tenants() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/devices/"))
+            .and(query_param("tenant_id", "20"))
+            .and(query_param("site_id", "7"))
+            .and(query_param("tag", "edge"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 2,
+                "results": [
+                    {"id": 1, "name": "sw1", "site": 7, "tenant": 10, "serial": "A1"},
+                    {"id": 2, "name": "sw2", "site": 7, "tenant": 20, "serial": "B2", "status": "planned"}
+                ]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let api = create_api(&mock_server);
+
+        let response = api
+            .search_devices(
+                &tenant_request("tenant-2"),
+                Query(None),
+                Query(Some("edge".to_string())),
+                Query(None),
+                Query(Some(7)),
+                Query(None),
+                Query(None),
+                Query(None),
+                Query(None),
+            )
+            .await
+            .unwrap();
+
+        let SearchDevicesResponse::Ok(Json(page)) = response else {
+            panic!("Expected Ok response");
+        };
+        assert_eq!(page.results.len(), 1);
+        assert_eq!(page.results[0].name.as_deref(), Some("sw2"));
+        assert_eq!(page.results[0].status.as_deref(), Some("planned"));
+    }
+
+    #[tokio::test]
+    async fn test_search_rejects_unknown_status() {
+        let mock_server = MockServer::start().await;
+        let api = create_api(&mock_server);
+
+        let response = api
+            .search_sites(
+                &tenant_request("tenant-1"),
+                Query(None),
+                Query(None),
+                Query(Some("bogus".to_string())),
+                Query(None),
+                Query(None),
+                Query(None),
+                Query(None),
+            )
+            .await
+            .unwrap();
+
+        assert!(matches!(response, SearchSitesResponse::BadRequest(_)));
+        assert!(mock_server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_unmapped_tenant_is_unauthorized() {
+        let mock_server = MockServer::start().await;
+        let api = create_api(&mock_server);
+
+        let response = api
+            .search_devices(
+                &tenant_request("tenant-3"),
+                Query(None),
+                Query(None),
+                Query(None),
+                Query(None),
+                Query(None),
+                Query(None),
+                Query(None),
+                Query(None),
+            )
+            .await
+            .unwrap();
+
+        assert!(matches!(response, SearchDevicesResponse::Unauthorized));
+    }
+
+    async fn search_sites_with_fallback(strategy: DegradationStrategy) -> Vec<SiteSearchResponse> {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 1,
+                "results": [{"id": 1, "name": "Berlin DC1", "tenant": 10}]
+            })))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&mock_server)
+            .await;
+        let config = Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let client = Arc::new(ResilientNetBoxClient::with_config(
+            Arc::new(NetBoxClient::new(config).unwrap()),
+            CircuitBreakerConfig::default(),
+            RetryConfig::new(1),
+            std::time::Duration::from_secs(60),
+            DegradationConfig {
+                list_strategy: strategy,
+                ..Default::default()
+            },
+        ));
+        let access_control = Arc::new(TenantAccessControl::new(TenantMappingService::from(HashMap::from([(
+            "tenant-1".to_string(),
+            10,
+        )]))));
+        let api = InventoryApi::with_netbox_client(Arc::new(TenantAwareNetBoxClient::new(client, access_control)));
+
+        let mut pages = Vec::new();
+        for _ in 0..2 {
+            let response = api
+                .search_sites(&tenant_request("tenant-1"), Query(None), Query(None), Query(None), Query(None), Query(None), Query(None), Query(None))
+                .await
+                .unwrap();
+            let SearchSitesResponse::Ok(Json(page)) = response else {
+                panic!("Expected Ok response");
+            };
+            pages.push(page);
+        }
+        pages
+    }
+
+    #[tokio::test]
+    async fn test_search_sites_marks_fallback_results_degraded() {
+        let pages = search_sites_with_fallback(DegradationStrategy::UseCache).await;
+        assert!(!pages[0].degraded);
+        assert!(pages[1].degraded);
+        assert_eq!(pages[1].results.len(), 1);
+
+        let pages = search_sites_with_fallback(DegradationStrategy::ReturnEmpty).await;
+        assert!(!pages[0].degraded);
+        assert!(pages[1].degraded);
+        assert!(pages[1].results.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_without_netbox_is_unavailable() {
+        let api = InventoryApi::new();
+
+        let response = api
+            .search_sites(&tenant_request("tenant-1"), Query(None), Query(None), Query(None), Query(None), Query(None), Query(None), Query(None))
+            .await
+            .unwrap();
+
+        assert!(matches!(response, SearchSitesResponse::ServiceUnavailable(_)));
+    }
+
+    #[tokio::test]
+    async fn test_local_source_reads_the_sync_read_model() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 1,
+                "results": [{"id": 1, "name": "Berlin DC1", "tenant": 10, "status": "active"}]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/devices/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 2,
+                "results": [
+                    {"id": 5, "name": "sw1", "site": 1, "tenant": 10},
+                    {"id": 6, "name": "sw2", "site": 2, "tenant": 10}
+                ]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let config = Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let client = Arc::new(NetBoxClient::new(config).unwrap());
+        let mappings = Arc::new(TenantMappingService::from(HashMap::from([("tenant-1".to_string(), 10)])));
+        let tenant_client = Arc::new(TenantAwareNetBoxClient::new(
+            client,
+            Arc::new(TenantAccessControl::from_shared(mappings.clone())),
+        ));
+        let sync = Arc::new(SyncService::new(tenant_client.clone(), mappings, Arc::new(ReadModel::new())));
+        sync.run().await;
+
+        // Served from the read model: NetBox sees no further requests
+        let api = InventoryApi::with_netbox_client(tenant_client).with_sync(sync);
+        let local = || Query(Some(ReadSource::Local));
+        let response = api
+            .search_sites(&tenant_request("tenant-1"), Query(Some("berlin".to_string())), Query(None), Query(None), Query(None), Query(None), Query(None), local())
+            .await
+            .unwrap();
+        let SearchSitesResponse::Ok(Json(page)) = response else {
+            panic!("Expected Ok response");
+        };
+        assert_eq!(page.results[0].name, "Berlin DC1");
+        assert_eq!(page.total, Some(1));
+
+        let response = api
+            .search_devices(
+                &tenant_request("tenant-1"),
+                Query(None),
+                Query(None),
+                Query(None),
+                Query(Some(1)),
+                Query(None),
+                Query(None),
+                Query(None),
+                local(),
+            )
+            .await
+            .unwrap();
+        let SearchDevicesResponse::Ok(Json(page)) = response else {
+            panic!("Expected Ok response");
+        };
+        let names: Vec<_> = page.results.iter().map(|device| device.name.as_deref()).collect();
+        assert_eq!(names, vec![Some("sw1")]);
+
+        let unmapped = api
+            .search_sites(&tenant_request("tenant-2"), Query(None), Query(None), Query(None), Query(None), Query(None), Query(None), local())
+            .await
+            .unwrap();
+        assert!(matches!(unmapped, SearchSitesResponse::Unauthorized));
+
+        // Without the sync there is no read model to serve from
+        let response = create_api(&mock_server)
+            .search_sites(&tenant_request("tenant-1"), Query(None), Query(None), Query(None), Query(None), Query(None), Query(None), local())
+            .await
+            .unwrap();
+        assert!(matches!(response, SearchSitesResponse::BadRequest(_)));
+    }
+
+    fn reader_request(tenant_id: &str) -> Request {
+        Request::builder()
+            .header("X-Tenant-Id", tenant_id)
+            .header("X-User-Id", "alice")
+            .header("X-Roles", "reader")
+            .finish()
+    }
+
+    #[tokio::test]
+    async fn test_site_changes_pages_through_changelog() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .and(query_param("id__in", "7"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 1,
+                "results": [{"id": 7, "name": "Berlin DC1", "slug": "berlin-dc1", "tenant": 10}]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .and(query_param("id__in", "8"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 1,
+                "results": [{"id": 8, "name": "Paris DC1", "slug": "paris-dc1", "tenant": 20}]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/extras/object-changes/"))
+            .and(query_param("changed_object_type", "dcim.site"))
+            .and(query_param("changed_object_id", "7"))
+            .and(query_param("offset", "0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 3,
+                "next": format!("{}/api/extras/object-changes/?limit=2&offset=2", mock_server.uri()),
+                "results": [
+                    {
+                        "id": 31, "time": "2026-10-02T09:30:00Z", "user_name": "bob", "request_id": "r-3",
+                        "action": {"value": "update", "label": "Updated"},
+                        "changed_object_type": "dcim.site", "changed_object_id": 7,
+                        "prechange_data": {"status": "planned"}, "postchange_data": {"status": "active"}
+                    },
+                    {
+                        "id": 22, "time": "2026-10-01T12:00:00Z", "user_name": "carol", "request_id": "r-2",
+                        "action": {"value": "update", "label": "Updated"},
+                        "changed_object_type": "dcim.site", "changed_object_id": 7,
+                        "prechange_data": {"description": ""}, "postchange_data": {"description": "Main DC"}
+                    }
+                ]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/extras/object-changes/"))
+            .and(query_param("changed_object_id", "7"))
+            .and(query_param("offset", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 3,
+                "next": null,
+                "results": [{
+                    "id": 10, "time": "2026-09-30T08:00:00Z", "user_name": "netgate", "request_id": "r-1",
+                    "action": {"value": "create", "label": "Created"},
+                    "changed_object_type": "dcim.site", "changed_object_id": 7,
+                    "prechange_data": null, "postchange_data": {"name": "Berlin DC1", "status": "planned"}
+                }]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let api = create_api(&mock_server);
+
+        let first = api
+            .site_changes(&reader_request("tenant-1"), Path(7), Query(Some(2)), Query(None))
+            .await
+            .unwrap();
+        let SiteChangesResponse::Ok(Json(first)) = first else {
+            panic!("Expected Ok response");
+        };
+        assert_eq!(first.total, Some(3));
+        assert!(first.has_more);
+        assert_eq!(first.results.iter().map(|c| c.id).collect::<Vec<_>>(), vec![31, 22]);
+        assert_eq!(first.results[0].user.as_deref(), Some("bob"));
+        assert_eq!(first.results[0].action, "update");
+        assert_eq!(first.results[0].prechange_data, Some(json!({"status": "planned"})));
+
+        let second = api
+            .site_changes(&reader_request("tenant-1"), Path(7), Query(Some(2)), Query(Some(2)))
+            .await
+            .unwrap();
+        let SiteChangesResponse::Ok(Json(second)) = second else {
+            panic!("Expected Ok response");
+        };
+        assert!(!second.has_more);
+        assert_eq!(second.offset, 2);
+        assert_eq!(second.results.len(), 1);
+        assert_eq!(second.results[0].action, "create");
+        assert_eq!(second.results[0].prechange_data, None);
+        assert_eq!(second.results[0].time, "2026-09-30T08:00:00+00:00");
+
+        // Another tenant's site looks like a missing one, and its history isn't fetched
+        let other = api
+            .site_changes(&reader_request("tenant-1"), Path(8), Query(None), Query(None))
+            .await
+            .unwrap();
+        assert!(matches!(other, SiteChangesResponse::NotFound(_)));
+
+        let viewer = Request::builder()
+            .header("X-Tenant-Id", "tenant-1")
+            .header("X-User-Id", "dave")
+            .header("X-Roles", "viewer")
+            .finish();
+        let refused = api.site_changes(&viewer, Path(7), Query(None), Query(None)).await;
+        assert_eq!(refused.err().unwrap().status(), poem::http::StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_list_vlans_is_tenant_scoped() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/ipam/vlans/"))
+            .and(query_param("tenant_id", "10"))
+            .and(query_param("site_id", "7"))
+            .and(query_param("group_id", "3"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 2,
+                "results": [
+                    { "id": 40, "vid": 100, "name": "users", "site": 7, "group": 3, "tenant": 10, "status": "active" },
+                    { "id": 41, "vid": 101, "name": "other", "site": 7, "group": 3, "tenant": 20 }
+                ]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let api = create_api(&mock_server);
+
+        let response = api
+            .list_vlans(&tenant_request("tenant-1"), Query(Some(7)), Query(Some(3)), Query(None), Query(None))
+            .await
+            .unwrap();
+
+        let ListVlansResponse::Ok(Json(page)) = response else {
+            panic!("Expected Ok response");
+        };
+        assert_eq!(page.results.len(), 1);
+        assert_eq!(page.results[0].vid, 100);
+        assert_eq!(page.results[0].status.as_deref(), Some("active"));
+
+        let unmapped = api
+            .list_vlans(&tenant_request("tenant-3"), Query(None), Query(None), Query(None), Query(None))
+            .await
+            .unwrap();
+        assert!(matches!(unmapped, ListVlansResponse::Unauthorized));
+    }
+
+    #[tokio::test]
+    async fn test_reports_are_tenant_scoped() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .and(query_param("id__in", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 1, "results": [{ "id": 2, "name": "Theirs", "tenant": 20 }]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .and(query_param("tenant_id", "10"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "count": 0, "results": [] })))
+            .mount(&mock_server)
+            .await;
+        let api = create_api(&mock_server);
+
+        let foreign = api.site_report(&tenant_request("tenant-1"), Path(2)).await.unwrap();
+        assert!(matches!(foreign, SiteReportResponse::NotFound(_)));
+
+        let CapacityReportResponse::Ok(Json(report)) = api.capacity_report(&tenant_request("tenant-1")).await.unwrap()
+        else {
+            panic!("Expected Ok response");
+        };
+        assert!(report.sites.is_empty());
+        assert!(report.complete);
+        let unmapped = api.capacity_report(&tenant_request("tenant-3")).await.unwrap();
+        assert!(matches!(unmapped, CapacityReportResponse::Unauthorized));
+    }
+}
@@ -1,24 +1,79 @@
-use poem_openapi::{payload::Json, ApiResponse, OpenApi};
+use poem_openapi::{
+    payload::{Json, PlainText},
+    ApiResponse, OpenApi,
+};
+use std::collections::HashMap;
+use std::fmt::Write;
 use std::sync::Arc;
 
-use crate::netbox::ResilientNetBoxClient;
+use crate::business::{OrderQueue, OrderState, WorkflowManager};
+use crate::cache::CacheStats;
+use crate::domain::OrderPriority;
+use crate::netbox::cached_client::CachedNetBoxClient;
+use crate::netbox::{NetBoxRouter, ResilientNetBoxClient};
+use crate::sync::{SyncService, SyncStatus};
 
 pub struct MetricsApi {
     netbox_client: Option<Arc<ResilientNetBoxClient>>,
+    cached_client: Option<Arc<CachedNetBoxClient>>,
+    workflow_manager: Option<Arc<WorkflowManager>>,
+    netbox_router: Option<Arc<NetBoxRouter>>,
+    sync: Option<Arc<SyncService>>,
+    scheduled_queue: Option<Arc<OrderQueue<String>>>,
 }
 
 impl MetricsApi {
     pub fn new() -> Self {
         Self {
             netbox_client: None,
+            cached_client: None,
+            workflow_manager: None,
+            netbox_router: None,
+            sync: None,
+            scheduled_queue: None,
         }
     }
 
     pub fn with_netbox_client(netbox_client: Arc<ResilientNetBoxClient>) -> Self {
         Self {
             netbox_client: Some(netbox_client),
+            cached_client: None,
+            workflow_manager: None,
+            netbox_router: None,
+            sync: None,
+            scheduled_queue: None,
         }
     }
+
+    /// Report the response cache of the tenant-facing NetBox client
+    pub fn with_cached_client(mut self, cached_client: Arc<CachedNetBoxClient>) -> Self {
+        self.cached_client = Some(cached_client);
+        self
+    }
+
+    /// Report order counts and durations
+    pub fn with_workflow_manager(mut self, workflow_manager: Arc<WorkflowManager>) -> Self {
+        self.workflow_manager = Some(workflow_manager);
+        self
+    }
+
+    /// Report the named NetBox endpoints tenants are routed to
+    pub fn with_netbox_router(mut self, router: Arc<NetBoxRouter>) -> Self {
+        self.netbox_router = Some(router);
+        self
+    }
+
+    /// Report read model sync runs
+    pub fn with_sync(mut self, sync: Arc<SyncService>) -> Self {
+        self.sync = Some(sync);
+        self
+    }
+
+    /// Report the queue orders parked during a maintenance freeze are released from
+    pub fn with_scheduled_queue(mut self, queue: Arc<OrderQueue<String>>) -> Self {
+        self.scheduled_queue = Some(queue);
+        self
+    }
 }
 
 impl Default for MetricsApi {
@@ -31,9 +86,50 @@ impl Default for MetricsApi {
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
 pub struct MetricsResponse {
     pub netbox: Option<NetBoxMetrics>,
+    /// Named NetBox endpoints besides the default one
+    pub netbox_endpoints: Option<Vec<NetBoxEndpointMetrics>>,
+    pub workflows: Option<OrderMetrics>,
+    /// Read model sync runs, when enabled
+    pub sync: Option<SyncStatus>,
     pub timestamp: String,
 }
 
+/// Order workflows held in memory, and counts recorded on every transition
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct OrderMetrics {
+    pub total: u64,
+    pub active: u64,
+    pub terminal: u64,
+    /// Orders currently in each non-terminal state
+    pub active_by_state: HashMap<String, u64>,
+    /// Orders that reached each terminal state, per tenant
+    pub terminal_by_tenant: Vec<TenantOrderCount>,
+    /// Time from order creation to Completed
+    pub completion_duration: DurationHistogram,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct TenantOrderCount {
+    pub tenant_id: String,
+    pub state: String,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct DurationHistogram {
+    /// Cumulative counts of observations up to each bound
+    pub buckets: Vec<HistogramBucket>,
+    pub count: u64,
+    pub sum_secs: f64,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct HistogramBucket {
+    /// Upper bound in seconds
+    pub le: f64,
+    pub count: u64,
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
 pub struct NetBoxMetrics {
     pub total_requests: u64,
@@ -42,17 +138,142 @@ pub struct NetBoxMetrics {
     pub success_rate: f64,
     pub failure_rate: f64,
     pub average_response_time_ms: f64,
+    /// Time finished requests took, retries included
+    pub response_time: DurationHistogram,
     pub total_retries: u64,
     pub circuit_breaker_rejections: u64,
+    pub bulkhead_rejections: u64,
+    /// Calls and retry attempts held back by the outbound rate limit
+    pub throttled_requests: u64,
+    /// Time spent waiting on the outbound rate limit
+    pub throttled_time_ms: u64,
+    /// Calls refused because the outbound rate limit would have held them too long
+    pub rate_limit_rejections: u64,
+    pub hedged_requests: u64,
+    pub hedge_wins: u64,
+    /// Attempts abandoned after the per-attempt timeout
+    pub timeouts: u64,
+    /// Calls abandoned after the total deadline across retries
+    pub deadline_exceeded: u64,
+    /// Attempts answered with a non-JSON 502/503/504, typically NetBox's maintenance page
+    pub maintenance_responses: u64,
+    /// Background probes sent while the circuit was open
+    pub recovery_probes: u64,
+    pub recovery_probe_failures: u64,
+    /// Times background probes closed the circuit
+    pub probe_recoveries: u64,
+    /// Attempts failed or delayed by fault injection, see `/admin/chaos`
+    pub injected_errors: u64,
+    pub injected_delays: u64,
+    /// Failed calls that had an injected error among their attempts
+    pub injected_failures: u64,
     pub circuit_breaker_state: String,
 }
 
+/// Client metrics of one named NetBox endpoint
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct NetBoxEndpointMetrics {
+    pub name: String,
+    pub netbox: NetBoxMetrics,
+}
+
+/// Everything the metrics API knows, each component under its own key
+///
+/// Components that aren't configured are absent.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct MetricsSummary {
+    pub timestamp: String,
+    /// Request, retry and rejection counts of the resilient NetBox client
+    pub netbox: Option<NetBoxMetrics>,
+    pub netbox_endpoints: Option<Vec<NetBoxEndpointMetrics>>,
+    pub circuit_breaker: Option<CircuitBreakerSummary>,
+    /// Fallback data served while NetBox is unavailable
+    pub degradation_cache: Option<DegradationCacheSummary>,
+    /// Response cache in front of tenant-scoped NetBox reads
+    pub response_cache: Option<ResponseCacheSummary>,
+    pub workflows: Option<OrderMetrics>,
+    pub sync: Option<SyncStatus>,
+    /// Orders accepted but not yet processing: pending, awaiting approval or validated
+    pub order_queue_depth: Option<u64>,
+    /// Orders parked during a maintenance freeze and their wait before release, per priority
+    pub scheduled_queue: Option<Vec<ScheduledQueueSummary>>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct ScheduledQueueSummary {
+    pub priority: OrderPriority,
+    /// Orders waiting now
+    pub queued: u64,
+    /// Orders taken off the queue so far
+    pub released: u64,
+    pub average_wait_secs: f64,
+    pub max_wait_secs: f64,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct CircuitBreakerSummary {
+    pub state: String,
+    pub failure_count: u32,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct DegradationCacheSummary {
+    pub sites: u64,
+    pub devices: u64,
+    pub site_lists: u64,
+    pub device_lists: u64,
+    pub expired_entries: u64,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct ResponseCacheSummary {
+    pub sites: CacheEntryCounts,
+    pub site_lists: CacheEntryCounts,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: f64,
+    pub evictions: u64,
+    pub invalidations: u64,
+    /// Site list invalidations by tenant scope (`global`, `tenant-<id>`, or `all`)
+    pub list_invalidations_by_scope: HashMap<String, u64>,
+    pub revalidations: u64,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct CacheEntryCounts {
+    pub total: u64,
+    pub valid: u64,
+    pub expired: u64,
+}
+
+impl From<&CacheStats> for CacheEntryCounts {
+    fn from(stats: &CacheStats) -> Self {
+        Self {
+            total: stats.total_entries as u64,
+            valid: stats.valid_entries as u64,
+            expired: stats.expired_entries as u64,
+        }
+    }
+}
+
+#[derive(ApiResponse)]
+pub enum GetMetricsSummaryResponse {
+    #[oai(status = 200)]
+    Ok(Json<MetricsSummary>),
+}
+
 #[derive(ApiResponse)]
 pub enum GetMetricsResponse {
     #[oai(status = 200)]
     Ok(Json<MetricsResponse>),
 }
 
+#[derive(ApiResponse)]
+pub enum GetPrometheusMetricsResponse {
+    #[oai(status = 200)]
+    Ok(PlainText<String>),
+}
+
 #[OpenApi]
 impl MetricsApi {
     /// Get metrics for monitoring and observability
@@ -64,35 +285,367 @@ impl MetricsApi {
     /// - Circuit breaker state
     #[oai(path = "/metrics", method = "get")]
     async fn get_metrics(&self) -> GetMetricsResponse {
+        GetMetricsResponse::Ok(Json(self.collect()))
+    }
+
+    /// Get NetBox client, circuit breaker, cache and order metrics in one document
+    #[oai(path = "/metrics/summary", method = "get")]
+    async fn get_metrics_summary(&self) -> GetMetricsSummaryResponse {
+        GetMetricsSummaryResponse::Ok(Json(self.summarize().await))
+    }
+
+    /// Get the same metrics in the Prometheus text exposition format
+    #[oai(path = "/metrics/prometheus", method = "get")]
+    async fn get_prometheus_metrics(&self) -> GetPrometheusMetricsResponse {
+        GetPrometheusMetricsResponse::Ok(PlainText(render_prometheus(&self.collect())))
+    }
+}
+
+impl MetricsApi {
+    fn collect(&self) -> MetricsResponse {
         let mut response = MetricsResponse {
-            netbox: None,
+            netbox: self.netbox_client.as_deref().map(netbox_metrics),
+            netbox_endpoints: self.netbox_router.as_ref().map(|router| {
+                router
+                    .endpoints()
+                    .iter()
+                    .skip(1)
+                    .map(|endpoint| NetBoxEndpointMetrics {
+                        name: endpoint.name.clone(),
+                        netbox: netbox_metrics(&endpoint.client),
+                    })
+                    .collect()
+            }),
+            workflows: None,
+            sync: self.sync.as_ref().map(|sync| sync.status()),
             timestamp: chrono::Utc::now().to_rfc3339(),
         };
 
+
+        if let Some(ref manager) = self.workflow_manager {
+            let total = manager.order_count() as u64;
+            let active = manager.active_order_count() as u64;
+            let snapshot = manager.metrics().snapshot();
+            response.workflows = Some(OrderMetrics {
+                total,
+                active,
+                terminal: total.saturating_sub(active),
+                active_by_state: snapshot
+                    .active
+                    .iter()
+                    .map(|(state, count)| (state.as_str().to_string(), *count))
+                    .collect(),
+                terminal_by_tenant: snapshot
+                    .terminal
+                    .iter()
+                    .map(|(tenant_id, state, count)| TenantOrderCount {
+                        tenant_id: tenant_id.clone(),
+                        state: state.as_str().to_string(),
+                        count: *count,
+                    })
+                    .collect(),
+                completion_duration: DurationHistogram {
+                    buckets: snapshot
+                        .completion_buckets
+                        .iter()
+                        .map(|(le, count)| HistogramBucket { le: *le, count: *count })
+                        .collect(),
+                    count: snapshot.completion_count,
+                    sum_secs: snapshot.completion_sum_secs,
+                },
+            });
+        }
+
+        response
+    }
+}
+
+impl MetricsApi {
+    /// Gather the summary; only the response cache stats need awaiting
+    async fn summarize(&self) -> MetricsSummary {
+        let MetricsResponse { netbox, netbox_endpoints, workflows, sync, timestamp } = self.collect();
+        let mut summary = MetricsSummary {
+            timestamp,
+            netbox,
+            netbox_endpoints,
+            circuit_breaker: None,
+            degradation_cache: None,
+            response_cache: None,
+            workflows,
+            sync,
+            order_queue_depth: None,
+            scheduled_queue: None,
+        };
+
         if let Some(ref client) = self.netbox_client {
-            let metrics_snapshot = client.metrics();
-            let cb_state = client.circuit_breaker_state();
-
-            response.netbox = Some(NetBoxMetrics {
-                total_requests: metrics_snapshot.total_requests,
-                successful_requests: metrics_snapshot.successful_requests,
-                failed_requests: metrics_snapshot.failed_requests,
-                success_rate: metrics_snapshot.success_rate,
-                failure_rate: metrics_snapshot.failure_rate,
-                average_response_time_ms: metrics_snapshot.average_response_time_ms,
-                total_retries: metrics_snapshot.total_retries,
-                circuit_breaker_rejections: metrics_snapshot.circuit_breaker_rejections,
-                circuit_breaker_state: format!("{:?}", cb_state),
+            summary.circuit_breaker = Some(CircuitBreakerSummary {
+                state: format!("{:?}", client.circuit_breaker_state()),
+                failure_count: client.circuit_breaker_failure_count(),
+            });
+            let stats = client.degradation_cache_stats();
+            summary.degradation_cache = Some(DegradationCacheSummary {
+                sites: stats.sites as u64,
+                devices: stats.devices as u64,
+                site_lists: stats.site_lists as u64,
+                device_lists: stats.device_lists as u64,
+                expired_entries: stats.expired_entries as u64,
+            });
+        }
+
+        if let Some(ref cached) = self.cached_client {
+            let stats = cached.cache_stats().await;
+            summary.response_cache = Some(ResponseCacheSummary {
+                sites: (&stats.site_cache).into(),
+                site_lists: (&stats.site_list_cache).into(),
+                hits: stats.metrics.hits,
+                misses: stats.metrics.misses,
+                hit_rate: stats.metrics.hit_rate,
+                evictions: stats.metrics.evictions,
+                invalidations: stats.metrics.invalidations,
+                list_invalidations_by_scope: stats.metrics.list_invalidations_by_scope,
+                revalidations: stats.metrics.revalidations,
             });
         }
 
-        GetMetricsResponse::Ok(Json(response))
+        if let Some(ref manager) = self.workflow_manager {
+            let snapshot = manager.metrics().snapshot();
+            summary.order_queue_depth = Some(
+                [OrderState::Pending, OrderState::AwaitingApproval, OrderState::Validated, OrderState::Scheduled]
+                    .into_iter()
+                    .map(|state| snapshot.active_count(state))
+                    .sum(),
+            );
+        }
+
+        if let Some(ref queue) = self.scheduled_queue {
+            let depths = queue.depth_by_priority();
+            summary.scheduled_queue = Some(
+                queue
+                    .wait_stats()
+                    .into_iter()
+                    .zip(depths)
+                    .map(|((priority, waits), (_, queued))| ScheduledQueueSummary {
+                        priority,
+                        queued: queued as u64,
+                        released: waits.dequeued,
+                        average_wait_secs: if waits.dequeued == 0 { 0.0 } else { waits.wait_sum_secs / waits.dequeued as f64 },
+                        max_wait_secs: waits.max_wait_secs,
+                    })
+                    .collect(),
+            );
+        }
+
+        summary
+    }
+}
+
+/// Request counts and circuit breaker state of a resilient NetBox client
+fn netbox_metrics(client: &ResilientNetBoxClient) -> NetBoxMetrics {
+    let metrics_snapshot = client.metrics();
+    NetBoxMetrics {
+        total_requests: metrics_snapshot.total_requests,
+        successful_requests: metrics_snapshot.successful_requests,
+        failed_requests: metrics_snapshot.failed_requests,
+        success_rate: metrics_snapshot.success_rate,
+        failure_rate: metrics_snapshot.failure_rate,
+        average_response_time_ms: metrics_snapshot.average_response_time_ms,
+        response_time: DurationHistogram {
+            buckets: metrics_snapshot
+                .response_time_histogram
+                .iter()
+                .filter(|(bound, _)| *bound != u64::MAX)
+                .map(|(bound, count)| HistogramBucket { le: *bound as f64 / 1000.0, count: *count })
+                .collect(),
+            count: metrics_snapshot.response_time_histogram.last().map_or(0, |(_, count)| *count),
+            sum_secs: metrics_snapshot.total_response_time_ms as f64 / 1000.0,
+        },
+        total_retries: metrics_snapshot.total_retries,
+        circuit_breaker_rejections: metrics_snapshot.circuit_breaker_rejections,
+        bulkhead_rejections: metrics_snapshot.bulkhead_rejections,
+        throttled_requests: metrics_snapshot.throttled_requests,
+        throttled_time_ms: metrics_snapshot.throttled_time_ms,
+        rate_limit_rejections: metrics_snapshot.rate_limit_rejections,
+        hedged_requests: metrics_snapshot.hedged_requests,
+        hedge_wins: metrics_snapshot.hedge_wins,
+        timeouts: metrics_snapshot.timeouts,
+        deadline_exceeded: metrics_snapshot.deadline_exceeded,
+        maintenance_responses: metrics_snapshot.maintenance_responses,
+        recovery_probes: metrics_snapshot.recovery_probes,
+        recovery_probe_failures: metrics_snapshot.recovery_probe_failures,
+        probe_recoveries: metrics_snapshot.probe_recoveries,
+        injected_errors: metrics_snapshot.injected_errors,
+        injected_delays: metrics_snapshot.injected_delays,
+        injected_failures: metrics_snapshot.injected_failures,
+        circuit_breaker_state: format!("{:?}", client.circuit_breaker_state()),
+    }
+}
+
+/// Escape a Prometheus label value
+fn label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Reads one counter out of a NetBox client's metrics
+type NetBoxCounter = fn(&NetBoxMetrics) -> u64;
+
+/// Render metrics in the Prometheus text exposition format
+fn render_prometheus(metrics: &MetricsResponse) -> String {
+    let mut out = String::new();
+
+    if let Some(ref netbox) = metrics.netbox {
+        let counters: [(&str, &str, NetBoxCounter); 16] = [
+            ("netgate_netbox_requests_total", "NetBox requests", |m| m.total_requests),
+            ("netgate_netbox_failed_requests_total", "Failed NetBox requests", |m| m.failed_requests),
+            ("netgate_netbox_retries_total", "NetBox request retries", |m| m.total_retries),
+            (
+                "netgate_netbox_circuit_breaker_rejections_total",
+                "NetBox requests rejected by the circuit breaker",
+                |m| m.circuit_breaker_rejections,
+            ),
+            (
+                "netgate_netbox_throttled_requests_total",
+                "NetBox calls and retry attempts held back by the outbound rate limit",
+                |m| m.throttled_requests,
+            ),
+            (
+                "netgate_netbox_throttled_milliseconds_total",
+                "Time NetBox calls spent waiting on the outbound rate limit",
+                |m| m.throttled_time_ms,
+            ),
+            (
+                "netgate_netbox_rate_limit_rejections_total",
+                "NetBox calls refused by the outbound rate limit",
+                |m| m.rate_limit_rejections,
+            ),
+            ("netgate_netbox_timeouts_total", "NetBox attempts that timed out", |m| m.timeouts),
+            (
+                "netgate_netbox_deadline_exceeded_total",
+                "NetBox calls that ran past their total deadline",
+                |m| m.deadline_exceeded,
+            ),
+            (
+                "netgate_netbox_maintenance_responses_total",
+                "NetBox attempts answered with a non-JSON 502/503/504, e.g. a maintenance page",
+                |m| m.maintenance_responses,
+            ),
+            ("netgate_netbox_recovery_probes_total", "Background NetBox recovery probes", |m| m.recovery_probes),
+            (
+                "netgate_netbox_recovery_probe_failures_total",
+                "Background NetBox recovery probes that failed",
+                |m| m.recovery_probe_failures,
+            ),
+            (
+                "netgate_netbox_probe_recoveries_total",
+                "Circuit breaker closures triggered by recovery probes",
+                |m| m.probe_recoveries,
+            ),
+            ("netgate_netbox_injected_errors_total", "NetBox attempts failed by fault injection", |m| {
+                m.injected_errors
+            }),
+            ("netgate_netbox_injected_delays_total", "NetBox attempts delayed by fault injection", |m| {
+                m.injected_delays
+            }),
+            (
+                "netgate_netbox_injected_failures_total",
+                "Failed NetBox calls that had an injected error",
+                |m| m.injected_failures,
+            ),
+        ];
+        let endpoints = metrics.netbox_endpoints.as_deref().unwrap_or_default();
+        for (name, help, value) in counters {
+            let _ = writeln!(out, "# HELP {} {}\n# TYPE {} counter\n{} {}", name, help, name, name, value(netbox));
+            // Named endpoints share the family, told apart by their label
+            for endpoint in endpoints {
+                let _ = writeln!(out, "{}{{endpoint=\"{}\"}} {}", name, label(&endpoint.name), value(&endpoint.netbox));
+            }
+        }
+
+        let name = "netgate_netbox_response_time_seconds";
+        let _ = writeln!(out, "# HELP {} Time finished NetBox requests took, retries included", name);
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        let histograms = std::iter::once((None, &netbox.response_time))
+            .chain(endpoints.iter().map(|endpoint| (Some(label(&endpoint.name)), &endpoint.netbox.response_time)));
+        for (endpoint, histogram) in histograms {
+            let labels = endpoint.map(|endpoint| format!("endpoint=\"{}\",", endpoint)).unwrap_or_default();
+            for bucket in &histogram.buckets {
+                let _ = writeln!(out, "{}_bucket{{{}le=\"{}\"}} {}", name, labels, bucket.le, bucket.count);
+            }
+            let _ = writeln!(out, "{}_bucket{{{}le=\"+Inf\"}} {}", name, labels, histogram.count);
+            let labels = labels.trim_end_matches(',');
+            let labels = if labels.is_empty() { String::new() } else { format!("{{{}}}", labels) };
+            let _ = writeln!(out, "{}_sum{} {}", name, labels, histogram.sum_secs);
+            let _ = writeln!(out, "{}_count{} {}", name, labels, histogram.count);
+        }
+    }
+
+    if let Some(ref orders) = metrics.workflows {
+        let _ = writeln!(out, "# HELP netgate_orders_in_memory Order workflows held in memory");
+        let _ = writeln!(out, "# TYPE netgate_orders_in_memory gauge");
+        let _ = writeln!(out, "netgate_orders_in_memory {}", orders.total);
+
+        let _ = writeln!(out, "# HELP netgate_orders_active Orders currently in each non-terminal state");
+        let _ = writeln!(out, "# TYPE netgate_orders_active gauge");
+        let mut active: Vec<_> = orders.active_by_state.iter().collect();
+        active.sort();
+        for (state, count) in active {
+            let _ = writeln!(out, "netgate_orders_active{{state=\"{}\"}} {}", label(state), count);
+        }
+
+        let _ = writeln!(out, "# HELP netgate_orders_terminal_total Orders that reached a terminal state");
+        let _ = writeln!(out, "# TYPE netgate_orders_terminal_total counter");
+        for entry in &orders.terminal_by_tenant {
+            let _ = writeln!(
+                out,
+                "netgate_orders_terminal_total{{tenant=\"{}\",state=\"{}\"}} {}",
+                label(&entry.tenant_id),
+                label(&entry.state),
+                entry.count
+            );
+        }
+
+        let histogram = &orders.completion_duration;
+        let name = "netgate_order_completion_duration_seconds";
+        let _ = writeln!(out, "# HELP {} Time from order creation to Completed", name);
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        for bucket in &histogram.buckets {
+            let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bucket.le, bucket.count);
+        }
+        let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, histogram.count);
+        let _ = writeln!(out, "{}_sum {}", name, histogram.sum_secs);
+        let _ = writeln!(out, "{}_count {}", name, histogram.count);
+    }
+
+    if let Some(ref sync) = metrics.sync {
+        let _ = writeln!(out, "# HELP netgate_sync_runs_total Read model sync runs");
+        let _ = writeln!(out, "# TYPE netgate_sync_runs_total counter");
+        let _ = writeln!(out, "netgate_sync_runs_total {}", sync.runs);
+
+        let _ = writeln!(out, "# HELP netgate_sync_last_duration_seconds Time the last sync run took");
+        let _ = writeln!(out, "# TYPE netgate_sync_last_duration_seconds gauge");
+        let _ = writeln!(
+            out,
+            "netgate_sync_last_duration_seconds {}",
+            sync.last_duration_ms.unwrap_or(0) as f64 / 1000.0
+        );
+
+        let _ = writeln!(out, "# HELP netgate_sync_last_errors Tenants the last sync run failed for");
+        let _ = writeln!(out, "# TYPE netgate_sync_last_errors gauge");
+        let _ = writeln!(out, "netgate_sync_last_errors {}", sync.errors.len());
+
+        let _ = writeln!(out, "# HELP netgate_sync_objects Objects mirrored in the read model");
+        let _ = writeln!(out, "# TYPE netgate_sync_objects gauge");
+        let _ = writeln!(out, "netgate_sync_objects{{kind=\"site\"}} {}", sync.sites);
+        let _ = writeln!(out, "netgate_sync_objects{{kind=\"device\"}} {}", sync.devices);
     }
+
+    out
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::business::OrderQueueConfig;
+    use crate::resilience::metrics::RESPONSE_TIME_BUCKETS_MS;
     use crate::config::Config;
     use crate::netbox::client::NetBoxClient;
     use serde_json::json;
@@ -111,6 +664,7 @@ mod tests {
             port: 8080,
             netbox_url: "http://localhost:8000".to_string(),
             netbox_token: "test-token".to_string(),
+            ..Default::default()
         };
         let netbox_client = Arc::new(NetBoxClient::new(config).unwrap());
         let resilient_client = Arc::new(ResilientNetBoxClient::new(netbox_client));
@@ -126,6 +680,7 @@ mod tests {
             port: 8080,
             netbox_url: mock_server.uri(),
             netbox_token: "test-token".to_string(),
+            ..Default::default()
         };
         let netbox_client = Arc::new(NetBoxClient::new(config).unwrap());
         let resilient_client = Arc::new(ResilientNetBoxClient::new(netbox_client));
@@ -156,10 +711,84 @@ mod tests {
                 assert!(netbox_metrics.successful_requests > 0);
                 assert!(netbox_metrics.success_rate > 0.0);
                 assert!(netbox_metrics.average_response_time_ms >= 0.0);
+                assert_eq!(netbox_metrics.response_time.count, netbox_metrics.successful_requests);
+                assert_eq!(netbox_metrics.response_time.buckets.len(), RESPONSE_TIME_BUCKETS_MS.len());
             }
         }
     }
 
+    #[tokio::test]
+    async fn test_get_metrics_reports_workflow_counts() {
+        let manager = Arc::new(WorkflowManager::new());
+        let cancelled = manager.create_order("t1".to_string());
+        manager.update_order_state(&cancelled, OrderState::Cancelled).unwrap();
+        manager.create_order("t1".to_string());
+        let api = MetricsApi::new().with_workflow_manager(manager);
+
+        let GetMetricsResponse::Ok(Json(metrics)) = api.get_metrics().await;
+        let workflows = metrics.workflows.unwrap();
+        assert_eq!(workflows.total, 2);
+        assert_eq!(workflows.active, 1);
+        assert_eq!(workflows.terminal, 1);
+        assert_eq!(workflows.active_by_state.get("pending"), Some(&1));
+        assert_eq!(workflows.terminal_by_tenant.len(), 1);
+        assert_eq!(workflows.terminal_by_tenant[0].state, "cancelled");
+    }
+
+    #[tokio::test]
+    async fn test_prometheus_metrics_expose_netbox_response_times() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 1, "name": "Test Site"})))
+            .mount(&mock_server)
+            .await;
+        let config = Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let resilient_client = Arc::new(ResilientNetBoxClient::new(Arc::new(NetBoxClient::new(config).unwrap())));
+        let _ = resilient_client.get_site(1).await;
+        let api = MetricsApi::with_netbox_client(resilient_client);
+
+        let GetPrometheusMetricsResponse::Ok(PlainText(body)) = api.get_prometheus_metrics().await;
+        let lines: Vec<&str> = body.lines().collect();
+        assert!(lines.contains(&"# TYPE netgate_netbox_response_time_seconds histogram"));
+        assert!(lines.contains(&"netgate_netbox_response_time_seconds_bucket{le=\"10\"} 1"));
+        assert!(lines.contains(&"netgate_netbox_response_time_seconds_bucket{le=\"+Inf\"} 1"));
+        assert!(lines.contains(&"netgate_netbox_response_time_seconds_count 1"));
+    }
+
+    #[tokio::test]
+    async fn test_prometheus_metrics_expose_order_counts() {
+        let manager = Arc::new(WorkflowManager::new());
+        for tenant in ["t1", "t1", "t2"] {
+            let order_id = manager.create_order(tenant.to_string());
+            manager.mark_order_failed(&order_id, "invalid".to_string()).unwrap();
+        }
+        let completed = manager.create_order("t1".to_string());
+        manager.update_order_state(&completed, OrderState::Validated).unwrap();
+        manager.update_order_state(&completed, OrderState::Processing).unwrap();
+        manager.mark_order_completed(&completed, 7).unwrap();
+        let processing = manager.create_order("t2\"x".to_string());
+        manager.update_order_state(&processing, OrderState::Validated).unwrap();
+        manager.update_order_state(&processing, OrderState::Processing).unwrap();
+        let api = MetricsApi::new().with_workflow_manager(manager);
+
+        let GetPrometheusMetricsResponse::Ok(PlainText(body)) = api.get_prometheus_metrics().await;
+        let lines: Vec<&str> = body.lines().collect();
+        assert!(lines.contains(&"netgate_orders_in_memory 5"));
+        assert!(lines.contains(&"netgate_orders_active{state=\"processing\"} 1"));
+        assert!(lines.contains(&"netgate_orders_terminal_total{tenant=\"t1\",state=\"failed\"} 2"));
+        assert!(lines.contains(&"netgate_orders_terminal_total{tenant=\"t2\",state=\"failed\"} 1"));
+        assert!(lines.contains(&"netgate_orders_terminal_total{tenant=\"t1\",state=\"completed\"} 1"));
+        assert!(lines.contains(&"netgate_order_completion_duration_seconds_bucket{le=\"+Inf\"} 1"));
+        assert!(lines.contains(&"netgate_order_completion_duration_seconds_count 1"));
+        assert!(lines.contains(&"# TYPE netgate_order_completion_duration_seconds histogram"));
+        assert!(!body.contains("netgate_netbox_requests_total"));
+    }
+
     #[tokio::test]
     async fn test_get_metrics_with_failures() {
         let mock_server = MockServer::start().await;
@@ -167,6 +796,7 @@ mod tests {
             port: 8080,
             netbox_url: mock_server.uri(),
             netbox_token: "test-token".to_string(),
+            ..Default::default()
         };
         let netbox_client = Arc::new(NetBoxClient::new(config).unwrap());
         let resilient_client = Arc::new(ResilientNetBoxClient::new(netbox_client));
@@ -201,6 +831,7 @@ mod tests {
             port: 8080,
             netbox_url: mock_server.uri(),
             netbox_token: "test-token".to_string(),
+            ..Default::default()
         };
         let netbox_client = Arc::new(NetBoxClient::new(config).unwrap());
         let resilient_client = Arc::new(ResilientNetBoxClient::new(netbox_client));
@@ -231,5 +862,88 @@ mod tests {
             }
         }
     }
-}
 
+    #[tokio::test]
+    async fn test_metrics_summary_endpoint_reports_every_component() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 1, "name": "Site 1"})))
+            .mount(&mock_server)
+            .await;
+        let config = Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let resilient_client = Arc::new(ResilientNetBoxClient::new(Arc::new(NetBoxClient::new(config).unwrap())));
+        let cached_client = Arc::new(CachedNetBoxClient::new(resilient_client.clone()));
+        cached_client.get_site(1).await.unwrap();
+        cached_client.get_site(1).await.unwrap();
+
+        let manager = Arc::new(WorkflowManager::new());
+        manager.create_order("t1".to_string());
+        let validated = manager.create_order("t1".to_string());
+        manager.update_order_state(&validated, OrderState::Validated).unwrap();
+        manager.update_order_state(&validated, OrderState::Processing).unwrap();
+        manager.create_order("t2".to_string());
+
+        let queue = Arc::new(OrderQueue::new(OrderQueueConfig::default()));
+        queue.push("t1".to_string(), OrderPriority::High, "order-1".to_string()).unwrap();
+        queue.push("t1".to_string(), OrderPriority::Low, "order-2".to_string()).unwrap();
+        queue.try_pop().unwrap();
+
+        let api = MetricsApi::with_netbox_client(resilient_client)
+            .with_cached_client(cached_client)
+            .with_workflow_manager(manager)
+            .with_scheduled_queue(queue);
+        let service = poem_openapi::OpenApiService::new(api, "test", "1.0");
+        let client = poem::test::TestClient::new(poem::Route::new().nest("/", service));
+
+        let response = client.get("/metrics/summary").send().await;
+        response.assert_status_is_ok();
+        let body = response.0.into_body().into_string().await.unwrap();
+
+        let value: serde_json::Value = serde_json::from_str(&body).unwrap();
+        for key in [
+            "timestamp",
+            "netbox",
+            "circuit_breaker",
+            "degradation_cache",
+            "response_cache",
+            "workflows",
+            "order_queue_depth",
+            "scheduled_queue",
+        ] {
+            assert!(!value[key].is_null(), "missing {} in {}", key, body);
+        }
+        let summary: MetricsSummary = serde_json::from_value(value).unwrap();
+        assert_eq!(summary.netbox.unwrap().total_requests, 1);
+        assert_eq!(summary.circuit_breaker.unwrap().state, "Closed");
+        assert_eq!(summary.degradation_cache.unwrap().sites, 1);
+        let response_cache = summary.response_cache.unwrap();
+        assert_eq!((response_cache.hits, response_cache.misses), (1, 1));
+        assert_eq!(response_cache.sites.valid, 1);
+        assert_eq!(summary.workflows.unwrap().total, 3);
+        assert_eq!(summary.order_queue_depth, Some(2));
+        let scheduled_queue = summary.scheduled_queue.unwrap();
+        let counts: Vec<_> = scheduled_queue.iter().map(|stats| (stats.priority, stats.queued, stats.released)).collect();
+        assert_eq!(
+            counts,
+            vec![(OrderPriority::High, 0, 1), (OrderPriority::Normal, 0, 0), (OrderPriority::Low, 1, 0)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_metrics_summary_omits_unconfigured_components() {
+        let summary = MetricsApi::new().summarize().await;
+
+        assert!(summary.netbox.is_none());
+        assert!(summary.circuit_breaker.is_none());
+        assert!(summary.degradation_cache.is_none());
+        assert!(summary.response_cache.is_none());
+        assert!(summary.order_queue_depth.is_none());
+        assert!(summary.scheduled_queue.is_none());
+        assert!(!summary.timestamp.is_empty());
+    }
+}
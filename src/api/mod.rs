@@ -1,10 +1,21 @@
+pub mod admin;
+pub mod catalog;
 pub mod health;
+pub mod inventory;
 pub mod metrics;
+pub mod netbox_webhooks;
 pub mod orders;
+pub mod projection;
 pub mod tenants;
+pub mod virtual_resources;
 
+pub use admin::*;
+pub use catalog::*;
 pub use health::*;
+pub use inventory::*;
 pub use metrics::*;
+pub use netbox_webhooks::*;
 pub use orders::*;
 pub use tenants::*;
+pub use virtual_resources::*;
 
@@ -0,0 +1,289 @@
+use hmac::Hmac;
+use poem::Request;
+use poem_openapi::{param::Query, payload::Json, ApiResponse, Object, OpenApi};
+use sha2::Sha512;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::netbox::models::NetBoxWebhookEvent;
+use crate::netbox::routing::NetBoxRouter;
+use crate::security::tenant::DEFAULT_NETBOX_ENDPOINT;
+use crate::security::webhook_signature::hmac_matches;
+use crate::sync::{EventOutcome, ObjectKind, SyncService};
+
+/// Header NetBox puts the hex HMAC-SHA512 of the body in, when its webhook has a secret
+pub const NETBOX_SIGNATURE_HEADER: &str = "X-Hook-Signature";
+
+/// Receives NetBox's change webhooks, keeping caches and the sync read model current
+pub struct NetBoxWebhooksApi {
+    router: Option<Arc<NetBoxRouter>>,
+    sync: Option<Arc<SyncService>>,
+    /// Secrets a webhook may be signed with; every webhook is refused when empty
+    secrets: Vec<String>,
+}
+
+impl NetBoxWebhooksApi {
+    pub fn new() -> Self {
+        Self {
+            router: None,
+            sync: None,
+            secrets: Vec::new(),
+        }
+    }
+
+    /// Invalidate the cached sites of the NetBox endpoint a webhook names
+    pub fn with_netbox_router(mut self, router: Arc<NetBoxRouter>) -> Self {
+        self.router = Some(router);
+        self
+    }
+
+    /// Apply webhooks to the sync read model
+    pub fn with_sync(mut self, sync: Arc<SyncService>) -> Self {
+        self.sync = Some(sync);
+        self
+    }
+
+    /// Accept webhooks signed with `secret`, the secret set on the NetBox webhook
+    ///
+    /// Without one, every webhook is refused: an unsigned one could plant or
+    /// delete sites in the read model tenants are served from.
+    ///
+    /// Call again with the old secret while rotating it; a signature under
+    /// either is accepted.
+    pub fn with_secret(mut self, secret: impl Into<String>) -> Self {
+        self.secrets.push(secret.into());
+        self
+    }
+}
+
+impl Default for NetBoxWebhooksApi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What a webhook did
+#[derive(Debug, Clone, Object)]
+pub struct WebhookReceipt {
+    /// "applied", "ignored" (stale or about no mapped tenant) or "resync" (earlier webhooks were missed)
+    pub outcome: String,
+}
+
+#[derive(ApiResponse)]
+pub enum NetBoxWebhookResponse {
+    #[oai(status = 200)]
+    Ok(Json<WebhookReceipt>),
+
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+
+    /// Missing or wrong signature, or no webhook secret configured
+    #[oai(status = 401)]
+    Unauthorized,
+
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<serde_json::Value>),
+}
+
+fn error_body(error: &AppError) -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "error": error.to_string() }))
+}
+
+/// Whether `signature` is the hex HMAC-SHA512 of `body` under one of `secrets`
+fn signature_matches(secrets: &[String], body: &[u8], signature: &str) -> bool {
+    secrets
+        .iter()
+        .any(|secret| hmac_matches::<Hmac<Sha512>>(secret, &[body], signature))
+}
+
+#[OpenApi]
+impl NetBoxWebhooksApi {
+    /// Receive a NetBox change webhook
+    ///
+    /// Point NetBox's site and device webhooks here; `endpoint` names the
+    /// NetBox sending them when there are several. Changed sites are dropped
+    /// from the cache, and the change is applied to the sync read model. A
+    /// change showing earlier webhooks were missed re-reads the tenant's
+    /// objects of that kind in the background. Webhooks must be signed with
+    /// the configured secret; all are refused when none is set.
+    #[oai(path = "/webhooks/netbox", method = "post")]
+    async fn netbox_webhook(
+        &self,
+        req: &Request,
+        endpoint: Query<Option<String>>,
+        body: Vec<u8>,
+    ) -> Result<NetBoxWebhookResponse, poem::Error> {
+        if self.secrets.is_empty() {
+            warn!("Rejected a NetBox webhook: no webhook secret is configured");
+            return Ok(NetBoxWebhookResponse::Unauthorized);
+        }
+        let signature = req.header(NETBOX_SIGNATURE_HEADER).unwrap_or_default();
+        if !signature_matches(&self.secrets, &body, signature) {
+            warn!("Rejected a NetBox webhook with a missing or wrong signature");
+            return Ok(NetBoxWebhookResponse::Unauthorized);
+        }
+        let event: NetBoxWebhookEvent = match serde_json::from_slice(&body) {
+            Ok(event) => event,
+            Err(e) => {
+                let error = AppError::ValidationError(format!("Not a NetBox webhook: {}", e));
+                return Ok(NetBoxWebhookResponse::BadRequest(error_body(&error)));
+            }
+        };
+
+        let Some(ref router) = self.router else {
+            let error = AppError::ServiceUnavailable("NetBox is not configured".to_string());
+            return Ok(NetBoxWebhookResponse::ServiceUnavailable(error_body(&error)));
+        };
+        let endpoint = endpoint.0.unwrap_or_else(|| DEFAULT_NETBOX_ENDPOINT.to_string());
+        let Some(netbox) = router.endpoint(&endpoint) else {
+            let error = AppError::ValidationError(format!("Unknown NetBox endpoint '{}'", endpoint));
+            return Ok(NetBoxWebhookResponse::BadRequest(error_body(&error)));
+        };
+
+        if ObjectKind::from_model(&event.model) == Some(ObjectKind::Site) {
+            if let Some(id) = event.data.get("id").and_then(|id| id.as_i64()) {
+                let tenant = event.data.get("tenant").and_then(|tenant| tenant.as_i64());
+                netbox.cached_client.invalidate_site(id as i32, tenant.map(|tenant| tenant as i32)).await;
+            }
+        }
+
+        let Some(ref sync) = self.sync else {
+            return Ok(NetBoxWebhookResponse::Ok(Json(WebhookReceipt {
+                outcome: "applied".to_string(),
+            })));
+        };
+        let outcome = match sync.apply_event(&endpoint, &event) {
+            Ok(EventOutcome::Applied) => "applied",
+            Ok(EventOutcome::Ignored) => "ignored",
+            Ok(EventOutcome::Gap { tenant, kind }) => {
+                info!(
+                    "Webhooks about tenant '{}' {}s were missed; re-reading them",
+                    tenant,
+                    kind.as_str()
+                );
+                let sync = sync.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = sync.resync(&tenant, kind).await {
+                        warn!("Re-reading tenant '{}' {}s failed: {}", tenant, kind.as_str(), e);
+                    }
+                });
+                "resync"
+            }
+            Err(e) => return Ok(NetBoxWebhookResponse::BadRequest(error_body(&e))),
+        };
+        Ok(NetBoxWebhookResponse::Ok(Json(WebhookReceipt {
+            outcome: outcome.to_string(),
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::netbox::routing::NetBoxEndpoint;
+    use crate::netbox::tenant_client::TenantAwareNetBoxClient;
+    use crate::netbox::NetBoxClient;
+    use crate::security::tenant::{TenantAccessControl, TenantMappingService};
+    use crate::sync::ReadModel;
+    use poem::http::StatusCode;
+    use poem::test::TestClient;
+    use poem_openapi::OpenApiService;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    use hmac::Mac;
+
+    fn sign(secret: &str, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha512>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(body);
+        mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn netbox_with_sync() -> (Arc<NetBoxRouter>, Arc<SyncService>) {
+        let config = Config {
+            netbox_url: "http://127.0.0.1:9".to_string(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let netbox = Arc::new(NetBoxClient::new(config.clone()).unwrap());
+        let mappings = Arc::new(TenantMappingService::from(HashMap::from([("acme".to_string(), 7)])));
+        let router = Arc::new(NetBoxRouter::new(
+            NetBoxEndpoint::new(DEFAULT_NETBOX_ENDPOINT, &config, netbox.clone()),
+            mappings.clone(),
+        ));
+        let access_control = Arc::new(TenantAccessControl::from_shared(mappings.clone()));
+        let client = Arc::new(TenantAwareNetBoxClient::new(netbox, access_control));
+        let sync = Arc::new(SyncService::new(client, mappings, Arc::new(ReadModel::new())));
+        (router, sync)
+    }
+
+    fn site_created() -> Vec<u8> {
+        serde_json::to_vec(&json!({
+            "event": "created",
+            "timestamp": "2024-05-02T10:00:00Z",
+            "model": "site",
+            "username": "admin",
+            "request_id": "5b4c",
+            "data": {"id": 11, "name": "Berlin", "tenant": 7, "last_updated": "2024-05-02T10:00:00Z"},
+            "snapshots": {"prechange": null, "postchange": {}}
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_signed_webhooks_update_the_read_model_of_the_named_endpoint() {
+        let (router, sync) = netbox_with_sync();
+
+        let api = NetBoxWebhooksApi::new()
+            .with_netbox_router(router)
+            .with_sync(sync.clone())
+            .with_secret("hook-secret")
+            .with_secret("old-hook-secret");
+        let cli = TestClient::new(OpenApiService::new(api, "test", "1.0"));
+        let body = site_created();
+
+        let resp = cli.post("/webhooks/netbox").body(body.clone()).send().await;
+        resp.assert_status(StatusCode::UNAUTHORIZED);
+        let resp = cli
+            .post("/webhooks/netbox")
+            .header(NETBOX_SIGNATURE_HEADER, sign("other-secret", &body))
+            .body(body.clone())
+            .send()
+            .await;
+        resp.assert_status(StatusCode::UNAUTHORIZED);
+        let resp = cli
+            .post("/webhooks/netbox")
+            .query("endpoint", &"eu")
+            .header(NETBOX_SIGNATURE_HEADER, sign("old-hook-secret", &body))
+            .body(body.clone())
+            .send()
+            .await;
+        resp.assert_status(StatusCode::BAD_REQUEST);
+        assert_eq!(sync.read_model().counts(), (0, 0));
+
+        let resp = cli
+            .post("/webhooks/netbox")
+            .header(NETBOX_SIGNATURE_HEADER, sign("hook-secret", &body))
+            .body(body)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_json(json!({"outcome": "applied"})).await;
+        assert_eq!(sync.read_model().counts(), (1, 0));
+        assert_eq!(sync.status().events_applied, 1);
+    }
+
+    #[tokio::test]
+    async fn test_webhooks_are_refused_without_a_secret() {
+        let (router, sync) = netbox_with_sync();
+        let api = NetBoxWebhooksApi::new().with_netbox_router(router).with_sync(sync.clone());
+        let cli = TestClient::new(OpenApiService::new(api, "test", "1.0"));
+
+        let resp = cli.post("/webhooks/netbox").body(site_created()).send().await;
+        resp.assert_status(StatusCode::UNAUTHORIZED);
+        assert_eq!(sync.read_model().counts(), (0, 0));
+        assert_eq!(sync.status().events_applied, 0);
+    }
+}
@@ -1,20 +1,69 @@
-use poem::Request;
-use poem_openapi::{payload::Json, ApiResponse, OpenApi, param::Path};
+use poem::{Body, Request};
+use poem_openapi::{
+    param::{Path, Query},
+    payload::{Attachment, AttachmentType, Json, PlainText},
+    ApiResponse, OpenApi,
+};
 use std::sync::Arc;
 
-use crate::business::OrderService;
-use crate::domain::CreateSiteOrder;
+use crate::api::projection::ListView;
+use crate::business::order_export::{export_body, export_rows, ExportFormat, OrderExportFilter};
+use crate::business::{
+    sort_newest_first, EnrichmentReport, ExtensibleOrderService, OrderCursor, OrderNote, OrderService, OrderState,
+    OrderStatus, OrderStep, OrderWorkflow, ProcessedOrderResult, WorkflowManager,
+};
+use crate::domain::{CreatePopOrder, CreateSiteOrder, DecommissionSiteOrder, UpdateSiteOrder};
 use crate::error::AppError;
-use crate::security::extract_tenant_id;
+use crate::netbox::error::NetBoxValidationErrors;
+use crate::security::{extract_tenant_id, require_role, ADMIN_ROLE, APPROVER_ROLE, USER_HEADER};
 
 pub struct OrdersApi {
-    order_service: Arc<OrderService>,
+    /// None when NetBox isn't configured; orders that need it are refused with 503
+    order_service: Option<Arc<OrderService>>,
+    workflow_manager: Arc<WorkflowManager>,
+    extensible_service: Option<Arc<ExtensibleOrderService>>,
 }
 
 impl OrdersApi {
     pub fn new(order_service: Arc<OrderService>) -> Self {
-        Self { order_service }
+        Self {
+            workflow_manager: order_service.workflow_manager().clone(),
+            order_service: Some(order_service),
+            extensible_service: None,
+        }
+    }
+
+    /// Orders API without NetBox: existing orders can be read, new ones get 503
+    pub fn without_netbox(workflow_manager: Arc<WorkflowManager>) -> Self {
+        Self {
+            order_service: None,
+            workflow_manager,
+            extensible_service: None,
+        }
+    }
+
+    /// Serve generic `POST /orders/{type}` orders through the processor registry
+    pub fn with_extensible_service(mut self, service: Arc<ExtensibleOrderService>) -> Self {
+        self.extensible_service = Some(service);
+        self
+    }
+
+    fn registry_not_configured() -> Json<serde_json::Value> {
+        Json(serde_json::json!({ "error": "Order type registry not configured" }))
     }
+
+    fn netbox_not_configured() -> Json<serde_json::Value> {
+        Json(serde_json::json!({ "error": "NetBox integration not configured" }))
+    }
+}
+
+/// 400 body listing NetBox's field-level errors as `{field, messages}` entries
+fn netbox_validation_body(errors: &NetBoxValidationErrors) -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "error": "NetBox validation failed",
+        "message": errors.to_string(),
+        "fields": errors.fields(),
+    }))
 }
 
 /// Response for site order creation
@@ -25,6 +74,24 @@ pub struct SiteOrderResponse {
     pub netbox_site_id: Option<i32>,
     pub state: String,
     pub site_name: String,
+    /// The site's `last_updated`, to send with the next update order
+    pub last_updated: Option<String>,
+    /// Tags and fields enrichment added to the site, and their sources
+    pub applied_enrichment: EnrichmentReport,
+}
+
+impl SiteOrderResponse {
+    fn from_result(result: ProcessedOrderResult, requested_name: String) -> Self {
+        Self {
+            order_id: result.order_id,
+            tenant_id: result.tenant_id,
+            netbox_site_id: result.netbox_site.as_ref().and_then(|site| site.id),
+            last_updated: result.netbox_site.as_ref().and_then(|site| site.last_updated).map(|at| at.to_rfc3339()),
+            state: format!("{:?}", result.workflow_state),
+            site_name: result.netbox_site.map(|site| site.name).unwrap_or(requested_name),
+            applied_enrichment: result.applied_enrichment,
+        }
+    }
 }
 
 #[derive(ApiResponse)]
@@ -32,14 +99,254 @@ pub enum CreateSiteResponse {
     #[oai(status = 201)]
     Created(Json<SiteOrderResponse>),
     
+    /// Order accepted and awaiting operator approval, or scheduled until a maintenance freeze ends
+    #[oai(status = 202)]
+    Accepted(Json<SiteOrderResponse>),
+    
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+    
+    #[oai(status = 401)]
+    Unauthorized,
+    
+    /// A site with the requested name already exists in NetBox
+    #[oai(status = 409)]
+    Conflict(Json<serde_json::Value>),
+    
+    /// NetBox is throttling requests; retry after `Retry-After` seconds
+    #[oai(status = 429)]
+    TooManyRequests(Json<serde_json::Value>, #[oai(header = "Retry-After")] Option<u64>),
+    
+    #[oai(status = 500)]
+    InternalError(Json<serde_json::Value>),
+    
+    /// NetBox could not be reached for the site name check
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<serde_json::Value>),
+}
+
+/// NetBox resource of a multi-resource order and how far it got
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct OrderResourceResponse {
+    /// `site` or `device`
+    pub kind: String,
+    pub name: String,
+    pub netbox_id: Option<i32>,
+    /// `pending`, `completed` or `failed`
+    pub status: String,
+    pub error: Option<String>,
+}
+
+impl From<OrderStep> for OrderResourceResponse {
+    fn from(step: OrderStep) -> Self {
+        Self {
+            kind: format!("{:?}", step.kind).to_lowercase(),
+            name: step.name,
+            netbox_id: step.netbox_id,
+            status: format!("{:?}", step.status).to_lowercase(),
+            error: step.error,
+        }
+    }
+}
+
+/// An operator's note on an order
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct OrderNoteResponse {
+    pub author: String,
+    pub at: String,
+    pub text: String,
+}
+
+impl From<OrderNote> for OrderNoteResponse {
+    fn from(note: OrderNote) -> Self {
+        Self {
+            author: note.author,
+            at: note.at.to_rfc3339(),
+            text: note.text,
+        }
+    }
+}
+
+/// Response for pop order creation
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct PopOrderResponse {
+    pub order_id: String,
+    pub tenant_id: String,
+    pub state: String,
+    /// The pop's site followed by its devices
+    pub resources: Vec<OrderResourceResponse>,
+    /// Tags and fields enrichment added to the pop's site, and their sources
+    pub applied_enrichment: EnrichmentReport,
+}
+
+#[derive(ApiResponse)]
+pub enum CreatePopResponse {
+    #[oai(status = 201)]
+    Created(Json<PopOrderResponse>),
+    
+    /// Order accepted and awaiting operator approval, or scheduled until a maintenance freeze ends
+    #[oai(status = 202)]
+    Accepted(Json<PopOrderResponse>),
+    
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+    
+    #[oai(status = 401)]
+    Unauthorized,
+    
+    /// A site with the requested name already exists in NetBox
+    #[oai(status = 409)]
+    Conflict(Json<serde_json::Value>),
+    
+    /// NetBox is throttling requests; retry after `Retry-After` seconds
+    #[oai(status = 429)]
+    TooManyRequests(Json<serde_json::Value>, #[oai(header = "Retry-After")] Option<u64>),
+    
+    #[oai(status = 500)]
+    InternalError(Json<serde_json::Value>),
+    
+    /// NetBox could not be reached for the site name check
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+pub enum UpdateSiteOrderResponse {
+    #[oai(status = 200)]
+    Ok(Json<SiteOrderResponse>),
+    
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+    
+    #[oai(status = 401)]
+    Unauthorized,
+    
+    /// The update would move the site to another tenant
+    #[oai(status = 403)]
+    Forbidden(Json<serde_json::Value>),
+    
+    #[oai(status = 404)]
+    NotFound(Json<serde_json::Value>),
+    
+    /// The site changed since the `last_updated` the caller sent
+    #[oai(status = 409)]
+    Conflict(Json<serde_json::Value>),
+    
+    /// NetBox is throttling requests; retry after `Retry-After` seconds
+    #[oai(status = 429)]
+    TooManyRequests(Json<serde_json::Value>, #[oai(header = "Retry-After")] Option<u64>),
+    
+    #[oai(status = 500)]
+    InternalError(Json<serde_json::Value>),
+    
+    /// Tenant mappings are not configured, so ownership can't be checked
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<serde_json::Value>),
+}
+
+/// Response for a site decommission order
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct DecommissionSiteResponse {
+    pub order_id: String,
+    pub tenant_id: String,
+    pub netbox_site_id: i32,
+    /// Devices deleted before the site, in deletion order
+    pub deleted_device_ids: Vec<i32>,
+    pub state: String,
+}
+
+#[derive(ApiResponse)]
+pub enum DecommissionSiteOrderResponse {
+    #[oai(status = 200)]
+    Ok(Json<DecommissionSiteResponse>),
+    
+    #[oai(status = 401)]
+    Unauthorized,
+    
+    #[oai(status = 404)]
+    NotFound(Json<serde_json::Value>),
+    
+    /// The site still has devices and the order did not set `force`
+    #[oai(status = 409)]
+    Conflict(Json<serde_json::Value>),
+    
+    /// NetBox is throttling requests; retry after `Retry-After` seconds
+    #[oai(status = 429)]
+    TooManyRequests(Json<serde_json::Value>, #[oai(header = "Retry-After")] Option<u64>),
+    
+    #[oai(status = 500)]
+    InternalError(Json<serde_json::Value>),
+    
+    /// Tenant mappings are not configured, so ownership can't be checked
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<serde_json::Value>),
+}
+
+/// Order type registered with the processor registry
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct OrderTypeInfo {
+    pub order_type: String,
+    /// JSON schema of the payload accepted by `POST /orders/{order_type}`
+    pub payload_schema: serde_json::Value,
+}
+
+#[derive(ApiResponse)]
+pub enum ListOrderTypesResponse {
+    #[oai(status = 200)]
+    Ok(Json<Vec<OrderTypeInfo>>),
+    
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+pub enum OrderTypeSchemaResponse {
+    /// JSON schema of the payload accepted by `POST /orders/{order_type}`
+    #[oai(status = 200)]
+    Ok(Json<serde_json::Value>),
+
+    #[oai(status = 404)]
+    NotFound(Json<serde_json::Value>),
+
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<serde_json::Value>),
+}
+
+/// Response for generic order creation
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct GenericOrderResponse {
+    pub order_id: String,
+    pub tenant_id: String,
+    pub order_type: String,
+    pub resource_type: String,
+    pub netbox_resource_id: Option<i32>,
+    pub state: String,
+}
+
+#[derive(ApiResponse)]
+pub enum CreateOrderResponse {
+    #[oai(status = 201)]
+    Created(Json<GenericOrderResponse>),
+    
     #[oai(status = 400)]
     BadRequest(Json<serde_json::Value>),
     
     #[oai(status = 401)]
     Unauthorized,
     
+    /// No processor is registered for the order type
+    #[oai(status = 404)]
+    NotFound(Json<serde_json::Value>),
+    
+    /// A quota such as a prefix's free addresses is used up, or NetBox is throttling requests
+    #[oai(status = 429)]
+    TooManyRequests(Json<serde_json::Value>, #[oai(header = "Retry-After")] Option<u64>),
+    
     #[oai(status = 500)]
     InternalError(Json<serde_json::Value>),
+    
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<serde_json::Value>),
 }
 
 /// Response for order status
@@ -48,10 +355,95 @@ pub struct OrderStatusResponse {
     pub order_id: String,
     pub state: String,
     pub netbox_site_id: Option<i32>,
+    /// Per-resource progress of multi-resource orders such as pops
+    pub resources: Vec<OrderResourceResponse>,
+    pub labels: Vec<String>,
+    /// Operator notes, oldest first
+    pub notes: Vec<OrderNoteResponse>,
+    /// When a Scheduled order is due to be executed
+    pub execute_at: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
 
+impl From<OrderStatus> for OrderStatusResponse {
+    fn from(status: OrderStatus) -> Self {
+        Self {
+            order_id: status.order_id,
+            state: format!("{:?}", status.state),
+            netbox_site_id: status.netbox_site_id,
+            resources: status.steps.into_iter().map(OrderResourceResponse::from).collect(),
+            labels: status.labels,
+            notes: status.notes.into_iter().map(OrderNoteResponse::from).collect(),
+            execute_at: status.execute_at.map(|at| at.to_rfc3339()),
+            created_at: status.created_at.to_rfc3339(),
+            updated_at: status.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Page size of GET /orders when `cursor` or `offset` is given without `limit`
+const DEFAULT_ORDER_PAGE_SIZE: usize = 50;
+const MAX_ORDER_PAGE_SIZE: usize = 500;
+
+/// One page of the tenant's orders
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct OrderListPage {
+    pub orders: Vec<OrderStatusResponse>,
+    /// Pass as `cursor` to get the next page; absent on the last page
+    pub next_cursor: Option<String>,
+}
+
+#[derive(ApiResponse)]
+pub enum ListOrdersResponse {
+    #[oai(status = 200)]
+    Ok(Json<Vec<OrderStatusResponse>>),
+
+    /// Orders reduced to the fields selected with `fields`
+    #[oai(status = 200)]
+    Projected(Json<Vec<serde_json::Value>>),
+
+    #[oai(status = 200, content_type = "text/csv")]
+    Csv(PlainText<String>),
+
+    /// A page of orders, when `limit`, `cursor` or `offset` is given
+    #[oai(status = 200)]
+    Page(Json<OrderListPage>),
+
+    /// A page of orders reduced to the fields selected with `fields`
+    #[oai(status = 200)]
+    ProjectedPage(Json<serde_json::Value>),
+
+    /// A page of orders as CSV, with the next page's cursor in a header
+    #[oai(status = 200, content_type = "text/csv")]
+    CsvPage(PlainText<String>, #[oai(header = "X-Next-Cursor")] Option<String>),
+
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+pub enum ExportOrdersResponse {
+    #[oai(status = 200, content_type = "text/csv")]
+    Csv(Attachment<Body>),
+
+    #[oai(status = 200, content_type = "application/x-ndjson")]
+    Jsonl(Attachment<Body>),
+
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+}
+
+/// Parse an export bound: an RFC 3339 timestamp, or a date meaning midnight UTC
+fn parse_export_time(name: &str, value: &str) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&chrono::Utc));
+    }
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc())
+        .map_err(|_| format!("{} must be an RFC 3339 timestamp or a YYYY-MM-DD date", name))
+}
+
 #[derive(ApiResponse)]
 pub enum GetOrderStatusResponse {
     #[oai(status = 200)]
@@ -64,6 +456,160 @@ pub enum GetOrderStatusResponse {
     NotFound,
 }
 
+/// Longest note accepted on an order
+const MAX_NOTE_CHARS: usize = 2000;
+/// Most labels an order may carry, and the longest label
+const MAX_LABELS: usize = 20;
+const MAX_LABEL_CHARS: usize = 64;
+
+/// A note to add to an order
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct AddOrderNoteRequest {
+    pub text: String,
+}
+
+/// The labels an order should carry, replacing its current ones
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct SetOrderLabelsRequest {
+    pub labels: Vec<String>,
+}
+
+#[derive(ApiResponse)]
+pub enum AnnotateOrderResponse {
+    /// The order with its notes and labels
+    #[oai(status = 200)]
+    Ok(Json<OrderStatusResponse>),
+
+    /// The note was added; the order with its notes and labels
+    #[oai(status = 201)]
+    Created(Json<OrderStatusResponse>),
+
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+
+    /// No tenant or operator identity, or another tenant's order
+    #[oai(status = 401)]
+    Unauthorized,
+
+    /// An operator without the admin or approver role
+    #[oai(status = 403)]
+    Forbidden,
+
+    #[oai(status = 404)]
+    NotFound,
+}
+
+impl AnnotateOrderResponse {
+    fn from_error(error: AppError) -> Self {
+        match error {
+            AppError::Forbidden(_) => AnnotateOrderResponse::Forbidden,
+            AppError::NotFound(_) => AnnotateOrderResponse::NotFound,
+            AppError::ValidationError(message) => {
+                AnnotateOrderResponse::BadRequest(Json(serde_json::json!({ "error": message })))
+            }
+            _ => AnnotateOrderResponse::Unauthorized,
+        }
+    }
+}
+
+/// Who is annotating `workflow`: the order's own tenant, or an operator with
+/// the admin or approver role
+///
+/// A tenant's notes are signed with the `X-User-Id` it passes, if any.
+fn annotation_author(req: &Request, workflow: &OrderWorkflow) -> Result<String, AppError> {
+    if extract_tenant_id(req).ok().as_deref() == Some(workflow.tenant_id.as_str()) {
+        return Ok(req
+            .header(USER_HEADER)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("tenant:{}", workflow.tenant_id)));
+    }
+    require_role(req, ADMIN_ROLE).or_else(|_| require_role(req, APPROVER_ROLE))
+}
+
+/// Trimmed labels without duplicates, in the order given
+fn normalize_labels(labels: Vec<String>) -> Result<Vec<String>, AppError> {
+    let mut normalized: Vec<String> = Vec::new();
+    for label in labels {
+        let label = label.trim();
+        let valid = label
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':' | '/'));
+        if label.is_empty() || label.len() > MAX_LABEL_CHARS || !valid {
+            return Err(AppError::ValidationError(format!(
+                "Invalid label '{}': use up to {} letters, digits or - _ . : /",
+                label, MAX_LABEL_CHARS
+            )));
+        }
+        if !normalized.iter().any(|existing| existing == label) {
+            normalized.push(label.to_string());
+        }
+    }
+    if normalized.len() > MAX_LABELS {
+        return Err(AppError::ValidationError(format!("An order can have at most {} labels", MAX_LABELS)));
+    }
+    Ok(normalized)
+}
+
+/// Operator decision on an order awaiting approval
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, poem_openapi::Object)]
+pub struct ApprovalDecisionRequest {
+    pub comment: Option<String>,
+}
+
+#[derive(ApiResponse)]
+pub enum ApprovalDecisionResponse {
+    #[oai(status = 200)]
+    Ok(Json<OrderStatusResponse>),
+    
+    #[oai(status = 401)]
+    Unauthorized,
+    
+    #[oai(status = 403)]
+    Forbidden,
+    
+    #[oai(status = 404)]
+    NotFound,
+    
+    /// The order is not awaiting this decision
+    #[oai(status = 409)]
+    Conflict(Json<serde_json::Value>),
+    
+    /// NetBox is throttling requests; retry after `Retry-After` seconds
+    #[oai(status = 429)]
+    TooManyRequests(Json<serde_json::Value>, #[oai(header = "Retry-After")] Option<u64>),
+    
+    #[oai(status = 500)]
+    InternalError(Json<serde_json::Value>),
+    
+    /// NetBox integration is not configured, or orders are frozen for maintenance
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<serde_json::Value>),
+}
+
+impl ApprovalDecisionResponse {
+    fn from_error(error: AppError) -> Self {
+        match error {
+            AppError::Unauthorized => ApprovalDecisionResponse::Unauthorized,
+            AppError::Forbidden(_) => ApprovalDecisionResponse::Forbidden,
+            AppError::NotFound(_) => ApprovalDecisionResponse::NotFound,
+            AppError::Conflict(msg) => {
+                ApprovalDecisionResponse::Conflict(Json(serde_json::json!({ "error": msg })))
+            }
+            e @ (AppError::RateLimited { .. } | AppError::QuotaExceeded { .. }) => {
+                let retry_after = e.retry_after_secs();
+                ApprovalDecisionResponse::TooManyRequests(Json(serde_json::json!({ "error": e.to_string() })), retry_after)
+            }
+            e if e.is_unavailable() => {
+                ApprovalDecisionResponse::ServiceUnavailable(Json(serde_json::json!({ "error": e.to_string() })))
+            }
+            e => ApprovalDecisionResponse::InternalError(Json(serde_json::json!({
+                "error": "Internal server error",
+                "message": e.to_string()
+            }))),
+        }
+    }
+}
+
 #[OpenApi]
 impl OrdersApi {
     /// Create a new site order
@@ -81,16 +627,18 @@ impl OrdersApi {
         body: Json<CreateSiteOrder>,
     ) -> Result<CreateSiteResponse, poem::Error> {
         let tenant_id = extract_tenant_id(req)?;
+        let requested_name = body.0.name.clone();
         
-        match self.order_service.process_site_order(body.0, tenant_id.clone()).await {
+        let Some(service) = &self.order_service else {
+            return Ok(CreateSiteResponse::ServiceUnavailable(Self::netbox_not_configured()));
+        };
+        
+        match service.process_site_order(body.0, tenant_id.clone()).await {
+            Ok(result) if matches!(result.workflow_state, OrderState::AwaitingApproval | OrderState::Scheduled) => {
+                Ok(CreateSiteResponse::Accepted(Json(SiteOrderResponse::from_result(result, requested_name))))
+            }
             Ok(result) => {
-                Ok(CreateSiteResponse::Created(Json(SiteOrderResponse {
-                    order_id: result.order_id,
-                    tenant_id: result.tenant_id,
-                    netbox_site_id: result.netbox_site.id,
-                    state: format!("{:?}", result.workflow_state),
-                    site_name: result.netbox_site.name,
-                })))
+                Ok(CreateSiteResponse::Created(Json(SiteOrderResponse::from_result(result, requested_name))))
             }
             Err(AppError::ValidationError(msg)) => {
                 Ok(CreateSiteResponse::BadRequest(Json(serde_json::json!({
@@ -98,9 +646,30 @@ impl OrdersApi {
                     "message": msg
                 }))))
             }
+            Err(AppError::NetBoxValidation(errors)) => {
+                Ok(CreateSiteResponse::BadRequest(netbox_validation_body(&errors)))
+            }
             Err(AppError::Unauthorized) => {
                 Ok(CreateSiteResponse::Unauthorized)
             }
+            Err(AppError::Conflict(msg)) => {
+                Ok(CreateSiteResponse::Conflict(Json(serde_json::json!({
+                    "error": "Conflict",
+                    "message": msg
+                }))))
+            }
+            Err(e @ (AppError::RateLimited { .. } | AppError::QuotaExceeded { .. })) => {
+                Ok(CreateSiteResponse::TooManyRequests(
+                    Json(serde_json::json!({ "error": "Too many requests", "message": e.to_string() })),
+                    e.retry_after_secs(),
+                ))
+            }
+            Err(e) if e.is_unavailable() => {
+                Ok(CreateSiteResponse::ServiceUnavailable(Json(serde_json::json!({
+                    "error": "Service unavailable",
+                    "message": e.to_string()
+                }))))
+            }
             Err(e) => {
                 Ok(CreateSiteResponse::InternalError(Json(serde_json::json!({
                     "error": "Internal server error",
@@ -110,35 +679,1141 @@ impl OrdersApi {
         }
     }
 
-    /// Get the status of an order
-    #[oai(path = "/orders/:order_id/status", method = "get")]
-    async fn get_order_status(
+    /// Create a pop: a site plus the devices installed in it
+    /// 
+    /// The site is created first and every device is placed in it. If any
+    /// device fails, the resources created so far are rolled back, the order's
+    /// error names the failing device and NetBox's error is returned.
+    #[oai(path = "/orders/pop", method = "post")]
+    async fn create_pop(
         &self,
         req: &Request,
-        order_id: Path<String>,
-    ) -> Result<GetOrderStatusResponse, poem::Error> {
+        body: Json<CreatePopOrder>,
+    ) -> Result<CreatePopResponse, poem::Error> {
         let tenant_id = extract_tenant_id(req)?;
         
-        match self.order_service.get_order_status(&order_id.0, &tenant_id).await {
-            Ok(status) => {
-                Ok(GetOrderStatusResponse::Ok(Json(OrderStatusResponse {
-                    order_id: status.order_id,
-                    state: format!("{:?}", status.state),
-                    netbox_site_id: status.netbox_site_id,
-                    created_at: status.created_at.to_rfc3339(),
-                    updated_at: status.updated_at.to_rfc3339(),
-                })))
-            }
-            Err(AppError::NotFound(_)) => {
-                Ok(GetOrderStatusResponse::NotFound)
+        let Some(service) = &self.order_service else {
+            return Ok(CreatePopResponse::ServiceUnavailable(Self::netbox_not_configured()));
+        };
+        
+        match service.process_pop_order(body.0, tenant_id.clone()).await {
+            Ok(result) => {
+                let status = service.order_status(&result.order_id);
+                let response = PopOrderResponse {
+                    order_id: result.order_id,
+                    tenant_id: result.tenant_id,
+                    state: format!("{:?}", result.workflow_state),
+                    resources: status
+                        .map(|status| status.steps.into_iter().map(OrderResourceResponse::from).collect())
+                        .unwrap_or_default(),
+                    applied_enrichment: result.applied_enrichment,
+                };
+                if matches!(result.workflow_state, OrderState::AwaitingApproval | OrderState::Scheduled) {
+                    Ok(CreatePopResponse::Accepted(Json(response)))
+                } else {
+                    Ok(CreatePopResponse::Created(Json(response)))
+                }
+            }
+            Err(AppError::ValidationError(msg)) => {
+                Ok(CreatePopResponse::BadRequest(Json(serde_json::json!({
+                    "error": "Validation failed",
+                    "message": msg
+                }))))
+            }
+            Err(AppError::NetBoxValidation(errors)) => {
+                Ok(CreatePopResponse::BadRequest(netbox_validation_body(&errors)))
+            }
+            Err(AppError::Unauthorized) => {
+                Ok(CreatePopResponse::Unauthorized)
+            }
+            Err(AppError::Conflict(msg)) => {
+                Ok(CreatePopResponse::Conflict(Json(serde_json::json!({
+                    "error": "Conflict",
+                    "message": msg
+                }))))
+            }
+            Err(e @ (AppError::RateLimited { .. } | AppError::QuotaExceeded { .. })) => {
+                Ok(CreatePopResponse::TooManyRequests(
+                    Json(serde_json::json!({ "error": "Too many requests", "message": e.to_string() })),
+                    e.retry_after_secs(),
+                ))
+            }
+            Err(e) if e.is_unavailable() => {
+                Ok(CreatePopResponse::ServiceUnavailable(Json(serde_json::json!({
+                    "error": "Service unavailable",
+                    "message": e.to_string()
+                }))))
+            }
+            Err(e) => {
+                Ok(CreatePopResponse::InternalError(Json(serde_json::json!({
+                    "error": "Internal server error",
+                    "message": e.to_string()
+                }))))
+            }
+        }
+    }
+
+    /// Update an existing site of the tenant
+    /// 
+    /// Only the fields in the body are changed; the site is the one in the path.
+    /// Moving a site to another tenant is refused with 403. With `last_updated`
+    /// set, a site changed since then is refused with 409 unless `force` is set.
+    #[oai(path = "/orders/sites/:site_id", method = "patch")]
+    async fn update_site(
+        &self,
+        req: &Request,
+        site_id: Path<i32>,
+        body: Json<UpdateSiteOrder>,
+    ) -> Result<UpdateSiteOrderResponse, poem::Error> {
+        let tenant_id = extract_tenant_id(req)?;
+        let order = UpdateSiteOrder {
+            site_id: Some(site_id.0),
+            site_slug: None,
+            ..body.0
+        };
+        
+        let Some(service) = &self.order_service else {
+            return Ok(UpdateSiteOrderResponse::ServiceUnavailable(Self::netbox_not_configured()));
+        };
+        
+        match service.process_site_update_order(order, tenant_id).await {
+            Ok(result) => {
+                let name = result.netbox_site.as_ref().map(|site| site.name.clone()).unwrap_or_default();
+                Ok(UpdateSiteOrderResponse::Ok(Json(SiteOrderResponse::from_result(result, name))))
+            }
+            Err(AppError::ValidationError(msg)) => {
+                Ok(UpdateSiteOrderResponse::BadRequest(Json(serde_json::json!({
+                    "error": "Validation failed",
+                    "message": msg
+                }))))
+            }
+            Err(AppError::NetBoxValidation(errors)) => {
+                Ok(UpdateSiteOrderResponse::BadRequest(netbox_validation_body(&errors)))
             }
             Err(AppError::Unauthorized) => {
-                Ok(GetOrderStatusResponse::Unauthorized)
+                Ok(UpdateSiteOrderResponse::Unauthorized)
             }
-            Err(_) => {
-                Ok(GetOrderStatusResponse::NotFound)
+            Err(AppError::Forbidden(msg)) => {
+                Ok(UpdateSiteOrderResponse::Forbidden(Json(serde_json::json!({ "error": msg }))))
+            }
+            Err(AppError::NotFound(msg)) => {
+                Ok(UpdateSiteOrderResponse::NotFound(Json(serde_json::json!({ "error": msg }))))
+            }
+            Err(AppError::Conflict(msg)) => {
+                Ok(UpdateSiteOrderResponse::Conflict(Json(serde_json::json!({ "error": msg }))))
+            }
+            Err(e @ (AppError::RateLimited { .. } | AppError::QuotaExceeded { .. })) => {
+                Ok(UpdateSiteOrderResponse::TooManyRequests(
+                    Json(serde_json::json!({ "error": "Too many requests", "message": e.to_string() })),
+                    e.retry_after_secs(),
+                ))
+            }
+            Err(e) if e.is_unavailable() => {
+                Ok(UpdateSiteOrderResponse::ServiceUnavailable(Json(serde_json::json!({
+                    "error": "Service unavailable",
+                    "message": e.to_string()
+                }))))
+            }
+            Err(e) => {
+                Ok(UpdateSiteOrderResponse::InternalError(Json(serde_json::json!({
+                    "error": "Internal server error",
+                    "message": e.to_string()
+                }))))
             }
         }
     }
+
+    /// Decommission a site: delete it, and with `force` its devices, from NetBox
+    /// 
+    /// The site must belong to the tenant. Without `force`, a site that still
+    /// has devices is refused with 409.
+    #[oai(path = "/orders/site/decommission", method = "post")]
+    async fn decommission_site(
+        &self,
+        req: &Request,
+        body: Json<DecommissionSiteOrder>,
+    ) -> Result<DecommissionSiteOrderResponse, poem::Error> {
+        let tenant_id = extract_tenant_id(req)?;
+        
+        let Some(service) = &self.order_service else {
+            return Ok(DecommissionSiteOrderResponse::ServiceUnavailable(Self::netbox_not_configured()));
+        };
+        
+        match service.process_decommission_site_order(body.0, tenant_id).await {
+            Ok(result) => {
+                Ok(DecommissionSiteOrderResponse::Ok(Json(DecommissionSiteResponse {
+                    order_id: result.order_id,
+                    tenant_id: result.tenant_id,
+                    netbox_site_id: result.site_id,
+                    deleted_device_ids: result.deleted_devices,
+                    state: format!("{:?}", result.workflow_state),
+                })))
+            }
+            Err(AppError::Unauthorized) => {
+                Ok(DecommissionSiteOrderResponse::Unauthorized)
+            }
+            Err(AppError::NotFound(msg)) => {
+                Ok(DecommissionSiteOrderResponse::NotFound(Json(serde_json::json!({ "error": msg }))))
+            }
+            Err(AppError::Conflict(msg)) => {
+                Ok(DecommissionSiteOrderResponse::Conflict(Json(serde_json::json!({
+                    "error": "Conflict",
+                    "message": msg
+                }))))
+            }
+            Err(e @ (AppError::RateLimited { .. } | AppError::QuotaExceeded { .. })) => {
+                Ok(DecommissionSiteOrderResponse::TooManyRequests(
+                    Json(serde_json::json!({ "error": "Too many requests", "message": e.to_string() })),
+                    e.retry_after_secs(),
+                ))
+            }
+            Err(e) if e.is_unavailable() => {
+                Ok(DecommissionSiteOrderResponse::ServiceUnavailable(Json(serde_json::json!({
+                    "error": "Service unavailable",
+                    "message": e.to_string()
+                }))))
+            }
+            Err(e) => {
+                Ok(DecommissionSiteOrderResponse::InternalError(Json(serde_json::json!({
+                    "error": "Internal server error",
+                    "message": e.to_string()
+                }))))
+            }
+        }
+    }
+
+    /// List the registered order types and their payload schemas
+    #[oai(path = "/orders/types", method = "get")]
+    async fn list_order_types(&self) -> ListOrderTypesResponse {
+        let Some(service) = &self.extensible_service else {
+            return ListOrderTypesResponse::ServiceUnavailable(Self::registry_not_configured());
+        };
+        
+        let types = service
+            .registry()
+            .list_registered_types()
+            .into_iter()
+            .map(|registered| OrderTypeInfo {
+                order_type: registered.order_type,
+                payload_schema: registered.payload_schema,
+            })
+            .collect();
+        ListOrderTypesResponse::Ok(Json(types))
+    }
+
+    /// Get the JSON schema of one order type's payload
+    ///
+    /// Payloads sent to `POST /orders/{order_type}` are checked against it.
+    #[oai(path = "/orders/types/:order_type/schema", method = "get")]
+    async fn get_order_type_schema(&self, order_type: Path<String>) -> OrderTypeSchemaResponse {
+        let Some(service) = &self.extensible_service else {
+            return OrderTypeSchemaResponse::ServiceUnavailable(Self::registry_not_configured());
+        };
+
+        match service.registry().get_processor(&order_type.0) {
+            Some(processor) => OrderTypeSchemaResponse::Ok(Json(processor.payload_schema())),
+            None => OrderTypeSchemaResponse::NotFound(Json(serde_json::json!({
+                "error": "Not found",
+                "message": format!("Order type {} is not registered", order_type.0)
+            }))),
+        }
+    }
+
+    /// Create an order of any registered type
+    /// 
+    /// The payload is parsed and processed by the processor registered for the type.
+    #[oai(path = "/orders/:order_type", method = "post")]
+    async fn create_order(
+        &self,
+        req: &Request,
+        order_type: Path<String>,
+        body: Json<serde_json::Value>,
+    ) -> Result<CreateOrderResponse, poem::Error> {
+        let tenant_id = extract_tenant_id(req)?;
+        if self.order_service.is_none() {
+            return Ok(CreateOrderResponse::ServiceUnavailable(Self::netbox_not_configured()));
+        }
+        let Some(service) = &self.extensible_service else {
+            return Ok(CreateOrderResponse::ServiceUnavailable(Self::registry_not_configured()));
+        };
+        
+        match service.process_json_order(&order_type.0, body.0, tenant_id).await {
+            Ok(result) => {
+                Ok(CreateOrderResponse::Created(Json(GenericOrderResponse {
+                    order_id: result.order_id,
+                    tenant_id: result.tenant_id,
+                    order_type: order_type.0,
+                    resource_type: result.netbox_resource.resource_type().to_string(),
+                    netbox_resource_id: result.netbox_resource.resource_id(),
+                    state: format!("{:?}", result.workflow_state),
+                })))
+            }
+            Err(AppError::ValidationError(msg)) => {
+                Ok(CreateOrderResponse::BadRequest(Json(serde_json::json!({
+                    "error": "Validation failed",
+                    "message": msg
+                }))))
+            }
+            Err(AppError::NetBoxValidation(errors)) => {
+                Ok(CreateOrderResponse::BadRequest(netbox_validation_body(&errors)))
+            }
+            Err(AppError::Unauthorized) => {
+                Ok(CreateOrderResponse::Unauthorized)
+            }
+            Err(AppError::NotFound(msg)) => {
+                Ok(CreateOrderResponse::NotFound(Json(serde_json::json!({ "error": msg }))))
+            }
+            Err(e @ (AppError::RateLimited { .. } | AppError::QuotaExceeded { .. })) => {
+                Ok(CreateOrderResponse::TooManyRequests(
+                    Json(serde_json::json!({ "error": "Too many requests", "message": e.to_string() })),
+                    e.retry_after_secs(),
+                ))
+            }
+            Err(e) => {
+                Ok(CreateOrderResponse::InternalError(Json(serde_json::json!({
+                    "error": "Internal server error",
+                    "message": e.to_string()
+                }))))
+            }
+        }
+    }
+
+    /// List the tenant's orders, newest first
+    ///
+    /// `fields` (comma-separated) reduces each order to those fields, and
+    /// `Accept: text/csv` returns the orders as CSV. With `limit`, `cursor` or
+    /// `offset` the orders come one page at a time; follow `next_cursor` rather
+    /// than stepping `offset`, which skips or repeats orders created meanwhile.
+    /// `label` lists only the orders carrying that label.
+    #[oai(path = "/orders", method = "get")]
+    async fn list_orders(
+        &self,
+        req: &Request,
+        fields: Query<Option<String>>,
+        limit: Query<Option<u32>>,
+        cursor: Query<Option<String>>,
+        offset: Query<Option<u32>>,
+        label: Query<Option<String>>,
+    ) -> Result<ListOrdersResponse, poem::Error> {
+        let tenant_id = extract_tenant_id(req)?;
+        let bad_request = |message: &str| ListOrdersResponse::BadRequest(Json(serde_json::json!({ "error": message })));
+        let view = match ListView::for_items::<OrderStatusResponse>(req, fields.0.as_deref()) {
+            Ok(view) => view,
+            Err(e) => return Ok(bad_request(&e.to_string())),
+        };
+
+        let labelled = |workflow: &OrderWorkflow| {
+            label.0.as_deref().is_none_or(|label| workflow.labels.iter().any(|l| l == label))
+        };
+
+        if limit.0.is_none() && cursor.0.is_none() && offset.0.is_none() {
+            let mut workflows = self.workflow_manager.get_tenant_orders(&tenant_id);
+            workflows.retain(labelled);
+            sort_newest_first(&mut workflows);
+            let orders: Vec<OrderStatusResponse> = workflows
+                .into_iter()
+                .map(|workflow| OrderStatusResponse::from(OrderStatus::from(workflow)))
+                .collect();
+
+            return Ok(if view.csv {
+                ListOrdersResponse::Csv(PlainText(view.to_csv(&orders)))
+            } else if view.is_default() {
+                ListOrdersResponse::Ok(Json(orders))
+            } else {
+                let projected = orders
+                    .iter()
+                    .map(|order| view.project(serde_json::to_value(order).unwrap_or_default()))
+                    .collect();
+                ListOrdersResponse::Projected(Json(projected))
+            });
+        }
+
+        let limit = limit.0.map_or(DEFAULT_ORDER_PAGE_SIZE, |limit| limit as usize).clamp(1, MAX_ORDER_PAGE_SIZE);
+        let (workflows, next) = match (cursor.0, offset.0) {
+            (Some(_), Some(_)) => return Ok(bad_request("cursor and offset can't be combined")),
+            (Some(token), None) => match OrderCursor::decode(&token) {
+                Some(cursor) => self.workflow_manager.list_matching_after(&tenant_id, labelled, Some(&cursor), limit),
+                None => return Ok(bad_request("Invalid cursor")),
+            },
+            (None, None) => self.workflow_manager.list_matching_after(&tenant_id, labelled, None, limit),
+            (None, Some(offset)) => {
+                let mut workflows = self.workflow_manager.get_tenant_orders(&tenant_id);
+                workflows.retain(labelled);
+                sort_newest_first(&mut workflows);
+                let mut page: Vec<_> = workflows.into_iter().skip(offset as usize).collect();
+                let has_more = page.len() > limit;
+                page.truncate(limit);
+                let next = if has_more { page.last().map(OrderCursor::after) } else { None };
+                (page, next)
+            }
+        };
+        let page = OrderListPage {
+            orders: workflows
+                .into_iter()
+                .map(|workflow| OrderStatusResponse::from(OrderStatus::from(workflow)))
+                .collect(),
+            next_cursor: next.map(|cursor| cursor.encode()),
+        };
+
+        Ok(if view.csv {
+            ListOrdersResponse::CsvPage(PlainText(view.to_csv(&page.orders)), page.next_cursor)
+        } else if view.is_default() {
+            ListOrdersResponse::Page(Json(page))
+        } else {
+            ListOrdersResponse::ProjectedPage(Json(view.project_list(&page, "orders")))
+        })
+    }
+
+    /// Export the tenant's order history as a CSV or JSONL download
+    ///
+    /// `from` (inclusive) and `to` (exclusive) bound the creation time and take an
+    /// RFC 3339 timestamp or a date. Admins may export another tenant, or every
+    /// tenant with `tenant=all`.
+    #[oai(path = "/orders/export", method = "get")]
+    async fn export_orders(
+        &self,
+        req: &Request,
+        from: Query<Option<String>>,
+        to: Query<Option<String>>,
+        state: Query<Option<String>>,
+        format: Query<Option<String>>,
+        tenant: Query<Option<String>>,
+    ) -> Result<ExportOrdersResponse, poem::Error> {
+        let tenant_id = match tenant.0 {
+            Some(requested) => {
+                if extract_tenant_id(req).ok().as_deref() != Some(requested.as_str()) {
+                    require_role(req, ADMIN_ROLE)?;
+                }
+                (requested != "all").then_some(requested)
+            }
+            None => Some(extract_tenant_id(req)?),
+        };
+
+        let bad_request = |message: String| ExportOrdersResponse::BadRequest(Json(serde_json::json!({ "error": message })));
+        let Some(format) = ExportFormat::parse(format.0.as_deref().unwrap_or("csv")) else {
+            return Ok(bad_request("format must be csv or jsonl".to_string()));
+        };
+        let state = match state.0 {
+            Some(state) => match serde_json::from_value::<OrderState>(serde_json::Value::String(state.to_lowercase())) {
+                Ok(state) => Some(state),
+                Err(_) => return Ok(bad_request(format!("Unknown order state '{}'", state))),
+            },
+            None => None,
+        };
+        let bound = |name: &str, value: Option<String>| value.map(|value| parse_export_time(name, &value)).transpose();
+        let (from, to) = match (bound("from", from.0), bound("to", to.0)) {
+            (Ok(from), Ok(to)) => (from, to),
+            (Err(message), _) | (_, Err(message)) => return Ok(bad_request(message)),
+        };
+
+        let filter = OrderExportFilter { tenant_id, from, to, state };
+        let rows = export_rows(&self.workflow_manager, &filter);
+        let filename = format!("orders-{}.{}", filter.tenant_id.as_deref().unwrap_or("all"), format.extension());
+        let attachment = Attachment::new(export_body(rows, format))
+            .attachment_type(AttachmentType::Attachment)
+            .filename(filename);
+        Ok(match format {
+            ExportFormat::Csv => ExportOrdersResponse::Csv(attachment),
+            ExportFormat::Jsonl => ExportOrdersResponse::Jsonl(attachment),
+        })
+    }
+
+    /// Get the status of an order
+    #[oai(path = "/orders/:order_id/status", method = "get")]
+    async fn get_order_status(
+        &self,
+        req: &Request,
+        order_id: Path<String>,
+    ) -> Result<GetOrderStatusResponse, poem::Error> {
+        let tenant_id = extract_tenant_id(req)?;
+        
+        // Order status is read from the workflow, so it is served with or without NetBox
+        match self.workflow_manager.get_order(&order_id.0) {
+            Some(workflow) if workflow.tenant_id == tenant_id => {
+                Ok(GetOrderStatusResponse::Ok(Json(OrderStatusResponse::from(OrderStatus::from(workflow)))))
+            }
+            Some(_) => Ok(GetOrderStatusResponse::Unauthorized),
+            None => Ok(GetOrderStatusResponse::NotFound),
+        }
+    }
+
+    /// Add a note to an order, e.g. "waiting on customer" while triaging it
+    ///
+    /// Open to the order's tenant and to operators with the admin or approver
+    /// role. Notes can't be edited or removed; they are listed oldest first.
+    #[oai(path = "/orders/:order_id/notes", method = "post")]
+    async fn add_order_note(
+        &self,
+        req: &Request,
+        order_id: Path<String>,
+        body: Json<AddOrderNoteRequest>,
+    ) -> AnnotateOrderResponse {
+        let Some(workflow) = self.workflow_manager.get_order(&order_id.0) else {
+            return AnnotateOrderResponse::NotFound;
+        };
+        let author = match annotation_author(req, &workflow) {
+            Ok(author) => author,
+            Err(e) => return AnnotateOrderResponse::from_error(e),
+        };
+        let text = body.0.text.trim();
+        if text.is_empty() || text.chars().count() > MAX_NOTE_CHARS {
+            return AnnotateOrderResponse::from_error(AppError::ValidationError(format!(
+                "A note needs 1 to {} characters of text",
+                MAX_NOTE_CHARS
+            )));
+        }
+
+        if self.workflow_manager.add_note(&order_id.0, author, text.to_string()).is_err() {
+            return AnnotateOrderResponse::NotFound;
+        }
+        match self.workflow_manager.get_order(&order_id.0) {
+            Some(workflow) => AnnotateOrderResponse::Created(Json(OrderStatusResponse::from(OrderStatus::from(workflow)))),
+            None => AnnotateOrderResponse::NotFound,
+        }
+    }
+
+    /// Replace an order's labels, e.g. `batch-migration`, to filter orders by
+    ///
+    /// Open to the order's tenant and to operators with the admin or approver role.
+    #[oai(path = "/orders/:order_id/labels", method = "put")]
+    async fn set_order_labels(
+        &self,
+        req: &Request,
+        order_id: Path<String>,
+        body: Json<SetOrderLabelsRequest>,
+    ) -> AnnotateOrderResponse {
+        let Some(workflow) = self.workflow_manager.get_order(&order_id.0) else {
+            return AnnotateOrderResponse::NotFound;
+        };
+        if let Err(e) = annotation_author(req, &workflow) {
+            return AnnotateOrderResponse::from_error(e);
+        }
+        let labels = match normalize_labels(body.0.labels) {
+            Ok(labels) => labels,
+            Err(e) => return AnnotateOrderResponse::from_error(e),
+        };
+
+        if self.workflow_manager.set_labels(&order_id.0, labels).is_err() {
+            return AnnotateOrderResponse::NotFound;
+        }
+        match self.workflow_manager.get_order(&order_id.0) {
+            Some(workflow) => AnnotateOrderResponse::Ok(Json(OrderStatusResponse::from(OrderStatus::from(workflow)))),
+            None => AnnotateOrderResponse::NotFound,
+        }
+    }
+
+    /// Approve an order awaiting approval (requires the approver role)
+    ///
+    /// The order then continues through the pipeline and its site is created in NetBox.
+    #[oai(path = "/orders/:order_id/approve", method = "post")]
+    async fn approve_order(
+        &self,
+        req: &Request,
+        order_id: Path<String>,
+        body: Json<ApprovalDecisionRequest>,
+    ) -> ApprovalDecisionResponse {
+        let approver = match require_role(req, APPROVER_ROLE) {
+            Ok(approver) => approver,
+            Err(e) => return ApprovalDecisionResponse::from_error(e),
+        };
+        
+        let Some(service) = &self.order_service else {
+            return ApprovalDecisionResponse::ServiceUnavailable(Self::netbox_not_configured());
+        };
+        
+        match service.approve_order(&order_id.0, approver, body.0.comment).await {
+            Ok(result) => match service.order_status(&result.order_id) {
+                Ok(status) => ApprovalDecisionResponse::Ok(Json(OrderStatusResponse::from(status))),
+                Err(e) => ApprovalDecisionResponse::from_error(e),
+            },
+            Err(e) => ApprovalDecisionResponse::from_error(e),
+        }
+    }
+
+    /// Reject an order awaiting approval, cancelling it (requires the approver role)
+    #[oai(path = "/orders/:order_id/reject", method = "post")]
+    async fn reject_order(
+        &self,
+        req: &Request,
+        order_id: Path<String>,
+        body: Json<ApprovalDecisionRequest>,
+    ) -> ApprovalDecisionResponse {
+        let approver = match require_role(req, APPROVER_ROLE) {
+            Ok(approver) => approver,
+            Err(e) => return ApprovalDecisionResponse::from_error(e),
+        };
+        
+        let Some(service) = &self.order_service else {
+            return ApprovalDecisionResponse::ServiceUnavailable(Self::netbox_not_configured());
+        };
+        
+        match service.reject_order(&order_id.0, approver, body.0.comment).await {
+            Ok(status) => ApprovalDecisionResponse::Ok(Json(OrderStatusResponse::from(status))),
+            Err(e) => ApprovalDecisionResponse::from_error(e),
+        }
+    }
+
+    /// Execute an order scheduled until a maintenance freeze ends, now (requires the admin role)
+    ///
+    /// The admin and comment are recorded on the order's history.
+    #[oai(path = "/orders/:order_id/execute", method = "post")]
+    async fn force_execute_order(
+        &self,
+        req: &Request,
+        order_id: Path<String>,
+        body: Json<ApprovalDecisionRequest>,
+    ) -> ApprovalDecisionResponse {
+        let admin = match require_role(req, ADMIN_ROLE) {
+            Ok(admin) => admin,
+            Err(e) => return ApprovalDecisionResponse::from_error(e),
+        };
+        
+        let Some(service) = &self.order_service else {
+            return ApprovalDecisionResponse::ServiceUnavailable(Self::netbox_not_configured());
+        };
+        
+        match service.force_execute_order(&order_id.0, admin, body.0.comment).await {
+            Ok(result) => match service.order_status(&result.order_id) {
+                Ok(status) => ApprovalDecisionResponse::Ok(Json(OrderStatusResponse::from(status))),
+                Err(e) => ApprovalDecisionResponse::from_error(e),
+            },
+            Err(e) => ApprovalDecisionResponse::from_error(e),
+        }
+    }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::business::{ApprovalRules, WorkflowManager};
+    use crate::config::Config;
+    use crate::netbox::client::NetBoxClient;
+    use crate::netbox::resilient_client::ResilientNetBoxClient;
+
+    fn create_api() -> (OrdersApi, Arc<WorkflowManager>) {
+        let config = Config {
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let client = Arc::new(ResilientNetBoxClient::new(Arc::new(NetBoxClient::new(config).unwrap())));
+        let workflow_manager = Arc::new(WorkflowManager::new());
+        let service = OrderService::new(workflow_manager.clone(), client)
+            .with_approval_policy(Arc::new(ApprovalRules::new().with_tenant("tenant1")));
+        (OrdersApi::new(Arc::new(service)), workflow_manager)
+    }
+
+    async fn export(api: &OrdersApi, req: &Request, tenant: Option<&str>, format: Option<&str>) -> poem::Result<ExportOrdersResponse> {
+        api.export_orders(
+            req,
+            Query(None),
+            Query(None),
+            Query(None),
+            Query(format.map(str::to_string)),
+            Query(tenant.map(str::to_string)),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_list_orders_with_fields_and_csv() {
+        let (api, manager) = create_api();
+        let order_id = manager.create_order("tenant1".to_string());
+        manager.create_order("tenant2".to_string());
+
+        let req = Request::builder().header("X-Tenant-Id", "tenant1").finish();
+        let ListOrdersResponse::Projected(Json(orders)) =
+            api.list_orders(&req, Query(Some("order_id,state".to_string())), Query(None), Query(None), Query(None), Query(None)).await.unwrap()
+        else {
+            panic!("Expected a projected response");
+        };
+        assert_eq!(orders, vec![serde_json::json!({ "order_id": order_id, "state": "Pending" })]);
+
+        let csv_req = Request::builder().header("X-Tenant-Id", "tenant1").header("Accept", "text/csv").finish();
+        let ListOrdersResponse::Csv(PlainText(csv)) = api.list_orders(&csv_req, Query(None), Query(None), Query(None), Query(None), Query(None)).await.unwrap() else {
+            panic!("Expected a CSV response");
+        };
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "order_id,state,netbox_site_id,resources,labels,notes,execute_at,created_at,updated_at");
+        assert!(lines[1].starts_with(&format!("{},Pending,,[],[],[],", order_id)));
+
+        let unknown = api.list_orders(&req, Query(Some("secret".to_string())), Query(None), Query(None), Query(None), Query(None)).await.unwrap();
+        assert!(matches!(unknown, ListOrdersResponse::BadRequest(_)));
+    }
+
+    #[tokio::test]
+    async fn test_list_orders_by_cursor_survives_new_orders() {
+        let (api, manager) = create_api();
+        let existing: Vec<String> = (0..5).map(|_| manager.create_order("tenant1".to_string())).collect();
+        let req = Request::builder().header("X-Tenant-Id", "tenant1").finish();
+
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let ListOrdersResponse::Page(Json(page)) =
+                api.list_orders(&req, Query(None), Query(Some(2)), Query(cursor), Query(None), Query(None)).await.unwrap()
+            else {
+                panic!("Expected a page");
+            };
+            seen.extend(page.orders.into_iter().map(|order| order.order_id));
+            manager.create_order("tenant1".to_string());
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        seen.sort();
+        let mut expected = existing;
+        expected.sort();
+        assert_eq!(seen, expected);
+
+        let invalid = api
+            .list_orders(&req, Query(None), Query(None), Query(Some("bogus".to_string())), Query(None), Query(None))
+            .await
+            .unwrap();
+        assert!(matches!(invalid, ListOrdersResponse::BadRequest(_)));
+        let both = api
+            .list_orders(&req, Query(None), Query(None), Query(Some("bogus".to_string())), Query(Some(1)), Query(None))
+            .await
+            .unwrap();
+        assert!(matches!(both, ListOrdersResponse::BadRequest(_)));
+    }
+
+    #[tokio::test]
+    async fn test_list_orders_by_offset_still_pages() {
+        let (api, manager) = create_api();
+        for _ in 0..3 {
+            manager.create_order("tenant1".to_string());
+        }
+        let req = Request::builder().header("X-Tenant-Id", "tenant1").finish();
+
+        let ListOrdersResponse::Page(Json(page)) =
+            api.list_orders(&req, Query(None), Query(Some(2)), Query(None), Query(Some(2)), Query(None)).await.unwrap()
+        else {
+            panic!("Expected a page");
+        };
+        assert_eq!(page.orders.len(), 1);
+        assert!(page.next_cursor.is_none());
+    }
+
+    fn note(text: &str) -> Json<AddOrderNoteRequest> {
+        Json(AddOrderNoteRequest { text: text.to_string() })
+    }
+
+    fn labels(labels: &[&str]) -> Json<SetOrderLabelsRequest> {
+        Json(SetOrderLabelsRequest {
+            labels: labels.iter().map(|label| label.to_string()).collect(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_order_notes_are_open_to_the_tenant_and_operators_only() {
+        let (api, manager) = create_api();
+        let order_id = manager.create_order("tenant1".to_string());
+        let tenant = Request::builder().header("X-Tenant-Id", "tenant1").finish();
+        let other_tenant = Request::builder().header("X-Tenant-Id", "tenant2").finish();
+        let operator = Request::builder().header("X-User-Id", "alice").header("X-Roles", "approver").finish();
+        let no_role = Request::builder().header("X-User-Id", "mallory").header("X-Roles", "reader").finish();
+
+        assert!(matches!(
+            api.add_order_note(&other_tenant, Path(order_id.clone()), note("mine now")).await,
+            AnnotateOrderResponse::Unauthorized
+        ));
+        assert!(matches!(
+            api.add_order_note(&no_role, Path(order_id.clone()), note("hello")).await,
+            AnnotateOrderResponse::Forbidden
+        ));
+        assert!(matches!(
+            api.set_order_labels(&other_tenant, Path(order_id.clone()), labels(&["x"])).await,
+            AnnotateOrderResponse::Unauthorized
+        ));
+        assert!(matches!(
+            api.add_order_note(&tenant, Path("missing".to_string()), note("hello")).await,
+            AnnotateOrderResponse::NotFound
+        ));
+        assert!(matches!(
+            api.add_order_note(&tenant, Path(order_id.clone()), note("   ")).await,
+            AnnotateOrderResponse::BadRequest(_)
+        ));
+
+        let AnnotateOrderResponse::Created(_) =
+            api.add_order_note(&tenant, Path(order_id.clone()), note("waiting on customer")).await
+        else {
+            panic!("Expected the tenant's note to be added");
+        };
+        let AnnotateOrderResponse::Created(Json(order)) =
+            api.add_order_note(&operator, Path(order_id.clone()), note(" NetBox ticket #123 ")).await
+        else {
+            panic!("Expected the operator's note to be added");
+        };
+        let notes: Vec<_> = order.notes.iter().map(|note| (note.author.as_str(), note.text.as_str())).collect();
+        assert_eq!(notes, vec![("tenant:tenant1", "waiting on customer"), ("alice", "NetBox ticket #123")]);
+        assert_eq!(manager.get_order(&order_id).unwrap().notes.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_list_orders_filters_by_label() {
+        let (api, manager) = create_api();
+        let migrated: Vec<String> = (0..3).map(|_| manager.create_order("tenant1".to_string())).collect();
+        let unlabelled = manager.create_order("tenant1".to_string());
+        let tenant = Request::builder().header("X-Tenant-Id", "tenant1").finish();
+        for order_id in &migrated {
+            let AnnotateOrderResponse::Ok(Json(order)) = api
+                .set_order_labels(&tenant, Path(order_id.clone()), labels(&[" batch-migration", "emea", "batch-migration"]))
+                .await
+            else {
+                panic!("Expected the labels to be set");
+            };
+            assert_eq!(order.labels, vec!["batch-migration", "emea"]);
+        }
+        assert!(matches!(
+            api.set_order_labels(&tenant, Path(unlabelled.clone()), labels(&["no spaces"])).await,
+            AnnotateOrderResponse::BadRequest(_)
+        ));
+        let label = || Query(Some("batch-migration".to_string()));
+
+        let ListOrdersResponse::Ok(Json(orders)) =
+            api.list_orders(&tenant, Query(None), Query(None), Query(None), Query(None), label()).await.unwrap()
+        else {
+            panic!("Expected orders");
+        };
+        let mut listed: Vec<_> = orders.into_iter().map(|order| order.order_id).collect();
+        listed.sort();
+        let mut expected = migrated.clone();
+        expected.sort();
+        assert_eq!(listed, expected);
+
+        let ListOrdersResponse::Page(Json(page)) =
+            api.list_orders(&tenant, Query(None), Query(Some(2)), Query(None), Query(None), label()).await.unwrap()
+        else {
+            panic!("Expected a page");
+        };
+        assert_eq!(page.orders.len(), 2);
+        let ListOrdersResponse::Page(Json(rest)) =
+            api.list_orders(&tenant, Query(None), Query(Some(2)), Query(page.next_cursor), Query(None), label()).await.unwrap()
+        else {
+            panic!("Expected a page");
+        };
+        assert_eq!(rest.orders.len(), 1);
+        assert!(rest.next_cursor.is_none());
+        assert!(rest.orders[0].order_id != unlabelled);
+    }
+
+    #[tokio::test]
+    async fn test_export_orders_scopes_to_caller_tenant() {
+        use poem::IntoResponse;
+
+        let (api, manager) = create_api();
+        manager.create_order("tenant1".to_string());
+        manager.create_order("tenant2".to_string());
+        let req = Request::builder().header("X-Tenant-Id", "tenant1").finish();
+
+        let response = export(&api, &req, None, None).await.unwrap().into_response();
+        assert_eq!(response.content_type(), Some("text/csv"));
+        assert!(response
+            .headers()
+            .get("content-disposition")
+            .is_some_and(|value| value.to_str().unwrap().contains("orders-tenant1.csv")));
+        let csv = response.into_body().into_string().await.unwrap();
+        assert_eq!(csv.lines().count(), 2);
+
+        let other = export(&api, &req, Some("all"), None).await;
+        assert_eq!(other.err().unwrap().status(), poem::http::StatusCode::UNAUTHORIZED);
+        let bad_format = export(&api, &req, None, Some("xml")).await.unwrap();
+        assert!(matches!(bad_format, ExportOrdersResponse::BadRequest(_)));
+
+        let admin = Request::builder().header("X-User-Id", "finance").header("X-Roles", "admin").finish();
+        let all = export(&api, &admin, Some("all"), Some("jsonl")).await.unwrap();
+        let jsonl = match all {
+            ExportOrdersResponse::Jsonl(attachment) => attachment.into_response().into_body().into_string().await.unwrap(),
+            _ => panic!("Expected a JSONL export"),
+        };
+        assert_eq!(jsonl.lines().count(), 2);
+    }
+
+    fn create_order_request() -> Json<CreateSiteOrder> {
+        Json(CreateSiteOrder {
+            name: "Test Site".to_string(),
+            description: None,
+            address: None,
+            region: None,
+            facility: None,
+            tags: vec![],
+            custom_fields: Default::default(),
+            priority: Default::default(),
+        })
+    }
+
+    fn decision() -> Json<ApprovalDecisionRequest> {
+        Json(ApprovalDecisionRequest { comment: None })
+    }
+
+    async fn held_order_id(api: &OrdersApi) -> String {
+        let req = Request::builder().header("X-Tenant-Id", "tenant1").finish();
+        match api.create_site(&req, create_order_request()).await.unwrap() {
+            CreateSiteResponse::Accepted(Json(response)) => {
+                assert_eq!(response.state, "AwaitingApproval");
+                assert_eq!(response.netbox_site_id, None);
+                response.order_id
+            }
+            _ => panic!("Expected Accepted response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_approval_requires_approver_role() {
+        let (api, _) = create_api();
+        let order_id = held_order_id(&api).await;
+
+        let anonymous = Request::builder().header("X-Tenant-Id", "tenant1").finish();
+        let result = api.approve_order(&anonymous, Path(order_id.clone()), decision()).await;
+        assert!(matches!(result, ApprovalDecisionResponse::Unauthorized));
+
+        let tenant_user = Request::builder()
+            .header("X-Tenant-Id", "tenant1")
+            .header("X-User-Id", "bob")
+            .header("X-Roles", "viewer")
+            .finish();
+        let result = api.approve_order(&tenant_user, Path(order_id), decision()).await;
+        assert!(matches!(result, ApprovalDecisionResponse::Forbidden));
+    }
+
+    #[tokio::test]
+    async fn test_reject_order_by_approver() {
+        let (api, workflow_manager) = create_api();
+        let order_id = held_order_id(&api).await;
+        let approver = Request::builder()
+            .header("X-User-Id", "alice")
+            .header("X-Roles", "approver")
+            .finish();
+
+        match api.reject_order(&approver, Path(order_id.clone()), decision()).await {
+            ApprovalDecisionResponse::Ok(Json(status)) => assert_eq!(status.state, "Cancelled"),
+            _ => panic!("Expected Ok response"),
+        }
+        assert_eq!(workflow_manager.get_order(&order_id).unwrap().state, OrderState::Cancelled);
+
+        let again = api.reject_order(&approver, Path(order_id), decision()).await;
+        assert!(matches!(again, ApprovalDecisionResponse::Conflict(_)));
+        let missing = api.reject_order(&approver, Path("missing".to_string()), decision()).await;
+        assert!(matches!(missing, ApprovalDecisionResponse::NotFound));
+    }
+
+    #[tokio::test]
+    async fn test_force_execute_requires_admin_role_and_a_scheduled_order() {
+        let (api, _) = create_api();
+        let order_id = held_order_id(&api).await;
+
+        let approver = Request::builder()
+            .header("X-User-Id", "alice")
+            .header("X-Roles", "approver")
+            .finish();
+        let result = api.force_execute_order(&approver, Path(order_id.clone()), decision()).await;
+        assert!(matches!(result, ApprovalDecisionResponse::Forbidden));
+
+        // Awaiting approval isn't scheduled; forcing it must not skip the approval
+        let admin = Request::builder()
+            .header("X-User-Id", "root")
+            .header("X-Roles", "admin")
+            .finish();
+        let result = api.force_execute_order(&admin, Path(order_id), decision()).await;
+        assert!(matches!(result, ApprovalDecisionResponse::Conflict(_)));
+    }
+
+    #[tokio::test]
+    async fn test_order_types_and_generic_orders() {
+        use crate::business::ExtensibleOrderServiceBuilder;
+
+        let (api, _) = create_api();
+        assert!(matches!(api.list_order_types().await, ListOrderTypesResponse::ServiceUnavailable(_)));
+
+        let client = Arc::new(ResilientNetBoxClient::new(Arc::new(
+            NetBoxClient::new(Config { netbox_token: "test-token".to_string(), ..Default::default() }).unwrap(),
+        )));
+        let extensible_service = ExtensibleOrderServiceBuilder::new()
+            .with_default_processors()
+            .build(Arc::new(WorkflowManager::new()), client);
+        let api = api.with_extensible_service(Arc::new(extensible_service));
+
+        match api.list_order_types().await {
+            ListOrderTypesResponse::Ok(Json(types)) => {
+                let names: Vec<&str> = types.iter().map(|t| t.order_type.as_str()).collect();
+                assert_eq!(names, vec!["device", "ip_address", "network", "site", "virtual_machine", "vlan"]);
+                assert!(types.iter().all(|t| t.payload_schema["type"] == "object"));
+            }
+            _ => panic!("Expected Ok response"),
+        }
+
+        let req = Request::builder().header("X-Tenant-Id", "tenant1").finish();
+        let unknown = api
+            .create_order(&req, Path("rack".to_string()), Json(serde_json::json!({})))
+            .await
+            .unwrap();
+        assert!(matches!(unknown, CreateOrderResponse::NotFound(_)));
+        let invalid = api
+            .create_order(&req, Path("site".to_string()), Json(serde_json::json!({ "name": 42 })))
+            .await
+            .unwrap();
+        assert!(matches!(invalid, CreateOrderResponse::BadRequest(_)));
+    }
+
+    #[tokio::test]
+    async fn test_order_type_schema_checked_before_processing() {
+        use crate::business::ExtensibleOrderServiceBuilder;
+
+        let (api, _) = create_api();
+        let client = Arc::new(ResilientNetBoxClient::new(Arc::new(
+            NetBoxClient::new(Config { netbox_token: "test-token".to_string(), ..Default::default() }).unwrap(),
+        )));
+        let workflow_manager = Arc::new(WorkflowManager::new());
+        let extensible_service = ExtensibleOrderServiceBuilder::new()
+            .with_default_processors()
+            .build(workflow_manager.clone(), client);
+        let api = api.with_extensible_service(Arc::new(extensible_service));
+
+        match api.get_order_type_schema(Path("device".to_string())).await {
+            OrderTypeSchemaResponse::Ok(Json(schema)) => {
+                assert_eq!(schema["required"], serde_json::json!(["name", "device_type", "role"]));
+            }
+            _ => panic!("Expected Ok response"),
+        }
+        assert!(matches!(
+            api.get_order_type_schema(Path("rack".to_string())).await,
+            OrderTypeSchemaResponse::NotFound(_)
+        ));
+
+        let req = Request::builder().header("X-Tenant-Id", "tenant1").finish();
+        let payload = serde_json::json!({ "name": "edge-01", "device_type": 0, "role": 2, "tags": ["core", 7] });
+        match api.create_order(&req, Path("device".to_string()), Json(payload)).await.unwrap() {
+            CreateOrderResponse::BadRequest(Json(body)) => {
+                let message = body["message"].as_str().unwrap();
+                assert!(message.contains("device_type: must be at least 1"), "{}", message);
+                assert!(message.contains("tags[1]: must be a string"), "{}", message);
+            }
+            _ => panic!("Expected BadRequest response"),
+        }
+        assert_eq!(workflow_manager.order_count(), 0);
+    }
+
+    fn create_pop_request(device_names: &[&str]) -> Json<CreatePopOrder> {
+        let devices = device_names
+            .iter()
+            .map(|name| crate::domain::PopDeviceDefinition {
+                name: name.to_string(),
+                device_type: 1,
+                role: 1,
+                serial: None,
+                tags: vec![],
+            })
+            .collect();
+        Json(CreatePopOrder {
+            site: create_order_request().0,
+            devices,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_pop_order_held_for_approval_lists_pending_resources() {
+        let (api, _) = create_api();
+        let req = Request::builder().header("X-Tenant-Id", "tenant1").finish();
+
+        let response = api.create_pop(&req, create_pop_request(&["sw1", "sw2"])).await.unwrap();
+
+        let CreatePopResponse::Accepted(Json(response)) = response else {
+            panic!("Expected Accepted response");
+        };
+        assert_eq!(response.state, "AwaitingApproval");
+        let resources: Vec<_> = response
+            .resources
+            .iter()
+            .map(|resource| (resource.kind.as_str(), resource.name.as_str(), resource.status.as_str()))
+            .collect();
+        assert_eq!(
+            resources,
+            vec![("site", "Test Site", "pending"), ("device", "sw1", "pending"), ("device", "sw2", "pending")]
+        );
+
+        let status = api.get_order_status(&req, Path(response.order_id)).await.unwrap();
+        let GetOrderStatusResponse::Ok(Json(status)) = status else {
+            panic!("Expected Ok response");
+        };
+        assert_eq!(status.resources.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_pop_order_with_duplicate_devices_is_rejected() {
+        let (api, _) = create_api();
+        let req = Request::builder().header("X-Tenant-Id", "tenant1").finish();
+
+        let response = api.create_pop(&req, create_pop_request(&["sw1", "sw1"])).await.unwrap();
+
+        assert!(matches!(response, CreatePopResponse::BadRequest(_)));
+    }
+
+    #[tokio::test]
+    async fn test_netbox_field_errors_are_returned_as_bad_request() {
+        use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "slug": ["site with this slug already exists."],
+                "non_field_errors": ["Sites must be unique."]
+            })))
+            .mount(&mock_server)
+            .await;
+        let config = Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let client = Arc::new(ResilientNetBoxClient::new(Arc::new(NetBoxClient::new(config).unwrap())));
+        let api = OrdersApi::new(Arc::new(OrderService::new(Arc::new(WorkflowManager::new()), client)));
+        let req = Request::builder().header("X-Tenant-Id", "tenant1").finish();
+
+        let response = api.create_site(&req, create_order_request()).await.unwrap();
+
+        let CreateSiteResponse::BadRequest(Json(body)) = response else {
+            panic!("Expected BadRequest response");
+        };
+        assert_eq!(
+            body["fields"],
+            serde_json::json!([
+                {"field": "non_field_errors", "messages": ["Sites must be unique."]},
+                {"field": "slug", "messages": ["site with this slug already exists."]}
+            ])
+        );
+        assert_eq!(body["message"], "Sites must be unique.; slug: site with this slug already exists.");
+    }
+
+    #[tokio::test]
+    async fn test_site_missing_from_netbox_is_not_found() {
+        use crate::security::tenant::{TenantAccessControl, TenantMappingService};
+        use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/99/"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({"detail": "Not found."})))
+            .mount(&mock_server)
+            .await;
+        let config = Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let client = Arc::new(ResilientNetBoxClient::new(Arc::new(NetBoxClient::new(config).unwrap())));
+        let mappings = TenantMappingService::new();
+        mappings.register_mapping("tenant1".to_string(), 10);
+        let service = OrderService::new(Arc::new(WorkflowManager::new()), client)
+            .with_access_control(Arc::new(TenantAccessControl::new(mappings)));
+        let api = OrdersApi::new(Arc::new(service));
+        let req = Request::builder().header("X-Tenant-Id", "tenant1").finish();
+
+        let order = DecommissionSiteOrder { site_id: 99, force: false, reason: None };
+        let response = api.decommission_site(&req, Json(order)).await.unwrap();
+
+        assert!(matches!(response, DecommissionSiteOrderResponse::NotFound(_)));
+    }
+}
@@ -0,0 +1,183 @@
+use poem::Request;
+use poem_openapi::registry::Registry;
+use poem_openapi::types::Type;
+use serde_json::{Map, Value};
+
+use crate::business::order_export::csv_field;
+use crate::error::AppError;
+
+/// Property names of `T`'s OpenAPI schema, in declaration order
+pub fn field_names<T: Type>() -> Vec<&'static str> {
+    let mut registry = Registry::new();
+    T::register(&mut registry);
+    registry
+        .schemas
+        .get(T::name().as_ref())
+        .map(|schema| schema.properties.iter().map(|(name, _)| *name).collect())
+        .unwrap_or_default()
+}
+
+/// How a list endpoint should render its items: `fields=` projection and `Accept: text/csv`
+///
+/// Works on the serialized JSON of any poem-openapi object, so list items need
+/// no per-model code; valid field names come from the object's schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListView {
+    /// Fields to keep, in the requested order; every field when `None`
+    pub fields: Option<Vec<String>>,
+    /// The client asked for `text/csv`
+    pub csv: bool,
+    /// Every field of the item type, used as CSV columns without `fields=`
+    all_fields: Vec<String>,
+}
+
+impl ListView {
+    /// Read `fields=` and the Accept header for a list of `T`
+    ///
+    /// Unknown fields are a validation error naming the valid ones.
+    pub fn for_items<T: Type>(req: &Request, fields: Option<&str>) -> Result<Self, AppError> {
+        let all_fields: Vec<String> = field_names::<T>().into_iter().map(str::to_string).collect();
+        let fields = match fields.map(str::trim).filter(|fields| !fields.is_empty()) {
+            Some(fields) => {
+                let requested: Vec<String> = fields.split(',').map(|field| field.trim().to_string()).collect();
+                let unknown: Vec<&str> = requested
+                    .iter()
+                    .filter(|field| !all_fields.contains(field))
+                    .map(String::as_str)
+                    .collect();
+                if !unknown.is_empty() {
+                    return Err(AppError::ValidationError(format!(
+                        "Unknown fields: {}. Valid fields: {}",
+                        unknown.join(", "),
+                        all_fields.join(", ")
+                    )));
+                }
+                Some(requested)
+            }
+            None => None,
+        };
+        let csv = req
+            .header("Accept")
+            .is_some_and(|accept| accept.split(',').any(|media| media.split(';').next().unwrap_or("").trim() == "text/csv"));
+        Ok(Self { fields, csv, all_fields })
+    }
+
+    /// Whether the typed response can be returned unchanged
+    pub fn is_default(&self) -> bool {
+        self.fields.is_none() && !self.csv
+    }
+
+    /// Keep only the selected fields of an item
+    pub fn project(&self, item: Value) -> Value {
+        match (&self.fields, item) {
+            (Some(fields), Value::Object(mut object)) => {
+                let projected: Map<String, Value> = fields
+                    .iter()
+                    .map(|field| (field.clone(), object.remove(field).unwrap_or(Value::Null)))
+                    .collect();
+                Value::Object(projected)
+            }
+            (_, item) => item,
+        }
+    }
+
+    /// Serialize `response` and project each item of its `key` array
+    pub fn project_list<T: serde::Serialize>(&self, response: &T, key: &str) -> Value {
+        let mut value = serde_json::to_value(response).unwrap_or(Value::Null);
+        if let Some(items) = value.get_mut(key).and_then(Value::as_array_mut) {
+            for item in items.iter_mut() {
+                *item = self.project(item.take());
+            }
+        }
+        value
+    }
+
+    /// Items as CSV with a header row of the selected (or all) fields
+    ///
+    /// Strings are written as is, nulls as empty fields, and other values as JSON.
+    pub fn to_csv<T: serde::Serialize>(&self, items: &[T]) -> String {
+        let columns = self.fields.as_ref().unwrap_or(&self.all_fields);
+        let mut csv = columns.iter().map(|column| csv_field(column)).collect::<Vec<_>>().join(",");
+        csv.push_str("\r\n");
+        for item in items {
+            let item = serde_json::to_value(item).unwrap_or(Value::Null);
+            let row: Vec<String> = columns
+                .iter()
+                .map(|column| match item.get(column) {
+                    None | Some(Value::Null) => String::new(),
+                    Some(Value::String(text)) => csv_field(text).into_owned(),
+                    Some(other) => csv_field(&other.to_string()).into_owned(),
+                })
+                .collect();
+            csv.push_str(&row.join(","));
+            csv.push_str("\r\n");
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poem_openapi::Object;
+    use serde::Serialize;
+
+    #[derive(Debug, Clone, Serialize, Object)]
+    struct Item {
+        id: i32,
+        name: String,
+        tags: Vec<String>,
+        note: Option<String>,
+    }
+
+    fn item() -> Item {
+        Item {
+            id: 1,
+            name: "Berlin, DC1".to_string(),
+            tags: vec!["edge".to_string()],
+            note: None,
+        }
+    }
+
+    fn request(accept: Option<&str>) -> Request {
+        match accept {
+            Some(accept) => Request::builder().header("Accept", accept).finish(),
+            None => Request::builder().finish(),
+        }
+    }
+
+    #[test]
+    fn test_field_names_follow_schema() {
+        assert_eq!(field_names::<Item>(), vec!["id", "name", "tags", "note"]);
+    }
+
+    #[test]
+    fn test_projection_keeps_requested_fields() {
+        let view = ListView::for_items::<Item>(&request(None), Some("name, id")).unwrap();
+        assert!(!view.is_default());
+
+        let projected = view.project_list(&serde_json::json!({ "results": [item()], "total": 1 }), "results");
+        assert_eq!(projected, serde_json::json!({ "results": [{ "id": 1, "name": "Berlin, DC1" }], "total": 1 }));
+    }
+
+    #[test]
+    fn test_unknown_fields_list_valid_options() {
+        let err = ListView::for_items::<Item>(&request(None), Some("id,bogus")).unwrap_err();
+        let AppError::ValidationError(message) = err else {
+            panic!("Expected a validation error");
+        };
+        assert!(message.contains("bogus"));
+        assert!(message.contains("id, name, tags, note"));
+    }
+
+    #[test]
+    fn test_csv_output_shape() {
+        let all = ListView::for_items::<Item>(&request(Some("text/csv;q=0.9, application/json")), None).unwrap();
+        assert!(all.csv);
+        assert_eq!(all.to_csv(&[item()]), "id,name,tags,note\r\n1,\"Berlin, DC1\",\"[\"\"edge\"\"]\",\r\n");
+
+        let projected = ListView::for_items::<Item>(&request(Some("text/csv")), Some("name")).unwrap();
+        assert_eq!(projected.to_csv(&[item()]), "name\r\n\"Berlin, DC1\"\r\n");
+        assert!(ListView::for_items::<Item>(&request(Some("application/json")), None).unwrap().is_default());
+    }
+}
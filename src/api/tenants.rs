@@ -2,21 +2,128 @@ use poem_openapi::{payload::Json, ApiResponse, OpenApi, param::Path};
 use poem::Request;
 use std::sync::Arc;
 
+use crate::business::WorkflowManager;
 use crate::domain::Site;
-use crate::domain::tenant::TenantStore;
+use crate::domain::tenant::{RegisterTenantRequest, TenantInfo, TenantStore, UpdateTenantMappingRequest};
+use crate::domain::webhook::{RegisterWebhookRequest, RotateWebhookSecretRequest, WebhookInfo, WebhookRegistration};
 use crate::error::AppError;
-use crate::security::extract_tenant_id;
+use crate::netbox::models::CreateTenantRequest;
+use crate::netbox::{NetBoxRouter, ResilientNetBoxClient};
+use crate::security::tenant::{MappingError, MappingStoreError, TenantMapping, DEFAULT_NETBOX_ENDPOINT};
+use crate::security::{extract_tenant_id, require_role, TenantMappingService, ADMIN_ROLE};
 
 pub struct TenantsApi {
     store: Arc<TenantStore>,
+    mappings: Arc<TenantMappingService>,
+    workflow_manager: Option<Arc<WorkflowManager>>,
+    netbox_client: Option<Arc<ResilientNetBoxClient>>,
+    netbox_router: Option<Arc<NetBoxRouter>>,
 }
 
 impl TenantsApi {
     pub fn new(store: Arc<TenantStore>) -> Self {
-        Self { store }
+        Self {
+            store,
+            mappings: Arc::new(TenantMappingService::new()),
+            workflow_manager: None,
+            netbox_client: None,
+            netbox_router: None,
+        }
+    }
+
+    /// Manage this mapping store, shared with the tenant-scoped NetBox reads
+    pub fn with_tenant_mappings(mut self, mappings: Arc<TenantMappingService>) -> Self {
+        self.mappings = mappings;
+        self
+    }
+
+    /// Refuse to delete tenants with orders still in flight
+    pub fn with_workflow_manager(mut self, workflow_manager: Arc<WorkflowManager>) -> Self {
+        self.workflow_manager = Some(workflow_manager);
+        self
+    }
+
+    /// Allow registering a tenant together with a new NetBox tenant
+    pub fn with_netbox_client(mut self, netbox_client: Arc<ResilientNetBoxClient>) -> Self {
+        self.netbox_client = Some(netbox_client);
+        self
+    }
+
+    /// Create NetBox tenants on the endpoint the new tenant is mapped to
+    pub fn with_netbox_router(mut self, router: Arc<NetBoxRouter>) -> Self {
+        self.netbox_router = Some(router);
+        self
+    }
+
+    /// Check a tenant's own NetBox token on its endpoint and encrypt it for the mapping
+    async fn checked_token(&self, endpoint: Option<&str>, token: Option<String>) -> Result<Option<String>, AppError> {
+        let Some(token) = token else {
+            return Ok(None);
+        };
+        let Some(ref router) = self.netbox_router else {
+            return Err(AppError::ServiceUnavailable("NetBox is not configured".to_string()));
+        };
+        router
+            .encrypt_checked_token(endpoint.unwrap_or(DEFAULT_NETBOX_ENDPOINT), &token)
+            .await
+            .map(Some)
+    }
+
+    /// Client for a NetBox endpoint, by name; `None` is the default endpoint
+    fn endpoint_client(&self, endpoint: Option<&str>) -> Option<Arc<ResilientNetBoxClient>> {
+        match (&self.netbox_router, endpoint) {
+            (Some(router), Some(name)) => router.endpoint(name).map(|endpoint| endpoint.client.clone()),
+            (Some(router), None) => Some(router.default_endpoint().client.clone()),
+            (None, _) => self.netbox_client.clone(),
+        }
     }
 }
 
+#[derive(ApiResponse)]
+pub enum RegisterTenantResponse {
+    #[oai(status = 201)]
+    Created(Json<TenantInfo>),
+
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+
+    #[oai(status = 409)]
+    Conflict(Json<serde_json::Value>),
+
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+pub enum ListTenantsResponse {
+    #[oai(status = 200)]
+    Ok(Json<Vec<TenantInfo>>),
+}
+
+#[derive(ApiResponse)]
+pub enum TenantResponse {
+    #[oai(status = 200)]
+    Ok(Json<TenantInfo>),
+
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+
+    #[oai(status = 404)]
+    NotFound,
+}
+
+#[derive(ApiResponse)]
+pub enum DeleteTenantResponse {
+    #[oai(status = 204)]
+    NoContent,
+
+    #[oai(status = 404)]
+    NotFound,
+
+    #[oai(status = 409)]
+    Conflict(Json<serde_json::Value>),
+}
+
 #[derive(ApiResponse)]
 pub enum GetSitesResponse {
     #[oai(status = 200)]
@@ -26,8 +133,295 @@ pub enum GetSitesResponse {
     Unauthorized,
 }
 
+#[derive(ApiResponse)]
+pub enum RegisterWebhookResponse {
+    #[oai(status = 201)]
+    Created(Json<WebhookInfo>),
+    
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+    
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+#[derive(ApiResponse)]
+pub enum ListWebhooksResponse {
+    #[oai(status = 200)]
+    Ok(Json<Vec<WebhookInfo>>),
+    
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+#[derive(ApiResponse)]
+pub enum DeleteWebhookResponse {
+    #[oai(status = 204)]
+    NoContent,
+    
+    #[oai(status = 401)]
+    Unauthorized,
+    
+    #[oai(status = 404)]
+    NotFound,
+}
+
+#[derive(ApiResponse)]
+pub enum WebhookSecretResponse {
+    #[oai(status = 200)]
+    Ok(Json<WebhookInfo>),
+    
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+    
+    #[oai(status = 401)]
+    Unauthorized,
+    
+    #[oai(status = 404)]
+    NotFound,
+}
+
+/// Verify the tenant_id in path matches the one in header
+fn authorize_tenant(req: &Request, tenant_id: &str) -> Result<String, AppError> {
+    let header_tenant_id = extract_tenant_id(req)?;
+    if header_tenant_id != tenant_id {
+        return Err(AppError::Unauthorized);
+    }
+    Ok(header_tenant_id)
+}
+
+/// Surface a failed write to the mapping store
+fn mapping_store_error(e: MappingStoreError) -> AppError {
+    AppError::Internal(anyhow::anyhow!("Failed to persist tenant mappings: {}", e))
+}
+
+/// Unknown endpoints are the caller's mistake; store failures are ours
+fn mapping_error(e: MappingError) -> AppError {
+    match e {
+        MappingError::UnknownEndpoint { .. } => AppError::ValidationError(e.to_string()),
+        MappingError::Store(e) => mapping_store_error(e),
+    }
+}
+
 #[OpenApi]
 impl TenantsApi {
+    /// Register a tenant and its NetBox tenant mapping (requires the admin role)
+    ///
+    /// With `netbox_tenant` set, the NetBox tenant is created first; it is deleted
+    /// again if the mapping cannot be registered. A `netbox_token` is checked
+    /// against NetBox before anything is created.
+    #[oai(path = "/tenants", method = "post")]
+    async fn register_tenant(
+        &self,
+        req: &Request,
+        body: Json<RegisterTenantRequest>,
+    ) -> Result<RegisterTenantResponse, poem::Error> {
+        require_role(req, ADMIN_ROLE)?;
+
+        let request = body.0;
+        if request.tenant_id.trim().is_empty() {
+            return Ok(RegisterTenantResponse::BadRequest(Json(serde_json::json!({
+                "error": "tenant_id is required"
+            }))));
+        }
+        if self.mappings.has_mapping(&request.tenant_id) {
+            return Ok(RegisterTenantResponse::Conflict(Json(serde_json::json!({
+                "error": format!("Tenant '{}' already exists", request.tenant_id)
+            }))));
+        }
+
+        // Refuse unknown endpoints before creating anything in NetBox
+        let endpoint = request.netbox_endpoint.clone();
+        if let Err(e) = self.mappings.check_endpoint(&request.tenant_id, &TenantMapping::new(0, endpoint.clone())) {
+            return Ok(RegisterTenantResponse::BadRequest(Json(serde_json::json!({
+                "error": e.to_string()
+            }))));
+        }
+        let netbox_token = match self.checked_token(endpoint.as_deref(), request.netbox_token).await {
+            Ok(token) => token,
+            Err(AppError::ValidationError(message)) => {
+                return Ok(RegisterTenantResponse::BadRequest(Json(serde_json::json!({ "error": message }))));
+            }
+            Err(e) if e.is_unavailable() => {
+                return Ok(RegisterTenantResponse::ServiceUnavailable(Json(serde_json::json!({ "error": e.to_string() }))));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let client = self.endpoint_client(endpoint.as_deref());
+
+        let (netbox_tenant_id, created_in_netbox) = match (request.netbox_tenant_id, request.netbox_tenant) {
+            (Some(id), None) => (id, false),
+            (None, Some(new_tenant)) => {
+                let Some(ref client) = client else {
+                    return Ok(RegisterTenantResponse::ServiceUnavailable(Json(serde_json::json!({
+                        "error": "NetBox is not configured"
+                    }))));
+                };
+                let tenant = client
+                    .create_tenant(CreateTenantRequest {
+                        name: new_tenant.name,
+                        slug: new_tenant.slug,
+                        description: new_tenant.description,
+                    })
+                    .await?;
+                let id = tenant
+                    .id
+                    .ok_or_else(|| AppError::Internal(anyhow::anyhow!("NetBox returned a tenant without an id")))?;
+                (id, true)
+            }
+            _ => {
+                return Ok(RegisterTenantResponse::BadRequest(Json(serde_json::json!({
+                    "error": "Set exactly one of netbox_tenant_id or netbox_tenant"
+                }))));
+            }
+        };
+
+        let mapping = TenantMapping::new(netbox_tenant_id, endpoint).with_netbox_token(netbox_token);
+        let registered = self.mappings.insert_mapping(request.tenant_id.clone(), mapping.clone()).await;
+        if !matches!(registered, Ok(true)) && created_in_netbox {
+            // Undo the NetBox side so a retry can create it again
+            if let Some(ref client) = client {
+                if let Err(e) = client.delete_tenant(netbox_tenant_id).await {
+                    tracing::warn!("Failed to delete NetBox tenant {} after a failed registration: {}", netbox_tenant_id, e);
+                }
+            }
+        }
+
+        match registered {
+            Ok(true) => Ok(RegisterTenantResponse::Created(Json(TenantInfo::new(request.tenant_id, mapping)))),
+            Ok(false) => Ok(RegisterTenantResponse::Conflict(Json(serde_json::json!({
+                "error": format!("Tenant '{}' already exists", request.tenant_id)
+            })))),
+            Err(e) => Err(mapping_error(e).into()),
+        }
+    }
+
+    /// List tenants with their NetBox tenant ids and endpoints (requires the admin role)
+    #[oai(path = "/tenants", method = "get")]
+    async fn list_tenants(&self, req: &Request) -> Result<ListTenantsResponse, poem::Error> {
+        require_role(req, ADMIN_ROLE)?;
+
+        let tenants = self
+            .mappings
+            .all_mappings()
+            .into_iter()
+            .map(|(tenant_id, mapping)| TenantInfo::new(tenant_id, mapping))
+            .collect();
+        Ok(ListTenantsResponse::Ok(Json(tenants)))
+    }
+
+    /// Get a tenant and its NetBox tenant id (requires the admin role)
+    #[oai(path = "/tenants/:tenant_id", method = "get")]
+    async fn get_tenant(&self, req: &Request, tenant_id: Path<String>) -> Result<TenantResponse, poem::Error> {
+        require_role(req, ADMIN_ROLE)?;
+
+        Ok(match self.mappings.get_mapping(&tenant_id.0) {
+            Some(mapping) => TenantResponse::Ok(Json(TenantInfo::new(tenant_id.0, mapping))),
+            None => TenantResponse::NotFound,
+        })
+    }
+
+    /// Map a tenant to another NetBox tenant or endpoint (requires the admin role)
+    ///
+    /// The tenant keeps its own NetBox token unless it moves to another
+    /// endpoint or a new token is given.
+    #[oai(path = "/tenants/:tenant_id", method = "put")]
+    async fn update_tenant(
+        &self,
+        req: &Request,
+        tenant_id: Path<String>,
+        body: Json<UpdateTenantMappingRequest>,
+    ) -> Result<TenantResponse, poem::Error> {
+        require_role(req, ADMIN_ROLE)?;
+
+        let Some(current) = self.mappings.get_mapping(&tenant_id.0) else {
+            return Ok(TenantResponse::NotFound);
+        };
+        let body = body.0;
+        let netbox_token = match body.netbox_token {
+            Some(token) => match self.checked_token(body.netbox_endpoint.as_deref(), Some(token)).await {
+                Ok(token) => token,
+                Err(AppError::ValidationError(message)) => {
+                    return Ok(TenantResponse::BadRequest(Json(serde_json::json!({ "error": message }))));
+                }
+                Err(e) => return Err(e.into()),
+            },
+            // A token only works on the NetBox it was issued by
+            None => current.netbox_token.filter(|_| current.endpoint == body.netbox_endpoint),
+        };
+        let mapping = TenantMapping::new(body.netbox_tenant_id, body.netbox_endpoint).with_netbox_token(netbox_token);
+        match self.mappings.update_mapping(&tenant_id.0, mapping.clone()).await {
+            Ok(true) => {
+                // The stored mapping also says whether the tenant is disabled
+                let mapping = self.mappings.get_mapping(&tenant_id.0).unwrap_or(mapping);
+                Ok(TenantResponse::Ok(Json(TenantInfo::new(tenant_id.0, mapping))))
+            }
+            Ok(false) => Ok(TenantResponse::NotFound),
+            Err(e @ MappingError::UnknownEndpoint { .. }) => Ok(TenantResponse::BadRequest(Json(serde_json::json!({
+                "error": e.to_string()
+            })))),
+            Err(e) => Err(mapping_error(e).into()),
+        }
+    }
+
+    /// Disable a tenant (requires the admin role)
+    ///
+    /// A soft delete: the tenant's API calls are refused with 403 except reads
+    /// of its orders, while orders already in flight finish. The mapping is
+    /// kept until the tenant is restored or purged.
+    #[oai(path = "/tenants/:tenant_id", method = "delete")]
+    async fn delete_tenant(&self, req: &Request, tenant_id: Path<String>) -> Result<DeleteTenantResponse, poem::Error> {
+        let user = require_role(req, ADMIN_ROLE)?;
+
+        match self.mappings.disable_tenant(&tenant_id.0, user).await.map_err(mapping_store_error)? {
+            Some(disabled) => {
+                tracing::info!("Tenant {} disabled by {}", tenant_id.0, disabled.by);
+                Ok(DeleteTenantResponse::NoContent)
+            }
+            None => Ok(DeleteTenantResponse::NotFound),
+        }
+    }
+
+    /// Re-enable a disabled tenant (requires the admin role)
+    #[oai(path = "/tenants/:tenant_id/restore", method = "post")]
+    async fn restore_tenant(&self, req: &Request, tenant_id: Path<String>) -> Result<TenantResponse, poem::Error> {
+        let user = require_role(req, ADMIN_ROLE)?;
+
+        Ok(match self.mappings.restore_tenant(&tenant_id.0).await.map_err(mapping_store_error)? {
+            Some(mapping) => {
+                tracing::info!("Tenant {} restored by {}", tenant_id.0, user);
+                TenantResponse::Ok(Json(TenantInfo::new(tenant_id.0, mapping)))
+            }
+            None => TenantResponse::NotFound,
+        })
+    }
+
+    /// Remove a tenant's mapping for good (requires the admin role)
+    ///
+    /// Refused while any order of the tenant is still kept, finished or not,
+    /// so order history never loses its tenant. The NetBox tenant itself is
+    /// left in place.
+    #[oai(path = "/tenants/:tenant_id/purge", method = "post")]
+    async fn purge_tenant(&self, req: &Request, tenant_id: Path<String>) -> Result<DeleteTenantResponse, poem::Error> {
+        let user = require_role(req, ADMIN_ROLE)?;
+
+        if let Some(ref manager) = self.workflow_manager {
+            let orders = manager.get_tenant_orders(&tenant_id.0).len();
+            if orders > 0 {
+                return Ok(DeleteTenantResponse::Conflict(Json(serde_json::json!({
+                    "error": format!("Tenant '{}' still has {} orders; disable it instead", tenant_id.0, orders)
+                }))));
+            }
+        }
+
+        if self.mappings.delete_mapping(&tenant_id.0).await.map_err(mapping_store_error)? {
+            tracing::warn!("Tenant {} purged by {}", tenant_id.0, user);
+            Ok(DeleteTenantResponse::NoContent)
+        } else {
+            Ok(DeleteTenantResponse::NotFound)
+        }
+    }
+
     #[oai(path = "/tenants/:tenant_id/sites", method = "get")]
     async fn get_sites(
         &self,
@@ -44,5 +438,553 @@ impl TenantsApi {
         let sites = self.store.get_sites(&header_tenant_id);
         Ok(GetSitesResponse::Ok(Json(sites)))
     }
+
+    /// Register a webhook notified when the tenant's orders reach a terminal state
+    ///
+    /// Deliveries carry `X-Netgate-Timestamp`, the Unix time they were sent, and
+    /// `X-Netgate-Signature: sha256=<hex>`, the HMAC-SHA256 of the timestamp, a
+    /// dot and the body under the secret.
+    #[oai(path = "/tenants/:tenant_id/webhooks", method = "post")]
+    async fn register_webhook(
+        &self,
+        req: &Request,
+        tenant_id: Path<String>,
+        body: Json<RegisterWebhookRequest>,
+    ) -> Result<RegisterWebhookResponse, poem::Error> {
+        let tenant_id = authorize_tenant(req, &tenant_id.0)?;
+        
+        let request = body.0;
+        if !(request.url.starts_with("http://") || request.url.starts_with("https://")) {
+            return Ok(RegisterWebhookResponse::BadRequest(Json(serde_json::json!({
+                "error": "Webhook URL must be an http or https URL"
+            }))));
+        }
+        if request.secret.is_empty() {
+            return Ok(RegisterWebhookResponse::BadRequest(Json(serde_json::json!({
+                "error": "Webhook secret is required"
+            }))));
+        }
+        
+        let webhook = WebhookRegistration::from_request(request, tenant_id);
+        let info = WebhookInfo::from(&webhook);
+        self.store.add_webhook(webhook);
+        Ok(RegisterWebhookResponse::Created(Json(info)))
+    }
+
+    /// List the tenant's webhooks
+    #[oai(path = "/tenants/:tenant_id/webhooks", method = "get")]
+    async fn list_webhooks(
+        &self,
+        req: &Request,
+        tenant_id: Path<String>,
+    ) -> Result<ListWebhooksResponse, poem::Error> {
+        let tenant_id = authorize_tenant(req, &tenant_id.0)?;
+        
+        let webhooks = self.store.get_webhooks(&tenant_id);
+        Ok(ListWebhooksResponse::Ok(Json(webhooks.iter().map(WebhookInfo::from).collect())))
+    }
+
+    /// Delete one of the tenant's webhooks
+    #[oai(path = "/tenants/:tenant_id/webhooks/:webhook_id", method = "delete")]
+    async fn delete_webhook(
+        &self,
+        req: &Request,
+        tenant_id: Path<String>,
+        webhook_id: Path<String>,
+    ) -> Result<DeleteWebhookResponse, poem::Error> {
+        let tenant_id = authorize_tenant(req, &tenant_id.0)?;
+        
+        if self.store.remove_webhook(&tenant_id, &webhook_id.0) {
+            Ok(DeleteWebhookResponse::NoContent)
+        } else {
+            Ok(DeleteWebhookResponse::NotFound)
+        }
+    }
+
+    /// Rotate a webhook's signing secret
+    ///
+    /// Deliveries are signed with both the new and the current secret, one
+    /// `sha256=` entry each, until the previous secret is retired.
+    #[oai(path = "/tenants/:tenant_id/webhooks/:webhook_id/secret", method = "post")]
+    async fn rotate_webhook_secret(
+        &self,
+        req: &Request,
+        tenant_id: Path<String>,
+        webhook_id: Path<String>,
+        body: Json<RotateWebhookSecretRequest>,
+    ) -> Result<WebhookSecretResponse, poem::Error> {
+        let tenant_id = authorize_tenant(req, &tenant_id.0)?;
+        
+        let secret = body.0.secret;
+        if secret.is_empty() {
+            return Ok(WebhookSecretResponse::BadRequest(Json(serde_json::json!({
+                "error": "Webhook secret is required"
+            }))));
+        }
+        
+        match self.store.update_webhook(&tenant_id, &webhook_id.0, |w| w.rotate_secret(secret)) {
+            Some(webhook) => Ok(WebhookSecretResponse::Ok(Json(WebhookInfo::from(&webhook)))),
+            None => Ok(WebhookSecretResponse::NotFound),
+        }
+    }
+
+    /// Stop signing a webhook's deliveries with its previous secret
+    #[oai(path = "/tenants/:tenant_id/webhooks/:webhook_id/previous-secret", method = "delete")]
+    async fn retire_previous_webhook_secret(
+        &self,
+        req: &Request,
+        tenant_id: Path<String>,
+        webhook_id: Path<String>,
+    ) -> Result<WebhookSecretResponse, poem::Error> {
+        let tenant_id = authorize_tenant(req, &tenant_id.0)?;
+        
+        match self.store.update_webhook(&tenant_id, &webhook_id.0, |w| w.previous_secret = None) {
+            Some(webhook) => Ok(WebhookSecretResponse::Ok(Json(WebhookInfo::from(&webhook)))),
+            None => Ok(WebhookSecretResponse::NotFound),
+        }
+    }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::business::OrderState;
+    use crate::config::Config;
+    use crate::domain::tenant::NewNetBoxTenant;
+    use crate::domain::webhook::WebhookEvent;
+    use crate::netbox::client::NetBoxClient;
+    use crate::security::tenant::JsonFileTenantMappingStore;
+    use serde_json::json;
+    use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+    fn tenant_request(tenant_id: &str) -> Request {
+        Request::builder().header("X-Tenant-Id", tenant_id).finish()
+    }
+
+    fn admin_request() -> Request {
+        Request::builder()
+            .header("X-User-Id", "ops")
+            .header("X-Roles", "admin")
+            .finish()
+    }
+
+    fn register_request(tenant_id: &str, netbox_tenant_id: Option<i32>, netbox_tenant: Option<&str>) -> Json<RegisterTenantRequest> {
+        Json(RegisterTenantRequest {
+            tenant_id: tenant_id.to_string(),
+            netbox_tenant_id,
+            netbox_tenant: netbox_tenant.map(|name| NewNetBoxTenant {
+                name: name.to_string(),
+                slug: name.to_lowercase(),
+                description: None,
+            }),
+            netbox_endpoint: None,
+            netbox_token: None,
+        })
+    }
+
+    fn netbox_client(uri: String) -> Arc<ResilientNetBoxClient> {
+        let config = Config {
+            netbox_url: uri,
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        Arc::new(ResilientNetBoxClient::new(Arc::new(NetBoxClient::new(config).unwrap())))
+    }
+
+    #[tokio::test]
+    async fn test_register_tenant_creates_netbox_tenant_and_mapping() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/tenancy/tenants/"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "id": 42,
+                "name": "Acme",
+                "slug": "acme"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mappings = Arc::new(TenantMappingService::new());
+        let api = TenantsApi::new(Arc::new(TenantStore::new()))
+            .with_tenant_mappings(mappings.clone())
+            .with_netbox_client(netbox_client(mock_server.uri()));
+
+        let created = api
+            .register_tenant(&admin_request(), register_request("acme", None, Some("Acme")))
+            .await
+            .unwrap();
+        match created {
+            RegisterTenantResponse::Created(Json(info)) => {
+                assert_eq!(info, TenantInfo::new("acme".to_string(), TenantMapping::new(42, None)));
+            }
+            _ => panic!("Expected Created response"),
+        }
+        assert_eq!(mappings.get_netbox_tenant_id(&"acme".to_string()), Some(42));
+
+        // Registering again conflicts without touching NetBox
+        let again = api
+            .register_tenant(&admin_request(), register_request("acme", None, Some("Acme")))
+            .await
+            .unwrap();
+        assert!(matches!(again, RegisterTenantResponse::Conflict(_)));
+
+        match api.list_tenants(&admin_request()).await.unwrap() {
+            ListTenantsResponse::Ok(Json(tenants)) => assert_eq!(tenants.len(), 1),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_tenant_rolls_back_netbox_tenant_when_mapping_fails() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/tenancy/tenants/"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "id": 42,
+                "name": "Acme",
+                "slug": "acme"
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/api/tenancy/tenants/42/"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let store = Arc::new(JsonFileTenantMappingStore::new("/nonexistent-dir/mappings.json"));
+        let mappings = Arc::new(TenantMappingService::load(store).await.unwrap());
+        let api = TenantsApi::new(Arc::new(TenantStore::new()))
+            .with_tenant_mappings(mappings.clone())
+            .with_netbox_client(netbox_client(mock_server.uri()));
+
+        let result = api
+            .register_tenant(&admin_request(), register_request("acme", None, Some("Acme")))
+            .await;
+        assert!(result.is_err());
+        assert!(!mappings.has_mapping(&"acme".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_register_tenant_requires_one_netbox_source() {
+        let api = TenantsApi::new(Arc::new(TenantStore::new()));
+
+        let neither = api
+            .register_tenant(&admin_request(), register_request("acme", None, None))
+            .await
+            .unwrap();
+        assert!(matches!(neither, RegisterTenantResponse::BadRequest(_)));
+        let both = api
+            .register_tenant(&admin_request(), register_request("acme", Some(1), Some("Acme")))
+            .await
+            .unwrap();
+        assert!(matches!(both, RegisterTenantResponse::BadRequest(_)));
+    }
+
+    #[tokio::test]
+    async fn test_tenant_admin_requires_admin_role() {
+        let api = TenantsApi::new(Arc::new(TenantStore::new()));
+        let not_admin = Request::builder()
+            .header("X-User-Id", "alice")
+            .header("X-Roles", "approver")
+            .finish();
+
+        let result = api.register_tenant(&not_admin, register_request("acme", Some(1), None)).await;
+        assert_eq!(result.err().unwrap().status(), poem::http::StatusCode::FORBIDDEN);
+        let result = api.list_tenants(&tenant_request("acme")).await;
+        assert_eq!(result.err().unwrap().status(), poem::http::StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_update_and_get_tenant_mapping() {
+        let api = TenantsApi::new(Arc::new(TenantStore::new()));
+        api.register_tenant(&admin_request(), register_request("acme", Some(10), None))
+            .await
+            .unwrap();
+
+        let updated = api
+            .update_tenant(
+                &admin_request(),
+                Path("acme".to_string()),
+                Json(UpdateTenantMappingRequest { netbox_tenant_id: 11, netbox_endpoint: None, netbox_token: None }),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(updated, TenantResponse::Ok(_)));
+        match api.get_tenant(&admin_request(), Path("acme".to_string())).await.unwrap() {
+            TenantResponse::Ok(Json(info)) => assert_eq!(info.netbox_tenant_id, 11),
+            _ => panic!("Expected the tenant"),
+        }
+
+        let missing = api
+            .update_tenant(
+                &admin_request(),
+                Path("other".to_string()),
+                Json(UpdateTenantMappingRequest { netbox_tenant_id: 11, netbox_endpoint: None, netbox_token: None }),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(missing, TenantResponse::NotFound));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_netbox_endpoint_refused_at_registration() {
+        let mock_server = MockServer::start().await;
+        let mappings = Arc::new(TenantMappingService::new().with_endpoints(["eu".to_string()]));
+        let api = TenantsApi::new(Arc::new(TenantStore::new()))
+            .with_tenant_mappings(mappings.clone())
+            .with_netbox_client(netbox_client(mock_server.uri()));
+
+        // Refused before anything is created in NetBox
+        let mut request = register_request("acme", None, Some("Acme"));
+        request.0.netbox_endpoint = Some("apac".to_string());
+        let refused = api.register_tenant(&admin_request(), request).await.unwrap();
+        assert!(matches!(refused, RegisterTenantResponse::BadRequest(_)));
+        assert!(!mappings.has_mapping(&"acme".to_string()));
+        assert!(mock_server.received_requests().await.unwrap().is_empty());
+
+        let mut request = register_request("acme", Some(10), None);
+        request.0.netbox_endpoint = Some("eu".to_string());
+        match api.register_tenant(&admin_request(), request).await.unwrap() {
+            RegisterTenantResponse::Created(Json(info)) => assert_eq!(info.netbox_endpoint.as_deref(), Some("eu")),
+            _ => panic!("Expected Created response"),
+        }
+
+        let moved = api
+            .update_tenant(
+                &admin_request(),
+                Path("acme".to_string()),
+                Json(UpdateTenantMappingRequest { netbox_tenant_id: 10, netbox_endpoint: Some("apac".to_string()), netbox_token: None }),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(moved, TenantResponse::BadRequest(_)));
+        assert_eq!(mappings.get_mapping(&"acme".to_string()).unwrap().endpoint.as_deref(), Some("eu"));
+    }
+
+    #[tokio::test]
+    async fn test_tenant_netbox_token_checked_and_stored_encrypted() {
+        use crate::netbox::routing::NetBoxEndpoint;
+        use crate::security::TokenCipher;
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/status/"))
+            .and(header("Authorization", "Token acme-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"netbox-version": "4.1.0"})))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/status/"))
+            .respond_with(ResponseTemplate::new(403).set_body_json(json!({"detail": "Invalid token"})))
+            .mount(&mock_server)
+            .await;
+        let config = Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "global-token".to_string(),
+            ..Default::default()
+        };
+        let client = Arc::new(NetBoxClient::new(config.clone()).unwrap());
+        let mappings = Arc::new(TenantMappingService::new());
+        let router = NetBoxRouter::new(NetBoxEndpoint::new(DEFAULT_NETBOX_ENDPOINT, &config, client), mappings.clone())
+            .with_token_cipher(TokenCipher::new("tenant-token-key"));
+        let api = TenantsApi::new(Arc::new(TenantStore::new()))
+            .with_tenant_mappings(mappings.clone())
+            .with_netbox_router(Arc::new(router));
+
+        let mut request = register_request("acme", Some(10), None);
+        request.0.netbox_token = Some("wrong-token".to_string());
+        let refused = api.register_tenant(&admin_request(), request).await.unwrap();
+        assert!(matches!(refused, RegisterTenantResponse::BadRequest(_)));
+        assert!(!mappings.has_mapping(&"acme".to_string()));
+
+        let mut request = register_request("acme", Some(10), None);
+        request.0.netbox_token = Some("acme-token".to_string());
+        match api.register_tenant(&admin_request(), request).await.unwrap() {
+            RegisterTenantResponse::Created(Json(info)) => assert!(info.has_netbox_token),
+            _ => panic!("Expected Created response"),
+        }
+        let stored = mappings.get_mapping(&"acme".to_string()).unwrap().netbox_token.unwrap();
+        assert!(stored.starts_with("v1:"));
+        assert!(!stored.contains("acme-token"));
+    }
+
+    #[tokio::test]
+    async fn test_delete_disables_tenant_until_restored() {
+        let api = TenantsApi::new(Arc::new(TenantStore::new()));
+        api.register_tenant(&admin_request(), register_request("acme", Some(10), None))
+            .await
+            .unwrap();
+
+        let deleted = api.delete_tenant(&admin_request(), Path("acme".to_string())).await.unwrap();
+        assert!(matches!(deleted, DeleteTenantResponse::NoContent));
+        let info = match api.get_tenant(&admin_request(), Path("acme".to_string())).await.unwrap() {
+            TenantResponse::Ok(Json(info)) => info,
+            _ => panic!("Expected the disabled tenant to be kept"),
+        };
+        assert_eq!(info.disabled_by.as_deref(), Some("ops"));
+        assert!(info.disabled_at.is_some());
+        // Updating the mapping keeps the tenant disabled
+        api.update_tenant(
+            &admin_request(),
+            Path("acme".to_string()),
+            Json(UpdateTenantMappingRequest { netbox_tenant_id: 11, netbox_endpoint: None, netbox_token: None }),
+        )
+        .await
+        .unwrap();
+        assert!(api.mappings.disabled(&"acme".to_string()).is_some());
+
+        let restored = api.restore_tenant(&admin_request(), Path("acme".to_string())).await.unwrap();
+        match restored {
+            TenantResponse::Ok(Json(info)) => {
+                assert_eq!(info.netbox_tenant_id, 11);
+                assert!(info.disabled_at.is_none());
+            }
+            _ => panic!("Expected the restored tenant"),
+        }
+        assert!(matches!(
+            api.restore_tenant(&admin_request(), Path("unknown".to_string())).await.unwrap(),
+            TenantResponse::NotFound
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_purge_refused_while_tenant_has_orders() {
+        let manager = Arc::new(WorkflowManager::new());
+        let api = TenantsApi::new(Arc::new(TenantStore::new())).with_workflow_manager(manager.clone());
+        api.register_tenant(&admin_request(), register_request("acme", Some(10), None))
+            .await
+            .unwrap();
+        let order_id = manager.create_order("acme".to_string());
+        manager.update_order_state(&order_id, OrderState::Cancelled).unwrap();
+
+        // Even finished orders keep the tenant from being purged
+        let refused = api.purge_tenant(&admin_request(), Path("acme".to_string())).await.unwrap();
+        assert!(matches!(refused, DeleteTenantResponse::Conflict(_)));
+        assert!(api.mappings.has_mapping(&"acme".to_string()));
+
+        api.register_tenant(&admin_request(), register_request("initech", Some(20), None))
+            .await
+            .unwrap();
+        let purged = api.purge_tenant(&admin_request(), Path("initech".to_string())).await.unwrap();
+        assert!(matches!(purged, DeleteTenantResponse::NoContent));
+        assert!(!api.mappings.has_mapping(&"initech".to_string()));
+        let purged_again = api.purge_tenant(&admin_request(), Path("initech".to_string())).await.unwrap();
+        assert!(matches!(purged_again, DeleteTenantResponse::NotFound));
+    }
+
+    fn webhook_request(url: &str) -> Json<RegisterWebhookRequest> {
+        Json(RegisterWebhookRequest {
+            url: url.to_string(),
+            secret: "s3cret".to_string(),
+            events: vec![WebhookEvent::OrderCompleted],
+        })
+    }
+
+    #[tokio::test]
+    async fn test_register_list_and_delete_webhook() {
+        let api = TenantsApi::new(Arc::new(TenantStore::new()));
+        let req = tenant_request("tenant1");
+
+        let created = match api
+            .register_webhook(&req, Path("tenant1".to_string()), webhook_request("https://example.com/hook"))
+            .await
+            .unwrap()
+        {
+            RegisterWebhookResponse::Created(Json(info)) => info,
+            _ => panic!("Expected Created response"),
+        };
+
+        match api.list_webhooks(&req, Path("tenant1".to_string())).await.unwrap() {
+            ListWebhooksResponse::Ok(Json(webhooks)) => {
+                assert_eq!(webhooks.len(), 1);
+                assert_eq!(webhooks[0].id, created.id);
+            }
+            _ => panic!("Expected Ok response"),
+        }
+
+        let deleted = api
+            .delete_webhook(&req, Path("tenant1".to_string()), Path(created.id.clone()))
+            .await
+            .unwrap();
+        assert!(matches!(deleted, DeleteWebhookResponse::NoContent));
+        let deleted_again = api
+            .delete_webhook(&req, Path("tenant1".to_string()), Path(created.id))
+            .await
+            .unwrap();
+        assert!(matches!(deleted_again, DeleteWebhookResponse::NotFound));
+    }
+
+    #[tokio::test]
+    async fn test_rotate_and_retire_webhook_secret() {
+        let store = Arc::new(TenantStore::new());
+        let api = TenantsApi::new(store.clone());
+        let req = tenant_request("tenant1");
+        let created = match api
+            .register_webhook(&req, Path("tenant1".to_string()), webhook_request("https://example.com/hook"))
+            .await
+            .unwrap()
+        {
+            RegisterWebhookResponse::Created(Json(info)) => info,
+            _ => panic!("Expected Created response"),
+        };
+        let new_secret = || Json(RotateWebhookSecretRequest { secret: "n3w".to_string() });
+
+        match api
+            .rotate_webhook_secret(&req, Path("tenant1".to_string()), Path(created.id.clone()), new_secret())
+            .await
+            .unwrap()
+        {
+            WebhookSecretResponse::Ok(Json(info)) => assert!(info.rotating_secret),
+            _ => panic!("Expected Ok response"),
+        }
+        let webhook = &store.get_webhooks(&"tenant1".to_string())[0];
+        assert_eq!(webhook.secrets().collect::<Vec<_>>(), vec!["n3w", "s3cret"]);
+
+        match api
+            .retire_previous_webhook_secret(&req, Path("tenant1".to_string()), Path(created.id.clone()))
+            .await
+            .unwrap()
+        {
+            WebhookSecretResponse::Ok(Json(info)) => assert!(!info.rotating_secret),
+            _ => panic!("Expected Ok response"),
+        }
+        let webhook = &store.get_webhooks(&"tenant1".to_string())[0];
+        assert_eq!(webhook.secrets().collect::<Vec<_>>(), vec!["n3w"]);
+
+        let unknown = api
+            .rotate_webhook_secret(&req, Path("tenant1".to_string()), Path("missing".to_string()), new_secret())
+            .await
+            .unwrap();
+        assert!(matches!(unknown, WebhookSecretResponse::NotFound));
+        let other_tenant = api
+            .rotate_webhook_secret(&tenant_request("tenant2"), Path("tenant1".to_string()), Path(created.id), new_secret())
+            .await;
+        assert!(other_tenant.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_register_webhook_rejects_invalid_url() {
+        let api = TenantsApi::new(Arc::new(TenantStore::new()));
+        let req = tenant_request("tenant1");
+
+        let result = api
+            .register_webhook(&req, Path("tenant1".to_string()), webhook_request("ftp://example.com"))
+            .await
+            .unwrap();
+        assert!(matches!(result, RegisterWebhookResponse::BadRequest(_)));
+    }
+
+    #[tokio::test]
+    async fn test_webhooks_require_matching_tenant() {
+        let api = TenantsApi::new(Arc::new(TenantStore::new()));
+        let req = tenant_request("tenant2");
+
+        let result = api
+            .register_webhook(&req, Path("tenant1".to_string()), webhook_request("https://example.com/hook"))
+            .await;
+        assert!(result.is_err());
+    }
+}
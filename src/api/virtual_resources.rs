@@ -0,0 +1,893 @@
+use poem::Request;
+use poem_openapi::{
+    param::{Path, Query},
+    payload::Json,
+    ApiResponse, Object, OpenApi,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::api::inventory::{DeviceSummary, SiteSummary, VirtualMachineSummary};
+use crate::error::AppError;
+use crate::netbox::tenant_client::TenantAwareNetBoxClient;
+use crate::r#virtual::{
+    VirtualDevice, VirtualNetwork, VirtualResource, VirtualResourceError, VirtualResourceService,
+    VirtualResourceStatus, VirtualResourceType, VirtualSite,
+};
+use crate::security::extract_tenant_id;
+
+/// Tenant-scoped virtual sites, devices and networks, and their mappings to NetBox objects
+pub struct VirtualApi {
+    service: Arc<VirtualResourceService>,
+    netbox_client: Option<Arc<TenantAwareNetBoxClient>>,
+}
+
+impl VirtualApi {
+    pub fn new(service: Arc<VirtualResourceService>) -> Self {
+        Self {
+            service,
+            netbox_client: None,
+        }
+    }
+
+    /// Verify and hydrate physical resources through the tenant-aware client
+    pub fn with_netbox_client(mut self, netbox_client: Arc<TenantAwareNetBoxClient>) -> Self {
+        self.netbox_client = Some(netbox_client);
+        self
+    }
+
+    fn client(&self) -> Result<&TenantAwareNetBoxClient, AppError> {
+        self.netbox_client
+            .as_deref()
+            .ok_or_else(|| AppError::ServiceUnavailable("NetBox integration is not configured".to_string()))
+    }
+
+    /// The tenant's virtual resource, or None when it doesn't exist or belongs to another tenant
+    fn tenant_resource(&self, tenant_id: &str, virtual_id: &str) -> Option<VirtualResource> {
+        self.service
+            .get_virtual_resource(virtual_id)
+            .filter(|resource| resource.as_resource().tenant_id() == tenant_id)
+    }
+
+    /// Check the tenant may map `resource` to NetBox object `physical_id`
+    async fn verify_physical(&self, tenant_id: &String, resource: &VirtualResource, physical_id: i32) -> Result<(), MappingRejection> {
+        let client = self.client().map_err(MappingRejection::Unavailable)?;
+        let result = match resource.physical_type() {
+            VirtualResourceType::Device => client.get_device(tenant_id, physical_id).await.map(|_| ()),
+            VirtualResourceType::VirtualMachine => client.get_virtual_machine(tenant_id, physical_id).await.map(|_| ()),
+            _ => client.get_site(tenant_id, physical_id).await.map(|_| ()),
+        };
+        let kind = type_name(resource.physical_type());
+        result.map_err(|e| match e {
+            AppError::Unauthorized | AppError::Forbidden(_) => {
+                MappingRejection::Forbidden(format!("NetBox {} {} does not belong to the tenant", kind, physical_id))
+            }
+            AppError::NotFound(_) => MappingRejection::NotFound(format!("NetBox {} {} not found", kind, physical_id)),
+            e if e.is_unavailable() => {
+                MappingRejection::Unavailable(e)
+            }
+            e => MappingRejection::Other(e),
+        })
+    }
+
+    /// Fetch the NetBox objects behind a resource's mappings, skipping any that can't be read
+    async fn hydrate(&self, tenant_id: &String, resource: &VirtualResource, info: &mut VirtualResourceInfo) -> Result<(), AppError> {
+        let client = self.client()?;
+        match resource.physical_type() {
+            VirtualResourceType::Device => {
+                let mut devices = Vec::new();
+                for (id, device) in client.get_devices_by_ids(tenant_id, &info.physical_ids).await? {
+                    match device {
+                        Some(device) => devices.push(DeviceSummary::from(device)),
+                        None => tracing::warn!("Skipping NetBox device {} of virtual resource {}: not found", id, info.id),
+                    }
+                }
+                info.physical_devices = Some(devices);
+            }
+            VirtualResourceType::VirtualMachine => {
+                let mut vms = Vec::new();
+                for (id, vm) in client.get_virtual_machines_by_ids(tenant_id, &info.physical_ids).await? {
+                    match vm {
+                        Some(vm) => vms.push(VirtualMachineSummary::from(vm)),
+                        None => tracing::warn!("Skipping NetBox VM {} of virtual resource {}: not found", id, info.id),
+                    }
+                }
+                info.physical_virtual_machines = Some(vms);
+            }
+            _ => {
+                let mut sites = Vec::new();
+                for (id, site) in client.get_sites_by_ids(tenant_id, &info.physical_ids).await? {
+                    match site {
+                        Some(site) => sites.push(SiteSummary::from(site)),
+                        None => tracing::warn!("Skipping NetBox site {} of virtual resource {}: not found", id, info.id),
+                    }
+                }
+                info.physical_sites = Some(sites);
+            }
+        }
+        Ok(())
+    }
+
+    fn info(&self, resource: &VirtualResource) -> VirtualResourceInfo {
+        let physical_ids = self
+            .service
+            .mapping_manager()
+            .get_physical_resources(resource.as_resource().id())
+            .iter()
+            .map(|m| m.physical_id)
+            .collect();
+        VirtualResourceInfo::new(resource, physical_ids)
+    }
+
+    async fn create(
+        &self,
+        req: &Request,
+        body: CreateVirtualResourceRequest,
+        build: impl FnOnce(String, String, String) -> VirtualResource,
+    ) -> Result<CreateVirtualResourceResponse, poem::Error> {
+        let tenant_id = extract_tenant_id(req)?;
+        if body.name.trim().is_empty() {
+            return Ok(CreateVirtualResourceResponse::BadRequest(error_json("name is required")));
+        }
+
+        let mut resource = build(uuid::Uuid::new_v4().to_string(), body.name, tenant_id.clone());
+        match resource {
+            VirtualResource::Site(ref mut site) => {
+                site.description = body.description;
+                site.tags = body.tags;
+                site.metadata = body.metadata;
+            }
+            VirtualResource::Device(ref mut device) => {
+                match body.physical_type.as_deref() {
+                    None | Some("device") => {}
+                    Some("virtual_machine") => device.physical_type = VirtualResourceType::VirtualMachine,
+                    Some(other) => {
+                        return Ok(CreateVirtualResourceResponse::BadRequest(error_json(&format!(
+                            "physical_type must be device or virtual_machine, not '{}'",
+                            other
+                        ))))
+                    }
+                }
+                device.description = body.description;
+                device.tags = body.tags;
+                device.metadata = body.metadata;
+            }
+            VirtualResource::Network(ref mut network) => {
+                network.description = body.description;
+                network.tags = body.tags;
+                network.metadata = body.metadata;
+                network.cidr = body.cidr;
+            }
+        }
+
+        let mut physical_ids = body.physical_ids;
+        physical_ids.sort_unstable();
+        physical_ids.dedup();
+        for physical_id in &physical_ids {
+            match self.verify_physical(&tenant_id, &resource, *physical_id).await {
+                Ok(()) => {}
+                Err(MappingRejection::Forbidden(message)) => {
+                    return Ok(CreateVirtualResourceResponse::Forbidden(error_json(&message)))
+                }
+                Err(MappingRejection::NotFound(message)) => {
+                    return Ok(CreateVirtualResourceResponse::BadRequest(error_json(&message)))
+                }
+                Err(MappingRejection::Unavailable(e)) => {
+                    return Ok(CreateVirtualResourceResponse::ServiceUnavailable(error_json(&e.to_string())))
+                }
+                Err(MappingRejection::Other(e)) => return Err(e.into()),
+            }
+        }
+
+        match self.service.add_virtual_resource(resource.clone(), &physical_ids) {
+            Ok(()) => {}
+            Err(e @ VirtualResourceError::InvalidCidr(_)) => {
+                return Ok(CreateVirtualResourceResponse::BadRequest(error_json(&e.to_string())))
+            }
+            Err(e) => return Ok(CreateVirtualResourceResponse::Conflict(error_json(&e.to_string()))),
+        }
+        Ok(CreateVirtualResourceResponse::Created(Json(Box::new(self.info(&resource)))))
+    }
+}
+
+/// Why a physical resource can't be mapped
+enum MappingRejection {
+    Forbidden(String),
+    NotFound(String),
+    Unavailable(AppError),
+    Other(AppError),
+}
+
+fn type_name(resource_type: VirtualResourceType) -> &'static str {
+    match resource_type {
+        VirtualResourceType::Site => "site",
+        VirtualResourceType::Device => "device",
+        VirtualResourceType::Network => "network",
+        VirtualResourceType::Service => "service",
+        VirtualResourceType::VirtualMachine => "virtual_machine",
+    }
+}
+
+fn error_json(message: &str) -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "error": message }))
+}
+
+/// Request to create a virtual site, device or network
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Object)]
+pub struct CreateVirtualResourceRequest {
+    pub name: String,
+    pub description: Option<String>,
+    #[oai(default)]
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[oai(default)]
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    /// Network CIDR, e.g. `10.0.0.0/16` or `2001:db8::/48`; ignored for sites and devices
+    pub cidr: Option<String>,
+    /// NetBox objects to map to: devices or VMs for a virtual device, sites otherwise
+    #[oai(default)]
+    #[serde(default)]
+    pub physical_ids: Vec<i32>,
+    /// What a virtual device maps to: `device` (the default) or `virtual_machine`; ignored for
+    /// sites and networks
+    pub physical_type: Option<String>,
+}
+
+/// Request to map a virtual resource to a NetBox object
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Object)]
+pub struct AddMappingRequest {
+    pub physical_id: i32,
+}
+
+/// Virtual resource with its physical mappings
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Object)]
+pub struct VirtualResourceInfo {
+    pub id: String,
+    /// `site`, `device` or `network`
+    pub resource_type: String,
+    /// Type of the mapped NetBox objects: `site`, `device` or `virtual_machine`
+    pub physical_type: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub cidr: Option<String>,
+    pub tags: Vec<String>,
+    pub metadata: HashMap<String, String>,
+    /// IDs of the mapped NetBox objects
+    pub physical_ids: Vec<i32>,
+    /// Mapped NetBox sites, with `hydrate=true`
+    pub physical_sites: Option<Vec<SiteSummary>>,
+    /// Mapped NetBox devices, with `hydrate=true`
+    pub physical_devices: Option<Vec<DeviceSummary>>,
+    /// Mapped NetBox virtual machines, with `hydrate=true`
+    pub physical_virtual_machines: Option<Vec<VirtualMachineSummary>>,
+    /// Aggregate status of the mapped NetBox objects, with `status=true`
+    pub status: Option<VirtualStatusInfo>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl VirtualResourceInfo {
+    fn new(resource: &VirtualResource, physical_ids: Vec<i32>) -> Self {
+        let (description, cidr, tags, metadata, created_at, updated_at) = match resource {
+            VirtualResource::Site(site) => (&site.description, None, &site.tags, &site.metadata, site.created_at, site.updated_at),
+            VirtualResource::Device(device) => (
+                &device.description,
+                None,
+                &device.tags,
+                &device.metadata,
+                device.created_at,
+                device.updated_at,
+            ),
+            VirtualResource::Network(network) => (
+                &network.description,
+                network.cidr.clone(),
+                &network.tags,
+                &network.metadata,
+                network.created_at,
+                network.updated_at,
+            ),
+        };
+        let resource_ref = resource.as_resource();
+        Self {
+            id: resource_ref.id().to_string(),
+            resource_type: type_name(resource_ref.resource_type()).to_string(),
+            physical_type: type_name(resource.physical_type()).to_string(),
+            name: resource_ref.name().to_string(),
+            description: description.clone(),
+            cidr,
+            tags: tags.clone(),
+            metadata: metadata.clone(),
+            physical_ids,
+            physical_sites: None,
+            physical_devices: None,
+            physical_virtual_machines: None,
+            status: None,
+            created_at: created_at.to_rfc3339(),
+            updated_at: updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Aggregate status of a virtual resource
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Object)]
+pub struct VirtualStatusInfo {
+    /// `healthy`, `degraded`, `down` or `unknown`
+    pub status: String,
+    pub physical: Vec<PhysicalStatusInfo>,
+    pub computed_at: String,
+}
+
+/// Status of one mapped NetBox object
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Object)]
+pub struct PhysicalStatusInfo {
+    pub physical_id: i32,
+    /// NetBox status value, e.g. `active`
+    pub netbox_status: Option<String>,
+    /// `healthy`, `degraded`, `down` or `unknown`
+    pub health: String,
+    /// Why NetBox couldn't be checked for this object
+    pub error: Option<String>,
+}
+
+impl From<VirtualResourceStatus> for VirtualStatusInfo {
+    fn from(status: VirtualResourceStatus) -> Self {
+        Self {
+            status: status.status.as_str().to_string(),
+            physical: status
+                .physical
+                .into_iter()
+                .map(|p| PhysicalStatusInfo {
+                    physical_id: p.physical_id,
+                    netbox_status: p.netbox_status,
+                    health: p.health.as_str().to_string(),
+                    error: p.error,
+                })
+                .collect(),
+            computed_at: status.computed_at.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(ApiResponse)]
+pub enum CreateVirtualResourceResponse {
+    #[oai(status = 201)]
+    Created(Json<Box<VirtualResourceInfo>>),
+
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+
+    #[oai(status = 403)]
+    Forbidden(Json<serde_json::Value>),
+
+    #[oai(status = 409)]
+    Conflict(Json<serde_json::Value>),
+
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+pub enum ListVirtualResourcesResponse {
+    #[oai(status = 200)]
+    Ok(Json<Vec<VirtualResourceInfo>>),
+}
+
+#[derive(ApiResponse)]
+pub enum GetVirtualResourceResponse {
+    #[oai(status = 200)]
+    Ok(Json<Box<VirtualResourceInfo>>),
+
+    #[oai(status = 404)]
+    NotFound,
+
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+pub enum AddMappingResponse {
+    #[oai(status = 200)]
+    Ok(Json<Box<VirtualResourceInfo>>),
+
+    #[oai(status = 400)]
+    BadRequest(Json<serde_json::Value>),
+
+    #[oai(status = 403)]
+    Forbidden(Json<serde_json::Value>),
+
+    #[oai(status = 404)]
+    NotFound,
+
+    #[oai(status = 409)]
+    Conflict(Json<serde_json::Value>),
+
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<serde_json::Value>),
+}
+
+#[derive(ApiResponse)]
+pub enum DeleteVirtualResponse {
+    #[oai(status = 204)]
+    NoContent,
+
+    #[oai(status = 404)]
+    NotFound,
+}
+
+#[OpenApi]
+impl VirtualApi {
+    /// Create a virtual site, optionally mapped to the tenant's NetBox sites
+    #[oai(path = "/virtual/sites", method = "post")]
+    async fn create_virtual_site(
+        &self,
+        req: &Request,
+        body: Json<CreateVirtualResourceRequest>,
+    ) -> Result<CreateVirtualResourceResponse, poem::Error> {
+        self.create(req, body.0, |id, name, tenant_id| {
+            VirtualResource::Site(VirtualSite::new(id, name, tenant_id))
+        })
+        .await
+    }
+
+    /// Create a virtual device, optionally mapped to the tenant's NetBox devices
+    #[oai(path = "/virtual/devices", method = "post")]
+    async fn create_virtual_device(
+        &self,
+        req: &Request,
+        body: Json<CreateVirtualResourceRequest>,
+    ) -> Result<CreateVirtualResourceResponse, poem::Error> {
+        self.create(req, body.0, |id, name, tenant_id| {
+            VirtualResource::Device(VirtualDevice::new(id, name, tenant_id))
+        })
+        .await
+    }
+
+    /// Create a virtual network, optionally mapped to the tenant's NetBox sites it spans
+    #[oai(path = "/virtual/networks", method = "post")]
+    async fn create_virtual_network(
+        &self,
+        req: &Request,
+        body: Json<CreateVirtualResourceRequest>,
+    ) -> Result<CreateVirtualResourceResponse, poem::Error> {
+        self.create(req, body.0, |id, name, tenant_id| {
+            VirtualResource::Network(VirtualNetwork::new(id, name, tenant_id))
+        })
+        .await
+    }
+
+    /// List the tenant's virtual resources
+    #[oai(path = "/virtual", method = "get")]
+    async fn list_virtual_resources(&self, req: &Request) -> Result<ListVirtualResourcesResponse, poem::Error> {
+        let tenant_id = extract_tenant_id(req)?;
+
+        let mut resources: Vec<VirtualResourceInfo> = self
+            .service
+            .get_tenant_virtual_resources(&tenant_id)
+            .iter()
+            .map(|resource| self.info(resource))
+            .collect();
+        resources.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        Ok(ListVirtualResourcesResponse::Ok(Json(resources)))
+    }
+
+    /// Get a virtual resource with its physical mappings
+    ///
+    /// With `hydrate=true` the mapped NetBox objects are fetched too, and with
+    /// `status=true` their aggregate status (briefly cached).
+    #[oai(path = "/virtual/:virtual_id", method = "get")]
+    async fn get_virtual_resource(
+        &self,
+        req: &Request,
+        virtual_id: Path<String>,
+        hydrate: Query<Option<bool>>,
+        status: Query<Option<bool>>,
+    ) -> Result<GetVirtualResourceResponse, poem::Error> {
+        let tenant_id = extract_tenant_id(req)?;
+        let Some(resource) = self.tenant_resource(&tenant_id, &virtual_id.0) else {
+            return Ok(GetVirtualResourceResponse::NotFound);
+        };
+
+        let mut info = self.info(&resource);
+        if hydrate.0.unwrap_or(false) {
+            match self.hydrate(&tenant_id, &resource, &mut info).await {
+                Ok(()) => {}
+                Err(e) if e.is_unavailable() => {
+                    return Ok(GetVirtualResourceResponse::ServiceUnavailable(error_json(&e.to_string())))
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        if status.0.unwrap_or(false) {
+            let client = match self.client() {
+                Ok(client) => client,
+                Err(e) => return Ok(GetVirtualResourceResponse::ServiceUnavailable(error_json(&e.to_string()))),
+            };
+            info.status = Some(self.service.compute_status(&resource, client).await.into());
+        }
+        Ok(GetVirtualResourceResponse::Ok(Json(Box::new(info))))
+    }
+
+    /// Map a virtual resource to one of the tenant's NetBox objects
+    #[oai(path = "/virtual/:virtual_id/mappings", method = "post")]
+    async fn add_mapping(
+        &self,
+        req: &Request,
+        virtual_id: Path<String>,
+        body: Json<AddMappingRequest>,
+    ) -> Result<AddMappingResponse, poem::Error> {
+        let tenant_id = extract_tenant_id(req)?;
+        let Some(resource) = self.tenant_resource(&tenant_id, &virtual_id.0) else {
+            return Ok(AddMappingResponse::NotFound);
+        };
+
+        let physical_id = body.0.physical_id;
+        if self.info(&resource).physical_ids.contains(&physical_id) {
+            return Ok(AddMappingResponse::Conflict(error_json(&format!(
+                "Already mapped to {}",
+                physical_id
+            ))));
+        }
+
+        match self.verify_physical(&tenant_id, &resource, physical_id).await {
+            Ok(()) => {}
+            Err(MappingRejection::Forbidden(message)) => return Ok(AddMappingResponse::Forbidden(error_json(&message))),
+            Err(MappingRejection::NotFound(message)) => return Ok(AddMappingResponse::BadRequest(error_json(&message))),
+            Err(MappingRejection::Unavailable(e)) => {
+                return Ok(AddMappingResponse::ServiceUnavailable(error_json(&e.to_string())))
+            }
+            Err(MappingRejection::Other(e)) => return Err(e.into()),
+        }
+
+        if let Err(e) = self.service.map_virtual_to_physical(&resource, physical_id) {
+            return Ok(AddMappingResponse::Conflict(error_json(&e.to_string())));
+        }
+        Ok(AddMappingResponse::Ok(Json(Box::new(self.info(&resource)))))
+    }
+
+    /// Remove a mapping from a virtual resource
+    #[oai(path = "/virtual/:virtual_id/mappings/:physical_id", method = "delete")]
+    async fn remove_mapping(
+        &self,
+        req: &Request,
+        virtual_id: Path<String>,
+        physical_id: Path<i32>,
+    ) -> Result<DeleteVirtualResponse, poem::Error> {
+        let tenant_id = extract_tenant_id(req)?;
+        if self.tenant_resource(&tenant_id, &virtual_id.0).is_none()
+            || !self.service.unmap_virtual_from_physical(&virtual_id.0, physical_id.0)
+        {
+            return Ok(DeleteVirtualResponse::NotFound);
+        }
+        Ok(DeleteVirtualResponse::NoContent)
+    }
+
+    /// Delete a virtual resource and all its mappings
+    #[oai(path = "/virtual/:virtual_id", method = "delete")]
+    async fn delete_virtual_resource(
+        &self,
+        req: &Request,
+        virtual_id: Path<String>,
+    ) -> Result<DeleteVirtualResponse, poem::Error> {
+        let tenant_id = extract_tenant_id(req)?;
+        if self.tenant_resource(&tenant_id, &virtual_id.0).is_none()
+            || self.service.delete_virtual_resource(&virtual_id.0).is_err()
+        {
+            return Ok(DeleteVirtualResponse::NotFound);
+        }
+        Ok(DeleteVirtualResponse::NoContent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::netbox::NetBoxClient;
+    use crate::security::tenant::{TenantAccessControl, TenantMappingService};
+    use serde_json::json;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn create_api(mock_server: &MockServer) -> VirtualApi {
+        let config = Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let client = Arc::new(NetBoxClient::new(config).unwrap());
+        let mappings = HashMap::from([("tenant-1".to_string(), 10), ("tenant-2".to_string(), 20)]);
+        let access_control = Arc::new(TenantAccessControl::new(TenantMappingService::from(mappings)));
+        VirtualApi::new(Arc::new(VirtualResourceService::new()))
+            .with_netbox_client(Arc::new(TenantAwareNetBoxClient::new(client, access_control)))
+    }
+
+    fn tenant_request(tenant_id: &str) -> Request {
+        Request::builder().header("X-Tenant-Id", tenant_id).finish()
+    }
+
+    fn create_request(name: &str, physical_ids: Vec<i32>) -> Json<CreateVirtualResourceRequest> {
+        Json(CreateVirtualResourceRequest {
+            name: name.to_string(),
+            description: None,
+            tags: vec![],
+            metadata: HashMap::new(),
+            cidr: None,
+            physical_ids,
+            physical_type: None,
+        })
+    }
+
+    async fn mount_site(mock_server: &MockServer, id: i32, netbox_tenant: i32) {
+        let site = json!({
+            "id": id,
+            "name": format!("Site {}", id),
+            "slug": format!("site-{}", id),
+            "tenant": netbox_tenant
+        });
+        Mock::given(method("GET"))
+            .and(path(format!("/api/dcim/sites/{}/", id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&site))
+            .mount(mock_server)
+            .await;
+        // Hydration fetches mapped sites in one batch
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .and(query_param("id__in", id.to_string()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"count": 1, "results": [site]})))
+            .mount(mock_server)
+            .await;
+    }
+
+    async fn create_site(api: &VirtualApi, tenant_id: &str, physical_ids: Vec<i32>) -> VirtualResourceInfo {
+        match api
+            .create_virtual_site(&tenant_request(tenant_id), create_request("Campus", physical_ids))
+            .await
+            .unwrap()
+        {
+            CreateVirtualResourceResponse::Created(Json(info)) => *info,
+            _ => panic!("Expected Created response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_and_get_virtual_site_with_hydrated_mappings() {
+        let mock_server = MockServer::start().await;
+        mount_site(&mock_server, 1, 10).await;
+        let api = create_api(&mock_server);
+
+        let created = create_site(&api, "tenant-1", vec![1]).await;
+        assert_eq!(created.resource_type, "site");
+        assert_eq!(created.physical_ids, vec![1]);
+
+        let response = api
+            .get_virtual_resource(&tenant_request("tenant-1"), Path(created.id.clone()), Query(Some(true)), Query(None))
+            .await
+            .unwrap();
+        let GetVirtualResourceResponse::Ok(Json(info)) = response else {
+            panic!("Expected Ok response");
+        };
+        let sites = info.physical_sites.unwrap();
+        assert_eq!(sites.len(), 1);
+        assert_eq!(sites[0].name, "Site 1");
+
+        match api.list_virtual_resources(&tenant_request("tenant-1")).await.unwrap() {
+            ListVirtualResourcesResponse::Ok(Json(resources)) => assert_eq!(resources.len(), 1),
+        }
+        match api.list_virtual_resources(&tenant_request("tenant-2")).await.unwrap() {
+            ListVirtualResourcesResponse::Ok(Json(resources)) => assert!(resources.is_empty()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_virtual_device_backed_by_virtual_machines() {
+        let mock_server = MockServer::start().await;
+        for (id, tenant) in [(11, 10), (12, 20)] {
+            Mock::given(method("GET"))
+                .and(path(format!("/api/virtualization/virtual-machines/{}/", id)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "id": id, "name": format!("app{}", id), "cluster": 3, "tenant": tenant, "status": "active"
+                })))
+                .mount(&mock_server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path("/api/virtualization/virtual-machines/"))
+            .and(query_param("id__in", "11"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"count": 1, "results": [
+                {"id": 11, "name": "app11", "cluster": 3, "tenant": 10, "status": "active"}
+            ]})))
+            .mount(&mock_server)
+            .await;
+        let api = create_api(&mock_server);
+        let request = |physical_ids: Vec<i32>, physical_type: &str| {
+            let mut request = create_request("App", physical_ids);
+            request.physical_type = Some(physical_type.to_string());
+            request
+        };
+
+        let response = api
+            .create_virtual_device(&tenant_request("tenant-1"), request(vec![11], "rack"))
+            .await
+            .unwrap();
+        assert!(matches!(response, CreateVirtualResourceResponse::BadRequest(_)));
+        let response = api
+            .create_virtual_device(&tenant_request("tenant-1"), request(vec![12], "virtual_machine"))
+            .await
+            .unwrap();
+        assert!(matches!(response, CreateVirtualResourceResponse::Forbidden(_)));
+
+        let response = api
+            .create_virtual_device(&tenant_request("tenant-1"), request(vec![11], "virtual_machine"))
+            .await
+            .unwrap();
+        let CreateVirtualResourceResponse::Created(Json(created)) = response else {
+            panic!("Expected Created response");
+        };
+        assert_eq!(created.resource_type, "device");
+        assert_eq!(created.physical_type, "virtual_machine");
+
+        let response = api
+            .get_virtual_resource(&tenant_request("tenant-1"), Path(created.id.clone()), Query(Some(true)), Query(None))
+            .await
+            .unwrap();
+        let GetVirtualResourceResponse::Ok(Json(info)) = response else {
+            panic!("Expected Ok response");
+        };
+        let vms = info.physical_virtual_machines.unwrap();
+        assert_eq!(vms.len(), 1);
+        assert_eq!(vms[0].name, "app11");
+        assert!(info.physical_devices.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_create_rejects_another_tenants_site() {
+        let mock_server = MockServer::start().await;
+        mount_site(&mock_server, 2, 20).await;
+        let api = create_api(&mock_server);
+
+        let response = api
+            .create_virtual_site(&tenant_request("tenant-1"), create_request("Campus", vec![2]))
+            .await
+            .unwrap();
+        assert!(matches!(response, CreateVirtualResourceResponse::Forbidden(_)));
+        match api.list_virtual_resources(&tenant_request("tenant-1")).await.unwrap() {
+            ListVirtualResourcesResponse::Ok(Json(resources)) => assert!(resources.is_empty()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_add_mapping_rejects_another_tenants_site() {
+        let mock_server = MockServer::start().await;
+        mount_site(&mock_server, 1, 10).await;
+        mount_site(&mock_server, 2, 20).await;
+        let api = create_api(&mock_server);
+        let created = create_site(&api, "tenant-1", vec![]).await;
+
+        let rejected = api
+            .add_mapping(
+                &tenant_request("tenant-1"),
+                Path(created.id.clone()),
+                Json(AddMappingRequest { physical_id: 2 }),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(rejected, AddMappingResponse::Forbidden(_)));
+
+        let added = api
+            .add_mapping(
+                &tenant_request("tenant-1"),
+                Path(created.id.clone()),
+                Json(AddMappingRequest { physical_id: 1 }),
+            )
+            .await
+            .unwrap();
+        let AddMappingResponse::Ok(Json(info)) = added else {
+            panic!("Expected Ok response");
+        };
+        assert_eq!(info.physical_ids, vec![1]);
+
+        let duplicate = api
+            .add_mapping(
+                &tenant_request("tenant-1"),
+                Path(created.id),
+                Json(AddMappingRequest { physical_id: 1 }),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(duplicate, AddMappingResponse::Conflict(_)));
+    }
+
+    #[tokio::test]
+    async fn test_other_tenants_cannot_see_or_change_virtual_resource() {
+        let mock_server = MockServer::start().await;
+        mount_site(&mock_server, 2, 20).await;
+        let api = create_api(&mock_server);
+        let created = create_site(&api, "tenant-1", vec![]).await;
+        let intruder = tenant_request("tenant-2");
+
+        let get = api
+            .get_virtual_resource(&intruder, Path(created.id.clone()), Query(None), Query(None))
+            .await
+            .unwrap();
+        assert!(matches!(get, GetVirtualResourceResponse::NotFound));
+        let map = api
+            .add_mapping(&intruder, Path(created.id.clone()), Json(AddMappingRequest { physical_id: 2 }))
+            .await
+            .unwrap();
+        assert!(matches!(map, AddMappingResponse::NotFound));
+        let delete = api.delete_virtual_resource(&intruder, Path(created.id.clone())).await.unwrap();
+        assert!(matches!(delete, DeleteVirtualResponse::NotFound));
+
+        let delete = api
+            .delete_virtual_resource(&tenant_request("tenant-1"), Path(created.id))
+            .await
+            .unwrap();
+        assert!(matches!(delete, DeleteVirtualResponse::NoContent));
+    }
+
+    #[tokio::test]
+    async fn test_get_virtual_resource_with_status() {
+        let mock_server = MockServer::start().await;
+        mount_site(&mock_server, 1, 10).await;
+        let api = create_api(&mock_server);
+        let created = create_site(&api, "tenant-1", vec![1]).await;
+
+        let response = api
+            .get_virtual_resource(&tenant_request("tenant-1"), Path(created.id), Query(None), Query(Some(true)))
+            .await
+            .unwrap();
+        let GetVirtualResourceResponse::Ok(Json(info)) = response else {
+            panic!("Expected Ok response");
+        };
+        // The mocked site has no status set
+        let status = info.status.unwrap();
+        assert_eq!(status.status, "unknown");
+        assert_eq!(status.physical.len(), 1);
+        assert_eq!(status.physical[0].physical_id, 1);
+        assert!(info.physical_sites.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_create_virtual_network_validates_cidr() {
+        // No physical mappings, so no NetBox client is needed
+        let api = VirtualApi::new(Arc::new(VirtualResourceService::new().with_cidr_overlap_rejection(true)));
+        let req = tenant_request("tenant-1");
+        let network = |cidr: &str| {
+            let mut body = create_request("Backbone", vec![]);
+            body.0.cidr = Some(cidr.to_string());
+            body
+        };
+
+        let created = api.create_virtual_network(&req, network("10.0.0.0/16")).await.unwrap();
+        let CreateVirtualResourceResponse::Created(Json(info)) = created else {
+            panic!("Expected Created response");
+        };
+        assert_eq!(info.cidr.as_deref(), Some("10.0.0.0/16"));
+
+        let invalid = api.create_virtual_network(&req, network("10.1.0.1/16")).await.unwrap();
+        assert!(matches!(invalid, CreateVirtualResourceResponse::BadRequest(_)));
+        let overlapping = api.create_virtual_network(&req, network("10.0.4.0/24")).await.unwrap();
+        assert!(matches!(overlapping, CreateVirtualResourceResponse::Conflict(_)));
+        // Other tenants' address space is separate
+        let other_tenant = api
+            .create_virtual_network(&tenant_request("tenant-2"), network("10.0.4.0/24"))
+            .await
+            .unwrap();
+        assert!(matches!(other_tenant, CreateVirtualResourceResponse::Created(_)));
+    }
+
+    #[tokio::test]
+    async fn test_remove_mapping() {
+        let mock_server = MockServer::start().await;
+        mount_site(&mock_server, 1, 10).await;
+        let api = create_api(&mock_server);
+        let created = create_site(&api, "tenant-1", vec![1]).await;
+        let req = tenant_request("tenant-1");
+
+        let removed = api.remove_mapping(&req, Path(created.id.clone()), Path(1)).await.unwrap();
+        assert!(matches!(removed, DeleteVirtualResponse::NoContent));
+        let removed_again = api.remove_mapping(&req, Path(created.id), Path(1)).await.unwrap();
+        assert!(matches!(removed_again, DeleteVirtualResponse::NotFound));
+    }
+}
@@ -0,0 +1,843 @@
+use std::sync::Arc;
+
+use poem_openapi::{ExtraHeader, OpenApiService};
+
+use crate::api::{
+    AdminApi, CatalogApi, HealthApi, InventoryApi, MetricsApi, NetBoxWebhooksApi, OrdersApi, TenantsApi, VirtualApi,
+};
+use crate::business::catalog::DeviceCatalog;
+use crate::business::enrichment::EnrichmentConfig;
+use crate::business::import::InventoryImporter;
+use crate::business::naming::{DeviceNamer, NameSequences, NamingPolicy};
+use crate::business::onboarding::TenantOnboardingService;
+use crate::business::site_report::SiteReportService;
+use crate::business::{
+    DeviceOrderProcessor, ExtensibleOrderService, ExtensibleOrderServiceBuilder, InMemoryOrderClaims,
+    IpAllocationOrderProcessor, JsonFileWorkflowStore, OrderService, OrderValidator, VirtualMachineOrderProcessor, VlanOrderProcessor,
+    WebhookNotifier, WorkflowManager, WorkflowStore, WorkflowStoreError,
+};
+use crate::config::Config;
+#[cfg(feature = "postgres")]
+use crate::database::{Database, PostgresTenantMappingStore, PostgresWorkflowStore};
+use crate::domain::tenant::TenantStore;
+use crate::error::AppError;
+use crate::netbox::cached_client::CachedNetBoxClient;
+use crate::netbox::tenant_client::TenantAwareNetBoxClient;
+use crate::netbox::{NetBoxClient, NetBoxEndpoint, NetBoxRouter, ResilientNetBoxClient, TokenScope};
+use crate::r#virtual::VirtualResourceService;
+use crate::resilience::ChaosInjector;
+use crate::security::tenant::{
+    JsonFileTenantMappingStore, TenantAccessControl, TenantMappingService, TenantMappingStore, DEFAULT_NETBOX_ENDPOINT,
+};
+use crate::security::TokenCipher;
+use crate::shutdown;
+use crate::sync::{ReadModel, SyncService};
+use crate::warmup::{HotSites, ReadinessGate, Warmup};
+
+/// How often the hot site list is written to WARMUP_HOT_SITES_FILE
+const HOT_SITES_SAVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// How often workflows are written to the workflow store, bounding what a crash loses
+const WORKFLOW_SAVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// All NetGate APIs, in the order they appear in the OpenAPI document
+pub type NetGateApis = (
+    HealthApi,
+    MetricsApi,
+    OrdersApi,
+    TenantsApi,
+    InventoryApi,
+    CatalogApi,
+    VirtualApi,
+    AdminApi,
+    NetBoxWebhooksApi,
+);
+
+/// Everything that talks to NetBox, present only when NetBox is configured
+///
+/// Layered raw -> resilient -> cached -> tenant-aware. `client` and
+/// `cached_client` belong to the default endpoint; the tenant-aware client and
+/// the order service pick each tenant's endpoint through `router`.
+pub struct NetBoxStack {
+    pub client: Arc<ResilientNetBoxClient>,
+    pub cached_client: Arc<CachedNetBoxClient>,
+    pub router: Arc<NetBoxRouter>,
+    pub tenant_client: Arc<TenantAwareNetBoxClient>,
+    pub order_service: Arc<OrderService>,
+    pub extensible_service: Arc<ExtensibleOrderService>,
+    /// The default endpoint's device types and roles
+    pub catalog: Arc<DeviceCatalog>,
+    /// Fault injection into every endpoint's calls, when enabled
+    pub chaos: Option<Arc<ChaosInjector>>,
+    pub onboarding: Arc<TenantOnboardingService>,
+    /// Mirrors tenants' sites and devices into a local read model, when enabled
+    pub sync: Option<Arc<SyncService>>,
+}
+
+/// Shared application state, built once at startup by [`bootstrap`]
+pub struct AppState {
+    pub config: Config,
+    /// None when NETBOX_TOKEN is unset or the client can't be created
+    pub netbox: Option<NetBoxStack>,
+    pub workflow_manager: Arc<WorkflowManager>,
+    /// Where workflows are kept across restarts; None keeps them in memory only
+    pub workflow_store: Option<Arc<dyn WorkflowStore>>,
+    /// Connections to DATABASE_URL, when set
+    #[cfg(feature = "postgres")]
+    pub database: Option<Database>,
+    pub tenant_store: Arc<TenantStore>,
+    /// Delivers tenant webhooks for the workflow manager's events
+    pub webhook_notifier: Arc<WebhookNotifier>,
+    pub tenant_mappings: Arc<TenantMappingService>,
+    pub virtual_service: Arc<VirtualResourceService>,
+    /// Sites tenants read recently, prefetched by warm-up
+    pub hot_sites: Arc<HotSites>,
+    /// Closed while warm-up runs, see [`crate::warmup`]
+    pub readiness: Arc<ReadinessGate>,
+}
+
+/// Build the application state from the configuration
+///
+/// NetBox is optional: without it the server still starts and order
+/// endpoints answer 503. A database that can't be reached or migrated, a
+/// tenant mapping store that can't be read, a tenant mapped to a NetBox
+/// endpoint that isn't configured, or an invalid enrichment config or device
+/// naming policy stops startup.
+pub async fn bootstrap(mut config: Config) -> Result<AppState, AppError> {
+    if let Some(ref path) = config.enrichment_config_file {
+        config.enrichment = EnrichmentConfig::from_file(path).map_err(AppError::Internal)?;
+    }
+    config
+        .enrichment
+        .validate()
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Invalid enrichment config: {}", e)))?;
+    if let Some(ref path) = config.device_naming_file {
+        config.device_naming = NamingPolicy::from_file(path).map_err(AppError::Internal)?;
+    }
+
+    let tenant_store = Arc::new(TenantStore::new());
+
+    #[cfg(feature = "postgres")]
+    let database = match config.database.url {
+        Some(ref url) => Some(
+            Database::connect(url, config.database.pool_size)
+                .await
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("{}", e)))?,
+        ),
+        None => None,
+    };
+    #[cfg(not(feature = "postgres"))]
+    if config.database.url.is_some() {
+        return Err(AppError::Internal(anyhow::anyhow!(
+            "DATABASE_URL is set but NetGate was built without the postgres feature"
+        )));
+    }
+
+    // Stored workflows are read back at startup, see `AppState::restore_workflows`
+    let workflow_manager = Arc::new(WorkflowManager::new());
+    let workflow_store = config
+        .workflow_store_file
+        .clone()
+        .map(|path| Arc::new(JsonFileWorkflowStore::new(path)) as Arc<dyn WorkflowStore>);
+    let mapping_store = config
+        .tenant_mappings_file
+        .clone()
+        .map(|path| Arc::new(JsonFileTenantMappingStore::new(path)) as Arc<dyn TenantMappingStore>);
+    // The database replaces both files
+    #[cfg(feature = "postgres")]
+    let (workflow_store, mapping_store) = match database {
+        Some(ref database) => {
+            if workflow_store.is_some() || mapping_store.is_some() {
+                tracing::warn!("WORKFLOW_STORE_FILE and TENANT_MAPPINGS_FILE are ignored: DATABASE_URL is set");
+            }
+            (
+                Some(Arc::new(PostgresWorkflowStore::new(database)) as Arc<dyn WorkflowStore>),
+                Some(Arc::new(PostgresTenantMappingStore::new(database)) as Arc<dyn TenantMappingStore>),
+            )
+        }
+        None => (workflow_store, mapping_store),
+    };
+
+    // Tenant mappings from the store, seeded with TENANT_MAPPINGS for tenants it lacks
+    let tenant_mappings = match mapping_store {
+        Some(store) => TenantMappingService::load(store)
+            .await
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to load tenant mappings: {}", e)))?,
+        None => TenantMappingService::new(),
+    }
+    .with_endpoints(config.netbox_endpoints.iter().map(|endpoint| endpoint.name.clone()));
+    let tenant_mappings = Arc::new(tenant_mappings);
+    for (tenant_id, mapping) in &config.tenant_mappings {
+        if !tenant_mappings.has_mapping(tenant_id) {
+            tenant_mappings.insert_mapping(tenant_id.clone(), mapping.clone()).await.map_err(|e| {
+                AppError::Internal(anyhow::anyhow!("Failed to register tenant mapping of '{}': {}", tenant_id, e))
+            })?;
+        }
+    }
+    tenant_mappings
+        .validate_endpoints()
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Invalid tenant mappings: {}", e)))?;
+    if config.tenant_token_key.is_none() && tenant_mappings.has_tenant_tokens() {
+        return Err(AppError::Internal(anyhow::anyhow!(
+            "Tenant mappings hold NetBox tokens but TENANT_TOKEN_KEY is not set"
+        )));
+    }
+
+    let virtual_service = Arc::new(
+        VirtualResourceService::new().with_cidr_overlap_rejection(config.reject_overlapping_virtual_networks),
+    );
+
+    // A hot site list that can't be read only makes warm-up colder
+    let per_tenant = config.warmup.sites_per_tenant;
+    let hot_sites = match config.warmup.hot_sites_file {
+        Some(ref path) => HotSites::load(path, per_tenant).unwrap_or_else(|e| {
+            tracing::warn!("Failed to load hot sites from {}: {}", path.display(), e);
+            HotSites::new(per_tenant)
+        }),
+        None => HotSites::new(per_tenant),
+    };
+    let hot_sites = Arc::new(hot_sites);
+
+    let netbox = match netbox_client(&config) {
+        Some(client) => Some(build_netbox_stack(
+            &config,
+            client,
+            &workflow_manager,
+            &tenant_mappings,
+            &virtual_service,
+            &hot_sites,
+        )?),
+        None => {
+            if !config.netbox_endpoints.is_empty() {
+                tracing::warn!("NETBOX_ENDPOINTS ignored: the default NetBox endpoint is not configured");
+            }
+            None
+        }
+    };
+
+    let readiness = if config.warmup.enabled && netbox.is_some() {
+        ReadinessGate::warming()
+    } else {
+        ReadinessGate::ready()
+    };
+
+    Ok(AppState {
+        config,
+        netbox,
+        workflow_manager,
+        workflow_store,
+        #[cfg(feature = "postgres")]
+        database,
+        webhook_notifier: Arc::new(WebhookNotifier::new(tenant_store.clone())),
+        tenant_store,
+        tenant_mappings,
+        virtual_service,
+        hot_sites,
+        readiness: Arc::new(readiness),
+    })
+}
+
+/// Raw NetBox client, or None (with a warning) when NetBox isn't usable
+fn netbox_client(config: &Config) -> Option<Arc<NetBoxClient>> {
+    if config.netbox_token.is_empty() {
+        tracing::warn!("NETBOX_TOKEN not set - NetBox features will be unavailable. Set NETBOX_TOKEN to enable NetBox integration.");
+        return None;
+    }
+    match NetBoxClient::new(config.clone()) {
+        Ok(client) => {
+            tracing::info!("NetBox client initialized successfully");
+            Some(Arc::new(client))
+        }
+        Err(e) => {
+            tracing::warn!("Failed to create NetBox client: {}. Server will run without NetBox integration.", e);
+            None
+        }
+    }
+}
+
+fn build_netbox_stack(
+    config: &Config,
+    client: Arc<NetBoxClient>,
+    workflow_manager: &Arc<WorkflowManager>,
+    tenant_mappings: &Arc<TenantMappingService>,
+    virtual_service: &Arc<VirtualResourceService>,
+    hot_sites: &Arc<HotSites>,
+) -> Result<NetBoxStack, AppError> {
+    let chaos = ChaosInjector::from_config(config);
+    let mut router = NetBoxRouter::new(
+        NetBoxEndpoint::build(DEFAULT_NETBOX_ENDPOINT, config, client, chaos.clone()),
+        tenant_mappings.clone(),
+    );
+    for endpoint in &config.netbox_endpoints {
+        let endpoint_config = config.for_netbox_endpoint(endpoint);
+        let client = NetBoxClient::new(endpoint_config.clone()).map_err(|e| {
+            AppError::Internal(anyhow::anyhow!("Failed to create NetBox client for endpoint '{}': {}", endpoint.name, e))
+        })?;
+        tracing::info!("NetBox endpoint '{}' initialized at {}", endpoint.name, endpoint.url);
+        router = router.with_endpoint(NetBoxEndpoint::build(
+            &endpoint.name,
+            &endpoint_config,
+            Arc::new(client),
+            chaos.clone(),
+        ));
+    }
+    if let Some(ref key) = config.tenant_token_key {
+        router = router.with_token_cipher(TokenCipher::new(key));
+    }
+    let router = Arc::new(router);
+    let client = router.default_endpoint().client.clone();
+    let cached_client = router.default_endpoint().cached_client.clone();
+
+    let tenant_client = Arc::new(
+        TenantAwareNetBoxClient::new(
+            cached_client.clone(),
+            Arc::new(TenantAccessControl::from_shared(tenant_mappings.clone())),
+        )
+        .with_router(router.clone())
+        .with_hot_sites(hot_sites.clone()),
+    );
+
+    let mut order_service = OrderService::new(workflow_manager.clone(), client.clone())
+        .with_router(router.clone())
+        .with_validator(
+            OrderValidator::new()
+                .with_allowed_custom_fields(config.allowed_custom_fields.clone())
+                .with_status_transitions(config.site_status_transitions.clone())
+                .with_address_normalizer(config.address_normalizer.clone()),
+        )
+        .with_site_name_check(config.site_name_check)
+        .with_custom_field_schema_check(config.custom_field_schema_check)
+        .with_processing_deadlines(config.processing_deadlines.clone())
+        .with_access_control(Arc::new(TenantAccessControl::from_shared(tenant_mappings.clone())));
+    if let Some(policy) = config.missing_tag_policy {
+        order_service = order_service.with_missing_tag_policy(policy);
+    }
+    if let Some(ref windows) = config.maintenance {
+        order_service = order_service
+            .with_maintenance_windows(Arc::new(windows.clone()))
+            .with_order_queue(config.order_queue.clone())
+            .with_order_claims(Arc::new(InMemoryOrderClaims::new()), config.order_claims.clone());
+    }
+    if !config.approval.is_empty() {
+        order_service = order_service.with_approval_policy(Arc::new(config.approval.clone()));
+    }
+    order_service = order_service.with_enrichment_config(config.enrichment.clone());
+    if !config.device_naming.is_empty() {
+        let sequences = match config.device_name_sequences_file {
+            Some(ref path) => NameSequences::load(path).map_err(|e| {
+                AppError::Internal(anyhow::anyhow!("Failed to load device name sequences from {}: {}", path.display(), e))
+            })?,
+            None => NameSequences::new(),
+        };
+        let max_length = DeviceOrderProcessor::MAX_NAME_LENGTH;
+        order_service = order_service
+            .with_device_namer(Arc::new(DeviceNamer::new(config.device_naming.clone(), sequences, max_length)));
+    }
+    // Shared with onboarding, which sets new tenants' profiles
+    order_service = order_service.with_transformation_profiles(Arc::new(config.transformation_profiles.clone()));
+    let onboarding = TenantOnboardingService::new(router.clone(), tenant_mappings.clone(), config.transformation_profiles.clone())
+        .with_templates(config.onboarding_templates.clone())
+        .with_virtual_service(virtual_service.clone());
+
+    // A read model file that can't be read only means the first sync starts from scratch
+    let sync = config.sync.enabled.then(|| {
+        let model = match config.sync.store_file {
+            Some(ref path) => ReadModel::load(path).unwrap_or_else(|e| {
+                tracing::warn!("Failed to load the sync read model from {}: {}", path.display(), e);
+                ReadModel::new()
+            }),
+            None => ReadModel::new(),
+        };
+        Arc::new(
+            SyncService::new(tenant_client.clone(), tenant_mappings.clone(), Arc::new(model))
+                .with_full_every(config.sync.full_every),
+        )
+    });
+
+    // Device orders may name device types and roles, resolved through the catalog
+    let catalog = Arc::new(DeviceCatalog::new(client.clone()));
+    // VLAN orders are held to each tenant's VID ranges and assigned to its NetBox tenant
+    let access_control = Arc::new(TenantAccessControl::from_shared(tenant_mappings.clone()));
+    let vlan_processor = VlanOrderProcessor::new()
+        .with_ranges(config.vlan_ranges.clone())
+        .with_access_control(access_control.clone());
+    // Addresses may only come from the tenant's own prefixes
+    let ip_processor = IpAllocationOrderProcessor::new().with_access_control(access_control.clone());
+    // VMs are assigned to the tenant's NetBox tenant and kept out of other tenants' clusters
+    let vm_processor = VirtualMachineOrderProcessor::new().with_access_control(access_control);
+    let extensible_service = ExtensibleOrderServiceBuilder::new()
+        .with_default_processors()
+        .with_processor(Arc::new(DeviceOrderProcessor::new().with_catalog(catalog.clone())))
+        .with_processor(Arc::new(vlan_processor))
+        .with_processor(Arc::new(ip_processor))
+        .with_processor(Arc::new(vm_processor))
+        .build(workflow_manager.clone(), client.clone());
+
+    Ok(NetBoxStack {
+        client,
+        cached_client,
+        router,
+        tenant_client,
+        order_service: Arc::new(order_service),
+        extensible_service: Arc::new(extensible_service),
+        catalog,
+        chaos,
+        onboarding: Arc::new(onboarding),
+        sync,
+    })
+}
+
+impl AppState {
+    /// Read back the stored workflows and flag orders a previous process left
+    /// in Processing, returning how many were restored
+    ///
+    /// Call once at startup before [`Self::spawn_background_tasks`], so the
+    /// stale order reconciliation it starts sees the restored orders. Without
+    /// a store only orders already in the workflow manager are flagged.
+    pub async fn restore_workflows(&self) -> Result<usize, WorkflowStoreError> {
+        let restored = match self.workflow_store {
+            Some(ref store) => crate::business::restore_workflows(&self.workflow_manager, store.as_ref()).await?,
+            None => 0,
+        };
+        if restored > 0 {
+            tracing::info!("Restored {} order workflow(s) from the workflow store", restored);
+        }
+        shutdown::reconcile_interrupted_orders(&self.workflow_manager);
+        Ok(restored)
+    }
+
+    /// Write every workflow to the workflow store, if there is one
+    pub async fn save_workflows(&self) -> Result<(), WorkflowStoreError> {
+        if let Some(ref store) = self.workflow_store {
+            crate::business::save_workflows(&self.workflow_manager, store.as_ref()).await?;
+        }
+        Ok(())
+    }
+
+    /// Start the periodic background tasks; must be called inside a Tokio runtime
+    pub fn spawn_background_tasks(&self) {
+        if let Some(ref netbox) = self.netbox {
+            // Prime the caches; the readiness endpoint reports warming until this is done
+            if self.config.warmup.enabled {
+                let warmup = Warmup::new(
+                    netbox.router.clone(),
+                    self.tenant_mappings.clone(),
+                    self.hot_sites.clone(),
+                    self.config.warmup.deadline,
+                );
+                let readiness = self.readiness.clone();
+                tokio::spawn(async move { warmup.run(&readiness).await });
+            }
+
+            for endpoint in netbox.router.endpoints() {
+                // Periodically re-check NetBox credentials so health recovers once a new token works
+                let client = endpoint.client.clone();
+                let name = endpoint.name.clone();
+                let interval = self.config.credential_check_interval;
+                let check_token_scope = self.config.check_token_scope;
+                tokio::spawn(async move {
+                    let mut ticker = tokio::time::interval(interval);
+                    loop {
+                        ticker.tick().await;
+                        if let Err(e) = client.check_credentials().await {
+                            tracing::debug!("NetBox credential check failed for endpoint '{}': {}", name, e);
+                        }
+                        // Probed on the first tick, then again while read-only so orders resume once it may write
+                        if check_token_scope && client.token_scope().is_none_or(|scope| scope == TokenScope::ReadOnly) {
+                            client.probe_token_scope().await;
+                        }
+                    }
+                });
+
+                // Probe NetBox while the circuit is open so it closes without user traffic;
+                // the prober itself decides when a probe is due
+                if self.config.recovery_probe.enabled {
+                    let client = endpoint.client.clone();
+                    tokio::spawn(async move {
+                        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(1));
+                        loop {
+                            ticker.tick().await;
+                            client.poll_recovery().await;
+                        }
+                    });
+                }
+            }
+
+            // Keep the device catalog current; names it lacks also reload it on demand
+            let catalog = netbox.catalog.clone();
+            let interval = self.config.device_catalog_refresh_interval;
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(interval);
+                loop {
+                    ticker.tick().await;
+                    if let Err(e) = catalog.refresh().await {
+                        tracing::debug!("Device catalog refresh failed: {}", e);
+                    }
+                }
+            });
+
+            // Mirror tenants' sites and devices, once at startup and then periodically
+            if let Some(ref sync) = netbox.sync {
+                let sync = sync.clone();
+                let interval = self.config.sync.interval;
+                tokio::spawn(async move {
+                    let mut ticker = tokio::time::interval(interval);
+                    loop {
+                        ticker.tick().await;
+                        sync.run().await;
+                    }
+                });
+            }
+
+            // Reconcile orders left in Processing, once at startup and then periodically
+            let service = netbox.order_service.clone();
+            let interval = self.config.reconcile_interval;
+            let max_age = self.config.reconcile_max_age;
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(interval);
+                loop {
+                    ticker.tick().await;
+                    let summary = service.reconcile_stale_orders(max_age).await;
+                    tracing::debug!("Order reconciliation finished: {:?}", summary);
+                }
+            });
+
+            // Fail orders whose processing task overran its deadline without failing them
+            let service = netbox.order_service.clone();
+            let interval = self.config.processing_deadlines.watchdog_interval;
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(interval);
+                loop {
+                    ticker.tick().await;
+                    let failed = service.fail_overdue_orders().await;
+                    if failed > 0 {
+                        tracing::warn!("Failed {} order(s) overdue in processing", failed);
+                    }
+                }
+            });
+
+            // Execute orders parked during a maintenance freeze once it has ended
+            if let Some(ref windows) = self.config.maintenance {
+                let service = netbox.order_service.clone();
+                let interval = windows.release_interval;
+                tokio::spawn(async move {
+                    let mut ticker = tokio::time::interval(interval);
+                    loop {
+                        ticker.tick().await;
+                        let released = service.release_scheduled_orders().await;
+                        if released > 0 {
+                            tracing::info!("Released {} order(s) scheduled after a maintenance freeze", released);
+                        }
+                    }
+                });
+            }
+        } else {
+            tracing::warn!("OrderService not initialized - NetBox client unavailable. Order endpoints will return 503.");
+        }
+
+        // Keep the hot site list on disk for the next start's warm-up
+        if let Some(path) = self.config.warmup.hot_sites_file.clone().filter(|_| self.config.warmup.enabled) {
+            let hot_sites = self.hot_sites.clone();
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(HOT_SITES_SAVE_INTERVAL);
+                loop {
+                    ticker.tick().await;
+                    if let Err(e) = hot_sites.save(&path) {
+                        tracing::warn!("Failed to save hot sites to {}: {}", path.display(), e);
+                    }
+                }
+            });
+        }
+
+        // Keep the stored workflows recent, so a crash loses little and leaves processing orders to flag
+        if let Some(store) = self.workflow_store.clone() {
+            let manager = self.workflow_manager.clone();
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(WORKFLOW_SAVE_INTERVAL);
+                loop {
+                    ticker.tick().await;
+                    if let Err(e) = crate::business::save_workflows(&manager, store.as_ref()).await {
+                        tracing::warn!("Failed to save order workflows: {}", e);
+                    }
+                }
+            });
+        }
+
+        // Webhooks follow order transitions from here on
+        self.webhook_notifier.clone().listen(self.workflow_manager.subscribe());
+
+        let manager = self.workflow_manager.clone();
+        let retention = self.config.workflow_retention.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(retention.interval);
+            loop {
+                ticker.tick().await;
+                let manager = manager.clone();
+                let policy = retention.clone();
+                match tokio::task::spawn_blocking(move || manager.apply_retention(&policy)).await {
+                    Ok(Ok(purged)) if purged > 0 => tracing::info!("Purged {} finished order workflows", purged),
+                    Ok(Ok(_)) => {}
+                    Ok(Err(e)) => tracing::warn!("Order archive failed, nothing purged: {}", e),
+                    Err(e) => tracing::warn!("Order retention task panicked: {}", e),
+                }
+            }
+        });
+    }
+
+    /// The APIs wired to this state, as one OpenAPI service
+    pub fn api_service(&self) -> OpenApiService<NetGateApis, ()> {
+        let netbox = self.netbox.as_ref();
+
+        let mut health_api = match netbox {
+            Some(netbox) => HealthApi::with_netbox_client(netbox.client.clone()).with_netbox_router(netbox.router.clone()),
+            None => HealthApi::new(),
+        }
+        .with_workflow_manager(self.workflow_manager.clone(), self.config.stuck_order_threshold)
+        .with_readiness(self.readiness.clone());
+        if let Some(ref windows) = self.config.maintenance {
+            health_api = health_api.with_maintenance_windows(Arc::new(windows.clone()));
+        }
+        if let Some(sync) = netbox.and_then(|netbox| netbox.sync.clone()) {
+            health_api = health_api.with_sync(sync);
+        }
+        #[cfg(feature = "postgres")]
+        if let Some(ref database) = self.database {
+            health_api = health_api.with_database(database.clone());
+        }
+
+        let mut metrics_api = match netbox {
+            Some(netbox) => MetricsApi::with_netbox_client(netbox.client.clone())
+                .with_cached_client(netbox.cached_client.clone())
+                .with_netbox_router(netbox.router.clone()),
+            None => MetricsApi::new(),
+        };
+        metrics_api = metrics_api.with_workflow_manager(self.workflow_manager.clone());
+        if let Some(netbox) = netbox.filter(|_| self.config.maintenance.is_some()) {
+            metrics_api = metrics_api.with_scheduled_queue(netbox.order_service.scheduled_queue().clone());
+        }
+        if let Some(sync) = netbox.and_then(|netbox| netbox.sync.clone()) {
+            metrics_api = metrics_api.with_sync(sync);
+        }
+
+        let orders_api = match netbox {
+            Some(netbox) => OrdersApi::new(netbox.order_service.clone())
+                .with_extensible_service(netbox.extensible_service.clone()),
+            None => OrdersApi::without_netbox(self.workflow_manager.clone()),
+        };
+
+        let mut tenants_api = TenantsApi::new(self.tenant_store.clone())
+            .with_tenant_mappings(self.tenant_mappings.clone())
+            .with_workflow_manager(self.workflow_manager.clone());
+        if let Some(netbox) = netbox {
+            tenants_api = tenants_api
+                .with_netbox_client(netbox.client.clone())
+                .with_netbox_router(netbox.router.clone());
+        }
+
+        // Tenant-scoped site/device search and capacity reports; tenants without a NetBox mapping get 401
+        let mut inventory_api = match netbox {
+            Some(netbox) => InventoryApi::with_netbox_client(netbox.tenant_client.clone()).with_site_reports(Arc::new(
+                SiteReportService::new(netbox.tenant_client.clone()).with_catalog(netbox.catalog.clone()),
+            )),
+            None => InventoryApi::new(),
+        };
+        if let Some(sync) = netbox.and_then(|netbox| netbox.sync.clone()) {
+            inventory_api = inventory_api.with_sync(sync);
+        }
+
+        // Device types and roles for order forms
+        let mut catalog_api = CatalogApi::new();
+        if let Some(netbox) = netbox {
+            catalog_api = catalog_api.with_catalog(netbox.catalog.clone());
+        }
+
+        // Virtual resources, mapped to NetBox objects the tenant owns
+        let mut virtual_api = VirtualApi::new(self.virtual_service.clone());
+        if let Some(netbox) = netbox {
+            virtual_api = virtual_api.with_netbox_client(netbox.tenant_client.clone());
+        }
+
+        // Admin-triggered import of inventory that already exists in NetBox, onboarding and fault injection
+        let mut admin_api = match netbox {
+            Some(netbox) => AdminApi::new()
+                .with_importer(Arc::new(
+                    InventoryImporter::new(self.workflow_manager.clone(), netbox.client.clone(), self.tenant_mappings.clone())
+                        .with_virtual_service(self.virtual_service.clone()),
+                ))
+                .with_onboarding(netbox.onboarding.clone()),
+            None => AdminApi::new(),
+        };
+        if let Some(chaos) = netbox.and_then(|netbox| netbox.chaos.clone()) {
+            admin_api = admin_api.with_chaos(chaos);
+        }
+
+        // NetBox change webhooks, feeding the caches and the sync read model
+        let mut netbox_webhooks_api = NetBoxWebhooksApi::new();
+        if let Some(netbox) = netbox {
+            netbox_webhooks_api = netbox_webhooks_api.with_netbox_router(netbox.router.clone());
+        }
+        if let Some(sync) = netbox.and_then(|netbox| netbox.sync.clone()) {
+            netbox_webhooks_api = netbox_webhooks_api.with_sync(sync);
+        }
+        if let Some(ref secret) = self.config.netbox_webhook_secret {
+            netbox_webhooks_api = netbox_webhooks_api.with_secret(secret.clone());
+            if let Some(ref previous) = self.config.netbox_webhook_previous_secret {
+                netbox_webhooks_api = netbox_webhooks_api.with_secret(previous.clone());
+            }
+        } else if netbox.is_some() {
+            tracing::warn!("NETBOX_WEBHOOK_SECRET not set - NetBox webhooks will be refused with 401.");
+        }
+
+        let api = &self.config.api;
+        let service = OpenApiService::new(
+            (
+                health_api,
+                metrics_api,
+                orders_api,
+                tenants_api,
+                inventory_api,
+                catalog_api,
+                virtual_api,
+                admin_api,
+                netbox_webhooks_api,
+            ),
+            api.title.clone(),
+            api.version.clone(),
+        )
+        .server(api.server());
+        if !api.deprecated {
+            return service;
+        }
+        let notice = match api.sunset {
+            Some(ref sunset) => format!("This version of the API is deprecated and is retired on {}.", sunset),
+            None => "This version of the API is deprecated.".to_string(),
+        };
+        service.description(notice).extra_response_header::<String, _>(
+            ExtraHeader::new("Deprecation").description("`true` while this version is deprecated"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::DatabaseConfig;
+    use poem::http::StatusCode;
+    use poem::test::TestClient;
+
+    async fn state_without_netbox() -> AppState {
+        bootstrap(Config {
+            netbox_token: String::new(),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_bootstrap_without_token_has_no_netbox() {
+        let state = state_without_netbox().await;
+        assert!(state.netbox.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_bootstrap_with_token_builds_netbox_stack() {
+        let state = bootstrap(Config {
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        assert!(state.netbox.is_some());
+    }
+
+    #[cfg(not(feature = "postgres"))]
+    #[tokio::test]
+    async fn test_bootstrap_refuses_a_database_without_postgres_support() {
+        let error = bootstrap(Config {
+            database: DatabaseConfig { url: Some("postgres://localhost/netgate".to_string()), ..Default::default() },
+            ..Default::default()
+        })
+        .await
+        .err()
+        .unwrap();
+        assert!(error.to_string().contains("without the postgres feature"), "{}", error);
+    }
+
+    #[cfg(feature = "postgres")]
+    fn database_config(url: String) -> Config {
+        Config {
+            database: DatabaseConfig { url: Some(url), ..Default::default() },
+            ..Default::default()
+        }
+    }
+
+    #[cfg(feature = "postgres")]
+    #[tokio::test]
+    async fn test_bootstrap_fails_when_migrations_cant_be_applied() {
+        use sqlx::Connection;
+
+        let Some(url) = crate::database::test_database_url().await else {
+            return;
+        };
+        // A table in the way of the first migration
+        let mut connection = sqlx::PgConnection::connect(&url).await.unwrap();
+        sqlx::query("CREATE TABLE order_workflows (id INTEGER)").execute(&mut connection).await.unwrap();
+
+        let error = bootstrap(database_config(url)).await.err().unwrap();
+        assert!(error.to_string().contains("Failed to apply database migrations"), "{}", error);
+    }
+
+    #[cfg(feature = "postgres")]
+    #[tokio::test]
+    async fn test_bootstrap_keeps_workflows_and_tenant_mappings_in_the_database() {
+        let Some(url) = crate::database::test_database_url().await else {
+            return;
+        };
+        let state = bootstrap(Config {
+            tenant_mappings: crate::security::tenant::parse_tenant_mappings("acme=10"),
+            ..database_config(url.clone())
+        })
+        .await
+        .unwrap();
+        let order_id = state.workflow_manager.create_order("acme".to_string());
+        state.save_workflows().await.unwrap();
+
+        let restarted = bootstrap(database_config(url)).await.unwrap();
+        assert_eq!(restarted.restore_workflows().await.unwrap(), 1);
+        assert!(restarted.workflow_manager.get_order(&order_id).is_some());
+        assert_eq!(restarted.tenant_mappings.get_netbox_tenant_id(&"acme".to_string()), Some(10));
+
+        let client = TestClient::new(restarted.api_service());
+        let response = client.get("/health").send().await;
+        response.assert_status_is_ok();
+        let health = response.json().await;
+        health.value().object().get("database").object().get("connected").assert_bool(true);
+    }
+
+    #[tokio::test]
+    async fn test_orders_without_netbox_return_503() {
+        let state = state_without_netbox().await;
+        let client = TestClient::new(state.api_service());
+
+        for (path, body) in [
+            ("/orders/site", serde_json::json!({ "name": "Edge 1" })),
+            ("/orders/device", serde_json::json!({ "name": "edge-sw-1" })),
+        ] {
+            let response = client
+                .post(path)
+                .header("X-Tenant-Id", "tenant1")
+                .body_json(&body)
+                .send()
+                .await;
+            response.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+            response
+                .assert_json(serde_json::json!({ "error": "NetBox integration not configured" }))
+                .await;
+        }
+
+        // Existing orders can still be listed without NetBox
+        let response = client.get("/orders").header("X-Tenant-Id", "tenant1").send().await;
+        response.assert_status_is_ok();
+    }
+}
@@ -0,0 +1,373 @@
+use regex::{Regex, RegexBuilder};
+use std::collections::HashMap;
+use tracing::warn;
+
+/// Tag added to sites whose address could not be verified
+pub const UNVERIFIED_ADDRESS_TAG: &str = "address-unverified";
+
+/// Postal code formats of common countries, by ISO country code
+const DEFAULT_POSTAL_CODES: [(&str, &str); 8] = [
+    ("US", r"\b\d{5}(?:-\d{4})?\b"),
+    ("CA", r"\b[A-Z]\d[A-Z] ?\d[A-Z]\d\b"),
+    ("GB", r"\b[A-Z]{1,2}\d[A-Z\d]? ?\d[A-Z]{2}\b"),
+    ("DE", r"\b\d{5}\b"),
+    ("FR", r"\b\d{5}\b"),
+    ("NL", r"\b\d{4} ?[A-Z]{2}\b"),
+    ("AU", r"\b\d{4}\b"),
+    ("JP", r"\b\d{3}-\d{4}\b"),
+];
+
+/// Names a country may be written as, besides its ISO code
+const COUNTRY_NAMES: [(&str, &[&str]); 8] = [
+    ("US", &["usa", "united states", "united states of america"]),
+    ("CA", &["canada"]),
+    ("GB", &["uk", "united kingdom", "great britain", "england", "scotland", "wales"]),
+    ("DE", &["germany", "deutschland"]),
+    ("FR", &["france"]),
+    ("NL", &["netherlands", "the netherlands", "nederland", "holland"]),
+    ("AU", &["australia"]),
+    ("JP", &["japan"]),
+];
+
+/// Street suffixes spelled out when they end a street that starts with its number
+const STREET_SUFFIXES: [(&str, &str); 8] = [
+    ("st", "Street"),
+    ("ave", "Avenue"),
+    ("rd", "Road"),
+    ("blvd", "Boulevard"),
+    ("ln", "Lane"),
+    ("dr", "Drive"),
+    ("ct", "Court"),
+    ("pl", "Place"),
+];
+
+/// Words kept lowercase inside a street or city name
+const LOWERCASE_WORDS: [&str; 12] = ["de", "den", "der", "van", "von", "la", "le", "du", "des", "of", "the", "and"];
+
+/// Parts of an address the parser recognized
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AddressComponents {
+    pub street: String,
+    pub city: Option<String>,
+    pub postal_code: Option<String>,
+    /// ISO country code
+    pub country: Option<String>,
+}
+
+/// An address after normalization
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedAddress {
+    pub address: String,
+    pub components: AddressComponents,
+    /// Why the address couldn't be verified; empty if it was
+    pub problems: Vec<String>,
+}
+
+impl NormalizedAddress {
+    pub fn is_verified(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Normalizes the free-text address of site orders
+///
+/// Whitespace is collapsed, empty comma-separated parts dropped and names
+/// title-cased. The address is then split into street, city, postal code
+/// and country: the country is the last part when it names a known country,
+/// and the postal code is found with that country's format. An address is
+/// verified when it has a house number, a known country and a postal code
+/// of the country's format.
+#[derive(Debug, Clone)]
+pub struct AddressNormalizer {
+    strict: bool,
+    postal_codes: HashMap<String, Regex>,
+}
+
+impl Default for AddressNormalizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AddressNormalizer {
+    /// Normalizer with the built-in postal code formats, accepting unverified addresses
+    pub fn new() -> Self {
+        let mut normalizer = Self {
+            strict: false,
+            postal_codes: HashMap::new(),
+        };
+        for (country, pattern) in DEFAULT_POSTAL_CODES {
+            normalizer = normalizer
+                .with_postal_code(country, pattern)
+                .expect("built-in postal code formats are valid");
+        }
+        normalizer
+    }
+
+    /// Refuse addresses that can't be verified instead of accepting them
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Set a country's postal code format, a regex matched case-insensitively
+    pub fn with_postal_code(mut self, country: &str, pattern: &str) -> Result<Self, regex::Error> {
+        let regex = RegexBuilder::new(pattern).case_insensitive(true).build()?;
+        self.postal_codes.insert(country.trim().to_uppercase(), regex);
+        Ok(self)
+    }
+
+    /// Load from ADDRESS_VALIDATION_STRICT and ADDRESS_POSTAL_CODES
+    ///
+    /// ADDRESS_POSTAL_CODES holds `CC=regex` pairs separated by semicolons,
+    /// replacing or adding to the built-in formats. Invalid pairs are skipped
+    /// with a warning.
+    pub fn from_env() -> Self {
+        let strict = std::env::var("ADDRESS_VALIDATION_STRICT")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(false);
+        Self::new()
+            .with_strict(strict)
+            .with_postal_codes(&std::env::var("ADDRESS_POSTAL_CODES").unwrap_or_default())
+    }
+
+    /// Add `CC=regex` pairs separated by semicolons, skipping invalid ones
+    pub fn with_postal_codes(self, spec: &str) -> Self {
+        spec.split(';').map(str::trim).filter(|pair| !pair.is_empty()).fold(self, |normalizer, pair| {
+            let Some((country, pattern)) = pair.split_once('=').filter(|(country, _)| !country.trim().is_empty()) else {
+                warn!("Ignoring postal code format '{}': expected CC=regex", pair);
+                return normalizer;
+            };
+            match normalizer.clone().with_postal_code(country, pattern.trim()) {
+                Ok(normalizer) => normalizer,
+                Err(e) => {
+                    warn!("Ignoring postal code format for {}: {}", country.trim(), e);
+                    normalizer
+                }
+            }
+        })
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Normalize an address and split it into its components
+    pub fn normalize(&self, address: &str) -> NormalizedAddress {
+        let mut parts: Vec<String> = address
+            .split(',')
+            .map(|part| part.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|part| !part.is_empty())
+            .collect();
+        if parts.is_empty() {
+            return NormalizedAddress {
+                address: String::new(),
+                components: AddressComponents::default(),
+                problems: vec!["Address is empty".to_string()],
+            };
+        }
+
+        let mut components = AddressComponents::default();
+        let country_index = (parts.len() > 1).then(|| parts.len() - 1);
+        components.country = country_index.and_then(|index| country_code(&parts[index]));
+
+        let mut problems = Vec::new();
+        for (index, part) in parts.iter_mut().enumerate() {
+            *part = title_case(part);
+            if index == 0 {
+                *part = expand_street_suffix(part);
+                components.street = part.clone();
+                continue;
+            }
+            if Some(index) == country_index && components.country.is_some() {
+                // Country codes such as `usa` are written in capitals
+                if part.len() <= 3 {
+                    *part = part.to_uppercase();
+                }
+                continue;
+            }
+            let mut remainder = part.clone();
+            if components.postal_code.is_none() {
+                if let Some(postal) = self.find_postal_code(part, components.country.as_deref()) {
+                    let code = part[postal.clone()].to_uppercase();
+                    part.replace_range(postal.clone(), &code);
+                    remainder = format!("{} {}", &part[..postal.start], &part[postal.end..]).trim().to_string();
+                    components.postal_code = Some(code);
+                }
+            }
+            if components.city.is_none() && !remainder.is_empty() {
+                components.city = Some(remainder.split_whitespace().collect::<Vec<_>>().join(" "));
+            }
+        }
+
+        if !components.street.chars().any(|c| c.is_ascii_digit()) {
+            problems.push("Street has no house number".to_string());
+        }
+        match components.country {
+            None => problems.push("No recognized country".to_string()),
+            Some(ref country) if components.postal_code.is_none() => {
+                problems.push(format!("No postal code in the format of {}", country));
+            }
+            Some(_) => {}
+        }
+
+        NormalizedAddress {
+            address: parts.join(", "),
+            components,
+            problems,
+        }
+    }
+
+    /// Byte range of the postal code in `part`, in the country's format or, if
+    /// the country is unknown, any known format
+    fn find_postal_code(&self, part: &str, country: Option<&str>) -> Option<std::ops::Range<usize>> {
+        match country {
+            Some(country) => self.postal_codes.get(country)?.find(part).map(|found| found.range()),
+            None => {
+                let mut countries: Vec<&String> = self.postal_codes.keys().collect();
+                countries.sort();
+                countries
+                    .into_iter()
+                    .find_map(|country| self.postal_codes[country].find(part))
+                    .map(|found| found.range())
+            }
+        }
+    }
+}
+
+/// ISO code of the country a part of an address names, if known
+fn country_code(part: &str) -> Option<String> {
+    let part = part.trim().trim_end_matches('.').to_lowercase();
+    COUNTRY_NAMES
+        .iter()
+        .find(|(code, names)| code.eq_ignore_ascii_case(&part) || names.contains(&part.as_str()))
+        .map(|(code, _)| code.to_string())
+}
+
+/// Capitalize each word, keeping numbers, short abbreviations and particles as they are
+fn title_case(part: &str) -> String {
+    part.split(' ')
+        .enumerate()
+        .map(|(index, word)| {
+            if word.chars().any(|c| c.is_ascii_digit())
+                || (word.len() <= 3 && word.chars().all(|c| c.is_ascii_uppercase()))
+            {
+                word.to_string()
+            } else if index > 0 && LOWERCASE_WORDS.contains(&word.to_lowercase().as_str()) {
+                word.to_lowercase()
+            } else {
+                word.split('-').map(capitalize).collect::<Vec<_>>().join("-")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect(),
+        None => String::new(),
+    }
+}
+
+/// Spell out an abbreviated suffix ending a street that starts with its number, e.g. `123 Main St`
+fn expand_street_suffix(street: &str) -> String {
+    let words: Vec<&str> = street.split(' ').collect();
+    let starts_with_number = words[0].chars().next().is_some_and(|c| c.is_ascii_digit());
+    let Some((last, rest)) = words.split_last().filter(|(_, rest)| starts_with_number && !rest.is_empty()) else {
+        return street.to_string();
+    };
+    let abbreviation = last.trim_end_matches('.').to_lowercase();
+    match STREET_SUFFIXES.iter().find(|(short, _)| *short == abbreviation) {
+        Some((_, suffix)) => format!("{} {}", rest.join(" "), suffix),
+        None => street.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn components(street: &str, city: &str, postal_code: &str, country: &str) -> AddressComponents {
+        AddressComponents {
+            street: street.to_string(),
+            city: Some(city.to_string()),
+            postal_code: Some(postal_code.to_string()),
+            country: Some(country.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_addresses_are_normalized_per_country() {
+        let normalizer = AddressNormalizer::new();
+        let cases = [
+            (
+                "123   main st ,springfield, IL 62704, usa",
+                "123 Main Street, Springfield, IL 62704, USA",
+                components("123 Main Street", "Springfield", "62704", "US"),
+            ),
+            (
+                "10 downing street, london sw1a 2aa, United Kingdom",
+                "10 Downing Street, London SW1A 2AA, United Kingdom",
+                components("10 Downing Street", "London", "SW1A 2AA", "GB"),
+            ),
+            (
+                "Unter den Linden 1, 10117 berlin, Germany",
+                "Unter den Linden 1, 10117 Berlin, Germany",
+                components("Unter den Linden 1", "Berlin", "10117", "DE"),
+            ),
+            (
+                "damrak 1, 1012 lg  amsterdam, the netherlands",
+                "Damrak 1, 1012 LG Amsterdam, The Netherlands",
+                components("Damrak 1", "Amsterdam", "1012 LG", "NL"),
+            ),
+            (
+                "290 bremner blvd, toronto, ON m5v 3l9, CA",
+                "290 Bremner Boulevard, Toronto, ON M5V 3L9, CA",
+                components("290 Bremner Boulevard", "Toronto", "M5V 3L9", "CA"),
+            ),
+        ];
+
+        for (raw, address, expected) in cases {
+            let normalized = normalizer.normalize(raw);
+            assert_eq!(normalized.address, address);
+            assert_eq!(normalized.components, expected);
+            assert!(normalized.is_verified(), "{}: {:?}", raw, normalized.problems);
+        }
+    }
+
+    #[test]
+    fn test_unverifiable_addresses_list_their_problems() {
+        let normalizer = AddressNormalizer::new();
+
+        let normalized = normalizer.normalize("123 main street, ");
+        assert_eq!(normalized.address, "123 Main Street");
+        assert_eq!(normalized.problems, vec!["No recognized country"]);
+
+        let normalized = normalizer.normalize("Main Street, Paris, France");
+        assert_eq!(normalized.components.country.as_deref(), Some("FR"));
+        assert_eq!(
+            normalized.problems,
+            vec!["Street has no house number", "No postal code in the format of FR"]
+        );
+
+        // A German postal code is not a Dutch one
+        let normalized = normalizer.normalize("Damrak 1, 10117 Amsterdam, NL");
+        assert_eq!(normalized.problems, vec!["No postal code in the format of NL"]);
+
+        assert_eq!(normalizer.normalize(" , ").problems, vec!["Address is empty"]);
+    }
+
+    #[test]
+    fn test_postal_code_formats_from_config() {
+        let normalizer = AddressNormalizer::new().with_postal_codes(r"US=\b\d{5}-\d{4}\b; broken; XX=(");
+
+        assert!(!normalizer.normalize("1 Main St, Springfield, 62704, US").is_verified());
+        let normalized = normalizer.normalize("1 Main St, Springfield, 62704-1234, US");
+        assert_eq!(normalized.components.postal_code.as_deref(), Some("62704-1234"));
+        assert!(normalized.is_verified());
+        assert!(!normalizer.postal_codes.contains_key("XX"));
+    }
+}
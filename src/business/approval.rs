@@ -0,0 +1,99 @@
+use std::collections::HashSet;
+
+/// Decides which orders must be approved by an operator before reaching NetBox
+pub trait ApprovalPolicy: Send + Sync {
+    /// Check if an order for `tenant_id` in `environment` needs approval
+    fn requires_approval(&self, tenant_id: &str, environment: Option<&str>) -> bool;
+}
+
+/// Approval rules keyed by tenant and by enrichment environment
+///
+/// An order needs approval if either its tenant or its environment is listed.
+/// With no rules every order is auto-approved.
+#[derive(Debug, Clone, Default)]
+pub struct ApprovalRules {
+    tenants: HashSet<String>,
+    environments: HashSet<String>,
+}
+
+impl ApprovalRules {
+    /// Create rules that auto-approve every order
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require approval for all orders of a tenant
+    pub fn with_tenant(mut self, tenant_id: impl Into<String>) -> Self {
+        self.tenants.insert(tenant_id.into());
+        self
+    }
+
+    /// Require approval for orders in an environment (e.g. "production")
+    pub fn with_environment(mut self, environment: impl Into<String>) -> Self {
+        self.environments.insert(environment.into().to_lowercase());
+        self
+    }
+
+    /// Load rules from APPROVAL_REQUIRED_TENANTS and APPROVAL_REQUIRED_ENVIRONMENTS
+    /// (comma-separated lists)
+    pub fn from_env() -> Self {
+        let list = |name: &str| -> Vec<String> {
+            std::env::var(name)
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+
+        let rules = list("APPROVAL_REQUIRED_TENANTS")
+            .into_iter()
+            .fold(Self::new(), Self::with_tenant);
+        list("APPROVAL_REQUIRED_ENVIRONMENTS")
+            .into_iter()
+            .fold(rules, Self::with_environment)
+    }
+
+    /// Check if any rule is configured
+    pub fn is_empty(&self) -> bool {
+        self.tenants.is_empty() && self.environments.is_empty()
+    }
+}
+
+impl ApprovalPolicy for ApprovalRules {
+    fn requires_approval(&self, tenant_id: &str, environment: Option<&str>) -> bool {
+        self.tenants.contains(tenant_id)
+            || environment.is_some_and(|env| self.environments.contains(&env.to_lowercase()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_rules_auto_approve() {
+        let rules = ApprovalRules::new();
+
+        assert!(rules.is_empty());
+        assert!(!rules.requires_approval("tenant1", Some("production")));
+    }
+
+    #[test]
+    fn test_tenant_rule() {
+        let rules = ApprovalRules::new().with_tenant("tenant1");
+
+        assert!(rules.requires_approval("tenant1", None));
+        assert!(!rules.requires_approval("tenant2", None));
+    }
+
+    #[test]
+    fn test_environment_rule_is_case_insensitive() {
+        let rules = ApprovalRules::new().with_environment("Production");
+
+        assert!(rules.requires_approval("tenant1", Some("production")));
+        assert!(!rules.requires_approval("tenant1", Some("staging")));
+        assert!(!rules.requires_approval("tenant1", None));
+    }
+}
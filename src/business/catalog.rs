@@ -0,0 +1,359 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::RwLock;
+use tracing::{debug, info};
+
+use crate::clock::{SharedClock, SystemClock};
+use crate::domain::CatalogRef;
+use crate::error::AppError;
+use crate::netbox::models::{NetBoxDeviceRole, NetBoxDeviceType};
+use crate::netbox::ResilientNetBoxClient;
+
+/// A name that isn't in the catalog reloads it at most this often, so unknown
+/// names can't turn every order into a NetBox round trip
+const MISS_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Suggestions offered for an unknown name
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Device types and roles as last loaded from NetBox
+#[derive(Debug, Clone, Default)]
+pub struct CatalogSnapshot {
+    pub device_types: Vec<NetBoxDeviceType>,
+    pub device_roles: Vec<NetBoxDeviceRole>,
+}
+
+/// NetBox's device types and roles, cached, with name resolution for orders
+///
+/// The catalog is loaded on first use and reloaded by the background refresh.
+/// A name it doesn't know reloads it once before the name is rejected, so
+/// types and roles added in NetBox work without waiting for the refresh.
+pub struct DeviceCatalog {
+    client: Arc<ResilientNetBoxClient>,
+    loaded: RwLock<Option<(Arc<CatalogSnapshot>, Instant)>>,
+    clock: SharedClock,
+}
+
+impl DeviceCatalog {
+    pub fn new(client: Arc<ResilientNetBoxClient>) -> Self {
+        Self {
+            client,
+            loaded: RwLock::new(None),
+            clock: SystemClock::shared(),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// The cached catalog, loading it if it hasn't been yet
+    pub async fn snapshot(&self) -> Result<Arc<CatalogSnapshot>, AppError> {
+        if let Some((snapshot, _)) = self.loaded.read().as_ref() {
+            return Ok(snapshot.clone());
+        }
+        self.refresh().await
+    }
+
+    /// Reload the catalog from NetBox
+    pub async fn refresh(&self) -> Result<Arc<CatalogSnapshot>, AppError> {
+        let device_types = self.client.list_device_types().await?;
+        let device_roles = self.client.list_device_roles().await?;
+        debug!("Loaded {} device type(s) and {} device role(s)", device_types.len(), device_roles.len());
+        let snapshot = Arc::new(CatalogSnapshot { device_types, device_roles });
+        *self.loaded.write() = Some((snapshot.clone(), self.clock.now_instant()));
+        Ok(snapshot)
+    }
+
+    /// The id of a device type given by id, slug, model, or `manufacturer/slug`
+    pub async fn resolve_device_type(&self, reference: &CatalogRef) -> Result<i32, AppError> {
+        self.resolve(reference, CatalogKind::DeviceType).await
+    }
+
+    /// The id of a device role given by id, slug or name
+    pub async fn resolve_device_role(&self, reference: &CatalogRef) -> Result<i32, AppError> {
+        self.resolve(reference, CatalogKind::DeviceRole).await
+    }
+
+    async fn resolve(&self, reference: &CatalogRef, kind: CatalogKind) -> Result<i32, AppError> {
+        let name = match reference {
+            CatalogRef::Id(id) => return Ok(*id),
+            CatalogRef::Name(name) => name.trim(),
+        };
+        let snapshot = self.snapshot().await?;
+        let mut found = kind.find(&snapshot, name);
+        if matches!(found, Lookup::Missing) && self.may_refresh_on_miss() {
+            info!("{} '{}' is not in the device catalog; reloading it", kind.label(), name);
+            found = kind.find(&*self.refresh().await?, name);
+        }
+        match found {
+            Lookup::Found(id) => Ok(id),
+            Lookup::Ambiguous(matches) => Err(AppError::ValidationError(format!(
+                "{} '{}' is ambiguous: it matches {}; use its id{}",
+                kind.label(),
+                name,
+                matches.join(", "),
+                kind.disambiguation_hint()
+            ))),
+            Lookup::Missing => {
+                let suggestions = kind.suggestions(&*self.snapshot().await?, name);
+                let mut message = format!("Unknown {} '{}'", kind.label().to_lowercase(), name);
+                if !suggestions.is_empty() {
+                    message.push_str(&format!("; did you mean {}?", suggestions.join(", ")));
+                }
+                Err(AppError::ValidationError(message))
+            }
+        }
+    }
+
+    fn may_refresh_on_miss(&self) -> bool {
+        self.loaded
+            .read()
+            .as_ref()
+            .is_none_or(|(_, loaded_at)| self.clock.now_instant().duration_since(*loaded_at) >= MISS_REFRESH_INTERVAL)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CatalogKind {
+    DeviceType,
+    DeviceRole,
+}
+
+/// The outcome of looking a name up in the catalog
+enum Lookup {
+    Found(i32),
+    /// Labels of every entry the name matches
+    Ambiguous(Vec<String>),
+    Missing,
+}
+
+/// A catalog entry with the names it can be ordered by, lowercased
+struct Candidate {
+    id: i32,
+    label: String,
+    names: Vec<String>,
+}
+
+impl CatalogKind {
+    fn label(self) -> &'static str {
+        match self {
+            Self::DeviceType => "Device type",
+            Self::DeviceRole => "Device role",
+        }
+    }
+
+    fn disambiguation_hint(self) -> &'static str {
+        match self {
+            Self::DeviceType => " or manufacturer/slug",
+            Self::DeviceRole => "",
+        }
+    }
+
+    fn candidates(self, snapshot: &CatalogSnapshot) -> Vec<Candidate> {
+        match self {
+            Self::DeviceType => snapshot
+                .device_types
+                .iter()
+                .filter_map(|device_type| {
+                    let id = device_type.id?;
+                    let mut names = vec![device_type.slug.to_lowercase(), device_type.model.to_lowercase()];
+                    let label = match device_type.manufacturer {
+                        Some(ref manufacturer) => {
+                            let qualified = format!("{}/{}", manufacturer.slug, device_type.slug);
+                            names.push(qualified.to_lowercase());
+                            qualified
+                        }
+                        None => device_type.slug.clone(),
+                    };
+                    Some(Candidate { id, label, names })
+                })
+                .collect(),
+            Self::DeviceRole => snapshot
+                .device_roles
+                .iter()
+                .filter_map(|role| {
+                    Some(Candidate {
+                        id: role.id?,
+                        label: role.slug.clone(),
+                        names: vec![role.slug.to_lowercase(), role.name.to_lowercase()],
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    fn find(self, snapshot: &CatalogSnapshot, name: &str) -> Lookup {
+        let name = name.to_lowercase();
+        let matches: Vec<Candidate> = self
+            .candidates(snapshot)
+            .into_iter()
+            .filter(|candidate| candidate.names.contains(&name))
+            .collect();
+        match matches.as_slice() {
+            [] => Lookup::Missing,
+            [only] => Lookup::Found(only.id),
+            _ => Lookup::Ambiguous(
+                matches.iter().map(|candidate| format!("{} (id {})", candidate.label, candidate.id)).collect(),
+            ),
+        }
+    }
+
+    /// The closest entries by edit distance, or containing the name
+    fn suggestions(self, snapshot: &CatalogSnapshot, name: &str) -> Vec<String> {
+        let name = name.to_lowercase();
+        let threshold = (name.chars().count() / 3).max(2);
+        let mut scored: Vec<(usize, String)> = self
+            .candidates(snapshot)
+            .into_iter()
+            .filter_map(|candidate| {
+                let distance = candidate
+                    .names
+                    .iter()
+                    .map(|known| if known.contains(&name) { 0 } else { edit_distance(known, &name) })
+                    .min()?;
+                (distance <= threshold).then_some((distance, candidate.label))
+            })
+            .collect();
+        scored.sort();
+        scored.into_iter().take(MAX_SUGGESTIONS).map(|(_, label)| label).collect()
+    }
+}
+
+/// Levenshtein distance between two strings, by characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::config::Config;
+    use crate::netbox::NetBoxClient;
+    use serde_json::json;
+    use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+    fn catalog(mock_server: &MockServer, clock: Arc<ManualClock>) -> DeviceCatalog {
+        let config = Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let client = Arc::new(ResilientNetBoxClient::new(Arc::new(NetBoxClient::new(config).unwrap())));
+        DeviceCatalog::new(client).with_clock(clock)
+    }
+
+    async fn mount_catalog(mock_server: &MockServer, device_types: serde_json::Value, times: Option<u64>) {
+        let types = Mock::given(method("GET"))
+            .and(path("/api/dcim/device-types/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "results": device_types })));
+        match times {
+            Some(times) => types.up_to_n_times(times).mount(mock_server).await,
+            None => types.mount(mock_server).await,
+        }
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/device-roles/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "results": [
+                { "id": 1, "name": "Leaf Switch", "slug": "leaf-switch" },
+                { "id": 2, "name": "Spine Switch", "slug": "spine-switch" }
+            ]})))
+            .mount(mock_server)
+            .await;
+    }
+
+    fn cisco(id: i32, model: &str, slug: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "model": model,
+            "slug": slug,
+            "manufacturer": { "id": 1, "name": "Cisco", "slug": "cisco" }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_resolves_ids_slugs_and_names() {
+        let mock_server = MockServer::start().await;
+        mount_catalog(&mock_server, json!([cisco(4, "C9300-48P", "cisco-c9300-48p")]), None).await;
+        let catalog = catalog(&mock_server, Arc::new(ManualClock::new()));
+
+        let name = |name: &str| CatalogRef::Name(name.to_string());
+        assert_eq!(catalog.resolve_device_type(&CatalogRef::Id(9)).await.unwrap(), 9);
+        assert_eq!(catalog.resolve_device_type(&name("cisco-c9300-48p")).await.unwrap(), 4);
+        assert_eq!(catalog.resolve_device_type(&name("c9300-48p")).await.unwrap(), 4);
+        assert_eq!(catalog.resolve_device_type(&name("cisco/cisco-c9300-48p")).await.unwrap(), 4);
+        assert_eq!(catalog.resolve_device_role(&name("leaf-switch")).await.unwrap(), 1);
+        assert_eq!(catalog.resolve_device_role(&name("Spine Switch")).await.unwrap(), 2);
+
+        let unknown = catalog.resolve_device_role(&name("leaf-swich")).await;
+        assert!(
+            matches!(unknown, Err(AppError::ValidationError(ref msg)) if msg == "Unknown device role 'leaf-swich'; did you mean leaf-switch?"),
+            "{:?}",
+            unknown
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unknown_name_reloads_catalog_once_it_is_stale() {
+        let mock_server = MockServer::start().await;
+        mount_catalog(&mock_server, json!([cisco(4, "C9300-48P", "cisco-c9300-48p")]), Some(1)).await;
+        mount_catalog(
+            &mock_server,
+            json!([cisco(4, "C9300-48P", "cisco-c9300-48p"), cisco(5, "C9500-32C", "cisco-c9500-32c")]),
+            None,
+        )
+        .await;
+        let clock = Arc::new(ManualClock::new());
+        let catalog = catalog(&mock_server, clock.clone());
+        let new_type = CatalogRef::Name("cisco-c9500-32c".to_string());
+
+        // Just loaded: the miss is answered from the cache
+        assert!(catalog.resolve_device_type(&CatalogRef::Name("cisco-c9300-48p".to_string())).await.is_ok());
+        assert!(catalog.resolve_device_type(&new_type).await.is_err());
+
+        clock.advance(MISS_REFRESH_INTERVAL);
+        assert_eq!(catalog.resolve_device_type(&new_type).await.unwrap(), 5);
+        assert_eq!(catalog.snapshot().await.unwrap().device_types.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_ambiguous_model_name_lists_matches() {
+        let mock_server = MockServer::start().await;
+        let arista = json!({
+            "id": 7,
+            "model": "Edge-1",
+            "slug": "arista-edge-1",
+            "manufacturer": { "id": 2, "name": "Arista", "slug": "arista" }
+        });
+        mount_catalog(&mock_server, json!([cisco(6, "Edge-1", "cisco-edge-1"), arista]), None).await;
+        let catalog = catalog(&mock_server, Arc::new(ManualClock::new()));
+
+        let result = catalog.resolve_device_type(&CatalogRef::Name("edge-1".to_string())).await;
+        assert!(
+            matches!(result, Err(AppError::ValidationError(ref msg)) if msg
+                == "Device type 'edge-1' is ambiguous: it matches cisco/cisco-edge-1 (id 6), arista/arista-edge-1 (id 7); use its id or manufacturer/slug"),
+            "{:?}",
+            result
+        );
+        assert_eq!(catalog.resolve_device_type(&CatalogRef::Name("arista-edge-1".to_string())).await.unwrap(), 7);
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("leaf-switch", "leaf-swich"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("spine", "spine"), 0);
+    }
+}
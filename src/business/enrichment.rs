@@ -1,6 +1,8 @@
 use crate::netbox::models::{NetBoxDevice, NetBoxSite, SiteStatus};
-use chrono::Utc;
+use poem_openapi::{Enum, Object};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
 
 /// Enrichment data from external sources
 #[derive(Debug, Clone, Default)]
@@ -20,8 +22,8 @@ pub struct EnrichmentData {
 /// Geographic enrichment data
 #[derive(Debug, Clone, Default)]
 pub struct GeographicData {
-    pub latitude: f64,
-    pub longitude: f64,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
     pub timezone: Option<String>,
     pub country: Option<String>,
     pub region: Option<String>,
@@ -45,10 +47,308 @@ pub struct BusinessMetadata {
     pub priority: Option<String>,    // e.g., "critical", "high", "medium", "low"
 }
 
+/// Business metadata fields whose custom field name can be configured
+pub const BUSINESS_CUSTOM_FIELDS: [&str; 4] = ["cost_center", "project_code", "environment", "priority"];
+
+/// What an applied enrichment was derived from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+#[serde(rename_all = "snake_case")]
+#[oai(rename_all = "snake_case")]
+pub enum EnrichmentSource {
+    /// The configured default tags
+    DefaultTags,
+    /// The tenant's environment and its configured tags
+    Environment,
+    /// Business metadata: cost center, project, priority
+    Business,
+    Geographic,
+    Contact,
+    /// The object's status
+    Status,
+    /// Free-form enrichment metadata
+    Metadata,
+    /// Tags supplied with the enrichment data
+    ExtraTags,
+}
+
+/// A tag the enricher added
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct AppliedTag {
+    pub tag: String,
+    pub source: EnrichmentSource,
+}
+
+/// A field the enricher set or changed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Object)]
+pub struct AppliedField {
+    pub field: String,
+    pub value: serde_json::Value,
+    pub source: EnrichmentSource,
+}
+
+/// What enriching an object changed, and why
+///
+/// Only actual changes are listed: a tag the object already had, or a field
+/// it already held the same value in, is left out.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Object)]
+pub struct EnrichmentReport {
+    pub tags_added: Vec<AppliedTag>,
+    /// Custom fields set from business metadata and enrichment metadata
+    pub custom_fields: Vec<AppliedField>,
+    /// Standard fields filled in, e.g. description, facility or coordinates
+    pub computed_fields: Vec<AppliedField>,
+}
+
+impl EnrichmentReport {
+    pub fn is_empty(&self) -> bool {
+        self.tags_added.is_empty() && self.custom_fields.is_empty() && self.computed_fields.is_empty()
+    }
+
+    /// Add another report's entries, skipping tags and fields already listed
+    pub fn merge(&mut self, other: EnrichmentReport) {
+        for tag in other.tags_added {
+            if !self.tags_added.iter().any(|applied| applied.tag == tag.tag) {
+                self.tags_added.push(tag);
+            }
+        }
+        for (fields, others) in [
+            (&mut self.custom_fields, other.custom_fields),
+            (&mut self.computed_fields, other.computed_fields),
+        ] {
+            for field in others {
+                match fields.iter_mut().find(|applied| applied.field == field.field) {
+                    Some(applied) => *applied = field,
+                    None => fields.push(field),
+                }
+            }
+        }
+    }
+
+    /// Tags in `after` but not `before`, with the source that derived them
+    fn added_tags(before: &[String], after: &[String], derived: &[(String, EnrichmentSource)]) -> Vec<AppliedTag> {
+        after
+            .iter()
+            .filter(|tag| !before.contains(tag))
+            .filter_map(|tag| {
+                derived
+                    .iter()
+                    .find(|(derived, _)| derived == tag)
+                    .map(|(_, source)| AppliedTag { tag: tag.clone(), source: *source })
+            })
+            .collect()
+    }
+
+    /// Custom fields whose value differs between `before` and `after`
+    fn changed_custom_fields(
+        before: Option<&serde_json::Value>,
+        after: Option<&serde_json::Value>,
+        derived: &[(String, serde_json::Value, EnrichmentSource)],
+    ) -> Vec<AppliedField> {
+        let mut fields: Vec<AppliedField> = derived
+            .iter()
+            .filter_map(|(field, _, source)| {
+                let value = after?.get(field)?;
+                (before.and_then(|before| before.get(field)) != Some(value)).then(|| AppliedField {
+                    field: field.clone(),
+                    value: value.clone(),
+                    source: *source,
+                })
+            })
+            .collect();
+        fields.sort_by(|a, b| a.field.cmp(&b.field));
+        fields.dedup_by(|a, b| a.field == b.field);
+        fields
+    }
+}
+
+/// Record a computed field if enriching changed it
+fn computed_field<T: Serialize + PartialEq>(
+    fields: &mut Vec<AppliedField>,
+    field: &str,
+    before: &Option<T>,
+    after: &Option<T>,
+    source: EnrichmentSource,
+) {
+    if before != after {
+        if let Some(value) = after {
+            fields.push(AppliedField {
+                field: field.to_string(),
+                value: serde_json::to_value(value).unwrap_or_default(),
+                source,
+            });
+        }
+    }
+}
+
+/// Why an enrichment configuration was refused
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum EnrichmentConfigError {
+    #[error("Empty tag name in {0}")]
+    EmptyTag(String),
+
+    #[error("Empty environment name in {0}")]
+    EmptyEnvironment(String),
+
+    #[error("Environment '{environment}' is listed more than once in {context}")]
+    DuplicateEnvironment { environment: String, context: String },
+
+    #[error("Unknown business metadata field '{field}' in {context}; expected one of {}", BUSINESS_CUSTOM_FIELDS.join(", "))]
+    UnknownCustomField { field: String, context: String },
+
+    #[error("Empty custom field name for '{field}' in {context}")]
+    EmptyCustomField { field: String, context: String },
+}
+
+/// Tag taxonomy and custom field names the enricher applies
+///
+/// Loaded from the YAML (or JSON) file named by ENRICHMENT_CONFIG_FILE.
+/// Tenants may override parts of it; see [`TenantEnrichment`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnrichmentConfig {
+    /// Tags added to every enriched object
+    pub default_tags: Vec<String>,
+    /// Tags added to objects of an environment, matched case-insensitively
+    pub environment_tags: HashMap<String, Vec<String>>,
+    /// Prepended to every tag the enricher derives, e.g. `ng-` gives `ng-prod`
+    pub tag_prefix: String,
+    /// Custom field names business metadata is stored under, by metadata field;
+    /// unlisted fields keep their own name
+    pub custom_fields: HashMap<String, String>,
+    /// Overrides merged over the rest for each tenant
+    pub tenants: HashMap<String, TenantEnrichment>,
+}
+
+/// A tenant's enrichment overrides
+///
+/// `default_tags` and `tag_prefix` replace the global ones; environment tags
+/// and custom field names are merged per entry, the tenant's winning.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TenantEnrichment {
+    /// Environment the tenant's orders are enriched, and approved, for
+    pub environment: Option<String>,
+    pub default_tags: Option<Vec<String>>,
+    pub environment_tags: HashMap<String, Vec<String>>,
+    pub tag_prefix: Option<String>,
+    pub custom_fields: HashMap<String, String>,
+}
+
+impl Default for EnrichmentConfig {
+    fn default() -> Self {
+        Self {
+            default_tags: vec!["netgate".to_string(), "enriched".to_string()],
+            environment_tags: HashMap::from([
+                ("production".to_string(), vec!["prod".to_string(), "critical".to_string()]),
+                ("staging".to_string(), vec!["staging".to_string(), "test".to_string()]),
+                ("development".to_string(), vec!["dev".to_string(), "non-prod".to_string()]),
+            ]),
+            tag_prefix: String::new(),
+            custom_fields: HashMap::new(),
+            tenants: HashMap::new(),
+        }
+    }
+}
+
+impl EnrichmentConfig {
+    /// Parse and validate a configuration in YAML, which includes JSON
+    pub fn from_yaml(yaml: &str) -> anyhow::Result<Self> {
+        let config: Self = serde_yaml::from_str(yaml)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Load and validate a configuration file
+    pub fn from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let yaml = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_yaml(&yaml).map_err(|e| anyhow::anyhow!("Invalid enrichment config in {}: {}", path.display(), e))
+    }
+
+    /// Refuse empty tag or environment names, environments listed twice and unknown custom fields
+    pub fn validate(&self) -> Result<(), EnrichmentConfigError> {
+        validate_layer("the enrichment config", Some(&self.default_tags), &self.environment_tags, &self.custom_fields)?;
+        for (tenant_id, tenant) in &self.tenants {
+            let context = format!("the enrichment overrides of tenant {}", tenant_id);
+            validate_layer(&context, tenant.default_tags.as_ref(), &tenant.environment_tags, &tenant.custom_fields)?;
+            if tenant.environment.as_ref().is_some_and(|environment| environment.trim().is_empty()) {
+                return Err(EnrichmentConfigError::EmptyEnvironment(context));
+            }
+        }
+        Ok(())
+    }
+
+    /// The configuration a tenant's objects are enriched with
+    pub fn for_tenant(&self, tenant_id: &str) -> Self {
+        let Some(tenant) = self.tenants.get(tenant_id) else {
+            return Self { tenants: HashMap::new(), ..self.clone() };
+        };
+        let mut environment_tags = self.environment_tags.clone();
+        for (environment, tags) in &tenant.environment_tags {
+            environment_tags.retain(|existing, _| !existing.eq_ignore_ascii_case(environment));
+            environment_tags.insert(environment.clone(), tags.clone());
+        }
+        let mut custom_fields = self.custom_fields.clone();
+        custom_fields.extend(tenant.custom_fields.clone());
+        Self {
+            default_tags: tenant.default_tags.clone().unwrap_or_else(|| self.default_tags.clone()),
+            environment_tags,
+            tag_prefix: tenant.tag_prefix.clone().unwrap_or_else(|| self.tag_prefix.clone()),
+            custom_fields,
+            tenants: HashMap::new(),
+        }
+    }
+
+    /// Environment configured for a tenant, if any
+    pub fn tenant_environment(&self, tenant_id: &str) -> Option<&str> {
+        self.tenants.get(tenant_id)?.environment.as_deref()
+    }
+}
+
+/// Validate one layer of an enrichment configuration
+fn validate_layer(
+    context: &str,
+    default_tags: Option<&Vec<String>>,
+    environment_tags: &HashMap<String, Vec<String>>,
+    custom_fields: &HashMap<String, String>,
+) -> Result<(), EnrichmentConfigError> {
+    let tags = default_tags.into_iter().flatten().chain(environment_tags.values().flatten());
+    if tags.into_iter().any(|tag| tag.trim().is_empty()) {
+        return Err(EnrichmentConfigError::EmptyTag(context.to_string()));
+    }
+    let mut environments: Vec<String> = environment_tags.keys().map(|environment| environment.trim().to_lowercase()).collect();
+    if environments.iter().any(String::is_empty) {
+        return Err(EnrichmentConfigError::EmptyEnvironment(context.to_string()));
+    }
+    environments.sort();
+    if let Some(pair) = environments.windows(2).find(|pair| pair[0] == pair[1]) {
+        return Err(EnrichmentConfigError::DuplicateEnvironment {
+            environment: pair[0].clone(),
+            context: context.to_string(),
+        });
+    }
+    for (field, name) in custom_fields {
+        if !BUSINESS_CUSTOM_FIELDS.contains(&field.as_str()) {
+            return Err(EnrichmentConfigError::UnknownCustomField {
+                field: field.clone(),
+                context: context.to_string(),
+            });
+        }
+        if name.trim().is_empty() {
+            return Err(EnrichmentConfigError::EmptyCustomField {
+                field: field.clone(),
+                context: context.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
 /// Object enrichment service
+#[derive(Debug, Clone)]
 pub struct ObjectEnricher {
-    default_tags: Vec<String>,
-    environment_tags: HashMap<String, Vec<String>>,
+    config: EnrichmentConfig,
 }
 
 impl Default for ObjectEnricher {
@@ -60,15 +360,7 @@ impl Default for ObjectEnricher {
 impl ObjectEnricher {
     /// Create a new enricher with default configuration
     pub fn new() -> Self {
-        let mut environment_tags = HashMap::new();
-        environment_tags.insert("production".to_string(), vec!["prod".to_string(), "critical".to_string()]);
-        environment_tags.insert("staging".to_string(), vec!["staging".to_string(), "test".to_string()]);
-        environment_tags.insert("development".to_string(), vec!["dev".to_string(), "non-prod".to_string()]);
-
-        Self {
-            default_tags: vec!["netgate".to_string(), "enriched".to_string()],
-            environment_tags,
-        }
+        Self::from_config(EnrichmentConfig::default())
     }
 
     /// Create an enricher with custom configuration
@@ -76,18 +368,103 @@ impl ObjectEnricher {
         default_tags: Vec<String>,
         environment_tags: HashMap<String, Vec<String>>,
     ) -> Self {
-        Self {
+        Self::from_config(EnrichmentConfig {
             default_tags,
             environment_tags,
+            ..Default::default()
+        })
+    }
+
+    /// Create an enricher from a loaded configuration
+    pub fn from_config(config: EnrichmentConfig) -> Self {
+        Self { config }
+    }
+
+    /// Enricher with a tenant's overrides merged over this one's configuration
+    pub fn for_tenant(&self, tenant_id: &str) -> Self {
+        Self::from_config(self.config.for_tenant(tenant_id))
+    }
+
+    /// Enrichment data for a tenant's orders: its configured environment, if any
+    pub fn enrichment_data(&self, tenant_id: &str) -> EnrichmentData {
+        EnrichmentData {
+            business: self.config.tenant_environment(tenant_id).map(|environment| BusinessMetadata {
+                environment: Some(environment.to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Tags to add to a request before it is sent to NetBox: the default tags
+    /// and those of the enrichment's environment
+    pub fn request_tags(&self, enrichment: &EnrichmentData) -> Vec<String> {
+        self.request_tag_sources(enrichment).into_iter().map(|(tag, _)| tag).collect()
+    }
+
+    /// The request tags `tags` lacks, as a report of what enriching the request added
+    pub fn request_tags_report(&self, tags: &[String], enrichment: &EnrichmentData) -> EnrichmentReport {
+        let derived = self.request_tag_sources(enrichment);
+        let mut added: Vec<String> = derived.iter().map(|(tag, _)| tag.clone()).collect();
+        added.dedup();
+        EnrichmentReport {
+            tags_added: EnrichmentReport::added_tags(tags, &added, &derived),
+            ..Default::default()
         }
     }
 
+    /// Request tags with the source each was derived from
+    fn request_tag_sources(&self, enrichment: &EnrichmentData) -> Vec<(String, EnrichmentSource)> {
+        let mut tags: Vec<(String, EnrichmentSource)> = self
+            .config
+            .default_tags
+            .iter()
+            .map(|tag| (self.tag(tag), EnrichmentSource::DefaultTags))
+            .collect();
+        tags.extend(self.environment_tags(enrichment).into_iter().map(|tag| (tag, EnrichmentSource::Environment)));
+        tags
+    }
+
+    /// Tags of the enrichment's environment, prefixed
+    fn environment_tags(&self, enrichment: &EnrichmentData) -> Vec<String> {
+        let Some(environment) = enrichment.business.as_ref().and_then(|b| b.environment.as_deref()) else {
+            return Vec::new();
+        };
+        self.config
+            .environment_tags
+            .iter()
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case(environment.trim()))
+            .map(|(_, tags)| tags.iter().map(|tag| self.tag(tag)).collect())
+            .unwrap_or_default()
+    }
+
+    /// A derived tag with the configured prefix
+    fn tag(&self, tag: &str) -> String {
+        format!("{}{}", self.config.tag_prefix, tag)
+    }
+
+    /// Custom field name a business metadata field is stored under
+    fn custom_field<'a>(&'a self, field: &'a str) -> &'a str {
+        self.config.custom_fields.get(field).map_or(field, String::as_str)
+    }
+
     /// Enrich a NetBox site with computed fields and metadata
     pub fn enrich_site(
         &self,
-        mut site: NetBoxSite,
+        site: NetBoxSite,
         enrichment: &EnrichmentData,
     ) -> NetBoxSite {
+        self.enrich_site_with_report(site, enrichment).0
+    }
+
+    /// Enrich a NetBox site, reporting what was changed and where it came from
+    pub fn enrich_site_with_report(
+        &self,
+        mut site: NetBoxSite,
+        enrichment: &EnrichmentData,
+    ) -> (NetBoxSite, EnrichmentReport) {
+        let before = site.clone();
+
         // Add computed/derived fields
         self.add_computed_fields_site(&mut site, enrichment);
 
@@ -97,15 +474,53 @@ impl ObjectEnricher {
         // Add metadata/tags based on business logic
         self.add_business_tags_site(&mut site, enrichment);
 
-        site
+        let mut computed_fields = Vec::new();
+        computed_field(&mut computed_fields, "latitude", &before.latitude, &site.latitude, EnrichmentSource::Geographic);
+        computed_field(&mut computed_fields, "longitude", &before.longitude, &site.longitude, EnrichmentSource::Geographic);
+        // The description leads with the environment when there is one
+        let description_source = match enrichment.business.as_ref().and_then(|b| b.environment.as_ref()) {
+            Some(_) => EnrichmentSource::Environment,
+            None => EnrichmentSource::Geographic,
+        };
+        computed_field(&mut computed_fields, "description", &before.description, &site.description, description_source);
+        computed_field(&mut computed_fields, "facility", &before.facility, &site.facility, EnrichmentSource::Business);
+        computed_field(&mut computed_fields, "contact_name", &before.contact_name, &site.contact_name, EnrichmentSource::Contact);
+        computed_field(&mut computed_fields, "contact_email", &before.contact_email, &site.contact_email, EnrichmentSource::Contact);
+        computed_field(&mut computed_fields, "contact_phone", &before.contact_phone, &site.contact_phone, EnrichmentSource::Contact);
+
+        let report = EnrichmentReport {
+            tags_added: EnrichmentReport::added_tags(
+                before.tags.as_deref().unwrap_or_default(),
+                site.tags.as_deref().unwrap_or_default(),
+                &self.derived_tags(enrichment, true, site.status),
+            ),
+            custom_fields: EnrichmentReport::changed_custom_fields(
+                before.custom_fields.as_ref(),
+                site.custom_fields.as_ref(),
+                &self.derived_custom_fields(enrichment),
+            ),
+            computed_fields,
+        };
+        (site, report)
     }
 
     /// Enrich a NetBox device with computed fields and metadata
     pub fn enrich_device(
         &self,
-        mut device: NetBoxDevice,
+        device: NetBoxDevice,
         enrichment: &EnrichmentData,
     ) -> NetBoxDevice {
+        self.enrich_device_with_report(device, enrichment).0
+    }
+
+    /// Enrich a NetBox device, reporting what was changed and where it came from
+    pub fn enrich_device_with_report(
+        &self,
+        mut device: NetBoxDevice,
+        enrichment: &EnrichmentData,
+    ) -> (NetBoxDevice, EnrichmentReport) {
+        let before = device.clone();
+
         // Add computed/derived fields
         self.add_computed_fields_device(&mut device, enrichment);
 
@@ -115,7 +530,23 @@ impl ObjectEnricher {
         // Add metadata/tags based on business logic
         self.add_business_tags_device(&mut device, enrichment);
 
-        device
+        let mut computed_fields = Vec::new();
+        computed_field(&mut computed_fields, "asset_tag", &before.asset_tag, &device.asset_tag, EnrichmentSource::Business);
+
+        let report = EnrichmentReport {
+            tags_added: EnrichmentReport::added_tags(
+                before.tags.as_deref().unwrap_or_default(),
+                device.tags.as_deref().unwrap_or_default(),
+                &self.derived_tags(enrichment, false, None),
+            ),
+            custom_fields: EnrichmentReport::changed_custom_fields(
+                before.custom_fields.as_ref(),
+                device.custom_fields.as_ref(),
+                &self.derived_custom_fields(enrichment),
+            ),
+            computed_fields,
+        };
+        (device, report)
     }
 
     /// Add computed/derived fields to a site
@@ -123,10 +554,10 @@ impl ObjectEnricher {
         // Compute full address if we have geographic data
         if let Some(ref geo) = enrichment.geographic {
             if site.latitude.is_none() {
-                site.latitude = Some(geo.latitude);
+                site.latitude = geo.latitude;
             }
             if site.longitude.is_none() {
-                site.longitude = Some(geo.longitude);
+                site.longitude = geo.longitude;
             }
         }
 
@@ -156,17 +587,9 @@ impl ObjectEnricher {
     }
 
     /// Add computed/derived fields to a device
+    ///
+    /// Names are not invented here; see [`crate::business::naming`].
     fn add_computed_fields_device(&self, device: &mut NetBoxDevice, enrichment: &EnrichmentData) {
-        // Compute device name if missing and we have metadata
-        if device.name.is_none() {
-            if let Some(ref business) = enrichment.business {
-                if let Some(ref project) = business.project_code {
-                    // Generate device name from project code
-                    device.name = Some(format!("DEV-{}-{}", project, Utc::now().format("%Y%m%d")));
-                }
-            }
-        }
-
         // Add computed asset tag if missing
         if device.asset_tag.is_none() {
             if let Some(ref business) = enrichment.business {
@@ -182,10 +605,10 @@ impl ObjectEnricher {
         // Merge geographic data
         if let Some(ref geo) = enrichment.geographic {
             if site.latitude.is_none() {
-                site.latitude = Some(geo.latitude);
+                site.latitude = geo.latitude;
             }
             if site.longitude.is_none() {
-                site.longitude = Some(geo.longitude);
+                site.longitude = geo.longitude;
             }
         }
 
@@ -203,27 +626,11 @@ impl ObjectEnricher {
         }
 
         // Merge business metadata into custom fields
-        if let Some(ref business) = enrichment.business {
+        if enrichment.business.is_some() {
             let mut custom_fields = site.custom_fields.clone().unwrap_or_default();
-            
-            if let Some(ref cost_center) = business.cost_center {
-                custom_fields["cost_center"] = serde_json::Value::String(cost_center.clone());
-            }
-            if let Some(ref project) = business.project_code {
-                custom_fields["project_code"] = serde_json::Value::String(project.clone());
-            }
-            if let Some(ref env) = business.environment {
-                custom_fields["environment"] = serde_json::Value::String(env.clone());
-            }
-            if let Some(ref priority) = business.priority {
-                custom_fields["priority"] = serde_json::Value::String(priority.clone());
+            for (field, value, _) in self.derived_custom_fields(enrichment) {
+                custom_fields[field] = value;
             }
-
-            // Merge additional metadata
-            for (key, value) in &enrichment.metadata {
-                custom_fields[key] = serde_json::Value::String(value.clone());
-            }
-
             site.custom_fields = Some(custom_fields);
         }
     }
@@ -231,114 +638,128 @@ impl ObjectEnricher {
     /// Merge enrichment data from multiple sources into a device
     fn merge_enrichment_data_device(&self, device: &mut NetBoxDevice, enrichment: &EnrichmentData) {
         // Merge business metadata into custom fields
-        if let Some(ref business) = enrichment.business {
+        if enrichment.business.is_some() {
             let mut custom_fields = device.custom_fields.clone().unwrap_or_default();
-            
-            if let Some(ref cost_center) = business.cost_center {
-                custom_fields["cost_center"] = serde_json::Value::String(cost_center.clone());
-            }
-            if let Some(ref project) = business.project_code {
-                custom_fields["project_code"] = serde_json::Value::String(project.clone());
-            }
-            if let Some(ref env) = business.environment {
-                custom_fields["environment"] = serde_json::Value::String(env.clone());
-            }
-            if let Some(ref priority) = business.priority {
-                custom_fields["priority"] = serde_json::Value::String(priority.clone());
+            for (field, value, _) in self.derived_custom_fields(enrichment) {
+                custom_fields[field] = value;
             }
+            device.custom_fields = Some(custom_fields);
+        }
+    }
 
-            // Merge additional metadata
-            for (key, value) in &enrichment.metadata {
-                custom_fields[key] = serde_json::Value::String(value.clone());
+    /// Custom fields the enrichment sets, with their sources, in the order they are applied
+    ///
+    /// Only enrichments carrying business metadata set custom fields.
+    fn derived_custom_fields(&self, enrichment: &EnrichmentData) -> Vec<(String, serde_json::Value, EnrichmentSource)> {
+        let Some(ref business) = enrichment.business else {
+            return Vec::new();
+        };
+        let mut fields = Vec::new();
+        for (field, value, source) in [
+            ("cost_center", &business.cost_center, EnrichmentSource::Business),
+            ("project_code", &business.project_code, EnrichmentSource::Business),
+            ("environment", &business.environment, EnrichmentSource::Environment),
+            ("priority", &business.priority, EnrichmentSource::Business),
+        ] {
+            if let Some(value) = value {
+                fields.push((self.custom_field(field).to_string(), serde_json::Value::String(value.clone()), source));
             }
+        }
 
-            device.custom_fields = Some(custom_fields);
+        // Merge additional metadata
+        for (key, value) in &enrichment.metadata {
+            fields.push((key.clone(), serde_json::Value::String(value.clone()), EnrichmentSource::Metadata));
         }
+        fields
     }
 
     /// Add business logic-based tags to a site
     fn add_business_tags_site(&self, site: &mut NetBoxSite, enrichment: &EnrichmentData) {
         let mut tags = site.tags.clone().unwrap_or_default();
+        tags.extend(self.derived_tags(enrichment, true, site.status).into_iter().map(|(tag, _)| tag));
 
-        // Add default tags
-        tags.extend(self.default_tags.clone());
+        // Deduplicate and sort
+        tags.sort();
+        tags.dedup();
+        site.tags = Some(tags);
+    }
 
-        // Add environment-based tags
-        if let Some(ref business) = enrichment.business {
-            if let Some(ref env) = business.environment {
-                if let Some(env_tags) = self.environment_tags.get(env) {
-                    tags.extend(env_tags.clone());
-                }
-            }
+    /// Tags the enrichment derives, with the source of each
+    ///
+    /// Geographic tags are only derived for objects that have a location of
+    /// their own, i.e. sites.
+    fn derived_tags(
+        &self,
+        enrichment: &EnrichmentData,
+        geographic: bool,
+        status: Option<SiteStatus>,
+    ) -> Vec<(String, EnrichmentSource)> {
+        // Add default and environment-based tags
+        let mut tags = self.request_tag_sources(enrichment);
 
+        if let Some(ref business) = enrichment.business {
             // Add priority-based tags
             if let Some(ref priority) = business.priority {
-                tags.push(format!("priority-{}", priority.to_lowercase()));
+                tags.push((self.tag(&format!("priority-{}", priority.to_lowercase())), EnrichmentSource::Business));
             }
 
             // Add cost center tag
             if let Some(ref cost_center) = business.cost_center {
-                tags.push(format!("cost-center-{}", cost_center.to_lowercase()));
+                tags.push((self.tag(&format!("cost-center-{}", cost_center.to_lowercase())), EnrichmentSource::Business));
             }
         }
 
         // Add geographic tags
-        if let Some(ref geo) = enrichment.geographic {
+        if let Some(geo) = enrichment.geographic.as_ref().filter(|_| geographic) {
             if let Some(ref country) = geo.country {
-                tags.push(format!("country-{}", country.to_lowercase()));
+                tags.push((self.tag(&format!("country-{}", country.to_lowercase())), EnrichmentSource::Geographic));
             }
             if let Some(ref region) = geo.region {
-                tags.push(format!("region-{}", region.to_lowercase()));
+                tags.push((self.tag(&format!("region-{}", region.to_lowercase())), EnrichmentSource::Geographic));
             }
         }
 
         // Add enrichment tags
-        tags.extend(enrichment.tags.clone());
+        tags.extend(enrichment.tags.iter().map(|tag| (tag.clone(), EnrichmentSource::ExtraTags)));
 
         // Add status-based tags
-        if let Some(ref status) = site.status {
-            match status {
-                SiteStatus::Active => tags.push("status-active".to_string()),
-                SiteStatus::Planned => tags.push("status-planned".to_string()),
-                SiteStatus::Retired => tags.push("status-retired".to_string()),
-                SiteStatus::Staging => tags.push("status-staging".to_string()),
-            }
+        if let Some(status) = status {
+            tags.push((self.status_tag(status), EnrichmentSource::Status));
         }
+        tags
+    }
 
-        // Deduplicate and sort
+    /// Tag marking a site's status, e.g. `status-retired`
+    fn status_tag(&self, status: SiteStatus) -> String {
+        let status = match status {
+            SiteStatus::Active => "active",
+            SiteStatus::Planned => "planned",
+            SiteStatus::Retired => "retired",
+            SiteStatus::Staging => "staging",
+        };
+        self.tag(&format!("status-{}", status))
+    }
+
+    /// Tags to store on a site after an update changed its tags or status
+    ///
+    /// Starts from `base`, drops any stale status tag, and adds the default
+    /// tags plus the tag for `status`.
+    pub fn derive_site_tags(&self, base: Vec<String>, status: Option<SiteStatus>) -> Vec<String> {
+        let status_prefix = self.tag("status-");
+        let mut tags: Vec<String> = base.into_iter().filter(|tag| !tag.starts_with(&status_prefix)).collect();
+        tags.extend(self.config.default_tags.iter().map(|tag| self.tag(tag)));
+        if let Some(status) = status {
+            tags.push(self.status_tag(status));
+        }
         tags.sort();
         tags.dedup();
-        site.tags = Some(tags);
+        tags
     }
 
     /// Add business logic-based tags to a device
     fn add_business_tags_device(&self, device: &mut NetBoxDevice, enrichment: &EnrichmentData) {
         let mut tags = device.tags.clone().unwrap_or_default();
-
-        // Add default tags
-        tags.extend(self.default_tags.clone());
-
-        // Add environment-based tags
-        if let Some(ref business) = enrichment.business {
-            if let Some(ref env) = business.environment {
-                if let Some(env_tags) = self.environment_tags.get(env) {
-                    tags.extend(env_tags.clone());
-                }
-            }
-
-            // Add priority-based tags
-            if let Some(ref priority) = business.priority {
-                tags.push(format!("priority-{}", priority.to_lowercase()));
-            }
-
-            // Add cost center tag
-            if let Some(ref cost_center) = business.cost_center {
-                tags.push(format!("cost-center-{}", cost_center.to_lowercase()));
-            }
-        }
-
-        // Add enrichment tags
-        tags.extend(enrichment.tags.clone());
+        tags.extend(self.derived_tags(enrichment, false, None).into_iter().map(|(tag, _)| tag));
 
         // Deduplicate and sort
         tags.sort();
@@ -399,6 +820,7 @@ impl ObjectEnricher {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
     use std::collections::HashMap;
 
     fn create_test_site() -> NetBoxSite {
@@ -463,8 +885,8 @@ mod tests {
 
         let enrichment = EnrichmentData {
             geographic: Some(GeographicData {
-                latitude: 40.7128,
-                longitude: -74.0060,
+                latitude: Some(40.7128),
+                longitude: Some(-74.0060),
                 timezone: Some("America/New_York".to_string()),
                 country: Some("USA".to_string()),
                 region: Some("North America".to_string()),
@@ -549,8 +971,8 @@ mod tests {
                 ..Default::default()
             }),
             geographic: Some(GeographicData {
-                latitude: 0.0,
-                longitude: 0.0,
+                latitude: Some(0.0),
+                longitude: Some(0.0),
                 country: Some("USA".to_string()),
                 ..Default::default()
             }),
@@ -582,9 +1004,8 @@ mod tests {
 
         let enriched = enricher.enrich_device(device, &enrichment);
 
-        // Check computed name
-        assert!(enriched.name.is_some());
-        assert!(enriched.name.as_ref().unwrap().starts_with("DEV-PROJ-ABC-"));
+        // Names come from the naming policy, not enrichment
+        assert!(enriched.name.is_none());
 
         // Check computed asset tag
         assert_eq!(enriched.asset_tag, Some("AT-CC-789".to_string()));
@@ -649,8 +1070,8 @@ mod tests {
     fn test_merge_enrichment_sources() {
         let source1 = EnrichmentData {
             geographic: Some(GeographicData {
-                latitude: 40.7128,
-                longitude: -74.0060,
+                latitude: Some(40.7128),
+                longitude: Some(-74.0060),
                 timezone: None,
                 country: Some("USA".to_string()),
                 region: None,
@@ -709,8 +1130,8 @@ mod tests {
 
         let enrichment = EnrichmentData {
             geographic: Some(GeographicData {
-                latitude: 40.7128,
-                longitude: -74.0060,
+                latitude: Some(40.7128),
+                longitude: Some(-74.0060),
                 timezone: None,
                 country: None,
                 region: None,
@@ -756,7 +1177,7 @@ mod tests {
     }
 
     #[test]
-    fn test_enrich_device_computed_name() {
+    fn test_enrich_device_leaves_name_to_naming_policy() {
         let enricher = ObjectEnricher::new();
         let mut device = create_test_device();
         device.name = None;
@@ -771,9 +1192,7 @@ mod tests {
 
         let enriched = enricher.enrich_device(device, &enrichment);
 
-        assert!(enriched.name.is_some());
-        let name = enriched.name.unwrap();
-        assert!(name.starts_with("DEV-TEST-PROJ-"));
+        assert!(enriched.name.is_none());
     }
 
     #[test]
@@ -790,5 +1209,193 @@ mod tests {
         assert!(tags.contains(&"netgate".to_string()));
         assert!(tags.contains(&"enriched".to_string()));
     }
-}
 
+    /// Check a report against the object's fields before and after enriching
+    fn assert_report_matches(report: &EnrichmentReport, before: &NetBoxSite, after: &NetBoxSite) {
+        let before_tags = before.tags.clone().unwrap_or_default();
+        let mut added: Vec<&String> = after.tags.iter().flatten().filter(|tag| !before_tags.contains(tag)).collect();
+        let mut reported: Vec<&String> = report.tags_added.iter().map(|applied| &applied.tag).collect();
+        added.sort();
+        reported.sort();
+        assert_eq!(added, reported);
+
+        for applied in &report.custom_fields {
+            assert_eq!(after.custom_fields.as_ref().unwrap()[&applied.field], applied.value);
+        }
+        let after_json = serde_json::to_value(after).unwrap();
+        let before_json = serde_json::to_value(before).unwrap();
+        for applied in &report.computed_fields {
+            assert_eq!(after_json[&applied.field], applied.value);
+            assert_ne!(before_json[&applied.field], applied.value);
+        }
+    }
+
+    #[test]
+    fn test_enrichment_report_matches_mutations() {
+        let enricher = ObjectEnricher::from_config(EnrichmentConfig {
+            custom_fields: HashMap::from([("cost_center".to_string(), "finance_code".to_string())]),
+            ..Default::default()
+        });
+        let full = EnrichmentData {
+            geographic: Some(GeographicData {
+                latitude: Some(52.37),
+                longitude: Some(4.89),
+                country: Some("NL".to_string()),
+                ..Default::default()
+            }),
+            contact: Some(ContactData { email: Some("noc@example.com".to_string()), ..Default::default() }),
+            business: Some(BusinessMetadata {
+                cost_center: Some("cc1".to_string()),
+                environment: Some("production".to_string()),
+                ..Default::default()
+            }),
+            tags: vec!["imported".to_string()],
+            metadata: HashMap::from([("owner".to_string(), "ops".to_string())]),
+        };
+
+        // Everything enriched on a bare site
+        let site = create_test_site();
+        let (enriched, report) = enricher.enrich_site_with_report(site.clone(), &full);
+        assert_report_matches(&report, &site, &enriched);
+        let source_of = |tag: &str| report.tags_added.iter().find(|applied| applied.tag == tag).map(|applied| applied.source);
+        assert_eq!(source_of("netgate"), Some(EnrichmentSource::DefaultTags));
+        assert_eq!(source_of("prod"), Some(EnrichmentSource::Environment));
+        assert_eq!(source_of("cost-center-cc1"), Some(EnrichmentSource::Business));
+        assert_eq!(source_of("country-nl"), Some(EnrichmentSource::Geographic));
+        assert_eq!(source_of("imported"), Some(EnrichmentSource::ExtraTags));
+        assert_eq!(source_of("status-active"), Some(EnrichmentSource::Status));
+        assert_eq!(
+            report.custom_fields,
+            vec![
+                AppliedField { field: "environment".to_string(), value: json!("production"), source: EnrichmentSource::Environment },
+                AppliedField { field: "finance_code".to_string(), value: json!("cc1"), source: EnrichmentSource::Business },
+                AppliedField { field: "owner".to_string(), value: json!("ops"), source: EnrichmentSource::Metadata },
+            ]
+        );
+        let computed: Vec<(&str, EnrichmentSource)> =
+            report.computed_fields.iter().map(|applied| (applied.field.as_str(), applied.source)).collect();
+        assert_eq!(
+            computed,
+            vec![
+                ("latitude", EnrichmentSource::Geographic),
+                ("longitude", EnrichmentSource::Geographic),
+                ("description", EnrichmentSource::Environment),
+                ("facility", EnrichmentSource::Business),
+                ("contact_email", EnrichmentSource::Contact),
+            ]
+        );
+
+        // Values and tags the site already has are not reported
+        let mut site = enriched.clone();
+        site.custom_fields.as_mut().unwrap()["owner"] = json!("netops");
+        let (enriched, report) = enricher.enrich_site_with_report(site.clone(), &full);
+        assert_report_matches(&report, &site, &enriched);
+        assert!(report.tags_added.is_empty());
+        assert!(report.computed_fields.is_empty());
+        assert_eq!(report.custom_fields.len(), 1);
+        assert_eq!(report.custom_fields[0].value, json!("ops"));
+
+        // Without business metadata only tags are applied; the description comes from geography
+        let geographic = EnrichmentData { geographic: full.geographic.clone(), ..Default::default() };
+        let site = create_test_site();
+        let (enriched, report) = enricher.enrich_site_with_report(site.clone(), &geographic);
+        assert_report_matches(&report, &site, &enriched);
+        assert!(report.custom_fields.is_empty());
+        assert_eq!(report.computed_fields[2].field, "description");
+        assert_eq!(report.computed_fields[2].source, EnrichmentSource::Geographic);
+
+        // Devices get no geographic tags; their computed asset tag comes from the cost center
+        let (device, report) = enricher.enrich_device_with_report(create_test_device(), &full);
+        assert!(!device.tags.as_ref().unwrap().contains(&"country-nl".to_string()));
+        assert_eq!(report.tags_added.len(), device.tags.as_ref().unwrap().len());
+        assert_eq!(
+            report.computed_fields,
+            vec![AppliedField { field: "asset_tag".to_string(), value: json!("AT-cc1"), source: EnrichmentSource::Business }]
+        );
+    }
+
+    #[test]
+    fn test_request_tags_report_skips_present_tags() {
+        let enricher = ObjectEnricher::new();
+        let enrichment = enricher.enrichment_data("tenant1");
+
+        let report = enricher.request_tags_report(&["netgate".to_string()], &enrichment);
+
+        assert_eq!(
+            report.tags_added,
+            vec![AppliedTag { tag: "enriched".to_string(), source: EnrichmentSource::DefaultTags }]
+        );
+    }
+
+    #[test]
+    fn test_tenant_overrides_take_precedence() {
+        let config = EnrichmentConfig::from_yaml(
+            r#"
+default_tags: [netgate]
+environment_tags:
+  production: [prod]
+  staging: [stage]
+tag_prefix: ng-
+custom_fields:
+  cost_center: finance_code
+tenants:
+  acme:
+    environment: production
+    default_tags: [acme-managed]
+    environment_tags:
+      Production: [acme-prod]
+    tag_prefix: ""
+    custom_fields:
+      priority: acme_priority
+"#,
+        )
+        .unwrap();
+
+        let acme = config.for_tenant("acme");
+        assert_eq!(acme.default_tags, vec!["acme-managed"]);
+        assert_eq!(acme.environment_tags.get("Production"), Some(&vec!["acme-prod".to_string()]));
+        assert!(!acme.environment_tags.contains_key("production"));
+        assert_eq!(acme.environment_tags.get("staging"), Some(&vec!["stage".to_string()]));
+        assert_eq!(acme.tag_prefix, "");
+        assert_eq!(acme.custom_fields.len(), 2);
+        assert_eq!(config.for_tenant("other"), EnrichmentConfig { tenants: HashMap::new(), ..config.clone() });
+
+        let enricher = ObjectEnricher::from_config(config);
+        let enrichment = EnrichmentData {
+            business: Some(BusinessMetadata {
+                cost_center: Some("CC-1".to_string()),
+                priority: Some("high".to_string()),
+                ..enricher.enrichment_data("acme").business.unwrap()
+            }),
+            ..Default::default()
+        };
+        let site = enricher.for_tenant("acme").enrich_site(create_test_site(), &enrichment);
+        assert_eq!(
+            site.tags.unwrap(),
+            vec!["acme-managed", "acme-prod", "cost-center-cc-1", "priority-high", "status-active"]
+        );
+        let custom_fields = site.custom_fields.unwrap();
+        assert_eq!(custom_fields["finance_code"], "CC-1");
+        assert_eq!(custom_fields["acme_priority"], "high");
+        assert_eq!(custom_fields["environment"], "production");
+
+        // Other tenants get the global taxonomy, prefixed
+        let site = enricher.for_tenant("other").enrich_site(create_test_site(), &enrichment);
+        assert!(site.tags.unwrap().contains(&"ng-prod".to_string()));
+        assert!(enricher.enrichment_data("other").business.is_none());
+    }
+
+    #[test]
+    fn test_invalid_enrichment_config_is_rejected() {
+        let invalid = |yaml: &str| EnrichmentConfig::from_yaml(yaml).unwrap_err().to_string();
+
+        assert!(invalid("default_tags: [netgate, \" \"]").contains("Empty tag name in the enrichment config"));
+        assert!(invalid("environment_tags:\n  Production: [prod]\n  production: [live]")
+            .contains("Environment 'production' is listed more than once"));
+        assert!(invalid("tenants:\n  acme:\n    environment_tags:\n      staging: ['']")
+            .contains("Empty tag name in the enrichment overrides of tenant acme"));
+        assert!(invalid("custom_fields:\n  budget: cc").contains("Unknown business metadata field 'budget'"));
+        assert!(invalid("enviroment_tags: {}").contains("unknown field"));
+        assert_eq!(EnrichmentConfig::from_yaml("{}").unwrap(), EnrichmentConfig::default());
+    }
+}
@@ -1,5 +1,7 @@
+use crate::business::order_service::roll_back_resources;
+use crate::business::payload_schema::validate_payload;
 use crate::business::plugin::{NetBoxResource, OrderPayload, OrderProcessor, OrderTypeRegistry};
-use crate::business::{EnrichmentData, OrderState, WorkflowManager};
+use crate::business::{EnrichmentData, OrderState, ValidationError, WorkflowManager};
 use crate::error::AppError;
 use crate::netbox::ResilientNetBoxClient;
 use crate::security::TenantId;
@@ -48,7 +50,7 @@ impl ExtensibleOrderService {
 
         // Step 1: Validate the order
         debug!("Validating {} order", order_type);
-        processor.validate(&order)?;
+        processor.validate_for_tenant(&order, &tenant_id)?;
 
         // Step 2: Create workflow entry
         debug!("Creating workflow");
@@ -61,7 +63,7 @@ impl ExtensibleOrderService {
 
         // Step 4: Transform order to NetBox request
         debug!("Transforming order {} to NetBox request", order_id);
-        let mut netbox_request = processor.transform(order, None)?;
+        let mut netbox_request = processor.transform_for_tenant(order, &tenant_id)?;
 
         // Step 5: Enrich the NetBox request
         debug!("Enriching NetBox request for order {}", order_id);
@@ -72,9 +74,14 @@ impl ExtensibleOrderService {
         self.workflow_manager.update_order_state(&order_id, OrderState::Processing)
             .map_err(|e| AppError::Internal(anyhow::anyhow!("Workflow error: {}", e)))?;
 
-        // Step 7: Create resource in NetBox
+        // Step 7: Create resource in NetBox, recording everything created for rollback
         debug!("Creating resource in NetBox for order {}", order_id);
-        let netbox_resource = match processor.create_resource(&self.netbox_client, netbox_request).await {
+        let mut created = Vec::new();
+        let result = processor.create_resources(&self.netbox_client, netbox_request, &mut created).await;
+        for resource in &created {
+            let _ = self.workflow_manager.record_created_resource(&order_id, resource.kind, resource.id);
+        }
+        let netbox_resource = match result {
             Ok(resource) => {
                 // Step 8: Enrich the created resource
                 let enriched_resource = processor.enrich_resource(resource, &enrichment_data);
@@ -91,8 +98,14 @@ impl ExtensibleOrderService {
             Err(e) => {
                 error!("Failed to create resource in NetBox for order {}: {}", order_id, e);
 
-                // Mark workflow as failed
-                let _ = self.workflow_manager.mark_order_failed(&order_id, e.to_string());
+                // Undo what was created before the failure, or just fail the workflow
+                if created.is_empty() {
+                    let _ = self.workflow_manager.mark_order_failed(&order_id, e.to_string());
+                } else if let Err(rollback_error) =
+                    roll_back_resources(&self.workflow_manager, &self.netbox_client, &order_id, e.to_string()).await
+                {
+                    error!("Failed to roll back order {}: {}", order_id, rollback_error);
+                }
 
                 return Err(e);
             }
@@ -110,6 +123,29 @@ impl ExtensibleOrderService {
         })
     }
 
+    /// Process a JSON payload for a registered order type
+    ///
+    /// The payload is checked against the type's schema, then parsed by its
+    /// processor before it enters the pipeline.
+    pub async fn process_json_order(
+        &self,
+        order_type: &str,
+        data: serde_json::Value,
+        tenant_id: TenantId,
+    ) -> Result<ProcessedOrderResult, AppError> {
+        let processor = self.registry
+            .get_processor(order_type)
+            .ok_or_else(|| AppError::NotFound(format!("Order type {} is not registered", order_type)))?;
+        let violations = validate_payload(&processor.payload_schema(), &data);
+        if !violations.is_empty() {
+            let error = ValidationError::PayloadSchema { order_type: order_type.to_string(), violations };
+            return Err(AppError::ValidationError(error.to_string()));
+        }
+        let order = processor.parse_payload(data)?;
+
+        self.process_order(order, tenant_id, Some(order_type)).await
+    }
+
     /// Get order status by order ID
     pub async fn get_order_status(
         &self,
@@ -173,15 +209,23 @@ impl ExtensibleOrderServiceBuilder {
     }
 
     /// Register a processor
-    pub fn with_processor(mut self, processor: Arc<dyn OrderProcessor>) -> Self {
+    pub fn with_processor(self, processor: Arc<dyn OrderProcessor>) -> Self {
         self.registry.register(processor);
         self
     }
 
-    /// Register the default site processor
-    pub fn with_default_processors(mut self) -> Self {
-        use crate::business::processors::SiteOrderProcessor;
+    /// Register the default site, device, network, VLAN, IP address, and VM processors
+    pub fn with_default_processors(self) -> Self {
+        use crate::business::processors::{
+            DeviceOrderProcessor, IpAllocationOrderProcessor, NetworkOrderProcessor, SiteOrderProcessor,
+            VirtualMachineOrderProcessor, VlanOrderProcessor,
+        };
         self.registry.register(Arc::new(SiteOrderProcessor::new()));
+        self.registry.register(Arc::new(DeviceOrderProcessor::new()));
+        self.registry.register(Arc::new(NetworkOrderProcessor::new()));
+        self.registry.register(Arc::new(VlanOrderProcessor::new()));
+        self.registry.register(Arc::new(IpAllocationOrderProcessor::new()));
+        self.registry.register(Arc::new(VirtualMachineOrderProcessor::new()));
         self
     }
 
@@ -208,15 +252,19 @@ impl Default for ExtensibleOrderServiceBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::business::plugin::NetBoxResourceRequest;
     use crate::config::Config;
+    use crate::netbox::models::CreateSiteRequest;
     use crate::netbox::client::NetBoxClient;
     use crate::netbox::resilient_client::ResilientNetBoxClient;
+    use crate::security::tenant::TenantAccessControl;
 
     fn create_test_netbox_client() -> Arc<ResilientNetBoxClient> {
         let config = Config {
             port: 8080,
             netbox_url: "http://localhost:8000".to_string(),
             netbox_token: "test-token".to_string(),
+            ..Default::default()
         };
         let client = Arc::new(NetBoxClient::new(config).unwrap());
         Arc::new(ResilientNetBoxClient::new(client))
@@ -251,5 +299,804 @@ mod tests {
             _ => panic!("Expected NotFound error"),
         }
     }
-}
 
+    /// Processor for "lab" orders, registered at runtime, that creates a tagged site
+    struct LabOrderProcessor;
+
+    #[async_trait::async_trait]
+    impl OrderProcessor for LabOrderProcessor {
+        fn order_type(&self) -> &'static str {
+            "lab"
+        }
+
+        fn payload_schema(&self) -> serde_json::Value {
+            serde_json::json!({ "type": "object", "required": ["lab_name"] })
+        }
+
+        fn validate(&self, order: &OrderPayload) -> Result<(), AppError> {
+            match order {
+                OrderPayload::Custom { data, .. } if data["lab_name"].is_string() => Ok(()),
+                _ => Err(AppError::ValidationError("lab_name is required".to_string())),
+            }
+        }
+
+        fn transform(
+            &self,
+            order: OrderPayload,
+            tenant_id: Option<i32>,
+        ) -> Result<NetBoxResourceRequest, AppError> {
+            let OrderPayload::Custom { data, .. } = order else {
+                return Err(AppError::ValidationError("Expected a lab order".to_string()));
+            };
+            Ok(NetBoxResourceRequest::Site(CreateSiteRequest {
+                name: format!("lab-{}", data["lab_name"].as_str().unwrap_or_default()),
+                slug: None,
+                description: None,
+                status: None,
+                region: None,
+                tenant: tenant_id,
+                facility: None,
+                physical_address: None,
+                shipping_address: None,
+                latitude: None,
+                longitude: None,
+                contact_name: None,
+                contact_phone: None,
+                contact_email: None,
+                comments: None,
+                tags: Some(vec!["lab".to_string()]),
+                custom_fields: None,
+            }))
+        }
+
+        fn enrich_request(
+            &self,
+            _request: &mut NetBoxResourceRequest,
+            _enrichment_data: &EnrichmentData,
+        ) -> Result<(), AppError> {
+            Ok(())
+        }
+
+        async fn create_resource(
+            &self,
+            client: &Arc<ResilientNetBoxClient>,
+            request: NetBoxResourceRequest,
+        ) -> Result<NetBoxResource, AppError> {
+            match request {
+                NetBoxResourceRequest::Site(site_request) => {
+                    Ok(NetBoxResource::Site(client.create_site(site_request).await?))
+                }
+                _ => Err(AppError::ValidationError("Expected a site request".to_string())),
+            }
+        }
+
+        fn enrich_resource(
+            &self,
+            resource: NetBoxResource,
+            _enrichment_data: &EnrichmentData,
+        ) -> NetBoxResource {
+            resource
+        }
+    }
+
+    #[tokio::test]
+    async fn test_runtime_registered_processor_end_to_end() {
+        use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/dcim/sites/"))
+            .and(body_partial_json(serde_json::json!({ "name": "lab-berlin", "tags": ["lab"] })))
+            .respond_with(
+                ResponseTemplate::new(201).set_body_json(serde_json::json!({ "id": 7, "name": "lab-berlin" })),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let config = Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let netbox_client = Arc::new(ResilientNetBoxClient::new(Arc::new(NetBoxClient::new(config).unwrap())));
+        let workflow_manager = Arc::new(WorkflowManager::new());
+        let service = ExtensibleOrderServiceBuilder::new()
+            .with_default_processors()
+            .build(workflow_manager.clone(), netbox_client);
+
+        service.registry().register_processor(Box::new(LabOrderProcessor));
+        let types: Vec<String> = service
+            .registry()
+            .list_registered_types()
+            .into_iter()
+            .map(|t| t.order_type)
+            .collect();
+        assert_eq!(types, vec!["device", "ip_address", "lab", "network", "site", "virtual_machine", "vlan"]);
+
+        let result = service
+            .process_json_order("lab", serde_json::json!({ "lab_name": "berlin" }), "tenant1".to_string())
+            .await
+            .unwrap();
+        assert_eq!(result.workflow_state, OrderState::Completed);
+        assert_eq!(result.netbox_resource.resource_id(), Some(7));
+        assert_eq!(workflow_manager.get_order(&result.order_id).unwrap().netbox_site_id, Some(7));
+
+        let invalid = service.process_json_order("lab", serde_json::json!({}), "tenant1".to_string()).await;
+        assert!(matches!(invalid, Err(AppError::ValidationError(_))));
+
+        assert!(service.registry().unregister("lab"));
+        let unregistered = service
+            .process_json_order("lab", serde_json::json!({ "lab_name": "berlin" }), "tenant1".to_string())
+            .await;
+        assert!(matches!(unregistered, Err(AppError::NotFound(_))));
+    }
+
+    fn create_mock_service(mock_server: &wiremock::MockServer) -> (ExtensibleOrderService, Arc<WorkflowManager>) {
+        let config = Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let netbox_client = Arc::new(ResilientNetBoxClient::new(Arc::new(NetBoxClient::new(config).unwrap())));
+        let workflow_manager = Arc::new(WorkflowManager::new());
+        let service = ExtensibleOrderServiceBuilder::new()
+            .with_default_processors()
+            .build(workflow_manager.clone(), netbox_client);
+        (service, workflow_manager)
+    }
+
+    #[tokio::test]
+    async fn test_device_order_end_to_end_resolves_site_slug() {
+        use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .and(query_param("slug", "dc1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "count": 1,
+                "results": [{ "id": 10, "name": "DC1", "slug": "dc1" }]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/dcim/devices/"))
+            .and(body_partial_json(serde_json::json!({
+                "name": "sw1",
+                "device_type": 3,
+                "device_role": 2,
+                "site": 10,
+                "tags": ["netgate", "enriched"]
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({ "id": 55, "name": "sw1" })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let (service, workflow_manager) = create_mock_service(&mock_server);
+
+        let result = service
+            .process_json_order(
+                "device",
+                serde_json::json!({ "name": "sw1", "device_type": 3, "role": 2, "site_slug": "dc1" }),
+                "tenant1".to_string(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.workflow_state, OrderState::Completed);
+        assert_eq!(result.netbox_resource.resource_type(), "device");
+        assert_eq!(result.netbox_resource.resource_id(), Some(55));
+        assert_eq!(workflow_manager.get_order(&result.order_id).unwrap().state, OrderState::Completed);
+    }
+
+    #[tokio::test]
+    async fn test_device_order_fails_for_unknown_site_slug() {
+        use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "count": 0, "results": [] })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+        let (service, _) = create_mock_service(&mock_server);
+
+        let result = service
+            .process_json_order(
+                "device",
+                serde_json::json!({ "name": "sw1", "device_type": 3, "role": 2, "site_slug": "nowhere" }),
+                "tenant1".to_string(),
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::ValidationError(msg)) if msg.contains("nowhere")));
+    }
+
+    #[tokio::test]
+    async fn test_network_order_end_to_end() {
+        use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/ipam/prefixes/"))
+            .and(body_partial_json(serde_json::json!({ "prefix": "10.20.0.0/24", "vlan": 100, "status": "active" })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "id": 8,
+                "prefix": "10.20.0.0/24",
+                "vlan": 100
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let (service, _) = create_mock_service(&mock_server);
+
+        let result = service
+            .process_json_order(
+                "network",
+                serde_json::json!({ "prefix": "10.20.0.0/24", "vlan": 100 }),
+                "tenant1".to_string(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.workflow_state, OrderState::Completed);
+        assert_eq!(result.netbox_resource.resource_type(), "prefix");
+        assert_eq!(result.netbox_resource.resource_id(), Some(8));
+
+        let invalid = service
+            .process_json_order("network", serde_json::json!({ "prefix": "10.20.0.1/24" }), "tenant1".to_string())
+            .await;
+        assert!(matches!(invalid, Err(AppError::ValidationError(msg)) if msg.contains("host bits")));
+    }
+
+    #[tokio::test]
+    async fn test_device_order_resolves_catalog_names() {
+        use crate::business::catalog::DeviceCatalog;
+        use crate::business::processors::DeviceOrderProcessor;
+        use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/device-types/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "results": [
+                { "id": 4, "model": "C9300-48P", "slug": "cisco-c9300-48p" }
+            ]})))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/device-roles/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "results": [
+                { "id": 2, "name": "Leaf Switch", "slug": "leaf-switch" }
+            ]})))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/dcim/devices/"))
+            .and(body_partial_json(serde_json::json!({ "device_type": 4, "device_role": 2, "site": 10 })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({ "id": 55, "name": "sw1" })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let config = Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let netbox_client = Arc::new(ResilientNetBoxClient::new(Arc::new(NetBoxClient::new(config).unwrap())));
+        let catalog = Arc::new(DeviceCatalog::new(netbox_client.clone()));
+        let service = ExtensibleOrderServiceBuilder::new()
+            .with_processor(Arc::new(DeviceOrderProcessor::new().with_catalog(catalog)))
+            .build(Arc::new(WorkflowManager::new()), netbox_client);
+
+        let order = |device_type: &str| {
+            serde_json::json!({ "name": "sw1", "device_type": device_type, "role": "leaf-switch", "site_id": 10 })
+        };
+        let result = service.process_json_order("device", order("C9300-48P"), "tenant1".to_string()).await.unwrap();
+        assert_eq!(result.netbox_resource.resource_id(), Some(55));
+
+        let unknown = service.process_json_order("device", order("cisco-c9300-48"), "tenant1".to_string()).await;
+        assert!(
+            matches!(unknown, Err(AppError::ValidationError(ref msg)) if msg.ends_with("did you mean cisco-c9300-48p?")),
+            "{:?}",
+            unknown
+        );
+    }
+
+    fn connected_device_order() -> serde_json::Value {
+        serde_json::json!({
+            "name": "sw1",
+            "device_type": 3,
+            "role": 2,
+            "site_id": 10,
+            "interfaces": [
+                { "name": "eth0", "type": "10gbase-x-sfpp" },
+                { "name": "mgmt", "type": "1000base-t", "enabled": false }
+            ],
+            "uplinks": [{ "local_interface": "eth0", "remote_device": 77, "remote_interface": "xe-0/0/1" }]
+        })
+    }
+
+    async fn mount_connected_device_creates(mock_server: &wiremock::MockServer) {
+        use wiremock::{matchers::*, Mock, ResponseTemplate};
+
+        Mock::given(method("POST"))
+            .and(path("/api/dcim/devices/"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({ "id": 55, "name": "sw1" })))
+            .expect(1)
+            .mount(mock_server)
+            .await;
+        for (name, id) in [("eth0", 101), ("mgmt", 102)] {
+            Mock::given(method("POST"))
+                .and(path("/api/dcim/interfaces/"))
+                .and(body_partial_json(serde_json::json!({ "device": 55, "name": name })))
+                .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                    "id": id, "device": 55, "name": name
+                })))
+                .expect(1)
+                .mount(mock_server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/interfaces/"))
+            .and(query_param("device_id", "77"))
+            .and(query_param("name", "xe-0/0/1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "count": 1,
+                "results": [{ "id": 900, "device": 77, "name": "xe-0/0/1" }]
+            })))
+            .mount(mock_server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_device_order_creates_interfaces_and_uplinks() {
+        use crate::business::{CreatedResource, ResourceKind};
+        use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        mount_connected_device_creates(&mock_server).await;
+        Mock::given(method("POST"))
+            .and(path("/api/dcim/cables/"))
+            .and(body_partial_json(serde_json::json!({
+                "a_terminations": [{ "object_type": "dcim.interface", "object_id": 101 }],
+                "b_terminations": [{ "object_type": "dcim.interface", "object_id": 900 }]
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({ "id": 300 })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let (service, workflow_manager) = create_mock_service(&mock_server);
+
+        let result = service
+            .process_json_order("device", connected_device_order(), "tenant1".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(result.workflow_state, OrderState::Completed);
+        assert_eq!(result.netbox_resource.resource_id(), Some(55));
+        let resource = |kind, id| CreatedResource { kind, id };
+        assert_eq!(
+            workflow_manager.get_order(&result.order_id).unwrap().created_resources,
+            vec![
+                resource(ResourceKind::Device, 55),
+                resource(ResourceKind::Interface, 101),
+                resource(ResourceKind::Interface, 102),
+                resource(ResourceKind::Cable, 300),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_device_order_rolls_back_when_cable_fails() {
+        use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        mount_connected_device_creates(&mock_server).await;
+        Mock::given(method("POST"))
+            .and(path("/api/dcim/cables/"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "a_terminations": ["Interface eth0 is already cabled"]
+            })))
+            .mount(&mock_server)
+            .await;
+        for deleted in ["/api/dcim/interfaces/102/", "/api/dcim/interfaces/101/", "/api/dcim/devices/55/"] {
+            Mock::given(method("DELETE"))
+                .and(path(deleted))
+                .respond_with(ResponseTemplate::new(204))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+        let (service, workflow_manager) = create_mock_service(&mock_server);
+
+        let result = service
+            .process_json_order("device", connected_device_order(), "tenant1".to_string())
+            .await;
+        assert!(result.is_err());
+
+        let workflow = workflow_manager.get_tenant_orders("tenant1").pop().unwrap();
+        assert_eq!(workflow.state, OrderState::Failed);
+        let report = workflow.rollback_report.unwrap();
+        assert!(report.is_complete());
+        assert_eq!(report.entries.len(), 3);
+        assert!(workflow.error_message.unwrap().contains("rolled back 3 resource(s)"));
+    }
+
+    fn create_vlan_service(mock_server: &wiremock::MockServer) -> ExtensibleOrderService {
+        use crate::business::processors::VlanOrderProcessor;
+        use crate::business::vlan::{VidRange, VlanRanges};
+        use crate::security::tenant::{TenantAccessControl, TenantMappingService};
+
+        let config = Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let netbox_client = Arc::new(ResilientNetBoxClient::new(Arc::new(NetBoxClient::new(config).unwrap())));
+        let ranges = VlanRanges::new()
+            .with_range("tenant1", VidRange::new(100, 103).unwrap())
+            .with_range("tenant1", VidRange::new(300, 300).unwrap());
+        let mappings = std::collections::HashMap::from([("tenant1".to_string(), 10)]);
+        let processor = VlanOrderProcessor::new()
+            .with_ranges(ranges)
+            .with_access_control(Arc::new(TenantAccessControl::new(TenantMappingService::from(mappings))));
+        ExtensibleOrderServiceBuilder::new()
+            .with_processor(Arc::new(processor))
+            .build(Arc::new(WorkflowManager::new()), netbox_client)
+    }
+
+    #[tokio::test]
+    async fn test_vlan_order_enforces_tenant_range_and_checks_collisions() {
+        use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/ipam/vlans/"))
+            .and(query_param("site_id", "7"))
+            .and(query_param("vid", "101"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "results": [
+                { "id": 40, "vid": 101, "name": "users", "site": 7 }
+            ]})))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/ipam/vlans/"))
+            .and(query_param("site_id", "7"))
+            .and(query_param("vid", "300"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "results": [] })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/ipam/vlans/"))
+            .and(body_partial_json(serde_json::json!({ "vid": 300, "name": "voice", "site": 7, "tenant": 10 })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "id": 41, "vid": 300, "name": "voice", "site": 7, "tenant": 10
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let service = create_vlan_service(&mock_server);
+        let order = |vid: i32| serde_json::json!({ "site_id": 7, "name": "voice", "vid": vid });
+
+        let outside = service.process_json_order("vlan", order(200), "tenant1".to_string()).await;
+        assert!(
+            matches!(outside, Err(AppError::ValidationError(ref msg)) if msg.contains("(100-103, 300)")),
+            "{:?}",
+            outside
+        );
+
+        let taken = service.process_json_order("vlan", order(101), "tenant1".to_string()).await;
+        assert!(
+            matches!(taken, Err(AppError::Conflict(ref msg)) if msg.contains("already used at site 7 by 'users' (id 40)")),
+            "{:?}",
+            taken
+        );
+
+        let created = service.process_json_order("vlan", order(300), "tenant1".to_string()).await.unwrap();
+        assert_eq!(created.workflow_state, OrderState::Completed);
+        assert_eq!(created.netbox_resource.resource_type(), "vlan");
+        assert_eq!(created.netbox_resource.resource_id(), Some(41));
+    }
+
+    /// A service with one processor that maps tenant1 to NetBox tenant 10
+    fn create_tenant_service(
+        mock_server: &wiremock::MockServer,
+        processor: impl FnOnce(Arc<TenantAccessControl>) -> Arc<dyn OrderProcessor>,
+    ) -> (ExtensibleOrderService, Arc<WorkflowManager>) {
+        use crate::security::tenant::TenantMappingService;
+
+        let config = Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let netbox_client = Arc::new(ResilientNetBoxClient::new(Arc::new(NetBoxClient::new(config).unwrap())));
+        let mappings = std::collections::HashMap::from([("tenant1".to_string(), 10)]);
+        let processor = processor(Arc::new(TenantAccessControl::new(TenantMappingService::from(mappings))));
+        let workflow_manager = Arc::new(WorkflowManager::new());
+        let service = ExtensibleOrderServiceBuilder::new()
+            .with_processor(processor)
+            .build(workflow_manager.clone(), netbox_client);
+        (service, workflow_manager)
+    }
+
+    fn create_ip_service(mock_server: &wiremock::MockServer) -> (ExtensibleOrderService, Arc<WorkflowManager>) {
+        use crate::business::processors::IpAllocationOrderProcessor;
+
+        create_tenant_service(mock_server, |access_control| {
+            Arc::new(IpAllocationOrderProcessor::new().with_access_control(access_control))
+        })
+    }
+
+    fn create_vm_service(mock_server: &wiremock::MockServer) -> (ExtensibleOrderService, Arc<WorkflowManager>) {
+        use crate::business::processors::VirtualMachineOrderProcessor;
+
+        create_tenant_service(mock_server, |access_control| {
+            Arc::new(VirtualMachineOrderProcessor::new().with_access_control(access_control))
+        })
+    }
+
+    async fn mount_cluster(mock_server: &wiremock::MockServer, id: i32, tenant: Option<i32>) {
+        use wiremock::{matchers::*, Mock, ResponseTemplate};
+
+        Mock::given(method("GET"))
+            .and(path(format!("/api/virtualization/clusters/{}/", id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": id, "name": format!("cluster-{}", id), "type": 1, "site": 4, "tenant": tenant
+            })))
+            .mount(mock_server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_vm_order_creates_vm_at_cluster_site() {
+        use crate::business::{CreatedResource, ResourceKind};
+        use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        // A cluster without a tenant is shared by every tenant
+        mount_cluster(&mock_server, 3, None).await;
+        Mock::given(method("GET"))
+            .and(path("/api/virtualization/virtual-machines/"))
+            .and(query_param("cluster_id", "3"))
+            .and(query_param("name", "app01"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "results": [] })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/virtualization/virtual-machines/"))
+            .and(body_partial_json(serde_json::json!({
+                "name": "app01", "cluster": 3, "site": 4, "tenant": 10, "vcpus": 2.0, "memory": 4096, "disk": 40
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "id": 11, "name": "app01", "cluster": 3, "site": 4, "tenant": 10, "status": "active"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let (service, workflow_manager) = create_vm_service(&mock_server);
+        let order = serde_json::json!({ "name": "app01", "cluster": 3, "vcpus": 2, "memory": 4096, "disk": 40 });
+
+        let result = service.process_json_order("virtual_machine", order, "tenant1".to_string()).await.unwrap();
+
+        assert_eq!(result.workflow_state, OrderState::Completed);
+        assert_eq!(result.netbox_resource.resource_type(), "virtual_machine");
+        assert_eq!(result.netbox_resource.resource_id(), Some(11));
+        let workflow = workflow_manager.get_order(&result.order_id).unwrap();
+        assert_eq!(workflow.created_resources, vec![CreatedResource { kind: ResourceKind::VirtualMachine, id: 11 }]);
+    }
+
+    #[tokio::test]
+    async fn test_vm_order_refuses_foreign_cluster_and_taken_name() {
+        use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        mount_cluster(&mock_server, 3, Some(20)).await;
+        mount_cluster(&mock_server, 4, Some(10)).await;
+        Mock::given(method("GET"))
+            .and(path("/api/virtualization/virtual-machines/"))
+            .and(query_param("cluster_id", "4"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "results": [
+                { "id": 12, "name": "app01", "cluster": 4, "tenant": 10 }
+            ]})))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/virtualization/virtual-machines/"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+        let (service, _) = create_vm_service(&mock_server);
+        let order = |cluster: i32| serde_json::json!({ "name": "app01", "cluster": cluster });
+
+        let foreign = service.process_json_order("virtual_machine", order(3), "tenant1".to_string()).await;
+        assert!(matches!(foreign, Err(AppError::Forbidden(_))), "{:?}", foreign);
+        let taken = service.process_json_order("virtual_machine", order(4), "tenant1".to_string()).await;
+        assert!(matches!(taken, Err(AppError::Conflict(_))), "{:?}", taken);
+    }
+
+    async fn mount_prefix(mock_server: &wiremock::MockServer, id: i32, tenant: i32) {
+        use wiremock::{matchers::*, Mock, ResponseTemplate};
+
+        Mock::given(method("GET"))
+            .and(path(format!("/api/ipam/prefixes/{}/", id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": id, "prefix": "10.0.0.0/29", "tenant": tenant
+            })))
+            .mount(mock_server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_ip_order_allocates_to_interface_and_records_address() {
+        use crate::business::{CreatedResource, ResourceKind};
+        use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        mount_prefix(&mock_server, 5, 10).await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/interfaces/"))
+            .and(query_param("device_id", "12"))
+            .and(query_param("name", "eth0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "results": [
+                { "id": 31, "device": 12, "name": "eth0" }
+            ]})))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/ipam/prefixes/5/available-ips/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "family": 4, "address": "10.0.0.2/29" }
+            ])))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/ipam/prefixes/5/available-ips/"))
+            .and(body_partial_json(serde_json::json!({
+                "assigned_object_type": "dcim.interface",
+                "assigned_object_id": 31,
+                "dns_name": "edge01.example.net",
+                "tenant": 10
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "id": 77, "address": "10.0.0.2/29", "tenant": 10,
+                "assigned_object_type": "dcim.interface", "assigned_object_id": 31
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let (service, workflow_manager) = create_ip_service(&mock_server);
+        let order = serde_json::json!({
+            "prefix_id": 5, "device_id": 12, "interface": "eth0", "dns_name": "edge01.example.net"
+        });
+
+        let result = service.process_json_order("ip_address", order, "tenant1".to_string()).await.unwrap();
+
+        assert_eq!(result.workflow_state, OrderState::Completed);
+        let NetBoxResource::IpAddress(ip) = result.netbox_resource else {
+            panic!("Expected an IP address");
+        };
+        assert_eq!(ip.address, "10.0.0.2/29");
+        let workflow = workflow_manager.get_order(&result.order_id).unwrap();
+        assert_eq!(workflow.netbox_site_id, Some(77));
+        assert_eq!(workflow.created_resources, vec![CreatedResource { kind: ResourceKind::IpAddress, id: 77 }]);
+    }
+
+    #[tokio::test]
+    async fn test_ip_order_from_exhausted_prefix_exceeds_its_quota() {
+        use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        mount_prefix(&mock_server, 5, 10).await;
+        mount_prefix(&mock_server, 6, 10).await;
+        Mock::given(method("GET"))
+            .and(path("/api/ipam/prefixes/5/available-ips/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+            .mount(&mock_server)
+            .await;
+        // Prefix 6 still lists a free address, but another allocation takes it first
+        Mock::given(method("GET"))
+            .and(path("/api/ipam/prefixes/6/available-ips/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "family": 4, "address": "10.0.0.6/29" }
+            ])))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/ipam/prefixes/6/available-ips/"))
+            .respond_with(ResponseTemplate::new(409).set_body_json(serde_json::json!({
+                "detail": "An insufficient number of IP addresses are available within prefix 10.0.0.0/29"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let (service, workflow_manager) = create_ip_service(&mock_server);
+
+        for prefix_id in [5, 6] {
+            let order = serde_json::json!({ "prefix_id": prefix_id });
+            let result = service.process_json_order("ip_address", order, "tenant1".to_string()).await;
+            let Err(error @ AppError::QuotaExceeded { .. }) = result else {
+                panic!("Expected QuotaExceeded, got {:?}", result);
+            };
+            assert_eq!(error.status_code(), poem::http::StatusCode::TOO_MANY_REQUESTS);
+            assert_eq!(error.to_string(), format!("Quota exceeded: 6 of 6 prefix {} addresses in use", prefix_id));
+        }
+        let workflows = workflow_manager.get_tenant_orders("tenant1");
+        assert!(workflows.iter().all(|workflow| workflow.state == OrderState::Failed));
+    }
+
+    #[tokio::test]
+    async fn test_ip_order_refuses_other_tenants_prefix() {
+        use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        mount_prefix(&mock_server, 5, 20).await;
+        Mock::given(method("POST"))
+            .and(path("/api/ipam/prefixes/5/available-ips/"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+        let (service, _) = create_ip_service(&mock_server);
+        let order = || serde_json::json!({ "prefix_id": 5 });
+
+        let foreign = service.process_json_order("ip_address", order(), "tenant1".to_string()).await;
+        assert!(matches!(foreign, Err(AppError::Forbidden(_))), "{:?}", foreign);
+        let unmapped = service.process_json_order("ip_address", order(), "tenant2".to_string()).await;
+        assert!(matches!(unmapped, Err(AppError::Forbidden(_))), "{:?}", unmapped);
+    }
+
+    #[tokio::test]
+    async fn test_vlan_order_auto_assigns_lowest_free_vid() {
+        use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/ipam/vlans/"))
+            .and(query_param("site_id", "7"))
+            .and(query_param("offset", "0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "count": 3,
+                "next": "http://netbox/api/ipam/vlans/?offset=2",
+                "results": [{ "vid": 100, "name": "mgmt" }, { "vid": 101, "name": "users" }]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/ipam/vlans/"))
+            .and(query_param("site_id", "7"))
+            .and(query_param("offset", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "count": 3,
+                "results": [{ "vid": 103, "name": "guests" }]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/ipam/vlans/"))
+            .and(body_partial_json(serde_json::json!({ "vid": 102, "name": "voice", "site": 7 })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "id": 42, "vid": 102, "name": "voice", "site": 7, "tenant": 10
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let service = create_vlan_service(&mock_server);
+
+        let result = service
+            .process_json_order("vlan", serde_json::json!({ "site_id": 7, "name": "voice" }), "tenant1".to_string())
+            .await
+            .unwrap();
+
+        let NetBoxResource::Vlan(vlan) = result.netbox_resource else {
+            panic!("Expected a VLAN");
+        };
+        assert_eq!(vlan.vid, 102);
+    }
+}
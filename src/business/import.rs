@@ -0,0 +1,436 @@
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::business::workflow::{CreatedResource, OrderWorkflow, ResourceKind, WorkflowManager};
+use crate::domain::import::{ImportJob, ImportJobStatus, ImportPhase};
+use crate::error::AppError;
+use crate::netbox::models::{NetBoxDevice, NetBoxResponse, NetBoxSite};
+use crate::netbox::ResilientNetBoxClient;
+use crate::r#virtual::mapping::MappingType;
+use crate::r#virtual::VirtualResourceService;
+use crate::security::tenant::TenantMappingService;
+
+/// Sites or devices read from NetBox per request
+pub const DEFAULT_IMPORT_PAGE_SIZE: u32 = 50;
+
+/// Reasons an import can't be started
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportError {
+    /// The tenant has no NetBox tenant mapping
+    UnknownTenant(String),
+    /// An import of the tenant is already running, with this job id
+    AlreadyRunning(String),
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::UnknownTenant(tenant_id) => write!(f, "Tenant '{}' has no NetBox mapping", tenant_id),
+            ImportError::AlreadyRunning(job_id) => write!(f, "Import job {} is already running", job_id),
+        }
+    }
+}
+
+/// Brings a tenant's existing NetBox sites and devices under NetGate
+///
+/// Each resource becomes a Completed workflow, so it shows up in the tenant's
+/// order history. Resources a workflow already accounts for are skipped, and a
+/// failed job resumes from the page it stopped at, so importing twice is safe.
+pub struct InventoryImporter {
+    workflow_manager: Arc<WorkflowManager>,
+    netbox_client: Arc<ResilientNetBoxClient>,
+    mappings: Arc<TenantMappingService>,
+    virtual_service: Option<Arc<VirtualResourceService>>,
+    jobs: RwLock<HashMap<String, ImportJob>>,
+    page_size: u32,
+}
+
+impl InventoryImporter {
+    pub fn new(
+        workflow_manager: Arc<WorkflowManager>,
+        netbox_client: Arc<ResilientNetBoxClient>,
+        mappings: Arc<TenantMappingService>,
+    ) -> Self {
+        Self {
+            workflow_manager,
+            netbox_client,
+            mappings,
+            virtual_service: None,
+            jobs: RwLock::new(HashMap::new()),
+            page_size: DEFAULT_IMPORT_PAGE_SIZE,
+        }
+    }
+
+    /// Service virtual resources are created in for jobs that ask for them
+    pub fn with_virtual_service(mut self, virtual_service: Arc<VirtualResourceService>) -> Self {
+        self.virtual_service = Some(virtual_service);
+        self
+    }
+
+    pub fn with_page_size(mut self, page_size: u32) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// Get an import job by ID
+    pub fn get_job(&self, job_id: &str) -> Option<ImportJob> {
+        self.jobs.read().get(job_id).cloned()
+    }
+
+    /// Start importing a tenant's inventory in the background
+    pub fn start(self: &Arc<Self>, tenant_id: &str, create_virtual_resources: bool) -> Result<ImportJob, ImportError> {
+        let job = self.prepare(tenant_id, create_virtual_resources)?;
+        let importer = Arc::clone(self);
+        let job_id = job.job_id.clone();
+        tokio::spawn(async move { importer.run(&job_id).await });
+        Ok(job)
+    }
+
+    /// Register a Running job for the tenant, resuming its last failed job if there is one
+    pub fn prepare(&self, tenant_id: &str, create_virtual_resources: bool) -> Result<ImportJob, ImportError> {
+        let netbox_tenant_id = self
+            .mappings
+            .get_netbox_tenant_id(&tenant_id.to_string())
+            .ok_or_else(|| ImportError::UnknownTenant(tenant_id.to_string()))?;
+
+        let mut jobs = self.jobs.write();
+        let mut tenant_jobs: Vec<&mut ImportJob> = jobs.values_mut().filter(|job| job.tenant_id == tenant_id).collect();
+        if let Some(running) = tenant_jobs.iter().find(|job| job.status == ImportJobStatus::Running) {
+            return Err(ImportError::AlreadyRunning(running.job_id.clone()));
+        }
+
+        tenant_jobs.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        if let Some(failed) = tenant_jobs
+            .into_iter()
+            .next()
+            .filter(|job| job.status == ImportJobStatus::Failed && job.netbox_tenant_id == netbox_tenant_id)
+        {
+            failed.status = ImportJobStatus::Running;
+            failed.create_virtual_resources = create_virtual_resources;
+            failed.error = None;
+            failed.finished_at = None;
+            failed.updated_at = chrono::Utc::now().to_rfc3339();
+            tracing::info!("Resuming import job {} of tenant {} at {:?} offset {}", failed.job_id, tenant_id, failed.phase, failed.offset);
+            return Ok(failed.clone());
+        }
+
+        let job = ImportJob::new(tenant_id.to_string(), netbox_tenant_id, create_virtual_resources);
+        jobs.insert(job.job_id.clone(), job.clone());
+        Ok(job)
+    }
+
+    /// Page through NetBox from the job's cursor until done or a page can't be read
+    pub async fn run(&self, job_id: &str) {
+        let Some(job) = self.get_job(job_id) else {
+            return;
+        };
+        let result = self.import_pages(&job).await;
+        self.update_job(job_id, |job| {
+            match result {
+                Ok(()) => job.status = ImportJobStatus::Completed,
+                Err(ref e) => {
+                    job.status = ImportJobStatus::Failed;
+                    job.error = Some(e.to_string());
+                }
+            }
+            job.finished_at = Some(chrono::Utc::now().to_rfc3339());
+        });
+        match result {
+            Ok(()) => tracing::info!("Import job {} of tenant {} completed", job_id, job.tenant_id),
+            Err(e) => tracing::warn!("Import job {} of tenant {} failed: {}", job_id, job.tenant_id, e),
+        }
+    }
+
+    async fn import_pages(&self, job: &ImportJob) -> Result<(), AppError> {
+        let netbox_tenant_id = Some(job.netbox_tenant_id);
+        let (mut phase, mut offset) = (job.phase, job.offset);
+        loop {
+            let done = match phase {
+                ImportPhase::Sites => {
+                    let page = self
+                        .netbox_client
+                        .list_sites(netbox_tenant_id, Some(self.page_size), Some(offset))
+                        .await?;
+                    let (sites, done) = self.page_results(&job.job_id, page, |job, total| job.sites_total = total)?;
+                    for site in &sites {
+                        self.import_site(&job.job_id, &job.tenant_id, site, job.create_virtual_resources);
+                    }
+                    offset += sites.len() as u32;
+                    done
+                }
+                ImportPhase::Devices => {
+                    let page = self
+                        .netbox_client
+                        .list_devices_with_filters(None, netbox_tenant_id, Some(self.page_size), Some(offset), &HashMap::new())
+                        .await?;
+                    let (devices, done) = self.page_results(&job.job_id, page, |job, total| job.devices_total = total)?;
+                    for device in &devices {
+                        self.import_device(&job.job_id, &job.tenant_id, device, job.create_virtual_resources);
+                    }
+                    offset += devices.len() as u32;
+                    done
+                }
+            };
+
+            if done && phase == ImportPhase::Devices {
+                self.update_job(&job.job_id, |job| job.offset = offset);
+                return Ok(());
+            }
+            if done {
+                (phase, offset) = (ImportPhase::Devices, 0);
+            }
+            // Only move the cursor once the page is imported, so a failed job resumes here
+            self.update_job(&job.job_id, |job| {
+                job.phase = phase;
+                job.offset = offset;
+            });
+        }
+    }
+
+    /// Results of a page and whether it was the last one, recording NetBox's total
+    fn page_results<T>(
+        &self,
+        job_id: &str,
+        page: NetBoxResponse<T>,
+        set_total: impl FnOnce(&mut ImportJob, Option<i32>),
+    ) -> Result<(Vec<T>, bool), AppError> {
+        // Cached fallback data may be incomplete; stop rather than import from it
+        if page.degraded {
+            return Err(AppError::ServiceUnavailable("NetBox is unavailable".to_string()));
+        }
+        self.update_job(job_id, |job| set_total(job, page.count));
+        let results = page.results.unwrap_or_default();
+        let done = page.next.is_none() || results.is_empty();
+        Ok((results, done))
+    }
+
+    fn import_site(&self, job_id: &str, tenant_id: &str, site: &NetBoxSite, create_virtual: bool) {
+        let Some(id) = site.id else {
+            return;
+        };
+        let mut workflow = OrderWorkflow::imported(tenant_id.to_string(), CreatedResource { kind: ResourceKind::Site, id });
+        workflow.netbox_site_id = Some(id);
+        workflow.site_name = Some(site.name.clone());
+        workflow.site_slug = site.slug.clone();
+        if !self.workflow_manager.insert_imported_order(workflow) {
+            self.update_job(job_id, |job| job.skipped += 1);
+            return;
+        }
+
+        let created_virtual = create_virtual
+            && self.create_virtual(|service| {
+                service
+                    .create_virtual_site(site.name.clone(), tenant_id.to_string(), vec![id], MappingType::OneToOne)
+                    .map(|_| ())
+            }, id);
+        self.update_job(job_id, |job| {
+            job.sites_imported += 1;
+            job.virtual_resources_created += u32::from(created_virtual);
+        });
+    }
+
+    fn import_device(&self, job_id: &str, tenant_id: &str, device: &NetBoxDevice, create_virtual: bool) {
+        let Some(id) = device.id else {
+            return;
+        };
+        let mut workflow = OrderWorkflow::imported(tenant_id.to_string(), CreatedResource { kind: ResourceKind::Device, id });
+        workflow.netbox_site_id = device.site;
+        if !self.workflow_manager.insert_imported_order(workflow) {
+            self.update_job(job_id, |job| job.skipped += 1);
+            return;
+        }
+
+        let name = device.name.clone().unwrap_or_else(|| format!("device-{}", id));
+        let created_virtual = create_virtual
+            && self.create_virtual(|service| {
+                service
+                    .create_virtual_device(name, tenant_id.to_string(), vec![id], MappingType::OneToOne)
+                    .map(|_| ())
+            }, id);
+        self.update_job(job_id, |job| {
+            job.devices_imported += 1;
+            job.virtual_resources_created += u32::from(created_virtual);
+        });
+    }
+
+    /// Create a virtual resource for an imported one; a rejected mapping doesn't fail the import
+    fn create_virtual<E: std::fmt::Display>(
+        &self,
+        create: impl FnOnce(&VirtualResourceService) -> Result<(), E>,
+        physical_id: i32,
+    ) -> bool {
+        let Some(ref service) = self.virtual_service else {
+            return false;
+        };
+        match create(service) {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!("No virtual resource created for imported NetBox object {}: {}", physical_id, e);
+                false
+            }
+        }
+    }
+
+    fn update_job(&self, job_id: &str, change: impl FnOnce(&mut ImportJob)) {
+        if let Some(job) = self.jobs.write().get_mut(job_id) {
+            change(job);
+            job.updated_at = chrono::Utc::now().to_rfc3339();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::business::OrderState;
+    use crate::config::Config;
+    use crate::netbox::client::NetBoxClient;
+    use serde_json::json;
+    use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+    fn netbox_client(uri: String) -> Arc<ResilientNetBoxClient> {
+        let config = Config {
+            netbox_url: uri,
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        Arc::new(ResilientNetBoxClient::new(Arc::new(NetBoxClient::new(config).unwrap())))
+    }
+
+    fn mappings() -> Arc<TenantMappingService> {
+        let mappings = Arc::new(TenantMappingService::new());
+        mappings.register_mapping("acme".to_string(), 5);
+        mappings
+    }
+
+    /// One page of a listing holding `ids`, with a `next` link unless it is the last
+    fn page(ids: &[i32], total: i32, last: bool, item: impl Fn(i32) -> serde_json::Value) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(json!({
+            "count": total,
+            "next": if last { None } else { Some("http://netbox/next") },
+            "previous": null,
+            "results": ids.iter().map(|id| item(*id)).collect::<Vec<_>>(),
+        }))
+    }
+
+    fn site(id: i32) -> serde_json::Value {
+        json!({ "id": id, "name": format!("site-{}", id), "slug": format!("site-{}", id), "tenant": 5 })
+    }
+
+    fn device(id: i32) -> serde_json::Value {
+        json!({ "id": id, "name": format!("sw-{}", id), "site": 1, "tenant": 5 })
+    }
+
+    /// Three pages of sites and one of devices for NetBox tenant 5
+    async fn mount_inventory(server: &MockServer) {
+        for (offset, ids, last) in [("0", vec![1, 2], false), ("2", vec![3, 4], false), ("4", vec![5], true)] {
+            Mock::given(method("GET"))
+                .and(path("/api/dcim/sites/"))
+                .and(query_param("tenant_id", "5"))
+                .and(query_param("offset", offset))
+                .respond_with(page(&ids, 5, last, site))
+                .mount(server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/devices/"))
+            .and(query_param("tenant_id", "5"))
+            .and(query_param("offset", "0"))
+            .respond_with(page(&[10, 11], 2, true, device))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_importing_twice_creates_no_duplicates() {
+        let server = MockServer::start().await;
+        mount_inventory(&server).await;
+        let manager = Arc::new(WorkflowManager::new());
+        let virtual_service = Arc::new(VirtualResourceService::new());
+        let importer = InventoryImporter::new(manager.clone(), netbox_client(server.uri()), mappings())
+            .with_virtual_service(virtual_service.clone())
+            .with_page_size(2);
+
+        let first = importer.prepare("acme", true).unwrap();
+        importer.run(&first.job_id).await;
+        let first = importer.get_job(&first.job_id).unwrap();
+        assert_eq!(first.status, ImportJobStatus::Completed);
+        assert_eq!((first.sites_imported, first.devices_imported, first.skipped), (5, 2, 0));
+        assert_eq!((first.sites_total, first.devices_total), (Some(5), Some(2)));
+        assert_eq!(first.virtual_resources_created, 7);
+
+        let second = importer.prepare("acme", true).unwrap();
+        assert_ne!(second.job_id, first.job_id);
+        importer.run(&second.job_id).await;
+        let second = importer.get_job(&second.job_id).unwrap();
+        assert_eq!(second.status, ImportJobStatus::Completed);
+        assert_eq!((second.sites_imported, second.devices_imported, second.skipped), (0, 0, 7));
+        assert_eq!(second.virtual_resources_created, 0);
+
+        let orders = manager.get_tenant_orders("acme");
+        assert_eq!(orders.len(), 7);
+        assert!(orders.iter().all(|w| w.state == OrderState::Completed && w.imported_from.is_some()));
+        assert_eq!(virtual_service.get_tenant_virtual_resources("acme").len(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_failed_import_resumes_from_cursor() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .and(query_param("offset", "0"))
+            .respond_with(page(&[1, 2], 3, false, site))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .and(query_param("offset", "2"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({ "detail": "bad page" })))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        let manager = Arc::new(WorkflowManager::new());
+        let importer = InventoryImporter::new(manager.clone(), netbox_client(server.uri()), mappings()).with_page_size(2);
+
+        let job = importer.prepare("acme", false).unwrap();
+        importer.run(&job.job_id).await;
+        let failed = importer.get_job(&job.job_id).unwrap();
+        assert_eq!(failed.status, ImportJobStatus::Failed);
+        assert!(failed.error.is_some());
+        assert_eq!((failed.phase, failed.offset, failed.sites_imported), (ImportPhase::Sites, 2, 2));
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .and(query_param("offset", "2"))
+            .respond_with(page(&[3], 3, true, site))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/devices/"))
+            .respond_with(page(&[], 0, true, device))
+            .mount(&server)
+            .await;
+
+        let resumed = importer.prepare("acme", false).unwrap();
+        assert_eq!(resumed.job_id, job.job_id);
+        importer.run(&resumed.job_id).await;
+        let resumed = importer.get_job(&job.job_id).unwrap();
+        assert_eq!(resumed.status, ImportJobStatus::Completed);
+        assert_eq!((resumed.sites_imported, resumed.skipped), (3, 0));
+        assert_eq!(manager.get_tenant_orders("acme").len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_prepare_refuses_unknown_tenant_and_concurrent_jobs() {
+        let importer = InventoryImporter::new(
+            Arc::new(WorkflowManager::new()),
+            netbox_client("http://localhost:1".to_string()),
+            mappings(),
+        );
+
+        assert_eq!(importer.prepare("other", false), Err(ImportError::UnknownTenant("other".to_string())));
+        let running = importer.prepare("acme", false).unwrap();
+        assert_eq!(importer.prepare("acme", false), Err(ImportError::AlreadyRunning(running.job_id)));
+    }
+}
@@ -0,0 +1,316 @@
+use chrono::offset::LocalResult;
+use chrono::{DateTime, Datelike, Days, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::warn;
+
+/// Back-to-back windows followed when working out when a freeze ends
+const MAX_CHAINED_WINDOWS: usize = 64;
+
+/// What happens to orders submitted during a maintenance freeze
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FreezeMode {
+    /// Held in Scheduled and executed once the freeze ends
+    #[default]
+    Park,
+    /// Refused; the client resubmits after the freeze
+    Reject,
+}
+
+impl FreezeMode {
+    /// `park` or `reject`, as configured
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FreezeMode::Park => "park",
+            FreezeMode::Reject => "reject",
+        }
+    }
+}
+
+impl FromStr for FreezeMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "park" => Ok(FreezeMode::Park),
+            "reject" => Ok(FreezeMode::Reject),
+            other => Err(format!("Unknown maintenance freeze mode '{}'", other)),
+        }
+    }
+}
+
+/// A weekly window during which orders are not executed
+///
+/// Written as `<days> <HH:MM>-<HH:MM>`, e.g. `Mon-Fri 22:00-02:00`, where a
+/// window ending at or before its start time ends the next day, or as one span
+/// across days, e.g. `Fri 18:00-Mon 06:00`. Days are `Mon` to `Sun`, ranges
+/// such as `Mon-Fri`, lists such as `Sat,Sun`, or `*` for every day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FreezeWindow {
+    /// Days the window starts on
+    days: Vec<Weekday>,
+    start: NaiveTime,
+    /// Days after the start day the window ends on
+    end_after_days: u64,
+    end: NaiveTime,
+}
+
+impl FreezeWindow {
+    /// End of the occurrence of this window covering `now`, if any
+    fn active_until(&self, now: DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let timezone = now.timezone();
+        (0..=self.end_after_days)
+            .filter_map(|back| now.date_naive().checked_sub_days(Days::new(back)))
+            .filter(|day| self.days.contains(&day.weekday()))
+            .filter_map(|day| {
+                let start = resolve_local(&timezone, day.and_time(self.start))?;
+                let end = resolve_local(&timezone, day.checked_add_days(Days::new(self.end_after_days))?.and_time(self.end))?;
+                (start <= now && now < end).then_some(end)
+            })
+            .max()
+    }
+}
+
+impl FromStr for FreezeWindow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid maintenance window '{}'", s.trim());
+        let (days, times) = s.trim().split_once(char::is_whitespace).ok_or_else(invalid)?;
+        let (start, end) = times.trim().split_once('-').ok_or_else(invalid)?;
+        let days = parse_days(days).ok_or_else(invalid)?;
+        let start = parse_time(start).filter(|(_, next_day)| !next_day).ok_or_else(invalid)?.0;
+
+        let (end, end_after_days) = match end.trim().split_once(char::is_whitespace) {
+            // A span across days starts on one day only
+            Some((end_day, end)) => {
+                let [start_day] = days[..] else {
+                    return Err(invalid());
+                };
+                let end_day: Weekday = end_day.parse().map_err(|_| invalid())?;
+                let (end, next_day) = parse_time(end).ok_or_else(invalid)?;
+                let mut after = (7 + end_day.num_days_from_monday() - start_day.num_days_from_monday()) % 7;
+                if after == 0 && end <= start {
+                    after = 7;
+                }
+                (end, u64::from(after) + u64::from(next_day))
+            }
+            None => {
+                let (end, next_day) = parse_time(end).ok_or_else(invalid)?;
+                (end, u64::from(next_day || end <= start))
+            }
+        };
+        Ok(Self {
+            days,
+            start,
+            end_after_days,
+            end,
+        })
+    }
+}
+
+/// `HH:MM`, with `24:00` as midnight of the next day
+fn parse_time(s: &str) -> Option<(NaiveTime, bool)> {
+    match s.trim() {
+        "24:00" => Some((NaiveTime::MIN, true)),
+        s => NaiveTime::parse_from_str(s, "%H:%M").ok().map(|time| (time, false)),
+    }
+}
+
+/// `*`, or a comma separated list of days and day ranges such as `Mon-Fri`
+fn parse_days(s: &str) -> Option<Vec<Weekday>> {
+    if s == "*" {
+        return parse_days("Mon-Sun");
+    }
+    let mut days = Vec::new();
+    for part in s.split(',') {
+        let (first, last) = part.split_once('-').unwrap_or((part, part));
+        let (mut day, last): (Weekday, Weekday) = (first.trim().parse().ok()?, last.trim().parse().ok()?);
+        loop {
+            if !days.contains(&day) {
+                days.push(day);
+            }
+            if day == last {
+                break;
+            }
+            day = day.succ();
+        }
+    }
+    Some(days)
+}
+
+/// Local time `local` in `timezone`: the earlier instant of a time repeated when
+/// clocks go back, and a time skipped when they go forward moved past the jump
+fn resolve_local(timezone: &Tz, local: NaiveDateTime) -> Option<DateTime<Tz>> {
+    match timezone.from_local_datetime(&local) {
+        LocalResult::Single(at) => Some(at),
+        LocalResult::Ambiguous(earlier, _) => Some(earlier),
+        LocalResult::None => {
+            let hour = chrono::Duration::hours(1);
+            timezone.from_local_datetime(&(local - hour)).earliest().map(|at| at + hour)
+        }
+    }
+}
+
+/// Weekly maintenance freezes during which orders are not executed
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaintenanceWindows {
+    pub windows: Vec<FreezeWindow>,
+    /// Time zone the windows' days and times are in, following its DST changes
+    pub timezone: Tz,
+    pub mode: FreezeMode,
+    /// How often orders parked during a freeze are checked for release
+    pub release_interval: Duration,
+}
+
+impl MaintenanceWindows {
+    pub fn new(windows: Vec<FreezeWindow>, timezone: Tz) -> Self {
+        Self {
+            windows,
+            timezone,
+            mode: FreezeMode::default(),
+            release_interval: Duration::from_secs(30),
+        }
+    }
+
+    pub fn with_mode(mut self, mode: FreezeMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Windows separated by `;`, skipping invalid ones with a warning
+    pub fn parse_windows(spec: &str) -> Vec<FreezeWindow> {
+        spec.split(';')
+            .map(str::trim)
+            .filter(|window| !window.is_empty())
+            .filter_map(|window| match window.parse() {
+                Ok(window) => Some(window),
+                Err(e) => {
+                    warn!("Ignoring {}", e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Load from MAINTENANCE_WINDOWS, MAINTENANCE_TIMEZONE, MAINTENANCE_FREEZE_MODE
+    /// and MAINTENANCE_RELEASE_INTERVAL_SECS; `None` without windows
+    pub fn from_env() -> Option<Self> {
+        let windows = Self::parse_windows(&std::env::var("MAINTENANCE_WINDOWS").ok()?);
+        if windows.is_empty() {
+            return None;
+        }
+        let timezone = std::env::var("MAINTENANCE_TIMEZONE")
+            .ok()
+            .and_then(|name| {
+                let timezone = name.parse().ok();
+                if timezone.is_none() {
+                    warn!("Unknown maintenance time zone '{}', using UTC", name);
+                }
+                timezone
+            })
+            .unwrap_or(Tz::UTC);
+        let mut config = Self::new(windows, timezone);
+        if let Some(mode) = std::env::var("MAINTENANCE_FREEZE_MODE").ok().and_then(|mode| mode.parse().ok()) {
+            config.mode = mode;
+        }
+        if let Some(secs) = std::env::var("MAINTENANCE_RELEASE_INTERVAL_SECS").ok().and_then(|secs| secs.parse().ok()) {
+            config.release_interval = Duration::from_secs(secs);
+        }
+        Some(config)
+    }
+
+    /// When the freeze covering `now` ends, or `None` outside a freeze
+    ///
+    /// Windows that overlap or follow on from one another make one freeze.
+    pub fn freeze_until(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut until = self.active_until(now)?;
+        for _ in 0..MAX_CHAINED_WINDOWS {
+            match self.active_until(until) {
+                Some(next) => until = next,
+                None => break,
+            }
+        }
+        Some(until)
+    }
+
+    fn active_until(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let now = now.with_timezone(&self.timezone);
+        self.windows
+            .iter()
+            .filter_map(|window| window.active_until(now))
+            .max()
+            .map(|until| until.with_timezone(&Utc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(at: &str) -> DateTime<Utc> {
+        at.parse().unwrap()
+    }
+
+    fn windows(spec: &str, timezone: Tz) -> MaintenanceWindows {
+        MaintenanceWindows::new(MaintenanceWindows::parse_windows(spec), timezone)
+    }
+
+    #[test]
+    fn test_windows_are_parsed_and_invalid_ones_skipped() {
+        let parsed = MaintenanceWindows::parse_windows("Mon-Fri 22:00-02:00; Sat,Sun 00:00-24:00; Fri 18:00-Mon 06:00; Mon 25:00-26:00; daily");
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(parsed[0].days.len(), 5);
+        assert_eq!(parsed[0].end_after_days, 1);
+        assert_eq!(parsed[1].days, vec![Weekday::Sat, Weekday::Sun]);
+        assert_eq!(parsed[1].end_after_days, 1);
+        assert_eq!(parsed[2].days, vec![Weekday::Fri]);
+        assert_eq!(parsed[2].end_after_days, 3);
+        assert!("Mon,Tue 18:00-Wed 06:00".parse::<FreezeWindow>().is_err());
+        assert_eq!("reject".parse(), Ok(FreezeMode::Reject));
+    }
+
+    #[test]
+    fn test_freeze_covers_the_window_up_to_but_not_including_its_end() {
+        let windows = windows("Mon-Fri 22:00-02:00", Tz::UTC);
+
+        // 2026-10-16 is a Friday
+        assert_eq!(windows.freeze_until(utc("2026-10-16T21:59:59Z")), None);
+        assert_eq!(windows.freeze_until(utc("2026-10-16T22:00:00Z")), Some(utc("2026-10-17T02:00:00Z")));
+        assert_eq!(windows.freeze_until(utc("2026-10-17T01:59:59Z")), Some(utc("2026-10-17T02:00:00Z")));
+        assert_eq!(windows.freeze_until(utc("2026-10-17T02:00:00Z")), None);
+        // No window starts on Saturday
+        assert_eq!(windows.freeze_until(utc("2026-10-17T23:00:00Z")), None);
+    }
+
+    #[test]
+    fn test_back_to_back_windows_make_one_freeze() {
+        let windows = windows("Fri 18:00-24:00; Sat,Sun 00:00-24:00", Tz::UTC);
+        assert_eq!(windows.freeze_until(utc("2026-10-16T20:00:00Z")), Some(utc("2026-10-19T00:00:00Z")));
+    }
+
+    #[test]
+    fn test_windows_follow_the_time_zone_across_dst_changes() {
+        let windows = windows("Sun 01:00-04:00", chrono_tz::Europe::Berlin);
+
+        // Clocks go forward at 02:00 on 2026-03-29, so the window lasts two hours
+        assert_eq!(windows.freeze_until(utc("2026-03-28T23:59:59Z")), None);
+        assert_eq!(windows.freeze_until(utc("2026-03-29T00:00:00Z")), Some(utc("2026-03-29T02:00:00Z")));
+        assert_eq!(windows.freeze_until(utc("2026-03-29T02:00:00Z")), None);
+
+        // Clocks go back at 03:00 on 2026-10-25, so it lasts four
+        assert_eq!(windows.freeze_until(utc("2026-10-24T23:00:00Z")), Some(utc("2026-10-25T03:00:00Z")));
+        assert_eq!(windows.freeze_until(utc("2026-10-25T02:30:00Z")), Some(utc("2026-10-25T03:00:00Z")));
+        assert_eq!(windows.freeze_until(utc("2026-10-25T03:00:00Z")), None);
+    }
+
+    #[test]
+    fn test_window_starting_in_the_skipped_hour_starts_after_the_jump() {
+        let windows = windows("Sun 02:30-05:00", chrono_tz::Europe::Berlin);
+
+        // 02:30 doesn't exist on 2026-03-29; the window starts at 03:30 CEST
+        assert_eq!(windows.freeze_until(utc("2026-03-29T01:29:59Z")), None);
+        assert_eq!(windows.freeze_until(utc("2026-03-29T01:30:00Z")), Some(utc("2026-03-29T03:00:00Z")));
+    }
+}
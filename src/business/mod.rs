@@ -1,25 +1,48 @@
+pub mod address;
+pub mod approval;
+pub mod catalog;
 pub mod enrichment;
 pub mod extensible_order_service;
+pub mod import;
+pub mod maintenance;
+pub mod naming;
+pub mod onboarding;
+pub mod order_claims;
+pub mod order_export;
+pub mod order_queue;
 pub mod order_service;
+pub mod payload_schema;
 pub mod plugin;
 pub mod processors;
+pub mod site_report;
 pub mod transformation;
 pub mod validation;
+pub mod vlan;
+pub mod webhooks;
 pub mod workflow;
+pub mod workflow_metrics;
+pub mod workflow_store;
 
+pub use approval::*;
 pub use enrichment::*;
+pub use maintenance::*;
+pub use order_claims::*;
+pub use order_queue::*;
 // Note: extensible_order_service and order_service both export ProcessedOrderResult and OrderStatus
 // We only export from order_service to avoid ambiguity
 pub use order_service::*;
 pub use transformation::*;
 pub use validation::*;
+pub use webhooks::*;
 pub use workflow::*;
+pub use workflow_metrics::*;
+pub use workflow_store::*;
 
 // Re-export plugin and processor types explicitly (public API)
-#[allow(unused_imports)] // These are public APIs for external use
-pub use plugin::{OrderPayload, OrderProcessor, OrderType, OrderTypeRegistry, NetBoxResource, NetBoxResourceRequest};
-#[allow(unused_imports)]
-pub use processors::SiteOrderProcessor;
-#[allow(unused_imports)]
+pub use plugin::{OrderPayload, OrderProcessor, OrderType, OrderTypeRegistry, NetBoxResource, NetBoxResourceRequest, RegisteredOrderType};
+pub use processors::{
+    DeviceOrderProcessor, IpAllocationOrderProcessor, NetworkOrderProcessor, SiteOrderProcessor,
+    VirtualMachineOrderProcessor, VlanOrderProcessor,
+};
 pub use extensible_order_service::{ExtensibleOrderService, ExtensibleOrderServiceBuilder};
 
@@ -0,0 +1,524 @@
+use parking_lot::Mutex;
+use serde::{Deserialize, Deserializer};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+use crate::netbox::ResilientNetBoxClient;
+
+/// Tenant key of the templates that apply to every tenant without its own
+pub const DEFAULT_NAMING_SCOPE: &str = "*";
+
+/// Rounds of picking fresh sequence numbers for names NetBox already has
+const MAX_COLLISION_ROUNDS: usize = 10;
+
+/// Why a template was refused or a name couldn't be generated
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum NamingError {
+    #[error("Invalid naming template '{template}': {reason}")]
+    InvalidTemplate { template: String, reason: String },
+
+    #[error("Naming template '{template}' needs {variable}, which the order doesn't have")]
+    MissingVariable { template: String, variable: &'static str },
+
+    #[error("Generated device name '{name}' exceeds NetBox's limit of {max} characters")]
+    TooLong { name: String, max: usize },
+
+    #[error("Generated device name '{0}' is already taken")]
+    Taken(String),
+}
+
+impl From<NamingError> for AppError {
+    fn from(error: NamingError) -> Self {
+        match error {
+            NamingError::Taken(_) => AppError::Conflict(error.to_string()),
+            _ => AppError::ValidationError(error.to_string()),
+        }
+    }
+}
+
+/// A value a naming template can refer to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Variable {
+    Tenant,
+    SiteSlug,
+    Role,
+    DeviceType,
+    Project,
+    Environment,
+    /// Next number of the name's sequence counter
+    Seq,
+}
+
+impl Variable {
+    const ALL: [(&'static str, Variable); 7] = [
+        ("tenant", Variable::Tenant),
+        ("site_slug", Variable::SiteSlug),
+        ("role", Variable::Role),
+        ("device_type", Variable::DeviceType),
+        ("project", Variable::Project),
+        ("environment", Variable::Environment),
+        ("seq", Variable::Seq),
+    ];
+
+    fn name(self) -> &'static str {
+        Self::ALL.iter().find(|(_, variable)| *variable == self).map_or("", |(name, _)| name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    /// A variable, zero-padded to `width` digits if set
+    Variable { variable: Variable, width: Option<usize> },
+}
+
+/// A device naming template such as `{site_slug}-{role}-{seq:03}`
+///
+/// Variables are written in braces, with an optional zero-padded width
+/// (`{seq:03}`); `{{` and `}}` are literal braces. Available variables are
+/// `tenant`, `site_slug`, `role` and `device_type` (NetBox ids), `project`,
+/// `environment` and `seq`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameTemplate {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl std::str::FromStr for NameTemplate {
+    type Err = NamingError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| NamingError::InvalidTemplate {
+            template: source.to_string(),
+            reason: reason.to_string(),
+        };
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = source.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '}' => return Err(invalid("unmatched '}'")),
+                '{' => {
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some('{') | None => return Err(invalid("unclosed '{'")),
+                            Some(c) => placeholder.push(c),
+                        }
+                    }
+                    let (name, width) = match placeholder.split_once(':') {
+                        Some((name, width)) => {
+                            let width = width
+                                .parse::<usize>()
+                                .ok()
+                                .filter(|width| (1..=20).contains(width))
+                                .ok_or_else(|| invalid(&format!("invalid width '{}'", width)))?;
+                            (name.trim(), Some(width))
+                        }
+                        None => (placeholder.trim(), None),
+                    };
+                    let variable = Variable::ALL
+                        .iter()
+                        .find(|(known, _)| *known == name)
+                        .map(|(_, variable)| *variable)
+                        .ok_or_else(|| invalid(&format!("unknown variable '{}'", name)))?;
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Variable { variable, width });
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        if !segments.iter().any(|segment| matches!(segment, Segment::Variable { .. })) {
+            return Err(invalid("it has no variables, so every device would get the same name"));
+        }
+        Ok(Self {
+            source: source.to_string(),
+            segments,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for NameTemplate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl NameTemplate {
+    /// Whether the template numbers names, so taken names can be skipped
+    pub fn has_seq(&self) -> bool {
+        self.segments
+            .iter()
+            .any(|segment| matches!(segment, Segment::Variable { variable: Variable::Seq, .. }))
+    }
+
+    /// Name of a device; `seq` is ignored unless the template uses it
+    pub fn render(&self, context: &NameContext, seq: u64) -> Result<String, NamingError> {
+        let mut name = String::new();
+        for segment in &self.segments {
+            let (variable, width) = match segment {
+                Segment::Literal(literal) => {
+                    name.push_str(literal);
+                    continue;
+                }
+                Segment::Variable { variable, width } => (*variable, *width),
+            };
+            let value = match variable {
+                Variable::Seq => Some(seq.to_string()),
+                Variable::Tenant => Some(context.tenant_id.clone()),
+                Variable::SiteSlug => context.site_slug.clone(),
+                Variable::Role => context.role.map(|role| role.to_string()),
+                Variable::DeviceType => context.device_type.map(|device_type| device_type.to_string()),
+                Variable::Project => context.project.clone(),
+                Variable::Environment => context.environment.clone(),
+            }
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| NamingError::MissingVariable {
+                template: self.source.clone(),
+                variable: variable.name(),
+            })?;
+            match width {
+                Some(width) => name.push_str(&format!("{:0>width$}", value, width = width)),
+                None => name.push_str(&value),
+            }
+        }
+        Ok(name)
+    }
+
+    /// Key of the sequence counter a device's names are numbered from
+    ///
+    /// Everything but the sequence number, so e.g. each site and role of
+    /// `{site_slug}-{role}-{seq:03}` counts from 1.
+    fn sequence_scope(&self, context: &NameContext) -> Result<String, NamingError> {
+        let template = Self {
+            source: self.source.clone(),
+            segments: self
+                .segments
+                .iter()
+                .map(|segment| match segment {
+                    Segment::Variable { variable: Variable::Seq, .. } => Segment::Literal("#".to_string()),
+                    segment => segment.clone(),
+                })
+                .collect(),
+        };
+        Ok(format!("{}/{}", context.tenant_id, template.render(context, 0)?))
+    }
+}
+
+/// Values a device's name can be built from
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NameContext {
+    pub tenant_id: String,
+    pub site_slug: Option<String>,
+    pub role: Option<i32>,
+    pub device_type: Option<i32>,
+    pub project: Option<String>,
+    pub environment: Option<String>,
+}
+
+/// Device naming templates per tenant and order type
+///
+/// A JSON object of tenant ids, or `*` for every other tenant, to objects of
+/// order types (`pop`) to templates. Invalid templates fail loading.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct NamingPolicy {
+    #[serde(flatten)]
+    templates: HashMap<String, HashMap<String, NameTemplate>>,
+}
+
+impl NamingPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Name `tenant_id`'s devices of `order_type` orders with `template`
+    pub fn with_template(mut self, tenant_id: impl Into<String>, order_type: impl Into<String>, template: NameTemplate) -> Self {
+        self.templates.entry(tenant_id.into()).or_default().insert(order_type.into(), template);
+        self
+    }
+
+    /// Parse a policy, refusing invalid templates
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Load a policy from a JSON file
+    pub fn from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_json(&json).map_err(|e| anyhow::anyhow!("Invalid naming policy in {}: {}", path.display(), e))
+    }
+
+    /// Template for a tenant's devices of an order type, falling back to `*`'s
+    pub fn template(&self, tenant_id: &str, order_type: &str) -> Option<&NameTemplate> {
+        [tenant_id, DEFAULT_NAMING_SCOPE]
+            .iter()
+            .find_map(|scope| self.templates.get(*scope)?.get(order_type))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+}
+
+/// Sequence counters of generated names, optionally persisted to a JSON file
+///
+/// Numbers are never handed out twice, also across restarts when persisted;
+/// a number whose name turned out to be taken is skipped, not reused.
+#[derive(Debug, Default)]
+pub struct NameSequences {
+    counters: Mutex<HashMap<String, u64>>,
+    store_path: Option<PathBuf>,
+}
+
+impl NameSequences {
+    /// Counters kept in memory only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load counters from a JSON file and write every later change back to it
+    ///
+    /// A missing file starts every counter at 1 and is created on first use.
+    pub fn load(path: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let path = path.into();
+        let counters = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self {
+            counters: Mutex::new(counters),
+            store_path: Some(path),
+        })
+    }
+
+    /// Take the next `count` numbers of a scope's sequence, starting at 1
+    ///
+    /// Nothing is taken if they can't be persisted.
+    pub fn take(&self, scope: &str, count: u64) -> std::io::Result<std::ops::Range<u64>> {
+        let mut counters = self.counters.lock();
+        let last = counters.get(scope).copied().unwrap_or(0);
+        counters.insert(scope.to_string(), last + count);
+        if let Err(e) = self.persist(&counters) {
+            counters.insert(scope.to_string(), last);
+            return Err(e);
+        }
+        Ok(last + 1..last + count + 1)
+    }
+
+    fn persist(&self, counters: &HashMap<String, u64>) -> std::io::Result<()> {
+        let Some(ref path) = self.store_path else {
+            return Ok(());
+        };
+        // Write a sibling file and rename it over the store, so a crash never leaves it half-written
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(counters)?)?;
+        std::fs::rename(&tmp, path)
+    }
+}
+
+/// Generates device names from a tenant's naming template
+///
+/// Names must fit NetBox's length limit and not exist in NetBox yet; taken
+/// names are renumbered from the sequence, checking all of an order's
+/// candidates with one lookup per round.
+pub struct DeviceNamer {
+    policy: NamingPolicy,
+    sequences: NameSequences,
+    max_length: usize,
+}
+
+impl DeviceNamer {
+    pub fn new(policy: NamingPolicy, sequences: NameSequences, max_length: usize) -> Self {
+        Self {
+            policy,
+            sequences,
+            max_length,
+        }
+    }
+
+    /// Whether `tenant_id`'s devices of `order_type` orders are named by template
+    pub fn applies(&self, tenant_id: &str, order_type: &str) -> bool {
+        self.policy.template(tenant_id, order_type).is_some()
+    }
+
+    /// Names for devices of an order, one per context, in order
+    ///
+    /// Fails if the tenant has no template for `order_type`, a name is too
+    /// long, or a template without `{seq}` yields a name that's taken.
+    pub async fn name_devices(
+        &self,
+        netbox: &ResilientNetBoxClient,
+        order_type: &str,
+        contexts: &[NameContext],
+    ) -> Result<Vec<String>, AppError> {
+        let mut names = Vec::with_capacity(contexts.len());
+        let mut templates = Vec::with_capacity(contexts.len());
+        for context in contexts {
+            let template = self.policy.template(&context.tenant_id, order_type).ok_or_else(|| {
+                AppError::ValidationError(format!(
+                    "No device naming template for {} orders of tenant {}",
+                    order_type, context.tenant_id
+                ))
+            })?;
+            templates.push(template);
+            names.push(String::new());
+        }
+
+        let mut pending: Vec<usize> = (0..contexts.len()).collect();
+        let mut accepted = HashSet::new();
+        for _ in 0..MAX_COLLISION_ROUNDS {
+            for &index in &pending {
+                let seq = match templates[index].has_seq() {
+                    true => self.next_seq(templates[index], &contexts[index])?,
+                    false => 0,
+                };
+                let name = templates[index].render(&contexts[index], seq)?;
+                if name.chars().count() > self.max_length {
+                    return Err(NamingError::TooLong { name, max: self.max_length }.into());
+                }
+                names[index] = name;
+            }
+
+            let candidates: Vec<String> = pending.iter().map(|&index| names[index].clone()).collect();
+            let taken = netbox.find_device_names(&candidates).await?;
+            let mut retry = Vec::new();
+            for index in pending {
+                // Taken in NetBox, or by an earlier device of the order
+                if taken.contains(&names[index]) || !accepted.insert(names[index].clone()) {
+                    if !templates[index].has_seq() {
+                        return Err(NamingError::Taken(names[index].clone()).into());
+                    }
+                    retry.push(index);
+                }
+            }
+            if retry.is_empty() {
+                return Ok(names);
+            }
+            tracing::debug!("Renumbering {} generated device name(s) already taken", retry.len());
+            pending = retry;
+        }
+        Err(NamingError::Taken(names[pending[0]].clone()).into())
+    }
+
+    fn next_seq(&self, template: &NameTemplate, context: &NameContext) -> Result<u64, AppError> {
+        let scope = template.sequence_scope(context)?;
+        self.sequences
+            .take(&scope, 1)
+            .map(|range| range.start)
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to persist device name sequences: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::netbox::fake::FakeNetBox;
+    use serde_json::json;
+
+    fn context() -> NameContext {
+        NameContext {
+            tenant_id: "acme".to_string(),
+            site_slug: Some("ams1".to_string()),
+            role: Some(4),
+            ..Default::default()
+        }
+    }
+
+    fn pop_namer(template: &str, sequences: NameSequences) -> DeviceNamer {
+        let policy = NamingPolicy::new().with_template("*", "pop", template.parse().unwrap());
+        DeviceNamer::new(policy, sequences, 64)
+    }
+
+    #[test]
+    fn test_template_parsing_and_rendering() {
+        let template: NameTemplate = "{site_slug}-r{role}-{seq:03}".parse().unwrap();
+        assert!(template.has_seq());
+        assert_eq!(template.render(&context(), 7).unwrap(), "ams1-r4-007");
+        assert_eq!(template.render(&context(), 1234).unwrap(), "ams1-r4-1234");
+        let braces: NameTemplate = "{{{tenant}}}".parse().unwrap();
+        assert_eq!(braces.render(&context(), 0).unwrap(), "{acme}");
+
+        for (source, reason) in [
+            ("{site}-{seq}", "unknown variable 'site'"),
+            ("{site_slug", "unclosed '{'"),
+            ("site}", "unmatched '}'"),
+            ("{seq:x}", "invalid width 'x'"),
+            ("switch", "no variables"),
+        ] {
+            let error = source.parse::<NameTemplate>().unwrap_err().to_string();
+            assert!(error.contains(reason), "{}: {}", source, error);
+        }
+        let missing = "{project}-{seq}".parse::<NameTemplate>().unwrap().render(&context(), 1);
+        assert!(matches!(missing, Err(NamingError::MissingVariable { variable: "project", .. })));
+
+        let policy = NamingPolicy::from_json(r#"{"*": {"pop": "{site_slug}-{seq}"}, "acme": {"pop": "{tenant}-{seq}"}}"#).unwrap();
+        assert_eq!(policy.template("acme", "pop").unwrap().render(&context(), 1).unwrap(), "acme-1");
+        assert!(policy.template("other", "pop").is_some());
+        assert!(policy.template("acme", "device").is_none());
+        assert!(NamingPolicy::from_json(r#"{"*": {"pop": "{rack}-{seq}"}}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sequences_continue_across_restarts() {
+        let fake = FakeNetBox::start().await;
+        let netbox = fake.resilient_client();
+        let dir = std::env::temp_dir().join(format!("netgate-naming-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("sequences.json");
+
+        let namer = pop_namer("{site_slug}-{seq:02}", NameSequences::load(&path).unwrap());
+        let names = namer.name_devices(&netbox, "pop", &[context(), context()]).await.unwrap();
+        assert_eq!(names, vec!["ams1-01", "ams1-02"]);
+        let other_site = NameContext { site_slug: Some("fra1".to_string()), ..context() };
+        assert_eq!(namer.name_devices(&netbox, "pop", &[other_site]).await.unwrap(), vec!["fra1-01"]);
+
+        // A restarted service carries on where the last one stopped
+        let namer = pop_namer("{site_slug}-{seq:02}", NameSequences::load(&path).unwrap());
+        assert_eq!(namer.name_devices(&netbox, "pop", &[context()]).await.unwrap(), vec!["ams1-03"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_taken_names_are_renumbered() {
+        let fake = FakeNetBox::start().await;
+        fake.seed_device(json!({"name": "ams1-001", "site": 1}));
+        fake.seed_device(json!({"name": "ams1-002", "site": 1}));
+        let netbox = fake.resilient_client();
+
+        let namer = pop_namer("{site_slug}-{seq:03}", NameSequences::new());
+        let names = namer.name_devices(&netbox, "pop", &[context(), context()]).await.unwrap();
+        assert_eq!(names, vec!["ams1-003", "ams1-004"]);
+
+        // Without a sequence number a taken name can't be avoided
+        let namer = pop_namer("{site_slug}-{role}", NameSequences::new());
+        fake.seed_device(json!({"name": "ams1-4", "site": 1}));
+        let result = namer.name_devices(&netbox, "pop", &[context()]).await;
+        assert!(matches!(result, Err(AppError::Conflict(msg)) if msg.contains("ams1-4")));
+
+        let namer = DeviceNamer::new(
+            NamingPolicy::new().with_template("acme", "pop", "{site_slug}-{seq:20}".parse().unwrap()),
+            NameSequences::new(),
+            16,
+        );
+        let result = namer.name_devices(&netbox, "pop", &[context()]).await;
+        assert!(matches!(result, Err(AppError::ValidationError(msg)) if msg.contains("limit of 16")));
+    }
+}
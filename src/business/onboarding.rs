@@ -0,0 +1,338 @@
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+use crate::business::transformation::{TransformationProfile, TransformationProfiles};
+use crate::domain::onboarding::{
+    OnboardTenantRequest, OnboardingStatus, OnboardingStep, OnboardingStepKind, OnboardingStepStatus, TenantOnboarding,
+};
+use crate::netbox::models::CreateTenantRequest;
+use crate::netbox::{NetBoxRouter, ResilientNetBoxClient};
+use crate::r#virtual::mapping::MappingType;
+use crate::r#virtual::VirtualResourceService;
+use crate::security::tenant::{TenantMapping, TenantMappingService};
+
+/// Reasons an onboarding is refused before anything is created
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum OnboardingError {
+    #[error("{0}")]
+    Invalid(String),
+
+    #[error("Tenant '{0}' already exists")]
+    AlreadyExists(String),
+}
+
+/// What a finished step created, for undoing it
+enum Undo {
+    NetBoxTenant(Arc<ResilientNetBoxClient>, i32),
+    /// The profile the tenant had before, if any
+    Profile(Option<TransformationProfile>),
+    VirtualSite(String),
+}
+
+/// Onboards customers in one operation instead of four manual steps
+///
+/// Creates the NetBox tenant, copies a transformation profile from a
+/// template, optionally creates a virtual site, and finally registers the
+/// tenant mapping, which makes the tenant live. If a step fails, the steps
+/// before it are undone, newest first. Every onboarding is kept as an audit
+/// record of its steps.
+pub struct TenantOnboardingService {
+    router: Arc<NetBoxRouter>,
+    mappings: Arc<TenantMappingService>,
+    profiles: TransformationProfiles,
+    templates: TransformationProfiles,
+    virtual_service: Option<Arc<VirtualResourceService>>,
+    records: RwLock<Vec<TenantOnboarding>>,
+}
+
+impl TenantOnboardingService {
+    /// Onboard onto `router`'s endpoints, setting profiles in `profiles`, which
+    /// should be shared with the order service
+    pub fn new(router: Arc<NetBoxRouter>, mappings: Arc<TenantMappingService>, profiles: TransformationProfiles) -> Self {
+        Self {
+            router,
+            mappings,
+            profiles,
+            templates: TransformationProfiles::new(),
+            virtual_service: None,
+            records: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Profiles, keyed by template name, new tenants' profiles are copied from
+    pub fn with_templates(mut self, templates: TransformationProfiles) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    /// Service default virtual sites are created in
+    pub fn with_virtual_service(mut self, virtual_service: Arc<VirtualResourceService>) -> Self {
+        self.virtual_service = Some(virtual_service);
+        self
+    }
+
+    /// All onboardings, oldest first
+    pub fn onboardings(&self) -> Vec<TenantOnboarding> {
+        self.records.read().clone()
+    }
+
+    /// Onboard a tenant, returning the audit record
+    ///
+    /// A request that can't succeed is refused before anything is created. A
+    /// step failing later yields a record with status `rolled_back`.
+    pub async fn onboard(&self, request: OnboardTenantRequest, actor: String) -> Result<TenantOnboarding, OnboardingError> {
+        let tenant_id = request.tenant_id.trim().to_string();
+        if tenant_id.is_empty() {
+            return Err(OnboardingError::Invalid("tenant_id is required".to_string()));
+        }
+        if self.mappings.has_mapping(&tenant_id) {
+            return Err(OnboardingError::AlreadyExists(tenant_id));
+        }
+        let mapping = TenantMapping::new(0, request.netbox_endpoint.clone());
+        self.mappings
+            .check_endpoint(&tenant_id, &mapping)
+            .map_err(|e| OnboardingError::Invalid(e.to_string()))?;
+        let client = match request.netbox_endpoint {
+            Some(ref name) => self.router.endpoint(name),
+            None => Some(self.router.default_endpoint()),
+        }
+        .map(|endpoint| endpoint.client.clone())
+        .ok_or_else(|| OnboardingError::Invalid(format!("Unknown NetBox endpoint '{}'", request.netbox_endpoint.as_deref().unwrap_or_default())))?;
+        let template = match request.profile_template {
+            Some(ref name) => Some(
+                self.templates
+                    .profile(name)
+                    .ok_or_else(|| OnboardingError::Invalid(format!("Unknown onboarding template '{}'", name)))?,
+            ),
+            None => None,
+        };
+        if request.default_virtual_site.is_some() && self.virtual_service.is_none() {
+            return Err(OnboardingError::Invalid("Virtual resources are not available".to_string()));
+        }
+
+        let started_at = chrono::Utc::now().to_rfc3339();
+        let mut steps = Vec::new();
+        let mut undo = Vec::new();
+        let mut netbox_tenant_id = None;
+        let outcome = self
+            .run_steps(&tenant_id, request, client, template, &mut steps, &mut undo, &mut netbox_tenant_id)
+            .await;
+
+        let (status, error) = match outcome {
+            Ok(()) => {
+                tracing::info!("Tenant {} onboarded by {}", tenant_id, actor);
+                (OnboardingStatus::Completed, None)
+            }
+            Err(error) => {
+                tracing::warn!("Onboarding tenant {} failed, rolling back: {}", tenant_id, error);
+                self.roll_back(&tenant_id, undo, &mut steps).await;
+                (OnboardingStatus::RolledBack, Some(error))
+            }
+        };
+        let record = TenantOnboarding {
+            onboarding_id: uuid::Uuid::new_v4().to_string(),
+            tenant_id,
+            requested_by: actor,
+            status,
+            netbox_tenant_id,
+            steps,
+            error,
+            started_at,
+            finished_at: chrono::Utc::now().to_rfc3339(),
+        };
+        self.records.write().push(record.clone());
+        Ok(record)
+    }
+
+    /// Run the steps in order, stopping at the first failure
+    #[allow(clippy::too_many_arguments)]
+    async fn run_steps(
+        &self,
+        tenant_id: &str,
+        request: OnboardTenantRequest,
+        client: Arc<ResilientNetBoxClient>,
+        template: Option<TransformationProfile>,
+        steps: &mut Vec<OnboardingStep>,
+        undo: &mut Vec<Undo>,
+        netbox_tenant_id: &mut Option<i32>,
+    ) -> Result<(), String> {
+        let step = |steps: &mut Vec<OnboardingStep>, step, result: Result<String, String>| {
+            let (status, detail) = match result {
+                Ok(ref detail) => (OnboardingStepStatus::Done, detail.clone()),
+                Err(ref error) => (OnboardingStepStatus::Failed, error.clone()),
+            };
+            steps.push(OnboardingStep { step, status, detail: Some(detail) });
+            result.map(|_| ())
+        };
+
+        let created = client
+            .create_tenant(CreateTenantRequest {
+                name: request.netbox_tenant.name,
+                slug: request.netbox_tenant.slug,
+                description: request.netbox_tenant.description,
+            })
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|tenant| tenant.id.ok_or_else(|| "NetBox returned a tenant without an id".to_string()));
+        if let Ok(id) = created {
+            *netbox_tenant_id = Some(id);
+            undo.push(Undo::NetBoxTenant(client, id));
+        }
+        step(steps, OnboardingStepKind::NetboxTenant, created.map(|id| format!("NetBox tenant {}", id)))?;
+
+        if let Some(profile) = template {
+            let previous = self.profiles.set_profile(tenant_id, profile);
+            undo.push(Undo::Profile(previous));
+            let template = request.profile_template.unwrap_or_default();
+            step(steps, OnboardingStepKind::TransformationProfile, Ok(format!("From template '{}'", template)))?;
+        }
+
+        if let (Some(name), Some(ref virtual_service)) = (request.default_virtual_site, &self.virtual_service) {
+            let created = virtual_service
+                .create_virtual_site(name, tenant_id.to_string(), Vec::new(), MappingType::OneToOne)
+                .map_err(|e| e.to_string());
+            if let Ok(ref site) = created {
+                undo.push(Undo::VirtualSite(site.id.clone()));
+            }
+            step(steps, OnboardingStepKind::VirtualSite, created.map(|site| format!("Virtual site {}", site.id)))?;
+        }
+
+        let mapping = TenantMapping::new(netbox_tenant_id.unwrap_or_default(), request.netbox_endpoint);
+        let registered = match self.mappings.insert_mapping(tenant_id.to_string(), mapping).await {
+            Ok(true) => Ok("Tenant mapping registered".to_string()),
+            Ok(false) => Err(format!("Tenant '{}' was registered meanwhile", tenant_id)),
+            Err(e) => Err(e.to_string()),
+        };
+        step(steps, OnboardingStepKind::TenantMapping, registered)
+    }
+
+    /// Undo finished steps, newest first, marking each as rolled back or not
+    async fn roll_back(&self, tenant_id: &str, undo: Vec<Undo>, steps: &mut [OnboardingStep]) {
+        let mut done = steps.iter_mut().filter(|step| step.status == OnboardingStepStatus::Done).rev();
+        for action in undo.into_iter().rev() {
+            let result = match action {
+                Undo::NetBoxTenant(client, id) => client.delete_tenant(id).await.map_err(|e| e.to_string()),
+                Undo::Profile(Some(previous)) => {
+                    self.profiles.set_profile(tenant_id, previous);
+                    Ok(())
+                }
+                Undo::Profile(None) => {
+                    self.profiles.remove_profile(tenant_id);
+                    Ok(())
+                }
+                Undo::VirtualSite(ref id) => self
+                    .virtual_service
+                    .as_ref()
+                    .map_or(Ok(()), |service| service.store().delete_virtual_site(id).map(|_| ()))
+                    .map_err(|e| e.to_string()),
+            };
+            let Some(step) = done.next() else {
+                continue;
+            };
+            match result {
+                Ok(()) => step.status = OnboardingStepStatus::RolledBack,
+                Err(e) => {
+                    tracing::error!("Failed to roll back {:?} of onboarding tenant {}: {}", step.step, tenant_id, e);
+                    step.status = OnboardingStepStatus::RollbackFailed;
+                    step.detail = Some(format!("Rollback failed: {}", e));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::tenant::NewNetBoxTenant;
+    use crate::netbox::fake::FakeNetBox;
+    use crate::netbox::NetBoxEndpoint;
+    use crate::security::tenant::{JsonFileTenantMappingStore, DEFAULT_NETBOX_ENDPOINT};
+
+    fn service(fake: &FakeNetBox, mappings: Arc<TenantMappingService>) -> (TenantOnboardingService, TransformationProfiles) {
+        let endpoint = NetBoxEndpoint::new(DEFAULT_NETBOX_ENDPOINT, &fake.config(), fake.client());
+        let router = Arc::new(NetBoxRouter::new(endpoint, mappings.clone()));
+        let profiles = TransformationProfiles::new();
+        let templates = TransformationProfiles::new().with_profile(
+            "standard",
+            TransformationProfile { tags: vec!["managed".to_string()], ..Default::default() },
+        );
+        let service = TenantOnboardingService::new(router, mappings, profiles.clone())
+            .with_templates(templates)
+            .with_virtual_service(Arc::new(VirtualResourceService::new()));
+        (service, profiles)
+    }
+
+    fn request(template: Option<&str>) -> OnboardTenantRequest {
+        OnboardTenantRequest {
+            tenant_id: "acme".to_string(),
+            netbox_tenant: NewNetBoxTenant { name: "Acme".to_string(), slug: "acme".to_string(), description: None },
+            netbox_endpoint: None,
+            profile_template: template.map(str::to_string),
+            default_virtual_site: Some("Acme HQ".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_onboard_provisions_tenant_and_defaults() {
+        let fake = FakeNetBox::start().await;
+        let mappings = Arc::new(TenantMappingService::new());
+        let (service, profiles) = service(&fake, mappings.clone());
+
+        let record = service.onboard(request(Some("standard")), "ops".to_string()).await.unwrap();
+
+        assert_eq!(record.status, OnboardingStatus::Completed);
+        let kinds: Vec<_> = record.steps.iter().map(|step| (step.step, step.status)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (OnboardingStepKind::NetboxTenant, OnboardingStepStatus::Done),
+                (OnboardingStepKind::TransformationProfile, OnboardingStepStatus::Done),
+                (OnboardingStepKind::VirtualSite, OnboardingStepStatus::Done),
+                (OnboardingStepKind::TenantMapping, OnboardingStepStatus::Done),
+            ]
+        );
+        let netbox_tenant_id = record.netbox_tenant_id.unwrap();
+        assert_eq!(fake.object("tenancy/tenants", netbox_tenant_id).unwrap()["slug"], "acme");
+        assert_eq!(mappings.get_netbox_tenant_id(&"acme".to_string()), Some(netbox_tenant_id));
+        assert_eq!(profiles.profile("acme").unwrap().tags, vec!["managed"]);
+        assert_eq!(service.onboardings(), vec![record]);
+
+        assert_eq!(
+            service.onboard(request(None), "ops".to_string()).await,
+            Err(OnboardingError::AlreadyExists("acme".to_string()))
+        );
+        let unknown = OnboardTenantRequest { tenant_id: "initech".to_string(), ..request(Some("gold")) };
+        assert!(matches!(service.onboard(unknown, "ops".to_string()).await, Err(OnboardingError::Invalid(_))));
+        assert_eq!(fake.objects("tenancy/tenants").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_onboard_rolls_back_when_mapping_store_fails() {
+        let fake = FakeNetBox::start().await;
+        // The store's directory doesn't exist, so registering the mapping fails
+        let store = Arc::new(JsonFileTenantMappingStore::new("/nonexistent/netgate/tenants.json"));
+        let mappings = Arc::new(TenantMappingService::load(store).await.unwrap());
+        let (service, profiles) = service(&fake, mappings.clone());
+        let virtual_service = service.virtual_service.clone().unwrap();
+
+        let record = service.onboard(request(Some("standard")), "ops".to_string()).await.unwrap();
+
+        assert_eq!(record.status, OnboardingStatus::RolledBack);
+        assert!(record.error.unwrap().contains("Failed to persist"));
+        let statuses: Vec<_> = record.steps.iter().map(|step| step.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                OnboardingStepStatus::RolledBack,
+                OnboardingStepStatus::RolledBack,
+                OnboardingStepStatus::RolledBack,
+                OnboardingStepStatus::Failed,
+            ]
+        );
+        assert!(fake.objects("tenancy/tenants").is_empty());
+        assert!(profiles.profile("acme").is_none());
+        assert!(virtual_service.store().get_tenant_virtual_sites("acme").is_empty());
+        assert!(!mappings.has_mapping(&"acme".to_string()));
+    }
+}
@@ -0,0 +1,238 @@
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::clock::{SharedClock, SystemClock};
+
+/// Why the claim store couldn't be used
+#[derive(Debug, thiserror::Error)]
+pub enum ClaimStoreError {
+    #[error("Order claim store failed: {0}")]
+    Backend(String),
+}
+
+/// Which worker this replica is, and how long its leases last
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderClaimConfig {
+    /// Unique among the replicas sharing the workflow store
+    pub worker_id: String,
+    /// An order is claimable by another worker once this long passes without a renewal
+    pub lease: Duration,
+}
+
+impl Default for OrderClaimConfig {
+    fn default() -> Self {
+        Self {
+            worker_id: format!("netgate-{}", uuid::Uuid::new_v4()),
+            lease: Duration::from_secs(60),
+        }
+    }
+}
+
+impl OrderClaimConfig {
+    /// Load from NETGATE_WORKER_ID and ORDER_CLAIM_LEASE_SECS
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            worker_id: std::env::var("NETGATE_WORKER_ID")
+                .ok()
+                .filter(|id| !id.is_empty())
+                .unwrap_or(defaults.worker_id),
+            lease: std::env::var("ORDER_CLAIM_LEASE_SECS")
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.lease),
+        }
+    }
+}
+
+/// Leases that let workers sharing a workflow store execute each order once
+///
+/// A worker claims an order before executing it and renews the lease while
+/// it runs. Once the order is done the worker finishes the claim, and the
+/// order can't be claimed again; a worker that gives up releases it instead.
+/// A lease that isn't renewed expires, and the order can then be claimed by
+/// another worker.
+#[async_trait]
+pub trait OrderClaimStore: Send + Sync {
+    /// Take or extend the worker's lease; false while another worker holds a live one
+    async fn claim(&self, order_id: &str, worker_id: &str, lease: Duration) -> Result<bool, ClaimStoreError>;
+    /// Extend the worker's lease; false when it expired and was taken over, or isn't held
+    async fn renew(&self, order_id: &str, worker_id: &str, lease: Duration) -> Result<bool, ClaimStoreError>;
+    /// Give up the worker's lease so another worker can claim the order; false when it wasn't held
+    async fn release(&self, order_id: &str, worker_id: &str) -> Result<bool, ClaimStoreError>;
+    /// Record that the worker is done with the order; it can't be claimed again
+    async fn finish(&self, order_id: &str, worker_id: &str) -> Result<bool, ClaimStoreError>;
+}
+
+#[derive(Debug, Clone)]
+enum Claim {
+    Leased { worker_id: String, expires_at: chrono::DateTime<chrono::Utc> },
+    Finished,
+}
+
+/// Claims of the workers of one process
+pub struct InMemoryOrderClaims {
+    claims: Mutex<HashMap<String, Claim>>,
+    clock: SharedClock,
+}
+
+impl InMemoryOrderClaims {
+    pub fn new() -> Self {
+        Self {
+            claims: Mutex::new(HashMap::new()),
+            clock: SystemClock::shared(),
+        }
+    }
+
+    /// Use `clock` to tell when leases expire
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    fn lease_expiry(now: chrono::DateTime<chrono::Utc>, lease: Duration) -> chrono::DateTime<chrono::Utc> {
+        let lease = chrono::Duration::from_std(lease).unwrap_or(chrono::Duration::MAX);
+        now.checked_add_signed(lease).unwrap_or(chrono::DateTime::<chrono::Utc>::MAX_UTC)
+    }
+
+    /// Whether `worker_id` holds a live lease on the order
+    fn holds(claims: &HashMap<String, Claim>, order_id: &str, worker_id: &str, now: chrono::DateTime<chrono::Utc>) -> bool {
+        matches!(
+            claims.get(order_id),
+            Some(Claim::Leased { worker_id: holder, expires_at }) if holder == worker_id && *expires_at > now
+        )
+    }
+}
+
+impl Default for InMemoryOrderClaims {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl OrderClaimStore for InMemoryOrderClaims {
+    async fn claim(&self, order_id: &str, worker_id: &str, lease: Duration) -> Result<bool, ClaimStoreError> {
+        let now = self.clock.now_utc();
+        let mut claims = self.claims.lock();
+        match claims.get(order_id) {
+            Some(Claim::Finished) => return Ok(false),
+            Some(Claim::Leased { worker_id: holder, expires_at }) if holder != worker_id => {
+                if *expires_at > now {
+                    return Ok(false);
+                }
+                tracing::warn!("Lease of worker {} on order {} expired, handing it to worker {}", holder, order_id, worker_id);
+            }
+            _ => {}
+        }
+        claims.insert(
+            order_id.to_string(),
+            Claim::Leased { worker_id: worker_id.to_string(), expires_at: Self::lease_expiry(now, lease) },
+        );
+        Ok(true)
+    }
+
+    async fn renew(&self, order_id: &str, worker_id: &str, lease: Duration) -> Result<bool, ClaimStoreError> {
+        let now = self.clock.now_utc();
+        let mut claims = self.claims.lock();
+        if !Self::holds(&claims, order_id, worker_id, now) {
+            return Ok(false);
+        }
+        claims.insert(
+            order_id.to_string(),
+            Claim::Leased { worker_id: worker_id.to_string(), expires_at: Self::lease_expiry(now, lease) },
+        );
+        Ok(true)
+    }
+
+    async fn release(&self, order_id: &str, worker_id: &str) -> Result<bool, ClaimStoreError> {
+        let now = self.clock.now_utc();
+        let mut claims = self.claims.lock();
+        if !Self::holds(&claims, order_id, worker_id, now) {
+            return Ok(false);
+        }
+        claims.remove(order_id);
+        Ok(true)
+    }
+
+    async fn finish(&self, order_id: &str, worker_id: &str) -> Result<bool, ClaimStoreError> {
+        let now = self.clock.now_utc();
+        let mut claims = self.claims.lock();
+        if !Self::holds(&claims, order_id, worker_id, now) {
+            return Ok(false);
+        }
+        claims.insert(order_id.to_string(), Claim::Finished);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    const LEASE: Duration = Duration::from_secs(30);
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_concurrent_workers_process_each_order_once() {
+        let claims = Arc::new(InMemoryOrderClaims::new());
+        let order_ids: Vec<String> = (0..200).map(|n| format!("order-{}", n)).collect();
+
+        let workers: Vec<_> = ["worker-a", "worker-b"]
+            .into_iter()
+            .map(|worker_id| {
+                let (claims, order_ids) = (claims.clone(), order_ids.clone());
+                tokio::spawn(async move {
+                    let mut processed = Vec::new();
+                    for order_id in order_ids {
+                        if claims.claim(&order_id, worker_id, LEASE).await.unwrap() {
+                            assert!(claims.renew(&order_id, worker_id, LEASE).await.unwrap());
+                            processed.push(order_id.clone());
+                            assert!(claims.finish(&order_id, worker_id).await.unwrap());
+                        }
+                        tokio::task::yield_now().await;
+                    }
+                    processed
+                })
+            })
+            .collect();
+        let mut processed = Vec::new();
+        for worker in workers {
+            processed.extend(worker.await.unwrap());
+        }
+
+        assert_eq!(processed.len(), order_ids.len());
+        assert_eq!(processed.into_iter().collect::<HashSet<_>>(), order_ids.into_iter().collect());
+    }
+
+    #[tokio::test]
+    async fn test_expired_lease_makes_order_claimable_again() {
+        let clock = Arc::new(ManualClock::new());
+        let claims = InMemoryOrderClaims::new().with_clock(clock.clone());
+        assert!(claims.claim("order-1", "a", LEASE).await.unwrap());
+        assert!(claims.claim("order-2", "b", LEASE).await.unwrap());
+        assert!(!claims.claim("order-1", "b", LEASE).await.unwrap());
+
+        // a keeps its lease alive, b's lapses and c takes the order over
+        clock.advance(Duration::from_secs(20));
+        assert!(claims.renew("order-1", "a", LEASE).await.unwrap());
+        clock.advance(Duration::from_secs(20));
+        assert!(!claims.claim("order-1", "c", LEASE).await.unwrap());
+        assert!(claims.claim("order-2", "c", LEASE).await.unwrap());
+        assert!(!claims.renew("order-2", "b", LEASE).await.unwrap());
+        assert!(!claims.finish("order-2", "b").await.unwrap());
+
+        // Released orders are claimable right away, finished ones never again
+        assert!(claims.release("order-2", "c").await.unwrap());
+        assert!(claims.claim("order-2", "b", LEASE).await.unwrap());
+        assert!(claims.finish("order-1", "a").await.unwrap());
+        clock.advance(LEASE * 2);
+        assert!(!claims.claim("order-1", "c", LEASE).await.unwrap());
+    }
+}
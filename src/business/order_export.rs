@@ -0,0 +1,301 @@
+use serde::Serialize;
+use std::borrow::Cow;
+use tokio::io::AsyncWriteExt;
+
+use crate::business::workflow::{OrderNote, OrderState, OrderWorkflow, WorkflowManager};
+
+/// Columns of an order export, in CSV header order
+pub const EXPORT_COLUMNS: [&str; 9] = [
+    "order_id",
+    "tenant_id",
+    "state",
+    "created_at",
+    "updated_at",
+    "netbox_site_id",
+    "error_message",
+    "labels",
+    "notes",
+];
+
+/// Bytes buffered between the export writer and the response body
+const EXPORT_BUFFER_BYTES: usize = 64 * 1024;
+
+/// File format of an order export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    /// One JSON object per line
+    Jsonl,
+}
+
+impl ExportFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "jsonl" => Some(ExportFormat::Jsonl),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Jsonl => "jsonl",
+        }
+    }
+}
+
+/// Which workflows an export includes
+#[derive(Debug, Clone, Default)]
+pub struct OrderExportFilter {
+    /// Only this tenant's orders; every tenant's when `None`
+    pub tenant_id: Option<String>,
+    /// Orders created at or after this time
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    /// Orders created before this time
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    pub state: Option<OrderState>,
+}
+
+impl OrderExportFilter {
+    pub fn matches(&self, workflow: &OrderWorkflow) -> bool {
+        self.tenant_id.as_ref().is_none_or(|tenant| *tenant == workflow.tenant_id)
+            && self.from.is_none_or(|from| workflow.created_at >= from)
+            && self.to.is_none_or(|to| workflow.created_at < to)
+            && self.state.is_none_or(|state| workflow.state == state)
+    }
+}
+
+/// One exported order
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExportRow {
+    pub order_id: String,
+    pub tenant_id: String,
+    pub state: &'static str,
+    pub created_at: String,
+    pub updated_at: String,
+    pub netbox_site_id: Option<i32>,
+    pub error_message: Option<String>,
+    pub labels: Vec<String>,
+    /// Operator notes, oldest first
+    pub notes: Vec<OrderNote>,
+}
+
+impl From<&OrderWorkflow> for ExportRow {
+    fn from(workflow: &OrderWorkflow) -> Self {
+        Self {
+            order_id: workflow.order_id.clone(),
+            tenant_id: workflow.tenant_id.clone(),
+            state: workflow.state.as_str(),
+            created_at: workflow.created_at.to_rfc3339(),
+            updated_at: workflow.updated_at.to_rfc3339(),
+            netbox_site_id: workflow.netbox_site_id,
+            error_message: workflow.error_message.clone(),
+            labels: workflow.labels.clone(),
+            notes: workflow.notes.clone(),
+        }
+    }
+}
+
+impl ExportRow {
+    /// The row as a CSV record, terminated by CRLF
+    ///
+    /// Labels are joined with `;`, and notes are one `<at> <author>: <text>` line each.
+    pub fn to_csv(&self) -> String {
+        let site_id = self.netbox_site_id.map(|id| id.to_string()).unwrap_or_default();
+        let labels = self.labels.join(";");
+        let notes = self
+            .notes
+            .iter()
+            .map(|note| format!("{} {}: {}", note.at.to_rfc3339(), note.author, note.text))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let fields = [
+            self.order_id.as_str(),
+            self.tenant_id.as_str(),
+            self.state,
+            self.created_at.as_str(),
+            self.updated_at.as_str(),
+            site_id.as_str(),
+            self.error_message.as_deref().unwrap_or(""),
+            labels.as_str(),
+            notes.as_str(),
+        ];
+        let mut line = fields.map(csv_field).join(",");
+        line.push_str("\r\n");
+        line
+    }
+}
+
+/// Quote a CSV field if it contains a delimiter, quote or line break (RFC 4180)
+pub fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\r', '\n']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// Rows of the workflows matching `filter`, oldest first
+pub fn export_rows(manager: &WorkflowManager, filter: &OrderExportFilter) -> Vec<ExportRow> {
+    let mut rows = manager.filter_map_orders(|workflow| filter.matches(workflow).then(|| ExportRow::from(workflow)));
+    rows.sort_by(|a, b| (&a.created_at, &a.order_id).cmp(&(&b.created_at, &b.order_id)));
+    rows
+}
+
+/// Stream `rows` as a response body, serializing them as the client reads
+pub fn export_body(rows: Vec<ExportRow>, format: ExportFormat) -> poem::Body {
+    let (mut writer, reader) = tokio::io::duplex(EXPORT_BUFFER_BYTES);
+    tokio::spawn(async move {
+        if format == ExportFormat::Csv {
+            let header = format!("{}\r\n", EXPORT_COLUMNS.join(","));
+            if writer.write_all(header.as_bytes()).await.is_err() {
+                return;
+            }
+        }
+        for row in rows {
+            let line = match format {
+                ExportFormat::Csv => row.to_csv(),
+                ExportFormat::Jsonl => match serde_json::to_string(&row) {
+                    Ok(json) => json + "\n",
+                    Err(e) => {
+                        tracing::warn!("Failed to serialize order {} for export: {}", row.order_id, e);
+                        continue;
+                    }
+                },
+            };
+            // The client went away; stop serializing
+            if writer.write_all(line.as_bytes()).await.is_err() {
+                return;
+            }
+        }
+    });
+    poem::Body::from_async_read(reader)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Split CSV text into records of fields, undoing RFC 4180 quoting
+    fn parse_csv(text: &str) -> Vec<Vec<String>> {
+        let mut records = Vec::new();
+        let mut record = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (quoted, c) {
+                (true, '"') if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                (true, '"') => quoted = false,
+                (true, c) => field.push(c),
+                (false, '"') => quoted = true,
+                (false, ',') => record.push(std::mem::take(&mut field)),
+                (false, '\r') => {}
+                (false, '\n') => {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+                (false, c) => field.push(c),
+            }
+        }
+        records
+    }
+
+    async fn read_body(body: poem::Body) -> String {
+        body.into_string().await.unwrap()
+    }
+
+    fn failed_order(manager: &WorkflowManager, tenant_id: &str, error: &str) -> String {
+        let order_id = manager.create_order(tenant_id.to_string());
+        manager.update_order_state(&order_id, OrderState::Validated).unwrap();
+        manager.update_order_state(&order_id, OrderState::Processing).unwrap();
+        manager.mark_order_failed(&order_id, error.to_string()).unwrap();
+        order_id
+    }
+
+    #[test]
+    fn test_csv_field_escaping() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("line\nbreak"), "\"line\nbreak\"");
+    }
+
+    #[tokio::test]
+    async fn test_csv_export_round_trips_workflows() {
+        let manager = WorkflowManager::new();
+        let failed = failed_order(&manager, "acme", "NetBox said: \"slug, name\" taken\nretry later");
+        manager.add_note(&failed, "alice".to_string(), "waiting on customer".to_string()).unwrap();
+        manager.add_note(&failed, "bob".to_string(), "NetBox ticket #123, see \"slug\"".to_string()).unwrap();
+        manager.set_labels(&failed, vec!["batch-migration".to_string(), "emea".to_string()]).unwrap();
+        let completed = manager.create_order("acme".to_string());
+        manager.update_order_state(&completed, OrderState::Validated).unwrap();
+        manager.update_order_state(&completed, OrderState::Processing).unwrap();
+        manager.mark_order_completed(&completed, 42).unwrap();
+        failed_order(&manager, "other", "not exported");
+
+        let filter = OrderExportFilter {
+            tenant_id: Some("acme".to_string()),
+            ..Default::default()
+        };
+        let rows = export_rows(&manager, &filter);
+        assert_eq!(rows.len(), 2);
+        let csv = read_body(export_body(rows, ExportFormat::Csv)).await;
+
+        let records = parse_csv(&csv);
+        assert_eq!(records[0], EXPORT_COLUMNS);
+        assert_eq!(records.len(), 3);
+        for record in &records[1..] {
+            let workflow = manager.get_order(&record[0]).unwrap();
+            let site_id = workflow.netbox_site_id.map(|id| id.to_string()).unwrap_or_default();
+            let expected = [
+                workflow.order_id.clone(),
+                workflow.tenant_id.clone(),
+                workflow.state.as_str().to_string(),
+                workflow.created_at.to_rfc3339(),
+                workflow.updated_at.to_rfc3339(),
+                site_id,
+                workflow.error_message.clone().unwrap_or_default(),
+                workflow.labels.join(";"),
+                workflow
+                    .notes
+                    .iter()
+                    .map(|note| format!("{} {}: {}", note.at.to_rfc3339(), note.author, note.text))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ];
+            assert_eq!(record, &expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_jsonl_export_filters_by_state_and_time() {
+        let manager = WorkflowManager::new();
+        let failed = failed_order(&manager, "acme", "boom");
+        manager.add_note(&failed, "alice".to_string(), "retried by hand".to_string()).unwrap();
+        manager.create_order("acme".to_string());
+
+        let filter = OrderExportFilter {
+            state: Some(OrderState::Failed),
+            from: Some(chrono::Utc::now() - chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        let jsonl = read_body(export_body(export_rows(&manager, &filter), ExportFormat::Jsonl)).await;
+        let lines: Vec<serde_json::Value> = jsonl.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0]["order_id"], failed.as_str());
+        assert_eq!(lines[0]["error_message"], "boom");
+        assert_eq!(lines[0]["notes"][0]["author"], "alice");
+        assert_eq!(lines[0]["notes"][0]["text"], "retried by hand");
+
+        let future = OrderExportFilter {
+            from: Some(chrono::Utc::now() + chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        assert!(export_rows(&manager, &future).is_empty());
+    }
+}
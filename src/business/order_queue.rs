@@ -0,0 +1,342 @@
+use parking_lot::Mutex;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::time::{Duration, Instant};
+
+use crate::clock::{SharedClock, SystemClock};
+use crate::domain::OrderPriority;
+use crate::security::tenant::TenantId;
+
+/// Limits of the order queue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderQueueConfig {
+    /// High priority orders one tenant may have queued at once
+    pub high_priority_per_tenant: usize,
+    /// Low priority orders waiting longer than this are promoted to normal
+    pub promote_low_after: Duration,
+}
+
+impl Default for OrderQueueConfig {
+    fn default() -> Self {
+        Self {
+            high_priority_per_tenant: 10,
+            promote_low_after: Duration::from_secs(300),
+        }
+    }
+}
+
+impl OrderQueueConfig {
+    /// Load from ORDER_QUEUE_HIGH_PRIORITY_PER_TENANT and ORDER_QUEUE_PROMOTE_LOW_AFTER_SECS
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            high_priority_per_tenant: std::env::var("ORDER_QUEUE_HIGH_PRIORITY_PER_TENANT")
+                .ok()
+                .and_then(|cap| cap.parse().ok())
+                .unwrap_or(defaults.high_priority_per_tenant),
+            promote_low_after: std::env::var("ORDER_QUEUE_PROMOTE_LOW_AFTER_SECS")
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.promote_low_after),
+        }
+    }
+}
+
+/// Why an order was refused a place in the queue
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum OrderQueueError {
+    #[error("Tenant {tenant_id} already has {cap} high priority order(s) queued")]
+    HighPriorityCapReached { tenant_id: TenantId, cap: usize },
+}
+
+/// An order taken off the queue
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedOrder<T> {
+    pub tenant_id: TenantId,
+    /// Priority the order was submitted with, before any promotion
+    pub priority: OrderPriority,
+    pub order: T,
+    /// Time spent in the queue
+    pub waited: Duration,
+}
+
+/// Orders dequeued and the time they waited, per submitted priority
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct QueueWaitStats {
+    pub dequeued: u64,
+    pub wait_sum_secs: f64,
+    pub max_wait_secs: f64,
+}
+
+struct Entry<T> {
+    /// Priority the queue orders by, raised when a low priority order is promoted
+    effective: OrderPriority,
+    sequence: u64,
+    enqueued_at: Instant,
+    tenant_id: TenantId,
+    priority: OrderPriority,
+    order: T,
+}
+
+impl<T> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Entry<T> {}
+
+impl<T> PartialOrd for Entry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Entry<T> {
+    /// Higher priority first, then first come first served
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.effective
+            .cmp(&other.effective)
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+struct QueueState<T> {
+    heap: BinaryHeap<Entry<T>>,
+    next_sequence: u64,
+    high_priority_queued: HashMap<TenantId, usize>,
+    waits: HashMap<OrderPriority, QueueWaitStats>,
+}
+
+impl<T> QueueState<T> {
+    /// Account for an entry leaving the queue
+    fn dequeued(&mut self, entry: Entry<T>, now: Instant) -> QueuedOrder<T> {
+        if entry.priority == OrderPriority::High {
+            if let Some(queued) = self.high_priority_queued.get_mut(&entry.tenant_id) {
+                *queued = queued.saturating_sub(1);
+                if *queued == 0 {
+                    self.high_priority_queued.remove(&entry.tenant_id);
+                }
+            }
+        }
+        let waited = now.saturating_duration_since(entry.enqueued_at);
+        let stats = self.waits.entry(entry.priority).or_default();
+        stats.dequeued += 1;
+        stats.wait_sum_secs += waited.as_secs_f64();
+        stats.max_wait_secs = stats.max_wait_secs.max(waited.as_secs_f64());
+
+        QueuedOrder {
+            tenant_id: entry.tenant_id,
+            priority: entry.priority,
+            order: entry.order,
+            waited,
+        }
+    }
+}
+
+/// Queue of orders awaiting execution, highest priority first
+///
+/// Orders of equal priority leave in submission order. Low priority orders
+/// that wait longer than `promote_low_after` are promoted to normal, so a
+/// steady stream of normal orders can't starve them.
+pub struct OrderQueue<T> {
+    state: Mutex<QueueState<T>>,
+    config: OrderQueueConfig,
+    clock: SharedClock,
+}
+
+impl<T> OrderQueue<T> {
+    pub fn new(config: OrderQueueConfig) -> Self {
+        Self {
+            state: Mutex::new(QueueState {
+                heap: BinaryHeap::new(),
+                next_sequence: 0,
+                high_priority_queued: HashMap::new(),
+                waits: HashMap::new(),
+            }),
+            config,
+            clock: SystemClock::shared(),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn config(&self) -> &OrderQueueConfig {
+        &self.config
+    }
+
+    /// Queue an order, refusing high priority ones past the tenant's cap
+    pub fn push(&self, tenant_id: TenantId, priority: OrderPriority, order: T) -> Result<(), OrderQueueError> {
+        let mut state = self.state.lock();
+        if priority == OrderPriority::High {
+            let queued = state.high_priority_queued.entry(tenant_id.clone()).or_default();
+            if *queued >= self.config.high_priority_per_tenant {
+                return Err(OrderQueueError::HighPriorityCapReached {
+                    tenant_id,
+                    cap: self.config.high_priority_per_tenant,
+                });
+            }
+            *queued += 1;
+        }
+
+        let sequence = state.next_sequence;
+        state.next_sequence += 1;
+        state.heap.push(Entry {
+            effective: priority,
+            sequence,
+            enqueued_at: self.clock.now_instant(),
+            tenant_id,
+            priority,
+            order,
+        });
+        Ok(())
+    }
+
+    /// Take the next order, if any is queued
+    pub fn try_pop(&self) -> Option<QueuedOrder<T>> {
+        let now = self.clock.now_instant();
+        let mut state = self.state.lock();
+        self.promote_starved(&mut state, now);
+        let entry = state.heap.pop()?;
+        Some(state.dequeued(entry, now))
+    }
+
+    /// Take the first queued order `matches` accepts, wherever it is in the queue
+    pub fn remove(&self, matches: impl Fn(&T) -> bool) -> Option<QueuedOrder<T>> {
+        let now = self.clock.now_instant();
+        let mut state = self.state.lock();
+        let mut entries = std::mem::take(&mut state.heap).into_vec();
+        let position = entries.iter().position(|entry| matches(&entry.order));
+        let entry = position.map(|position| entries.swap_remove(position));
+        state.heap = BinaryHeap::from(entries);
+        entry.map(|entry| state.dequeued(entry, now))
+    }
+
+    /// Orders currently queued
+    pub fn len(&self) -> usize {
+        self.state.lock().heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Queued orders per submitted priority, highest first
+    pub fn depth_by_priority(&self) -> Vec<(OrderPriority, usize)> {
+        let state = self.state.lock();
+        OrderPriority::ALL
+            .into_iter()
+            .map(|priority| (priority, state.heap.iter().filter(|entry| entry.priority == priority).count()))
+            .collect()
+    }
+
+    /// Queue wait times of dequeued orders per submitted priority, highest first
+    pub fn wait_stats(&self) -> Vec<(OrderPriority, QueueWaitStats)> {
+        let state = self.state.lock();
+        OrderPriority::ALL
+            .into_iter()
+            .map(|priority| (priority, state.waits.get(&priority).copied().unwrap_or_default()))
+            .collect()
+    }
+
+    /// Raise low priority orders that waited too long to normal
+    fn promote_starved(&self, state: &mut QueueState<T>, now: Instant) {
+        let starved = |entry: &Entry<T>| {
+            entry.effective == OrderPriority::Low
+                && now.saturating_duration_since(entry.enqueued_at) >= self.config.promote_low_after
+        };
+        if !state.heap.iter().any(starved) {
+            return;
+        }
+        let mut entries = std::mem::take(&mut state.heap).into_vec();
+        for entry in entries.iter_mut().filter(|entry| starved(entry)) {
+            entry.effective = OrderPriority::Normal;
+        }
+        state.heap = BinaryHeap::from(entries);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use std::sync::Arc;
+
+    fn queue(clock: Arc<ManualClock>) -> OrderQueue<&'static str> {
+        OrderQueue::new(OrderQueueConfig {
+            high_priority_per_tenant: 2,
+            promote_low_after: Duration::from_secs(60),
+        })
+        .with_clock(clock)
+    }
+
+    fn drain(queue: &OrderQueue<&'static str>) -> Vec<&'static str> {
+        std::iter::from_fn(|| queue.try_pop().map(|queued| queued.order)).collect()
+    }
+
+    #[test]
+    fn test_orders_leave_by_priority_then_submission() {
+        let clock = Arc::new(ManualClock::new());
+        let queue = queue(clock.clone());
+        queue.push("t1".to_string(), OrderPriority::Low, "import-1").unwrap();
+        queue.push("t1".to_string(), OrderPriority::Normal, "site-1").unwrap();
+        queue.push("t2".to_string(), OrderPriority::High, "decommission-1").unwrap();
+        queue.push("t1".to_string(), OrderPriority::Low, "import-2").unwrap();
+        queue.push("t2".to_string(), OrderPriority::Normal, "site-2").unwrap();
+        queue.push("t1".to_string(), OrderPriority::High, "decommission-2").unwrap();
+        assert_eq!(
+            queue.depth_by_priority(),
+            vec![(OrderPriority::High, 2), (OrderPriority::Normal, 2), (OrderPriority::Low, 2)]
+        );
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(
+            drain(&queue),
+            vec!["decommission-1", "decommission-2", "site-1", "site-2", "import-1", "import-2"]
+        );
+        let waits = queue.wait_stats();
+        let high = QueueWaitStats { dequeued: 2, wait_sum_secs: 20.0, max_wait_secs: 10.0 };
+        assert_eq!(waits[0], (OrderPriority::High, high));
+    }
+
+    #[test]
+    fn test_starved_low_priority_orders_are_promoted() {
+        let clock = Arc::new(ManualClock::new());
+        let queue = queue(clock.clone());
+        queue.push("t1".to_string(), OrderPriority::Low, "import").unwrap();
+        clock.advance(Duration::from_secs(30));
+        queue.push("t1".to_string(), OrderPriority::Normal, "site-1").unwrap();
+        clock.advance(Duration::from_secs(30));
+        queue.push("t1".to_string(), OrderPriority::Normal, "site-2").unwrap();
+        queue.push("t1".to_string(), OrderPriority::High, "decommission").unwrap();
+
+        // The import waited 60s: it goes ahead of normal orders submitted after it, not of high ones
+        assert_eq!(drain(&queue), vec!["decommission", "import", "site-1", "site-2"]);
+    }
+
+    #[test]
+    fn test_high_priority_capped_per_tenant() {
+        let queue = queue(Arc::new(ManualClock::new()));
+        queue.push("t1".to_string(), OrderPriority::High, "a").unwrap();
+        queue.push("t1".to_string(), OrderPriority::High, "b").unwrap();
+        assert_eq!(
+            queue.push("t1".to_string(), OrderPriority::High, "c"),
+            Err(OrderQueueError::HighPriorityCapReached { tenant_id: "t1".to_string(), cap: 2 })
+        );
+        queue.push("t2".to_string(), OrderPriority::High, "d").unwrap();
+        queue.push("t1".to_string(), OrderPriority::Normal, "e").unwrap();
+
+        assert_eq!(queue.try_pop().unwrap().order, "a");
+        queue.push("t1".to_string(), OrderPriority::High, "c").unwrap();
+
+        // Taking an order out of turn frees its place under the cap too
+        assert_eq!(queue.remove(|order| *order == "b").unwrap().order, "b");
+        assert!(queue.remove(|order| *order == "b").is_none());
+        queue.push("t1".to_string(), OrderPriority::High, "f").unwrap();
+        assert_eq!(drain(&queue), vec!["d", "c", "f", "e"]);
+    }
+}
@@ -1,15 +1,30 @@
 use crate::business::{
-    OrderTransformer, OrderValidator, ObjectEnricher, EnrichmentData,
-    OrderState, WorkflowManager,
+    OrderTransformer, OrderValidator, ObjectEnricher, EnrichmentConfig, EnrichmentData, EnrichmentReport,
+    ApprovalPolicy, DeviceOrderProcessor, NetBoxResourceRequest, OrderProcessor, OrderState,
+    OrderNote, OrderStep, OrderWorkflow, ResourceKind, TransformationProfiles, RollbackEntry, RollbackReport,
+    WorkflowError, WorkflowManager,
 };
-use crate::domain::CreateSiteOrder;
+use crate::business::address::UNVERIFIED_ADDRESS_TAG;
+use crate::business::maintenance::{FreezeMode, MaintenanceWindows};
+use crate::business::order_claims::{OrderClaimConfig, OrderClaimStore};
+use crate::business::order_queue::{OrderQueue, OrderQueueConfig, OrderQueueError};
+use crate::business::naming::{DeviceNamer, NameContext};
+use crate::clock::{SharedClock, SystemClock};
+use crate::domain::{CreatePopOrder, CreateSiteOrder, DecommissionSiteOrder, OrderPriority, UpdateSiteOrder};
 use crate::error::AppError;
+use crate::netbox::custom_fields::SITE_OBJECT_TYPE;
+use crate::netbox::resilient_client::DEFAULT_CUSTOM_FIELD_SCHEMA_TTL;
 use crate::netbox::{
-    ResilientNetBoxClient, NetBoxSite,
+    diff_site, NetBoxRouter, ResilientNetBoxClient, NetBoxDevice, NetBoxSite, SiteStatus,
 };
+use crate::security::tenant::TenantAccessControl;
 use crate::security::TenantId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use parking_lot::RwLock;
 use std::sync::Arc;
-use tracing::{debug, error, info};
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
 
 /// Order service that orchestrates the full order processing flow
 pub struct OrderService {
@@ -18,6 +33,272 @@ pub struct OrderService {
     enricher: ObjectEnricher,
     workflow_manager: Arc<WorkflowManager>,
     netbox_client: Arc<ResilientNetBoxClient>,
+    /// Sends each tenant's orders to its own NetBox endpoint instead of `netbox_client`
+    router: Option<Arc<NetBoxRouter>>,
+    approval_policy: Option<Arc<dyn ApprovalPolicy>>,
+    /// Per-tenant defaults applied when transforming site orders
+    transformation_profiles: Arc<TransformationProfiles>,
+    site_name_check: SiteNameCheckConfig,
+    custom_field_schema_check: CustomFieldSchemaCheckConfig,
+    /// Handling of order tags NetBox doesn't have yet; `None` sends them as they are
+    missing_tag_policy: Option<MissingTagPolicy>,
+    /// Tenant mappings for orders that change existing NetBox resources
+    access_control: Option<Arc<TenantAccessControl>>,
+    /// Orders held for approval, resumed when approved
+    awaiting_approval: RwLock<HashMap<String, HeldOrder>>,
+    /// Freezes during which validated orders aren't executed
+    maintenance: Option<Arc<MaintenanceWindows>>,
+    /// Ids of the orders parked in Scheduled during a freeze, in the order they are released when it ends
+    scheduled_queue: Arc<OrderQueue<String>>,
+    /// Leases taken on Scheduled orders before executing them, so that workers
+    /// sharing the workflow store execute each once; `None` takes none
+    order_claims: Option<(Arc<dyn OrderClaimStore>, OrderClaimConfig)>,
+    /// Tells whether a maintenance freeze is on
+    clock: SharedClock,
+    processing_deadlines: ProcessingDeadlineConfig,
+    /// Names pop devices ordered without a name
+    device_namer: Option<Arc<DeviceNamer>>,
+}
+
+/// Order type naming templates of pop devices are configured under
+pub const POP_NAMING_ORDER_TYPE: &str = "pop";
+
+/// Devices fetched per NetBox request when listing a site's devices
+const SITE_DEVICES_PAGE_SIZE: u32 = 1000;
+
+/// Pre-flight check that rejects site orders whose name already exists in NetBox
+///
+/// Off by default because it costs an extra NetBox round-trip per order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SiteNameCheckConfig {
+    pub enabled: bool,
+    /// Skip the check (instead of failing the order) when NetBox can't be queried
+    pub skip_on_outage: bool,
+}
+
+impl Default for SiteNameCheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            skip_on_outage: true,
+        }
+    }
+}
+
+impl SiteNameCheckConfig {
+    /// Load from SITE_NAME_CHECK_ENABLED and SITE_NAME_CHECK_SKIP_ON_OUTAGE
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let flag = |name: &str, default: bool| {
+            std::env::var(name)
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(default)
+        };
+        Self {
+            enabled: flag("SITE_NAME_CHECK_ENABLED", defaults.enabled),
+            skip_on_outage: flag("SITE_NAME_CHECK_SKIP_ON_OUTAGE", defaults.skip_on_outage),
+        }
+    }
+}
+
+/// Pre-flight check of site order custom fields against NetBox's custom field schema
+///
+/// On by default; air-gapped test setups without the extras API turn it off.
+/// Orders without custom fields never fetch the schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CustomFieldSchemaCheckConfig {
+    pub enabled: bool,
+    /// How long a fetched schema is used before it is fetched again
+    pub ttl: Duration,
+}
+
+impl Default for CustomFieldSchemaCheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl: DEFAULT_CUSTOM_FIELD_SCHEMA_TTL,
+        }
+    }
+}
+
+impl CustomFieldSchemaCheckConfig {
+    /// Load from CUSTOM_FIELD_SCHEMA_CHECK_ENABLED and CUSTOM_FIELD_SCHEMA_TTL_SECS
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            enabled: std::env::var("CUSTOM_FIELD_SCHEMA_CHECK_ENABLED")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(defaults.enabled),
+            ttl: std::env::var("CUSTOM_FIELD_SCHEMA_TTL_SECS")
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.ttl),
+        }
+    }
+}
+
+/// Kinds of order [`OrderService`] processes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderKind {
+    Site,
+    Pop,
+    SiteUpdate,
+    Decommission,
+}
+
+impl std::str::FromStr for OrderKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "site" => Ok(OrderKind::Site),
+            "pop" => Ok(OrderKind::Pop),
+            "site_update" => Ok(OrderKind::SiteUpdate),
+            "decommission" => Ok(OrderKind::Decommission),
+            other => Err(format!(
+                "Invalid order type '{}'; expected site, pop, site_update or decommission",
+                other
+            )),
+        }
+    }
+}
+
+/// Error message prefix of orders failed for taking too long
+pub const PROCESSING_TIMEOUT_ERROR: &str = "Processing timeout";
+
+/// How long an order may spend processing in NetBox before it is failed
+///
+/// Bounds each order's NetBox phase, and a watchdog fails orders whose
+/// deadline passed without the bound taking effect (e.g. because the request
+/// processing them was dropped).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessingDeadlineConfig {
+    pub default: Duration,
+    /// Deadlines of order types that need more or less time than `default`
+    pub per_kind: HashMap<OrderKind, Duration>,
+    /// How often the watchdog looks for overdue orders
+    pub watchdog_interval: Duration,
+}
+
+impl Default for ProcessingDeadlineConfig {
+    fn default() -> Self {
+        Self {
+            default: Duration::from_secs(300),
+            per_kind: HashMap::new(),
+            watchdog_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+impl ProcessingDeadlineConfig {
+    /// Deadline of orders of `kind`
+    pub fn for_kind(&self, kind: OrderKind) -> Duration {
+        self.per_kind.get(&kind).copied().unwrap_or(self.default)
+    }
+
+    /// Parse `type=secs` pairs separated by commas, e.g. `pop=900,decommission=600`
+    ///
+    /// Malformed pairs are skipped with a warning.
+    pub fn parse_per_kind(spec: &str) -> HashMap<OrderKind, Duration> {
+        spec.split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+            .filter_map(|pair| {
+                let parsed = pair.split_once('=').and_then(|(kind, secs)| {
+                    let secs: u64 = secs.trim().parse().ok().filter(|secs| *secs > 0)?;
+                    Some((kind.parse().ok()?, Duration::from_secs(secs)))
+                });
+                if parsed.is_none() {
+                    warn!("Ignoring invalid order processing deadline '{}'", pair);
+                }
+                parsed
+            })
+            .collect()
+    }
+
+    /// Load from ORDER_PROCESSING_DEADLINE_SECS, ORDER_PROCESSING_DEADLINES and
+    /// ORDER_PROCESSING_WATCHDOG_INTERVAL_SECS
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let secs = |name: &str, default: Duration| {
+            std::env::var(name)
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(default)
+        };
+        Self {
+            default: secs("ORDER_PROCESSING_DEADLINE_SECS", defaults.default),
+            per_kind: Self::parse_per_kind(&std::env::var("ORDER_PROCESSING_DEADLINES").unwrap_or_default()),
+            watchdog_interval: secs("ORDER_PROCESSING_WATCHDOG_INTERVAL_SECS", defaults.watchdog_interval),
+        }
+    }
+}
+
+/// Color of tags NetGate creates, NetBox's default grey
+const DEFAULT_TAG_COLOR: &str = "9e9e9e";
+
+/// What to do with order tags that don't exist in NetBox yet
+///
+/// NetBox rejects a site referencing an unknown tag, so without a policy an
+/// order with a novel tag fails at site creation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissingTagPolicy {
+    /// Create the missing tags, then the site
+    Create,
+    /// Leave the missing tags off the site
+    Strip,
+    /// Reject the order, naming the missing tags
+    Fail,
+}
+
+impl std::str::FromStr for MissingTagPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "create" => Ok(MissingTagPolicy::Create),
+            "strip" => Ok(MissingTagPolicy::Strip),
+            "fail" => Ok(MissingTagPolicy::Fail),
+            other => Err(format!("Invalid missing tag policy '{}'; expected create, strip, fail or off", other)),
+        }
+    }
+}
+
+impl MissingTagPolicy {
+    /// Load from MISSING_TAG_POLICY; `off` disables the check, unset or invalid means `create`
+    pub fn from_env() -> Option<Self> {
+        match std::env::var("MISSING_TAG_POLICY") {
+            Ok(value) if value.trim().eq_ignore_ascii_case("off") => None,
+            Ok(value) => Some(value.parse().unwrap_or_else(|e| {
+                warn!("{}", e);
+                MissingTagPolicy::Create
+            })),
+            Err(_) => Some(MissingTagPolicy::Create),
+        }
+    }
+}
+
+/// Payload of an order held for approval or parked during a maintenance freeze
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "order", rename_all = "snake_case")]
+pub enum HeldOrder {
+    Site(CreateSiteOrder),
+    Pop(CreatePopOrder),
+}
+
+impl HeldOrder {
+    fn priority(&self) -> OrderPriority {
+        match self {
+            HeldOrder::Site(order) => order.priority,
+            HeldOrder::Pop(order) => order.site.priority,
+        }
+    }
 }
 
 impl OrderService {
@@ -32,351 +313,2984 @@ impl OrderService {
             enricher: ObjectEnricher::new(),
             workflow_manager,
             netbox_client,
+            router: None,
+            approval_policy: None,
+            transformation_profiles: Arc::new(TransformationProfiles::new()),
+            site_name_check: SiteNameCheckConfig::default(),
+            custom_field_schema_check: CustomFieldSchemaCheckConfig::default(),
+            missing_tag_policy: None,
+            access_control: None,
+            awaiting_approval: RwLock::new(HashMap::new()),
+            maintenance: None,
+            scheduled_queue: Arc::new(OrderQueue::new(OrderQueueConfig::default())),
+            order_claims: None,
+            clock: SystemClock::shared(),
+            processing_deadlines: ProcessingDeadlineConfig::default(),
+            device_namer: None,
+        }
+    }
+
+    /// Configure the pre-flight check for duplicate site names
+    pub fn with_site_name_check(mut self, config: SiteNameCheckConfig) -> Self {
+        self.site_name_check = config;
+        self
+    }
+
+    /// Configure the pre-flight check of custom fields against NetBox's schema
+    ///
+    /// The schema TTL is set on the NetBox clients, see
+    /// [`ResilientNetBoxClient::with_custom_field_schema_ttl`].
+    pub fn with_custom_field_schema_check(mut self, config: CustomFieldSchemaCheckConfig) -> Self {
+        self.custom_field_schema_check = config;
+        self
+    }
+
+    /// Make sure the tags of created and updated sites exist in NetBox first
+    pub fn with_missing_tag_policy(mut self, policy: MissingTagPolicy) -> Self {
+        self.missing_tag_policy = Some(policy);
+        self
+    }
+
+    /// Check tenant ownership of existing NetBox resources, required by decommission orders
+    pub fn with_access_control(mut self, access_control: Arc<TenantAccessControl>) -> Self {
+        self.access_control = Some(access_control);
+        self
+    }
+
+    /// Validate orders with custom rules, e.g. a custom field allowlist
+    pub fn with_validator(mut self, validator: OrderValidator) -> Self {
+        self.validator = validator;
+        self
+    }
+
+    /// Enrich orders with this tag taxonomy and its per-tenant overrides
+    pub fn with_enrichment_config(mut self, config: EnrichmentConfig) -> Self {
+        self.enricher = ObjectEnricher::from_config(config);
+        self
+    }
+
+    /// Transform each tenant's site orders with its profile's defaults
+    pub fn with_transformation_profiles(mut self, profiles: Arc<TransformationProfiles>) -> Self {
+        self.transformation_profiles = profiles;
+        self
+    }
+
+    /// Name pop devices ordered without a name from the tenant's naming template
+    pub fn with_device_namer(mut self, namer: Arc<DeviceNamer>) -> Self {
+        self.device_namer = Some(namer);
+        self
+    }
+
+    /// Fail orders whose NetBox phase outlasts these deadlines
+    pub fn with_processing_deadlines(mut self, deadlines: ProcessingDeadlineConfig) -> Self {
+        self.processing_deadlines = deadlines;
+        self
+    }
+
+    /// Hold orders matching the policy for operator approval before touching NetBox
+    pub fn with_approval_policy(mut self, policy: Arc<dyn ApprovalPolicy>) -> Self {
+        self.approval_policy = Some(policy);
+        self
+    }
+
+    /// Keep validated orders out of NetBox during these maintenance freezes
+    pub fn with_maintenance_windows(mut self, windows: Arc<MaintenanceWindows>) -> Self {
+        self.maintenance = Some(windows);
+        self
+    }
+
+    /// Use `clock` to tell whether a maintenance freeze is on
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        let config = self.scheduled_queue.config().clone();
+        self.scheduled_queue = Arc::new(OrderQueue::new(config).with_clock(clock.clone()));
+        self.clock = clock;
+        self
+    }
+
+    /// Configure the priority queue Scheduled orders are released from
+    pub fn with_order_queue(mut self, config: OrderQueueConfig) -> Self {
+        self.scheduled_queue = Arc::new(OrderQueue::new(config).with_clock(self.clock.clone()));
+        self
+    }
+
+    /// Claim Scheduled orders in `store` before executing them, as the worker in `config`
+    pub fn with_order_claims(mut self, store: Arc<dyn OrderClaimStore>, config: OrderClaimConfig) -> Self {
+        self.order_claims = Some((store, config));
+        self
+    }
+
+    /// Queue of the Scheduled orders' ids
+    pub fn scheduled_queue(&self) -> &Arc<OrderQueue<String>> {
+        &self.scheduled_queue
+    }
+
+    /// Route each tenant's NetBox calls to the endpoint it is mapped to
+    pub fn with_router(mut self, router: Arc<NetBoxRouter>) -> Self {
+        self.router = Some(router);
+        self
+    }
+
+    /// The NetBox client serving this tenant
+    fn netbox(&self, tenant_id: &TenantId) -> Result<Arc<ResilientNetBoxClient>, AppError> {
+        match self.router {
+            Some(ref router) => Ok(router.endpoint_for(tenant_id)?.client.clone()),
+            None => Ok(self.netbox_client.clone()),
         }
     }
 
+    /// Workflows of the orders this service processes
+    pub fn workflow_manager(&self) -> &Arc<WorkflowManager> {
+        &self.workflow_manager
+    }
+
     /// Process a site order through the full pipeline:
     /// 1. Validate the order
     /// 2. Create workflow entry
-    /// 3. Transform order to NetBox request
-    /// 4. Enrich the NetBox request
-    /// 5. Create site in NetBox
-    /// 6. Update workflow state
+    /// 3. Hold for approval if the approval policy requires it, or park it
+    ///    in Scheduled during a maintenance freeze
+    /// 4. Transform order to NetBox request
+    /// 5. Enrich the NetBox request
+    /// 6. Create site in NetBox
+    /// 7. Update workflow state
     pub async fn process_site_order(
         &self,
-        order: CreateSiteOrder,
+        mut order: CreateSiteOrder,
         tenant_id: TenantId,
     ) -> Result<ProcessedOrderResult, AppError> {
         // Step 1: Validate the order
         debug!("Validating order");
+        self.normalize_site_address(&mut order)?;
         self.validator.validate_site_order(&order)?;
+        self.ensure_custom_fields_match_schema(&order, &tenant_id).await?;
+        self.ensure_site_name_available(&order.name, &tenant_id).await?;
+        let enrichment_data = self.enricher.enrichment_data(&tenant_id);
+        let needs_approval = self.requires_approval(&tenant_id, &enrichment_data);
+        if !needs_approval {
+            // Orders held for approval are checked once approved
+            self.refuse_while_frozen(true)?;
+        }
 
         // Step 2: Create workflow entry (this generates the order ID)
         debug!("Creating workflow");
         let order_id = self.workflow_manager.create_order(tenant_id.clone());
         info!("Processing site order {} for tenant {}", order_id, tenant_id);
         
-        // Step 3: Update workflow to Validated state
+        // Step 3: Hold for approval, or update workflow to Validated state
+        if needs_approval {
+            return self.hold_for_approval(order_id, tenant_id, HeldOrder::Site(order));
+        }
         self.workflow_manager.update_order_state(&order_id, OrderState::Validated)
-            .map_err(|e| AppError::Internal(anyhow::anyhow!("Workflow error: {}", e)))?;
+            .map_err(Self::transition_error)?;
 
-        // Step 4: Transform order to NetBox request
-        debug!("Transforming order {} to NetBox request", order_id);
-        let mut netbox_request = self.transformer.transform_site_order(order, None);
+        self.execute_or_schedule(order_id, HeldOrder::Site(order), tenant_id, enrichment_data).await
+    }
 
-        // Step 5: Enrich the NetBox request (apply enrichment to tags and description)
-        debug!("Enriching NetBox request for order {}", order_id);
-        let enrichment_data = EnrichmentData::default();
-        
-        // Apply enrichment tags to the request
-        let mut tags = netbox_request.tags.unwrap_or_default();
-        tags.push("netgate".to_string());
-        tags.push("enriched".to_string());
-        netbox_request.tags = Some(tags);
+    /// Process a pop order: one site plus the devices installed in it
+    ///
+    /// The site is created first and its id injected into each device request.
+    /// Every resource is tracked as a step on a single workflow; if a device
+    /// cannot be created, the resources created so far are rolled back, the
+    /// workflow's error names the failing device and the device's own error
+    /// is returned.
+    pub async fn process_pop_order(
+        &self,
+        order: CreatePopOrder,
+        tenant_id: TenantId,
+    ) -> Result<ProcessedOrderResult, AppError> {
+        let mut order = self.name_pop_devices(order, &tenant_id).await?;
+        debug!("Validating pop order");
+        self.normalize_site_address(&mut order.site)?;
+        self.validate_pop_order(&order)?;
+        self.ensure_custom_fields_match_schema(&order.site, &tenant_id).await?;
+        self.ensure_site_name_available(&order.site.name, &tenant_id).await?;
+        let enrichment_data = self.enricher.enrichment_data(&tenant_id);
+        let needs_approval = self.requires_approval(&tenant_id, &enrichment_data);
+        if !needs_approval {
+            self.refuse_while_frozen(true)?;
+        }
 
-        // Step 6: Update workflow to Processing state
-        self.workflow_manager.update_order_state(&order_id, OrderState::Processing)
-            .map_err(|e| AppError::Internal(anyhow::anyhow!("Workflow error: {}", e)))?;
-
-        // Step 7: Create site in NetBox
-        debug!("Creating site in NetBox for order {}", order_id);
-        let netbox_site = match self.netbox_client.create_site(netbox_request).await {
-            Ok(site) => {
-                // Step 8: Enrich the created site
-                let enriched_site = self.enricher.enrich_site(site, &enrichment_data);
-                
-                // Step 9: Update workflow with NetBox ID and mark as completed
-                if let Some(site_id) = enriched_site.id {
-                    self.workflow_manager.mark_order_completed(&order_id, site_id)
-                        .map_err(|e| AppError::Internal(anyhow::anyhow!("Workflow error: {}", e)))?;
+        let order_id = self.workflow_manager.create_order(tenant_id.clone());
+        info!(
+            "Processing pop order {} ({} device(s)) for tenant {}",
+            order_id,
+            order.devices.len(),
+            tenant_id
+        );
+        let steps = std::iter::once(OrderStep::new(ResourceKind::Site, order.site.name.clone()))
+            .chain(order.devices.iter().map(|device| OrderStep::new(ResourceKind::Device, device.name.clone())))
+            .collect();
+        self.workflow_manager.set_order_steps(&order_id, steps)
+            .map_err(Self::transition_error)?;
+
+        if needs_approval {
+            return self.hold_for_approval(order_id, tenant_id, HeldOrder::Pop(order));
+        }
+        self.workflow_manager.update_order_state(&order_id, OrderState::Validated)
+            .map_err(Self::transition_error)?;
+
+        self.execute_or_schedule(order_id, HeldOrder::Pop(order), tenant_id, enrichment_data).await
+    }
+
+    /// Normalize a site order's address, tagging the site if it couldn't be verified
+    fn normalize_site_address(&self, order: &mut CreateSiteOrder) -> Result<(), AppError> {
+        let Some(ref address) = order.address else {
+            return Ok(());
+        };
+        let normalized = self.validator.normalize_address(address)?;
+        if !normalized.is_verified() {
+            warn!(
+                "Address '{}' of site '{}' could not be verified: {}",
+                normalized.address,
+                order.name,
+                normalized.problems.join("; ")
+            );
+            if !order.tags.iter().any(|tag| tag == UNVERIFIED_ADDRESS_TAG) {
+                order.tags.push(UNVERIFIED_ADDRESS_TAG.to_string());
+            }
+        }
+        order.address = Some(normalized.address).filter(|address| !address.is_empty());
+        Ok(())
+    }
+
+    /// Enrichment data with the country of the order's address, if it names one
+    fn with_address_country(&self, mut enrichment_data: EnrichmentData, order: &CreateSiteOrder) -> EnrichmentData {
+        let country = order
+            .address
+            .as_deref()
+            .and_then(|address| self.validator.normalize_address(address).ok())
+            .and_then(|normalized| normalized.components.country);
+        if let Some(country) = country {
+            let geographic = enrichment_data.geographic.get_or_insert_with(Default::default);
+            geographic.country.get_or_insert(country);
+        }
+        enrichment_data
+    }
+
+    /// Name the pop's devices ordered without a name, if the tenant has a naming template
+    async fn name_pop_devices(&self, mut order: CreatePopOrder, tenant_id: &TenantId) -> Result<CreatePopOrder, AppError> {
+        let Some(ref namer) = self.device_namer else {
+            return Ok(order);
+        };
+        let unnamed: Vec<usize> = (0..order.devices.len())
+            .filter(|&index| order.devices[index].name.trim().is_empty())
+            .collect();
+        if unnamed.is_empty() || !namer.applies(tenant_id, POP_NAMING_ORDER_TYPE) {
+            return Ok(order);
+        }
+
+        let business = self.enricher.enrichment_data(tenant_id).business.unwrap_or_default();
+        let site_slug = self.transformer.generate_slug(&order.site.name);
+        let contexts: Vec<NameContext> = unnamed
+            .iter()
+            .map(|&index| NameContext {
+                tenant_id: tenant_id.clone(),
+                site_slug: Some(site_slug.clone()),
+                role: Some(order.devices[index].role),
+                device_type: Some(order.devices[index].device_type),
+                project: business.project_code.clone(),
+                environment: business.environment.clone(),
+            })
+            .collect();
+        let netbox = self.netbox(tenant_id)?;
+        let names = namer.name_devices(&netbox, POP_NAMING_ORDER_TYPE, &contexts).await?;
+        for (index, name) in unnamed.into_iter().zip(names) {
+            debug!("Named pop device {} '{}'", index, name);
+            order.devices[index].name = name;
+        }
+        Ok(order)
+    }
+
+    fn validate_pop_order(&self, order: &CreatePopOrder) -> Result<(), AppError> {
+        self.validator.validate_site_order(&order.site)?;
+        if order.devices.is_empty() {
+            return Err(AppError::ValidationError("Pop order requires at least one device".to_string()));
+        }
+        let mut names = std::collections::HashSet::new();
+        for device in &order.devices {
+            DeviceOrderProcessor::validate_device_details(&device.for_site(None)).map_err(|e| match e {
+                AppError::ValidationError(msg) => {
+                    AppError::ValidationError(format!("Device '{}': {}", device.name, msg))
                 }
+                other => other,
+            })?;
+            if !names.insert(device.name.trim()) {
+                return Err(AppError::ValidationError(format!(
+                    "Duplicate device name '{}' in pop order",
+                    device.name.trim()
+                )));
+            }
+        }
+        Ok(())
+    }
 
-                info!("Successfully processed order {} - NetBox site created", order_id);
-                enriched_site
+    /// Fail fast if NetBox already has a site with this name
+    ///
+    /// Sites are created without a NetBox tenant, so the lookup isn't tenant
+    /// filtered. When NetBox can't be queried the check is skipped or the
+    /// order fails, as configured.
+    async fn ensure_site_name_available(&self, name: &str, tenant_id: &TenantId) -> Result<(), AppError> {
+        if !self.site_name_check.enabled {
+            return Ok(());
+        }
+        match self.netbox(tenant_id)?.find_sites_by_name(None, name).await {
+            Ok(sites) if sites.is_empty() => Ok(()),
+            Ok(_) => Err(AppError::Conflict(format!("Site name '{}' already exists", name))),
+            Err(e) if self.site_name_check.skip_on_outage => {
+                warn!("Skipping site name check for '{}', NetBox unavailable: {}", name, e);
+                Ok(())
             }
+            Err(e) => Err(AppError::ServiceUnavailable(format!("Cannot check site name '{}': {}", name, e))),
+        }
+    }
+
+    /// Check the site's custom fields, with the tenant's profile defaults, against NetBox's schema
+    ///
+    /// The check is skipped with a warning when the schema can't be fetched;
+    /// creating the site then reports whatever NetBox makes of the fields.
+    async fn ensure_custom_fields_match_schema(&self, order: &CreateSiteOrder, tenant_id: &TenantId) -> Result<(), AppError> {
+        if !self.custom_field_schema_check.enabled {
+            return Ok(());
+        }
+        let mut fields = self
+            .transformation_profiles
+            .profile(tenant_id)
+            .map(|profile| profile.custom_fields)
+            .unwrap_or_default();
+        fields.extend(order.custom_fields.clone());
+        if fields.is_empty() {
+            return Ok(());
+        }
+        match self.netbox(tenant_id)?.custom_field_schema().await {
+            Ok(schema) => Ok(self.validator.validate_custom_field_values(&schema, SITE_OBJECT_TYPE, &fields)?),
             Err(e) => {
-                error!("Failed to create site in NetBox for order {}: {}", order_id, e);
-                
-                // Mark workflow as failed
-                let _ = self.workflow_manager.mark_order_failed(&order_id, e.to_string());
-                
-                return Err(e);
+                warn!("Skipping custom field schema check for '{}', schema unavailable: {}", order.name, e);
+                Ok(())
+            }
+        }
+    }
+
+    /// Apply the missing tag policy to a site's tags, returning the tags to send
+    ///
+    /// The check is skipped with a warning when NetBox's tags can't be listed;
+    /// the site request then fails or succeeds as it would without it.
+    async fn ensure_tags_exist(&self, netbox: &ResilientNetBoxClient, tags: Vec<String>) -> Result<Vec<String>, AppError> {
+        let Some(policy) = self.missing_tag_policy else {
+            return Ok(tags);
+        };
+        if tags.is_empty() {
+            return Ok(tags);
+        }
+        let missing = match netbox.missing_tags(&tags).await {
+            Ok(missing) if missing.is_empty() => return Ok(tags),
+            Ok(missing) => missing,
+            Err(e) => {
+                warn!("Skipping tag check, NetBox tags unavailable: {}", e);
+                return Ok(tags);
             }
         };
+        match policy {
+            MissingTagPolicy::Create => {
+                for name in &missing {
+                    netbox.create_tag(name, &self.transformer.normalize_tag(name), DEFAULT_TAG_COLOR).await?;
+                    info!("Created NetBox tag '{}'", name);
+                }
+                Ok(tags)
+            }
+            MissingTagPolicy::Strip => {
+                warn!("Leaving tags missing from NetBox off the site: {}", missing.join(", "));
+                Ok(tags.into_iter().filter(|tag| !missing.contains(tag)).collect())
+            }
+            MissingTagPolicy::Fail => Err(AppError::ValidationError(format!(
+                "Tags not defined in NetBox: {}",
+                missing.join(", ")
+            ))),
+        }
+    }
 
-        // Get final workflow state
-        let workflow = self.workflow_manager.get_order(&order_id)
-            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Workflow not found after processing")))?;
+    /// Process a site update order: change fields of a tenant's existing site
+    ///
+    /// The site must belong to the tenant and can't be moved to another tenant.
+    /// With `last_updated` set, the update is refused with a conflict if the
+    /// site changed since the caller read it, unless `force` is set.
+    /// Tags are re-derived only when the order changes the tags or the status;
+    /// otherwise the update leaves the site's tags in NetBox as they are.
+    /// Only fields that differ from the site are sent, and an order that
+    /// changes nothing completes without a request to NetBox.
+    /// Update orders aren't parked during a maintenance freeze but refused.
+    pub async fn process_site_update_order(
+        &self,
+        mut order: UpdateSiteOrder,
+        tenant_id: TenantId,
+    ) -> Result<ProcessedOrderResult, AppError> {
+        let workflow_error = Self::transition_error;
+
+        self.validator.validate_site_update(&order)?;
+        if let Some(ref address) = order.address {
+            let normalized = self.validator.normalize_address(address)?;
+            if !normalized.is_verified() {
+                warn!("Address '{}' could not be verified: {}", normalized.address, normalized.problems.join("; "));
+            }
+            order.address = Some(normalized.address);
+        }
+        self.refuse_while_frozen(false)?;
+        let site = self.find_tenant_site(order.site_id, order.site_slug.as_deref(), &tenant_id).await?;
+        let site_id = site.id.ok_or_else(|| AppError::Internal(anyhow::anyhow!("NetBox returned a site without an id")))?;
+        if let Some(tenant) = order.tenant {
+            if site.tenant != Some(tenant) {
+                return Err(AppError::Forbidden(format!("Site {} can't be moved to another tenant", site_id)));
+            }
+        }
+        if !order.force {
+            if let Some(ref seen) = order.last_updated {
+                ensure_site_unchanged(&site, seen)?;
+            }
+        }
 
+        let new_status = order.status.as_deref().and_then(|status| status.parse::<SiteStatus>().ok());
+        if let (Some(current), Some(requested)) = (site.status, new_status) {
+            self.validator.validate_status_change(current, requested)?;
+        }
+        let status_changed = new_status.is_some_and(|status| site.status != Some(status));
+        let tags_changed = order.tags.is_some();
+        let mut request = self.transformer.transform_site_update(order, self.transformation_profiles.profile(&tenant_id).as_ref());
+        if tags_changed || status_changed {
+            let base = request.tags.take().or_else(|| site.tags.clone()).unwrap_or_default();
+            request.tags = Some(self.enricher.for_tenant(&tenant_id).derive_site_tags(base, new_status.or(site.status)));
+        }
+        let mut request = diff_site(&site, &request);
+
+        let order_id = self.workflow_manager.create_order(tenant_id.clone());
+        info!("Processing site update order {} for site {} of tenant {}", order_id, site_id, tenant_id);
+        self.workflow_manager
+            .record_site_request(
+                &order_id,
+                OrderKind::SiteUpdate,
+                request.name.clone().unwrap_or_else(|| site.name.clone()),
+                site.slug.clone(),
+            )
+            .map_err(workflow_error)?;
+        self.workflow_manager.update_order_state(&order_id, OrderState::Validated).map_err(workflow_error)?;
+        self.workflow_manager.update_order_state(&order_id, OrderState::Processing).map_err(workflow_error)?;
+
+        let netbox = self.netbox(&tenant_id)?;
+        let updated = self
+            .within_deadline(&order_id, OrderKind::SiteUpdate, async {
+                if let Some(tags) = request.tags.take() {
+                    match self.ensure_tags_exist(&netbox, tags).await {
+                        Ok(tags) => request.tags = Some(tags),
+                        Err(e) => {
+                            self.fail_order(&order_id, e.to_string()).await;
+                            return Err(e);
+                        }
+                    }
+                }
+                let updated = if request.is_empty() {
+                    info!("Site update order {} changes nothing on site {}; not sending it", order_id, site_id);
+                    site.clone()
+                } else {
+                    match netbox.update_site(site_id, request).await {
+                        Ok(updated) => updated,
+                        Err(e) => {
+                            error!("Failed to update site {} for order {}: {}", site_id, order_id, e);
+                            self.fail_order(&order_id, e.to_string()).await;
+                            return Err(e);
+                        }
+                    }
+                };
+                self.workflow_manager.mark_order_completed(&order_id, site_id).map_err(workflow_error)?;
+                info!("Successfully processed site update order {} - site {} updated", order_id, site_id);
+                Ok(updated)
+            })
+            .await?;
+
+        let (enriched, applied_enrichment) = self
+            .enricher
+            .for_tenant(&tenant_id)
+            .enrich_site_with_report(updated, &self.enricher.enrichment_data(&tenant_id));
         Ok(ProcessedOrderResult {
             order_id,
             tenant_id,
-            netbox_site,
-            workflow_state: workflow.state,
+            netbox_site: Some(enriched),
+            workflow_state: OrderState::Completed,
+            applied_enrichment,
         })
     }
 
-    /// Get order status by order ID
-    pub async fn get_order_status(
+    /// Look up a site by id or slug and check it belongs to the tenant
+    ///
+    /// Tenant mappings are needed to tell whose site it is; without them this fails closed.
+    async fn find_tenant_site(
         &self,
-        order_id: &str,
+        site_id: Option<i32>,
+        site_slug: Option<&str>,
         tenant_id: &TenantId,
-    ) -> Result<OrderStatus, AppError> {
-        let workflow = self.workflow_manager
-            .get_order(order_id)
-            .ok_or_else(|| AppError::NotFound(format!("Order {} not found", order_id)))?;
+    ) -> Result<NetBoxSite, AppError> {
+        let access_control = self.access_control.as_ref().ok_or_else(|| {
+            AppError::ServiceUnavailable("Tenant mappings are not configured".to_string())
+        })?;
+        let netbox = self.netbox(tenant_id)?;
+        let site = match (site_id, site_slug) {
+            (Some(id), _) => netbox.get_site(id).await?,
+            (None, Some(slug)) => netbox
+                .find_site_by_slug(slug)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("Site '{}' not found", slug)))?,
+            (None, None) => return Err(AppError::ValidationError("No site given".to_string())),
+        };
+        access_control.verify_site_access(tenant_id, &site)?;
+        Ok(site)
+    }
 
-        // Verify tenant access
-        if workflow.tenant_id != *tenant_id {
-            return Err(AppError::Unauthorized);
+    /// Process a site decommission order: delete a tenant's site from NetBox
+    ///
+    /// A site that still has devices is refused unless the order sets `force`,
+    /// in which case the devices are deleted first. Deletions can't be rolled
+    /// back, so a failure part-way fails the order with the resources deleted
+    /// so far recorded on its workflow. Decommissions are refused during a
+    /// maintenance freeze.
+    pub async fn process_decommission_site_order(
+        &self,
+        order: DecommissionSiteOrder,
+        tenant_id: TenantId,
+    ) -> Result<DecommissionResult, AppError> {
+        let workflow_error = Self::transition_error;
+
+        self.refuse_while_frozen(false)?;
+        let site = self.find_tenant_site(Some(order.site_id), None, &tenant_id).await?;
+
+        let netbox = self.netbox(&tenant_id)?;
+        let devices = self.site_devices(&netbox, order.site_id).await?;
+        if !devices.is_empty() && !order.force {
+            return Err(AppError::Conflict(format!(
+                "Site {} still has {} device(s); set force to delete them with the site",
+                order.site_id,
+                devices.len()
+            )));
         }
 
-        Ok(OrderStatus {
-            order_id: order_id.to_string(),
-            state: workflow.state,
-            netbox_site_id: workflow.netbox_site_id,
-            created_at: workflow.created_at,
-            updated_at: workflow.updated_at,
-        })
-    }
-}
+        let order_id = self.workflow_manager.create_order(tenant_id.clone());
+        info!(
+            "Processing decommission order {} for site {} ({} device(s)) of tenant {}",
+            order_id,
+            order.site_id,
+            devices.len(),
+            tenant_id
+        );
+        let device_name = |device: &NetBoxDevice| {
+            device.name.clone().unwrap_or_else(|| format!("device {}", device.id.unwrap_or_default()))
+        };
+        let steps = devices
+            .iter()
+            .map(|device| OrderStep::new(ResourceKind::Device, device_name(device)))
+            .chain(std::iter::once(OrderStep::new(ResourceKind::Site, site.name.clone())))
+            .collect();
+        self.workflow_manager.set_order_steps(&order_id, steps).map_err(workflow_error)?;
+        self.workflow_manager
+            .record_site_request(&order_id, OrderKind::Decommission, site.name.clone(), site.slug.clone())
+            .map_err(workflow_error)?;
+        self.workflow_manager.update_order_state(&order_id, OrderState::Validated).map_err(workflow_error)?;
+        self.workflow_manager.update_order_state(&order_id, OrderState::Processing).map_err(workflow_error)?;
 
-/// Result of processing an order
-#[derive(Debug, Clone)]
-pub struct ProcessedOrderResult {
-    pub order_id: String,
-    pub tenant_id: TenantId,
-    pub netbox_site: NetBoxSite,
-    pub workflow_state: OrderState,
-}
+        let deleted_devices = self
+            .within_deadline(&order_id, OrderKind::Decommission, async {
+                let mut deleted_devices = Vec::new();
+                let targets = devices
+                    .iter()
+                    .filter_map(|device| device.id.map(|id| (ResourceKind::Device, id, device_name(device))))
+                    .chain(std::iter::once((ResourceKind::Site, order.site_id, site.name.clone())));
+                for (step, (kind, id, name)) in targets.enumerate() {
+                    let result = match kind {
+                        ResourceKind::Site => netbox.delete_site(id).await,
+                        _ => netbox.delete_device(id).await,
+                    };
+                    if let Err(e) = result {
+                        error!("Failed to delete {:?} {} for decommission order {}: {}", kind, id, order_id, e);
+                        let _ = self.workflow_manager.finish_step(&order_id, step, Err(e.to_string()));
+                        self.fail_order(&order_id, format!("Deleting {:?} '{}' failed: {}", kind, name, e)).await;
+                        return Err(e);
+                    }
+                    self.workflow_manager.record_deleted_resource(&order_id, kind, id)
+                        .and_then(|_| self.workflow_manager.finish_step(&order_id, step, Ok(id)))
+                        .map_err(workflow_error)?;
+                    if kind == ResourceKind::Device {
+                        deleted_devices.push(id);
+                    }
+                }
 
-/// Order status information
-#[derive(Debug, Clone)]
-pub struct OrderStatus {
-    pub order_id: String,
-    pub state: OrderState,
-    pub netbox_site_id: Option<i32>,
-    pub created_at: chrono::DateTime<chrono::Utc>,
-    pub updated_at: chrono::DateTime<chrono::Utc>,
-}
+                self.workflow_manager.mark_order_completed(&order_id, order.site_id).map_err(workflow_error)?;
+                Ok(deleted_devices)
+            })
+            .await?;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::config::Config;
-    use crate::netbox::client::NetBoxClient;
-    use std::sync::Arc;
+        info!(
+            "Decommission order {} of tenant {} deleted site {} and devices {:?} (reason: {})",
+            order_id,
+            tenant_id,
+            order.site_id,
+            deleted_devices,
+            order.reason.as_deref().unwrap_or("none given")
+        );
 
-    fn create_test_order() -> CreateSiteOrder {
-        CreateSiteOrder {
-            name: "Test Site".to_string(),
-            description: Some("Test Description".to_string()),
-            address: Some("123 Test St".to_string()),
+        Ok(DecommissionResult {
+            order_id,
+            tenant_id,
+            site_id: order.site_id,
+            deleted_devices,
+            workflow_state: OrderState::Completed,
+        })
+    }
+
+    /// All devices of a site, a page at a time
+    async fn site_devices(&self, netbox: &ResilientNetBoxClient, site_id: i32) -> Result<Vec<NetBoxDevice>, AppError> {
+        let mut devices = Vec::new();
+        loop {
+            let page = netbox
+                .list_devices_with_filters(
+                    Some(site_id),
+                    None,
+                    Some(SITE_DEVICES_PAGE_SIZE),
+                    Some(devices.len() as u32),
+                    &HashMap::new(),
+                )
+                .await?;
+            let results = page.results.unwrap_or_default();
+            if results.is_empty() {
+                break;
+            }
+            devices.extend(results);
+            if page.next.is_none() {
+                break;
+            }
         }
+        Ok(devices)
     }
 
-    fn create_test_netbox_client() -> Arc<ResilientNetBoxClient> {
-        let config = Config {
+    /// Check if the approval policy holds orders of this tenant and environment
+    fn requires_approval(&self, tenant_id: &str, enrichment_data: &EnrichmentData) -> bool {
+        let environment = enrichment_data.business.as_ref().and_then(|b| b.environment.as_deref());
+        self.approval_policy
+            .as_ref()
+            .is_some_and(|policy| policy.requires_approval(tenant_id, environment))
+    }
+
+    /// Park a validated order until an operator approves or rejects it
+    fn hold_for_approval(
+        &self,
+        order_id: String,
+        tenant_id: TenantId,
+        order: HeldOrder,
+    ) -> Result<ProcessedOrderResult, AppError> {
+        self.workflow_manager.update_order_state(&order_id, OrderState::AwaitingApproval)
+            .map_err(Self::transition_error)?;
+        self.awaiting_approval.write().insert(order_id.clone(), order);
+        info!("Order {} is awaiting approval", order_id);
+
+        Ok(ProcessedOrderResult {
+            order_id,
+            tenant_id,
+            netbox_site: None,
+            workflow_state: OrderState::AwaitingApproval,
+            applied_enrichment: EnrichmentReport::default(),
+        })
+    }
+
+    /// Approve an order held for approval and continue processing it
+    pub async fn approve_order(
+        &self,
+        order_id: &str,
+        approver: String,
+        comment: Option<String>,
+    ) -> Result<ProcessedOrderResult, AppError> {
+        self.refuse_while_frozen(true)?;
+        self.workflow_manager
+            .decide_approval(order_id, true, approver.clone(), comment)
+            .map_err(Self::transition_error)?;
+        info!("Order {} approved by {}", order_id, approver);
+
+        let order = self.awaiting_approval.write().remove(order_id);
+        let (Some(order), Some(workflow)) = (order, self.workflow_manager.get_order(order_id)) else {
+            let _ = self.workflow_manager.mark_order_failed(order_id, "Approved order payload not found".to_string());
+            return Err(AppError::Internal(anyhow::anyhow!("Approved order {} payload not found", order_id)));
+        };
+
+        let (order_id, tenant_id) = (order_id.to_string(), workflow.tenant_id);
+        let enrichment_data = self.enricher.enrichment_data(&tenant_id);
+        self.execute_or_schedule(order_id, order, tenant_id, enrichment_data).await
+    }
+
+    /// When the current maintenance freeze ends, or `None` outside one
+    pub fn freeze_until(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.maintenance.as_ref()?.freeze_until(self.clock.now_utc())
+    }
+
+    /// Refuse an order during a maintenance freeze, unless it is `parkable` until
+    /// the freeze ends and the freeze mode parks orders
+    fn refuse_while_frozen(&self, parkable: bool) -> Result<(), AppError> {
+        let Some(until) = self.freeze_until() else {
+            return Ok(());
+        };
+        if parkable && self.maintenance.as_ref().is_some_and(|windows| windows.mode == FreezeMode::Park) {
+            return Ok(());
+        }
+        Err(AppError::ServiceUnavailable(format!(
+            "Orders are frozen for maintenance until {}",
+            until.to_rfc3339()
+        )))
+    }
+
+    /// Create a validated order's resources, or park it in Scheduled until the
+    /// maintenance freeze that is on ends
+    async fn execute_or_schedule(
+        &self,
+        order_id: String,
+        order: HeldOrder,
+        tenant_id: TenantId,
+        enrichment_data: EnrichmentData,
+    ) -> Result<ProcessedOrderResult, AppError> {
+        let Some(until) = self.freeze_until() else {
+            return self.execute_held_order(order_id, order, tenant_id, enrichment_data).await;
+        };
+        if let Err(OrderQueueError::HighPriorityCapReached { cap, .. }) =
+            self.scheduled_queue.push(tenant_id.clone(), order.priority(), order_id.clone())
+        {
+            let error = AppError::QuotaExceeded {
+                resource: "queued high priority orders".to_string(),
+                limit: cap as u64,
+                current: cap as u64,
+            };
+            // Nothing was done for it yet, so the refused order is cancelled rather than failed
+            let _ = self.workflow_manager.update_order_state(&order_id, OrderState::Cancelled);
+            return Err(error);
+        }
+        if let Err(e) = self.workflow_manager.schedule_order(&order_id, until, order) {
+            self.scheduled_queue.remove(|queued| *queued == order_id);
+            return Err(Self::transition_error(e));
+        }
+        info!("Order {} is scheduled for {}, when the maintenance freeze ends", order_id, until.to_rfc3339());
+
+        Ok(ProcessedOrderResult {
+            order_id,
+            tenant_id,
+            netbox_site: None,
+            workflow_state: OrderState::Scheduled,
+            applied_enrichment: EnrichmentReport::default(),
+        })
+    }
+
+    /// Create the resources of a validated order
+    async fn execute_held_order(
+        &self,
+        order_id: String,
+        order: HeldOrder,
+        tenant_id: TenantId,
+        enrichment_data: EnrichmentData,
+    ) -> Result<ProcessedOrderResult, AppError> {
+        match order {
+            HeldOrder::Site(order) => {
+                self.create_site_for_order(order_id, order, tenant_id, enrichment_data).await
+            }
+            HeldOrder::Pop(order) => {
+                self.create_pop_for_order(order_id, order, tenant_id, enrichment_data).await
+            }
+        }
+    }
+
+    /// Execute Scheduled orders once the maintenance freeze has ended
+    ///
+    /// Orders leave highest priority first, first come first served within a
+    /// priority, with starved low priority orders promoted. Nothing is released
+    /// while a freeze is on, and orders another worker has claimed are left to
+    /// it. Returns how many orders were released, whether or not they then
+    /// went through.
+    pub async fn release_scheduled_orders(&self) -> usize {
+        if self.freeze_until().is_some() {
+            return 0;
+        }
+        let mut released = 0;
+        while let Some(queued) = self.scheduled_queue.try_pop() {
+            debug!("Order {} waited {:?} at {:?} priority", queued.order, queued.waited, queued.priority);
+            released += usize::from(self.release_scheduled_order(&queued.order).await);
+        }
+        // Scheduled orders that never went through this service's queue, such as
+        // ones restored from the workflow store or parked by another worker
+        let mut unqueued = self.workflow_manager.get_orders_by_state(OrderState::Scheduled);
+        unqueued.sort_by_key(|workflow| {
+            let priority = workflow.parked_order.as_ref().map(HeldOrder::priority).unwrap_or_default();
+            (std::cmp::Reverse(priority), workflow.created_at)
+        });
+        for workflow in unqueued {
+            released += usize::from(self.release_scheduled_order(&workflow.order_id).await);
+        }
+        released
+    }
+
+    /// Execute a Scheduled order the freeze no longer holds back, unless it left
+    /// Scheduled meanwhile or another worker claimed it; true when it was executed
+    async fn release_scheduled_order(&self, order_id: &str) -> bool {
+        if !self.is_scheduled(order_id) {
+            return false;
+        }
+        match self.claim_scheduled_order(order_id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                debug!("Order {} is claimed by another worker", order_id);
+                return false;
+            }
+            Err(e) => {
+                warn!("Scheduled order {} could not be claimed: {}", order_id, e);
+                return false;
+            }
+        }
+        let comment = Some("Maintenance freeze ended".to_string());
+        if let Err(e) = self.execute_claimed_order(order_id, None, comment).await {
+            warn!("Scheduled order {} failed: {}", order_id, e);
+        }
+        true
+    }
+
+    /// Execute a Scheduled order now, during the maintenance freeze (for operators)
+    ///
+    /// The operator and their comment are recorded on the order's history.
+    pub async fn force_execute_order(
+        &self,
+        order_id: &str,
+        actor: String,
+        comment: Option<String>,
+    ) -> Result<ProcessedOrderResult, AppError> {
+        let comment = match comment {
+            Some(comment) => format!("Executed during the maintenance freeze: {}", comment),
+            None => "Executed during the maintenance freeze".to_string(),
+        };
+        warn!("Order {} is being executed during the maintenance freeze by {}", order_id, actor);
+        if !self.claim_scheduled_order(order_id).await? {
+            if self.is_scheduled(order_id) {
+                return Err(AppError::Conflict(format!("Order {} is being executed by another worker", order_id)));
+            }
+            // No longer Scheduled: refused as any other transition would be
+            return self.execute_scheduled_order(order_id, Some(actor), Some(comment)).await;
+        }
+        self.scheduled_queue.remove(|queued| queued == order_id);
+        self.execute_claimed_order(order_id, Some(actor), Some(comment)).await
+    }
+
+    fn is_scheduled(&self, order_id: &str) -> bool {
+        self.workflow_manager
+            .get_order(order_id)
+            .is_some_and(|workflow| workflow.state == OrderState::Scheduled)
+    }
+
+    /// Take a lease on a Scheduled order before executing it; false when another worker holds one
+    async fn claim_scheduled_order(&self, order_id: &str) -> Result<bool, AppError> {
+        let Some((ref store, ref config)) = self.order_claims else {
+            return Ok(true);
+        };
+        store
+            .claim(order_id, &config.worker_id, config.lease)
+            .await
+            .map_err(|e| AppError::ServiceUnavailable(e.to_string()))
+    }
+
+    /// Execute a Scheduled order this worker claimed, renewing the lease until it is done
+    async fn execute_claimed_order(
+        &self,
+        order_id: &str,
+        actor: Option<String>,
+        comment: Option<String>,
+    ) -> Result<ProcessedOrderResult, AppError> {
+        let execution = self.execute_scheduled_order(order_id, actor, comment);
+        let Some((ref store, ref config)) = self.order_claims else {
+            return execution.await;
+        };
+        tokio::pin!(execution);
+        // Renew well before the lease runs out, so a slow NetBox doesn't hand the order to another worker
+        let mut renewal = tokio::time::interval((config.lease / 3).max(Duration::from_secs(1)));
+        renewal.tick().await;
+        let result = loop {
+            tokio::select! {
+                result = &mut execution => break result,
+                _ = renewal.tick() => {
+                    if !matches!(store.renew(order_id, &config.worker_id, config.lease).await, Ok(true)) {
+                        warn!("Worker {} lost its lease on order {}", config.worker_id, order_id);
+                    }
+                }
+            }
+        };
+
+        // An order that left Scheduled is never executed again; one still in it is left to the next worker
+        let done = if self.is_scheduled(order_id) {
+            store.release(order_id, &config.worker_id).await
+        } else {
+            store.finish(order_id, &config.worker_id).await
+        };
+        if let Err(e) = done {
+            warn!("Worker {} could not give up its lease on order {}: {}", config.worker_id, order_id, e);
+        }
+        result
+    }
+
+    /// Move a Scheduled order back to Validated and create its resources
+    async fn execute_scheduled_order(
+        &self,
+        order_id: &str,
+        actor: Option<String>,
+        comment: Option<String>,
+    ) -> Result<ProcessedOrderResult, AppError> {
+        let order = self
+            .workflow_manager
+            .release_scheduled_order(order_id, actor, comment)
+            .map_err(Self::transition_error)?;
+        let (Some(order), Some(workflow)) = (order, self.workflow_manager.get_order(order_id)) else {
+            // Without its payload the order can never run; fail it rather than leave it parked
+            let _ = self.workflow_manager.mark_order_failed(order_id, "Scheduled order payload not found".to_string());
+            return Err(AppError::Internal(anyhow::anyhow!("Scheduled order {} payload not found", order_id)));
+        };
+
+        let tenant_id = workflow.tenant_id;
+        let enrichment_data = self.enricher.enrichment_data(&tenant_id);
+        self.execute_held_order(order_id.to_string(), order, tenant_id, enrichment_data).await
+    }
+
+    /// Reject an order held for approval, cancelling it
+    pub async fn reject_order(
+        &self,
+        order_id: &str,
+        approver: String,
+        comment: Option<String>,
+    ) -> Result<OrderStatus, AppError> {
+        self.workflow_manager
+            .decide_approval(order_id, false, approver.clone(), comment)
+            .map_err(Self::transition_error)?;
+        self.awaiting_approval.write().remove(order_id);
+        info!("Order {} rejected by {}", order_id, approver);
+
+        self.order_status(order_id)
+    }
+
+    /// Current status of an order, without a tenant check (for operator endpoints)
+    pub fn order_status(&self, order_id: &str) -> Result<OrderStatus, AppError> {
+        let workflow = self.workflow_manager.get_order(order_id)
+            .ok_or_else(|| AppError::NotFound(format!("Order {} not found", order_id)))?;
+        Ok(OrderStatus::from(workflow))
+    }
+
+    /// Map a rejected workflow transition to NotFound or Conflict
+    ///
+    /// Mid-processing this means someone else moved the order on, e.g.
+    /// cancelled it; nothing went wrong internally.
+    fn transition_error(error: WorkflowError) -> AppError {
+        match error {
+            WorkflowError::OrderNotFound(id) => AppError::NotFound(format!("Order {} not found", id)),
+            WorkflowError::InvalidTransition { from, to } => {
+                AppError::Conflict(format!("Order is {:?} and cannot move to {:?}", from, to))
+            }
+        }
+    }
+
+    /// Transform, enrich, and create a validated order's site in NetBox
+    async fn create_site_for_order(
+        &self,
+        order_id: String,
+        order: CreateSiteOrder,
+        tenant_id: TenantId,
+        enrichment_data: EnrichmentData,
+    ) -> Result<ProcessedOrderResult, AppError> {
+        let netbox = self.netbox(&tenant_id)?;
+        let enrichment_data = self.with_address_country(enrichment_data, &order);
+
+        // Step 4: Transform order to NetBox request
+        debug!("Transforming order {} to NetBox request", order_id);
+        let mut netbox_request = self.transformer.transform_site_order(order, None, self.transformation_profiles.profile(&tenant_id).as_ref());
+
+        // Step 5: Enrich the NetBox request (apply enrichment to tags and description)
+        debug!("Enriching NetBox request for order {}", order_id);
+        
+        // Apply the tenant's default and environment tags to the request
+        let enricher = self.enricher.for_tenant(&tenant_id);
+        let mut tags = netbox_request.tags.unwrap_or_default();
+        let mut applied_enrichment = enricher.request_tags_report(&tags, &enrichment_data);
+        tags.extend(enricher.request_tags(&enrichment_data));
+        netbox_request.tags = Some(tags);
+
+        // Step 6: Record the requested site and update workflow to Processing state
+        self.workflow_manager
+            .record_site_request(&order_id, OrderKind::Site, netbox_request.name.clone(), netbox_request.slug.clone())
+            .map_err(Self::transition_error)?;
+        self.workflow_manager.update_order_state(&order_id, OrderState::Processing)
+            .map_err(Self::transition_error)?;
+
+        // Step 7: Create site in NetBox, once its tags exist there
+        let netbox_site = self
+            .within_deadline(&order_id, OrderKind::Site, async {
+                match self.ensure_tags_exist(&netbox, netbox_request.tags.take().unwrap_or_default()).await {
+                    Ok(tags) => netbox_request.tags = Some(tags),
+                    Err(e) => {
+                        self.fail_order(&order_id, e.to_string()).await;
+                        return Err(e);
+                    }
+                }
+                debug!("Creating site in NetBox for order {}", order_id);
+                let netbox_site = match netbox.create_site(netbox_request).await {
+                    Ok(site) => {
+                        // Step 8: Enrich the created site
+                        let (enriched_site, report) = enricher.enrich_site_with_report(site, &enrichment_data);
+                        applied_enrichment.merge(report);
+                
+                        // Step 9: Update workflow with NetBox ID and mark as completed
+                        if let Some(site_id) = enriched_site.id {
+                            let completed = self.workflow_manager
+                                .record_created_resource(&order_id, ResourceKind::Site, site_id)
+                                .and_then(|_| self.workflow_manager.mark_order_completed(&order_id, site_id));
+                            if let Err(e) = completed {
+                                self.fail_order(&order_id, format!("Workflow error: {}", e)).await;
+                                return Err(Self::transition_error(e));
+                            }
+                        }
+
+                        info!("Successfully processed order {} - NetBox site created", order_id);
+                        enriched_site
+                    }
+                    Err(e) => {
+                        error!("Failed to create site in NetBox for order {}: {}", order_id, e);
+                
+                        // Mark workflow as failed, rolling back anything already created
+                        self.fail_order(&order_id, e.to_string()).await;
+                
+                        return Err(e);
+                    }
+                };
+                Ok(netbox_site)
+            })
+            .await?;
+
+        // Get final workflow state
+        let workflow = self.workflow_manager.get_order(&order_id)
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Workflow not found after processing")))?;
+
+        Ok(ProcessedOrderResult {
+            order_id,
+            tenant_id,
+            netbox_site: Some(netbox_site),
+            workflow_state: workflow.state,
+            applied_enrichment,
+        })
+    }
+
+    /// Create a validated pop order's site, then its devices, in NetBox
+    async fn create_pop_for_order(
+        &self,
+        order_id: String,
+        order: CreatePopOrder,
+        tenant_id: TenantId,
+        enrichment_data: EnrichmentData,
+    ) -> Result<ProcessedOrderResult, AppError> {
+        let workflow_error = Self::transition_error;
+
+        let netbox = self.netbox(&tenant_id)?;
+        let enrichment_data = self.with_address_country(enrichment_data, &order.site);
+
+        let mut site_request = self.transformer.transform_site_order(order.site, None, self.transformation_profiles.profile(&tenant_id).as_ref());
+        let enricher = self.enricher.for_tenant(&tenant_id);
+        let mut tags = site_request.tags.unwrap_or_default();
+        let mut applied_enrichment = enricher.request_tags_report(&tags, &enrichment_data);
+        tags.extend(enricher.request_tags(&enrichment_data));
+        site_request.tags = Some(tags);
+
+        self.workflow_manager
+            .record_site_request(&order_id, OrderKind::Pop, site_request.name.clone(), site_request.slug.clone())
+            .map_err(workflow_error)?;
+        self.workflow_manager.update_order_state(&order_id, OrderState::Processing)
+            .map_err(workflow_error)?;
+
+        let site = self
+            .within_deadline(&order_id, OrderKind::Pop, async {
+                // Step 1: the site, whose id every device needs
+                match self.ensure_tags_exist(&netbox, site_request.tags.take().unwrap_or_default()).await {
+                    Ok(tags) => site_request.tags = Some(tags),
+                    Err(e) => {
+                        let _ = self.workflow_manager.finish_step(&order_id, 0, Err(e.to_string()));
+                        self.fail_order(&order_id, e.to_string()).await;
+                        return Err(e);
+                    }
+                }
+                let site = match netbox.create_site(site_request).await {
+                    Ok(site) => {
+                        let (site, report) = enricher.enrich_site_with_report(site, &enrichment_data);
+                        applied_enrichment.merge(report);
+                        site
+                    }
+                    Err(e) => {
+                        error!("Failed to create site for pop order {}: {}", order_id, e);
+                        let _ = self.workflow_manager.finish_step(&order_id, 0, Err(e.to_string()));
+                        self.fail_order(&order_id, format!("Site creation failed: {}", e)).await;
+                        return Err(e);
+                    }
+                };
+                let Some(site_id) = site.id else {
+                    let error = "NetBox returned the pop site without an id".to_string();
+                    let _ = self.workflow_manager.finish_step(&order_id, 0, Err(error.clone()));
+                    self.fail_order(&order_id, error.clone()).await;
+                    return Err(AppError::Internal(anyhow::anyhow!(error)));
+                };
+                self.workflow_manager.record_created_resource(&order_id, ResourceKind::Site, site_id)
+                    .and_then(|_| self.workflow_manager.finish_step(&order_id, 0, Ok(site_id)))
+                    .map_err(workflow_error)?;
+
+                // Steps 2..: the devices, placed in the new site
+                let device_processor = DeviceOrderProcessor::new();
+                for (index, device) in order.devices.iter().enumerate() {
+                    let step = index + 1;
+                    let result = self.create_pop_device(&netbox, &device_processor, device.for_site(Some(site_id)), &enrichment_data).await;
+                    match result {
+                        Ok(device_id) => {
+                            self.workflow_manager.record_created_resource(&order_id, ResourceKind::Device, device_id)
+                                .and_then(|_| self.workflow_manager.finish_step(&order_id, step, Ok(device_id)))
+                                .map_err(workflow_error)?;
+                        }
+                        Err(e) => {
+                            error!("Failed to create device '{}' for pop order {}: {}", device.name, order_id, e);
+                            let _ = self.workflow_manager.finish_step(&order_id, step, Err(e.to_string()));
+                            self.fail_order(&order_id, format!("Device '{}' failed: {}", device.name, e)).await;
+                            return Err(e);
+                        }
+                    }
+                }
+
+                self.workflow_manager.mark_order_completed(&order_id, site_id).map_err(workflow_error)?;
+                info!("Successfully processed pop order {} - site {} and {} device(s) created", order_id, site_id, order.devices.len());
+                Ok(site)
+            })
+            .await?;
+
+        Ok(ProcessedOrderResult {
+            order_id,
+            tenant_id,
+            netbox_site: Some(site),
+            workflow_state: OrderState::Completed,
+            applied_enrichment,
+        })
+    }
+
+    /// Transform, enrich, and create one pop device, returning its NetBox id
+    async fn create_pop_device(
+        &self,
+        netbox: &ResilientNetBoxClient,
+        processor: &DeviceOrderProcessor,
+        device: crate::domain::CreateDeviceOrder,
+        enrichment_data: &EnrichmentData,
+    ) -> Result<i32, AppError> {
+        let mut request = processor.transform(crate::business::OrderPayload::Device(device), None)?;
+        processor.enrich_request(&mut request, enrichment_data)?;
+        let NetBoxResourceRequest::Device { request, .. } = request else {
+            return Err(AppError::Internal(anyhow::anyhow!("Device processor returned a non-device request")));
+        };
+        netbox
+            .create_device(request)
+            .await?
+            .id
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("NetBox returned a device without an id")))
+    }
+
+    /// Run the NetBox phase of an order within its kind's processing deadline
+    ///
+    /// The deadline is recorded on the workflow for the watchdog. A phase that
+    /// overruns it is dropped and the order failed, rolling back what it created.
+    async fn within_deadline<T>(
+        &self,
+        order_id: &str,
+        kind: OrderKind,
+        phase: impl std::future::Future<Output = Result<T, AppError>>,
+    ) -> Result<T, AppError> {
+        let deadline = self.processing_deadlines.for_kind(kind);
+        let _ = self.workflow_manager.set_processing_deadline(order_id, deadline);
+        match tokio::time::timeout(deadline, phase).await {
+            Ok(result) => result,
+            Err(_) => {
+                let error = format!("{} after {}s", PROCESSING_TIMEOUT_ERROR, deadline.as_secs());
+                error!("Order {} overran its processing deadline of {:?}", order_id, deadline);
+                self.fail_order(order_id, error.clone()).await;
+                Err(AppError::ServiceUnavailable(format!("Order {}: {}", order_id, error)))
+            }
+        }
+    }
+
+    /// Fail orders still in Processing past their deadline
+    ///
+    /// Catches orders whose processing task died or hung outside the NetBox
+    /// phase. Returns how many orders were failed.
+    pub async fn fail_overdue_orders(&self) -> usize {
+        let mut failed = 0;
+        for workflow in self.workflow_manager.get_overdue_processing_orders(self.processing_deadlines.default) {
+            warn!("Order {} is still processing past its deadline, failing it", workflow.order_id);
+            let error = format!("{}: order was still processing past its deadline", PROCESSING_TIMEOUT_ERROR);
+            self.fail_order(&workflow.order_id, error).await;
+            if self.workflow_manager.get_order(&workflow.order_id).is_some_and(|w| w.state == OrderState::Failed) {
+                failed += 1;
+            }
+        }
+        failed
+    }
+
+    /// Fail an order, first rolling back any NetBox resources it already created
+    async fn fail_order(&self, order_id: &str, error: String) {
+        let has_resources = self
+            .workflow_manager
+            .get_order(order_id)
+            .is_some_and(|workflow| !workflow.created_resources.is_empty());
+
+        if has_resources {
+            if let Err(e) = self.rollback_order(order_id, error).await {
+                error!("Failed to roll back order {}: {}", order_id, e);
+            }
+        } else {
+            let _ = self.workflow_manager.mark_order_failed(order_id, error);
+        }
+    }
+
+    /// Delete the NetBox resources created by a partially processed order, newest
+    /// first, then fail the order with a rollback report
+    ///
+    /// Rollback is best-effort: a deletion that fails is recorded in the report and
+    /// the remaining resources are still deleted. Resources that are already gone
+    /// count as deleted, so an interrupted rollback can simply be run again.
+    pub async fn rollback_order(&self, order_id: &str, reason: String) -> Result<RollbackReport, AppError> {
+        let workflow = self.workflow_manager
+            .get_order(order_id)
+            .ok_or_else(|| AppError::NotFound(format!("Order {} not found", order_id)))?;
+        let netbox = self.netbox(&workflow.tenant_id)?;
+        roll_back_resources(&self.workflow_manager, &netbox, order_id, reason).await
+    }
+
+    /// Resolve orders stuck in Processing for longer than `max_age`
+    ///
+    /// A site order's recorded slug is looked up in NetBox: if the site exists
+    /// the order is completed with its id, otherwise it is failed with a
+    /// reconciliation note. Orders whose lookup fails are left for the next run.
+    /// A site existing says nothing about whether any other kind of order got
+    /// through, so those are failed, rolling back what they created.
+    pub async fn reconcile_stale_orders(&self, max_age: Duration) -> ReconciliationSummary {
+        let cutoff = chrono::Utc::now()
+            - chrono::Duration::from_std(max_age).unwrap_or_else(|_| chrono::Duration::zero());
+        let mut summary = ReconciliationSummary::default();
+
+        for workflow in self.workflow_manager.get_orders_by_state(OrderState::Processing) {
+            if workflow.updated_at > cutoff {
+                continue;
+            }
+            let order_id = workflow.order_id;
+
+            if workflow.kind != Some(OrderKind::Site) {
+                let note = match workflow.kind {
+                    Some(kind) => format!("Reconciliation: {:?} order was stuck in processing", kind),
+                    None => "Reconciliation: order was stuck in processing".to_string(),
+                };
+                info!("Reconciling order {}: only site orders can be checked against NetBox, failing it", order_id);
+                self.fail_order(&order_id, note).await;
+                if self.workflow_manager.get_order(&order_id).is_some_and(|w| w.state == OrderState::Failed) {
+                    summary.failed += 1;
+                }
+                continue;
+            }
+            let Some(slug) = workflow.site_slug else {
+                let note = "Reconciliation: no site request recorded for order".to_string();
+                if self.workflow_manager.mark_order_failed(&order_id, note).is_ok() {
+                    summary.failed += 1;
+                }
+                continue;
+            };
+
+            let netbox = match self.netbox(&workflow.tenant_id) {
+                Ok(netbox) => netbox,
+                Err(e) => {
+                    warn!("Could not reconcile order {}, will retry: {}", order_id, e);
+                    summary.unresolved += 1;
+                    continue;
+                }
+            };
+            match netbox.find_site_by_slug(&slug).await {
+                Ok(Some(NetBoxSite { id: Some(site_id), .. })) => {
+                    info!("Reconciled order {}: found NetBox site {}", order_id, site_id);
+                    if self.workflow_manager.mark_order_completed(&order_id, site_id).is_ok() {
+                        summary.completed += 1;
+                    }
+                }
+                Ok(_) => {
+                    info!("Reconciled order {}: site {} not found in NetBox", order_id, slug);
+                    let note = format!("Reconciliation: site '{}' was not created in NetBox", slug);
+                    if self.workflow_manager.mark_order_failed(&order_id, note).is_ok() {
+                        summary.failed += 1;
+                    }
+                }
+                Err(e) => {
+                    warn!("Could not reconcile order {}, will retry: {}", order_id, e);
+                    summary.unresolved += 1;
+                }
+            }
+        }
+
+        summary
+    }
+
+    /// Get order status by order ID
+    pub async fn get_order_status(
+        &self,
+        order_id: &str,
+        tenant_id: &TenantId,
+    ) -> Result<OrderStatus, AppError> {
+        let workflow = self.workflow_manager
+            .get_order(order_id)
+            .ok_or_else(|| AppError::NotFound(format!("Order {} not found", order_id)))?;
+
+        // Verify tenant access
+        if workflow.tenant_id != *tenant_id {
+            return Err(AppError::Unauthorized);
+        }
+
+        Ok(OrderStatus::from(workflow))
+    }
+}
+
+/// Delete the resources recorded on an order, newest first, then fail the
+/// order with a rollback report; see [`OrderService::rollback_order`]
+pub(crate) async fn roll_back_resources(
+    workflow_manager: &WorkflowManager,
+    netbox: &ResilientNetBoxClient,
+    order_id: &str,
+    reason: String,
+) -> Result<RollbackReport, AppError> {
+    let resources = workflow_manager.begin_rollback(order_id).map_err(OrderService::transition_error)?;
+    warn!("Rolling back {} resource(s) for order {}: {}", resources.len(), order_id, reason);
+
+    let mut report = RollbackReport::default();
+    for resource in resources.into_iter().rev() {
+        let result = match resource.kind {
+            ResourceKind::Site => netbox.delete_site(resource.id).await,
+            ResourceKind::Device => netbox.delete_device(resource.id).await,
+            ResourceKind::Prefix => netbox.delete_prefix(resource.id).await,
+            ResourceKind::Interface => netbox.delete_interface(resource.id).await,
+            ResourceKind::Cable => netbox.delete_cable(resource.id).await,
+            ResourceKind::Vlan => netbox.delete_vlan(resource.id).await,
+            ResourceKind::IpAddress => netbox.delete_ip_address(resource.id).await,
+            ResourceKind::VirtualMachine => netbox.delete_virtual_machine(resource.id).await,
+        };
+        if let Err(ref e) = result {
+            error!("Rollback of {:?} {} for order {} failed: {}", resource.kind, resource.id, order_id, e);
+        }
+        report.entries.push(RollbackEntry {
+            resource,
+            succeeded: result.is_ok(),
+            error: result.err().map(|e| e.to_string()),
+        });
+    }
+
+    let error = if report.is_complete() {
+        format!("{} (rolled back {} resource(s))", reason, report.entries.len())
+    } else {
+        format!(
+            "{} (rollback incomplete: {} of {} resource(s) could not be deleted)",
+            reason,
+            report.failed_resources().len(),
+            report.entries.len()
+        )
+    };
+    workflow_manager
+        .finish_rollback(order_id, report.clone(), error)
+        .map_err(OrderService::transition_error)?;
+
+    Ok(report)
+}
+
+/// Refuse an update when the site's `last_updated` differs from the caller's copy
+///
+/// NetBox PATCH is last-writer-wins; this narrows the window for overwriting a
+/// concurrent change to the time between the fetch and the PATCH.
+fn ensure_site_unchanged(site: &NetBoxSite, seen: &str) -> Result<(), AppError> {
+    let seen = chrono::DateTime::parse_from_rfc3339(seen)
+        .map_err(|_| AppError::ValidationError(format!("last_updated '{}' is not an RFC 3339 timestamp", seen)))?
+        .with_timezone(&chrono::Utc);
+    if site.last_updated == Some(seen) {
+        return Ok(());
+    }
+    Err(AppError::Conflict(format!(
+        "Site '{}' was changed at {} since {}; fetch it again or set force to overwrite",
+        site.name,
+        site.last_updated.map_or_else(|| "an unknown time".to_string(), |at| at.to_rfc3339()),
+        seen.to_rfc3339()
+    )))
+}
+
+/// Result of processing an order
+#[derive(Debug, Clone)]
+pub struct ProcessedOrderResult {
+    pub order_id: String,
+    pub tenant_id: TenantId,
+    /// Created site; `None` while the order awaits approval
+    pub netbox_site: Option<NetBoxSite>,
+    pub workflow_state: OrderState,
+    /// What enrichment added to the site; empty while the order awaits approval
+    pub applied_enrichment: EnrichmentReport,
+}
+
+/// Result of processing a site decommission order
+#[derive(Debug, Clone)]
+pub struct DecommissionResult {
+    pub order_id: String,
+    pub tenant_id: TenantId,
+    /// The deleted site
+    pub site_id: i32,
+    /// Devices deleted before the site, in deletion order
+    pub deleted_devices: Vec<i32>,
+    pub workflow_state: OrderState,
+}
+
+/// Outcome of a reconciliation run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReconciliationSummary {
+    /// Site orders completed because their site was found in NetBox
+    pub completed: usize,
+    /// Site orders failed because their site was not found, and other orders failed outright
+    pub failed: usize,
+    /// Orders left in Processing because NetBox could not be queried
+    pub unresolved: usize,
+}
+
+/// Order status information
+#[derive(Debug, Clone)]
+pub struct OrderStatus {
+    pub order_id: String,
+    pub state: OrderState,
+    pub netbox_site_id: Option<i32>,
+    /// Per-resource steps; empty for single-resource orders
+    pub steps: Vec<OrderStep>,
+    pub labels: Vec<String>,
+    /// Operator notes, oldest first
+    pub notes: Vec<OrderNote>,
+    /// When a Scheduled order is due to be executed
+    pub execute_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<OrderWorkflow> for OrderStatus {
+    fn from(workflow: OrderWorkflow) -> Self {
+        Self {
+            order_id: workflow.order_id,
+            state: workflow.state,
+            netbox_site_id: workflow.netbox_site_id,
+            steps: workflow.steps,
+            labels: workflow.labels,
+            notes: workflow.notes,
+            execute_at: workflow.execute_at,
+            created_at: workflow.created_at,
+            updated_at: workflow.updated_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::business::address::AddressNormalizer;
+    use crate::business::{EnrichmentSource, InMemoryOrderClaims, StepStatus};
+    use crate::clock::ManualClock;
+    use crate::netbox::client::NetBoxClient;
+    use crate::netbox::fake::FakeNetBox;
+    use crate::resilience::RetryConfig;
+    use poem::http::Method;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn create_test_order() -> CreateSiteOrder {
+        CreateSiteOrder {
+            name: "Test Site".to_string(),
+            description: Some("Test Description".to_string()),
+            address: Some("123 Test Street, Springfield, IL 62704, USA".to_string()),
+            region: None,
+            facility: None,
+            tags: vec![],
+            custom_fields: Default::default(),
+            priority: Default::default(),
+        }
+    }
+
+    fn create_test_netbox_client() -> Arc<ResilientNetBoxClient> {
+        let config = Config {
+            port: 8080,
+            netbox_url: "http://localhost:8000".to_string(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let client = Arc::new(NetBoxClient::new(config).unwrap());
+        Arc::new(ResilientNetBoxClient::new(client))
+    }
+
+    /// Calls needed to exhaust the client's retries
+    fn all_attempts() -> usize {
+        RetryConfig::default().max_attempts as usize
+    }
+
+    #[tokio::test]
+    async fn test_order_service_creation() {
+        let workflow_manager = Arc::new(WorkflowManager::new());
+        let netbox_client = create_test_netbox_client();
+        let service = OrderService::new(workflow_manager, netbox_client);
+        
+        // Service should be created successfully
+        assert!(true); // Just verify it compiles and creates
+    }
+
+    #[tokio::test]
+    async fn test_get_order_status_not_found() {
+        let workflow_manager = Arc::new(WorkflowManager::new());
+        let netbox_client = create_test_netbox_client();
+        let service = OrderService::new(workflow_manager, netbox_client);
+        
+        let result = service.get_order_status("nonexistent", &"tenant1".to_string()).await;
+        assert!(result.is_err());
+        match result.unwrap_err() {
+            AppError::NotFound(_) => {}
+            _ => panic!("Expected NotFound error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_order_status_unauthorized() {
+        let workflow_manager = Arc::new(WorkflowManager::new());
+        let netbox_client = create_test_netbox_client();
+        let service = OrderService::new(workflow_manager.clone(), netbox_client);
+        
+        // Create workflow for tenant1
+        let order_id = workflow_manager.create_order("tenant1".to_string());
+        
+        // Try to access with tenant2
+        let result = service.get_order_status(&order_id, &"tenant2".to_string()).await;
+        assert!(result.is_err());
+        match result.unwrap_err() {
+            AppError::Unauthorized => {}
+            _ => panic!("Expected Unauthorized error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_order_status_success() {
+        let workflow_manager = Arc::new(WorkflowManager::new());
+        let netbox_client = create_test_netbox_client();
+        let service = OrderService::new(workflow_manager.clone(), netbox_client);
+        
+        // Create workflow for tenant1
+        let order_id = workflow_manager.create_order("tenant1".to_string());
+        workflow_manager.update_order_state(&order_id, OrderState::Validated).unwrap();
+        
+        let result = service.get_order_status(&order_id, &"tenant1".to_string()).await;
+        assert!(result.is_ok());
+        let status = result.unwrap();
+        assert_eq!(status.order_id, order_id);
+        assert_eq!(status.state, OrderState::Validated);
+    }
+
+    #[tokio::test]
+    async fn test_process_site_order_validation_failure() {
+        let workflow_manager = Arc::new(WorkflowManager::new());
+        let netbox_client = create_test_netbox_client();
+        let service = OrderService::new(workflow_manager, netbox_client);
+        
+        // Create invalid order (empty name)
+        let invalid_order = CreateSiteOrder {
+            name: "".to_string(),
+            description: None,
+            address: None,
+            region: None,
+            facility: None,
+            tags: vec![],
+            custom_fields: Default::default(),
+            priority: Default::default(),
+        };
+        
+        let result = service.process_site_order(invalid_order, "tenant1".to_string()).await;
+        assert!(result.is_err());
+        match result.unwrap_err() {
+            AppError::ValidationError(_) => {}
+            _ => panic!("Expected ValidationError"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_process_site_order_workflow_states() {
+        let fake = FakeNetBox::start().await;
+        fake.fail_next_creates(all_attempts());
+        let (service, workflow_manager) = create_reconciling_service(&fake);
+        
+        let order = create_test_order();
+        
+        // Process will fail at NetBox creation, but we can verify workflow states
+        let result = service.process_site_order(order, "tenant1".to_string()).await;
+        
+        // Should fail at NetBox creation, but workflow should be created
+        assert!(result.is_err());
+        
+        // Verify workflow was created and transitioned through states
+        // The order_id is generated in process_site_order, so we need to check all orders
+        let orders = workflow_manager.get_tenant_orders("tenant1");
+        assert!(!orders.is_empty());
+        
+        // The last order should be in Failed state
+        let last_order = orders.last().unwrap();
+        assert_eq!(last_order.state, OrderState::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_order_service_full_flow_with_mock() {
+        let fake = FakeNetBox::start().await;
+        let (service, workflow_manager) = create_reconciling_service(&fake);
+        
+        let order = create_test_order();
+        let result = service.process_site_order(order, "tenant1".to_string()).await;
+        assert!(result.is_ok());
+        let processed = result.unwrap();
+        let netbox_site = processed.netbox_site.unwrap();
+        let site_id = netbox_site.id.unwrap();
+        assert_eq!(netbox_site.name, "Test Site");
+        assert_eq!(processed.workflow_state, OrderState::Completed);
+        assert_eq!(fake.object("dcim/sites", site_id).unwrap()["description"], "Test Description");
+        
+        // Verify workflow state
+        let workflow = workflow_manager.get_order(&processed.order_id).unwrap();
+        assert_eq!(workflow.state, OrderState::Completed);
+        assert_eq!(workflow.netbox_site_id, Some(site_id));
+    }
+
+    #[tokio::test]
+    async fn test_order_service_netbox_failure_handling() {
+        let fake = FakeNetBox::start().await;
+        fake.fail_next_creates(all_attempts());
+        let (service, workflow_manager) = create_reconciling_service(&fake);
+        
+        let order = create_test_order();
+        let result = service.process_site_order(order, "tenant1".to_string()).await;
+        
+        assert!(result.is_err());
+        assert!(fake.sites().is_empty());
+        
+        // Verify workflow is in Failed state
+        let orders = workflow_manager.get_tenant_orders("tenant1");
+        assert!(!orders.is_empty());
+        let failed_order = orders.last().unwrap();
+        assert_eq!(failed_order.state, OrderState::Failed);
+        assert!(failed_order.error_message.is_some());
+    }
+
+    fn create_reconciling_service(fake: &FakeNetBox) -> (OrderService, Arc<WorkflowManager>) {
+        let workflow_manager = Arc::new(WorkflowManager::new());
+        let service = OrderService::new(workflow_manager.clone(), fake.resilient_client());
+        (service, workflow_manager)
+    }
+
+    /// Service whose NetBox calls go to a wiremock server, for exact HTTP responses
+    fn create_mocked_service(mock_server: &wiremock::MockServer) -> (OrderService, Arc<WorkflowManager>) {
+        let config = Config {
             port: 8080,
-            netbox_url: "http://localhost:8000".to_string(),
+            netbox_url: mock_server.uri(),
             netbox_token: "test-token".to_string(),
+            ..Default::default()
         };
-        let client = Arc::new(NetBoxClient::new(config).unwrap());
-        Arc::new(ResilientNetBoxClient::new(client))
+        let netbox_client = Arc::new(NetBoxClient::new(config).unwrap());
+        let resilient_client = Arc::new(ResilientNetBoxClient::new(netbox_client));
+        let workflow_manager = Arc::new(WorkflowManager::new());
+        let service = OrderService::new(workflow_manager.clone(), resilient_client);
+        (service, workflow_manager)
+    }
+
+    fn create_processing_order(workflow_manager: &WorkflowManager, slug: &str) -> String {
+        create_processing_order_of_kind(workflow_manager, OrderKind::Site, slug)
+    }
+
+    fn create_processing_order_of_kind(workflow_manager: &WorkflowManager, kind: OrderKind, slug: &str) -> String {
+        let order_id = workflow_manager.create_order("tenant1".to_string());
+        workflow_manager
+            .record_site_request(&order_id, kind, "Test Site".to_string(), Some(slug.to_string()))
+            .unwrap();
+        workflow_manager.update_order_state(&order_id, OrderState::Validated).unwrap();
+        workflow_manager.update_order_state(&order_id, OrderState::Processing).unwrap();
+        order_id
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_completes_order_when_site_exists() {
+        let fake = FakeNetBox::start().await;
+        fake.seed_site(json!({"id": 77, "name": "Test Site", "slug": "test-site"}));
+        fake.seed_site(json!({"name": "Other Site", "slug": "other-site"}));
+        let (service, workflow_manager) = create_reconciling_service(&fake);
+        let order_id = create_processing_order(&workflow_manager, "test-site");
+
+        let summary = service.reconcile_stale_orders(Duration::ZERO).await;
+
+        assert_eq!(summary.completed, 1);
+        let workflow = workflow_manager.get_order(&order_id).unwrap();
+        assert_eq!(workflow.state, OrderState::Completed);
+        assert_eq!(workflow.netbox_site_id, Some(77));
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_fails_order_when_site_missing() {
+        let fake = FakeNetBox::start().await;
+        fake.seed_site(json!({"name": "Other Site", "slug": "other-site"}));
+        let (service, workflow_manager) = create_reconciling_service(&fake);
+        let order_id = create_processing_order(&workflow_manager, "test-site");
+
+        let summary = service.reconcile_stale_orders(Duration::ZERO).await;
+
+        assert_eq!(summary.failed, 1);
+        let workflow = workflow_manager.get_order(&order_id).unwrap();
+        assert_eq!(workflow.state, OrderState::Failed);
+        assert!(workflow.error_message.unwrap().starts_with("Reconciliation:"));
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_fails_update_and_decommission_orders_whatever_the_site() {
+        let fake = FakeNetBox::start().await;
+        fake.seed_site(json!({"id": 77, "name": "Test Site", "slug": "test-site"}));
+        let (service, workflow_manager) = create_reconciling_service(&fake);
+        let update = create_processing_order_of_kind(&workflow_manager, OrderKind::SiteUpdate, "test-site");
+        let decommission = create_processing_order_of_kind(&workflow_manager, OrderKind::Decommission, "test-site");
+        let gone = create_processing_order_of_kind(&workflow_manager, OrderKind::Decommission, "gone-site");
+
+        let summary = service.reconcile_stale_orders(Duration::ZERO).await;
+
+        assert_eq!(summary, ReconciliationSummary { completed: 0, failed: 3, unresolved: 0 });
+        for order_id in [update, decommission, gone] {
+            let workflow = workflow_manager.get_order(&order_id).unwrap();
+            assert_eq!(workflow.state, OrderState::Failed);
+            assert_eq!(workflow.netbox_site_id, None);
+            assert!(workflow.error_message.unwrap().ends_with("order was stuck in processing"));
+        }
+        // Nothing to look up: the site existing or not doesn't tell how these went
+        assert!(fake.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_skips_recent_orders() {
+        let fake = FakeNetBox::start().await;
+        let (service, workflow_manager) = create_reconciling_service(&fake);
+        let order_id = create_processing_order(&workflow_manager, "test-site");
+
+        let summary = service.reconcile_stale_orders(Duration::from_secs(600)).await;
+
+        assert_eq!(summary, ReconciliationSummary::default());
+        assert_eq!(workflow_manager.get_order(&order_id).unwrap().state, OrderState::Processing);
+        assert!(fake.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_completed_order_notifies_webhooks() {
+        use crate::domain::tenant::TenantStore;
+        use crate::domain::webhook::{RegisterWebhookRequest, WebhookRegistration};
+        use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+        let fake = FakeNetBox::start().await;
+        let receiver = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/hook"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&receiver)
+            .await;
+        let store = Arc::new(TenantStore::new());
+        store.add_webhook(WebhookRegistration::from_request(
+            RegisterWebhookRequest {
+                url: format!("{}/hook", receiver.uri()),
+                secret: "s3cret".to_string(),
+                events: vec![],
+            },
+            "tenant1".to_string(),
+        ));
+        let notifier = Arc::new(crate::business::WebhookNotifier::new(store));
+        let (service, _) = create_reconciling_service(&fake);
+        notifier.clone().listen(service.workflow_manager().subscribe());
+
+        let processed = service.process_site_order(create_test_order(), "tenant1".to_string()).await.unwrap();
+
+        // Delivery happens in the background
+        for _ in 0..50 {
+            if !notifier.deliveries().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let deliveries = notifier.deliveries();
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].order_id, processed.order_id);
+        assert!(deliveries[0].succeeded());
+    }
+
+    #[tokio::test]
+    async fn test_order_requiring_approval_is_held_until_approved() {
+        use crate::business::approval::ApprovalRules;
+
+        let fake = FakeNetBox::start().await;
+        let (service, workflow_manager) = create_reconciling_service(&fake);
+        let service = service.with_approval_policy(Arc::new(ApprovalRules::new().with_tenant("tenant1")));
+
+        let held = service.process_site_order(create_test_order(), "tenant1".to_string()).await.unwrap();
+        assert_eq!(held.workflow_state, OrderState::AwaitingApproval);
+        assert!(held.netbox_site.is_none());
+        assert!(fake.requests().is_empty());
+
+        let approved = service
+            .approve_order(&held.order_id, "alice".to_string(), Some("looks good".to_string()))
+            .await
+            .unwrap();
+
+        assert_eq!(approved.workflow_state, OrderState::Completed);
+        assert_eq!(fake.sites().len(), 1);
+        let workflow = workflow_manager.get_order(&held.order_id).unwrap();
+        assert_eq!(workflow.netbox_site_id, approved.netbox_site.unwrap().id);
+        let approval = workflow
+            .history
+            .iter()
+            .find(|entry| entry.from == OrderState::AwaitingApproval)
+            .unwrap();
+        assert_eq!(approval.actor.as_deref(), Some("alice"));
+        assert_eq!(approval.comment.as_deref(), Some("looks good"));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_order_is_cancelled_without_netbox_call() {
+        use crate::business::approval::ApprovalRules;
+
+        let fake = FakeNetBox::start().await;
+        let (service, _) = create_reconciling_service(&fake);
+        let service = service.with_approval_policy(Arc::new(ApprovalRules::new().with_tenant("tenant1")));
+
+        let held = service.process_site_order(create_test_order(), "tenant1".to_string()).await.unwrap();
+        let status = service.reject_order(&held.order_id, "alice".to_string(), None).await.unwrap();
+
+        assert_eq!(status.state, OrderState::Cancelled);
+        let result = service.approve_order(&held.order_id, "alice".to_string(), None).await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+        assert!(fake.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_order_not_matching_policy_is_auto_approved() {
+        use crate::business::approval::ApprovalRules;
+
+        let fake = FakeNetBox::start().await;
+        let (service, _) = create_reconciling_service(&fake);
+        let service = service.with_approval_policy(Arc::new(ApprovalRules::new().with_tenant("tenant2")));
+
+        let processed = service.process_site_order(create_test_order(), "tenant1".to_string()).await.unwrap();
+
+        assert_eq!(processed.workflow_state, OrderState::Completed);
+    }
+
+    /// Service frozen every Friday 22:00-23:00 UTC, with a clock stopped at `at`
+    fn create_frozen_service(fake: &FakeNetBox, at: &str, mode: FreezeMode) -> (OrderService, Arc<WorkflowManager>, Arc<ManualClock>) {
+        let clock = Arc::new(ManualClock::starting_at(at.parse().unwrap()));
+        let workflow_manager = Arc::new(WorkflowManager::new().with_clock(clock.clone()));
+        let windows = MaintenanceWindows::new(MaintenanceWindows::parse_windows("Fri 22:00-23:00"), chrono_tz::Tz::UTC)
+            .with_mode(mode);
+        let service = OrderService::new(workflow_manager.clone(), fake.resilient_client())
+            .with_clock(clock.clone())
+            .with_maintenance_windows(Arc::new(windows));
+        (service, workflow_manager, clock)
+    }
+
+    #[tokio::test]
+    async fn test_order_during_freeze_is_scheduled_until_it_ends() {
+        // 2026-10-16 is a Friday
+        let fake = FakeNetBox::start().await;
+        let (service, workflow_manager, clock) = create_frozen_service(&fake, "2026-10-16T21:59:59Z", FreezeMode::Park);
+
+        let before = service.process_site_order(create_test_order(), "tenant1".to_string()).await.unwrap();
+        assert_eq!(before.workflow_state, OrderState::Completed);
+
+        clock.advance(Duration::from_secs(1));
+        let mut order = create_test_order();
+        order.name = "Frozen Site".to_string();
+        let scheduled = service.process_site_order(order, "tenant1".to_string()).await.unwrap();
+        assert_eq!(scheduled.workflow_state, OrderState::Scheduled);
+        let workflow = workflow_manager.get_order(&scheduled.order_id).unwrap();
+        assert_eq!(workflow.execute_at, Some("2026-10-16T23:00:00Z".parse().unwrap()));
+        assert_eq!(fake.sites().len(), 1);
+
+        clock.advance(Duration::from_secs(3599));
+        assert_eq!(service.release_scheduled_orders().await, 0);
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(service.release_scheduled_orders().await, 1);
+        let workflow = workflow_manager.get_order(&scheduled.order_id).unwrap();
+        assert_eq!(workflow.state, OrderState::Completed);
+        assert_eq!(workflow.execute_at, None);
+        assert_eq!(fake.sites().len(), 2);
+        let release = workflow.history.iter().find(|entry| entry.from == OrderState::Scheduled).unwrap();
+        assert_eq!(release.actor, None);
+        assert_eq!(release.comment.as_deref(), Some("Maintenance freeze ended"));
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_order_can_be_forced_by_an_operator() {
+        let fake = FakeNetBox::start().await;
+        let (service, workflow_manager, _) = create_frozen_service(&fake, "2026-10-16T22:30:00Z", FreezeMode::Park);
+        let scheduled = service.process_site_order(create_test_order(), "tenant1".to_string()).await.unwrap();
+
+        let forced = service
+            .force_execute_order(&scheduled.order_id, "alice".to_string(), Some("core router swap".to_string()))
+            .await
+            .unwrap();
+
+        assert_eq!(forced.workflow_state, OrderState::Completed);
+        assert_eq!(fake.sites().len(), 1);
+        let workflow = workflow_manager.get_order(&scheduled.order_id).unwrap();
+        let release = workflow.history.iter().find(|entry| entry.from == OrderState::Scheduled).unwrap();
+        assert_eq!(release.actor.as_deref(), Some("alice"));
+        assert_eq!(release.comment.as_deref(), Some("Executed during the maintenance freeze: core router swap"));
+        let again = service.force_execute_order(&scheduled.order_id, "alice".to_string(), None).await;
+        assert!(matches!(again, Err(AppError::Conflict(_))));
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_orders_are_released_by_priority() {
+        let fake = FakeNetBox::start().await;
+        let (service, workflow_manager, clock) = create_frozen_service(&fake, "2026-10-16T22:00:00Z", FreezeMode::Park);
+        let service = service.with_order_queue(OrderQueueConfig {
+            high_priority_per_tenant: 1,
+            promote_low_after: Duration::from_secs(45 * 60),
+        });
+        let order = |name: &str, priority: OrderPriority| CreateSiteOrder {
+            name: name.to_string(),
+            priority,
+            ..create_test_order()
+        };
+
+        for (name, priority) in [
+            ("Import 1", OrderPriority::Low),
+            ("Site 1", OrderPriority::Normal),
+            ("Emergency 1", OrderPriority::High),
+            ("Import 2", OrderPriority::Low),
+        ] {
+            let result = service.process_site_order(order(name, priority), "tenant1".to_string()).await.unwrap();
+            assert_eq!(result.workflow_state, OrderState::Scheduled);
+            clock.advance(Duration::from_secs(10 * 60));
+        }
+        let capped = service.process_site_order(order("Emergency 2", OrderPriority::High), "tenant1".to_string()).await;
+        let Err(error @ AppError::QuotaExceeded { .. }) = capped else {
+            panic!("Expected QuotaExceeded, got {:?}", capped);
+        };
+        assert_eq!(error.to_string(), "Quota exceeded: 1 of 1 queued high priority orders in use");
+        assert_eq!(workflow_manager.get_orders_by_state(OrderState::Cancelled).len(), 1);
+
+        // At 23:00 the first import has waited an hour, past the 45 minutes that promote it to normal
+        clock.advance(Duration::from_secs(20 * 60));
+        assert_eq!(service.release_scheduled_orders().await, 4);
+        let names: Vec<_> = fake.sites().iter().map(|site| site["name"].as_str().unwrap().to_string()).collect();
+        assert_eq!(names, ["Emergency 1", "Import 1", "Site 1", "Import 2"]);
+
+        let waits = service.scheduled_queue().wait_stats();
+        let (high, low) = (waits[0].1, waits[2].1);
+        assert_eq!((high.dequeued, high.max_wait_secs), (1, 2400.0));
+        assert_eq!((low.dequeued, low.max_wait_secs), (2, 3600.0));
+        assert!(service.scheduled_queue().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_forced_order_leaves_the_queue() {
+        let fake = FakeNetBox::start().await;
+        let (service, _, _) = create_frozen_service(&fake, "2026-10-16T22:30:00Z", FreezeMode::Park);
+        let service = service.with_order_queue(OrderQueueConfig { high_priority_per_tenant: 1, ..Default::default() });
+        let order = CreateSiteOrder { priority: OrderPriority::High, ..create_test_order() };
+        let scheduled = service.process_site_order(order.clone(), "tenant1".to_string()).await.unwrap();
+
+        service.force_execute_order(&scheduled.order_id, "alice".to_string(), None).await.unwrap();
+
+        // The forced order no longer holds the tenant's only high priority place
+        assert!(service.scheduled_queue().is_empty());
+        let next = CreateSiteOrder { name: "Next Site".to_string(), ..order };
+        let result = service.process_site_order(next, "tenant1".to_string()).await.unwrap();
+        assert_eq!(result.workflow_state, OrderState::Scheduled);
+    }
+
+    #[tokio::test]
+    async fn test_replicas_sharing_a_store_execute_each_parked_order_once() {
+        let fake = FakeNetBox::start().await;
+        let (replica_a, workflow_manager, clock) = create_frozen_service(&fake, "2026-10-16T22:00:00Z", FreezeMode::Park);
+        let claims: Arc<dyn OrderClaimStore> = Arc::new(InMemoryOrderClaims::new().with_clock(clock.clone()));
+        let windows = MaintenanceWindows::new(MaintenanceWindows::parse_windows("Fri 22:00-23:00"), chrono_tz::Tz::UTC);
+        let worker = |worker_id: &str| OrderClaimConfig { worker_id: worker_id.to_string(), lease: Duration::from_secs(60) };
+        let replica_a = replica_a.with_order_claims(claims.clone(), worker("a"));
+        let replica_b = OrderService::new(workflow_manager.clone(), fake.resilient_client())
+            .with_clock(clock.clone())
+            .with_maintenance_windows(Arc::new(windows))
+            .with_order_claims(claims, worker("b"));
+
+        let mut order_ids = Vec::new();
+        for n in 0..10 {
+            let replica = if n % 2 == 0 { &replica_a } else { &replica_b };
+            let order = CreateSiteOrder { name: format!("Site {}", n), ..create_test_order() };
+            order_ids.push(replica.process_site_order(order, "tenant1".to_string()).await.unwrap().order_id);
+        }
+
+        clock.advance(Duration::from_secs(3600));
+        let (released_a, released_b) = tokio::join!(replica_a.release_scheduled_orders(), replica_b.release_scheduled_orders());
+        assert_eq!(released_a + released_b, 10);
+        assert_eq!(fake.sites().len(), 10);
+        for order_id in order_ids {
+            let workflow = workflow_manager.get_order(&order_id).unwrap();
+            assert_eq!(workflow.state, OrderState::Completed);
+            assert_eq!(workflow.history.iter().filter(|entry| entry.from == OrderState::Scheduled).count(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_order_claimed_by_another_worker_waits_for_its_lease_to_expire() {
+        let fake = FakeNetBox::start().await;
+        let (service, workflow_manager, clock) = create_frozen_service(&fake, "2026-10-16T22:30:00Z", FreezeMode::Park);
+        let claims = Arc::new(InMemoryOrderClaims::new().with_clock(clock.clone()));
+        let lease = Duration::from_secs(60);
+        let config = OrderClaimConfig { worker_id: "a".to_string(), lease };
+        let service = service.with_order_claims(claims.clone(), config);
+        let scheduled = service.process_site_order(create_test_order(), "tenant1".to_string()).await.unwrap();
+        assert!(claims.claim(&scheduled.order_id, "b", lease).await.unwrap());
+
+        let forced = service.force_execute_order(&scheduled.order_id, "alice".to_string(), None).await;
+        assert!(matches!(forced, Err(AppError::Conflict(_))));
+        clock.advance(Duration::from_secs(30 * 60));
+        assert!(claims.claim(&scheduled.order_id, "b", lease).await.unwrap());
+        assert_eq!(service.release_scheduled_orders().await, 0);
+        assert_eq!(workflow_manager.get_order(&scheduled.order_id).unwrap().state, OrderState::Scheduled);
+
+        // b stopped renewing: once its lease is out the order is a's
+        clock.advance(lease);
+        assert_eq!(service.release_scheduled_orders().await, 1);
+        assert_eq!(workflow_manager.get_order(&scheduled.order_id).unwrap().state, OrderState::Completed);
+        assert!(!claims.claim(&scheduled.order_id, "b", lease).await.unwrap());
+        assert_eq!(fake.sites().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_parked_order_restored_from_the_store_is_executed() {
+        let fake = FakeNetBox::start().await;
+        let (service, workflow_manager, clock) = create_frozen_service(&fake, "2026-10-16T22:30:00Z", FreezeMode::Park);
+        let scheduled = service.process_site_order(create_test_order(), "tenant1".to_string()).await.unwrap();
+        let stored = serde_json::to_string(&workflow_manager.get_order(&scheduled.order_id).unwrap()).unwrap();
+
+        // A restart: a new manager and service that only have what was stored
+        let restarted = Arc::new(WorkflowManager::new().with_clock(clock.clone()));
+        assert_eq!(restarted.restore_orders(vec![serde_json::from_str(&stored).unwrap()]), 1);
+        let service = OrderService::new(restarted.clone(), fake.resilient_client()).with_clock(clock.clone());
+
+        assert_eq!(service.release_scheduled_orders().await, 1);
+        let workflow = restarted.get_order(&scheduled.order_id).unwrap();
+        assert_eq!(workflow.state, OrderState::Completed);
+        assert!(workflow.parked_order.is_none());
+        assert_eq!(fake.sites().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_freeze_refuses_orders_that_cannot_be_parked() {
+        let fake = FakeNetBox::start().await;
+        let (service, workflow_manager, _) = create_frozen_service(&fake, "2026-10-16T22:30:00Z", FreezeMode::Reject);
+
+        let result = service.process_site_order(create_test_order(), "tenant1".to_string()).await;
+        assert!(matches!(result, Err(AppError::ServiceUnavailable(ref msg)) if msg.contains("2026-10-16T23:00:00")));
+        assert_eq!(workflow_manager.order_count(), 0);
+
+        // Decommissions are refused even when orders are parked
+        let (service, _, _) = create_frozen_service(&fake, "2026-10-16T22:30:00Z", FreezeMode::Park);
+        let order = DecommissionSiteOrder { site_id: 1, force: false, reason: None };
+        let result = service.process_decommission_site_order(order, "tenant1".to_string()).await;
+        assert!(matches!(result, Err(AppError::ServiceUnavailable(ref msg)) if msg.contains("frozen")));
+        assert!(fake.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rollback_deletes_created_resources_in_reverse_order() {
+        let fake = FakeNetBox::start().await;
+        fake.seed_device(json!({"id": 20, "name": "sw1", "site": 10}));
+        // Site 10 is already gone, which counts as rolled back
+        let (service, workflow_manager) = create_reconciling_service(&fake);
+        let order_id = create_processing_order(&workflow_manager, "test-site");
+        workflow_manager.record_created_resource(&order_id, ResourceKind::Site, 10).unwrap();
+        workflow_manager.record_created_resource(&order_id, ResourceKind::Device, 20).unwrap();
+
+        let report = service.rollback_order(&order_id, "device step failed".to_string()).await.unwrap();
+
+        assert!(report.is_complete());
+        assert_eq!(report.entries[0].resource.kind, ResourceKind::Device);
+        assert_eq!(report.entries[1].resource.kind, ResourceKind::Site);
+        let paths: Vec<String> = fake.requests().into_iter().map(|request| request.path).collect();
+        assert_eq!(paths, vec!["/api/dcim/devices/20/", "/api/dcim/sites/10/"]);
+        assert!(fake.devices().is_empty());
+        let workflow = workflow_manager.get_order(&order_id).unwrap();
+        assert_eq!(workflow.state, OrderState::Failed);
+        assert!(workflow.error_message.unwrap().starts_with("device step failed"));
+        assert!(workflow.rollback_report.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_rollback_is_best_effort() {
+        use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .and(path("/api/dcim/devices/20/"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/api/dcim/sites/10/"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let (service, workflow_manager) = create_mocked_service(&mock_server);
+        let order_id = create_processing_order(&workflow_manager, "test-site");
+        workflow_manager.record_created_resource(&order_id, ResourceKind::Site, 10).unwrap();
+        workflow_manager.record_created_resource(&order_id, ResourceKind::Device, 20).unwrap();
+
+        let report = service.rollback_order(&order_id, "device step failed".to_string()).await.unwrap();
+
+        assert!(!report.is_complete());
+        assert_eq!(
+            report.failed_resources(),
+            vec![crate::business::CreatedResource { kind: ResourceKind::Device, id: 20 }]
+        );
+        assert!(report.entries[0].error.is_some());
+        assert!(report.entries[1].succeeded);
+        let workflow = workflow_manager.get_order(&order_id).unwrap();
+        assert_eq!(workflow.state, OrderState::Failed);
+        assert!(workflow.error_message.unwrap().contains("rollback incomplete: 1 of 2"));
+    }
+
+    #[tokio::test]
+    async fn test_rollback_resumes_interrupted_rollback() {
+        let fake = FakeNetBox::start().await;
+        let (service, workflow_manager) = create_reconciling_service(&fake);
+        let order_id = create_processing_order(&workflow_manager, "test-site");
+        workflow_manager.record_created_resource(&order_id, ResourceKind::Site, 10).unwrap();
+        workflow_manager.begin_rollback(&order_id).unwrap();
+
+        let report = service.rollback_order(&order_id, "interrupted".to_string()).await.unwrap();
+        assert!(report.is_complete());
+
+        // A finished rollback cannot be started again
+        let again = service.rollback_order(&order_id, "again".to_string()).await;
+        assert!(matches!(again, Err(AppError::Conflict(_))));
+    }
+
+    fn create_test_pop_order() -> CreatePopOrder {
+        let device = |name: &str| crate::domain::PopDeviceDefinition {
+            name: name.to_string(),
+            device_type: 1,
+            role: 2,
+            serial: None,
+            tags: vec![],
+        };
+        CreatePopOrder {
+            site: create_test_order(),
+            devices: vec![device("sw1"), device("sw2")],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pop_order_creates_site_then_devices() {
+        let fake = FakeNetBox::start().await;
+        let (service, _) = create_reconciling_service(&fake);
+
+        let processed = service.process_pop_order(create_test_pop_order(), "tenant1".to_string()).await.unwrap();
+
+        assert_eq!(processed.workflow_state, OrderState::Completed);
+        let site_id = processed.netbox_site.unwrap().id;
+        let status = service.order_status(&processed.order_id).unwrap();
+        assert_eq!(status.netbox_site_id, site_id);
+        let devices: Vec<_> = fake.devices().iter().map(|device| (device["id"].as_i64(), device["site"].as_i64())).collect();
+        let site_id = site_id.map(i64::from);
+        assert_eq!(devices, vec![(Some(1), site_id), (Some(2), site_id)]);
+        let steps: Vec<_> = status.steps.iter().map(|step| (step.kind, step.name.as_str(), step.status, step.netbox_id)).collect();
+        assert_eq!(
+            steps,
+            vec![
+                (ResourceKind::Site, "Test Site", StepStatus::Completed, status.netbox_site_id),
+                (ResourceKind::Device, "sw1", StepStatus::Completed, Some(1)),
+                (ResourceKind::Device, "sw2", StepStatus::Completed, Some(2)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pop_order_names_unnamed_devices_from_template() {
+        use crate::business::naming::{NameSequences, NamingPolicy};
+
+        let fake = FakeNetBox::start().await;
+        fake.seed_device(json!({"name": "test-site-r2-01", "site": 99}));
+        let (service, _) = create_reconciling_service(&fake);
+        let policy = NamingPolicy::new().with_template("tenant1", POP_NAMING_ORDER_TYPE, "{site_slug}-r{role}-{seq:02}".parse().unwrap());
+        let service = service.with_device_namer(Arc::new(DeviceNamer::new(policy, NameSequences::new(), 64)));
+        let mut order = create_test_pop_order();
+        order.devices[0].name = String::new();
+        order.devices[1].name = " ".to_string();
+
+        let processed = service.process_pop_order(order, "tenant1".to_string()).await.unwrap();
+
+        let names: Vec<_> = service
+            .order_status(&processed.order_id)
+            .unwrap()
+            .steps
+            .iter()
+            .map(|step| step.name.clone())
+            .collect();
+        // The first device drew the taken 01 and was renumbered after the second took 02
+        assert_eq!(names, vec!["Test Site", "test-site-r2-03", "test-site-r2-02"]);
+
+        // Tenants without a template still have to name their devices
+        let mut order = create_test_pop_order();
+        order.site.name = "Other Site".to_string();
+        order.devices[0].name = String::new();
+        let result = service.process_pop_order(order, "tenant2".to_string()).await;
+        assert!(matches!(result, Err(AppError::ValidationError(msg)) if msg.contains("Device name cannot be empty")));
+    }
+
+    #[tokio::test]
+    async fn test_pop_order_rolls_back_when_a_device_fails() {
+        use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"id": 10, "name": "Test Site", "status": "active"})))
+            .mount(&mock_server)
+            .await;
+        for (name, response) in [
+            ("sw1", ResponseTemplate::new(201).set_body_json(json!({"id": 20, "name": "sw1", "site": 10}))),
+            ("sw2", ResponseTemplate::new(400).set_body_json(json!({"device_type": ["Select a valid device type."]}))),
+        ] {
+            Mock::given(method("POST"))
+                .and(path("/api/dcim/devices/"))
+                .and(body_partial_json(json!({"name": name, "site": 10})))
+                .respond_with(response)
+                .mount(&mock_server)
+                .await;
+        }
+        for resource in ["/api/dcim/devices/20/", "/api/dcim/sites/10/"] {
+            Mock::given(method("DELETE"))
+                .and(path(resource))
+                .respond_with(ResponseTemplate::new(204))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+        let (service, workflow_manager) = create_mocked_service(&mock_server);
+
+        let result = service.process_pop_order(create_test_pop_order(), "tenant1".to_string()).await;
+
+        // NetBox's field errors reach the caller as they are, not as an internal error
+        assert!(matches!(result, Err(AppError::NetBoxValidation(_))), "{:?}", result);
+        let workflow = workflow_manager.get_tenant_orders("tenant1").into_iter().next().unwrap();
+        let error = workflow.error_message.clone().unwrap();
+        assert!(error.contains("Device 'sw2' failed"), "{}", error);
+        assert!(error.contains("rolled back 2 resource(s)"), "{}", error);
+        assert_eq!(workflow.state, OrderState::Failed);
+        assert!(workflow.rollback_report.unwrap().is_complete());
+        assert_eq!(workflow.steps[1].status, StepStatus::Completed);
+        assert_eq!(workflow.steps[2].status, StepStatus::Failed);
+        assert!(workflow.steps[2].error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_pop_order_validation() {
+        let service = OrderService::new(Arc::new(WorkflowManager::new()), create_test_netbox_client());
+
+        let mut order = create_test_pop_order();
+        order.devices[1].name = "sw1".to_string();
+        let result = service.process_pop_order(order, "tenant1".to_string()).await;
+        assert!(matches!(result, Err(AppError::ValidationError(msg)) if msg.contains("Duplicate device name 'sw1'")));
+
+        let mut order = create_test_pop_order();
+        order.devices.clear();
+        let result = service.process_pop_order(order, "tenant1".to_string()).await;
+        assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+        let mut order = create_test_pop_order();
+        order.devices[0].device_type = 0;
+        let result = service.process_pop_order(order, "tenant1".to_string()).await;
+        assert!(matches!(result, Err(AppError::ValidationError(msg)) if msg.starts_with("Device 'sw1'")));
+    }
+
+    #[tokio::test]
+    async fn test_site_order_uses_tenant_transformation_profile() {
+        use crate::business::TransformationProfile;
+
+        let fake = FakeNetBox::start().await;
+        let (service, _) = create_reconciling_service(&fake);
+        let profile = TransformationProfile {
+            status: Some(crate::netbox::models::SiteStatus::Active),
+            region: Some(3),
+            ..Default::default()
+        };
+        let service = service.with_transformation_profiles(Arc::new(
+            TransformationProfiles::new().with_profile("tenant1", profile),
+        ));
+
+        let processed = service.process_site_order(create_test_order(), "tenant1".to_string()).await.unwrap();
+
+        assert_eq!(processed.workflow_state, OrderState::Completed);
+        let site = fake.object("dcim/sites", processed.netbox_site.unwrap().id.unwrap()).unwrap();
+        assert_eq!(site["region"], 3);
+        assert_eq!(site["status"], "active");
+    }
+
+    #[tokio::test]
+    async fn test_site_order_enriched_for_tenant_environment() {
+        use crate::business::approval::ApprovalRules;
+
+        let fake = FakeNetBox::start().await;
+        let (service, _) = create_reconciling_service(&fake);
+        let config = EnrichmentConfig::from_yaml(
+            "tenants:\n  tenant1:\n    environment: staging\n    default_tags: [portal]\n",
+        )
+        .unwrap();
+        let service = service
+            .with_enrichment_config(config)
+            .with_approval_policy(Arc::new(ApprovalRules::new().with_environment("production")));
+
+        let processed = service.process_site_order(create_test_order(), "tenant1".to_string()).await.unwrap();
+
+        assert_eq!(processed.workflow_state, OrderState::Completed);
+        let applied: Vec<(&str, EnrichmentSource)> = processed
+            .applied_enrichment
+            .tags_added
+            .iter()
+            .map(|applied| (applied.tag.as_str(), applied.source))
+            .collect();
+        assert_eq!(
+            applied,
+            vec![
+                ("portal", EnrichmentSource::DefaultTags),
+                ("staging", EnrichmentSource::Environment),
+                ("test", EnrichmentSource::Environment),
+                ("country-us", EnrichmentSource::Geographic),
+                ("status-planned", EnrichmentSource::Status),
+            ]
+        );
+        assert_eq!(processed.applied_enrichment.custom_fields[0].field, "environment");
+        let site = fake.object("dcim/sites", processed.netbox_site.unwrap().id.unwrap()).unwrap();
+        assert_eq!(site["tags"], json!(["netgate", "order-portal", "portal", "staging", "test"]));
+
+        // Another tenant keeps the global defaults and no environment
+        let order = CreateSiteOrder { name: "Other Site".to_string(), ..create_test_order() };
+        let processed = service.process_site_order(order, "tenant2".to_string()).await.unwrap();
+        let site = fake.object("dcim/sites", processed.netbox_site.unwrap().id.unwrap()).unwrap();
+        assert_eq!(site["tags"], json!(["netgate", "order-portal", "netgate", "enriched"]));
+    }
+
+    #[tokio::test]
+    async fn test_site_order_address_is_normalized() {
+        let fake = FakeNetBox::start().await;
+        let (service, _) = create_reconciling_service(&fake);
+
+        let order = CreateSiteOrder { address: Some("123  test st, springfield, IL 62704, usa ".to_string()), ..create_test_order() };
+        let processed = service.process_site_order(order, "tenant1".to_string()).await.unwrap();
+        let site = fake.object("dcim/sites", processed.netbox_site.unwrap().id.unwrap()).unwrap();
+        assert_eq!(site["physical_address"], "123 Test Street, Springfield, IL 62704, USA");
+        assert!(!site["tags"].as_array().unwrap().contains(&json!(UNVERIFIED_ADDRESS_TAG)));
+
+        // Unverified addresses are tagged, not refused
+        let order = CreateSiteOrder {
+            name: "Other Site".to_string(),
+            address: Some("123 test st".to_string()),
+            ..create_test_order()
+        };
+        let processed = service.process_site_order(order, "tenant1".to_string()).await.unwrap();
+        let site = fake.object("dcim/sites", processed.netbox_site.unwrap().id.unwrap()).unwrap();
+        assert_eq!(site["physical_address"], "123 Test Street");
+        assert!(site["tags"].as_array().unwrap().contains(&json!(UNVERIFIED_ADDRESS_TAG)));
+
+        let service = service.with_validator(
+            OrderValidator::new().with_address_normalizer(AddressNormalizer::new().with_strict(true)),
+        );
+        let order = CreateSiteOrder {
+            name: "Third Site".to_string(),
+            address: Some("123 test st".to_string()),
+            ..create_test_order()
+        };
+        let result = service.process_site_order(order, "tenant1".to_string()).await;
+        assert!(matches!(result, Err(AppError::ValidationError(msg)) if msg == "Address could not be verified: No recognized country"));
+    }
+
+    #[tokio::test]
+    async fn test_site_order_with_unlisted_custom_field_is_rejected() {
+        let workflow_manager = Arc::new(WorkflowManager::new());
+        let service = OrderService::new(workflow_manager.clone(), create_test_netbox_client())
+            .with_validator(OrderValidator::new().with_allowed_custom_fields(["cost_center"]));
+        let mut order = create_test_order();
+        order.custom_fields.insert("owner".to_string(), json!("ops"));
+
+        let result = service.process_site_order(order, "tenant1".to_string()).await;
+
+        assert!(matches!(result, Err(AppError::ValidationError(msg)) if msg == "Custom fields not allowed: owner"));
+        assert!(workflow_manager.get_tenant_orders("tenant1").is_empty());
+    }
+
+    fn create_name_checking_service(fake: &FakeNetBox, skip_on_outage: bool) -> (OrderService, Arc<WorkflowManager>) {
+        let (service, workflow_manager) = create_reconciling_service(fake);
+        let service = service.with_site_name_check(SiteNameCheckConfig { enabled: true, skip_on_outage });
+        (service, workflow_manager)
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_site_name_is_rejected_before_workflow() {
+        let fake = FakeNetBox::start().await;
+        fake.seed_site(json!({"id": 5, "name": "Test Site", "status": "active"}));
+        let (service, workflow_manager) = create_name_checking_service(&fake, true);
+
+        let result = service.process_site_order(create_test_order(), "tenant1".to_string()).await;
+
+        assert!(matches!(result, Err(AppError::Conflict(msg)) if msg == "Site name 'Test Site' already exists"));
+        assert!(workflow_manager.get_tenant_orders("tenant1").is_empty());
+        assert!(fake.requests_to(Method::POST, "/api/dcim/sites/").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unique_site_name_passes_check() {
+        let fake = FakeNetBox::start().await;
+        // NetBox name filtering is case-insensitive; only an exact match is a duplicate
+        fake.seed_site(json!({"id": 5, "name": "TEST SITE", "status": "active"}));
+        let (service, _) = create_name_checking_service(&fake, true);
+
+        let processed = service.process_site_order(create_test_order(), "tenant1".to_string()).await.unwrap();
+
+        assert_eq!(processed.workflow_state, OrderState::Completed);
+        assert_eq!(fake.sites().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_site_name_check_skipped_on_outage() {
+        let fake = FakeNetBox::start().await;
+        fake.fail_next_reads(all_attempts());
+        let (service, _) = create_name_checking_service(&fake, true);
+
+        let processed = service.process_site_order(create_test_order(), "tenant1".to_string()).await.unwrap();
+
+        assert_eq!(processed.workflow_state, OrderState::Completed);
+        assert_eq!(fake.sites().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_site_name_check_can_fail_closed_on_outage() {
+        let fake = FakeNetBox::start().await;
+        fake.fail_next_reads(all_attempts());
+        let (service, workflow_manager) = create_name_checking_service(&fake, false);
+
+        let result = service.process_site_order(create_test_order(), "tenant1".to_string()).await;
+
+        assert!(matches!(result, Err(AppError::ServiceUnavailable(_))));
+        assert!(workflow_manager.get_tenant_orders("tenant1").is_empty());
+        assert!(fake.sites().is_empty());
+    }
+
+    fn create_schema_checking_service(fake: &FakeNetBox) -> (OrderService, Arc<WorkflowManager>) {
+        fake.seed(
+            "extras/custom-fields",
+            json!({"id": 1, "name": "cost_center", "type": {"value": "text", "label": "Text"}, "object_types": ["dcim.site"]}),
+        );
+        fake.seed(
+            "extras/custom-fields",
+            json!({"id": 2, "name": "tier", "type": {"value": "select", "label": "Selection"},
+                   "object_types": ["dcim.site"], "choice_set": {"id": 7, "name": "Tiers"}}),
+        );
+        fake.seed(
+            "extras/custom-field-choice-sets",
+            json!({"id": 7, "name": "Tiers", "base_choices": null, "extra_choices": [["gold", "Gold"]]}),
+        );
+        let (service, workflow_manager) = create_reconciling_service(fake);
+        let service = service
+            .with_validator(OrderValidator::new().with_allowed_custom_fields(["cost_center", "cost_centre", "tier"]));
+        (service, workflow_manager)
+    }
+
+    fn create_order_with_fields(fields: serde_json::Value) -> CreateSiteOrder {
+        let mut order = create_test_order();
+        order.custom_fields = serde_json::from_value(fields).unwrap();
+        order
     }
 
     #[tokio::test]
-    async fn test_order_service_creation() {
-        let workflow_manager = Arc::new(WorkflowManager::new());
-        let netbox_client = create_test_netbox_client();
-        let service = OrderService::new(workflow_manager, netbox_client);
-        
-        // Service should be created successfully
-        assert!(true); // Just verify it compiles and creates
+    async fn test_custom_fields_checked_against_cached_netbox_schema() {
+        let fake = FakeNetBox::start().await;
+        let (service, workflow_manager) = create_schema_checking_service(&fake);
+
+        let typo = service
+            .process_site_order(create_order_with_fields(json!({"cost_centre": "CC-1"})), "tenant1".to_string())
+            .await;
+        let bad_choice = service
+            .process_site_order(create_order_with_fields(json!({"tier": "bronze"})), "tenant1".to_string())
+            .await;
+        let processed = service
+            .process_site_order(
+                create_order_with_fields(json!({"cost_center": "CC-1", "tier": "gold"})),
+                "tenant1".to_string(),
+            )
+            .await
+            .unwrap();
+
+        assert!(matches!(typo, Err(AppError::ValidationError(msg))
+            if msg == "Custom fields not defined in NetBox for dcim.site: cost_centre"));
+        assert!(matches!(bad_choice, Err(AppError::ValidationError(msg))
+            if msg == "'bronze' is not a valid choice for custom field 'tier'; expected one of: gold"));
+        assert_eq!(processed.workflow_state, OrderState::Completed);
+        assert_eq!(workflow_manager.get_tenant_orders("tenant1").len(), 1);
+        assert_eq!(fake.requests_to(Method::GET, "/api/extras/custom-fields/").len(), 1);
+        assert_eq!(fake.sites().len(), 1);
     }
 
     #[tokio::test]
-    async fn test_get_order_status_not_found() {
-        let workflow_manager = Arc::new(WorkflowManager::new());
-        let netbox_client = create_test_netbox_client();
-        let service = OrderService::new(workflow_manager, netbox_client);
-        
-        let result = service.get_order_status("nonexistent", &"tenant1".to_string()).await;
-        assert!(result.is_err());
-        match result.unwrap_err() {
-            AppError::NotFound(_) => {}
-            _ => panic!("Expected NotFound error"),
+    async fn test_custom_field_schema_check_can_be_disabled() {
+        let fake = FakeNetBox::start().await;
+        let (service, _) = create_reconciling_service(&fake);
+        let service = service
+            .with_validator(OrderValidator::new().with_allowed_custom_fields(["cost_centre"]))
+            .with_custom_field_schema_check(CustomFieldSchemaCheckConfig { enabled: false, ..Default::default() });
+
+        let processed = service
+            .process_site_order(create_order_with_fields(json!({"cost_centre": "CC-1"})), "tenant1".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(processed.workflow_state, OrderState::Completed);
+        let schema_requests = fake
+            .requests()
+            .into_iter()
+            .filter(|request| request.path.starts_with("/api/extras/"))
+            .count();
+        assert_eq!(schema_requests, 0);
+    }
+
+    fn create_tag_checking_service(fake: &FakeNetBox, policy: MissingTagPolicy) -> (OrderService, Arc<WorkflowManager>) {
+        for name in ["netgate", "order-portal", "enriched"] {
+            fake.seed_tag(name);
+        }
+        let (service, workflow_manager) = create_reconciling_service(fake);
+        (service.with_missing_tag_policy(policy), workflow_manager)
+    }
+
+    fn create_tagged_order(name: &str) -> CreateSiteOrder {
+        CreateSiteOrder {
+            name: name.to_string(),
+            tags: vec!["Edge POP".to_string()],
+            ..create_test_order()
         }
     }
 
+    fn posted_bodies(fake: &FakeNetBox, url_path: &str) -> Vec<serde_json::Value> {
+        fake.requests_to(Method::POST, url_path)
+            .into_iter()
+            .filter_map(|request| request.body)
+            .collect()
+    }
+
     #[tokio::test]
-    async fn test_get_order_status_unauthorized() {
-        let workflow_manager = Arc::new(WorkflowManager::new());
-        let netbox_client = create_test_netbox_client();
-        let service = OrderService::new(workflow_manager.clone(), netbox_client);
-        
-        // Create workflow for tenant1
-        let order_id = workflow_manager.create_order("tenant1".to_string());
-        
-        // Try to access with tenant2
-        let result = service.get_order_status(&order_id, &"tenant2".to_string()).await;
-        assert!(result.is_err());
-        match result.unwrap_err() {
-            AppError::Unauthorized => {}
-            _ => panic!("Expected Unauthorized error"),
+    async fn test_missing_tags_are_created_once_before_the_site() {
+        let fake = FakeNetBox::start().await;
+        let (service, _) = create_tag_checking_service(&fake, MissingTagPolicy::Create);
+
+        for name in ["Edge 1", "Edge 2"] {
+            let processed = service.process_site_order(create_tagged_order(name), "tenant1".to_string()).await.unwrap();
+            assert_eq!(processed.workflow_state, OrderState::Completed);
         }
+
+        let created_tags = posted_bodies(&fake, "/api/extras/tags/");
+        assert_eq!(created_tags, vec![json!({"name": "edge-pop", "slug": "edge-pop", "color": "9e9e9e"})]);
+        assert_eq!(fake.requests_to(Method::GET, "/api/extras/tags/").len(), 1);
+        let sites = fake.sites();
+        assert_eq!(sites.len(), 2);
+        assert!(sites.iter().all(|site| site["tags"].as_array().unwrap().contains(&json!("edge-pop"))));
     }
 
     #[tokio::test]
-    async fn test_get_order_status_success() {
-        let workflow_manager = Arc::new(WorkflowManager::new());
-        let netbox_client = create_test_netbox_client();
-        let service = OrderService::new(workflow_manager.clone(), netbox_client);
-        
-        // Create workflow for tenant1
-        let order_id = workflow_manager.create_order("tenant1".to_string());
-        workflow_manager.update_order_state(&order_id, OrderState::Validated).unwrap();
-        
-        let result = service.get_order_status(&order_id, &"tenant1".to_string()).await;
-        assert!(result.is_ok());
-        let status = result.unwrap();
-        assert_eq!(status.order_id, order_id);
-        assert_eq!(status.state, OrderState::Validated);
+    async fn test_missing_tags_can_be_stripped() {
+        let fake = FakeNetBox::start().await;
+        let (service, _) = create_tag_checking_service(&fake, MissingTagPolicy::Strip);
+
+        let processed = service.process_site_order(create_tagged_order("Edge 1"), "tenant1".to_string()).await.unwrap();
+
+        assert_eq!(processed.workflow_state, OrderState::Completed);
+        assert!(posted_bodies(&fake, "/api/extras/tags/").is_empty());
+        let sites = fake.sites();
+        assert!(!sites[0]["tags"].as_array().unwrap().contains(&json!("edge-pop")));
+        assert!(sites[0]["tags"].as_array().unwrap().contains(&json!("netgate")));
     }
 
     #[tokio::test]
-    async fn test_process_site_order_validation_failure() {
-        let workflow_manager = Arc::new(WorkflowManager::new());
-        let netbox_client = create_test_netbox_client();
-        let service = OrderService::new(workflow_manager, netbox_client);
-        
-        // Create invalid order (empty name)
-        let invalid_order = CreateSiteOrder {
-            name: "".to_string(),
-            description: None,
-            address: None,
-        };
-        
-        let result = service.process_site_order(invalid_order, "tenant1".to_string()).await;
-        assert!(result.is_err());
-        match result.unwrap_err() {
-            AppError::ValidationError(_) => {}
-            _ => panic!("Expected ValidationError"),
+    async fn test_missing_tags_can_fail_the_order() {
+        let fake = FakeNetBox::start().await;
+        let (service, workflow_manager) = create_tag_checking_service(&fake, MissingTagPolicy::Fail);
+
+        let result = service.process_site_order(create_tagged_order("Edge 1"), "tenant1".to_string()).await;
+
+        assert!(matches!(result, Err(AppError::ValidationError(msg)) if msg == "Tags not defined in NetBox: edge-pop"));
+        let orders = workflow_manager.get_tenant_orders("tenant1");
+        assert_eq!(orders[0].state, OrderState::Failed);
+        assert!(fake.sites().is_empty());
+    }
+
+    fn create_tenant_checking_service(fake: &FakeNetBox) -> (OrderService, Arc<WorkflowManager>) {
+        use crate::security::tenant::TenantMappingService;
+
+        let (service, workflow_manager) = create_reconciling_service(fake);
+        let mappings = TenantMappingService::new();
+        mappings.register_mapping("tenant1".to_string(), 10);
+        mappings.register_mapping("tenant2".to_string(), 20);
+        let service = service.with_access_control(Arc::new(TenantAccessControl::new(mappings)));
+        (service, workflow_manager)
+    }
+
+    /// Seed site 5 of NetBox tenant 10 with the given devices
+    fn seed_decommission_site(fake: &FakeNetBox, device_ids: &[i32]) {
+        fake.seed_site(json!({"id": 5, "name": "Old Site", "slug": "old-site", "tenant": 10}));
+        fake.seed_site(json!({"id": 6, "name": "Next Door", "slug": "next-door", "tenant": 10}));
+        fake.seed_device(json!({"id": 9, "name": "dev-9", "site": 6, "tenant": 10}));
+        for id in device_ids {
+            fake.seed_device(json!({"id": id, "name": format!("dev-{}", id), "site": 5, "tenant": 10}));
+        }
+    }
+
+    fn decommission_order(force: bool) -> DecommissionSiteOrder {
+        DecommissionSiteOrder {
+            site_id: 5,
+            force,
+            reason: Some("Lease ended".to_string()),
         }
     }
 
+    fn deleted_paths(fake: &FakeNetBox) -> Vec<String> {
+        fake.requests()
+            .into_iter()
+            .filter(|request| request.method == Method::DELETE)
+            .map(|request| request.path)
+            .collect()
+    }
+
     #[tokio::test]
-    async fn test_process_site_order_workflow_states() {
-        let workflow_manager = Arc::new(WorkflowManager::new());
-        let netbox_client = create_test_netbox_client();
-        let service = OrderService::new(workflow_manager.clone(), netbox_client);
-        
-        let order = create_test_order();
-        
-        // Process will fail at NetBox creation (no mock server), but we can verify workflow states
-        let result = service.process_site_order(order, "tenant1".to_string()).await;
-        
-        // Should fail at NetBox creation, but workflow should be created
-        assert!(result.is_err());
-        
-        // Verify workflow was created and transitioned through states
-        // The order_id is generated in process_site_order, so we need to check all orders
-        let orders = workflow_manager.get_tenant_orders("tenant1");
-        assert!(!orders.is_empty());
-        
-        // The last order should be in Failed state
-        let last_order = orders.last().unwrap();
-        assert_eq!(last_order.state, OrderState::Failed);
+    async fn test_decommission_refused_while_site_has_devices() {
+        let fake = FakeNetBox::start().await;
+        seed_decommission_site(&fake, &[7, 8]);
+        let (service, workflow_manager) = create_tenant_checking_service(&fake);
+
+        let result = service.process_decommission_site_order(decommission_order(false), "tenant1".to_string()).await;
+
+        match result {
+            Err(AppError::Conflict(msg)) => assert!(msg.contains("2 device(s)")),
+            other => panic!("Expected Conflict, got {:?}", other),
+        }
+        assert!(workflow_manager.get_tenant_orders("tenant1").is_empty());
+        assert!(deleted_paths(&fake).is_empty());
     }
 
     #[tokio::test]
-    async fn test_order_service_full_flow_with_mock() {
-        use crate::netbox::client::NetBoxClient;
-        use crate::netbox::resilient_client::ResilientNetBoxClient;
-        use serde_json::json;
-        use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
-        
-        let mock_server = MockServer::start().await;
-        let config = Config {
-            port: 8080,
-            netbox_url: mock_server.uri(),
-            netbox_token: "test-token".to_string(),
-        };
-        let netbox_client = Arc::new(NetBoxClient::new(config).unwrap());
-        let resilient_client = Arc::new(ResilientNetBoxClient::new(netbox_client));
-        
-        let workflow_manager = Arc::new(WorkflowManager::new());
-        let service = OrderService::new(workflow_manager.clone(), resilient_client);
-        
-        // Mock NetBox API response
-        let site_response = json!({
-            "id": 123,
-            "name": "Test Site",
-            "description": "Test Description",
-            "status": "active",
-            "tags": ["netgate", "enriched"]
-        });
-        
-        Mock::given(method("POST"))
-            .and(path("/api/dcim/sites/"))
-            .respond_with(ResponseTemplate::new(201).set_body_json(&site_response))
-            .mount(&mock_server)
-            .await;
-        
-        let order = create_test_order();
-        let result = service.process_site_order(order, "tenant1".to_string()).await;
-        
-        assert!(result.is_ok());
-        let processed = result.unwrap();
-        assert_eq!(processed.netbox_site.id, Some(123));
-        assert_eq!(processed.netbox_site.name, "Test Site");
-        assert_eq!(processed.workflow_state, OrderState::Completed);
-        
-        // Verify workflow state
-        let workflow = workflow_manager.get_order(&processed.order_id).unwrap();
+    async fn test_forced_decommission_deletes_devices_then_site() {
+        let fake = FakeNetBox::start().await;
+        seed_decommission_site(&fake, &[7, 8]);
+        let (service, workflow_manager) = create_tenant_checking_service(&fake);
+
+        let result = service
+            .process_decommission_site_order(decommission_order(true), "tenant1".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(result.workflow_state, OrderState::Completed);
+        assert_eq!(result.deleted_devices, vec![7, 8]);
+        assert_eq!(
+            deleted_paths(&fake),
+            vec!["/api/dcim/devices/7/", "/api/dcim/devices/8/", "/api/dcim/sites/5/"]
+        );
+        assert_eq!(fake.sites().len(), 1);
+        assert_eq!(fake.devices().len(), 1);
+        let workflow = workflow_manager.get_order(&result.order_id).unwrap();
         assert_eq!(workflow.state, OrderState::Completed);
-        assert_eq!(workflow.netbox_site_id, Some(123));
+        let deleted: Vec<_> = workflow.deleted_resources.iter().map(|r| (r.kind, r.id)).collect();
+        assert_eq!(
+            deleted,
+            vec![(ResourceKind::Device, 7), (ResourceKind::Device, 8), (ResourceKind::Site, 5)]
+        );
+        assert!(workflow.steps.iter().all(|step| step.status == StepStatus::Completed));
+        let states: Vec<_> = workflow.history.iter().map(|entry| entry.to).collect();
+        assert_eq!(states, vec![OrderState::Validated, OrderState::Processing, OrderState::Completed]);
     }
 
     #[tokio::test]
-    async fn test_order_service_netbox_failure_handling() {
-        use crate::netbox::client::NetBoxClient;
-        use crate::netbox::resilient_client::ResilientNetBoxClient;
-        use serde_json::json;
-        use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
-        
-        let mock_server = MockServer::start().await;
-        let config = Config {
-            port: 8080,
-            netbox_url: mock_server.uri(),
-            netbox_token: "test-token".to_string(),
+    async fn test_decommission_of_another_tenants_site_is_unauthorized() {
+        let fake = FakeNetBox::start().await;
+        seed_decommission_site(&fake, &[]);
+        let (service, workflow_manager) = create_tenant_checking_service(&fake);
+
+        let result = service.process_decommission_site_order(decommission_order(true), "tenant2".to_string()).await;
+
+        assert!(matches!(result, Err(AppError::Unauthorized)));
+        assert!(workflow_manager.get_tenant_orders("tenant2").is_empty());
+        assert!(deleted_paths(&fake).is_empty());
+    }
+
+    /// Seed site 5 ("old-site") of NetBox tenant 10, last updated at a known time
+    fn seed_update_site(fake: &FakeNetBox) {
+        fake.seed_site(json!({"id": 5, "name": "Old Site", "slug": "old-site", "tenant": 10,
+                              "status": "active", "tags": ["netgate", "edge"],
+                              "last_updated": "2024-05-01T10:00:00.123456Z"}));
+    }
+
+    fn patched_bodies(fake: &FakeNetBox) -> Vec<serde_json::Value> {
+        fake.requests_to(Method::PATCH, "/api/dcim/sites/5/")
+            .into_iter()
+            .filter_map(|request| request.body)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_site_update_sends_only_changed_fields() {
+        let fake = FakeNetBox::start().await;
+        seed_update_site(&fake);
+        let (service, workflow_manager) = create_tenant_checking_service(&fake);
+
+        let order = UpdateSiteOrder {
+            site_slug: Some("old-site".to_string()),
+            description: Some("Moved to hall B".to_string()),
+            address: Some("1 New St".to_string()),
+            ..Default::default()
         };
-        let netbox_client = Arc::new(NetBoxClient::new(config).unwrap());
-        let resilient_client = Arc::new(ResilientNetBoxClient::new(netbox_client));
-        
-        let workflow_manager = Arc::new(WorkflowManager::new());
-        let service = OrderService::new(workflow_manager.clone(), resilient_client);
-        
-        // Mock NetBox API error
-        Mock::given(method("POST"))
-            .and(path("/api/dcim/sites/"))
-            .respond_with(ResponseTemplate::new(500).set_body_json(json!({
-                "detail": "Internal server error"
-            })))
-            .mount(&mock_server)
-            .await;
-        
-        let order = create_test_order();
-        let result = service.process_site_order(order, "tenant1".to_string()).await;
-        
-        assert!(result.is_err());
-        
-        // Verify workflow is in Failed state
-        let orders = workflow_manager.get_tenant_orders("tenant1");
-        assert!(!orders.is_empty());
-        let failed_order = orders.last().unwrap();
-        assert_eq!(failed_order.state, OrderState::Failed);
-        assert!(failed_order.error_message.is_some());
+        let result = service.process_site_update_order(order, "tenant1".to_string()).await.unwrap();
+
+        assert_eq!(result.workflow_state, OrderState::Completed);
+        assert_eq!(result.netbox_site.unwrap().description.as_deref(), Some("Moved to hall B"));
+        assert_eq!(
+            patched_bodies(&fake),
+            vec![json!({
+                "description": "Moved to hall B",
+                "physical_address": "1 New Street",
+                "shipping_address": "1 New Street"
+            })]
+        );
+        let workflow = workflow_manager.get_order(&result.order_id).unwrap();
+        assert_eq!(workflow.netbox_site_id, Some(5));
+        assert_eq!(workflow.site_slug.as_deref(), Some("old-site"));
+    }
+
+    #[tokio::test]
+    async fn test_site_update_round_tripping_the_site_sends_nothing() {
+        let fake = FakeNetBox::start().await;
+        seed_update_site(&fake);
+        let (service, _) = create_tenant_checking_service(&fake);
+
+        let order = UpdateSiteOrder {
+            site_id: Some(5),
+            name: Some("Old Site".to_string()),
+            status: Some("active".to_string()),
+            tenant: Some(10),
+            ..Default::default()
+        };
+        let result = service.process_site_update_order(order, "tenant1".to_string()).await.unwrap();
+        assert_eq!(result.workflow_state, OrderState::Completed);
+        assert!(patched_bodies(&fake).is_empty());
+
+        let order = UpdateSiteOrder {
+            site_id: Some(5),
+            name: Some("Old Site".to_string()),
+            description: Some("Hall C".to_string()),
+            ..Default::default()
+        };
+        service.process_site_update_order(order, "tenant1".to_string()).await.unwrap();
+        assert_eq!(patched_bodies(&fake), vec![json!({"description": "Hall C"})]);
+    }
+
+    #[tokio::test]
+    async fn test_site_update_status_change_rederives_tags() {
+        let fake = FakeNetBox::start().await;
+        seed_update_site(&fake);
+        let (service, _) = create_tenant_checking_service(&fake);
+
+        let order = UpdateSiteOrder {
+            site_id: Some(5),
+            status: Some("retired".to_string()),
+            ..Default::default()
+        };
+        let result = service.process_site_update_order(order, "tenant1".to_string()).await.unwrap();
+
+        assert_eq!(result.workflow_state, OrderState::Completed);
+        let site = fake.object("dcim/sites", 5).unwrap();
+        assert_eq!(site["status"], "retired");
+        assert_eq!(site["tags"], json!(["edge", "enriched", "netgate", "status-retired"]));
+        let patched = patched_bodies(&fake);
+        let fields: Vec<_> = patched[0].as_object().unwrap().keys().collect();
+        assert_eq!(fields, vec!["status", "tags"]);
+    }
+
+    #[tokio::test]
+    async fn test_site_update_cannot_move_site_to_another_tenant() {
+        let fake = FakeNetBox::start().await;
+        seed_update_site(&fake);
+        let (service, workflow_manager) = create_tenant_checking_service(&fake);
+
+        let order = UpdateSiteOrder {
+            site_id: Some(5),
+            tenant: Some(20),
+            ..Default::default()
+        };
+        let result = service.process_site_update_order(order, "tenant1".to_string()).await;
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+
+        // Nor can another tenant update it
+        let order = UpdateSiteOrder {
+            site_id: Some(5),
+            description: Some("Mine now".to_string()),
+            ..Default::default()
+        };
+        let result = service.process_site_update_order(order, "tenant2".to_string()).await;
+        assert!(matches!(result, Err(AppError::Unauthorized)));
+        assert!(workflow_manager.get_tenant_orders("tenant1").is_empty());
+        assert!(patched_bodies(&fake).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_site_update_refused_when_site_changed_since_read() {
+        let fake = FakeNetBox::start().await;
+        seed_update_site(&fake);
+        let (service, workflow_manager) = create_tenant_checking_service(&fake);
+
+        let order = UpdateSiteOrder {
+            site_id: Some(5),
+            description: Some("Hall B".to_string()),
+            last_updated: Some("2024-04-30T08:00:00Z".to_string()),
+            ..Default::default()
+        };
+        let result = service.process_site_update_order(order, "tenant1".to_string()).await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+        assert!(workflow_manager.get_tenant_orders("tenant1").is_empty());
+        assert!(patched_bodies(&fake).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_site_update_with_current_version_or_force_proceeds() {
+        let fake = FakeNetBox::start().await;
+        seed_update_site(&fake);
+        let (service, _) = create_tenant_checking_service(&fake);
+
+        // Same instant in another offset still matches
+        let current = UpdateSiteOrder {
+            site_id: Some(5),
+            description: Some("Hall B".to_string()),
+            last_updated: Some("2024-05-01T12:00:00.123456+02:00".to_string()),
+            ..Default::default()
+        };
+        let result = service.process_site_update_order(current, "tenant1".to_string()).await.unwrap();
+        assert_eq!(result.workflow_state, OrderState::Completed);
+
+        let forced = UpdateSiteOrder {
+            site_id: Some(5),
+            description: Some("Hall C".to_string()),
+            last_updated: Some("2024-04-30T08:00:00Z".to_string()),
+            force: true,
+            ..Default::default()
+        };
+        let result = service.process_site_update_order(forced, "tenant1".to_string()).await.unwrap();
+        assert_eq!(result.workflow_state, OrderState::Completed);
+        assert_eq!(patched_bodies(&fake).len(), 2);
+        assert_eq!(fake.object("dcim/sites", 5).unwrap()["description"], "Hall C");
+    }
+
+    #[tokio::test]
+    async fn test_site_update_refuses_disallowed_status_transition() {
+        use crate::business::SiteStatusTransitions;
+
+        let fake = FakeNetBox::start().await;
+        seed_update_site(&fake);
+        let (service, _) = create_tenant_checking_service(&fake);
+        let service = service.with_validator(OrderValidator::new().with_status_transitions(
+            SiteStatusTransitions::parse("planned>active,active>planned"),
+        ));
+
+        // The seeded site is active
+        let order = UpdateSiteOrder {
+            site_id: Some(5),
+            status: Some("retired".to_string()),
+            ..Default::default()
+        };
+        let result = service.process_site_update_order(order, "tenant1".to_string()).await;
+        match result {
+            Err(AppError::ValidationError(msg)) => assert_eq!(msg, "Site status can't change from active to retired"),
+            other => panic!("Expected a validation error, got {:?}", other.map(|r| r.order_id)),
+        }
+        assert!(patched_bodies(&fake).is_empty());
+    }
+
+    fn short_deadlines() -> ProcessingDeadlineConfig {
+        ProcessingDeadlineConfig {
+            default: Duration::from_secs(5),
+            per_kind: ProcessingDeadlineConfig::parse_per_kind("pop=60, bogus=1, decommission=zero"),
+            ..Default::default()
+        }
     }
-}
 
+    #[test]
+    fn test_processing_deadline_per_order_type() {
+        let deadlines = short_deadlines();
+        assert_eq!(deadlines.for_kind(OrderKind::Pop), Duration::from_secs(60));
+        assert_eq!(deadlines.for_kind(OrderKind::Site), Duration::from_secs(5));
+        assert_eq!(deadlines.for_kind(OrderKind::Decommission), Duration::from_secs(5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_order_hanging_in_netbox_fails_at_its_deadline() {
+        let fake = FakeNetBox::start().await;
+        fake.hang_writes();
+        let (service, workflow_manager) = create_reconciling_service(&fake);
+        let service = service.with_processing_deadlines(short_deadlines());
+        let mut events = workflow_manager.subscribe();
+
+        let result = service.process_site_order(create_test_order(), "tenant1".to_string()).await;
+
+        match result {
+            Err(AppError::ServiceUnavailable(msg)) => assert!(msg.contains("Processing timeout after 5s")),
+            other => panic!("Expected a processing timeout, got {:?}", other.map(|r| r.order_id)),
+        }
+        let failed = workflow_manager.get_orders_by_state(OrderState::Failed);
+        assert_eq!(failed.len(), 1);
+        assert!(failed[0].error_message.as_deref().unwrap().starts_with(PROCESSING_TIMEOUT_ERROR));
+        let mut last = None;
+        while let Ok(event) = events.try_recv() {
+            last = Some(event);
+        }
+        let last = last.unwrap();
+        assert_eq!((last.from, last.to), (OrderState::Processing, OrderState::Failed));
+    }
+
+    #[tokio::test]
+    async fn test_watchdog_fails_overdue_processing_orders() {
+        use crate::clock::ManualClock;
+
+        let fake = FakeNetBox::start().await;
+        let clock = Arc::new(ManualClock::new());
+        let workflow_manager = Arc::new(WorkflowManager::new().with_clock(clock.clone()));
+        let service = OrderService::new(workflow_manager.clone(), fake.resilient_client())
+            .with_processing_deadlines(short_deadlines());
+        // Left over from before a restart, for reconciliation
+        let interrupted = create_processing_order(&workflow_manager, "interrupted");
+        workflow_manager.mark_interrupted_processing_orders();
+        let overdue = create_processing_order(&workflow_manager, "overdue");
+        let pop = create_processing_order(&workflow_manager, "pop");
+        workflow_manager.set_processing_deadline(&pop, Duration::from_secs(60)).unwrap();
+        let also_overdue = create_processing_order(&workflow_manager, "also-overdue");
+        let mut events = workflow_manager.subscribe();
+
+        assert_eq!(service.fail_overdue_orders().await, 0);
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(service.fail_overdue_orders().await, 2);
+
+        for order_id in [&overdue, &also_overdue] {
+            let workflow = workflow_manager.get_order(order_id).unwrap();
+            assert_eq!(workflow.state, OrderState::Failed);
+            assert!(workflow.error_message.unwrap().starts_with(PROCESSING_TIMEOUT_ERROR));
+        }
+        for order_id in [&pop, &interrupted] {
+            assert_eq!(workflow_manager.get_order(order_id).unwrap().state, OrderState::Processing);
+        }
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.to, OrderState::Failed);
+        assert!(event.context.error_message.unwrap().starts_with(PROCESSING_TIMEOUT_ERROR));
+    }
+}
@@ -0,0 +1,200 @@
+use serde_json::Value;
+
+/// One place where a payload doesn't match its order type's schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// Where in the payload, e.g. `tags[1]`; `payload` for the payload itself
+    pub path: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Check a payload against the JSON schema of its order type
+///
+/// Covers the keywords processor schemas use: `type`, `enum`, `required`,
+/// `properties`, `additionalProperties`, `items`, `minimum`, `maximum`,
+/// `minLength` and `maxLength`; others are ignored. A `null` optional
+/// property counts as absent, as it does when the payload is parsed.
+pub fn validate_payload(schema: &Value, payload: &Value) -> Vec<SchemaViolation> {
+    let mut violations = Vec::new();
+    check(schema, payload, "payload", &mut violations);
+    violations
+}
+
+fn check(schema: &Value, value: &Value, path: &str, violations: &mut Vec<SchemaViolation>) {
+    let mut violation = |message: String| {
+        violations.push(SchemaViolation {
+            path: path.to_string(),
+            message,
+        })
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|name| has_type(value, name)) {
+            violation(format!("must be {}", types.iter().map(|name| describe(name)).collect::<Vec<_>>().join(" or ")));
+            return;
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+            violation(format!("must be one of {}", allowed.join(", ")));
+        }
+    }
+
+    match value {
+        Value::String(text) => {
+            let length = text.chars().count() as u64;
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64).filter(|max| length > *max) {
+                violation(format!("must be at most {} characters", max));
+            }
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64).filter(|min| length < *min) {
+                violation(format!("must be at least {} characters", min));
+            }
+        }
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or_default();
+            if let Some(min) = schema.get("minimum").and_then(Value::as_f64).filter(|min| number < *min) {
+                violation(format!("must be at least {}", min));
+            }
+            if let Some(max) = schema.get("maximum").and_then(Value::as_f64).filter(|max| number > *max) {
+                violation(format!("must be at most {}", max));
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    check(item_schema, item, &format!("{}[{}]", path, index), violations);
+                }
+            }
+        }
+        Value::Object(fields) => check_object(schema, fields, path, violations),
+        _ => {}
+    }
+}
+
+fn check_object(
+    schema: &Value,
+    fields: &serde_json::Map<String, Value>,
+    path: &str,
+    violations: &mut Vec<SchemaViolation>,
+) {
+    let field_path = |name: &str| match path {
+        "payload" => name.to_string(),
+        _ => format!("{}.{}", path, name),
+    };
+
+    for required in schema.get("required").and_then(Value::as_array).into_iter().flatten() {
+        let Some(name) = required.as_str() else { continue };
+        if fields.get(name).is_none_or(Value::is_null) {
+            violations.push(SchemaViolation {
+                path: field_path(name),
+                message: "is required".to_string(),
+            });
+        }
+    }
+
+    let properties = schema.get("properties").and_then(Value::as_object);
+    for (name, value) in fields {
+        if value.is_null() {
+            continue;
+        }
+        match properties.and_then(|properties| properties.get(name)) {
+            Some(property) => check(property, value, &field_path(name), violations),
+            None => match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => violations.push(SchemaViolation {
+                    path: field_path(name),
+                    message: "is not a known field".to_string(),
+                }),
+                Some(additional @ Value::Object(_)) => check(additional, value, &field_path(name), violations),
+                _ => {}
+            },
+        }
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn describe(name: &str) -> &str {
+    match name {
+        "object" => "an object",
+        "array" => "an array",
+        "string" => "a string",
+        "integer" => "an integer",
+        "number" => "a number",
+        "boolean" => "a boolean",
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_violations_name_the_field_path() {
+        let schema = json!({
+            "type": "object",
+            "required": ["name", "role"],
+            "properties": {
+                "name": { "type": "string", "maxLength": 5 },
+                "role": { "type": "integer", "minimum": 1 },
+                "description": { "type": "string" },
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "location": {
+                    "type": "object",
+                    "additionalProperties": false,
+                    "properties": { "rack": { "type": "integer" } }
+                }
+            }
+        });
+
+        let violations = validate_payload(
+            &schema,
+            &json!({
+                "name": "berlin-dc1",
+                "description": null,
+                "tags": ["core", 7],
+                "location": { "rack": "A1", "row": 3 }
+            }),
+        );
+
+        let mut messages: Vec<String> = violations.iter().map(ToString::to_string).collect();
+        messages.sort();
+        assert_eq!(
+            messages,
+            vec![
+                "location.rack: must be an integer",
+                "location.row: is not a known field",
+                "name: must be at most 5 characters",
+                "role: is required",
+                "tags[1]: must be a string",
+            ]
+        );
+        assert!(validate_payload(&schema, &json!({"name": "ber", "role": 2})).is_empty());
+        assert_eq!(validate_payload(&schema, &json!([]))[0].to_string(), "payload: must be an object");
+    }
+}
@@ -1,10 +1,18 @@
 use crate::business::enrichment::EnrichmentData;
+use crate::business::vlan::VidRange;
+use crate::business::workflow::CreatedResource;
+use crate::domain::{DeviceInterfaceDefinition, DeviceUplink};
 use crate::error::AppError;
-use crate::netbox::models::{CreateSiteRequest, NetBoxSite};
+use crate::netbox::models::{
+    AllocateIpRequest, CreateDeviceRequest, CreatePrefixRequest, CreateSiteRequest, CreateVirtualMachineRequest,
+    CreateVlanRequest, NetBoxDevice, NetBoxIpAddress, NetBoxPrefix, NetBoxSite, NetBoxVirtualMachine, NetBoxVlan,
+};
 use crate::netbox::ResilientNetBoxClient;
+use crate::security::TenantId;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use parking_lot::RwLock;
 use std::sync::Arc;
 use tracing::debug;
 
@@ -15,14 +23,25 @@ pub type OrderType = String;
 #[derive(Debug, Clone)]
 pub enum OrderPayload {
     Site(crate::domain::CreateSiteOrder),
-    // Future: Device(crate::domain::CreateDeviceOrder),
-    // Future: Network(crate::domain::CreateNetworkOrder),
+    Device(crate::domain::CreateDeviceOrder),
+    Network(crate::domain::CreateNetworkOrder),
+    Vlan(crate::domain::CreateVlanOrder),
+    IpAddress(crate::domain::AllocateIpOrder),
+    VirtualMachine(crate::domain::CreateVmOrder),
+    /// Raw JSON payload for order types registered at runtime
+    Custom { order_type: OrderType, data: serde_json::Value },
 }
 
 impl OrderPayload {
-    pub fn order_type(&self) -> &'static str {
+    pub fn order_type(&self) -> &str {
         match self {
             OrderPayload::Site(_) => "site",
+            OrderPayload::Device(_) => "device",
+            OrderPayload::Network(_) => "network",
+            OrderPayload::Vlan(_) => "vlan",
+            OrderPayload::IpAddress(_) => "ip_address",
+            OrderPayload::VirtualMachine(_) => "virtual_machine",
+            OrderPayload::Custom { order_type, .. } => order_type,
         }
     }
 }
@@ -31,13 +50,47 @@ impl OrderPayload {
 #[derive(Debug, Clone)]
 pub enum NetBoxResourceRequest {
     Site(CreateSiteRequest),
-    // Future: Device(CreateDeviceRequest),
+    /// Device request; a site given by slug is resolved into `request.site`, and a
+    /// device type or role given by name into its id, when created
+    ///
+    /// The interfaces and uplinks are created once the device exists.
+    Device {
+        request: CreateDeviceRequest,
+        site_slug: Option<String>,
+        device_type_name: Option<String>,
+        role_name: Option<String>,
+        interfaces: Vec<DeviceInterfaceDefinition>,
+        uplinks: Vec<DeviceUplink>,
+    },
+    Prefix(CreatePrefixRequest),
+    /// VLAN request; with `auto_assign`, `request.vid` is set to the lowest VID
+    /// in those ranges that is free at the site when created
+    Vlan {
+        request: CreateVlanRequest,
+        auto_assign: Option<Vec<VidRange>>,
+    },
+    /// Allocation from a prefix; with `request.tenant` set, the prefix must
+    /// belong to that NetBox tenant, and an interface given by device and name
+    /// is resolved into the assignment when created
+    IpAddress {
+        prefix_id: i32,
+        request: AllocateIpRequest,
+        device_id: Option<i32>,
+        interface: Option<String>,
+    },
+    /// VM request; `request.site` is taken from its cluster when created
+    VirtualMachine(CreateVirtualMachineRequest),
 }
 
 impl NetBoxResourceRequest {
     pub fn resource_type(&self) -> &str {
         match self {
             NetBoxResourceRequest::Site(_) => "site",
+            NetBoxResourceRequest::Device { .. } => "device",
+            NetBoxResourceRequest::Prefix(_) => "prefix",
+            NetBoxResourceRequest::Vlan { .. } => "vlan",
+            NetBoxResourceRequest::IpAddress { .. } => "ip_address",
+            NetBoxResourceRequest::VirtualMachine(_) => "virtual_machine",
         }
     }
 }
@@ -46,19 +99,33 @@ impl NetBoxResourceRequest {
 #[derive(Debug, Clone)]
 pub enum NetBoxResource {
     Site(NetBoxSite),
-    // Future: Device(NetBoxDevice),
+    Device(NetBoxDevice),
+    Prefix(NetBoxPrefix),
+    Vlan(NetBoxVlan),
+    IpAddress(NetBoxIpAddress),
+    VirtualMachine(NetBoxVirtualMachine),
 }
 
 impl NetBoxResource {
     pub fn resource_id(&self) -> Option<i32> {
         match self {
             NetBoxResource::Site(site) => site.id,
+            NetBoxResource::Device(device) => device.id,
+            NetBoxResource::Prefix(prefix) => prefix.id,
+            NetBoxResource::Vlan(vlan) => vlan.id,
+            NetBoxResource::IpAddress(ip) => ip.id,
+            NetBoxResource::VirtualMachine(vm) => vm.id,
         }
     }
 
     pub fn resource_type(&self) -> &str {
         match self {
             NetBoxResource::Site(_) => "site",
+            NetBoxResource::Device(_) => "device",
+            NetBoxResource::Prefix(_) => "prefix",
+            NetBoxResource::Vlan(_) => "vlan",
+            NetBoxResource::IpAddress(_) => "ip_address",
+            NetBoxResource::VirtualMachine(_) => "virtual_machine",
         }
     }
 }
@@ -69,9 +136,29 @@ pub trait OrderProcessor: Send + Sync {
     /// Get the order type this processor handles
     fn order_type(&self) -> &'static str;
 
+    /// JSON schema of the payload accepted by `POST /orders/{type}`
+    fn payload_schema(&self) -> serde_json::Value;
+
+    /// Parse a JSON payload into an order
+    ///
+    /// The default keeps the raw JSON as a custom payload for the processor to interpret.
+    fn parse_payload(&self, data: serde_json::Value) -> Result<OrderPayload, AppError> {
+        Ok(OrderPayload::Custom {
+            order_type: self.order_type().to_string(),
+            data,
+        })
+    }
+
     /// Validate the order
     fn validate(&self, order: &OrderPayload) -> Result<(), AppError>;
 
+    /// Validate the order for the tenant placing it
+    ///
+    /// The default ignores the tenant and calls [`Self::validate`].
+    fn validate_for_tenant(&self, order: &OrderPayload, _tenant_id: &TenantId) -> Result<(), AppError> {
+        self.validate(order)
+    }
+
     /// Transform the order to a NetBox resource request
     fn transform(
         &self,
@@ -79,6 +166,17 @@ pub trait OrderProcessor: Send + Sync {
         tenant_id: Option<i32>,
     ) -> Result<NetBoxResourceRequest, AppError>;
 
+    /// Transform the order for the tenant placing it
+    ///
+    /// The default ignores the tenant and calls [`Self::transform`] without a NetBox tenant.
+    fn transform_for_tenant(
+        &self,
+        order: OrderPayload,
+        _tenant_id: &TenantId,
+    ) -> Result<NetBoxResourceRequest, AppError> {
+        self.transform(order, None)
+    }
+
     /// Enrich the NetBox resource request
     fn enrich_request(
         &self,
@@ -93,6 +191,20 @@ pub trait OrderProcessor: Send + Sync {
         request: NetBoxResourceRequest,
     ) -> Result<NetBoxResource, AppError>;
 
+    /// Create the resource in NetBox together with the resources that hang off
+    /// it, pushing each onto `created` as soon as NetBox returns it
+    ///
+    /// When this fails, whatever is in `created` is rolled back. The default
+    /// creates the single resource with [`Self::create_resource`].
+    async fn create_resources(
+        &self,
+        client: &Arc<ResilientNetBoxClient>,
+        request: NetBoxResourceRequest,
+        _created: &mut Vec<CreatedResource>,
+    ) -> Result<NetBoxResource, AppError> {
+        self.create_resource(client, request).await
+    }
+
     /// Enrich the created NetBox resource
     fn enrich_resource(
         &self,
@@ -101,9 +213,18 @@ pub trait OrderProcessor: Send + Sync {
     ) -> NetBoxResource;
 }
 
+/// Registered order type with the schema of its payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisteredOrderType {
+    pub order_type: OrderType,
+    pub payload_schema: serde_json::Value,
+}
+
 /// Order type registry for managing order processors
+///
+/// Processors can be registered and removed at runtime while orders are being processed.
 pub struct OrderTypeRegistry {
-    processors: HashMap<String, Arc<dyn OrderProcessor>>,
+    processors: RwLock<HashMap<String, Arc<dyn OrderProcessor>>>,
     default_order_type: String,
 }
 
@@ -111,21 +232,34 @@ impl OrderTypeRegistry {
     /// Create a new registry
     pub fn new(default_order_type: OrderType) -> Self {
         Self {
-            processors: HashMap::new(),
+            processors: RwLock::new(HashMap::new()),
             default_order_type,
         }
     }
 
-    /// Register an order processor
-    pub fn register(&mut self, processor: Arc<dyn OrderProcessor>) {
+    /// Register an order processor, replacing any processor for the same type
+    pub fn register(&self, processor: Arc<dyn OrderProcessor>) {
         let order_type = processor.order_type().to_string();
         debug!("Registering order processor for type: {}", order_type);
-        self.processors.insert(order_type, processor);
+        self.processors.write().insert(order_type, processor);
+    }
+
+    /// Register an owned order processor
+    pub fn register_processor(&self, processor: Box<dyn OrderProcessor>) {
+        self.register(Arc::from(processor));
+    }
+
+    /// Remove the processor for an order type, returning whether one was registered
+    ///
+    /// Orders already being processed keep their processor until they finish.
+    pub fn unregister(&self, order_type: &str) -> bool {
+        debug!("Unregistering order processor for type: {}", order_type);
+        self.processors.write().remove(order_type).is_some()
     }
 
     /// Get a processor for an order type
     pub fn get_processor(&self, order_type: &str) -> Option<Arc<dyn OrderProcessor>> {
-        self.processors.get(order_type).cloned()
+        self.processors.read().get(order_type).cloned()
     }
 
     /// Get the default order type
@@ -135,12 +269,26 @@ impl OrderTypeRegistry {
 
     /// Get all registered order types
     pub fn registered_types(&self) -> Vec<String> {
-        self.processors.keys().cloned().collect()
+        self.processors.read().keys().cloned().collect()
+    }
+
+    /// Get all registered order types with their payload schemas, sorted by type
+    pub fn list_registered_types(&self) -> Vec<RegisteredOrderType> {
+        let processors = self.processors.read();
+        let mut types: Vec<RegisteredOrderType> = processors
+            .iter()
+            .map(|(order_type, processor)| RegisteredOrderType {
+                order_type: order_type.clone(),
+                payload_schema: processor.payload_schema(),
+            })
+            .collect();
+        types.sort_by(|a, b| a.order_type.cmp(&b.order_type));
+        types
     }
 
     /// Check if an order type is registered
     pub fn is_registered(&self, order_type: &str) -> bool {
-        self.processors.contains_key(order_type)
+        self.processors.read().contains_key(order_type)
     }
 }
 
@@ -203,7 +351,7 @@ mod tests {
 
     #[test]
     fn test_order_type_registry_register() {
-        let mut registry = OrderTypeRegistry::new("site".to_string());
+        let registry = OrderTypeRegistry::new("site".to_string());
         let processor = Arc::new(SiteOrderProcessor::new());
         
         registry.register(processor);
@@ -213,7 +361,7 @@ mod tests {
 
     #[test]
     fn test_order_type_registry_get_processor() {
-        let mut registry = OrderTypeRegistry::new("site".to_string());
+        let registry = OrderTypeRegistry::new("site".to_string());
         let processor = Arc::new(SiteOrderProcessor::new());
         
         registry.register(processor);
@@ -230,6 +378,21 @@ mod tests {
         assert!(retrieved.is_none());
     }
 
+    #[test]
+    fn test_order_type_registry_runtime_registration() {
+        let registry = OrderTypeRegistry::new("site".to_string());
+        registry.register_processor(Box::new(SiteOrderProcessor::new()));
+
+        let types = registry.list_registered_types();
+        assert_eq!(types.len(), 1);
+        assert_eq!(types[0].order_type, "site");
+        assert_eq!(types[0].payload_schema["required"][0], "name");
+
+        assert!(registry.unregister("site"));
+        assert!(!registry.unregister("site"));
+        assert!(registry.list_registered_types().is_empty());
+    }
+
     #[test]
     fn test_order_type_config_loader_default() {
         let configs = OrderTypeConfigLoader::default_configs();
@@ -243,6 +406,11 @@ mod tests {
             name: "Test".to_string(),
             description: None,
             address: None,
+            region: None,
+            facility: None,
+            tags: vec![],
+            custom_fields: Default::default(),
+            priority: Default::default(),
         });
         assert_eq!(order.order_type(), "site");
     }
@@ -250,24 +418,7 @@ mod tests {
     #[test]
     fn test_netbox_resource_request_type() {
         use crate::netbox::models::CreateSiteRequest;
-        let request = NetBoxResourceRequest::Site(CreateSiteRequest {
-            name: "Test".to_string(),
-            slug: None,
-            description: None,
-            status: None,
-            region: None,
-            tenant: None,
-            facility: None,
-            physical_address: None,
-            shipping_address: None,
-            latitude: None,
-            longitude: None,
-            contact_name: None,
-            contact_phone: None,
-            contact_email: None,
-            comments: None,
-            tags: None,
-        });
+        let request = NetBoxResourceRequest::Site(CreateSiteRequest::builder("Test").build());
         assert_eq!(request.resource_type(), "site");
     }
 
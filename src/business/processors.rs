@@ -1,11 +1,30 @@
 use crate::business::plugin::{NetBoxResource, NetBoxResourceRequest, OrderPayload, OrderProcessor};
+use crate::business::catalog::DeviceCatalog;
 use crate::business::enrichment::EnrichmentData;
+use crate::business::vlan::{self, VidRange, VlanRanges, MAX_VID, MIN_VID};
+use crate::business::workflow::{CreatedResource, ResourceKind};
 use crate::business::{ObjectEnricher, OrderTransformer, OrderValidator};
+use crate::domain::{AllocateIpOrder, CatalogRef, CreateDeviceOrder, CreateNetworkOrder, CreateVlanOrder, CreateVmOrder};
 use crate::error::AppError;
+use crate::netbox::models::{
+    AllocateIpRequest, CableTermination, CreateCableRequest, CreateDeviceRequest, CreateInterfaceRequest,
+    CreatePrefixRequest, CreateVirtualMachineRequest, CreateVlanRequest, DeviceStatus, IpAddressStatus, PrefixStatus,
+    VirtualMachineStatus, VlanStatus,
+};
+use crate::netbox::NetBoxError;
 use crate::netbox::ResilientNetBoxClient;
+use crate::security::tenant::TenantAccessControl;
+use crate::security::TenantId;
 use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
 use std::sync::Arc;
 
+/// The id NetBox assigned a created object, which it always returns
+fn created_id(id: Option<i32>, kind: &str) -> Result<i32, AppError> {
+    id.ok_or_else(|| AppError::Internal(anyhow::anyhow!("NetBox returned a {} without an id", kind)))
+}
+
 /// Site order processor implementation
 pub struct SiteOrderProcessor {
     validator: OrderValidator,
@@ -13,34 +32,998 @@ pub struct SiteOrderProcessor {
     enricher: ObjectEnricher,
 }
 
-impl SiteOrderProcessor {
+impl SiteOrderProcessor {
+    pub fn new() -> Self {
+        Self {
+            validator: OrderValidator::new(),
+            transformer: OrderTransformer::new(),
+            enricher: ObjectEnricher::new(),
+        }
+    }
+}
+
+impl Default for SiteOrderProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn unsupported_payload(processor: &str, order: &OrderPayload) -> AppError {
+    AppError::ValidationError(format!("{} processor cannot handle {} orders", processor, order.order_type()))
+}
+
+fn unsupported_request(processor: &str, request: &NetBoxResourceRequest) -> AppError {
+    AppError::ValidationError(format!(
+        "{} processor cannot create {} resources",
+        processor,
+        request.resource_type()
+    ))
+}
+
+/// Tag every resource created through NetGate
+fn add_netgate_tags(tags: &mut Option<Vec<String>>) {
+    let tags = tags.get_or_insert_with(Vec::new);
+    for tag in ["netgate", "enriched"] {
+        if !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+}
+
+#[async_trait]
+impl OrderProcessor for SiteOrderProcessor {
+    fn order_type(&self) -> &'static str {
+        "site"
+    }
+
+    fn payload_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "required": ["name"],
+            "properties": {
+                "name": { "type": "string" },
+                "description": { "type": "string" },
+                "address": { "type": "string" }
+            }
+        })
+    }
+
+    fn parse_payload(&self, data: serde_json::Value) -> Result<OrderPayload, AppError> {
+        serde_json::from_value(data)
+            .map(OrderPayload::Site)
+            .map_err(|e| AppError::ValidationError(format!("Invalid site order: {}", e)))
+    }
+
+    fn validate(&self, order: &OrderPayload) -> Result<(), AppError> {
+        match order {
+            OrderPayload::Site(site_order) => {
+                self.validator.validate_site_order(site_order)
+                    .map_err(|e| AppError::ValidationError(e.to_string()))
+            }
+            other => Err(unsupported_payload("Site", other)),
+        }
+    }
+
+    fn transform(
+        &self,
+        order: OrderPayload,
+        tenant_id: Option<i32>,
+    ) -> Result<NetBoxResourceRequest, AppError> {
+        match order {
+            OrderPayload::Site(site_order) => {
+                let request = self.transformer.transform_site_order(site_order, tenant_id, None);
+                Ok(NetBoxResourceRequest::Site(request))
+            }
+            other => Err(unsupported_payload("Site", &other)),
+        }
+    }
+
+    fn enrich_request(
+        &self,
+        request: &mut NetBoxResourceRequest,
+        _enrichment_data: &EnrichmentData,
+    ) -> Result<(), AppError> {
+        match request {
+            NetBoxResourceRequest::Site(site_request) => {
+                let mut tags = site_request.tags.take().unwrap_or_default();
+                tags.push("netgate".to_string());
+                tags.push("enriched".to_string());
+                site_request.tags = Some(tags);
+                Ok(())
+            }
+            other => Err(unsupported_request("Site", other)),
+        }
+    }
+
+    async fn create_resource(
+        &self,
+        client: &Arc<ResilientNetBoxClient>,
+        request: NetBoxResourceRequest,
+    ) -> Result<NetBoxResource, AppError> {
+        match request {
+            NetBoxResourceRequest::Site(site_request) => {
+                let site = client.create_site(site_request).await?;
+                Ok(NetBoxResource::Site(site))
+            }
+            other => Err(unsupported_request("Site", &other)),
+        }
+    }
+
+    fn enrich_resource(
+        &self,
+        resource: NetBoxResource,
+        enrichment_data: &EnrichmentData,
+    ) -> NetBoxResource {
+        match resource {
+            NetBoxResource::Site(site) => {
+                let enriched = self.enricher.enrich_site(site, enrichment_data);
+                NetBoxResource::Site(enriched)
+            }
+            other => other,
+        }
+    }
+}
+
+/// Device order processor: creates a device in an existing site
+///
+/// Device types and roles given by name are resolved through the catalog.
+#[derive(Default)]
+pub struct DeviceOrderProcessor {
+    catalog: Option<Arc<DeviceCatalog>>,
+}
+
+impl DeviceOrderProcessor {
+    pub(crate) const MAX_NAME_LENGTH: usize = 64;
+    const MAX_SERIAL_LENGTH: usize = 50;
+    const MAX_INTERFACE_NAME_LENGTH: usize = 64;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_catalog(mut self, catalog: Arc<DeviceCatalog>) -> Self {
+        self.catalog = Some(catalog);
+        self
+    }
+
+    fn validate_device_order(order: &CreateDeviceOrder) -> Result<(), AppError> {
+        let invalid = |msg: &str| Err(AppError::ValidationError(msg.to_string()));
+
+        match (order.site_id, order.site_slug.as_deref()) {
+            (None, None) => return invalid("Device order requires a target site_id or site_slug"),
+            (Some(_), Some(_)) => return invalid("Specify either site_id or site_slug, not both"),
+            (Some(id), None) if id <= 0 => return invalid("Site id must be a positive integer"),
+            (None, Some(slug)) if slug.trim().is_empty() => return invalid("Site slug cannot be empty"),
+            _ => {}
+        }
+        Self::validate_device_details(order)
+    }
+
+    /// Validate everything but the target site, e.g. for devices of a site not created yet
+    pub(crate) fn validate_device_details(order: &CreateDeviceOrder) -> Result<(), AppError> {
+        let invalid = |msg: &str| Err(AppError::ValidationError(msg.to_string()));
+
+        let name = order.name.trim();
+        if name.is_empty() {
+            return invalid("Device name cannot be empty");
+        }
+        if name.len() > Self::MAX_NAME_LENGTH {
+            return invalid("Device name exceeds maximum length of 64 characters");
+        }
+        match order.device_type {
+            CatalogRef::Id(id) if id <= 0 => return invalid("Device type id must be a positive integer"),
+            CatalogRef::Name(ref name) if name.trim().is_empty() => return invalid("Device type name cannot be empty"),
+            _ => {}
+        }
+        match order.role {
+            CatalogRef::Id(id) if id <= 0 => return invalid("Device role id must be a positive integer"),
+            CatalogRef::Name(ref name) if name.trim().is_empty() => return invalid("Device role name cannot be empty"),
+            _ => {}
+        }
+        if order.serial.as_ref().is_some_and(|serial| serial.len() > Self::MAX_SERIAL_LENGTH) {
+            return invalid("Serial number exceeds maximum length of 50 characters");
+        }
+        if order.tags.iter().any(|tag| tag.trim().is_empty()) {
+            return invalid("Device tags cannot be empty");
+        }
+        Self::validate_connectivity(order)
+    }
+
+    /// Interfaces must have distinct names, and each uplink must start at one of them
+    fn validate_connectivity(order: &CreateDeviceOrder) -> Result<(), AppError> {
+        let invalid = |msg: String| Err(AppError::ValidationError(msg));
+
+        let mut names = HashSet::new();
+        for interface in &order.interfaces {
+            let name = interface.name.trim();
+            if name.is_empty() {
+                return invalid("Interface name cannot be empty".to_string());
+            }
+            if name.len() > Self::MAX_INTERFACE_NAME_LENGTH {
+                return invalid(format!("Interface name '{}' exceeds maximum length of 64 characters", name));
+            }
+            if interface.interface_type.trim().is_empty() {
+                return invalid(format!("Interface '{}' requires a type", name));
+            }
+            if !names.insert(name) {
+                return invalid(format!("Interface '{}' appears more than once in the order", name));
+            }
+        }
+
+        let mut uplinked = HashSet::new();
+        for uplink in &order.uplinks {
+            let local = uplink.local_interface.trim();
+            if !names.contains(local) {
+                return invalid(format!("Uplink interface '{}' is not one of the order's interfaces", local));
+            }
+            if !uplinked.insert(local) {
+                return invalid(format!("Interface '{}' has more than one uplink", local));
+            }
+            if uplink.remote_device <= 0 {
+                return invalid("Uplink remote device id must be a positive integer".to_string());
+            }
+            if uplink.remote_interface.trim().is_empty() {
+                return invalid(format!("Uplink from '{}' requires a remote interface", local));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OrderProcessor for DeviceOrderProcessor {
+    fn order_type(&self) -> &'static str {
+        "device"
+    }
+
+    fn payload_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "required": ["name", "device_type", "role"],
+            "properties": {
+                "name": { "type": "string", "maxLength": Self::MAX_NAME_LENGTH },
+                "device_type": { "type": ["integer", "string"], "minimum": 1 },
+                "role": { "type": ["integer", "string"], "minimum": 1 },
+                "site_id": { "type": "integer", "minimum": 1 },
+                "site_slug": { "type": "string" },
+                "serial": { "type": "string", "maxLength": Self::MAX_SERIAL_LENGTH },
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "interfaces": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["name", "type"],
+                        "properties": {
+                            "name": { "type": "string", "maxLength": Self::MAX_INTERFACE_NAME_LENGTH },
+                            "type": { "type": "string" },
+                            "enabled": { "type": "boolean" },
+                            "description": { "type": "string" }
+                        }
+                    }
+                },
+                "uplinks": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["local_interface", "remote_device", "remote_interface"],
+                        "properties": {
+                            "local_interface": { "type": "string" },
+                            "remote_device": { "type": "integer", "minimum": 1 },
+                            "remote_interface": { "type": "string" }
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    fn parse_payload(&self, data: serde_json::Value) -> Result<OrderPayload, AppError> {
+        serde_json::from_value(data)
+            .map(OrderPayload::Device)
+            .map_err(|e| AppError::ValidationError(format!("Invalid device order: {}", e)))
+    }
+
+    fn validate(&self, order: &OrderPayload) -> Result<(), AppError> {
+        match order {
+            OrderPayload::Device(device_order) => Self::validate_device_order(device_order),
+            other => Err(unsupported_payload("Device", other)),
+        }
+    }
+
+    fn transform(
+        &self,
+        order: OrderPayload,
+        tenant_id: Option<i32>,
+    ) -> Result<NetBoxResourceRequest, AppError> {
+        let OrderPayload::Device(device_order) = order else {
+            return Err(unsupported_payload("Device", &order));
+        };
+        // Names are resolved into ids at creation time
+        let (device_type, device_type_name) = match device_order.device_type {
+            CatalogRef::Id(id) => (id, None),
+            CatalogRef::Name(name) => (0, Some(name)),
+        };
+        let (device_role, role_name) = match device_order.role {
+            CatalogRef::Id(id) => (id, None),
+            CatalogRef::Name(name) => (0, Some(name)),
+        };
+        let request = CreateDeviceRequest {
+            name: Some(device_order.name.trim().to_string()),
+            device_type,
+            device_role,
+            tenant: tenant_id,
+            platform: None,
+            serial: device_order.serial,
+            asset_tag: None,
+            // Resolved from the slug at creation time when no id was given
+            site: device_order.site_id.unwrap_or_default(),
+            location: None,
+            rack: None,
+            position: None,
+            face: None,
+            status: Some(DeviceStatus::Planned),
+            cluster: None,
+            comments: None,
+            tags: (!device_order.tags.is_empty()).then_some(device_order.tags),
+        };
+        Ok(NetBoxResourceRequest::Device {
+            request,
+            site_slug: device_order.site_slug,
+            device_type_name,
+            role_name,
+            interfaces: device_order.interfaces,
+            uplinks: device_order.uplinks,
+        })
+    }
+
+    fn enrich_request(
+        &self,
+        request: &mut NetBoxResourceRequest,
+        _enrichment_data: &EnrichmentData,
+    ) -> Result<(), AppError> {
+        match request {
+            NetBoxResourceRequest::Device { request, .. } => {
+                add_netgate_tags(&mut request.tags);
+                Ok(())
+            }
+            other => Err(unsupported_request("Device", other)),
+        }
+    }
+
+    async fn create_resource(
+        &self,
+        client: &Arc<ResilientNetBoxClient>,
+        request: NetBoxResourceRequest,
+    ) -> Result<NetBoxResource, AppError> {
+        self.create_resources(client, request, &mut Vec::new()).await
+    }
+
+    /// Create the device, then its interfaces, then a cable per uplink
+    async fn create_resources(
+        &self,
+        client: &Arc<ResilientNetBoxClient>,
+        request: NetBoxResourceRequest,
+        created: &mut Vec<CreatedResource>,
+    ) -> Result<NetBoxResource, AppError> {
+        let NetBoxResourceRequest::Device {
+            mut request,
+            site_slug,
+            device_type_name,
+            role_name,
+            interfaces,
+            uplinks,
+        } = request
+        else {
+            return Err(unsupported_request("Device", &request));
+        };
+        if device_type_name.is_some() || role_name.is_some() {
+            let catalog = self.catalog.as_ref().ok_or_else(|| {
+                AppError::ValidationError(
+                    "Device types and roles can only be given by NetBox id: the device catalog is unavailable".to_string(),
+                )
+            })?;
+            if let Some(name) = device_type_name {
+                request.device_type = catalog.resolve_device_type(&CatalogRef::Name(name)).await?;
+            }
+            if let Some(name) = role_name {
+                request.device_role = catalog.resolve_device_role(&CatalogRef::Name(name)).await?;
+            }
+        }
+        if let Some(slug) = site_slug {
+            let site = client.find_site_by_slug(&slug).await?;
+            request.site = site
+                .and_then(|site| site.id)
+                .ok_or_else(|| AppError::ValidationError(format!("Site with slug '{}' not found", slug)))?;
+        }
+        let device = client.create_device(request).await?;
+        let device_id = created_id(device.id, "device")?;
+        created.push(CreatedResource { kind: ResourceKind::Device, id: device_id });
+
+        let mut interface_ids = HashMap::new();
+        for interface in interfaces {
+            let name = interface.name.trim().to_string();
+            let created_interface = client
+                .create_interface(CreateInterfaceRequest {
+                    device: device_id,
+                    name: name.clone(),
+                    interface_type: interface.interface_type,
+                    enabled: interface.enabled,
+                    description: interface.description,
+                    tags: None,
+                })
+                .await?;
+            let interface_id = created_id(created_interface.id, "interface")?;
+            created.push(CreatedResource { kind: ResourceKind::Interface, id: interface_id });
+            interface_ids.insert(name, interface_id);
+        }
+
+        for uplink in uplinks {
+            let local = uplink.local_interface.trim();
+            let local_id = *interface_ids.get(local).ok_or_else(|| {
+                AppError::ValidationError(format!("Uplink interface '{}' is not one of the order's interfaces", local))
+            })?;
+            let remote_name = uplink.remote_interface.trim();
+            let remote = client.find_interface(uplink.remote_device, remote_name).await?;
+            let remote_id = remote.and_then(|interface| interface.id).ok_or_else(|| {
+                AppError::ValidationError(format!(
+                    "Interface '{}' not found on device {}",
+                    remote_name, uplink.remote_device
+                ))
+            })?;
+            let cable = client
+                .create_cable(CreateCableRequest {
+                    a_terminations: vec![CableTermination::interface(local_id)],
+                    b_terminations: vec![CableTermination::interface(remote_id)],
+                    status: None,
+                    label: None,
+                    description: None,
+                    tags: None,
+                })
+                .await?;
+            created.push(CreatedResource { kind: ResourceKind::Cable, id: created_id(cable.id, "cable")? });
+        }
+
+        Ok(NetBoxResource::Device(device))
+    }
+
+    fn enrich_resource(
+        &self,
+        resource: NetBoxResource,
+        _enrichment_data: &EnrichmentData,
+    ) -> NetBoxResource {
+        resource
+    }
+}
+
+/// Network order processor: creates an IPAM prefix
+#[derive(Default)]
+pub struct NetworkOrderProcessor;
+
+impl NetworkOrderProcessor {
+    const MAX_DESCRIPTION_LENGTH: usize = 200;
+
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Parse a CIDR prefix, rejecting addresses with host bits set
+    fn parse_prefix(prefix: &str) -> Result<(IpAddr, u8), AppError> {
+        let invalid = |msg: String| AppError::ValidationError(msg);
+
+        let (address, length) = prefix
+            .trim()
+            .split_once('/')
+            .ok_or_else(|| invalid(format!("Prefix '{}' must be in CIDR notation (address/length)", prefix)))?;
+        let address: IpAddr = address
+            .parse()
+            .map_err(|_| invalid(format!("Prefix address '{}' is not a valid IP address", address)))?;
+        let max_length = if address.is_ipv4() { 32 } else { 128 };
+        let length: u8 = length
+            .parse()
+            .ok()
+            .filter(|length| *length <= max_length)
+            .ok_or_else(|| invalid(format!("Prefix length must be between 0 and {}", max_length)))?;
+
+        let network = match address {
+            IpAddr::V4(v4) => {
+                let mask = u32::MAX.checked_shl(32 - u32::from(length)).unwrap_or(0);
+                IpAddr::from((u32::from(v4) & mask).to_be_bytes())
+            }
+            IpAddr::V6(v6) => {
+                let mask = u128::MAX.checked_shl(128 - u32::from(length)).unwrap_or(0);
+                IpAddr::from((u128::from(v6) & mask).to_be_bytes())
+            }
+        };
+        if network != address {
+            return Err(invalid(format!(
+                "Prefix {} has host bits set; did you mean {}/{}?",
+                prefix.trim(),
+                network,
+                length
+            )));
+        }
+        Ok((address, length))
+    }
+
+    fn validate_network_order(order: &CreateNetworkOrder) -> Result<(), AppError> {
+        Self::parse_prefix(&order.prefix)?;
+        if order.vlan.is_some_and(|vlan| vlan <= 0) {
+            return Err(AppError::ValidationError("VLAN id must be a positive integer".to_string()));
+        }
+        if order
+            .description
+            .as_ref()
+            .is_some_and(|description| description.len() > Self::MAX_DESCRIPTION_LENGTH)
+        {
+            return Err(AppError::ValidationError(
+                "Description exceeds maximum length of 200 characters".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OrderProcessor for NetworkOrderProcessor {
+    fn order_type(&self) -> &'static str {
+        "network"
+    }
+
+    fn payload_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "required": ["prefix"],
+            "properties": {
+                "prefix": { "type": "string", "description": "CIDR, e.g. 10.20.0.0/24" },
+                "description": { "type": "string", "maxLength": Self::MAX_DESCRIPTION_LENGTH },
+                "vlan": { "type": "integer", "minimum": 1 }
+            }
+        })
+    }
+
+    fn parse_payload(&self, data: serde_json::Value) -> Result<OrderPayload, AppError> {
+        serde_json::from_value(data)
+            .map(OrderPayload::Network)
+            .map_err(|e| AppError::ValidationError(format!("Invalid network order: {}", e)))
+    }
+
+    fn validate(&self, order: &OrderPayload) -> Result<(), AppError> {
+        match order {
+            OrderPayload::Network(network_order) => Self::validate_network_order(network_order),
+            other => Err(unsupported_payload("Network", other)),
+        }
+    }
+
+    fn transform(
+        &self,
+        order: OrderPayload,
+        tenant_id: Option<i32>,
+    ) -> Result<NetBoxResourceRequest, AppError> {
+        let OrderPayload::Network(network_order) = order else {
+            return Err(unsupported_payload("Network", &order));
+        };
+        let (address, length) = Self::parse_prefix(&network_order.prefix)?;
+        Ok(NetBoxResourceRequest::Prefix(CreatePrefixRequest {
+            prefix: format!("{}/{}", address, length),
+            site: None,
+            vlan: network_order.vlan,
+            tenant: tenant_id,
+            status: Some(PrefixStatus::Active),
+            description: network_order.description,
+            tags: None,
+        }))
+    }
+
+    fn enrich_request(
+        &self,
+        request: &mut NetBoxResourceRequest,
+        _enrichment_data: &EnrichmentData,
+    ) -> Result<(), AppError> {
+        match request {
+            NetBoxResourceRequest::Prefix(prefix_request) => {
+                add_netgate_tags(&mut prefix_request.tags);
+                Ok(())
+            }
+            other => Err(unsupported_request("Network", other)),
+        }
+    }
+
+    async fn create_resource(
+        &self,
+        client: &Arc<ResilientNetBoxClient>,
+        request: NetBoxResourceRequest,
+    ) -> Result<NetBoxResource, AppError> {
+        match request {
+            NetBoxResourceRequest::Prefix(prefix_request) => {
+                let prefix = client.create_prefix(prefix_request).await?;
+                Ok(NetBoxResource::Prefix(prefix))
+            }
+            other => Err(unsupported_request("Network", &other)),
+        }
+    }
+
+    fn enrich_resource(
+        &self,
+        resource: NetBoxResource,
+        _enrichment_data: &EnrichmentData,
+    ) -> NetBoxResource {
+        resource
+    }
+}
+
+/// VLAN order processor: creates a VLAN at an existing site
+///
+/// VIDs must lie in the ordering tenant's ranges and be free at the site;
+/// orders without a VID get the lowest free one. With access control, the
+/// VLAN is assigned to the tenant's NetBox tenant.
+#[derive(Default)]
+pub struct VlanOrderProcessor {
+    ranges: VlanRanges,
+    access_control: Option<Arc<TenantAccessControl>>,
+}
+
+impl VlanOrderProcessor {
+    const MAX_NAME_LENGTH: usize = 64;
+    const MAX_DESCRIPTION_LENGTH: usize = 200;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_ranges(mut self, ranges: VlanRanges) -> Self {
+        self.ranges = ranges;
+        self
+    }
+
+    pub fn with_access_control(mut self, access_control: Arc<TenantAccessControl>) -> Self {
+        self.access_control = Some(access_control);
+        self
+    }
+
+    fn validate_vlan_order(order: &CreateVlanOrder) -> Result<(), AppError> {
+        let invalid = |msg: &str| Err(AppError::ValidationError(msg.to_string()));
+
+        if order.site_id <= 0 {
+            return invalid("Site id must be a positive integer");
+        }
+        let name = order.name.trim();
+        if name.is_empty() {
+            return invalid("VLAN name cannot be empty");
+        }
+        if name.len() > Self::MAX_NAME_LENGTH {
+            return invalid("VLAN name exceeds maximum length of 64 characters");
+        }
+        if order.vid.is_some() && Self::vid(order).is_none() {
+            return invalid("VLAN id must be between 1 and 4094");
+        }
+        if order.group.is_some_and(|group| group <= 0) {
+            return invalid("VLAN group id must be a positive integer");
+        }
+        if order
+            .description
+            .as_ref()
+            .is_some_and(|description| description.len() > Self::MAX_DESCRIPTION_LENGTH)
+        {
+            return invalid("Description exceeds maximum length of 200 characters");
+        }
+        if order.tags.iter().any(|tag| tag.trim().is_empty()) {
+            return invalid("VLAN tags cannot be empty");
+        }
+        Ok(())
+    }
+
+    /// The order's VID, if it gave a valid one
+    fn vid(order: &CreateVlanOrder) -> Option<u16> {
+        order
+            .vid
+            .and_then(|vid| u16::try_from(vid).ok())
+            .filter(|vid| (MIN_VID..=MAX_VID).contains(vid))
+    }
+
+    /// Build the request, auto-assigning from `ranges` when the order has no VID
+    fn vlan_request(
+        order: CreateVlanOrder,
+        tenant_id: Option<i32>,
+        ranges: Vec<VidRange>,
+    ) -> NetBoxResourceRequest {
+        let vid = Self::vid(&order);
+        NetBoxResourceRequest::Vlan {
+            request: CreateVlanRequest {
+                vid: vid.unwrap_or_default(),
+                name: order.name.trim().to_string(),
+                site: Some(order.site_id),
+                group: order.group,
+                tenant: tenant_id,
+                status: Some(VlanStatus::Active),
+                description: order.description,
+                tags: (!order.tags.is_empty()).then_some(order.tags),
+            },
+            auto_assign: vid.is_none().then_some(ranges),
+        }
+    }
+}
+
+#[async_trait]
+impl OrderProcessor for VlanOrderProcessor {
+    fn order_type(&self) -> &'static str {
+        "vlan"
+    }
+
+    fn payload_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "required": ["site_id", "name"],
+            "properties": {
+                "site_id": { "type": "integer", "minimum": 1 },
+                "name": { "type": "string", "maxLength": Self::MAX_NAME_LENGTH },
+                "vid": {
+                    "type": "integer",
+                    "minimum": MIN_VID,
+                    "maximum": MAX_VID,
+                    "description": "Left out, the lowest free VID in the tenant's ranges is assigned"
+                },
+                "group": { "type": "integer", "minimum": 1 },
+                "description": { "type": "string", "maxLength": Self::MAX_DESCRIPTION_LENGTH },
+                "tags": { "type": "array", "items": { "type": "string" } }
+            }
+        })
+    }
+
+    fn parse_payload(&self, data: serde_json::Value) -> Result<OrderPayload, AppError> {
+        serde_json::from_value(data)
+            .map(OrderPayload::Vlan)
+            .map_err(|e| AppError::ValidationError(format!("Invalid VLAN order: {}", e)))
+    }
+
+    fn validate(&self, order: &OrderPayload) -> Result<(), AppError> {
+        match order {
+            OrderPayload::Vlan(vlan_order) => Self::validate_vlan_order(vlan_order),
+            other => Err(unsupported_payload("VLAN", other)),
+        }
+    }
+
+    fn validate_for_tenant(&self, order: &OrderPayload, tenant_id: &TenantId) -> Result<(), AppError> {
+        self.validate(order)?;
+        match order {
+            OrderPayload::Vlan(vlan_order) => match Self::vid(vlan_order) {
+                Some(vid) => self.ranges.check(tenant_id, vid),
+                None => Ok(()),
+            },
+            other => Err(unsupported_payload("VLAN", other)),
+        }
+    }
+
+    fn transform(
+        &self,
+        order: OrderPayload,
+        tenant_id: Option<i32>,
+    ) -> Result<NetBoxResourceRequest, AppError> {
+        match order {
+            OrderPayload::Vlan(vlan_order) => Ok(Self::vlan_request(vlan_order, tenant_id, vec![VidRange::ALL])),
+            other => Err(unsupported_payload("VLAN", &other)),
+        }
+    }
+
+    fn transform_for_tenant(
+        &self,
+        order: OrderPayload,
+        tenant_id: &TenantId,
+    ) -> Result<NetBoxResourceRequest, AppError> {
+        let netbox_tenant_id = self
+            .access_control
+            .as_ref()
+            .and_then(|access_control| access_control.get_netbox_tenant_id(tenant_id));
+        match order {
+            OrderPayload::Vlan(vlan_order) => Ok(Self::vlan_request(
+                vlan_order,
+                netbox_tenant_id,
+                self.ranges.ranges_for(tenant_id),
+            )),
+            other => Err(unsupported_payload("VLAN", &other)),
+        }
+    }
+
+    fn enrich_request(
+        &self,
+        request: &mut NetBoxResourceRequest,
+        _enrichment_data: &EnrichmentData,
+    ) -> Result<(), AppError> {
+        match request {
+            NetBoxResourceRequest::Vlan { request, .. } => {
+                add_netgate_tags(&mut request.tags);
+                Ok(())
+            }
+            other => Err(unsupported_request("VLAN", other)),
+        }
+    }
+
+    /// Check the VID is free at the site, or pick the lowest free one, then create the VLAN
+    async fn create_resource(
+        &self,
+        client: &Arc<ResilientNetBoxClient>,
+        request: NetBoxResourceRequest,
+    ) -> Result<NetBoxResource, AppError> {
+        let NetBoxResourceRequest::Vlan { mut request, auto_assign } = request else {
+            return Err(unsupported_request("VLAN", &request));
+        };
+        let site_id = request
+            .site
+            .ok_or_else(|| AppError::ValidationError("VLAN order requires a site".to_string()))?;
+
+        match auto_assign {
+            Some(ranges) => {
+                let used = vlan::used_vids(client, site_id).await?;
+                request.vid = vlan::lowest_free_vid(&ranges, &used).ok_or_else(|| {
+                    let ranges: Vec<String> = ranges.iter().map(VidRange::to_string).collect();
+                    AppError::Conflict(format!(
+                        "No free VLAN id at site {} in {}",
+                        site_id,
+                        ranges.join(", ")
+                    ))
+                })?;
+            }
+            None => {
+                if let Some(existing) = client.find_vlan(site_id, request.vid).await? {
+                    return Err(AppError::Conflict(format!(
+                        "VLAN {} is already used at site {} by '{}'{}",
+                        request.vid,
+                        site_id,
+                        existing.name,
+                        existing.id.map_or_else(String::new, |id| format!(" (id {})", id))
+                    )));
+                }
+            }
+        }
+
+        let vlan = client.create_vlan(request).await?;
+        Ok(NetBoxResource::Vlan(vlan))
+    }
+
+    fn enrich_resource(
+        &self,
+        resource: NetBoxResource,
+        _enrichment_data: &EnrichmentData,
+    ) -> NetBoxResource {
+        resource
+    }
+}
+
+/// IP address order processor: allocates the next free address of a prefix
+///
+/// With access control, the prefix must belong to the ordering tenant's
+/// NetBox tenant, and so does the address. The address may be assigned to an
+/// interface of an existing device.
+#[derive(Default)]
+pub struct IpAllocationOrderProcessor {
+    access_control: Option<Arc<TenantAccessControl>>,
+}
+
+impl IpAllocationOrderProcessor {
+    const MAX_DNS_NAME_LENGTH: usize = 255;
+    const MAX_DNS_LABEL_LENGTH: usize = 63;
+    const MAX_DESCRIPTION_LENGTH: usize = 200;
+
     pub fn new() -> Self {
-        Self {
-            validator: OrderValidator::new(),
-            transformer: OrderTransformer::new(),
-            enricher: ObjectEnricher::new(),
+        Self::default()
+    }
+
+    pub fn with_access_control(mut self, access_control: Arc<TenantAccessControl>) -> Self {
+        self.access_control = Some(access_control);
+        self
+    }
+
+    fn validate_ip_order(order: &AllocateIpOrder) -> Result<(), AppError> {
+        let invalid = |msg: &str| Err(AppError::ValidationError(msg.to_string()));
+
+        if order.prefix_id <= 0 {
+            return invalid("Prefix id must be a positive integer");
+        }
+        if order.device_id.is_some_and(|device| device <= 0) {
+            return invalid("Device id must be a positive integer");
+        }
+        match (&order.device_id, &order.interface) {
+            (Some(_), None) => return invalid("An address assigned to a device needs an interface"),
+            (None, Some(_)) => return invalid("An interface needs the device it belongs to"),
+            (Some(_), Some(interface)) if interface.trim().is_empty() => {
+                return invalid("Interface name cannot be empty")
+            }
+            _ => {}
+        }
+        if let Some(dns_name) = &order.dns_name {
+            if !Self::is_valid_dns_name(dns_name) {
+                return Err(AppError::ValidationError(format!("'{}' is not a valid DNS name", dns_name)));
+            }
+        }
+        if order
+            .description
+            .as_ref()
+            .is_some_and(|description| description.len() > Self::MAX_DESCRIPTION_LENGTH)
+        {
+            return invalid("Description exceeds maximum length of 200 characters");
         }
+        if order.tags.iter().any(|tag| tag.trim().is_empty()) {
+            return invalid("IP address tags cannot be empty");
+        }
+        Ok(())
     }
-}
 
-impl Default for SiteOrderProcessor {
-    fn default() -> Self {
-        Self::new()
+    /// Letters, digits, and inner hyphens in labels of up to 63 characters,
+    /// optionally ending in a dot
+    fn is_valid_dns_name(name: &str) -> bool {
+        let name = name.strip_suffix('.').unwrap_or(name);
+        !name.is_empty()
+            && name.len() <= Self::MAX_DNS_NAME_LENGTH
+            && name.split('.').all(|label| {
+                !label.is_empty()
+                    && label.len() <= Self::MAX_DNS_LABEL_LENGTH
+                    && !label.starts_with('-')
+                    && !label.ends_with('-')
+                    && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            })
+    }
+
+    fn ip_request(order: AllocateIpOrder, tenant_id: Option<i32>) -> NetBoxResourceRequest {
+        NetBoxResourceRequest::IpAddress {
+            prefix_id: order.prefix_id,
+            request: AllocateIpRequest {
+                dns_name: order.dns_name,
+                tenant: tenant_id,
+                status: Some(IpAddressStatus::Active),
+                description: order.description,
+                tags: (!order.tags.is_empty()).then_some(order.tags),
+                ..Default::default()
+            },
+            device_id: order.device_id,
+            interface: order.interface.map(|name| name.trim().to_string()),
+        }
     }
 }
 
 #[async_trait]
-impl OrderProcessor for SiteOrderProcessor {
+impl OrderProcessor for IpAllocationOrderProcessor {
     fn order_type(&self) -> &'static str {
-        "site"
+        "ip_address"
+    }
+
+    fn payload_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "required": ["prefix_id"],
+            "properties": {
+                "prefix_id": { "type": "integer", "minimum": 1 },
+                "device_id": { "type": "integer", "minimum": 1 },
+                "interface": {
+                    "type": "string",
+                    "description": "Name of the device's interface that gets the address"
+                },
+                "dns_name": { "type": "string", "maxLength": Self::MAX_DNS_NAME_LENGTH },
+                "description": { "type": "string", "maxLength": Self::MAX_DESCRIPTION_LENGTH },
+                "tags": { "type": "array", "items": { "type": "string" } }
+            }
+        })
+    }
+
+    fn parse_payload(&self, data: serde_json::Value) -> Result<OrderPayload, AppError> {
+        serde_json::from_value(data)
+            .map(OrderPayload::IpAddress)
+            .map_err(|e| AppError::ValidationError(format!("Invalid IP address order: {}", e)))
     }
 
     fn validate(&self, order: &OrderPayload) -> Result<(), AppError> {
         match order {
-            OrderPayload::Site(site_order) => {
-                self.validator.validate_site_order(site_order)
-                    .map_err(|e| AppError::ValidationError(e.to_string()))
-            }
+            OrderPayload::IpAddress(ip_order) => Self::validate_ip_order(ip_order),
+            other => Err(unsupported_payload("IP address", other)),
+        }
+    }
+
+    /// Refuse tenants without a NetBox tenant, whose prefixes can't be checked
+    fn validate_for_tenant(&self, order: &OrderPayload, tenant_id: &TenantId) -> Result<(), AppError> {
+        self.validate(order)?;
+        match &self.access_control {
+            Some(access_control) if access_control.get_netbox_tenant_id(tenant_id).is_none() => Err(
+                AppError::Forbidden(format!("Tenant {} has no NetBox tenant to allocate addresses for", tenant_id)),
+            ),
+            _ => Ok(()),
         }
     }
 
@@ -50,26 +1033,34 @@ impl OrderProcessor for SiteOrderProcessor {
         tenant_id: Option<i32>,
     ) -> Result<NetBoxResourceRequest, AppError> {
         match order {
-            OrderPayload::Site(site_order) => {
-                let request = self.transformer.transform_site_order(site_order, tenant_id);
-                Ok(NetBoxResourceRequest::Site(request))
-            }
+            OrderPayload::IpAddress(ip_order) => Ok(Self::ip_request(ip_order, tenant_id)),
+            other => Err(unsupported_payload("IP address", &other)),
         }
     }
 
+    fn transform_for_tenant(
+        &self,
+        order: OrderPayload,
+        tenant_id: &TenantId,
+    ) -> Result<NetBoxResourceRequest, AppError> {
+        let netbox_tenant_id = self
+            .access_control
+            .as_ref()
+            .and_then(|access_control| access_control.get_netbox_tenant_id(tenant_id));
+        self.transform(order, netbox_tenant_id)
+    }
+
     fn enrich_request(
         &self,
         request: &mut NetBoxResourceRequest,
         _enrichment_data: &EnrichmentData,
     ) -> Result<(), AppError> {
         match request {
-            NetBoxResourceRequest::Site(site_request) => {
-                let mut tags = site_request.tags.take().unwrap_or_default();
-                tags.push("netgate".to_string());
-                tags.push("enriched".to_string());
-                site_request.tags = Some(tags);
+            NetBoxResourceRequest::IpAddress { request, .. } => {
+                add_netgate_tags(&mut request.tags);
                 Ok(())
             }
+            other => Err(unsupported_request("IP address", other)),
         }
     }
 
@@ -78,32 +1069,259 @@ impl OrderProcessor for SiteOrderProcessor {
         client: &Arc<ResilientNetBoxClient>,
         request: NetBoxResourceRequest,
     ) -> Result<NetBoxResource, AppError> {
-        match request {
-            NetBoxResourceRequest::Site(site_request) => {
-                let site = client.create_site(site_request).await?;
-                Ok(NetBoxResource::Site(site))
-            }
+        self.create_resources(client, request, &mut Vec::new()).await
+    }
+
+    /// Check the prefix's owner and free space, resolve the interface, then allocate
+    ///
+    /// Another order can take the last address between the check and the
+    /// allocation; NetBox then refuses it and the order fails the same way.
+    async fn create_resources(
+        &self,
+        client: &Arc<ResilientNetBoxClient>,
+        request: NetBoxResourceRequest,
+        created: &mut Vec<CreatedResource>,
+    ) -> Result<NetBoxResource, AppError> {
+        let NetBoxResourceRequest::IpAddress { prefix_id, mut request, device_id, interface } = request else {
+            return Err(unsupported_request("IP address", &request));
+        };
+
+        let prefix = client.get_prefix(prefix_id).await?;
+        if request.tenant.is_some_and(|owner| prefix.tenant != Some(owner)) {
+            return Err(AppError::Forbidden(format!("Prefix {} does not belong to the tenant", prefix_id)));
+        }
+        if let (Some(device_id), Some(name)) = (device_id, interface) {
+            let interface = client.find_interface(device_id, &name).await?.ok_or_else(|| {
+                AppError::ValidationError(format!("Device {} has no interface '{}'", device_id, name))
+            })?;
+            request.assign_to_interface(created_id(interface.id, "interface")?);
         }
+        if client.list_available_ips(prefix_id, Some(1)).await?.is_empty() {
+            return Err(NetBoxError::prefix_exhausted(prefix_id, &prefix).into());
+        }
+
+        let ip = client.allocate_ip(prefix_id, request).await?;
+        created.push(CreatedResource { kind: ResourceKind::IpAddress, id: created_id(ip.id, "IP address")? });
+        Ok(NetBoxResource::IpAddress(ip))
     }
 
     fn enrich_resource(
         &self,
         resource: NetBoxResource,
-        enrichment_data: &EnrichmentData,
+        _enrichment_data: &EnrichmentData,
     ) -> NetBoxResource {
-        match resource {
-            NetBoxResource::Site(site) => {
-                let enriched = self.enricher.enrich_site(site, enrichment_data);
-                NetBoxResource::Site(enriched)
+        resource
+    }
+}
+
+/// Virtual machine order processor: creates a VM in an existing cluster
+///
+/// With access control, the VM is assigned to the ordering tenant's NetBox
+/// tenant, and clusters of other tenants are refused; clusters without a
+/// tenant are shared.
+#[derive(Default)]
+pub struct VirtualMachineOrderProcessor {
+    access_control: Option<Arc<TenantAccessControl>>,
+}
+
+impl VirtualMachineOrderProcessor {
+    const MAX_NAME_LENGTH: usize = 64;
+    const MAX_DESCRIPTION_LENGTH: usize = 200;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_access_control(mut self, access_control: Arc<TenantAccessControl>) -> Self {
+        self.access_control = Some(access_control);
+        self
+    }
+
+    fn validate_vm_order(order: &CreateVmOrder) -> Result<(), AppError> {
+        let invalid = |msg: &str| Err(AppError::ValidationError(msg.to_string()));
+
+        let name = order.name.trim();
+        if name.is_empty() {
+            return invalid("VM name cannot be empty");
+        }
+        if name.len() > Self::MAX_NAME_LENGTH {
+            return invalid("VM name exceeds maximum length of 64 characters");
+        }
+        if order.cluster <= 0 {
+            return invalid("Cluster id must be a positive integer");
+        }
+        if order.vcpus.is_some_and(|vcpus| vcpus.is_nan() || vcpus <= 0.0) {
+            return invalid("vCPUs must be greater than 0");
+        }
+        if order.memory.is_some_and(|memory| memory <= 0) {
+            return invalid("Memory must be a positive number of MB");
+        }
+        if order.disk.is_some_and(|disk| disk <= 0) {
+            return invalid("Disk must be a positive number of GB");
+        }
+        if order.role.is_some_and(|role| role <= 0) {
+            return invalid("Role id must be a positive integer");
+        }
+        if order
+            .description
+            .as_ref()
+            .is_some_and(|description| description.len() > Self::MAX_DESCRIPTION_LENGTH)
+        {
+            return invalid("Description exceeds maximum length of 200 characters");
+        }
+        if order.tags.iter().any(|tag| tag.trim().is_empty()) {
+            return invalid("VM tags cannot be empty");
+        }
+        Ok(())
+    }
+
+    fn vm_request(order: CreateVmOrder, tenant_id: Option<i32>) -> NetBoxResourceRequest {
+        NetBoxResourceRequest::VirtualMachine(CreateVirtualMachineRequest {
+            name: order.name.trim().to_string(),
+            cluster: Some(order.cluster),
+            site: None,
+            tenant: tenant_id,
+            role: order.role,
+            platform: None,
+            status: Some(VirtualMachineStatus::Active),
+            vcpus: order.vcpus,
+            memory: order.memory,
+            disk: order.disk,
+            description: order.description,
+            tags: (!order.tags.is_empty()).then_some(order.tags),
+        })
+    }
+}
+
+#[async_trait]
+impl OrderProcessor for VirtualMachineOrderProcessor {
+    fn order_type(&self) -> &'static str {
+        "virtual_machine"
+    }
+
+    fn payload_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "required": ["name", "cluster"],
+            "properties": {
+                "name": { "type": "string", "maxLength": Self::MAX_NAME_LENGTH },
+                "cluster": { "type": "integer", "minimum": 1 },
+                "vcpus": { "type": "number", "exclusiveMinimum": 0 },
+                "memory": { "type": "integer", "minimum": 1, "description": "MB" },
+                "disk": { "type": "integer", "minimum": 1, "description": "GB" },
+                "role": { "type": "integer", "minimum": 1 },
+                "description": { "type": "string", "maxLength": Self::MAX_DESCRIPTION_LENGTH },
+                "tags": { "type": "array", "items": { "type": "string" } }
+            }
+        })
+    }
+
+    fn parse_payload(&self, data: serde_json::Value) -> Result<OrderPayload, AppError> {
+        serde_json::from_value(data)
+            .map(OrderPayload::VirtualMachine)
+            .map_err(|e| AppError::ValidationError(format!("Invalid VM order: {}", e)))
+    }
+
+    fn validate(&self, order: &OrderPayload) -> Result<(), AppError> {
+        match order {
+            OrderPayload::VirtualMachine(vm_order) => Self::validate_vm_order(vm_order),
+            other => Err(unsupported_payload("VM", other)),
+        }
+    }
+
+    fn transform(
+        &self,
+        order: OrderPayload,
+        tenant_id: Option<i32>,
+    ) -> Result<NetBoxResourceRequest, AppError> {
+        match order {
+            OrderPayload::VirtualMachine(vm_order) => Ok(Self::vm_request(vm_order, tenant_id)),
+            other => Err(unsupported_payload("VM", &other)),
+        }
+    }
+
+    fn transform_for_tenant(
+        &self,
+        order: OrderPayload,
+        tenant_id: &TenantId,
+    ) -> Result<NetBoxResourceRequest, AppError> {
+        let netbox_tenant_id = self
+            .access_control
+            .as_ref()
+            .and_then(|access_control| access_control.get_netbox_tenant_id(tenant_id));
+        self.transform(order, netbox_tenant_id)
+    }
+
+    fn enrich_request(
+        &self,
+        request: &mut NetBoxResourceRequest,
+        _enrichment_data: &EnrichmentData,
+    ) -> Result<(), AppError> {
+        match request {
+            NetBoxResourceRequest::VirtualMachine(request) => {
+                add_netgate_tags(&mut request.tags);
+                Ok(())
+            }
+            other => Err(unsupported_request("VM", other)),
+        }
+    }
+
+    async fn create_resource(
+        &self,
+        client: &Arc<ResilientNetBoxClient>,
+        request: NetBoxResourceRequest,
+    ) -> Result<NetBoxResource, AppError> {
+        self.create_resources(client, request, &mut Vec::new()).await
+    }
+
+    /// Check the cluster may be used and the name is free in it, then create the VM at the cluster's site
+    async fn create_resources(
+        &self,
+        client: &Arc<ResilientNetBoxClient>,
+        request: NetBoxResourceRequest,
+        created: &mut Vec<CreatedResource>,
+    ) -> Result<NetBoxResource, AppError> {
+        let NetBoxResourceRequest::VirtualMachine(mut request) = request else {
+            return Err(unsupported_request("VM", &request));
+        };
+        let cluster_id = request
+            .cluster
+            .ok_or_else(|| AppError::ValidationError("VM order requires a cluster".to_string()))?;
+
+        let cluster = client.get_cluster(cluster_id).await?;
+        if let (Some(owner), Some(tenant)) = (cluster.tenant, request.tenant) {
+            if owner != tenant {
+                return Err(AppError::Forbidden(format!("Cluster {} does not belong to the tenant", cluster_id)));
             }
         }
+        if let Some(existing) = client.find_virtual_machine(cluster_id, &request.name).await? {
+            return Err(AppError::Conflict(format!(
+                "VM '{}' already exists in cluster {}{}",
+                request.name,
+                cluster_id,
+                existing.id.map_or_else(String::new, |id| format!(" (id {})", id))
+            )));
+        }
+        request.site = cluster.site;
+
+        let vm = client.create_virtual_machine(request).await?;
+        created.push(CreatedResource { kind: ResourceKind::VirtualMachine, id: created_id(vm.id, "VM")? });
+        Ok(NetBoxResource::VirtualMachine(vm))
+    }
+
+    fn enrich_resource(
+        &self,
+        resource: NetBoxResource,
+        _enrichment_data: &EnrichmentData,
+    ) -> NetBoxResource {
+        resource
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::CreateSiteOrder;
+    use crate::domain::{CreateSiteOrder, DeviceInterfaceDefinition, DeviceUplink};
 
     #[test]
     fn test_site_order_processor_creation() {
@@ -118,6 +1336,11 @@ mod tests {
             name: "Test Site".to_string(),
             description: Some("Test".to_string()),
             address: None,
+            region: None,
+            facility: None,
+            tags: vec![],
+            custom_fields: Default::default(),
+            priority: Default::default(),
         });
         
         let result = processor.validate(&order);
@@ -131,6 +1354,11 @@ mod tests {
             name: "".to_string(), // Invalid: empty name
             description: None,
             address: None,
+            region: None,
+            facility: None,
+            tags: vec![],
+            custom_fields: Default::default(),
+            priority: Default::default(),
         });
         
         let result = processor.validate(&order);
@@ -144,13 +1372,327 @@ mod tests {
             name: "Test Site".to_string(),
             description: Some("Test".to_string()),
             address: None,
+            region: None,
+            facility: None,
+            tags: vec![],
+            custom_fields: Default::default(),
+            priority: Default::default(),
         });
         
         let result = processor.transform(order, None);
         assert!(result.is_ok());
         match result.unwrap() {
             NetBoxResourceRequest::Site(_) => {}
+            _ => panic!("Expected a site request"),
+        }
+    }
+
+    fn device_order() -> CreateDeviceOrder {
+        CreateDeviceOrder {
+            name: "edge-router-1".to_string(),
+            device_type: 3.into(),
+            role: 2.into(),
+            site_id: Some(10),
+            site_slug: None,
+            serial: Some("SN123".to_string()),
+            tags: vec!["edge".to_string()],
+            interfaces: Vec::new(),
+            uplinks: Vec::new(),
+        }
+    }
+
+    fn device_error(order: CreateDeviceOrder) -> String {
+        match DeviceOrderProcessor::new().validate(&OrderPayload::Device(order)) {
+            Err(AppError::ValidationError(msg)) => msg,
+            other => panic!("Expected validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_device_order_validation_messages() {
+        assert!(DeviceOrderProcessor::new().validate(&OrderPayload::Device(device_order())).is_ok());
+
+        let mut order = device_order();
+        order.name = "  ".to_string();
+        assert_eq!(device_error(order), "Device name cannot be empty");
+
+        let mut order = device_order();
+        order.device_type = 0.into();
+        assert_eq!(device_error(order), "Device type id must be a positive integer");
+
+        let mut order = device_order();
+        order.role = CatalogRef::Name(" ".to_string());
+        assert_eq!(device_error(order), "Device role name cannot be empty");
+
+        let mut order = device_order();
+        order.site_id = None;
+        assert_eq!(device_error(order), "Device order requires a target site_id or site_slug");
+
+        let mut order = device_order();
+        order.site_slug = Some("dc1".to_string());
+        assert_eq!(device_error(order), "Specify either site_id or site_slug, not both");
+
+        let mut order = device_order();
+        order.serial = Some("X".repeat(51));
+        assert_eq!(device_error(order), "Serial number exceeds maximum length of 50 characters");
+    }
+
+    #[test]
+    fn test_device_order_interface_validation() {
+        let interface = |name: &str| DeviceInterfaceDefinition {
+            name: name.to_string(),
+            interface_type: "1000base-t".to_string(),
+            enabled: None,
+            description: None,
+        };
+        let uplink = |local: &str| DeviceUplink {
+            local_interface: local.to_string(),
+            remote_device: 7,
+            remote_interface: "eth1".to_string(),
+        };
+
+        let mut order = device_order();
+        order.interfaces = vec![interface("eth0"), interface("eth1")];
+        order.uplinks = vec![uplink("eth0")];
+        assert!(DeviceOrderProcessor::new().validate(&OrderPayload::Device(order.clone())).is_ok());
+
+        order.interfaces.push(interface(" eth0 "));
+        assert_eq!(device_error(order.clone()), "Interface 'eth0' appears more than once in the order");
+
+        order.interfaces.pop();
+        order.uplinks.push(uplink("eth2"));
+        assert_eq!(device_error(order.clone()), "Uplink interface 'eth2' is not one of the order's interfaces");
+
+        order.uplinks[1] = uplink("eth0");
+        assert_eq!(device_error(order), "Interface 'eth0' has more than one uplink");
+    }
+
+    #[test]
+    fn test_device_order_transform_and_enrich() {
+        let processor = DeviceOrderProcessor::new();
+        let mut request = processor
+            .transform(OrderPayload::Device(device_order()), Some(4))
+            .unwrap();
+        processor.enrich_request(&mut request, &EnrichmentData::default()).unwrap();
+
+        let NetBoxResourceRequest::Device { request, site_slug, .. } = request else {
+            panic!("Expected a device request");
+        };
+        assert_eq!(site_slug, None);
+        assert_eq!(request.name.as_deref(), Some("edge-router-1"));
+        assert_eq!((request.device_type, request.device_role, request.site), (3, 2, 10));
+        assert_eq!(request.tenant, Some(4));
+        assert_eq!(request.tags.unwrap(), vec!["edge", "netgate", "enriched"]);
+    }
+
+    #[test]
+    fn test_device_payload_parsing() {
+        let processor = DeviceOrderProcessor::new();
+        let parsed = processor.parse_payload(serde_json::json!({
+            "name": "sw1", "device_type": 1, "role": 1, "site_slug": "dc1"
+        }));
+        assert!(matches!(parsed, Ok(OrderPayload::Device(_))));
+
+        let missing = processor.parse_payload(serde_json::json!({ "name": "sw1" }));
+        assert!(matches!(missing, Err(AppError::ValidationError(_))));
+    }
+
+    fn network_error(prefix: &str) -> String {
+        let order = CreateNetworkOrder {
+            prefix: prefix.to_string(),
+            description: None,
+            vlan: None,
+        };
+        match NetworkOrderProcessor::new().validate(&OrderPayload::Network(order)) {
+            Err(AppError::ValidationError(msg)) => msg,
+            other => panic!("Expected validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_network_order_prefix_validation() {
+        assert_eq!(network_error("10.0.0.0"), "Prefix '10.0.0.0' must be in CIDR notation (address/length)");
+        assert_eq!(network_error("10.0.0.300/24"), "Prefix address '10.0.0.300' is not a valid IP address");
+        assert_eq!(network_error("10.0.0.0/33"), "Prefix length must be between 0 and 32");
+        assert_eq!(network_error("10.0.0.1/24"), "Prefix 10.0.0.1/24 has host bits set; did you mean 10.0.0.0/24?");
+        assert_eq!(network_error("2001:db8::1/64"), "Prefix 2001:db8::1/64 has host bits set; did you mean 2001:db8::/64?");
+
+        let processor = NetworkOrderProcessor::new();
+        for prefix in ["10.0.0.0/24", "0.0.0.0/0", "192.168.1.7/32", "2001:db8::/32"] {
+            let order = CreateNetworkOrder {
+                prefix: prefix.to_string(),
+                description: None,
+                vlan: Some(100),
+            };
+            assert!(processor.validate(&OrderPayload::Network(order)).is_ok(), "{}", prefix);
+        }
+    }
+
+    #[test]
+    fn test_network_order_vlan_validation() {
+        let order = CreateNetworkOrder {
+            prefix: "10.0.0.0/24".to_string(),
+            description: None,
+            vlan: Some(0),
+        };
+        let result = NetworkOrderProcessor::new().validate(&OrderPayload::Network(order));
+        assert!(matches!(result, Err(AppError::ValidationError(msg)) if msg == "VLAN id must be a positive integer"));
+    }
+
+    #[test]
+    fn test_network_order_transform() {
+        let processor = NetworkOrderProcessor::new();
+        let order = CreateNetworkOrder {
+            prefix: " 10.20.0.0/16 ".to_string(),
+            description: Some("Office LAN".to_string()),
+            vlan: Some(100),
+        };
+        let mut request = processor.transform(OrderPayload::Network(order), None).unwrap();
+        processor.enrich_request(&mut request, &EnrichmentData::default()).unwrap();
+
+        let NetBoxResourceRequest::Prefix(request) = request else {
+            panic!("Expected a prefix request");
+        };
+        assert_eq!(request.prefix, "10.20.0.0/16");
+        assert_eq!(request.vlan, Some(100));
+        assert_eq!(request.status, Some(PrefixStatus::Active));
+        assert_eq!(request.tags.unwrap(), vec!["netgate", "enriched"]);
+    }
+
+    #[test]
+    fn test_processors_reject_other_payloads() {
+        let site_order = OrderPayload::Site(CreateSiteOrder {
+            name: "Test Site".to_string(),
+            description: None,
+            address: None,
+            region: None,
+            facility: None,
+            tags: vec![],
+            custom_fields: Default::default(),
+            priority: Default::default(),
+        });
+        assert!(DeviceOrderProcessor::new().validate(&site_order).is_err());
+        assert!(NetworkOrderProcessor::new().validate(&site_order).is_err());
+        assert!(VlanOrderProcessor::new().validate(&site_order).is_err());
+        assert!(IpAllocationOrderProcessor::new().validate(&site_order).is_err());
+        assert!(VirtualMachineOrderProcessor::new().validate(&site_order).is_err());
+    }
+
+    #[test]
+    fn test_vm_order_validation() {
+        let processor = VirtualMachineOrderProcessor::new();
+        let order = || CreateVmOrder {
+            name: " app01 ".to_string(),
+            cluster: 3,
+            vcpus: Some(2.0),
+            memory: Some(4096),
+            disk: Some(40),
+            role: None,
+            description: None,
+            tags: vec![],
+        };
+        let validate = |order: CreateVmOrder| processor.validate(&OrderPayload::VirtualMachine(order));
+
+        assert!(validate(order()).is_ok());
+        let invalid = [
+            (CreateVmOrder { name: " ".to_string(), ..order() }, "VM name cannot be empty"),
+            (CreateVmOrder { cluster: 0, ..order() }, "Cluster id must be a positive integer"),
+            (CreateVmOrder { vcpus: Some(0.0), ..order() }, "vCPUs must be greater than 0"),
+            (CreateVmOrder { vcpus: Some(f64::NAN), ..order() }, "vCPUs must be greater than 0"),
+            (CreateVmOrder { memory: Some(0), ..order() }, "Memory must be a positive number of MB"),
+            (CreateVmOrder { disk: Some(-1), ..order() }, "Disk must be a positive number of GB"),
+        ];
+        for (order, expected) in invalid {
+            let result = validate(order);
+            assert!(matches!(result, Err(AppError::ValidationError(ref msg)) if msg == expected), "{:?}", result);
+        }
+
+        let request = processor.transform(OrderPayload::VirtualMachine(order()), Some(10)).unwrap();
+        let NetBoxResourceRequest::VirtualMachine(request) = request else {
+            panic!("Expected a VM request");
+        };
+        assert_eq!(request.name, "app01");
+        assert_eq!(request.cluster, Some(3));
+        assert_eq!(request.tenant, Some(10));
+    }
+
+    #[test]
+    fn test_ip_order_validation() {
+        let processor = IpAllocationOrderProcessor::new();
+        let order = || AllocateIpOrder {
+            prefix_id: 5,
+            device_id: Some(12),
+            interface: Some("eth0".to_string()),
+            dns_name: Some("edge01.example.net.".to_string()),
+            description: None,
+            tags: vec![],
+        };
+        let validate = |order: AllocateIpOrder| processor.validate(&OrderPayload::IpAddress(order));
+
+        assert!(validate(order()).is_ok());
+        assert!(validate(AllocateIpOrder { device_id: None, interface: None, dns_name: None, ..order() }).is_ok());
+        let invalid = [
+            (AllocateIpOrder { prefix_id: 0, ..order() }, "Prefix id must be a positive integer"),
+            (AllocateIpOrder { interface: None, ..order() }, "An address assigned to a device needs an interface"),
+            (AllocateIpOrder { device_id: None, ..order() }, "An interface needs the device it belongs to"),
+            (AllocateIpOrder { interface: Some(" ".to_string()), ..order() }, "Interface name cannot be empty"),
+            (AllocateIpOrder { dns_name: Some("-edge.example.net".to_string()), ..order() }, "'-edge.example.net' is not a valid DNS name"),
+            (AllocateIpOrder { dns_name: Some("edge..net".to_string()), ..order() }, "'edge..net' is not a valid DNS name"),
+        ];
+        for (order, expected) in invalid {
+            let result = validate(order);
+            assert!(matches!(result, Err(AppError::ValidationError(ref msg)) if msg == expected), "{:?}", result);
+        }
+    }
+
+    fn vlan_order(vid: Option<i32>) -> CreateVlanOrder {
+        CreateVlanOrder {
+            site_id: 7,
+            name: " voice ".to_string(),
+            vid,
+            group: None,
+            description: None,
+            tags: vec![],
+        }
+    }
+
+    #[test]
+    fn test_vlan_order_validation() {
+        let processor = VlanOrderProcessor::new()
+            .with_ranges(VlanRanges::new().with_range("tenant1", VidRange::new(100, 199).unwrap()));
+        let validate = |order: CreateVlanOrder| processor.validate_for_tenant(&OrderPayload::Vlan(order), &"tenant1".to_string());
+
+        assert!(validate(vlan_order(Some(150))).is_ok());
+        assert!(validate(vlan_order(None)).is_ok());
+        let outside = validate(vlan_order(Some(250)));
+        assert!(matches!(outside, Err(AppError::ValidationError(msg)) if msg.contains("(100-199)")));
+        for vid in [0, 4095] {
+            let invalid = validate(vlan_order(Some(vid)));
+            assert!(matches!(invalid, Err(AppError::ValidationError(msg)) if msg == "VLAN id must be between 1 and 4094"));
         }
+        // Tenants without ranges may use any VID
+        assert!(processor.validate_for_tenant(&OrderPayload::Vlan(vlan_order(Some(4094))), &"tenant2".to_string()).is_ok());
+    }
+
+    #[test]
+    fn test_vlan_order_transform_auto_assigns_from_tenant_ranges() {
+        let processor = VlanOrderProcessor::new()
+            .with_ranges(VlanRanges::new().with_range("tenant1", VidRange::new(100, 199).unwrap()));
+
+        let request = processor.transform_for_tenant(OrderPayload::Vlan(vlan_order(None)), &"tenant1".to_string()).unwrap();
+        let NetBoxResourceRequest::Vlan { request, auto_assign } = request else {
+            panic!("Expected a VLAN request");
+        };
+        assert_eq!(request.name, "voice");
+        assert_eq!(request.site, Some(7));
+        assert_eq!(auto_assign, Some(vec![VidRange::new(100, 199).unwrap()]));
+
+        let request = processor.transform_for_tenant(OrderPayload::Vlan(vlan_order(Some(120))), &"tenant1".to_string()).unwrap();
+        let NetBoxResourceRequest::Vlan { request, auto_assign } = request else {
+            panic!("Expected a VLAN request");
+        };
+        assert_eq!(request.vid, 120);
+        assert_eq!(auto_assign, None);
     }
 }
 
@@ -0,0 +1,526 @@
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::business::catalog::DeviceCatalog;
+use crate::error::AppError;
+use crate::netbox::models::{NetBoxDevice, NetBoxPrefix, NetBoxRack, NetBoxSite};
+use crate::netbox::tenant_client::{TenantAwareNetBoxClient, TenantScopedPage};
+use crate::security::tenant::TenantId;
+
+/// Page size used when reading a site's devices, racks and prefixes
+const REPORT_PAGE_SIZE: u32 = 1000;
+
+/// Sites reported on at once by the tenant-wide report
+const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Height assumed for a racked device whose device type height isn't known
+const DEFAULT_DEVICE_HEIGHT: f64 = 1.0;
+
+/// A site's devices
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Object)]
+pub struct DeviceUsage {
+    pub count: Option<u32>,
+    /// Devices mounted at a position in one of the site's racks
+    pub racked: Option<u32>,
+    /// Why the devices couldn't be read; the counts are then absent
+    pub error: Option<String>,
+}
+
+/// A site's racks and how full they are
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Object)]
+pub struct RackUsage {
+    pub count: Option<u32>,
+    /// Rack units across the site's racks
+    pub total_units: Option<u32>,
+    /// Rack units taken by racked devices; absent when the devices couldn't be read
+    pub used_units: Option<f64>,
+    /// `used_units` as a percentage of `total_units`
+    pub utilization: Option<f64>,
+    /// Why the racks couldn't be read; the counts are then absent
+    pub error: Option<String>,
+}
+
+/// One prefix and how much of it is in use
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Object)]
+pub struct PrefixUtilization {
+    pub id: Option<i32>,
+    pub prefix: String,
+    /// Percentage in use, as NetBox reports it
+    pub utilization: Option<f64>,
+}
+
+/// A site's prefixes
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Object)]
+pub struct PrefixUsage {
+    pub count: Option<u32>,
+    pub prefixes: Vec<PrefixUtilization>,
+    /// Why the prefixes couldn't be read; the count is then absent
+    pub error: Option<String>,
+}
+
+/// Capacity and utilization of one site
+///
+/// Each section is read separately; one that fails carries an `error` and
+/// leaves the others intact.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Object)]
+pub struct SiteReport {
+    pub site_id: i32,
+    pub site_name: String,
+    pub devices: DeviceUsage,
+    pub racks: RackUsage,
+    pub prefixes: PrefixUsage,
+    /// Whether every section was read
+    pub complete: bool,
+}
+
+/// Sums across a tenant's sites, over the sections that were read
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Object)]
+pub struct CapacityTotals {
+    pub sites: u32,
+    pub devices: u32,
+    pub racks: u32,
+    pub total_units: u32,
+    pub used_units: f64,
+    /// `used_units` as a percentage of `total_units`, over sites whose racks and devices were both read
+    pub utilization: Option<f64>,
+    pub prefixes: u32,
+}
+
+/// Capacity and utilization of all of a tenant's sites
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Object)]
+pub struct CapacityReport {
+    /// One report per site, by site id
+    pub sites: Vec<SiteReport>,
+    pub totals: CapacityTotals,
+    /// Whether every section of every site was read
+    pub complete: bool,
+}
+
+/// Builds capacity reports from a tenant's devices, racks and prefixes
+///
+/// Everything is read through the tenant-aware client, so reports only ever
+/// cover the tenant's own objects. Rack units in use are worked out from the
+/// position of each racked device and the height of its device type, taken
+/// from the device catalog when one is set.
+#[derive(Clone)]
+pub struct SiteReportService {
+    client: Arc<TenantAwareNetBoxClient>,
+    catalog: Option<Arc<DeviceCatalog>>,
+    concurrency: usize,
+}
+
+impl SiteReportService {
+    pub fn new(client: Arc<TenantAwareNetBoxClient>) -> Self {
+        Self {
+            client,
+            catalog: None,
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+
+    /// Catalog device type heights are read from; without one every racked device counts as 1U
+    pub fn with_catalog(mut self, catalog: Arc<DeviceCatalog>) -> Self {
+        self.catalog = Some(catalog);
+        self
+    }
+
+    /// How many sites the tenant-wide report reads at once
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Report on one of the tenant's sites
+    ///
+    /// Sites that don't exist and sites of other tenants are both `NotFound`.
+    pub async fn site_report(&self, tenant_id: &TenantId, site_id: i32) -> Result<SiteReport, AppError> {
+        let site = self
+            .client
+            .get_sites_by_ids(tenant_id, &[site_id])
+            .await?
+            .into_iter()
+            .find_map(|(_, site)| site)
+            .ok_or_else(|| AppError::NotFound(format!("Site {} not found", site_id)))?;
+        let heights = self.device_heights().await;
+        Ok(self.report_for(tenant_id, site, &heights).await)
+    }
+
+    /// Report on every site of the tenant, a few sites at a time
+    pub async fn capacity_report(&self, tenant_id: &TenantId) -> Result<CapacityReport, AppError> {
+        let sites = all_pages(|offset| self.client.list_sites(tenant_id, Some(REPORT_PAGE_SIZE), Some(offset))).await?;
+        let heights = Arc::new(self.device_heights().await);
+        let permits = Arc::new(Semaphore::new(self.concurrency));
+
+        let mut tasks = JoinSet::new();
+        for site in sites {
+            let service = self.clone();
+            let tenant_id = tenant_id.clone();
+            let heights = heights.clone();
+            let permits = permits.clone();
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await.expect("report semaphore is never closed");
+                service.report_for(&tenant_id, site, &heights).await
+            });
+        }
+
+        let mut reports = Vec::new();
+        while let Some(report) = tasks.join_next().await {
+            reports.push(report.map_err(|e| AppError::Internal(anyhow::anyhow!("Site report task failed: {}", e)))?);
+        }
+        reports.sort_by_key(|report| report.site_id);
+
+        Ok(CapacityReport {
+            totals: totals(&reports),
+            complete: reports.iter().all(|report| report.complete),
+            sites: reports,
+        })
+    }
+
+    /// Device type heights by id; empty when there is no catalog or it can't be read
+    async fn device_heights(&self) -> HashMap<i32, f64> {
+        let Some(ref catalog) = self.catalog else {
+            return HashMap::new();
+        };
+        match catalog.snapshot().await {
+            Ok(snapshot) => snapshot
+                .device_types
+                .iter()
+                .filter_map(|device_type| Some((device_type.id?, device_type.u_height?)))
+                .collect(),
+            Err(e) => {
+                tracing::warn!("Device catalog unavailable, counting racked devices as 1U: {}", e);
+                HashMap::new()
+            }
+        }
+    }
+
+    async fn report_for(&self, tenant_id: &TenantId, site: NetBoxSite, heights: &HashMap<i32, f64>) -> SiteReport {
+        let site_id = site.id.unwrap_or_default();
+        let client = &self.client;
+        let (devices, racks, prefixes) = tokio::join!(
+            all_pages(|offset| client.list_devices(
+                tenant_id,
+                Some(site_id),
+                None,
+                None,
+                Some(REPORT_PAGE_SIZE),
+                Some(offset)
+            )),
+            all_pages(|offset| client.list_racks(tenant_id, Some(site_id), Some(REPORT_PAGE_SIZE), Some(offset))),
+            all_pages(|offset| client.list_prefixes(tenant_id, Some(site_id), Some(REPORT_PAGE_SIZE), Some(offset))),
+        );
+
+        let (racks, rack_ids) = rack_usage(racks, devices.as_deref().ok(), heights);
+        let devices = device_usage(devices, &racks, &rack_ids);
+        let prefixes = prefix_usage(prefixes);
+        for error in [&devices.error, &racks.error, &prefixes.error].into_iter().flatten() {
+            tracing::warn!("Site {} report for tenant {} is partial: {}", site_id, tenant_id, error);
+        }
+
+        SiteReport {
+            site_id,
+            site_name: site.name,
+            complete: devices.error.is_none() && racks.error.is_none() && prefixes.error.is_none(),
+            devices,
+            racks,
+            prefixes,
+        }
+    }
+}
+
+/// Read every page of a tenant-scoped listing
+pub(crate) async fn all_pages<T, F, Fut>(mut fetch: F) -> Result<Vec<T>, AppError>
+where
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = Result<TenantScopedPage<T>, AppError>>,
+{
+    let mut items = Vec::new();
+    let mut offset = 0;
+    loop {
+        let page = fetch(offset).await?;
+        // Items dropped for belonging to other tenants still advance the offset
+        let returned = page.results.len() + page.filtered_out;
+        offset += returned as u32;
+        items.extend(page.results);
+        if !page.has_more || returned == 0 {
+            return Ok(items);
+        }
+    }
+}
+
+/// The racks' units, and the units taken by devices mounted in them
+fn rack_usage(
+    racks: Result<Vec<NetBoxRack>, AppError>,
+    devices: Option<&[NetBoxDevice]>,
+    heights: &HashMap<i32, f64>,
+) -> (RackUsage, HashSet<i32>) {
+    let racks = match racks {
+        Ok(racks) => racks,
+        Err(e) => return (RackUsage { error: Some(e.to_string()), ..Default::default() }, HashSet::new()),
+    };
+    let rack_ids: HashSet<i32> = racks.iter().filter_map(|rack| rack.id).collect();
+    let total_units: u32 = racks.iter().filter_map(|rack| rack.u_height).map(|height| height.max(0) as u32).sum();
+    let used_units = devices.map(|devices| {
+        devices
+            .iter()
+            .filter(|device| is_racked(device, &rack_ids))
+            .map(|device| {
+                device
+                    .device_type
+                    .and_then(|device_type| heights.get(&device_type).copied())
+                    .unwrap_or(DEFAULT_DEVICE_HEIGHT)
+            })
+            .sum::<f64>()
+    });
+
+    let usage = RackUsage {
+        count: Some(racks.len() as u32),
+        total_units: Some(total_units),
+        utilization: used_units.and_then(|used| percentage(used, total_units)),
+        used_units,
+        error: None,
+    };
+    (usage, rack_ids)
+}
+
+fn device_usage(
+    devices: Result<Vec<NetBoxDevice>, AppError>,
+    racks: &RackUsage,
+    rack_ids: &HashSet<i32>,
+) -> DeviceUsage {
+    match devices {
+        Ok(devices) => DeviceUsage {
+            count: Some(devices.len() as u32),
+            // Unknown when the racks couldn't be read
+            racked: racks
+                .error
+                .is_none()
+                .then(|| devices.iter().filter(|device| is_racked(device, rack_ids)).count() as u32),
+            error: None,
+        },
+        Err(e) => DeviceUsage { error: Some(e.to_string()), ..Default::default() },
+    }
+}
+
+fn prefix_usage(prefixes: Result<Vec<NetBoxPrefix>, AppError>) -> PrefixUsage {
+    match prefixes {
+        Ok(prefixes) => PrefixUsage {
+            count: Some(prefixes.len() as u32),
+            prefixes: prefixes
+                .into_iter()
+                .map(|prefix| PrefixUtilization {
+                    id: prefix.id,
+                    prefix: prefix.prefix,
+                    utilization: prefix.utilization,
+                })
+                .collect(),
+            error: None,
+        },
+        Err(e) => PrefixUsage { error: Some(e.to_string()), ..Default::default() },
+    }
+}
+
+/// Whether a device is mounted at a position in one of `rack_ids`
+fn is_racked(device: &NetBoxDevice, rack_ids: &HashSet<i32>) -> bool {
+    device.position.is_some() && device.rack.is_some_and(|rack| rack_ids.contains(&rack))
+}
+
+/// `part` as a percentage of `whole`, to one decimal place
+fn percentage(part: f64, whole: u32) -> Option<f64> {
+    (whole > 0).then(|| (part / whole as f64 * 1000.0).round() / 10.0)
+}
+
+fn totals(reports: &[SiteReport]) -> CapacityTotals {
+    let mut totals = CapacityTotals { sites: reports.len() as u32, ..Default::default() };
+    let mut measured_units = 0;
+    for report in reports {
+        totals.devices += report.devices.count.unwrap_or(0);
+        totals.racks += report.racks.count.unwrap_or(0);
+        totals.total_units += report.racks.total_units.unwrap_or(0);
+        totals.prefixes += report.prefixes.count.unwrap_or(0);
+        if let (Some(used), Some(units)) = (report.racks.used_units, report.racks.total_units) {
+            totals.used_units += used;
+            measured_units += units;
+        }
+    }
+    totals.utilization = percentage(totals.used_units, measured_units);
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::netbox::NetBoxClient;
+    use crate::security::tenant::{TenantAccessControl, TenantMappingService};
+    use serde_json::json;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// A service over the raw client, so failed sub-requests aren't retried
+    fn create_service(mock_server: &MockServer) -> SiteReportService {
+        let config = Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let client = Arc::new(NetBoxClient::new(config).unwrap());
+        let mappings = HashMap::from([("tenant-1".to_string(), 10)]);
+        let access_control = Arc::new(TenantAccessControl::new(TenantMappingService::from(mappings)));
+        SiteReportService::new(Arc::new(TenantAwareNetBoxClient::new(client, access_control)))
+    }
+
+    async fn mount_list(mock_server: &MockServer, list_path: &str, site_id: i32, results: serde_json::Value) {
+        Mock::given(method("GET"))
+            .and(path(list_path))
+            .and(query_param("site_id", site_id.to_string()))
+            .and(query_param("tenant_id", "10"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "count": 1, "results": results })))
+            .mount(mock_server)
+            .await;
+    }
+
+    async fn mount_failure(mock_server: &MockServer, list_path: &str, site_id: i32) {
+        Mock::given(method("GET"))
+            .and(path(list_path))
+            .and(query_param("site_id", site_id.to_string()))
+            .respond_with(ResponseTemplate::new(500).set_body_string("boom"))
+            .mount(mock_server)
+            .await;
+    }
+
+    /// Site 1: two racks of 42U and 48U, a 2U racked device, an unracked device and one prefix
+    async fn mount_site_one(mock_server: &MockServer) {
+        mount_list(mock_server, "/api/dcim/devices/", 1, json!([
+            {"id": 1, "name": "leaf1", "site": 1, "tenant": 10, "rack": 5, "position": 10.0, "device_type": 7},
+            {"id": 2, "name": "spare", "site": 1, "tenant": 10}
+        ]))
+        .await;
+        mount_list(mock_server, "/api/dcim/racks/", 1, json!([
+            {"id": 5, "name": "R1", "site": 1, "tenant": 10, "u_height": 42},
+            {"id": 6, "name": "R2", "site": 1, "tenant": 10, "u_height": 48}
+        ]))
+        .await;
+        mount_list(mock_server, "/api/ipam/prefixes/", 1, json!([
+            {"id": 3, "prefix": "10.0.0.0/24", "site": 1, "tenant": 10, "utilization": 37.5}
+        ]))
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_site_report_counts_rack_units_and_prefixes() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .and(query_param("id__in", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 1, "results": [{"id": 1, "name": "Berlin", "tenant": 10}]
+            })))
+            .mount(&mock_server)
+            .await;
+        // Another tenant's site reads the same as a missing one
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .and(query_param("id__in", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 1, "results": [{"id": 2, "name": "Theirs", "tenant": 20}]
+            })))
+            .mount(&mock_server)
+            .await;
+        mount_site_one(&mock_server).await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/device-types/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 1, "results": [{"id": 7, "model": "C9300", "slug": "c9300", "u_height": 2.0}]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/device-roles/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "count": 0, "results": [] })))
+            .mount(&mock_server)
+            .await;
+        let catalog_client = Arc::new(crate::netbox::ResilientNetBoxClient::new(Arc::new(
+            NetBoxClient::new(Config {
+                netbox_url: mock_server.uri(),
+                netbox_token: "test-token".to_string(),
+                ..Default::default()
+            })
+            .unwrap(),
+        )));
+        let service = create_service(&mock_server).with_catalog(Arc::new(DeviceCatalog::new(catalog_client)));
+
+        let report = service.site_report(&"tenant-1".to_string(), 1).await.unwrap();
+
+        assert!(report.complete);
+        assert_eq!(report.site_name, "Berlin");
+        assert_eq!(report.devices, DeviceUsage { count: Some(2), racked: Some(1), error: None });
+        assert_eq!(report.racks.count, Some(2));
+        assert_eq!(report.racks.total_units, Some(90));
+        assert_eq!(report.racks.used_units, Some(2.0));
+        assert_eq!(report.racks.utilization, Some(2.2));
+        assert_eq!(report.prefixes.prefixes[0].utilization, Some(37.5));
+
+        let missing = service.site_report(&"tenant-1".to_string(), 2).await;
+        assert!(matches!(missing, Err(AppError::NotFound(_))), "{:?}", missing);
+    }
+
+    #[tokio::test]
+    async fn test_capacity_report_marks_failed_sections() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .and(query_param("tenant_id", "10"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 2, "results": [
+                    {"id": 2, "name": "Paris", "tenant": 10},
+                    {"id": 1, "name": "Berlin", "tenant": 10}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+        mount_site_one(&mock_server).await;
+        // Site 2's racks and devices fail; its prefixes still come through
+        mount_failure(&mock_server, "/api/dcim/racks/", 2).await;
+        mount_failure(&mock_server, "/api/dcim/devices/", 2).await;
+        mount_list(&mock_server, "/api/ipam/prefixes/", 2, json!([
+            {"id": 4, "prefix": "10.1.0.0/24", "site": 2, "tenant": 10}
+        ]))
+        .await;
+        let service = create_service(&mock_server).with_concurrency(1);
+
+        let report = service.capacity_report(&"tenant-1".to_string()).await.unwrap();
+
+        assert!(!report.complete);
+        let ids: Vec<i32> = report.sites.iter().map(|site| site.site_id).collect();
+        assert_eq!(ids, vec![1, 2]);
+        assert!(report.sites[0].complete);
+        // Without a catalog the racked device counts as 1U
+        assert_eq!(report.sites[0].racks.used_units, Some(1.0));
+
+        let paris = &report.sites[1];
+        assert!(paris.racks.error.is_some());
+        assert_eq!(paris.racks.count, None);
+        assert!(paris.devices.error.is_some());
+        assert_eq!(paris.prefixes.count, Some(1));
+        assert_eq!(paris.prefixes.error, None);
+
+        assert_eq!(
+            report.totals,
+            CapacityTotals {
+                sites: 2,
+                devices: 2,
+                racks: 2,
+                total_units: 90,
+                used_units: 1.0,
+                utilization: Some(1.1),
+                prefixes: 2,
+            }
+        );
+    }
+}
@@ -1,5 +1,11 @@
-use crate::domain::CreateSiteOrder;
-use crate::netbox::models::{CreateSiteRequest, SiteStatus};
+use crate::domain::{CreateSiteOrder, UpdateSiteOrder};
+use crate::netbox::models::{CreateSiteRequest, SiteStatus, UpdateSiteRequest};
+use serde::{Deserialize, Serialize};
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use tracing::warn;
 
 /// Transform a CreateSiteOrder to a NetBox CreateSiteRequest
 pub struct OrderTransformer {
@@ -28,36 +34,119 @@ impl OrderTransformer {
     }
 
     /// Transform a CreateSiteOrder to CreateSiteRequest
+    ///
+    /// The tenant's profile, if any, supplies defaults underneath the order:
+    /// values set explicitly on the order always win.
     pub fn transform_site_order(
         &self,
         order: CreateSiteOrder,
         tenant_id: Option<i32>,
+        profile: Option<&TransformationProfile>,
     ) -> CreateSiteRequest {
         // Generate slug from name (lowercase, replace spaces with hyphens, remove special chars)
         let slug = self.generate_slug(&order.name);
+        let default_profile = TransformationProfile::default();
+        let profile = profile.unwrap_or(&default_profile);
 
-        CreateSiteRequest {
-            name: order.name,
-            slug: Some(slug),
-            description: order.description,
-            status: Some(self.default_status.clone()),
-            region: None, // Can be enriched later based on business rules
-            tenant: tenant_id,
-            facility: None,
-            physical_address: order.address.clone(),
-            shipping_address: order.address,
-            latitude: None, // Can be enriched from address geocoding
-            longitude: None,
-            contact_name: None,
-            contact_phone: None,
-            contact_email: None,
-            comments: Some(format!("Created via NetGate order portal")),
-            tags: Some(vec!["netgate".to_string(), "order-portal".to_string()]),
+        let facility = order.facility.or_else(|| {
+            profile
+                .facility_prefix
+                .as_ref()
+                .map(|prefix| format!("{}-{}", prefix, slug.to_uppercase()).chars().take(50).collect())
+        });
+        let mut tags = vec!["netgate".to_string(), "order-portal".to_string()];
+        for tag in profile.tags.iter().chain(&order.tags) {
+            let tag = self.normalize_tag(tag);
+            if !tag.is_empty() && !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        // Order values win over profile defaults
+        let mut custom_fields: serde_json::Map<String, serde_json::Value> =
+            profile.custom_fields.clone().into_iter().collect();
+        custom_fields.extend(order.custom_fields);
+        let custom_fields = (!custom_fields.is_empty()).then_some(serde_json::Value::Object(custom_fields));
+
+        let mut request = CreateSiteRequest::builder(order.name)
+            .with_slug(slug)
+            .with_status(profile.status.unwrap_or(self.default_status))
+            .with_comments("Created via NetGate order portal")
+            .with_tags(tags);
+        if let Some(description) = order.description {
+            request = request.with_description(description);
+        }
+        if let Some(region) = order.region.or(profile.region) {
+            request = request.with_region(region);
+        }
+        if let Some(tenant_id) = tenant_id {
+            request = request.with_tenant(tenant_id);
+        }
+        if let Some(facility) = facility {
+            request = request.with_facility(facility);
+        }
+        if let Some(address) = order.address {
+            // Latitude/longitude can be enriched from address geocoding
+            request = request
+                .with_physical_address(address.clone())
+                .with_shipping_address(address);
         }
+        if let Some(custom_fields) = custom_fields {
+            request = request.with_custom_fields(custom_fields);
+        }
+        request.build()
+    }
+
+    /// Transform an UpdateSiteOrder to a sparse UpdateSiteRequest
+    ///
+    /// Only fields set on the order are set on the request. The slug is kept
+    /// when the site is renamed, since orders look sites up by it. Tags are
+    /// normalized and prefixed like a new site's; the profile's other defaults
+    /// only apply to new sites.
+    pub fn transform_site_update(
+        &self,
+        order: UpdateSiteOrder,
+        profile: Option<&TransformationProfile>,
+    ) -> UpdateSiteRequest {
+        let mut request = UpdateSiteRequest::builder();
+        if let Some(name) = order.name {
+            request = request.with_name(name.trim());
+        }
+        if let Some(description) = order.description {
+            request = request.with_description(description);
+        }
+        if let Some(status) = order.status.and_then(|status| status.parse().ok()) {
+            request = request.with_status(status);
+        }
+        if let Some(region) = order.region {
+            request = request.with_region(region);
+        }
+        if let Some(tenant) = order.tenant {
+            request = request.with_tenant(tenant);
+        }
+        if let Some(facility) = order.facility {
+            request = request.with_facility(facility);
+        }
+        if let Some(address) = order.address {
+            request = request
+                .with_physical_address(address.clone())
+                .with_shipping_address(address);
+        }
+        if let Some(order_tags) = order.tags {
+            let mut tags = vec!["netgate".to_string(), "order-portal".to_string()];
+            let profile_tags = profile.map(|profile| profile.tags.as_slice()).unwrap_or_default();
+            for tag in profile_tags.iter().chain(&order_tags) {
+                let tag = self.normalize_tag(tag);
+                if !tag.is_empty() && !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+            request = request.with_tags(tags);
+        }
+        request.build()
     }
 
     /// Generate a URL-friendly slug from a name
-    fn generate_slug(&self, name: &str) -> String {
+    pub(crate) fn generate_slug(&self, name: &str) -> String {
         name.to_lowercase()
             .chars()
             .map(|c| match c {
@@ -75,6 +164,11 @@ impl OrderTransformer {
             .collect()
     }
 
+    /// Normalize a tag to the slug form NetBox uses (e.g. "Edge POP" -> "edge-pop")
+    pub fn normalize_tag(&self, tag: &str) -> String {
+        self.generate_slug(tag)
+    }
+
     /// Enrich site request with additional business logic
     pub fn enrich_site_request(
         &self,
@@ -101,6 +195,16 @@ impl OrderTransformer {
             request.contact_phone = Some(phone.clone());
         }
 
+        // Enrichment custom fields take precedence over order and profile values
+        if !enrichment_data.custom_fields.is_empty() {
+            let mut custom_fields = match request.custom_fields.take() {
+                Some(serde_json::Value::Object(fields)) => fields,
+                _ => serde_json::Map::new(),
+            };
+            custom_fields.extend(enrichment_data.custom_fields.clone());
+            request.custom_fields = Some(serde_json::Value::Object(custom_fields));
+        }
+
         // Merge tags
         if let Some(ref enrichment_tags) = enrichment_data.tags {
             let mut tags = request.tags.unwrap_or_default();
@@ -114,6 +218,98 @@ impl OrderTransformer {
     }
 }
 
+/// Per-tenant defaults layered under site orders by [`OrderTransformer`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransformationProfile {
+    /// Site status used instead of the transformer's default
+    pub status: Option<SiteStatus>,
+    /// NetBox region id for orders that don't name one
+    pub region: Option<i32>,
+    /// Facility naming scheme: `<prefix>-<SLUG>` for orders without a facility
+    pub facility_prefix: Option<String>,
+    /// Tags added to every site of the tenant
+    pub tags: Vec<String>,
+    /// NetBox custom field values set on every site of the tenant
+    pub custom_fields: HashMap<String, serde_json::Value>,
+}
+
+/// Transformation profiles keyed by tenant id
+///
+/// Clones share the same profiles, so a profile set at runtime (e.g. when a
+/// tenant is onboarded) applies to every order service holding them.
+#[derive(Debug, Clone, Default)]
+pub struct TransformationProfiles {
+    profiles: Arc<RwLock<HashMap<String, TransformationProfile>>>,
+}
+
+impl TransformationProfiles {
+    /// Create an empty set; every tenant gets the built-in mapping
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the profile of a tenant
+    pub fn with_profile(self, tenant_id: impl Into<String>, profile: TransformationProfile) -> Self {
+        self.set_profile(tenant_id, profile);
+        self
+    }
+
+    /// Parse profiles from a JSON object mapping tenant ids to profiles
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            profiles: Arc::new(RwLock::new(serde_json::from_str(json)?)),
+        })
+    }
+
+    /// Load profiles from a JSON file
+    pub fn from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_json(&json).map_err(|e| anyhow::anyhow!("Invalid profiles in {}: {}", path.display(), e))
+    }
+
+    /// Load profiles from the file named by TRANSFORMATION_PROFILES_FILE
+    ///
+    /// An unreadable or invalid file is logged and ignored, so a bad profile
+    /// file doesn't keep the service from starting.
+    pub fn from_env() -> Self {
+        Self::from_env_file("TRANSFORMATION_PROFILES_FILE")
+    }
+
+    /// Load profiles from the file named by the `var` env var, if set
+    pub fn from_env_file(var: &str) -> Self {
+        let Ok(path) = std::env::var(var) else {
+            return Self::new();
+        };
+        Self::from_file(&path).unwrap_or_else(|e| {
+            warn!("Ignoring profiles from {}: {}", var, e);
+            Self::new()
+        })
+    }
+
+    /// Profile of a tenant, if one is configured
+    pub fn profile(&self, tenant_id: &str) -> Option<TransformationProfile> {
+        self.profiles.read().get(tenant_id).cloned()
+    }
+
+    /// Set a tenant's profile, returning the one it replaces
+    pub fn set_profile(&self, tenant_id: impl Into<String>, profile: TransformationProfile) -> Option<TransformationProfile> {
+        self.profiles.write().insert(tenant_id.into(), profile)
+    }
+
+    /// Remove a tenant's profile, returning it
+    pub fn remove_profile(&self, tenant_id: &str) -> Option<TransformationProfile> {
+        self.profiles.write().remove(tenant_id)
+    }
+
+    /// Check if no profile is configured
+    pub fn is_empty(&self) -> bool {
+        self.profiles.read().is_empty()
+    }
+}
+
 /// Data for enriching site requests
 #[derive(Debug, Clone, Default)]
 pub struct SiteEnrichmentData {
@@ -123,6 +319,7 @@ pub struct SiteEnrichmentData {
     pub contact_email: Option<String>,
     pub contact_phone: Option<String>,
     pub tags: Option<Vec<String>>,
+    pub custom_fields: HashMap<String, serde_json::Value>,
 }
 
 #[cfg(test)]
@@ -136,9 +333,14 @@ mod tests {
             name: "Test Site".to_string(),
             description: Some("Test Description".to_string()),
             address: Some("123 Main St".to_string()),
+            region: None,
+            facility: None,
+            tags: vec![],
+            custom_fields: Default::default(),
+            priority: Default::default(),
         };
 
-        let request = transformer.transform_site_order(order, Some(10));
+        let request = transformer.transform_site_order(order, Some(10), None);
 
         assert_eq!(request.name, "Test Site");
         assert_eq!(request.description, Some("Test Description".to_string()));
@@ -167,9 +369,14 @@ mod tests {
             name: "Active Site".to_string(),
             description: None,
             address: None,
+            region: None,
+            facility: None,
+            tags: vec![],
+            custom_fields: Default::default(),
+            priority: Default::default(),
         };
 
-        let request = transformer.transform_site_order(order, None);
+        let request = transformer.transform_site_order(order, None, None);
         assert_eq!(request.status, Some(SiteStatus::Active));
     }
 
@@ -180,9 +387,14 @@ mod tests {
             name: "Test Site".to_string(),
             description: None,
             address: None,
+            region: None,
+            facility: None,
+            tags: vec![],
+            custom_fields: Default::default(),
+            priority: Default::default(),
         };
 
-        let mut request = transformer.transform_site_order(order, None);
+        let mut request = transformer.transform_site_order(order, None, None);
 
         let enrichment = SiteEnrichmentData {
             region_id: Some(5),
@@ -191,6 +403,7 @@ mod tests {
             contact_email: Some("john@example.com".to_string()),
             contact_phone: Some("+1-555-0123".to_string()),
             tags: Some(vec!["production".to_string()]),
+            custom_fields: HashMap::new(),
         };
 
         let enriched = transformer.enrich_site_request(request, &enrichment);
@@ -203,5 +416,141 @@ mod tests {
         assert!(enriched.tags.as_ref().unwrap().contains(&"production".to_string()));
         assert!(enriched.tags.as_ref().unwrap().contains(&"netgate".to_string()));
     }
-}
 
+    fn create_profiles() -> TransformationProfiles {
+        TransformationProfiles::from_json(
+            r#"{
+                "acme": {
+                    "status": "active",
+                    "region": 3,
+                    "facility_prefix": "ACME",
+                    "tags": ["acme", "netgate"],
+                    "custom_fields": {"cost_center": "CC-1"}
+                },
+                "globex": {"region": 7}
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn create_plain_order() -> CreateSiteOrder {
+        CreateSiteOrder {
+            name: "Edge Site".to_string(),
+            description: None,
+            address: None,
+            region: None,
+            facility: None,
+            tags: vec![],
+            custom_fields: Default::default(),
+            priority: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_profiles_differ_per_tenant() {
+        let transformer = OrderTransformer::new();
+        let profiles = create_profiles();
+
+        let acme = transformer.transform_site_order(create_plain_order(), None, profiles.profile("acme").as_ref());
+        let globex = transformer.transform_site_order(create_plain_order(), None, profiles.profile("globex").as_ref());
+
+        assert_eq!(acme.status, Some(SiteStatus::Active));
+        assert_eq!(acme.region, Some(3));
+        assert_eq!(acme.facility, Some("ACME-EDGE-SITE".to_string()));
+        assert_eq!(acme.tags, Some(vec!["netgate".to_string(), "order-portal".to_string(), "acme".to_string()]));
+        assert_eq!(acme.custom_fields, Some(serde_json::json!({"cost_center": "CC-1"})));
+
+        assert_eq!(globex.status, Some(SiteStatus::Planned));
+        assert_eq!(globex.region, Some(7));
+        assert_eq!(globex.facility, None);
+        assert_eq!(globex.custom_fields, None);
+    }
+
+    #[test]
+    fn test_order_values_override_profile() {
+        let transformer = OrderTransformer::new();
+        let profiles = create_profiles();
+        let order = CreateSiteOrder {
+            region: Some(42),
+            facility: Some("Building 9".to_string()),
+            ..create_plain_order()
+        };
+
+        let request = transformer.transform_site_order(order, None, profiles.profile("acme").as_ref());
+
+        assert_eq!(request.region, Some(42));
+        assert_eq!(request.facility, Some("Building 9".to_string()));
+        // Defaults the order doesn't set still come from the profile
+        assert_eq!(request.status, Some(SiteStatus::Active));
+    }
+
+    #[test]
+    fn test_unknown_tenant_uses_built_in_mapping() {
+        let transformer = OrderTransformer::new();
+        let profiles = create_profiles();
+
+        let with_lookup = transformer.transform_site_order(create_plain_order(), None, profiles.profile("initech").as_ref());
+        let without = transformer.transform_site_order(create_plain_order(), None, None);
+
+        assert_eq!(serde_json::to_value(with_lookup).unwrap(), serde_json::to_value(without).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_profiles_are_rejected() {
+        assert!(TransformationProfiles::from_json(r#"{"acme": {"status": "bogus"}}"#).is_err());
+        assert!(TransformationProfiles::from_file("/nonexistent/profiles.json").is_err());
+    }
+
+    #[test]
+    fn test_custom_field_precedence() {
+        let transformer = OrderTransformer::new();
+        let profiles = create_profiles();
+        let order = CreateSiteOrder {
+            custom_fields: HashMap::from([
+                ("cost_center".to_string(), serde_json::json!("CC-ORDER")),
+                ("environment".to_string(), serde_json::json!("staging")),
+            ]),
+            ..create_plain_order()
+        };
+
+        // Order overrides the profile's default
+        let request = transformer.transform_site_order(order, None, profiles.profile("acme").as_ref());
+        assert_eq!(
+            request.custom_fields,
+            Some(serde_json::json!({"cost_center": "CC-ORDER", "environment": "staging"}))
+        );
+
+        // Enrichment overrides the order
+        let enrichment = SiteEnrichmentData {
+            custom_fields: HashMap::from([("environment".to_string(), serde_json::json!("production"))]),
+            ..Default::default()
+        };
+        let enriched = transformer.enrich_site_request(request, &enrichment);
+        assert_eq!(
+            enriched.custom_fields,
+            Some(serde_json::json!({"cost_center": "CC-ORDER", "environment": "production"}))
+        );
+    }
+
+    #[test]
+    fn test_order_tags_are_normalized() {
+        let transformer = OrderTransformer::new();
+        let profiles = create_profiles();
+        let order = CreateSiteOrder {
+            tags: vec!["Edge POP".to_string(), "ACME".to_string(), "  ".to_string()],
+            ..create_plain_order()
+        };
+
+        let request = transformer.transform_site_order(order, None, profiles.profile("acme").as_ref());
+
+        assert_eq!(
+            request.tags,
+            Some(vec![
+                "netgate".to_string(),
+                "order-portal".to_string(),
+                "acme".to_string(),
+                "edge-pop".to_string(),
+            ])
+        );
+    }
+}
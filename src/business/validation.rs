@@ -1,5 +1,9 @@
-use crate::domain::CreateSiteOrder;
-use std::collections::HashSet;
+use crate::business::address::{AddressNormalizer, NormalizedAddress};
+use crate::business::payload_schema::SchemaViolation;
+use crate::domain::{CreateSiteOrder, UpdateSiteOrder};
+use crate::netbox::custom_fields::{CustomFieldDefinition, CustomFieldKind, CustomFieldSchema};
+use crate::netbox::models::SiteStatus;
+use std::collections::{HashMap, HashSet};
 
 /// Validation errors
 #[derive(Debug, Clone, PartialEq)]
@@ -9,7 +13,28 @@ pub enum ValidationError {
     InvalidNameFormat,
     DescriptionTooLong,
     AddressTooLong,
+    /// Address that couldn't be verified, refused in strict mode
+    UnverifiedAddress(Vec<String>),
     InvalidCharacters(String),
+    /// Custom field keys missing from the allowlist
+    UnknownCustomFields(Vec<String>),
+    /// Update order names no site, or names it twice
+    InvalidSiteReference(String),
+    InvalidStatus(String),
+    /// Update order sets no field
+    EmptyUpdate,
+    /// `last_updated` isn't an RFC 3339 timestamp
+    InvalidLastUpdated(String),
+    /// The site status transition matrix doesn't allow this change
+    DisallowedStatusTransition { from: SiteStatus, to: SiteStatus },
+    /// Custom field keys NetBox defines no field for on this object type
+    UndefinedCustomFields { object_type: String, keys: Vec<String> },
+    /// Custom field value of the wrong type for its NetBox field
+    InvalidCustomFieldType { field: String, expected: CustomFieldKind },
+    /// Select field value that isn't one of the field's choices
+    InvalidCustomFieldChoice { field: String, value: String, choices: Vec<String> },
+    /// Order payload that doesn't match its order type's schema
+    PayloadSchema { order_type: String, violations: Vec<SchemaViolation> },
 }
 
 impl std::fmt::Display for ValidationError {
@@ -20,9 +45,97 @@ impl std::fmt::Display for ValidationError {
             ValidationError::InvalidNameFormat => write!(f, "Site name contains invalid characters"),
             ValidationError::DescriptionTooLong => write!(f, "Description exceeds maximum length of 500 characters"),
             ValidationError::AddressTooLong => write!(f, "Address exceeds maximum length of 200 characters"),
+            ValidationError::UnverifiedAddress(problems) => {
+                write!(f, "Address could not be verified: {}", problems.join("; "))
+            }
             ValidationError::InvalidCharacters(field) => write!(f, "Invalid characters in field: {}", field),
+            ValidationError::UnknownCustomFields(keys) => {
+                write!(f, "Custom fields not allowed: {}", keys.join(", "))
+            }
+            ValidationError::InvalidSiteReference(msg) => write!(f, "{}", msg),
+            ValidationError::InvalidStatus(msg) => write!(f, "{}", msg),
+            ValidationError::EmptyUpdate => write!(f, "Site update order changes no fields"),
+            ValidationError::InvalidLastUpdated(value) => {
+                write!(f, "last_updated '{}' is not an RFC 3339 timestamp", value)
+            }
+            ValidationError::DisallowedStatusTransition { from, to } => {
+                write!(f, "Site status can't change from {} to {}", from.as_str(), to.as_str())
+            }
+            ValidationError::UndefinedCustomFields { object_type, keys } => {
+                write!(f, "Custom fields not defined in NetBox for {}: {}", object_type, keys.join(", "))
+            }
+            ValidationError::InvalidCustomFieldType { field, expected } => {
+                write!(f, "Custom field '{}' must be {}", field, expected.describe())
+            }
+            ValidationError::InvalidCustomFieldChoice { field, value, choices } => write!(
+                f,
+                "'{}' is not a valid choice for custom field '{}'; expected one of: {}",
+                value,
+                field,
+                choices.join(", ")
+            ),
+            ValidationError::PayloadSchema { order_type, violations } => {
+                let violations: Vec<String> = violations.iter().map(ToString::to_string).collect();
+                write!(f, "Invalid {} order: {}", order_type, violations.join("; "))
+            }
+        }
+    }
+}
+
+/// Site status changes update orders may make
+///
+/// Permissive by default. Once any transition is listed, only the listed ones
+/// are allowed, in the spirit of `OrderState::can_transition_to`. Keeping the
+/// current status is always allowed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SiteStatusTransitions {
+    allowed: Option<HashSet<(SiteStatus, SiteStatus)>>,
+}
+
+impl SiteStatusTransitions {
+    /// Allow every transition
+    pub fn permissive() -> Self {
+        Self::default()
+    }
+
+    /// Allow only these transitions
+    pub fn only(transitions: impl IntoIterator<Item = (SiteStatus, SiteStatus)>) -> Self {
+        Self {
+            allowed: Some(transitions.into_iter().collect()),
         }
     }
+
+    /// Parse `from>to` pairs separated by commas, e.g. `planned>staging,staging>active`
+    ///
+    /// An empty spec is permissive. Malformed pairs are skipped with a warning.
+    pub fn parse(spec: &str) -> Self {
+        let pairs: Vec<&str> = spec.split(',').map(str::trim).filter(|pair| !pair.is_empty()).collect();
+        if pairs.is_empty() {
+            return Self::permissive();
+        }
+        Self::only(pairs.into_iter().filter_map(|pair| {
+            let parsed = pair
+                .split_once('>')
+                .and_then(|(from, to)| Some((from.parse().ok()?, to.parse().ok()?)));
+            if parsed.is_none() {
+                tracing::warn!("Ignoring invalid site status transition '{}'", pair);
+            }
+            parsed
+        }))
+    }
+
+    /// Load from SITE_STATUS_TRANSITIONS
+    pub fn from_env() -> Self {
+        Self::parse(&std::env::var("SITE_STATUS_TRANSITIONS").unwrap_or_default())
+    }
+
+    pub fn is_permissive(&self) -> bool {
+        self.allowed.is_none()
+    }
+
+    pub fn allows(&self, from: SiteStatus, to: SiteStatus) -> bool {
+        from == to || self.allowed.as_ref().is_none_or(|allowed| allowed.contains(&(from, to)))
+    }
 }
 
 /// Business rules for order validation
@@ -31,6 +144,9 @@ pub struct OrderValidator {
     max_description_length: usize,
     max_address_length: usize,
     allowed_name_chars: HashSet<char>,
+    allowed_custom_fields: HashSet<String>,
+    status_transitions: SiteStatusTransitions,
+    address_normalizer: AddressNormalizer,
 }
 
 impl Default for OrderValidator {
@@ -53,6 +169,9 @@ impl OrderValidator {
             max_description_length: 500,
             max_address_length: 200,
             allowed_name_chars: allowed_chars,
+            allowed_custom_fields: HashSet::new(),
+            status_transitions: SiteStatusTransitions::permissive(),
+            address_normalizer: AddressNormalizer::new(),
         }
     }
 
@@ -72,9 +191,34 @@ impl OrderValidator {
             max_description_length,
             max_address_length,
             allowed_name_chars: allowed_chars,
+            allowed_custom_fields: HashSet::new(),
+            status_transitions: SiteStatusTransitions::permissive(),
+            address_normalizer: AddressNormalizer::new(),
         }
     }
 
+    /// Restrict the site status changes update orders may make
+    pub fn with_status_transitions(mut self, transitions: SiteStatusTransitions) -> Self {
+        self.status_transitions = transitions;
+        self
+    }
+
+    /// Normalize order addresses with this normalizer
+    pub fn with_address_normalizer(mut self, normalizer: AddressNormalizer) -> Self {
+        self.address_normalizer = normalizer;
+        self
+    }
+
+    /// Permit these NetBox custom field keys on orders; any other key is rejected
+    pub fn with_allowed_custom_fields<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed_custom_fields.extend(keys.into_iter().map(Into::into));
+        self
+    }
+
     /// Validate a site order
     pub fn validate_site_order(&self, order: &CreateSiteOrder) -> Result<(), ValidationError> {
         // Validate name
@@ -90,6 +234,110 @@ impl OrderValidator {
             self.validate_address(addr)?;
         }
 
+        self.validate_custom_fields(order.custom_fields.keys())?;
+
+        Ok(())
+    }
+
+    /// Validate a site update order: its target and the fields it sets
+    pub fn validate_site_update(&self, order: &UpdateSiteOrder) -> Result<(), ValidationError> {
+        let invalid = |msg: &str| Err(ValidationError::InvalidSiteReference(msg.to_string()));
+        match (order.site_id, order.site_slug.as_deref()) {
+            (None, None) => return invalid("Site update order requires a site_id or site_slug"),
+            (Some(_), Some(_)) => return invalid("Specify either site_id or site_slug, not both"),
+            (Some(id), None) if id <= 0 => return invalid("Site id must be a positive integer"),
+            (None, Some(slug)) if slug.trim().is_empty() => return invalid("Site slug cannot be empty"),
+            _ => {}
+        }
+
+        let changes_nothing = order.name.is_none()
+            && order.description.is_none()
+            && order.address.is_none()
+            && order.status.is_none()
+            && order.region.is_none()
+            && order.facility.is_none()
+            && order.tenant.is_none()
+            && order.tags.is_none();
+        if changes_nothing {
+            return Err(ValidationError::EmptyUpdate);
+        }
+
+        if let Some(ref name) = order.name {
+            self.validate_name(name)?;
+        }
+        if let Some(ref desc) = order.description {
+            self.validate_description(desc)?;
+        }
+        if let Some(ref addr) = order.address {
+            self.validate_address(addr)?;
+        }
+        if let Some(ref status) = order.status {
+            status.parse::<SiteStatus>().map_err(ValidationError::InvalidStatus)?;
+        }
+        if let Some(ref last_updated) = order.last_updated {
+            chrono::DateTime::parse_from_rfc3339(last_updated)
+                .map_err(|_| ValidationError::InvalidLastUpdated(last_updated.clone()))?;
+        }
+        Ok(())
+    }
+
+    /// Check an update order's status change against the site's current status
+    pub fn validate_status_change(&self, current: SiteStatus, requested: SiteStatus) -> Result<(), ValidationError> {
+        if self.status_transitions.allows(current, requested) {
+            Ok(())
+        } else {
+            Err(ValidationError::DisallowedStatusTransition { from: current, to: requested })
+        }
+    }
+
+    /// Check custom field keys against the allowlist, listing every unknown key
+    pub fn validate_custom_fields<'a>(
+        &self,
+        keys: impl IntoIterator<Item = &'a String>,
+    ) -> Result<(), ValidationError> {
+        let mut unknown: Vec<String> = keys
+            .into_iter()
+            .filter(|key| !self.allowed_custom_fields.contains(*key))
+            .cloned()
+            .collect();
+        if unknown.is_empty() {
+            return Ok(());
+        }
+        unknown.sort();
+        Err(ValidationError::UnknownCustomFields(unknown))
+    }
+
+    /// Check custom field values against NetBox's schema for `object_type`
+    ///
+    /// Unknown names are all listed at once; otherwise the first field (by
+    /// name) with a value of the wrong type or an invalid choice is reported.
+    /// `null` clears a field and is always accepted.
+    pub fn validate_custom_field_values(
+        &self,
+        schema: &CustomFieldSchema,
+        object_type: &str,
+        fields: &HashMap<String, serde_json::Value>,
+    ) -> Result<(), ValidationError> {
+        let mut names: Vec<&String> = fields.keys().collect();
+        names.sort();
+
+        let undefined: Vec<String> = names
+            .iter()
+            .filter(|name| schema.field_for(object_type, name).is_none())
+            .map(|name| name.to_string())
+            .collect();
+        if !undefined.is_empty() {
+            return Err(ValidationError::UndefinedCustomFields {
+                object_type: object_type.to_string(),
+                keys: undefined,
+            });
+        }
+
+        for name in names {
+            if let Some(field) = schema.field_for(object_type, name) {
+                check_custom_field_value(field, &fields[name])?;
+            }
+        }
         Ok(())
     }
 
@@ -130,6 +378,60 @@ impl OrderValidator {
         }
         Ok(())
     }
+
+    /// Normalize an order's address and validate the result
+    ///
+    /// An address that can't be verified is returned as such, for the caller
+    /// to flag, unless the normalizer is strict.
+    pub fn normalize_address(&self, address: &str) -> Result<NormalizedAddress, ValidationError> {
+        let normalized = self.address_normalizer.normalize(address);
+        self.validate_address(&normalized.address)?;
+        if self.address_normalizer.is_strict() && !normalized.is_verified() {
+            return Err(ValidationError::UnverifiedAddress(normalized.problems));
+        }
+        Ok(normalized)
+    }
+}
+
+/// Check one value against its field's type and choices
+fn check_custom_field_value(field: &CustomFieldDefinition, value: &serde_json::Value) -> Result<(), ValidationError> {
+    use serde_json::Value;
+
+    let wrong_type = || ValidationError::InvalidCustomFieldType {
+        field: field.name.clone(),
+        expected: field.kind,
+    };
+    let check_choice = |choice: &Value| -> Result<(), ValidationError> {
+        let choice = choice.as_str().ok_or_else(wrong_type)?;
+        match field.choices {
+            Some(ref choices) if !choices.iter().any(|c| c == choice) => Err(ValidationError::InvalidCustomFieldChoice {
+                field: field.name.clone(),
+                value: choice.to_string(),
+                choices: choices.clone(),
+            }),
+            _ => Ok(()),
+        }
+    };
+
+    let type_ok = match (field.kind, value) {
+        (_, Value::Null) | (CustomFieldKind::Any, _) => true,
+        (CustomFieldKind::Text, Value::String(_)) => true,
+        (CustomFieldKind::Integer, Value::Number(n)) => n.is_i64() || n.is_u64(),
+        (CustomFieldKind::Decimal, Value::Number(_)) => true,
+        (CustomFieldKind::Boolean, Value::Bool(_)) => true,
+        (CustomFieldKind::Date, Value::String(date)) => chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok(),
+        (CustomFieldKind::DateTime, Value::String(ts)) => chrono::DateTime::parse_from_rfc3339(ts).is_ok(),
+        (CustomFieldKind::Select, choice) => return check_choice(choice),
+        (CustomFieldKind::MultiSelect, Value::Array(choices)) => return choices.iter().try_for_each(check_choice),
+        (CustomFieldKind::Object, Value::Number(id)) => id.is_u64(),
+        (CustomFieldKind::MultiObject, Value::Array(ids)) => ids.iter().all(|id| id.is_u64()),
+        _ => false,
+    };
+    if type_ok {
+        Ok(())
+    } else {
+        Err(wrong_type())
+    }
 }
 
 use crate::error::AppError;
@@ -144,6 +446,82 @@ impl From<ValidationError> for AppError {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_status_transitions_default_to_permissive() {
+        let validator = OrderValidator::new();
+        assert!(validator.validate_status_change(SiteStatus::Planned, SiteStatus::Retired).is_ok());
+        assert!(validator.validate_status_change(SiteStatus::Retired, SiteStatus::Active).is_ok());
+        assert!(SiteStatusTransitions::parse("").is_permissive());
+        assert!(SiteStatusTransitions::parse(" , ").is_permissive());
+    }
+
+    #[test]
+    fn test_status_transitions_restrict_to_listed_pairs() {
+        let lifecycle = SiteStatusTransitions::parse("planned>staging, staging>active, active>retired");
+        let validator = OrderValidator::new().with_status_transitions(lifecycle);
+
+        assert!(validator.validate_status_change(SiteStatus::Planned, SiteStatus::Staging).is_ok());
+        assert!(validator.validate_status_change(SiteStatus::Active, SiteStatus::Retired).is_ok());
+        assert!(validator.validate_status_change(SiteStatus::Active, SiteStatus::Active).is_ok());
+        let skipped = validator.validate_status_change(SiteStatus::Planned, SiteStatus::Retired);
+        assert_eq!(
+            skipped,
+            Err(ValidationError::DisallowedStatusTransition { from: SiteStatus::Planned, to: SiteStatus::Retired })
+        );
+        assert_eq!(skipped.unwrap_err().to_string(), "Site status can't change from planned to retired");
+        assert!(validator.validate_status_change(SiteStatus::Retired, SiteStatus::Active).is_err());
+    }
+
+    #[test]
+    fn test_status_transitions_skip_malformed_pairs() {
+        let transitions = SiteStatusTransitions::parse("planned>active,active-retired,staging>gone");
+        assert_eq!(transitions, SiteStatusTransitions::only([(SiteStatus::Planned, SiteStatus::Active)]));
+        assert!(!transitions.allows(SiteStatus::Active, SiteStatus::Retired));
+
+        // A matrix allowing nothing still lets a site keep its status
+        let frozen = SiteStatusTransitions::only([]);
+        assert!(frozen.allows(SiteStatus::Staging, SiteStatus::Staging));
+        assert!(!frozen.allows(SiteStatus::Staging, SiteStatus::Active));
+    }
+
+    #[test]
+    fn test_validate_site_update() {
+        let validator = OrderValidator::new();
+        let order = UpdateSiteOrder {
+            site_id: Some(3),
+            status: Some("Retired".to_string()),
+            ..Default::default()
+        };
+        assert!(validator.validate_site_update(&order).is_ok());
+
+        let no_target = UpdateSiteOrder { status: Some("active".to_string()), ..Default::default() };
+        assert!(matches!(
+            validator.validate_site_update(&no_target),
+            Err(ValidationError::InvalidSiteReference(_))
+        ));
+
+        let no_changes = UpdateSiteOrder { site_slug: Some("dc1".to_string()), ..Default::default() };
+        assert_eq!(validator.validate_site_update(&no_changes), Err(ValidationError::EmptyUpdate));
+
+        let bad_status = UpdateSiteOrder {
+            site_id: Some(3),
+            status: Some("decommissioned".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(validator.validate_site_update(&bad_status), Err(ValidationError::InvalidStatus(_))));
+
+        let bad_version = UpdateSiteOrder {
+            site_id: Some(3),
+            description: Some("Hall B".to_string()),
+            last_updated: Some("yesterday".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            validator.validate_site_update(&bad_version),
+            Err(ValidationError::InvalidLastUpdated(_))
+        ));
+    }
+
     #[test]
     fn test_validate_name_empty() {
         let validator = OrderValidator::new();
@@ -211,6 +589,25 @@ mod tests {
         assert_eq!(result.unwrap_err(), ValidationError::AddressTooLong);
     }
 
+    #[test]
+    fn test_normalize_address_strict_mode() {
+        let validator = OrderValidator::new();
+        let normalized = validator.normalize_address("123 main st").unwrap();
+        assert_eq!(normalized.address, "123 Main Street");
+        assert!(!normalized.is_verified());
+
+        // Length is checked after whitespace is collapsed
+        let padded = format!("1 Main St,{}Boston 02101, US", " ".repeat(300));
+        assert!(validator.normalize_address(&padded).is_ok());
+
+        let strict = OrderValidator::new().with_address_normalizer(AddressNormalizer::new().with_strict(true));
+        assert_eq!(
+            strict.normalize_address("123 main st"),
+            Err(ValidationError::UnverifiedAddress(vec!["No recognized country".to_string()]))
+        );
+        assert!(strict.normalize_address("1 Main St, Boston, MA 02101, US").is_ok());
+    }
+
     #[test]
     fn test_validate_site_order_success() {
         let validator = OrderValidator::new();
@@ -218,6 +615,11 @@ mod tests {
             name: "Valid Site".to_string(),
             description: Some("Valid description".to_string()),
             address: Some("123 Main St".to_string()),
+            region: None,
+            facility: None,
+            tags: vec![],
+            custom_fields: Default::default(),
+            priority: Default::default(),
         };
         assert!(validator.validate_site_order(&order).is_ok());
     }
@@ -229,6 +631,11 @@ mod tests {
             name: "".to_string(),
             description: None,
             address: None,
+            region: None,
+            facility: None,
+            tags: vec![],
+            custom_fields: Default::default(),
+            priority: Default::default(),
         };
         assert!(validator.validate_site_order(&order).is_err());
     }
@@ -240,8 +647,96 @@ mod tests {
             name: "Minimal Site".to_string(),
             description: None,
             address: None,
+            region: None,
+            facility: None,
+            tags: vec![],
+            custom_fields: Default::default(),
+            priority: Default::default(),
         };
         assert!(validator.validate_site_order(&order).is_ok());
     }
-}
 
+    fn create_order_with_custom_fields(keys: &[&str]) -> CreateSiteOrder {
+        CreateSiteOrder {
+            name: "Test Site".to_string(),
+            description: None,
+            address: None,
+            region: None,
+            facility: None,
+            tags: vec![],
+            custom_fields: keys
+                .iter()
+                .map(|key| (key.to_string(), serde_json::json!("value")))
+                .collect(),
+            priority: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_custom_fields_must_be_allowlisted() {
+        let validator = OrderValidator::new().with_allowed_custom_fields(["cost_center"]);
+
+        assert!(validator.validate_site_order(&create_order_with_custom_fields(&["cost_center"])).is_ok());
+        let result = validator.validate_site_order(&create_order_with_custom_fields(&["team", "cost_center", "owner"]));
+        assert_eq!(
+            result.unwrap_err(),
+            ValidationError::UnknownCustomFields(vec!["owner".to_string(), "team".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_custom_fields_rejected_without_allowlist() {
+        let validator = OrderValidator::new();
+
+        let err = validator.validate_site_order(&create_order_with_custom_fields(&["cost_center"])).unwrap_err();
+        assert_eq!(err.to_string(), "Custom fields not allowed: cost_center");
+        assert!(validator.validate_site_order(&create_order_with_custom_fields(&[])).is_ok());
+    }
+
+    fn site_schema() -> CustomFieldSchema {
+        let fields = serde_json::from_value(serde_json::json!([
+            {"name": "cost_center", "type": {"value": "text"}, "object_types": ["dcim.site"]},
+            {"name": "rack_count", "type": {"value": "integer"}, "object_types": ["dcim.site"]},
+            {"name": "tier", "type": {"value": "select"}, "object_types": ["dcim.site"], "choices": ["gold", "silver"]},
+            {"name": "asset_tag", "type": {"value": "text"}, "object_types": ["dcim.device"]}
+        ]))
+        .unwrap();
+        CustomFieldSchema::new(fields, &[])
+    }
+
+    fn check_fields(fields: serde_json::Value) -> Result<(), ValidationError> {
+        let fields: HashMap<String, serde_json::Value> = serde_json::from_value(fields).unwrap();
+        OrderValidator::new().validate_custom_field_values(&site_schema(), "dcim.site", &fields)
+    }
+
+    #[test]
+    fn test_custom_field_values_matching_schema_pass() {
+        assert!(check_fields(serde_json::json!({"cost_center": "CC-1", "rack_count": 4, "tier": "gold"})).is_ok());
+        assert!(check_fields(serde_json::json!({"rack_count": null})).is_ok());
+    }
+
+    #[test]
+    fn test_custom_fields_undefined_for_sites_are_rejected() {
+        let err = check_fields(serde_json::json!({"owner": "ops", "asset_tag": "A1", "cost_center": "CC-1"})).unwrap_err();
+        assert_eq!(err.to_string(), "Custom fields not defined in NetBox for dcim.site: asset_tag, owner");
+    }
+
+    #[test]
+    fn test_custom_field_of_wrong_type_is_rejected() {
+        let err = check_fields(serde_json::json!({"rack_count": "4"})).unwrap_err();
+        assert_eq!(err.to_string(), "Custom field 'rack_count' must be an integer");
+        let err = check_fields(serde_json::json!({"cost_center": 17})).unwrap_err();
+        assert_eq!(err.to_string(), "Custom field 'cost_center' must be a string");
+        let err = check_fields(serde_json::json!({"tier": 1})).unwrap_err();
+        assert_eq!(err.to_string(), "Custom field 'tier' must be a string choice");
+    }
+
+    #[test]
+    fn test_custom_field_with_invalid_choice_is_rejected() {
+        let err = check_fields(serde_json::json!({"tier": "bronze"})).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "'bronze' is not a valid choice for custom field 'tier'; expected one of: gold, silver"
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::AppError;
+use crate::netbox::ResilientNetBoxClient;
+
+/// Lowest and highest VIDs 802.1Q allows for ordinary VLANs
+pub const MIN_VID: u16 = 1;
+pub const MAX_VID: u16 = 4094;
+
+/// Page size used when reading the VIDs taken at a site
+const VLAN_PAGE_SIZE: u32 = 1000;
+
+/// An inclusive range of VLAN ids
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct VidRange {
+    pub start: u16,
+    pub end: u16,
+}
+
+impl VidRange {
+    /// Every VID a VLAN may have
+    pub const ALL: VidRange = VidRange { start: MIN_VID, end: MAX_VID };
+
+    /// A range of valid VIDs, or `None` if it is empty or leaves 1-4094
+    pub fn new(start: u16, end: u16) -> Option<Self> {
+        (MIN_VID <= start && start <= end && end <= MAX_VID).then_some(Self { start, end })
+    }
+
+    pub fn contains(&self, vid: u16) -> bool {
+        self.start <= vid && vid <= self.end
+    }
+}
+
+impl fmt::Display for VidRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start == self.end {
+            write!(f, "{}", self.start)
+        } else {
+            write!(f, "{}-{}", self.start, self.end)
+        }
+    }
+}
+
+impl FromStr for VidRange {
+    type Err = String;
+
+    /// Parse `100-199`, or `100` for a single VID
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s.split_once('-').unwrap_or((s, s));
+        let parse = |vid: &str| vid.trim().parse::<u16>().map_err(|_| format!("'{}' is not a VLAN id", vid.trim()));
+        let (start, end) = (parse(start)?, parse(end)?);
+        Self::new(start, end).ok_or_else(|| format!("'{}' is not a range within {}-{}", s.trim(), MIN_VID, MAX_VID))
+    }
+}
+
+/// The VIDs each tenant may give its VLANs
+///
+/// Tenants without configured ranges may use any VID.
+#[derive(Debug, Clone, Default)]
+pub struct VlanRanges {
+    tenants: HashMap<String, Vec<VidRange>>,
+}
+
+impl VlanRanges {
+    /// Create ranges that let every tenant use any VID
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Let a tenant use the VIDs in `range`, besides any it already has
+    pub fn with_range(mut self, tenant_id: impl Into<String>, range: VidRange) -> Self {
+        let ranges = self.tenants.entry(tenant_id.into()).or_default();
+        ranges.push(range);
+        ranges.sort();
+        self
+    }
+
+    /// Parse `tenant=start-end` entries, comma-separated; a tenant may be listed more than once
+    ///
+    /// Malformed entries are logged and skipped.
+    pub fn parse(spec: &str) -> Self {
+        spec.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .fold(Self::new(), |ranges, entry| {
+                let parsed = entry
+                    .split_once('=')
+                    .filter(|(tenant, _)| !tenant.trim().is_empty())
+                    .ok_or_else(|| "expected tenant=start-end".to_string())
+                    .and_then(|(tenant, range)| Ok((tenant.trim(), range.parse::<VidRange>()?)));
+                match parsed {
+                    Ok((tenant, range)) => ranges.with_range(tenant, range),
+                    Err(reason) => {
+                        tracing::warn!("Ignoring malformed VLAN range '{}': {}", entry, reason);
+                        ranges
+                    }
+                }
+            })
+    }
+
+    /// Load ranges from TENANT_VLAN_RANGES, e.g. `acme=100-199,acme=300-349,initech=200-299`
+    pub fn from_env() -> Self {
+        Self::parse(&std::env::var("TENANT_VLAN_RANGES").unwrap_or_default())
+    }
+
+    /// The ranges a tenant may use, lowest first
+    pub fn ranges_for(&self, tenant_id: &str) -> Vec<VidRange> {
+        self.tenants
+            .get(tenant_id)
+            .cloned()
+            .unwrap_or_else(|| vec![VidRange::ALL])
+    }
+
+    /// Refuse a VID outside the tenant's ranges
+    pub fn check(&self, tenant_id: &str, vid: u16) -> Result<(), AppError> {
+        let ranges = self.ranges_for(tenant_id);
+        if ranges.iter().any(|range| range.contains(vid)) {
+            return Ok(());
+        }
+        let allowed: Vec<String> = ranges.iter().map(VidRange::to_string).collect();
+        Err(AppError::ValidationError(format!(
+            "VLAN {} is outside the VIDs tenant '{}' may use ({})",
+            vid,
+            tenant_id,
+            allowed.join(", ")
+        )))
+    }
+}
+
+/// The lowest VID in `ranges` that isn't in `used`
+pub fn lowest_free_vid(ranges: &[VidRange], used: &HashSet<u16>) -> Option<u16> {
+    let mut candidates: Vec<u16> = ranges
+        .iter()
+        .filter_map(|range| (range.start..=range.end).find(|vid| !used.contains(vid)))
+        .collect();
+    candidates.sort_unstable();
+    candidates.first().copied()
+}
+
+/// The VIDs of every VLAN at a site
+pub async fn used_vids(client: &ResilientNetBoxClient, site_id: i32) -> Result<HashSet<u16>, AppError> {
+    let mut used = HashSet::new();
+    let mut offset = 0;
+    loop {
+        let page = client
+            .list_vlans_with_filters(Some(site_id), None, None, Some(VLAN_PAGE_SIZE), Some(offset), &HashMap::new())
+            .await?;
+        let vlans = page.results.unwrap_or_default();
+        offset += vlans.len() as u32;
+        used.extend(vlans.iter().map(|vlan| vlan.vid));
+        if page.next.is_none() || vlans.is_empty() {
+            return Ok(used);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ranges() {
+        let ranges = VlanRanges::parse("acme=300-349, acme=100-199,initech=200,bad,globex=0-10,=5-6");
+
+        assert_eq!(
+            ranges.ranges_for("acme"),
+            vec![VidRange::new(100, 199).unwrap(), VidRange::new(300, 349).unwrap()]
+        );
+        assert_eq!(ranges.ranges_for("initech"), vec![VidRange::new(200, 200).unwrap()]);
+        // Unlisted tenants, including those whose only entry was malformed, may use any VID
+        assert_eq!(ranges.ranges_for("globex"), vec![VidRange::ALL]);
+    }
+
+    #[test]
+    fn test_range_enforcement() {
+        let ranges = VlanRanges::new()
+            .with_range("acme", VidRange::new(100, 199).unwrap())
+            .with_range("acme", VidRange::new(300, 349).unwrap());
+
+        assert!(ranges.check("acme", 100).is_ok());
+        assert!(ranges.check("acme", 349).is_ok());
+        let error = ranges.check("acme", 250).unwrap_err().to_string();
+        assert!(error.contains("VLAN 250 is outside the VIDs tenant 'acme' may use (100-199, 300-349)"), "{}", error);
+        assert!(ranges.check("initech", 4094).is_ok());
+    }
+
+    #[test]
+    fn test_lowest_free_vid_fills_gaps() {
+        let ranges = [VidRange::new(300, 301).unwrap(), VidRange::new(100, 103).unwrap()];
+
+        assert_eq!(lowest_free_vid(&ranges, &HashSet::new()), Some(100));
+        assert_eq!(lowest_free_vid(&ranges, &HashSet::from([100, 101, 103])), Some(102));
+        assert_eq!(lowest_free_vid(&ranges, &HashSet::from([100, 101, 102, 103, 300])), Some(301));
+        assert_eq!(lowest_free_vid(&ranges, &HashSet::from([100, 101, 102, 103, 300, 301])), None);
+    }
+}
@@ -0,0 +1,450 @@
+use crate::business::{OrderState, WorkflowEvent};
+use crate::domain::tenant::TenantStore;
+use crate::domain::webhook::{WebhookEvent, WebhookRegistration};
+use crate::security::webhook_signature::signature_header;
+use serde::{Deserialize, Serialize};
+use parking_lot::RwLock;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{debug, warn};
+
+pub use crate::security::webhook_signature::{SIGNATURE_HEADER, TIMESTAMP_HEADER};
+
+/// Webhook delivery configuration
+#[derive(Debug, Clone)]
+pub struct WebhookDeliveryConfig {
+    /// Maximum delivery attempts per event
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled on each subsequent retry
+    pub initial_delay: Duration,
+    /// Timeout for a single delivery attempt
+    pub request_timeout: Duration,
+    /// Most recent delivery attempts kept for inspection; older ones are dropped
+    pub max_recorded_attempts: usize,
+}
+
+impl Default for WebhookDeliveryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_delay: Duration::from_millis(500),
+            request_timeout: Duration::from_secs(10),
+            max_recorded_attempts: 1000,
+        }
+    }
+}
+
+/// JSON body posted to webhook receivers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    pub event: WebhookEvent,
+    pub order_id: String,
+    pub tenant_id: String,
+    pub state: OrderState,
+    pub netbox_site_id: Option<i32>,
+    pub error_message: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Record of a single delivery attempt
+#[derive(Debug, Clone)]
+pub struct DeliveryAttempt {
+    pub webhook_id: String,
+    pub order_id: String,
+    pub attempt: u32,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+impl DeliveryAttempt {
+    pub fn succeeded(&self) -> bool {
+        self.status_code.is_some_and(|code| (200..300).contains(&code))
+    }
+}
+
+/// Webhook event for a workflow state, if the state is one tenants can subscribe to
+fn event_for_state(state: OrderState) -> Option<WebhookEvent> {
+    match state {
+        OrderState::Completed => Some(WebhookEvent::OrderCompleted),
+        OrderState::Failed => Some(WebhookEvent::OrderFailed),
+        OrderState::Cancelled => Some(WebhookEvent::OrderCancelled),
+        _ => None,
+    }
+}
+
+/// Delivers order lifecycle events to tenant webhooks
+pub struct WebhookNotifier {
+    store: Arc<TenantStore>,
+    client: reqwest::Client,
+    config: WebhookDeliveryConfig,
+    /// The last `max_recorded_attempts` attempts, oldest first
+    deliveries: RwLock<VecDeque<DeliveryAttempt>>,
+}
+
+impl WebhookNotifier {
+    /// Create a notifier with default delivery configuration
+    pub fn new(store: Arc<TenantStore>) -> Self {
+        Self::with_config(store, WebhookDeliveryConfig::default())
+    }
+
+    /// Create a notifier with custom delivery configuration
+    pub fn with_config(store: Arc<TenantStore>, config: WebhookDeliveryConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(config.request_timeout)
+            .build()
+            .unwrap_or_default();
+        Self {
+            store,
+            client,
+            config,
+            deliveries: RwLock::new(VecDeque::new()),
+        }
+    }
+
+    /// Deliver webhooks for `events` until the workflow manager goes away
+    ///
+    /// Should the notifier fall too far behind, the missed events are logged
+    /// and their webhooks never sent.
+    pub fn listen(self: Arc<Self>, mut events: broadcast::Receiver<WorkflowEvent>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        self.notify(&event);
+                    }
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!("Webhook notifier fell behind; {} order events were not delivered", missed);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                }
+            }
+        })
+    }
+
+    /// Notify the tenant's webhooks about an order that reached a terminal state
+    ///
+    /// Deliveries run on spawned tasks so this never blocks order processing.
+    /// Returns the handles of the spawned deliveries.
+    pub fn notify(self: &Arc<Self>, transition: &WorkflowEvent) -> Vec<tokio::task::JoinHandle<()>> {
+        let Some(event) = event_for_state(transition.to) else {
+            return Vec::new();
+        };
+        let payload = WebhookPayload {
+            event,
+            order_id: transition.order_id.clone(),
+            tenant_id: transition.tenant_id.clone(),
+            state: transition.to,
+            netbox_site_id: transition.context.netbox_site_id,
+            error_message: transition.context.error_message.clone(),
+            created_at: transition.context.created_at,
+            updated_at: transition.at,
+        };
+        let body = match serde_json::to_vec(&payload) {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("Failed to serialize webhook payload for order {}: {}", payload.order_id, e);
+                return Vec::new();
+            }
+        };
+
+        self.store
+            .get_webhooks(&transition.tenant_id)
+            .into_iter()
+            .filter(|webhook| webhook.accepts(event))
+            .map(|webhook| {
+                let notifier = Arc::clone(self);
+                let body = body.clone();
+                let order_id = payload.order_id.clone();
+                tokio::spawn(async move { notifier.deliver(webhook, order_id, body).await })
+            })
+            .collect()
+    }
+
+    /// POST the payload, retrying with backoff on 5xx and network errors
+    ///
+    /// Each attempt is signed afresh, so retries late in the backoff still
+    /// pass the receiver's timestamp check.
+    async fn deliver(&self, webhook: WebhookRegistration, order_id: String, body: Vec<u8>) {
+        let mut delay = self.config.initial_delay;
+
+        for attempt in 1..=self.config.max_attempts {
+            let timestamp = chrono::Utc::now().timestamp();
+            let result = self
+                .client
+                .post(&webhook.url)
+                .header("Content-Type", "application/json")
+                .header(TIMESTAMP_HEADER, timestamp.to_string())
+                .header(SIGNATURE_HEADER, signature_header(webhook.secrets(), timestamp, &body))
+                .body(body.clone())
+                .send()
+                .await;
+
+            let (status_code, error) = match result {
+                Ok(response) => (Some(response.status().as_u16()), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            let record = DeliveryAttempt {
+                webhook_id: webhook.id.clone(),
+                order_id: order_id.clone(),
+                attempt,
+                status_code,
+                error,
+                timestamp: chrono::Utc::now(),
+            };
+            let retryable = match status_code {
+                Some(code) => code >= 500,
+                None => true,
+            };
+            let succeeded = record.succeeded();
+            self.record(record);
+
+            if succeeded {
+                debug!("Delivered webhook {} for order {}", webhook.id, order_id);
+                return;
+            }
+            if !retryable {
+                warn!("Webhook {} rejected order {} event: {:?}", webhook.id, order_id, status_code);
+                return;
+            }
+            if attempt < self.config.max_attempts {
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+        }
+
+        warn!(
+            "Giving up delivering webhook {} for order {} after {} attempts",
+            webhook.id, order_id, self.config.max_attempts
+        );
+    }
+
+    fn record(&self, attempt: DeliveryAttempt) {
+        let mut deliveries = self.deliveries.write();
+        if deliveries.len() >= self.config.max_recorded_attempts {
+            deliveries.pop_front();
+        }
+        if self.config.max_recorded_attempts > 0 {
+            deliveries.push_back(attempt);
+        }
+    }
+
+    /// The most recent delivery attempts, oldest first
+    pub fn deliveries(&self) -> Vec<DeliveryAttempt> {
+        self.deliveries.read().iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::business::WorkflowManager;
+    use crate::domain::webhook::RegisterWebhookRequest;
+    use crate::security::verify_webhook_signature;
+    use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+    fn fast_config() -> WebhookDeliveryConfig {
+        WebhookDeliveryConfig {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(10),
+            ..Default::default()
+        }
+    }
+
+    fn register(store: &TenantStore, url: String, events: Vec<WebhookEvent>) {
+        store.add_webhook(WebhookRegistration::from_request(
+            RegisterWebhookRequest {
+                url,
+                secret: "s3cret".to_string(),
+                events,
+            },
+            "tenant1".to_string(),
+        ));
+    }
+
+    /// Events of an order taken from Pending to Completed
+    fn completed_order_events() -> Vec<WorkflowEvent> {
+        let manager = WorkflowManager::new();
+        let mut events = manager.subscribe();
+        let order_id = manager.create_order("tenant1".to_string());
+        manager.update_order_state(&order_id, OrderState::Validated).unwrap();
+        manager.update_order_state(&order_id, OrderState::Processing).unwrap();
+        manager.mark_order_completed(&order_id, 42).unwrap();
+        std::iter::from_fn(|| events.try_recv().ok()).collect()
+    }
+
+    fn completed_event() -> WorkflowEvent {
+        completed_order_events().pop().unwrap()
+    }
+
+    async fn deliver_all(notifier: &Arc<WebhookNotifier>, event: &WorkflowEvent) {
+        for handle in notifier.notify(event) {
+            handle.await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delivers_signed_payload() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/hook"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let store = Arc::new(TenantStore::new());
+        register(&store, format!("{}/hook", mock_server.uri()), vec![]);
+        let notifier = Arc::new(WebhookNotifier::with_config(store, fast_config()));
+
+        deliver_all(&notifier, &completed_event()).await;
+
+        let request = &mock_server.received_requests().await.unwrap()[0];
+        let payload: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(payload["event"], "order_completed");
+        assert_eq!(payload["tenant_id"], "tenant1");
+        assert_eq!(payload["netbox_site_id"], 42);
+        let mut headers = poem::http::HeaderMap::new();
+        for name in [SIGNATURE_HEADER, TIMESTAMP_HEADER] {
+            let value = request.headers.get(&name.into()).unwrap().as_str();
+            headers.insert(name, value.parse().unwrap());
+        }
+        assert_eq!(verify_webhook_signature("s3cret", &headers, &request.body), Ok(()));
+        assert!(verify_webhook_signature("other", &headers, &request.body).is_err());
+        assert!(notifier.deliveries()[0].succeeded());
+    }
+
+    #[tokio::test]
+    async fn test_retries_on_server_error() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&mock_server)
+            .await;
+        let store = Arc::new(TenantStore::new());
+        register(&store, mock_server.uri(), vec![]);
+        let notifier = Arc::new(WebhookNotifier::with_config(store, fast_config()));
+
+        deliver_all(&notifier, &completed_event()).await;
+
+        let deliveries = notifier.deliveries();
+        assert_eq!(deliveries.len(), 2);
+        assert_eq!(deliveries[0].status_code, Some(503));
+        assert!(deliveries[1].succeeded());
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&mock_server)
+            .await;
+        let store = Arc::new(TenantStore::new());
+        register(&store, mock_server.uri(), vec![]);
+        let notifier = Arc::new(WebhookNotifier::with_config(store, fast_config()));
+
+        deliver_all(&notifier, &completed_event()).await;
+
+        assert_eq!(notifier.deliveries().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_keeps_only_the_most_recent_attempts() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&mock_server)
+            .await;
+        let store = Arc::new(TenantStore::new());
+        register(&store, mock_server.uri(), vec![]);
+        let config = WebhookDeliveryConfig {
+            max_recorded_attempts: 2,
+            ..fast_config()
+        };
+        let notifier = Arc::new(WebhookNotifier::with_config(store, config));
+
+        deliver_all(&notifier, &completed_event()).await;
+        deliver_all(&notifier, &completed_event()).await;
+
+        let attempts: Vec<u32> = notifier.deliveries().iter().map(|delivery| delivery.attempt).collect();
+        assert_eq!(attempts, vec![2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_client_error_is_not_retried() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(400))
+            .mount(&mock_server)
+            .await;
+        let store = Arc::new(TenantStore::new());
+        register(&store, mock_server.uri(), vec![]);
+        let notifier = Arc::new(WebhookNotifier::with_config(store, fast_config()));
+
+        deliver_all(&notifier, &completed_event()).await;
+
+        assert_eq!(notifier.deliveries().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_event_filter_and_non_terminal_states() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+        let store = Arc::new(TenantStore::new());
+        register(&store, mock_server.uri(), vec![WebhookEvent::OrderFailed]);
+        let notifier = Arc::new(WebhookNotifier::with_config(store, fast_config()));
+
+        deliver_all(&notifier, &completed_event()).await;
+        for validated in &completed_order_events()[..2] {
+            assert!(notifier.notify(validated).is_empty());
+        }
+
+        assert!(notifier.deliveries().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_listens_to_workflow_events() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+        let store = Arc::new(TenantStore::new());
+        register(&store, mock_server.uri(), vec![]);
+        let notifier = Arc::new(WebhookNotifier::with_config(store, fast_config()));
+        let manager = WorkflowManager::new();
+        let listener = notifier.clone().listen(manager.subscribe());
+
+        let order_id = manager.create_order("tenant1".to_string());
+        manager.update_order_state(&order_id, OrderState::Validated).unwrap();
+        manager.update_order_state(&order_id, OrderState::Processing).unwrap();
+        manager.mark_order_failed(&order_id, "NetBox unavailable".to_string()).unwrap();
+
+        for _ in 0..50 {
+            if !notifier.deliveries().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let request = &mock_server.received_requests().await.unwrap()[0];
+        let payload: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(payload["event"], "order_failed");
+        assert_eq!(payload["order_id"], order_id);
+        assert_eq!(payload["error_message"], "NetBox unavailable");
+
+        drop(manager);
+        listener.await.unwrap();
+    }
+}
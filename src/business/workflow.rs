@@ -1,18 +1,33 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::RwLock;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use parking_lot::RwLock;
+use tokio::sync::broadcast;
 use uuid::Uuid;
 
+use crate::business::order_service::{HeldOrder, OrderKind};
+use crate::business::workflow_metrics::WorkflowMetrics;
+use crate::clock::{SharedClock, SystemClock};
+
 /// Order state in the workflow
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum OrderState {
     /// Order received, pending validation
     Pending,
+    /// Order held until an operator approves or rejects it
+    AwaitingApproval,
     /// Order validated, ready for processing
     Validated,
+    /// Order validated during a maintenance freeze, waiting for it to end
+    Scheduled,
     /// Order being processed (transforming, creating in NetBox)
     Processing,
+    /// Order failed part-way; NetBox resources it created are being deleted
+    RollingBack,
     /// Order completed successfully
     Completed,
     /// Order failed (validation, transformation, or NetBox error)
@@ -29,14 +44,29 @@ impl OrderState {
             (OrderState::Pending, OrderState::Validated) => true,
             (OrderState::Pending, OrderState::Failed) => true,
             (OrderState::Pending, OrderState::Cancelled) => true,
+            (OrderState::Pending, OrderState::AwaitingApproval) => true,
+            
+            // From AwaitingApproval
+            (OrderState::AwaitingApproval, OrderState::Validated) => true,
+            (OrderState::AwaitingApproval, OrderState::Cancelled) => true,
             
             // From Validated
             (OrderState::Validated, OrderState::Processing) => true,
             (OrderState::Validated, OrderState::Cancelled) => true,
+            (OrderState::Validated, OrderState::Scheduled) => true,
+            
+            // From Scheduled, once the freeze ends or an operator forces it
+            (OrderState::Scheduled, OrderState::Validated) => true,
+            (OrderState::Scheduled, OrderState::Failed) => true,
+            (OrderState::Scheduled, OrderState::Cancelled) => true,
             
             // From Processing
             (OrderState::Processing, OrderState::Completed) => true,
             (OrderState::Processing, OrderState::Failed) => true,
+            (OrderState::Processing, OrderState::RollingBack) => true,
+            
+            // From RollingBack
+            (OrderState::RollingBack, OrderState::Failed) => true,
             
             // Terminal states
             (OrderState::Completed, _) => false,
@@ -49,6 +79,21 @@ impl OrderState {
     }
 
     /// Check if state is terminal (cannot transition further)
+    /// Lowercase name, as serialized
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderState::Pending => "pending",
+            OrderState::AwaitingApproval => "awaitingapproval",
+            OrderState::Validated => "validated",
+            OrderState::Scheduled => "scheduled",
+            OrderState::Processing => "processing",
+            OrderState::RollingBack => "rollingback",
+            OrderState::Completed => "completed",
+            OrderState::Failed => "failed",
+            OrderState::Cancelled => "cancelled",
+        }
+    }
+
     pub fn is_terminal(&self) -> bool {
         matches!(
             self,
@@ -57,6 +102,158 @@ impl OrderState {
     }
 }
 
+/// A recorded state transition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowHistoryEntry {
+    pub from: OrderState,
+    pub to: OrderState,
+    pub at: chrono::DateTime<chrono::Utc>,
+    /// Operator who made the transition, if it was not automatic
+    pub actor: Option<String>,
+    pub comment: Option<String>,
+}
+
+/// Workflow events a subscriber may fall behind by before it starts missing them
+pub const WORKFLOW_EVENT_CAPACITY: usize = 1024;
+
+/// An order moving from one state to another, broadcast by [`WorkflowManager`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowEvent {
+    pub order_id: String,
+    pub tenant_id: String,
+    pub from: OrderState,
+    pub to: OrderState,
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub context: WorkflowEventContext,
+}
+
+/// What else subscribers need to know about the order as of an event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowEventContext {
+    /// Operator who made the transition, if it was not automatic
+    pub actor: Option<String>,
+    pub comment: Option<String>,
+    pub error_message: Option<String>,
+    pub netbox_site_id: Option<i32>,
+    /// When the order was created, e.g. to measure how long it took
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl WorkflowEvent {
+    /// Event for `workflow` having just moved out of `from`
+    fn transition(workflow: &OrderWorkflow, from: OrderState) -> Self {
+        let entry = workflow.history.last();
+        Self {
+            order_id: workflow.order_id.clone(),
+            tenant_id: workflow.tenant_id.clone(),
+            from,
+            to: workflow.state,
+            at: workflow.updated_at,
+            context: WorkflowEventContext {
+                actor: entry.and_then(|entry| entry.actor.clone()),
+                comment: entry.and_then(|entry| entry.comment.clone()),
+                error_message: workflow.error_message.clone(),
+                netbox_site_id: workflow.netbox_site_id,
+                created_at: workflow.created_at,
+            },
+        }
+    }
+}
+
+/// Kind of NetBox resource created by an order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceKind {
+    Site,
+    Device,
+    Prefix,
+    Interface,
+    Cable,
+    Vlan,
+    IpAddress,
+    VirtualMachine,
+}
+
+/// NetBox resource created while processing an order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CreatedResource {
+    pub kind: ResourceKind,
+    pub id: i32,
+}
+
+/// Progress of one sub-resource step of an order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepStatus {
+    /// Not attempted yet (or not attempted because an earlier step failed)
+    Pending,
+    Completed,
+    Failed,
+}
+
+/// One NetBox resource an order creates, e.g. the site or a device of a pop
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderStep {
+    pub kind: ResourceKind,
+    /// Name of the resource as requested
+    pub name: String,
+    pub status: StepStatus,
+    pub netbox_id: Option<i32>,
+    pub error: Option<String>,
+}
+
+impl OrderStep {
+    pub fn new(kind: ResourceKind, name: impl Into<String>) -> Self {
+        Self {
+            kind,
+            name: name.into(),
+            status: StepStatus::Pending,
+            netbox_id: None,
+            error: None,
+        }
+    }
+}
+
+/// Outcome of deleting one resource during rollback
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollbackEntry {
+    pub resource: CreatedResource,
+    pub succeeded: bool,
+    pub error: Option<String>,
+}
+
+/// Result of rolling back a partially processed order
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RollbackReport {
+    /// Deletions in the order they were attempted (newest resource first)
+    pub entries: Vec<RollbackEntry>,
+}
+
+impl RollbackReport {
+    /// Check if every created resource was deleted
+    pub fn is_complete(&self) -> bool {
+        self.entries.iter().all(|entry| entry.succeeded)
+    }
+
+    /// Resources that could not be deleted and need manual cleanup
+    pub fn failed_resources(&self) -> Vec<CreatedResource> {
+        self.entries
+            .iter()
+            .filter(|entry| !entry.succeeded)
+            .map(|entry| entry.resource)
+            .collect()
+    }
+}
+
+/// An operator's remark on an order, e.g. "waiting on customer"
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderNote {
+    /// Operator or tenant that wrote the note
+    pub author: String,
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub text: String,
+}
+
 /// Order workflow entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderWorkflow {
@@ -67,6 +264,51 @@ pub struct OrderWorkflow {
     pub error_message: Option<String>,
     pub netbox_site_id: Option<i32>,
     pub tenant_id: String,
+    /// Set when NetGate stopped while the order was still processing
+    #[serde(default)]
+    pub interrupted_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// What the order does to its site; None for orders not about one site
+    #[serde(default)]
+    pub kind: Option<OrderKind>,
+    /// Name of the NetBox site requested by this order
+    #[serde(default)]
+    pub site_name: Option<String>,
+    /// Slug of the NetBox site requested by this order, used for reconciliation
+    #[serde(default)]
+    pub site_slug: Option<String>,
+    /// State transitions in the order they happened
+    #[serde(default)]
+    pub history: Vec<WorkflowHistoryEntry>,
+    /// NetBox resources created so far, in creation order
+    #[serde(default)]
+    pub created_resources: Vec<CreatedResource>,
+    /// Per-resource steps of multi-resource orders
+    #[serde(default)]
+    pub steps: Vec<OrderStep>,
+    /// Set once a rollback has run for this order
+    #[serde(default)]
+    pub rollback_report: Option<RollbackReport>,
+    /// NetBox resources deleted by a decommission order, in deletion order
+    #[serde(default)]
+    pub deleted_resources: Vec<CreatedResource>,
+    /// Existing NetBox resource this workflow was imported from, rather than ordered
+    #[serde(default)]
+    pub imported_from: Option<CreatedResource>,
+    /// When the order must be done processing; set on entering Processing
+    #[serde(default)]
+    pub processing_deadline: Option<chrono::DateTime<chrono::Utc>>,
+    /// Operator notes, oldest first; notes are only ever added
+    #[serde(default)]
+    pub notes: Vec<OrderNote>,
+    /// Labels for grouping orders, e.g. `batch-migration`
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// When a Scheduled order is due to be executed
+    #[serde(default)]
+    pub execute_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Payload of a Scheduled order, kept with it so any worker sharing the store can execute it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parked_order: Option<HeldOrder>,
 }
 
 impl OrderWorkflow {
@@ -81,11 +323,57 @@ impl OrderWorkflow {
             error_message: None,
             netbox_site_id: None,
             tenant_id,
+            interrupted_at: None,
+            kind: None,
+            site_name: None,
+            site_slug: None,
+            history: Vec::new(),
+            created_resources: Vec::new(),
+            steps: Vec::new(),
+            rollback_report: None,
+            deleted_resources: Vec::new(),
+            imported_from: None,
+            processing_deadline: None,
+            notes: Vec::new(),
+            labels: Vec::new(),
+            execute_at: None,
+            parked_order: None,
         }
     }
 
+    /// Completed workflow standing for a resource that already existed in NetBox
+    pub fn imported(tenant_id: String, resource: CreatedResource) -> Self {
+        let mut workflow = Self::new(Uuid::new_v4().to_string(), tenant_id);
+        workflow.history.push(WorkflowHistoryEntry {
+            from: OrderState::Pending,
+            to: OrderState::Completed,
+            at: workflow.created_at,
+            actor: None,
+            comment: Some("Imported from NetBox".to_string()),
+        });
+        workflow.state = OrderState::Completed;
+        workflow.imported_from = Some(resource);
+        workflow
+    }
+
+    /// Check if this workflow accounts for `resource`, by importing or by creating it
+    pub fn covers_resource(&self, resource: CreatedResource) -> bool {
+        self.imported_from == Some(resource)
+            || (self.state == OrderState::Completed && self.created_resources.contains(&resource))
+    }
+
     /// Transition to a new state
     pub fn transition_to(&mut self, new_state: OrderState) -> Result<(), WorkflowError> {
+        self.transition_by(new_state, None, None)
+    }
+
+    /// Transition to a new state on behalf of an operator, recording who did it and why
+    pub fn transition_by(
+        &mut self,
+        new_state: OrderState,
+        actor: Option<String>,
+        comment: Option<String>,
+    ) -> Result<(), WorkflowError> {
         if !self.state.can_transition_to(new_state) {
             return Err(WorkflowError::InvalidTransition {
                 from: self.state,
@@ -93,11 +381,28 @@ impl OrderWorkflow {
             });
         }
 
+        let now = chrono::Utc::now();
+        self.history.push(WorkflowHistoryEntry {
+            from: self.state,
+            to: new_state,
+            at: now,
+            actor,
+            comment,
+        });
         self.state = new_state;
-        self.updated_at = chrono::Utc::now();
+        self.updated_at = now;
         Ok(())
     }
 
+    /// When the workflow entered its current state
+    pub fn entered_state_at(&self) -> chrono::DateTime<chrono::Utc> {
+        self.history
+            .iter()
+            .rev()
+            .find(|entry| entry.to == self.state)
+            .map_or(self.created_at, |entry| entry.at)
+    }
+
     /// Mark as failed with error message
     pub fn mark_failed(&mut self, error: String) -> Result<(), WorkflowError> {
         self.transition_to(OrderState::Failed)?;
@@ -133,9 +438,136 @@ impl std::fmt::Display for WorkflowError {
     }
 }
 
+/// Position in a tenant's order list, after the order it was taken from
+///
+/// Orders are listed newest first, ties broken by order id, so a cursor stays
+/// valid while new orders arrive: they sort before it and never shift the
+/// pages after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderCursor {
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub order_id: String,
+}
+
+impl OrderCursor {
+    /// Cursor pointing just past `workflow`
+    pub fn after(workflow: &OrderWorkflow) -> Self {
+        Self {
+            created_at: workflow.created_at,
+            order_id: workflow.order_id.clone(),
+        }
+    }
+
+    /// Opaque URL-safe token for clients to hand back
+    pub fn encode(&self) -> String {
+        use base64::Engine;
+        let raw = format!("{}|{}", self.created_at.timestamp_nanos_opt().unwrap_or_default(), self.order_id);
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    /// Read a token made by [`Self::encode`]; `None` if it isn't one
+    pub fn decode(token: &str) -> Option<Self> {
+        use base64::Engine;
+        let raw = base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(token).ok()?;
+        let raw = String::from_utf8(raw).ok()?;
+        let (nanos, order_id) = raw.split_once('|')?;
+        if order_id.is_empty() {
+            return None;
+        }
+        Some(Self {
+            created_at: chrono::DateTime::from_timestamp_nanos(nanos.parse().ok()?),
+            order_id: order_id.to_string(),
+        })
+    }
+
+    /// Whether `workflow` comes after this cursor in list order
+    fn precedes(&self, workflow: &OrderWorkflow) -> bool {
+        (std::cmp::Reverse(workflow.created_at), workflow.order_id.as_str())
+            > (std::cmp::Reverse(self.created_at), self.order_id.as_str())
+    }
+}
+
+/// Sort orders the way order lists are paged: newest first, then by order id
+pub fn sort_newest_first(workflows: &mut [OrderWorkflow]) {
+    workflows.sort_by(|a, b| {
+        b.created_at
+            .cmp(&a.created_at)
+            .then_with(|| a.order_id.cmp(&b.order_id))
+    });
+}
+
+/// How long finished (terminal) workflows are kept in memory
+///
+/// Active workflows are never evicted, whatever the policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkflowRetentionConfig {
+    /// Evict terminal workflows last updated longer ago than this
+    pub max_age: Option<Duration>,
+    /// Keep at most this many terminal workflows per tenant, newest first
+    pub max_per_tenant: Option<usize>,
+    /// How often the cleanup runs
+    pub interval: Duration,
+    /// JSONL file evicted workflows are appended to first
+    pub archive_path: Option<PathBuf>,
+}
+
+impl Default for WorkflowRetentionConfig {
+    fn default() -> Self {
+        Self {
+            max_age: Some(Duration::from_secs(7 * 24 * 3600)),
+            max_per_tenant: None,
+            interval: Duration::from_secs(300),
+            archive_path: None,
+        }
+    }
+}
+
+impl WorkflowRetentionConfig {
+    /// Load from ORDER_RETENTION_MAX_AGE_SECS (0 disables), ORDER_RETENTION_MAX_PER_TENANT,
+    /// ORDER_RETENTION_INTERVAL_SECS and ORDER_ARCHIVE_FILE
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let number = |name: &str| std::env::var(name).ok().and_then(|value| value.parse::<u64>().ok());
+        Self {
+            max_age: match number("ORDER_RETENTION_MAX_AGE_SECS") {
+                Some(0) => None,
+                Some(secs) => Some(Duration::from_secs(secs)),
+                None => defaults.max_age,
+            },
+            max_per_tenant: number("ORDER_RETENTION_MAX_PER_TENANT").map(|max| max as usize),
+            interval: number("ORDER_RETENTION_INTERVAL_SECS")
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.interval),
+            archive_path: std::env::var("ORDER_ARCHIVE_FILE").ok().filter(|path| !path.is_empty()).map(PathBuf::from),
+        }
+    }
+}
+
+/// Append workflows to a JSONL file, one workflow per line
+pub fn archive_workflows(path: &Path, workflows: &[OrderWorkflow]) -> std::io::Result<()> {
+    if workflows.is_empty() {
+        return Ok(());
+    }
+    let mut lines = Vec::new();
+    for workflow in workflows {
+        serde_json::to_writer(&mut lines, workflow)?;
+        lines.push(b'\n');
+    }
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(&lines)
+}
+
 /// Workflow manager for tracking order states
+///
+/// Every state transition is broadcast as a [`WorkflowEvent`], see [`Self::subscribe`].
 pub struct WorkflowManager {
     orders: RwLock<HashMap<String, OrderWorkflow>>,
+    metrics: Arc<WorkflowMetrics>,
+    events: broadcast::Sender<WorkflowEvent>,
+    clock: SharedClock,
 }
 
 impl Default for WorkflowManager {
@@ -149,22 +581,114 @@ impl WorkflowManager {
     pub fn new() -> Self {
         Self {
             orders: RwLock::new(HashMap::new()),
+            metrics: Arc::new(WorkflowMetrics::new()),
+            events: broadcast::channel(WORKFLOW_EVENT_CAPACITY).0,
+            clock: SystemClock::shared(),
+        }
+    }
+
+    /// Use `clock` to timestamp orders and their transitions
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Order counts and durations recorded on every transition
+    pub fn metrics(&self) -> &WorkflowMetrics {
+        &self.metrics
+    }
+
+    /// Receive an event for every state transition from now on
+    ///
+    /// Transitions never wait for subscribers. A subscriber more than
+    /// [`WORKFLOW_EVENT_CAPACITY`] events behind misses the oldest ones, and its
+    /// next `recv` reports how many with `RecvError::Lagged`.
+    pub fn subscribe(&self) -> broadcast::Receiver<WorkflowEvent> {
+        self.events.subscribe()
+    }
+
+    /// Apply `change` to an order, publishing any state transition it makes
+    fn change_order<T>(
+        &self,
+        order_id: &str,
+        change: impl FnOnce(&mut OrderWorkflow) -> Result<T, WorkflowError>,
+    ) -> Result<T, WorkflowError> {
+        let mut orders = self.orders.write();
+        let workflow = orders
+            .get_mut(order_id)
+            .ok_or_else(|| WorkflowError::OrderNotFound(order_id.to_string()))?;
+
+        let from = workflow.state;
+        let result = change(workflow);
+        if workflow.state == from {
+            return result;
         }
+        // Transitions stamp the system time; restamp them with this manager's clock
+        let now = self.clock.now_utc();
+        workflow.updated_at = now;
+        if let Some(entry) = workflow.history.last_mut() {
+            entry.at = now;
+        }
+        let event = WorkflowEvent::transition(workflow, from);
+        drop(orders);
+
+        // Metrics must not miss events the way a lagging subscriber may, so they're fed directly
+        self.metrics.record_event(&event);
+        // Without subscribers there is no one to tell
+        let _ = self.events.send(event);
+        result
     }
 
     /// Create a new order workflow
     pub fn create_order(&self, tenant_id: String) -> String {
         let order_id = Uuid::new_v4().to_string();
-        let workflow = OrderWorkflow::new(order_id.clone(), tenant_id);
+        let mut workflow = OrderWorkflow::new(order_id.clone(), tenant_id);
+        workflow.created_at = self.clock.now_utc();
+        workflow.updated_at = workflow.created_at;
 
-        let mut orders = self.orders.write().unwrap();
+        let mut orders = self.orders.write();
         orders.insert(order_id.clone(), workflow);
+        self.metrics.record_created();
         order_id
     }
 
+    /// Add a workflow built by [`OrderWorkflow::imported`], returning false if the
+    /// tenant already has a workflow for that NetBox resource
+    pub fn insert_imported_order(&self, workflow: OrderWorkflow) -> bool {
+        let Some(resource) = workflow.imported_from else {
+            return false;
+        };
+        let mut orders = self.orders.write();
+        if orders
+            .values()
+            .any(|w| w.tenant_id == workflow.tenant_id && w.covers_resource(resource))
+        {
+            return false;
+        }
+        self.metrics.record_imported(&workflow);
+        orders.insert(workflow.order_id.clone(), workflow);
+        true
+    }
+
+    /// Add workflows read back from a [`WorkflowStore`](crate::business::workflow_store::WorkflowStore),
+    /// skipping orders already known; returns how many were added
+    pub fn restore_orders(&self, workflows: Vec<OrderWorkflow>) -> usize {
+        let mut orders = self.orders.write();
+        let mut restored = 0;
+        for workflow in workflows {
+            if orders.contains_key(&workflow.order_id) {
+                continue;
+            }
+            self.metrics.record_restored(&workflow);
+            orders.insert(workflow.order_id.clone(), workflow);
+            restored += 1;
+        }
+        restored
+    }
+
     /// Get order workflow by ID
     pub fn get_order(&self, order_id: &str) -> Option<OrderWorkflow> {
-        let orders = self.orders.read().unwrap();
+        let orders = self.orders.read();
         orders.get(order_id).cloned()
     }
 
@@ -174,41 +698,285 @@ impl WorkflowManager {
         order_id: &str,
         new_state: OrderState,
     ) -> Result<(), WorkflowError> {
-        let mut orders = self.orders.write().unwrap();
+        self.change_order(order_id, |workflow| workflow.transition_to(new_state))
+    }
+
+    /// Approve or reject an order awaiting approval
+    pub fn decide_approval(
+        &self,
+        order_id: &str,
+        approved: bool,
+        approver: String,
+        comment: Option<String>,
+    ) -> Result<(), WorkflowError> {
+        let new_state = if approved {
+            OrderState::Validated
+        } else {
+            OrderState::Cancelled
+        };
+        self.change_order(order_id, |workflow| {
+            // Only orders held for approval can be decided; Pending -> Cancelled is not a rejection
+            if workflow.state != OrderState::AwaitingApproval {
+                return Err(WorkflowError::InvalidTransition {
+                    from: workflow.state,
+                    to: new_state,
+                });
+            }
+            workflow.transition_by(new_state, Some(approver), comment)
+        })
+    }
+
+    /// Park a validated order and its payload until `execute_at`, the end of a maintenance freeze
+    pub fn schedule_order(
+        &self,
+        order_id: &str,
+        execute_at: chrono::DateTime<chrono::Utc>,
+        order: HeldOrder,
+    ) -> Result<(), WorkflowError> {
+        self.change_order(order_id, |workflow| {
+            let comment = format!("Maintenance freeze until {}", execute_at.to_rfc3339());
+            workflow.transition_by(OrderState::Scheduled, None, Some(comment))?;
+            workflow.execute_at = Some(execute_at);
+            workflow.parked_order = Some(order);
+            Ok(())
+        })
+    }
+
+    /// Return a Scheduled order to Validated so it can be processed, recording
+    /// the operator who forced it early, if any, and hand out its payload
+    ///
+    /// An order without a payload stays Scheduled and `None` is returned.
+    pub fn release_scheduled_order(
+        &self,
+        order_id: &str,
+        actor: Option<String>,
+        comment: Option<String>,
+    ) -> Result<Option<HeldOrder>, WorkflowError> {
+        self.change_order(order_id, |workflow| {
+            // Releasing must not approve an order awaiting approval
+            if workflow.state != OrderState::Scheduled {
+                return Err(WorkflowError::InvalidTransition {
+                    from: workflow.state,
+                    to: OrderState::Validated,
+                });
+            }
+            let Some(order) = workflow.parked_order.take() else {
+                return Ok(None);
+            };
+            workflow.transition_by(OrderState::Validated, actor, comment)?;
+            workflow.execute_at = None;
+            Ok(Some(order))
+        })
+    }
+
+    /// Record the kind of order and the site it is about, so it can be looked up in NetBox later
+    pub fn record_site_request(
+        &self,
+        order_id: &str,
+        kind: OrderKind,
+        site_name: String,
+        site_slug: Option<String>,
+    ) -> Result<(), WorkflowError> {
+        let mut orders = self.orders.write();
         let workflow = orders
             .get_mut(order_id)
             .ok_or_else(|| WorkflowError::OrderNotFound(order_id.to_string()))?;
 
-        workflow.transition_to(new_state)
+        workflow.kind = Some(kind);
+        workflow.site_name = Some(site_name);
+        workflow.site_slug = site_slug;
+        Ok(())
     }
 
-    /// Mark order as failed
-    pub fn mark_order_failed(&self, order_id: &str, error: String) -> Result<(), WorkflowError> {
-        let mut orders = self.orders.write().unwrap();
+    /// Record a NetBox resource created by an order, so it can be rolled back
+    pub fn record_created_resource(
+        &self,
+        order_id: &str,
+        kind: ResourceKind,
+        id: i32,
+    ) -> Result<(), WorkflowError> {
+        let mut orders = self.orders.write();
         let workflow = orders
             .get_mut(order_id)
             .ok_or_else(|| WorkflowError::OrderNotFound(order_id.to_string()))?;
 
-        workflow.mark_failed(error)
+        workflow.created_resources.push(CreatedResource { kind, id });
+        workflow.updated_at = self.clock.now_utc();
+        Ok(())
     }
 
-    /// Mark order as completed
-    pub fn mark_order_completed(
+    /// Record a NetBox resource deleted by an order, for the audit trail
+    pub fn record_deleted_resource(
         &self,
         order_id: &str,
-        netbox_site_id: i32,
+        kind: ResourceKind,
+        id: i32,
     ) -> Result<(), WorkflowError> {
-        let mut orders = self.orders.write().unwrap();
+        let mut orders = self.orders.write();
+        let workflow = orders
+            .get_mut(order_id)
+            .ok_or_else(|| WorkflowError::OrderNotFound(order_id.to_string()))?;
+
+        workflow.deleted_resources.push(CreatedResource { kind, id });
+        workflow.updated_at = self.clock.now_utc();
+        Ok(())
+    }
+
+    /// Append a note to an order, in any state
+    pub fn add_note(&self, order_id: &str, author: String, text: String) -> Result<OrderNote, WorkflowError> {
+        let mut orders = self.orders.write();
+        let workflow = orders
+            .get_mut(order_id)
+            .ok_or_else(|| WorkflowError::OrderNotFound(order_id.to_string()))?;
+
+        let note = OrderNote {
+            author,
+            at: self.clock.now_utc(),
+            text,
+        };
+        workflow.notes.push(note.clone());
+        Ok(note)
+    }
+
+    /// Replace an order's labels, in any state
+    pub fn set_labels(&self, order_id: &str, labels: Vec<String>) -> Result<(), WorkflowError> {
+        let mut orders = self.orders.write();
+        let workflow = orders
+            .get_mut(order_id)
+            .ok_or_else(|| WorkflowError::OrderNotFound(order_id.to_string()))?;
+
+        workflow.labels = labels;
+        Ok(())
+    }
+
+    /// Give a processing order until `timeout` from now to finish
+    pub fn set_processing_deadline(&self, order_id: &str, timeout: Duration) -> Result<(), WorkflowError> {
+        let mut orders = self.orders.write();
         let workflow = orders
             .get_mut(order_id)
             .ok_or_else(|| WorkflowError::OrderNotFound(order_id.to_string()))?;
 
-        workflow.mark_completed(netbox_site_id)
+        let timeout = chrono::Duration::from_std(timeout).unwrap_or(chrono::Duration::MAX);
+        workflow.processing_deadline = Some(
+            self.clock
+                .now_utc()
+                .checked_add_signed(timeout)
+                .unwrap_or(chrono::DateTime::<chrono::Utc>::MAX_UTC),
+        );
+        Ok(())
+    }
+
+    /// Orders still in Processing past their deadline
+    ///
+    /// Orders without a recorded deadline get `default_timeout` from when they
+    /// entered Processing. Orders interrupted by a restart are left out: they
+    /// are for reconciliation, which checks NetBox for their outcome.
+    pub fn get_overdue_processing_orders(&self, default_timeout: Duration) -> Vec<OrderWorkflow> {
+        let now = self.clock.now_utc();
+        let default_timeout = chrono::Duration::from_std(default_timeout).unwrap_or(chrono::Duration::MAX);
+        let orders = self.orders.read();
+        orders
+            .values()
+            .filter(|w| w.state == OrderState::Processing && w.interrupted_at.is_none())
+            .filter(|w| {
+                w.processing_deadline
+                    .or_else(|| w.entered_state_at().checked_add_signed(default_timeout))
+                    .is_some_and(|deadline| deadline <= now)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Set the sub-resource steps of an order
+    pub fn set_order_steps(&self, order_id: &str, steps: Vec<OrderStep>) -> Result<(), WorkflowError> {
+        let mut orders = self.orders.write();
+        let workflow = orders
+            .get_mut(order_id)
+            .ok_or_else(|| WorkflowError::OrderNotFound(order_id.to_string()))?;
+
+        workflow.steps = steps;
+        workflow.updated_at = self.clock.now_utc();
+        Ok(())
+    }
+
+    /// Record the outcome of a step: the created resource id, or the error
+    pub fn finish_step(
+        &self,
+        order_id: &str,
+        step: usize,
+        result: Result<i32, String>,
+    ) -> Result<(), WorkflowError> {
+        let mut orders = self.orders.write();
+        let workflow = orders
+            .get_mut(order_id)
+            .ok_or_else(|| WorkflowError::OrderNotFound(order_id.to_string()))?;
+
+        if let Some(step) = workflow.steps.get_mut(step) {
+            match result {
+                Ok(id) => {
+                    step.status = StepStatus::Completed;
+                    step.netbox_id = Some(id);
+                }
+                Err(error) => {
+                    step.status = StepStatus::Failed;
+                    step.error = Some(error);
+                }
+            }
+            workflow.updated_at = self.clock.now_utc();
+        }
+        Ok(())
+    }
+
+    /// Move a processing order to RollingBack, returning the resources to delete
+    ///
+    /// An order already rolling back (e.g. interrupted mid-rollback) is returned
+    /// as is so the rollback can be resumed.
+    pub fn begin_rollback(&self, order_id: &str) -> Result<Vec<CreatedResource>, WorkflowError> {
+        self.change_order(order_id, |workflow| {
+            if workflow.state != OrderState::RollingBack {
+                workflow.transition_to(OrderState::RollingBack)?;
+            }
+            Ok(workflow.created_resources.clone())
+        })
+    }
+
+    /// Fail a rolling-back order, attaching the rollback report
+    pub fn finish_rollback(
+        &self,
+        order_id: &str,
+        report: RollbackReport,
+        error: String,
+    ) -> Result<(), WorkflowError> {
+        self.change_order(order_id, |workflow| {
+            if workflow.state != OrderState::RollingBack {
+                return Err(WorkflowError::InvalidTransition {
+                    from: workflow.state,
+                    to: OrderState::Failed,
+                });
+            }
+            workflow.mark_failed(error)?;
+            workflow.rollback_report = Some(report);
+            Ok(())
+        })
+    }
+
+    /// Mark order as failed
+    pub fn mark_order_failed(&self, order_id: &str, error: String) -> Result<(), WorkflowError> {
+        self.change_order(order_id, |workflow| workflow.mark_failed(error))
+    }
+
+    /// Mark order as completed
+    pub fn mark_order_completed(
+        &self,
+        order_id: &str,
+        netbox_site_id: i32,
+    ) -> Result<(), WorkflowError> {
+        self.change_order(order_id, |workflow| workflow.mark_completed(netbox_site_id))
     }
 
     /// Get all orders for a tenant
     pub fn get_tenant_orders(&self, tenant_id: &str) -> Vec<OrderWorkflow> {
-        let orders = self.orders.read().unwrap();
+        let orders = self.orders.read();
         orders
             .values()
             .filter(|w| w.tenant_id == tenant_id)
@@ -216,20 +984,158 @@ impl WorkflowManager {
             .collect()
     }
 
+    /// Up to `limit` of the tenant's orders after `cursor` (from the newest when
+    /// `None`), with the cursor for the next page if there is one
+    pub fn list_after(
+        &self,
+        tenant_id: &str,
+        cursor: Option<&OrderCursor>,
+        limit: usize,
+    ) -> (Vec<OrderWorkflow>, Option<OrderCursor>) {
+        self.list_matching_after(tenant_id, |_| true, cursor, limit)
+    }
+
+    /// Like [`Self::list_after`], listing only the orders `filter` accepts
+    pub fn list_matching_after(
+        &self,
+        tenant_id: &str,
+        filter: impl Fn(&OrderWorkflow) -> bool,
+        cursor: Option<&OrderCursor>,
+        limit: usize,
+    ) -> (Vec<OrderWorkflow>, Option<OrderCursor>) {
+        let mut workflows: Vec<OrderWorkflow> = self
+            .orders
+            .read()
+            .values()
+            .filter(|w| w.tenant_id == tenant_id && cursor.is_none_or(|cursor| cursor.precedes(w)) && filter(w))
+            .cloned()
+            .collect();
+        sort_newest_first(&mut workflows);
+
+        let has_more = workflows.len() > limit;
+        workflows.truncate(limit);
+        let next = if has_more { workflows.last().map(OrderCursor::after) } else { None };
+        (workflows, next)
+    }
+
+    /// Apply `f` to every workflow, keeping what it returns
+    ///
+    /// Lets callers copy only the fields they need instead of whole workflows.
+    pub fn filter_map_orders<T>(&self, f: impl FnMut(&OrderWorkflow) -> Option<T>) -> Vec<T> {
+        self.orders.read().values().filter_map(f).collect()
+    }
+
     /// Get orders by state
     pub fn get_orders_by_state(&self, state: OrderState) -> Vec<OrderWorkflow> {
-        let orders = self.orders.read().unwrap();
+        let orders = self.orders.read();
         orders
             .values()
             .filter(|w| w.state == state)
             .cloned()
             .collect()
     }
+
+    /// Orders that have been in `state` for longer than `older_than`
+    pub fn get_orders_stuck_in_state(&self, state: OrderState, older_than: Duration) -> Vec<OrderWorkflow> {
+        let cutoff = self.clock.now_utc() - chrono::Duration::from_std(older_than).unwrap_or(chrono::Duration::MAX);
+        let orders = self.orders.read();
+        orders
+            .values()
+            .filter(|w| w.state == state && w.entered_state_at() < cutoff)
+            .cloned()
+            .collect()
+    }
+
+    /// Number of workflows held in memory
+    pub fn order_count(&self) -> usize {
+        self.orders.read().len()
+    }
+
+    /// Number of workflows not yet in a terminal state
+    pub fn active_order_count(&self) -> usize {
+        self.orders.read().values().filter(|w| !w.state.is_terminal()).count()
+    }
+
+    /// Evict terminal workflows last updated more than `age` ago, returning how many were evicted
+    pub fn purge_terminal_older_than(&self, age: Duration) -> usize {
+        let expired = self.expired_terminal_orders(Some(age), None, self.clock.now_utc());
+        self.remove_terminal_orders(&expired)
+    }
+
+    /// Evict terminal workflows outside the retention policy, archiving them first if configured
+    ///
+    /// Nothing is evicted when archiving fails, so no workflow is lost.
+    pub fn apply_retention(&self, config: &WorkflowRetentionConfig) -> std::io::Result<usize> {
+        let expired = self.expired_terminal_orders(config.max_age, config.max_per_tenant, self.clock.now_utc());
+        if let Some(ref path) = config.archive_path {
+            archive_workflows(path, &expired)?;
+        }
+        Ok(self.remove_terminal_orders(&expired))
+    }
+
+    /// Terminal workflows older than `max_age`, or beyond the newest `max_per_tenant` of their tenant
+    fn expired_terminal_orders(
+        &self,
+        max_age: Option<Duration>,
+        max_per_tenant: Option<usize>,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Vec<OrderWorkflow> {
+        // An age reaching back before the earliest representable time has no cutoff
+        let cutoff = max_age.and_then(|age| {
+            now.checked_sub_signed(chrono::Duration::from_std(age).unwrap_or(chrono::Duration::MAX))
+        });
+        let orders = self.orders.read();
+
+        let mut by_tenant: HashMap<&str, Vec<&OrderWorkflow>> = HashMap::new();
+        for workflow in orders.values().filter(|w| w.state.is_terminal()) {
+            by_tenant.entry(workflow.tenant_id.as_str()).or_default().push(workflow);
+        }
+
+        let mut expired = Vec::new();
+        for mut workflows in by_tenant.into_values() {
+            workflows.sort_by_key(|w| std::cmp::Reverse(w.updated_at));
+            for (rank, workflow) in workflows.into_iter().enumerate() {
+                let too_old = cutoff.is_some_and(|cutoff| workflow.updated_at < cutoff);
+                let over_count = max_per_tenant.is_some_and(|max| rank >= max);
+                if too_old || over_count {
+                    expired.push(workflow.clone());
+                }
+            }
+        }
+        expired
+    }
+
+    /// Remove the given workflows if they are still terminal
+    fn remove_terminal_orders(&self, workflows: &[OrderWorkflow]) -> usize {
+        let ids: HashSet<&str> = workflows.iter().map(|w| w.order_id.as_str()).collect();
+        let mut orders = self.orders.write();
+        let before = orders.len();
+        orders.retain(|order_id, workflow| !(ids.contains(order_id.as_str()) && workflow.state.is_terminal()));
+        before - orders.len()
+    }
+
+    /// Flag orders still in Processing as interrupted, returning how many were flagged
+    ///
+    /// Orders stay in Processing so reconciliation can check NetBox for the outcome.
+    pub fn mark_interrupted_processing_orders(&self) -> usize {
+        let now = self.clock.now_utc();
+        let mut orders = self.orders.write();
+        let mut marked = 0;
+        for workflow in orders.values_mut() {
+            if workflow.state == OrderState::Processing && workflow.interrupted_at.is_none() {
+                workflow.interrupted_at = Some(now);
+                workflow.updated_at = now;
+                marked += 1;
+            }
+        }
+        marked
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::{Clock, ManualClock};
 
     #[test]
     fn test_order_state_transitions() {
@@ -340,5 +1246,437 @@ mod tests {
         let processing = manager.get_orders_by_state(OrderState::Processing);
         assert_eq!(processing.len(), 1);
     }
-}
 
+    #[test]
+    fn test_mark_interrupted_processing_orders() {
+        let manager = WorkflowManager::new();
+        let processing = manager.create_order("tenant-1".to_string());
+        manager.update_order_state(&processing, OrderState::Validated).unwrap();
+        manager.update_order_state(&processing, OrderState::Processing).unwrap();
+        let pending = manager.create_order("tenant-1".to_string());
+
+        assert_eq!(manager.mark_interrupted_processing_orders(), 1);
+        // Already flagged orders are not counted again
+        assert_eq!(manager.mark_interrupted_processing_orders(), 0);
+
+        let workflow = manager.get_order(&processing).unwrap();
+        assert_eq!(workflow.state, OrderState::Processing);
+        assert!(workflow.interrupted_at.is_some());
+        assert!(manager.get_order(&pending).unwrap().interrupted_at.is_none());
+    }
+
+    #[test]
+    fn test_approval_transitions() {
+        assert!(OrderState::Pending.can_transition_to(OrderState::AwaitingApproval));
+        assert!(OrderState::AwaitingApproval.can_transition_to(OrderState::Validated));
+        assert!(OrderState::AwaitingApproval.can_transition_to(OrderState::Cancelled));
+        assert!(!OrderState::AwaitingApproval.can_transition_to(OrderState::Processing));
+    }
+
+    #[test]
+    fn test_decide_approval_records_approver() {
+        let manager = WorkflowManager::new();
+        let order_id = manager.create_order("tenant-1".to_string());
+        manager.update_order_state(&order_id, OrderState::AwaitingApproval).unwrap();
+
+        manager
+            .decide_approval(&order_id, true, "alice".to_string(), Some("LGTM".to_string()))
+            .unwrap();
+
+        let workflow = manager.get_order(&order_id).unwrap();
+        assert_eq!(workflow.state, OrderState::Validated);
+        let entry = workflow.history.last().unwrap();
+        assert_eq!(entry.from, OrderState::AwaitingApproval);
+        assert_eq!(entry.actor.as_deref(), Some("alice"));
+        assert_eq!(entry.comment.as_deref(), Some("LGTM"));
+    }
+
+    #[test]
+    fn test_decide_approval_requires_awaiting_approval() {
+        let manager = WorkflowManager::new();
+        let order_id = manager.create_order("tenant-1".to_string());
+
+        let result = manager.decide_approval(&order_id, false, "alice".to_string(), None);
+
+        assert!(matches!(result, Err(WorkflowError::InvalidTransition { .. })));
+        assert_eq!(manager.get_order(&order_id).unwrap().state, OrderState::Pending);
+    }
+
+    #[test]
+    fn test_rollback_transitions() {
+        assert!(OrderState::Processing.can_transition_to(OrderState::RollingBack));
+        assert!(OrderState::RollingBack.can_transition_to(OrderState::Failed));
+        assert!(!OrderState::RollingBack.can_transition_to(OrderState::Completed));
+        assert!(!OrderState::RollingBack.is_terminal());
+    }
+
+    #[test]
+    fn test_begin_and_finish_rollback() {
+        let manager = WorkflowManager::new();
+        let order_id = manager.create_order("tenant-1".to_string());
+        manager.update_order_state(&order_id, OrderState::Validated).unwrap();
+        manager.update_order_state(&order_id, OrderState::Processing).unwrap();
+        manager.record_created_resource(&order_id, ResourceKind::Site, 1).unwrap();
+        manager.record_created_resource(&order_id, ResourceKind::Device, 2).unwrap();
+
+        let resources = manager.begin_rollback(&order_id).unwrap();
+        assert_eq!(resources.len(), 2);
+        // Resuming an interrupted rollback is allowed
+        assert_eq!(manager.begin_rollback(&order_id).unwrap(), resources);
+
+        manager
+            .finish_rollback(&order_id, RollbackReport::default(), "device failed".to_string())
+            .unwrap();
+        let workflow = manager.get_order(&order_id).unwrap();
+        assert_eq!(workflow.state, OrderState::Failed);
+        assert!(workflow.rollback_report.unwrap().is_complete());
+        assert!(manager.begin_rollback(&order_id).is_err());
+    }
+
+    #[test]
+    fn test_insert_imported_order_skips_known_resources() {
+        let manager = WorkflowManager::new();
+        let site = CreatedResource { kind: ResourceKind::Site, id: 7 };
+        let imported = OrderWorkflow::imported("tenant-1".to_string(), site);
+        let order_id = imported.order_id.clone();
+
+        assert!(manager.insert_imported_order(imported));
+        assert!(!manager.insert_imported_order(OrderWorkflow::imported("tenant-1".to_string(), site)));
+        // Another tenant's workflows don't count
+        assert!(manager.insert_imported_order(OrderWorkflow::imported("tenant-2".to_string(), site)));
+
+        let workflow = manager.get_order(&order_id).unwrap();
+        assert_eq!(workflow.state, OrderState::Completed);
+        assert_eq!(workflow.history.len(), 1);
+        assert_eq!(manager.metrics().snapshot().terminal_count("tenant-1", OrderState::Completed), 1);
+
+        // Resources created by a completed order are not imported again
+        let ordered = manager.create_order("tenant-1".to_string());
+        manager.update_order_state(&ordered, OrderState::Validated).unwrap();
+        manager.update_order_state(&ordered, OrderState::Processing).unwrap();
+        manager.record_created_resource(&ordered, ResourceKind::Device, 9).unwrap();
+        manager.update_order_state(&ordered, OrderState::Completed).unwrap();
+        let device = CreatedResource { kind: ResourceKind::Device, id: 9 };
+        assert!(!manager.insert_imported_order(OrderWorkflow::imported("tenant-1".to_string(), device)));
+    }
+
+    #[test]
+    fn test_order_steps() {
+        let manager = WorkflowManager::new();
+        let order_id = manager.create_order("tenant-1".to_string());
+        manager
+            .set_order_steps(
+                &order_id,
+                vec![
+                    OrderStep::new(ResourceKind::Site, "pop-1"),
+                    OrderStep::new(ResourceKind::Device, "sw1"),
+                    OrderStep::new(ResourceKind::Device, "sw2"),
+                ],
+            )
+            .unwrap();
+
+        manager.finish_step(&order_id, 0, Ok(10)).unwrap();
+        manager.finish_step(&order_id, 1, Err("bad device type".to_string())).unwrap();
+
+        let steps = manager.get_order(&order_id).unwrap().steps;
+        assert_eq!((steps[0].status, steps[0].netbox_id), (StepStatus::Completed, Some(10)));
+        assert_eq!(steps[1].status, StepStatus::Failed);
+        assert_eq!(steps[1].error.as_deref(), Some("bad device type"));
+        assert_eq!(steps[2].status, StepStatus::Pending);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_order_updates_complete_quickly() {
+        let manager = std::sync::Arc::new(WorkflowManager::new());
+
+        let tasks: Vec<_> = (0..64)
+            .map(|task| {
+                let manager = manager.clone();
+                tokio::spawn(async move {
+                    for _ in 0..50 {
+                        let order_id = manager.create_order(format!("tenant-{}", task % 4));
+                        manager.update_order_state(&order_id, OrderState::Validated).unwrap();
+                        manager.update_order_state(&order_id, OrderState::Processing).unwrap();
+                        tokio::task::yield_now().await;
+                        manager.update_order_state(&order_id, OrderState::Completed).unwrap();
+                        assert!(manager.get_order(&order_id).is_some());
+                    }
+                })
+            })
+            .collect();
+
+        let all = async {
+            for task in tasks {
+                task.await.unwrap();
+            }
+        };
+        tokio::time::timeout(std::time::Duration::from_secs(10), all)
+            .await
+            .expect("concurrent workflow updates stalled");
+
+        assert_eq!(manager.get_tenant_orders("tenant-0").len(), 16 * 50);
+    }
+
+    /// Create an order in `state` whose last update was `age` ago
+    fn insert_aged_order(manager: &WorkflowManager, tenant_id: &str, state: OrderState, age: chrono::Duration) -> String {
+        let order_id = manager.create_order(tenant_id.to_string());
+        let mut orders = manager.orders.write();
+        let workflow = orders.get_mut(&order_id).unwrap();
+        workflow.state = state;
+        workflow.updated_at = chrono::Utc::now() - age;
+        order_id
+    }
+
+    #[test]
+    fn test_order_cursor_round_trips() {
+        let cursor = OrderCursor {
+            created_at: chrono::Utc::now(),
+            order_id: "abc|def".to_string(),
+        };
+        assert_eq!(OrderCursor::decode(&cursor.encode()), Some(cursor));
+        assert_eq!(OrderCursor::decode("not a cursor"), None);
+        assert_eq!(OrderCursor::decode(""), None);
+    }
+
+    #[test]
+    fn test_list_after_has_no_gaps_or_duplicates_while_orders_arrive() {
+        let clock = Arc::new(ManualClock::new());
+        let manager = WorkflowManager::new().with_clock(clock.clone());
+        let mut existing = Vec::new();
+        for i in 0..7 {
+            // Every other pair shares a timestamp, so ties are broken by id
+            if i % 2 == 0 {
+                clock.advance(Duration::from_secs(1));
+            }
+            existing.push(manager.create_order("t1".to_string()));
+        }
+        manager.create_order("t2".to_string());
+
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (page, next) = manager.list_after("t1", cursor.as_ref(), 3);
+            seen.extend(page.into_iter().map(|w| w.order_id));
+            // New orders between pages are newer than the cursor and don't shift it
+            clock.advance(Duration::from_secs(1));
+            manager.create_order("t1".to_string());
+            match next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        let unique: HashSet<_> = seen.iter().collect();
+        assert_eq!(unique.len(), seen.len());
+        assert_eq!(seen.len(), existing.len());
+        assert!(existing.iter().all(|id| unique.contains(id)));
+    }
+
+    #[test]
+    fn test_notes_are_appended_in_order_and_survive_archiving() {
+        let clock = Arc::new(ManualClock::new());
+        let manager = WorkflowManager::new().with_clock(clock.clone());
+        let order_id = manager.create_order("t1".to_string());
+        manager.update_order_state(&order_id, OrderState::Failed).unwrap();
+
+        manager.add_note(&order_id, "alice".to_string(), "waiting on customer".to_string()).unwrap();
+        clock.advance(Duration::from_secs(60));
+        manager.add_note(&order_id, "bob".to_string(), "NetBox ticket #123".to_string()).unwrap();
+        manager.set_labels(&order_id, vec!["batch-migration".to_string()]).unwrap();
+        assert!(manager.add_note("missing", "alice".to_string(), "lost".to_string()).is_err());
+
+        let workflow = manager.get_order(&order_id).unwrap();
+        let texts: Vec<_> = workflow.notes.iter().map(|note| note.text.as_str()).collect();
+        assert_eq!(texts, vec!["waiting on customer", "NetBox ticket #123"]);
+        assert!(workflow.notes[0].at < workflow.notes[1].at);
+        assert_eq!(workflow.notes[1].author, "bob");
+
+        let archived: OrderWorkflow = serde_json::from_str(&serde_json::to_string(&workflow).unwrap()).unwrap();
+        assert_eq!(archived.notes, workflow.notes);
+        assert_eq!(archived.labels, vec!["batch-migration"]);
+
+        // Workflows archived before notes existed still load
+        let mut old = serde_json::to_value(&workflow).unwrap();
+        old.as_object_mut().unwrap().retain(|field, _| field != "notes" && field != "labels");
+        let old: OrderWorkflow = serde_json::from_value(old).unwrap();
+        assert!(old.notes.is_empty() && old.labels.is_empty());
+    }
+
+    #[test]
+    fn test_get_orders_stuck_in_state_uses_time_entered() {
+        let clock = Arc::new(ManualClock::new());
+        let manager = WorkflowManager::new().with_clock(clock.clone());
+        let stuck = manager.create_order("t1".to_string());
+        let fresh = manager.create_order("t1".to_string());
+        manager.update_order_state(&stuck, OrderState::Validated).unwrap();
+        manager.update_order_state(&stuck, OrderState::Processing).unwrap();
+
+        clock.advance(Duration::from_secs(20 * 60));
+        manager.update_order_state(&fresh, OrderState::Validated).unwrap();
+        manager.update_order_state(&fresh, OrderState::Processing).unwrap();
+        // Unrelated updates don't reset how long the order has been processing
+        manager.record_created_resource(&stuck, ResourceKind::Site, 1).unwrap();
+        assert_eq!(manager.get_order(&stuck).unwrap().updated_at, clock.now_utc());
+
+        let found = manager.get_orders_stuck_in_state(OrderState::Processing, Duration::from_secs(600));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].order_id, stuck);
+        assert!(manager
+            .get_orders_stuck_in_state(OrderState::Validated, Duration::from_secs(0))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_purge_terminal_older_than_respects_boundary() {
+        let manager = WorkflowManager::new();
+        let old_completed = insert_aged_order(&manager, "t1", OrderState::Completed, chrono::Duration::minutes(61));
+        let old_failed = insert_aged_order(&manager, "t1", OrderState::Failed, chrono::Duration::hours(5));
+        let recent = insert_aged_order(&manager, "t1", OrderState::Cancelled, chrono::Duration::minutes(59));
+        let old_active = insert_aged_order(&manager, "t1", OrderState::Processing, chrono::Duration::days(30));
+        let old_held = insert_aged_order(&manager, "t1", OrderState::AwaitingApproval, chrono::Duration::days(30));
+
+        let purged = manager.purge_terminal_older_than(Duration::from_secs(3600));
+
+        assert_eq!(purged, 2);
+        assert!(manager.get_order(&old_completed).is_none());
+        assert!(manager.get_order(&old_failed).is_none());
+        assert!(manager.get_order(&recent).is_some());
+        assert!(manager.get_order(&old_active).is_some());
+        assert!(manager.get_order(&old_held).is_some());
+        assert_eq!(manager.order_count(), 3);
+        assert_eq!(manager.active_order_count(), 2);
+    }
+
+    #[test]
+    fn test_purge_with_unrepresentable_age_keeps_everything() {
+        let manager = WorkflowManager::new();
+        let ancient = insert_aged_order(&manager, "t1", OrderState::Completed, chrono::Duration::days(3650));
+
+        assert_eq!(manager.purge_terminal_older_than(Duration::MAX), 0);
+        assert_eq!(manager.purge_terminal_older_than(Duration::from_secs(u64::MAX / 2)), 0);
+        assert!(manager.get_order(&ancient).is_some());
+    }
+
+    #[test]
+    fn test_retention_keeps_newest_terminal_orders_per_tenant() {
+        let manager = WorkflowManager::new();
+        let newest = insert_aged_order(&manager, "t1", OrderState::Completed, chrono::Duration::minutes(1));
+        let second = insert_aged_order(&manager, "t1", OrderState::Completed, chrono::Duration::minutes(2));
+        let oldest = insert_aged_order(&manager, "t1", OrderState::Failed, chrono::Duration::minutes(3));
+        let active = insert_aged_order(&manager, "t1", OrderState::Pending, chrono::Duration::minutes(10));
+        let other_tenant = insert_aged_order(&manager, "t2", OrderState::Completed, chrono::Duration::minutes(10));
+
+        let config = WorkflowRetentionConfig {
+            max_age: None,
+            max_per_tenant: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(manager.apply_retention(&config).unwrap(), 1);
+
+        assert!(manager.get_order(&newest).is_some());
+        assert!(manager.get_order(&second).is_some());
+        assert!(manager.get_order(&oldest).is_none());
+        assert!(manager.get_order(&active).is_some());
+        assert!(manager.get_order(&other_tenant).is_some());
+    }
+
+    #[test]
+    fn test_retention_archives_before_evicting() {
+        let manager = WorkflowManager::new();
+        let expired = insert_aged_order(&manager, "t1", OrderState::Completed, chrono::Duration::days(2));
+        insert_aged_order(&manager, "t1", OrderState::Completed, chrono::Duration::minutes(5));
+        let archive = std::env::temp_dir().join(format!("netgate-archive-{}.jsonl", Uuid::new_v4()));
+
+        let config = WorkflowRetentionConfig {
+            max_age: Some(Duration::from_secs(24 * 3600)),
+            archive_path: Some(archive.clone()),
+            ..Default::default()
+        };
+        assert_eq!(manager.apply_retention(&config).unwrap(), 1);
+
+        let contents = std::fs::read_to_string(&archive).unwrap();
+        std::fs::remove_file(&archive).unwrap();
+        let lines: Vec<_> = contents.lines().collect();
+        assert_eq!(lines.len(), 1);
+        let archived: OrderWorkflow = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(archived.order_id, expired);
+    }
+
+    #[test]
+    fn test_retention_keeps_orders_when_archiving_fails() {
+        let manager = WorkflowManager::new();
+        let expired = insert_aged_order(&manager, "t1", OrderState::Completed, chrono::Duration::days(2));
+
+        let config = WorkflowRetentionConfig {
+            max_age: Some(Duration::from_secs(60)),
+            archive_path: Some(PathBuf::from("/nonexistent-dir/archive.jsonl")),
+            ..Default::default()
+        };
+        assert!(manager.apply_retention(&config).is_err());
+        assert!(manager.get_order(&expired).is_some());
+    }
+
+    fn received(events: &mut broadcast::Receiver<WorkflowEvent>) -> Vec<(OrderState, OrderState)> {
+        std::iter::from_fn(|| events.try_recv().ok())
+            .map(|event| (event.from, event.to))
+            .collect()
+    }
+
+    #[test]
+    fn test_every_transition_is_published() {
+        let manager = WorkflowManager::new();
+        let mut events = manager.subscribe();
+
+        let completed = manager.create_order("t1".to_string());
+        manager.update_order_state(&completed, OrderState::Validated).unwrap();
+        manager.update_order_state(&completed, OrderState::Processing).unwrap();
+        manager.mark_order_completed(&completed, 7).unwrap();
+        let late = manager.subscribe();
+        assert!(manager.update_order_state(&completed, OrderState::Processing).is_err());
+        assert_eq!(
+            received(&mut events),
+            vec![
+                (OrderState::Pending, OrderState::Validated),
+                (OrderState::Validated, OrderState::Processing),
+                (OrderState::Processing, OrderState::Completed),
+            ]
+        );
+        assert!(late.is_empty());
+
+        let failed = manager.create_order("t1".to_string());
+        manager.update_order_state(&failed, OrderState::Validated).unwrap();
+        manager.update_order_state(&failed, OrderState::Processing).unwrap();
+        manager.begin_rollback(&failed).unwrap();
+        manager.finish_rollback(&failed, RollbackReport::default(), "NetBox down".to_string()).unwrap();
+        let transitions: Vec<_> = std::iter::from_fn(|| events.try_recv().ok()).collect();
+        assert_eq!(transitions.len(), 4);
+        let last = transitions.last().unwrap();
+        assert_eq!((last.from, last.to), (OrderState::RollingBack, OrderState::Failed));
+        assert_eq!(last.order_id, failed);
+        assert_eq!(last.context.error_message.as_deref(), Some("NetBox down"));
+
+        let rejected = manager.create_order("t1".to_string());
+        manager.update_order_state(&rejected, OrderState::AwaitingApproval).unwrap();
+        manager.decide_approval(&rejected, false, "bob".to_string(), Some("Not now".to_string())).unwrap();
+        let decision = std::iter::from_fn(|| events.try_recv().ok()).last().unwrap();
+        assert_eq!(decision.to, OrderState::Cancelled);
+        assert_eq!(decision.context.actor.as_deref(), Some("bob"));
+        assert_eq!(decision.context.comment.as_deref(), Some("Not now"));
+    }
+
+    #[test]
+    fn test_lagging_subscriber_misses_events_without_blocking_transitions() {
+        let manager = WorkflowManager::new();
+        let mut events = manager.subscribe();
+
+        for _ in 0..WORKFLOW_EVENT_CAPACITY + 10 {
+            let order_id = manager.create_order("t1".to_string());
+            manager.update_order_state(&order_id, OrderState::Validated).unwrap();
+        }
+
+        assert_eq!(events.try_recv(), Err(broadcast::error::TryRecvError::Lagged(10)));
+        assert_eq!(received(&mut events).len(), WORKFLOW_EVENT_CAPACITY);
+        assert_eq!(manager.metrics().snapshot().active_count(OrderState::Validated), WORKFLOW_EVENT_CAPACITY as u64 + 10);
+    }
+
+}
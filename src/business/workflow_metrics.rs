@@ -0,0 +1,228 @@
+use parking_lot::Mutex;
+use std::collections::HashMap;
+
+use crate::business::workflow::{OrderState, OrderWorkflow, WorkflowEvent};
+
+/// Upper bounds, in seconds, of the order completion duration histogram buckets
+pub const COMPLETION_DURATION_BUCKETS: [f64; 9] = [1.0, 5.0, 15.0, 30.0, 60.0, 300.0, 900.0, 3600.0, 21600.0];
+
+/// Order counts and durations, fed the workflow manager's events
+#[derive(Debug, Default)]
+pub struct WorkflowMetrics {
+    state: Mutex<MetricsState>,
+}
+
+#[derive(Debug, Default)]
+struct MetricsState {
+    terminal: HashMap<(String, OrderState), u64>,
+    active: HashMap<OrderState, u64>,
+    completion_buckets: [u64; COMPLETION_DURATION_BUCKETS.len()],
+    completion_count: u64,
+    completion_sum_secs: f64,
+}
+
+/// Point-in-time copy of the workflow metrics
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorkflowMetricsSnapshot {
+    /// Orders that reached a terminal state, as (tenant, state, count), sorted
+    pub terminal: Vec<(String, OrderState, u64)>,
+    /// Orders currently in each non-terminal state, sorted by state
+    pub active: Vec<(OrderState, u64)>,
+    /// Cumulative completion counts per bucket of [`COMPLETION_DURATION_BUCKETS`]
+    pub completion_buckets: Vec<(f64, u64)>,
+    /// Number of orders that went from Pending to Completed
+    pub completion_count: u64,
+    /// Total Pending to Completed time of those orders, in seconds
+    pub completion_sum_secs: f64,
+}
+
+impl WorkflowMetricsSnapshot {
+    /// Orders of `tenant_id` that reached `state`
+    pub fn terminal_count(&self, tenant_id: &str, state: OrderState) -> u64 {
+        self.terminal
+            .iter()
+            .find(|(tenant, s, _)| tenant == tenant_id && *s == state)
+            .map_or(0, |(_, _, count)| *count)
+    }
+
+    /// Orders currently in `state`
+    pub fn active_count(&self, state: OrderState) -> u64 {
+        self.active
+            .iter()
+            .find(|(s, _)| *s == state)
+            .map_or(0, |(_, count)| *count)
+    }
+}
+
+impl WorkflowMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a newly created order, which starts in Pending
+    pub fn record_created(&self) {
+        *self.state.lock().active.entry(OrderState::Pending).or_default() += 1;
+    }
+
+    /// Record an order imported already completed, without a completion duration
+    pub fn record_imported(&self, workflow: &OrderWorkflow) {
+        *self.state.lock().terminal.entry((workflow.tenant_id.clone(), workflow.state)).or_default() += 1;
+    }
+
+    /// Record an order restored from a workflow store
+    ///
+    /// Only orders still active count; finished ones were counted by the process that finished them.
+    pub fn record_restored(&self, workflow: &OrderWorkflow) {
+        if !workflow.state.is_terminal() {
+            *self.state.lock().active.entry(workflow.state).or_default() += 1;
+        }
+    }
+
+    /// Record an order moving from one state to another
+    pub fn record_event(&self, event: &WorkflowEvent) {
+        let (from, to) = (event.from, event.to);
+        let mut state = self.state.lock();
+
+        if !from.is_terminal() {
+            if let Some(count) = state.active.get_mut(&from) {
+                *count = count.saturating_sub(1);
+            }
+        }
+
+        if !to.is_terminal() {
+            *state.active.entry(to).or_default() += 1;
+            return;
+        }
+
+        *state.terminal.entry((event.tenant_id.clone(), to)).or_default() += 1;
+        if to == OrderState::Completed {
+            let secs = (event.at - event.context.created_at)
+                .to_std()
+                .unwrap_or_default()
+                .as_secs_f64();
+            for (bucket, bound) in state.completion_buckets.iter_mut().zip(COMPLETION_DURATION_BUCKETS) {
+                if secs <= bound {
+                    *bucket += 1;
+                }
+            }
+            state.completion_count += 1;
+            state.completion_sum_secs += secs;
+        }
+    }
+
+    /// Copy the current metrics
+    pub fn snapshot(&self) -> WorkflowMetricsSnapshot {
+        let state = self.state.lock();
+
+        let mut terminal: Vec<_> = state
+            .terminal
+            .iter()
+            .map(|((tenant, s), count)| (tenant.clone(), *s, *count))
+            .collect();
+        terminal.sort_by(|a, b| (&a.0, a.1.as_str()).cmp(&(&b.0, b.1.as_str())));
+
+        let mut active: Vec<_> = state.active.iter().map(|(s, count)| (*s, *count)).collect();
+        active.sort_by_key(|(s, _)| s.as_str());
+
+        WorkflowMetricsSnapshot {
+            terminal,
+            active,
+            completion_buckets: COMPLETION_DURATION_BUCKETS
+                .iter()
+                .copied()
+                .zip(state.completion_buckets)
+                .collect(),
+            completion_count: state.completion_count,
+            completion_sum_secs: state.completion_sum_secs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::business::workflow::{WorkflowEventContext, WorkflowManager};
+
+    fn complete(manager: &WorkflowManager, order_id: &str) {
+        manager.update_order_state(order_id, OrderState::Validated).unwrap();
+        manager.update_order_state(order_id, OrderState::Processing).unwrap();
+        manager.mark_order_completed(order_id, 1).unwrap();
+    }
+
+    #[test]
+    fn test_metrics_aggregate_several_workflows() {
+        let manager = WorkflowManager::new();
+
+        let done1 = manager.create_order("t1".to_string());
+        complete(&manager, &done1);
+        let done2 = manager.create_order("t1".to_string());
+        complete(&manager, &done2);
+
+        let failed1 = manager.create_order("t1".to_string());
+        manager.mark_order_failed(&failed1, "invalid".to_string()).unwrap();
+        let failed2 = manager.create_order("t2".to_string());
+        manager.update_order_state(&failed2, OrderState::Validated).unwrap();
+        manager.update_order_state(&failed2, OrderState::Processing).unwrap();
+        manager.mark_order_failed(&failed2, "netbox down".to_string()).unwrap();
+
+        let processing = manager.create_order("t2".to_string());
+        manager.update_order_state(&processing, OrderState::Validated).unwrap();
+        manager.update_order_state(&processing, OrderState::Processing).unwrap();
+        manager.create_order("t2".to_string());
+        manager.create_order("t3".to_string());
+
+        let snapshot = manager.metrics().snapshot();
+        assert_eq!(snapshot.terminal_count("t1", OrderState::Completed), 2);
+        assert_eq!(snapshot.terminal_count("t1", OrderState::Failed), 1);
+        assert_eq!(snapshot.terminal_count("t2", OrderState::Failed), 1);
+        assert_eq!(snapshot.terminal_count("t2", OrderState::Completed), 0);
+
+        assert_eq!(snapshot.active_count(OrderState::Pending), 2);
+        assert_eq!(snapshot.active_count(OrderState::Processing), 1);
+        assert_eq!(snapshot.active_count(OrderState::Validated), 0);
+
+        assert_eq!(snapshot.completion_count, 2);
+        assert_eq!(snapshot.completion_buckets.first(), Some(&(1.0, 2)));
+        assert_eq!(snapshot.completion_buckets.last(), Some(&(21600.0, 2)));
+    }
+
+    #[test]
+    fn test_rejected_transitions_are_not_counted() {
+        let manager = WorkflowManager::new();
+        let order_id = manager.create_order("t1".to_string());
+        assert!(manager.update_order_state(&order_id, OrderState::Completed).is_err());
+
+        let snapshot = manager.metrics().snapshot();
+        assert_eq!(snapshot.active_count(OrderState::Pending), 1);
+        assert!(snapshot.terminal.is_empty());
+        assert_eq!(snapshot.completion_count, 0);
+    }
+
+    #[test]
+    fn test_completion_duration_lands_in_matching_bucket() {
+        let metrics = WorkflowMetrics::new();
+        let created_at = chrono::Utc::now();
+        metrics.record_created();
+        metrics.record_event(&WorkflowEvent {
+            order_id: "order-1".to_string(),
+            tenant_id: "t1".to_string(),
+            from: OrderState::Processing,
+            to: OrderState::Completed,
+            at: created_at + chrono::Duration::seconds(45),
+            context: WorkflowEventContext {
+                actor: None,
+                comment: None,
+                error_message: None,
+                netbox_site_id: Some(1),
+                created_at,
+            },
+        });
+
+        let snapshot = metrics.snapshot();
+        let bucket = |bound: f64| snapshot.completion_buckets.iter().find(|(b, _)| *b == bound).unwrap().1;
+        assert_eq!(bucket(30.0), 0);
+        assert_eq!(bucket(60.0), 1);
+        assert_eq!(bucket(3600.0), 1);
+        assert_eq!(snapshot.completion_sum_secs, 45.0);
+    }
+}
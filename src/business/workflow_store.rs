@@ -0,0 +1,129 @@
+use async_trait::async_trait;
+use std::path::PathBuf;
+
+use crate::business::workflow::{OrderWorkflow, WorkflowManager};
+
+/// Why stored workflows couldn't be read or written
+#[derive(Debug, thiserror::Error)]
+pub enum WorkflowStoreError {
+    #[error("Workflow store I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Workflow store holds invalid workflows: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[cfg(feature = "postgres")]
+    #[error("Workflow store query failed: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Where workflows are kept across restarts
+#[async_trait]
+pub trait WorkflowStore: Send + Sync {
+    /// Every stored workflow
+    async fn load(&self) -> Result<Vec<OrderWorkflow>, WorkflowStoreError>;
+    /// Replace the stored workflows with `workflows`
+    async fn save(&self, workflows: &[OrderWorkflow]) -> Result<(), WorkflowStoreError>;
+}
+
+/// Workflows kept in a JSON file, rewritten whole on every save
+#[derive(Debug, Clone)]
+pub struct JsonFileWorkflowStore {
+    path: PathBuf,
+}
+
+impl JsonFileWorkflowStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl WorkflowStore for JsonFileWorkflowStore {
+    /// No file yet means no workflows
+    async fn load(&self) -> Result<Vec<OrderWorkflow>, WorkflowStoreError> {
+        match tokio::fs::read(&self.path).await {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn save(&self, workflows: &[OrderWorkflow]) -> Result<(), WorkflowStoreError> {
+        // Write a sibling file and rename it over the store, so a crash never leaves it half-written
+        let tmp = self.path.with_extension("tmp");
+        tokio::fs::write(&tmp, serde_json::to_vec(workflows)?).await?;
+        tokio::fs::rename(&tmp, &self.path).await?;
+        Ok(())
+    }
+}
+
+/// Add the stored workflows to `manager`, returning how many it didn't have yet
+pub async fn restore_workflows(manager: &WorkflowManager, store: &dyn WorkflowStore) -> Result<usize, WorkflowStoreError> {
+    let workflows = store.load().await?;
+    Ok(manager.restore_orders(workflows))
+}
+
+/// Store every workflow of `manager`, returning how many were stored
+pub async fn save_workflows(manager: &WorkflowManager, store: &dyn WorkflowStore) -> Result<usize, WorkflowStoreError> {
+    let workflows = manager.filter_map_orders(|workflow| Some(workflow.clone()));
+    store.save(&workflows).await?;
+    Ok(workflows.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::business::workflow::OrderState;
+
+    /// Workflows saved to `store` come back after a restart
+    async fn check_workflows_survive_a_save_and_restore(store: &dyn WorkflowStore) {
+        assert!(store.load().await.unwrap().is_empty());
+
+        let manager = WorkflowManager::new();
+        let processing = manager.create_order("tenant1".to_string());
+        manager.update_order_state(&processing, OrderState::Validated).unwrap();
+        manager.update_order_state(&processing, OrderState::Processing).unwrap();
+        let completed = manager.create_order("tenant2".to_string());
+        manager.update_order_state(&completed, OrderState::Validated).unwrap();
+        manager.update_order_state(&completed, OrderState::Processing).unwrap();
+        manager.mark_order_completed(&completed, 7).unwrap();
+        assert_eq!(save_workflows(&manager, store).await.unwrap(), 2);
+
+        let restarted = WorkflowManager::new();
+        assert_eq!(restore_workflows(&restarted, store).await.unwrap(), 2);
+        assert_eq!(restarted.get_order(&processing).unwrap().state, OrderState::Processing);
+        assert_eq!(restarted.get_order(&completed).unwrap().netbox_site_id, Some(7));
+        assert_eq!(restarted.metrics().snapshot().active_count(OrderState::Processing), 1);
+
+        // Orders it already has are left alone
+        assert_eq!(restore_workflows(&restarted, store).await.unwrap(), 0);
+        assert_eq!(restarted.order_count(), 2);
+
+        // A save replaces what was stored
+        let later = WorkflowManager::new();
+        later.create_order("tenant3".to_string());
+        assert_eq!(save_workflows(&later, store).await.unwrap(), 1);
+        assert_eq!(store.load().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_workflows_survive_a_save_and_restore() {
+        let path = std::env::temp_dir().join(format!("netgate-workflows-{}.json", uuid::Uuid::new_v4()));
+        let store = JsonFileWorkflowStore::new(&path);
+        check_workflows_survive_a_save_and_restore(&store).await;
+
+        std::fs::write(&path, b"not json").unwrap();
+        assert!(matches!(store.load().await, Err(WorkflowStoreError::Serialization(_))));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[cfg(feature = "postgres")]
+    #[tokio::test]
+    async fn test_workflows_survive_a_save_and_restore_in_postgres() {
+        let Some(database) = crate::database::test_database().await else {
+            return;
+        };
+        check_workflows_survive_a_save_and_restore(&crate::database::PostgresWorkflowStore::new(&database)).await;
+    }
+}
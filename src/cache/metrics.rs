@@ -1,3 +1,6 @@
+use crate::cache::CacheScope;
+use parking_lot::Mutex;
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 
 /// Cache metrics for tracking cache performance
@@ -8,6 +11,10 @@ pub struct CacheMetrics {
     evictions: AtomicU64,
     invalidations: AtomicU64,
     puts: AtomicU64,
+    revalidations: AtomicU64,
+    full_fetches: AtomicU64,
+    /// List invalidations by the scope they targeted; `all` when every scope was
+    list_invalidations: Mutex<HashMap<String, u64>>,
 }
 
 impl CacheMetrics {
@@ -18,6 +25,9 @@ impl CacheMetrics {
             evictions: AtomicU64::new(0),
             invalidations: AtomicU64::new(0),
             puts: AtomicU64::new(0),
+            revalidations: AtomicU64::new(0),
+            full_fetches: AtomicU64::new(0),
+            list_invalidations: Mutex::new(HashMap::new()),
         }
     }
 
@@ -37,16 +47,37 @@ impl CacheMetrics {
         self.invalidations.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Lists in `scope` were invalidated, or lists in every scope when `None`
+    pub fn record_list_invalidation(&self, scope: Option<CacheScope>) {
+        let label = scope.map_or_else(|| "all".to_string(), |scope| scope.to_string());
+        *self.list_invalidations.lock().entry(label).or_insert(0) += 1;
+    }
+
     pub fn record_put(&self) {
         self.puts.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// An expired entry NetBox confirmed unchanged (304 Not Modified)
+    pub fn record_revalidation(&self) {
+        self.revalidations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A full payload downloaded from NetBox
+    pub fn record_full_fetch(&self) {
+        self.full_fetches.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Copy the counters
+    ///
+    /// Each counter is read once and the totals and rates are computed from
+    /// those reads, so they always agree with each other even while other
+    /// threads record; the counters may just be slightly behind.
     pub fn snapshot(&self) -> CacheMetricsSnapshot {
         let hits = self.hits.load(Ordering::Relaxed);
         let misses = self.misses.load(Ordering::Relaxed);
         let total_requests = hits + misses;
         let hit_rate = if total_requests > 0 {
-            hits as f64 / total_requests as f64
+            (hits as f64 / total_requests as f64).clamp(0.0, 1.0)
         } else {
             0.0
         };
@@ -59,6 +90,9 @@ impl CacheMetrics {
             evictions: self.evictions.load(Ordering::Relaxed),
             invalidations: self.invalidations.load(Ordering::Relaxed),
             puts: self.puts.load(Ordering::Relaxed),
+            revalidations: self.revalidations.load(Ordering::Relaxed),
+            full_fetches: self.full_fetches.load(Ordering::Relaxed),
+            list_invalidations_by_scope: self.list_invalidations.lock().clone(),
             total_requests,
         }
     }
@@ -69,6 +103,9 @@ impl CacheMetrics {
         self.evictions.store(0, Ordering::Relaxed);
         self.invalidations.store(0, Ordering::Relaxed);
         self.puts.store(0, Ordering::Relaxed);
+        self.revalidations.store(0, Ordering::Relaxed);
+        self.full_fetches.store(0, Ordering::Relaxed);
+        self.list_invalidations.lock().clear();
     }
 }
 
@@ -88,6 +125,10 @@ pub struct CacheMetricsSnapshot {
     pub evictions: u64,
     pub invalidations: u64,
     pub puts: u64,
+    pub revalidations: u64,
+    pub full_fetches: u64,
+    /// List invalidations keyed by scope (`global`, `tenant-<id>`, or `all`)
+    pub list_invalidations_by_scope: HashMap<String, u64>,
     pub total_requests: u64,
 }
 
@@ -105,6 +146,12 @@ mod tests {
         metrics.record_put();
         metrics.record_eviction();
         metrics.record_invalidation();
+        metrics.record_revalidation();
+        metrics.record_full_fetch();
+        metrics.record_full_fetch();
+        metrics.record_list_invalidation(Some(CacheScope::Tenant(4)));
+        metrics.record_list_invalidation(Some(CacheScope::Tenant(4)));
+        metrics.record_list_invalidation(None);
 
         let snapshot = metrics.snapshot();
         assert_eq!(snapshot.hits, 2);
@@ -112,10 +159,46 @@ mod tests {
         assert_eq!(snapshot.puts, 1);
         assert_eq!(snapshot.evictions, 1);
         assert_eq!(snapshot.invalidations, 1);
+        assert_eq!(snapshot.revalidations, 1);
+        assert_eq!(snapshot.full_fetches, 2);
+        assert_eq!(snapshot.list_invalidations_by_scope.get("tenant-4"), Some(&2));
+        assert_eq!(snapshot.list_invalidations_by_scope.get("all"), Some(&1));
         assert_eq!(snapshot.total_requests, 3);
         assert!((snapshot.hit_rate - 2.0 / 3.0).abs() < 0.001);
     }
 
+    #[test]
+    fn test_cache_snapshots_stay_consistent_under_concurrent_recording() {
+        let metrics = std::sync::Arc::new(CacheMetrics::new());
+        let recorders: Vec<_> = (0..8)
+            .map(|recorder| {
+                let metrics = metrics.clone();
+                std::thread::spawn(move || {
+                    for i in 0..5_000 {
+                        if (i + recorder) % 4 == 0 {
+                            metrics.record_miss();
+                        } else {
+                            metrics.record_hit();
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        let mut snapshots = 0;
+        while snapshots < 5_000 || !recorders.iter().all(|recorder| recorder.is_finished()) {
+            let snapshot = metrics.snapshot();
+            assert_eq!(snapshot.hits + snapshot.misses, snapshot.total_requests);
+            assert!((0.0..=1.0).contains(&snapshot.hit_rate), "{:?}", snapshot);
+            assert!((0.0..=1.0).contains(&snapshot.miss_rate), "{:?}", snapshot);
+            snapshots += 1;
+        }
+        for recorder in recorders {
+            recorder.join().unwrap();
+        }
+        assert_eq!(metrics.snapshot().total_requests, 40_000);
+    }
+
     #[test]
     fn test_cache_metrics_reset() {
         let metrics = CacheMetrics::new();
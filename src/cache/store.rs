@@ -1,5 +1,6 @@
+use crate::clock::{SharedClock, SystemClock};
 use crate::netbox::models::{NetBoxDevice, NetBoxSite};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::hash::Hash;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -16,8 +17,7 @@ struct CacheEntry<T> {
 }
 
 impl<T> CacheEntry<T> {
-    fn new(value: T, ttl: Duration) -> Self {
-        let now = Instant::now();
+    fn new(value: T, ttl: Duration, now: Instant) -> Self {
         Self {
             value,
             expires_at: now + ttl,
@@ -25,13 +25,13 @@ impl<T> CacheEntry<T> {
         }
     }
 
-    fn is_expired(&self) -> bool {
-        Instant::now() > self.expires_at
+    fn is_expired(&self, now: Instant) -> bool {
+        now > self.expires_at
     }
 
     #[allow(dead_code)] // Reserved for future use (cache age statistics)
-    fn age(&self) -> Duration {
-        self.created_at.elapsed()
+    fn age(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.created_at)
     }
 }
 
@@ -40,6 +40,7 @@ pub struct Cache<K, V> {
     store: Arc<RwLock<HashMap<K, CacheEntry<V>>>>,
     default_ttl: Duration,
     max_size: Option<usize>,
+    clock: SharedClock,
 }
 
 impl<K, V> Cache<K, V>
@@ -53,6 +54,7 @@ where
             store: Arc::new(RwLock::new(HashMap::new())),
             default_ttl,
             max_size: None,
+            clock: SystemClock::shared(),
         }
     }
 
@@ -62,15 +64,22 @@ where
             store: Arc::new(RwLock::new(HashMap::new())),
             default_ttl,
             max_size: Some(max_size),
+            clock: SystemClock::shared(),
         }
     }
 
+    /// Use `clock` to expire entries
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Get a value from cache
     pub async fn get(&self, key: &K) -> Option<V> {
         let store = self.store.read().await;
         let entry = store.get(key)?;
 
-        if entry.is_expired() {
+        if entry.is_expired(self.clock.now_instant()) {
             trace!("Cache entry expired for key: {:?}", key);
             drop(store);
             // Remove expired entry
@@ -83,6 +92,28 @@ where
         Some(entry.value.clone())
     }
 
+    /// Get a value even if it has expired, with whether it has
+    ///
+    /// Expired entries are left in place so callers can revalidate them.
+    pub async fn get_including_expired(&self, key: &K) -> Option<(V, bool)> {
+        let store = self.store.read().await;
+        let now = self.clock.now_instant();
+        store.get(key).map(|entry| (entry.value.clone(), entry.is_expired(now)))
+    }
+
+    /// Restart an entry's TTL without replacing its value; false if the key isn't cached
+    pub async fn refresh(&self, key: &K) -> bool {
+        let mut store = self.store.write().await;
+        match store.get_mut(key) {
+            Some(entry) => {
+                entry.expires_at = self.clock.now_instant() + self.default_ttl;
+                trace!("Refreshed TTL for key: {:?}", key);
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Put a value into cache
     pub async fn put(&self, key: K, value: V) {
         self.put_with_ttl(key, value, self.default_ttl).await;
@@ -104,7 +135,7 @@ where
             }
         }
 
-        let entry = CacheEntry::new(value, ttl);
+        let entry = CacheEntry::new(value, ttl, self.clock.now_instant());
         store.insert(key_clone.clone(), entry);
         debug!("Cached value for key: {:?} with TTL: {:?}", key_clone, ttl);
     }
@@ -148,8 +179,9 @@ where
     pub async fn evict_expired(&self) -> usize {
         let mut store = self.store.write().await;
         let initial_len = store.len();
+        let now = self.clock.now_instant();
         
-        store.retain(|_, entry| !entry.is_expired());
+        store.retain(|_, entry| !entry.is_expired(now));
         
         let removed = initial_len - store.len();
         if removed > 0 {
@@ -168,7 +200,8 @@ where
     pub async fn stats(&self) -> CacheStats {
         let store = self.store.read().await;
         let total_entries = store.len();
-        let expired_count = store.values().filter(|e| e.is_expired()).count();
+        let now = self.clock.now_instant();
+        let expired_count = store.values().filter(|e| e.is_expired(now)).count();
         let valid_entries = total_entries - expired_count;
 
         CacheStats {
@@ -193,38 +226,178 @@ pub type SiteCache = Cache<CacheKey, NetBoxSite>;
 /// Type alias for device cache
 pub type DeviceCache = Cache<CacheKey, NetBoxDevice>;
 
+/// Kind of NetBox resource a cache entry holds
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum ResourceKind {
+    Site,
+    Device,
+}
+
+/// Whose view of NetBox a cache entry belongs to
+///
+/// Tenant scopes carry the NetBox tenant id the query was filtered on, so
+/// two tenants asking the same question never share an entry.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum CacheScope {
+    /// Not filtered by tenant
+    Global,
+    Tenant(i32),
+}
+
+impl CacheScope {
+    /// Scope for a query optionally filtered on a NetBox tenant id
+    pub fn for_tenant(tenant_id: Option<i32>) -> Self {
+        tenant_id.map_or(Self::Global, Self::Tenant)
+    }
+}
+
+impl std::fmt::Display for CacheScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Global => write!(f, "global"),
+            Self::Tenant(id) => write!(f, "tenant-{}", id),
+        }
+    }
+}
+
+/// Query filters of a list entry, ordered by name so equal sets hash equally
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub struct CacheFilters(BTreeMap<String, String>);
+
+impl CacheFilters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a filter; `None` values are left out
+    pub fn with<K: Into<String>, V: ToString>(mut self, name: K, value: Option<V>) -> Self {
+        if let Some(value) = value {
+            self.0.insert(name.into(), value.to_string());
+        }
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for CacheFilters {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self(iter.into_iter().map(|(name, value)| (name.into(), value.into())).collect())
+    }
+}
+
 /// Cache key for NetBox resources
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub enum CacheKey {
-    Site(i32),
-    Device(i32),
-    SiteList(String), // Query string as key
-    DeviceList(String), // Query string as key
+    /// A single object by id
+    Object {
+        kind: ResourceKind,
+        scope: CacheScope,
+        id: i32,
+    },
+    /// One list query
+    List {
+        kind: ResourceKind,
+        scope: CacheScope,
+        filters: CacheFilters,
+    },
+    /// Every list of a kind in `scope`, or in all scopes when `None`;
+    /// only used to pick entries to invalidate, never stored
+    Lists {
+        kind: ResourceKind,
+        scope: Option<CacheScope>,
+    },
 }
 
 impl CacheKey {
-    pub fn site(id: i32) -> Self {
-        Self::Site(id)
+    pub fn site(scope: CacheScope, id: i32) -> Self {
+        Self::Object { kind: ResourceKind::Site, scope, id }
+    }
+
+    pub fn device(scope: CacheScope, id: i32) -> Self {
+        Self::Object { kind: ResourceKind::Device, scope, id }
     }
 
-    pub fn device(id: i32) -> Self {
-        Self::Device(id)
+    pub fn site_list(scope: CacheScope, filters: CacheFilters) -> Self {
+        Self::List { kind: ResourceKind::Site, scope, filters }
     }
 
-    pub fn site_list<S: Into<String>>(query: S) -> Self {
-        Self::SiteList(query.into())
+    pub fn device_list(scope: CacheScope, filters: CacheFilters) -> Self {
+        Self::List { kind: ResourceKind::Device, scope, filters }
     }
 
-    pub fn device_list<S: Into<String>>(query: S) -> Self {
-        Self::DeviceList(query.into())
+    /// Pattern for every list of `kind` in `scope` (all scopes when `None`)
+    pub fn lists(kind: ResourceKind, scope: Option<CacheScope>) -> Self {
+        Self::Lists { kind, scope }
+    }
+
+    pub fn kind(&self) -> ResourceKind {
+        match self {
+            Self::Object { kind, .. } | Self::List { kind, .. } | Self::Lists { kind, .. } => *kind,
+        }
+    }
+
+    /// Whether invalidating `self` should drop the entry stored under `key`
+    pub fn covers(&self, key: &CacheKey) -> bool {
+        match (self, key) {
+            (Self::Lists { kind, scope }, Self::List { kind: list_kind, scope: list_scope, .. }) => {
+                kind == list_kind && scope.is_none_or(|scope| scope == *list_scope)
+            }
+            _ => self == key,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::ManualClock;
+    use std::collections::HashSet;
     use std::time::Duration;
 
+    #[test]
+    fn test_filter_order_does_not_change_key() {
+        let forward = CacheFilters::new().with("limit", Some(10)).with("status", Some("active"));
+        let backward: CacheFilters = [("status", "active"), ("limit", "10")].into_iter().collect();
+        assert_eq!(
+            CacheKey::site_list(CacheScope::Global, forward),
+            CacheKey::site_list(CacheScope::Global, backward)
+        );
+        assert!(CacheFilters::new().with("offset", None::<u32>).is_empty());
+    }
+
+    #[test]
+    fn test_tenant_scopes_never_collide() {
+        let filters = CacheFilters::new().with("limit", Some(10));
+        let keys: HashSet<_> = [CacheScope::Global, CacheScope::Tenant(1), CacheScope::Tenant(2)]
+            .into_iter()
+            .flat_map(|scope| [CacheKey::site_list(scope, filters.clone()), CacheKey::site(scope, 7)])
+            .collect();
+        assert_eq!(keys.len(), 6);
+        assert_ne!(CacheKey::site(CacheScope::Global, 7), CacheKey::device(CacheScope::Global, 7));
+    }
+
+    #[test]
+    fn test_lists_pattern_covers_lists_only() {
+        let list = CacheKey::site_list(CacheScope::Tenant(1), CacheFilters::new());
+        assert!(CacheKey::lists(ResourceKind::Site, None).covers(&list));
+        assert!(CacheKey::lists(ResourceKind::Site, Some(CacheScope::Tenant(1))).covers(&list));
+        assert!(!CacheKey::lists(ResourceKind::Site, Some(CacheScope::Tenant(2))).covers(&list));
+        assert!(!CacheKey::lists(ResourceKind::Device, None).covers(&list));
+        assert!(!CacheKey::lists(ResourceKind::Site, None).covers(&CacheKey::site(CacheScope::Global, 1)));
+    }
+
+    fn manual_cache(ttl: Duration) -> (Cache<String, String>, Arc<ManualClock>) {
+        let clock = Arc::new(ManualClock::new());
+        (Cache::new(ttl).with_clock(clock.clone()), clock)
+    }
+
     #[tokio::test]
     async fn test_cache_put_and_get() {
         let cache = Cache::new(Duration::from_secs(60));
@@ -236,19 +409,32 @@ mod tests {
 
     #[tokio::test]
     async fn test_cache_expiration() {
-        let cache = Cache::new(Duration::from_millis(10));
+        let (cache, clock) = manual_cache(Duration::from_millis(10));
         cache.put("key1".to_string(), "value1".to_string()).await;
 
         // Value should be available immediately
         assert!(cache.get(&"key1".to_string()).await.is_some());
 
-        // Wait for expiration
-        tokio::time::sleep(Duration::from_millis(20)).await;
+        // Let the TTL pass
+        clock.advance(Duration::from_millis(20));
 
         // Value should be expired
         assert!(cache.get(&"key1".to_string()).await.is_none());
     }
 
+    #[tokio::test]
+    async fn test_expired_entry_can_be_refreshed() {
+        let (cache, clock) = manual_cache(Duration::from_millis(10));
+        cache.put("key1".to_string(), "value1".to_string()).await;
+        clock.advance(Duration::from_millis(20));
+
+        let key = "key1".to_string();
+        assert_eq!(cache.get_including_expired(&key).await, Some(("value1".to_string(), true)));
+        assert!(cache.refresh(&key).await);
+        assert_eq!(cache.get(&key).await, Some("value1".to_string()));
+        assert!(!cache.refresh(&"missing".to_string()).await);
+    }
+
     #[tokio::test]
     async fn test_cache_invalidation() {
         let cache = Cache::new(Duration::from_secs(60));
@@ -298,11 +484,11 @@ mod tests {
 
     #[tokio::test]
     async fn test_cache_evict_expired() {
-        let cache = Cache::new(Duration::from_millis(10));
+        let (cache, clock) = manual_cache(Duration::from_millis(10));
         cache.put("key1".to_string(), "value1".to_string()).await;
         cache.put("key2".to_string(), "value2".to_string()).await;
 
-        tokio::time::sleep(Duration::from_millis(20)).await;
+        clock.advance(Duration::from_millis(20));
 
         let evicted = cache.evict_expired().await;
         assert_eq!(evicted, 2);
@@ -1,4 +1,4 @@
-use crate::cache::CacheKey;
+use crate::cache::{CacheKey, CacheScope, ResourceKind};
 use std::time::Duration;
 
 /// Cache invalidation strategy
@@ -63,36 +63,41 @@ impl CacheConfig {
 }
 
 /// Helper to determine which cache keys to invalidate
+///
+/// List invalidations are `CacheKey::Lists` patterns; match them against
+/// stored keys with `CacheKey::covers`. An object key's scope names the
+/// tenant owning the written object: its lists and unfiltered lists are
+/// invalidated, and every list of the kind when the owner is `Global`
+/// (no tenant, or not known).
 pub fn get_invalidation_keys(key: &CacheKey, strategy: InvalidationStrategy) -> Vec<CacheKey> {
     match strategy {
         InvalidationStrategy::Never => vec![],
         InvalidationStrategy::WriteThrough => vec![key.clone()],
-        InvalidationStrategy::WriteBack => {
-            // Invalidate the specific key and related list queries
-            match key {
-                CacheKey::Site(id) => {
-                    vec![
-                        CacheKey::Site(*id),
-                        CacheKey::SiteList("*".to_string()), // Invalidate all site lists
-                    ]
-                }
-                CacheKey::Device(id) => {
-                    vec![
-                        CacheKey::Device(*id),
-                        CacheKey::DeviceList("*".to_string()), // Invalidate all device lists
-                    ]
-                }
-                _ => vec![key.clone()],
+        InvalidationStrategy::WriteBack => match key {
+            // Invalidate the specific key and the lists it may appear in
+            CacheKey::Object { kind, scope, .. } => {
+                let mut keys = vec![key.clone()];
+                keys.extend(list_patterns(*kind, *scope));
+                keys
             }
-        }
-        InvalidationStrategy::TypeBased => {
+            _ => vec![key.clone()],
+        },
+        InvalidationStrategy::TypeBased => match key {
             // Invalidate all entries of the same type
-            match key {
-                CacheKey::Site(_) => vec![CacheKey::SiteList("*".to_string())],
-                CacheKey::Device(_) => vec![CacheKey::DeviceList("*".to_string())],
-                _ => vec![key.clone()],
-            }
-        }
+            CacheKey::Object { kind, .. } => vec![CacheKey::lists(*kind, None)],
+            _ => vec![key.clone()],
+        },
+    }
+}
+
+/// List patterns an object owned by `owner` can show up in
+fn list_patterns(kind: ResourceKind, owner: CacheScope) -> Vec<CacheKey> {
+    match owner {
+        CacheScope::Global => vec![CacheKey::lists(kind, None)],
+        CacheScope::Tenant(_) => vec![
+            CacheKey::lists(kind, Some(owner)),
+            CacheKey::lists(kind, Some(CacheScope::Global)),
+        ],
     }
 }
 
@@ -102,26 +107,47 @@ mod tests {
 
     #[test]
     fn test_invalidation_strategy_never() {
-        let key = CacheKey::Site(1);
+        let key = CacheKey::site(CacheScope::Global, 1);
         let keys = get_invalidation_keys(&key, InvalidationStrategy::Never);
         assert!(keys.is_empty());
     }
 
     #[test]
     fn test_invalidation_strategy_write_through() {
-        let key = CacheKey::Site(1);
+        let key = CacheKey::site(CacheScope::Global, 1);
         let keys = get_invalidation_keys(&key, InvalidationStrategy::WriteThrough);
         assert_eq!(keys.len(), 1);
-        assert_eq!(keys[0], CacheKey::Site(1));
+        assert_eq!(keys[0], CacheKey::site(CacheScope::Global, 1));
     }
 
     #[test]
     fn test_invalidation_strategy_write_back() {
-        let key = CacheKey::Site(1);
+        let key = CacheKey::site(CacheScope::Global, 1);
         let keys = get_invalidation_keys(&key, InvalidationStrategy::WriteBack);
         assert_eq!(keys.len(), 2);
-        assert!(keys.contains(&CacheKey::Site(1)));
-        assert!(keys.contains(&CacheKey::SiteList("*".to_string())));
+        assert!(keys.contains(&CacheKey::site(CacheScope::Global, 1)));
+        assert!(keys.contains(&CacheKey::lists(ResourceKind::Site, None)));
+    }
+
+    #[test]
+    fn test_write_back_targets_owning_tenant_lists() {
+        let key = CacheKey::site(CacheScope::Tenant(5), 1);
+        let keys = get_invalidation_keys(&key, InvalidationStrategy::WriteBack);
+        assert_eq!(
+            keys,
+            vec![
+                key.clone(),
+                CacheKey::lists(ResourceKind::Site, Some(CacheScope::Tenant(5))),
+                CacheKey::lists(ResourceKind::Site, Some(CacheScope::Global)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalidation_strategy_type_based() {
+        let key = CacheKey::device(CacheScope::Tenant(3), 9);
+        let keys = get_invalidation_keys(&key, InvalidationStrategy::TypeBased);
+        assert_eq!(keys, vec![CacheKey::lists(ResourceKind::Device, None)]);
     }
 
     #[test]
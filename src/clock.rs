@@ -0,0 +1,105 @@
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Source of the current time for time-dependent components
+///
+/// Components default to [`SystemClock`]; tests inject a [`ManualClock`] and
+/// advance it instead of sleeping.
+pub trait Clock: Send + Sync {
+    /// Monotonic time, for measuring durations and TTLs
+    fn now_instant(&self) -> Instant;
+
+    /// Wall-clock time, for timestamps
+    fn now_utc(&self) -> chrono::DateTime<chrono::Utc>;
+}
+
+/// Clock shared between the components it drives
+pub type SharedClock = Arc<dyn Clock>;
+
+/// The real system time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl SystemClock {
+    pub fn shared() -> SharedClock {
+        Arc::new(SystemClock)
+    }
+}
+
+impl Clock for SystemClock {
+    fn now_instant(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn now_utc(&self) -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc::now()
+    }
+}
+
+/// Clock that only moves when advanced, for deterministic tests
+#[allow(dead_code)] // Used by tests
+#[derive(Debug)]
+pub struct ManualClock {
+    start_instant: Instant,
+    start_utc: chrono::DateTime<chrono::Utc>,
+    elapsed: Mutex<Duration>,
+}
+
+#[allow(dead_code)] // Used by tests
+impl ManualClock {
+    /// A clock stopped at the current system time
+    pub fn new() -> Self {
+        Self {
+            start_instant: Instant::now(),
+            start_utc: chrono::Utc::now(),
+            elapsed: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// A clock stopped at `at`, e.g. just before a time of day a test is about
+    pub fn starting_at(at: chrono::DateTime<chrono::Utc>) -> Self {
+        Self {
+            start_utc: at,
+            ..Self::new()
+        }
+    }
+
+    /// Move both the monotonic and the wall-clock time forward
+    pub fn advance(&self, by: Duration) {
+        *self.elapsed.lock() += by;
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_instant(&self) -> Instant {
+        self.start_instant + *self.elapsed.lock()
+    }
+
+    fn now_utc(&self) -> chrono::DateTime<chrono::Utc> {
+        self.start_utc + chrono::Duration::from_std(*self.elapsed.lock()).unwrap_or(chrono::Duration::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock_moves_only_when_advanced() {
+        let clock = ManualClock::new();
+        let (instant, utc) = (clock.now_instant(), clock.now_utc());
+        assert_eq!(clock.now_instant(), instant);
+        assert_eq!(clock.now_utc(), utc);
+
+        clock.advance(Duration::from_secs(90));
+        assert_eq!(clock.now_instant() - instant, Duration::from_secs(90));
+        assert_eq!(clock.now_utc() - utc, chrono::Duration::seconds(90));
+    }
+}
@@ -1,7 +1,220 @@
+use crate::business::approval::ApprovalRules;
+use crate::business::enrichment::EnrichmentConfig;
+use crate::business::maintenance::MaintenanceWindows;
+use crate::business::naming::NamingPolicy;
+use crate::business::order_claims::OrderClaimConfig;
+use crate::business::order_queue::OrderQueueConfig;
+use crate::business::order_service::{
+    CustomFieldSchemaCheckConfig, MissingTagPolicy, ProcessingDeadlineConfig, SiteNameCheckConfig,
+};
+use crate::business::transformation::TransformationProfiles;
+use crate::business::address::AddressNormalizer;
+use crate::business::validation::SiteStatusTransitions;
+use crate::business::vlan::VlanRanges;
+use crate::business::workflow::WorkflowRetentionConfig;
+use crate::database::DatabaseConfig;
+use crate::netbox::resilient_client::TimeoutConfig;
+use crate::netbox::transport::{PoolConfig, TransportConfig};
+use crate::observability::middleware::{CorsConfig, HttpCacheConfig};
+use crate::resilience::bulkhead::BulkheadConfig;
+use crate::resilience::circuit_breaker::CircuitBreakerConfig;
+use crate::resilience::degradation::DegradationConfig;
+use crate::resilience::rate_limit::RateLimitConfig;
+use crate::resilience::recovery::RecoveryProbeConfig;
+use crate::resilience::retry::{BackoffStrategy, RetryConfig};
+use crate::server::ApiConfig;
+use crate::security::tenant::{parse_tenant_mappings, TenantId, TenantMapping, DEFAULT_NETBOX_ENDPOINT};
+use crate::sync::SyncConfig;
+use crate::warmup::WarmupConfig;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
 pub struct Config {
     pub port: u16,
     pub netbox_url: String,
     pub netbox_token: String,
+    /// Further NetBox deployments tenants can be routed to, besides NETBOX_URL
+    pub netbox_endpoints: Vec<NetBoxEndpointConfig>,
+    pub degradation: DegradationConfig,
+    /// How delays between NetBox retries are randomized
+    pub retry_backoff: BackoffStrategy,
+    /// Retries of NetBox reads
+    pub read_retry: RetryConfig,
+    /// Retries of NetBox creates, updates and deletes
+    pub write_retry: RetryConfig,
+    /// Per-attempt and total deadlines of NetBox calls
+    pub netbox_timeouts: TimeoutConfig,
+    /// Delay after which NetBox reads are hedged; hedging is off when unset
+    pub hedge_delay: Option<Duration>,
+    /// Request budget of each NetBox deployment; calls aren't limited when unset
+    pub netbox_rate_limit: Option<RateLimitConfig>,
+    /// Concurrent reads each NetBox deployment is sent, and how long a read waits for a slot
+    pub netbox_read_bulkhead: BulkheadConfig,
+    /// Concurrent creates, updates and deletes each NetBox deployment is sent
+    pub netbox_write_bulkhead: BulkheadConfig,
+    /// How long to wait for in-flight requests to finish on shutdown
+    pub shutdown_grace_period: Duration,
+    /// How often orders stuck in Processing are reconciled against NetBox
+    pub reconcile_interval: Duration,
+    /// How long an order may stay in Processing before it is reconciled
+    pub reconcile_max_age: Duration,
+    /// How long an order may stay in Processing before health reports it as stuck
+    pub stuck_order_threshold: Duration,
+    /// How often NetBox credentials are re-checked against /api/status/
+    pub credential_check_interval: Duration,
+    /// Probe at startup whether the NetBox token may write, refusing orders if it can't
+    pub check_token_scope: bool,
+    /// How often the device type and role catalog is reloaded from NetBox
+    pub device_catalog_refresh_interval: Duration,
+    /// When the NetBox circuit breaker opens; consecutive failures unless a sliding window is configured
+    pub circuit_breaker: CircuitBreakerConfig,
+    /// Background probing that closes the circuit once NetBox is back
+    pub recovery_probe: RecoveryProbeConfig,
+    /// Tenants and environments whose orders need operator approval
+    pub approval: ApprovalRules,
+    /// VIDs each tenant's VLAN orders may use
+    pub vlan_ranges: VlanRanges,
+    /// Per-tenant transformation defaults for site orders
+    pub transformation_profiles: TransformationProfiles,
+    /// Transformation profiles, by template name, that onboarding copies to new tenants
+    pub onboarding_templates: TransformationProfiles,
+    /// Tag taxonomy and per-tenant overrides orders are enriched with
+    pub enrichment: EnrichmentConfig,
+    /// YAML file replacing `enrichment` at startup
+    pub enrichment_config_file: Option<PathBuf>,
+    /// Templates naming devices ordered without a name
+    pub device_naming: NamingPolicy,
+    /// JSON file replacing `device_naming` at startup
+    pub device_naming_file: Option<PathBuf>,
+    /// JSON file device name sequence counters are persisted to
+    pub device_name_sequences_file: Option<PathBuf>,
+    /// NetBox custom field keys orders may set
+    pub allowed_custom_fields: Vec<String>,
+    /// Pre-flight check for duplicate site names
+    pub site_name_check: SiteNameCheckConfig,
+    /// Pre-flight check of order custom fields against NetBox's custom field schema
+    pub custom_field_schema_check: CustomFieldSchemaCheckConfig,
+    /// How long an order may spend processing, and how often overdue orders are failed
+    pub processing_deadlines: ProcessingDeadlineConfig,
+    /// Freezes during which orders are parked or refused; `None` never freezes
+    pub maintenance: Option<MaintenanceWindows>,
+    /// Priorities orders parked during a freeze are released by
+    pub order_queue: OrderQueueConfig,
+    /// This replica's worker id and the lease it takes on parked orders it executes
+    pub order_claims: OrderClaimConfig,
+    /// Handling of site tags missing from NetBox; `None` sends them unchecked
+    pub missing_tag_policy: Option<MissingTagPolicy>,
+    /// Site status changes update orders may make
+    pub site_status_transitions: SiteStatusTransitions,
+    /// Normalization of order addresses, and whether unverified ones are refused
+    pub address_normalizer: AddressNormalizer,
+    /// Portal tenant IDs mapped to NetBox tenant IDs and endpoints, for tenant-scoped reads
+    pub tenant_mappings: HashMap<TenantId, TenantMapping>,
+    /// JSON file tenant mappings managed through the API are persisted to
+    pub tenant_mappings_file: Option<PathBuf>,
+    /// Passphrase tenants' own NetBox tokens are encrypted with; None refuses tenant tokens
+    pub tenant_token_key: Option<String>,
+    /// Secret NetBox signs its webhooks with; None refuses every webhook
+    pub netbox_webhook_secret: Option<String>,
+    /// Secret being rotated out, still accepted alongside `netbox_webhook_secret`
+    pub netbox_webhook_previous_secret: Option<String>,
+    /// How long finished orders are kept in memory
+    pub workflow_retention: WorkflowRetentionConfig,
+    /// JSON file workflows are kept in across restarts; None keeps them in memory only
+    pub workflow_store_file: Option<PathBuf>,
+    /// Postgres database workflows and tenant mappings are kept in, instead of their files
+    pub database: DatabaseConfig,
+    /// Refuse virtual networks whose CIDR overlaps another network of the tenant
+    pub reject_overlapping_virtual_networks: bool,
+    /// Largest NetBox response body read before giving up, after decompression
+    pub netbox_max_response_bytes: usize,
+    /// Proxy, extra CAs and client certificate for reaching NetBox
+    pub netbox_transport: TransportConfig,
+    /// Connection pool, keepalive and HTTP/2 settings for NetBox
+    pub netbox_pool: PoolConfig,
+    /// Log redacted NetBox request/response bodies at trace level
+    pub log_netbox_bodies: bool,
+    /// Allow NetBox fault injection via /admin/chaos; also needs NETGATE_ALLOW_FAULT_INJECTION=true
+    pub chaos_enabled: bool,
+    /// Origins, methods and headers browsers may use cross-origin; off without origins
+    pub cors: CorsConfig,
+    /// Cache-Control for list, resource and order status GETs
+    pub http_cache: HttpCacheConfig,
+    /// Cache priming before the replica reports ready
+    pub warmup: WarmupConfig,
+    /// Mirroring of tenants' sites and devices into a local read model
+    pub sync: SyncConfig,
+    /// OpenAPI metadata, mount path and deprecation of the served API
+    pub api: ApiConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            port: 8080,
+            netbox_url: "http://localhost:8000".to_string(),
+            netbox_token: String::new(),
+            netbox_endpoints: Vec::new(),
+            degradation: DegradationConfig::default(),
+            retry_backoff: BackoffStrategy::Full,
+            read_retry: RetryConfig::default(),
+            write_retry: RetryConfig::for_writes(),
+            netbox_timeouts: TimeoutConfig::default(),
+            hedge_delay: None,
+            netbox_rate_limit: None,
+            netbox_read_bulkhead: BulkheadConfig::default(),
+            netbox_write_bulkhead: BulkheadConfig::default_write(),
+            shutdown_grace_period: Duration::from_secs(30),
+            reconcile_interval: Duration::from_secs(300),
+            reconcile_max_age: Duration::from_secs(600),
+            stuck_order_threshold: Duration::from_secs(900),
+            credential_check_interval: Duration::from_secs(300),
+            check_token_scope: false,
+            device_catalog_refresh_interval: Duration::from_secs(300),
+            circuit_breaker: CircuitBreakerConfig::default(),
+            recovery_probe: RecoveryProbeConfig::default(),
+            approval: ApprovalRules::default(),
+            vlan_ranges: VlanRanges::default(),
+            transformation_profiles: TransformationProfiles::default(),
+            onboarding_templates: TransformationProfiles::default(),
+            enrichment: EnrichmentConfig::default(),
+            enrichment_config_file: None,
+            device_naming: NamingPolicy::default(),
+            device_naming_file: None,
+            device_name_sequences_file: None,
+            allowed_custom_fields: Vec::new(),
+            site_name_check: SiteNameCheckConfig::default(),
+            custom_field_schema_check: CustomFieldSchemaCheckConfig::default(),
+            processing_deadlines: ProcessingDeadlineConfig::default(),
+            maintenance: None,
+            order_queue: OrderQueueConfig::default(),
+            order_claims: OrderClaimConfig::default(),
+            missing_tag_policy: Some(MissingTagPolicy::Create),
+            site_status_transitions: SiteStatusTransitions::permissive(),
+            address_normalizer: AddressNormalizer::new(),
+            tenant_mappings: HashMap::new(),
+            tenant_mappings_file: None,
+            tenant_token_key: None,
+            netbox_webhook_secret: None,
+            netbox_webhook_previous_secret: None,
+            workflow_retention: WorkflowRetentionConfig::default(),
+            workflow_store_file: None,
+            database: DatabaseConfig::default(),
+            reject_overlapping_virtual_networks: false,
+            netbox_max_response_bytes: 64 * 1024 * 1024,
+            netbox_transport: TransportConfig::default(),
+            netbox_pool: PoolConfig::default(),
+            log_netbox_bodies: false,
+            chaos_enabled: false,
+            cors: CorsConfig::default(),
+            http_cache: HttpCacheConfig::default(),
+            warmup: WarmupConfig::default(),
+            sync: SyncConfig::default(),
+            api: ApiConfig::default(),
+        }
+    }
 }
 
 impl Config {
@@ -15,8 +228,184 @@ impl Config {
                 .unwrap_or_else(|_| "http://localhost:8000".to_string()),
             netbox_token: std::env::var("NETBOX_TOKEN")
                 .unwrap_or_else(|_| "".to_string()),
+            netbox_endpoints: NetBoxEndpointConfig::from_env(),
+            degradation: DegradationConfig::from_env(),
+            retry_backoff: std::env::var("NETBOX_RETRY_BACKOFF")
+                .ok()
+                .and_then(|strategy| strategy.parse().ok())
+                .unwrap_or(BackoffStrategy::Full),
+            read_retry: RetryConfig::from_env("NETBOX_READ_RETRY", RetryConfig::default()),
+            write_retry: RetryConfig::from_env("NETBOX_WRITE_RETRY", RetryConfig::for_writes()),
+            netbox_timeouts: TimeoutConfig::from_env(),
+            hedge_delay: std::env::var("NETBOX_HEDGE_DELAY_MS")
+                .ok()
+                .and_then(|ms| ms.parse().ok())
+                .map(Duration::from_millis),
+            netbox_rate_limit: RateLimitConfig::from_env(),
+            netbox_read_bulkhead: BulkheadConfig::from_env("NETBOX_BULKHEAD_READ", BulkheadConfig::default()),
+            netbox_write_bulkhead: BulkheadConfig::from_env("NETBOX_BULKHEAD_WRITE", BulkheadConfig::default_write()),
+            shutdown_grace_period: std::env::var("SHUTDOWN_GRACE_PERIOD_SECS")
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(Duration::from_secs(30)),
+            reconcile_interval: std::env::var("ORDER_RECONCILE_INTERVAL_SECS")
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(Duration::from_secs(300)),
+            reconcile_max_age: std::env::var("ORDER_RECONCILE_MAX_AGE_SECS")
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(Duration::from_secs(600)),
+            stuck_order_threshold: std::env::var("ORDER_STUCK_THRESHOLD_SECS")
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(Duration::from_secs(900)),
+            device_catalog_refresh_interval: std::env::var("DEVICE_CATALOG_REFRESH_SECS")
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(Duration::from_secs(300)),
+            credential_check_interval: std::env::var("NETBOX_CREDENTIAL_CHECK_INTERVAL_SECS")
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(Duration::from_secs(300)),
+            check_token_scope: std::env::var("NETBOX_TOKEN_SCOPE_CHECK")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(false),
+            circuit_breaker: CircuitBreakerConfig::from_env(),
+            recovery_probe: RecoveryProbeConfig::from_env(),
+            approval: ApprovalRules::from_env(),
+            vlan_ranges: VlanRanges::from_env(),
+            transformation_profiles: TransformationProfiles::from_env(),
+            onboarding_templates: TransformationProfiles::from_env_file("ONBOARDING_TEMPLATES_FILE"),
+            enrichment: EnrichmentConfig::default(),
+            enrichment_config_file: std::env::var("ENRICHMENT_CONFIG_FILE")
+                .ok()
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from),
+            device_naming: NamingPolicy::default(),
+            device_naming_file: std::env::var("DEVICE_NAMING_FILE")
+                .ok()
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from),
+            device_name_sequences_file: std::env::var("DEVICE_NAME_SEQUENCES_FILE")
+                .ok()
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from),
+            allowed_custom_fields: std::env::var("ALLOWED_CUSTOM_FIELDS")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|key| !key.is_empty())
+                .map(str::to_string)
+                .collect(),
+            site_name_check: SiteNameCheckConfig::from_env(),
+            custom_field_schema_check: CustomFieldSchemaCheckConfig::from_env(),
+            processing_deadlines: ProcessingDeadlineConfig::from_env(),
+            maintenance: MaintenanceWindows::from_env(),
+            order_queue: OrderQueueConfig::from_env(),
+            order_claims: OrderClaimConfig::from_env(),
+            missing_tag_policy: MissingTagPolicy::from_env(),
+            site_status_transitions: SiteStatusTransitions::from_env(),
+            address_normalizer: AddressNormalizer::from_env(),
+            tenant_mappings: parse_tenant_mappings(&std::env::var("TENANT_MAPPINGS").unwrap_or_default()),
+            tenant_mappings_file: std::env::var("TENANT_MAPPINGS_FILE")
+                .ok()
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from),
+            tenant_token_key: std::env::var("TENANT_TOKEN_KEY").ok().filter(|key| !key.is_empty()),
+            netbox_webhook_secret: std::env::var("NETBOX_WEBHOOK_SECRET").ok().filter(|secret| !secret.is_empty()),
+            netbox_webhook_previous_secret: std::env::var("NETBOX_WEBHOOK_PREVIOUS_SECRET")
+                .ok()
+                .filter(|secret| !secret.is_empty()),
+            workflow_retention: WorkflowRetentionConfig::from_env(),
+            workflow_store_file: std::env::var("WORKFLOW_STORE_FILE")
+                .ok()
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from),
+            database: DatabaseConfig::from_env(),
+            reject_overlapping_virtual_networks: std::env::var("VIRTUAL_NETWORK_REJECT_OVERLAP")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(false),
+            netbox_max_response_bytes: std::env::var("NETBOX_MAX_RESPONSE_BYTES")
+                .ok()
+                .and_then(|bytes| bytes.parse().ok())
+                .unwrap_or(64 * 1024 * 1024),
+            netbox_transport: TransportConfig::from_env(),
+            netbox_pool: PoolConfig::from_env(),
+            log_netbox_bodies: std::env::var("NETBOX_LOG_BODIES")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(false),
+            chaos_enabled: std::env::var("CHAOS_ENABLED")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(false),
+            cors: CorsConfig::from_env(),
+            http_cache: HttpCacheConfig::from_env(),
+            warmup: WarmupConfig::from_env(),
+            sync: SyncConfig::from_env(),
+            api: ApiConfig::from_env(),
         }
     }
+
+    /// This config pointed at another NetBox endpoint, for building its client
+    pub fn for_netbox_endpoint(&self, endpoint: &NetBoxEndpointConfig) -> Config {
+        Config {
+            netbox_url: endpoint.url.clone(),
+            netbox_token: endpoint.token.clone(),
+            netbox_endpoints: Vec::new(),
+            ..self.clone()
+        }
+    }
+}
+
+/// A named NetBox deployment, e.g. one per region
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetBoxEndpointConfig {
+    pub name: String,
+    pub url: String,
+    pub token: String,
+}
+
+impl NetBoxEndpointConfig {
+    /// Load the endpoints listed in NETBOX_ENDPOINTS (comma-separated names), each
+    /// from NETBOX_ENDPOINT_<NAME>_URL and NETBOX_ENDPOINT_<NAME>_TOKEN
+    ///
+    /// Endpoints missing their URL or token are skipped with a warning, so tenants
+    /// mapped to them are refused at startup.
+    pub fn from_env() -> Vec<Self> {
+        std::env::var("NETBOX_ENDPOINTS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .filter_map(|name| {
+                if name == DEFAULT_NETBOX_ENDPOINT {
+                    tracing::warn!("NetBox endpoint name '{}' is reserved for NETBOX_URL", name);
+                    return None;
+                }
+                let var = |suffix: &str| {
+                    let key = format!("NETBOX_ENDPOINT_{}_{}", name.to_uppercase().replace('-', "_"), suffix);
+                    std::env::var(key).ok().filter(|value| !value.is_empty())
+                };
+                match (var("URL"), var("TOKEN")) {
+                    (Some(url), Some(token)) => Some(Self { name: name.to_string(), url, token }),
+                    _ => {
+                        tracing::warn!("Ignoring NetBox endpoint '{}' without a URL and token", name);
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -94,6 +483,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_netbox_endpoints_from_env() {
+        std::env::set_var("NETBOX_ENDPOINTS", "eu, us-east,default,apac");
+        std::env::set_var("NETBOX_ENDPOINT_EU_URL", "https://netbox.eu.example.com");
+        std::env::set_var("NETBOX_ENDPOINT_EU_TOKEN", "eu-token");
+        std::env::set_var("NETBOX_ENDPOINT_US_EAST_URL", "https://netbox.us.example.com");
+        std::env::set_var("NETBOX_ENDPOINT_US_EAST_TOKEN", "us-token");
+        std::env::set_var("NETBOX_ENDPOINT_APAC_URL", "https://netbox.apac.example.com");
+
+        let endpoints = NetBoxEndpointConfig::from_env();
+
+        for name in ["NETBOX_ENDPOINTS", "NETBOX_ENDPOINT_EU_URL", "NETBOX_ENDPOINT_EU_TOKEN",
+            "NETBOX_ENDPOINT_US_EAST_URL", "NETBOX_ENDPOINT_US_EAST_TOKEN", "NETBOX_ENDPOINT_APAC_URL"]
+        {
+            std::env::remove_var(name);
+        }
+        let names: Vec<&str> = endpoints.iter().map(|endpoint| endpoint.name.as_str()).collect();
+        assert_eq!(names, vec!["eu", "us-east"]);
+        assert_eq!(endpoints[1].url, "https://netbox.us.example.com");
+        assert_eq!(endpoints[1].token, "us-token");
+
+        let config = Config::default().for_netbox_endpoint(&endpoints[0]);
+        assert_eq!(config.netbox_url, "https://netbox.eu.example.com");
+        assert_eq!(config.netbox_token, "eu-token");
+    }
+
     #[test]
     fn test_config_invalid_port() {
         // Save original values
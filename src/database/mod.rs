@@ -0,0 +1,40 @@
+//! Postgres database the workflow and tenant mapping stores can be kept in
+//!
+//! Only built with the `postgres` feature; [`DatabaseConfig`] is always there
+//! so a DATABASE_URL set for a build without it can be refused at startup.
+
+#[cfg(feature = "postgres")]
+mod postgres;
+
+#[cfg(feature = "postgres")]
+pub use postgres::*;
+
+/// Where the database is and how many connections it's sent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatabaseConfig {
+    /// `postgres://` URL; None keeps workflows and tenant mappings in files or memory
+    pub url: Option<String>,
+    /// Most connections kept open to the database
+    pub pool_size: u32,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self { url: None, pool_size: 10 }
+    }
+}
+
+impl DatabaseConfig {
+    /// Load from DATABASE_URL and DATABASE_POOL_SIZE
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            url: std::env::var("DATABASE_URL").ok().filter(|url| !url.is_empty()),
+            pool_size: std::env::var("DATABASE_POOL_SIZE")
+                .ok()
+                .and_then(|size| size.parse().ok())
+                .filter(|size| *size > 0)
+                .unwrap_or(defaults.pool_size),
+        }
+    }
+}
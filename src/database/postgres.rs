@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use sqlx::postgres::{PgConnectOptions, PgPool, PgPoolOptions};
+
+use crate::business::workflow::OrderWorkflow;
+use crate::business::workflow_store::{WorkflowStore, WorkflowStoreError};
+use crate::security::tenant::{MappingStoreError, TenantDisabled, TenantId, TenantMapping, TenantMappingStore};
+
+/// Migrations creating the store tables, applied by [`Database::connect`]
+static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!();
+
+/// Why the database couldn't be used at startup
+#[derive(Debug, thiserror::Error)]
+pub enum DatabaseError {
+    #[error("Failed to connect to the database: {0}")]
+    Connect(#[source] sqlx::Error),
+
+    #[error("Failed to apply database migrations: {0}")]
+    Migrate(#[from] sqlx::migrate::MigrateError),
+}
+
+/// Connection pool to a database migrated to the current schema
+#[derive(Debug, Clone)]
+pub struct Database {
+    pool: PgPool,
+    pool_size: u32,
+}
+
+impl Database {
+    /// Connect to the configured database and apply pending migrations
+    pub async fn connect(url: &str, pool_size: u32) -> Result<Self, DatabaseError> {
+        let options = url.parse::<PgConnectOptions>().map_err(DatabaseError::Connect)?;
+        let pool = PgPoolOptions::new()
+            .max_connections(pool_size)
+            .acquire_timeout(std::time::Duration::from_secs(5))
+            .connect_with(options)
+            .await
+            .map_err(DatabaseError::Connect)?;
+        MIGRATOR.run(&pool).await?;
+        Ok(Self { pool, pool_size })
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    /// Most connections kept open to the database
+    pub fn pool_size(&self) -> u32 {
+        self.pool_size
+    }
+
+    /// Open connections not in use right now
+    pub fn idle_connections(&self) -> u32 {
+        self.pool.num_idle() as u32
+    }
+
+    /// Check the database answers a query
+    pub async fn ping(&self) -> Result<(), sqlx::Error> {
+        sqlx::query("SELECT 1").execute(&self.pool).await.map(|_| ())
+    }
+}
+
+/// Workflows kept in the `order_workflows` table, replaced whole on every save
+#[derive(Debug, Clone)]
+pub struct PostgresWorkflowStore {
+    pool: PgPool,
+}
+
+impl PostgresWorkflowStore {
+    pub fn new(database: &Database) -> Self {
+        Self { pool: database.pool.clone() }
+    }
+}
+
+#[async_trait]
+impl WorkflowStore for PostgresWorkflowStore {
+    async fn load(&self) -> Result<Vec<OrderWorkflow>, WorkflowStoreError> {
+        let rows: Vec<(serde_json::Value,)> = sqlx::query_as("SELECT workflow FROM order_workflows ORDER BY order_id")
+            .fetch_all(&self.pool)
+            .await?;
+        let workflows = rows
+            .into_iter()
+            .map(|(workflow,)| serde_json::from_value(workflow))
+            .collect::<Result<_, _>>()?;
+        Ok(workflows)
+    }
+
+    async fn save(&self, workflows: &[OrderWorkflow]) -> Result<(), WorkflowStoreError> {
+        // One transaction, so a failed save leaves the previous workflows in place
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM order_workflows").execute(&mut *tx).await?;
+        sqlx::query(
+            "INSERT INTO order_workflows (order_id, workflow)
+             SELECT workflow->>'order_id', workflow FROM jsonb_array_elements($1) AS workflow",
+        )
+        .bind(sqlx::types::Json(workflows))
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+/// Tenant mappings kept in the `tenant_mappings` table, a row per tenant
+#[derive(Debug, Clone)]
+pub struct PostgresTenantMappingStore {
+    pool: PgPool,
+}
+
+impl PostgresTenantMappingStore {
+    pub fn new(database: &Database) -> Self {
+        Self { pool: database.pool.clone() }
+    }
+}
+
+type MappingRow = (
+    String,
+    i32,
+    Option<String>,
+    Option<String>,
+    Option<chrono::DateTime<chrono::Utc>>,
+    Option<String>,
+);
+
+#[async_trait]
+impl TenantMappingStore for PostgresTenantMappingStore {
+    async fn load(&self) -> Result<HashMap<TenantId, TenantMapping>, MappingStoreError> {
+        let rows: Vec<MappingRow> = sqlx::query_as(
+            "SELECT tenant_id, netbox_tenant_id, endpoint, netbox_token, disabled_at, disabled_by FROM tenant_mappings",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let mappings = rows
+            .into_iter()
+            .map(|(tenant_id, netbox_tenant_id, endpoint, netbox_token, disabled_at, disabled_by)| {
+                let mapping = TenantMapping {
+                    disabled: disabled_at.zip(disabled_by).map(|(at, by)| TenantDisabled { at, by }),
+                    ..TenantMapping::new(netbox_tenant_id, endpoint).with_netbox_token(netbox_token)
+                };
+                (tenant_id, mapping)
+            })
+            .collect();
+        Ok(mappings)
+    }
+
+    async fn put(&self, tenant_id: &TenantId, mapping: &TenantMapping) -> Result<(), MappingStoreError> {
+        sqlx::query(
+            "INSERT INTO tenant_mappings (tenant_id, netbox_tenant_id, endpoint, netbox_token, disabled_at, disabled_by)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (tenant_id) DO UPDATE SET
+                 netbox_tenant_id = EXCLUDED.netbox_tenant_id,
+                 endpoint = EXCLUDED.endpoint,
+                 netbox_token = EXCLUDED.netbox_token,
+                 disabled_at = EXCLUDED.disabled_at,
+                 disabled_by = EXCLUDED.disabled_by",
+        )
+        .bind(tenant_id)
+        .bind(mapping.netbox_tenant_id)
+        .bind(&mapping.endpoint)
+        .bind(&mapping.netbox_token)
+        .bind(mapping.disabled.as_ref().map(|disabled| disabled.at))
+        .bind(mapping.disabled.as_ref().map(|disabled| &disabled.by))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn delete(&self, tenant_id: &TenantId) -> Result<(), MappingStoreError> {
+        sqlx::query("DELETE FROM tenant_mappings WHERE tenant_id = $1")
+            .bind(tenant_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+/// DATABASE_URL_TEST pointed at a new, empty schema, or None when it is unset
+///
+/// Schemas are left behind, so point DATABASE_URL_TEST at a throwaway database.
+#[cfg(test)]
+pub(crate) async fn test_database_url() -> Option<String> {
+    use sqlx::Connection;
+
+    let Some(url) = std::env::var("DATABASE_URL_TEST").ok().filter(|url| !url.is_empty()) else {
+        eprintln!("DATABASE_URL_TEST is not set, skipping the Postgres test");
+        return None;
+    };
+    let schema = format!("netgate_test_{}", uuid::Uuid::new_v4().simple());
+    let mut connection = sqlx::PgConnection::connect(&url).await.unwrap();
+    sqlx::query(&format!("CREATE SCHEMA {}", schema)).execute(&mut connection).await.unwrap();
+    let separator = if url.contains('?') { '&' } else { '?' };
+    Some(format!("{}{}options=-c%20search_path%3D{}", url, separator, schema))
+}
+
+/// A migrated database in a schema of its own, or None when DATABASE_URL_TEST is unset
+#[cfg(test)]
+pub(crate) async fn test_database() -> Option<Database> {
+    let url = test_database_url().await?;
+    Some(Database::connect(&url, 2).await.unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_unreachable_database_fails_to_connect() {
+        let err = Database::connect("postgres://netgate@127.0.0.1:1/netgate", 1).await.unwrap_err();
+        assert!(matches!(err, DatabaseError::Connect(_)));
+        assert!(err.to_string().starts_with("Failed to connect to the database"));
+    }
+
+    #[tokio::test]
+    async fn test_migrations_are_applied_once() {
+        let Some(database) = test_database().await else {
+            return;
+        };
+        database.ping().await.unwrap();
+
+        // Running them again finds nothing left to apply
+        let applied: (i64,) = sqlx::query_as("SELECT count(*) FROM _sqlx_migrations")
+            .fetch_one(database.pool())
+            .await
+            .unwrap();
+        MIGRATOR.run(database.pool()).await.unwrap();
+        let reapplied: (i64,) = sqlx::query_as("SELECT count(*) FROM _sqlx_migrations")
+            .fetch_one(database.pool())
+            .await
+            .unwrap();
+        assert_eq!(applied, reapplied);
+        assert_eq!(applied.0, MIGRATOR.iter().count() as i64);
+    }
+}
@@ -0,0 +1,17 @@
+use poem_openapi::Union;
+use serde::{Deserialize, Serialize};
+
+/// A NetBox device type or role, by id or by slug or name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Union)]
+#[serde(untagged)]
+pub enum CatalogRef {
+    Id(i32),
+    /// Slug or name, resolved through the device catalog
+    Name(String),
+}
+
+impl From<i32> for CatalogRef {
+    fn from(id: i32) -> Self {
+        Self::Id(id)
+    }
+}
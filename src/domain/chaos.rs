@@ -0,0 +1,58 @@
+use poem_openapi::{Enum, Object};
+use serde::{Deserialize, Serialize};
+
+/// Error an injected fault fails a NetBox call with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+#[serde(rename_all = "snake_case")]
+#[oai(rename_all = "snake_case")]
+pub enum ChaosErrorKind {
+    /// HTTP 500, retried
+    ServerError,
+    /// HTTP 503, retried
+    Unavailable,
+    /// An attempt timing out, retried
+    Timeout,
+    /// HTTP 401; not retried and not counted by the circuit breaker
+    Authentication,
+}
+
+/// Faults to inject into NetBox calls for a while
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Object)]
+pub struct ChaosExperimentRequest {
+    /// Client operations to affect, e.g. `get_site` or `create_device`; all when empty
+    #[serde(default)]
+    #[oai(default)]
+    pub operations: Vec<String>,
+    /// Share of attempts failed with `error`, 0.0 to 1.0
+    pub failure_rate: Option<f64>,
+    /// Delay added to every affected attempt
+    pub latency_ms: Option<u64>,
+    /// Error of failed attempts; `server_error` when unset
+    pub error: Option<ChaosErrorKind>,
+    /// Seconds until the experiment switches itself off
+    pub duration_secs: u64,
+}
+
+/// A running fault injection experiment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Object)]
+pub struct ChaosExperiment {
+    pub experiment_id: String,
+    /// Affected client operations; all when empty
+    pub operations: Vec<String>,
+    pub failure_rate: f64,
+    pub latency_ms: Option<u64>,
+    pub error: ChaosErrorKind,
+    /// RFC 3339 timestamps
+    pub started_at: String,
+    pub expires_at: String,
+    /// Attempts failed and delayed so far
+    pub injected_errors: u64,
+    pub injected_delays: u64,
+}
+
+/// Fault injection state, as served by `GET /admin/chaos`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Object)]
+pub struct ChaosStatus {
+    /// The running experiment, if any
+    pub experiment: Option<ChaosExperiment>,
+}
@@ -0,0 +1,75 @@
+use poem_openapi::{Enum, Object};
+use serde::{Deserialize, Serialize};
+
+/// State of an inventory import job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+#[serde(rename_all = "snake_case")]
+#[oai(rename_all = "snake_case")]
+pub enum ImportJobStatus {
+    Running,
+    Completed,
+    /// Stopped on an error; importing the tenant again resumes from the cursor
+    Failed,
+}
+
+/// Which NetBox listing an import job is paging through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+#[serde(rename_all = "snake_case")]
+#[oai(rename_all = "snake_case")]
+pub enum ImportPhase {
+    Sites,
+    Devices,
+}
+
+/// Progress of importing a tenant's existing NetBox sites and devices
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Object)]
+pub struct ImportJob {
+    pub job_id: String,
+    pub tenant_id: String,
+    pub netbox_tenant_id: i32,
+    pub status: ImportJobStatus,
+    /// Also create a virtual resource mapped to each imported site and device
+    pub create_virtual_resources: bool,
+    /// Listing the next page is read from
+    pub phase: ImportPhase,
+    /// Offset of the next page within `phase`
+    pub offset: u32,
+    /// Sites and devices NetBox reported for the tenant, once known
+    pub sites_total: Option<i32>,
+    pub devices_total: Option<i32>,
+    pub sites_imported: u32,
+    pub devices_imported: u32,
+    /// Resources skipped because a workflow already accounts for them
+    pub skipped: u32,
+    pub virtual_resources_created: u32,
+    pub error: Option<String>,
+    /// RFC 3339 timestamps
+    pub started_at: String,
+    pub updated_at: String,
+    pub finished_at: Option<String>,
+}
+
+impl ImportJob {
+    pub fn new(tenant_id: String, netbox_tenant_id: i32, create_virtual_resources: bool) -> Self {
+        let now = chrono::Utc::now().to_rfc3339();
+        Self {
+            job_id: uuid::Uuid::new_v4().to_string(),
+            tenant_id,
+            netbox_tenant_id,
+            status: ImportJobStatus::Running,
+            create_virtual_resources,
+            phase: ImportPhase::Sites,
+            offset: 0,
+            sites_total: None,
+            devices_total: None,
+            sites_imported: 0,
+            devices_imported: 0,
+            skipped: 0,
+            virtual_resources_created: 0,
+            error: None,
+            started_at: now.clone(),
+            updated_at: now,
+            finished_at: None,
+        }
+    }
+}
@@ -1,5 +1,13 @@
+// The code the Union derive generates trips this lint
+#[allow(clippy::match_result_ok)]
+mod catalog_ref;
+pub mod chaos;
+pub mod import;
+pub mod onboarding;
 pub mod order;
 pub mod tenant;
+pub mod webhook;
 
+pub use catalog_ref::CatalogRef;
 pub use order::*;
 
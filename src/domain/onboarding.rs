@@ -0,0 +1,76 @@
+use poem_openapi::{Enum, Object};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::tenant::NewNetBoxTenant;
+
+/// Request to onboard a customer: its NetBox tenant, mapping and defaults in one go
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct OnboardTenantRequest {
+    pub tenant_id: String,
+    /// NetBox tenant to create for the customer
+    pub netbox_tenant: NewNetBoxTenant,
+    /// Named NetBox endpoint the tenant lives on; the default endpoint if unset
+    pub netbox_endpoint: Option<String>,
+    /// Onboarding template the tenant's transformation profile is copied from
+    pub profile_template: Option<String>,
+    /// Name of a virtual site to create for the tenant
+    pub default_virtual_site: Option<String>,
+}
+
+/// A step of onboarding a tenant, in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+#[serde(rename_all = "snake_case")]
+#[oai(rename_all = "snake_case")]
+pub enum OnboardingStepKind {
+    NetboxTenant,
+    TransformationProfile,
+    VirtualSite,
+    /// Registering the mapping, which makes the tenant live
+    TenantMapping,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+#[serde(rename_all = "snake_case")]
+#[oai(rename_all = "snake_case")]
+pub enum OnboardingStepStatus {
+    Done,
+    Failed,
+    /// Done, then undone because a later step failed
+    RolledBack,
+    /// Done, and undoing it failed; needs cleaning up by hand
+    RollbackFailed,
+}
+
+/// Outcome of one onboarding step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Object)]
+pub struct OnboardingStep {
+    pub step: OnboardingStepKind,
+    pub status: OnboardingStepStatus,
+    /// What the step created, or why it or its rollback failed
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+#[serde(rename_all = "snake_case")]
+#[oai(rename_all = "snake_case")]
+pub enum OnboardingStatus {
+    Completed,
+    /// A step failed and the steps before it were undone
+    RolledBack,
+}
+
+/// Audit record of onboarding a tenant
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Object)]
+pub struct TenantOnboarding {
+    pub onboarding_id: String,
+    pub tenant_id: String,
+    /// Admin who onboarded the tenant
+    pub requested_by: String,
+    pub status: OnboardingStatus,
+    pub netbox_tenant_id: Option<i32>,
+    pub steps: Vec<OnboardingStep>,
+    pub error: Option<String>,
+    /// RFC 3339 timestamps
+    pub started_at: String,
+    pub finished_at: String,
+}
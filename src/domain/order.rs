@@ -1,11 +1,241 @@
-use poem_openapi::Object;
+use poem_openapi::{Enum, Object};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::CatalogRef;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Object)]
 pub struct CreateSiteOrder {
     pub name: String,
     pub description: Option<String>,
     pub address: Option<String>,
+    /// NetBox region id; overrides the tenant's transformation profile
+    pub region: Option<i32>,
+    /// NetBox facility name; overrides the profile's facility naming scheme
+    pub facility: Option<String>,
+    /// Extra NetBox tags; normalized to slugs
+    #[oai(default)]
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// NetBox custom field values; keys must be on the configured allowlist
+    #[oai(default)]
+    #[serde(default)]
+    pub custom_fields: HashMap<String, serde_json::Value>,
+    /// Place in the queue of orders parked during a maintenance freeze; pop orders use their site's
+    #[oai(default)]
+    #[serde(default)]
+    pub priority: OrderPriority,
+}
+
+/// How urgently an order is executed when it has to wait its turn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Enum)]
+#[serde(rename_all = "snake_case")]
+#[oai(rename_all = "snake_case")]
+pub enum OrderPriority {
+    /// Bulk work such as imports
+    Low,
+    #[default]
+    Normal,
+    /// Emergencies; capped per tenant
+    High,
+}
+
+impl OrderPriority {
+    /// Highest first
+    pub const ALL: [OrderPriority; 3] = [OrderPriority::High, OrderPriority::Normal, OrderPriority::Low];
+}
+
+/// Order for a device in an existing NetBox site
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct CreateDeviceOrder {
+    pub name: String,
+    /// NetBox device type id, slug or model name
+    pub device_type: CatalogRef,
+    /// NetBox device role id, slug or name
+    pub role: CatalogRef,
+    /// Target site by id; exactly one of `site_id` and `site_slug` is required
+    pub site_id: Option<i32>,
+    /// Target site by slug
+    pub site_slug: Option<String>,
+    pub serial: Option<String>,
+    #[oai(default)]
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Interfaces created on the device once it exists
+    #[oai(default)]
+    #[serde(default)]
+    pub interfaces: Vec<DeviceInterfaceDefinition>,
+    /// Cables from the device's new interfaces to existing devices
+    #[oai(default)]
+    #[serde(default)]
+    pub uplinks: Vec<DeviceUplink>,
+}
+
+/// Interface of a device order
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct DeviceInterfaceDefinition {
+    pub name: String,
+    /// NetBox interface type slug, e.g. "1000base-t" or "virtual"
+    #[oai(rename = "type")]
+    #[serde(rename = "type")]
+    pub interface_type: String,
+    /// Defaults to enabled
+    pub enabled: Option<bool>,
+    pub description: Option<String>,
+}
+
+/// Cable from one of the ordered device's interfaces to an interface of an existing device
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct DeviceUplink {
+    /// Name of an interface in the same order
+    pub local_interface: String,
+    /// NetBox id of the device at the far end
+    pub remote_device: i32,
+    /// Name of the interface on the far-end device
+    pub remote_interface: String,
+}
+
+/// Order for an IPAM prefix
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct CreateNetworkOrder {
+    /// Prefix in CIDR notation, e.g. "10.20.0.0/24"
+    pub prefix: String,
+    pub description: Option<String>,
+    /// NetBox VLAN id
+    pub vlan: Option<i32>,
+}
+
+/// Order for a VLAN at an existing NetBox site
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct CreateVlanOrder {
+    /// NetBox site id
+    pub site_id: i32,
+    pub name: String,
+    /// 802.1Q VLAN id; left out, the lowest free VID in the tenant's ranges is assigned
+    pub vid: Option<i32>,
+    /// NetBox VLAN group id
+    pub group: Option<i32>,
+    pub description: Option<String>,
+    #[oai(default)]
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Order for a virtual machine in an existing NetBox cluster
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct CreateVmOrder {
+    pub name: String,
+    /// NetBox cluster id; the VM takes the cluster's site
+    pub cluster: i32,
+    pub vcpus: Option<f64>,
+    /// Memory in MB
+    pub memory: Option<i32>,
+    /// Disk in GB
+    pub disk: Option<i32>,
+    /// NetBox device role id
+    pub role: Option<i32>,
+    pub description: Option<String>,
+    #[oai(default)]
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Order for the next free IP address of an existing prefix
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct AllocateIpOrder {
+    /// NetBox prefix id; the prefix must belong to the ordering tenant
+    pub prefix_id: i32,
+    /// NetBox id of the device to assign the address to; requires `interface`
+    pub device_id: Option<i32>,
+    /// Name of the device's interface that gets the address
+    pub interface: Option<String>,
+    pub dns_name: Option<String>,
+    pub description: Option<String>,
+    #[oai(default)]
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Device of a pop order; it is placed in the pop's site
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct PopDeviceDefinition {
+    /// May be left out if the tenant has a device naming template
+    #[oai(default)]
+    #[serde(default)]
+    pub name: String,
+    /// NetBox device type id
+    pub device_type: i32,
+    /// NetBox device role id
+    pub role: i32,
+    pub serial: Option<String>,
+    #[oai(default)]
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl PopDeviceDefinition {
+    /// Device order for this definition, targeting the given site
+    pub fn for_site(&self, site_id: Option<i32>) -> CreateDeviceOrder {
+        CreateDeviceOrder {
+            name: self.name.clone(),
+            device_type: self.device_type.into(),
+            role: self.role.into(),
+            site_id,
+            site_slug: None,
+            serial: self.serial.clone(),
+            tags: self.tags.clone(),
+            interfaces: Vec::new(),
+            uplinks: Vec::new(),
+        }
+    }
+}
+
+/// Order for a point of presence: one site plus the devices installed in it
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct CreatePopOrder {
+    pub site: CreateSiteOrder,
+    pub devices: Vec<PopDeviceDefinition>,
+}
+
+/// Order to change an existing NetBox site of the tenant; fields left out are not changed
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Object)]
+pub struct UpdateSiteOrder {
+    /// Target site by NetBox id; exactly one of `site_id` and `site_slug` is required
+    pub site_id: Option<i32>,
+    /// Target site by slug
+    pub site_slug: Option<String>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub address: Option<String>,
+    /// `active`, `planned`, `retired` or `staging`
+    pub status: Option<String>,
+    /// NetBox region id
+    pub region: Option<i32>,
+    pub facility: Option<String>,
+    /// NetBox tenant id; a site can't be moved to another tenant
+    pub tenant: Option<i32>,
+    /// Replaces the site's tags; normalized to slugs
+    pub tags: Option<Vec<String>>,
+    /// The site's `last_updated` (RFC 3339) as the caller last saw it; if the
+    /// site changed since, the update is refused with 409
+    pub last_updated: Option<String>,
+    /// Update even if the site changed since `last_updated`
+    #[oai(default)]
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// Order to delete an existing NetBox site of the tenant
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct DecommissionSiteOrder {
+    /// NetBox site id
+    pub site_id: i32,
+    /// Delete the site's devices first; without it a site with devices is refused
+    #[oai(default)]
+    #[serde(default)]
+    pub force: bool,
+    /// Why the site is decommissioned, logged with the deleted resources
+    pub reason: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Object)]
@@ -39,6 +269,11 @@ mod tests {
             name: "Test Site".to_string(),
             description: Some("Test Description".to_string()),
             address: Some("123 Test St".to_string()),
+            region: None,
+            facility: None,
+            tags: vec![],
+            custom_fields: Default::default(),
+            priority: Default::default(),
         };
 
         let site = Site::from_order(order, "tenant1".to_string());
@@ -56,6 +291,11 @@ mod tests {
             name: "Minimal Site".to_string(),
             description: None,
             address: None,
+            region: None,
+            facility: None,
+            tags: vec![],
+            custom_fields: Default::default(),
+            priority: Default::default(),
         };
 
         let site = Site::from_order(order, "tenant2".to_string());
@@ -72,6 +312,11 @@ mod tests {
             name: "Site".to_string(),
             description: None,
             address: None,
+            region: None,
+            facility: None,
+            tags: vec![],
+            custom_fields: Default::default(),
+            priority: Default::default(),
         };
 
         let site1 = Site::from_order(order.clone(), "tenant1".to_string());
@@ -1,31 +1,140 @@
 use std::collections::HashMap;
-use std::sync::RwLock;
+use parking_lot::RwLock;
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
 
 use crate::domain::Site;
+use crate::domain::webhook::WebhookRegistration;
+use crate::security::tenant::TenantMapping;
 
 pub type TenantId = String;
 
+/// NetBox tenant to create for a new portal tenant
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct NewNetBoxTenant {
+    pub name: String,
+    pub slug: String,
+    pub description: Option<String>,
+}
+
+/// Request to register a portal tenant
+///
+/// Set exactly one of `netbox_tenant_id`, to map to an existing NetBox tenant,
+/// or `netbox_tenant`, to create one.
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct RegisterTenantRequest {
+    pub tenant_id: String,
+    pub netbox_tenant_id: Option<i32>,
+    pub netbox_tenant: Option<NewNetBoxTenant>,
+    /// Named NetBox endpoint the tenant lives on; the default endpoint if unset
+    pub netbox_endpoint: Option<String>,
+    /// The tenant's own NetBox API token, so its writes are attributed to its
+    /// account; checked against NetBox and stored encrypted
+    pub netbox_token: Option<String>,
+}
+
+/// Request to map a tenant to another NetBox tenant
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct UpdateTenantMappingRequest {
+    pub netbox_tenant_id: i32,
+    /// Named NetBox endpoint; the default endpoint if unset
+    pub netbox_endpoint: Option<String>,
+    /// New NetBox API token for the tenant; if unset the current token is kept
+    /// while the endpoint stays the same
+    pub netbox_token: Option<String>,
+}
+
+/// Portal tenant and the NetBox tenant it maps to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Object)]
+pub struct TenantInfo {
+    pub tenant_id: String,
+    pub netbox_tenant_id: i32,
+    /// Named NetBox endpoint; None for the default endpoint
+    pub netbox_endpoint: Option<String>,
+    /// Whether the tenant calls NetBox with its own token (never returned)
+    pub has_netbox_token: bool,
+    /// When the tenant was soft-deleted (RFC 3339); None while it is active
+    pub disabled_at: Option<String>,
+    /// Admin who soft-deleted the tenant
+    pub disabled_by: Option<String>,
+}
+
+impl TenantInfo {
+    pub fn new(tenant_id: String, mapping: TenantMapping) -> Self {
+        let (disabled_at, disabled_by) = match mapping.disabled {
+            Some(disabled) => (Some(disabled.at.to_rfc3339()), Some(disabled.by)),
+            None => (None, None),
+        };
+        Self {
+            tenant_id,
+            netbox_tenant_id: mapping.netbox_tenant_id,
+            has_netbox_token: mapping.netbox_token.is_some(),
+            netbox_endpoint: mapping.endpoint,
+            disabled_at,
+            disabled_by,
+        }
+    }
+}
+
 pub struct TenantStore {
     // Map from tenant_id to Vec<Site>
     sites: RwLock<HashMap<TenantId, Vec<Site>>>,
+    webhooks: RwLock<HashMap<TenantId, Vec<WebhookRegistration>>>,
 }
 
 impl TenantStore {
     pub fn new() -> Self {
         Self {
             sites: RwLock::new(HashMap::new()),
+            webhooks: RwLock::new(HashMap::new()),
         }
     }
 
     pub fn add_site(&self, tenant_id: TenantId, site: Site) {
-        let mut sites = self.sites.write().unwrap();
-        sites.entry(tenant_id).or_insert_with(Vec::new).push(site);
+        let mut sites = self.sites.write();
+        sites.entry(tenant_id).or_default().push(site);
     }
 
     pub fn get_sites(&self, tenant_id: &TenantId) -> Vec<Site> {
-        let sites = self.sites.read().unwrap();
+        let sites = self.sites.read();
         sites.get(tenant_id).cloned().unwrap_or_default()
     }
+
+    pub fn add_webhook(&self, webhook: WebhookRegistration) {
+        let mut webhooks = self.webhooks.write();
+        webhooks.entry(webhook.tenant_id.clone()).or_default().push(webhook);
+    }
+
+    pub fn get_webhooks(&self, tenant_id: &TenantId) -> Vec<WebhookRegistration> {
+        let webhooks = self.webhooks.read();
+        webhooks.get(tenant_id).cloned().unwrap_or_default()
+    }
+
+    /// Remove a tenant's webhook, returning whether it existed
+    pub fn remove_webhook(&self, tenant_id: &TenantId, webhook_id: &str) -> bool {
+        let mut webhooks = self.webhooks.write();
+        match webhooks.get_mut(tenant_id) {
+            Some(registered) => {
+                let before = registered.len();
+                registered.retain(|w| w.id != webhook_id);
+                registered.len() != before
+            }
+            None => false,
+        }
+    }
+
+    /// Apply `update` to a tenant's webhook, returning it as updated
+    pub fn update_webhook(
+        &self,
+        tenant_id: &TenantId,
+        webhook_id: &str,
+        update: impl FnOnce(&mut WebhookRegistration),
+    ) -> Option<WebhookRegistration> {
+        let mut webhooks = self.webhooks.write();
+        let webhook = webhooks.get_mut(tenant_id)?.iter_mut().find(|w| w.id == webhook_id)?;
+        update(webhook);
+        Some(webhook.clone())
+    }
 }
 
 impl Default for TenantStore {
@@ -95,5 +204,52 @@ mod tests {
         let sites = store.get_sites(&"nonexistent".to_string());
         assert!(sites.is_empty());
     }
-}
 
+    fn create_test_webhook(tenant_id: &str) -> WebhookRegistration {
+        WebhookRegistration::from_request(
+            crate::domain::webhook::RegisterWebhookRequest {
+                url: "https://example.com/hook".to_string(),
+                secret: "s3cret".to_string(),
+                events: vec![],
+            },
+            tenant_id.to_string(),
+        )
+    }
+
+    #[test]
+    fn test_webhooks_are_tenant_scoped() {
+        let store = TenantStore::new();
+        let webhook = create_test_webhook("tenant1");
+        store.add_webhook(webhook.clone());
+
+        assert_eq!(store.get_webhooks(&"tenant1".to_string()).len(), 1);
+        assert!(store.get_webhooks(&"tenant2".to_string()).is_empty());
+        // Another tenant cannot remove it
+        assert!(!store.remove_webhook(&"tenant2".to_string(), &webhook.id));
+    }
+
+    #[test]
+    fn test_remove_webhook() {
+        let store = TenantStore::new();
+        let webhook = create_test_webhook("tenant1");
+        store.add_webhook(webhook.clone());
+
+        assert!(store.remove_webhook(&"tenant1".to_string(), &webhook.id));
+        assert!(store.get_webhooks(&"tenant1".to_string()).is_empty());
+        assert!(!store.remove_webhook(&"tenant1".to_string(), &webhook.id));
+    }
+
+    #[test]
+    fn test_update_webhook() {
+        let store = TenantStore::new();
+        let webhook = create_test_webhook("tenant1");
+        store.add_webhook(webhook.clone());
+
+        let rotate = |w: &mut WebhookRegistration| w.rotate_secret("n3w".to_string());
+        assert!(store.update_webhook(&"tenant2".to_string(), &webhook.id, rotate).is_none());
+        let updated = store.update_webhook(&"tenant1".to_string(), &webhook.id, rotate).unwrap();
+
+        assert_eq!(updated.previous_secret.as_deref(), Some("s3cret"));
+        assert_eq!(store.get_webhooks(&"tenant1".to_string())[0].secret, "n3w");
+    }
+}
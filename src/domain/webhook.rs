@@ -0,0 +1,149 @@
+use poem_openapi::{Enum, Object};
+use serde::{Deserialize, Serialize};
+
+/// Order lifecycle events a webhook can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+#[serde(rename_all = "snake_case")]
+#[oai(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    OrderCompleted,
+    OrderFailed,
+    OrderCancelled,
+}
+
+/// Request to register a webhook for a tenant
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct RegisterWebhookRequest {
+    pub url: String,
+    /// Shared secret used to sign deliveries
+    pub secret: String,
+    /// Events to deliver; all events when empty or omitted
+    #[oai(default)]
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+}
+
+/// Webhook registered by a tenant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookRegistration {
+    pub id: String,
+    pub tenant_id: String,
+    pub url: String,
+    pub secret: String,
+    /// Secret being rotated out; deliveries are signed with both until it is retired
+    #[serde(default)]
+    pub previous_secret: Option<String>,
+    pub events: Vec<WebhookEvent>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl WebhookRegistration {
+    pub fn from_request(request: RegisterWebhookRequest, tenant_id: String) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            tenant_id,
+            url: request.url,
+            secret: request.secret,
+            previous_secret: None,
+            events: request.events,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    /// Secrets deliveries are signed with, the current one first
+    pub fn secrets(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.secret.as_str()).chain(self.previous_secret.as_deref())
+    }
+
+    /// Start signing with `secret`, keeping the current one active until retired
+    pub fn rotate_secret(&mut self, secret: String) {
+        self.previous_secret = Some(std::mem::replace(&mut self.secret, secret));
+    }
+
+    /// Check if this webhook wants the given event
+    pub fn accepts(&self, event: WebhookEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+/// Webhook registration as returned by the API (the secret is never echoed back)
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct WebhookInfo {
+    pub id: String,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    /// Whether deliveries are still also signed with the previous secret
+    pub rotating_secret: bool,
+    pub created_at: String,
+}
+
+/// Request to rotate a webhook's signing secret
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+pub struct RotateWebhookSecretRequest {
+    /// New secret; the current one stays active until the previous secret is retired
+    pub secret: String,
+}
+
+impl From<&WebhookRegistration> for WebhookInfo {
+    fn from(webhook: &WebhookRegistration) -> Self {
+        Self {
+            id: webhook.id.clone(),
+            url: webhook.url.clone(),
+            events: webhook.events.clone(),
+            rotating_secret: webhook.previous_secret.is_some(),
+            created_at: webhook.created_at.to_rfc3339(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_request(events: Vec<WebhookEvent>) -> RegisterWebhookRequest {
+        RegisterWebhookRequest {
+            url: "https://example.com/hook".to_string(),
+            secret: "s3cret".to_string(),
+            events,
+        }
+    }
+
+    #[test]
+    fn test_empty_event_filter_accepts_all() {
+        let webhook = WebhookRegistration::from_request(create_request(vec![]), "tenant1".to_string());
+
+        assert!(webhook.accepts(WebhookEvent::OrderCompleted));
+        assert!(webhook.accepts(WebhookEvent::OrderFailed));
+        assert!(webhook.accepts(WebhookEvent::OrderCancelled));
+    }
+
+    #[test]
+    fn test_event_filter() {
+        let webhook = WebhookRegistration::from_request(
+            create_request(vec![WebhookEvent::OrderFailed]),
+            "tenant1".to_string(),
+        );
+
+        assert!(webhook.accepts(WebhookEvent::OrderFailed));
+        assert!(!webhook.accepts(WebhookEvent::OrderCompleted));
+    }
+
+    #[test]
+    fn test_rotation_signs_with_both_secrets() {
+        let mut webhook = WebhookRegistration::from_request(create_request(vec![]), "tenant1".to_string());
+        assert_eq!(webhook.secrets().collect::<Vec<_>>(), vec!["s3cret"]);
+
+        webhook.rotate_secret("n3w".to_string());
+        assert_eq!(webhook.secrets().collect::<Vec<_>>(), vec!["n3w", "s3cret"]);
+        assert!(WebhookInfo::from(&webhook).rotating_secret);
+    }
+
+    #[test]
+    fn test_webhook_info_omits_secret() {
+        let webhook = WebhookRegistration::from_request(create_request(vec![]), "tenant1".to_string());
+        let json = serde_json::to_value(WebhookInfo::from(&webhook)).unwrap();
+
+        assert!(json.get("secret").is_none());
+        assert_eq!(json["url"], "https://example.com/hook");
+    }
+}
@@ -1,18 +1,57 @@
-use poem::http::StatusCode;
-use poem::Error as PoemError;
+use poem::http::{header, StatusCode};
+use poem::{Error as PoemError, Response};
+use serde_json::json;
+use std::time::Duration;
 use thiserror::Error;
 
+use crate::netbox::error::{NetBoxError, NetBoxValidationErrors};
+
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("Unauthorized: missing or invalid tenant ID")]
     Unauthorized,
     
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+    
+    /// The tenant was soft-deleted; only its order history stays readable
+    #[error("Tenant disabled: {0}")]
+    TenantDisabled(String),
+    
     #[error("Not found: {0}")]
     NotFound(String),
     
     #[error("Validation error: {0}")]
     ValidationError(String),
     
+    /// NetBox rejected the request with field-level errors
+    #[error("NetBox validation error: {0}")]
+    NetBoxValidation(NetBoxValidationErrors),
+    
+    #[error("Conflict: {0}")]
+    Conflict(String),
+    
+    /// The tenant already has `limit` of `resource`, and `current` in use
+    #[error("Quota exceeded: {current} of {limit} {resource} in use")]
+    QuotaExceeded { resource: String, limit: u64, current: u64 },
+    
+    /// Too many requests, here or at NetBox; the client may try again after `retry_after`
+    #[error("Rate limited: retry after {}s", retry_after_secs(.retry_after))]
+    RateLimited { retry_after: Duration },
+    
+    #[error("Service unavailable: {0}")]
+    ServiceUnavailable(String),
+    
+    /// NetBox is struggling (open circuit, timeouts) and no fallback could stand in
+    ///
+    /// Reads that a fallback does answer succeed with their `degraded` flag set instead.
+    #[error("Service degraded: {0}")]
+    ServiceDegraded(String),
+    
+    /// Too many NetBox calls already in flight; this one was refused rather than queued
+    #[error("Overloaded: {0}")]
+    Overloaded(String),
+    
     #[error("Internal server error: {0}")]
     Internal(#[from] anyhow::Error),
 }
@@ -21,16 +60,261 @@ impl AppError {
     pub fn status_code(&self) -> StatusCode {
         match self {
             AppError::Unauthorized => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::TenantDisabled(_) => StatusCode::FORBIDDEN,
             AppError::NotFound(_) => StatusCode::NOT_FOUND,
             AppError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            AppError::NetBoxValidation(_) => StatusCode::BAD_REQUEST,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::QuotaExceeded { .. } => StatusCode::TOO_MANY_REQUESTS,
+            AppError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            AppError::ServiceUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::ServiceDegraded(_) => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::Overloaded(_) => StatusCode::SERVICE_UNAVAILABLE,
             AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
+
+    /// Kebab-case name of the kind of error, ending its problem `type`
+    fn problem_kind(&self) -> &'static str {
+        match self {
+            AppError::Unauthorized => "unauthorized",
+            AppError::Forbidden(_) => "forbidden",
+            AppError::TenantDisabled(_) => "tenant-disabled",
+            AppError::NotFound(_) => "not-found",
+            AppError::ValidationError(_) => "validation-error",
+            AppError::NetBoxValidation(_) => "netbox-validation-error",
+            AppError::Conflict(_) => "conflict",
+            AppError::QuotaExceeded { .. } => "quota-exceeded",
+            AppError::RateLimited { .. } => "rate-limited",
+            AppError::ServiceUnavailable(_) => "service-unavailable",
+            AppError::ServiceDegraded(_) => "service-degraded",
+            AppError::Overloaded(_) => "overloaded",
+            AppError::Internal(_) => "internal",
+        }
+    }
+
+    /// Summary of the kind of error, the same for every occurrence
+    fn problem_title(&self) -> &'static str {
+        match self {
+            AppError::Unauthorized => "Unauthorized",
+            AppError::Forbidden(_) => "Forbidden",
+            AppError::TenantDisabled(_) => "Tenant disabled",
+            AppError::NotFound(_) => "Not found",
+            AppError::ValidationError(_) => "Validation error",
+            AppError::NetBoxValidation(_) => "NetBox validation error",
+            AppError::Conflict(_) => "Conflict",
+            AppError::QuotaExceeded { .. } => "Quota exceeded",
+            AppError::RateLimited { .. } => "Rate limited",
+            AppError::ServiceUnavailable(_) => "Service unavailable",
+            AppError::ServiceDegraded(_) => "Service degraded",
+            AppError::Overloaded(_) => "Overloaded",
+            AppError::Internal(_) => "Internal server error",
+        }
+    }
+
+    /// RFC 9457 problem details document describing this error
+    pub fn problem_details(&self) -> serde_json::Value {
+        let mut problem = json!({
+            "type": format!("urn:netgate:problem:{}", self.problem_kind()),
+            "title": self.problem_title(),
+            "status": self.status_code().as_u16(),
+            "detail": self.to_string(),
+        });
+        if let Some(secs) = self.retry_after_secs() {
+            problem["retry_after"] = json!(secs);
+        }
+        problem
+    }
+
+    /// Whether NetBox couldn't serve the request right now: unavailable, degraded or overloaded
+    ///
+    /// Handlers answer these with 503, so clients retry instead of treating them as bugs.
+    pub fn is_unavailable(&self) -> bool {
+        matches!(
+            self,
+            AppError::ServiceUnavailable(_) | AppError::ServiceDegraded(_) | AppError::Overloaded(_)
+        )
+    }
+
+    /// Seconds to send as `Retry-After`, for errors worth retrying later
+    pub fn retry_after_secs(&self) -> Option<u64> {
+        match self {
+            AppError::RateLimited { retry_after } => Some(retry_after_secs(retry_after)),
+            _ => None,
+        }
+    }
+}
+
+/// Whole seconds, rounded up so a client never retries too early
+fn retry_after_secs(retry_after: &Duration) -> u64 {
+    retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0)
+}
+
+/// NetBox field-level validation errors become 400s, missing objects 404s, rejected
+/// credentials 503s, exhausted prefixes and throttling 429s and timeouts 503s;
+/// everything else stays internal
+impl From<NetBoxError> for AppError {
+    fn from(err: NetBoxError) -> Self {
+        match err {
+            NetBoxError::RateLimited { retry_after } => AppError::RateLimited { retry_after },
+            timeout @ NetBoxError::Timeout(_) => AppError::ServiceDegraded(timeout.to_string()),
+            NetBoxError::ValidationError { errors: Some(errors), .. } => AppError::NetBoxValidation(errors),
+            NetBoxError::NotFound(message) => AppError::NotFound(message),
+            NetBoxError::AuthenticationError(message) => {
+                AppError::ServiceUnavailable(format!("NetBox rejected the configured credentials: {}", message))
+            }
+            NetBoxError::Exhausted { prefix_id, capacity } => AppError::QuotaExceeded {
+                resource: format!("prefix {} addresses", prefix_id),
+                limit: capacity,
+                current: capacity,
+            },
+            unavailable @ NetBoxError::ServiceUnavailable { .. } => AppError::ServiceUnavailable(unavailable.to_string()),
+            other => AppError::Internal(anyhow::Error::from(other)),
+        }
+    }
 }
 
+/// Errors render as `application/problem+json`, with `Retry-After` when there is a time to wait
 impl From<AppError> for PoemError {
     fn from(err: AppError) -> Self {
-        PoemError::from_string(err.to_string(), err.status_code())
+        let mut response = Response::builder()
+            .status(err.status_code())
+            .content_type("application/problem+json");
+        if let Some(secs) = err.retry_after_secs() {
+            response = response.header(header::RETRY_AFTER, secs);
+        }
+        PoemError::from_response(response.body(err.problem_details().to_string()))
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Status, `Retry-After` and problem document of the response `error` renders as
+    async fn rendered(error: AppError) -> (StatusCode, Option<String>, serde_json::Value) {
+        let response = PoemError::from(error).into_response();
+        assert_eq!(response.content_type(), Some("application/problem+json"));
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(header::RETRY_AFTER)
+            .map(|value| value.to_str().unwrap().to_string());
+        (status, retry_after, response.into_body().into_json().await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_variants_map_to_their_status_and_body() {
+        let cases = [
+            (
+                AppError::Conflict("Site name 'ams1' already exists".to_string()),
+                StatusCode::CONFLICT,
+                "conflict",
+                "Conflict",
+                "Conflict: Site name 'ams1' already exists",
+            ),
+            (
+                AppError::QuotaExceeded { resource: "sites".to_string(), limit: 10, current: 10 },
+                StatusCode::TOO_MANY_REQUESTS,
+                "quota-exceeded",
+                "Quota exceeded",
+                "Quota exceeded: 10 of 10 sites in use",
+            ),
+            (
+                AppError::NotFound("Site with ID 99 not found".to_string()),
+                StatusCode::NOT_FOUND,
+                "not-found",
+                "Not found",
+                "Not found: Site with ID 99 not found",
+            ),
+            (
+                AppError::ServiceDegraded("NetBox circuit breaker open".to_string()),
+                StatusCode::SERVICE_UNAVAILABLE,
+                "service-degraded",
+                "Service degraded",
+                "Service degraded: NetBox circuit breaker open",
+            ),
+            (
+                AppError::Overloaded("Bulkhead full".to_string()),
+                StatusCode::SERVICE_UNAVAILABLE,
+                "overloaded",
+                "Overloaded",
+                "Overloaded: Bulkhead full",
+            ),
+            (
+                AppError::TenantDisabled("acme".to_string()),
+                StatusCode::FORBIDDEN,
+                "tenant-disabled",
+                "Tenant disabled",
+                "Tenant disabled: acme",
+            ),
+        ];
+        for (error, status, kind, title, detail) in cases {
+            assert_eq!(error.status_code(), status, "{}", error);
+            let problem = json!({
+                "type": format!("urn:netgate:problem:{}", kind),
+                "title": title,
+                "status": status.as_u16(),
+                "detail": detail,
+            });
+            assert_eq!(rendered(error).await, (status, None, problem));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_sends_retry_after_rounded_up() {
+        let error = AppError::RateLimited { retry_after: Duration::from_millis(1500) };
+        assert_eq!(error.retry_after_secs(), Some(2));
+
+        let (status, retry_after, problem) = rendered(error).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(retry_after.as_deref(), Some("2"));
+        assert_eq!(
+            problem,
+            json!({
+                "type": "urn:netgate:problem:rate-limited",
+                "title": "Rate limited",
+                "status": 429,
+                "detail": "Rate limited: retry after 2s",
+                "retry_after": 2,
+            })
+        );
+    }
+
+    #[test]
+    fn test_netbox_throttling_and_timeouts_keep_their_meaning() {
+        let throttled = AppError::from(NetBoxError::from_status_code(429, "Too many requests".to_string()));
+        assert!(matches!(throttled, AppError::RateLimited { retry_after } if retry_after == Duration::from_secs(1)));
+
+        let timed_out = AppError::from(NetBoxError::Timeout(Duration::from_secs(30)));
+        assert!(matches!(timed_out, AppError::ServiceDegraded(_)));
+        assert_eq!(timed_out.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let missing = AppError::from(NetBoxError::from_status_code(404, "Not found.".to_string()));
+        assert!(matches!(missing, AppError::NotFound(ref message) if message == "Not found."));
+    }
+
+    #[test]
+    fn test_unavailable_errors_are_the_503s() {
+        let errors = [
+            AppError::Unauthorized,
+            AppError::NotFound("site".to_string()),
+            AppError::Conflict("exists".to_string()),
+            AppError::RateLimited { retry_after: Duration::from_secs(1) },
+            AppError::ServiceUnavailable("down".to_string()),
+            AppError::ServiceDegraded("circuit breaker open".to_string()),
+            AppError::Overloaded("bulkhead full".to_string()),
+            AppError::Internal(anyhow::anyhow!("boom")),
+        ];
+        for error in errors {
+            assert_eq!(
+                error.is_unavailable(),
+                error.status_code() == StatusCode::SERVICE_UNAVAILABLE,
+                "{}",
+                error
+            );
+        }
+    }
+}
@@ -1,7 +1,10 @@
 pub mod api;
+pub mod app;
 pub mod business;
 pub mod cache;
+pub mod clock;
 pub mod config;
+pub mod database;
 pub mod domain;
 pub mod error;
 pub mod logging;
@@ -9,5 +12,9 @@ pub mod netbox;
 pub mod observability;
 pub mod resilience;
 pub mod security;
+pub mod server;
+pub mod shutdown;
+pub mod sync;
 pub mod r#virtual;
+pub mod warmup;
 
@@ -1,14 +1,26 @@
-use crate::cache::{Cache, CacheConfig, CacheKey, CacheMetrics};
+use crate::cache::{Cache, CacheConfig, CacheFilters, CacheKey, CacheMetrics, CacheScope};
 use crate::error::AppError;
+use crate::netbox::client::{Conditional, Validator};
 use crate::netbox::models::*;
 use crate::netbox::ResilientNetBoxClient;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{debug, trace};
 
+/// A cached site and the validator to revalidate it with once it expires
+#[derive(Debug, Clone)]
+struct ValidatedSite {
+    site: NetBoxSite,
+    validator: Validator,
+}
+
 /// Cached NetBox client that wraps ResilientNetBoxClient with caching
+///
+/// Sites are cached by id in the global scope; listings are keyed by the
+/// NetBox tenant they're filtered on plus their other query filters.
 pub struct CachedNetBoxClient {
     client: Arc<ResilientNetBoxClient>,
-    site_cache: Arc<Cache<CacheKey, NetBoxSite>>,
+    site_cache: Arc<Cache<CacheKey, ValidatedSite>>,
     site_list_cache: Arc<Cache<CacheKey, Vec<NetBoxSite>>>,
     metrics: Arc<CacheMetrics>,
     config: CacheConfig,
@@ -44,33 +56,108 @@ impl CachedNetBoxClient {
     }
 
     /// Get a site with caching
+    ///
+    /// An expired entry with an ETag or Last-Modified is revalidated with a
+    /// conditional GET; if NetBox answers 304 its TTL restarts and it is served as is.
     pub async fn get_site(&self, id: i32) -> Result<NetBoxSite, AppError> {
-        let key = CacheKey::site(id);
+        let key = CacheKey::site(CacheScope::Global, id);
 
         // Try cache first
-        if let Some(cached) = self.site_cache.get(&key).await {
-            if self.config.enable_metrics {
-                self.metrics.record_hit();
+        let stale = match self.site_cache.get_including_expired(&key).await {
+            Some((cached, false)) => {
+                if self.config.enable_metrics {
+                    self.metrics.record_hit();
+                }
+                trace!("Cache hit for site {}", id);
+                return Ok(cached.site);
             }
-            trace!("Cache hit for site {}", id);
-            return Ok(cached);
-        }
+            Some((cached, true)) if !cached.validator.is_empty() => Some(cached),
+            _ => None,
+        };
 
-        // Cache miss - fetch from NetBox
+        // Cache miss - fetch from NetBox, or ask whether the stale copy still holds
         if self.config.enable_metrics {
             self.metrics.record_miss();
         }
-        trace!("Cache miss for site {}", id);
-
-        let site = self.client.get_site(id).await?;
+        trace!("Cache miss for site {} (revalidating: {})", id, stale.is_some());
+
+        let validator = stale.as_ref().map(|cached| &cached.validator);
+        match self.client.get_site_with_validator(id, validator).await? {
+            Conditional::NotModified => {
+                let Some(cached) = stale else {
+                    // Without a validator NetBox has no reason to answer 304
+                    return self.client.get_site(id).await;
+                };
+                if self.config.enable_metrics {
+                    self.metrics.record_revalidation();
+                }
+                if !self.site_cache.refresh(&key).await {
+                    // Invalidated while we were asking; put it back rather than lose it
+                    self.site_cache.put(key, cached.clone()).await;
+                }
+                Ok(cached.site)
+            }
+            Conditional::Fresh(site, validator) => {
+                if self.config.enable_metrics {
+                    self.metrics.record_full_fetch();
+                }
+                self.site_cache
+                    .put(key, ValidatedSite { site: site.clone(), validator })
+                    .await;
+                if self.config.enable_metrics {
+                    self.metrics.record_put();
+                }
+                Ok(site)
+            }
+        }
+    }
 
-        // Store in cache
-        self.site_cache.put(key, site.clone()).await;
-        if self.config.enable_metrics {
-            self.metrics.record_put();
+    /// Get many sites, fetching only the ones missing from the cache
+    pub async fn get_sites_by_ids(&self, ids: &[i32]) -> Result<Vec<(i32, Option<NetBoxSite>)>, AppError> {
+        let mut found = HashMap::new();
+        let mut misses = Vec::new();
+        for id in ids {
+            if found.contains_key(id) || misses.contains(id) {
+                continue;
+            }
+            match self.site_cache.get(&CacheKey::site(CacheScope::Global, *id)).await {
+                Some(cached) => {
+                    if self.config.enable_metrics {
+                        self.metrics.record_hit();
+                    }
+                    found.insert(*id, cached.site);
+                }
+                None => {
+                    if self.config.enable_metrics {
+                        self.metrics.record_miss();
+                    }
+                    misses.push(*id);
+                }
+            }
+        }
+        trace!("Batch site lookup: {} cached, {} to fetch", found.len(), misses.len());
+
+        if !misses.is_empty() {
+            for (id, site) in self.client.get_sites_by_ids(&misses).await? {
+                if let Some(site) = site {
+                    // List responses carry no per-object validators
+                    let cached = ValidatedSite { site: site.clone(), validator: Validator::default() };
+                    self.site_cache.put(CacheKey::site(CacheScope::Global, id), cached).await;
+                    if self.config.enable_metrics {
+                        self.metrics.record_full_fetch();
+                        self.metrics.record_put();
+                    }
+                    found.insert(id, site);
+                }
+            }
         }
 
-        Ok(site)
+        Ok(ids.iter().map(|id| (*id, found.get(id).cloned())).collect())
+    }
+
+    /// Get many devices; devices aren't cached, so this goes straight to NetBox
+    pub async fn get_devices_by_ids(&self, ids: &[i32]) -> Result<Vec<(i32, Option<NetBoxDevice>)>, AppError> {
+        self.client.get_devices_by_ids(ids).await
     }
 
     /// List sites with caching
@@ -80,24 +167,37 @@ impl CachedNetBoxClient {
         limit: Option<u32>,
         offset: Option<u32>,
     ) -> Result<NetBoxResponse<NetBoxSite>, AppError> {
-        // Create cache key from query parameters
-        let query_key = format!(
-            "tenant={:?}&limit={:?}&offset={:?}",
-            tenant_id, limit, offset
-        );
-        let key = CacheKey::site_list(query_key.clone());
+        self.list_sites_with_filters(tenant_id, limit, offset, &HashMap::new()).await
+    }
+
+    /// List sites with extra NetBox filters, cached per tenant and filter set
+    pub async fn list_sites_with_filters(
+        &self,
+        tenant_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+        extra_filters: &HashMap<String, String>,
+    ) -> Result<NetBoxResponse<NetBoxSite>, AppError> {
+        let filters = extra_filters
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect::<CacheFilters>()
+            .with("limit", limit)
+            .with("offset", offset);
+        let key = CacheKey::site_list(CacheScope::for_tenant(tenant_id), filters);
 
         // Try cache first
         if let Some(cached) = self.site_list_cache.get(&key).await {
             if self.config.enable_metrics {
                 self.metrics.record_hit();
             }
-            trace!("Cache hit for site list: {}", query_key);
+            trace!("Cache hit for site list: {:?}", key);
             return Ok(NetBoxResponse {
                 count: Some(cached.len() as i32),
                 next: None,
                 previous: None,
                 results: Some(cached),
+                degraded: false,
             });
         }
 
@@ -105,12 +205,19 @@ impl CachedNetBoxClient {
         if self.config.enable_metrics {
             self.metrics.record_miss();
         }
-        trace!("Cache miss for site list: {}", query_key);
+        trace!("Cache miss for site list: {:?}", key);
 
-        let response = self.client.list_sites(tenant_id, limit, offset).await?;
+        let response = if extra_filters.is_empty() {
+            self.client.list_sites(tenant_id, limit, offset).await?
+        } else {
+            self.client
+                .list_sites_with_filters(tenant_id, limit, offset, extra_filters)
+                .await?
+        };
 
-        // Store in cache if we have results
-        if let Some(ref sites) = response.results {
+        // Store in cache if we have results, unless they are fallback data that
+        // would later be served as if NetBox had returned them
+        if let Some(sites) = response.results.as_ref().filter(|_| !response.degraded) {
             self.site_list_cache.put(key, sites.clone()).await;
             if self.config.enable_metrics {
                 self.metrics.record_put();
@@ -125,41 +232,103 @@ impl CachedNetBoxClient {
         let site = self.client.create_site(request).await?;
 
         // Invalidate cache based on strategy
-        self.invalidate_site_cache(&site.id).await;
+        if let Some(id) = site.id {
+            self.invalidate_site_cache(id, &[CacheScope::for_tenant(site.tenant)]).await;
+        }
+
+        Ok(site)
+    }
 
+    /// Update a site and invalidate cache
+    ///
+    /// A site moved to another tenant also leaves the old tenant's lists, so
+    /// those go too; if the old tenant isn't cached every list is invalidated.
+    pub async fn update_site(&self, id: i32, request: UpdateSiteRequest) -> Result<NetBoxSite, AppError> {
+        let previous = match request.tenant {
+            Some(_) => Some(self.cached_owner(id).await.unwrap_or(CacheScope::Global)),
+            None => None,
+        };
+        let site = self.client.update_site(id, request).await?;
+
+        let mut owners = vec![CacheScope::for_tenant(site.tenant)];
+        owners.extend(previous.filter(|previous| !owners.contains(previous)));
+        self.invalidate_site_cache(id, &owners).await;
         Ok(site)
     }
 
+    /// Delete a site and invalidate cache
+    pub async fn delete_site(&self, id: i32) -> Result<(), AppError> {
+        let owner = self.cached_owner(id).await.unwrap_or(CacheScope::Global);
+        self.client.delete_site(id).await?;
+        self.invalidate_site_cache(id, &[owner]).await;
+        Ok(())
+    }
+
+    /// Drop a site changed outside NetGate, e.g. as reported by a NetBox webhook
+    ///
+    /// `tenant` is the NetBox tenant the site belongs to now; lists of the
+    /// tenant of the cached copy, if another, are dropped too.
+    pub async fn invalidate_site(&self, id: i32, tenant: Option<i32>) {
+        let mut owners = vec![CacheScope::for_tenant(tenant)];
+        owners.extend(self.cached_owner(id).await.filter(|previous| !owners.contains(previous)));
+        self.invalidate_site_cache(id, &owners).await;
+    }
+
+    /// Tenant scope of a cached site, expired or not
+    async fn cached_owner(&self, id: i32) -> Option<CacheScope> {
+        self.site_cache
+            .get_including_expired(&CacheKey::site(CacheScope::Global, id))
+            .await
+            .map(|(cached, _)| CacheScope::for_tenant(cached.site.tenant))
+    }
+
+    /// The resilient client underneath, for operations that aren't cached
+    pub fn inner(&self) -> &Arc<ResilientNetBoxClient> {
+        &self.client
+    }
+
     /// Invalidate site cache based on strategy
-    async fn invalidate_site_cache(&self, site_id: &Option<i32>) {
-        if let Some(id) = site_id {
-            let keys = crate::cache::strategy::get_invalidation_keys(
-                &CacheKey::site(*id),
+    ///
+    /// `owners` are the tenant scopes the site belongs or belonged to; lists
+    /// of other tenants are left alone unless an owner is `Global`.
+    async fn invalidate_site_cache(&self, id: i32, owners: &[CacheScope]) {
+        let mut keys = Vec::new();
+        for owner in owners {
+            for key in crate::cache::strategy::get_invalidation_keys(
+                &CacheKey::site(*owner, id),
                 self.config.invalidation_strategy,
-            );
+            ) {
+                if !keys.contains(&key) {
+                    keys.push(key);
+                }
+            }
+        }
 
-            for key in keys {
-                match key {
-                    CacheKey::Site(id) => {
-                        self.site_cache.invalidate(&CacheKey::site(id)).await;
-                    }
-                    CacheKey::SiteList(_) => {
-                        self.invalidate_site_list_cache().await;
+        for key in keys {
+            match key {
+                // Sites are cached in the global scope whoever owns them
+                CacheKey::Object { id, .. } => {
+                    self.site_cache.invalidate(&CacheKey::site(CacheScope::Global, id)).await;
+                }
+                CacheKey::Lists { scope, .. } => {
+                    self.invalidate_site_list_cache(&key).await;
+                    if self.config.enable_metrics {
+                        self.metrics.record_list_invalidation(scope);
                     }
-                    _ => {}
                 }
+                CacheKey::List { .. } => self.site_list_cache.invalidate(&key).await,
             }
+        }
 
-            if self.config.enable_metrics {
-                self.metrics.record_invalidation();
-            }
+        if self.config.enable_metrics {
+            self.metrics.record_invalidation();
         }
     }
 
-    /// Invalidate all site list cache entries
-    async fn invalidate_site_list_cache(&self) {
+    /// Invalidate the site list cache entries `pattern` covers
+    async fn invalidate_site_list_cache(&self, pattern: &CacheKey) {
         self.site_list_cache
-            .invalidate_matching(|k| matches!(k, CacheKey::SiteList(_)))
+            .invalidate_matching(|k| pattern.covers(k))
             .await;
     }
 
@@ -216,6 +385,7 @@ mod tests {
             port: 8080,
             netbox_url: uri,
             netbox_token: "test-token".to_string(),
+            ..Default::default()
         };
         let client = Arc::new(NetBoxClient::new(config).unwrap());
         Arc::new(ResilientNetBoxClient::new(client))
@@ -275,6 +445,127 @@ mod tests {
         assert_eq!(metrics.puts, 1);
     }
 
+    fn short_ttl_client(uri: String) -> CachedNetBoxClient {
+        CachedNetBoxClient::with_config(create_test_client(uri), CacheConfig::new(Duration::from_millis(20)))
+    }
+
+    #[tokio::test]
+    async fn test_expired_site_revalidated_with_etag() {
+        let mock_server = MockServer::start().await;
+        let cached = short_ttl_client(mock_server.uri());
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .and(header("If-None-Match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"v1\"")
+                    .set_body_json(json!({"id": 1, "name": "Test Site"})),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        assert_eq!(cached.get_site(1).await.unwrap().name, "Test Site");
+        for _ in 0..2 {
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            assert_eq!(cached.get_site(1).await.unwrap().name, "Test Site");
+        }
+        // Served from the refreshed entry without asking NetBox
+        assert_eq!(cached.get_site(1).await.unwrap().name, "Test Site");
+
+        let metrics = cached.cache_metrics();
+        assert_eq!(metrics.full_fetches, 1);
+        assert_eq!(metrics.revalidations, 2);
+        assert_eq!(metrics.hits, 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_site_replaced_when_changed() {
+        let mock_server = MockServer::start().await;
+        let cached = short_ttl_client(mock_server.uri());
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .and(header("If-None-Match", "\"v1\""))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"v2\"")
+                    .set_body_json(json!({"id": 1, "name": "Renamed"})),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"v1\"")
+                    .set_body_json(json!({"id": 1, "name": "Original"})),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        assert_eq!(cached.get_site(1).await.unwrap().name, "Original");
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!(cached.get_site(1).await.unwrap().name, "Renamed");
+
+        let metrics = cached.cache_metrics();
+        assert_eq!(metrics.full_fetches, 2);
+        assert_eq!(metrics.revalidations, 0);
+    }
+
+    #[tokio::test]
+    async fn test_cached_get_sites_by_ids_fetches_only_misses() {
+        let mock_server = MockServer::start().await;
+        let client = create_test_client(mock_server.uri());
+        let cached = CachedNetBoxClient::new(client);
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 1, "name": "Cached"})))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .and(query_param("id__in", "2,3"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 1,
+                "results": [{"id": 2, "name": "Fetched"}]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        cached.get_site(1).await.unwrap();
+        let sites = cached.get_sites_by_ids(&[3, 1, 2]).await.unwrap();
+
+        let names: Vec<_> = sites
+            .iter()
+            .map(|(id, site)| (*id, site.as_ref().map(|s| s.name.as_str())))
+            .collect();
+        assert_eq!(names, vec![(3, None), (1, Some("Cached")), (2, Some("Fetched"))]);
+
+        // The fetched site is now cached too, so nothing goes upstream
+        let sites = cached.get_sites_by_ids(&[1, 2]).await.unwrap();
+        assert!(sites.iter().all(|(_, site)| site.is_some()));
+        let batch_requests = mock_server
+            .received_requests()
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|r| r.url.path() == "/api/dcim/sites/")
+            .count();
+        assert_eq!(batch_requests, 1);
+    }
+
     #[tokio::test]
     async fn test_cached_list_sites() {
         let mock_server = MockServer::start().await;
@@ -310,6 +601,37 @@ mod tests {
         assert_eq!(metrics.misses, 1);
     }
 
+    #[tokio::test]
+    async fn test_cached_list_sites_keyed_by_tenant_and_filters() {
+        let mock_server = MockServer::start().await;
+        let cached = CachedNetBoxClient::new(create_test_client(mock_server.uri()));
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 1,
+                "results": [{"id": 1, "name": "Site 1"}]
+            })))
+            .expect(3)
+            .mount(&mock_server)
+            .await;
+
+        let filters: HashMap<String, String> =
+            [("status".to_string(), "active".to_string()), ("tag".to_string(), "edge".to_string())].into();
+        let reordered: HashMap<String, String> =
+            [("tag".to_string(), "edge".to_string()), ("status".to_string(), "active".to_string())].into();
+
+        cached.list_sites_with_filters(Some(1), Some(10), None, &filters).await.unwrap();
+        cached.list_sites_with_filters(Some(1), Some(10), None, &reordered).await.unwrap();
+        // Same filters for another tenant, and unscoped, are separate entries
+        cached.list_sites_with_filters(Some(2), Some(10), None, &filters).await.unwrap();
+        cached.list_sites_with_filters(None, Some(10), None, &filters).await.unwrap();
+
+        let metrics = cached.cache_metrics();
+        assert_eq!(metrics.hits, 1);
+        assert_eq!(metrics.misses, 3);
+    }
+
     #[tokio::test]
     async fn test_cached_create_site_invalidation() {
         let mock_server = MockServer::start().await;
@@ -344,24 +666,7 @@ mod tests {
             .mount(&mock_server)
             .await;
 
-        let create_request = CreateSiteRequest {
-            name: "New Site".to_string(),
-            slug: None,
-            description: None,
-            status: None,
-            region: None,
-            tenant: None,
-            facility: None,
-            physical_address: None,
-            shipping_address: None,
-            latitude: None,
-            longitude: None,
-            contact_name: None,
-            contact_phone: None,
-            contact_email: None,
-            comments: None,
-            tags: None,
-        };
+        let create_request = CreateSiteRequest::builder("New Site").build();
 
         let result = cached.create_site(create_request).await;
         assert!(result.is_ok());
@@ -370,6 +675,67 @@ mod tests {
         assert!(metrics.invalidations > 0);
     }
 
+    #[tokio::test]
+    async fn test_create_leaves_other_tenant_lists_cached() {
+        let mock_server = MockServer::start().await;
+        let config = CacheConfig::default().with_invalidation_strategy(InvalidationStrategy::WriteBack);
+        let cached = CachedNetBoxClient::with_config(create_test_client(mock_server.uri()), config);
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 1,
+                "results": [{"id": 1, "name": "Site 1"}]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "id": 2,
+                "name": "New Site",
+                "tenant": 1
+            })))
+            .mount(&mock_server)
+            .await;
+
+        for tenant in [Some(1), Some(2), None] {
+            cached.list_sites(tenant, None, None).await.unwrap();
+        }
+        cached
+            .create_site(CreateSiteRequest::builder("New Site").with_tenant(1).build())
+            .await
+            .unwrap();
+
+        // Tenant 2's list is still a hit; tenant 1's and the unfiltered one are refetched
+        for tenant in [Some(2), Some(1), None] {
+            cached.list_sites(tenant, None, None).await.unwrap();
+        }
+        let metrics = cached.cache_metrics();
+        assert_eq!(metrics.hits, 1);
+        assert_eq!(metrics.misses, 5);
+        assert_eq!(metrics.list_invalidations_by_scope.get("tenant-1"), Some(&1));
+        assert_eq!(metrics.list_invalidations_by_scope.get("global"), Some(&1));
+        assert!(!metrics.list_invalidations_by_scope.contains_key("all"));
+    }
+
+    #[tokio::test]
+    async fn test_delete_of_uncached_site_invalidates_every_list() {
+        let mock_server = MockServer::start().await;
+        let config = CacheConfig::default().with_invalidation_strategy(InvalidationStrategy::WriteBack);
+        let cached = CachedNetBoxClient::with_config(create_test_client(mock_server.uri()), config);
+
+        Mock::given(method("DELETE"))
+            .and(path("/api/dcim/sites/9/"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&mock_server)
+            .await;
+
+        cached.delete_site(9).await.unwrap();
+        let metrics = cached.cache_metrics();
+        assert_eq!(metrics.list_invalidations_by_scope.get("all"), Some(&1));
+    }
+
     #[tokio::test]
     async fn test_cache_stats() {
         let mock_server = MockServer::start().await;
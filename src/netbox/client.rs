@@ -1,16 +1,73 @@
 use crate::config::Config;
 use crate::netbox::error::NetBoxError;
+use crate::netbox::logging::{redact_body, redact_url};
 use crate::netbox::models::*;
-use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use crate::netbox::transport::PoolConfig;
+use reqwest::header::{
+    HeaderMap, HeaderValue, AUTHORIZATION, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use crate::netbox::token_scope::TokenScope;
+use crate::netbox::version::{NetBoxStatus, NetBoxVersion};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::fmt::Write;
-use tracing::{debug, error};
+use parking_lot::RwLock;
+use tracing::{debug, error, trace};
+
+/// Most IDs sent in one `id__in` query; NetBox sits behind servers that cap URL length
+pub const BATCH_GET_CHUNK_SIZE: usize = 50;
+
+/// Page size for extras listings such as custom fields, NetBox's default MAX_PAGE_SIZE
+const EXTRAS_PAGE_SIZE: u32 = 1000;
+/// Page size for the device type and role catalogs, which are listed whole
+const CATALOG_PAGE_SIZE: u32 = 1000;
+
+/// Cache validators NetBox sent with a response
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Validator {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validator {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name| headers.get(name).and_then(|v: &HeaderValue| v.to_str().ok()).map(str::to_string);
+        Self {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        }
+    }
+
+    /// Whether there's anything to revalidate with
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// Outcome of a conditional GET
+#[derive(Debug, Clone, PartialEq)]
+pub enum Conditional<T> {
+    /// 304: the copy the validator came from is still current
+    NotModified,
+    /// 200: a new body and the validator to send next time
+    Fresh(T, Validator),
+}
 
 /// NetBox API Client
 pub struct NetBoxClient {
     base_url: String,
-    #[allow(dead_code)] // Token is used in headers, but field itself is not directly accessed
+    /// Sent in the Authorization header; kept to scrub it from logs
     token: String,
     client: reqwest::Client,
+    /// Last `/api/status/` response, used for version-specific payloads
+    status: RwLock<Option<NetBoxStatus>>,
+    /// Responses larger than this fail with `ResponseTooLarge`
+    max_response_bytes: usize,
+    /// Pool settings the HTTP client was built with, reported by /health
+    pool: PoolConfig,
+    /// Log redacted request and response bodies at trace level
+    log_bodies: bool,
 }
 
 impl NetBoxClient {
@@ -39,8 +96,14 @@ impl NetBoxClient {
         );
         headers.insert("Accept", HeaderValue::from_static("application/json"));
 
-        let client = reqwest::Client::builder()
+        // gzip/brotli/deflate are negotiated and decoded by reqwest's features
+        let builder = reqwest::Client::builder()
             .default_headers(headers)
+            .gzip(true)
+            .brotli(true)
+            .deflate(true);
+        let builder = config.netbox_pool.apply(builder);
+        let client = config.netbox_transport.apply(builder)?
             .build()
             .map_err(|e| NetBoxError::NetworkError(e))?;
 
@@ -48,9 +111,86 @@ impl NetBoxClient {
             base_url,
             token,
             client,
+            status: RwLock::new(None),
+            max_response_bytes: config.netbox_max_response_bytes,
+            pool: config.netbox_pool,
+            log_bodies: config.log_netbox_bodies,
         })
     }
 
+    /// Send a request, logging method, URL, status and duration
+    ///
+    /// With `log_bodies` on and trace logging enabled, the request and response
+    /// bodies are logged too, redacted. The response body is then buffered
+    /// (within `max_response_bytes`) and handed back in a rebuilt response.
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response, NetBoxError> {
+        let request = request.build().map_err(NetBoxError::NetworkError)?;
+        let method = request.method().clone();
+        let url = redact_url(request.url(), &self.token);
+        let log_bodies = self.log_bodies && tracing::enabled!(tracing::Level::TRACE);
+        if log_bodies {
+            if let Some(body) = request.body().and_then(|body| body.as_bytes()) {
+                trace!("NetBox {} {} request body: {}", method, url, redact_body(body, &self.token));
+            }
+        }
+
+        let start = std::time::Instant::now();
+        let response = match self.client.execute(request).await {
+            Ok(response) => response,
+            Err(e) => {
+                debug!("NetBox {} {} failed after {:?}: {}", method, url, start.elapsed(), e);
+                return Err(NetBoxError::NetworkError(e));
+            }
+        };
+        let status = response.status();
+        debug!("NetBox {} {} -> {} in {:?}", method, url, status.as_u16(), start.elapsed());
+
+        if !log_bodies {
+            return Ok(response);
+        }
+        let mut rebuilt = http::Response::builder().status(status).version(response.version());
+        if let Some(headers) = rebuilt.headers_mut() {
+            *headers = response.headers().clone();
+        }
+        let body = self.read_body(response).await?;
+        trace!("NetBox {} {} response body: {}", method, url, redact_body(&body, &self.token));
+        let rebuilt = rebuilt
+            .body(body)
+            .map_err(|e| NetBoxError::UnexpectedResponse(format!("Failed to rebuild response: {}", e)))?;
+        Ok(reqwest::Response::from(rebuilt))
+    }
+
+    /// Connection pool settings in effect
+    pub fn pool_config(&self) -> &PoolConfig {
+        &self.pool
+    }
+
+    /// Read a response body, giving up as soon as it passes `max_response_bytes`
+    ///
+    /// The limit applies to the decompressed body, chunk by chunk, so an
+    /// oversized response is never buffered whole.
+    async fn read_body(&self, mut response: reqwest::Response) -> Result<Vec<u8>, NetBoxError> {
+        let limit = self.max_response_bytes;
+        if response.content_length().is_some_and(|len| len as usize > limit) {
+            return Err(NetBoxError::ResponseTooLarge { limit });
+        }
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(NetBoxError::NetworkError)? {
+            if body.len() + chunk.len() > limit {
+                return Err(NetBoxError::ResponseTooLarge { limit });
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body)
+    }
+
+    /// Read a response body as text, within `max_response_bytes` like `read_body`
+    async fn read_text(&self, response: reqwest::Response) -> Result<String, NetBoxError> {
+        let body = self.read_body(response).await?;
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+
     /// Build URL for a NetBox API endpoint
     fn build_url(&self, endpoint: &str) -> Result<String, NetBoxError> {
         let mut url = self.base_url.clone();
@@ -66,6 +206,240 @@ impl NetBoxClient {
         Ok(url)
     }
 
+    /// GET a list endpoint with percent-encoded query parameters
+    pub async fn list<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        params: &[(&str, String)],
+    ) -> Result<NetBoxResponse<T>, NetBoxError> {
+        let url = self.build_url(endpoint)?;
+        debug!("Listing {} from NetBox", url);
+
+        let response = self.send(self.client.get(&url).query(params)).await?;
+
+        let status = response.status();
+        let body = self.read_body(response).await?;
+
+        if !status.is_success() {
+            let text = String::from_utf8_lossy(&body).into_owned();
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        // Parse straight from the bytes rather than copying them into a String first
+        serde_json::from_slice(&body).map_err(NetBoxError::SerializationError)
+    }
+
+    /// POST a GraphQL query to NetBox's `/graphql/` endpoint and return its `data`
+    ///
+    /// NetBox reports query errors with HTTP 200 and an `errors` array; those
+    /// come back as `NetBoxError::GraphQLError`.
+    pub async fn graphql(&self, query: &str, variables: serde_json::Value) -> Result<serde_json::Value, NetBoxError> {
+        let url = format!("{}/graphql/", self.base_url);
+        let payload = serde_json::json!({ "query": query, "variables": variables });
+        let response = self.send(self.client.post(&url).json(&payload)).await?;
+
+        let status = response.status();
+        let body = self.read_body(response).await?;
+
+        if !status.is_success() {
+            let text = String::from_utf8_lossy(&body).into_owned();
+            error!("NetBox GraphQL error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        let mut response: serde_json::Value = serde_json::from_slice(&body).map_err(NetBoxError::SerializationError)?;
+        if let Some(errors) = response.get("errors").and_then(|errors| errors.as_array()) {
+            if !errors.is_empty() {
+                let messages = errors
+                    .iter()
+                    .map(|error| match error.get("message").and_then(|m| m.as_str()) {
+                        Some(message) => message.to_string(),
+                        None => error.to_string(),
+                    })
+                    .collect();
+                return Err(NetBoxError::GraphQLError(messages));
+            }
+        }
+        match response.get_mut("data").map(serde_json::Value::take) {
+            Some(data) if !data.is_null() => Ok(data),
+            _ => Err(NetBoxError::UnexpectedResponse("GraphQL response has no data".to_string())),
+        }
+    }
+
+    /// Fetch objects by ID with the `id__in` filter, a chunk at a time so URLs stay short
+    ///
+    /// The result follows the order of `ids`, with `None` for IDs NetBox didn't return.
+    async fn get_by_ids<T: DeserializeOwned + Clone>(
+        &self,
+        endpoint: &str,
+        ids: &[i32],
+        id_of: fn(&T) -> Option<i32>,
+    ) -> Result<Vec<(i32, Option<T>)>, NetBoxError> {
+        let mut unique = ids.to_vec();
+        unique.sort_unstable();
+        unique.dedup();
+
+        let mut found = HashMap::new();
+        for chunk in unique.chunks(BATCH_GET_CHUNK_SIZE) {
+            let id_list = chunk.iter().map(i32::to_string).collect::<Vec<_>>().join(",");
+            let params = [("id__in", id_list), ("limit", chunk.len().to_string())];
+            let response: NetBoxResponse<T> = self.list(endpoint, &params).await?;
+            for object in response.results.unwrap_or_default() {
+                if let Some(id) = id_of(&object) {
+                    found.insert(id, object);
+                }
+            }
+        }
+
+        Ok(ids.iter().map(|id| (*id, found.get(id).cloned())).collect())
+    }
+
+    /// GET a single object, sending `If-None-Match`/`If-Modified-Since` from `validator`
+    ///
+    /// The body is returned unparsed, so a 304 costs nothing beyond the round trip.
+    pub async fn get_with_validator(
+        &self,
+        endpoint: &str,
+        validator: Option<&Validator>,
+    ) -> Result<Conditional<Vec<u8>>, NetBoxError> {
+        let url = self.build_url(endpoint)?;
+        debug!("Conditional GET from NetBox: {} ({:?})", url, validator);
+
+        let mut request = self.client.get(&url);
+        if let Some(validator) = validator {
+            if let Some(etag) = &validator.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &validator.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let response = self.send(request).await?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(Conditional::NotModified);
+        }
+        let fresh_validator = Validator::from_headers(response.headers());
+        let body = self.read_body(response).await?;
+
+        if !status.is_success() {
+            if status == 404 {
+                return Err(NetBoxError::NotFound(format!("{} not found", endpoint)));
+            }
+            let text = String::from_utf8_lossy(&body).into_owned();
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        Ok(Conditional::Fresh(body, fresh_validator))
+    }
+
+    fn push_page_params(params: &mut Vec<(&str, String)>, limit: Option<u32>, offset: Option<u32>) {
+        if let Some(lim) = limit {
+            params.push(("limit", lim.to_string()));
+        }
+        if let Some(off) = offset {
+            params.push(("offset", off.to_string()));
+        }
+    }
+
+    /// Append extra filters in key order, so equal filters give equal URLs
+    fn push_extra_filters<'a>(params: &mut Vec<(&'a str, String)>, extra_filters: &'a HashMap<String, String>) {
+        let mut filters: Vec<_> = extra_filters.iter().collect();
+        filters.sort();
+        params.extend(filters.into_iter().map(|(key, value)| (key.as_str(), value.clone())));
+    }
+
+    /// Fetch `/api/status/` and remember it, refreshing the detected version
+    ///
+    /// The endpoint is cheap and authenticated, so it doubles as a credential check.
+    pub async fn get_status(&self) -> Result<NetBoxStatus, NetBoxError> {
+        let url = self.build_url("status/")?;
+        debug!("Getting NetBox status: {}", url);
+
+        let response = self.send(self.client.get(&url)).await?;
+
+        let status = response.status();
+        let text = self.read_text(response).await?;
+
+        if !status.is_success() {
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        let netbox_status: NetBoxStatus = serde_json::from_str(&text).map_err(NetBoxError::SerializationError)?;
+        *self.status.write() = Some(netbox_status.clone());
+        Ok(netbox_status)
+    }
+
+    /// NetBox version, detected on first use and cached
+    ///
+    /// Returns `None` while the status endpoint can't be read; detection is
+    /// retried on the next call.
+    pub async fn version(&self) -> Option<NetBoxVersion> {
+        let cached = self.status.read().clone();
+        let status = match cached {
+            Some(status) => status,
+            None => match self.get_status().await {
+                Ok(status) => status,
+                Err(e) => {
+                    debug!("NetBox version detection failed: {}", e);
+                    return None;
+                }
+            },
+        };
+        status.version()
+    }
+
+    /// Find out whether the token may write, without writing anything
+    ///
+    /// The token's own entry in `/api/users/tokens/` is checked first; when
+    /// it can't be listed or found there, an OPTIONS request to `dcim/sites/`
+    /// shows whether NetBox would accept a POST.
+    pub async fn token_scope(&self) -> TokenScope {
+        match self.list::<serde_json::Value>("users/tokens/", &[("limit", "1000".to_string())]).await {
+            Ok(response) => {
+                let tokens = response.results.unwrap_or_default();
+                if let Some(scope) = TokenScope::from_token_list(&tokens, &self.token) {
+                    return scope;
+                }
+                debug!("The NetBox token is not among the tokens NetBox lists");
+            }
+            Err(e) => debug!("NetBox tokens can't be listed: {}", e),
+        }
+
+        let url = match self.build_url("dcim/sites/") {
+            Ok(url) => url,
+            Err(_) => return TokenScope::Undetermined,
+        };
+        let response = match self.send(self.client.request(reqwest::Method::OPTIONS, &url)).await {
+            Ok(response) if response.status().is_success() => response,
+            Ok(response) => {
+                debug!("NetBox answered the OPTIONS probe with {}", response.status());
+                return TokenScope::Undetermined;
+            }
+            Err(e) => {
+                debug!("NetBox OPTIONS probe failed: {}", e);
+                return TokenScope::Undetermined;
+            }
+        };
+        match self.read_body(response).await.map(|body| serde_json::from_slice::<serde_json::Value>(&body)) {
+            Ok(Ok(body)) => TokenScope::from_options(&body),
+            _ => TokenScope::Undetermined,
+        }
+    }
+
+    /// Serialize a write request in the wire format of the detected NetBox version
+    async fn versioned_payload<T: Serialize>(&self, request: &T) -> Result<serde_json::Value, NetBoxError> {
+        let mut payload = serde_json::to_value(request)?;
+        if let Some(version) = self.version().await {
+            version.adapt_payload(&mut payload);
+        }
+        Ok(payload)
+    }
+
     // ========== Site CRUD Operations ==========
 
     /// Create a new site in NetBox
@@ -73,19 +447,15 @@ impl NetBoxClient {
         let url = self.build_url("dcim/sites/")?;
         debug!("Creating site in NetBox: {}", url);
 
-        let response = self
-            .client
-            .post(&url)
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| NetBoxError::NetworkError(e))?;
+        let payload = self.versioned_payload(&request).await?;
+
+        let response = self.send(self.client.post(&url).json(&payload)).await?;
 
         let status = response.status();
-        let text = response.text().await.map_err(|e| NetBoxError::NetworkError(e))?;
+        let text = self.read_text(response).await?;
 
         if !status.is_success() {
-            error!("NetBox API error: {} - {}", status, text);
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
             return Err(NetBoxError::from_status_code(status.as_u16(), text));
         }
 
@@ -97,76 +467,66 @@ impl NetBoxClient {
         let url = self.build_url(&format!("dcim/sites/{}/", id))?;
         debug!("Getting site from NetBox: {}", url);
 
-        let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| NetBoxError::NetworkError(e))?;
+        let response = self.send(self.client.get(&url)).await?;
 
         let status = response.status();
-        let text = response.text().await.map_err(|e| NetBoxError::NetworkError(e))?;
+        let text = self.read_text(response).await?;
 
         if !status.is_success() {
             if status == 404 {
                 return Err(NetBoxError::NotFound(format!("Site with ID {} not found", id)));
             }
-            error!("NetBox API error: {} - {}", status, text);
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
             return Err(NetBoxError::from_status_code(status.as_u16(), text));
         }
 
         serde_json::from_str(&text).map_err(|e| NetBoxError::SerializationError(e))
     }
 
+    /// Get many sites in as few requests as possible, in the order of `ids`
+    pub async fn get_sites_by_ids(&self, ids: &[i32]) -> Result<Vec<(i32, Option<NetBoxSite>)>, NetBoxError> {
+        self.get_by_ids("dcim/sites/", ids, |site: &NetBoxSite| site.id).await
+    }
+
     /// List sites with optional filters
     pub async fn list_sites(
         &self,
         tenant_id: Option<i32>,
+        name: Option<&str>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<NetBoxResponse<NetBoxSite>, NetBoxError> {
+        self.list_sites_with_filters(tenant_id, name, limit, offset, &HashMap::new())
+            .await
+    }
+
+    /// List sites with optional filters plus arbitrary NetBox filters
+    /// (e.g. `status=active`, `tag=netgate`)
+    pub async fn list_sites_with_filters(
+        &self,
+        tenant_id: Option<i32>,
+        name: Option<&str>,
         limit: Option<u32>,
         offset: Option<u32>,
+        extra_filters: &HashMap<String, String>,
     ) -> Result<NetBoxResponse<NetBoxSite>, NetBoxError> {
-        let mut url = self.build_url("dcim/sites/")?;
-        
         let mut params = Vec::new();
         if let Some(tenant) = tenant_id {
             params.push(("tenant_id", tenant.to_string()));
         }
-        if let Some(lim) = limit {
-            params.push(("limit", lim.to_string()));
-        }
-        if let Some(off) = offset {
-            params.push(("offset", off.to_string()));
-        }
-
-        if !params.is_empty() {
-            let query_string: String = params
-                .iter()
-                .map(|(k, v)| format!("{}={}", k, v))
-                .collect::<Vec<_>>()
-                .join("&");
-            write!(url, "?{}", query_string).map_err(|e| {
-                NetBoxError::InvalidUrl(format!("Failed to build query: {}", e))
-            })?;
+        if let Some(name) = name {
+            params.push(("name", name.to_string()));
         }
+        Self::push_page_params(&mut params, limit, offset);
+        Self::push_extra_filters(&mut params, extra_filters);
 
-        debug!("Listing sites from NetBox: {}", url);
-
-        let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| NetBoxError::NetworkError(e))?;
-
-        let status = response.status();
-        let text = response.text().await.map_err(|e| NetBoxError::NetworkError(e))?;
-
-        if !status.is_success() {
-            error!("NetBox API error: {} - {}", status, text);
-            return Err(NetBoxError::from_status_code(status.as_u16(), text));
-        }
+        self.list("dcim/sites/", &params).await
+    }
 
-        serde_json::from_str(&text).map_err(|e| NetBoxError::SerializationError(e))
+    /// Find a site by its slug, returning `None` if NetBox has no such site
+    pub async fn find_site_by_slug(&self, slug: &str) -> Result<Option<NetBoxSite>, NetBoxError> {
+        let response: NetBoxResponse<NetBoxSite> = self.list("dcim/sites/", &[("slug", slug.to_string())]).await?;
+        Ok(response.results.and_then(|sites| sites.into_iter().next()))
     }
 
     /// Update a site
@@ -178,22 +538,18 @@ impl NetBoxClient {
         let url = self.build_url(&format!("dcim/sites/{}/", id))?;
         debug!("Updating site in NetBox: {}", url);
 
-        let response = self
-            .client
-            .patch(&url)
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| NetBoxError::NetworkError(e))?;
+        let payload = self.versioned_payload(&request).await?;
+
+        let response = self.send(self.client.patch(&url).json(&payload)).await?;
 
         let status = response.status();
-        let text = response.text().await.map_err(|e| NetBoxError::NetworkError(e))?;
+        let text = self.read_text(response).await?;
 
         if !status.is_success() {
             if status == 404 {
                 return Err(NetBoxError::NotFound(format!("Site with ID {} not found", id)));
             }
-            error!("NetBox API error: {} - {}", status, text);
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
             return Err(NetBoxError::from_status_code(status.as_u16(), text));
         }
 
@@ -205,12 +561,7 @@ impl NetBoxClient {
         let url = self.build_url(&format!("dcim/sites/{}/", id))?;
         debug!("Deleting site from NetBox: {}", url);
 
-        let response = self
-            .client
-            .delete(&url)
-            .send()
-            .await
-            .map_err(|e| NetBoxError::NetworkError(e))?;
+        let response = self.send(self.client.delete(&url)).await?;
 
         let status = response.status();
 
@@ -218,8 +569,8 @@ impl NetBoxClient {
             if status == 404 {
                 return Err(NetBoxError::NotFound(format!("Site with ID {} not found", id)));
             }
-            let text = response.text().await.unwrap_or_default();
-            error!("NetBox API error: {} - {}", status, text);
+            let text = self.read_text(response).await.unwrap_or_default();
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
             return Err(NetBoxError::from_status_code(status.as_u16(), text));
         }
 
@@ -236,19 +587,15 @@ impl NetBoxClient {
         let url = self.build_url("dcim/devices/")?;
         debug!("Creating device in NetBox: {}", url);
 
-        let response = self
-            .client
-            .post(&url)
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| NetBoxError::NetworkError(e))?;
+        let payload = self.versioned_payload(&request).await?;
+
+        let response = self.send(self.client.post(&url).json(&payload)).await?;
 
         let status = response.status();
-        let text = response.text().await.map_err(|e| NetBoxError::NetworkError(e))?;
+        let text = self.read_text(response).await?;
 
         if !status.is_success() {
-            error!("NetBox API error: {} - {}", status, text);
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
             return Err(NetBoxError::from_status_code(status.as_u16(), text));
         }
 
@@ -260,21 +607,16 @@ impl NetBoxClient {
         let url = self.build_url(&format!("dcim/devices/{}/", id))?;
         debug!("Getting device from NetBox: {}", url);
 
-        let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| NetBoxError::NetworkError(e))?;
+        let response = self.send(self.client.get(&url)).await?;
 
         let status = response.status();
-        let text = response.text().await.map_err(|e| NetBoxError::NetworkError(e))?;
+        let text = self.read_text(response).await?;
 
         if !status.is_success() {
             if status == 404 {
                 return Err(NetBoxError::NotFound(format!("Device with ID {} not found", id)));
             }
-            error!("NetBox API error: {} - {}", status, text);
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
             return Err(NetBoxError::from_status_code(status.as_u16(), text));
         }
 
@@ -289,8 +631,24 @@ impl NetBoxClient {
         limit: Option<u32>,
         offset: Option<u32>,
     ) -> Result<NetBoxResponse<NetBoxDevice>, NetBoxError> {
-        let mut url = self.build_url("dcim/devices/")?;
-        
+        self.list_devices_with_filters(site_id, tenant_id, limit, offset, &HashMap::new())
+            .await
+    }
+
+    /// Get many devices in as few requests as possible, in the order of `ids`
+    pub async fn get_devices_by_ids(&self, ids: &[i32]) -> Result<Vec<(i32, Option<NetBoxDevice>)>, NetBoxError> {
+        self.get_by_ids("dcim/devices/", ids, |device: &NetBoxDevice| device.id).await
+    }
+
+    /// List devices with optional filters plus arbitrary NetBox filters
+    pub async fn list_devices_with_filters(
+        &self,
+        site_id: Option<i32>,
+        tenant_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+        extra_filters: &HashMap<String, String>,
+    ) -> Result<NetBoxResponse<NetBoxDevice>, NetBoxError> {
         let mut params = Vec::new();
         if let Some(site) = site_id {
             params.push(("site_id", site.to_string()));
@@ -298,42 +656,10 @@ impl NetBoxClient {
         if let Some(tenant) = tenant_id {
             params.push(("tenant_id", tenant.to_string()));
         }
-        if let Some(lim) = limit {
-            params.push(("limit", lim.to_string()));
-        }
-        if let Some(off) = offset {
-            params.push(("offset", off.to_string()));
-        }
-
-        if !params.is_empty() {
-            let query_string: String = params
-                .iter()
-                .map(|(k, v)| format!("{}={}", k, v))
-                .collect::<Vec<_>>()
-                .join("&");
-            write!(url, "?{}", query_string).map_err(|e| {
-                NetBoxError::InvalidUrl(format!("Failed to build query: {}", e))
-            })?;
-        }
-
-        debug!("Listing devices from NetBox: {}", url);
-
-        let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| NetBoxError::NetworkError(e))?;
-
-        let status = response.status();
-        let text = response.text().await.map_err(|e| NetBoxError::NetworkError(e))?;
-
-        if !status.is_success() {
-            error!("NetBox API error: {} - {}", status, text);
-            return Err(NetBoxError::from_status_code(status.as_u16(), text));
-        }
+        Self::push_page_params(&mut params, limit, offset);
+        Self::push_extra_filters(&mut params, extra_filters);
 
-        serde_json::from_str(&text).map_err(|e| NetBoxError::SerializationError(e))
+        self.list("dcim/devices/", &params).await
     }
 
     /// Update a device
@@ -345,22 +671,18 @@ impl NetBoxClient {
         let url = self.build_url(&format!("dcim/devices/{}/", id))?;
         debug!("Updating device in NetBox: {}", url);
 
-        let response = self
-            .client
-            .patch(&url)
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| NetBoxError::NetworkError(e))?;
+        let payload = self.versioned_payload(&request).await?;
+
+        let response = self.send(self.client.patch(&url).json(&payload)).await?;
 
         let status = response.status();
-        let text = response.text().await.map_err(|e| NetBoxError::NetworkError(e))?;
+        let text = self.read_text(response).await?;
 
         if !status.is_success() {
             if status == 404 {
                 return Err(NetBoxError::NotFound(format!("Device with ID {} not found", id)));
             }
-            error!("NetBox API error: {} - {}", status, text);
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
             return Err(NetBoxError::from_status_code(status.as_u16(), text));
         }
 
@@ -372,12 +694,7 @@ impl NetBoxClient {
         let url = self.build_url(&format!("dcim/devices/{}/", id))?;
         debug!("Deleting device from NetBox: {}", url);
 
-        let response = self
-            .client
-            .delete(&url)
-            .send()
-            .await
-            .map_err(|e| NetBoxError::NetworkError(e))?;
+        let response = self.send(self.client.delete(&url)).await?;
 
         let status = response.status();
 
@@ -385,255 +702,1585 @@ impl NetBoxClient {
             if status == 404 {
                 return Err(NetBoxError::NotFound(format!("Device with ID {} not found", id)));
             }
-            let text = response.text().await.unwrap_or_default();
-            error!("NetBox API error: {} - {}", status, text);
+            let text = self.read_text(response).await.unwrap_or_default();
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
             return Err(NetBoxError::from_status_code(status.as_u16(), text));
         }
 
         Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::config::Config;
-    use serde_json::json;
-    use wiremock::{
-        matchers::{header, method, path},
-        Mock, MockServer, ResponseTemplate,
-    };
+    // ========== IPAM Prefix Operations ==========
 
-    fn create_test_config(base_url: String, token: String) -> Config {
-        Config {
-            port: 8080,
-            netbox_url: base_url,
-            netbox_token: token,
+    /// Create a new prefix in NetBox
+    pub async fn create_prefix(
+        &self,
+        request: CreatePrefixRequest,
+    ) -> Result<NetBoxPrefix, NetBoxError> {
+        let url = self.build_url("ipam/prefixes/")?;
+        debug!("Creating prefix in NetBox: {}", url);
+
+        let payload = self.versioned_payload(&request).await?;
+
+        let response = self.send(self.client.post(&url).json(&payload)).await?;
+
+        let status = response.status();
+        let text = self.read_text(response).await?;
+
+        if !status.is_success() {
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
         }
-    }
 
-    #[tokio::test]
-    async fn test_client_creation_success() {
-        let config = create_test_config("http://localhost:8000".to_string(), "test-token".to_string());
-        let result = NetBoxClient::new(config);
-        assert!(result.is_ok());
+        serde_json::from_str(&text).map_err(NetBoxError::SerializationError)
     }
 
-    #[tokio::test]
-    async fn test_client_creation_no_token() {
-        let config = create_test_config("http://localhost:8000".to_string(), "".to_string());
-        let result = NetBoxClient::new(config);
-        assert!(result.is_err());
-        if let Err(NetBoxError::AuthenticationError(_)) = result {
-            // Expected error
-        } else {
-            panic!("Expected AuthenticationError");
+    /// Get a prefix by ID
+    pub async fn get_prefix(&self, id: i32) -> Result<NetBoxPrefix, NetBoxError> {
+        let url = self.build_url(&format!("ipam/prefixes/{}/", id))?;
+        debug!("Getting prefix from NetBox: {}", url);
+
+        let response = self.send(self.client.get(&url)).await?;
+
+        let status = response.status();
+        let text = self.read_text(response).await?;
+
+        if !status.is_success() {
+            if status == 404 {
+                return Err(NetBoxError::NotFound(format!("Prefix with ID {} not found", id)));
+            }
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
         }
+
+        serde_json::from_str(&text).map_err(NetBoxError::SerializationError)
     }
 
-    #[tokio::test]
-    async fn test_create_site_success() {
-        let mock_server = MockServer::start().await;
-        let config = create_test_config(mock_server.uri(), "test-token".to_string());
-        let client = NetBoxClient::new(config).unwrap();
+    /// Delete a prefix
+    pub async fn delete_prefix(&self, id: i32) -> Result<(), NetBoxError> {
+        let url = self.build_url(&format!("ipam/prefixes/{}/", id))?;
+        debug!("Deleting prefix from NetBox: {}", url);
 
-        let site_response = json!({
-            "id": 1,
-            "name": "Test Site",
-            "description": "Test Description",
-            "status": "active"
-        });
+        let response = self.send(self.client.delete(&url)).await?;
 
-        Mock::given(method("POST"))
-            .and(path("/api/dcim/sites/"))
-            .and(header("Authorization", "Token test-token"))
-            .respond_with(ResponseTemplate::new(201).set_body_json(&site_response))
+        let status = response.status();
+
+        if !status.is_success() {
+            if status == 404 {
+                return Err(NetBoxError::NotFound(format!("Prefix with ID {} not found", id)));
+            }
+            let text = self.read_text(response).await.unwrap_or_default();
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        Ok(())
+    }
+
+    /// List prefixes, optionally only those of one site or tenant
+    pub async fn list_prefixes_with_filters(
+        &self,
+        site_id: Option<i32>,
+        tenant_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+        extra_filters: &HashMap<String, String>,
+    ) -> Result<NetBoxResponse<NetBoxPrefix>, NetBoxError> {
+        let mut params = Vec::new();
+        if let Some(site) = site_id {
+            params.push(("site_id", site.to_string()));
+        }
+        if let Some(tenant) = tenant_id {
+            params.push(("tenant_id", tenant.to_string()));
+        }
+        Self::push_page_params(&mut params, limit, offset);
+        Self::push_extra_filters(&mut params, extra_filters);
+
+        self.list("ipam/prefixes/", &params).await
+    }
+
+    // ========== IPAM VLAN Operations ==========
+
+    /// Create a VLAN in NetBox
+    pub async fn create_vlan(&self, request: CreateVlanRequest) -> Result<NetBoxVlan, NetBoxError> {
+        let url = self.build_url("ipam/vlans/")?;
+        debug!("Creating VLAN in NetBox: {}", url);
+
+        let payload = self.versioned_payload(&request).await?;
+
+        let response = self.send(self.client.post(&url).json(&payload)).await?;
+
+        let status = response.status();
+        let text = self.read_text(response).await?;
+
+        if !status.is_success() {
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        serde_json::from_str(&text).map_err(NetBoxError::SerializationError)
+    }
+
+    /// Get a VLAN by ID
+    pub async fn get_vlan(&self, id: i32) -> Result<NetBoxVlan, NetBoxError> {
+        let url = self.build_url(&format!("ipam/vlans/{}/", id))?;
+        debug!("Getting VLAN from NetBox: {}", url);
+
+        let response = self.send(self.client.get(&url)).await?;
+
+        let status = response.status();
+        let text = self.read_text(response).await?;
+
+        if !status.is_success() {
+            if status == 404 {
+                return Err(NetBoxError::NotFound(format!("VLAN with ID {} not found", id)));
+            }
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        serde_json::from_str(&text).map_err(NetBoxError::SerializationError)
+    }
+
+    /// Find a site's VLAN by its VID
+    pub async fn find_vlan(&self, site_id: i32, vid: u16) -> Result<Option<NetBoxVlan>, NetBoxError> {
+        let params = [("site_id", site_id.to_string()), ("vid", vid.to_string())];
+        let response: NetBoxResponse<NetBoxVlan> = self.list("ipam/vlans/", &params).await?;
+        Ok(response.results.unwrap_or_default().into_iter().find(|vlan| vlan.vid == vid))
+    }
+
+    /// List VLANs, optionally only those of one site, group, or tenant
+    pub async fn list_vlans_with_filters(
+        &self,
+        site_id: Option<i32>,
+        group_id: Option<i32>,
+        tenant_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+        extra_filters: &HashMap<String, String>,
+    ) -> Result<NetBoxResponse<NetBoxVlan>, NetBoxError> {
+        let mut params = Vec::new();
+        if let Some(site) = site_id {
+            params.push(("site_id", site.to_string()));
+        }
+        if let Some(group) = group_id {
+            params.push(("group_id", group.to_string()));
+        }
+        if let Some(tenant) = tenant_id {
+            params.push(("tenant_id", tenant.to_string()));
+        }
+        Self::push_page_params(&mut params, limit, offset);
+        Self::push_extra_filters(&mut params, extra_filters);
+
+        self.list("ipam/vlans/", &params).await
+    }
+
+    /// Update a VLAN
+    pub async fn update_vlan(&self, id: i32, request: UpdateVlanRequest) -> Result<NetBoxVlan, NetBoxError> {
+        let url = self.build_url(&format!("ipam/vlans/{}/", id))?;
+        debug!("Updating VLAN in NetBox: {}", url);
+
+        let payload = self.versioned_payload(&request).await?;
+
+        let response = self.send(self.client.patch(&url).json(&payload)).await?;
+
+        let status = response.status();
+        let text = self.read_text(response).await?;
+
+        if !status.is_success() {
+            if status == 404 {
+                return Err(NetBoxError::NotFound(format!("VLAN with ID {} not found", id)));
+            }
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        serde_json::from_str(&text).map_err(NetBoxError::SerializationError)
+    }
+
+    /// Delete a VLAN
+    pub async fn delete_vlan(&self, id: i32) -> Result<(), NetBoxError> {
+        let url = self.build_url(&format!("ipam/vlans/{}/", id))?;
+        debug!("Deleting VLAN from NetBox: {}", url);
+
+        let response = self.send(self.client.delete(&url)).await?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            if status == 404 {
+                return Err(NetBoxError::NotFound(format!("VLAN with ID {} not found", id)));
+            }
+            let text = self.read_text(response).await.unwrap_or_default();
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        Ok(())
+    }
+
+    // ========== IPAM IP Address Operations ==========
+
+    /// List addresses NetBox reports free within a prefix, lowest first
+    pub async fn list_available_ips(
+        &self,
+        prefix_id: i32,
+        limit: Option<u32>,
+    ) -> Result<Vec<AvailableIp>, NetBoxError> {
+        let url = self.build_url(&format!("ipam/prefixes/{}/available-ips/", prefix_id))?;
+        debug!("Listing available IPs from NetBox: {}", url);
+
+        let mut params = Vec::new();
+        Self::push_page_params(&mut params, limit, None);
+        let response = self.send(self.client.get(&url).query(&params)).await?;
+
+        let status = response.status();
+        let text = self.read_text(response).await?;
+
+        if !status.is_success() {
+            if status == 404 {
+                return Err(NetBoxError::NotFound(format!("Prefix with ID {} not found", prefix_id)));
+            }
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        serde_json::from_str(&text).map_err(NetBoxError::SerializationError)
+    }
+
+    /// Allocate the next free address of a prefix
+    ///
+    /// NetBox answers 409, or 204 with no body, once the prefix has nothing
+    /// left; both become `NetBoxError::Exhausted`, with the prefix's size
+    /// looked up to report how many addresses are in use.
+    pub async fn allocate_ip(
+        &self,
+        prefix_id: i32,
+        request: AllocateIpRequest,
+    ) -> Result<NetBoxIpAddress, NetBoxError> {
+        let url = self.build_url(&format!("ipam/prefixes/{}/available-ips/", prefix_id))?;
+        debug!("Allocating IP address in NetBox: {}", url);
+
+        let payload = self.versioned_payload(&request).await?;
+
+        let response = self.send(self.client.post(&url).json(&payload)).await?;
+
+        let status = response.status();
+        let text = self.read_text(response).await?;
+
+        if status == 409 || status == 204 {
+            debug!("Prefix {} is exhausted: {}", prefix_id, redact_body(text.as_bytes(), &self.token));
+            let prefix = self.get_prefix(prefix_id).await?;
+            return Err(NetBoxError::prefix_exhausted(prefix_id, &prefix));
+        }
+        if !status.is_success() {
+            if status == 404 {
+                return Err(NetBoxError::NotFound(format!("Prefix with ID {} not found", prefix_id)));
+            }
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        serde_json::from_str(&text).map_err(NetBoxError::SerializationError)
+    }
+
+    /// Get an IP address by ID
+    pub async fn get_ip_address(&self, id: i32) -> Result<NetBoxIpAddress, NetBoxError> {
+        let url = self.build_url(&format!("ipam/ip-addresses/{}/", id))?;
+        debug!("Getting IP address from NetBox: {}", url);
+
+        let response = self.send(self.client.get(&url)).await?;
+
+        let status = response.status();
+        let text = self.read_text(response).await?;
+
+        if !status.is_success() {
+            if status == 404 {
+                return Err(NetBoxError::NotFound(format!("IP address with ID {} not found", id)));
+            }
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        serde_json::from_str(&text).map_err(NetBoxError::SerializationError)
+    }
+
+    /// Delete an IP address, returning it to its prefix
+    pub async fn delete_ip_address(&self, id: i32) -> Result<(), NetBoxError> {
+        let url = self.build_url(&format!("ipam/ip-addresses/{}/", id))?;
+        debug!("Deleting IP address from NetBox: {}", url);
+
+        let response = self.send(self.client.delete(&url)).await?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            if status == 404 {
+                return Err(NetBoxError::NotFound(format!("IP address with ID {} not found", id)));
+            }
+            let text = self.read_text(response).await.unwrap_or_default();
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        Ok(())
+    }
+
+    // ========== Virtualization Operations ==========
+
+    /// Create a cluster in NetBox
+    pub async fn create_cluster(&self, request: CreateClusterRequest) -> Result<NetBoxCluster, NetBoxError> {
+        let url = self.build_url("virtualization/clusters/")?;
+        debug!("Creating cluster in NetBox: {}", url);
+
+        let payload = self.versioned_payload(&request).await?;
+
+        let response = self.send(self.client.post(&url).json(&payload)).await?;
+
+        let status = response.status();
+        let text = self.read_text(response).await?;
+
+        if !status.is_success() {
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        serde_json::from_str(&text).map_err(NetBoxError::SerializationError)
+    }
+
+    /// Get a cluster by ID
+    pub async fn get_cluster(&self, id: i32) -> Result<NetBoxCluster, NetBoxError> {
+        let url = self.build_url(&format!("virtualization/clusters/{}/", id))?;
+        debug!("Getting cluster from NetBox: {}", url);
+
+        let response = self.send(self.client.get(&url)).await?;
+
+        let status = response.status();
+        let text = self.read_text(response).await?;
+
+        if !status.is_success() {
+            if status == 404 {
+                return Err(NetBoxError::NotFound(format!("Cluster with ID {} not found", id)));
+            }
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        serde_json::from_str(&text).map_err(NetBoxError::SerializationError)
+    }
+
+    /// List clusters, optionally only those of one site or tenant
+    pub async fn list_clusters_with_filters(
+        &self,
+        site_id: Option<i32>,
+        tenant_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+        extra_filters: &HashMap<String, String>,
+    ) -> Result<NetBoxResponse<NetBoxCluster>, NetBoxError> {
+        let mut params = Vec::new();
+        if let Some(site) = site_id {
+            params.push(("site_id", site.to_string()));
+        }
+        if let Some(tenant) = tenant_id {
+            params.push(("tenant_id", tenant.to_string()));
+        }
+        Self::push_page_params(&mut params, limit, offset);
+        Self::push_extra_filters(&mut params, extra_filters);
+
+        self.list("virtualization/clusters/", &params).await
+    }
+
+    /// Update a cluster
+    pub async fn update_cluster(&self, id: i32, request: UpdateClusterRequest) -> Result<NetBoxCluster, NetBoxError> {
+        let url = self.build_url(&format!("virtualization/clusters/{}/", id))?;
+        debug!("Updating cluster in NetBox: {}", url);
+
+        let payload = self.versioned_payload(&request).await?;
+
+        let response = self.send(self.client.patch(&url).json(&payload)).await?;
+
+        let status = response.status();
+        let text = self.read_text(response).await?;
+
+        if !status.is_success() {
+            if status == 404 {
+                return Err(NetBoxError::NotFound(format!("Cluster with ID {} not found", id)));
+            }
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        serde_json::from_str(&text).map_err(NetBoxError::SerializationError)
+    }
+
+    /// Delete a cluster
+    pub async fn delete_cluster(&self, id: i32) -> Result<(), NetBoxError> {
+        let url = self.build_url(&format!("virtualization/clusters/{}/", id))?;
+        debug!("Deleting cluster from NetBox: {}", url);
+
+        let response = self.send(self.client.delete(&url)).await?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            if status == 404 {
+                return Err(NetBoxError::NotFound(format!("Cluster with ID {} not found", id)));
+            }
+            let text = self.read_text(response).await.unwrap_or_default();
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        Ok(())
+    }
+
+    /// Create a virtual machine in NetBox
+    pub async fn create_virtual_machine(
+        &self,
+        request: CreateVirtualMachineRequest,
+    ) -> Result<NetBoxVirtualMachine, NetBoxError> {
+        let url = self.build_url("virtualization/virtual-machines/")?;
+        debug!("Creating virtual machine in NetBox: {}", url);
+
+        let payload = self.versioned_payload(&request).await?;
+
+        let response = self.send(self.client.post(&url).json(&payload)).await?;
+
+        let status = response.status();
+        let text = self.read_text(response).await?;
+
+        if !status.is_success() {
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        serde_json::from_str(&text).map_err(NetBoxError::SerializationError)
+    }
+
+    /// Get a virtual machine by ID
+    pub async fn get_virtual_machine(&self, id: i32) -> Result<NetBoxVirtualMachine, NetBoxError> {
+        let url = self.build_url(&format!("virtualization/virtual-machines/{}/", id))?;
+        debug!("Getting virtual machine from NetBox: {}", url);
+
+        let response = self.send(self.client.get(&url)).await?;
+
+        let status = response.status();
+        let text = self.read_text(response).await?;
+
+        if !status.is_success() {
+            if status == 404 {
+                return Err(NetBoxError::NotFound(format!("Virtual machine with ID {} not found", id)));
+            }
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        serde_json::from_str(&text).map_err(NetBoxError::SerializationError)
+    }
+
+    /// Get many virtual machines in as few requests as possible, in the order of `ids`
+    pub async fn get_virtual_machines_by_ids(
+        &self,
+        ids: &[i32],
+    ) -> Result<Vec<(i32, Option<NetBoxVirtualMachine>)>, NetBoxError> {
+        self.get_by_ids("virtualization/virtual-machines/", ids, |vm: &NetBoxVirtualMachine| vm.id).await
+    }
+
+    /// Find a cluster's virtual machine by name, which NetBox keeps unique per cluster
+    pub async fn find_virtual_machine(
+        &self,
+        cluster_id: i32,
+        name: &str,
+    ) -> Result<Option<NetBoxVirtualMachine>, NetBoxError> {
+        let params = [("cluster_id", cluster_id.to_string()), ("name", name.to_string())];
+        let response: NetBoxResponse<NetBoxVirtualMachine> =
+            self.list("virtualization/virtual-machines/", &params).await?;
+        Ok(response.results.unwrap_or_default().into_iter().find(|vm| vm.name == name))
+    }
+
+    /// List virtual machines, optionally only those of one site, cluster, or tenant
+    pub async fn list_virtual_machines_with_filters(
+        &self,
+        site_id: Option<i32>,
+        cluster_id: Option<i32>,
+        tenant_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+        extra_filters: &HashMap<String, String>,
+    ) -> Result<NetBoxResponse<NetBoxVirtualMachine>, NetBoxError> {
+        let mut params = Vec::new();
+        if let Some(site) = site_id {
+            params.push(("site_id", site.to_string()));
+        }
+        if let Some(cluster) = cluster_id {
+            params.push(("cluster_id", cluster.to_string()));
+        }
+        if let Some(tenant) = tenant_id {
+            params.push(("tenant_id", tenant.to_string()));
+        }
+        Self::push_page_params(&mut params, limit, offset);
+        Self::push_extra_filters(&mut params, extra_filters);
+
+        self.list("virtualization/virtual-machines/", &params).await
+    }
+
+    /// Update a virtual machine
+    pub async fn update_virtual_machine(
+        &self,
+        id: i32,
+        request: UpdateVirtualMachineRequest,
+    ) -> Result<NetBoxVirtualMachine, NetBoxError> {
+        let url = self.build_url(&format!("virtualization/virtual-machines/{}/", id))?;
+        debug!("Updating virtual machine in NetBox: {}", url);
+
+        let payload = self.versioned_payload(&request).await?;
+
+        let response = self.send(self.client.patch(&url).json(&payload)).await?;
+
+        let status = response.status();
+        let text = self.read_text(response).await?;
+
+        if !status.is_success() {
+            if status == 404 {
+                return Err(NetBoxError::NotFound(format!("Virtual machine with ID {} not found", id)));
+            }
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        serde_json::from_str(&text).map_err(NetBoxError::SerializationError)
+    }
+
+    /// Delete a virtual machine
+    pub async fn delete_virtual_machine(&self, id: i32) -> Result<(), NetBoxError> {
+        let url = self.build_url(&format!("virtualization/virtual-machines/{}/", id))?;
+        debug!("Deleting virtual machine from NetBox: {}", url);
+
+        let response = self.send(self.client.delete(&url)).await?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            if status == 404 {
+                return Err(NetBoxError::NotFound(format!("Virtual machine with ID {} not found", id)));
+            }
+            let text = self.read_text(response).await.unwrap_or_default();
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        Ok(())
+    }
+
+    // ========== DCIM Catalog Operations ==========
+
+    /// List device types with their manufacturers, up to the catalog page size
+    pub async fn list_device_types(&self) -> Result<Vec<NetBoxDeviceType>, NetBoxError> {
+        let response: NetBoxResponse<NetBoxDeviceType> =
+            self.list("dcim/device-types/", &[("limit", CATALOG_PAGE_SIZE.to_string())]).await?;
+        Ok(response.results.unwrap_or_default())
+    }
+
+    /// List device roles, up to the catalog page size
+    pub async fn list_device_roles(&self) -> Result<Vec<NetBoxDeviceRole>, NetBoxError> {
+        let response: NetBoxResponse<NetBoxDeviceRole> =
+            self.list("dcim/device-roles/", &[("limit", CATALOG_PAGE_SIZE.to_string())]).await?;
+        Ok(response.results.unwrap_or_default())
+    }
+
+    // ========== DCIM Rack Operations ==========
+
+    /// List racks, optionally only those of one site or tenant
+    pub async fn list_racks_with_filters(
+        &self,
+        site_id: Option<i32>,
+        tenant_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+        extra_filters: &HashMap<String, String>,
+    ) -> Result<NetBoxResponse<NetBoxRack>, NetBoxError> {
+        let mut params = Vec::new();
+        if let Some(site) = site_id {
+            params.push(("site_id", site.to_string()));
+        }
+        if let Some(tenant) = tenant_id {
+            params.push(("tenant_id", tenant.to_string()));
+        }
+        Self::push_page_params(&mut params, limit, offset);
+        Self::push_extra_filters(&mut params, extra_filters);
+
+        self.list("dcim/racks/", &params).await
+    }
+
+    // ========== DCIM Interface Operations ==========
+
+    /// Create a interface in NetBox
+    pub async fn create_interface(&self, request: CreateInterfaceRequest) -> Result<NetBoxInterface, NetBoxError> {
+        let url = self.build_url("dcim/interfaces/")?;
+        debug!("Creating interface in NetBox: {}", url);
+
+        let payload = self.versioned_payload(&request).await?;
+
+        let response = self.send(self.client.post(&url).json(&payload)).await?;
+
+        let status = response.status();
+        let text = self.read_text(response).await?;
+
+        if !status.is_success() {
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        serde_json::from_str(&text).map_err(NetBoxError::SerializationError)
+    }
+
+    /// Get a interface by ID
+    pub async fn get_interface(&self, id: i32) -> Result<NetBoxInterface, NetBoxError> {
+        let url = self.build_url(&format!("dcim/interfaces/{}/", id))?;
+        debug!("Getting interface from NetBox: {}", url);
+
+        let response = self.send(self.client.get(&url)).await?;
+
+        let status = response.status();
+        let text = self.read_text(response).await?;
+
+        if !status.is_success() {
+            if status == 404 {
+                return Err(NetBoxError::NotFound(format!("Interface with ID {} not found", id)));
+            }
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        serde_json::from_str(&text).map_err(NetBoxError::SerializationError)
+    }
+
+    /// Find a device's interface by its exact name
+    pub async fn find_interface(&self, device_id: i32, name: &str) -> Result<Option<NetBoxInterface>, NetBoxError> {
+        let params = [("device_id", device_id.to_string()), ("name", name.to_string())];
+        let response: NetBoxResponse<NetBoxInterface> = self.list("dcim/interfaces/", &params).await?;
+        Ok(response.results.unwrap_or_default().into_iter().find(|interface| interface.name == name))
+    }
+
+    /// List interfaces, optionally only those of one device
+    pub async fn list_interfaces(
+        &self,
+        device_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<NetBoxResponse<NetBoxInterface>, NetBoxError> {
+        let mut params = Vec::new();
+        if let Some(device) = device_id {
+            params.push(("device_id", device.to_string()));
+        }
+        Self::push_page_params(&mut params, limit, offset);
+
+        self.list("dcim/interfaces/", &params).await
+    }
+
+    /// Delete a interface
+    pub async fn delete_interface(&self, id: i32) -> Result<(), NetBoxError> {
+        let url = self.build_url(&format!("dcim/interfaces/{}/", id))?;
+        debug!("Deleting interface from NetBox: {}", url);
+
+        let response = self.send(self.client.delete(&url)).await?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            if status == 404 {
+                return Err(NetBoxError::NotFound(format!("Interface with ID {} not found", id)));
+            }
+            let text = self.read_text(response).await.unwrap_or_default();
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        Ok(())
+    }
+
+    // ========== DCIM Cable Operations ==========
+
+    /// Create a cable in NetBox
+    pub async fn create_cable(&self, request: CreateCableRequest) -> Result<NetBoxCable, NetBoxError> {
+        let url = self.build_url("dcim/cables/")?;
+        debug!("Creating cable in NetBox: {}", url);
+
+        let payload = self.versioned_payload(&request).await?;
+
+        let response = self.send(self.client.post(&url).json(&payload)).await?;
+
+        let status = response.status();
+        let text = self.read_text(response).await?;
+
+        if !status.is_success() {
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        serde_json::from_str(&text).map_err(NetBoxError::SerializationError)
+    }
+
+    /// Get a cable by ID
+    pub async fn get_cable(&self, id: i32) -> Result<NetBoxCable, NetBoxError> {
+        let url = self.build_url(&format!("dcim/cables/{}/", id))?;
+        debug!("Getting cable from NetBox: {}", url);
+
+        let response = self.send(self.client.get(&url)).await?;
+
+        let status = response.status();
+        let text = self.read_text(response).await?;
+
+        if !status.is_success() {
+            if status == 404 {
+                return Err(NetBoxError::NotFound(format!("Cable with ID {} not found", id)));
+            }
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        serde_json::from_str(&text).map_err(NetBoxError::SerializationError)
+    }
+
+    /// List cables, optionally only those of one device
+    pub async fn list_cables(
+        &self,
+        device_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<NetBoxResponse<NetBoxCable>, NetBoxError> {
+        let mut params = Vec::new();
+        if let Some(device) = device_id {
+            params.push(("device_id", device.to_string()));
+        }
+        Self::push_page_params(&mut params, limit, offset);
+
+        self.list("dcim/cables/", &params).await
+    }
+
+    /// Delete a cable
+    pub async fn delete_cable(&self, id: i32) -> Result<(), NetBoxError> {
+        let url = self.build_url(&format!("dcim/cables/{}/", id))?;
+        debug!("Deleting cable from NetBox: {}", url);
+
+        let response = self.send(self.client.delete(&url)).await?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            if status == 404 {
+                return Err(NetBoxError::NotFound(format!("Cable with ID {} not found", id)));
+            }
+            let text = self.read_text(response).await.unwrap_or_default();
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        Ok(())
+    }
+
+    // ========== Extras Operations ==========
+
+    /// List tags, up to NetBox's default maximum page size
+    pub async fn list_tags(&self) -> Result<Vec<NetBoxTag>, NetBoxError> {
+        let response: NetBoxResponse<NetBoxTag> =
+            self.list("extras/tags/", &[("limit", EXTRAS_PAGE_SIZE.to_string())]).await?;
+        Ok(response.results.unwrap_or_default())
+    }
+
+    /// Create a tag in NetBox
+    pub async fn create_tag(&self, name: &str, slug: &str, color: &str) -> Result<NetBoxTag, NetBoxError> {
+        let url = self.build_url("extras/tags/")?;
+        debug!("Creating tag '{}' in NetBox: {}", name, url);
+
+        let request = CreateTagRequest {
+            name: name.to_string(),
+            slug: slug.to_string(),
+            color: color.to_string(),
+        };
+        let response = self.send(self.client.post(&url).json(&request)).await?;
+
+        let status = response.status();
+        let text = self.read_text(response).await?;
+
+        if !status.is_success() {
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        serde_json::from_str(&text).map_err(NetBoxError::SerializationError)
+    }
+
+    /// List custom field definitions, up to NetBox's default maximum page size
+    pub async fn list_custom_fields(&self) -> Result<Vec<NetBoxCustomField>, NetBoxError> {
+        let response: NetBoxResponse<NetBoxCustomField> =
+            self.list("extras/custom-fields/", &[("limit", EXTRAS_PAGE_SIZE.to_string())]).await?;
+        Ok(response.results.unwrap_or_default())
+    }
+
+    /// List custom field choice sets (NetBox 3.6+), up to NetBox's default maximum page size
+    pub async fn list_custom_field_choice_sets(&self) -> Result<Vec<NetBoxCustomFieldChoiceSet>, NetBoxError> {
+        let response: NetBoxResponse<NetBoxCustomFieldChoiceSet> =
+            self.list("extras/custom-field-choice-sets/", &[("limit", EXTRAS_PAGE_SIZE.to_string())]).await?;
+        Ok(response.results.unwrap_or_default())
+    }
+
+    /// A page of an object's changelog, newest first
+    ///
+    /// `object_type` is NetBox's `app_label.model`, e.g. `dcim.site`.
+    pub async fn get_object_changes(
+        &self,
+        object_type: &str,
+        object_id: i32,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<NetBoxResponse<NetBoxObjectChange>, NetBoxError> {
+        let mut params = vec![
+            ("changed_object_type", object_type.to_string()),
+            ("changed_object_id", object_id.to_string()),
+        ];
+        Self::push_page_params(&mut params, limit, offset);
+
+        self.list("extras/object-changes/", &params).await
+    }
+
+    // ========== Tenancy Operations ==========
+
+    /// Create a tenant in NetBox
+    pub async fn create_tenant(&self, request: CreateTenantRequest) -> Result<NetBoxTenant, NetBoxError> {
+        let url = self.build_url("tenancy/tenants/")?;
+        debug!("Creating tenant in NetBox: {}", url);
+
+        let response = self.send(self.client.post(&url).json(&request)).await?;
+
+        let status = response.status();
+        let text = self.read_text(response).await?;
+
+        if !status.is_success() {
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        serde_json::from_str(&text).map_err(NetBoxError::SerializationError)
+    }
+
+    /// Delete a tenant from NetBox
+    pub async fn delete_tenant(&self, id: i32) -> Result<(), NetBoxError> {
+        let url = self.build_url(&format!("tenancy/tenants/{}/", id))?;
+        debug!("Deleting tenant from NetBox: {}", url);
+
+        let response = self.send(self.client.delete(&url)).await?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            if status == 404 {
+                return Err(NetBoxError::NotFound(format!("Tenant with ID {} not found", id)));
+            }
+            let text = self.read_text(response).await.unwrap_or_default();
+            error!("NetBox API error: {} - {}", status, redact_body(text.as_bytes(), &self.token));
+            return Err(NetBoxError::from_status_code(status.as_u16(), text));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use serde_json::json;
+    use wiremock::{
+        matchers::{header, method, path, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    fn create_test_config(base_url: String, token: String) -> Config {
+        Config {
+            port: 8080,
+            netbox_url: base_url,
+            netbox_token: token,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_creation_success() {
+        let config = create_test_config("http://localhost:8000".to_string(), "test-token".to_string());
+        let result = NetBoxClient::new(config);
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_client_creation_no_token() {
+        let config = create_test_config("http://localhost:8000".to_string(), "".to_string());
+        let result = NetBoxClient::new(config);
+        assert!(result.is_err());
+        if let Err(NetBoxError::AuthenticationError(_)) = result {
+            // Expected error
+        } else {
+            panic!("Expected AuthenticationError");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_creation_bad_ca_bundle() {
+        let mut config = create_test_config("https://netbox.example.com".to_string(), "test-token".to_string());
+        config.netbox_transport.ca_bundle_path = Some("/nonexistent/netbox-ca.pem".into());
+        match NetBoxClient::new(config) {
+            Err(NetBoxError::InvalidConfig(message)) => assert!(message.contains("/nonexistent/netbox-ca.pem")),
+            Err(other) => panic!("Expected InvalidConfig, got {}", other),
+            Ok(_) => panic!("Expected client construction to fail"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_works_with_each_http2_mode() {
+        use crate::netbox::transport::Http2Mode;
+
+        for mode in [Http2Mode::Auto, Http2Mode::Off, Http2Mode::PriorKnowledge] {
+            let mock_server = MockServer::start().await;
+            let mut config = create_test_config(mock_server.uri(), "test-token".to_string());
+            config.netbox_pool.http2 = mode;
+            config.netbox_pool.max_idle_per_host = 1;
+            let client = NetBoxClient::new(config).unwrap();
+            assert_eq!(client.pool_config().http2, mode);
+
+            Mock::given(method("GET"))
+                .and(path("/api/dcim/sites/1/"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 1, "name": "Site 1"})))
+                .expect(2)
+                .mount(&mock_server)
+                .await;
+
+            for _ in 0..2 {
+                let site = client.get_site(1).await.unwrap_or_else(|e| panic!("{:?} failed: {}", mode, e));
+                assert_eq!(site.name, "Site 1");
+            }
+        }
+    }
+
+    /// Log lines written by a test-local subscriber
+    #[derive(Clone, Default)]
+    struct CapturedLogs(std::sync::Arc<parking_lot::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for CapturedLogs {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_body_logging_redacts_token() {
+        let secret = "swordfish";
+        let token = format!("{}-0123", secret);
+        let mock_server = MockServer::start().await;
+        let config = Config {
+            log_netbox_bodies: true,
+            ..create_test_config(mock_server.uri(), token.clone())
+        };
+        let client = NetBoxClient::new(config).unwrap();
+
+        Mock::given(method("POST"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "id": 1,
+                "name": "Site 1",
+                "description": format!("created with {}", token),
+                "api_token": "abc"
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(400).set_body_string(format!("bad token {}", token)))
+            .mount(&mock_server)
+            .await;
+
+        let logs = CapturedLogs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::TRACE)
+            .with_ansi(false)
+            .with_writer(logs.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let site = client
+            .create_site(
+                CreateSiteRequest::builder("Site 1")
+                    .with_description(format!("requested with {}", token))
+                    .build(),
+            )
+            .await
+            .unwrap();
+        // The rebuilt response still parses
+        assert_eq!(site.id, Some(1));
+        assert!(client.list_sites(None, Some(&token), None, None).await.is_err());
+
+        let output = String::from_utf8(logs.0.lock().clone()).unwrap();
+        assert!(output.contains("POST"), "{}", output);
+        assert!(output.contains("response body"), "{}", output);
+        assert!(output.contains("[REDACTED]"), "{}", output);
+        assert!(!output.contains(secret), "{}", output);
+    }
+
+    #[tokio::test]
+    async fn test_create_site_success() {
+        let mock_server = MockServer::start().await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let client = NetBoxClient::new(config).unwrap();
+
+        let site_response = json!({
+            "id": 1,
+            "name": "Test Site",
+            "description": "Test Description",
+            "status": "active"
+        });
+
+        Mock::given(method("POST"))
+            .and(path("/api/dcim/sites/"))
+            .and(header("Authorization", "Token test-token"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(&site_response))
+            .mount(&mock_server)
+            .await;
+
+        let request = CreateSiteRequest::builder("Test Site")
+            .with_description("Test Description")
+            .with_status(SiteStatus::Active)
+            .build();
+
+        let result = client.create_site(request).await;
+        assert!(result.is_ok());
+        let site = result.unwrap();
+        assert_eq!(site.name, "Test Site");
+        assert_eq!(site.description, Some("Test Description".to_string()));
+        assert_eq!(site.id, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_create_site_authentication_error() {
+        let mock_server = MockServer::start().await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let client = NetBoxClient::new(config).unwrap();
+
+        Mock::given(method("POST"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+                "detail": "Invalid token"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let request = CreateSiteRequest::builder("Test Site").build();
+
+        let result = client.create_site(request).await;
+        assert!(result.is_err());
+        match result.unwrap_err() {
+            NetBoxError::AuthenticationError(_) => {}
+            _ => panic!("Expected AuthenticationError"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_site_success() {
+        let mock_server = MockServer::start().await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let client = NetBoxClient::new(config).unwrap();
+
+        let site_response = json!({
+            "id": 1,
+            "name": "Test Site",
+            "description": "Test Description",
+            "status": "active"
+        });
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .and(header("Authorization", "Token test-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&site_response))
+            .mount(&mock_server)
+            .await;
+
+        let result = client.get_site(1).await;
+        assert!(result.is_ok());
+        let site = result.unwrap();
+        assert_eq!(site.id, Some(1));
+        assert_eq!(site.name, "Test Site");
+    }
+
+    #[tokio::test]
+    async fn test_get_site_not_found() {
+        let mock_server = MockServer::start().await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let client = NetBoxClient::new(config).unwrap();
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/999/"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+                "detail": "Not found"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = client.get_site(999).await;
+        assert!(result.is_err());
+        match result.unwrap_err() {
+            NetBoxError::NotFound(_) => {}
+            _ => panic!("Expected NotFound error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_sites_success() {
+        let mock_server = MockServer::start().await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let client = NetBoxClient::new(config).unwrap();
+
+        let sites_response = json!({
+            "count": 2,
+            "next": null,
+            "previous": null,
+            "results": [
+                {
+                    "id": 1,
+                    "name": "Site 1",
+                    "status": "active"
+                },
+                {
+                    "id": 2,
+                    "name": "Site 2",
+                    "status": "active"
+                }
+            ]
+        });
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&sites_response))
+            .mount(&mock_server)
+            .await;
+
+        let result = client.list_sites(None, None, None, None).await;
+        assert!(result.is_ok());
+        let response = result.unwrap();
+        assert_eq!(response.count, Some(2));
+        assert_eq!(response.results.as_ref().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_list_sites_with_tenant_filter() {
+        let mock_server = MockServer::start().await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let client = NetBoxClient::new(config).unwrap();
+
+        let sites_response = json!({
+            "count": 1,
+            "results": [
+                {
+                    "id": 1,
+                    "name": "Tenant Site",
+                    "tenant": 10,
+                    "status": "active"
+                }
+            ]
+        });
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&sites_response))
+            .mount(&mock_server)
+            .await;
+
+        let result = client.list_sites(Some(10), None, None, None).await;
+        assert!(result.is_ok());
+        let response = result.unwrap();
+        assert_eq!(response.count, Some(1));
+        assert_eq!(response.results.as_ref().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_list_sites_with_name_filter() {
+        let mock_server = MockServer::start().await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let client = NetBoxClient::new(config).unwrap();
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .and(query_param("name", "Site & Co"))
+            .and(query_param("tenant_id", "10"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"count": 0, "results": []})))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let response = client.list_sites(Some(10), Some("Site & Co"), None, None).await.unwrap();
+        assert_eq!(response.count, Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_list_sites_encodes_filter_values() {
+        let mock_server = MockServer::start().await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let client = NetBoxClient::new(config).unwrap();
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"count": 0, "results": []})))
+            .mount(&mock_server)
+            .await;
+
+        let filters = HashMap::from([
+            ("tag".to_string(), "net gate".to_string()),
+            ("status".to_string(), "active".to_string()),
+        ]);
+        client
+            .list_sites_with_filters(None, Some("A&B =C"), Some(10), None, &filters)
+            .await
+            .unwrap();
+
+        let requests = mock_server.received_requests().await.unwrap();
+        assert_eq!(
+            requests[0].url.query(),
+            Some("name=A%26B+%3DC&limit=10&status=active&tag=net+gate")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_rejects_response_over_size_limit() {
+        let mock_server = MockServer::start().await;
+        let config = Config {
+            netbox_max_response_bytes: 4096,
+            ..create_test_config(mock_server.uri(), "test-token".to_string())
+        };
+        let client = NetBoxClient::new(config).unwrap();
+
+        let padding = "x".repeat(8192);
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 1,
+                "results": [{"id": 1, "name": "Site 1", "description": padding}]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = client.list_sites(None, None, None, None).await;
+        assert!(matches!(result, Err(NetBoxError::ResponseTooLarge { limit: 4096 })));
+    }
+
+    #[tokio::test]
+    async fn test_single_object_calls_reject_responses_over_size_limit() {
+        let mock_server = MockServer::start().await;
+        let config = Config {
+            netbox_max_response_bytes: 4096,
+            ..create_test_config(mock_server.uri(), "test-token".to_string())
+        };
+        let client = NetBoxClient::new(config).unwrap();
+
+        let padding = "x".repeat(8192);
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": 1,
+                "name": "Site 1",
+                "description": padding
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(
+                ResponseTemplate::new(503)
+                    .set_body_raw(format!("<html><body>{}</body></html>", padding), "text/html"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let result = client.get_site(1).await;
+        assert!(matches!(result, Err(NetBoxError::ResponseTooLarge { limit: 4096 })));
+        let result = client.create_site(CreateSiteRequest::builder("Site 2").build()).await;
+        assert!(matches!(result, Err(NetBoxError::ResponseTooLarge { limit: 4096 })));
+    }
+
+    #[tokio::test]
+    async fn test_client_accepts_compressed_responses() {
+        let mock_server = MockServer::start().await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let client = NetBoxClient::new(config).unwrap();
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"count": 0, "results": []})))
+            .mount(&mock_server)
+            .await;
+
+        client.list_sites(None, None, None, None).await.unwrap();
+        let requests = mock_server.received_requests().await.unwrap();
+        let accept_encoding = requests[0].headers.get(&"accept-encoding".into()).unwrap().to_string();
+        assert!(accept_encoding.contains("gzip"));
+        assert!(accept_encoding.contains("br"));
+    }
+
+    #[tokio::test]
+    async fn test_list_devices_with_extra_filters() {
+        let mock_server = MockServer::start().await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let client = NetBoxClient::new(config).unwrap();
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/devices/"))
+            .and(query_param("site_id", "3"))
+            .and(query_param("role", "core switch"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"count": 0, "results": []})))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let filters = HashMap::from([("role".to_string(), "core switch".to_string())]);
+        let response = client
+            .list_devices_with_filters(Some(3), None, None, None, &filters)
+            .await
+            .unwrap();
+        assert_eq!(response.count, Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_get_with_validator_returns_not_modified_for_matching_etag() {
+        let mock_server = MockServer::start().await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let client = NetBoxClient::new(config).unwrap();
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .and(header("If-None-Match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"v1\"")
+                    .insert_header("Last-Modified", "Wed, 01 Jan 2025 00:00:00 GMT")
+                    .set_body_json(json!({"id": 1, "name": "Site 1"})),
+            )
             .mount(&mock_server)
             .await;
 
-        let request = CreateSiteRequest {
-            name: "Test Site".to_string(),
-            description: Some("Test Description".to_string()),
-            slug: None,
-            status: Some(SiteStatus::Active),
-            region: None,
-            tenant: None,
-            facility: None,
-            physical_address: None,
-            shipping_address: None,
-            latitude: None,
-            longitude: None,
-            contact_name: None,
-            contact_phone: None,
-            contact_email: None,
-            comments: None,
-            tags: None,
+        let Conditional::Fresh(body, validator) = client.get_with_validator("dcim/sites/1/", None).await.unwrap() else {
+            panic!("Expected a fresh response");
         };
+        assert!(String::from_utf8_lossy(&body).contains("Site 1"));
+        assert_eq!(validator.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(validator.last_modified.as_deref(), Some("Wed, 01 Jan 2025 00:00:00 GMT"));
 
-        let result = client.create_site(request).await;
-        assert!(result.is_ok());
-        let site = result.unwrap();
-        assert_eq!(site.name, "Test Site");
-        assert_eq!(site.description, Some("Test Description".to_string()));
-        assert_eq!(site.id, Some(1));
+        let revalidated = client.get_with_validator("dcim/sites/1/", Some(&validator)).await.unwrap();
+        assert_eq!(revalidated, Conditional::NotModified);
+
+        let stale = Validator { etag: Some("\"v0\"".to_string()), last_modified: None };
+        let refetched = client.get_with_validator("dcim/sites/1/", Some(&stale)).await.unwrap();
+        assert!(matches!(refetched, Conditional::Fresh(_, _)));
     }
 
     #[tokio::test]
-    async fn test_create_site_authentication_error() {
+    async fn test_get_sites_by_ids_chunks_and_preserves_order() {
         let mock_server = MockServer::start().await;
         let config = create_test_config(mock_server.uri(), "test-token".to_string());
         let client = NetBoxClient::new(config).unwrap();
 
-        Mock::given(method("POST"))
+        // Every chunk comes back with the even IDs it asked for
+        Mock::given(method("GET"))
             .and(path("/api/dcim/sites/"))
-            .respond_with(ResponseTemplate::new(401).set_body_json(json!({
-                "detail": "Invalid token"
-            })))
+            .respond_with(|request: &wiremock::Request| {
+                let (_, ids) = request.url.query_pairs().find(|(k, _)| k == "id__in").unwrap();
+                let results: Vec<_> = ids
+                    .split(',')
+                    .map(|id| id.parse::<i32>().unwrap())
+                    .filter(|id| id % 2 == 0)
+                    .map(|id| json!({"id": id, "name": format!("Site {}", id)}))
+                    .collect();
+                ResponseTemplate::new(200).set_body_json(json!({"count": results.len(), "results": results}))
+            })
             .mount(&mock_server)
             .await;
 
-        let request = CreateSiteRequest {
-            name: "Test Site".to_string(),
-            description: None,
-            slug: None,
-            status: None,
-            region: None,
-            tenant: None,
-            facility: None,
-            physical_address: None,
-            shipping_address: None,
-            latitude: None,
-            longitude: None,
-            contact_name: None,
-            contact_phone: None,
-            contact_email: None,
-            comments: None,
-            tags: None,
-        };
+        let ids: Vec<i32> = (1..=150).rev().collect();
+        let sites = client.get_sites_by_ids(&ids).await.unwrap();
 
-        let result = client.create_site(request).await;
-        assert!(result.is_err());
-        match result.unwrap_err() {
-            NetBoxError::AuthenticationError(_) => {}
-            _ => panic!("Expected AuthenticationError"),
+        let requests = mock_server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 150 / BATCH_GET_CHUNK_SIZE);
+        assert_eq!(sites.len(), 150);
+        assert_eq!(sites.iter().map(|(id, _)| *id).collect::<Vec<_>>(), ids);
+        for (id, site) in &sites {
+            assert_eq!(site.as_ref().map(|s| s.name.clone()), (id % 2 == 0).then(|| format!("Site {}", id)));
         }
     }
 
     #[tokio::test]
-    async fn test_get_site_success() {
+    async fn test_get_devices_by_ids_deduplicates() {
         let mock_server = MockServer::start().await;
         let config = create_test_config(mock_server.uri(), "test-token".to_string());
         let client = NetBoxClient::new(config).unwrap();
 
-        let site_response = json!({
-            "id": 1,
-            "name": "Test Site",
-            "description": "Test Description",
-            "status": "active"
-        });
-
         Mock::given(method("GET"))
-            .and(path("/api/dcim/sites/1/"))
-            .and(header("Authorization", "Token test-token"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(&site_response))
+            .and(path("/api/dcim/devices/"))
+            .and(query_param("id__in", "3,7"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 1,
+                "results": [{"id": 7, "name": "sw-7"}]
+            })))
+            .expect(1)
             .mount(&mock_server)
             .await;
 
-        let result = client.get_site(1).await;
-        assert!(result.is_ok());
-        let site = result.unwrap();
-        assert_eq!(site.id, Some(1));
-        assert_eq!(site.name, "Test Site");
+        let devices = client.get_devices_by_ids(&[7, 3, 7]).await.unwrap();
+        let names: Vec<_> = devices
+            .iter()
+            .map(|(id, device)| (*id, device.as_ref().and_then(|d| d.name.clone())))
+            .collect();
+        assert_eq!(names, vec![(7, Some("sw-7".to_string())), (3, None), (7, Some("sw-7".to_string()))]);
+        assert!(client.get_devices_by_ids(&[]).await.unwrap().is_empty());
     }
 
-    #[tokio::test]
-    async fn test_get_site_not_found() {
+    async fn create_device_against(netbox_version: &str) -> serde_json::Value {
         let mock_server = MockServer::start().await;
         let config = create_test_config(mock_server.uri(), "test-token".to_string());
         let client = NetBoxClient::new(config).unwrap();
-
         Mock::given(method("GET"))
-            .and(path("/api/dcim/sites/999/"))
-            .respond_with(ResponseTemplate::new(404).set_body_json(json!({
-                "detail": "Not found"
-            })))
+            .and(path("/api/status/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"netbox-version": netbox_version})))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/dcim/devices/"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"id": 1, "name": "sw1"})))
             .mount(&mock_server)
             .await;
 
-        let result = client.get_site(999).await;
-        assert!(result.is_err());
-        match result.unwrap_err() {
-            NetBoxError::NotFound(_) => {}
-            _ => panic!("Expected NotFound error"),
-        }
+        let request: CreateDeviceRequest = serde_json::from_value(json!({
+            "name": "sw1",
+            "device_type": 1,
+            "device_role": 2,
+            "site": 3,
+            "tags": ["netgate"]
+        }))
+        .unwrap();
+        client.create_device(request).await.unwrap();
+
+        let requests = mock_server.received_requests().await.unwrap();
+        let post = requests.iter().find(|request| request.method == wiremock::http::Method::Post).unwrap();
+        serde_json::from_slice(&post.body).unwrap()
     }
 
     #[tokio::test]
-    async fn test_list_sites_success() {
-        let mock_server = MockServer::start().await;
-        let config = create_test_config(mock_server.uri(), "test-token".to_string());
-        let client = NetBoxClient::new(config).unwrap();
+    async fn test_device_payload_for_legacy_netbox() {
+        let body = create_device_against("2.11.12").await;
 
-        let sites_response = json!({
-            "count": 2,
-            "next": null,
-            "previous": null,
-            "results": [
-                {
-                    "id": 1,
-                    "name": "Site 1",
-                    "status": "active"
-                },
-                {
-                    "id": 2,
-                    "name": "Site 2",
-                    "status": "active"
-                }
-            ]
-        });
+        assert_eq!(body["device_role"], 2);
+        assert!(body.get("role").is_none());
+        assert_eq!(body["tags"], json!(["netgate"]));
+    }
+
+    #[tokio::test]
+    async fn test_device_payload_for_netbox_3_6() {
+        let body = create_device_against("3.6.9").await;
+
+        assert_eq!(body["role"], 2);
+        assert!(body.get("device_role").is_none());
+        assert_eq!(body["tags"], json!([{"name": "netgate"}]));
+    }
+
+    #[tokio::test]
+    async fn test_token_scope_from_the_token_list() {
+        for (write_enabled, expected) in [(false, TokenScope::ReadOnly), (true, TokenScope::ReadWrite)] {
+            let mock_server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/users/tokens/"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "count": 2,
+                    "results": [
+                        {"id": 1, "key": "other-token", "write_enabled": !write_enabled},
+                        {"id": 2, "key": "test-token", "write_enabled": write_enabled}
+                    ]
+                })))
+                .mount(&mock_server)
+                .await;
+            Mock::given(method("OPTIONS"))
+                .and(path("/api/dcim/sites/"))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(0)
+                .mount(&mock_server)
+                .await;
+            let client = NetBoxClient::new(create_test_config(mock_server.uri(), "test-token".to_string())).unwrap();
+
+            assert_eq!(client.token_scope().await, expected);
+        }
+    }
 
+    #[tokio::test]
+    async fn test_token_scope_falls_back_to_an_options_probe() {
+        let mock_server = MockServer::start().await;
         Mock::given(method("GET"))
+            .and(path("/api/users/tokens/"))
+            .respond_with(ResponseTemplate::new(403).set_body_json(json!({"detail": "Permission denied"})))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("OPTIONS"))
             .and(path("/api/dcim/sites/"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(&sites_response))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "name": "Site List",
+                "actions": {"POST": {"name": {"type": "string", "required": true}}}
+            })))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("OPTIONS"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"name": "Site List"})))
+            .up_to_n_times(1)
             .mount(&mock_server)
             .await;
+        Mock::given(method("OPTIONS"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&mock_server)
+            .await;
+        let client = NetBoxClient::new(create_test_config(mock_server.uri(), "test-token".to_string())).unwrap();
 
-        let result = client.list_sites(None, None, None).await;
-        assert!(result.is_ok());
-        let response = result.unwrap();
-        assert_eq!(response.count, Some(2));
-        assert_eq!(response.results.as_ref().unwrap().len(), 2);
+        assert_eq!(client.token_scope().await, TokenScope::ReadWrite);
+        assert_eq!(client.token_scope().await, TokenScope::ReadOnly);
+        assert_eq!(client.token_scope().await, TokenScope::Undetermined);
     }
 
     #[tokio::test]
-    async fn test_list_sites_with_tenant_filter() {
+    async fn test_version_is_detected_once() {
         let mock_server = MockServer::start().await;
         let config = create_test_config(mock_server.uri(), "test-token".to_string());
         let client = NetBoxClient::new(config).unwrap();
-
-        let sites_response = json!({
-            "count": 1,
-            "results": [
-                {
-                    "id": 1,
-                    "name": "Tenant Site",
-                    "tenant": 10,
-                    "status": "active"
-                }
-            ]
-        });
-
         Mock::given(method("GET"))
-            .and(path("/api/dcim/sites/"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(&sites_response))
+            .and(path("/api/status/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"netbox-version": "3.7.2"})))
+            .expect(1)
             .mount(&mock_server)
             .await;
 
-        let result = client.list_sites(Some(10), None, None).await;
-        assert!(result.is_ok());
-        let response = result.unwrap();
-        assert_eq!(response.count, Some(1));
-        assert_eq!(response.results.as_ref().unwrap().len(), 1);
+        assert_eq!(client.version().await, Some(NetBoxVersion::new(3, 7, 2)));
+        assert_eq!(client.version().await, Some(NetBoxVersion::new(3, 7, 2)));
     }
 
     #[tokio::test]
@@ -655,24 +2302,11 @@ mod tests {
             .mount(&mock_server)
             .await;
 
-        let request = UpdateSiteRequest {
-            name: Some("Updated Site".to_string()),
-            description: Some("Updated Description".to_string()),
-            slug: None,
-            status: Some(SiteStatus::Active),
-            region: None,
-            tenant: None,
-            facility: None,
-            physical_address: None,
-            shipping_address: None,
-            latitude: None,
-            longitude: None,
-            contact_name: None,
-            contact_phone: None,
-            contact_email: None,
-            comments: None,
-            tags: None,
-        };
+        let request = UpdateSiteRequest::builder()
+            .with_name("Updated Site")
+            .with_description("Updated Description")
+            .with_status(SiteStatus::Active)
+            .build();
 
         let result = client.update_site(1, request).await;
         assert!(result.is_ok());
@@ -740,24 +2374,10 @@ mod tests {
             .mount(&mock_server)
             .await;
 
-        let request = CreateDeviceRequest {
-            name: Some("test-device".to_string()),
-            device_type: 1,
-            device_role: 1,
-            site: 1,
-            tenant: None,
-            platform: None,
-            serial: None,
-            asset_tag: None,
-            location: None,
-            rack: None,
-            position: None,
-            face: None,
-            status: Some(DeviceStatus::Active),
-            cluster: None,
-            comments: None,
-            tags: None,
-        };
+        let request = CreateDeviceRequest::builder(1, 1, 1)
+            .with_name("test-device")
+            .with_status(DeviceStatus::Active)
+            .build();
 
         let result = client.create_device(request).await;
         assert!(result.is_ok());
@@ -899,30 +2519,244 @@ mod tests {
             .mount(&mock_server)
             .await;
 
-        let request = CreateSiteRequest {
-            name: "".to_string(), // Invalid: empty name
-            description: None,
-            slug: None,
-            status: None,
-            region: None,
-            tenant: None,
-            facility: None,
-            physical_address: None,
-            shipping_address: None,
-            latitude: None,
-            longitude: None,
-            contact_name: None,
-            contact_phone: None,
-            contact_email: None,
-            comments: None,
-            tags: None,
-        };
+        let request = CreateSiteRequest::builder("") // Invalid: empty name
+            .build();
 
         let result = client.create_site(request).await;
         assert!(result.is_err());
         match result.unwrap_err() {
-            NetBoxError::ValidationError(_) => {}
+            NetBoxError::ValidationError { .. } => {}
             _ => panic!("Expected ValidationError"),
         }
     }
+
+    #[tokio::test]
+    async fn test_create_prefix_success() {
+        let mock_server = MockServer::start().await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let client = NetBoxClient::new(config).unwrap();
+
+        Mock::given(method("POST"))
+            .and(path("/api/ipam/prefixes/"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "id": 5,
+                "prefix": "10.0.0.0/24",
+                "vlan": 100,
+                "status": "active"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let request = CreatePrefixRequest {
+            prefix: "10.0.0.0/24".to_string(),
+            site: None,
+            vlan: Some(100),
+            tenant: None,
+            status: Some(PrefixStatus::Active),
+            description: None,
+            tags: None,
+        };
+
+        let prefix = client.create_prefix(request).await.unwrap();
+        assert_eq!(prefix.id, Some(5));
+        assert_eq!(prefix.prefix, "10.0.0.0/24");
+        assert_eq!(prefix.status, Some(PrefixStatus::Active));
+    }
+
+    #[tokio::test]
+    async fn test_get_and_delete_prefix_not_found() {
+        let mock_server = MockServer::start().await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let client = NetBoxClient::new(config).unwrap();
+
+        Mock::given(path("/api/ipam/prefixes/9/"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        assert!(matches!(client.get_prefix(9).await, Err(NetBoxError::NotFound(_))));
+        assert!(matches!(client.delete_prefix(9).await, Err(NetBoxError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_allocate_ip_assigns_interface() {
+        let mock_server = MockServer::start().await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let client = NetBoxClient::new(config).unwrap();
+
+        Mock::given(method("GET"))
+            .and(path("/api/ipam/prefixes/5/available-ips/"))
+            .and(query_param("limit", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {"family": 4, "address": "10.0.0.2/24"}
+            ])))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/ipam/prefixes/5/available-ips/"))
+            .and(wiremock::matchers::body_partial_json(json!({
+                "assigned_object_type": "dcim.interface",
+                "assigned_object_id": 31,
+                "dns_name": "edge01.example.net"
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "id": 77,
+                "address": "10.0.0.2/24",
+                "status": "active",
+                "assigned_object_type": "dcim.interface",
+                "assigned_object_id": 31,
+                "dns_name": "edge01.example.net"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let available = client.list_available_ips(5, Some(1)).await.unwrap();
+        assert_eq!(available[0].address, "10.0.0.2/24");
+
+        let mut request = AllocateIpRequest {
+            dns_name: Some("edge01.example.net".to_string()),
+            ..Default::default()
+        };
+        request.assign_to_interface(31);
+        let ip = client.allocate_ip(5, request).await.unwrap();
+        assert_eq!(ip.id, Some(77));
+        assert_eq!(ip.address, "10.0.0.2/24");
+        assert_eq!(ip.assigned_object_id, Some(31));
+    }
+
+    #[tokio::test]
+    async fn test_allocate_ip_from_exhausted_prefix() {
+        let mock_server = MockServer::start().await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let client = NetBoxClient::new(config).unwrap();
+
+        Mock::given(method("POST"))
+            .and(path("/api/ipam/prefixes/5/available-ips/"))
+            .respond_with(ResponseTemplate::new(409).set_body_json(json!({
+                "detail": "An insufficient number of IP addresses are available within prefix 10.0.0.0/30 (1 requested, 0 available)"
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/ipam/prefixes/6/available-ips/"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&mock_server)
+            .await;
+        for prefix_id in [5, 6] {
+            Mock::given(method("GET"))
+                .and(path(format!("/api/ipam/prefixes/{}/", prefix_id)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "id": prefix_id, "prefix": "10.0.0.0/30"
+                })))
+                .mount(&mock_server)
+                .await;
+        }
+
+        for prefix_id in [5, 6] {
+            match client.allocate_ip(prefix_id, AllocateIpRequest::default()).await {
+                Err(NetBoxError::Exhausted { prefix_id: exhausted, capacity }) => {
+                    assert_eq!((exhausted, capacity), (prefix_id, 2))
+                }
+                other => panic!("Expected Exhausted, got {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_virtual_machine_and_list_by_cluster() {
+        let mock_server = MockServer::start().await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let client = NetBoxClient::new(config).unwrap();
+
+        Mock::given(method("POST"))
+            .and(path("/api/virtualization/virtual-machines/"))
+            .and(wiremock::matchers::body_partial_json(json!({
+                "name": "app01",
+                "cluster": 3,
+                "vcpus": 2.0,
+                "memory": 4096,
+                "disk": 40
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "id": 11,
+                "name": "app01",
+                "cluster": 3,
+                "site": 1,
+                "status": "active",
+                "vcpus": 2.0,
+                "memory": 4096,
+                "disk": 40
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/virtualization/virtual-machines/"))
+            .and(query_param("cluster_id", "3"))
+            .and(query_param("tenant_id", "10"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 1,
+                "results": [{"id": 11, "name": "app01", "cluster": 3, "tenant": 10}]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let request = CreateVirtualMachineRequest {
+            name: "app01".to_string(),
+            cluster: Some(3),
+            site: None,
+            tenant: None,
+            role: None,
+            platform: None,
+            status: Some(VirtualMachineStatus::Active),
+            vcpus: Some(2.0),
+            memory: Some(4096),
+            disk: Some(40),
+            description: None,
+            tags: None,
+        };
+        let vm = client.create_virtual_machine(request).await.unwrap();
+        assert_eq!(vm.id, Some(11));
+        assert_eq!(vm.site, Some(1));
+        assert_eq!(vm.status, Some(VirtualMachineStatus::Active));
+
+        let page = client
+            .list_virtual_machines_with_filters(None, Some(3), Some(10), None, None, &HashMap::new())
+            .await
+            .unwrap();
+        let vms = page.results.unwrap();
+        assert_eq!(vms.len(), 1);
+        assert_eq!(vms[0].tenant, Some(10));
+    }
+
+    #[tokio::test]
+    async fn test_create_tenant_success() {
+        let mock_server = MockServer::start().await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let client = NetBoxClient::new(config).unwrap();
+
+        Mock::given(method("POST"))
+            .and(path("/api/tenancy/tenants/"))
+            .and(wiremock::matchers::body_json(json!({"name": "Acme", "slug": "acme", "description": null})))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "id": 42,
+                "name": "Acme",
+                "slug": "acme"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let tenant = client
+            .create_tenant(CreateTenantRequest {
+                name: "Acme".to_string(),
+                slug: "acme".to_string(),
+                description: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(tenant.id, Some(42));
+        assert_eq!(tenant.slug, "acme");
+    }
 }
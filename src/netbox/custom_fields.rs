@@ -0,0 +1,169 @@
+use crate::netbox::models::{NetBoxCustomField, NetBoxCustomFieldChoiceSet};
+use std::collections::HashMap;
+
+/// NetBox object type of sites, as custom fields list it
+pub const SITE_OBJECT_TYPE: &str = "dcim.site";
+
+/// What values a custom field accepts, by NetBox field type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CustomFieldKind {
+    /// text, longtext and url
+    Text,
+    Integer,
+    Decimal,
+    Boolean,
+    /// `YYYY-MM-DD`
+    Date,
+    /// RFC 3339 timestamp
+    DateTime,
+    Select,
+    MultiSelect,
+    /// A related object's id
+    Object,
+    /// A list of related object ids
+    MultiObject,
+    /// json, and types NetGate doesn't know; any value is accepted
+    Any,
+}
+
+impl CustomFieldKind {
+    /// Kind for NetBox's type value, e.g. `text` or `multiselect`
+    pub fn from_netbox(value: &str) -> Self {
+        match value {
+            "text" | "longtext" | "url" => CustomFieldKind::Text,
+            "integer" => CustomFieldKind::Integer,
+            "decimal" => CustomFieldKind::Decimal,
+            "boolean" => CustomFieldKind::Boolean,
+            "date" => CustomFieldKind::Date,
+            "datetime" => CustomFieldKind::DateTime,
+            "select" => CustomFieldKind::Select,
+            "multiselect" => CustomFieldKind::MultiSelect,
+            "object" => CustomFieldKind::Object,
+            "multiobject" => CustomFieldKind::MultiObject,
+            _ => CustomFieldKind::Any,
+        }
+    }
+
+    /// What a value of this kind looks like, for error messages
+    pub fn describe(&self) -> &'static str {
+        match self {
+            CustomFieldKind::Text => "a string",
+            CustomFieldKind::Integer => "an integer",
+            CustomFieldKind::Decimal => "a number",
+            CustomFieldKind::Boolean => "a boolean",
+            CustomFieldKind::Date => "a date (YYYY-MM-DD)",
+            CustomFieldKind::DateTime => "an RFC 3339 timestamp",
+            CustomFieldKind::Select => "a string choice",
+            CustomFieldKind::MultiSelect => "a list of string choices",
+            CustomFieldKind::Object => "an object id",
+            CustomFieldKind::MultiObject => "a list of object ids",
+            CustomFieldKind::Any => "any value",
+        }
+    }
+}
+
+/// One custom field as NetGate validates against it
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomFieldDefinition {
+    pub name: String,
+    pub kind: CustomFieldKind,
+    pub required: bool,
+    /// NetBox object types the field applies to, e.g. `dcim.site`
+    pub object_types: Vec<String>,
+    /// Valid values of a select field; `None` when they aren't known
+    pub choices: Option<Vec<String>>,
+}
+
+impl CustomFieldDefinition {
+    pub fn applies_to(&self, object_type: &str) -> bool {
+        self.object_types.iter().any(|t| t == object_type)
+    }
+}
+
+/// The custom fields defined in one NetBox, keyed by name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CustomFieldSchema {
+    fields: HashMap<String, CustomFieldDefinition>,
+}
+
+impl CustomFieldSchema {
+    /// Build from NetBox's listings, resolving choice sets to their values
+    pub fn new(fields: Vec<NetBoxCustomField>, choice_sets: &[NetBoxCustomFieldChoiceSet]) -> Self {
+        let fields = fields
+            .into_iter()
+            .map(|field| {
+                let choices = match field.choice_set {
+                    Some(ref set) => choice_sets
+                        .iter()
+                        .find(|candidate| candidate.id == set.id)
+                        .filter(|candidate| candidate.base_choices.as_ref().is_none_or(|base| base.is_null()))
+                        .map(|candidate| candidate.extra_choices.iter().map(|(value, _)| value.clone()).collect()),
+                    None => field.choices,
+                };
+                let definition = CustomFieldDefinition {
+                    kind: CustomFieldKind::from_netbox(&field.field_type.value),
+                    required: field.required,
+                    object_types: field.object_types,
+                    choices,
+                    name: field.name,
+                };
+                (definition.name.clone(), definition)
+            })
+            .collect();
+        Self { fields }
+    }
+
+    /// Whether any field takes its choices from a choice set
+    pub fn needs_choice_sets(fields: &[NetBoxCustomField]) -> bool {
+        fields.iter().any(|field| field.choice_set.is_some())
+    }
+
+    pub fn field(&self, name: &str) -> Option<&CustomFieldDefinition> {
+        self.fields.get(name)
+    }
+
+    /// A field by name, if it applies to this object type
+    pub fn field_for(&self, object_type: &str, name: &str) -> Option<&CustomFieldDefinition> {
+        self.field(name).filter(|field| field.applies_to(object_type))
+    }
+
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_reads_legacy_and_choice_set_fields() {
+        let fields: Vec<NetBoxCustomField> = serde_json::from_value(serde_json::json!([
+            {"name": "tier", "type": {"value": "select"}, "content_types": ["dcim.site"], "choices": ["gold", "silver"]},
+            {"name": "env", "type": {"value": "select"}, "object_types": ["dcim.site"], "choice_set": {"id": 1}},
+            {"name": "airport", "type": {"value": "select"}, "object_types": ["dcim.site"], "choice_set": {"id": 2}},
+            {"name": "rack_units", "type": {"value": "integer"}, "object_types": ["dcim.device"], "required": true}
+        ]))
+        .unwrap();
+        let choice_sets: Vec<NetBoxCustomFieldChoiceSet> = serde_json::from_value(serde_json::json!([
+            {"id": 1, "name": "Environments", "base_choices": null, "extra_choices": [["prod", "Production"], ["lab", "Lab"]]},
+            {"id": 2, "name": "Airports", "base_choices": {"value": "IATA"}, "extra_choices": []}
+        ]))
+        .unwrap();
+        assert!(CustomFieldSchema::needs_choice_sets(&fields));
+
+        let schema = CustomFieldSchema::new(fields, &choice_sets);
+
+        assert_eq!(schema.len(), 4);
+        assert_eq!(schema.field("tier").unwrap().choices, Some(vec!["gold".to_string(), "silver".to_string()]));
+        assert_eq!(schema.field("env").unwrap().choices, Some(vec!["prod".to_string(), "lab".to_string()]));
+        assert_eq!(schema.field("airport").unwrap().choices, None);
+        assert!(schema.field("rack_units").unwrap().required);
+        assert!(schema.field_for(SITE_OBJECT_TYPE, "rack_units").is_none());
+        assert_eq!(schema.field_for(SITE_OBJECT_TYPE, "env").unwrap().kind, CustomFieldKind::Select);
+    }
+}
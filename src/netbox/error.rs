@@ -1,4 +1,9 @@
+use crate::netbox::models::NetBoxPrefix;
 use crate::resilience::retry::RetryableError;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -12,8 +17,13 @@ pub enum NetBoxError {
     #[error("Resource not found: {0}")]
     NotFound(String),
 
-    #[error("Validation error: {0}")]
-    ValidationError(String),
+    /// NetBox rejected the request; `errors` holds the field-level errors
+    /// when the body was NetBox's JSON error format
+    #[error("Validation error: {message}")]
+    ValidationError {
+        message: String,
+        errors: Option<NetBoxValidationErrors>,
+    },
 
     #[error("Network error: {0}")]
     NetworkError(#[from] reqwest::Error),
@@ -26,8 +36,47 @@ pub enum NetBoxError {
 
     #[error("Unexpected response: {0}")]
     UnexpectedResponse(String),
+
+    #[error("Invalid NetBox client configuration: {0}")]
+    InvalidConfig(String),
+
+    #[error("NetBox response exceeds the {limit} byte limit")]
+    ResponseTooLarge { limit: usize },
+
+    /// Messages from the `errors` array of a GraphQL response, which NetBox
+    /// returns with HTTP 200
+    #[error("NetBox GraphQL error: {}", .0.join("; "))]
+    GraphQLError(Vec<String>),
+
+    /// An attempt, or the whole call with its retries, ran out of time
+    #[error("NetBox call timed out after {0:?}")]
+    Timeout(std::time::Duration),
+
+    /// A prefix has nothing left to allocate; all `capacity` of its addresses are in use
+    #[error("Prefix {prefix_id} has no free IP addresses ({capacity} in use)")]
+    Exhausted { prefix_id: i32, capacity: u64 },
+
+    /// A 502/503/504 whose body isn't JSON, such as the page a reverse proxy
+    /// serves while NetBox is being upgraded; `snippet` is the start of its text
+    #[error("NetBox unavailable (HTTP {status}), maintenance suspected: {snippet}")]
+    ServiceUnavailable { status: u16, snippet: String },
+
+    /// NetBox, or the proxy in front of it, answered 429
+    #[error("NetBox is rate limiting requests; retry after {retry_after:?}")]
+    RateLimited { retry_after: Duration },
 }
 
+/// Shortest wait before retrying a [`NetBoxError::ServiceUnavailable`]: maintenance
+/// outlasts the usual backoff
+pub const MAINTENANCE_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// How long to back off after a 429: the status code is all that reaches
+/// [`NetBoxError::from_status_code`], not a `Retry-After` header
+pub const RATE_LIMIT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Characters of an error page kept in [`NetBoxError::ServiceUnavailable`]
+const SNIPPET_CHARS: usize = 200;
+
 impl RetryableError for NetBoxError {
     fn is_retryable(&self) -> bool {
         match self {
@@ -42,28 +91,183 @@ impl RetryableError for NetBoxError {
             // Not found is not retryable
             NetBoxError::NotFound(_) => false,
             // Validation errors are not retryable (bad request)
-            NetBoxError::ValidationError(_) => false,
+            NetBoxError::ValidationError { .. } => false,
             // Serialization errors are not retryable
             NetBoxError::SerializationError(_) => false,
             // Invalid URL is not retryable
             NetBoxError::InvalidUrl(_) => false,
             // Unexpected response might be retryable
             NetBoxError::UnexpectedResponse(_) => true,
+            // Configuration doesn't fix itself between attempts
+            NetBoxError::InvalidConfig(_) => false,
+            // The same request would return the same oversized body
+            NetBoxError::ResponseTooLarge { .. } => false,
+            // A rejected query is rejected again
+            NetBoxError::GraphQLError(_) => false,
+            // A hung attempt may well succeed on a fresh connection
+            NetBoxError::Timeout(_) => true,
+            // Nothing frees up addresses between attempts
+            NetBoxError::Exhausted { .. } => false,
+            // Maintenance ends; see `min_retry_delay`
+            NetBoxError::ServiceUnavailable { .. } => true,
+            // Throttling passes; see `min_retry_delay`
+            NetBoxError::RateLimited { .. } => true,
+        }
+    }
+
+    fn min_retry_delay(&self) -> Option<Duration> {
+        match self {
+            NetBoxError::ServiceUnavailable { .. } => Some(MAINTENANCE_RETRY_DELAY),
+            NetBoxError::RateLimited { retry_after } => Some(*retry_after),
+            _ => None,
         }
     }
 }
 
 impl NetBoxError {
+    /// A prefix with no free addresses left
+    pub fn prefix_exhausted(prefix_id: i32, prefix: &NetBoxPrefix) -> Self {
+        NetBoxError::Exhausted {
+            prefix_id,
+            capacity: prefix.address_capacity(),
+        }
+    }
+
     pub fn from_status_code(status: u16, message: String) -> Self {
         match status {
             401 | 403 => NetBoxError::AuthenticationError(message),
             404 => NetBoxError::NotFound(message),
-            400 | 422 => NetBoxError::ValidationError(message),
+            400 | 422 => match NetBoxValidationErrors::parse(&message) {
+                Some(errors) => NetBoxError::ValidationError {
+                    message: errors.to_string(),
+                    errors: Some(errors),
+                },
+                None => NetBoxError::ValidationError { message, errors: None },
+            },
+            429 => NetBoxError::RateLimited {
+                retry_after: RATE_LIMIT_RETRY_DELAY,
+            },
+            // NetBox's own errors are JSON; anything else came from a proxy in front of it
+            502..=504 if is_error_page(&message) => NetBoxError::ServiceUnavailable {
+                status,
+                snippet: body_snippet(&message),
+            },
             _ => NetBoxError::ApiError(format!("HTTP {}: {}", status, message)),
         }
     }
 }
 
+/// Whether a response body is an HTML page rather than NetBox's JSON, as
+/// served by the proxy in front of NetBox while it is down
+fn is_error_page(body: &str) -> bool {
+    body.trim_start().starts_with('<') && serde_json::from_str::<serde::de::IgnoredAny>(body).is_err()
+}
+
+/// The text of an error page: tags dropped, whitespace collapsed, cut at [`SNIPPET_CHARS`]
+fn body_snippet(body: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in body.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let text = words.join(" ");
+    match text.char_indices().nth(SNIPPET_CHARS) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text,
+    }
+}
+
+/// Key NetBox uses for errors not tied to a single field
+pub const NON_FIELD_ERRORS: &str = "non_field_errors";
+
+/// Field-level errors from a NetBox 400 response body, e.g.
+/// `{"name": ["This field is required."], "slug": ["site with this slug already exists."]}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetBoxValidationErrors(pub HashMap<String, Vec<String>>);
+
+/// Errors of one field, in the stable order used by API responses
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldErrors {
+    pub field: String,
+    pub messages: Vec<String>,
+}
+
+impl NetBoxValidationErrors {
+    /// Parse a NetBox error body, returning `None` if it isn't a JSON object of
+    /// fields to messages
+    ///
+    /// A field's messages may be a single string or a list; nested objects
+    /// (errors on related objects) are flattened into their JSON text.
+    pub fn parse(body: &str) -> Option<Self> {
+        let serde_json::Value::Object(fields) = serde_json::from_str(body).ok()? else {
+            return None;
+        };
+        if fields.is_empty() {
+            return None;
+        }
+        let errors = fields
+            .into_iter()
+            .map(|(field, value)| {
+                let messages = match value {
+                    serde_json::Value::Array(items) => items.into_iter().map(Self::message_text).collect(),
+                    other => vec![Self::message_text(other)],
+                };
+                (field, messages)
+            })
+            .collect();
+        Some(Self(errors))
+    }
+
+    fn message_text(value: serde_json::Value) -> String {
+        match value {
+            serde_json::Value::String(message) => message,
+            other => other.to_string(),
+        }
+    }
+
+    /// Errors per field, sorted by field with `non_field_errors` first
+    pub fn fields(&self) -> Vec<FieldErrors> {
+        let mut fields: Vec<FieldErrors> = self
+            .0
+            .iter()
+            .map(|(field, messages)| FieldErrors {
+                field: field.clone(),
+                messages: messages.clone(),
+            })
+            .collect();
+        fields.sort_by(|a, b| {
+            (a.field != NON_FIELD_ERRORS, &a.field).cmp(&(b.field != NON_FIELD_ERRORS, &b.field))
+        });
+        fields
+    }
+}
+
+impl fmt::Display for NetBoxValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields: Vec<String> = self
+            .fields()
+            .into_iter()
+            .map(|field| {
+                if field.field == NON_FIELD_ERRORS {
+                    field.messages.join(" ")
+                } else {
+                    format!("{}: {}", field.field, field.messages.join(" "))
+                }
+            })
+            .collect();
+        write!(f, "{}", fields.join("; "))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -99,7 +303,10 @@ mod tests {
     fn test_from_status_code_400() {
         let error = NetBoxError::from_status_code(400, "Bad request".to_string());
         match error {
-            NetBoxError::ValidationError(msg) => assert_eq!(msg, "Bad request"),
+            NetBoxError::ValidationError { message, errors } => {
+                assert_eq!(message, "Bad request");
+                assert!(errors.is_none());
+            }
             _ => panic!("Expected ValidationError"),
         }
     }
@@ -108,7 +315,7 @@ mod tests {
     fn test_from_status_code_422() {
         let error = NetBoxError::from_status_code(422, "Unprocessable".to_string());
         match error {
-            NetBoxError::ValidationError(msg) => assert_eq!(msg, "Unprocessable"),
+            NetBoxError::ValidationError { message, .. } => assert_eq!(message, "Unprocessable"),
             _ => panic!("Expected ValidationError"),
         }
     }
@@ -121,5 +328,74 @@ mod tests {
             _ => panic!("Expected ApiError"),
         }
     }
-}
 
+    #[test]
+    fn test_from_status_code_400_parses_field_errors() {
+        let body = r#"{"slug": ["site with this slug already exists."], "name": ["This field is required.", "Too short."]}"#;
+        let error = NetBoxError::from_status_code(400, body.to_string());
+
+        let NetBoxError::ValidationError { message, errors: Some(errors) } = error else {
+            panic!("Expected parsed ValidationError");
+        };
+        assert_eq!(
+            errors.fields(),
+            vec![
+                FieldErrors {
+                    field: "name".to_string(),
+                    messages: vec!["This field is required.".to_string(), "Too short.".to_string()],
+                },
+                FieldErrors {
+                    field: "slug".to_string(),
+                    messages: vec!["site with this slug already exists.".to_string()],
+                },
+            ]
+        );
+        assert_eq!(message, "name: This field is required. Too short.; slug: site with this slug already exists.");
+    }
+
+    #[test]
+    fn test_non_field_errors_are_listed_first() {
+        let body = r#"{"status": "Invalid choice.", "non_field_errors": ["The fields name, site must make a unique set."]}"#;
+        let errors = NetBoxValidationErrors::parse(body).unwrap();
+
+        let fields = errors.fields();
+        assert_eq!(fields[0].field, NON_FIELD_ERRORS);
+        assert_eq!(fields[1].messages, vec!["Invalid choice.".to_string()]);
+        assert_eq!(
+            errors.to_string(),
+            "The fields name, site must make a unique set.; status: Invalid choice."
+        );
+    }
+
+    #[test]
+    fn test_html_gateway_errors_suggest_maintenance() {
+        let page = format!(
+            "<html><head><title>503 Service Unavailable</title></head>\n<body><h1>NetBox is down for maintenance</h1>\n<p>{}</p></body></html>",
+            "Back soon. ".repeat(40)
+        );
+        let error = NetBoxError::from_status_code(503, page);
+        let NetBoxError::ServiceUnavailable { status, ref snippet } = error else {
+            panic!("Expected ServiceUnavailable, got {:?}", error);
+        };
+        assert_eq!(status, 503);
+        assert!(snippet.starts_with("503 Service Unavailable NetBox is down for maintenance Back soon."));
+        assert!(snippet.ends_with("..."));
+        assert_eq!(snippet.chars().count(), SNIPPET_CHARS + 3);
+        assert!(error.is_retryable());
+        assert_eq!(error.min_retry_delay(), Some(MAINTENANCE_RETRY_DELAY));
+
+        // JSON, plain-text and empty bodies, and 500s, are NetBox's own errors
+        assert!(matches!(NetBoxError::from_status_code(503, "busy".to_string()), NetBoxError::ApiError(_)));
+        let json = NetBoxError::from_status_code(503, r#"{"detail": "busy"}"#.to_string());
+        assert!(matches!(json, NetBoxError::ApiError(_)));
+        assert!(matches!(NetBoxError::from_status_code(502, String::new()), NetBoxError::ApiError(_)));
+        assert!(matches!(NetBoxError::from_status_code(500, "<html></html>".to_string()), NetBoxError::ApiError(_)));
+    }
+
+    #[test]
+    fn test_non_json_error_bodies_are_not_parsed() {
+        assert!(NetBoxValidationErrors::parse("<html>Bad Request</html>").is_none());
+        assert!(NetBoxValidationErrors::parse(r#"["not", "an", "object"]"#).is_none());
+        assert!(NetBoxValidationErrors::parse("{}").is_none());
+    }
+}
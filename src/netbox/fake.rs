@@ -0,0 +1,607 @@
+use crate::config::Config;
+use crate::netbox::client::NetBoxClient;
+use crate::netbox::resilient_client::ResilientNetBoxClient;
+use chrono::{SecondsFormat, Utc};
+use parking_lot::Mutex;
+use poem::endpoint::make;
+use poem::http::{Method, StatusCode};
+use poem::listener::{Acceptor, Listener, TcpListener};
+use poem::{Request, Response};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// Token the clients of a fake NetBox are configured with
+pub const FAKE_TOKEN: &str = "fake-token";
+
+/// NetBox version reported by `/api/status/` unless set with `with_version`
+pub const FAKE_NETBOX_VERSION: &str = "4.1.0";
+
+/// User changelog entries are recorded for
+const FAKE_USER: &str = "netgate";
+
+/// NetBox's default page size
+const DEFAULT_PAGE_SIZE: usize = 50;
+/// NetBox's default maximum page size
+const MAX_PAGE_SIZE: usize = 1000;
+
+/// Collections the fake serves, with the object type changelog entries name them by
+const COLLECTIONS: &[(&str, &str)] = &[
+    ("dcim/sites", "dcim.site"),
+    ("dcim/devices", "dcim.device"),
+    ("ipam/prefixes", "ipam.prefix"),
+    ("tenancy/tenants", "tenancy.tenant"),
+    ("extras/tags", "extras.tag"),
+    ("extras/custom-fields", "extras.customfield"),
+    ("extras/custom-field-choice-sets", "extras.customfieldchoiceset"),
+    ("extras/object-changes", "core.objectchange"),
+];
+
+/// Changelog collection; written by the fake itself, never by clients
+const OBJECT_CHANGES: &str = "extras/object-changes";
+
+/// Fields that must be unique within a collection, as NetBox enforces them
+fn unique_fields(collection: &str) -> &'static [&'static str] {
+    match collection {
+        "dcim/sites" | "tenancy/tenants" | "extras/tags" => &["name", "slug"],
+        "extras/custom-fields" => &["name"],
+        _ => &[],
+    }
+}
+
+/// A request the fake received
+#[derive(Debug, Clone, PartialEq)]
+pub struct FakeRequest {
+    pub method: Method,
+    /// e.g. `/api/dcim/sites/5/`
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    pub body: Option<Value>,
+    /// Token from the `Authorization` header
+    pub token: Option<String>,
+}
+
+#[derive(Default)]
+struct FakeState {
+    base_url: String,
+    version: String,
+    objects: HashMap<String, BTreeMap<i32, Value>>,
+    next_ids: HashMap<String, i32>,
+    requests: Vec<FakeRequest>,
+    failing_creates: usize,
+    failing_reads: usize,
+    read_delay: Duration,
+    hanging_writes: bool,
+}
+
+/// An in-memory NetBox for tests
+///
+/// Serves the REST endpoints NetGate uses over HTTP on a local port, so the
+/// real client stack runs against it: sites, devices, prefixes, tenants,
+/// tags, custom fields, the changelog and `/api/status/`. Objects are stored
+/// as JSON in the format NetGate's models read; lists take NetBox's filters and pages. Names
+/// and slugs are unique like in NetBox, and every write is recorded in the
+/// changelog. Data can be seeded, failures, slow reads and hanging writes
+/// injected, and the received requests inspected. The server stops when the
+/// fake is dropped.
+pub struct FakeNetBox {
+    state: Arc<Mutex<FakeState>>,
+    uri: String,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl FakeNetBox {
+    /// Start an empty fake NetBox on a free local port
+    pub async fn start() -> Self {
+        let acceptor = TcpListener::bind("127.0.0.1:0")
+            .into_acceptor()
+            .await
+            .expect("bind a local port for the fake NetBox");
+        let addr = *acceptor.local_addr()[0]
+            .as_socket_addr()
+            .expect("the fake NetBox listens on TCP");
+        let uri = format!("http://{}", addr);
+        let state = Arc::new(Mutex::new(FakeState {
+            base_url: uri.clone(),
+            version: FAKE_NETBOX_VERSION.to_string(),
+            ..Default::default()
+        }));
+
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        let handler_state = state.clone();
+        let app = make(move |req: Request| {
+            let state = handler_state.clone();
+            async move { handle(state, req).await }
+        });
+        tokio::spawn(async move {
+            let _ = poem::Server::new_with_acceptor(acceptor)
+                .run_with_graceful_shutdown(app, async { stopped.await.unwrap_or_default() }, None)
+                .await;
+        });
+
+        Self {
+            state,
+            uri,
+            shutdown: Some(shutdown),
+        }
+    }
+
+    /// Report another NetBox version from `/api/status/`
+    pub fn with_version(self, version: &str) -> Self {
+        self.state.lock().version = version.to_string();
+        self
+    }
+
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Config pointing at the fake
+    pub fn config(&self) -> Config {
+        Config {
+            netbox_url: self.uri.clone(),
+            netbox_token: FAKE_TOKEN.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn client(&self) -> Arc<NetBoxClient> {
+        Arc::new(NetBoxClient::new(self.config()).expect("client for the fake NetBox"))
+    }
+
+    /// Resilient client with default settings
+    pub fn resilient_client(&self) -> Arc<ResilientNetBoxClient> {
+        Arc::new(ResilientNetBoxClient::new(self.client()))
+    }
+
+    /// Add an object as if it existed before, returning its id
+    ///
+    /// `collection` is the API path without `/api/`, e.g. `dcim/sites`. The
+    /// object keeps its `id` if it has one. Nothing is recorded in the changelog.
+    pub fn seed(&self, collection: &str, object: impl Serialize) -> i32 {
+        let object = serde_json::to_value(object).expect("seeded objects serialize to JSON");
+        self.state.lock().insert(collection, object).0
+    }
+
+    pub fn seed_site(&self, site: impl Serialize) -> i32 {
+        self.seed("dcim/sites", site)
+    }
+
+    pub fn seed_device(&self, device: impl Serialize) -> i32 {
+        self.seed("dcim/devices", device)
+    }
+
+    /// Seed a tag whose slug is its name
+    pub fn seed_tag(&self, name: &str) -> i32 {
+        self.seed("extras/tags", json!({"name": name, "slug": name}))
+    }
+
+    pub fn object(&self, collection: &str, id: i32) -> Option<Value> {
+        self.state.lock().objects.get(collection)?.get(&id).cloned()
+    }
+
+    /// A collection's objects in id order
+    pub fn objects(&self, collection: &str) -> Vec<Value> {
+        self.state
+            .lock()
+            .objects
+            .get(collection)
+            .map(|objects| objects.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn sites(&self) -> Vec<Value> {
+        self.objects("dcim/sites")
+    }
+
+    pub fn devices(&self) -> Vec<Value> {
+        self.objects("dcim/devices")
+    }
+
+    /// Every request received so far, oldest first
+    pub fn requests(&self) -> Vec<FakeRequest> {
+        self.state.lock().requests.clone()
+    }
+
+    /// Requests with this method and path
+    pub fn requests_to(&self, method: Method, path: &str) -> Vec<FakeRequest> {
+        self.requests()
+            .into_iter()
+            .filter(|request| request.method == method && request.path == path)
+            .collect()
+    }
+
+    /// Answer the next `count` POSTs with a 500, creating nothing
+    pub fn fail_next_creates(&self, count: usize) {
+        self.state.lock().failing_creates = count;
+    }
+
+    /// Answer the next `count` GETs with a 503
+    pub fn fail_next_reads(&self, count: usize) {
+        self.state.lock().failing_reads = count;
+    }
+
+    /// Hold every GET for `delay` before answering
+    pub fn delay_reads(&self, delay: Duration) {
+        self.state.lock().read_delay = delay;
+    }
+
+    /// Never answer creates, updates or deletes, like a black-holed connection
+    pub fn hang_writes(&self) {
+        self.state.lock().hanging_writes = true;
+    }
+}
+
+impl Drop for FakeNetBox {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+impl FakeState {
+    /// Store an object, giving it the next id unless it has one
+    fn insert(&mut self, collection: &str, mut object: Value) -> (i32, Value) {
+        let next_id = self.next_ids.entry(collection.to_string()).or_insert(1);
+        let id = match object.get("id").and_then(Value::as_i64) {
+            Some(id) => id as i32,
+            None => *next_id,
+        };
+        *next_id = (*next_id).max(id + 1);
+        object["id"] = json!(id);
+        self.objects
+            .entry(collection.to_string())
+            .or_default()
+            .insert(id, object.clone());
+        (id, object)
+    }
+
+    /// The field of another object in the collection that `object` would duplicate
+    fn duplicate_field(&self, collection: &str, object: &Value, own_id: Option<i32>) -> Option<&'static str> {
+        let existing = self.objects.get(collection)?;
+        unique_fields(collection).iter().copied().find(|field| {
+            let Some(value) = object.get(*field).filter(|value| !value.is_null()) else {
+                return false;
+            };
+            existing
+                .iter()
+                .any(|(id, other)| Some(*id) != own_id && other.get(*field) == Some(value))
+        })
+    }
+
+    fn record_change(&mut self, collection: &str, id: i32, action: &str, before: Option<Value>, after: Option<Value>) {
+        let Some((_, object_type)) = COLLECTIONS.iter().find(|(name, _)| *name == collection) else {
+            return;
+        };
+        let label = match action {
+            "create" => "Created",
+            "update" => "Updated",
+            _ => "Deleted",
+        };
+        let change = json!({
+            "time": now(),
+            "user_name": FAKE_USER,
+            "request_id": uuid::Uuid::new_v4().to_string(),
+            "action": {"value": action, "label": label},
+            "changed_object_type": object_type,
+            "changed_object_id": id,
+            "prechange_data": before,
+            "postchange_data": after,
+        });
+        self.insert(OBJECT_CHANGES, change);
+    }
+
+    fn list(&self, collection: &str, path: &str, query: &[(String, String)]) -> Value {
+        let mut filters: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        let mut limit = DEFAULT_PAGE_SIZE;
+        let mut offset = 0;
+        for (key, value) in query {
+            match key.as_str() {
+                "limit" => limit = value.parse().unwrap_or(DEFAULT_PAGE_SIZE),
+                "offset" => offset = value.parse().unwrap_or(0),
+                "brief" | "ordering" => {}
+                key => filters.entry(key).or_default().push(value),
+            }
+        }
+        if limit == 0 || limit > MAX_PAGE_SIZE {
+            limit = MAX_PAGE_SIZE;
+        }
+
+        let mut matches: Vec<&Value> = self
+            .objects
+            .get(collection)
+            .map(|objects| objects.values().collect())
+            .unwrap_or_default();
+        // The changelog lists newest first, like NetBox
+        if collection == OBJECT_CHANGES {
+            matches.reverse();
+        }
+        matches.retain(|object| {
+            filters
+                .iter()
+                .all(|(key, values)| values.iter().any(|value| matches_filter(object, key, value)))
+        });
+
+        let count = matches.len();
+        let results: Vec<Value> = matches.into_iter().skip(offset).take(limit).cloned().collect();
+        let page_url = |offset: usize| {
+            let mut params: Vec<(String, String)> = query
+                .iter()
+                .filter(|(key, _)| key != "limit" && key != "offset")
+                .cloned()
+                .collect();
+            params.push(("limit".to_string(), limit.to_string()));
+            params.push(("offset".to_string(), offset.to_string()));
+            reqwest::Url::parse_with_params(&format!("{}{}", self.base_url, path), params)
+                .map(String::from)
+                .unwrap_or_default()
+        };
+        json!({
+            "count": count,
+            "next": (offset + limit < count).then(|| page_url(offset + limit)),
+            "previous": (offset > 0).then(|| page_url(offset.saturating_sub(limit))),
+            "results": results,
+        })
+    }
+}
+
+/// Whether `object` passes one NetBox filter value
+///
+/// Follows NetBox's filters loosely: `id__in` takes a comma-separated list,
+/// `q` searches names, `tag` a tag, `name` ignores case, and `<field>_id`
+/// matches the related object's id.
+fn matches_filter(object: &Value, key: &str, value: &str) -> bool {
+    match key {
+        "id__in" => value.split(',').any(|id| field_matches(object.get("id"), id.trim())),
+        "q" => ["name", "slug", "description"].iter().any(|field| {
+            object
+                .get(*field)
+                .and_then(Value::as_str)
+                .is_some_and(|text| text.to_lowercase().contains(&value.to_lowercase()))
+        }),
+        "tag" => field_matches(object.get("tags"), value),
+        "name" => object
+            .get("name")
+            .and_then(Value::as_str)
+            .is_some_and(|name| name.eq_ignore_ascii_case(value)),
+        key if object.get(key).is_some() => field_matches(object.get(key), value),
+        "role_id" => field_matches(object.get("role").or_else(|| object.get("device_role")), value),
+        key => match key.strip_suffix("_id") {
+            Some(field) => field_matches(object.get(field), value),
+            None => false,
+        },
+    }
+}
+
+/// Whether a stored field equals a query value, looking into choice values, nested ids and lists
+fn field_matches(field: Option<&Value>, value: &str) -> bool {
+    match field {
+        Some(Value::String(text)) => text == value,
+        Some(Value::Number(number)) => number.to_string() == value,
+        Some(Value::Bool(flag)) => flag.to_string() == value,
+        Some(Value::Array(items)) => items.iter().any(|item| field_matches(Some(item), value)),
+        Some(Value::Object(nested)) => {
+            ["value", "id", "slug", "name"].iter().any(|key| field_matches(nested.get(*key), value))
+        }
+        _ => false,
+    }
+}
+
+/// Store a written object in the format NetGate's models read
+///
+/// Clients send tags as objects and `role` to NetBox 4; the models hold tag
+/// names and `device_role`, as NetGate's requests are modelled.
+fn to_model_format(object: &mut serde_json::Map<String, Value>) {
+    if let Some(Value::Array(tags)) = object.get_mut("tags") {
+        for tag in tags.iter_mut() {
+            if let Some(name) = tag.get("name").or_else(|| tag.get("slug")).cloned() {
+                *tag = name;
+            }
+        }
+    }
+    if let Some(role) = object.remove("role") {
+        object.entry("device_role").or_insert(role);
+    }
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+fn json_response(status: StatusCode, body: Value) -> Response {
+    Response::builder()
+        .status(status)
+        .content_type("application/json")
+        .body(body.to_string())
+}
+
+fn not_found() -> Response {
+    json_response(StatusCode::NOT_FOUND, json!({"detail": "Not found."}))
+}
+
+/// Split `/api/<app>/<model>/[<id>/]` into the collection and id
+fn route(path: &str) -> Option<(&'static str, Option<i32>)> {
+    let rest = path.strip_prefix("/api/")?.trim_end_matches('/');
+    let (collection, id) = match rest.rsplit_once('/') {
+        Some((collection, id)) if id.chars().all(|c| c.is_ascii_digit()) => (collection, Some(id.parse().ok()?)),
+        _ => (rest, None),
+    };
+    let (name, _) = COLLECTIONS.iter().find(|(name, _)| *name == collection)?;
+    Some((name, id))
+}
+
+async fn handle(state: Arc<Mutex<FakeState>>, req: Request) -> Response {
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    let query: Vec<(String, String)> = req.params().unwrap_or_default();
+    let token = req
+        .header("Authorization")
+        .and_then(|value| value.strip_prefix("Token "))
+        .map(str::to_string);
+    let body = req.into_body().into_bytes().await.unwrap_or_default();
+    let body: Option<Value> = serde_json::from_slice(&body).ok();
+
+    let (read_delay, hang) = {
+        let mut state = state.lock();
+        state.requests.push(FakeRequest {
+            method: method.clone(),
+            path: path.clone(),
+            query: query.clone(),
+            body: body.clone(),
+            token,
+        });
+        if method == Method::GET && state.failing_reads > 0 {
+            state.failing_reads -= 1;
+            return json_response(StatusCode::SERVICE_UNAVAILABLE, json!({"detail": "Injected read failure"}));
+        }
+        if method == Method::POST && state.failing_creates > 0 {
+            state.failing_creates -= 1;
+            return json_response(StatusCode::INTERNAL_SERVER_ERROR, json!({"detail": "Injected create failure"}));
+        }
+        (state.read_delay, state.hanging_writes && method != Method::GET)
+    };
+    if hang {
+        std::future::pending::<()>().await;
+    }
+    if method == Method::GET && !read_delay.is_zero() {
+        tokio::time::sleep(read_delay).await;
+    }
+
+    let mut state = state.lock();
+    if path == "/api/status/" && method == Method::GET {
+        return json_response(StatusCode::OK, json!({"netbox-version": state.version}));
+    }
+    let Some((collection, id)) = route(&path) else {
+        return not_found();
+    };
+    let read_only = collection == OBJECT_CHANGES;
+
+    match (method, id) {
+        (Method::GET, None) => json_response(StatusCode::OK, state.list(collection, &path, &query)),
+        (Method::GET, Some(id)) => match state.objects.get(collection).and_then(|objects| objects.get(&id)) {
+            Some(object) => json_response(StatusCode::OK, object.clone()),
+            None => not_found(),
+        },
+        (Method::POST, None) if !read_only => {
+            let Some(Value::Object(mut fields)) = body else {
+                return json_response(StatusCode::BAD_REQUEST, json!({"detail": "Expected a JSON object"}));
+            };
+            to_model_format(&mut fields);
+            fields.remove("id");
+            let mut object = Value::Object(fields);
+            if let Some(field) = state.duplicate_field(collection, &object, None) {
+                return json_response(
+                    StatusCode::BAD_REQUEST,
+                    json!({ field: [format!("An object with this {} already exists.", field)] }),
+                );
+            }
+            let timestamp = now();
+            object["created"] = json!(timestamp);
+            object["last_updated"] = json!(timestamp);
+            let (id, object) = state.insert(collection, object);
+            state.record_change(collection, id, "create", None, Some(object.clone()));
+            json_response(StatusCode::CREATED, object)
+        }
+        (Method::PATCH | Method::PUT, Some(id)) if !read_only => {
+            let Some(before) = state.objects.get(collection).and_then(|objects| objects.get(&id)).cloned() else {
+                return not_found();
+            };
+            let Some(Value::Object(mut changes)) = body else {
+                return json_response(StatusCode::BAD_REQUEST, json!({"detail": "Expected a JSON object"}));
+            };
+            to_model_format(&mut changes);
+            let mut after = before.clone();
+            for (field, value) in changes {
+                after[field] = value;
+            }
+            after["id"] = json!(id);
+            if let Some(field) = state.duplicate_field(collection, &after, Some(id)) {
+                return json_response(
+                    StatusCode::BAD_REQUEST,
+                    json!({ field: [format!("An object with this {} already exists.", field)] }),
+                );
+            }
+            after["last_updated"] = json!(now());
+            state.insert(collection, after.clone());
+            state.record_change(collection, id, "update", Some(before), Some(after.clone()));
+            json_response(StatusCode::OK, after)
+        }
+        (Method::DELETE, Some(id)) if !read_only => {
+            match state.objects.get_mut(collection).and_then(|objects| objects.remove(&id)) {
+                Some(before) => {
+                    state.record_change(collection, id, "delete", Some(before), None);
+                    Response::builder().status(StatusCode::NO_CONTENT).finish()
+                }
+                None => not_found(),
+            }
+        }
+        _ => json_response(StatusCode::METHOD_NOT_ALLOWED, json!({"detail": "Method not allowed."})),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::netbox::models::{CreateSiteRequest, UpdateSiteRequestBuilder};
+
+    #[tokio::test]
+    async fn test_fake_serves_crud_filters_pages_and_changelog() {
+        let fake = FakeNetBox::start().await;
+        fake.seed_site(json!({"name": "Seeded", "slug": "seeded", "tenant": 20}));
+        let client = fake.client();
+
+        let site = client
+            .create_site(CreateSiteRequest::builder("Berlin").with_slug("berlin").with_tenant(10).build())
+            .await
+            .unwrap();
+        assert_eq!(site.id, Some(2));
+        assert!(site.last_updated.is_some());
+        let duplicate = client
+            .create_site(CreateSiteRequest::builder("Berlin").with_slug("berlin-2").build())
+            .await;
+        assert!(duplicate.is_err());
+
+        let page = client.list_sites(Some(10), None, None, None).await.unwrap();
+        assert_eq!(page.count, Some(1));
+        assert_eq!(page.results.unwrap()[0].name, "Berlin");
+        let page = client.list_sites(None, None, Some(1), None).await.unwrap();
+        assert_eq!(page.count, Some(2));
+        assert!(page.next.is_some());
+
+        client
+            .update_site(2, UpdateSiteRequestBuilder::new().with_description("Hall B").build())
+            .await
+            .unwrap();
+        client.delete_site(2).await.unwrap();
+        assert!(client.get_site(2).await.is_err());
+
+        let changes = client.get_object_changes("dcim.site", 2, None, None).await.unwrap();
+        let actions: Vec<_> = changes.results.unwrap().into_iter().map(|change| change.action.value).collect();
+        assert_eq!(actions, vec!["delete", "update", "create"]);
+    }
+
+    #[tokio::test]
+    async fn test_fake_injects_failures_and_delays() {
+        let fake = FakeNetBox::start().await;
+        let client = fake.client();
+
+        fake.fail_next_creates(1);
+        assert!(client.create_site(CreateSiteRequest::builder("Berlin").build()).await.is_err());
+        assert!(client.create_site(CreateSiteRequest::builder("Berlin").build()).await.is_ok());
+
+        fake.fail_next_reads(1);
+        assert!(client.get_site(1).await.is_err());
+        fake.delay_reads(Duration::from_millis(50));
+        let started = std::time::Instant::now();
+        assert!(client.get_site(1).await.is_ok());
+        assert!(started.elapsed() >= Duration::from_millis(50));
+
+        assert_eq!(fake.requests_to(Method::POST, "/api/dcim/sites/").len(), 2);
+        assert_eq!(fake.sites().len(), 1);
+        assert!(fake.requests().iter().all(|request| request.token.as_deref() == Some(FAKE_TOKEN)));
+    }
+}
@@ -0,0 +1,269 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::json;
+
+use crate::netbox::client::NetBoxClient;
+use crate::netbox::error::NetBoxError;
+
+const SITES_WITH_DEVICE_COUNTS: &str = r#"
+query SitesWithDeviceCounts($tenant_id: [String]) {
+  site_list(tenant_id: $tenant_id) {
+    id
+    name
+    slug
+    status
+    tenant { id }
+    devices { id }
+  }
+}"#;
+
+const DEVICES_WITH_INTERFACES: &str = r#"
+query DevicesWithInterfaces($site_id: [String], $tenant_id: [String]) {
+  device_list(site_id: $site_id, tenant_id: $tenant_id) {
+    id
+    name
+    status
+    tenant { id }
+    interfaces { id name type enabled mac_address }
+  }
+}"#;
+
+/// A site and how many devices it holds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SiteDeviceCount {
+    pub id: i32,
+    pub name: String,
+    pub slug: Option<String>,
+    pub status: Option<String>,
+    pub tenant_id: Option<i32>,
+    pub device_count: usize,
+}
+
+/// A device and its interfaces
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceInterfaces {
+    pub id: i32,
+    pub name: Option<String>,
+    pub status: Option<String>,
+    pub tenant_id: Option<i32>,
+    pub interfaces: Vec<InterfaceSummary>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InterfaceSummary {
+    pub id: i32,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub interface_type: Option<String>,
+    pub enabled: Option<bool>,
+    pub mac_address: Option<String>,
+}
+
+/// GraphQL IDs are strings; NetBox's are always numeric
+fn graphql_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i32, D::Error> {
+    let id = String::deserialize(deserializer)?;
+    id.parse().map_err(serde::de::Error::custom)
+}
+
+/// GraphQL enums come back upper case (`ACTIVE`); REST uses lower case
+fn graphql_enum<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.map(|value| value.to_lowercase()))
+}
+
+#[derive(Deserialize)]
+struct IdOnly {
+    #[serde(deserialize_with = "graphql_id")]
+    id: i32,
+}
+
+#[derive(Deserialize)]
+struct RawSite {
+    #[serde(deserialize_with = "graphql_id")]
+    id: i32,
+    name: String,
+    slug: Option<String>,
+    #[serde(default, deserialize_with = "graphql_enum")]
+    status: Option<String>,
+    tenant: Option<IdOnly>,
+    #[serde(default)]
+    devices: Vec<IdOnly>,
+}
+
+#[derive(Deserialize)]
+struct RawInterface {
+    #[serde(deserialize_with = "graphql_id")]
+    id: i32,
+    name: String,
+    #[serde(rename = "type", default, deserialize_with = "graphql_enum")]
+    interface_type: Option<String>,
+    enabled: Option<bool>,
+    mac_address: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawDevice {
+    #[serde(deserialize_with = "graphql_id")]
+    id: i32,
+    name: Option<String>,
+    #[serde(default, deserialize_with = "graphql_enum")]
+    status: Option<String>,
+    tenant: Option<IdOnly>,
+    #[serde(default)]
+    interfaces: Vec<RawInterface>,
+}
+
+/// GraphQL list filters take strings
+fn id_filter(id: Option<i32>) -> serde_json::Value {
+    match id {
+        Some(id) => json!([id.to_string()]),
+        None => serde_json::Value::Null,
+    }
+}
+
+fn field<T: serde::de::DeserializeOwned>(data: &mut serde_json::Value, name: &str) -> Result<T, NetBoxError> {
+    let value = data
+        .get_mut(name)
+        .map(serde_json::Value::take)
+        .ok_or_else(|| NetBoxError::UnexpectedResponse(format!("GraphQL response has no '{}'", name)))?;
+    serde_json::from_value(value).map_err(NetBoxError::SerializationError)
+}
+
+impl NetBoxClient {
+    /// Sites with the number of devices in each, optionally for one NetBox tenant
+    pub async fn sites_with_device_counts(&self, tenant_id: Option<i32>) -> Result<Vec<SiteDeviceCount>, NetBoxError> {
+        let mut data = self
+            .graphql(SITES_WITH_DEVICE_COUNTS, json!({ "tenant_id": id_filter(tenant_id) }))
+            .await?;
+        let sites: Vec<RawSite> = field(&mut data, "site_list")?;
+        Ok(sites
+            .into_iter()
+            .map(|site| SiteDeviceCount {
+                id: site.id,
+                name: site.name,
+                slug: site.slug,
+                status: site.status,
+                tenant_id: site.tenant.map(|t| t.id),
+                device_count: site.devices.len(),
+            })
+            .collect())
+    }
+
+    /// A site's devices with their interfaces, optionally for one NetBox tenant
+    pub async fn devices_with_interfaces(
+        &self,
+        site_id: i32,
+        tenant_id: Option<i32>,
+    ) -> Result<Vec<DeviceInterfaces>, NetBoxError> {
+        let variables = json!({ "site_id": id_filter(Some(site_id)), "tenant_id": id_filter(tenant_id) });
+        let mut data = self.graphql(DEVICES_WITH_INTERFACES, variables).await?;
+        let devices: Vec<RawDevice> = field(&mut data, "device_list")?;
+        Ok(devices
+            .into_iter()
+            .map(|device| DeviceInterfaces {
+                id: device.id,
+                name: device.name,
+                status: device.status,
+                tenant_id: device.tenant.map(|t| t.id),
+                interfaces: device
+                    .interfaces
+                    .into_iter()
+                    .map(|i| InterfaceSummary {
+                        id: i.id,
+                        name: i.name,
+                        interface_type: i.interface_type,
+                        enabled: i.enabled,
+                        mac_address: i.mac_address,
+                    })
+                    .collect(),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn client(mock_server: &MockServer) -> NetBoxClient {
+        NetBoxClient::new(Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sites_with_device_counts() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/graphql/"))
+            .and(header("Authorization", "Token test-token"))
+            .and(body_partial_json(json!({"variables": {"tenant_id": ["7"]}})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "data": {"site_list": [
+                    {"id": "1", "name": "DC1", "slug": "dc1", "status": "ACTIVE", "tenant": {"id": "7"},
+                     "devices": [{"id": "10"}, {"id": "11"}]},
+                    {"id": "2", "name": "DC2", "slug": "dc2", "status": "PLANNED", "tenant": {"id": "7"}, "devices": []}
+                ]}
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let sites = client(&mock_server).sites_with_device_counts(Some(7)).await.unwrap();
+        assert_eq!(sites.len(), 2);
+        assert_eq!(sites[0].device_count, 2);
+        assert_eq!(sites[0].status.as_deref(), Some("active"));
+        assert_eq!(sites[1].tenant_id, Some(7));
+        assert_eq!(sites[1].device_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_devices_with_interfaces() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/graphql/"))
+            .and(body_partial_json(json!({"variables": {"site_id": ["3"], "tenant_id": null}})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "data": {"device_list": [
+                    {"id": "10", "name": "sw1", "status": "ACTIVE", "tenant": null, "interfaces": [
+                        {"id": "100", "name": "eth0", "type": "A_1000BASE_T", "enabled": true, "mac_address": null}
+                    ]}
+                ]}
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let devices = client(&mock_server).devices_with_interfaces(3, None).await.unwrap();
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].interfaces[0].name, "eth0");
+        assert_eq!(devices[0].interfaces[0].interface_type.as_deref(), Some("a_1000base_t"));
+    }
+
+    #[tokio::test]
+    async fn test_graphql_errors_with_http_200() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/graphql/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "data": null,
+                "errors": [
+                    {"message": "Cannot query field 'devices' on type 'SiteType'."},
+                    {"message": "Unknown argument 'tenant_id'."}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        match client(&mock_server).sites_with_device_counts(None).await {
+            Err(NetBoxError::GraphQLError(messages)) => {
+                assert_eq!(messages.len(), 2);
+                assert!(messages[0].contains("Cannot query field"));
+            }
+            other => panic!("Expected GraphQLError, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+/// Logged request and response bodies are cut to this many bytes
+pub const MAX_LOGGED_BODY_BYTES: usize = 2048;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// JSON keys whose values are never logged
+const SECRET_KEYS: &[&str] = &["token", "key", "password", "secret", "authorization"];
+
+/// Make a request or response body safe to log
+///
+/// The configured token is removed wherever it appears, JSON values under
+/// secret-sounding keys are replaced, and so is anything shaped like a NetBox
+/// token (40 hex characters). The result is truncated to `MAX_LOGGED_BODY_BYTES`.
+pub fn redact_body(body: &[u8], token: &str) -> String {
+    let text = String::from_utf8_lossy(body);
+    let text = match serde_json::from_str::<serde_json::Value>(&text) {
+        Ok(mut json) => {
+            redact_json(&mut json);
+            json.to_string()
+        }
+        Err(_) => text.into_owned(),
+    };
+    let text = if token.is_empty() { text } else { text.replace(token, REDACTED) };
+    truncate(&redact_token_shaped(&text), MAX_LOGGED_BODY_BYTES)
+}
+
+/// A URL safe to log, with the token removed should it appear in the query string
+pub fn redact_url(url: &reqwest::Url, token: &str) -> String {
+    let url = url.as_str();
+    if token.is_empty() {
+        url.to_string()
+    } else {
+        url.replace(token, REDACTED)
+    }
+}
+
+fn redact_json(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_lowercase();
+                if SECRET_KEYS.iter().any(|secret| key.contains(secret)) && !value.is_null() {
+                    *value = serde_json::Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(value);
+                }
+            }
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+/// Replace runs of exactly 40 hex digits, the shape of a NetBox API token
+fn redact_token_shaped(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut run = String::new();
+    let flush = |run: &mut String, out: &mut String| {
+        if run.len() == 40 {
+            out.push_str(REDACTED);
+        } else {
+            out.push_str(run);
+        }
+        run.clear();
+    };
+    for c in text.chars() {
+        if c.is_ascii_hexdigit() {
+            run.push(c);
+        } else {
+            flush(&mut run, &mut out);
+            out.push(c);
+        }
+    }
+    flush(&mut run, &mut out);
+    out
+}
+
+fn truncate(text: &str, max_bytes: usize) -> String {
+    if text.len() <= max_bytes {
+        return text.to_string();
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}... ({} bytes total)", &text[..end], text.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_body() {
+        let body = br#"{"name":"Site","token":"abc","nested":[{"api_key":"xyz"}],"note":"uses hunter2"}"#;
+        let redacted = redact_body(body, "hunter2");
+        assert!(!redacted.contains("abc"));
+        assert!(!redacted.contains("xyz"));
+        assert!(!redacted.contains("hunter2"));
+        assert!(redacted.contains("\"name\":\"Site\""));
+    }
+
+    #[test]
+    fn test_redact_token_shaped_values() {
+        let token = "0123456789abcdef0123456789abcdef01234567";
+        let redacted = redact_body(format!("Token {} was rejected", token).as_bytes(), "");
+        assert_eq!(redacted, "Token [REDACTED] was rejected");
+        // Shorter hex runs, like IDs, are kept
+        assert_eq!(redact_body(b"site 1234abcd", ""), "site 1234abcd");
+    }
+
+    #[test]
+    fn test_truncate_on_char_boundary() {
+        let text = "é".repeat(MAX_LOGGED_BODY_BYTES);
+        let redacted = redact_body(text.as_bytes(), "");
+        assert!(redacted.len() < text.len());
+        assert!(redacted.ends_with(&format!("({} bytes total)", text.len())));
+    }
+}
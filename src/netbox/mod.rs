@@ -1,14 +1,33 @@
 pub mod cached_client;
 pub mod client;
+pub mod custom_fields;
 pub mod error;
+#[cfg(any(test, feature = "test-util"))]
+pub mod fake;
+pub mod graphql;
+pub mod logging;
 pub mod models;
+pub mod operations;
 pub mod resilient_client;
+pub mod routing;
 pub mod tenant_client;
+pub mod token_scope;
+pub mod transport;
+pub mod version;
 
 // Re-export commonly used types explicitly (public API)
 pub use client::NetBoxClient;
 pub use resilient_client::ResilientNetBoxClient;
+pub use routing::{NetBoxEndpoint, NetBoxRouter};
 pub use models::*;
-#[allow(unused_imports)] // Public API for external use
 pub use error::NetBoxError;
+pub use custom_fields::{CustomFieldDefinition, CustomFieldKind, CustomFieldSchema};
+pub use graphql::{DeviceInterfaces, InterfaceSummary, SiteDeviceCount};
+pub use operations::{
+    CapacityOperations, DeviceOperations, ListQuery, NetBoxOperations, SiteOperations, VirtualizationOperations,
+    VlanOperations,
+};
+pub use transport::{Http2Mode, PoolConfig, TransportConfig};
+pub use version::{NetBoxStatus, NetBoxVersion};
+pub use token_scope::TokenScope;
 
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 /// NetBox API response wrapper
@@ -7,6 +8,9 @@ pub struct NetBoxResponse<T> {
     pub next: Option<String>,
     pub previous: Option<String>,
     pub results: Option<Vec<T>>,
+    /// Set by NetGate when the response was served from fallback data instead of NetBox
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub degraded: bool,
 }
 
 /// NetBox Site model
@@ -31,7 +35,8 @@ pub struct NetBoxSite {
     pub tags: Option<Vec<String>>,
     pub custom_fields: Option<serde_json::Value>,
     pub created: Option<String>,
-    pub last_updated: Option<String>,
+    /// Compared against the caller's copy for optimistic concurrency on updates
+    pub last_updated: Option<DateTime<Utc>>,
 }
 
 impl Default for NetBoxSite {
@@ -62,7 +67,7 @@ impl Default for NetBoxSite {
 }
 
 /// NetBox Site Status
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum SiteStatus {
     Active,
@@ -71,6 +76,32 @@ pub enum SiteStatus {
     Staging,
 }
 
+impl SiteStatus {
+    /// NetBox's value for the status
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SiteStatus::Active => "active",
+            SiteStatus::Planned => "planned",
+            SiteStatus::Retired => "retired",
+            SiteStatus::Staging => "staging",
+        }
+    }
+}
+
+impl std::str::FromStr for SiteStatus {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "active" => Ok(SiteStatus::Active),
+            "planned" => Ok(SiteStatus::Planned),
+            "retired" => Ok(SiteStatus::Retired),
+            "staging" => Ok(SiteStatus::Staging),
+            other => Err(format!("Invalid site status '{}'; expected active, planned, retired or staging", other)),
+        }
+    }
+}
+
 /// NetBox Device model
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetBoxDevice {
@@ -134,7 +165,7 @@ impl Default for NetBoxDevice {
 }
 
 /// NetBox Device Face
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum DeviceFace {
     Front,
@@ -142,7 +173,7 @@ pub enum DeviceFace {
 }
 
 /// NetBox Device Status
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum DeviceStatus {
     Offline,
@@ -173,26 +204,44 @@ pub struct CreateSiteRequest {
     pub contact_email: Option<String>,
     pub comments: Option<String>,
     pub tags: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_fields: Option<serde_json::Value>,
 }
 
-/// Request payload for updating a site
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Request payload for updating a site; unset fields are left out of the PATCH
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UpdateSiteRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub slug: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub status: Option<SiteStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub region: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tenant: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub facility: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub physical_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub shipping_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub latitude: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub longitude: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub contact_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub contact_phone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub contact_email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub comments: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tags: Option<Vec<String>>,
 }
 
@@ -218,7 +267,7 @@ pub struct CreateDeviceRequest {
 }
 
 /// Request payload for updating a device
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UpdateDeviceRequest {
     pub name: Option<String>,
     pub device_type: Option<i32>,
@@ -238,3 +287,1167 @@ pub struct UpdateDeviceRequest {
     pub tags: Option<Vec<String>>,
 }
 
+/// Builder for [`CreateSiteRequest`]
+///
+/// ```
+/// use netgate::netbox::{CreateSiteRequestBuilder, SiteStatus};
+///
+/// let request = CreateSiteRequestBuilder::new("Berlin DC1")
+///     .with_slug("berlin-dc1")
+///     .with_status(SiteStatus::Planned)
+///     .with_tags(["netgate"])
+///     .build();
+///
+/// assert_eq!(request.name, "Berlin DC1");
+/// assert_eq!(request.slug.as_deref(), Some("berlin-dc1"));
+/// assert_eq!(request.region, None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CreateSiteRequestBuilder {
+    request: CreateSiteRequest,
+}
+
+impl CreateSiteRequestBuilder {
+    /// Start a request for a site with the given name
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            request: CreateSiteRequest {
+                name: name.into(),
+                slug: None,
+                description: None,
+                status: None,
+                region: None,
+                tenant: None,
+                facility: None,
+                physical_address: None,
+                shipping_address: None,
+                latitude: None,
+                longitude: None,
+                contact_name: None,
+                contact_phone: None,
+                contact_email: None,
+                comments: None,
+                tags: None,
+                custom_fields: None,
+            },
+        }
+    }
+
+    /// Set the URL slug
+    pub fn with_slug(mut self, slug: impl Into<String>) -> Self {
+        self.request.slug = Some(slug.into());
+        self
+    }
+
+    /// Set the description
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.request.description = Some(description.into());
+        self
+    }
+
+    /// Set the site status
+    pub fn with_status(mut self, status: SiteStatus) -> Self {
+        self.request.status = Some(status);
+        self
+    }
+
+    /// Set the region ID
+    pub fn with_region(mut self, region: i32) -> Self {
+        self.request.region = Some(region);
+        self
+    }
+
+    /// Set the tenant ID
+    pub fn with_tenant(mut self, tenant: i32) -> Self {
+        self.request.tenant = Some(tenant);
+        self
+    }
+
+    /// Set the facility code
+    pub fn with_facility(mut self, facility: impl Into<String>) -> Self {
+        self.request.facility = Some(facility.into());
+        self
+    }
+
+    /// Set the physical address
+    pub fn with_physical_address(mut self, physical_address: impl Into<String>) -> Self {
+        self.request.physical_address = Some(physical_address.into());
+        self
+    }
+
+    /// Set the shipping address
+    pub fn with_shipping_address(mut self, shipping_address: impl Into<String>) -> Self {
+        self.request.shipping_address = Some(shipping_address.into());
+        self
+    }
+
+    /// Set the latitude
+    pub fn with_latitude(mut self, latitude: f64) -> Self {
+        self.request.latitude = Some(latitude);
+        self
+    }
+
+    /// Set the longitude
+    pub fn with_longitude(mut self, longitude: f64) -> Self {
+        self.request.longitude = Some(longitude);
+        self
+    }
+
+    /// Set the contact name
+    pub fn with_contact_name(mut self, contact_name: impl Into<String>) -> Self {
+        self.request.contact_name = Some(contact_name.into());
+        self
+    }
+
+    /// Set the contact phone number
+    pub fn with_contact_phone(mut self, contact_phone: impl Into<String>) -> Self {
+        self.request.contact_phone = Some(contact_phone.into());
+        self
+    }
+
+    /// Set the contact email
+    pub fn with_contact_email(mut self, contact_email: impl Into<String>) -> Self {
+        self.request.contact_email = Some(contact_email.into());
+        self
+    }
+
+    /// Set the comments
+    pub fn with_comments(mut self, comments: impl Into<String>) -> Self {
+        self.request.comments = Some(comments.into());
+        self
+    }
+
+    /// Set the tag names
+    pub fn with_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.request.tags = Some(tags.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Set the custom field values
+    pub fn with_custom_fields(mut self, custom_fields: serde_json::Value) -> Self {
+        self.request.custom_fields = Some(custom_fields);
+        self
+    }
+
+    /// Build the request
+    pub fn build(self) -> CreateSiteRequest {
+        self.request
+    }
+}
+
+impl CreateSiteRequest {
+    /// Start building a request for a site with the given name
+    pub fn builder(name: impl Into<String>) -> CreateSiteRequestBuilder {
+        CreateSiteRequestBuilder::new(name)
+    }
+}
+
+/// Builder for [`UpdateSiteRequest`]; only the fields that are set are changed
+///
+/// ```
+/// use netgate::netbox::UpdateSiteRequestBuilder;
+///
+/// let request = UpdateSiteRequestBuilder::new()
+///     .with_description("Moved to hall B")
+///     .build();
+///
+/// assert_eq!(request.description.as_deref(), Some("Moved to hall B"));
+/// assert_eq!(request.name, None);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct UpdateSiteRequestBuilder {
+    request: UpdateSiteRequest,
+}
+
+impl UpdateSiteRequestBuilder {
+    /// Start an update that changes nothing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the site name
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.request.name = Some(name.into());
+        self
+    }
+
+    /// Set the URL slug
+    pub fn with_slug(mut self, slug: impl Into<String>) -> Self {
+        self.request.slug = Some(slug.into());
+        self
+    }
+
+    /// Set the description
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.request.description = Some(description.into());
+        self
+    }
+
+    /// Set the site status
+    pub fn with_status(mut self, status: SiteStatus) -> Self {
+        self.request.status = Some(status);
+        self
+    }
+
+    /// Set the region ID
+    pub fn with_region(mut self, region: i32) -> Self {
+        self.request.region = Some(region);
+        self
+    }
+
+    /// Set the tenant ID
+    pub fn with_tenant(mut self, tenant: i32) -> Self {
+        self.request.tenant = Some(tenant);
+        self
+    }
+
+    /// Set the facility code
+    pub fn with_facility(mut self, facility: impl Into<String>) -> Self {
+        self.request.facility = Some(facility.into());
+        self
+    }
+
+    /// Set the physical address
+    pub fn with_physical_address(mut self, physical_address: impl Into<String>) -> Self {
+        self.request.physical_address = Some(physical_address.into());
+        self
+    }
+
+    /// Set the shipping address
+    pub fn with_shipping_address(mut self, shipping_address: impl Into<String>) -> Self {
+        self.request.shipping_address = Some(shipping_address.into());
+        self
+    }
+
+    /// Set the latitude
+    pub fn with_latitude(mut self, latitude: f64) -> Self {
+        self.request.latitude = Some(latitude);
+        self
+    }
+
+    /// Set the longitude
+    pub fn with_longitude(mut self, longitude: f64) -> Self {
+        self.request.longitude = Some(longitude);
+        self
+    }
+
+    /// Set the contact name
+    pub fn with_contact_name(mut self, contact_name: impl Into<String>) -> Self {
+        self.request.contact_name = Some(contact_name.into());
+        self
+    }
+
+    /// Set the contact phone number
+    pub fn with_contact_phone(mut self, contact_phone: impl Into<String>) -> Self {
+        self.request.contact_phone = Some(contact_phone.into());
+        self
+    }
+
+    /// Set the contact email
+    pub fn with_contact_email(mut self, contact_email: impl Into<String>) -> Self {
+        self.request.contact_email = Some(contact_email.into());
+        self
+    }
+
+    /// Set the comments
+    pub fn with_comments(mut self, comments: impl Into<String>) -> Self {
+        self.request.comments = Some(comments.into());
+        self
+    }
+
+    /// Set the tag names
+    pub fn with_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.request.tags = Some(tags.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Build the request
+    pub fn build(self) -> UpdateSiteRequest {
+        self.request
+    }
+}
+
+impl UpdateSiteRequest {
+    /// Start building an update
+    pub fn builder() -> UpdateSiteRequestBuilder {
+        UpdateSiteRequestBuilder::new()
+    }
+
+    /// Whether the update changes nothing
+    pub fn is_empty(&self) -> bool {
+        serde_json::to_value(self).is_ok_and(|value| value.as_object().is_some_and(|fields| fields.is_empty()))
+    }
+}
+
+/// `desired` unless it's what the object already has
+fn changed<T: PartialEq>(current: Option<&T>, desired: Option<T>) -> Option<T> {
+    desired.filter(|desired| current != Some(desired))
+}
+
+/// Like [`changed`] for text NetBox clears to a blank string: blank and null are the same value
+fn changed_text(current: Option<&str>, desired: Option<String>) -> Option<String> {
+    desired.filter(|desired| current.unwrap_or_default() != desired)
+}
+
+/// Like [`changed`] for tags, whose order NetBox doesn't keep
+fn changed_tags(current: Option<&Vec<String>>, desired: Option<Vec<String>>) -> Option<Vec<String>> {
+    let sorted = |tags: &[String]| {
+        let mut tags = tags.to_vec();
+        tags.sort();
+        tags.dedup();
+        tags
+    };
+    desired.filter(|desired| sorted(desired) != sorted(current.map(Vec::as_slice).unwrap_or_default()))
+}
+
+/// The part of `desired` that changes `current`
+///
+/// Fields already at the desired value are dropped so the PATCH, and
+/// NetBox's changelog, only carry real changes. An unset field stays unset;
+/// a text field set to a blank string clears it, which is no change when
+/// it's already blank or null. Tags compare regardless of order.
+pub fn diff_site(current: &NetBoxSite, desired: &UpdateSiteRequest) -> UpdateSiteRequest {
+    let desired = desired.clone();
+    UpdateSiteRequest {
+        name: changed_text(Some(&current.name), desired.name),
+        slug: changed_text(current.slug.as_deref(), desired.slug),
+        description: changed_text(current.description.as_deref(), desired.description),
+        status: changed(current.status.as_ref(), desired.status),
+        region: changed(current.region.as_ref(), desired.region),
+        tenant: changed(current.tenant.as_ref(), desired.tenant),
+        facility: changed_text(current.facility.as_deref(), desired.facility),
+        physical_address: changed_text(current.physical_address.as_deref(), desired.physical_address),
+        shipping_address: changed_text(current.shipping_address.as_deref(), desired.shipping_address),
+        latitude: changed(current.latitude.as_ref(), desired.latitude),
+        longitude: changed(current.longitude.as_ref(), desired.longitude),
+        contact_name: changed_text(current.contact_name.as_deref(), desired.contact_name),
+        contact_phone: changed_text(current.contact_phone.as_deref(), desired.contact_phone),
+        contact_email: changed_text(current.contact_email.as_deref(), desired.contact_email),
+        comments: changed_text(current.comments.as_deref(), desired.comments),
+        tags: changed_tags(current.tags.as_ref(), desired.tags),
+    }
+}
+
+/// The part of `desired` that changes `current`; see [`diff_site`]
+pub fn diff_device(current: &NetBoxDevice, desired: &UpdateDeviceRequest) -> UpdateDeviceRequest {
+    let desired = desired.clone();
+    UpdateDeviceRequest {
+        name: changed_text(current.name.as_deref(), desired.name),
+        device_type: changed(current.device_type.as_ref(), desired.device_type),
+        device_role: changed(current.device_role.as_ref(), desired.device_role),
+        tenant: changed(current.tenant.as_ref(), desired.tenant),
+        platform: changed(current.platform.as_ref(), desired.platform),
+        serial: changed_text(current.serial.as_deref(), desired.serial),
+        asset_tag: changed_text(current.asset_tag.as_deref(), desired.asset_tag),
+        site: changed(current.site.as_ref(), desired.site),
+        location: changed(current.location.as_ref(), desired.location),
+        rack: changed(current.rack.as_ref(), desired.rack),
+        position: changed(current.position.as_ref(), desired.position),
+        face: changed(current.face.as_ref(), desired.face),
+        status: changed(current.status.as_ref(), desired.status),
+        cluster: changed(current.cluster.as_ref(), desired.cluster),
+        comments: changed_text(current.comments.as_deref(), desired.comments),
+        tags: changed_tags(current.tags.as_ref(), desired.tags),
+    }
+}
+
+/// Builder for [`CreateDeviceRequest`]
+///
+/// ```
+/// use netgate::netbox::{CreateDeviceRequestBuilder, DeviceStatus};
+///
+/// let request = CreateDeviceRequestBuilder::new(1, 2, 3)
+///     .with_name("edge-sw-01")
+///     .with_status(DeviceStatus::Planned)
+///     .build();
+///
+/// assert_eq!(request.device_type, 1);
+/// assert_eq!(request.device_role, 2);
+/// assert_eq!(request.site, 3);
+/// assert_eq!(request.name.as_deref(), Some("edge-sw-01"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CreateDeviceRequestBuilder {
+    request: CreateDeviceRequest,
+}
+
+impl CreateDeviceRequestBuilder {
+    /// Start a request for a device of the given type and role at a site
+    pub fn new(device_type: i32, device_role: i32, site: i32) -> Self {
+        Self {
+            request: CreateDeviceRequest {
+                name: None,
+                device_type,
+                device_role,
+                tenant: None,
+                platform: None,
+                serial: None,
+                asset_tag: None,
+                site,
+                location: None,
+                rack: None,
+                position: None,
+                face: None,
+                status: None,
+                cluster: None,
+                comments: None,
+                tags: None,
+            },
+        }
+    }
+
+    /// Set the device name
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.request.name = Some(name.into());
+        self
+    }
+
+    /// Set the tenant ID
+    pub fn with_tenant(mut self, tenant: i32) -> Self {
+        self.request.tenant = Some(tenant);
+        self
+    }
+
+    /// Set the platform ID
+    pub fn with_platform(mut self, platform: i32) -> Self {
+        self.request.platform = Some(platform);
+        self
+    }
+
+    /// Set the serial number
+    pub fn with_serial(mut self, serial: impl Into<String>) -> Self {
+        self.request.serial = Some(serial.into());
+        self
+    }
+
+    /// Set the asset tag
+    pub fn with_asset_tag(mut self, asset_tag: impl Into<String>) -> Self {
+        self.request.asset_tag = Some(asset_tag.into());
+        self
+    }
+
+    /// Set the location ID
+    pub fn with_location(mut self, location: i32) -> Self {
+        self.request.location = Some(location);
+        self
+    }
+
+    /// Set the rack ID
+    pub fn with_rack(mut self, rack: i32) -> Self {
+        self.request.rack = Some(rack);
+        self
+    }
+
+    /// Set the rack position
+    pub fn with_position(mut self, position: f64) -> Self {
+        self.request.position = Some(position);
+        self
+    }
+
+    /// Set the rack face
+    pub fn with_face(mut self, face: DeviceFace) -> Self {
+        self.request.face = Some(face);
+        self
+    }
+
+    /// Set the device status
+    pub fn with_status(mut self, status: DeviceStatus) -> Self {
+        self.request.status = Some(status);
+        self
+    }
+
+    /// Set the cluster ID
+    pub fn with_cluster(mut self, cluster: i32) -> Self {
+        self.request.cluster = Some(cluster);
+        self
+    }
+
+    /// Set the comments
+    pub fn with_comments(mut self, comments: impl Into<String>) -> Self {
+        self.request.comments = Some(comments.into());
+        self
+    }
+
+    /// Set the tag names
+    pub fn with_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.request.tags = Some(tags.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Build the request
+    pub fn build(self) -> CreateDeviceRequest {
+        self.request
+    }
+}
+
+impl CreateDeviceRequest {
+    /// Start building a request for a device of the given type and role at a site
+    pub fn builder(device_type: i32, device_role: i32, site: i32) -> CreateDeviceRequestBuilder {
+        CreateDeviceRequestBuilder::new(device_type, device_role, site)
+    }
+}
+
+/// NetBox DCIM manufacturer, in the brief form device types nest it in
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetBoxManufacturer {
+    pub id: i32,
+    pub name: String,
+    pub slug: String,
+}
+
+/// NetBox DCIM Device Type model
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetBoxDeviceType {
+    pub id: Option<i32>,
+    pub manufacturer: Option<NetBoxManufacturer>,
+    pub model: String,
+    pub slug: String,
+    pub part_number: Option<String>,
+    pub u_height: Option<f64>,
+    pub description: Option<String>,
+}
+
+/// NetBox DCIM Device Role model
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetBoxDeviceRole {
+    pub id: Option<i32>,
+    pub name: String,
+    pub slug: String,
+    /// Six hex digits without the `#`
+    pub color: Option<String>,
+    pub description: Option<String>,
+}
+
+/// NetBox DCIM Interface model
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetBoxInterface {
+    pub id: Option<i32>,
+    pub device: Option<i32>,
+    pub name: String,
+    /// NetBox interface type slug, e.g. `1000base-t` or `virtual`
+    #[serde(rename = "type")]
+    pub interface_type: Option<String>,
+    pub enabled: Option<bool>,
+    pub description: Option<String>,
+    pub created: Option<String>,
+    pub last_updated: Option<String>,
+}
+
+/// Request payload for creating a device interface
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateInterfaceRequest {
+    pub device: i32,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub interface_type: String,
+    pub enabled: Option<bool>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+/// One end of a cable, in the termination lists NetBox 3.3+ uses
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CableTermination {
+    /// NetBox's `app_label.model` of the terminated object, e.g. `dcim.interface`
+    pub object_type: String,
+    pub object_id: i32,
+}
+
+impl CableTermination {
+    /// Termination on a device interface
+    pub fn interface(id: i32) -> Self {
+        Self {
+            object_type: "dcim.interface".to_string(),
+            object_id: id,
+        }
+    }
+}
+
+/// NetBox DCIM Rack model
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetBoxRack {
+    pub id: Option<i32>,
+    pub name: String,
+    pub site: Option<i32>,
+    pub location: Option<i32>,
+    pub tenant: Option<i32>,
+    pub status: Option<RackStatus>,
+    /// Height in rack units
+    pub u_height: Option<i32>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub created: Option<String>,
+    pub last_updated: Option<String>,
+}
+
+/// NetBox Rack Status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RackStatus {
+    Reserved,
+    Available,
+    Planned,
+    Active,
+    Deprecated,
+}
+
+/// NetBox DCIM Cable model
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetBoxCable {
+    pub id: Option<i32>,
+    #[serde(default)]
+    pub a_terminations: Vec<CableTermination>,
+    #[serde(default)]
+    pub b_terminations: Vec<CableTermination>,
+    pub status: Option<String>,
+    pub label: Option<String>,
+    pub description: Option<String>,
+    pub created: Option<String>,
+    pub last_updated: Option<String>,
+}
+
+/// Request payload for creating a cable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCableRequest {
+    pub a_terminations: Vec<CableTermination>,
+    pub b_terminations: Vec<CableTermination>,
+    pub status: Option<String>,
+    pub label: Option<String>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+/// NetBox IPAM Prefix model
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetBoxPrefix {
+    pub id: Option<i32>,
+    pub prefix: String,
+    pub site: Option<i32>,
+    pub vlan: Option<i32>,
+    pub tenant: Option<i32>,
+    pub status: Option<PrefixStatus>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+    /// Percentage of the prefix in use, when NetBox includes it
+    pub utilization: Option<f64>,
+    pub created: Option<String>,
+    pub last_updated: Option<String>,
+}
+
+impl NetBoxPrefix {
+    /// Addresses NetBox hands out from this prefix, 0 if it isn't a valid CIDR
+    ///
+    /// As in NetBox, the network and broadcast addresses of an IPv4 prefix
+    /// and the subnet-router anycast address of an IPv6 one aren't counted,
+    /// except in point-to-point and host prefixes.
+    pub fn address_capacity(&self) -> u64 {
+        let Ok(network) = self.prefix.parse::<ipnet::IpNet>() else {
+            return 0;
+        };
+        let host_bits = u32::from(network.max_prefix_len() - network.prefix_len());
+        let size = 1u64.checked_shl(host_bits).unwrap_or(u64::MAX);
+        match network {
+            _ if host_bits <= 1 => size,
+            ipnet::IpNet::V4(_) => size - 2,
+            ipnet::IpNet::V6(_) => size - 1,
+        }
+    }
+}
+
+/// NetBox Prefix Status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrefixStatus {
+    Container,
+    Active,
+    Reserved,
+    Deprecated,
+}
+
+/// Request payload for creating an IPAM prefix
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePrefixRequest {
+    pub prefix: String,
+    pub site: Option<i32>,
+    pub vlan: Option<i32>,
+    pub tenant: Option<i32>,
+    pub status: Option<PrefixStatus>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+/// NetBox virtualization cluster model
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetBoxCluster {
+    pub id: Option<i32>,
+    pub name: String,
+    /// Cluster type id
+    #[serde(rename = "type")]
+    pub cluster_type: Option<i32>,
+    pub group: Option<i32>,
+    pub site: Option<i32>,
+    pub tenant: Option<i32>,
+    pub status: Option<ClusterStatus>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub created: Option<String>,
+    pub last_updated: Option<String>,
+}
+
+/// NetBox Cluster Status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClusterStatus {
+    Planned,
+    Staging,
+    Active,
+    Decommissioning,
+    Offline,
+}
+
+/// Request payload for creating a cluster
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateClusterRequest {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub cluster_type: i32,
+    pub group: Option<i32>,
+    pub site: Option<i32>,
+    pub tenant: Option<i32>,
+    pub status: Option<ClusterStatus>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+/// Request payload for updating a cluster
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateClusterRequest {
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub cluster_type: Option<i32>,
+    pub group: Option<i32>,
+    pub site: Option<i32>,
+    pub tenant: Option<i32>,
+    pub status: Option<ClusterStatus>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+/// NetBox virtual machine model
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetBoxVirtualMachine {
+    pub id: Option<i32>,
+    pub name: String,
+    pub status: Option<VirtualMachineStatus>,
+    pub cluster: Option<i32>,
+    pub site: Option<i32>,
+    pub tenant: Option<i32>,
+    pub role: Option<i32>,
+    pub platform: Option<i32>,
+    pub vcpus: Option<f64>,
+    /// Memory in MB
+    pub memory: Option<i32>,
+    /// Disk in GB
+    pub disk: Option<i32>,
+    pub description: Option<String>,
+    pub comments: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub created: Option<String>,
+    pub last_updated: Option<String>,
+}
+
+/// NetBox Virtual Machine Status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VirtualMachineStatus {
+    Offline,
+    Active,
+    Planned,
+    Staged,
+    Failed,
+    Decommissioning,
+}
+
+/// Request payload for creating a virtual machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateVirtualMachineRequest {
+    pub name: String,
+    pub cluster: Option<i32>,
+    pub site: Option<i32>,
+    pub tenant: Option<i32>,
+    pub role: Option<i32>,
+    pub platform: Option<i32>,
+    pub status: Option<VirtualMachineStatus>,
+    pub vcpus: Option<f64>,
+    pub memory: Option<i32>,
+    pub disk: Option<i32>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+/// Request payload for updating a virtual machine
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateVirtualMachineRequest {
+    pub name: Option<String>,
+    pub cluster: Option<i32>,
+    pub site: Option<i32>,
+    pub tenant: Option<i32>,
+    pub role: Option<i32>,
+    pub platform: Option<i32>,
+    pub status: Option<VirtualMachineStatus>,
+    pub vcpus: Option<f64>,
+    pub memory: Option<i32>,
+    pub disk: Option<i32>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+/// NetBox IPAM IP address model
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetBoxIpAddress {
+    pub id: Option<i32>,
+    /// Address with its prefix length, e.g. "10.20.0.5/24"
+    pub address: String,
+    pub status: Option<IpAddressStatus>,
+    pub tenant: Option<i32>,
+    /// e.g. "dcim.interface"
+    pub assigned_object_type: Option<String>,
+    pub assigned_object_id: Option<i32>,
+    pub dns_name: Option<String>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub created: Option<String>,
+    pub last_updated: Option<String>,
+}
+
+/// NetBox IP address Status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IpAddressStatus {
+    Active,
+    Reserved,
+    Deprecated,
+    Dhcp,
+    Slaac,
+}
+
+/// An address NetBox reports free within a prefix
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvailableIp {
+    pub family: Option<u8>,
+    pub address: String,
+}
+
+/// Request payload for allocating the next free address of a prefix
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AllocateIpRequest {
+    pub assigned_object_type: Option<String>,
+    pub assigned_object_id: Option<i32>,
+    pub dns_name: Option<String>,
+    pub tenant: Option<i32>,
+    pub status: Option<IpAddressStatus>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+impl AllocateIpRequest {
+    /// Assign the address to a device interface
+    pub fn assign_to_interface(&mut self, interface_id: i32) {
+        self.assigned_object_type = Some("dcim.interface".to_string());
+        self.assigned_object_id = Some(interface_id);
+    }
+}
+
+/// NetBox IPAM VLAN model
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetBoxVlan {
+    pub id: Option<i32>,
+    /// 802.1Q VLAN id, 1-4094
+    pub vid: u16,
+    pub name: String,
+    pub site: Option<i32>,
+    /// NetBox VLAN group id
+    pub group: Option<i32>,
+    pub tenant: Option<i32>,
+    pub status: Option<VlanStatus>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub created: Option<String>,
+    pub last_updated: Option<String>,
+}
+
+/// NetBox VLAN Status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VlanStatus {
+    Active,
+    Reserved,
+    Deprecated,
+}
+
+/// Request payload for creating a VLAN
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateVlanRequest {
+    pub vid: u16,
+    pub name: String,
+    pub site: Option<i32>,
+    pub group: Option<i32>,
+    pub tenant: Option<i32>,
+    pub status: Option<VlanStatus>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+/// Request payload for updating a VLAN
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateVlanRequest {
+    pub vid: Option<u16>,
+    pub name: Option<String>,
+    pub group: Option<i32>,
+    pub tenant: Option<i32>,
+    pub status: Option<VlanStatus>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+/// NetBox tenancy Tenant model
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetBoxTenant {
+    pub id: Option<i32>,
+    pub name: String,
+    pub slug: String,
+    pub description: Option<String>,
+    pub created: Option<String>,
+    pub last_updated: Option<String>,
+}
+
+/// Request payload for creating a NetBox tenant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateTenantRequest {
+    pub name: String,
+    pub slug: String,
+    pub description: Option<String>,
+}
+
+/// NetBox extras Tag model
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetBoxTag {
+    pub id: Option<i32>,
+    pub name: String,
+    pub slug: String,
+    pub color: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Request payload for creating a NetBox tag
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateTagRequest {
+    pub name: String,
+    pub slug: String,
+    /// Six hex digits without the `#`, e.g. `9e9e9e`
+    pub color: String,
+}
+
+/// NetBox custom field definition, from `/api/extras/custom-fields/`
+///
+/// NetBox 4 lists the models a field applies to as `object_types`, older
+/// releases as `content_types`. Select fields carry their `choices` inline
+/// before NetBox 3.6 and reference a `choice_set` since.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetBoxCustomField {
+    pub id: Option<i32>,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: NetBoxChoice,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default, alias = "content_types")]
+    pub object_types: Vec<String>,
+    pub choices: Option<Vec<String>>,
+    pub choice_set: Option<NetBoxNestedObject>,
+}
+
+/// A NetBox choice value with its display label
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NetBoxChoice {
+    pub value: String,
+    pub label: Option<String>,
+}
+
+/// The brief form NetBox nests related objects in
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NetBoxNestedObject {
+    pub id: i32,
+    pub name: Option<String>,
+}
+
+/// NetBox custom field choice set, from `/api/extras/custom-field-choice-sets/`
+///
+/// `extra_choices` holds `[value, label]` pairs. A set built on
+/// `base_choices` (e.g. IATA codes) has choices NetBox doesn't list here.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetBoxCustomFieldChoiceSet {
+    pub id: i32,
+    pub name: String,
+    pub base_choices: Option<serde_json::Value>,
+    #[serde(default)]
+    pub extra_choices: Vec<(String, String)>,
+}
+
+/// A changelog entry, from `/api/extras/object-changes/`
+///
+/// The snapshots hold the object's serialized fields before and after the
+/// change; `prechange_data` is null on create and `postchange_data` on delete.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetBoxObjectChange {
+    pub id: i32,
+    pub time: DateTime<Utc>,
+    /// Username at the time of the change, kept even if the user is deleted
+    pub user_name: Option<String>,
+    pub request_id: Option<String>,
+    /// `create`, `update` or `delete`
+    pub action: NetBoxChoice,
+    pub changed_object_type: String,
+    pub changed_object_id: i32,
+    pub prechange_data: Option<serde_json::Value>,
+    pub postchange_data: Option<serde_json::Value>,
+}
+
+/// Body of a NetBox webhook for an object change
+///
+/// `data` is the object after the change, or as it was for `deleted`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetBoxWebhookEvent {
+    /// `created`, `updated` or `deleted`
+    pub event: String,
+    /// When NetBox fired the webhook
+    pub timestamp: DateTime<Utc>,
+    /// Model name, e.g. `site` or `device`
+    pub model: String,
+    pub username: Option<String>,
+    pub request_id: Option<String>,
+    pub data: serde_json::Value,
+    pub snapshots: Option<NetBoxWebhookSnapshots>,
+}
+
+/// The object before and after the change a webhook reports
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetBoxWebhookSnapshots {
+    pub prechange: Option<serde_json::Value>,
+    pub postchange: Option<serde_json::Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn site() -> NetBoxSite {
+        NetBoxSite {
+            id: Some(3),
+            name: "Berlin".to_string(),
+            slug: Some("berlin".to_string()),
+            description: Some("Hall A".to_string()),
+            status: Some(SiteStatus::Active),
+            region: Some(2),
+            tenant: Some(7),
+            latitude: Some(52.52),
+            tags: Some(vec!["netgate".to_string(), "edge".to_string()]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_diff_site_drops_unchanged_fields() {
+        let desired = UpdateSiteRequest::builder()
+            .with_name("Berlin")
+            .with_slug("berlin")
+            .with_description("Hall B")
+            .with_status(SiteStatus::Active)
+            .with_region(2)
+            .with_tenant(7)
+            .with_latitude(52.52)
+            .with_tags(["edge", "netgate"])
+            .build();
+
+        let diff = diff_site(&site(), &desired);
+        assert_eq!(serde_json::to_value(&diff).unwrap(), serde_json::json!({"description": "Hall B"}));
+
+        let same = UpdateSiteRequest { description: None, ..desired };
+        assert!(diff_site(&site(), &same).is_empty());
+        // Unset fields stay unset whatever the site has
+        assert!(diff_site(&site(), &UpdateSiteRequest::default()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_site_keeps_clearing_and_real_changes() {
+        // A blank string clears a text field, unless it's already null
+        let clear = UpdateSiteRequest::builder().with_description("").with_facility("").build();
+        let diff = diff_site(&site(), &clear);
+        assert_eq!(diff.description.as_deref(), Some(""));
+        assert_eq!(diff.facility, None);
+
+        // Setting a field the site doesn't have is a change
+        let diff = diff_site(&site(), &UpdateSiteRequest::builder().with_longitude(13.4).build());
+        assert_eq!(diff.longitude, Some(13.4));
+
+        // Tags are compared as a set, so reordering isn't a change but dropping one is
+        let reordered = UpdateSiteRequest::builder().with_tags(["edge", "netgate", "edge"]).build();
+        assert_eq!(diff_site(&site(), &reordered).tags, None);
+        let dropped = UpdateSiteRequest::builder().with_tags(["netgate"]).build();
+        assert_eq!(diff_site(&site(), &dropped).tags, Some(vec!["netgate".to_string()]));
+        let untagged = NetBoxSite { tags: None, ..site() };
+        assert_eq!(diff_site(&untagged, &UpdateSiteRequest::builder().with_tags(Vec::<String>::new()).build()).tags, None);
+    }
+
+    #[test]
+    fn test_diff_device_drops_unchanged_fields() {
+        let current = NetBoxDevice {
+            id: Some(9),
+            name: Some("edge-01".to_string()),
+            serial: None,
+            status: Some(DeviceStatus::Active),
+            rack: Some(4),
+            position: Some(12.0),
+            ..Default::default()
+        };
+        let desired = UpdateDeviceRequest {
+            name: Some("edge-01".to_string()),
+            serial: Some(String::new()),
+            status: Some(DeviceStatus::Offline),
+            rack: Some(4),
+            position: Some(14.0),
+            ..Default::default()
+        };
+
+        let diff = diff_device(&current, &desired);
+        assert_eq!(diff.name, None);
+        assert_eq!(diff.serial, None);
+        assert_eq!(diff.status, Some(DeviceStatus::Offline));
+        assert_eq!(diff.rack, None);
+        assert_eq!(diff.position, Some(14.0));
+    }
+
+    #[test]
+    fn test_prefix_address_capacity_skips_reserved_addresses() {
+        let capacity = |prefix: &str| NetBoxPrefix { prefix: prefix.to_string(), ..Default::default() }.address_capacity();
+        assert_eq!(capacity("10.0.0.0/29"), 6);
+        assert_eq!(capacity("10.0.0.0/31"), 2);
+        assert_eq!(capacity("10.0.0.1/32"), 1);
+        assert_eq!(capacity("2001:db8::/120"), 255);
+        assert_eq!(capacity("2001:db8::/127"), 2);
+        assert_eq!(capacity("2001:db8::/32"), u64::MAX - 1);
+        assert_eq!(capacity("not a prefix"), 0);
+    }
+}
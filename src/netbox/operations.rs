@@ -0,0 +1,789 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+use crate::error::AppError;
+use crate::netbox::cached_client::CachedNetBoxClient;
+use crate::netbox::client::NetBoxClient;
+use crate::netbox::graphql::{DeviceInterfaces, SiteDeviceCount};
+use crate::netbox::models::*;
+use crate::netbox::resilient_client::ResilientNetBoxClient;
+
+/// Filters and paging for list operations
+#[derive(Debug, Clone, Default)]
+pub struct ListQuery {
+    /// NetBox tenant ID
+    pub tenant_id: Option<i32>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+    /// Additional NetBox filters, e.g. `status=active`
+    pub filters: HashMap<String, String>,
+}
+
+/// Site CRUD, implemented by each NetBox client layer so they can be stacked
+#[async_trait]
+pub trait SiteOperations: Send + Sync {
+    async fn get_site(&self, id: i32) -> Result<NetBoxSite, AppError>;
+    /// Sites in the order of `ids`, `None` for IDs NetBox doesn't have
+    async fn get_sites_by_ids(&self, ids: &[i32]) -> Result<Vec<(i32, Option<NetBoxSite>)>, AppError>;
+    async fn list_sites(&self, query: &ListQuery) -> Result<NetBoxResponse<NetBoxSite>, AppError>;
+    async fn create_site(&self, request: CreateSiteRequest) -> Result<NetBoxSite, AppError>;
+    async fn update_site(&self, id: i32, request: UpdateSiteRequest) -> Result<NetBoxSite, AppError>;
+    async fn delete_site(&self, id: i32) -> Result<(), AppError>;
+}
+
+/// Device CRUD, implemented by each NetBox client layer so they can be stacked
+#[async_trait]
+pub trait DeviceOperations: Send + Sync {
+    async fn get_device(&self, id: i32) -> Result<NetBoxDevice, AppError>;
+    /// Devices in the order of `ids`, `None` for IDs NetBox doesn't have
+    async fn get_devices_by_ids(&self, ids: &[i32]) -> Result<Vec<(i32, Option<NetBoxDevice>)>, AppError>;
+    async fn list_devices(
+        &self,
+        site_id: Option<i32>,
+        query: &ListQuery,
+    ) -> Result<NetBoxResponse<NetBoxDevice>, AppError>;
+    async fn create_device(&self, request: CreateDeviceRequest) -> Result<NetBoxDevice, AppError>;
+    async fn update_device(&self, id: i32, request: UpdateDeviceRequest) -> Result<NetBoxDevice, AppError>;
+    async fn delete_device(&self, id: i32) -> Result<(), AppError>;
+}
+
+/// VLAN CRUD, implemented by each NetBox client layer so they can be stacked
+#[async_trait]
+pub trait VlanOperations: Send + Sync {
+    async fn get_vlan(&self, id: i32) -> Result<NetBoxVlan, AppError>;
+    /// `group_id` is a NetBox VLAN group ID
+    async fn list_vlans(
+        &self,
+        site_id: Option<i32>,
+        group_id: Option<i32>,
+        query: &ListQuery,
+    ) -> Result<NetBoxResponse<NetBoxVlan>, AppError>;
+    async fn create_vlan(&self, request: CreateVlanRequest) -> Result<NetBoxVlan, AppError>;
+    async fn update_vlan(&self, id: i32, request: UpdateVlanRequest) -> Result<NetBoxVlan, AppError>;
+    async fn delete_vlan(&self, id: i32) -> Result<(), AppError>;
+}
+
+/// Cluster and virtual machine CRUD, implemented by each NetBox client layer so they can be stacked
+#[async_trait]
+pub trait VirtualizationOperations: Send + Sync {
+    async fn get_cluster(&self, id: i32) -> Result<NetBoxCluster, AppError>;
+    async fn list_clusters(
+        &self,
+        site_id: Option<i32>,
+        query: &ListQuery,
+    ) -> Result<NetBoxResponse<NetBoxCluster>, AppError>;
+    async fn create_cluster(&self, request: CreateClusterRequest) -> Result<NetBoxCluster, AppError>;
+    async fn update_cluster(&self, id: i32, request: UpdateClusterRequest) -> Result<NetBoxCluster, AppError>;
+    async fn delete_cluster(&self, id: i32) -> Result<(), AppError>;
+    async fn get_virtual_machine(&self, id: i32) -> Result<NetBoxVirtualMachine, AppError>;
+    /// Each ID with its VM, or `None` if NetBox doesn't have it
+    async fn get_virtual_machines_by_ids(
+        &self,
+        ids: &[i32],
+    ) -> Result<Vec<(i32, Option<NetBoxVirtualMachine>)>, AppError>;
+    async fn list_virtual_machines(
+        &self,
+        site_id: Option<i32>,
+        cluster_id: Option<i32>,
+        query: &ListQuery,
+    ) -> Result<NetBoxResponse<NetBoxVirtualMachine>, AppError>;
+    async fn create_virtual_machine(
+        &self,
+        request: CreateVirtualMachineRequest,
+    ) -> Result<NetBoxVirtualMachine, AppError>;
+    async fn update_virtual_machine(
+        &self,
+        id: i32,
+        request: UpdateVirtualMachineRequest,
+    ) -> Result<NetBoxVirtualMachine, AppError>;
+    async fn delete_virtual_machine(&self, id: i32) -> Result<(), AppError>;
+}
+
+/// Rack and prefix listings behind capacity reports
+#[async_trait]
+pub trait CapacityOperations: Send + Sync {
+    async fn list_racks(&self, site_id: Option<i32>, query: &ListQuery) -> Result<NetBoxResponse<NetBoxRack>, AppError>;
+    async fn list_prefixes(
+        &self,
+        site_id: Option<i32>,
+        query: &ListQuery,
+    ) -> Result<NetBoxResponse<NetBoxPrefix>, AppError>;
+}
+
+/// Read-only reports served by NetBox's GraphQL API
+#[async_trait]
+pub trait ReportOperations: Send + Sync {
+    async fn sites_with_device_counts(&self, tenant_id: Option<i32>) -> Result<Vec<SiteDeviceCount>, AppError>;
+    async fn devices_with_interfaces(
+        &self,
+        site_id: i32,
+        tenant_id: Option<i32>,
+    ) -> Result<Vec<DeviceInterfaces>, AppError>;
+}
+
+/// Read-only access to NetBox's changelog
+#[async_trait]
+pub trait ChangelogOperations: Send + Sync {
+    /// A page of an object's changes, newest first; `object_type` is e.g. `dcim.site`
+    async fn get_object_changes(
+        &self,
+        object_type: &str,
+        object_id: i32,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<NetBoxResponse<NetBoxObjectChange>, AppError>;
+}
+
+/// Site, device, VLAN, virtualization, capacity, report and changelog operations together, for
+/// holding a client layer as a trait object
+pub trait NetBoxOperations:
+    SiteOperations
+    + DeviceOperations
+    + VlanOperations
+    + VirtualizationOperations
+    + CapacityOperations
+    + ReportOperations
+    + ChangelogOperations
+{
+}
+
+impl<T> NetBoxOperations for T where
+    T: SiteOperations
+        + DeviceOperations
+        + VlanOperations
+        + VirtualizationOperations
+        + CapacityOperations
+        + ReportOperations
+        + ChangelogOperations
+{
+}
+
+// The impls below call each client's inherent methods, which take precedence
+// over the trait methods of the same name.
+
+#[async_trait]
+impl SiteOperations for NetBoxClient {
+    async fn get_site(&self, id: i32) -> Result<NetBoxSite, AppError> {
+        Ok(self.get_site(id).await?)
+    }
+
+    async fn get_sites_by_ids(&self, ids: &[i32]) -> Result<Vec<(i32, Option<NetBoxSite>)>, AppError> {
+        Ok(self.get_sites_by_ids(ids).await?)
+    }
+
+    async fn list_sites(&self, query: &ListQuery) -> Result<NetBoxResponse<NetBoxSite>, AppError> {
+        Ok(self
+            .list_sites_with_filters(query.tenant_id, None, query.limit, query.offset, &query.filters)
+            .await?)
+    }
+
+    async fn create_site(&self, request: CreateSiteRequest) -> Result<NetBoxSite, AppError> {
+        Ok(self.create_site(request).await?)
+    }
+
+    async fn update_site(&self, id: i32, request: UpdateSiteRequest) -> Result<NetBoxSite, AppError> {
+        Ok(self.update_site(id, request).await?)
+    }
+
+    async fn delete_site(&self, id: i32) -> Result<(), AppError> {
+        Ok(self.delete_site(id).await?)
+    }
+}
+
+#[async_trait]
+impl DeviceOperations for NetBoxClient {
+    async fn get_device(&self, id: i32) -> Result<NetBoxDevice, AppError> {
+        Ok(self.get_device(id).await?)
+    }
+
+    async fn get_devices_by_ids(&self, ids: &[i32]) -> Result<Vec<(i32, Option<NetBoxDevice>)>, AppError> {
+        Ok(self.get_devices_by_ids(ids).await?)
+    }
+
+    async fn list_devices(
+        &self,
+        site_id: Option<i32>,
+        query: &ListQuery,
+    ) -> Result<NetBoxResponse<NetBoxDevice>, AppError> {
+        Ok(self
+            .list_devices_with_filters(site_id, query.tenant_id, query.limit, query.offset, &query.filters)
+            .await?)
+    }
+
+    async fn create_device(&self, request: CreateDeviceRequest) -> Result<NetBoxDevice, AppError> {
+        Ok(self.create_device(request).await?)
+    }
+
+    async fn update_device(&self, id: i32, request: UpdateDeviceRequest) -> Result<NetBoxDevice, AppError> {
+        Ok(self.update_device(id, request).await?)
+    }
+
+    async fn delete_device(&self, id: i32) -> Result<(), AppError> {
+        Ok(self.delete_device(id).await?)
+    }
+}
+
+#[async_trait]
+impl SiteOperations for ResilientNetBoxClient {
+    async fn get_site(&self, id: i32) -> Result<NetBoxSite, AppError> {
+        self.get_site(id).await
+    }
+
+    async fn get_sites_by_ids(&self, ids: &[i32]) -> Result<Vec<(i32, Option<NetBoxSite>)>, AppError> {
+        self.get_sites_by_ids(ids).await
+    }
+
+    async fn list_sites(&self, query: &ListQuery) -> Result<NetBoxResponse<NetBoxSite>, AppError> {
+        self.list_sites_with_filters(query.tenant_id, query.limit, query.offset, &query.filters)
+            .await
+    }
+
+    async fn create_site(&self, request: CreateSiteRequest) -> Result<NetBoxSite, AppError> {
+        self.create_site(request).await
+    }
+
+    async fn update_site(&self, id: i32, request: UpdateSiteRequest) -> Result<NetBoxSite, AppError> {
+        self.update_site(id, request).await
+    }
+
+    async fn delete_site(&self, id: i32) -> Result<(), AppError> {
+        self.delete_site(id).await
+    }
+}
+
+#[async_trait]
+impl DeviceOperations for ResilientNetBoxClient {
+    async fn get_device(&self, id: i32) -> Result<NetBoxDevice, AppError> {
+        self.get_device(id).await
+    }
+
+    async fn get_devices_by_ids(&self, ids: &[i32]) -> Result<Vec<(i32, Option<NetBoxDevice>)>, AppError> {
+        self.get_devices_by_ids(ids).await
+    }
+
+    async fn list_devices(
+        &self,
+        site_id: Option<i32>,
+        query: &ListQuery,
+    ) -> Result<NetBoxResponse<NetBoxDevice>, AppError> {
+        self.list_devices_with_filters(site_id, query.tenant_id, query.limit, query.offset, &query.filters)
+            .await
+    }
+
+    async fn create_device(&self, request: CreateDeviceRequest) -> Result<NetBoxDevice, AppError> {
+        self.create_device(request).await
+    }
+
+    async fn update_device(&self, id: i32, request: UpdateDeviceRequest) -> Result<NetBoxDevice, AppError> {
+        self.update_device(id, request).await
+    }
+
+    async fn delete_device(&self, id: i32) -> Result<(), AppError> {
+        self.delete_device(id).await
+    }
+}
+
+#[async_trait]
+impl SiteOperations for CachedNetBoxClient {
+    async fn get_site(&self, id: i32) -> Result<NetBoxSite, AppError> {
+        self.get_site(id).await
+    }
+
+    async fn get_sites_by_ids(&self, ids: &[i32]) -> Result<Vec<(i32, Option<NetBoxSite>)>, AppError> {
+        self.get_sites_by_ids(ids).await
+    }
+
+    async fn list_sites(&self, query: &ListQuery) -> Result<NetBoxResponse<NetBoxSite>, AppError> {
+        self.list_sites_with_filters(query.tenant_id, query.limit, query.offset, &query.filters)
+            .await
+    }
+
+    async fn create_site(&self, request: CreateSiteRequest) -> Result<NetBoxSite, AppError> {
+        self.create_site(request).await
+    }
+
+    async fn update_site(&self, id: i32, request: UpdateSiteRequest) -> Result<NetBoxSite, AppError> {
+        self.update_site(id, request).await
+    }
+
+    async fn delete_site(&self, id: i32) -> Result<(), AppError> {
+        self.delete_site(id).await
+    }
+}
+
+/// Devices aren't cached, so the cached client passes them straight through
+#[async_trait]
+impl DeviceOperations for CachedNetBoxClient {
+    async fn get_device(&self, id: i32) -> Result<NetBoxDevice, AppError> {
+        self.inner().get_device(id).await
+    }
+
+    async fn get_devices_by_ids(&self, ids: &[i32]) -> Result<Vec<(i32, Option<NetBoxDevice>)>, AppError> {
+        self.get_devices_by_ids(ids).await
+    }
+
+    async fn list_devices(
+        &self,
+        site_id: Option<i32>,
+        query: &ListQuery,
+    ) -> Result<NetBoxResponse<NetBoxDevice>, AppError> {
+        DeviceOperations::list_devices(self.inner().as_ref(), site_id, query).await
+    }
+
+    async fn create_device(&self, request: CreateDeviceRequest) -> Result<NetBoxDevice, AppError> {
+        self.inner().create_device(request).await
+    }
+
+    async fn update_device(&self, id: i32, request: UpdateDeviceRequest) -> Result<NetBoxDevice, AppError> {
+        self.inner().update_device(id, request).await
+    }
+
+    async fn delete_device(&self, id: i32) -> Result<(), AppError> {
+        self.inner().delete_device(id).await
+    }
+}
+
+#[async_trait]
+impl VlanOperations for NetBoxClient {
+    async fn get_vlan(&self, id: i32) -> Result<NetBoxVlan, AppError> {
+        Ok(self.get_vlan(id).await?)
+    }
+
+    async fn list_vlans(
+        &self,
+        site_id: Option<i32>,
+        group_id: Option<i32>,
+        query: &ListQuery,
+    ) -> Result<NetBoxResponse<NetBoxVlan>, AppError> {
+        Ok(self
+            .list_vlans_with_filters(site_id, group_id, query.tenant_id, query.limit, query.offset, &query.filters)
+            .await?)
+    }
+
+    async fn create_vlan(&self, request: CreateVlanRequest) -> Result<NetBoxVlan, AppError> {
+        Ok(self.create_vlan(request).await?)
+    }
+
+    async fn update_vlan(&self, id: i32, request: UpdateVlanRequest) -> Result<NetBoxVlan, AppError> {
+        Ok(self.update_vlan(id, request).await?)
+    }
+
+    async fn delete_vlan(&self, id: i32) -> Result<(), AppError> {
+        Ok(self.delete_vlan(id).await?)
+    }
+}
+
+#[async_trait]
+impl VlanOperations for ResilientNetBoxClient {
+    async fn get_vlan(&self, id: i32) -> Result<NetBoxVlan, AppError> {
+        self.get_vlan(id).await
+    }
+
+    async fn list_vlans(
+        &self,
+        site_id: Option<i32>,
+        group_id: Option<i32>,
+        query: &ListQuery,
+    ) -> Result<NetBoxResponse<NetBoxVlan>, AppError> {
+        self.list_vlans_with_filters(site_id, group_id, query.tenant_id, query.limit, query.offset, &query.filters)
+            .await
+    }
+
+    async fn create_vlan(&self, request: CreateVlanRequest) -> Result<NetBoxVlan, AppError> {
+        self.create_vlan(request).await
+    }
+
+    async fn update_vlan(&self, id: i32, request: UpdateVlanRequest) -> Result<NetBoxVlan, AppError> {
+        self.update_vlan(id, request).await
+    }
+
+    async fn delete_vlan(&self, id: i32) -> Result<(), AppError> {
+        self.delete_vlan(id).await
+    }
+}
+
+/// VLANs aren't cached, so the cached client passes them straight through
+#[async_trait]
+impl VlanOperations for CachedNetBoxClient {
+    async fn get_vlan(&self, id: i32) -> Result<NetBoxVlan, AppError> {
+        self.inner().get_vlan(id).await
+    }
+
+    async fn list_vlans(
+        &self,
+        site_id: Option<i32>,
+        group_id: Option<i32>,
+        query: &ListQuery,
+    ) -> Result<NetBoxResponse<NetBoxVlan>, AppError> {
+        VlanOperations::list_vlans(self.inner().as_ref(), site_id, group_id, query).await
+    }
+
+    async fn create_vlan(&self, request: CreateVlanRequest) -> Result<NetBoxVlan, AppError> {
+        self.inner().create_vlan(request).await
+    }
+
+    async fn update_vlan(&self, id: i32, request: UpdateVlanRequest) -> Result<NetBoxVlan, AppError> {
+        self.inner().update_vlan(id, request).await
+    }
+
+    async fn delete_vlan(&self, id: i32) -> Result<(), AppError> {
+        self.inner().delete_vlan(id).await
+    }
+}
+
+#[async_trait]
+impl VirtualizationOperations for NetBoxClient {
+    async fn get_cluster(&self, id: i32) -> Result<NetBoxCluster, AppError> {
+        Ok(self.get_cluster(id).await?)
+    }
+
+    async fn list_clusters(
+        &self,
+        site_id: Option<i32>,
+        query: &ListQuery,
+    ) -> Result<NetBoxResponse<NetBoxCluster>, AppError> {
+        Ok(self
+            .list_clusters_with_filters(site_id, query.tenant_id, query.limit, query.offset, &query.filters)
+            .await?)
+    }
+
+    async fn create_cluster(&self, request: CreateClusterRequest) -> Result<NetBoxCluster, AppError> {
+        Ok(self.create_cluster(request).await?)
+    }
+
+    async fn update_cluster(&self, id: i32, request: UpdateClusterRequest) -> Result<NetBoxCluster, AppError> {
+        Ok(self.update_cluster(id, request).await?)
+    }
+
+    async fn delete_cluster(&self, id: i32) -> Result<(), AppError> {
+        Ok(self.delete_cluster(id).await?)
+    }
+
+    async fn get_virtual_machine(&self, id: i32) -> Result<NetBoxVirtualMachine, AppError> {
+        Ok(self.get_virtual_machine(id).await?)
+    }
+
+    async fn get_virtual_machines_by_ids(
+        &self,
+        ids: &[i32],
+    ) -> Result<Vec<(i32, Option<NetBoxVirtualMachine>)>, AppError> {
+        Ok(self.get_virtual_machines_by_ids(ids).await?)
+    }
+
+    async fn list_virtual_machines(
+        &self,
+        site_id: Option<i32>,
+        cluster_id: Option<i32>,
+        query: &ListQuery,
+    ) -> Result<NetBoxResponse<NetBoxVirtualMachine>, AppError> {
+        Ok(self
+            .list_virtual_machines_with_filters(
+                site_id,
+                cluster_id,
+                query.tenant_id,
+                query.limit,
+                query.offset,
+                &query.filters,
+            )
+            .await?)
+    }
+
+    async fn create_virtual_machine(
+        &self,
+        request: CreateVirtualMachineRequest,
+    ) -> Result<NetBoxVirtualMachine, AppError> {
+        Ok(self.create_virtual_machine(request).await?)
+    }
+
+    async fn update_virtual_machine(
+        &self,
+        id: i32,
+        request: UpdateVirtualMachineRequest,
+    ) -> Result<NetBoxVirtualMachine, AppError> {
+        Ok(self.update_virtual_machine(id, request).await?)
+    }
+
+    async fn delete_virtual_machine(&self, id: i32) -> Result<(), AppError> {
+        Ok(self.delete_virtual_machine(id).await?)
+    }
+}
+
+#[async_trait]
+impl VirtualizationOperations for ResilientNetBoxClient {
+    async fn get_cluster(&self, id: i32) -> Result<NetBoxCluster, AppError> {
+        self.get_cluster(id).await
+    }
+
+    async fn list_clusters(
+        &self,
+        site_id: Option<i32>,
+        query: &ListQuery,
+    ) -> Result<NetBoxResponse<NetBoxCluster>, AppError> {
+        self.list_clusters_with_filters(site_id, query.tenant_id, query.limit, query.offset, &query.filters)
+            .await
+    }
+
+    async fn create_cluster(&self, request: CreateClusterRequest) -> Result<NetBoxCluster, AppError> {
+        self.create_cluster(request).await
+    }
+
+    async fn update_cluster(&self, id: i32, request: UpdateClusterRequest) -> Result<NetBoxCluster, AppError> {
+        self.update_cluster(id, request).await
+    }
+
+    async fn delete_cluster(&self, id: i32) -> Result<(), AppError> {
+        self.delete_cluster(id).await
+    }
+
+    async fn get_virtual_machine(&self, id: i32) -> Result<NetBoxVirtualMachine, AppError> {
+        self.get_virtual_machine(id).await
+    }
+
+    async fn get_virtual_machines_by_ids(
+        &self,
+        ids: &[i32],
+    ) -> Result<Vec<(i32, Option<NetBoxVirtualMachine>)>, AppError> {
+        self.get_virtual_machines_by_ids(ids).await
+    }
+
+    async fn list_virtual_machines(
+        &self,
+        site_id: Option<i32>,
+        cluster_id: Option<i32>,
+        query: &ListQuery,
+    ) -> Result<NetBoxResponse<NetBoxVirtualMachine>, AppError> {
+        self.list_virtual_machines_with_filters(
+            site_id,
+            cluster_id,
+            query.tenant_id,
+            query.limit,
+            query.offset,
+            &query.filters,
+        )
+        .await
+    }
+
+    async fn create_virtual_machine(
+        &self,
+        request: CreateVirtualMachineRequest,
+    ) -> Result<NetBoxVirtualMachine, AppError> {
+        self.create_virtual_machine(request).await
+    }
+
+    async fn update_virtual_machine(
+        &self,
+        id: i32,
+        request: UpdateVirtualMachineRequest,
+    ) -> Result<NetBoxVirtualMachine, AppError> {
+        self.update_virtual_machine(id, request).await
+    }
+
+    async fn delete_virtual_machine(&self, id: i32) -> Result<(), AppError> {
+        self.delete_virtual_machine(id).await
+    }
+}
+
+/// Clusters and VMs aren't cached, so the cached client passes them straight through
+#[async_trait]
+impl VirtualizationOperations for CachedNetBoxClient {
+    async fn get_cluster(&self, id: i32) -> Result<NetBoxCluster, AppError> {
+        self.inner().get_cluster(id).await
+    }
+
+    async fn list_clusters(
+        &self,
+        site_id: Option<i32>,
+        query: &ListQuery,
+    ) -> Result<NetBoxResponse<NetBoxCluster>, AppError> {
+        VirtualizationOperations::list_clusters(self.inner().as_ref(), site_id, query).await
+    }
+
+    async fn create_cluster(&self, request: CreateClusterRequest) -> Result<NetBoxCluster, AppError> {
+        self.inner().create_cluster(request).await
+    }
+
+    async fn update_cluster(&self, id: i32, request: UpdateClusterRequest) -> Result<NetBoxCluster, AppError> {
+        self.inner().update_cluster(id, request).await
+    }
+
+    async fn delete_cluster(&self, id: i32) -> Result<(), AppError> {
+        self.inner().delete_cluster(id).await
+    }
+
+    async fn get_virtual_machine(&self, id: i32) -> Result<NetBoxVirtualMachine, AppError> {
+        self.inner().get_virtual_machine(id).await
+    }
+
+    async fn get_virtual_machines_by_ids(
+        &self,
+        ids: &[i32],
+    ) -> Result<Vec<(i32, Option<NetBoxVirtualMachine>)>, AppError> {
+        self.inner().get_virtual_machines_by_ids(ids).await
+    }
+
+    async fn list_virtual_machines(
+        &self,
+        site_id: Option<i32>,
+        cluster_id: Option<i32>,
+        query: &ListQuery,
+    ) -> Result<NetBoxResponse<NetBoxVirtualMachine>, AppError> {
+        VirtualizationOperations::list_virtual_machines(self.inner().as_ref(), site_id, cluster_id, query).await
+    }
+
+    async fn create_virtual_machine(
+        &self,
+        request: CreateVirtualMachineRequest,
+    ) -> Result<NetBoxVirtualMachine, AppError> {
+        self.inner().create_virtual_machine(request).await
+    }
+
+    async fn update_virtual_machine(
+        &self,
+        id: i32,
+        request: UpdateVirtualMachineRequest,
+    ) -> Result<NetBoxVirtualMachine, AppError> {
+        self.inner().update_virtual_machine(id, request).await
+    }
+
+    async fn delete_virtual_machine(&self, id: i32) -> Result<(), AppError> {
+        self.inner().delete_virtual_machine(id).await
+    }
+}
+
+#[async_trait]
+impl CapacityOperations for NetBoxClient {
+    async fn list_racks(&self, site_id: Option<i32>, query: &ListQuery) -> Result<NetBoxResponse<NetBoxRack>, AppError> {
+        Ok(self
+            .list_racks_with_filters(site_id, query.tenant_id, query.limit, query.offset, &query.filters)
+            .await?)
+    }
+
+    async fn list_prefixes(
+        &self,
+        site_id: Option<i32>,
+        query: &ListQuery,
+    ) -> Result<NetBoxResponse<NetBoxPrefix>, AppError> {
+        Ok(self
+            .list_prefixes_with_filters(site_id, query.tenant_id, query.limit, query.offset, &query.filters)
+            .await?)
+    }
+}
+
+#[async_trait]
+impl CapacityOperations for ResilientNetBoxClient {
+    async fn list_racks(&self, site_id: Option<i32>, query: &ListQuery) -> Result<NetBoxResponse<NetBoxRack>, AppError> {
+        self.list_racks_with_filters(site_id, query.tenant_id, query.limit, query.offset, &query.filters)
+            .await
+    }
+
+    async fn list_prefixes(
+        &self,
+        site_id: Option<i32>,
+        query: &ListQuery,
+    ) -> Result<NetBoxResponse<NetBoxPrefix>, AppError> {
+        self.list_prefixes_with_filters(site_id, query.tenant_id, query.limit, query.offset, &query.filters)
+            .await
+    }
+}
+
+/// Racks and prefixes aren't cached, so the cached client passes them straight through
+#[async_trait]
+impl CapacityOperations for CachedNetBoxClient {
+    async fn list_racks(&self, site_id: Option<i32>, query: &ListQuery) -> Result<NetBoxResponse<NetBoxRack>, AppError> {
+        CapacityOperations::list_racks(self.inner().as_ref(), site_id, query).await
+    }
+
+    async fn list_prefixes(
+        &self,
+        site_id: Option<i32>,
+        query: &ListQuery,
+    ) -> Result<NetBoxResponse<NetBoxPrefix>, AppError> {
+        CapacityOperations::list_prefixes(self.inner().as_ref(), site_id, query).await
+    }
+}
+
+#[async_trait]
+impl ReportOperations for NetBoxClient {
+    async fn sites_with_device_counts(&self, tenant_id: Option<i32>) -> Result<Vec<SiteDeviceCount>, AppError> {
+        Ok(self.sites_with_device_counts(tenant_id).await?)
+    }
+
+    async fn devices_with_interfaces(
+        &self,
+        site_id: i32,
+        tenant_id: Option<i32>,
+    ) -> Result<Vec<DeviceInterfaces>, AppError> {
+        Ok(self.devices_with_interfaces(site_id, tenant_id).await?)
+    }
+}
+
+#[async_trait]
+impl ReportOperations for ResilientNetBoxClient {
+    async fn sites_with_device_counts(&self, tenant_id: Option<i32>) -> Result<Vec<SiteDeviceCount>, AppError> {
+        self.sites_with_device_counts(tenant_id).await
+    }
+
+    async fn devices_with_interfaces(
+        &self,
+        site_id: i32,
+        tenant_id: Option<i32>,
+    ) -> Result<Vec<DeviceInterfaces>, AppError> {
+        self.devices_with_interfaces(site_id, tenant_id).await
+    }
+}
+
+/// Reports aren't cached, so the cached client passes them straight through
+#[async_trait]
+impl ReportOperations for CachedNetBoxClient {
+    async fn sites_with_device_counts(&self, tenant_id: Option<i32>) -> Result<Vec<SiteDeviceCount>, AppError> {
+        self.inner().sites_with_device_counts(tenant_id).await
+    }
+
+    async fn devices_with_interfaces(
+        &self,
+        site_id: i32,
+        tenant_id: Option<i32>,
+    ) -> Result<Vec<DeviceInterfaces>, AppError> {
+        self.inner().devices_with_interfaces(site_id, tenant_id).await
+    }
+}
+
+#[async_trait]
+impl ChangelogOperations for NetBoxClient {
+    async fn get_object_changes(
+        &self,
+        object_type: &str,
+        object_id: i32,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<NetBoxResponse<NetBoxObjectChange>, AppError> {
+        Ok(self.get_object_changes(object_type, object_id, limit, offset).await?)
+    }
+}
+
+#[async_trait]
+impl ChangelogOperations for ResilientNetBoxClient {
+    async fn get_object_changes(
+        &self,
+        object_type: &str,
+        object_id: i32,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<NetBoxResponse<NetBoxObjectChange>, AppError> {
+        self.get_object_changes(object_type, object_id, limit, offset).await
+    }
+}
+
+/// The changelog isn't cached, so the cached client passes it straight through
+#[async_trait]
+impl ChangelogOperations for CachedNetBoxClient {
+    async fn get_object_changes(
+        &self,
+        object_type: &str,
+        object_id: i32,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<NetBoxResponse<NetBoxObjectChange>, AppError> {
+        self.inner().get_object_changes(object_type, object_id, limit, offset).await
+    }
+}
@@ -1,12 +1,109 @@
+use crate::cache::Cache;
 use crate::error::AppError;
-use crate::netbox::client::NetBoxClient;
+use crate::netbox::client::{Conditional, NetBoxClient, Validator};
+use crate::netbox::custom_fields::CustomFieldSchema;
+use crate::netbox::error::NetBoxError;
+use crate::netbox::graphql::{DeviceInterfaces, SiteDeviceCount};
 use crate::netbox::models::*;
+use crate::netbox::token_scope::TokenScope;
+use crate::resilience::bulkhead::{Bulkhead, BulkheadConfig};
+use crate::resilience::chaos::ChaosInjector;
 use crate::resilience::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig};
-use crate::resilience::degradation::DegradationCache;
+use crate::resilience::degradation::{
+    degrade_site_list_retrieval, degrade_site_retrieval, DegradationCache, DegradationConfig,
+};
+use crate::resilience::hedging::hedged_request;
 use crate::resilience::metrics::ApiMetrics;
+use crate::resilience::rate_limit::{RateLimited, RateLimiter};
+use crate::resilience::recovery::{ProbeOutcome, RecoveryProbeConfig, RecoveryProbeStatus, RecoveryProber};
 use crate::resilience::retry::{RetryConfig, retry_with_backoff};
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet, VecDeque};
+use parking_lot::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tracing::warn;
+use std::time::Duration;
+use tokio::sync::OwnedSemaphorePermit;
+use tracing::{error, info, warn};
+
+/// Deadlines applied at the resilience layer, independent of the HTTP client's
+///
+/// A call takes at most `total`: up to `max_attempts` attempts of at most
+/// `per_attempt` each, plus the backoff between them, cut off at `total`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeoutConfig {
+    /// Longest a single attempt may take before it counts as a retryable failure
+    pub per_attempt: Duration,
+    /// Longest a call may take across all its attempts and backoff
+    pub total: Duration,
+}
+
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        Self {
+            per_attempt: Duration::from_secs(30),
+            total: Duration::from_secs(90),
+        }
+    }
+}
+
+impl TimeoutConfig {
+    /// Load from NETBOX_OPERATION_TIMEOUT_SECS and NETBOX_TOTAL_TIMEOUT_SECS
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let secs = |name: &str, default: Duration| {
+            std::env::var(name)
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(default)
+        };
+        Self {
+            per_attempt: secs("NETBOX_OPERATION_TIMEOUT_SECS", defaults.per_attempt),
+            total: secs("NETBOX_TOTAL_TIMEOUT_SECS", defaults.total),
+        }
+    }
+}
+
+/// Run one attempt, failing it with a retryable timeout after `limit`
+async fn attempt_with_timeout<T>(
+    limit: Duration,
+    attempt: impl std::future::Future<Output = Result<T, NetBoxError>>,
+    metrics: Arc<ApiMetrics>,
+) -> Result<T, NetBoxError> {
+    match tokio::time::timeout(limit, attempt).await {
+        Ok(Err(unavailable @ NetBoxError::ServiceUnavailable { .. })) => {
+            metrics.record_maintenance_response();
+            Err(unavailable)
+        }
+        Ok(result) => result,
+        Err(_) => {
+            metrics.record_timeout();
+            warn!("NetBox attempt timed out after {:?}", limit);
+            Err(NetBoxError::Timeout(limit))
+        }
+    }
+}
+
+/// Whether a failed write may still have been applied by NetBox
+///
+/// A connection that failed to open never carried the request; a timeout or
+/// a connection dropped mid-exchange may have lost only the response.
+fn is_ambiguous(error: &NetBoxError) -> bool {
+    match error {
+        NetBoxError::Timeout(_) => true,
+        NetBoxError::NetworkError(e) => !e.is_connect() && !e.is_builder(),
+        _ => false,
+    }
+}
+
+/// Whether a call reads or writes, which picks its bulkhead and retry policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CallKind {
+    Read,
+    Write,
+}
 
 /// Resilient NetBox client with retry, circuit breaker, metrics, and graceful degradation
 pub struct ResilientNetBoxClient {
@@ -14,9 +111,39 @@ pub struct ResilientNetBoxClient {
     circuit_breaker: Arc<CircuitBreaker>,
     metrics: Arc<ApiMetrics>,
     cache: Arc<DegradationCache>,
-    retry_config: RetryConfig,
+    /// Retries of reads, which are safe to repeat
+    read_retry: RetryConfig,
+    /// Retries of creates, updates and deletes
+    write_retry: RetryConfig,
+    degradation: DegradationConfig,
+    read_bulkhead: Arc<Bulkhead>,
+    write_bulkhead: Arc<Bulkhead>,
+    hedge_delay: Option<std::time::Duration>,
+    timeouts: TimeoutConfig,
+    /// Background probing of `/api/status/` while the circuit is open
+    recovery: Option<RecoveryProber>,
+    /// When NetBox first rejected our credentials; `None` while they work
+    auth_failed_at: RwLock<Option<DateTime<Utc>>>,
+    /// Whether each failed call since the last success looked like maintenance, newest last
+    recent_failures: RwLock<VecDeque<bool>>,
+    /// What the token may do, once probed; writes are refused while it's read-only
+    token_scope: RwLock<Option<TokenScope>>,
+    /// Custom field definitions, refetched once they expire
+    custom_field_schema: Cache<(), Arc<CustomFieldSchema>>,
+    /// Names of tags seen in NetBox, so orders reusing them skip the tag listing
+    known_tags: RwLock<HashSet<String>>,
+    /// Faults injected into attempts, for resilience testing
+    chaos: Option<Arc<ChaosInjector>>,
+    /// Request budget of the NetBox deployment, shared by every client calling it
+    rate_limiter: Option<Arc<RateLimiter>>,
 }
 
+/// Failed calls [`ResilientNetBoxClient::maintenance_suspected`] looks back over
+const RECENT_FAILURES: usize = 10;
+
+/// How long a fetched custom field schema is used before it is fetched again
+pub const DEFAULT_CUSTOM_FIELD_SCHEMA_TTL: Duration = Duration::from_secs(300);
+
 impl ResilientNetBoxClient {
     /// Create a new resilient client with default configuration
     pub fn new(client: Arc<NetBoxClient>) -> Self {
@@ -25,76 +152,389 @@ impl ResilientNetBoxClient {
             circuit_breaker: Arc::new(CircuitBreaker::new()),
             metrics: Arc::new(ApiMetrics::new()),
             cache: Arc::new(DegradationCache::default()),
-            retry_config: RetryConfig::default(),
+            read_retry: RetryConfig::default(),
+            write_retry: RetryConfig::for_writes(),
+            degradation: DegradationConfig::default(),
+            read_bulkhead: Arc::new(Bulkhead::new()),
+            write_bulkhead: Arc::new(Bulkhead::with_config(BulkheadConfig::default_write())),
+            hedge_delay: None,
+            timeouts: TimeoutConfig::default(),
+            recovery: None,
+            auth_failed_at: RwLock::new(None),
+            recent_failures: RwLock::new(VecDeque::new()),
+            token_scope: RwLock::new(None),
+            custom_field_schema: Cache::new(DEFAULT_CUSTOM_FIELD_SCHEMA_TTL),
+            known_tags: RwLock::new(HashSet::new()),
+            chaos: None,
+            rate_limiter: None,
         }
     }
 
     /// Create a new resilient client with custom configuration
+    ///
+    /// `retry_config` applies to reads; writes keep [`RetryConfig::for_writes`]
+    /// unless set with [`Self::with_write_retry`].
     pub fn with_config(
         client: Arc<NetBoxClient>,
         circuit_breaker_config: CircuitBreakerConfig,
         retry_config: RetryConfig,
         cache_ttl: std::time::Duration,
+        degradation: DegradationConfig,
     ) -> Self {
         Self {
             client,
             circuit_breaker: Arc::new(CircuitBreaker::with_config(circuit_breaker_config)),
             metrics: Arc::new(ApiMetrics::new()),
             cache: Arc::new(DegradationCache::new(cache_ttl)),
-            retry_config,
+            read_retry: retry_config,
+            write_retry: RetryConfig::for_writes(),
+            degradation,
+            read_bulkhead: Arc::new(Bulkhead::new()),
+            write_bulkhead: Arc::new(Bulkhead::with_config(BulkheadConfig::default_write())),
+            hedge_delay: None,
+            timeouts: TimeoutConfig::default(),
+            recovery: None,
+            auth_failed_at: RwLock::new(None),
+            recent_failures: RwLock::new(VecDeque::new()),
+            token_scope: RwLock::new(None),
+            custom_field_schema: Cache::new(DEFAULT_CUSTOM_FIELD_SCHEMA_TTL),
+            known_tags: RwLock::new(HashSet::new()),
+            chaos: None,
+            rate_limiter: None,
         }
     }
 
-    /// Get a site with resilience features
-    pub async fn get_site(&self, id: i32) -> Result<NetBoxSite, AppError> {
-        // Check circuit breaker
-        if !self.circuit_breaker.allow_request() {
-            self.metrics.record_circuit_breaker_rejection();
-            warn!("Circuit breaker is open, attempting graceful degradation for site {}", id);
-            
-            // Try graceful degradation
-            if let Some(cached_site) = self.cache.get_site(id) {
-                return Ok(cached_site);
-            }
-            return Err(AppError::Internal(anyhow::anyhow!("Service unavailable (circuit breaker open)")));
-        }
+    /// Keep fetched custom field schemas for `ttl` instead of the default five minutes
+    pub fn with_custom_field_schema_ttl(mut self, ttl: Duration) -> Self {
+        self.custom_field_schema = Cache::new(ttl);
+        self
+    }
 
-        let start_time = self.metrics.record_request_start();
+    /// Set separate concurrency limits for read and write calls
+    pub fn with_bulkheads(mut self, read: BulkheadConfig, write: BulkheadConfig) -> Self {
+        self.read_bulkhead = Arc::new(Bulkhead::with_config(read));
+        self.write_bulkhead = Arc::new(Bulkhead::with_config(write));
+        self
+    }
 
-        // Execute with retry
-        let result = retry_with_backoff(&self.retry_config, || {
-            let client = Arc::clone(&self.client);
-            let id = id;
+    /// Draw read and write slots from bulkheads shared with other clients
+    ///
+    /// Clients of the same NetBox deployment share them, so that the limits
+    /// bound the calls the deployment sees, whatever the number of clients.
+    pub fn with_shared_bulkheads(mut self, read: Arc<Bulkhead>, write: Arc<Bulkhead>) -> Self {
+        self.read_bulkhead = read;
+        self.write_bulkhead = write;
+        self
+    }
+
+    /// Retry creates, updates and deletes with `retry` instead of the write defaults
+    ///
+    /// One attempt disables write retries.
+    pub fn with_write_retry(mut self, retry: RetryConfig) -> Self {
+        self.write_retry = retry;
+        self
+    }
+
+    /// Hedge reads that have not completed within `delay` (e.g. NetBox p95 latency)
+    ///
+    /// A hedge takes its own read bulkhead slot and is skipped when none is
+    /// free. Writes are never hedged.
+    pub fn with_hedging(mut self, delay: std::time::Duration) -> Self {
+        self.hedge_delay = Some(delay);
+        self
+    }
+
+    /// Bound each attempt and each whole call (including retries) in time
+    pub fn with_timeouts(mut self, timeouts: TimeoutConfig) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Inject the faults of `chaos`'s running experiment into attempts
+    ///
+    /// Faults apply per attempt, inside retries and the circuit breaker, and
+    /// an injected error never reaches NetBox.
+    pub fn with_chaos(mut self, chaos: Arc<ChaosInjector>) -> Self {
+        self.chaos = Some(chaos);
+        self
+    }
+
+    /// Pace calls and retry attempts with `limiter`
+    ///
+    /// Pass every client of one NetBox deployment the same limiter so that,
+    /// whatever token they call with, together they stay within its budget.
+    /// Reads that would wait longer than the limiter allows are degraded like
+    /// calls rejected by an open circuit; writes fail with `AppError::RateLimited`.
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Probe NetBox in the background while the circuit is open, see [`Self::poll_recovery`]
+    pub fn with_recovery_probe(mut self, config: RecoveryProbeConfig) -> Self {
+        self.recovery = Some(RecoveryProber::new(
+            config,
+            Arc::clone(&self.circuit_breaker),
+            Arc::clone(&self.metrics),
+        ));
+        self
+    }
+
+    /// Probe `/api/status/` if the circuit is open and a probe is due
+    ///
+    /// Meant to be called periodically by a background task; returns `None`
+    /// when recovery probing isn't configured. Rejected credentials still
+    /// mean NetBox is reachable, so they count as a successful probe.
+    pub async fn poll_recovery(&self) -> Option<ProbeOutcome> {
+        let prober = self.recovery.as_ref()?;
+        let per_attempt = self.timeouts.per_attempt;
+        let outcome = prober
+            .poll(|| async {
+                match tokio::time::timeout(per_attempt, self.client.get_status()).await {
+                    Ok(Ok(_)) | Ok(Err(NetBoxError::AuthenticationError(_))) => true,
+                    Ok(Err(_)) | Err(_) => false,
+                }
+            })
+            .await;
+        Some(outcome)
+    }
+
+    /// Recovery probing progress, when probing is configured
+    pub fn recovery_probe_status(&self) -> Option<RecoveryProbeStatus> {
+        self.recovery.as_ref().map(RecoveryProber::status)
+    }
+
+    /// Run `operation` with retries, timing out each attempt and the call as a whole
+    ///
+    /// A timed-out attempt is a retryable `NetBoxError::Timeout`; running out of
+    /// total time ends the call with the same error. Either way the caller
+    /// records it as a failure, so timeouts count toward the circuit breaker.
+    async fn call_with_retry<T, F>(&self, retry: &RetryConfig, operation: F) -> Result<T, NetBoxError>
+    where
+        T: 'static,
+        F: Fn() -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<T, NetBoxError>> + Send>>,
+    {
+        let per_attempt = self.timeouts.per_attempt;
+        let retrying = AtomicBool::new(false);
+        let attempts = retry_with_backoff(retry, || {
+            let attempt = attempt_with_timeout(per_attempt, operation(), Arc::clone(&self.metrics));
+            // The first attempt took its token before the call started
+            let limiter = self.rate_limiter.clone().filter(|_| retrying.swap(true, Ordering::SeqCst));
+            let metrics = Arc::clone(&self.metrics);
             Box::pin(async move {
-                client.get_site(id).await
+                if let Some(limiter) = limiter {
+                    let waited = limiter.acquire_unbounded().await;
+                    if !waited.is_zero() {
+                        metrics.record_throttled(waited);
+                    }
+                }
+                attempt.await
             })
-        }).await;
+        });
+        match tokio::time::timeout(self.timeouts.total, attempts).await {
+            Ok(result) => result,
+            Err(_) => {
+                self.metrics.record_deadline_exceeded();
+                warn!("NetBox call exceeded its {:?} deadline", self.timeouts.total);
+                Err(NetBoxError::Timeout(self.timeouts.total))
+            }
+        }
+    }
 
-        match result {
-            Ok(site) => {
-                self.circuit_breaker.record_success();
-                self.metrics.record_success(start_time);
-                // Cache the result
-                if let Some(site_id) = site.id {
-                    self.cache.cache_site(site_id, site.clone());
+    /// Acquire a bulkhead slot, recording a rejection if none frees up in time
+    async fn acquire_slot(&self, bulkhead: &Bulkhead) -> Result<OwnedSemaphorePermit, AppError> {
+        bulkhead.acquire().await.map_err(|full| {
+            self.metrics.record_bulkhead_rejection();
+            AppError::Overloaded(full.to_string())
+        })
+    }
+
+    /// Wait for the rate limiter to let a call through, if one is configured
+    async fn throttle(&self, op_name: &str) -> Result<(), RateLimited> {
+        let Some(ref limiter) = self.rate_limiter else {
+            return Ok(());
+        };
+        match limiter.acquire().await {
+            Ok(waited) => {
+                if !waited.is_zero() {
+                    self.metrics.record_throttled(waited);
                 }
-                Ok(site)
+                Ok(())
+            }
+            Err(limited) => {
+                self.metrics.record_rate_limit_rejection();
+                warn!("Rate limit holding back NetBox {}: {}", op_name, limited);
+                Err(limited)
+            }
+        }
+    }
+
+    /// Record a successful call: NetBox is reachable and accepts our credentials
+    fn record_success(&self) {
+        self.circuit_breaker.record_success();
+        self.mark_credentials_valid();
+        self.recent_failures.write().clear();
+    }
+
+    /// Record a failed call
+    ///
+    /// Rejected credentials don't count toward the circuit breaker: NetBox is
+    /// healthy, and the fix is a new token, not backing off. They flag the
+    /// credentials as invalid instead. A maintenance page opens the circuit
+    /// right away: NetBox won't be back within the next few calls.
+    fn record_failure(&self, error: &NetBoxError) {
+        let maintenance = matches!(error, NetBoxError::ServiceUnavailable { .. });
+        {
+            let mut recent = self.recent_failures.write();
+            if recent.len() == RECENT_FAILURES {
+                recent.pop_front();
+            }
+            recent.push_back(maintenance);
+        }
+        match error {
+            NetBoxError::AuthenticationError(message) => self.mark_credentials_invalid(message),
+            NetBoxError::ServiceUnavailable { .. } => self.circuit_breaker.trip(),
+            _ => self.circuit_breaker.record_failure(),
+        }
+    }
+
+    /// Whether most calls failed since the last success got maintenance pages
+    pub fn maintenance_suspected(&self) -> bool {
+        let recent = self.recent_failures.read();
+        recent.iter().filter(|maintenance| **maintenance).count() * 2 > recent.len()
+    }
+
+    fn mark_credentials_invalid(&self, message: &str) {
+        let mut failed_at = self.auth_failed_at.write();
+        if failed_at.is_none() {
+            error!("NetBox rejected the configured credentials: {}", message);
+            *failed_at = Some(Utc::now());
+        }
+    }
+
+    fn mark_credentials_valid(&self) {
+        if self.auth_failed_at.write().take().is_some() {
+            info!("NetBox accepts the configured credentials again");
+        }
+    }
+
+    /// When NetBox first rejected our credentials, if they are currently failing
+    pub fn auth_failed_at(&self) -> Option<DateTime<Utc>> {
+        *self.auth_failed_at.read()
+    }
+
+    /// Connection pool settings of the underlying HTTP client
+    pub fn pool_config(&self) -> &crate::netbox::transport::PoolConfig {
+        self.client.pool_config()
+    }
+
+    /// Detected NetBox version, probing `/api/status/` on first use
+    pub async fn netbox_version(&self) -> Option<crate::netbox::version::NetBoxVersion> {
+        self.client.version().await
+    }
+
+    /// Probe NetBox's status endpoint to re-validate the credentials
+    ///
+    /// Bypasses the circuit breaker and retries so a periodic check always
+    /// reaches NetBox. Only an authentication result changes the auth flag;
+    /// other failures leave it as is.
+    pub async fn check_credentials(&self) -> Result<(), AppError> {
+        match self.client.get_status().await {
+            Ok(_) => {
+                self.mark_credentials_valid();
+                Ok(())
             }
             Err(e) => {
-                self.circuit_breaker.record_failure();
-                self.metrics.record_failure(start_time);
-                
-                // Try graceful degradation
-                if let Some(cached_site) = self.cache.get_site(id) {
-                    warn!("Using cached site {} due to error: {}", id, e);
-                    return Ok(cached_site);
+                if let NetBoxError::AuthenticationError(ref message) = e {
+                    self.mark_credentials_invalid(message);
                 }
-                
-                Err(AppError::Internal(anyhow::Error::from(e)))
+                Err(AppError::from(e))
             }
         }
     }
 
+    /// Probe whether the token may write, and refuse writes from now on if it can't
+    ///
+    /// Like [`Self::check_credentials`], this bypasses the circuit breaker and retries.
+    pub async fn probe_token_scope(&self) -> TokenScope {
+        let scope = self.client.token_scope().await;
+        match scope {
+            TokenScope::ReadOnly => error!("The NetBox token is read-only; orders will be refused until it may write"),
+            TokenScope::ReadWrite => info!("The NetBox token may read and write"),
+            TokenScope::Undetermined => warn!("Could not tell whether the NetBox token may write"),
+        }
+        *self.token_scope.write() = Some(scope);
+        scope
+    }
+
+    /// What the token may do, if [`Self::probe_token_scope`] ran
+    pub fn token_scope(&self) -> Option<TokenScope> {
+        *self.token_scope.read()
+    }
+
+    /// Cache key used for degraded site list lookups
+    fn site_list_cache_key(tenant_id: Option<i32>, limit: Option<u32>, offset: Option<u32>) -> String {
+        format!(
+            "sites:tenant:{}:limit:{}:offset:{}",
+            tenant_id.unwrap_or(0),
+            limit.unwrap_or(0),
+            offset.unwrap_or(0)
+        )
+    }
+
+    /// Apply the configured get strategy, surfacing `error` if it yields nothing
+    fn degrade_get_site(&self, id: i32, error: AppError) -> Result<NetBoxSite, AppError> {
+        match degrade_site_retrieval(&self.cache, id, self.degradation.get_strategy) {
+            Ok(Some(site)) => Ok(site),
+            _ => Err(error),
+        }
+    }
+
+    /// Apply the configured list strategy, surfacing `error` if it yields nothing
+    fn degrade_list_sites(
+        &self,
+        cache_key: &str,
+        error: AppError,
+    ) -> Result<NetBoxResponse<NetBoxSite>, AppError> {
+        match degrade_site_list_retrieval(&self.cache, cache_key, self.degradation.list_strategy) {
+            Ok(Some(sites)) => Ok(NetBoxResponse {
+                count: Some(sites.len() as i32),
+                next: None,
+                previous: None,
+                results: Some(sites),
+                degraded: true,
+            }),
+            _ => Err(error),
+        }
+    }
+
+    /// Get a site with resilience features
+    pub async fn get_site(&self, id: i32) -> Result<NetBoxSite, AppError> {
+        self.execute(
+            "get_site",
+            CallKind::Read,
+            |error| self.degrade_get_site(id, error),
+            |site: &NetBoxSite| {
+                if let Some(site_id) = site.id {
+                    self.cache.cache_site(site_id, site.clone());
+                }
+            },
+            || {
+                let client = Arc::clone(&self.client);
+                let metrics = Arc::clone(&self.metrics);
+                let hedge_delay = self.hedge_delay;
+                let bulkhead = Arc::clone(&self.read_bulkhead);
+                Box::pin(async move {
+                    match hedge_delay {
+                        Some(delay) => hedged_request(delay, &metrics, &bulkhead, || client.get_site(id)).await,
+                        None => client.get_site(id).await,
+                    }
+                })
+            },
+        )
+        .await
+    }
+
     /// List sites with resilience features
     pub async fn list_sites(
         &self,
@@ -102,179 +542,1389 @@ impl ResilientNetBoxClient {
         limit: Option<u32>,
         offset: Option<u32>,
     ) -> Result<NetBoxResponse<NetBoxSite>, AppError> {
-        // Check circuit breaker
-        if !self.circuit_breaker.allow_request() {
-            self.metrics.record_circuit_breaker_rejection();
-            warn!("Circuit breaker is open, attempting graceful degradation for site list");
-            
-            // Try graceful degradation
-            let cache_key = format!("sites:tenant:{}:limit:{}:offset:{}", 
-                tenant_id.unwrap_or(0), limit.unwrap_or(0), offset.unwrap_or(0));
-            if let Some(cached_sites) = self.cache.get_site_list(&cache_key) {
-                return Ok(NetBoxResponse {
-                    count: Some(cached_sites.len() as i32),
-                    next: None,
-                    previous: None,
-                    results: Some(cached_sites),
-                });
-            }
-            return Err(AppError::Internal(anyhow::anyhow!("Service unavailable (circuit breaker open)")));
-        }
+        let cache_key = Self::site_list_cache_key(tenant_id, limit, offset);
+        self.execute(
+            "list_sites",
+            CallKind::Read,
+            |error| self.degrade_list_sites(&cache_key, error),
+            |response: &NetBoxResponse<NetBoxSite>| {
+                if let Some(ref sites) = response.results {
+                    self.cache.cache_site_list(cache_key.clone(), sites.clone());
+                }
+            },
+            || {
+                let client = Arc::clone(&self.client);
+                let metrics = Arc::clone(&self.metrics);
+                let hedge_delay = self.hedge_delay;
+                let bulkhead = Arc::clone(&self.read_bulkhead);
+                Box::pin(async move {
+                    match hedge_delay {
+                        Some(delay) => {
+                            hedged_request(delay, &metrics, &bulkhead, || client.list_sites(tenant_id, None, limit, offset)).await
+                        }
+                        None => client.list_sites(tenant_id, None, limit, offset).await,
+                    }
+                })
+            },
+        )
+        .await
+    }
 
-        let start_time = self.metrics.record_request_start();
+    /// Find a site by slug with resilience features
+    ///
+    /// No degraded fallback is applied: callers use this to decide whether a
+    /// write happened, so stale cache data would be misleading.
+    pub async fn find_site_by_slug(&self, slug: &str) -> Result<Option<NetBoxSite>, AppError> {
+        let client = Arc::clone(&self.client);
+        let slug = slug.to_string();
+        self.read_resource("find_site_by_slug", move || {
+            let client = Arc::clone(&client);
+            let slug = slug.clone();
+            Box::pin(async move { client.find_site_by_slug(&slug).await })
+        })
+        .await
+    }
 
-        // Execute with retry
-        let result = retry_with_backoff(&self.retry_config, || {
-            let client = Arc::clone(&self.client);
-            let tenant_id = tenant_id;
-            let limit = limit;
-            let offset = offset;
+    /// Find sites with exactly this name, optionally within a NetBox tenant
+    ///
+    /// Like `find_site_by_slug`, no degraded fallback is applied: an outage
+    /// surfaces as an error so callers can tell "no such site" from "unknown".
+    pub async fn find_sites_by_name(
+        &self,
+        tenant_id: Option<i32>,
+        name: &str,
+    ) -> Result<Vec<NetBoxSite>, AppError> {
+        let client = Arc::clone(&self.client);
+        let filter = name.to_string();
+        let response = self
+            .read_resource("find_sites_by_name", move || {
+                let client = Arc::clone(&client);
+                let name = filter.clone();
+                Box::pin(async move { client.list_sites(tenant_id, Some(&name), None, None).await })
+            })
+            .await?;
+        Ok(response
+            .results
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|site| site.name == name)
+            .collect())
+    }
+
+    /// List sites with arbitrary NetBox filters
+    ///
+    /// Unfiltered listings go through `list_sites` and its degraded fallback;
+    /// filtered results aren't cached, so an outage surfaces as an error.
+    pub async fn list_sites_with_filters(
+        &self,
+        tenant_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+        extra_filters: &HashMap<String, String>,
+    ) -> Result<NetBoxResponse<NetBoxSite>, AppError> {
+        if extra_filters.is_empty() {
+            return self.list_sites(tenant_id, limit, offset).await;
+        }
+        let client = Arc::clone(&self.client);
+        let extra_filters = extra_filters.clone();
+        self.read_resource("list_sites_with_filters", move || {
+            let client = Arc::clone(&client);
+            let extra_filters = extra_filters.clone();
             Box::pin(async move {
-                client.list_sites(tenant_id, limit, offset).await
+                client.list_sites_with_filters(tenant_id, None, limit, offset, &extra_filters).await
             })
-        }).await;
+        })
+        .await
+    }
 
-        match result {
-            Ok(response) => {
-                self.circuit_breaker.record_success();
-                self.metrics.record_success(start_time);
-                
-                // Cache the result
-                if let Some(ref sites) = response.results {
-                    let cache_key = format!("sites:tenant:{}:limit:{}:offset:{}", 
-                        tenant_id.unwrap_or(0), limit.unwrap_or(0), offset.unwrap_or(0));
-                    self.cache.cache_site_list(cache_key, sites.clone());
-                }
-                
-                Ok(response)
-            }
-            Err(e) => {
-                self.circuit_breaker.record_failure();
-                self.metrics.record_failure(start_time);
-                
-                // Try graceful degradation
-                let cache_key = format!("sites:tenant:{}:limit:{}:offset:{}", 
-                    tenant_id.unwrap_or(0), limit.unwrap_or(0), offset.unwrap_or(0));
-                if let Some(cached_sites) = self.cache.get_site_list(&cache_key) {
-                    warn!("Using cached site list due to error: {}", e);
-                    return Ok(NetBoxResponse {
-                        count: Some(cached_sites.len() as i32),
-                        next: None,
-                        previous: None,
-                        results: Some(cached_sites),
-                    });
+    /// Get a device with resilience features
+    pub async fn get_device(&self, id: i32) -> Result<NetBoxDevice, AppError> {
+        let client = Arc::clone(&self.client);
+        let metrics = Arc::clone(&self.metrics);
+        let hedge_delay = self.hedge_delay;
+        let bulkhead = Arc::clone(&self.read_bulkhead);
+        let device = self
+            .read_resource("get_device", move || {
+                let client = Arc::clone(&client);
+                let metrics = Arc::clone(&metrics);
+                let bulkhead = Arc::clone(&bulkhead);
+                Box::pin(async move {
+                    match hedge_delay {
+                        Some(delay) => hedged_request(delay, &metrics, &bulkhead, || client.get_device(id)).await,
+                        None => client.get_device(id).await,
+                    }
+                })
+            })
+            .await?;
+        self.cache.cache_device(id, device.clone());
+        Ok(device)
+    }
+
+    /// Get a site unless it still matches `validator`
+    ///
+    /// Falls back like `get_site` when NetBox is unavailable; a fallback site
+    /// comes with an empty validator, so it is fetched in full next time.
+    pub async fn get_site_with_validator(
+        &self,
+        id: i32,
+        validator: Option<&Validator>,
+    ) -> Result<Conditional<NetBoxSite>, AppError> {
+        let client = Arc::clone(&self.client);
+        let validator = validator.cloned();
+        self.execute(
+            "get_site_with_validator",
+            CallKind::Read,
+            |error| {
+                self.degrade_get_site(id, error)
+                    .map(|site| Conditional::Fresh(site, Validator::default()))
+            },
+            |result: &Conditional<NetBoxSite>| {
+                if let Conditional::Fresh(ref site, _) = result {
+                    if let Some(site_id) = site.id {
+                        self.cache.cache_site(site_id, site.clone());
+                    }
                 }
-                
-                Err(AppError::Internal(anyhow::Error::from(e)))
+            },
+            move || {
+                let client = Arc::clone(&client);
+                let validator = validator.clone();
+                Box::pin(async move {
+                    let endpoint = format!("dcim/sites/{}/", id);
+                    match client.get_with_validator(&endpoint, validator.as_ref()).await? {
+                        Conditional::NotModified => Ok(Conditional::NotModified),
+                        Conditional::Fresh(body, validator) => {
+                            let site: NetBoxSite = serde_json::from_slice(&body).map_err(NetBoxError::SerializationError)?;
+                            Ok(Conditional::Fresh(site, validator))
+                        }
+                    }
+                })
+            },
+        )
+        .await
+    }
+
+    /// Get many sites with resilience features, `None` marking IDs NetBox doesn't have
+    ///
+    /// Runs as one read even when it spans several chunks, so a retry refetches the lot.
+    pub async fn get_sites_by_ids(&self, ids: &[i32]) -> Result<Vec<(i32, Option<NetBoxSite>)>, AppError> {
+        let client = Arc::clone(&self.client);
+        let ids = ids.to_vec();
+        let sites = self
+            .read_resource("get_sites_by_ids", move || {
+                let client = Arc::clone(&client);
+                let ids = ids.clone();
+                Box::pin(async move { client.get_sites_by_ids(&ids).await })
+            })
+            .await?;
+        for (id, site) in &sites {
+            if let Some(site) = site {
+                self.cache.cache_site(*id, site.clone());
             }
         }
+        Ok(sites)
     }
 
-    /// Create a site with resilience features
-    pub async fn create_site(&self, request: CreateSiteRequest) -> Result<NetBoxSite, AppError> {
-        // Check circuit breaker
-        if !self.circuit_breaker.allow_request() {
-            self.metrics.record_circuit_breaker_rejection();
-            return Err(AppError::Internal(anyhow::anyhow!("Service unavailable (circuit breaker open)")));
+    /// Get many devices with resilience features, `None` marking IDs NetBox doesn't have
+    pub async fn get_devices_by_ids(&self, ids: &[i32]) -> Result<Vec<(i32, Option<NetBoxDevice>)>, AppError> {
+        let client = Arc::clone(&self.client);
+        let ids = ids.to_vec();
+        let devices = self
+            .read_resource("get_devices_by_ids", move || {
+                let client = Arc::clone(&client);
+                let ids = ids.clone();
+                Box::pin(async move { client.get_devices_by_ids(&ids).await })
+            })
+            .await?;
+        for (id, device) in &devices {
+            if let Some(device) = device {
+                self.cache.cache_device(*id, device.clone());
+            }
         }
+        Ok(devices)
+    }
 
-        let start_time = self.metrics.record_request_start();
+    /// Which of these device names NetBox already has, in one request
+    ///
+    /// No degraded fallback is applied; an outage surfaces as an error.
+    pub async fn find_device_names(&self, names: &[String]) -> Result<std::collections::HashSet<String>, AppError> {
+        if names.is_empty() {
+            return Ok(Default::default());
+        }
+        let client = Arc::clone(&self.client);
+        let mut params: Vec<(&'static str, String)> = names.iter().map(|name| ("name", name.clone())).collect();
+        params.push(("limit", names.len().to_string()));
+        let response: NetBoxResponse<NetBoxDevice> = self
+            .read_resource("find_device_names", move || {
+                let client = Arc::clone(&client);
+                let params = params.clone();
+                Box::pin(async move { client.list("dcim/devices/", &params).await })
+            })
+            .await?;
+        Ok(response
+            .results
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|device| device.name)
+            .filter(|name| names.contains(name))
+            .collect())
+    }
 
-        // Execute with retry
-        let result = retry_with_backoff(&self.retry_config, || {
+    /// The custom fields defined in NetBox, cached for the schema TTL
+    ///
+    /// Choice sets are only listed when a field uses one. No degraded fallback
+    /// is applied; an outage surfaces as an error.
+    pub async fn custom_field_schema(&self) -> Result<Arc<CustomFieldSchema>, AppError> {
+        if let Some(schema) = self.custom_field_schema.get(&()).await {
+            return Ok(schema);
+        }
+        let client = Arc::clone(&self.client);
+        let fields = self
+            .read_resource("list_custom_fields", move || {
+                let client = Arc::clone(&client);
+                Box::pin(async move { client.list_custom_fields().await })
+            })
+            .await?;
+        let choice_sets = if CustomFieldSchema::needs_choice_sets(&fields) {
             let client = Arc::clone(&self.client);
-            let request = request.clone();
-            Box::pin(async move {
-                client.create_site(request).await
+            self.read_resource("list_custom_field_choice_sets", move || {
+                let client = Arc::clone(&client);
+                Box::pin(async move { client.list_custom_field_choice_sets().await })
             })
-        }).await;
+            .await?
+        } else {
+            Vec::new()
+        };
+        let schema = Arc::new(CustomFieldSchema::new(fields, &choice_sets));
+        self.custom_field_schema.put((), schema.clone()).await;
+        Ok(schema)
+    }
 
-        match result {
-            Ok(site) => {
-                self.circuit_breaker.record_success();
-                self.metrics.record_success(start_time);
-                // Cache the result
-                if let Some(site_id) = site.id {
-                    self.cache.cache_site(site_id, site.clone());
-                }
-                Ok(site)
-            }
-            Err(e) => {
-                self.circuit_breaker.record_failure();
-                self.metrics.record_failure(start_time);
-                Err(AppError::Internal(anyhow::Error::from(e)))
-            }
+    /// List NetBox's tags, remembering their names
+    pub async fn list_tags(&self) -> Result<Vec<NetBoxTag>, AppError> {
+        let client = Arc::clone(&self.client);
+        let tags = self
+            .read_resource("list_tags", move || {
+                let client = Arc::clone(&client);
+                Box::pin(async move { client.list_tags().await })
+            })
+            .await?;
+        self.known_tags.write().extend(tags.iter().map(|tag| tag.name.clone()));
+        Ok(tags)
+    }
+
+    /// The tags among `names` that don't exist in NetBox
+    ///
+    /// Tags seen before aren't checked again; only when a name is new are
+    /// NetBox's tags listed. Tags deleted in NetBox are still taken to exist.
+    pub async fn missing_tags(&self, names: &[String]) -> Result<Vec<String>, AppError> {
+        let unknown = |known: &HashSet<String>| -> Vec<String> {
+            let mut seen = HashSet::new();
+            names
+                .iter()
+                .filter(|name| !known.contains(*name) && seen.insert(name.as_str()))
+                .cloned()
+                .collect()
+        };
+        if unknown(&self.known_tags.read()).is_empty() {
+            return Ok(Vec::new());
         }
+        self.list_tags().await?;
+        Ok(unknown(&self.known_tags.read()))
     }
 
-    /// Get metrics snapshot
-    pub fn metrics(&self) -> crate::resilience::MetricsSnapshot {
-        self.metrics.snapshot()
+    /// Create a tag with resilience features, remembering its name
+    ///
+    /// A retry after an ambiguous failure first looks the tag up by slug.
+    pub async fn create_tag(&self, name: &str, slug: &str, color: &str) -> Result<NetBoxTag, AppError> {
+        let create_client = Arc::clone(&self.client);
+        let lookup_client = Arc::clone(&self.client);
+        let (name, slug, color) = (name.to_string(), slug.to_string(), color.to_string());
+        let lookup_slug = slug.clone();
+        let tag = self
+            .create_resource_once(
+                "create_tag",
+                move || {
+                    let client = Arc::clone(&create_client);
+                    let (name, slug, color) = (name.clone(), slug.clone(), color.clone());
+                    Box::pin(async move { client.create_tag(&name, &slug, &color).await })
+                },
+                move || {
+                    let client = Arc::clone(&lookup_client);
+                    let slug = lookup_slug.clone();
+                    Box::pin(async move {
+                        let tags = client.list::<NetBoxTag>("extras/tags/", &[("slug", slug)]).await?;
+                        Ok(tags.results.and_then(|tags| tags.into_iter().next()))
+                    })
+                },
+            )
+            .await?;
+        self.known_tags.write().insert(tag.name.clone());
+        Ok(tag)
     }
 
-    /// Get circuit breaker state
-    pub fn circuit_breaker_state(&self) -> crate::resilience::CircuitState {
-        self.circuit_breaker.state()
+    /// A page of an object's changelog, newest first
+    pub async fn get_object_changes(
+        &self,
+        object_type: &str,
+        object_id: i32,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<NetBoxResponse<NetBoxObjectChange>, AppError> {
+        let client = Arc::clone(&self.client);
+        let object_type = object_type.to_string();
+        self.read_resource("get_object_changes", move || {
+            let client = Arc::clone(&client);
+            let object_type = object_type.clone();
+            Box::pin(async move { client.get_object_changes(&object_type, object_id, limit, offset).await })
+        })
+        .await
     }
 
-    /// Get circuit breaker failure count
-    pub fn circuit_breaker_failure_count(&self) -> u32 {
-        self.circuit_breaker.failure_count()
+    /// Sites with their device counts, via GraphQL
+    pub async fn sites_with_device_counts(&self, tenant_id: Option<i32>) -> Result<Vec<SiteDeviceCount>, AppError> {
+        let client = Arc::clone(&self.client);
+        self.read_resource("sites_with_device_counts", move || {
+            let client = Arc::clone(&client);
+            Box::pin(async move { client.sites_with_device_counts(tenant_id).await })
+        })
+        .await
     }
 
-    /// Clear cache
-    pub fn clear_cache(&self) {
-        self.cache.clear_all();
+    /// A site's devices with their interfaces, via GraphQL
+    pub async fn devices_with_interfaces(
+        &self,
+        site_id: i32,
+        tenant_id: Option<i32>,
+    ) -> Result<Vec<DeviceInterfaces>, AppError> {
+        let client = Arc::clone(&self.client);
+        self.read_resource("devices_with_interfaces", move || {
+            let client = Arc::clone(&client);
+            Box::pin(async move { client.devices_with_interfaces(site_id, tenant_id).await })
+        })
+        .await
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::config::Config;
-    use serde_json::json;
-    use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+    /// List devices with optional site, tenant, and arbitrary NetBox filters
+    pub async fn list_devices_with_filters(
+        &self,
+        site_id: Option<i32>,
+        tenant_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+        extra_filters: &HashMap<String, String>,
+    ) -> Result<NetBoxResponse<NetBoxDevice>, AppError> {
+        let client = Arc::clone(&self.client);
+        let metrics = Arc::clone(&self.metrics);
+        let hedge_delay = self.hedge_delay;
+        let bulkhead = Arc::clone(&self.read_bulkhead);
+        let extra_filters = extra_filters.clone();
+        self.read_resource("list_devices_with_filters", move || {
+            let client = Arc::clone(&client);
+            let metrics = Arc::clone(&metrics);
+            let bulkhead = Arc::clone(&bulkhead);
+            let extra_filters = extra_filters.clone();
+            Box::pin(async move {
+                let list = || client.list_devices_with_filters(site_id, tenant_id, limit, offset, &extra_filters);
+                match hedge_delay {
+                    Some(delay) => hedged_request(delay, &metrics, &bulkhead, list).await,
+                    None => list().await,
+                }
+            })
+        })
+        .await
+    }
 
-    fn create_test_config(base_url: String, token: String) -> Config {
-        Config {
-            port: 8080,
-            netbox_url: base_url,
-            netbox_token: token,
-        }
+    /// List racks, optionally only those of one site or tenant
+    pub async fn list_racks_with_filters(
+        &self,
+        site_id: Option<i32>,
+        tenant_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+        extra_filters: &HashMap<String, String>,
+    ) -> Result<NetBoxResponse<NetBoxRack>, AppError> {
+        let client = Arc::clone(&self.client);
+        let extra_filters = extra_filters.clone();
+        self.read_resource("list_racks_with_filters", move || {
+            let client = Arc::clone(&client);
+            let extra_filters = extra_filters.clone();
+            Box::pin(async move {
+                client
+                    .list_racks_with_filters(site_id, tenant_id, limit, offset, &extra_filters)
+                    .await
+            })
+        })
+        .await
     }
 
-    #[tokio::test]
-    async fn test_resilient_client_get_site_success() {
-        let mock_server = MockServer::start().await;
-        let config = create_test_config(mock_server.uri(), "test-token".to_string());
-        let client = Arc::new(NetBoxClient::new(config).unwrap());
-        let resilient_client = ResilientNetBoxClient::new(client);
+    /// List NetBox's device types with their manufacturers
+    pub async fn list_device_types(&self) -> Result<Vec<NetBoxDeviceType>, AppError> {
+        let client = Arc::clone(&self.client);
+        self.read_resource("list_device_types", move || {
+            let client = Arc::clone(&client);
+            Box::pin(async move { client.list_device_types().await })
+        })
+        .await
+    }
 
-        let site_response = json!({
-            "id": 1,
-            "name": "Test Site",
-            "status": "active"
-        });
+    /// List NetBox's device roles
+    pub async fn list_device_roles(&self) -> Result<Vec<NetBoxDeviceRole>, AppError> {
+        let client = Arc::clone(&self.client);
+        self.read_resource("list_device_roles", move || {
+            let client = Arc::clone(&client);
+            Box::pin(async move { client.list_device_roles().await })
+        })
+        .await
+    }
 
-        Mock::given(method("GET"))
-            .and(path("/api/dcim/sites/1/"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(&site_response))
-            .mount(&mock_server)
-            .await;
+    /// Find a device's interface by its exact name
+    pub async fn find_interface(&self, device_id: i32, name: &str) -> Result<Option<NetBoxInterface>, AppError> {
+        let client = Arc::clone(&self.client);
+        let name = name.to_string();
+        self.read_resource("find_interface", move || {
+            let client = Arc::clone(&client);
+            let name = name.clone();
+            Box::pin(async move { client.find_interface(device_id, &name).await })
+        })
+        .await
+    }
 
-        let result = resilient_client.get_site(1).await;
-        assert!(result.is_ok());
-        
-        let metrics = resilient_client.metrics();
-        assert_eq!(metrics.total_requests, 1);
-        assert_eq!(metrics.successful_requests, 1);
-        assert_eq!(metrics.success_rate, 1.0);
+    /// List interfaces, optionally only those of one device
+    pub async fn list_interfaces(
+        &self,
+        device_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<NetBoxResponse<NetBoxInterface>, AppError> {
+        let client = Arc::clone(&self.client);
+        self.read_resource("list_interfaces", move || {
+            let client = Arc::clone(&client);
+            Box::pin(async move { client.list_interfaces(device_id, limit, offset).await })
+        })
+        .await
     }
 
-    #[tokio::test]
-    async fn test_resilient_client_retries_on_failure() {
-        let mock_server = MockServer::start().await;
-        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+    /// List cables, optionally only those attached to one device
+    pub async fn list_cables(
+        &self,
+        device_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<NetBoxResponse<NetBoxCable>, AppError> {
+        let client = Arc::clone(&self.client);
+        self.read_resource("list_cables", move || {
+            let client = Arc::clone(&client);
+            Box::pin(async move { client.list_cables(device_id, limit, offset).await })
+        })
+        .await
+    }
+
+    /// Get a VLAN with resilience features
+    pub async fn get_vlan(&self, id: i32) -> Result<NetBoxVlan, AppError> {
+        let client = Arc::clone(&self.client);
+        self.read_resource("get_vlan", move || {
+            let client = Arc::clone(&client);
+            Box::pin(async move { client.get_vlan(id).await })
+        })
+        .await
+    }
+
+    /// Find a site's VLAN by its VID
+    pub async fn find_vlan(&self, site_id: i32, vid: u16) -> Result<Option<NetBoxVlan>, AppError> {
+        let client = Arc::clone(&self.client);
+        self.read_resource("find_vlan", move || {
+            let client = Arc::clone(&client);
+            Box::pin(async move { client.find_vlan(site_id, vid).await })
+        })
+        .await
+    }
+
+    /// List VLANs, optionally only those of one site, group, or tenant
+    pub async fn list_vlans_with_filters(
+        &self,
+        site_id: Option<i32>,
+        group_id: Option<i32>,
+        tenant_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+        extra_filters: &HashMap<String, String>,
+    ) -> Result<NetBoxResponse<NetBoxVlan>, AppError> {
+        let client = Arc::clone(&self.client);
+        let extra_filters = extra_filters.clone();
+        self.read_resource("list_vlans_with_filters", move || {
+            let client = Arc::clone(&client);
+            let extra_filters = extra_filters.clone();
+            Box::pin(async move {
+                client
+                    .list_vlans_with_filters(site_id, group_id, tenant_id, limit, offset, &extra_filters)
+                    .await
+            })
+        })
+        .await
+    }
+
+    /// Get an IPAM prefix with resilience features
+    pub async fn get_prefix(&self, id: i32) -> Result<NetBoxPrefix, AppError> {
+        let client = Arc::clone(&self.client);
+        self.read_resource("get_prefix", move || {
+            let client = Arc::clone(&client);
+            Box::pin(async move { client.get_prefix(id).await })
+        })
+        .await
+    }
+
+    /// List prefixes, optionally only those of one site or tenant
+    pub async fn list_prefixes_with_filters(
+        &self,
+        site_id: Option<i32>,
+        tenant_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+        extra_filters: &HashMap<String, String>,
+    ) -> Result<NetBoxResponse<NetBoxPrefix>, AppError> {
+        let client = Arc::clone(&self.client);
+        let extra_filters = extra_filters.clone();
+        self.read_resource("list_prefixes_with_filters", move || {
+            let client = Arc::clone(&client);
+            let extra_filters = extra_filters.clone();
+            Box::pin(async move {
+                client
+                    .list_prefixes_with_filters(site_id, tenant_id, limit, offset, &extra_filters)
+                    .await
+            })
+        })
+        .await
+    }
+
+    /// List addresses NetBox reports free within a prefix, lowest first
+    pub async fn list_available_ips(&self, prefix_id: i32, limit: Option<u32>) -> Result<Vec<AvailableIp>, AppError> {
+        let client = Arc::clone(&self.client);
+        self.read_resource("list_available_ips", move || {
+            let client = Arc::clone(&client);
+            Box::pin(async move { client.list_available_ips(prefix_id, limit).await })
+        })
+        .await
+    }
+
+    /// Get an IP address with resilience features
+    pub async fn get_ip_address(&self, id: i32) -> Result<NetBoxIpAddress, AppError> {
+        let client = Arc::clone(&self.client);
+        self.read_resource("get_ip_address", move || {
+            let client = Arc::clone(&client);
+            Box::pin(async move { client.get_ip_address(id).await })
+        })
+        .await
+    }
+
+    /// Get a virtualization cluster with resilience features
+    pub async fn get_cluster(&self, id: i32) -> Result<NetBoxCluster, AppError> {
+        let client = Arc::clone(&self.client);
+        self.read_resource("get_cluster", move || {
+            let client = Arc::clone(&client);
+            Box::pin(async move { client.get_cluster(id).await })
+        })
+        .await
+    }
+
+    /// List clusters, optionally only those of one site or tenant
+    pub async fn list_clusters_with_filters(
+        &self,
+        site_id: Option<i32>,
+        tenant_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+        extra_filters: &HashMap<String, String>,
+    ) -> Result<NetBoxResponse<NetBoxCluster>, AppError> {
+        let client = Arc::clone(&self.client);
+        let extra_filters = extra_filters.clone();
+        self.read_resource("list_clusters_with_filters", move || {
+            let client = Arc::clone(&client);
+            let extra_filters = extra_filters.clone();
+            Box::pin(async move {
+                client
+                    .list_clusters_with_filters(site_id, tenant_id, limit, offset, &extra_filters)
+                    .await
+            })
+        })
+        .await
+    }
+
+    /// Get a virtual machine with resilience features
+    pub async fn get_virtual_machine(&self, id: i32) -> Result<NetBoxVirtualMachine, AppError> {
+        let client = Arc::clone(&self.client);
+        self.read_resource("get_virtual_machine", move || {
+            let client = Arc::clone(&client);
+            Box::pin(async move { client.get_virtual_machine(id).await })
+        })
+        .await
+    }
+
+    /// Get many virtual machines at once, in the order of `ids`
+    pub async fn get_virtual_machines_by_ids(
+        &self,
+        ids: &[i32],
+    ) -> Result<Vec<(i32, Option<NetBoxVirtualMachine>)>, AppError> {
+        let client = Arc::clone(&self.client);
+        let ids = ids.to_vec();
+        self.read_resource("get_virtual_machines_by_ids", move || {
+            let client = Arc::clone(&client);
+            let ids = ids.clone();
+            Box::pin(async move { client.get_virtual_machines_by_ids(&ids).await })
+        })
+        .await
+    }
+
+    /// Find a cluster's virtual machine by name
+    pub async fn find_virtual_machine(
+        &self,
+        cluster_id: i32,
+        name: &str,
+    ) -> Result<Option<NetBoxVirtualMachine>, AppError> {
+        let client = Arc::clone(&self.client);
+        let name = name.to_string();
+        self.read_resource("find_virtual_machine", move || {
+            let client = Arc::clone(&client);
+            let name = name.clone();
+            Box::pin(async move { client.find_virtual_machine(cluster_id, &name).await })
+        })
+        .await
+    }
+
+    /// List virtual machines, optionally only those of one site, cluster, or tenant
+    pub async fn list_virtual_machines_with_filters(
+        &self,
+        site_id: Option<i32>,
+        cluster_id: Option<i32>,
+        tenant_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+        extra_filters: &HashMap<String, String>,
+    ) -> Result<NetBoxResponse<NetBoxVirtualMachine>, AppError> {
+        let client = Arc::clone(&self.client);
+        let extra_filters = extra_filters.clone();
+        self.read_resource("list_virtual_machines_with_filters", move || {
+            let client = Arc::clone(&client);
+            let extra_filters = extra_filters.clone();
+            Box::pin(async move {
+                client
+                    .list_virtual_machines_with_filters(site_id, cluster_id, tenant_id, limit, offset, &extra_filters)
+                    .await
+            })
+        })
+        .await
+    }
+
+    /// Run a read through the circuit breaker, read bulkhead, and retry, without degradation
+    async fn read_resource<T, F>(&self, op_name: &str, operation: F) -> Result<T, AppError>
+    where
+        T: 'static,
+        F: Fn() -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<T, NetBoxError>> + Send>>,
+    {
+        self.execute(op_name, CallKind::Read, Err, |_| {}, operation).await
+    }
+
+    /// Run `operation` through the circuit breaker, bulkhead, retry, and metrics
+    ///
+    /// `kind` picks the bulkhead and retry policy. `degrade` turns the error of
+    /// a rejected or failed call into the result, e.g. from the degradation
+    /// cache; pass `Err` for no fallback. `store` sees a successful result
+    /// before it is returned, e.g. to cache it. A full bulkhead is never
+    /// degraded.
+    async fn execute<T, F>(
+        &self,
+        op_name: &str,
+        kind: CallKind,
+        degrade: impl FnOnce(AppError) -> Result<T, AppError>,
+        store: impl FnOnce(&T),
+        operation: F,
+    ) -> Result<T, AppError>
+    where
+        T: 'static,
+        F: Fn() -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<T, NetBoxError>> + Send>>,
+    {
+        let (bulkhead, retry) = match kind {
+            CallKind::Read => (&self.read_bulkhead, &self.read_retry),
+            CallKind::Write => (&self.write_bulkhead, &self.write_retry),
+        };
+
+        // NetBox would refuse the write on every attempt
+        if kind == CallKind::Write && self.token_scope() == Some(TokenScope::ReadOnly) {
+            warn!("Refusing NetBox {}: the token is read-only", op_name);
+            return Err(AppError::ServiceUnavailable(
+                "The NetBox API token is read-only; give it write permission to submit orders".to_string(),
+            ));
+        }
+
+        // The permit holds a probe slot while half-open
+        let Some(_circuit_permit) = self.circuit_breaker.try_acquire() else {
+            self.metrics.record_circuit_breaker_rejection();
+            warn!("Circuit breaker is open, rejecting NetBox {}", op_name);
+            return degrade(AppError::ServiceDegraded("NetBox circuit breaker open".to_string()));
+        };
+
+        // Before the bulkhead, so calls waiting their turn don't hold its slots
+        if let Err(limited) = self.throttle(op_name).await {
+            let error = AppError::RateLimited { retry_after: limited.wait };
+            return match kind {
+                CallKind::Read => degrade(error),
+                CallKind::Write => Err(error),
+            };
+        }
+
+        let _bulkhead_permit = self.acquire_slot(bulkhead).await?;
+        let start_time = self.metrics.record_request_start();
+
+        let injected = Arc::new(AtomicBool::new(false));
+        let attempt = || -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<T, NetBoxError>> + Send>> {
+            let Some(fault) = self.chaos.as_ref().and_then(|chaos| chaos.fault_for(op_name)) else {
+                return operation();
+            };
+            if let Some(latency) = fault.latency {
+                self.metrics.record_injected_delay();
+                info!("Injecting {:?} of latency into NetBox {}", latency, op_name);
+            }
+            let call = match fault.netbox_error() {
+                Some(error) => {
+                    self.metrics.record_injected_error();
+                    injected.store(true, Ordering::SeqCst);
+                    info!("Injecting a failure into NetBox {}: {}", op_name, error);
+                    Err(error)
+                }
+                None => Ok(operation()),
+            };
+            Box::pin(async move {
+                if let Some(latency) = fault.latency {
+                    tokio::time::sleep(latency).await;
+                }
+                call?.await
+            })
+        };
+
+        match self.call_with_retry(retry, attempt).await {
+            Ok(resource) => {
+                self.record_success();
+                self.metrics.record_success(start_time);
+                store(&resource);
+                Ok(resource)
+            }
+            Err(e) => {
+                self.record_failure(&e);
+                self.metrics.record_failure(start_time);
+                if injected.load(Ordering::SeqCst) {
+                    self.metrics.record_injected_failure();
+                }
+                warn!("NetBox {} failed: {}", op_name, e);
+                degrade(AppError::from(e))
+            }
+        }
+    }
+
+    /// Create a site with resilience features
+    ///
+    /// A retry after an ambiguous failure first looks the site up by slug, or
+    /// by name without one, and returns it if the failed attempt created it.
+    pub async fn create_site(&self, request: CreateSiteRequest) -> Result<NetBoxSite, AppError> {
+        let create_client = Arc::clone(&self.client);
+        let lookup_client = Arc::clone(&self.client);
+        let create_request = request.clone();
+        let site = self
+            .create_resource_once(
+                "create_site",
+                move || {
+                    let client = Arc::clone(&create_client);
+                    let request = create_request.clone();
+                    Box::pin(async move { client.create_site(request).await })
+                },
+                move || {
+                    let client = Arc::clone(&lookup_client);
+                    let request = request.clone();
+                    Box::pin(async move {
+                        match request.slug {
+                            Some(ref slug) => client.find_site_by_slug(slug).await,
+                            None => {
+                                let sites = client.list_sites(request.tenant, Some(&request.name), None, None).await?;
+                                Ok(sites.results.unwrap_or_default().into_iter().find(|site| site.name == request.name))
+                            }
+                        }
+                    })
+                },
+            )
+            .await?;
+        if let Some(site_id) = site.id {
+            self.cache.cache_site(site_id, site.clone());
+        }
+        Ok(site)
+    }
+
+    /// Create a device with resilience features
+    ///
+    /// A retry after an ambiguous failure first looks a named device up within
+    /// its site and returns it if the failed attempt created it.
+    pub async fn create_device(&self, request: CreateDeviceRequest) -> Result<NetBoxDevice, AppError> {
+        let create_client = Arc::clone(&self.client);
+        let lookup_client = Arc::clone(&self.client);
+        let create_request = request.clone();
+        let device = self
+            .create_resource_once(
+                "create_device",
+                move || {
+                    let client = Arc::clone(&create_client);
+                    let request = create_request.clone();
+                    Box::pin(async move { client.create_device(request).await })
+                },
+                move || {
+                    let client = Arc::clone(&lookup_client);
+                    let (site, name) = (request.site, request.name.clone());
+                    Box::pin(async move {
+                        // Unnamed devices can't be told apart, so they are never matched
+                        let Some(name) = name else { return Ok(None) };
+                        let filters = HashMap::from([("name".to_string(), name.clone())]);
+                        let devices = client.list_devices_with_filters(Some(site), None, None, None, &filters).await?;
+                        Ok(devices
+                            .results
+                            .unwrap_or_default()
+                            .into_iter()
+                            .find(|device| device.name.as_deref() == Some(name.as_str())))
+                    })
+                },
+            )
+            .await?;
+        if let Some(device_id) = device.id {
+            self.cache.cache_device(device_id, device.clone());
+        }
+        Ok(device)
+    }
+
+    /// Create an IPAM prefix with resilience features
+    ///
+    /// NetBox allows duplicate prefixes, so there is nothing to look up before
+    /// a retry; retries follow the write policy.
+    pub async fn create_prefix(&self, request: CreatePrefixRequest) -> Result<NetBoxPrefix, AppError> {
+        let client = Arc::clone(&self.client);
+        self.create_resource("create_prefix", move || {
+            let client = Arc::clone(&client);
+            let request = request.clone();
+            Box::pin(async move { client.create_prefix(request).await })
+        })
+        .await
+    }
+
+    /// Create a device interface with resilience features
+    ///
+    /// Interface names are unique per device, so a retry after an ambiguous
+    /// failure first looks the name up on the device.
+    pub async fn create_interface(&self, request: CreateInterfaceRequest) -> Result<NetBoxInterface, AppError> {
+        let create_client = Arc::clone(&self.client);
+        let lookup_client = Arc::clone(&self.client);
+        let create_request = request.clone();
+        self.create_resource_once(
+            "create_interface",
+            move || {
+                let client = Arc::clone(&create_client);
+                let request = create_request.clone();
+                Box::pin(async move { client.create_interface(request).await })
+            },
+            move || {
+                let client = Arc::clone(&lookup_client);
+                let (device, name) = (request.device, request.name.clone());
+                Box::pin(async move { client.find_interface(device, &name).await })
+            },
+        )
+        .await
+    }
+
+    /// Create a VLAN with resilience features
+    ///
+    /// A retry after an ambiguous failure first looks the VID up at the VLAN's site.
+    pub async fn create_vlan(&self, request: CreateVlanRequest) -> Result<NetBoxVlan, AppError> {
+        let create_client = Arc::clone(&self.client);
+        let lookup_client = Arc::clone(&self.client);
+        let create_request = request.clone();
+        self.create_resource_once(
+            "create_vlan",
+            move || {
+                let client = Arc::clone(&create_client);
+                let request = create_request.clone();
+                Box::pin(async move { client.create_vlan(request).await })
+            },
+            move || {
+                let client = Arc::clone(&lookup_client);
+                let (site, vid) = (request.site, request.vid);
+                Box::pin(async move {
+                    match site {
+                        Some(site) => client.find_vlan(site, vid).await,
+                        None => Ok(None),
+                    }
+                })
+            },
+        )
+        .await
+    }
+
+    /// Create a virtualization cluster with resilience features
+    ///
+    /// Retries follow the write policy.
+    pub async fn create_cluster(&self, request: CreateClusterRequest) -> Result<NetBoxCluster, AppError> {
+        let client = Arc::clone(&self.client);
+        self.create_resource("create_cluster", move || {
+            let client = Arc::clone(&client);
+            let request = request.clone();
+            Box::pin(async move { client.create_cluster(request).await })
+        })
+        .await
+    }
+
+    /// Create a virtual machine with resilience features
+    ///
+    /// VM names are unique per cluster, so a retry after an ambiguous failure
+    /// first looks the name up in the VM's cluster.
+    pub async fn create_virtual_machine(
+        &self,
+        request: CreateVirtualMachineRequest,
+    ) -> Result<NetBoxVirtualMachine, AppError> {
+        let create_client = Arc::clone(&self.client);
+        let lookup_client = Arc::clone(&self.client);
+        let create_request = request.clone();
+        self.create_resource_once(
+            "create_virtual_machine",
+            move || {
+                let client = Arc::clone(&create_client);
+                let request = create_request.clone();
+                Box::pin(async move { client.create_virtual_machine(request).await })
+            },
+            move || {
+                let client = Arc::clone(&lookup_client);
+                let (cluster, name) = (request.cluster, request.name.clone());
+                Box::pin(async move {
+                    match cluster {
+                        Some(cluster) => client.find_virtual_machine(cluster, &name).await,
+                        None => Ok(None),
+                    }
+                })
+            },
+        )
+        .await
+    }
+
+    /// Allocate the next free address of a prefix with resilience features
+    ///
+    /// An exhausted prefix fails at once. Allocated addresses carry nothing
+    /// unique to look up, so retries follow the write policy.
+    pub async fn allocate_ip(&self, prefix_id: i32, request: AllocateIpRequest) -> Result<NetBoxIpAddress, AppError> {
+        let client = Arc::clone(&self.client);
+        self.create_resource("allocate_ip", move || {
+            let client = Arc::clone(&client);
+            let request = request.clone();
+            Box::pin(async move { client.allocate_ip(prefix_id, request).await })
+        })
+        .await
+    }
+
+    /// Connect two terminations with a cable, with resilience features
+    ///
+    /// NetBox refuses to cable an interface twice, so a retry can't create a
+    /// duplicate; retries follow the write policy.
+    pub async fn create_cable(&self, request: CreateCableRequest) -> Result<NetBoxCable, AppError> {
+        let client = Arc::clone(&self.client);
+        self.create_resource("create_cable", move || {
+            let client = Arc::clone(&client);
+            let request = request.clone();
+            Box::pin(async move { client.create_cable(request).await })
+        })
+        .await
+    }
+
+    /// Create a NetBox tenant with resilience features
+    ///
+    /// A retry after an ambiguous failure first looks the tenant up by slug.
+    pub async fn create_tenant(&self, request: CreateTenantRequest) -> Result<NetBoxTenant, AppError> {
+        let create_client = Arc::clone(&self.client);
+        let lookup_client = Arc::clone(&self.client);
+        let slug = request.slug.clone();
+        self.create_resource_once(
+            "create_tenant",
+            move || {
+                let client = Arc::clone(&create_client);
+                let request = request.clone();
+                Box::pin(async move { client.create_tenant(request).await })
+            },
+            move || {
+                let client = Arc::clone(&lookup_client);
+                let slug = slug.clone();
+                Box::pin(async move {
+                    let tenants = client.list::<NetBoxTenant>("tenancy/tenants/", &[("slug", slug)]).await?;
+                    Ok(tenants.results.and_then(|tenants| tenants.into_iter().next()))
+                })
+            },
+        )
+        .await
+    }
+
+    /// Update a site with resilience features
+    pub async fn update_site(&self, id: i32, request: UpdateSiteRequest) -> Result<NetBoxSite, AppError> {
+        let client = Arc::clone(&self.client);
+        let site = self
+            .create_resource("update_site", move || {
+                let client = Arc::clone(&client);
+                let request = request.clone();
+                Box::pin(async move { client.update_site(id, request).await })
+            })
+            .await?;
+        self.cache.cache_site(id, site.clone());
+        Ok(site)
+    }
+
+    /// Update a device with resilience features
+    pub async fn update_device(&self, id: i32, request: UpdateDeviceRequest) -> Result<NetBoxDevice, AppError> {
+        let client = Arc::clone(&self.client);
+        let device = self
+            .create_resource("update_device", move || {
+                let client = Arc::clone(&client);
+                let request = request.clone();
+                Box::pin(async move { client.update_device(id, request).await })
+            })
+            .await?;
+        self.cache.cache_device(id, device.clone());
+        Ok(device)
+    }
+
+    /// Update a VLAN with resilience features
+    pub async fn update_vlan(&self, id: i32, request: UpdateVlanRequest) -> Result<NetBoxVlan, AppError> {
+        let client = Arc::clone(&self.client);
+        self.create_resource("update_vlan", move || {
+            let client = Arc::clone(&client);
+            let request = request.clone();
+            Box::pin(async move { client.update_vlan(id, request).await })
+        })
+        .await
+    }
+
+    /// Update a virtualization cluster with resilience features
+    pub async fn update_cluster(&self, id: i32, request: UpdateClusterRequest) -> Result<NetBoxCluster, AppError> {
+        let client = Arc::clone(&self.client);
+        self.create_resource("update_cluster", move || {
+            let client = Arc::clone(&client);
+            let request = request.clone();
+            Box::pin(async move { client.update_cluster(id, request).await })
+        })
+        .await
+    }
+
+    /// Update a virtual machine with resilience features
+    pub async fn update_virtual_machine(
+        &self,
+        id: i32,
+        request: UpdateVirtualMachineRequest,
+    ) -> Result<NetBoxVirtualMachine, AppError> {
+        let client = Arc::clone(&self.client);
+        self.create_resource("update_virtual_machine", move || {
+            let client = Arc::clone(&client);
+            let request = request.clone();
+            Box::pin(async move { client.update_virtual_machine(id, request).await })
+        })
+        .await
+    }
+
+    /// Run a create through `create_resource`, checking with `lookup` before
+    /// retrying an attempt that may have reached NetBox
+    ///
+    /// An attempt is in doubt from the moment it is sent until it fails in a
+    /// way that proves NetBox didn't apply it. Attempts cut off by the
+    /// per-attempt timeout never report back, so they stay in doubt.
+    async fn create_resource_once<T, F, L>(&self, op_name: &str, create: F, lookup: L) -> Result<T, AppError>
+    where
+        T: Send + 'static,
+        F: Fn() -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<T, NetBoxError>> + Send>>,
+        L: Fn() -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Option<T>, NetBoxError>> + Send>>,
+    {
+        let in_doubt = Arc::new(AtomicBool::new(false));
+        self.create_resource(op_name, || {
+            let in_doubt = Arc::clone(&in_doubt);
+            let create = create();
+            let lookup = lookup();
+            Box::pin(async move {
+                if in_doubt.load(Ordering::SeqCst) {
+                    if let Some(existing) = lookup.await? {
+                        info!("Previous NetBox create attempt succeeded; returning the existing object");
+                        return Ok(existing);
+                    }
+                }
+                in_doubt.store(true, Ordering::SeqCst);
+                let result = create.await;
+                in_doubt.store(matches!(result, Err(ref e) if is_ambiguous(e)), Ordering::SeqCst);
+                result
+            })
+        })
+        .await
+    }
+
+    /// Run a create or update through the circuit breaker, write bulkhead, and retry
+    async fn create_resource<T, F>(&self, op_name: &str, operation: F) -> Result<T, AppError>
+    where
+        T: 'static,
+        F: Fn() -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<T, NetBoxError>> + Send>>,
+    {
+        self.execute(op_name, CallKind::Write, Err, |_| {}, operation).await
+    }
+
+    /// Delete a site with resilience features
+    ///
+    /// A site that no longer exists counts as deleted, so deletes can be repeated safely.
+    pub async fn delete_site(&self, id: i32) -> Result<(), AppError> {
+        let client = Arc::clone(&self.client);
+        self.delete_resource("delete_site", move || {
+            let client = Arc::clone(&client);
+            Box::pin(async move { client.delete_site(id).await })
+        })
+        .await?;
+        self.cache.remove_site(id);
+        Ok(())
+    }
+
+    /// Delete a device with resilience features
+    ///
+    /// A device that no longer exists counts as deleted, so deletes can be repeated safely.
+    pub async fn delete_device(&self, id: i32) -> Result<(), AppError> {
+        let client = Arc::clone(&self.client);
+        self.delete_resource("delete_device", move || {
+            let client = Arc::clone(&client);
+            Box::pin(async move { client.delete_device(id).await })
+        })
+        .await?;
+        self.cache.remove_device(id);
+        Ok(())
+    }
+
+    /// Delete a NetBox tenant with resilience features
+    ///
+    /// A tenant that no longer exists counts as deleted, so deletes can be repeated safely.
+    pub async fn delete_tenant(&self, id: i32) -> Result<(), AppError> {
+        let client = Arc::clone(&self.client);
+        self.delete_resource("delete_tenant", move || {
+            let client = Arc::clone(&client);
+            Box::pin(async move { client.delete_tenant(id).await })
+        })
+        .await
+    }
+
+    /// Delete an IPAM prefix with resilience features
+    ///
+    /// A prefix that no longer exists counts as deleted, so deletes can be repeated safely.
+    pub async fn delete_prefix(&self, id: i32) -> Result<(), AppError> {
+        let client = Arc::clone(&self.client);
+        self.delete_resource("delete_prefix", move || {
+            let client = Arc::clone(&client);
+            Box::pin(async move { client.delete_prefix(id).await })
+        })
+        .await
+    }
+
+    /// Delete a device interface with resilience features
+    ///
+    /// An interface that no longer exists counts as deleted, so deletes can be repeated safely.
+    pub async fn delete_interface(&self, id: i32) -> Result<(), AppError> {
+        let client = Arc::clone(&self.client);
+        self.delete_resource("delete_interface", move || {
+            let client = Arc::clone(&client);
+            Box::pin(async move { client.delete_interface(id).await })
+        })
+        .await
+    }
+
+    /// Delete a cable with resilience features
+    ///
+    /// A cable that no longer exists counts as deleted, so deletes can be repeated safely.
+    pub async fn delete_cable(&self, id: i32) -> Result<(), AppError> {
+        let client = Arc::clone(&self.client);
+        self.delete_resource("delete_cable", move || {
+            let client = Arc::clone(&client);
+            Box::pin(async move { client.delete_cable(id).await })
+        })
+        .await
+    }
+
+    /// Delete a VLAN with resilience features
+    ///
+    /// A VLAN that no longer exists counts as deleted, so deletes can be repeated safely.
+    pub async fn delete_vlan(&self, id: i32) -> Result<(), AppError> {
+        let client = Arc::clone(&self.client);
+        self.delete_resource("delete_vlan", move || {
+            let client = Arc::clone(&client);
+            Box::pin(async move { client.delete_vlan(id).await })
+        })
+        .await
+    }
+
+    /// Delete a virtualization cluster with resilience features
+    ///
+    /// A cluster that no longer exists counts as deleted, so deletes can be repeated safely.
+    pub async fn delete_cluster(&self, id: i32) -> Result<(), AppError> {
+        let client = Arc::clone(&self.client);
+        self.delete_resource("delete_cluster", move || {
+            let client = Arc::clone(&client);
+            Box::pin(async move { client.delete_cluster(id).await })
+        })
+        .await
+    }
+
+    /// Delete a virtual machine with resilience features
+    ///
+    /// A virtual machine that no longer exists counts as deleted, so deletes can be repeated safely.
+    pub async fn delete_virtual_machine(&self, id: i32) -> Result<(), AppError> {
+        let client = Arc::clone(&self.client);
+        self.delete_resource("delete_virtual_machine", move || {
+            let client = Arc::clone(&client);
+            Box::pin(async move { client.delete_virtual_machine(id).await })
+        })
+        .await
+    }
+
+    /// Delete an IP address with resilience features
+    ///
+    /// An address that no longer exists counts as deleted, so deletes can be repeated safely.
+    pub async fn delete_ip_address(&self, id: i32) -> Result<(), AppError> {
+        let client = Arc::clone(&self.client);
+        self.delete_resource("delete_ip_address", move || {
+            let client = Arc::clone(&client);
+            Box::pin(async move { client.delete_ip_address(id).await })
+        })
+        .await
+    }
+
+    /// Run a delete through the circuit breaker, write bulkhead, and retry, treating 404 as success
+    async fn delete_resource<F>(&self, op_name: &str, operation: F) -> Result<(), AppError>
+    where
+        F: Fn() -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), NetBoxError>> + Send>>,
+    {
+        self.execute(op_name, CallKind::Write, Err, |_| {}, || {
+            let attempt = operation();
+            Box::pin(async move {
+                match attempt.await {
+                    Err(NetBoxError::NotFound(_)) => Ok(()),
+                    result => result,
+                }
+            })
+        })
+        .await
+    }
+
+    /// Get metrics snapshot
+    pub fn metrics(&self) -> crate::resilience::MetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    /// Get circuit breaker state
+    pub fn circuit_breaker_state(&self) -> crate::resilience::CircuitState {
+        self.circuit_breaker.state()
+    }
+
+    /// Get circuit breaker failure count
+    pub fn circuit_breaker_failure_count(&self) -> u32 {
+        self.circuit_breaker.failure_count()
+    }
+
+    /// Size of the cache degraded reads fall back to
+    pub fn degradation_cache_stats(&self) -> crate::resilience::DegradationCacheStats {
+        self.cache.stats()
+    }
+
+    /// Clear cache
+    pub fn clear_cache(&self) {
+        self.cache.clear_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::resilience::circuit_breaker::FailureMode;
+    use crate::resilience::degradation::DegradationStrategy;
+    use crate::resilience::rate_limit::RateLimitConfig;
+    use crate::resilience::retry::BackoffStrategy;
+    use serde_json::json;
+    use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+    fn create_test_config(base_url: String, token: String) -> Config {
+        Config {
+            port: 8080,
+            netbox_url: base_url,
+            netbox_token: token,
+            ..Default::default()
+        }
+    }
+
+    fn create_degrading_client(
+        base_url: String,
+        degradation: DegradationConfig,
+    ) -> ResilientNetBoxClient {
+        let config = create_test_config(base_url, "test-token".to_string());
+        let client = Arc::new(NetBoxClient::new(config).unwrap());
+        ResilientNetBoxClient::with_config(
+            client,
+            CircuitBreakerConfig::default(),
+            RetryConfig::new(1),
+            std::time::Duration::from_secs(60),
+            degradation,
+        )
+    }
+
+    fn list_strategy(strategy: DegradationStrategy) -> DegradationConfig {
+        DegradationConfig {
+            list_strategy: strategy,
+            ..Default::default()
+        }
+    }
+
+    async fn mount_site_list_then_fail(mock_server: &MockServer) {
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 1,
+                "results": [{"id": 1, "name": "Site 1"}]
+            })))
+            .up_to_n_times(1)
+            .mount(mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(mock_server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_resilient_client_get_site_success() {
+        let mock_server = MockServer::start().await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let client = Arc::new(NetBoxClient::new(config).unwrap());
+        let resilient_client = ResilientNetBoxClient::new(client);
+
+        let site_response = json!({
+            "id": 1,
+            "name": "Test Site",
+            "status": "active"
+        });
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&site_response))
+            .mount(&mock_server)
+            .await;
+
+        let result = resilient_client.get_site(1).await;
+        assert!(result.is_ok());
+        
+        let metrics = resilient_client.metrics();
+        assert_eq!(metrics.total_requests, 1);
+        assert_eq!(metrics.successful_requests, 1);
+        assert_eq!(metrics.success_rate, 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_resilient_client_retries_on_failure() {
+        let mock_server = MockServer::start().await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
         let client = Arc::new(NetBoxClient::new(config).unwrap());
         
         let retry_config = RetryConfig {
@@ -282,13 +1932,14 @@ mod tests {
             initial_delay_ms: 10,
             max_delay_ms: 100,
             backoff_multiplier: 2.0,
-            use_jitter: false,
+            backoff_strategy: BackoffStrategy::None,
         };
         let resilient_client = ResilientNetBoxClient::with_config(
             client,
             CircuitBreakerConfig::default(),
             retry_config,
             std::time::Duration::from_secs(60),
+            DegradationConfig::default(),
         );
 
         // First two calls fail, third succeeds
@@ -330,12 +1981,15 @@ mod tests {
             success_threshold: 1,
             timeout_duration: std::time::Duration::from_secs(60),
             window_duration: std::time::Duration::from_secs(60),
+            failure_mode: FailureMode::Consecutive,
+            ..Default::default()
         };
         let resilient_client = ResilientNetBoxClient::with_config(
             client,
             cb_config,
             RetryConfig::default(),
             std::time::Duration::from_secs(60),
+            DegradationConfig::default(),
         );
 
         // Fail twice to open circuit breaker
@@ -392,5 +2046,922 @@ mod tests {
         let result2 = resilient_client.get_site(1).await;
         assert!(result2.is_ok()); // Should return cached value
     }
-}
 
+    #[tokio::test]
+    async fn test_list_sites_use_cache_serves_cached_list_as_degraded() {
+        let mock_server = MockServer::start().await;
+        let client = create_degrading_client(mock_server.uri(), list_strategy(DegradationStrategy::UseCache));
+        mount_site_list_then_fail(&mock_server).await;
+
+        let fresh = client.list_sites(None, None, None).await.unwrap();
+        assert!(!fresh.degraded);
+
+        let fallback = client.list_sites(None, None, None).await.unwrap();
+        assert!(fallback.degraded);
+        assert_eq!(fallback.results.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_list_sites_use_cache_cold_cache_returns_error() {
+        let mock_server = MockServer::start().await;
+        let client = create_degrading_client(mock_server.uri(), list_strategy(DegradationStrategy::UseCache));
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&mock_server)
+            .await;
+
+        assert!(client.list_sites(None, None, None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_sites_return_empty_ignores_cache() {
+        let mock_server = MockServer::start().await;
+        let client = create_degrading_client(mock_server.uri(), list_strategy(DegradationStrategy::ReturnEmpty));
+        mount_site_list_then_fail(&mock_server).await;
+
+        let _ = client.list_sites(None, None, None).await.unwrap();
+        let fallback = client.list_sites(None, None, None).await.unwrap();
+        assert!(fallback.degraded);
+        assert_eq!(fallback.count, Some(0));
+        assert!(fallback.results.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_sites_return_error_ignores_cache() {
+        let mock_server = MockServer::start().await;
+        let client = create_degrading_client(mock_server.uri(), list_strategy(DegradationStrategy::ReturnError));
+        mount_site_list_then_fail(&mock_server).await;
+
+        let _ = client.list_sites(None, None, None).await.unwrap();
+        assert!(client.list_sites(None, None, None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_sites_return_partial_with_cold_cache() {
+        let mock_server = MockServer::start().await;
+        let client = create_degrading_client(mock_server.uri(), list_strategy(DegradationStrategy::ReturnPartial));
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&mock_server)
+            .await;
+
+        let fallback = client.list_sites(None, None, None).await.unwrap();
+        assert!(fallback.degraded);
+        assert!(fallback.results.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_site_return_partial_with_cold_cache_returns_error() {
+        let mock_server = MockServer::start().await;
+        let degradation = DegradationConfig {
+            get_strategy: DegradationStrategy::ReturnPartial,
+            ..Default::default()
+        };
+        let client = create_degrading_client(mock_server.uri(), degradation);
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&mock_server)
+            .await;
+
+        assert!(client.get_site(1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_site_return_error_ignores_cache() {
+        let mock_server = MockServer::start().await;
+        let degradation = DegradationConfig {
+            get_strategy: DegradationStrategy::ReturnError,
+            ..Default::default()
+        };
+        let client = create_degrading_client(mock_server.uri(), degradation);
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 1, "name": "Test Site"})))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&mock_server)
+            .await;
+
+        assert!(client.get_site(1).await.is_ok());
+        assert!(client.get_site(1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_bulkhead_rejects_call_over_limit() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({"id": 1, "name": "Test Site"}))
+                    .set_delay(std::time::Duration::from_millis(500)),
+            )
+            .mount(&mock_server)
+            .await;
+        let client = Arc::new(
+            create_degrading_client(mock_server.uri(), DegradationConfig::default()).with_bulkheads(
+                BulkheadConfig::new(2, std::time::Duration::from_millis(20)),
+                BulkheadConfig::default(),
+            ),
+        );
+
+        let in_flight: Vec<_> = (0..2)
+            .map(|_| {
+                let client = Arc::clone(&client);
+                tokio::spawn(async move { client.get_site(1).await })
+            })
+            .collect();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        let started = std::time::Instant::now();
+        let result = client.get_site(1).await;
+        assert!(matches!(result, Err(AppError::Overloaded(_))));
+        assert!(started.elapsed() < std::time::Duration::from_millis(250));
+        assert_eq!(client.metrics().bulkhead_rejections, 1);
+
+        for handle in in_flight {
+            assert!(handle.await.unwrap().is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_full_read_bulkhead_does_not_block_writes() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"id": 2, "name": "New Site"})))
+            .mount(&mock_server)
+            .await;
+        let client = create_degrading_client(mock_server.uri(), DegradationConfig::default())
+            .with_bulkheads(
+                BulkheadConfig::new(1, std::time::Duration::from_millis(20)),
+                BulkheadConfig::new(1, std::time::Duration::from_millis(20)),
+            );
+        let _held = client.read_bulkhead.acquire().await.unwrap();
+
+        let request = CreateSiteRequest::builder("New Site").build();
+        assert!(client.create_site(request).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_shared_rate_limit_degrades_reads_and_refuses_writes() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 1, "name": "Test Site"})))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"id": 2, "name": "New Site"})))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+        // One request, then the next slot is ten seconds away
+        let limiter = Arc::new(RateLimiter::new(RateLimitConfig::new(0.1, 1, Duration::ZERO)));
+        let first = create_degrading_client(mock_server.uri(), DegradationConfig::default())
+            .with_rate_limiter(Arc::clone(&limiter));
+        let second = create_degrading_client(mock_server.uri(), DegradationConfig::default())
+            .with_rate_limiter(Arc::clone(&limiter));
+
+        assert!(first.get_site(1).await.is_ok());
+        // Served from the cache instead of waiting
+        assert_eq!(first.get_site(1).await.unwrap().name, "Test Site");
+        // The other client shares the budget, and has nothing cached
+        assert!(matches!(second.get_site(1).await, Err(AppError::RateLimited { .. })));
+        let request = CreateSiteRequest::builder("New Site").build();
+        assert!(matches!(second.create_site(request).await, Err(AppError::RateLimited { .. })));
+
+        assert_eq!(first.metrics().rate_limit_rejections, 1);
+        assert_eq!(second.metrics().rate_limit_rejections, 2);
+        assert_eq!(second.metrics().total_requests, 0);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_reads_wait_their_turn() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 1, "name": "Test Site"})))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        let limiter = RateLimiter::new(RateLimitConfig::new(10.0, 1, Duration::from_secs(1)));
+        let client = create_degrading_client(mock_server.uri(), DegradationConfig::default())
+            .with_rate_limiter(Arc::new(limiter));
+
+        let started = std::time::Instant::now();
+        assert!(client.get_site(1).await.is_ok());
+        assert!(client.get_site(1).await.is_ok());
+        assert!(started.elapsed() >= Duration::from_millis(100));
+
+        let metrics = client.metrics();
+        assert_eq!((metrics.throttled_requests, metrics.rate_limit_rejections), (1, 0));
+        assert!(metrics.throttled_time_ms > 0, "{}", metrics.throttled_time_ms);
+    }
+
+    #[tokio::test]
+    async fn test_half_open_limits_concurrent_probes() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({"id": 1, "name": "Test Site"}))
+                    .set_delay(std::time::Duration::from_millis(300)),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let cb_config = CircuitBreakerConfig {
+            failure_threshold: 1,
+            timeout_duration: std::time::Duration::from_millis(50),
+            failure_mode: FailureMode::Consecutive,
+            half_open_max_calls: 2,
+            ..Default::default()
+        };
+        let client = Arc::new(ResilientNetBoxClient::with_config(
+            Arc::new(NetBoxClient::new(config).unwrap()),
+            cb_config,
+            RetryConfig::new(1),
+            std::time::Duration::from_secs(60),
+            DegradationConfig {
+                get_strategy: DegradationStrategy::ReturnError,
+                ..Default::default()
+            },
+        ));
+
+        // Open the circuit, then wait for it to allow probing
+        assert!(client.get_site(1).await.is_err());
+        assert_eq!(client.circuit_breaker_state(), crate::resilience::CircuitState::Open);
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        let calls: Vec<_> = (0..5)
+            .map(|_| {
+                let client = Arc::clone(&client);
+                tokio::spawn(async move { client.get_site(1).await })
+            })
+            .collect();
+        let mut succeeded = 0;
+        for call in calls {
+            if call.await.unwrap().is_ok() {
+                succeeded += 1;
+            }
+        }
+
+        assert_eq!(succeeded, 2);
+        assert_eq!(mock_server.received_requests().await.unwrap().len(), 3);
+        assert_eq!(client.metrics().circuit_breaker_rejections, 3);
+        assert_eq!(client.circuit_breaker_state(), crate::resilience::CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_hedged_get_site_fast_hedge_wins() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({"id": 1, "name": "Slow Site"}))
+                    .set_delay(std::time::Duration::from_secs(2)),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 1, "name": "Fast Site"})))
+            .mount(&mock_server)
+            .await;
+        let client = create_degrading_client(mock_server.uri(), DegradationConfig::default())
+            .with_hedging(std::time::Duration::from_millis(50));
+
+        let started = std::time::Instant::now();
+        let site = client.get_site(1).await.unwrap();
+
+        assert_eq!(site.name, "Fast Site");
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+        let metrics = client.metrics();
+        assert_eq!(metrics.hedged_requests, 1);
+        assert_eq!(metrics.hedge_wins, 1);
+        assert_eq!(client.circuit_breaker_failure_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_hedged_get_site_is_not_hedged_without_a_free_read_slot() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({"id": 1, "name": "Slow Site"}))
+                    .set_delay(std::time::Duration::from_millis(200)),
+            )
+            .mount(&mock_server)
+            .await;
+        let read = BulkheadConfig::new(1, std::time::Duration::from_millis(10));
+        let client = create_degrading_client(mock_server.uri(), DegradationConfig::default())
+            .with_bulkheads(read, BulkheadConfig::default_write())
+            .with_hedging(std::time::Duration::from_millis(20));
+
+        let site = client.get_site(1).await.unwrap();
+
+        assert_eq!(site.name, "Slow Site");
+        assert_eq!(client.metrics().hedged_requests, 0);
+        assert_eq!(mock_server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_hedged_device_reads_fast_hedge_wins() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/devices/7/"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({"id": 7, "name": "slow-sw"}))
+                    .set_delay(std::time::Duration::from_secs(2)),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/devices/7/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 7, "name": "fast-sw"})))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/devices/"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({"count": 1, "results": [{"id": 7, "name": "slow-sw"}]}))
+                    .set_delay(std::time::Duration::from_secs(2)),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/devices/"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({"count": 1, "results": [{"id": 7, "name": "fast-sw"}]})),
+            )
+            .mount(&mock_server)
+            .await;
+        let client = create_degrading_client(mock_server.uri(), DegradationConfig::default())
+            .with_hedging(std::time::Duration::from_millis(50));
+
+        let started = std::time::Instant::now();
+        let device = client.get_device(7).await.unwrap();
+        let devices = client
+            .list_devices_with_filters(Some(1), None, None, None, &HashMap::new())
+            .await
+            .unwrap();
+
+        assert_eq!(device.name.as_deref(), Some("fast-sw"));
+        assert_eq!(devices.results.unwrap()[0].name.as_deref(), Some("fast-sw"));
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+        let metrics = client.metrics();
+        assert_eq!(metrics.hedged_requests, 2);
+        assert_eq!(metrics.hedge_wins, 2);
+        assert_eq!(client.circuit_breaker_failure_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_create_site_is_never_hedged() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(
+                ResponseTemplate::new(201)
+                    .set_body_json(json!({"id": 2, "name": "New Site"}))
+                    .set_delay(std::time::Duration::from_millis(200)),
+            )
+            .mount(&mock_server)
+            .await;
+        let client = create_degrading_client(mock_server.uri(), DegradationConfig::default())
+            .with_hedging(std::time::Duration::from_millis(20));
+
+        let request = CreateSiteRequest::builder("New Site").build();
+        assert!(client.create_site(request).await.is_ok());
+        let posts = mock_server
+            .received_requests()
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|request| request.method == wiremock::http::Method::Post)
+            .count();
+        assert_eq!(posts, 1);
+        assert_eq!(client.metrics().hedged_requests, 0);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_token_flags_credentials_without_tripping_breaker() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .respond_with(ResponseTemplate::new(401).set_body_string("Invalid token"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let client = ResilientNetBoxClient::new(Arc::new(NetBoxClient::new(config).unwrap()));
+
+        let result = client.get_site(1).await;
+
+        assert!(matches!(result, Err(AppError::ServiceUnavailable(_))));
+        assert!(client.auth_failed_at().is_some());
+        assert_eq!(client.circuit_breaker_failure_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_credential_check_recovers_auth_status() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/status/"))
+            .respond_with(ResponseTemplate::new(403).set_body_string("Permission denied"))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/status/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"netbox-version": "3.7.2"})))
+            .mount(&mock_server)
+            .await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let client = ResilientNetBoxClient::new(Arc::new(NetBoxClient::new(config).unwrap()));
+
+        assert!(client.check_credentials().await.is_err());
+        let failed_at = client.auth_failed_at().unwrap();
+
+        assert!(client.check_credentials().await.is_ok());
+        assert!(client.auth_failed_at().is_none());
+        assert!(failed_at <= Utc::now());
+    }
+
+    #[tokio::test]
+    async fn test_writes_fail_fast_with_a_read_only_token() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/users/tokens/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "results": [{"id": 1, "key": "test-token", "write_enabled": false}]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(403))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 1, "name": "Lab"})))
+            .mount(&mock_server)
+            .await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let client = ResilientNetBoxClient::new(Arc::new(NetBoxClient::new(config).unwrap()));
+        assert_eq!(client.token_scope(), None);
+
+        assert_eq!(client.probe_token_scope().await, TokenScope::ReadOnly);
+        assert_eq!(client.token_scope(), Some(TokenScope::ReadOnly));
+        match client.create_site(CreateSiteRequest::builder("Lab").build()).await {
+            Err(AppError::ServiceUnavailable(message)) => assert!(message.contains("read-only"), "{}", message),
+            other => panic!("Expected ServiceUnavailable, got {:?}", other),
+        }
+        // Reads are unaffected, and nothing counted against the breaker or the credentials
+        assert_eq!(client.get_site(1).await.unwrap().name, "Lab");
+        assert_eq!(client.circuit_breaker_failure_count(), 0);
+        assert!(client.auth_failed_at().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_first_auth_failure_time_is_kept() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/status/"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&mock_server)
+            .await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let client = ResilientNetBoxClient::new(Arc::new(NetBoxClient::new(config).unwrap()));
+
+        let _ = client.check_credentials().await;
+        let first = client.auth_failed_at().unwrap();
+        let _ = client.check_credentials().await;
+
+        assert_eq!(client.auth_failed_at(), Some(first));
+    }
+
+    /// Client whose NetBox answers site 1 only after a minute
+    async fn slow_netbox_client(timeouts: TimeoutConfig) -> (ResilientNetBoxClient, MockServer) {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "id": 1, "name": "Slow Site", "status": "active" }))
+                    .set_delay(Duration::from_secs(60)),
+            )
+            .mount(&mock_server)
+            .await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let retry_config = RetryConfig {
+            max_attempts: 3,
+            initial_delay_ms: 1000,
+            max_delay_ms: 1000,
+            backoff_multiplier: 1.0,
+            backoff_strategy: BackoffStrategy::None,
+        };
+        let client = ResilientNetBoxClient::with_config(
+            Arc::new(NetBoxClient::new(config).unwrap()),
+            CircuitBreakerConfig {
+                failure_mode: FailureMode::Consecutive,
+                ..Default::default()
+            },
+            retry_config,
+            Duration::from_secs(60),
+            DegradationConfig::default(),
+        )
+        .with_timeouts(timeouts);
+        (client, mock_server)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_hung_attempts_time_out_and_are_retried() {
+        let (client, _server) = slow_netbox_client(TimeoutConfig {
+            per_attempt: Duration::from_secs(5),
+            total: Duration::from_secs(60),
+        })
+        .await;
+
+        let start = tokio::time::Instant::now();
+        let err = client.get_site(1).await.unwrap_err();
+        assert!(err.to_string().contains("timed out after 5s"), "{}", err);
+        // Three 5s attempts with 1s backoff in between
+        assert_eq!(start.elapsed(), Duration::from_secs(17));
+
+        let metrics = client.metrics();
+        assert_eq!((metrics.timeouts, metrics.deadline_exceeded), (3, 0));
+        assert_eq!(metrics.failed_requests, 1);
+        assert_eq!(client.circuit_breaker_failure_count(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_total_deadline_bounds_retries() {
+        let (client, _server) = slow_netbox_client(TimeoutConfig {
+            per_attempt: Duration::from_secs(10),
+            total: Duration::from_secs(15),
+        })
+        .await;
+
+        let start = tokio::time::Instant::now();
+        let err = client.get_site(1).await.unwrap_err();
+        assert!(err.to_string().contains("timed out after 15s"), "{}", err);
+        assert_eq!(start.elapsed(), Duration::from_secs(15));
+
+        // The first attempt timed out; the second was cut off by the deadline
+        let metrics = client.metrics();
+        assert_eq!((metrics.timeouts, metrics.deadline_exceeded), (1, 1));
+        assert_eq!(client.circuit_breaker_failure_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_recovery_probe_closes_circuit_once_status_answers() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/status/"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/status/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"netbox-version": "4.0.1"})))
+            .mount(&mock_server)
+            .await;
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        let client = ResilientNetBoxClient::with_config(
+            Arc::new(NetBoxClient::new(config).unwrap()),
+            CircuitBreakerConfig {
+                failure_threshold: 1,
+                failure_mode: FailureMode::Consecutive,
+                ..Default::default()
+            },
+            RetryConfig::new(1),
+            std::time::Duration::from_secs(60),
+            DegradationConfig::default(),
+        )
+        .with_recovery_probe(RecoveryProbeConfig {
+            enabled: true,
+            interval: std::time::Duration::ZERO,
+            max_interval: std::time::Duration::ZERO,
+            successes_to_close: 1,
+        });
+
+        assert_eq!(client.poll_recovery().await, Some(ProbeOutcome::Idle));
+        client.circuit_breaker.record_failure();
+        assert_eq!(client.circuit_breaker_state(), crate::resilience::CircuitState::Open);
+
+        assert_eq!(client.poll_recovery().await, Some(ProbeOutcome::Waiting));
+        assert_eq!(client.poll_recovery().await, Some(ProbeOutcome::Failed));
+        assert!(client.recovery_probe_status().unwrap().active);
+        assert_eq!(client.poll_recovery().await, Some(ProbeOutcome::Recovered));
+        assert_eq!(client.circuit_breaker_state(), crate::resilience::CircuitState::Closed);
+
+        let metrics = client.metrics();
+        assert_eq!((metrics.recovery_probes, metrics.probe_recoveries), (2, 1));
+        assert!(!client.recovery_probe_status().unwrap().active);
+    }
+
+    /// Client whose writes time out after 100ms and are retried once, 10ms later
+    fn write_retry_client(base_url: String) -> ResilientNetBoxClient {
+        create_degrading_client(base_url, DegradationConfig::default())
+            .with_write_retry(RetryConfig {
+                initial_delay_ms: 10,
+                ..RetryConfig::for_writes()
+            })
+            .with_timeouts(TimeoutConfig {
+                per_attempt: Duration::from_millis(100),
+                total: Duration::from_secs(10),
+            })
+    }
+
+    async fn count_requests(mock_server: &MockServer, method: wiremock::http::Method, path: &str) -> usize {
+        mock_server
+            .received_requests()
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|request| request.method == method && request.url.path() == path)
+            .count()
+    }
+
+    #[tokio::test]
+    async fn test_create_site_timing_out_after_success_returns_existing_site() {
+        let mock_server = MockServer::start().await;
+        // NetBox creates the site but the response arrives after the attempt timed out
+        Mock::given(method("POST"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(
+                ResponseTemplate::new(201)
+                    .set_body_json(json!({"id": 7, "name": "Edge", "slug": "edge"}))
+                    .set_delay(Duration::from_millis(500)),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .and(query_param("slug", "edge"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 1,
+                "results": [{"id": 7, "name": "Edge", "slug": "edge"}]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let client = write_retry_client(mock_server.uri());
+
+        let request = CreateSiteRequest::builder("Edge").with_slug("edge").build();
+        let site = client.create_site(request).await.unwrap();
+
+        assert_eq!(site.id, Some(7));
+        assert_eq!(count_requests(&mock_server, wiremock::http::Method::Post, "/api/dcim/sites/").await, 1);
+        assert_eq!(client.metrics().timeouts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_create_site_is_resent_when_lookup_finds_nothing() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(201).set_delay(Duration::from_millis(500)))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"id": 8, "name": "Edge", "slug": "edge"})))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"count": 0, "results": []})))
+            .mount(&mock_server)
+            .await;
+        let client = write_retry_client(mock_server.uri());
+
+        let request = CreateSiteRequest::builder("Edge").with_slug("edge").build();
+        let site = client.create_site(request).await.unwrap();
+
+        assert_eq!(site.id, Some(8));
+        assert_eq!(count_requests(&mock_server, wiremock::http::Method::Post, "/api/dcim/sites/").await, 2);
+        assert_eq!(count_requests(&mock_server, wiremock::http::Method::Get, "/api/dcim/sites/").await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_create_site_server_error_is_retried_without_lookup() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&mock_server)
+            .await;
+        let client = write_retry_client(mock_server.uri());
+
+        let request = CreateSiteRequest::builder("Edge").with_slug("edge").build();
+        assert!(client.create_site(request).await.is_err());
+
+        // A 503 is a definite failure, so NetBox isn't asked whether the site exists
+        assert_eq!(count_requests(&mock_server, wiremock::http::Method::Post, "/api/dcim/sites/").await, 2);
+        assert_eq!(count_requests(&mock_server, wiremock::http::Method::Get, "/api/dcim/sites/").await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_write_retries_can_be_disabled_independently_of_reads() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .and(path("/api/dcim/sites/3/"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/3/"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&mock_server)
+            .await;
+        let client = create_degrading_client(mock_server.uri(), DegradationConfig::default())
+            .with_write_retry(RetryConfig::new(1));
+
+        assert!(client.delete_site(3).await.is_err());
+        assert_eq!(count_requests(&mock_server, wiremock::http::Method::Delete, "/api/dcim/sites/3/").await, 1);
+        assert_eq!(client.metrics().total_retries, 0);
+
+        // Reads keep their own policy of one attempt here, too
+        assert!(client.get_site(3).await.is_err());
+        assert_eq!(count_requests(&mock_server, wiremock::http::Method::Get, "/api/dcim/sites/3/").await, 1);
+    }
+
+    /// Client for exercising `execute` with stub operations that never reach NetBox
+    fn stub_client() -> ResilientNetBoxClient {
+        create_degrading_client("http://127.0.0.1:9".to_string(), DegradationConfig::default())
+    }
+
+    /// Operation that counts its attempts and fails each with a retryable 503
+    fn failing_operation(
+        attempts: &Arc<std::sync::atomic::AtomicU32>,
+    ) -> impl Fn() -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<u32, NetBoxError>> + Send>> {
+        let attempts = Arc::clone(attempts);
+        move || {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async { Err(NetBoxError::ApiError("HTTP 503: unavailable".to_string())) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_stores_and_returns_success() {
+        let client = stub_client();
+        let stored = std::sync::Mutex::new(None);
+
+        let result = client
+            .execute("stub", CallKind::Read, Err, |value: &u32| *stored.lock().unwrap() = Some(*value), || {
+                Box::pin(async { Ok(42) })
+            })
+            .await;
+
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(*stored.lock().unwrap(), Some(42));
+        let metrics = client.metrics();
+        assert_eq!((metrics.total_requests, metrics.successful_requests), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_execute_degrades_failures_without_storing() {
+        let client = stub_client();
+        let attempts = Arc::new(std::sync::atomic::AtomicU32::new(0));
+
+        let result = client
+            .execute(
+                "stub",
+                CallKind::Read,
+                |error| {
+                    assert!(error.to_string().contains("503"), "{}", error);
+                    Ok(7)
+                },
+                |_| panic!("stored a failed result"),
+                failing_operation(&attempts),
+            )
+            .await;
+
+        assert_eq!(result.unwrap(), 7);
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+        assert_eq!(client.metrics().failed_requests, 1);
+        assert_eq!(client.circuit_breaker_failure_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_execute_skips_operation_while_circuit_is_open() {
+        let client = stub_client();
+        for _ in 0..10 {
+            client.circuit_breaker.record_failure();
+        }
+        assert_eq!(client.circuit_breaker_state(), crate::resilience::CircuitState::Open);
+        let attempts = Arc::new(std::sync::atomic::AtomicU32::new(0));
+
+        let result = client
+            .execute("stub", CallKind::Read, Err, |_| {}, failing_operation(&attempts))
+            .await;
+
+        assert!(matches!(result, Err(AppError::ServiceDegraded(ref msg)) if msg.contains("circuit breaker open")));
+        assert_eq!(attempts.load(Ordering::SeqCst), 0);
+        assert_eq!(client.metrics().circuit_breaker_rejections, 1);
+        assert_eq!(client.metrics().total_requests, 0);
+    }
+
+    #[tokio::test]
+    async fn test_execute_picks_retry_policy_by_call_kind() {
+        let client = stub_client().with_write_retry(RetryConfig {
+            max_attempts: 3,
+            initial_delay_ms: 1,
+            ..RetryConfig::default()
+        });
+        let read_attempts = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let write_attempts = Arc::new(std::sync::atomic::AtomicU32::new(0));
+
+        let _ = client
+            .execute("stub", CallKind::Read, Err, |_| {}, failing_operation(&read_attempts))
+            .await;
+        let _ = client
+            .execute("stub", CallKind::Write, Err, |_| {}, failing_operation(&write_attempts))
+            .await;
+
+        assert_eq!(read_attempts.load(Ordering::SeqCst), 1);
+        assert_eq!(write_attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_injected_faults_open_the_circuit_without_reaching_netbox() {
+        use crate::domain::chaos::{ChaosErrorKind, ChaosExperimentRequest};
+        use crate::netbox::fake::FakeNetBox;
+
+        let fake = FakeNetBox::start().await;
+        let site_id = fake.seed_site(json!({ "name": "Lab", "slug": "lab" }));
+        let chaos = Arc::new(ChaosInjector::new());
+        let client = ResilientNetBoxClient::with_config(
+            fake.client(),
+            CircuitBreakerConfig {
+                failure_threshold: 3,
+                failure_mode: FailureMode::Consecutive,
+                ..Default::default()
+            },
+            RetryConfig {
+                max_attempts: 2,
+                initial_delay_ms: 1,
+                max_delay_ms: 1,
+                ..RetryConfig::default()
+            },
+            std::time::Duration::from_secs(60),
+            DegradationConfig::default(),
+        )
+        .with_chaos(chaos.clone());
+
+        chaos
+            .start(ChaosExperimentRequest {
+                operations: vec!["get_site".to_string()],
+                failure_rate: Some(1.0),
+                latency_ms: Some(1),
+                error: Some(ChaosErrorKind::Unavailable),
+                duration_secs: 60,
+            })
+            .unwrap();
+        for _ in 0..3 {
+            let error = client.get_site(site_id).await.unwrap_err();
+            assert!(error.to_string().contains("503"), "{}", error);
+        }
+
+        assert_eq!(client.circuit_breaker_state(), crate::resilience::CircuitState::Open);
+        assert!(fake.requests().iter().all(|request| !request.path.starts_with("/api/dcim/")));
+        let metrics = client.metrics();
+        assert_eq!((metrics.injected_errors, metrics.injected_delays), (6, 6));
+        assert_eq!((metrics.injected_failures, metrics.failed_requests), (3, 3));
+
+        // Calls reach NetBox again once the experiment is stopped
+        chaos.stop();
+        client.circuit_breaker.reset();
+        assert_eq!(client.get_site(site_id).await.unwrap().name, "Lab");
+    }
+}
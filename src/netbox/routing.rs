@@ -0,0 +1,296 @@
+use crate::config::Config;
+use crate::error::AppError;
+use crate::netbox::cached_client::CachedNetBoxClient;
+use crate::netbox::{NetBoxClient, ResilientNetBoxClient};
+use crate::resilience::{Bulkhead, ChaosInjector, RateLimiter, RetryConfig};
+use crate::security::tenant::{TenantId, TenantMappingService, DEFAULT_NETBOX_ENDPOINT};
+use crate::security::TokenCipher;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// One NetBox deployment and the client stack talking to it
+pub struct NetBoxEndpoint {
+    pub name: String,
+    pub url: String,
+    pub client: Arc<ResilientNetBoxClient>,
+    pub cached_client: Arc<CachedNetBoxClient>,
+    /// Settings the clients were built from, for clients with another token
+    config: Config,
+    /// Fault injection shared with clients built with another token
+    chaos: Option<Arc<ChaosInjector>>,
+    /// The deployment's request budget and call slots, shared with clients built with another token
+    limits: SharedLimits,
+}
+
+/// Limits on the calls one NetBox deployment sees, whatever the token they are made with
+#[derive(Clone)]
+struct SharedLimits {
+    rate_limiter: Option<Arc<RateLimiter>>,
+    read_bulkhead: Arc<Bulkhead>,
+    write_bulkhead: Arc<Bulkhead>,
+}
+
+impl SharedLimits {
+    fn from_config(config: &Config) -> Self {
+        Self {
+            rate_limiter: config.netbox_rate_limit.map(|limit| Arc::new(RateLimiter::new(limit))),
+            read_bulkhead: Arc::new(Bulkhead::with_config(config.netbox_read_bulkhead.clone())),
+            write_bulkhead: Arc::new(Bulkhead::with_config(config.netbox_write_bulkhead.clone())),
+        }
+    }
+}
+
+impl NetBoxEndpoint {
+    /// Wrap `client` in resilient and cached clients configured from `config`
+    pub fn new(name: &str, config: &Config, client: Arc<NetBoxClient>) -> Self {
+        Self::build(name, config, client, None)
+    }
+
+    /// Like [`Self::new`], injecting `chaos`'s faults into the resilient client's calls
+    pub fn build(name: &str, config: &Config, client: Arc<NetBoxClient>, chaos: Option<Arc<ChaosInjector>>) -> Self {
+        Self::build_with_limits(name, config, client, chaos, SharedLimits::from_config(config))
+    }
+
+    fn build_with_limits(
+        name: &str,
+        config: &Config,
+        client: Arc<NetBoxClient>,
+        chaos: Option<Arc<ChaosInjector>>,
+        limits: SharedLimits,
+    ) -> Self {
+        let mut resilient = ResilientNetBoxClient::with_config(
+            client,
+            config.circuit_breaker.clone(),
+            RetryConfig {
+                backoff_strategy: config.retry_backoff,
+                ..config.read_retry.clone()
+            },
+            std::time::Duration::from_secs(300),
+            config.degradation,
+        )
+        .with_write_retry(RetryConfig {
+            backoff_strategy: config.retry_backoff,
+            ..config.write_retry.clone()
+        })
+        .with_timeouts(config.netbox_timeouts)
+        .with_custom_field_schema_ttl(config.custom_field_schema_check.ttl)
+        .with_shared_bulkheads(limits.read_bulkhead.clone(), limits.write_bulkhead.clone());
+        if config.recovery_probe.enabled {
+            resilient = resilient.with_recovery_probe(config.recovery_probe);
+        }
+        if let Some(delay) = config.hedge_delay {
+            resilient = resilient.with_hedging(delay);
+        }
+        if let Some(ref chaos) = chaos {
+            resilient = resilient.with_chaos(chaos.clone());
+        }
+        if let Some(ref limiter) = limits.rate_limiter {
+            resilient = resilient.with_rate_limiter(limiter.clone());
+        }
+        let client = Arc::new(resilient);
+
+        Self {
+            name: name.to_string(),
+            url: config.netbox_url.clone(),
+            cached_client: Arc::new(CachedNetBoxClient::new(client.clone())),
+            client,
+            config: config.clone(),
+            chaos,
+            limits,
+        }
+    }
+
+    /// The same endpoint, called with another API token
+    pub fn with_token(&self, token: &str) -> Result<Self, AppError> {
+        let config = Config {
+            netbox_token: token.to_string(),
+            ..self.config.clone()
+        };
+        let client = NetBoxClient::new(config.clone()).map_err(|e| {
+            AppError::Internal(anyhow::anyhow!("Failed to create NetBox client for endpoint '{}': {}", self.name, e))
+        })?;
+        // NetBox counts requests against its budget whatever the token
+        Ok(Self::build_with_limits(
+            &self.name,
+            &config,
+            Arc::new(client),
+            self.chaos.clone(),
+            self.limits.clone(),
+        ))
+    }
+}
+
+/// Routes each tenant's NetBox calls to the endpoint named in its mapping
+///
+/// Tenants without a mapping, or mapped without an endpoint, go to the default
+/// endpoint. Mappings are checked against the configured endpoints when they
+/// are registered, so an unknown name here means the two got out of sync.
+/// Tenants with their own token get a client of their own on their endpoint.
+pub struct NetBoxRouter {
+    /// The default endpoint first, then the named ones in configuration order
+    endpoints: Vec<Arc<NetBoxEndpoint>>,
+    mappings: Arc<TenantMappingService>,
+    /// Decrypts tenant tokens; without it no tenant can use its own token
+    token_cipher: Option<TokenCipher>,
+    /// Endpoints built with a tenant's token, with the encrypted token they were built from
+    delegated: RwLock<HashMap<TenantId, (String, Arc<NetBoxEndpoint>)>>,
+}
+
+impl NetBoxRouter {
+    pub fn new(default: NetBoxEndpoint, mappings: Arc<TenantMappingService>) -> Self {
+        Self {
+            endpoints: vec![Arc::new(default)],
+            mappings,
+            token_cipher: None,
+            delegated: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Add a named endpoint tenants can be mapped to
+    pub fn with_endpoint(mut self, endpoint: NetBoxEndpoint) -> Self {
+        self.endpoints.push(Arc::new(endpoint));
+        self
+    }
+
+    /// Let tenants call NetBox with their own tokens, encrypted with `cipher`
+    pub fn with_token_cipher(mut self, cipher: TokenCipher) -> Self {
+        self.token_cipher = Some(cipher);
+        self
+    }
+
+    /// All endpoints, the default first; tenant token clients aren't listed
+    pub fn endpoints(&self) -> &[Arc<NetBoxEndpoint>] {
+        &self.endpoints
+    }
+
+    pub fn default_endpoint(&self) -> &Arc<NetBoxEndpoint> {
+        &self.endpoints[0]
+    }
+
+    /// Endpoint by name; `default` is the NETBOX_URL endpoint
+    pub fn endpoint(&self, name: &str) -> Option<&Arc<NetBoxEndpoint>> {
+        if name == DEFAULT_NETBOX_ENDPOINT {
+            return Some(self.default_endpoint());
+        }
+        self.endpoints.iter().skip(1).find(|endpoint| endpoint.name == name)
+    }
+
+    /// The endpoint a tenant's NetBox calls go to, with the tenant's token if it has one
+    pub fn endpoint_for(&self, tenant_id: &TenantId) -> Result<Arc<NetBoxEndpoint>, AppError> {
+        let Some(mapping) = self.mappings.get_mapping(tenant_id) else {
+            return Ok(self.default_endpoint().clone());
+        };
+        let endpoint = self.endpoint(mapping.endpoint_name()).ok_or_else(|| {
+            AppError::Internal(anyhow::anyhow!(
+                "Tenant '{}' is mapped to unknown NetBox endpoint '{}'",
+                tenant_id,
+                mapping.endpoint_name()
+            ))
+        })?;
+        match mapping.netbox_token {
+            Some(ref encrypted) => self.delegated_endpoint(tenant_id, endpoint, encrypted),
+            None => Ok(endpoint.clone()),
+        }
+    }
+
+    /// Check NetBox accepts a tenant's own token on the endpoint, returning it encrypted for the mapping
+    pub async fn encrypt_checked_token(&self, endpoint: &str, token: &str) -> Result<String, AppError> {
+        let cipher = self.token_cipher.as_ref().ok_or_else(|| {
+            AppError::ValidationError("Tenant NetBox tokens need TENANT_TOKEN_KEY to be set".to_string())
+        })?;
+        let endpoint = self
+            .endpoint(endpoint)
+            .ok_or_else(|| AppError::ValidationError(format!("Unknown NetBox endpoint '{}'", endpoint)))?;
+        let probe = endpoint.with_token(token)?;
+        match probe.client.check_credentials().await {
+            Ok(()) => Ok(cipher.encrypt(token)),
+            Err(_) if probe.client.auth_failed_at().is_some() => Err(AppError::ValidationError(format!(
+                "NetBox endpoint '{}' rejected the tenant's token",
+                endpoint.name
+            ))),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The tenant's endpoint with its own token, built on first use
+    fn delegated_endpoint(
+        &self,
+        tenant_id: &TenantId,
+        endpoint: &NetBoxEndpoint,
+        encrypted: &str,
+    ) -> Result<Arc<NetBoxEndpoint>, AppError> {
+        if let Some((built_from, delegated)) = self.delegated.read().get(tenant_id) {
+            if built_from == encrypted && delegated.url == endpoint.url {
+                return Ok(delegated.clone());
+            }
+        }
+        let cipher = self.token_cipher.as_ref().ok_or_else(|| {
+            AppError::Internal(anyhow::anyhow!(
+                "Tenant '{}' has its own NetBox token but TENANT_TOKEN_KEY is not set",
+                tenant_id
+            ))
+        })?;
+        let token = cipher.decrypt(encrypted).map_err(|e| {
+            AppError::Internal(anyhow::anyhow!("NetBox token of tenant '{}': {}", tenant_id, e))
+        })?;
+        let delegated = Arc::new(endpoint.with_token(&token)?);
+        self.delegated.write().insert(tenant_id.clone(), (encrypted.to_string(), delegated.clone()));
+        Ok(delegated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::tenant::TenantMapping;
+
+    fn endpoint(name: &str, url: &str) -> NetBoxEndpoint {
+        let config = Config {
+            netbox_url: url.to_string(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        NetBoxEndpoint::new(name, &config, Arc::new(NetBoxClient::new(config.clone()).unwrap()))
+    }
+
+    #[test]
+    fn test_endpoint_for_follows_tenant_mapping() {
+        let mappings = Arc::new(TenantMappingService::new().with_endpoints(["eu".to_string()]));
+        mappings.register_mapping("acme".to_string(), TenantMapping::new(10, Some("eu".to_string())));
+        mappings.register_mapping("globex".to_string(), 20);
+        mappings.register_mapping("initech".to_string(), TenantMapping::new(30, Some("us".to_string())));
+        let router = NetBoxRouter::new(endpoint(DEFAULT_NETBOX_ENDPOINT, "http://us.example.com"), mappings)
+            .with_endpoint(endpoint("eu", "http://eu.example.com"));
+
+        assert_eq!(router.endpoint_for(&"acme".to_string()).unwrap().name, "eu");
+        assert_eq!(router.endpoint_for(&"globex".to_string()).unwrap().name, DEFAULT_NETBOX_ENDPOINT);
+        assert_eq!(router.endpoint_for(&"unmapped".to_string()).unwrap().name, DEFAULT_NETBOX_ENDPOINT);
+        assert!(router.endpoint_for(&"initech".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_clients_with_another_token_share_the_endpoint_limits() {
+        let config = Config {
+            netbox_url: "http://eu.example.com".to_string(),
+            netbox_token: "test-token".to_string(),
+            netbox_rate_limit: Some(crate::resilience::RateLimitConfig::new(5.0, 5, std::time::Duration::ZERO)),
+            netbox_write_bulkhead: crate::resilience::BulkheadConfig::new(3, std::time::Duration::ZERO),
+            ..Default::default()
+        };
+        let eu = NetBoxEndpoint::new("eu", &config, Arc::new(NetBoxClient::new(config.clone()).unwrap()));
+        let us = NetBoxEndpoint::new("us", &config, Arc::new(NetBoxClient::new(config.clone()).unwrap()));
+        let tenant = eu.with_token("tenant-token").unwrap();
+
+        let limiter = |endpoint: &NetBoxEndpoint| endpoint.limits.rate_limiter.clone().unwrap();
+        assert!(Arc::ptr_eq(&limiter(&eu), &limiter(&tenant)));
+        let reads = |endpoint: &NetBoxEndpoint| endpoint.limits.read_bulkhead.clone();
+        let writes = |endpoint: &NetBoxEndpoint| endpoint.limits.write_bulkhead.clone();
+        assert!(Arc::ptr_eq(&reads(&eu), &reads(&tenant)));
+        assert!(Arc::ptr_eq(&writes(&eu), &writes(&tenant)));
+        assert_eq!(writes(&eu).config().max_concurrent, 3);
+        // Each deployment has a budget of its own
+        assert!(!Arc::ptr_eq(&limiter(&eu), &limiter(&us)));
+        assert!(!Arc::ptr_eq(&reads(&eu), &reads(&us)));
+    }
+}
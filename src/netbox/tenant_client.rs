@@ -1,20 +1,78 @@
 use crate::error::AppError;
-use crate::netbox::client::NetBoxClient;
+use crate::netbox::custom_fields::SITE_OBJECT_TYPE;
+use crate::netbox::graphql::{DeviceInterfaces, SiteDeviceCount};
 use crate::netbox::models::*;
+use crate::netbox::operations::{ListQuery, NetBoxOperations};
+use crate::netbox::routing::NetBoxRouter;
 use crate::security::tenant::{TenantAccessControl, TenantId, TenantResourceVisibility};
+use crate::warmup::HotSites;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use tracing::warn;
+
+/// One page of a tenant-scoped listing
+///
+/// `results` holds only the tenant's resources; `filtered_out` counts items
+/// NetBox returned for other tenants, so pages may be shorter than `limit`.
+#[derive(Debug, Clone, Serialize)]
+pub struct TenantScopedPage<T> {
+    pub results: Vec<T>,
+    /// Total NetBox reported for the query, before visibility filtering
+    pub total: Option<i32>,
+    pub filtered_out: usize,
+    pub has_more: bool,
+    /// Served from fallback data because NetBox couldn't be reached
+    pub degraded: bool,
+}
+
+/// Search filters passed through to NetBox list endpoints
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilter {
+    /// Free-text search (NetBox's `?q=`)
+    pub q: Option<String>,
+    /// Tag slug
+    pub tag: Option<String>,
+    /// Status value, e.g. `active`
+    pub status: Option<String>,
+    /// Only objects changed at or after this time (NetBox's `last_updated__gte`)
+    pub updated_since: Option<DateTime<Utc>>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+impl SearchFilter {
+    fn netbox_filters(&self) -> HashMap<String, String> {
+        let mut filters: HashMap<String, String> = [("q", &self.q), ("tag", &self.tag), ("status", &self.status)]
+            .into_iter()
+            .filter_map(|(key, value)| value.clone().map(|value| (key.to_string(), value)))
+            .collect();
+        if let Some(since) = self.updated_since {
+            filters.insert("last_updated__gte".to_string(), since.to_rfc3339_opts(SecondsFormat::Micros, true));
+        }
+        filters
+    }
+}
 
 /// Tenant-aware NetBox client wrapper
 /// Ensures all operations are scoped to a specific tenant
+///
+/// Wraps any client layer, so it can sit on top of the resilient/cached
+/// clients and keep their retries, circuit breaking, and caching.
 pub struct TenantAwareNetBoxClient {
-    client: Arc<NetBoxClient>,
+    client: Arc<dyn NetBoxOperations>,
     access_control: Arc<TenantAccessControl>,
     visibility: Arc<TenantResourceVisibility>,
+    /// Sends each tenant to its own NetBox endpoint instead of `client`
+    router: Option<Arc<NetBoxRouter>>,
+    /// Remembers the sites each tenant reads, for warm-up to prefetch
+    hot_sites: Option<Arc<HotSites>>,
 }
 
 impl TenantAwareNetBoxClient {
     pub fn new(
-        client: Arc<NetBoxClient>,
+        client: Arc<dyn NetBoxOperations>,
         access_control: Arc<TenantAccessControl>,
     ) -> Self {
         // Create a new TenantResourceVisibility that shares the same mapping service
@@ -27,40 +85,121 @@ impl TenantAwareNetBoxClient {
             client,
             access_control,
             visibility,
+            router: None,
+            hot_sites: None,
+        }
+    }
+
+    /// Route each tenant's calls to the cached client of its mapped endpoint
+    pub fn with_router(mut self, router: Arc<NetBoxRouter>) -> Self {
+        self.router = Some(router);
+        self
+    }
+
+    /// Record the sites tenants read in `hot_sites`
+    pub fn with_hot_sites(mut self, hot_sites: Arc<HotSites>) -> Self {
+        self.hot_sites = Some(hot_sites);
+        self
+    }
+
+    /// The client layer serving this tenant
+    fn client_for(&self, tenant_id: &TenantId) -> Result<Arc<dyn NetBoxOperations>, AppError> {
+        match self.router {
+            Some(ref router) => Ok(router.endpoint_for(tenant_id)?.cached_client.clone()),
+            None => Ok(self.client.clone()),
         }
     }
 
     /// Get a site by ID with tenant access control
     pub async fn get_site(&self, tenant_id: &TenantId, site_id: i32) -> Result<NetBoxSite, AppError> {
-        let site = self.client.get_site(site_id).await
-            .map_err(|e| AppError::Internal(anyhow::Error::from(e)))?;
+        let site = self.client_for(tenant_id)?.get_site(site_id).await?;
         
         self.visibility.ensure_site_visible(tenant_id, &site)?;
+        if let Some(ref hot_sites) = self.hot_sites {
+            hot_sites.record(tenant_id, site_id);
+        }
         Ok(site)
     }
 
+    /// Get many sites at once; sites that don't exist or belong to another tenant come back as `None`
+    pub async fn get_sites_by_ids(
+        &self,
+        tenant_id: &TenantId,
+        site_ids: &[i32],
+    ) -> Result<Vec<(i32, Option<NetBoxSite>)>, AppError> {
+        let sites = self.client_for(tenant_id)?.get_sites_by_ids(site_ids).await?;
+        Ok(sites
+            .into_iter()
+            .map(|(id, site)| {
+                (id, site.filter(|site| self.visibility.ensure_site_visible(tenant_id, site).is_ok()))
+            })
+            .collect())
+    }
+
+    /// A page of a site's NetBox changelog, newest first
+    ///
+    /// The site is checked to be the tenant's first; sites that don't exist
+    /// and sites of other tenants are both `NotFound`.
+    pub async fn site_changes(
+        &self,
+        tenant_id: &TenantId,
+        site_id: i32,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<NetBoxResponse<NetBoxObjectChange>, AppError> {
+        self.access_control
+            .get_netbox_tenant_id(tenant_id)
+            .ok_or(AppError::Unauthorized)?;
+
+        let client = self.client_for(tenant_id)?;
+        let owned = client
+            .get_sites_by_ids(&[site_id])
+            .await?
+            .into_iter()
+            .any(|(_, site)| site.is_some_and(|site| self.visibility.ensure_site_visible(tenant_id, &site).is_ok()));
+        if !owned {
+            return Err(AppError::NotFound(format!("Site {} not found", site_id)));
+        }
+
+        client.get_object_changes(SITE_OBJECT_TYPE, site_id, limit, offset).await
+    }
+
     /// List sites for a tenant (automatically filters by tenant)
     pub async fn list_sites(
         &self,
         tenant_id: &TenantId,
         limit: Option<u32>,
         offset: Option<u32>,
-    ) -> Result<Vec<NetBoxSite>, AppError> {
-        // Get NetBox tenant ID for filtering
+    ) -> Result<TenantScopedPage<NetBoxSite>, AppError> {
+        self.search_sites(tenant_id, &SearchFilter { limit, offset, ..Default::default() })
+            .await
+    }
+
+    /// Search a tenant's sites
+    ///
+    /// NetBox is asked for the tenant's sites only, and anything it returns
+    /// for another tenant is dropped before returning.
+    pub async fn search_sites(
+        &self,
+        tenant_id: &TenantId,
+        filter: &SearchFilter,
+    ) -> Result<TenantScopedPage<NetBoxSite>, AppError> {
         let netbox_tenant_id = self.access_control
             .get_netbox_tenant_id(tenant_id)
             .ok_or(AppError::Unauthorized)?;
 
-        // List sites from NetBox with tenant filter
-        let response = self.client.list_sites(Some(netbox_tenant_id), limit, offset).await
-            .map_err(|e| AppError::Internal(anyhow::Error::from(e)))?;
+        let query = ListQuery {
+            tenant_id: Some(netbox_tenant_id),
+            limit: filter.limit,
+            offset: filter.offset,
+            filters: filter.netbox_filters(),
+        };
+        let response = self.client_for(tenant_id)?.list_sites(&query).await?;
 
-        // Extract sites and ensure they're all visible to the tenant
-        let sites = response.results.unwrap_or_default();
-        
         // Double-check visibility (defense in depth)
-        let filtered = self.visibility.get_tenant_sites(tenant_id, sites)?;
-        Ok(filtered)
+        scoped_page(tenant_id, "site", response, |site| site.id, |sites| {
+            self.visibility.get_tenant_sites(tenant_id, sites)
+        })
     }
 
     /// Create a site for a tenant (automatically assigns tenant)
@@ -78,8 +217,7 @@ impl TenantAwareNetBoxClient {
         request.tenant = Some(netbox_tenant_id);
 
         // Create site in NetBox
-        let site = self.client.create_site(request).await
-            .map_err(|e| AppError::Internal(anyhow::Error::from(e)))?;
+        let site = self.client_for(tenant_id)?.create_site(request).await?;
 
         // Verify the created site belongs to the tenant
         self.visibility.ensure_site_visible(tenant_id, &site)?;
@@ -97,8 +235,7 @@ impl TenantAwareNetBoxClient {
         let _existing_site = self.get_site(tenant_id, site_id).await?;
 
         // Update site
-        let site = self.client.update_site(site_id, request).await
-            .map_err(|e| AppError::Internal(anyhow::Error::from(e)))?;
+        let site = self.client_for(tenant_id)?.update_site(site_id, request).await?;
 
         // Verify the updated site still belongs to the tenant
         self.visibility.ensure_site_visible(tenant_id, &site)?;
@@ -111,44 +248,95 @@ impl TenantAwareNetBoxClient {
         let _site = self.get_site(tenant_id, site_id).await?;
 
         // Delete site
-        self.client.delete_site(site_id).await
-            .map_err(|e| AppError::Internal(anyhow::Error::from(e)))?;
+        self.client_for(tenant_id)?.delete_site(site_id).await?;
         
         Ok(())
     }
 
     /// Get a device by ID with tenant access control
     pub async fn get_device(&self, tenant_id: &TenantId, device_id: i32) -> Result<NetBoxDevice, AppError> {
-        let device = self.client.get_device(device_id).await
-            .map_err(|e| AppError::Internal(anyhow::Error::from(e)))?;
+        let device = self.client_for(tenant_id)?.get_device(device_id).await?;
         
         self.visibility.ensure_device_visible(tenant_id, &device)?;
         Ok(device)
     }
 
+    /// Get many devices at once; devices that don't exist or belong to another tenant come back as `None`
+    pub async fn get_devices_by_ids(
+        &self,
+        tenant_id: &TenantId,
+        device_ids: &[i32],
+    ) -> Result<Vec<(i32, Option<NetBoxDevice>)>, AppError> {
+        let devices = self.client_for(tenant_id)?.get_devices_by_ids(device_ids).await?;
+        Ok(devices
+            .into_iter()
+            .map(|(id, device)| {
+                (id, device.filter(|device| self.visibility.ensure_device_visible(tenant_id, device).is_ok()))
+            })
+            .collect())
+    }
+
     /// List devices for a tenant (automatically filters by tenant)
+    ///
+    /// `role` is a NetBox device role ID.
     pub async fn list_devices(
         &self,
         tenant_id: &TenantId,
         site_id: Option<i32>,
+        status: Option<DeviceStatus>,
+        role: Option<i32>,
         limit: Option<u32>,
         offset: Option<u32>,
-    ) -> Result<Vec<NetBoxDevice>, AppError> {
-        // Get NetBox tenant ID for filtering
+    ) -> Result<TenantScopedPage<NetBoxDevice>, AppError> {
+        let mut extra_filters = HashMap::new();
+        if let Some(status) = status.and_then(|status| serde_json::to_value(status).ok()) {
+            if let Some(status) = status.as_str() {
+                extra_filters.insert("status".to_string(), status.to_string());
+            }
+        }
+        if let Some(role) = role {
+            extra_filters.insert("role_id".to_string(), role.to_string());
+        }
+        self.fetch_devices(tenant_id, site_id, limit, offset, extra_filters).await
+    }
+
+    /// Search a tenant's devices, optionally within one site
+    ///
+    /// As with sites, devices of other tenants are dropped even if NetBox returns them.
+    pub async fn search_devices(
+        &self,
+        tenant_id: &TenantId,
+        site_id: Option<i32>,
+        filter: &SearchFilter,
+    ) -> Result<TenantScopedPage<NetBoxDevice>, AppError> {
+        self.fetch_devices(tenant_id, site_id, filter.limit, filter.offset, filter.netbox_filters())
+            .await
+    }
+
+    async fn fetch_devices(
+        &self,
+        tenant_id: &TenantId,
+        site_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+        filters: HashMap<String, String>,
+    ) -> Result<TenantScopedPage<NetBoxDevice>, AppError> {
         let netbox_tenant_id = self.access_control
             .get_netbox_tenant_id(tenant_id)
             .ok_or(AppError::Unauthorized)?;
 
-        // List devices from NetBox with tenant filter
-        let response = self.client.list_devices(site_id, Some(netbox_tenant_id), limit, offset).await
-            .map_err(|e| AppError::Internal(anyhow::Error::from(e)))?;
+        let query = ListQuery {
+            tenant_id: Some(netbox_tenant_id),
+            limit,
+            offset,
+            filters,
+        };
+        let response = self.client_for(tenant_id)?.list_devices(site_id, &query).await?;
 
-        // Extract devices and ensure they're all visible to the tenant
-        let devices = response.results.unwrap_or_default();
-        
         // Double-check visibility (defense in depth)
-        let filtered = self.visibility.get_tenant_devices(tenant_id, devices)?;
-        Ok(filtered)
+        scoped_page(tenant_id, "device", response, |device| device.id, |devices| {
+            self.visibility.get_tenant_devices(tenant_id, devices)
+        })
     }
 
     /// Create a device for a tenant (automatically assigns tenant)
@@ -166,8 +354,7 @@ impl TenantAwareNetBoxClient {
         request.tenant = Some(netbox_tenant_id);
 
         // Create device in NetBox
-        let device = self.client.create_device(request).await
-            .map_err(|e| AppError::Internal(anyhow::Error::from(e)))?;
+        let device = self.client_for(tenant_id)?.create_device(request).await?;
 
         // Verify the created device belongs to the tenant
         self.visibility.ensure_device_visible(tenant_id, &device)?;
@@ -185,8 +372,7 @@ impl TenantAwareNetBoxClient {
         let _existing_device = self.get_device(tenant_id, device_id).await?;
 
         // Update device
-        let device = self.client.update_device(device_id, request).await
-            .map_err(|e| AppError::Internal(anyhow::Error::from(e)))?;
+        let device = self.client_for(tenant_id)?.update_device(device_id, request).await?;
 
         // Verify the updated device still belongs to the tenant
         self.visibility.ensure_device_visible(tenant_id, &device)?;
@@ -199,19 +385,383 @@ impl TenantAwareNetBoxClient {
         let _device = self.get_device(tenant_id, device_id).await?;
 
         // Delete device
-        self.client.delete_device(device_id).await
-            .map_err(|e| AppError::Internal(anyhow::Error::from(e)))?;
+        self.client_for(tenant_id)?.delete_device(device_id).await?;
         
         Ok(())
     }
+
+    /// Get a VLAN by ID with tenant access control
+    pub async fn get_vlan(&self, tenant_id: &TenantId, vlan_id: i32) -> Result<NetBoxVlan, AppError> {
+        let vlan = self.client_for(tenant_id)?.get_vlan(vlan_id).await?;
+
+        self.ensure_owned(tenant_id, vlan.tenant)?;
+        Ok(vlan)
+    }
+
+    /// List a tenant's VLANs, optionally within one site or VLAN group
+    pub async fn list_vlans(
+        &self,
+        tenant_id: &TenantId,
+        site_id: Option<i32>,
+        group_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<TenantScopedPage<NetBoxVlan>, AppError> {
+        let netbox_tenant_id = self.access_control
+            .get_netbox_tenant_id(tenant_id)
+            .ok_or(AppError::Unauthorized)?;
+
+        let query = ListQuery {
+            tenant_id: Some(netbox_tenant_id),
+            limit,
+            offset,
+            filters: HashMap::new(),
+        };
+        let response = self.client_for(tenant_id)?.list_vlans(site_id, group_id, &query).await?;
+
+        // Double-check visibility (defense in depth)
+        scoped_page(tenant_id, "VLAN", response, |vlan| vlan.id, |vlans| {
+            Ok(vlans.into_iter().filter(|vlan| vlan.tenant == Some(netbox_tenant_id)).collect())
+        })
+    }
+
+    /// Create a VLAN for a tenant (automatically assigns tenant)
+    pub async fn create_vlan(
+        &self,
+        tenant_id: &TenantId,
+        mut request: CreateVlanRequest,
+    ) -> Result<NetBoxVlan, AppError> {
+        let netbox_tenant_id = self.access_control
+            .get_netbox_tenant_id(tenant_id)
+            .ok_or(AppError::Unauthorized)?;
+        request.tenant = Some(netbox_tenant_id);
+
+        let vlan = self.client_for(tenant_id)?.create_vlan(request).await?;
+
+        self.ensure_owned(tenant_id, vlan.tenant)?;
+        Ok(vlan)
+    }
+
+    /// Update a VLAN with tenant access control
+    ///
+    /// A VLAN can't be handed to another tenant this way; `request.tenant` is ignored.
+    pub async fn update_vlan(
+        &self,
+        tenant_id: &TenantId,
+        vlan_id: i32,
+        mut request: UpdateVlanRequest,
+    ) -> Result<NetBoxVlan, AppError> {
+        let _existing_vlan = self.get_vlan(tenant_id, vlan_id).await?;
+        request.tenant = None;
+
+        let vlan = self.client_for(tenant_id)?.update_vlan(vlan_id, request).await?;
+
+        self.ensure_owned(tenant_id, vlan.tenant)?;
+        Ok(vlan)
+    }
+
+    /// Delete a VLAN with tenant access control
+    pub async fn delete_vlan(&self, tenant_id: &TenantId, vlan_id: i32) -> Result<(), AppError> {
+        let _vlan = self.get_vlan(tenant_id, vlan_id).await?;
+
+        self.client_for(tenant_id)?.delete_vlan(vlan_id).await?;
+
+        Ok(())
+    }
+
+    /// Get a virtualization cluster by ID with tenant access control
+    pub async fn get_cluster(&self, tenant_id: &TenantId, cluster_id: i32) -> Result<NetBoxCluster, AppError> {
+        let cluster = self.client_for(tenant_id)?.get_cluster(cluster_id).await?;
+
+        self.ensure_owned(tenant_id, cluster.tenant)?;
+        Ok(cluster)
+    }
+
+    /// List a tenant's clusters, optionally within one site
+    pub async fn list_clusters(
+        &self,
+        tenant_id: &TenantId,
+        site_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<TenantScopedPage<NetBoxCluster>, AppError> {
+        let netbox_tenant_id = self.access_control
+            .get_netbox_tenant_id(tenant_id)
+            .ok_or(AppError::Unauthorized)?;
+
+        let query = ListQuery {
+            tenant_id: Some(netbox_tenant_id),
+            limit,
+            offset,
+            filters: HashMap::new(),
+        };
+        let response = self.client_for(tenant_id)?.list_clusters(site_id, &query).await?;
+
+        // Double-check visibility (defense in depth)
+        scoped_page(tenant_id, "cluster", response, |cluster| cluster.id, |clusters| {
+            Ok(clusters.into_iter().filter(|cluster| cluster.tenant == Some(netbox_tenant_id)).collect())
+        })
+    }
+
+    /// Get a virtual machine by ID with tenant access control
+    pub async fn get_virtual_machine(
+        &self,
+        tenant_id: &TenantId,
+        vm_id: i32,
+    ) -> Result<NetBoxVirtualMachine, AppError> {
+        let vm = self.client_for(tenant_id)?.get_virtual_machine(vm_id).await?;
+
+        self.ensure_owned(tenant_id, vm.tenant)?;
+        Ok(vm)
+    }
+
+    /// Get many virtual machines at once; VMs that don't exist or belong to another tenant come back as `None`
+    pub async fn get_virtual_machines_by_ids(
+        &self,
+        tenant_id: &TenantId,
+        vm_ids: &[i32],
+    ) -> Result<Vec<(i32, Option<NetBoxVirtualMachine>)>, AppError> {
+        let vms = self.client_for(tenant_id)?.get_virtual_machines_by_ids(vm_ids).await?;
+        Ok(vms
+            .into_iter()
+            .map(|(id, vm)| (id, vm.filter(|vm| self.ensure_owned(tenant_id, vm.tenant).is_ok())))
+            .collect())
+    }
+
+    /// List a tenant's virtual machines, optionally within one site or cluster
+    pub async fn list_virtual_machines(
+        &self,
+        tenant_id: &TenantId,
+        site_id: Option<i32>,
+        cluster_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<TenantScopedPage<NetBoxVirtualMachine>, AppError> {
+        let netbox_tenant_id = self.access_control
+            .get_netbox_tenant_id(tenant_id)
+            .ok_or(AppError::Unauthorized)?;
+
+        let query = ListQuery {
+            tenant_id: Some(netbox_tenant_id),
+            limit,
+            offset,
+            filters: HashMap::new(),
+        };
+        let response = self
+            .client_for(tenant_id)?
+            .list_virtual_machines(site_id, cluster_id, &query)
+            .await?;
+
+        // Double-check visibility (defense in depth)
+        scoped_page(tenant_id, "virtual machine", response, |vm| vm.id, |vms| {
+            Ok(vms.into_iter().filter(|vm| vm.tenant == Some(netbox_tenant_id)).collect())
+        })
+    }
+
+    /// Create a virtual machine for a tenant (automatically assigns tenant)
+    pub async fn create_virtual_machine(
+        &self,
+        tenant_id: &TenantId,
+        mut request: CreateVirtualMachineRequest,
+    ) -> Result<NetBoxVirtualMachine, AppError> {
+        let netbox_tenant_id = self.access_control
+            .get_netbox_tenant_id(tenant_id)
+            .ok_or(AppError::Unauthorized)?;
+        request.tenant = Some(netbox_tenant_id);
+
+        let vm = self.client_for(tenant_id)?.create_virtual_machine(request).await?;
+
+        self.ensure_owned(tenant_id, vm.tenant)?;
+        Ok(vm)
+    }
+
+    /// Update a virtual machine with tenant access control
+    ///
+    /// A VM can't be handed to another tenant this way; `request.tenant` is ignored.
+    pub async fn update_virtual_machine(
+        &self,
+        tenant_id: &TenantId,
+        vm_id: i32,
+        mut request: UpdateVirtualMachineRequest,
+    ) -> Result<NetBoxVirtualMachine, AppError> {
+        let _existing_vm = self.get_virtual_machine(tenant_id, vm_id).await?;
+        request.tenant = None;
+
+        let vm = self.client_for(tenant_id)?.update_virtual_machine(vm_id, request).await?;
+
+        self.ensure_owned(tenant_id, vm.tenant)?;
+        Ok(vm)
+    }
+
+    /// Delete a virtual machine with tenant access control
+    pub async fn delete_virtual_machine(&self, tenant_id: &TenantId, vm_id: i32) -> Result<(), AppError> {
+        let _vm = self.get_virtual_machine(tenant_id, vm_id).await?;
+
+        self.client_for(tenant_id)?.delete_virtual_machine(vm_id).await?;
+
+        Ok(())
+    }
+
+    /// Refuse objects whose NetBox tenant isn't the tenant's own
+    fn ensure_owned(&self, tenant_id: &TenantId, owner: Option<i32>) -> Result<(), AppError> {
+        match owner {
+            Some(owner) if self.access_control.has_access_to_netbox_tenant(tenant_id, owner) => Ok(()),
+            _ => Err(AppError::Unauthorized),
+        }
+    }
+
+    /// List a tenant's racks, optionally within one site
+    pub async fn list_racks(
+        &self,
+        tenant_id: &TenantId,
+        site_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<TenantScopedPage<NetBoxRack>, AppError> {
+        let netbox_tenant_id = self.access_control
+            .get_netbox_tenant_id(tenant_id)
+            .ok_or(AppError::Unauthorized)?;
+
+        let query = ListQuery {
+            tenant_id: Some(netbox_tenant_id),
+            limit,
+            offset,
+            filters: HashMap::new(),
+        };
+        let response = self.client_for(tenant_id)?.list_racks(site_id, &query).await?;
+
+        // Double-check visibility (defense in depth)
+        scoped_page(tenant_id, "rack", response, |rack| rack.id, |racks| {
+            Ok(racks.into_iter().filter(|rack| rack.tenant == Some(netbox_tenant_id)).collect())
+        })
+    }
+
+    /// List a tenant's prefixes, optionally within one site
+    pub async fn list_prefixes(
+        &self,
+        tenant_id: &TenantId,
+        site_id: Option<i32>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<TenantScopedPage<NetBoxPrefix>, AppError> {
+        let netbox_tenant_id = self.access_control
+            .get_netbox_tenant_id(tenant_id)
+            .ok_or(AppError::Unauthorized)?;
+
+        let query = ListQuery {
+            tenant_id: Some(netbox_tenant_id),
+            limit,
+            offset,
+            filters: HashMap::new(),
+        };
+        let response = self.client_for(tenant_id)?.list_prefixes(site_id, &query).await?;
+
+        // Double-check visibility (defense in depth)
+        scoped_page(tenant_id, "prefix", response, |prefix| prefix.id, |prefixes| {
+            Ok(prefixes.into_iter().filter(|prefix| prefix.tenant == Some(netbox_tenant_id)).collect())
+        })
+    }
+
+    /// The tenant's sites with their device counts
+    pub async fn sites_with_device_counts(&self, tenant_id: &TenantId) -> Result<Vec<SiteDeviceCount>, AppError> {
+        let netbox_tenant_id = self.access_control
+            .get_netbox_tenant_id(tenant_id)
+            .ok_or(AppError::Unauthorized)?;
+
+        let sites = self.client_for(tenant_id)?.sites_with_device_counts(Some(netbox_tenant_id)).await?;
+        Ok(owned_by(tenant_id, netbox_tenant_id, "site", sites, |site| (site.id, site.tenant_id)))
+    }
+
+    /// The tenant's devices at a site, with their interfaces
+    pub async fn devices_with_interfaces(
+        &self,
+        tenant_id: &TenantId,
+        site_id: i32,
+    ) -> Result<Vec<DeviceInterfaces>, AppError> {
+        let netbox_tenant_id = self.access_control
+            .get_netbox_tenant_id(tenant_id)
+            .ok_or(AppError::Unauthorized)?;
+
+        let devices = self.client_for(tenant_id)?.devices_with_interfaces(site_id, Some(netbox_tenant_id)).await?;
+        Ok(owned_by(tenant_id, netbox_tenant_id, "device", devices, |device| (device.id, device.tenant_id)))
+    }
+}
+
+/// Keep report rows owned by the tenant, logging any NetBox returned for others
+fn owned_by<T>(
+    tenant_id: &TenantId,
+    netbox_tenant_id: i32,
+    kind: &str,
+    rows: Vec<T>,
+    owner: impl Fn(&T) -> (i32, Option<i32>),
+) -> Vec<T> {
+    let (kept, removed): (Vec<T>, Vec<T>) = rows
+        .into_iter()
+        .partition(|row| owner(row).1 == Some(netbox_tenant_id));
+    if !removed.is_empty() {
+        let ids: Vec<String> = removed.iter().map(|row| owner(row).0.to_string()).collect();
+        warn!(
+            "NetBox returned {} {}(s) outside tenant {}: ids [{}]",
+            removed.len(),
+            kind,
+            tenant_id,
+            ids.join(", ")
+        );
+    }
+    kept
+}
+
+/// Apply visibility filtering to a NetBox page, keeping its pagination metadata
+///
+/// NetBox was asked for the tenant's resources only, so anything dropped here
+/// points at a tenancy misconfiguration in NetBox and is logged.
+fn scoped_page<T>(
+    tenant_id: &TenantId,
+    kind: &str,
+    response: NetBoxResponse<T>,
+    id: impl Fn(&T) -> Option<i32>,
+    filter: impl FnOnce(Vec<T>) -> Result<Vec<T>, AppError>,
+) -> Result<TenantScopedPage<T>, AppError> {
+    let items = response.results.unwrap_or_default();
+    let returned_ids: Vec<Option<i32>> = items.iter().map(&id).collect();
+    let results = filter(items)?;
+
+    let kept: HashSet<Option<i32>> = results.iter().map(&id).collect();
+    let removed: Vec<String> = returned_ids
+        .iter()
+        .filter(|returned| !kept.contains(returned))
+        .map(|returned| returned.map_or_else(|| "?".to_string(), |id| id.to_string()))
+        .collect();
+    if !removed.is_empty() {
+        warn!(
+            "NetBox returned {} {}(s) outside tenant {}: ids [{}]",
+            removed.len(),
+            kind,
+            tenant_id,
+            removed.join(", ")
+        );
+    }
+
+    Ok(TenantScopedPage {
+        filtered_out: returned_ids.len() - results.len(),
+        results,
+        total: response.count,
+        has_more: response.next.is_some(),
+        degraded: response.degraded,
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::config::Config;
+    use crate::netbox::client::NetBoxClient;
+    use crate::netbox::fake::FakeNetBox;
+    use crate::netbox::ResilientNetBoxClient;
+    use crate::resilience::{CircuitBreakerConfig, RetryConfig};
     use crate::netbox::models::{SiteStatus, DeviceStatus};
     use crate::security::tenant::TenantMappingService;
+    use poem::http::Method;
     use serde_json::json;
     use wiremock::{
         matchers::{method, path, query_param},
@@ -223,15 +773,11 @@ mod tests {
             port: 8080,
             netbox_url: base_url,
             netbox_token: token,
+            ..Default::default()
         }
     }
 
-    fn setup_tenant_aware_client(
-        mock_server: &MockServer,
-    ) -> (TenantAwareNetBoxClient, Arc<TenantMappingService>) {
-        let config = create_test_config(mock_server.uri(), "test-token".to_string());
-        let client = Arc::new(NetBoxClient::new(config).unwrap());
-        
+    fn tenant_aware_client(client: Arc<NetBoxClient>) -> (TenantAwareNetBoxClient, Arc<TenantMappingService>) {
         let mapping_service = Arc::new(TenantMappingService::new());
         mapping_service.register_mapping("tenant-1".to_string(), 10);
         mapping_service.register_mapping("tenant-2".to_string(), 20);
@@ -244,50 +790,72 @@ mod tests {
         (tenant_client, mapping_service)
     }
 
+    fn setup_tenant_aware_client(
+        mock_server: &MockServer,
+    ) -> (TenantAwareNetBoxClient, Arc<TenantMappingService>) {
+        let config = create_test_config(mock_server.uri(), "test-token".to_string());
+        tenant_aware_client(Arc::new(NetBoxClient::new(config).unwrap()))
+    }
+
+    fn setup_fake_client(fake: &FakeNetBox) -> TenantAwareNetBoxClient {
+        tenant_aware_client(fake.client()).0
+    }
+
     #[tokio::test]
-    async fn test_get_site_with_tenant_access_control_success() {
+    async fn test_sites_with_device_counts_scoped_to_tenant() {
+        use wiremock::matchers::body_partial_json;
+
         let mock_server = MockServer::start().await;
         let (client, _) = setup_tenant_aware_client(&mock_server);
 
-        let site_response = json!({
-            "id": 1,
-            "name": "Test Site",
-            "tenant": 10,
-            "status": "active"
-        });
-
-        Mock::given(method("GET"))
-            .and(path("/api/dcim/sites/1/"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(&site_response))
+        Mock::given(method("POST"))
+            .and(path("/graphql/"))
+            .and(body_partial_json(json!({"variables": {"tenant_id": ["10"]}})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "data": {"site_list": [
+                    {"id": "1", "name": "Mine", "slug": "mine", "status": "ACTIVE", "tenant": {"id": "10"},
+                     "devices": [{"id": "5"}]},
+                    {"id": "2", "name": "Theirs", "slug": "theirs", "status": "ACTIVE", "tenant": {"id": "20"},
+                     "devices": []}
+                ]}
+            })))
+            .expect(1)
             .mount(&mock_server)
             .await;
 
-        let result = client.get_site(&"tenant-1".to_string(), 1).await;
+        let tenant = "tenant-1".to_string();
+        let sites = client.sites_with_device_counts(&tenant).await.unwrap();
+        assert_eq!(sites.len(), 1);
+        assert_eq!(sites[0].name, "Mine");
+        assert_eq!(sites[0].device_count, 1);
+
+        let unknown = "unknown".to_string();
+        assert!(matches!(
+            client.devices_with_interfaces(&unknown, 1).await,
+            Err(AppError::Unauthorized)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_site_with_tenant_access_control_success() {
+        let fake = FakeNetBox::start().await;
+        let client = setup_fake_client(&fake);
+        let id = fake.seed_site(json!({"name": "Test Site", "tenant": 10, "status": "active"}));
+
+        let result = client.get_site(&"tenant-1".to_string(), id).await;
         assert!(result.is_ok());
         let site = result.unwrap();
-        assert_eq!(site.id, Some(1));
+        assert_eq!(site.id, Some(id));
         assert_eq!(site.tenant, Some(10));
     }
 
     #[tokio::test]
     async fn test_get_site_with_tenant_access_control_unauthorized() {
-        let mock_server = MockServer::start().await;
-        let (client, _) = setup_tenant_aware_client(&mock_server);
-
-        let site_response = json!({
-            "id": 1,
-            "name": "Test Site",
-            "tenant": 20, // Different tenant
-            "status": "active"
-        });
-
-        Mock::given(method("GET"))
-            .and(path("/api/dcim/sites/1/"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(&site_response))
-            .mount(&mock_server)
-            .await;
+        let fake = FakeNetBox::start().await;
+        let client = setup_fake_client(&fake);
+        let id = fake.seed_site(json!({"name": "Test Site", "tenant": 20, "status": "active"}));
 
-        let result = client.get_site(&"tenant-1".to_string(), 1).await;
+        let result = client.get_site(&"tenant-1".to_string(), id).await;
         assert!(result.is_err());
         match result.unwrap_err() {
             AppError::Unauthorized => {}
@@ -297,39 +865,18 @@ mod tests {
 
     #[tokio::test]
     async fn test_list_sites_with_tenant_filter() {
-        let mock_server = MockServer::start().await;
-        let (client, _) = setup_tenant_aware_client(&mock_server);
-
-        let sites_response = json!({
-            "count": 2,
-            "results": [
-                {
-                    "id": 1,
-                    "name": "Site 1",
-                    "tenant": 10,
-                    "status": "active"
-                },
-                {
-                    "id": 2,
-                    "name": "Site 2",
-                    "tenant": 10,
-                    "status": "active"
-                }
-            ]
-        });
-
-        Mock::given(method("GET"))
-            .and(path("/api/dcim/sites/"))
-            .and(query_param("tenant_id", "10"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(&sites_response))
-            .mount(&mock_server)
-            .await;
+        let fake = FakeNetBox::start().await;
+        let client = setup_fake_client(&fake);
+        fake.seed_site(json!({"name": "Site 1", "tenant": 10, "status": "active"}));
+        fake.seed_site(json!({"name": "Site 2", "tenant": 10, "status": "active"}));
+        fake.seed_site(json!({"name": "Other", "tenant": 20, "status": "active"}));
 
         let result = client.list_sites(&"tenant-1".to_string(), None, None).await;
         assert!(result.is_ok());
-        let sites = result.unwrap();
-        assert_eq!(sites.len(), 2);
-        assert!(sites.iter().all(|s| s.tenant == Some(10)));
+        let page = result.unwrap();
+        assert_eq!(page.results.len(), 2);
+        assert_eq!(page.filtered_out, 0);
+        assert!(page.results.iter().all(|s| s.tenant == Some(10)));
     }
 
     #[tokio::test]
@@ -371,301 +918,231 @@ mod tests {
 
         let result = client.list_sites(&"tenant-1".to_string(), None, None).await;
         assert!(result.is_ok());
-        let sites = result.unwrap();
+        let page = result.unwrap();
         // Should filter out tenant-2's site
-        assert_eq!(sites.len(), 2);
-        assert!(sites.iter().all(|s| s.tenant == Some(10)));
+        assert_eq!(page.results.len(), 2);
+        assert_eq!(page.filtered_out, 1);
+        assert!(page.results.iter().all(|s| s.tenant == Some(10)));
     }
 
     #[tokio::test]
     async fn test_create_site_assigns_tenant() {
-        let mock_server = MockServer::start().await;
-        let (client, _) = setup_tenant_aware_client(&mock_server);
-
-        let site_response = json!({
-            "id": 1,
-            "name": "New Site",
-            "tenant": 10,
-            "status": "active"
-        });
-
-        Mock::given(method("POST"))
-            .and(path("/api/dcim/sites/"))
-            .respond_with(ResponseTemplate::new(201).set_body_json(&site_response))
-            .mount(&mock_server)
-            .await;
+        let fake = FakeNetBox::start().await;
+        let client = setup_fake_client(&fake);
 
-        let request = CreateSiteRequest {
-            name: "New Site".to_string(),
-            description: None,
-            slug: None,
-            status: Some(SiteStatus::Active),
-            region: None,
-            tenant: None, // Will be set automatically
-            facility: None,
-            physical_address: None,
-            shipping_address: None,
-            latitude: None,
-            longitude: None,
-            contact_name: None,
-            contact_phone: None,
-            contact_email: None,
-            comments: None,
-            tags: None,
-        };
+        let request = CreateSiteRequest::builder("New Site")
+            .with_status(SiteStatus::Active)
+            .build();
 
         let result = client.create_site(&"tenant-1".to_string(), request).await;
         assert!(result.is_ok());
         let site = result.unwrap();
         assert_eq!(site.tenant, Some(10));
+        assert_eq!(fake.object("dcim/sites", site.id.unwrap()).unwrap()["tenant"], 10);
     }
 
     #[tokio::test]
-    async fn test_update_site_verifies_access() {
-        let mock_server = MockServer::start().await;
-        let (client, _) = setup_tenant_aware_client(&mock_server);
-
-        // First GET to verify access
-        let existing_site = json!({
-            "id": 1,
-            "name": "Existing Site",
-            "tenant": 10,
-            "status": "active"
-        });
-
-        Mock::given(method("GET"))
-            .and(path("/api/dcim/sites/1/"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(&existing_site))
-            .mount(&mock_server)
-            .await;
-
-        // Then PATCH to update
-        let updated_site = json!({
-            "id": 1,
-            "name": "Updated Site",
-            "tenant": 10,
-            "status": "active"
-        });
+    async fn test_tenant_with_own_token_calls_netbox_with_it() {
+        use crate::netbox::{NetBoxEndpoint, NetBoxRouter};
+        use crate::security::tenant::{TenantMapping, DEFAULT_NETBOX_ENDPOINT};
+        use crate::security::TokenCipher;
+
+        let fake = FakeNetBox::start().await;
+        let cipher = TokenCipher::new("tenant-token-key");
+        let mappings = Arc::new(TenantMappingService::new());
+        mappings.register_mapping("globex".to_string(), 10);
+        mappings.register_mapping(
+            "acme".to_string(),
+            TenantMapping::new(20, None).with_netbox_token(Some(cipher.encrypt("acme-token"))),
+        );
+        let config = create_test_config(fake.uri().to_string(), "global-token".to_string());
+        let default = NetBoxEndpoint::new(DEFAULT_NETBOX_ENDPOINT, &config, Arc::new(NetBoxClient::new(config.clone()).unwrap()));
+        let router = Arc::new(NetBoxRouter::new(default, mappings.clone()).with_token_cipher(cipher));
+        let client = TenantAwareNetBoxClient::new(
+            router.default_endpoint().cached_client.clone(),
+            Arc::new(TenantAccessControl::from_shared(mappings)),
+        )
+        .with_router(router.clone());
+
+        let globex = client.create_site(&"globex".to_string(), CreateSiteRequest::builder("Globex").build()).await.unwrap();
+        let acme = client.create_site(&"acme".to_string(), CreateSiteRequest::builder("Acme").build()).await.unwrap();
+
+        assert_eq!(globex.tenant, Some(10));
+        assert_eq!(acme.tenant, Some(20));
+        let tokens: Vec<_> = fake
+            .requests_to(Method::POST, "/api/dcim/sites/")
+            .into_iter()
+            .map(|request| request.token)
+            .collect();
+        assert_eq!(tokens, vec![Some("global-token".to_string()), Some("acme-token".to_string())]);
+        assert_eq!(router.endpoints().len(), 1, "tenant token clients aren't listed as endpoints");
+    }
 
-        Mock::given(method("PATCH"))
-            .and(path("/api/dcim/sites/1/"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(&updated_site))
-            .mount(&mock_server)
-            .await;
+    #[tokio::test]
+    async fn test_update_site_verifies_access() {
+        let fake = FakeNetBox::start().await;
+        let client = setup_fake_client(&fake);
+        let id = fake.seed_site(json!({"name": "Existing Site", "tenant": 10, "status": "active"}));
 
-        let request = UpdateSiteRequest {
-            name: Some("Updated Site".to_string()),
-            description: None,
-            slug: None,
-            status: None,
-            region: None,
-            tenant: None,
-            facility: None,
-            physical_address: None,
-            shipping_address: None,
-            latitude: None,
-            longitude: None,
-            contact_name: None,
-            contact_phone: None,
-            contact_email: None,
-            comments: None,
-            tags: None,
-        };
+        let request = UpdateSiteRequest::builder().with_name("Updated Site").build();
 
-        let result = client.update_site(&"tenant-1".to_string(), 1, request).await;
+        let result = client.update_site(&"tenant-1".to_string(), id, request).await;
         assert!(result.is_ok());
+        assert_eq!(fake.object("dcim/sites", id).unwrap()["name"], "Updated Site");
     }
 
     #[tokio::test]
     async fn test_update_site_unauthorized() {
-        let mock_server = MockServer::start().await;
-        let (client, _) = setup_tenant_aware_client(&mock_server);
-
-        // Site belongs to different tenant
-        let existing_site = json!({
-            "id": 1,
-            "name": "Existing Site",
-            "tenant": 20, // tenant-2
-            "status": "active"
-        });
+        let fake = FakeNetBox::start().await;
+        let client = setup_fake_client(&fake);
+        let id = fake.seed_site(json!({"name": "Existing Site", "tenant": 20, "status": "active"}));
 
-        Mock::given(method("GET"))
-            .and(path("/api/dcim/sites/1/"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(&existing_site))
-            .mount(&mock_server)
-            .await;
+        let request = UpdateSiteRequest::builder().with_name("Updated Site").build();
 
-        let request = UpdateSiteRequest {
-            name: Some("Updated Site".to_string()),
-            description: None,
-            slug: None,
-            status: None,
-            region: None,
-            tenant: None,
-            facility: None,
-            physical_address: None,
-            shipping_address: None,
-            latitude: None,
-            longitude: None,
-            contact_name: None,
-            contact_phone: None,
-            contact_email: None,
-            comments: None,
-            tags: None,
-        };
-
-        let result = client.update_site(&"tenant-1".to_string(), 1, request).await;
+        let result = client.update_site(&"tenant-1".to_string(), id, request).await;
         assert!(result.is_err());
         match result.unwrap_err() {
             AppError::Unauthorized => {}
             _ => panic!("Expected Unauthorized error"),
         }
+        assert_eq!(fake.object("dcim/sites", id).unwrap()["name"], "Existing Site");
     }
 
     #[tokio::test]
     async fn test_delete_site_verifies_access() {
-        let mock_server = MockServer::start().await;
-        let (client, _) = setup_tenant_aware_client(&mock_server);
-
-        // First GET to verify access
-        let existing_site = json!({
-            "id": 1,
-            "name": "Site to Delete",
-            "tenant": 10,
-            "status": "active"
-        });
-
-        Mock::given(method("GET"))
-            .and(path("/api/dcim/sites/1/"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(&existing_site))
-            .mount(&mock_server)
-            .await;
-
-        // Then DELETE
-        Mock::given(method("DELETE"))
-            .and(path("/api/dcim/sites/1/"))
-            .respond_with(ResponseTemplate::new(204))
-            .mount(&mock_server)
-            .await;
+        let fake = FakeNetBox::start().await;
+        let client = setup_fake_client(&fake);
+        let id = fake.seed_site(json!({"name": "Site to Delete", "tenant": 10, "status": "active"}));
 
-        let result = client.delete_site(&"tenant-1".to_string(), 1).await;
+        let result = client.delete_site(&"tenant-1".to_string(), id).await;
         assert!(result.is_ok());
+        assert!(fake.sites().is_empty());
     }
 
     #[tokio::test]
     async fn test_get_device_with_tenant_access_control() {
-        let mock_server = MockServer::start().await;
-        let (client, _) = setup_tenant_aware_client(&mock_server);
+        let fake = FakeNetBox::start().await;
+        let client = setup_fake_client(&fake);
+        let id = fake.seed_device(json!({"name": "Test Device", "tenant": 10, "status": "active"}));
 
-        let device_response = json!({
-            "id": 1,
-            "name": "Test Device",
-            "tenant": 10,
-            "status": "active"
-        });
-
-        Mock::given(method("GET"))
-            .and(path("/api/dcim/devices/1/"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(&device_response))
-            .mount(&mock_server)
-            .await;
-
-        let result = client.get_device(&"tenant-1".to_string(), 1).await;
+        let result = client.get_device(&"tenant-1".to_string(), id).await;
         assert!(result.is_ok());
         let device = result.unwrap();
-        assert_eq!(device.id, Some(1));
+        assert_eq!(device.id, Some(id));
         assert_eq!(device.tenant, Some(10));
     }
 
     #[tokio::test]
     async fn test_list_devices_with_tenant_filter() {
+        let fake = FakeNetBox::start().await;
+        let client = setup_fake_client(&fake);
+        fake.seed_device(json!({"name": "Device 1", "tenant": 10, "status": "active"}));
+        fake.seed_device(json!({"name": "Device 2", "tenant": 10, "status": "active"}));
+        fake.seed_device(json!({"name": "Other", "tenant": 20, "status": "active"}));
+
+        let result = client.list_devices(&"tenant-1".to_string(), None, None, None, None, None).await;
+        assert!(result.is_ok());
+        let devices = result.unwrap().results;
+        assert_eq!(devices.len(), 2);
+        assert!(devices.iter().all(|d| d.tenant == Some(10)));
+    }
+
+    #[tokio::test]
+    async fn test_list_devices_page_with_half_filtered_out() {
         let mock_server = MockServer::start().await;
         let (client, _) = setup_tenant_aware_client(&mock_server);
 
-        let devices_response = json!({
-            "count": 2,
-            "results": [
-                {
-                    "id": 1,
-                    "name": "Device 1",
-                    "tenant": 10,
-                    "status": "active"
-                },
-                {
-                    "id": 2,
-                    "name": "Device 2",
-                    "tenant": 10,
-                    "status": "active"
-                }
-            ]
-        });
-
         Mock::given(method("GET"))
             .and(path("/api/dcim/devices/"))
             .and(query_param("tenant_id", "10"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(&devices_response))
+            .and(query_param("status", "active"))
+            .and(query_param("role_id", "4"))
+            .and(query_param("limit", "4"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 9,
+                "next": "http://netbox/api/dcim/devices/?limit=4&offset=4",
+                "previous": null,
+                "results": [
+                    {"id": 1, "name": "Device 1", "tenant": 10},
+                    {"id": 2, "name": "Device 2", "tenant": 20},
+                    {"id": 3, "name": "Device 3", "tenant": 10},
+                    {"id": 4, "name": "Device 4"}
+                ]
+            })))
+            .expect(1)
             .mount(&mock_server)
             .await;
 
-        let result = client.list_devices(&"tenant-1".to_string(), None, None, None).await;
-        assert!(result.is_ok());
-        let devices = result.unwrap();
-        assert_eq!(devices.len(), 2);
-        assert!(devices.iter().all(|d| d.tenant == Some(10)));
+        let page = client
+            .list_devices(&"tenant-1".to_string(), None, Some(DeviceStatus::Active), Some(4), Some(4), None)
+            .await
+            .unwrap();
+
+        let ids: Vec<_> = page.results.iter().map(|d| d.id).collect();
+        assert_eq!(ids, vec![Some(1), Some(3)]);
+        assert_eq!(page.filtered_out, 2);
+        assert_eq!(page.total, Some(9));
+        assert!(page.has_more);
     }
 
     #[tokio::test]
     async fn test_create_device_assigns_tenant() {
+        let fake = FakeNetBox::start().await;
+        let client = setup_fake_client(&fake);
+
+        let request = CreateDeviceRequest::builder(1, 1, 1)
+            .with_name("New Device")
+            .with_status(DeviceStatus::Active)
+            .build();
+
+        let result = client.create_device(&"tenant-1".to_string(), request).await;
+        assert!(result.is_ok());
+        let device = result.unwrap();
+        assert_eq!(device.tenant, Some(10));
+    }
+
+    #[tokio::test]
+    async fn test_virtual_machines_scoped_to_tenant() {
         let mock_server = MockServer::start().await;
         let (client, _) = setup_tenant_aware_client(&mock_server);
 
-        let device_response = json!({
-            "id": 1,
-            "name": "New Device",
-            "tenant": 10,
-            "status": "active"
-        });
-
-        Mock::given(method("POST"))
-            .and(path("/api/dcim/devices/"))
-            .respond_with(ResponseTemplate::new(201).set_body_json(&device_response))
+        Mock::given(method("GET"))
+            .and(path("/api/virtualization/virtual-machines/"))
+            .and(query_param("tenant_id", "10"))
+            .and(query_param("cluster_id", "3"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 3,
+                "results": [
+                    {"id": 1, "name": "app01", "cluster": 3, "tenant": 10},
+                    {"id": 2, "name": "app02", "cluster": 3, "tenant": 20},
+                    {"id": 3, "name": "app03", "cluster": 3}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/virtualization/virtual-machines/2/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": 2, "name": "app02", "cluster": 3, "tenant": 20
+            })))
             .mount(&mock_server)
             .await;
 
-        let request = CreateDeviceRequest {
-            name: Some("New Device".to_string()),
-            device_type: 1,
-            device_role: 1,
-            site: 1,
-            tenant: None, // Will be set automatically
-            platform: None,
-            serial: None,
-            asset_tag: None,
-            location: None,
-            rack: None,
-            position: None,
-            face: None,
-            status: Some(DeviceStatus::Active),
-            cluster: None,
-            comments: None,
-            tags: None,
-        };
+        let tenant = "tenant-1".to_string();
+        let page = client.list_virtual_machines(&tenant, None, Some(3), None, None).await.unwrap();
+        let ids: Vec<_> = page.results.iter().map(|vm| vm.id).collect();
+        assert_eq!(ids, vec![Some(1)]);
+        assert_eq!(page.filtered_out, 2);
 
-        let result = client.create_device(&"tenant-1".to_string(), request).await;
-        assert!(result.is_ok());
-        let device = result.unwrap();
-        assert_eq!(device.tenant, Some(10));
+        assert!(matches!(client.get_virtual_machine(&tenant, 2).await, Err(AppError::Unauthorized)));
     }
 
     #[tokio::test]
     async fn test_list_sites_no_mapping() {
-        let mock_server = MockServer::start().await;
-        let config = create_test_config(mock_server.uri(), "test-token".to_string());
-        let client = Arc::new(NetBoxClient::new(config).unwrap());
-        
+        let fake = FakeNetBox::start().await;
         let mapping_service = TenantMappingService::new();
         let access_control = Arc::new(TenantAccessControl::new(mapping_service));
-        let tenant_client = TenantAwareNetBoxClient::new(client, access_control);
+        let tenant_client = TenantAwareNetBoxClient::new(fake.client(), access_control);
 
         let result = tenant_client.list_sites(&"nonexistent".to_string(), None, None).await;
         assert!(result.is_err());
@@ -673,33 +1150,47 @@ mod tests {
             AppError::Unauthorized => {}
             _ => panic!("Expected Unauthorized error"),
         }
+        assert!(fake.requests().is_empty());
     }
 
     #[tokio::test]
     async fn test_tenant_isolation_between_tenants() {
-        let mock_server = MockServer::start().await;
-        let (client, _) = setup_tenant_aware_client(&mock_server);
+        let fake = FakeNetBox::start().await;
+        let client = setup_fake_client(&fake);
+        let theirs = fake.seed_site(json!({"name": "Tenant 2 Site", "tenant": 20, "status": "active"}));
 
         // tenant-1 tries to access tenant-2's site
-        let site_response = json!({
-            "id": 1,
-            "name": "Tenant 2 Site",
-            "tenant": 20, // tenant-2
-            "status": "active"
-        });
-
-        Mock::given(method("GET"))
-            .and(path("/api/dcim/sites/1/"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(&site_response))
-            .mount(&mock_server)
-            .await;
-
-        let result = client.get_site(&"tenant-1".to_string(), 1).await;
+        let result = client.get_site(&"tenant-1".to_string(), theirs).await;
         assert!(result.is_err());
         match result.unwrap_err() {
             AppError::Unauthorized => {}
             _ => panic!("Expected Unauthorized error"),
         }
+        assert!(client.get_site(&"tenant-2".to_string(), theirs).await.is_ok());
     }
-}
 
+    #[tokio::test]
+    async fn test_retries_happen_beneath_tenant_wrapper() {
+        let fake = FakeNetBox::start().await;
+        let retry_config = RetryConfig {
+            initial_delay_ms: 1,
+            ..Default::default()
+        };
+        let resilient = Arc::new(ResilientNetBoxClient::with_config(
+            fake.client(),
+            CircuitBreakerConfig::default(),
+            retry_config,
+            std::time::Duration::from_secs(60),
+            Default::default(),
+        ));
+        let mapping_service = TenantMappingService::new();
+        mapping_service.register_mapping("tenant-1".to_string(), 10);
+        let client = TenantAwareNetBoxClient::new(resilient, Arc::new(TenantAccessControl::new(mapping_service)));
+        let id = fake.seed_site(json!({"name": "Test Site", "tenant": 10}));
+        fake.fail_next_reads(1);
+
+        let site = client.get_site(&"tenant-1".to_string(), id).await.unwrap();
+        assert_eq!(site.id, Some(id));
+        assert_eq!(fake.requests().len(), 2);
+    }
+}
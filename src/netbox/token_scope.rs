@@ -0,0 +1,50 @@
+use std::fmt;
+
+/// What the configured API token may do in NetBox, as probed at startup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenScope {
+    /// Reads work, but every create, update and delete is refused
+    ReadOnly,
+    ReadWrite,
+    /// NetBox didn't tell: the token list isn't visible and the OPTIONS probe failed
+    Undetermined,
+}
+
+impl TokenScope {
+    /// `read-only`, `read-write` or `undetermined`, as reported by /health
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenScope::ReadOnly => "read-only",
+            TokenScope::ReadWrite => "read-write",
+            TokenScope::Undetermined => "undetermined",
+        }
+    }
+
+    /// The scope of the token with `key` in a `/api/users/tokens/` listing
+    ///
+    /// NetBox only shows keys when token retrieval is allowed, so the token
+    /// may well not be found; `None` then.
+    pub fn from_token_list(tokens: &[serde_json::Value], key: &str) -> Option<Self> {
+        let token = tokens.iter().find(|token| token.get("key").and_then(|k| k.as_str()) == Some(key))?;
+        match token.get("write_enabled").and_then(|enabled| enabled.as_bool())? {
+            true => Some(TokenScope::ReadWrite),
+            false => Some(TokenScope::ReadOnly),
+        }
+    }
+
+    /// The scope implied by the body of an OPTIONS request to a list endpoint
+    ///
+    /// NetBox describes the POST action only to clients allowed to create.
+    pub fn from_options(body: &serde_json::Value) -> Self {
+        match body.get("actions").and_then(|actions| actions.get("POST")) {
+            Some(_) => TokenScope::ReadWrite,
+            None => TokenScope::ReadOnly,
+        }
+    }
+}
+
+impl fmt::Display for TokenScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
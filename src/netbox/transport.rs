@@ -0,0 +1,357 @@
+use crate::netbox::error::NetBoxError;
+use reqwest::{Certificate, ClientBuilder, Identity, Proxy};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// How NetBoxClient reaches NetBox: proxy, extra trust roots, and client certificate
+#[derive(Debug, Clone, Default)]
+pub struct TransportConfig {
+    /// Proxy for all NetBox traffic, e.g. `http://proxy.corp:3128`
+    pub proxy_url: Option<String>,
+    /// PEM bundle of root CAs trusted in addition to the system ones
+    pub ca_bundle_path: Option<PathBuf>,
+    /// PEM certificate presented to NetBox for mutual TLS
+    pub client_cert_path: Option<PathBuf>,
+    /// PKCS#8 PEM private key for `client_cert_path`
+    pub client_key_path: Option<PathBuf>,
+    /// Skip certificate verification entirely; for lab environments only
+    pub danger_accept_invalid_certs: bool,
+}
+
+impl TransportConfig {
+    /// Load from NETBOX_PROXY_URL, NETBOX_CA_BUNDLE, NETBOX_CLIENT_CERT,
+    /// NETBOX_CLIENT_KEY and NETBOX_DANGER_ACCEPT_INVALID_CERTS
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        Self {
+            proxy_url: var("NETBOX_PROXY_URL"),
+            ca_bundle_path: var("NETBOX_CA_BUNDLE").map(PathBuf::from),
+            client_cert_path: var("NETBOX_CLIENT_CERT").map(PathBuf::from),
+            client_key_path: var("NETBOX_CLIENT_KEY").map(PathBuf::from),
+            danger_accept_invalid_certs: var("NETBOX_DANGER_ACCEPT_INVALID_CERTS")
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(false),
+        }
+    }
+
+    /// Apply the settings to a client builder, reading and checking every file now
+    /// so a bad path fails at startup rather than on the first request
+    pub fn apply(&self, mut builder: ClientBuilder) -> Result<ClientBuilder, NetBoxError> {
+        if let Some(proxy_url) = &self.proxy_url {
+            let proxy = Proxy::all(proxy_url).map_err(|e| {
+                NetBoxError::InvalidConfig(format!("Invalid NetBox proxy URL '{}': {}", proxy_url, e))
+            })?;
+            info!("Routing NetBox traffic through proxy {}", proxy_url);
+            builder = builder.proxy(proxy);
+        }
+
+        if let Some(path) = &self.ca_bundle_path {
+            let pem = read_file(path, "CA bundle")?;
+            let certs = Certificate::from_pem_bundle(&pem).map_err(|e| {
+                NetBoxError::InvalidConfig(format!("Invalid NetBox CA bundle {}: {}", path.display(), e))
+            })?;
+            if certs.is_empty() {
+                return Err(NetBoxError::InvalidConfig(format!(
+                    "NetBox CA bundle {} contains no PEM certificates",
+                    path.display()
+                )));
+            }
+            info!("Trusting {} additional CA certificate(s) from {}", certs.len(), path.display());
+            for cert in certs {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+
+        match (&self.client_cert_path, &self.client_key_path) {
+            (Some(cert_path), Some(key_path)) => {
+                let cert = read_file(cert_path, "client certificate")?;
+                let key = read_file(key_path, "client key")?;
+                let identity = Identity::from_pkcs8_pem(&cert, &key).map_err(|e| {
+                    NetBoxError::InvalidConfig(format!(
+                        "Invalid NetBox client certificate {} or key {}: {}",
+                        cert_path.display(),
+                        key_path.display(),
+                        e
+                    ))
+                })?;
+                builder = builder.identity(identity);
+            }
+            (None, None) => {}
+            _ => {
+                return Err(NetBoxError::InvalidConfig(
+                    "NETBOX_CLIENT_CERT and NETBOX_CLIENT_KEY must be set together".to_string(),
+                ))
+            }
+        }
+
+        if self.danger_accept_invalid_certs {
+            warn!("!!! TLS certificate verification for NetBox is DISABLED; never use this outside a lab !!!");
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+
+        Ok(builder)
+    }
+}
+
+/// Whether NetBoxClient speaks HTTP/2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Http2Mode {
+    /// HTTP/2 when TLS negotiates it (ALPN), HTTP/1.1 otherwise
+    #[default]
+    Auto,
+    /// HTTP/1.1 only
+    Off,
+    /// HTTP/2 from the first byte, for NetBox served over cleartext h2
+    PriorKnowledge,
+}
+
+impl Http2Mode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Http2Mode::Auto => "auto",
+            Http2Mode::Off => "off",
+            Http2Mode::PriorKnowledge => "prior_knowledge",
+        }
+    }
+}
+
+impl FromStr for Http2Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(Http2Mode::Auto),
+            "off" | "false" => Ok(Http2Mode::Off),
+            "prior_knowledge" => Ok(Http2Mode::PriorKnowledge),
+            other => Err(format!("Unknown HTTP/2 mode: {}", other)),
+        }
+    }
+}
+
+/// Connection reuse towards NetBox
+///
+/// The defaults suit a long-lived service talking to a single host: keep
+/// plenty of idle connections so bursts don't reconnect, and send TCP
+/// keepalives so proxies and load balancers don't silently drop them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolConfig {
+    /// Idle connections kept open to NetBox
+    pub max_idle_per_host: usize,
+    /// How long an idle connection is kept; `None` keeps it until the server closes it
+    pub idle_timeout: Option<Duration>,
+    /// TCP keepalive interval; `None` leaves keepalive off
+    pub tcp_keepalive: Option<Duration>,
+    pub http2: Http2Mode,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_idle_per_host: 32,
+            idle_timeout: Some(Duration::from_secs(90)),
+            tcp_keepalive: Some(Duration::from_secs(60)),
+            http2: Http2Mode::Auto,
+        }
+    }
+}
+
+impl PoolConfig {
+    /// Load from NETBOX_POOL_MAX_IDLE_PER_HOST, NETBOX_POOL_IDLE_TIMEOUT_SECS,
+    /// NETBOX_TCP_KEEPALIVE_SECS (0 disables either) and NETBOX_HTTP2
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let var = |name: &str| std::env::var(name).ok();
+        let secs = |name: &str, default: Option<Duration>| match var(name).and_then(|s| s.parse::<u64>().ok()) {
+            Some(0) => None,
+            Some(secs) => Some(Duration::from_secs(secs)),
+            None => default,
+        };
+        Self {
+            max_idle_per_host: var("NETBOX_POOL_MAX_IDLE_PER_HOST")
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(defaults.max_idle_per_host),
+            idle_timeout: secs("NETBOX_POOL_IDLE_TIMEOUT_SECS", defaults.idle_timeout),
+            tcp_keepalive: secs("NETBOX_TCP_KEEPALIVE_SECS", defaults.tcp_keepalive),
+            http2: var("NETBOX_HTTP2")
+                .and_then(|mode| mode.parse().ok())
+                .unwrap_or(defaults.http2),
+        }
+    }
+
+    /// Apply the pool settings to a client builder
+    pub fn apply(&self, builder: ClientBuilder) -> ClientBuilder {
+        let builder = builder
+            .pool_max_idle_per_host(self.max_idle_per_host)
+            .pool_idle_timeout(self.idle_timeout)
+            .tcp_keepalive(self.tcp_keepalive);
+        match self.http2 {
+            Http2Mode::Auto => builder,
+            Http2Mode::Off => builder.http1_only(),
+            Http2Mode::PriorKnowledge => builder.http2_prior_knowledge(),
+        }
+    }
+}
+
+fn read_file(path: &Path, what: &str) -> Result<Vec<u8>, NetBoxError> {
+    std::fs::read(path).map_err(|e| {
+        NetBoxError::InvalidConfig(format!("Cannot read NetBox {} {}: {}", what, path.display(), e))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Self-signed, for exercising the CA bundle path
+    const TEST_CA: &str = "\
+-----BEGIN CERTIFICATE-----\n\
+MIIDETCCAfmgAwIBAgIUMySO37XUPDKunh7nXCsFEr6pDpMwDQYJKoZIhvcNAQEL\n\
+BQAwFzEVMBMGA1UEAwwMbmV0Z2F0ZS10ZXN0MCAXDTI2MTAxNzExMzUwOFoYDzIx\n\
+MjYwOTIzMTEzNTA4WjAXMRUwEwYDVQQDDAxuZXRnYXRlLXRlc3QwggEiMA0GCSqG\n\
+SIb3DQEBAQUAA4IBDwAwggEKAoIBAQDRRFzXGHtcL8cbxkDc1LeRElLCj4NdkUIK\n\
+Ut48oHGmlaX8gx83MSEHF0fsqqyeHh9eCOgDYdFLeG6eAMwsod+n0YbB9QTpLCbh\n\
+bwqeBM1XmwlObpzI9UYki7FKL5u7fLVAGvZFVtAL/ZhcnINW5xk1DjQE4j3DYdbP\n\
+hPG4a9M+ydYjMnF2Zc6pQxj+Gr9XKF3imwKyxTD0Y1qhPCNkosK+WKQn8Ny3B/Kk\n\
+Kw2OUTGzn+96eP7rxqmHCid/J1wWuRewVb6qZwGtsm3P9/ZAXgrV46IT88TuTE2e\n\
+noBp00Z40l7NsZVNmeAJUhCnLdELSKK4xo+GbIP1sZPJ6J4rxuEHAgMBAAGjUzBR\n\
+MB0GA1UdDgQWBBSzmVp69vx85vAfxaJBPK3nC+jhIDAfBgNVHSMEGDAWgBSzmVp6\n\
+9vx85vAfxaJBPK3nC+jhIDAPBgNVHRMBAf8EBTADAQH/MA0GCSqGSIb3DQEBCwUA\n\
+A4IBAQDFiDBAX/JnK3JMvLwnZjcMlZ5VdhMsvts9rBvJhwi8UT336iSNsFFBhKir\n\
+dKG5P+1ecG9x5hxP4siOwpQ05SlVdOslI0oK1e8dZV8DO7QXoV2OekerpdGg2dgx\n\
+qp4yD7WSbczX+h5wsEK1uTuaEDR71qcXF8X1eHw0Sw64keqkuhQOfOGPFhPds07D\n\
+mq96i+yqgnEUrHqod6o2iXON+3XodSI5ewCjV1R8JWeSX11epc2eSTCQwFc5c63F\n\
+/RusaAZd1j5ojdOwQoF+wU0MS46k2aL90WXxGRgVqfZLJTuvTqQ3hX3TCTO2EQRk\n\
+E9PhYQt1pfLsgIsciNWs8p156s0f\n\
+-----END CERTIFICATE-----\n";
+
+    fn temp_file(contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("netgate-transport-{}.pem", uuid::Uuid::new_v4()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn apply(config: TransportConfig) -> Result<ClientBuilder, NetBoxError> {
+        config.apply(reqwest::Client::builder())
+    }
+
+    fn error_message(result: Result<ClientBuilder, NetBoxError>) -> String {
+        match result {
+            Err(NetBoxError::InvalidConfig(message)) => message,
+            Err(other) => panic!("Expected InvalidConfig, got {}", other),
+            Ok(_) => panic!("Expected InvalidConfig, got a builder"),
+        }
+    }
+
+    #[test]
+    fn test_from_env() {
+        std::env::set_var("NETBOX_PROXY_URL", "http://proxy.corp:3128");
+        std::env::set_var("NETBOX_CA_BUNDLE", "/etc/netgate/ca.pem");
+        std::env::set_var("NETBOX_CLIENT_CERT", "");
+        std::env::set_var("NETBOX_DANGER_ACCEPT_INVALID_CERTS", "true");
+
+        let config = TransportConfig::from_env();
+        assert_eq!(config.proxy_url.as_deref(), Some("http://proxy.corp:3128"));
+        assert_eq!(config.ca_bundle_path, Some(PathBuf::from("/etc/netgate/ca.pem")));
+        assert_eq!(config.client_cert_path, None);
+        assert!(config.danger_accept_invalid_certs);
+
+        for name in ["NETBOX_PROXY_URL", "NETBOX_CA_BUNDLE", "NETBOX_CLIENT_CERT", "NETBOX_DANGER_ACCEPT_INVALID_CERTS"] {
+            std::env::remove_var(name);
+        }
+    }
+
+    #[test]
+    fn test_pool_config_from_env() {
+        assert_eq!(PoolConfig::from_env(), PoolConfig::default());
+
+        std::env::set_var("NETBOX_POOL_MAX_IDLE_PER_HOST", "4");
+        std::env::set_var("NETBOX_POOL_IDLE_TIMEOUT_SECS", "0");
+        std::env::set_var("NETBOX_TCP_KEEPALIVE_SECS", "15");
+        std::env::set_var("NETBOX_HTTP2", "OFF");
+
+        let config = PoolConfig::from_env();
+        assert_eq!(config.max_idle_per_host, 4);
+        assert_eq!(config.idle_timeout, None);
+        assert_eq!(config.tcp_keepalive, Some(Duration::from_secs(15)));
+        assert_eq!(config.http2, Http2Mode::Off);
+
+        for name in [
+            "NETBOX_POOL_MAX_IDLE_PER_HOST",
+            "NETBOX_POOL_IDLE_TIMEOUT_SECS",
+            "NETBOX_TCP_KEEPALIVE_SECS",
+            "NETBOX_HTTP2",
+        ] {
+            std::env::remove_var(name);
+        }
+    }
+
+    #[test]
+    fn test_http2_mode_parsing() {
+        assert_eq!("auto".parse(), Ok(Http2Mode::Auto));
+        assert_eq!("prior_knowledge".parse(), Ok(Http2Mode::PriorKnowledge));
+        assert!("sometimes".parse::<Http2Mode>().is_err());
+        assert_eq!(Http2Mode::PriorKnowledge.as_str(), "prior_knowledge");
+    }
+
+    #[test]
+    fn test_valid_settings_apply() {
+        let ca = temp_file(TEST_CA);
+        let config = TransportConfig {
+            proxy_url: Some("http://proxy.corp:3128".to_string()),
+            ca_bundle_path: Some(ca.clone()),
+            danger_accept_invalid_certs: true,
+            ..Default::default()
+        };
+        assert!(apply(config).unwrap().build().is_ok());
+        std::fs::remove_file(ca).unwrap();
+    }
+
+    #[test]
+    fn test_unreadable_ca_bundle() {
+        let message = error_message(apply(TransportConfig {
+            ca_bundle_path: Some(PathBuf::from("/nonexistent/ca.pem")),
+            ..Default::default()
+        }));
+        assert!(message.contains("Cannot read NetBox CA bundle /nonexistent/ca.pem"), "{}", message);
+    }
+
+    #[test]
+    fn test_ca_bundle_without_certificates() {
+        let ca = temp_file("not a certificate");
+        let message = error_message(apply(TransportConfig {
+            ca_bundle_path: Some(ca.clone()),
+            ..Default::default()
+        }));
+        assert!(message.contains("contains no PEM certificates"), "{}", message);
+        std::fs::remove_file(ca).unwrap();
+    }
+
+    #[test]
+    fn test_client_certificate_errors() {
+        let cert = temp_file(TEST_CA);
+        let message = error_message(apply(TransportConfig {
+            client_cert_path: Some(cert.clone()),
+            ..Default::default()
+        }));
+        assert!(message.contains("must be set together"), "{}", message);
+
+        let message = error_message(apply(TransportConfig {
+            client_cert_path: Some(cert.clone()),
+            client_key_path: Some(cert.clone()),
+            ..Default::default()
+        }));
+        assert!(message.contains("Invalid NetBox client certificate"), "{}", message);
+        std::fs::remove_file(cert).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_proxy_url() {
+        let message = error_message(apply(TransportConfig {
+            proxy_url: Some("not a url".to_string()),
+            ..Default::default()
+        }));
+        assert!(message.contains("Invalid NetBox proxy URL"), "{}", message);
+    }
+}
@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Response of NetBox's `/api/status/` endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetBoxStatus {
+    #[serde(rename = "netbox-version")]
+    pub netbox_version: String,
+    /// Installed plugins and their versions
+    #[serde(default)]
+    pub plugins: HashMap<String, serde_json::Value>,
+}
+
+impl NetBoxStatus {
+    /// Parsed NetBox version, if the reported version is recognizable
+    pub fn version(&self) -> Option<NetBoxVersion> {
+        NetBoxVersion::parse(&self.netbox_version)
+    }
+}
+
+/// NetBox release version, e.g. 3.7.2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NetBoxVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl NetBoxVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self { major, minor, patch }
+    }
+
+    /// Parse versions like "3.7.2", "4.0" or "4.1.0-beta1"
+    pub fn parse(version: &str) -> Option<Self> {
+        let release = version.trim().split(['-', '+']).next()?;
+        let mut parts = release.split('.').map(|part| part.parse::<u32>());
+        let major = parts.next()?.ok()?;
+        let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+        let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+        Some(Self::new(major, minor, patch))
+    }
+
+    /// Device payloads use `role` instead of `device_role` from NetBox 3.6
+    pub fn uses_device_role_rename(&self) -> bool {
+        *self >= Self::new(3, 6, 0)
+    }
+
+    /// Tags are written as `{"name": ...}` objects from NetBox 3.0
+    pub fn uses_tag_objects(&self) -> bool {
+        self.major >= 3
+    }
+
+    /// Adapt a serialized request payload to this version's wire format
+    ///
+    /// Requests are modelled in the legacy format (tag names, `device_role`),
+    /// which is also what is sent while the version is unknown.
+    pub fn adapt_payload(&self, payload: &mut serde_json::Value) {
+        let Some(fields) = payload.as_object_mut() else {
+            return;
+        };
+        if self.uses_device_role_rename() {
+            if let Some(role) = fields.remove("device_role") {
+                fields.insert("role".to_string(), role);
+            }
+        }
+        if self.uses_tag_objects() {
+            if let Some(serde_json::Value::Array(tags)) = fields.get_mut("tags") {
+                for tag in tags.iter_mut() {
+                    if let serde_json::Value::String(name) = tag {
+                        *tag = serde_json::json!({ "name": name });
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for NetBoxVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(NetBoxVersion::parse("3.7.2"), Some(NetBoxVersion::new(3, 7, 2)));
+        assert_eq!(NetBoxVersion::parse("4.0"), Some(NetBoxVersion::new(4, 0, 0)));
+        assert_eq!(NetBoxVersion::parse("4.1.0-beta1"), Some(NetBoxVersion::new(4, 1, 0)));
+        assert_eq!(NetBoxVersion::parse("unknown"), None);
+    }
+
+    #[test]
+    fn test_status_deserialization() {
+        let status: NetBoxStatus = serde_json::from_value(json!({
+            "django-version": "4.2.7",
+            "netbox-version": "3.6.5",
+            "plugins": {"netbox_bgp": "0.11.0"},
+            "python-version": "3.11.4"
+        }))
+        .unwrap();
+
+        assert_eq!(status.version(), Some(NetBoxVersion::new(3, 6, 5)));
+        assert_eq!(status.plugins["netbox_bgp"], "0.11.0");
+    }
+
+    #[test]
+    fn test_adapt_payload_for_old_netbox() {
+        let mut payload = json!({"device_role": 2, "tags": ["netgate"]});
+        NetBoxVersion::new(2, 11, 0).adapt_payload(&mut payload);
+
+        assert_eq!(payload, json!({"device_role": 2, "tags": ["netgate"]}));
+    }
+
+    #[test]
+    fn test_adapt_payload_for_new_netbox() {
+        let mut payload = json!({"device_role": 2, "tags": ["netgate"]});
+        NetBoxVersion::new(3, 6, 0).adapt_payload(&mut payload);
+
+        assert_eq!(payload, json!({"role": 2, "tags": [{"name": "netgate"}]}));
+    }
+
+    #[test]
+    fn test_adapt_payload_between_tag_and_role_changes() {
+        let mut payload = json!({"device_role": 2, "tags": ["netgate"]});
+        NetBoxVersion::new(3, 5, 9).adapt_payload(&mut payload);
+
+        assert_eq!(payload, json!({"device_role": 2, "tags": [{"name": "netgate"}]}));
+    }
+}
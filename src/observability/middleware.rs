@@ -1,6 +1,8 @@
+use poem::http::{header, HeaderValue, Method, StatusCode};
 use poem::{
-    Endpoint, Middleware, Request, Result as PoemResult,
+    Endpoint, IntoResponse, Middleware, Request, Response, Result as PoemResult,
 };
+use std::time::Duration;
 use tracing::{info_span, Instrument};
 use uuid::Uuid;
 
@@ -56,6 +58,384 @@ impl<E: Endpoint> Endpoint for RequestTracingEndpoint<E> {
     }
 }
 
+/// Cross-origin access for browser clients such as the web console
+///
+/// Off unless at least one origin is configured; `*` allows any origin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorsConfig {
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    /// How long browsers may cache a preflight response
+    pub max_age: Duration,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            allowed_methods: ["GET", "POST", "PUT", "PATCH", "DELETE"].map(str::to_string).to_vec(),
+            allowed_headers: [
+                "Authorization",
+                "Content-Type",
+                "If-None-Match",
+                "X-Correlation-Id",
+                "X-Roles",
+                "X-Tenant-Id",
+                "X-User-Id",
+            ]
+            .map(str::to_string)
+            .to_vec(),
+            max_age: Duration::from_secs(600),
+        }
+    }
+}
+
+impl CorsConfig {
+    /// Load from CORS_ALLOWED_ORIGINS, CORS_ALLOWED_METHODS, CORS_ALLOWED_HEADERS
+    /// (comma-separated) and CORS_MAX_AGE_SECS
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let list = |name: &str, default: Vec<String>| match std::env::var(name) {
+            Ok(value) => value
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect(),
+            Err(_) => default,
+        };
+        Self {
+            allowed_origins: list("CORS_ALLOWED_ORIGINS", defaults.allowed_origins),
+            allowed_methods: list("CORS_ALLOWED_METHODS", defaults.allowed_methods),
+            allowed_headers: list("CORS_ALLOWED_HEADERS", defaults.allowed_headers),
+            max_age: std::env::var("CORS_MAX_AGE_SECS")
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.max_age),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.allowed_origins.is_empty()
+    }
+
+    fn allows_origin(&self, origin: &str) -> bool {
+        self.allowed_origins.iter().any(|allowed| allowed == "*" || allowed == origin)
+    }
+}
+
+/// Middleware answering CORS preflights and adding CORS headers to responses
+pub struct CorsMiddleware {
+    config: CorsConfig,
+}
+
+impl CorsMiddleware {
+    pub fn new(config: CorsConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for CorsMiddleware {
+    type Output = CorsEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        CorsEndpoint {
+            ep,
+            config: self.config.clone(),
+        }
+    }
+}
+
+/// Endpoint wrapper that applies the CORS policy
+pub struct CorsEndpoint<E> {
+    ep: E,
+    config: CorsConfig,
+}
+
+#[poem::async_trait]
+impl<E: Endpoint> Endpoint for CorsEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> PoemResult<Self::Output> {
+        let origin = req
+            .header(header::ORIGIN)
+            .filter(|origin| self.config.allows_origin(origin))
+            .map(str::to_string);
+        let Some(origin) = origin else {
+            return Ok(call_into_response(&self.ep, req).await);
+        };
+
+        // Preflights carry no credentials, so answer them here, before any
+        // handler checks the caller's headers
+        if req.method() == Method::OPTIONS && req.header(header::ACCESS_CONTROL_REQUEST_METHOD).is_some() {
+            let mut resp = StatusCode::NO_CONTENT.into_response();
+            set_header(&mut resp, header::ACCESS_CONTROL_ALLOW_METHODS, &self.config.allowed_methods.join(", "));
+            set_header(&mut resp, header::ACCESS_CONTROL_ALLOW_HEADERS, &self.config.allowed_headers.join(", "));
+            set_header(&mut resp, header::ACCESS_CONTROL_MAX_AGE, &self.config.max_age.as_secs().to_string());
+            set_origin(&mut resp, &origin);
+            return Ok(resp);
+        }
+
+        let mut resp = call_into_response(&self.ep, req).await;
+        set_origin(&mut resp, &origin);
+        set_header(&mut resp, header::ACCESS_CONTROL_EXPOSE_HEADERS, "ETag, X-Request-Id, X-Correlation-Id");
+        Ok(resp)
+    }
+}
+
+/// Middleware adding standard security headers to every response
+///
+/// API responses carry tenant data, so they are marked `no-store` unless the
+/// handler chose its own caching policy.
+pub struct SecurityHeadersMiddleware;
+
+impl<E: Endpoint> Middleware<E> for SecurityHeadersMiddleware {
+    type Output = SecurityHeadersEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        SecurityHeadersEndpoint { ep }
+    }
+}
+
+/// Endpoint wrapper that adds security headers
+pub struct SecurityHeadersEndpoint<E> {
+    ep: E,
+}
+
+#[poem::async_trait]
+impl<E: Endpoint> Endpoint for SecurityHeadersEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> PoemResult<Self::Output> {
+        let mut resp = call_into_response(&self.ep, req).await;
+        let headers = resp.headers_mut();
+        headers.insert(header::X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+        headers.insert(header::X_FRAME_OPTIONS, HeaderValue::from_static("DENY"));
+        if !headers.contains_key(header::CACHE_CONTROL) {
+            headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+        }
+        Ok(resp)
+    }
+}
+
+/// Middleware marking every response of a deprecated API version
+///
+/// Adds `Deprecation: true` and, when a retirement date is known, `Sunset`
+/// (an HTTP date), so clients can find out before the version goes away.
+pub struct DeprecationMiddleware {
+    sunset: Option<String>,
+}
+
+impl DeprecationMiddleware {
+    pub fn new(sunset: Option<String>) -> Self {
+        Self { sunset }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for DeprecationMiddleware {
+    type Output = DeprecationEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        DeprecationEndpoint {
+            ep,
+            sunset: self.sunset.clone(),
+        }
+    }
+}
+
+/// Endpoint wrapper that adds deprecation headers
+pub struct DeprecationEndpoint<E> {
+    ep: E,
+    sunset: Option<String>,
+}
+
+#[poem::async_trait]
+impl<E: Endpoint> Endpoint for DeprecationEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> PoemResult<Self::Output> {
+        let mut resp = call_into_response(&self.ep, req).await;
+        resp.headers_mut().insert("Deprecation", HeaderValue::from_static("true"));
+        if let Some(ref sunset) = self.sunset {
+            set_header(&mut resp, header::HeaderName::from_static("sunset"), sunset);
+        }
+        Ok(resp)
+    }
+}
+
+/// Cache-Control policy for each class of GET endpoint
+///
+/// Endpoints outside these classes (health, metrics, exports) keep the
+/// `no-store` default of [`SecurityHeadersMiddleware`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpCacheConfig {
+    /// Collections such as `GET /sites`, `GET /orders` and `GET /tenants`
+    pub lists: String,
+    /// Single resources such as `GET /tenants/{id}` and `GET /virtual/{id}`
+    pub resources: String,
+    /// `GET /orders/{id}/status`, polled while an order is in flight
+    pub order_status: String,
+}
+
+impl Default for HttpCacheConfig {
+    fn default() -> Self {
+        Self {
+            lists: "private, max-age=5".to_string(),
+            resources: "private, no-cache".to_string(),
+            order_status: "no-store".to_string(),
+        }
+    }
+}
+
+impl HttpCacheConfig {
+    /// Load from HTTP_CACHE_LISTS, HTTP_CACHE_RESOURCES and HTTP_CACHE_ORDER_STATUS,
+    /// each a Cache-Control header value
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let value = |name: &str, default: String| {
+            std::env::var(name)
+                .ok()
+                .filter(|value| HeaderValue::from_str(value).is_ok())
+                .unwrap_or(default)
+        };
+        Self {
+            lists: value("HTTP_CACHE_LISTS", defaults.lists),
+            resources: value("HTTP_CACHE_RESOURCES", defaults.resources),
+            order_status: value("HTTP_CACHE_ORDER_STATUS", defaults.order_status),
+        }
+    }
+
+    /// Cache-Control for a GET of `path`, if it belongs to a known class
+    fn policy_for(&self, path: &str) -> Option<&str> {
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match segments.as_slice() {
+            ["orders", _, "status"] => Some(&self.order_status),
+            ["sites" | "devices" | "orders" | "tenants" | "virtual"]
+            | ["orders", "types"]
+            | ["tenants", _, "sites" | "webhooks"] => Some(&self.lists),
+            ["orders", "export"] => None,
+            ["tenants" | "virtual", _] => Some(&self.resources),
+            _ => None,
+        }
+    }
+}
+
+/// Middleware adding ETags to GET responses and answering conditional GETs
+///
+/// The ETag is a hash of the response body, so handlers need no changes: a
+/// request whose `If-None-Match` matches gets 304 without a body. Known
+/// endpoint classes also get the Cache-Control from [`HttpCacheConfig`].
+pub struct ConditionalGetMiddleware {
+    config: HttpCacheConfig,
+}
+
+impl ConditionalGetMiddleware {
+    pub fn new(config: HttpCacheConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for ConditionalGetMiddleware {
+    type Output = ConditionalGetEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        ConditionalGetEndpoint {
+            ep,
+            config: self.config.clone(),
+        }
+    }
+}
+
+/// Endpoint wrapper that adds ETags and Cache-Control to GET responses
+pub struct ConditionalGetEndpoint<E> {
+    ep: E,
+    config: HttpCacheConfig,
+}
+
+#[poem::async_trait]
+impl<E: Endpoint> Endpoint for ConditionalGetEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> PoemResult<Self::Output> {
+        if req.method() != Method::GET {
+            return Ok(call_into_response(&self.ep, req).await);
+        }
+        let policy = self.config.policy_for(req.uri().path()).map(str::to_string);
+        let if_none_match = req.header(header::IF_NONE_MATCH).map(str::to_string);
+
+        let mut resp = call_into_response(&self.ep, req).await;
+        // Downloads are streamed and may be large, so they are left alone
+        if resp.status() != StatusCode::OK || resp.headers().contains_key(header::CONTENT_DISPOSITION) {
+            return Ok(resp);
+        }
+        if let Some(policy) = policy {
+            if !resp.headers().contains_key(header::CACHE_CONTROL) {
+                set_header(&mut resp, header::CACHE_CONTROL, &policy);
+                resp.headers_mut().append(header::VARY, HeaderValue::from_static("Accept, X-Tenant-Id"));
+            }
+        }
+
+        let etag = match resp.header(header::ETAG) {
+            Some(etag) => etag.to_string(),
+            None => {
+                let body = resp.take_body().into_bytes().await?;
+                let etag = body_etag(&body);
+                resp.set_body(body);
+                set_header(&mut resp, header::ETAG, &etag);
+                etag
+            }
+        };
+
+        if if_none_match.is_some_and(|candidates| etag_matches(&candidates, &etag)) {
+            let mut not_modified = StatusCode::NOT_MODIFIED.into_response();
+            for name in [header::ETAG, header::CACHE_CONTROL, header::VARY] {
+                for value in resp.headers().get_all(&name) {
+                    not_modified.headers_mut().append(name.clone(), value.clone());
+                }
+            }
+            return Ok(not_modified);
+        }
+        Ok(resp)
+    }
+}
+
+/// Strong ETag: the first 16 bytes of the body's SHA-256, hex-encoded and quoted
+fn body_etag(body: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    let digest = Sha256::digest(body);
+    let hex: String = digest[..16].iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("\"{}\"", hex)
+}
+
+/// Whether an `If-None-Match` list names `etag`, using the weak comparison
+fn etag_matches(candidates: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    candidates
+        .split(',')
+        .any(|candidate| candidate.trim() == "*" || opaque(candidate) == opaque(etag))
+}
+
+/// Call `ep`, turning errors into their responses so headers can be added to them too
+async fn call_into_response<E: Endpoint>(ep: &E, req: Request) -> Response {
+    match ep.call(req).await {
+        Ok(resp) => resp.into_response(),
+        Err(err) => err.into_response(),
+    }
+}
+
+fn set_origin(resp: &mut Response, origin: &str) {
+    set_header(resp, header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+    resp.headers_mut().append(header::VARY, HeaderValue::from_static("Origin"));
+}
+
+fn set_header(resp: &mut Response, name: header::HeaderName, value: &str) {
+    if let Ok(value) = HeaderValue::from_str(value) {
+        resp.headers_mut().insert(name, value);
+    }
+}
+
 /// Extract request ID from request
 pub fn extract_request_id(req: &Request) -> Option<String> {
     req.header("X-Request-Id").map(|s| s.to_string())
@@ -69,7 +449,7 @@ pub fn extract_correlation_id(req: &Request) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use poem::EndpointExt;
+    use poem::{handler, EndpointExt};
 
     #[tokio::test]
     async fn test_request_id_extraction() {
@@ -97,5 +477,161 @@ mod tests {
         let request_id = extract_request_id(&req);
         assert!(request_id.is_none());
     }
-}
 
+    #[handler]
+    fn authenticated(req: &Request) -> Response {
+        match req.header("X-User-Id") {
+            Some(_) => "ok".into_response(),
+            None => StatusCode::UNAUTHORIZED.into_response(),
+        }
+    }
+
+    fn cors_config() -> CorsConfig {
+        CorsConfig {
+            allowed_origins: vec!["https://console.example.com".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cors_preflight_skips_authentication() {
+        let app = authenticated.with(CorsMiddleware::new(cors_config()));
+        let req = Request::builder()
+            .method(Method::OPTIONS)
+            .header("Origin", "https://console.example.com")
+            .header("Access-Control-Request-Method", "POST")
+            .header("Access-Control-Request-Headers", "x-tenant-id")
+            .finish();
+
+        let resp = app.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        assert_eq!(resp.header("Access-Control-Allow-Origin"), Some("https://console.example.com"));
+        assert_eq!(resp.header("Access-Control-Allow-Methods"), Some("GET, POST, PUT, PATCH, DELETE"));
+        assert!(resp.header("Access-Control-Allow-Headers").unwrap().contains("X-Tenant-Id"));
+        assert_eq!(resp.header("Access-Control-Max-Age"), Some("600"));
+        assert_eq!(resp.header("Vary"), Some("Origin"));
+    }
+
+    #[tokio::test]
+    async fn test_cors_simple_request_headers() {
+        let app = authenticated.with(CorsMiddleware::new(cors_config()));
+
+        let allowed = Request::builder()
+            .header("Origin", "https://console.example.com")
+            .header("X-User-Id", "alice")
+            .finish();
+        let resp = app.call(allowed).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.header("Access-Control-Allow-Origin"), Some("https://console.example.com"));
+        assert_eq!(resp.header("Vary"), Some("Origin"));
+
+        // Rejections carry the headers too, so the browser can read them
+        let unauthenticated = Request::builder().header("Origin", "https://console.example.com").finish();
+        let resp = app.call(unauthenticated).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert!(resp.header("Access-Control-Allow-Origin").is_some());
+
+        let other = Request::builder()
+            .header("Origin", "https://evil.example.com")
+            .header("X-User-Id", "alice")
+            .finish();
+        let resp = app.call(other).await.unwrap();
+        assert!(resp.header("Access-Control-Allow-Origin").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cors_off_without_origins() {
+        assert!(!CorsConfig::default().is_enabled());
+        let app = authenticated.with(CorsMiddleware::new(CorsConfig::default()));
+        let req = Request::builder()
+            .method(Method::OPTIONS)
+            .header("Origin", "https://console.example.com")
+            .header("Access-Control-Request-Method", "GET")
+            .finish();
+
+        let resp = app.call(req).await.unwrap();
+        assert_ne!(resp.status(), StatusCode::NO_CONTENT);
+        assert!(resp.header("Access-Control-Allow-Origin").is_none());
+    }
+
+    #[handler]
+    fn sites(req: &Request) -> Response {
+        let tenant = req.header("X-Tenant-Id").unwrap_or_default().to_string();
+        poem::web::Json(serde_json::json!([{ "name": "Site 1", "tenant": tenant }])).into_response()
+    }
+
+    fn conditional_app() -> impl Endpoint<Output = Response> {
+        poem::Route::new()
+            .at("/sites", sites)
+            .at("/orders/:id/status", sites)
+            .at("/health", sites)
+            .with(ConditionalGetMiddleware::new(HttpCacheConfig::default()))
+    }
+
+    fn get(path: &str, if_none_match: Option<&str>) -> Request {
+        let builder = Request::builder().uri_str(path).header("X-Tenant-Id", "tenant1");
+        match if_none_match {
+            Some(etag) => builder.header("If-None-Match", etag).finish(),
+            None => builder.finish(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_conditional_get_returns_not_modified() {
+        let app = conditional_app();
+
+        let first = app.call(get("/sites", None)).await.unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+        assert_eq!(first.header("Cache-Control"), Some("private, max-age=5"));
+        let etag = first.header("ETag").unwrap().to_string();
+        assert!(etag.starts_with('"') && etag.ends_with('"'));
+
+        let mut second = app.call(get("/sites", Some(&etag))).await.unwrap();
+        assert_eq!(second.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(second.header("ETag"), Some(etag.as_str()));
+        assert!(second.take_body().into_bytes().await.unwrap().is_empty());
+
+        let weak = app.call(get("/sites", Some(&format!("\"other\", W/{}", etag)))).await.unwrap();
+        assert_eq!(weak.status(), StatusCode::NOT_MODIFIED);
+        let changed = app.call(get("/sites", Some("\"other\""))).await.unwrap();
+        assert_eq!(changed.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_cache_control_by_endpoint_class() {
+        let app = conditional_app();
+
+        let status = app.call(get("/orders/o-1/status", None)).await.unwrap();
+        assert_eq!(status.header("Cache-Control"), Some("no-store"));
+        assert!(status.header("ETag").is_some());
+
+        // Unclassified endpoints get an ETag but keep the security default
+        let health = app.call(get("/health", None)).await.unwrap();
+        assert!(health.header("Cache-Control").is_none());
+        assert!(health.header("ETag").is_some());
+
+        let post = app.call(Request::builder().method(Method::POST).uri_str("/sites").finish()).await.unwrap();
+        assert!(post.header("ETag").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_security_headers() {
+        #[handler]
+        fn cached() -> Response {
+            Response::builder().header("Cache-Control", "max-age=60").body("cached")
+        }
+
+        let app = authenticated.with(SecurityHeadersMiddleware);
+        let resp = app.call(Request::builder().header("X-User-Id", "alice").finish()).await.unwrap();
+        assert_eq!(resp.header("X-Content-Type-Options"), Some("nosniff"));
+        assert_eq!(resp.header("X-Frame-Options"), Some("DENY"));
+        assert_eq!(resp.header("Cache-Control"), Some("no-store"));
+
+        let resp = app.call(Request::builder().finish()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(resp.header("X-Frame-Options"), Some("DENY"));
+
+        let resp = cached.with(SecurityHeadersMiddleware).call(Request::default()).await.unwrap();
+        assert_eq!(resp.header("Cache-Control"), Some("max-age=60"));
+    }
+}
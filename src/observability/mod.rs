@@ -2,8 +2,6 @@ pub mod middleware;
 pub mod tracing;
 
 // Public API exports (may not be used internally but available for external use)
-#[allow(unused_imports)]
 pub use middleware::*;
-#[allow(unused_imports)]
 pub use tracing::*;
 
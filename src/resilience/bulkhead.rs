@@ -0,0 +1,189 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::timeout;
+use tracing::warn;
+
+/// Bulkhead configuration
+#[derive(Debug, Clone)]
+pub struct BulkheadConfig {
+    /// Maximum number of calls allowed in flight at the same time
+    pub max_concurrent: usize,
+    /// Maximum time a caller waits for a free slot before being rejected
+    pub max_wait: Duration,
+}
+
+impl Default for BulkheadConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 32,
+            max_wait: Duration::from_millis(500),
+        }
+    }
+}
+
+impl BulkheadConfig {
+    pub fn new(max_concurrent: usize, max_wait: Duration) -> Self {
+        Self {
+            max_concurrent,
+            max_wait,
+        }
+    }
+
+    /// Writes are heavier on NetBox, so they get a tighter limit than reads by default
+    pub fn default_write() -> Self {
+        Self {
+            max_concurrent: 8,
+            ..Self::default()
+        }
+    }
+
+    /// Load from `<prefix>_MAX_CONCURRENT` and `<prefix>_MAX_WAIT_MS`, falling back to `defaults`
+    ///
+    /// A limit of zero would refuse every call, so it is ignored.
+    pub fn from_env(prefix: &str, defaults: Self) -> Self {
+        let max_concurrent = std::env::var(format!("{}_MAX_CONCURRENT", prefix))
+            .ok()
+            .and_then(|limit| limit.parse().ok())
+            .filter(|limit| *limit > 0)
+            .unwrap_or(defaults.max_concurrent);
+        let max_wait = std::env::var(format!("{}_MAX_WAIT_MS", prefix))
+            .ok()
+            .and_then(|ms| ms.parse().ok())
+            .map(Duration::from_millis)
+            .unwrap_or(defaults.max_wait);
+        Self::new(max_concurrent, max_wait)
+    }
+}
+
+/// Error returned when the bulkhead has no free slot within `max_wait`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkheadFull {
+    pub max_concurrent: usize,
+    pub waited: Duration,
+}
+
+impl std::fmt::Display for BulkheadFull {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Bulkhead full: {} concurrent calls in flight, no slot freed within {:?}",
+            self.max_concurrent, self.waited
+        )
+    }
+}
+
+/// Semaphore-based bulkhead that bounds concurrent outbound calls
+pub struct Bulkhead {
+    config: BulkheadConfig,
+    semaphore: Arc<Semaphore>,
+}
+
+impl Bulkhead {
+    /// Create a new bulkhead with default configuration
+    pub fn new() -> Self {
+        Self::with_config(BulkheadConfig::default())
+    }
+
+    /// Create a new bulkhead with custom configuration
+    pub fn with_config(config: BulkheadConfig) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(config.max_concurrent)),
+            config,
+        }
+    }
+
+    /// Acquire a slot, waiting at most `max_wait`
+    ///
+    /// The slot is released when the returned permit is dropped.
+    pub async fn acquire(&self) -> Result<OwnedSemaphorePermit, BulkheadFull> {
+        match timeout(self.config.max_wait, Arc::clone(&self.semaphore).acquire_owned()).await {
+            Ok(Ok(permit)) => Ok(permit),
+            // The semaphore is never closed, but treat it as full rather than panicking
+            Ok(Err(_)) | Err(_) => {
+                warn!(
+                    "Bulkhead rejected call: {} in flight, waited {:?}",
+                    self.config.max_concurrent, self.config.max_wait
+                );
+                Err(BulkheadFull {
+                    max_concurrent: self.config.max_concurrent,
+                    waited: self.config.max_wait,
+                })
+            }
+        }
+    }
+
+    /// Take a slot only if one is free right now
+    pub fn try_acquire(&self) -> Option<OwnedSemaphorePermit> {
+        Arc::clone(&self.semaphore).try_acquire_owned().ok()
+    }
+
+    /// Number of free slots
+    pub fn available(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
+    /// Get the bulkhead configuration
+    pub fn config(&self) -> &BulkheadConfig {
+        &self.config
+    }
+}
+
+impl Default for Bulkhead {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bulkhead_allows_up_to_limit() {
+        let bulkhead = Bulkhead::with_config(BulkheadConfig::new(2, Duration::from_millis(10)));
+
+        let _p1 = bulkhead.acquire().await.unwrap();
+        let _p2 = bulkhead.acquire().await.unwrap();
+        assert_eq!(bulkhead.available(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_bulkhead_rejects_when_full() {
+        let bulkhead = Bulkhead::with_config(BulkheadConfig::new(1, Duration::from_millis(10)));
+
+        let _p1 = bulkhead.acquire().await.unwrap();
+        let result = bulkhead.acquire().await;
+        assert_eq!(
+            result.unwrap_err(),
+            BulkheadFull {
+                max_concurrent: 1,
+                waited: Duration::from_millis(10),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bulkhead_releases_on_drop() {
+        let bulkhead = Bulkhead::with_config(BulkheadConfig::new(1, Duration::from_millis(10)));
+
+        let permit = bulkhead.acquire().await.unwrap();
+        drop(permit);
+        assert!(bulkhead.acquire().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_bulkhead_waiter_gets_freed_slot() {
+        let bulkhead = Arc::new(Bulkhead::with_config(BulkheadConfig::new(1, Duration::from_secs(1))));
+
+        let permit = bulkhead.acquire().await.unwrap();
+        let waiter = {
+            let bulkhead = Arc::clone(&bulkhead);
+            tokio::spawn(async move { bulkhead.acquire().await.is_ok() })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drop(permit);
+
+        assert!(waiter.await.unwrap());
+    }
+}
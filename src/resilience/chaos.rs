@@ -0,0 +1,254 @@
+use crate::clock::{SharedClock, SystemClock};
+use crate::config::Config;
+use crate::domain::chaos::{ChaosErrorKind, ChaosExperiment, ChaosExperimentRequest};
+use crate::netbox::error::NetBoxError;
+use parking_lot::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Whether fault injection is built in: debug builds, or release builds with the `chaos` feature
+pub const FAULT_INJECTION_COMPILED_IN: bool = cfg!(any(debug_assertions, feature = "chaos"));
+
+/// Env var that must be `true` on the host, besides `CHAOS_ENABLED`, to allow fault injection
+pub const ALLOW_FAULT_INJECTION_ENV: &str = "NETGATE_ALLOW_FAULT_INJECTION";
+
+/// Longest an experiment may run before it switches itself off
+pub const MAX_EXPERIMENT_DURATION: Duration = Duration::from_secs(3600);
+
+/// Why an experiment was refused
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum ChaosError {
+    #[error("failure_rate must be between 0.0 and 1.0")]
+    InvalidFailureRate,
+
+    #[error("duration_secs must be between 1 and {}", MAX_EXPERIMENT_DURATION.as_secs())]
+    InvalidDuration,
+
+    #[error("An experiment needs a failure_rate above 0 or a latency_ms")]
+    NoFault,
+}
+
+/// What to do to one attempt of a NetBox call
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fault {
+    /// Wait this long before the attempt
+    pub latency: Option<Duration>,
+    /// Fail the attempt with this error instead of calling NetBox
+    pub error: Option<ChaosErrorKind>,
+}
+
+impl Fault {
+    /// The error the attempt fails with, shaped like NetBox's own
+    pub fn netbox_error(&self) -> Option<NetBoxError> {
+        let message = "Injected fault".to_string();
+        self.error.map(|kind| match kind {
+            ChaosErrorKind::ServerError => NetBoxError::from_status_code(500, message),
+            ChaosErrorKind::Unavailable => NetBoxError::from_status_code(503, message),
+            ChaosErrorKind::Timeout => NetBoxError::Timeout(self.latency.unwrap_or_default()),
+            ChaosErrorKind::Authentication => NetBoxError::from_status_code(401, message),
+        })
+    }
+}
+
+struct ActiveExperiment {
+    experiment: ChaosExperiment,
+    expires_at: Instant,
+    injected_errors: AtomicU64,
+    injected_delays: AtomicU64,
+}
+
+impl ActiveExperiment {
+    fn snapshot(&self) -> ChaosExperiment {
+        ChaosExperiment {
+            injected_errors: self.injected_errors.load(Ordering::SeqCst),
+            injected_delays: self.injected_delays.load(Ordering::SeqCst),
+            ..self.experiment.clone()
+        }
+    }
+}
+
+/// Injects failures and latency into NetBox calls, for resilience testing in staging
+///
+/// Resilient clients consult it on every attempt, inside their retries and
+/// circuit breaker, so injected faults exercise the same paths real ones do.
+/// One experiment runs at a time and switches itself off when it expires.
+pub struct ChaosInjector {
+    active: RwLock<Option<ActiveExperiment>>,
+    clock: SharedClock,
+}
+
+impl ChaosInjector {
+    pub fn new() -> Self {
+        Self {
+            active: RwLock::new(None),
+            clock: SystemClock::shared(),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// The injector for this deployment, if fault injection is allowed
+    ///
+    /// Needs a build with fault injection compiled in, `CHAOS_ENABLED` in the
+    /// config, and `NETGATE_ALLOW_FAULT_INJECTION=true` in the environment.
+    pub fn from_config(config: &Config) -> Option<Arc<Self>> {
+        if !config.chaos_enabled {
+            return None;
+        }
+        if !FAULT_INJECTION_COMPILED_IN {
+            warn!("CHAOS_ENABLED is set, but this build has no fault injection (build with the chaos feature)");
+            return None;
+        }
+        let allowed = std::env::var(ALLOW_FAULT_INJECTION_ENV)
+            .map(|value| value.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        if !allowed {
+            warn!("CHAOS_ENABLED is set, but {} is not true; fault injection stays off", ALLOW_FAULT_INJECTION_ENV);
+            return None;
+        }
+        warn!("NetBox fault injection is available via /admin/chaos");
+        Some(Arc::new(Self::new()))
+    }
+
+    /// Start an experiment, replacing any running one
+    pub fn start(&self, request: ChaosExperimentRequest) -> Result<ChaosExperiment, ChaosError> {
+        let failure_rate = request.failure_rate.unwrap_or(0.0);
+        if !(0.0..=1.0).contains(&failure_rate) {
+            return Err(ChaosError::InvalidFailureRate);
+        }
+        let duration = Duration::from_secs(request.duration_secs);
+        if duration.is_zero() || duration > MAX_EXPERIMENT_DURATION {
+            return Err(ChaosError::InvalidDuration);
+        }
+        if failure_rate == 0.0 && request.latency_ms.unwrap_or(0) == 0 {
+            return Err(ChaosError::NoFault);
+        }
+
+        let started_at = self.clock.now_utc();
+        let experiment = ChaosExperiment {
+            experiment_id: uuid::Uuid::new_v4().to_string(),
+            operations: request.operations,
+            failure_rate,
+            latency_ms: request.latency_ms.filter(|ms| *ms > 0),
+            error: request.error.unwrap_or(ChaosErrorKind::ServerError),
+            started_at: started_at.to_rfc3339(),
+            expires_at: (started_at + chrono::Duration::from_std(duration).unwrap_or(chrono::Duration::MAX))
+                .to_rfc3339(),
+            injected_errors: 0,
+            injected_delays: 0,
+        };
+        warn!(
+            "Starting fault injection experiment {} for {:?}: failure rate {}, latency {:?} ms, operations {:?}",
+            experiment.experiment_id, duration, failure_rate, experiment.latency_ms, experiment.operations
+        );
+        *self.active.write() = Some(ActiveExperiment {
+            experiment: experiment.clone(),
+            expires_at: self.clock.now_instant() + duration,
+            injected_errors: AtomicU64::new(0),
+            injected_delays: AtomicU64::new(0),
+        });
+        Ok(experiment)
+    }
+
+    /// Stop the running experiment, returning it
+    pub fn stop(&self) -> Option<ChaosExperiment> {
+        let stopped = self.active.write().take().map(|active| active.snapshot());
+        if let Some(ref experiment) = stopped {
+            info!("Stopped fault injection experiment {}", experiment.experiment_id);
+        }
+        stopped
+    }
+
+    /// The running experiment, if it hasn't expired
+    pub fn current(&self) -> Option<ChaosExperiment> {
+        self.expire();
+        self.active.read().as_ref().map(ActiveExperiment::snapshot)
+    }
+
+    /// The fault to apply to an attempt of `operation`, if any
+    pub fn fault_for(&self, operation: &str) -> Option<Fault> {
+        self.expire();
+        let active = self.active.read();
+        let active = active.as_ref()?;
+        let experiment = &active.experiment;
+        if !experiment.operations.is_empty() && !experiment.operations.iter().any(|op| op == operation) {
+            return None;
+        }
+
+        let latency = experiment.latency_ms.map(Duration::from_millis);
+        let fail = experiment.failure_rate >= 1.0 || fastrand::f64() < experiment.failure_rate;
+        let error = fail.then_some(experiment.error);
+        if latency.is_some() {
+            active.injected_delays.fetch_add(1, Ordering::SeqCst);
+        }
+        if error.is_some() {
+            active.injected_errors.fetch_add(1, Ordering::SeqCst);
+        }
+        (latency.is_some() || error.is_some()).then_some(Fault { latency, error })
+    }
+
+    /// Switch an expired experiment off
+    fn expire(&self) {
+        let now = self.clock.now_instant();
+        let expired = matches!(*self.active.read(), Some(ref active) if now >= active.expires_at);
+        if expired {
+            if let Some(active) = self.active.write().take() {
+                info!(
+                    "Fault injection experiment {} expired after injecting {} error(s) and {} delay(s)",
+                    active.experiment.experiment_id,
+                    active.injected_errors.load(Ordering::SeqCst),
+                    active.injected_delays.load(Ordering::SeqCst)
+                );
+            }
+        }
+    }
+}
+
+impl Default for ChaosInjector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    fn request(failure_rate: Option<f64>, latency_ms: Option<u64>, duration_secs: u64) -> ChaosExperimentRequest {
+        ChaosExperimentRequest {
+            operations: vec!["get_site".to_string()],
+            failure_rate,
+            latency_ms,
+            error: None,
+            duration_secs,
+        }
+    }
+
+    #[test]
+    fn test_experiment_targets_operations_and_switches_itself_off() {
+        let clock = Arc::new(ManualClock::new());
+        let chaos = ChaosInjector::new().with_clock(clock.clone());
+        assert_eq!(chaos.start(request(Some(1.5), None, 60)), Err(ChaosError::InvalidFailureRate));
+        assert_eq!(chaos.start(request(None, None, 60)), Err(ChaosError::NoFault));
+        assert_eq!(chaos.start(request(Some(1.0), None, 0)), Err(ChaosError::InvalidDuration));
+
+        chaos.start(request(Some(1.0), Some(20), 60)).unwrap();
+
+        let fault = chaos.fault_for("get_site").unwrap();
+        assert_eq!(fault.error, Some(ChaosErrorKind::ServerError));
+        assert_eq!(fault.latency, Some(Duration::from_millis(20)));
+        assert!(chaos.fault_for("create_site").is_none());
+        let running = chaos.current().unwrap();
+        assert_eq!((running.injected_errors, running.injected_delays), (1, 1));
+
+        clock.advance(Duration::from_secs(60));
+        assert!(chaos.fault_for("get_site").is_none());
+        assert!(chaos.current().is_none());
+    }
+}
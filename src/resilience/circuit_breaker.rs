@@ -1,8 +1,12 @@
+use std::collections::VecDeque;
 use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use parking_lot::Mutex;
 use std::sync::Arc;
 use std::time::Duration;
 use tracing::{debug, warn};
 
+use crate::clock::{SharedClock, SystemClock};
+
 /// Circuit breaker state
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CircuitState {
@@ -14,17 +18,46 @@ pub enum CircuitState {
     HalfOpen,
 }
 
+/// How the circuit breaker decides to open while closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureMode {
+    /// Open after `failure_threshold` failures with no success in between
+    Consecutive,
+    /// Open when the failure rate within `window_duration` exceeds `failure_rate_threshold`
+    SlidingWindow,
+}
+
+impl std::str::FromStr for FailureMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('-', "_").as_str() {
+            "consecutive" => Ok(FailureMode::Consecutive),
+            "sliding_window" => Ok(FailureMode::SlidingWindow),
+            other => Err(format!("Unknown circuit breaker mode: {}", other)),
+        }
+    }
+}
+
 /// Circuit breaker configuration
 #[derive(Debug, Clone)]
 pub struct CircuitBreakerConfig {
-    /// Failure threshold - number of failures before opening circuit
+    /// Failure threshold - number of failures before opening circuit (consecutive mode)
     pub failure_threshold: u32,
     /// Success threshold - number of successes in half-open to close circuit
     pub success_threshold: u32,
     /// Timeout duration for open state before transitioning to half-open
     pub timeout_duration: Duration,
-    /// Window duration for counting failures
+    /// Window duration for counting failures (sliding window mode)
     pub window_duration: Duration,
+    /// Trip policy used while closed
+    pub failure_mode: FailureMode,
+    /// Failure percentage (0-100) within the window that opens the circuit
+    pub failure_rate_threshold: f64,
+    /// Minimum calls within the window before the failure rate is evaluated
+    pub minimum_calls: u32,
+    /// Maximum number of probe requests in flight while half-open
+    pub half_open_max_calls: u32,
 }
 
 impl Default for CircuitBreakerConfig {
@@ -34,10 +67,94 @@ impl Default for CircuitBreakerConfig {
             success_threshold: 2,
             timeout_duration: Duration::from_secs(60),
             window_duration: Duration::from_secs(60),
+            failure_mode: FailureMode::Consecutive,
+            failure_rate_threshold: 50.0,
+            minimum_calls: 5,
+            half_open_max_calls: 1,
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    /// Defaults overridden by `CIRCUIT_BREAKER_MODE` and, for the sliding window,
+    /// `CIRCUIT_BREAKER_WINDOW_SECS`, `CIRCUIT_BREAKER_FAILURE_RATE` and
+    /// `CIRCUIT_BREAKER_MINIMUM_CALLS`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            failure_mode: std::env::var("CIRCUIT_BREAKER_MODE")
+                .ok()
+                .and_then(|mode| mode.parse().ok())
+                .unwrap_or(defaults.failure_mode),
+            window_duration: std::env::var("CIRCUIT_BREAKER_WINDOW_SECS")
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.window_duration),
+            failure_rate_threshold: std::env::var("CIRCUIT_BREAKER_FAILURE_RATE")
+                .ok()
+                .and_then(|rate| rate.parse().ok())
+                .filter(|rate: &f64| *rate > 0.0 && *rate <= 100.0)
+                .unwrap_or(defaults.failure_rate_threshold),
+            minimum_calls: std::env::var("CIRCUIT_BREAKER_MINIMUM_CALLS")
+                .ok()
+                .and_then(|calls| calls.parse().ok())
+                .unwrap_or(defaults.minimum_calls),
+            ..defaults
         }
     }
 }
 
+/// Timestamped call outcomes within the sliding window
+#[derive(Default)]
+struct OutcomeWindow {
+    /// (timestamp in milliseconds, success)
+    outcomes: VecDeque<(u64, bool)>,
+    failures: u32,
+}
+
+impl OutcomeWindow {
+    fn record(&mut self, now: u64, success: bool, window: Duration) {
+        self.evict(now, window);
+        self.outcomes.push_back((now, success));
+        if !success {
+            self.failures += 1;
+        }
+    }
+
+    /// Drop outcomes that fell out of the window
+    fn evict(&mut self, now: u64, window: Duration) {
+        let cutoff = now.saturating_sub(window.as_millis() as u64);
+        while let Some(&(timestamp, success)) = self.outcomes.front() {
+            if timestamp > cutoff {
+                break;
+            }
+            self.outcomes.pop_front();
+            if !success {
+                self.failures -= 1;
+            }
+        }
+    }
+
+    fn calls(&self) -> u32 {
+        self.outcomes.len() as u32
+    }
+
+    fn failure_rate(&self) -> f64 {
+        if self.outcomes.is_empty() {
+            0.0
+        } else {
+            self.failures as f64 * 100.0 / self.outcomes.len() as f64
+        }
+    }
+
+    fn clear(&mut self) {
+        self.outcomes.clear();
+        self.failures = 0;
+    }
+}
+
 /// Internal state for circuit breaker
 struct CircuitBreakerState {
     state: Arc<AtomicU32>, // 0=Closed, 1=Open, 2=HalfOpen
@@ -45,6 +162,9 @@ struct CircuitBreakerState {
     success_count: Arc<AtomicU32>,
     last_failure_time: Arc<AtomicU64>, // Unix timestamp in milliseconds
     state_changed_time: Arc<AtomicU64>, // When state last changed
+    window: Mutex<OutcomeWindow>,
+    half_open_in_flight: AtomicU32,
+    half_open_epoch: AtomicU64, // Incremented each time the circuit enters HalfOpen
 }
 
 impl CircuitBreakerState {
@@ -55,6 +175,9 @@ impl CircuitBreakerState {
             success_count: Arc::new(AtomicU32::new(0)),
             last_failure_time: Arc::new(AtomicU64::new(0)),
             state_changed_time: Arc::new(AtomicU64::new(0)),
+            window: Mutex::new(OutcomeWindow::default()),
+            half_open_in_flight: AtomicU32::new(0),
+            half_open_epoch: AtomicU64::new(0),
         }
     }
 
@@ -67,20 +190,47 @@ impl CircuitBreakerState {
         }
     }
 
-    fn set_state(&self, new_state: CircuitState) {
+    fn set_state(&self, new_state: CircuitState, now: u64) {
         let state_val = match new_state {
             CircuitState::Closed => 0,
             CircuitState::Open => 1,
             CircuitState::HalfOpen => 2,
         };
         self.state.store(state_val, Ordering::SeqCst);
-        self.state_changed_time.store(
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_millis() as u64,
-            Ordering::SeqCst,
-        );
+        self.state_changed_time.store(now, Ordering::SeqCst);
+    }
+
+    /// Move from Open to HalfOpen; only one concurrent caller wins the transition
+    fn try_half_open(&self, now: u64) -> bool {
+        if self.state.compare_exchange(1, 2, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+            return false;
+        }
+        self.state_changed_time.store(now, Ordering::SeqCst);
+        self.success_count.store(0, Ordering::SeqCst);
+        self.half_open_in_flight.store(0, Ordering::SeqCst);
+        self.half_open_epoch.fetch_add(1, Ordering::SeqCst);
+        true
+    }
+}
+
+/// Permission to make one call through the circuit breaker
+///
+/// While half-open the permit holds one of the limited probe slots, which is
+/// released when the permit is dropped.
+pub struct CircuitPermit<'a> {
+    breaker: &'a CircuitBreaker,
+    probe_epoch: Option<u64>,
+}
+
+impl Drop for CircuitPermit<'_> {
+    fn drop(&mut self) {
+        let state = &self.breaker.state;
+        // Probes from an earlier half-open period no longer hold a slot
+        if self.probe_epoch == Some(state.half_open_epoch.load(Ordering::SeqCst)) {
+            let _ = state
+                .half_open_in_flight
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1));
+        }
     }
 }
 
@@ -88,15 +238,13 @@ impl CircuitBreakerState {
 pub struct CircuitBreaker {
     config: CircuitBreakerConfig,
     state: CircuitBreakerState,
+    clock: SharedClock,
 }
 
 impl CircuitBreaker {
     /// Create a new circuit breaker with default configuration
     pub fn new() -> Self {
-        Self {
-            config: CircuitBreakerConfig::default(),
-            state: CircuitBreakerState::new(),
-        }
+        Self::with_config(CircuitBreakerConfig::default())
     }
 
     /// Create a new circuit breaker with custom configuration
@@ -104,51 +252,91 @@ impl CircuitBreaker {
         Self {
             config,
             state: CircuitBreakerState::new(),
+            clock: SystemClock::shared(),
         }
     }
 
+    /// Use `clock` for the open timeout and the sliding window
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Current time in Unix milliseconds
+    fn now_millis(&self) -> u64 {
+        self.clock.now_utc().timestamp_millis().max(0) as u64
+    }
+
     /// Check if request should be allowed
+    ///
+    /// The half-open probe slot is released immediately; callers that make the
+    /// call afterwards should use [`CircuitBreaker::try_acquire`] instead.
     pub fn allow_request(&self) -> bool {
+        self.try_acquire().is_some()
+    }
+
+    /// Acquire permission to make a call, holding it until the returned permit is dropped
+    pub fn try_acquire(&self) -> Option<CircuitPermit<'_>> {
         let current_state = self.state.get_state();
         
         match current_state {
-            CircuitState::Closed => true,
+            CircuitState::Closed => Some(CircuitPermit {
+                breaker: self,
+                probe_epoch: None,
+            }),
             CircuitState::Open => {
                 // Check if timeout has passed
-                let now = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis() as u64;
+                let now = self.now_millis();
                 let state_changed = self.state.state_changed_time.load(Ordering::SeqCst);
                 
                 if now.saturating_sub(state_changed) >= self.config.timeout_duration.as_millis() as u64 {
                     // Transition to half-open
-                    debug!("Circuit breaker transitioning from Open to HalfOpen");
-                    self.state.set_state(CircuitState::HalfOpen);
-                    self.state.success_count.store(0, Ordering::SeqCst);
-                    true
+                    if self.state.try_half_open(now) {
+                        debug!("Circuit breaker transitioning from Open to HalfOpen");
+                    }
+                    self.try_acquire_probe()
                 } else {
-                    false
+                    None
                 }
             }
-            CircuitState::HalfOpen => true, // Allow limited requests to test recovery
+            CircuitState::HalfOpen => self.try_acquire_probe(), // Allow limited requests to test recovery
         }
     }
 
+    /// Take one of the `half_open_max_calls` probe slots, if any is free
+    fn try_acquire_probe(&self) -> Option<CircuitPermit<'_>> {
+        let epoch = self.state.half_open_epoch.load(Ordering::SeqCst);
+        self.state
+            .half_open_in_flight
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                (n < self.config.half_open_max_calls).then_some(n + 1)
+            })
+            .ok()
+            .map(|_| CircuitPermit {
+                breaker: self,
+                probe_epoch: Some(epoch),
+            })
+    }
+
     /// Record a successful call
     pub fn record_success(&self) {
+        self.record_success_at(self.now_millis());
+    }
+
+    fn record_success_at(&self, now: u64) {
         let current_state = self.state.get_state();
         
         match current_state {
-            CircuitState::Closed => {
+            CircuitState::Closed => match self.config.failure_mode {
                 // Reset failure count on success
-                self.state.failure_count.store(0, Ordering::SeqCst);
-            }
+                FailureMode::Consecutive => self.state.failure_count.store(0, Ordering::SeqCst),
+                FailureMode::SlidingWindow => self.record_in_window(now, true),
+            },
             CircuitState::HalfOpen => {
                 let success_count = self.state.success_count.fetch_add(1, Ordering::SeqCst) + 1;
                 if success_count >= self.config.success_threshold {
                     debug!("Circuit breaker transitioning from HalfOpen to Closed");
-                    self.state.set_state(CircuitState::Closed);
+                    self.state.set_state(CircuitState::Closed, now);
                     self.state.failure_count.store(0, Ordering::SeqCst);
                     self.state.success_count.store(0, Ordering::SeqCst);
                 }
@@ -161,39 +349,75 @@ impl CircuitBreaker {
 
     /// Record a failed call
     pub fn record_failure(&self) {
+        self.record_failure_at(self.now_millis());
+    }
+
+    fn record_failure_at(&self, now: u64) {
         let current_state = self.state.get_state();
         
         match current_state {
+            CircuitState::Closed if self.config.failure_mode == FailureMode::SlidingWindow => {
+                self.state.last_failure_time.store(now, Ordering::SeqCst);
+                self.record_in_window(now, false);
+            }
             CircuitState::Closed => {
                 let failure_count = self.state.failure_count.fetch_add(1, Ordering::SeqCst) + 1;
-                let now = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis() as u64;
                 self.state.last_failure_time.store(now, Ordering::SeqCst);
                 
                 if failure_count >= self.config.failure_threshold {
                     warn!("Circuit breaker transitioning from Closed to Open ({} failures)", failure_count);
-                    self.state.set_state(CircuitState::Open);
+                    self.state.set_state(CircuitState::Open, now);
                 }
             }
             CircuitState::HalfOpen => {
                 // Any failure in half-open immediately opens the circuit
                 warn!("Circuit breaker transitioning from HalfOpen to Open (failure detected)");
-                self.state.set_state(CircuitState::Open);
+                self.state.set_state(CircuitState::Open, now);
                 self.state.success_count.store(0, Ordering::SeqCst);
             }
             CircuitState::Open => {
                 // Already open, just update failure time
-                let now = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis() as u64;
                 self.state.last_failure_time.store(now, Ordering::SeqCst);
             }
         }
     }
 
+    /// Open the circuit now, without waiting for the failure threshold
+    ///
+    /// For failures showing the service is down for a while, such as a
+    /// maintenance page. Recovery then works as after any other opening.
+    pub fn trip(&self) {
+        let now = self.now_millis();
+        self.state.last_failure_time.store(now, Ordering::SeqCst);
+        if self.state.get_state() != CircuitState::Open {
+            warn!("Circuit breaker tripped open");
+            self.state.set_state(CircuitState::Open, now);
+            self.state.success_count.store(0, Ordering::SeqCst);
+        }
+    }
+
+    /// Add an outcome to the sliding window and open the circuit if the failure rate is too high
+    fn record_in_window(&self, now: u64, success: bool) {
+        let mut window = self.state.window.lock();
+        window.record(now, success, self.config.window_duration);
+        self.state.failure_count.store(window.failures, Ordering::SeqCst);
+
+        if success || window.calls() < self.config.minimum_calls {
+            return;
+        }
+        let failure_rate = window.failure_rate();
+        if failure_rate >= self.config.failure_rate_threshold {
+            warn!(
+                "Circuit breaker transitioning from Closed to Open ({:.0}% of {} calls failed)",
+                failure_rate,
+                window.calls()
+            );
+            window.clear();
+            self.state.failure_count.store(0, Ordering::SeqCst);
+            self.state.set_state(CircuitState::Open, now);
+        }
+    }
+
     /// Get current circuit state
     pub fn state(&self) -> CircuitState {
         self.state.get_state()
@@ -206,7 +430,8 @@ impl CircuitBreaker {
 
     /// Reset circuit breaker to closed state
     pub fn reset(&self) {
-        self.state.set_state(CircuitState::Closed);
+        self.state.set_state(CircuitState::Closed, self.now_millis());
+        self.state.window.lock().clear();
         self.state.failure_count.store(0, Ordering::SeqCst);
         self.state.success_count.store(0, Ordering::SeqCst);
     }
@@ -221,8 +446,22 @@ impl Default for CircuitBreaker {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::ManualClock;
     use std::time::Duration;
 
+    /// Breaker driven by a manual clock, so timeouts pass without sleeping
+    fn manual_breaker(config: CircuitBreakerConfig) -> (CircuitBreaker, Arc<ManualClock>) {
+        let clock = Arc::new(ManualClock::new());
+        (CircuitBreaker::with_config(config).with_clock(clock.clone()), clock)
+    }
+
+    fn sliding_window_config() -> CircuitBreakerConfig {
+        CircuitBreakerConfig {
+            failure_mode: FailureMode::SlidingWindow,
+            ..Default::default()
+        }
+    }
+
     #[test]
     fn test_circuit_breaker_starts_closed() {
         let cb = CircuitBreaker::new();
@@ -243,6 +482,18 @@ mod tests {
         assert!(!cb.allow_request());
     }
 
+    #[test]
+    fn test_trip_opens_the_circuit_until_the_timeout() {
+        let (cb, clock) = manual_breaker(CircuitBreakerConfig::default());
+        cb.trip();
+        assert_eq!(cb.state(), CircuitState::Open);
+        assert!(!cb.allow_request());
+
+        clock.advance(cb.config.timeout_duration);
+        assert!(cb.allow_request());
+        assert_eq!(cb.state(), CircuitState::HalfOpen);
+    }
+
     #[test]
     fn test_circuit_breaker_resets_on_success() {
         let cb = CircuitBreaker::new();
@@ -259,9 +510,11 @@ mod tests {
 
     #[test]
     fn test_circuit_breaker_transitions_to_half_open() {
-        let mut config = CircuitBreakerConfig::default();
-        config.timeout_duration = Duration::from_millis(100);
-        let cb = CircuitBreaker::with_config(config);
+        let config = CircuitBreakerConfig {
+            timeout_duration: Duration::from_millis(100),
+            ..Default::default()
+        };
+        let (cb, clock) = manual_breaker(config);
         
         // Open the circuit
         for _ in 0..cb.config.failure_threshold {
@@ -269,8 +522,13 @@ mod tests {
         }
         assert_eq!(cb.state(), CircuitState::Open);
         
-        // Wait for timeout
-        std::thread::sleep(Duration::from_millis(150));
+        // Still open just before the timeout
+        clock.advance(Duration::from_millis(99));
+        assert!(!cb.allow_request());
+        assert_eq!(cb.state(), CircuitState::Open);
+        
+        // Let the timeout pass
+        clock.advance(Duration::from_millis(1));
         
         // Should transition to half-open
         assert!(cb.allow_request());
@@ -279,18 +537,20 @@ mod tests {
 
     #[test]
     fn test_circuit_breaker_closes_after_success_threshold() {
-        let mut config = CircuitBreakerConfig::default();
-        config.timeout_duration = Duration::from_millis(100);
-        config.success_threshold = 2;
-        let cb = CircuitBreaker::with_config(config);
+        let config = CircuitBreakerConfig {
+            timeout_duration: Duration::from_millis(100),
+            success_threshold: 2,
+            ..Default::default()
+        };
+        let (cb, clock) = manual_breaker(config);
         
         // Open the circuit
         for _ in 0..cb.config.failure_threshold {
             cb.record_failure();
         }
         
-        // Wait and transition to half-open
-        std::thread::sleep(Duration::from_millis(150));
+        // Let the timeout pass and transition to half-open
+        clock.advance(Duration::from_millis(150));
         cb.allow_request();
         
         // Record successes
@@ -303,17 +563,19 @@ mod tests {
 
     #[test]
     fn test_circuit_breaker_failure_in_half_open_opens_again() {
-        let mut config = CircuitBreakerConfig::default();
-        config.timeout_duration = Duration::from_millis(100);
-        let cb = CircuitBreaker::with_config(config);
+        let config = CircuitBreakerConfig {
+            timeout_duration: Duration::from_millis(100),
+            ..Default::default()
+        };
+        let (cb, clock) = manual_breaker(config);
         
         // Open the circuit
         for _ in 0..cb.config.failure_threshold {
             cb.record_failure();
         }
         
-        // Wait and transition to half-open
-        std::thread::sleep(Duration::from_millis(150));
+        // Let the timeout pass and transition to half-open
+        clock.advance(Duration::from_millis(150));
         cb.allow_request();
         assert_eq!(cb.state(), CircuitState::HalfOpen);
         
@@ -337,5 +599,131 @@ mod tests {
         assert_eq!(cb.state(), CircuitState::Closed);
         assert_eq!(cb.failure_count(), 0);
     }
-}
 
+    #[test]
+    fn test_consecutive_mode_opens_after_threshold() {
+        let cb = CircuitBreaker::new();
+
+        for _ in 0..cb.config.failure_threshold {
+            cb.record_failure();
+        }
+        assert_eq!(cb.state(), CircuitState::Open);
+    }
+
+    #[test]
+    fn test_failure_mode_parses() {
+        assert_eq!("consecutive".parse(), Ok(FailureMode::Consecutive));
+        assert_eq!("sliding-window".parse(), Ok(FailureMode::SlidingWindow));
+        assert!("rolling".parse::<FailureMode>().is_err());
+    }
+
+    #[test]
+    fn test_sliding_window_opens_on_failure_rate() {
+        let cb = CircuitBreaker::with_config(sliding_window_config());
+        let start = 1_000_000;
+
+        // 3 of 5 calls failing is 60%, above the 50% threshold
+        cb.record_success_at(start);
+        cb.record_success_at(start + 1);
+        cb.record_failure_at(start + 2);
+        cb.record_failure_at(start + 3);
+        assert_eq!(cb.state(), CircuitState::Closed);
+        cb.record_failure_at(start + 4);
+
+        assert_eq!(cb.state(), CircuitState::Open);
+    }
+
+    #[test]
+    fn test_sliding_window_requires_minimum_calls() {
+        let cb = CircuitBreaker::with_config(sliding_window_config());
+
+        for i in 0..cb.config.minimum_calls - 1 {
+            cb.record_failure_at(1_000_000 + i as u64);
+        }
+
+        assert_eq!(cb.state(), CircuitState::Closed);
+        assert_eq!(cb.failure_count(), cb.config.minimum_calls - 1);
+    }
+
+    #[test]
+    fn test_sliding_window_old_failures_age_out() {
+        let cb = CircuitBreaker::with_config(sliding_window_config());
+        let hour = 60 * 60 * 1000;
+
+        // One failure per hour never accumulates within a 60s window
+        for i in 0..10u64 {
+            cb.record_failure_at(i * hour);
+            assert_eq!(cb.failure_count(), 1);
+        }
+
+        assert_eq!(cb.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_sliding_window_successes_dilute_failure_rate() {
+        let cb = CircuitBreaker::with_config(sliding_window_config());
+        let start = 1_000_000;
+
+        for i in 0..6 {
+            cb.record_success_at(start + i);
+        }
+        for i in 6..10 {
+            cb.record_failure_at(start + i);
+        }
+
+        // 4 of 10 calls failing stays below 50%
+        assert_eq!(cb.state(), CircuitState::Closed);
+
+        // Once the successes leave the window, the remaining failures trip the breaker
+        let later = start + cb.config.window_duration.as_millis() as u64 + 5;
+        cb.record_failure_at(later);
+        assert_eq!(cb.state(), CircuitState::Open);
+    }
+
+    #[test]
+    fn test_half_open_limits_in_flight_probes() {
+        let config = CircuitBreakerConfig {
+            timeout_duration: Duration::from_millis(10),
+            half_open_max_calls: 2,
+            failure_mode: FailureMode::Consecutive,
+            ..Default::default()
+        };
+        let (cb, clock) = manual_breaker(config);
+        for _ in 0..cb.config.failure_threshold {
+            cb.record_failure();
+        }
+        clock.advance(Duration::from_millis(20));
+
+        let first = cb.try_acquire();
+        let second = cb.try_acquire();
+        assert!(first.is_some() && second.is_some());
+        assert_eq!(cb.state(), CircuitState::HalfOpen);
+        assert!(cb.try_acquire().is_none());
+
+        // Releasing a probe frees its slot
+        drop(first);
+        assert!(cb.try_acquire().is_some());
+    }
+
+    #[test]
+    fn test_stale_probe_does_not_free_new_half_open_slot() {
+        let config = CircuitBreakerConfig {
+            timeout_duration: Duration::from_millis(10),
+            failure_mode: FailureMode::Consecutive,
+            ..Default::default()
+        };
+        let (cb, clock) = manual_breaker(config);
+        for _ in 0..cb.config.failure_threshold {
+            cb.record_failure();
+        }
+        clock.advance(Duration::from_millis(20));
+
+        let stale = cb.try_acquire().unwrap();
+        cb.record_failure();
+        clock.advance(Duration::from_millis(20));
+        let _current = cb.try_acquire().unwrap();
+
+        drop(stale);
+        assert!(cb.try_acquire().is_none());
+    }
+}
@@ -1,7 +1,9 @@
+use crate::clock::{SharedClock, SystemClock};
 use crate::error::AppError;
 use crate::netbox::models::{NetBoxDevice, NetBoxSite};
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use parking_lot::RwLock;
+use std::sync::Arc;
 use tracing::{debug, warn};
 
 /// Cache for graceful degradation
@@ -11,6 +13,7 @@ pub struct DegradationCache {
     site_lists: Arc<RwLock<HashMap<String, CachedSiteList>>>,
     device_lists: Arc<RwLock<HashMap<String, CachedDeviceList>>>,
     ttl: std::time::Duration,
+    clock: SharedClock,
 }
 
 #[derive(Debug, Clone)]
@@ -45,18 +48,25 @@ impl DegradationCache {
             site_lists: Arc::new(RwLock::new(HashMap::new())),
             device_lists: Arc::new(RwLock::new(HashMap::new())),
             ttl,
+            clock: SystemClock::shared(),
         }
     }
 
-    pub fn default() -> Self {
-        Self::new(std::time::Duration::from_secs(300)) // 5 minutes default TTL
+    /// Use `clock` to age cached entries
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    fn is_fresh(&self, cached_at: std::time::Instant) -> bool {
+        self.clock.now_instant().saturating_duration_since(cached_at) < self.ttl
     }
 
     /// Get cached site if available and not expired
     pub fn get_site(&self, id: i32) -> Option<NetBoxSite> {
-        let sites = self.sites.read().unwrap();
+        let sites = self.sites.read();
         if let Some(cached) = sites.get(&id) {
-            if cached.cached_at.elapsed() < self.ttl {
+            if self.is_fresh(cached.cached_at) {
                 debug!("Returning cached site {}", id);
                 return Some(cached.site.clone());
             }
@@ -66,18 +76,23 @@ impl DegradationCache {
 
     /// Cache a site
     pub fn cache_site(&self, id: i32, site: NetBoxSite) {
-        let mut sites = self.sites.write().unwrap();
+        let mut sites = self.sites.write();
         sites.insert(id, CachedSite {
             site,
-            cached_at: std::time::Instant::now(),
+            cached_at: self.clock.now_instant(),
         });
     }
 
+    /// Remove a site, e.g. after it was deleted
+    pub fn remove_site(&self, id: i32) {
+        self.sites.write().remove(&id);
+    }
+
     /// Get cached device if available and not expired
     pub fn get_device(&self, id: i32) -> Option<NetBoxDevice> {
-        let devices = self.devices.read().unwrap();
+        let devices = self.devices.read();
         if let Some(cached) = devices.get(&id) {
-            if cached.cached_at.elapsed() < self.ttl {
+            if self.is_fresh(cached.cached_at) {
                 debug!("Returning cached device {}", id);
                 return Some(cached.device.clone());
             }
@@ -87,18 +102,23 @@ impl DegradationCache {
 
     /// Cache a device
     pub fn cache_device(&self, id: i32, device: NetBoxDevice) {
-        let mut devices = self.devices.write().unwrap();
+        let mut devices = self.devices.write();
         devices.insert(id, CachedDevice {
             device,
-            cached_at: std::time::Instant::now(),
+            cached_at: self.clock.now_instant(),
         });
     }
 
+    /// Remove a device, e.g. after it was deleted
+    pub fn remove_device(&self, id: i32) {
+        self.devices.write().remove(&id);
+    }
+
     /// Get cached site list if available and not expired
     pub fn get_site_list(&self, key: &str) -> Option<Vec<NetBoxSite>> {
-        let lists = self.site_lists.read().unwrap();
+        let lists = self.site_lists.read();
         if let Some(cached) = lists.get(key) {
-            if cached.cached_at.elapsed() < self.ttl {
+            if self.is_fresh(cached.cached_at) {
                 debug!("Returning cached site list for key: {}", key);
                 return Some(cached.sites.clone());
             }
@@ -108,18 +128,18 @@ impl DegradationCache {
 
     /// Cache a site list
     pub fn cache_site_list(&self, key: String, sites: Vec<NetBoxSite>) {
-        let mut lists = self.site_lists.write().unwrap();
+        let mut lists = self.site_lists.write();
         lists.insert(key, CachedSiteList {
             sites,
-            cached_at: std::time::Instant::now(),
+            cached_at: self.clock.now_instant(),
         });
     }
 
     /// Get cached device list if available and not expired
     pub fn get_device_list(&self, key: &str) -> Option<Vec<NetBoxDevice>> {
-        let lists = self.device_lists.read().unwrap();
+        let lists = self.device_lists.read();
         if let Some(cached) = lists.get(key) {
-            if cached.cached_at.elapsed() < self.ttl {
+            if self.is_fresh(cached.cached_at) {
                 debug!("Returning cached device list for key: {}", key);
                 return Some(cached.devices.clone());
             }
@@ -129,52 +149,88 @@ impl DegradationCache {
 
     /// Cache a device list
     pub fn cache_device_list(&self, key: String, devices: Vec<NetBoxDevice>) {
-        let mut lists = self.device_lists.write().unwrap();
+        let mut lists = self.device_lists.write();
         lists.insert(key, CachedDeviceList {
             devices,
-            cached_at: std::time::Instant::now(),
+            cached_at: self.clock.now_instant(),
         });
     }
 
     /// Clear expired entries
     pub fn clear_expired(&self) {
-        let now = std::time::Instant::now();
         
         // Clear expired sites
         {
-            let mut sites = self.sites.write().unwrap();
-            sites.retain(|_, cached| now.duration_since(cached.cached_at) < self.ttl);
+            let mut sites = self.sites.write();
+            sites.retain(|_, cached| self.is_fresh(cached.cached_at));
         }
         
         // Clear expired devices
         {
-            let mut devices = self.devices.write().unwrap();
-            devices.retain(|_, cached| now.duration_since(cached.cached_at) < self.ttl);
+            let mut devices = self.devices.write();
+            devices.retain(|_, cached| self.is_fresh(cached.cached_at));
         }
         
         // Clear expired site lists
         {
-            let mut lists = self.site_lists.write().unwrap();
-            lists.retain(|_, cached| now.duration_since(cached.cached_at) < self.ttl);
+            let mut lists = self.site_lists.write();
+            lists.retain(|_, cached| self.is_fresh(cached.cached_at));
         }
         
         // Clear expired device lists
         {
-            let mut lists = self.device_lists.write().unwrap();
-            lists.retain(|_, cached| now.duration_since(cached.cached_at) < self.ttl);
+            let mut lists = self.device_lists.write();
+            lists.retain(|_, cached| self.is_fresh(cached.cached_at));
+        }
+    }
+
+    /// Entry counts per kind, including expired entries not yet cleared
+    pub fn stats(&self) -> DegradationCacheStats {
+        let sites = self.sites.read();
+        let devices = self.devices.read();
+        let site_lists = self.site_lists.read();
+        let device_lists = self.device_lists.read();
+        let expired = sites.values().filter(|cached| !self.is_fresh(cached.cached_at)).count()
+            + devices.values().filter(|cached| !self.is_fresh(cached.cached_at)).count()
+            + site_lists.values().filter(|cached| !self.is_fresh(cached.cached_at)).count()
+            + device_lists.values().filter(|cached| !self.is_fresh(cached.cached_at)).count();
+        DegradationCacheStats {
+            sites: sites.len(),
+            devices: devices.len(),
+            site_lists: site_lists.len(),
+            device_lists: device_lists.len(),
+            expired_entries: expired,
         }
     }
 
     /// Clear all cache
     pub fn clear_all(&self) {
-        self.sites.write().unwrap().clear();
-        self.devices.write().unwrap().clear();
-        self.site_lists.write().unwrap().clear();
-        self.device_lists.write().unwrap().clear();
+        self.sites.write().clear();
+        self.devices.write().clear();
+        self.site_lists.write().clear();
+        self.device_lists.write().clear();
+    }
+}
+
+/// Size of the degradation cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DegradationCacheStats {
+    pub sites: usize,
+    pub devices: usize,
+    pub site_lists: usize,
+    pub device_lists: usize,
+    /// Entries past their TTL, which are no longer served
+    pub expired_entries: usize,
+}
+
+impl Default for DegradationCache {
+    fn default() -> Self {
+        Self::new(std::time::Duration::from_secs(300)) // 5 minutes default TTL
     }
 }
 
 /// Graceful degradation strategies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DegradationStrategy {
     /// Return cached data if available
     UseCache,
@@ -186,6 +242,57 @@ pub enum DegradationStrategy {
     ReturnPartial,
 }
 
+impl std::str::FromStr for DegradationStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('-', "_").as_str() {
+            "use_cache" => Ok(DegradationStrategy::UseCache),
+            "return_empty" => Ok(DegradationStrategy::ReturnEmpty),
+            "return_error" => Ok(DegradationStrategy::ReturnError),
+            "return_partial" => Ok(DegradationStrategy::ReturnPartial),
+            other => Err(format!("Unknown degradation strategy: {}", other)),
+        }
+    }
+}
+
+/// Degradation strategy per operation class
+///
+/// Writes are never degraded: a failed create/update always surfaces its error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DegradationConfig {
+    /// Strategy for single-resource reads (get_site, get_device)
+    pub get_strategy: DegradationStrategy,
+    /// Strategy for list reads (list_sites, list_devices)
+    pub list_strategy: DegradationStrategy,
+}
+
+impl Default for DegradationConfig {
+    fn default() -> Self {
+        Self {
+            get_strategy: DegradationStrategy::UseCache,
+            list_strategy: DegradationStrategy::UseCache,
+        }
+    }
+}
+
+impl DegradationConfig {
+    /// Load degradation strategies from environment variables, falling back to UseCache
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            get_strategy: std::env::var("DEGRADATION_GET_STRATEGY")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(defaults.get_strategy),
+            list_strategy: std::env::var("DEGRADATION_LIST_STRATEGY")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(defaults.list_strategy),
+        }
+    }
+}
+
 /// Apply graceful degradation for site retrieval
 pub fn degrade_site_retrieval(
     cache: &DegradationCache,
@@ -218,22 +325,25 @@ pub fn degrade_site_retrieval(
 }
 
 /// Apply graceful degradation for site list retrieval
+///
+/// Returns `Ok(None)` when the strategy has nothing to serve (UseCache with a cold cache),
+/// so the caller can surface the original error.
 pub fn degrade_site_list_retrieval(
     cache: &DegradationCache,
     cache_key: &str,
     strategy: DegradationStrategy,
-) -> Result<Vec<NetBoxSite>, AppError> {
+) -> Result<Option<Vec<NetBoxSite>>, AppError> {
     match strategy {
         DegradationStrategy::UseCache => {
             if let Some(cached_sites) = cache.get_site_list(cache_key) {
                 warn!("Using cached site list for key {} due to service degradation", cache_key);
-                return Ok(cached_sites);
+                return Ok(Some(cached_sites));
             }
-            Ok(vec![])
+            Ok(None)
         }
         DegradationStrategy::ReturnEmpty => {
             warn!("Returning empty site list due to service degradation");
-            Ok(vec![])
+            Ok(Some(vec![]))
         }
         DegradationStrategy::ReturnError => {
             Err(AppError::Internal(anyhow::anyhow!("Service unavailable")))
@@ -241,9 +351,10 @@ pub fn degrade_site_list_retrieval(
         DegradationStrategy::ReturnPartial => {
             if let Some(cached_sites) = cache.get_site_list(cache_key) {
                 warn!("Returning cached site list as partial data");
-                return Ok(cached_sites);
+                return Ok(Some(cached_sites));
             }
-            Ok(vec![])
+            warn!("No cached site list for key {}, returning empty partial result", cache_key);
+            Ok(Some(vec![]))
         }
     }
 }
@@ -251,6 +362,7 @@ pub fn degrade_site_list_retrieval(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::ManualClock;
     use crate::netbox::models::{SiteStatus, DeviceStatus};
     use std::time::Duration;
 
@@ -286,12 +398,15 @@ mod tests {
 
     #[test]
     fn test_cache_expires() {
-        let cache = DegradationCache::new(Duration::from_millis(10));
+        let clock = Arc::new(ManualClock::new());
+        let cache = DegradationCache::new(Duration::from_millis(10)).with_clock(clock.clone());
         let site = create_test_site(1);
         
         cache.cache_site(1, site);
-        std::thread::sleep(Duration::from_millis(20));
-        
+        clock.advance(Duration::from_millis(9));
+        assert!(cache.get_site(1).is_some());
+
+        clock.advance(Duration::from_millis(1));
         let retrieved = cache.get_site(1);
         assert!(retrieved.is_none());
     }
@@ -344,14 +459,46 @@ mod tests {
         cache.cache_site_list("key1".to_string(), sites);
         
         let result = degrade_site_list_retrieval(&cache, "key1", DegradationStrategy::UseCache).unwrap();
-        assert_eq!(result.len(), 2);
+        assert_eq!(result.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_degrade_site_list_retrieval_use_cache_cold() {
+        let cache = DegradationCache::new(Duration::from_secs(60));
+        let result = degrade_site_list_retrieval(&cache, "key1", DegradationStrategy::UseCache).unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_degrade_site_list_retrieval_return_partial_cold() {
+        let cache = DegradationCache::new(Duration::from_secs(60));
+        let result = degrade_site_list_retrieval(&cache, "key1", DegradationStrategy::ReturnPartial).unwrap();
+        assert!(result.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_degrade_site_list_retrieval_return_error() {
+        let cache = DegradationCache::new(Duration::from_secs(60));
+        cache.cache_site_list("key1".to_string(), vec![create_test_site(1)]);
+        let result = degrade_site_list_retrieval(&cache, "key1", DegradationStrategy::ReturnError);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_degradation_strategy_from_str() {
+        assert_eq!("use_cache".parse::<DegradationStrategy>(), Ok(DegradationStrategy::UseCache));
+        assert_eq!("return-empty".parse::<DegradationStrategy>(), Ok(DegradationStrategy::ReturnEmpty));
+        assert_eq!("RETURN_ERROR".parse::<DegradationStrategy>(), Ok(DegradationStrategy::ReturnError));
+        assert_eq!("return_partial".parse::<DegradationStrategy>(), Ok(DegradationStrategy::ReturnPartial));
+        assert!("bogus".parse::<DegradationStrategy>().is_err());
     }
 
     #[test]
     fn test_clear_expired() {
-        let cache = DegradationCache::new(Duration::from_millis(10));
+        let clock = Arc::new(ManualClock::new());
+        let cache = DegradationCache::new(Duration::from_millis(10)).with_clock(clock.clone());
         cache.cache_site(1, create_test_site(1));
-        std::thread::sleep(Duration::from_millis(20));
+        clock.advance(Duration::from_millis(20));
         cache.clear_expired();
         
         assert!(cache.get_site(1).is_none());
@@ -368,5 +515,26 @@ mod tests {
         assert!(cache.get_site(1).is_none());
         assert!(cache.get_device(1).is_none());
     }
-}
 
+    #[test]
+    fn test_stats_count_entries_and_expired() {
+        let clock = Arc::new(ManualClock::new());
+        let cache = DegradationCache::new(Duration::from_secs(10)).with_clock(clock.clone());
+        cache.cache_site(1, create_test_site(1));
+        clock.advance(Duration::from_secs(10));
+        cache.cache_site(2, create_test_site(2));
+        cache.cache_device(1, create_test_device(1));
+        cache.cache_site_list("all".to_string(), vec![create_test_site(2)]);
+
+        assert_eq!(
+            cache.stats(),
+            DegradationCacheStats {
+                sites: 2,
+                devices: 1,
+                site_lists: 1,
+                device_lists: 0,
+                expired_entries: 1,
+            }
+        );
+    }
+}
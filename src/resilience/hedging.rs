@@ -0,0 +1,184 @@
+use crate::resilience::bulkhead::Bulkhead;
+use crate::resilience::metrics::ApiMetrics;
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::debug;
+
+/// Run an idempotent request, issuing a second concurrent attempt if the first
+/// has not completed within `delay`
+///
+/// The first successful attempt wins and the other is cancelled by dropping it.
+/// An error is only returned once both attempts have failed, so a single slow or
+/// failed attempt is never surfaced to the circuit breaker. Only use this for reads.
+///
+/// The caller holds a slot of `bulkhead` for the first attempt. The second takes
+/// another while it runs, so the bulkhead keeps counting NetBox calls in flight;
+/// when none is free it is not sent and the first attempt is awaited alone.
+pub async fn hedged_request<F, Fut, T, E>(
+    delay: Duration,
+    metrics: &ApiMetrics,
+    bulkhead: &Bulkhead,
+    mut attempt: F,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let primary = attempt();
+    tokio::pin!(primary);
+
+    tokio::select! {
+        result = &mut primary => return result,
+        _ = sleep(delay) => {}
+    }
+
+    let Some(_hedge_slot) = bulkhead.try_acquire() else {
+        debug!("Request exceeded hedge delay of {:?}, but no bulkhead slot is free to hedge it", delay);
+        return primary.await;
+    };
+    debug!("Request exceeded hedge delay of {:?}, issuing hedged attempt", delay);
+    metrics.record_hedged_request();
+    let hedge = attempt();
+    tokio::pin!(hedge);
+
+    tokio::select! {
+        result = &mut primary => match result {
+            Ok(value) => Ok(value),
+            Err(_) => {
+                let result = hedge.await;
+                if result.is_ok() {
+                    metrics.record_hedge_win();
+                }
+                result
+            }
+        },
+        result = &mut hedge => match result {
+            Ok(value) => {
+                metrics.record_hedge_win();
+                Ok(value)
+            }
+            Err(_) => primary.await,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resilience::bulkhead::BulkheadConfig;
+    use parking_lot::Mutex;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Attempts resolve after the given delays, in call order
+    fn delayed_attempts(
+        delays: &'static [(u64, Result<&'static str, &'static str>)],
+    ) -> impl FnMut() -> std::pin::Pin<Box<dyn Future<Output = Result<&'static str, &'static str>>>> {
+        let calls = AtomicU32::new(0);
+        move || {
+            let (delay_ms, result) = delays[calls.fetch_add(1, Ordering::SeqCst) as usize];
+            Box::pin(async move {
+                sleep(Duration::from_millis(delay_ms)).await;
+                result
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fast_request_is_not_hedged() {
+        let metrics = ApiMetrics::new();
+        let result = hedged_request(
+            Duration::from_millis(100),
+            &metrics,
+            &Bulkhead::new(),
+            delayed_attempts(&[(10, Ok("primary"))]),
+        )
+        .await;
+
+        assert_eq!(result, Ok("primary"));
+        assert_eq!(metrics.hedged_requests(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_fast_hedge_wins_over_slow_primary() {
+        let metrics = ApiMetrics::new();
+        let result = hedged_request(
+            Duration::from_millis(20),
+            &metrics,
+            &Bulkhead::new(),
+            delayed_attempts(&[(2000, Ok("primary")), (10, Ok("hedge"))]),
+        )
+        .await;
+
+        assert_eq!(result, Ok("hedge"));
+        assert_eq!(metrics.hedged_requests(), 1);
+        assert_eq!(metrics.hedge_wins(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_hedge_waits_for_primary() {
+        let metrics = ApiMetrics::new();
+        let result = hedged_request(
+            Duration::from_millis(20),
+            &metrics,
+            &Bulkhead::new(),
+            delayed_attempts(&[(100, Ok("primary")), (10, Err("hedge"))]),
+        )
+        .await;
+
+        assert_eq!(result, Ok("primary"));
+        assert_eq!(metrics.hedge_wins(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_error_only_when_both_attempts_fail() {
+        let metrics = ApiMetrics::new();
+        let result = hedged_request(
+            Duration::from_millis(20),
+            &metrics,
+            &Bulkhead::new(),
+            delayed_attempts(&[(50, Err("primary")), (100, Err("hedge"))]),
+        )
+        .await;
+
+        assert_eq!(result, Err("hedge"));
+        assert_eq!(metrics.hedged_requests(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_hedge_holds_its_own_bulkhead_slot() {
+        let metrics = ApiMetrics::new();
+        let bulkhead = Bulkhead::with_config(BulkheadConfig::new(2, Duration::from_millis(10)));
+        let _primary_slot = bulkhead.try_acquire().unwrap();
+        let free_slots = Mutex::new(Vec::new());
+        let mut attempts = delayed_attempts(&[(200, Ok("primary")), (10, Ok("hedge"))]);
+
+        let result = hedged_request(Duration::from_millis(20), &metrics, &bulkhead, || {
+            free_slots.lock().push(bulkhead.available());
+            attempts()
+        })
+        .await;
+
+        assert_eq!(result, Ok("hedge"));
+        assert_eq!(*free_slots.lock(), vec![1, 0]);
+        assert_eq!(bulkhead.available(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_no_hedge_without_a_free_bulkhead_slot() {
+        let metrics = ApiMetrics::new();
+        let bulkhead = Bulkhead::with_config(BulkheadConfig::new(1, Duration::from_millis(10)));
+        let _primary_slot = bulkhead.try_acquire().unwrap();
+
+        let result = hedged_request(
+            Duration::from_millis(20),
+            &metrics,
+            &bulkhead,
+            delayed_attempts(&[(100, Ok("primary")), (10, Ok("hedge"))]),
+        )
+        .await;
+
+        assert_eq!(result, Ok("primary"));
+        assert_eq!(metrics.hedged_requests(), 0);
+    }
+}
@@ -1,8 +1,18 @@
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+/// Upper bounds, in milliseconds, of the response time histogram buckets
+pub const RESPONSE_TIME_BUCKETS_MS: [u64; 11] = [5, 10, 25, 50, 100, 250, 500, 1000, 2500, 5000, 10000];
 
 /// Metrics for tracking API call performance and health
+///
+/// Counters are plain atomics bumped with `Relaxed`, except a request's
+/// outcome, which is published with `Release` after its start, duration and
+/// histogram bucket were recorded. A snapshot reads the outcomes first, with
+/// `Acquire`, so every request it counts as finished is also counted as
+/// started: it may lag writers slightly, but never shows more successes than
+/// requests. Nothing here takes a lock, so snapshots never block recorders.
 #[derive(Debug, Clone)]
 pub struct ApiMetrics {
     /// Total number of requests
@@ -13,10 +23,42 @@ pub struct ApiMetrics {
     failed_requests: Arc<AtomicU64>,
     /// Total response time in milliseconds
     total_response_time_ms: Arc<AtomicU64>,
+    /// Finished requests per bucket of [`RESPONSE_TIME_BUCKETS_MS`], plus one past the last bound
+    response_time_buckets: Arc<[AtomicU64; RESPONSE_TIME_BUCKETS_MS.len() + 1]>,
     /// Number of retries performed
     total_retries: Arc<AtomicU64>,
     /// Number of circuit breaker rejections
     circuit_breaker_rejections: Arc<AtomicU64>,
+    /// Number of calls rejected because the bulkhead was full
+    bulkhead_rejections: Arc<AtomicU64>,
+    /// Calls delayed by the outbound rate limit
+    throttled_requests: Arc<AtomicU64>,
+    /// Time calls spent waiting on the outbound rate limit
+    throttled_time_ms: Arc<AtomicU64>,
+    /// Calls refused because the outbound rate limit would have delayed them too long
+    rate_limit_rejections: Arc<AtomicU64>,
+    /// Number of reads that issued a hedged second attempt
+    hedged_requests: Arc<AtomicU64>,
+    /// Number of hedged reads answered by the second attempt
+    hedge_wins: Arc<AtomicU64>,
+    /// Number of attempts abandoned after the per-attempt timeout
+    timeouts: Arc<AtomicU64>,
+    /// Number of calls abandoned after the total deadline across retries
+    deadline_exceeded: Arc<AtomicU64>,
+    /// Number of attempts answered with a non-JSON 502/503/504, e.g. a maintenance page
+    maintenance_responses: Arc<AtomicU64>,
+    /// Number of background probes sent while the circuit was open
+    recovery_probes: Arc<AtomicU64>,
+    /// Number of background probes that failed
+    recovery_probe_failures: Arc<AtomicU64>,
+    /// Number of times background probes closed the circuit
+    probe_recoveries: Arc<AtomicU64>,
+    /// Number of attempts failed by fault injection
+    injected_errors: Arc<AtomicU64>,
+    /// Number of attempts delayed by fault injection
+    injected_delays: Arc<AtomicU64>,
+    /// Number of failed calls that had an injected error among their attempts
+    injected_failures: Arc<AtomicU64>,
     /// Timestamp of last request
     last_request_time: Arc<AtomicU64>,
 }
@@ -28,121 +70,346 @@ impl ApiMetrics {
             successful_requests: Arc::new(AtomicU64::new(0)),
             failed_requests: Arc::new(AtomicU64::new(0)),
             total_response_time_ms: Arc::new(AtomicU64::new(0)),
+            response_time_buckets: Arc::new(std::array::from_fn(|_| AtomicU64::new(0))),
             total_retries: Arc::new(AtomicU64::new(0)),
             circuit_breaker_rejections: Arc::new(AtomicU64::new(0)),
+            bulkhead_rejections: Arc::new(AtomicU64::new(0)),
+            throttled_requests: Arc::new(AtomicU64::new(0)),
+            throttled_time_ms: Arc::new(AtomicU64::new(0)),
+            rate_limit_rejections: Arc::new(AtomicU64::new(0)),
+            hedged_requests: Arc::new(AtomicU64::new(0)),
+            hedge_wins: Arc::new(AtomicU64::new(0)),
+            timeouts: Arc::new(AtomicU64::new(0)),
+            deadline_exceeded: Arc::new(AtomicU64::new(0)),
+            maintenance_responses: Arc::new(AtomicU64::new(0)),
+            recovery_probes: Arc::new(AtomicU64::new(0)),
+            recovery_probe_failures: Arc::new(AtomicU64::new(0)),
+            probe_recoveries: Arc::new(AtomicU64::new(0)),
+            injected_errors: Arc::new(AtomicU64::new(0)),
+            injected_delays: Arc::new(AtomicU64::new(0)),
+            injected_failures: Arc::new(AtomicU64::new(0)),
             last_request_time: Arc::new(AtomicU64::new(0)),
         }
     }
 
     /// Record a request start
     pub fn record_request_start(&self) -> Instant {
-        self.total_requests.fetch_add(1, Ordering::SeqCst);
+        self.total_requests.fetch_add(1, Ordering::Relaxed);
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_millis() as u64;
-        self.last_request_time.store(now, Ordering::SeqCst);
+        self.last_request_time.store(now, Ordering::Relaxed);
         Instant::now()
     }
 
     /// Record a successful request
     pub fn record_success(&self, start_time: Instant) {
-        self.successful_requests.fetch_add(1, Ordering::SeqCst);
-        let duration_ms = start_time.elapsed().as_millis() as u64;
-        self.total_response_time_ms.fetch_add(duration_ms, Ordering::SeqCst);
+        self.record_response_time(start_time.elapsed());
+        self.successful_requests.fetch_add(1, Ordering::Release);
     }
 
     /// Record a failed request
     pub fn record_failure(&self, start_time: Instant) {
-        self.failed_requests.fetch_add(1, Ordering::SeqCst);
-        let duration_ms = start_time.elapsed().as_millis() as u64;
-        self.total_response_time_ms.fetch_add(duration_ms, Ordering::SeqCst);
+        self.record_response_time(start_time.elapsed());
+        self.failed_requests.fetch_add(1, Ordering::Release);
+    }
+
+    /// Add a finished request's duration to the total and its histogram bucket
+    fn record_response_time(&self, duration: Duration) {
+        let duration_ms = duration.as_millis() as u64;
+        self.total_response_time_ms.fetch_add(duration_ms, Ordering::Relaxed);
+        let bucket = RESPONSE_TIME_BUCKETS_MS
+            .iter()
+            .position(|&bound| duration_ms <= bound)
+            .unwrap_or(RESPONSE_TIME_BUCKETS_MS.len());
+        self.response_time_buckets[bucket].fetch_add(1, Ordering::Relaxed);
     }
 
     /// Record a retry
     pub fn record_retry(&self) {
-        self.total_retries.fetch_add(1, Ordering::SeqCst);
+        self.total_retries.fetch_add(1, Ordering::Relaxed);
     }
 
     /// Record a circuit breaker rejection
     pub fn record_circuit_breaker_rejection(&self) {
-        self.circuit_breaker_rejections.fetch_add(1, Ordering::SeqCst);
+        self.circuit_breaker_rejections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a bulkhead rejection
+    pub fn record_bulkhead_rejection(&self) {
+        self.bulkhead_rejections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a call or attempt held back by the outbound rate limit for `waited`
+    pub fn record_throttled(&self, waited: Duration) {
+        self.throttled_requests.fetch_add(1, Ordering::Relaxed);
+        self.throttled_time_ms.fetch_add(waited.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Record a call refused by the outbound rate limit
+    pub fn record_rate_limit_rejection(&self) {
+        self.rate_limit_rejections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a hedged second attempt
+    pub fn record_hedged_request(&self) {
+        self.hedged_requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a hedged attempt finishing before the original
+    pub fn record_hedge_win(&self) {
+        self.hedge_wins.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record an attempt that timed out
+    pub fn record_timeout(&self) {
+        self.timeouts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a call that ran past its total deadline
+    pub fn record_deadline_exceeded(&self) {
+        self.deadline_exceeded.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record an attempt answered with what looks like a maintenance page
+    pub fn record_maintenance_response(&self) {
+        self.maintenance_responses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a background recovery probe
+    pub fn record_recovery_probe(&self) {
+        self.recovery_probes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a background recovery probe that failed
+    pub fn record_recovery_probe_failure(&self) {
+        self.recovery_probe_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record recovery probes closing the circuit
+    pub fn record_probe_recovery(&self) {
+        self.probe_recoveries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record an attempt failed by fault injection
+    pub fn record_injected_error(&self) {
+        self.injected_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record an attempt delayed by fault injection
+    pub fn record_injected_delay(&self) {
+        self.injected_delays.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a failed call that had an injected error
+    pub fn record_injected_failure(&self) {
+        self.injected_failures.fetch_add(1, Ordering::Relaxed);
     }
 
     /// Get total number of requests
     pub fn total_requests(&self) -> u64 {
-        self.total_requests.load(Ordering::SeqCst)
+        self.total_requests.load(Ordering::Relaxed)
     }
 
     /// Get number of successful requests
     pub fn successful_requests(&self) -> u64 {
-        self.successful_requests.load(Ordering::SeqCst)
+        self.successful_requests.load(Ordering::Acquire)
     }
 
     /// Get number of failed requests
     pub fn failed_requests(&self) -> u64 {
-        self.failed_requests.load(Ordering::SeqCst)
+        self.failed_requests.load(Ordering::Acquire)
     }
 
     /// Get success rate (0.0 to 1.0)
     pub fn success_rate(&self) -> f64 {
-        let total = self.total_requests();
-        if total == 0 {
-            return 1.0;
-        }
-        self.successful_requests() as f64 / total as f64
+        self.request_counts().success_rate()
     }
 
     /// Get failure rate (0.0 to 1.0)
     pub fn failure_rate(&self) -> f64 {
-        let total = self.total_requests();
-        if total == 0 {
-            return 0.0;
-        }
-        self.failed_requests() as f64 / total as f64
+        self.request_counts().failure_rate()
     }
 
     /// Get average response time in milliseconds
     pub fn average_response_time_ms(&self) -> f64 {
-        let total = self.total_requests();
-        if total == 0 {
-            return 0.0;
-        }
-        self.total_response_time_ms.load(Ordering::SeqCst) as f64 / total as f64
+        self.request_counts().average_response_time_ms()
+    }
+
+    /// Read the request counters consistently: outcomes before the total
+    fn request_counts(&self) -> RequestCounts {
+        let successful = self.successful_requests();
+        let failed = self.failed_requests();
+        let response_time_ms = self.total_response_time_ms.load(Ordering::Relaxed);
+        // Every finished request was started; the max only guards against a reset racing the reads
+        let total = self.total_requests().max(successful + failed);
+        RequestCounts { total, successful, failed, response_time_ms }
+    }
+
+    /// Finished requests per response time bucket, cumulative, as (upper bound in ms, count)
+    ///
+    /// The last entry, bounded by `u64::MAX`, counts every finished request.
+    pub fn response_time_histogram(&self) -> Vec<(u64, u64)> {
+        let mut cumulative = 0;
+        RESPONSE_TIME_BUCKETS_MS
+            .iter()
+            .copied()
+            .chain(std::iter::once(u64::MAX))
+            .zip(self.response_time_buckets.iter())
+            .map(|(bound, bucket)| {
+                cumulative += bucket.load(Ordering::Relaxed);
+                (bound, cumulative)
+            })
+            .collect()
     }
 
     /// Get total number of retries
     pub fn total_retries(&self) -> u64 {
-        self.total_retries.load(Ordering::SeqCst)
+        self.total_retries.load(Ordering::Relaxed)
     }
 
     /// Get number of circuit breaker rejections
     pub fn circuit_breaker_rejections(&self) -> u64 {
-        self.circuit_breaker_rejections.load(Ordering::SeqCst)
+        self.circuit_breaker_rejections.load(Ordering::Relaxed)
+    }
+
+    /// Get number of bulkhead rejections
+    pub fn bulkhead_rejections(&self) -> u64 {
+        self.bulkhead_rejections.load(Ordering::Relaxed)
+    }
+
+    /// Get number of calls and attempts delayed by the outbound rate limit
+    pub fn throttled_requests(&self) -> u64 {
+        self.throttled_requests.load(Ordering::Relaxed)
+    }
+
+    /// Get total time spent waiting on the outbound rate limit, in milliseconds
+    pub fn throttled_time_ms(&self) -> u64 {
+        self.throttled_time_ms.load(Ordering::Relaxed)
+    }
+
+    /// Get number of calls refused by the outbound rate limit
+    pub fn rate_limit_rejections(&self) -> u64 {
+        self.rate_limit_rejections.load(Ordering::Relaxed)
+    }
+
+    /// Get number of hedged requests
+    pub fn hedged_requests(&self) -> u64 {
+        self.hedged_requests.load(Ordering::Relaxed)
+    }
+
+    /// Get number of requests won by the hedged attempt
+    pub fn hedge_wins(&self) -> u64 {
+        self.hedge_wins.load(Ordering::Relaxed)
+    }
+
+    /// Get number of attempts that timed out
+    pub fn timeouts(&self) -> u64 {
+        self.timeouts.load(Ordering::Relaxed)
+    }
+
+    /// Get number of calls that ran past their total deadline
+    pub fn deadline_exceeded(&self) -> u64 {
+        self.deadline_exceeded.load(Ordering::Relaxed)
+    }
+
+    /// Get number of attempts answered with what looks like a maintenance page
+    pub fn maintenance_responses(&self) -> u64 {
+        self.maintenance_responses.load(Ordering::Relaxed)
+    }
+
+    /// Get number of background recovery probes
+    pub fn recovery_probes(&self) -> u64 {
+        self.recovery_probes.load(Ordering::Relaxed)
+    }
+
+    /// Get number of background recovery probes that failed
+    pub fn recovery_probe_failures(&self) -> u64 {
+        self.recovery_probe_failures.load(Ordering::Relaxed)
+    }
+
+    /// Get number of times recovery probes closed the circuit
+    pub fn probe_recoveries(&self) -> u64 {
+        self.probe_recoveries.load(Ordering::Relaxed)
+    }
+
+    /// Get number of attempts failed by fault injection
+    pub fn injected_errors(&self) -> u64 {
+        self.injected_errors.load(Ordering::Relaxed)
+    }
+
+    /// Get number of attempts delayed by fault injection
+    pub fn injected_delays(&self) -> u64 {
+        self.injected_delays.load(Ordering::Relaxed)
+    }
+
+    /// Get number of failed calls that had an injected error
+    pub fn injected_failures(&self) -> u64 {
+        self.injected_failures.load(Ordering::Relaxed)
     }
 
     /// Get metrics snapshot
+    ///
+    /// Counts and rates come from one read of each counter, so the rates
+    /// always agree with the counts and stay within 0.0 to 1.0.
     pub fn snapshot(&self) -> MetricsSnapshot {
+        let counts = self.request_counts();
         MetricsSnapshot {
-            total_requests: self.total_requests(),
-            successful_requests: self.successful_requests(),
-            failed_requests: self.failed_requests(),
-            success_rate: self.success_rate(),
-            failure_rate: self.failure_rate(),
-            average_response_time_ms: self.average_response_time_ms(),
+            total_requests: counts.total,
+            successful_requests: counts.successful,
+            failed_requests: counts.failed,
+            success_rate: counts.success_rate(),
+            failure_rate: counts.failure_rate(),
+            average_response_time_ms: counts.average_response_time_ms(),
+            total_response_time_ms: counts.response_time_ms,
+            response_time_histogram: self.response_time_histogram(),
             total_retries: self.total_retries(),
             circuit_breaker_rejections: self.circuit_breaker_rejections(),
+            bulkhead_rejections: self.bulkhead_rejections(),
+            throttled_requests: self.throttled_requests(),
+            throttled_time_ms: self.throttled_time_ms(),
+            rate_limit_rejections: self.rate_limit_rejections(),
+            hedged_requests: self.hedged_requests(),
+            hedge_wins: self.hedge_wins(),
+            timeouts: self.timeouts(),
+            deadline_exceeded: self.deadline_exceeded(),
+            maintenance_responses: self.maintenance_responses(),
+            recovery_probes: self.recovery_probes(),
+            recovery_probe_failures: self.recovery_probe_failures(),
+            probe_recoveries: self.probe_recoveries(),
+            injected_errors: self.injected_errors(),
+            injected_delays: self.injected_delays(),
+            injected_failures: self.injected_failures(),
         }
     }
 
     /// Reset all metrics
     pub fn reset(&self) {
-        self.total_requests.store(0, Ordering::SeqCst);
-        self.successful_requests.store(0, Ordering::SeqCst);
-        self.failed_requests.store(0, Ordering::SeqCst);
-        self.total_response_time_ms.store(0, Ordering::SeqCst);
-        self.total_retries.store(0, Ordering::SeqCst);
-        self.circuit_breaker_rejections.store(0, Ordering::SeqCst);
+        self.total_requests.store(0, Ordering::Relaxed);
+        self.successful_requests.store(0, Ordering::Relaxed);
+        self.failed_requests.store(0, Ordering::Relaxed);
+        self.total_response_time_ms.store(0, Ordering::Relaxed);
+        for bucket in self.response_time_buckets.iter() {
+            bucket.store(0, Ordering::Relaxed);
+        }
+        self.total_retries.store(0, Ordering::Relaxed);
+        self.circuit_breaker_rejections.store(0, Ordering::Relaxed);
+        self.bulkhead_rejections.store(0, Ordering::Relaxed);
+        self.throttled_requests.store(0, Ordering::Relaxed);
+        self.throttled_time_ms.store(0, Ordering::Relaxed);
+        self.rate_limit_rejections.store(0, Ordering::Relaxed);
+        self.hedged_requests.store(0, Ordering::Relaxed);
+        self.hedge_wins.store(0, Ordering::Relaxed);
+        self.timeouts.store(0, Ordering::Relaxed);
+        self.deadline_exceeded.store(0, Ordering::Relaxed);
+        self.maintenance_responses.store(0, Ordering::Relaxed);
+        self.recovery_probes.store(0, Ordering::Relaxed);
+        self.recovery_probe_failures.store(0, Ordering::Relaxed);
+        self.probe_recoveries.store(0, Ordering::Relaxed);
+        self.injected_errors.store(0, Ordering::Relaxed);
+        self.injected_delays.store(0, Ordering::Relaxed);
+        self.injected_failures.store(0, Ordering::Relaxed);
     }
 }
 
@@ -152,6 +419,37 @@ impl Default for ApiMetrics {
     }
 }
 
+/// One read of the request counters, with `total >= successful + failed`
+struct RequestCounts {
+    total: u64,
+    successful: u64,
+    failed: u64,
+    response_time_ms: u64,
+}
+
+impl RequestCounts {
+    fn success_rate(&self) -> f64 {
+        if self.total == 0 {
+            return 1.0;
+        }
+        (self.successful as f64 / self.total as f64).clamp(0.0, 1.0)
+    }
+
+    fn failure_rate(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        (self.failed as f64 / self.total as f64).clamp(0.0, 1.0)
+    }
+
+    fn average_response_time_ms(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.response_time_ms as f64 / self.total as f64
+    }
+}
+
 /// Snapshot of metrics at a point in time
 #[derive(Debug, Clone)]
 pub struct MetricsSnapshot {
@@ -161,8 +459,26 @@ pub struct MetricsSnapshot {
     pub success_rate: f64,
     pub failure_rate: f64,
     pub average_response_time_ms: f64,
+    pub total_response_time_ms: u64,
+    /// Cumulative finished requests per response time bucket, see [`ApiMetrics::response_time_histogram`]
+    pub response_time_histogram: Vec<(u64, u64)>,
     pub total_retries: u64,
     pub circuit_breaker_rejections: u64,
+    pub bulkhead_rejections: u64,
+    pub throttled_requests: u64,
+    pub throttled_time_ms: u64,
+    pub rate_limit_rejections: u64,
+    pub hedged_requests: u64,
+    pub hedge_wins: u64,
+    pub timeouts: u64,
+    pub deadline_exceeded: u64,
+    pub maintenance_responses: u64,
+    pub recovery_probes: u64,
+    pub recovery_probe_failures: u64,
+    pub probe_recoveries: u64,
+    pub injected_errors: u64,
+    pub injected_delays: u64,
+    pub injected_failures: u64,
 }
 
 #[cfg(test)]
@@ -242,6 +558,15 @@ mod tests {
         assert_eq!(metrics.circuit_breaker_rejections(), 2);
     }
 
+    #[test]
+    fn test_metrics_bulkhead_rejections() {
+        let metrics = ApiMetrics::new();
+        metrics.record_bulkhead_rejection();
+
+        assert_eq!(metrics.bulkhead_rejections(), 1);
+        assert_eq!(metrics.snapshot().bulkhead_rejections, 1);
+    }
+
     #[test]
     fn test_metrics_snapshot() {
         let metrics = ApiMetrics::new();
@@ -254,6 +579,63 @@ mod tests {
         assert_eq!(snapshot.success_rate, 1.0);
     }
 
+    #[test]
+    fn test_response_time_histogram() {
+        let metrics = ApiMetrics::new();
+        for millis in [0, 3, 5, 30, 50, 20_000] {
+            metrics.record_response_time(Duration::from_millis(millis));
+        }
+
+        let histogram = metrics.snapshot().response_time_histogram;
+        assert_eq!(histogram.len(), RESPONSE_TIME_BUCKETS_MS.len() + 1);
+        assert_eq!(histogram[0], (5, 3));
+        assert_eq!(histogram[2], (25, 3));
+        assert_eq!(histogram[3], (50, 5));
+        assert_eq!(histogram[RESPONSE_TIME_BUCKETS_MS.len() - 1], (10_000, 5));
+        assert_eq!(histogram.last(), Some(&(u64::MAX, 6)));
+        assert!(histogram.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+    }
+
+    #[test]
+    fn test_snapshots_stay_consistent_under_concurrent_recording() {
+        let metrics = ApiMetrics::new();
+        let recorders: Vec<_> = (0..8)
+            .map(|recorder| {
+                let metrics = metrics.clone();
+                std::thread::spawn(move || {
+                    for i in 0..5_000 {
+                        let start = metrics.record_request_start();
+                        if (i + recorder) % 3 == 0 {
+                            metrics.record_failure(start);
+                        } else {
+                            metrics.record_success(start);
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        let mut snapshots = 0;
+        while snapshots < 5_000 || !recorders.iter().all(|recorder| recorder.is_finished()) {
+            let snapshot = metrics.snapshot();
+            let finished = snapshot.successful_requests + snapshot.failed_requests;
+            assert!(finished <= snapshot.total_requests, "{:?}", snapshot);
+            assert!((0.0..=1.0).contains(&snapshot.success_rate), "{:?}", snapshot);
+            assert!((0.0..=1.0).contains(&snapshot.failure_rate), "{:?}", snapshot);
+            assert!(snapshot.success_rate + snapshot.failure_rate <= 1.0 + f64::EPSILON, "{:?}", snapshot);
+            assert!(snapshot.response_time_histogram.last().unwrap().1 >= finished, "{:?}", snapshot);
+            snapshots += 1;
+        }
+        for recorder in recorders {
+            recorder.join().unwrap();
+        }
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.total_requests, 40_000);
+        assert_eq!(snapshot.successful_requests + snapshot.failed_requests, 40_000);
+        assert_eq!(snapshot.response_time_histogram.last().unwrap().1, 40_000);
+    }
+
     #[test]
     fn test_metrics_reset() {
         let metrics = ApiMetrics::new();
@@ -1,13 +1,21 @@
+pub mod bulkhead;
+pub mod chaos;
 pub mod circuit_breaker;
+pub mod hedging;
 pub mod metrics;
+pub mod rate_limit;
+pub mod recovery;
 pub mod retry;
 pub mod degradation;
 
 // Public API exports
+pub use bulkhead::*;
+pub use chaos::{ChaosInjector, Fault};
 pub use circuit_breaker::*;
+pub use hedging::*;
 pub use metrics::*;
-#[allow(unused_imports)] // Public API for external use
+pub use rate_limit::*;
+pub use recovery::*;
 pub use retry::*;
-#[allow(unused_imports)] // Public API for external use
 pub use degradation::*;
 
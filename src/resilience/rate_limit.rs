@@ -0,0 +1,188 @@
+use parking_lot::Mutex;
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
+
+/// Outbound request budget, refilled at `requests_per_sec` up to `burst` requests
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimitConfig {
+    /// Sustained rate requests are let through at
+    pub requests_per_sec: f64,
+    /// Requests that may go out back to back after an idle period
+    pub burst: u32,
+    /// Longest a call waits for its turn before it is refused
+    pub max_wait: Duration,
+}
+
+impl RateLimitConfig {
+    pub fn new(requests_per_sec: f64, burst: u32, max_wait: Duration) -> Self {
+        Self {
+            requests_per_sec,
+            burst: burst.max(1),
+            max_wait,
+        }
+    }
+
+    /// Load from NETBOX_RATE_LIMIT_RPS, NETBOX_RATE_LIMIT_BURST and NETBOX_RATE_LIMIT_MAX_WAIT_MS
+    ///
+    /// Requests aren't limited unless NETBOX_RATE_LIMIT_RPS is a positive
+    /// number. The burst defaults to one second's worth of requests and the
+    /// wait to two seconds.
+    pub fn from_env() -> Option<Self> {
+        let requests_per_sec: f64 = std::env::var("NETBOX_RATE_LIMIT_RPS")
+            .ok()
+            .and_then(|rps| rps.parse().ok())
+            .filter(|rps: &f64| rps.is_finite() && *rps > 0.0)?;
+        let burst = std::env::var("NETBOX_RATE_LIMIT_BURST")
+            .ok()
+            .and_then(|burst| burst.parse().ok())
+            .filter(|burst| *burst > 0)
+            .unwrap_or(requests_per_sec.ceil() as u32);
+        let max_wait = std::env::var("NETBOX_RATE_LIMIT_MAX_WAIT_MS")
+            .ok()
+            .and_then(|ms| ms.parse().ok())
+            .map(Duration::from_millis)
+            .unwrap_or(Duration::from_secs(2));
+        Some(Self::new(requests_per_sec, burst, max_wait))
+    }
+}
+
+/// Error returned when a call would have to wait longer than `max_wait` for its turn
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimited {
+    pub wait: Duration,
+    pub max_wait: Duration,
+}
+
+impl std::fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Outbound rate limit reached: next request slot in {:?}, more than the {:?} allowed",
+            self.wait, self.max_wait
+        )
+    }
+}
+
+/// Tokens left and when they were last topped up
+struct Bucket {
+    /// Negative while callers are waiting for tokens reserved ahead of time
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Token bucket pacing outbound calls to a shared budget
+///
+/// A caller reserves its token up front and then sleeps until it is due, so
+/// waiting callers go out in the order they arrived, one per refill. Uses
+/// tokio's clock, so tests can pause time instead of waiting.
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    bucket: Mutex<Bucket>,
+}
+
+impl RateLimiter {
+    /// Create a limiter starting with a full burst
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            bucket: Mutex::new(Bucket {
+                tokens: config.burst as f64,
+                refilled_at: Instant::now(),
+            }),
+            config,
+        }
+    }
+
+    /// Reserve a token, returning how long to wait before using it
+    ///
+    /// Nothing is reserved when the wait would exceed `max_wait`.
+    fn reserve(&self, max_wait: Option<Duration>) -> Result<Duration, RateLimited> {
+        let mut bucket = self.bucket.lock();
+        let now = Instant::now();
+        let refill = now.duration_since(bucket.refilled_at).as_secs_f64() * self.config.requests_per_sec;
+        bucket.tokens = (bucket.tokens + refill).min(self.config.burst as f64);
+        bucket.refilled_at = now;
+
+        let wait = match bucket.tokens >= 1.0 {
+            true => Duration::ZERO,
+            false => Duration::from_secs_f64((1.0 - bucket.tokens) / self.config.requests_per_sec),
+        };
+        if let Some(max_wait) = max_wait.filter(|max_wait| wait > *max_wait) {
+            return Err(RateLimited { wait, max_wait });
+        }
+        bucket.tokens -= 1.0;
+        Ok(wait)
+    }
+
+    /// Wait for a token, at most `max_wait`; returns the time waited
+    ///
+    /// A caller that gives up while waiting still uses up its token.
+    pub async fn acquire(&self) -> Result<Duration, RateLimited> {
+        let wait = self.reserve(Some(self.config.max_wait))?;
+        if !wait.is_zero() {
+            sleep(wait).await;
+        }
+        Ok(wait)
+    }
+
+    /// Wait for a token however long it takes; returns the time waited
+    pub async fn acquire_unbounded(&self) -> Duration {
+        let wait = self.reserve(None).unwrap_or_default();
+        if !wait.is_zero() {
+            sleep(wait).await;
+        }
+        wait
+    }
+
+    /// Get the limiter configuration
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_burst_goes_out_at_once_then_paced_at_the_rate() {
+        let limiter = RateLimiter::new(RateLimitConfig::new(10.0, 3, Duration::from_secs(5)));
+        let start = Instant::now();
+
+        let mut sent_at = Vec::new();
+        for _ in 0..6 {
+            limiter.acquire().await.unwrap();
+            sent_at.push(start.elapsed());
+        }
+
+        let ms = |ms| Duration::from_millis(ms);
+        assert_eq!(sent_at, vec![ms(0), ms(0), ms(0), ms(100), ms(200), ms(300)]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_refused_beyond_max_wait_without_using_a_token() {
+        let limiter = RateLimiter::new(RateLimitConfig::new(2.0, 1, Duration::from_millis(600)));
+        limiter.acquire().await.unwrap();
+        assert_eq!(limiter.acquire().await.unwrap(), Duration::from_millis(500));
+
+        // Someone else took the next token, due in 500ms; ours would be 1s away
+        limiter.reserve(None).unwrap();
+        let refused = limiter.acquire().await.unwrap_err();
+        assert_eq!(refused.wait, Duration::from_secs(1));
+
+        // The refusal took nothing: the next slot is still one token away
+        assert_eq!(limiter.acquire_unbounded().await, Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_idle_time_refills_up_to_the_burst() {
+        let limiter = RateLimiter::new(RateLimitConfig::new(1.0, 2, Duration::ZERO));
+        limiter.acquire().await.unwrap();
+        limiter.acquire().await.unwrap();
+        assert!(limiter.acquire().await.is_err());
+
+        tokio::time::advance(Duration::from_secs(60)).await;
+        limiter.acquire().await.unwrap();
+        limiter.acquire().await.unwrap();
+        assert!(limiter.acquire().await.is_err());
+    }
+}
@@ -0,0 +1,297 @@
+use parking_lot::Mutex;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+use crate::clock::{SharedClock, SystemClock};
+use crate::resilience::circuit_breaker::{CircuitBreaker, CircuitState};
+use crate::resilience::metrics::ApiMetrics;
+
+/// Background probing that closes an open circuit once the service answers again
+///
+/// Without it, the first user requests after an outage are the ones probing
+/// the service in HalfOpen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecoveryProbeConfig {
+    pub enabled: bool,
+    /// Wait before the first probe once the circuit opens, doubling after each failed probe
+    pub interval: Duration,
+    /// Longest wait between probes while the service stays down
+    pub max_interval: Duration,
+    /// Consecutive successful probes that close the circuit
+    pub successes_to_close: u32,
+}
+
+impl Default for RecoveryProbeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: Duration::from_secs(5),
+            max_interval: Duration::from_secs(60),
+            successes_to_close: 2,
+        }
+    }
+}
+
+impl RecoveryProbeConfig {
+    /// Load from NETBOX_RECOVERY_PROBE_ENABLED, NETBOX_RECOVERY_PROBE_INTERVAL_SECS,
+    /// NETBOX_RECOVERY_PROBE_MAX_INTERVAL_SECS and NETBOX_RECOVERY_PROBE_SUCCESSES
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let secs = |name: &str, default: Duration| {
+            std::env::var(name)
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(default)
+        };
+        Self {
+            enabled: std::env::var("NETBOX_RECOVERY_PROBE_ENABLED")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(defaults.enabled),
+            interval: secs("NETBOX_RECOVERY_PROBE_INTERVAL_SECS", defaults.interval),
+            max_interval: secs("NETBOX_RECOVERY_PROBE_MAX_INTERVAL_SECS", defaults.max_interval),
+            successes_to_close: std::env::var("NETBOX_RECOVERY_PROBE_SUCCESSES")
+                .ok()
+                .and_then(|count| count.parse().ok())
+                .filter(|count| *count > 0)
+                .unwrap_or(defaults.successes_to_close),
+        }
+    }
+}
+
+/// What one poll of the prober did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbeOutcome {
+    /// The circuit is closed; nothing to probe
+    Idle,
+    /// The next probe isn't due yet
+    Waiting,
+    Succeeded,
+    Failed,
+    /// Enough consecutive probes succeeded and the circuit was closed
+    Recovered,
+}
+
+/// Probing progress, as shown by the health endpoint
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecoveryProbeStatus {
+    /// The circuit isn't closed, so the prober is running
+    pub active: bool,
+    pub consecutive_successes: u32,
+    pub consecutive_failures: u32,
+    pub last_probe_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Default)]
+struct ProbeState {
+    consecutive_successes: u32,
+    consecutive_failures: u32,
+    next_probe_at: Option<Instant>,
+    last_probe_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Probes a service while its circuit is open and closes the circuit once it recovers
+pub struct RecoveryProber {
+    config: RecoveryProbeConfig,
+    circuit_breaker: Arc<CircuitBreaker>,
+    metrics: Arc<ApiMetrics>,
+    clock: SharedClock,
+    state: Mutex<ProbeState>,
+}
+
+impl RecoveryProber {
+    pub fn new(config: RecoveryProbeConfig, circuit_breaker: Arc<CircuitBreaker>, metrics: Arc<ApiMetrics>) -> Self {
+        Self {
+            config,
+            circuit_breaker,
+            metrics,
+            clock: SystemClock::shared(),
+            state: Mutex::new(ProbeState::default()),
+        }
+    }
+
+    /// Use `clock` to schedule probes
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn config(&self) -> &RecoveryProbeConfig {
+        &self.config
+    }
+
+    pub fn status(&self) -> RecoveryProbeStatus {
+        let state = self.state.lock();
+        RecoveryProbeStatus {
+            active: self.circuit_breaker.state() != CircuitState::Closed,
+            consecutive_successes: state.consecutive_successes,
+            consecutive_failures: state.consecutive_failures,
+            last_probe_at: state.last_probe_at,
+        }
+    }
+
+    /// Run `probe` if the circuit isn't closed and the next probe is due
+    ///
+    /// The first probe comes `interval` after the circuit is seen open; each
+    /// failed probe doubles the wait, up to `max_interval`.
+    pub async fn poll<F, Fut>(&self, probe: F) -> ProbeOutcome
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = bool>,
+    {
+        if self.circuit_breaker.state() == CircuitState::Closed {
+            let mut state = self.state.lock();
+            let last_probe_at = state.last_probe_at;
+            *state = ProbeState {
+                last_probe_at,
+                ..Default::default()
+            };
+            return ProbeOutcome::Idle;
+        }
+
+        {
+            let mut state = self.state.lock();
+            let now = self.clock.now_instant();
+            match state.next_probe_at {
+                None => {
+                    state.next_probe_at = Some(now + self.config.interval);
+                    return ProbeOutcome::Waiting;
+                }
+                Some(due) if now < due => return ProbeOutcome::Waiting,
+                Some(_) => {}
+            }
+        }
+
+        self.metrics.record_recovery_probe();
+        let healthy = probe().await;
+
+        let mut state = self.state.lock();
+        state.last_probe_at = Some(self.clock.now_utc());
+        let now = self.clock.now_instant();
+        if !healthy {
+            state.consecutive_successes = 0;
+            state.consecutive_failures += 1;
+            let delay = self.backoff(state.consecutive_failures);
+            state.next_probe_at = Some(now + delay);
+            self.metrics.record_recovery_probe_failure();
+            debug!("Recovery probe failed, next probe in {:?}", delay);
+            return ProbeOutcome::Failed;
+        }
+
+        state.consecutive_failures = 0;
+        state.consecutive_successes += 1;
+        if state.consecutive_successes < self.config.successes_to_close {
+            state.next_probe_at = Some(now + self.config.interval);
+            return ProbeOutcome::Succeeded;
+        }
+
+        info!(
+            "Closing circuit breaker after {} successful recovery probes",
+            state.consecutive_successes
+        );
+        state.consecutive_successes = 0;
+        state.next_probe_at = None;
+        drop(state);
+        self.circuit_breaker.reset();
+        self.metrics.record_probe_recovery();
+        ProbeOutcome::Recovered
+    }
+
+    /// Wait after `failures` consecutive failed probes
+    fn backoff(&self, failures: u32) -> Duration {
+        let factor = 2u32.saturating_pow(failures.saturating_sub(1).min(16));
+        self.config.interval.saturating_mul(factor).min(self.config.max_interval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::resilience::circuit_breaker::{CircuitBreakerConfig, FailureMode};
+
+    fn open_prober() -> (RecoveryProber, Arc<CircuitBreaker>, Arc<ManualClock>) {
+        let breaker = Arc::new(CircuitBreaker::with_config(CircuitBreakerConfig {
+            failure_mode: FailureMode::Consecutive,
+            ..Default::default()
+        }));
+        for _ in 0..5 {
+            breaker.record_failure();
+        }
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        let clock = Arc::new(ManualClock::new());
+        let config = RecoveryProbeConfig {
+            enabled: true,
+            interval: Duration::from_secs(5),
+            max_interval: Duration::from_secs(30),
+            successes_to_close: 2,
+        };
+        let prober = RecoveryProber::new(config, breaker.clone(), Arc::new(ApiMetrics::new())).with_clock(clock.clone());
+        (prober, breaker, clock)
+    }
+
+    #[tokio::test]
+    async fn test_prober_closes_circuit_after_consecutive_successes() {
+        let (prober, breaker, clock) = open_prober();
+
+        // Seeing the circuit open schedules the first probe
+        assert_eq!(prober.poll(|| async { true }).await, ProbeOutcome::Waiting);
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(prober.poll(|| async { true }).await, ProbeOutcome::Succeeded);
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert_eq!(prober.status().consecutive_successes, 1);
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(prober.poll(|| async { true }).await, ProbeOutcome::Recovered);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.allow_request());
+
+        // Probing stops once the circuit is closed
+        assert_eq!(prober.poll(|| async { panic!("probed a closed circuit") }).await, ProbeOutcome::Idle);
+        let status = prober.status();
+        assert!(!status.active);
+        assert!(status.last_probe_at.is_some());
+        assert_eq!(prober.metrics.snapshot().probe_recoveries, 1);
+    }
+
+    #[tokio::test]
+    async fn test_prober_backs_off_while_service_is_down() {
+        let (prober, breaker, clock) = open_prober();
+        prober.poll(|| async { false }).await;
+
+        // Waits of 5s, then 5s, 10s, 20s and 30s (capped) after each failure
+        let mut probe_times = Vec::new();
+        for second in 1..=120 {
+            clock.advance(Duration::from_secs(1));
+            if prober.poll(|| async { false }).await == ProbeOutcome::Failed {
+                probe_times.push(second);
+            }
+        }
+        assert_eq!(probe_times, vec![5, 10, 20, 40, 70, 100]);
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert_eq!(prober.status().consecutive_failures, 6);
+
+        let metrics = prober.metrics.snapshot();
+        assert_eq!((metrics.recovery_probes, metrics.recovery_probe_failures), (6, 6));
+        assert_eq!(metrics.probe_recoveries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_probe_resets_success_streak() {
+        let (prober, breaker, clock) = open_prober();
+        prober.poll(|| async { true }).await;
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(prober.poll(|| async { true }).await, ProbeOutcome::Succeeded);
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(prober.poll(|| async { false }).await, ProbeOutcome::Failed);
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(prober.poll(|| async { true }).await, ProbeOutcome::Succeeded);
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+}
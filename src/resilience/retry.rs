@@ -2,6 +2,36 @@ use std::time::Duration;
 use tokio::time::sleep;
 use tracing::{debug, warn};
 
+/// How the delay between retries is randomized
+///
+/// See "Exponential Backoff And Jitter" on the AWS Architecture Blog. `cap` is
+/// `max_delay_ms` and `exp` the capped exponential delay of the attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackoffStrategy {
+    /// Exactly `exp`
+    None,
+    /// Uniform in `[0, exp]`
+    Full,
+    /// Uniform in `[exp / 2, exp]`
+    Equal,
+    /// Uniform in `[initial, previous * 3]`, capped; ignores `backoff_multiplier`
+    Decorrelated,
+}
+
+impl std::str::FromStr for BackoffStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('-', "_").as_str() {
+            "none" | "no_jitter" => Ok(BackoffStrategy::None),
+            "full" | "full_jitter" => Ok(BackoffStrategy::Full),
+            "equal" | "equal_jitter" => Ok(BackoffStrategy::Equal),
+            "decorrelated" | "decorrelated_jitter" => Ok(BackoffStrategy::Decorrelated),
+            other => Err(format!("Unknown backoff strategy: {}", other)),
+        }
+    }
+}
+
 /// Retry configuration
 #[derive(Debug, Clone)]
 pub struct RetryConfig {
@@ -13,8 +43,8 @@ pub struct RetryConfig {
     pub max_delay_ms: u64,
     /// Exponential backoff multiplier
     pub backoff_multiplier: f64,
-    /// Whether to use jitter (random variation in delay)
-    pub use_jitter: bool,
+    /// How the delay is randomized to spread out retries from many clients
+    pub backoff_strategy: BackoffStrategy,
 }
 
 impl Default for RetryConfig {
@@ -24,7 +54,7 @@ impl Default for RetryConfig {
             initial_delay_ms: 100,
             max_delay_ms: 5000,
             backoff_multiplier: 2.0,
-            use_jitter: true,
+            backoff_strategy: BackoffStrategy::Full,
         }
     }
 }
@@ -37,21 +67,83 @@ impl RetryConfig {
         }
     }
 
-    /// Calculate delay for a given attempt number
+    /// Defaults for writes: a retried write may repeat one NetBox already applied,
+    /// so retry once, after a longer pause
+    pub fn for_writes() -> Self {
+        Self {
+            max_attempts: 2,
+            initial_delay_ms: 1000,
+            ..Default::default()
+        }
+    }
+
+    /// Override `defaults` from `{prefix}_ATTEMPTS`, `{prefix}_INITIAL_DELAY_MS`
+    /// and `{prefix}_MAX_DELAY_MS`; one attempt disables retries
+    pub fn from_env(prefix: &str, defaults: Self) -> Self {
+        let number = |suffix: &str| {
+            std::env::var(format!("{}_{}", prefix, suffix))
+                .ok()
+                .and_then(|value| value.parse::<u64>().ok())
+        };
+        Self {
+            max_attempts: number("ATTEMPTS")
+                .filter(|attempts| *attempts > 0)
+                .map(|attempts| attempts.min(u32::MAX as u64) as u32)
+                .unwrap_or(defaults.max_attempts),
+            initial_delay_ms: number("INITIAL_DELAY_MS").unwrap_or(defaults.initial_delay_ms),
+            max_delay_ms: number("MAX_DELAY_MS").unwrap_or(defaults.max_delay_ms),
+            ..defaults
+        }
+    }
+
+    /// Capped exponential delay for a given attempt number, before jitter
     fn calculate_delay(&self, attempt: u32) -> Duration {
         let base_delay = (self.initial_delay_ms as f64) * (self.backoff_multiplier.powi(attempt as i32 - 1));
-        let delay_ms = base_delay.min(self.max_delay_ms as f64) as u64;
-        
-        let final_delay = if self.use_jitter {
-            // Add jitter: random variation between 0.5x and 1.5x
-            let jitter_range = delay_ms / 2;
-            let jitter = fastrand::u64(0..=jitter_range * 2);
-            delay_ms.saturating_sub(jitter_range).saturating_add(jitter)
-        } else {
-            delay_ms
+        Duration::from_millis(base_delay.min(self.max_delay_ms as f64) as u64)
+    }
+
+    /// Delays for one operation's retries, randomized by `backoff_strategy`
+    pub fn backoff(&self) -> Backoff<'_> {
+        Backoff::with_rng(self, fastrand::Rng::new())
+    }
+}
+
+/// Successive retry delays of one operation
+///
+/// Decorrelated jitter depends on the previous delay, so the delays of an
+/// operation come from one `Backoff`.
+pub struct Backoff<'a> {
+    config: &'a RetryConfig,
+    rng: fastrand::Rng,
+    previous_ms: u64,
+}
+
+impl<'a> Backoff<'a> {
+    /// Draw jitter from `rng`; a seeded RNG makes the delays reproducible
+    pub fn with_rng(config: &'a RetryConfig, rng: fastrand::Rng) -> Self {
+        Self {
+            config,
+            rng,
+            previous_ms: config.initial_delay_ms,
+        }
+    }
+
+    /// Delay after the given failed attempt (starting at 1)
+    pub fn delay(&mut self, attempt: u32) -> Duration {
+        let cap = self.config.max_delay_ms;
+        let exp = self.config.calculate_delay(attempt).as_millis() as u64;
+        let delay_ms = match self.config.backoff_strategy {
+            BackoffStrategy::None => exp,
+            BackoffStrategy::Full => self.rng.u64(0..=exp),
+            BackoffStrategy::Equal => exp / 2 + self.rng.u64(0..=exp - exp / 2),
+            BackoffStrategy::Decorrelated => {
+                let low = self.config.initial_delay_ms.min(cap);
+                let high = self.previous_ms.saturating_mul(3).clamp(low, cap);
+                self.rng.u64(low..=high)
+            }
         };
-        
-        Duration::from_millis(final_delay)
+        self.previous_ms = delay_ms;
+        Duration::from_millis(delay_ms)
     }
 }
 
@@ -59,6 +151,11 @@ impl RetryConfig {
 pub trait RetryableError: std::error::Error {
     /// Check if this error should trigger a retry
     fn is_retryable(&self) -> bool;
+
+    /// Shortest delay before retrying, for errors known to outlast the usual backoff
+    fn min_retry_delay(&self) -> Option<Duration> {
+        None
+    }
 }
 
 /// Retry a function with exponential backoff
@@ -71,6 +168,7 @@ where
     E: RetryableError + Send + 'static,
 {
     let mut last_error = None;
+    let mut backoff = config.backoff();
     
     for attempt in 1..=config.max_attempts {
         match operation().await {
@@ -92,7 +190,7 @@ where
                 
                 // Don't retry on last attempt
                 if attempt < config.max_attempts {
-                    let delay = config.calculate_delay(attempt);
+                    let delay = backoff.delay(attempt).max(err.min_retry_delay().unwrap_or_default());
                     warn!(
                         "Operation failed (attempt {}/{}), retrying in {:?}: {}",
                         attempt,
@@ -100,6 +198,7 @@ where
                         delay,
                         err
                     );
+                    // tokio's timer, so tests can pause time instead of waiting
                     sleep(delay).await;
                 }
             }
@@ -160,14 +259,14 @@ mod tests {
         assert_eq!(call_count.load(Ordering::SeqCst), 1);
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn test_retry_success_after_failures() {
         let config = RetryConfig {
             max_attempts: 3,
             initial_delay_ms: 10,
             max_delay_ms: 100,
             backoff_multiplier: 2.0,
-            use_jitter: false,
+            backoff_strategy: BackoffStrategy::None,
         };
         let call_count = Arc::new(AtomicU32::new(0));
         let call_count_clone = Arc::clone(&call_count);
@@ -189,17 +288,18 @@ mod tests {
         assert_eq!(call_count.load(Ordering::SeqCst), 3);
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn test_retry_fails_after_max_attempts() {
         let config = RetryConfig {
             max_attempts: 3,
             initial_delay_ms: 10,
             max_delay_ms: 100,
             backoff_multiplier: 2.0,
-            use_jitter: false,
+            backoff_strategy: BackoffStrategy::None,
         };
         let call_count = Arc::new(AtomicU32::new(0));
         let call_count_clone = Arc::clone(&call_count);
+        let start = tokio::time::Instant::now();
         
         let result: Result<i32, TestError> = retry_with_backoff(&config, move || {
             let count = Arc::clone(&call_count_clone);
@@ -211,6 +311,8 @@ mod tests {
         
         assert!(result.is_err());
         assert_eq!(call_count.load(Ordering::SeqCst), 3);
+        // Backed off 10ms then 20ms, on the paused clock
+        assert_eq!(start.elapsed(), Duration::from_millis(30));
     }
 
     #[tokio::test]
@@ -220,7 +322,7 @@ mod tests {
             initial_delay_ms: 10,
             max_delay_ms: 100,
             backoff_multiplier: 2.0,
-            use_jitter: false,
+            backoff_strategy: BackoffStrategy::None,
         };
         let call_count = Arc::new(AtomicU32::new(0));
         let call_count_clone = Arc::clone(&call_count);
@@ -244,7 +346,7 @@ mod tests {
             initial_delay_ms: 100,
             max_delay_ms: 1000,
             backoff_multiplier: 2.0,
-            use_jitter: false,
+            backoff_strategy: BackoffStrategy::None,
         };
         
         let delay1 = config.calculate_delay(1);
@@ -264,11 +366,113 @@ mod tests {
             initial_delay_ms: 1000,
             max_delay_ms: 2000,
             backoff_multiplier: 2.0,
-            use_jitter: false,
+            backoff_strategy: BackoffStrategy::None,
         };
         
         let delay4 = config.calculate_delay(4);
         assert!(delay4.as_millis() <= 2000);
     }
-}
 
+    fn jitter_config(backoff_strategy: BackoffStrategy) -> RetryConfig {
+        RetryConfig {
+            max_attempts: 10,
+            initial_delay_ms: 100,
+            max_delay_ms: 3000,
+            backoff_multiplier: 2.0,
+            backoff_strategy,
+        }
+    }
+
+    /// Delays of `runs` seeded operations, with the capped exponential delay of each attempt
+    fn sample_delays(config: &RetryConfig, runs: u64) -> Vec<(u64, u64)> {
+        let mut samples = Vec::new();
+        for seed in 0..runs {
+            let mut backoff = Backoff::with_rng(config, fastrand::Rng::with_seed(seed));
+            for attempt in 1..config.max_attempts {
+                let exp = config.calculate_delay(attempt).as_millis() as u64;
+                samples.push((backoff.delay(attempt).as_millis() as u64, exp));
+            }
+        }
+        samples
+    }
+
+    #[test]
+    fn test_full_jitter_bounds() {
+        let config = jitter_config(BackoffStrategy::Full);
+        let samples = sample_delays(&config, 500);
+        assert!(samples.iter().all(|&(delay, exp)| delay <= exp));
+        // The whole range is used, not just its upper end
+        assert!(samples.iter().any(|&(delay, exp)| delay < exp / 4));
+    }
+
+    #[test]
+    fn test_equal_jitter_bounds() {
+        let config = jitter_config(BackoffStrategy::Equal);
+        let samples = sample_delays(&config, 500);
+        assert!(samples.iter().all(|&(delay, exp)| exp / 2 <= delay && delay <= exp));
+        assert!(samples.iter().any(|&(delay, exp)| delay < exp * 3 / 5));
+    }
+
+    #[test]
+    fn test_decorrelated_jitter_bounds() {
+        let config = jitter_config(BackoffStrategy::Decorrelated);
+        for seed in 0..500 {
+            let mut backoff = Backoff::with_rng(&config, fastrand::Rng::with_seed(seed));
+            let mut previous = config.initial_delay_ms;
+            for attempt in 1..config.max_attempts {
+                let delay = backoff.delay(attempt).as_millis() as u64;
+                assert!(delay >= config.initial_delay_ms);
+                assert!(delay <= (previous * 3).min(config.max_delay_ms));
+                previous = delay;
+            }
+        }
+    }
+
+    #[test]
+    fn test_jitter_never_exceeds_max_delay() {
+        for strategy in [
+            BackoffStrategy::None,
+            BackoffStrategy::Full,
+            BackoffStrategy::Equal,
+            BackoffStrategy::Decorrelated,
+        ] {
+            let config = RetryConfig {
+                initial_delay_ms: 5000,
+                max_delay_ms: 2000,
+                ..jitter_config(strategy)
+            };
+            assert!(sample_delays(&config, 100).iter().all(|&(delay, _)| delay <= 2000), "{:?}", strategy);
+        }
+    }
+
+    #[test]
+    fn test_seeded_backoff_is_reproducible() {
+        let config = jitter_config(BackoffStrategy::Decorrelated);
+        assert_eq!(sample_delays(&config, 3), sample_delays(&config, 3));
+    }
+
+    #[test]
+    fn test_write_retry_config_from_env() {
+        std::env::set_var("TEST_WRITE_RETRY_ATTEMPTS", "1");
+        std::env::set_var("TEST_WRITE_RETRY_INITIAL_DELAY_MS", "2500");
+        std::env::remove_var("TEST_WRITE_RETRY_MAX_DELAY_MS");
+
+        let config = RetryConfig::from_env("TEST_WRITE_RETRY", RetryConfig::for_writes());
+        assert_eq!(config.max_attempts, 1);
+        assert_eq!(config.initial_delay_ms, 2500);
+        assert_eq!(config.max_delay_ms, RetryConfig::default().max_delay_ms);
+
+        std::env::set_var("TEST_WRITE_RETRY_ATTEMPTS", "0");
+        let config = RetryConfig::from_env("TEST_WRITE_RETRY", RetryConfig::for_writes());
+        assert_eq!(config.max_attempts, RetryConfig::for_writes().max_attempts);
+    }
+
+    #[test]
+    fn test_backoff_strategy_from_str() {
+        assert_eq!("none".parse::<BackoffStrategy>(), Ok(BackoffStrategy::None));
+        assert_eq!("full".parse::<BackoffStrategy>(), Ok(BackoffStrategy::Full));
+        assert_eq!("Equal-Jitter".parse::<BackoffStrategy>(), Ok(BackoffStrategy::Equal));
+        assert_eq!("decorrelated".parse::<BackoffStrategy>(), Ok(BackoffStrategy::Decorrelated));
+        assert!("bogus".parse::<BackoffStrategy>().is_err());
+    }
+}
@@ -1,7 +1,20 @@
-use poem::Request;
+use std::sync::Arc;
+use poem::http::Method;
+use poem::{Endpoint, Middleware, Request, Result as PoemResult};
 use crate::error::AppError;
+use crate::security::tenant::TenantMappingService;
 
 pub const TENANT_HEADER: &str = "X-Tenant-Id";
+/// Operator identity, set by the authenticating gateway
+pub const USER_HEADER: &str = "X-User-Id";
+/// Comma-separated operator roles, set by the authenticating gateway
+pub const ROLES_HEADER: &str = "X-Roles";
+/// Role allowed to approve or reject orders
+pub const APPROVER_ROLE: &str = "approver";
+/// Role allowed to manage tenants and their NetBox mappings
+pub const ADMIN_ROLE: &str = "admin";
+/// Role allowed to read NetBox change history
+pub const READER_ROLE: &str = "reader";
 
 pub fn extract_tenant_id(req: &Request) -> Result<String, AppError> {
     req.header(TENANT_HEADER)
@@ -9,6 +22,73 @@ pub fn extract_tenant_id(req: &Request) -> Result<String, AppError> {
         .ok_or(AppError::Unauthorized)
 }
 
+/// Require the caller to hold `role`, returning their user id
+pub fn require_role(req: &Request, role: &str) -> Result<String, AppError> {
+    let user_id = req.header(USER_HEADER).ok_or(AppError::Unauthorized)?;
+    let has_role = req
+        .header(ROLES_HEADER)
+        .map(|roles| roles.split(',').any(|r| r.trim() == role))
+        .unwrap_or(false);
+
+    if !has_role {
+        return Err(AppError::Forbidden(format!("Role '{}' required", role)));
+    }
+    Ok(user_id.to_string())
+}
+
+/// Middleware refusing disabled tenants everything but reading their orders
+///
+/// Requests from admins pass, so a disabled tenant can still be restored or purged.
+pub struct TenantStatusMiddleware {
+    mappings: Arc<TenantMappingService>,
+}
+
+impl TenantStatusMiddleware {
+    pub fn new(mappings: Arc<TenantMappingService>) -> Self {
+        Self { mappings }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for TenantStatusMiddleware {
+    type Output = TenantStatusEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        TenantStatusEndpoint {
+            ep,
+            mappings: self.mappings.clone(),
+        }
+    }
+}
+
+/// Endpoint wrapper that rejects disabled tenants with 403
+pub struct TenantStatusEndpoint<E> {
+    ep: E,
+    mappings: Arc<TenantMappingService>,
+}
+
+#[poem::async_trait]
+impl<E: Endpoint> Endpoint for TenantStatusEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, req: Request) -> PoemResult<Self::Output> {
+        if let Ok(tenant_id) = extract_tenant_id(&req) {
+            if let Some(disabled) = self.mappings.disabled(&tenant_id) {
+                let reads_orders = req.method() == Method::GET && req.uri().path().starts_with("/orders");
+                if !reads_orders && require_role(&req, ADMIN_ROLE).is_err() {
+                    return Err(AppError::TenantDisabled(format!(
+                        "Tenant '{}' was disabled by {} at {}; only its orders can still be read",
+                        tenant_id,
+                        disabled.by,
+                        disabled.at.to_rfc3339()
+                    ))
+                    .into());
+                }
+            }
+        }
+        self.ep.call(req).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -36,5 +116,31 @@ mod tests {
             _ => panic!("Expected Unauthorized error"),
         }
     }
-}
 
+    #[test]
+    fn test_require_role_success() {
+        let req = Request::builder()
+            .header(USER_HEADER, "alice")
+            .header(ROLES_HEADER, "viewer, approver")
+            .finish();
+
+        assert_eq!(require_role(&req, APPROVER_ROLE).unwrap(), "alice");
+    }
+
+    #[test]
+    fn test_require_role_missing_role() {
+        let req = Request::builder()
+            .header(USER_HEADER, "alice")
+            .header(ROLES_HEADER, "viewer")
+            .finish();
+
+        assert!(matches!(require_role(&req, APPROVER_ROLE), Err(AppError::Forbidden(_))));
+    }
+
+    #[test]
+    fn test_require_role_missing_user() {
+        let req = Request::builder().header(ROLES_HEADER, "approver").finish();
+
+        assert!(matches!(require_role(&req, APPROVER_ROLE), Err(AppError::Unauthorized)));
+    }
+}
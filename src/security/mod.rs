@@ -1,6 +1,12 @@
 pub mod auth;
+pub mod secrets;
 pub mod tenant;
+pub mod webhook_signature;
 
 pub use auth::*;
+pub use secrets::TokenCipher;
 pub use tenant::*;
+pub use webhook_signature::{
+    signature_header, sign_webhook, verify_webhook_signature, verify_webhook_signature_any, WebhookSignatureError,
+};
 
@@ -0,0 +1,88 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+
+/// Prefix of stored ciphertexts, so the format can change later
+const CIPHERTEXT_PREFIX: &str = "v1:";
+
+/// AES-GCM nonce length in bytes
+const NONCE_LEN: usize = 12;
+
+/// Why a stored token couldn't be decrypted
+#[derive(Debug, thiserror::Error)]
+pub enum SecretError {
+    #[error("Stored token is not in the expected format")]
+    Malformed,
+
+    #[error("Stored token can't be decrypted with the configured key")]
+    Decrypt,
+}
+
+/// Encrypts tenant NetBox tokens for storage with AES-256-GCM
+///
+/// The key is the SHA-256 of the configured passphrase. Each ciphertext gets a
+/// fresh random nonce and is stored as `v1:` plus base64 of nonce and ciphertext.
+#[derive(Clone)]
+pub struct TokenCipher {
+    cipher: Aes256Gcm,
+}
+
+impl TokenCipher {
+    pub fn new(passphrase: &str) -> Self {
+        let key = Sha256::digest(passphrase.as_bytes());
+        Self {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)),
+        }
+    }
+
+    pub fn encrypt(&self, token: &str) -> String {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, token.as_bytes())
+            .expect("AES-GCM encryption of an in-memory buffer cannot fail");
+        let mut stored = nonce.to_vec();
+        stored.extend(ciphertext);
+        format!("{}{}", CIPHERTEXT_PREFIX, BASE64.encode(stored))
+    }
+
+    pub fn decrypt(&self, stored: &str) -> Result<String, SecretError> {
+        let encoded = stored.strip_prefix(CIPHERTEXT_PREFIX).ok_or(SecretError::Malformed)?;
+        let bytes = BASE64.decode(encoded).map_err(|_| SecretError::Malformed)?;
+        if bytes.len() <= NONCE_LEN {
+            return Err(SecretError::Malformed);
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| SecretError::Decrypt)?;
+        String::from_utf8(plaintext).map_err(|_| SecretError::Malformed)
+    }
+}
+
+impl std::fmt::Debug for TokenCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TokenCipher(..)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_round_trip_and_need_the_same_key() {
+        let cipher = TokenCipher::new("correct horse battery staple");
+        let first = cipher.encrypt("0123456789abcdef");
+        let second = cipher.encrypt("0123456789abcdef");
+
+        assert_ne!(first, second, "each ciphertext gets its own nonce");
+        assert!(!first.contains("0123456789abcdef"));
+        assert_eq!(cipher.decrypt(&first).unwrap(), "0123456789abcdef");
+        assert!(matches!(TokenCipher::new("other key").decrypt(&first), Err(SecretError::Decrypt)));
+        assert!(matches!(cipher.decrypt("0123456789abcdef"), Err(SecretError::Malformed)));
+    }
+}
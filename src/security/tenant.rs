@@ -1,5 +1,9 @@
-use std::collections::HashMap;
-use std::sync::RwLock;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use async_trait::async_trait;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 use crate::error::AppError;
 use crate::netbox::models::{NetBoxSite, NetBoxDevice};
 
@@ -9,50 +13,422 @@ pub type TenantId = String;
 /// NetBox tenant ID type
 pub type NetBoxTenantId = i32;
 
+/// Name of the NetBox endpoint configured with NETBOX_URL/NETBOX_TOKEN
+pub const DEFAULT_NETBOX_ENDPOINT: &str = "default";
+
+/// A tenant's NetBox tenant and the NetBox endpoint it lives on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "StoredMapping", into = "StoredMapping")]
+pub struct TenantMapping {
+    pub netbox_tenant_id: NetBoxTenantId,
+    /// Named NetBox endpoint; None routes to the default endpoint
+    pub endpoint: Option<String>,
+    /// The tenant's own NetBox API token, encrypted with TENANT_TOKEN_KEY;
+    /// None uses the endpoint's token
+    pub netbox_token: Option<String>,
+    /// Set while the tenant is soft-deleted
+    pub disabled: Option<TenantDisabled>,
+}
+
+/// When and by whom a tenant was soft-deleted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TenantDisabled {
+    pub at: chrono::DateTime<chrono::Utc>,
+    /// User id of the admin who disabled the tenant
+    pub by: String,
+}
+
+impl TenantMapping {
+    pub fn new(netbox_tenant_id: NetBoxTenantId, endpoint: Option<String>) -> Self {
+        Self { netbox_tenant_id, endpoint, netbox_token: None, disabled: None }
+    }
+
+    /// Call NetBox with the tenant's own token, already encrypted
+    pub fn with_netbox_token(mut self, encrypted_token: Option<String>) -> Self {
+        self.netbox_token = encrypted_token;
+        self
+    }
+
+    /// Endpoint the tenant's NetBox calls go to
+    pub fn endpoint_name(&self) -> &str {
+        self.endpoint.as_deref().unwrap_or(DEFAULT_NETBOX_ENDPOINT)
+    }
+}
+
+impl From<NetBoxTenantId> for TenantMapping {
+    fn from(netbox_tenant_id: NetBoxTenantId) -> Self {
+        Self::new(netbox_tenant_id, None)
+    }
+}
+
+/// Store file form: a bare id for the default endpoint, so older files still load
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum StoredMapping {
+    Default(NetBoxTenantId),
+    Routed {
+        netbox_tenant_id: NetBoxTenantId,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        endpoint: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        netbox_token: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        disabled: Option<TenantDisabled>,
+    },
+}
+
+impl From<StoredMapping> for TenantMapping {
+    fn from(stored: StoredMapping) -> Self {
+        match stored {
+            StoredMapping::Default(id) => Self::new(id, None),
+            StoredMapping::Routed { netbox_tenant_id, endpoint, netbox_token, disabled } => Self {
+                disabled,
+                ..Self::new(netbox_tenant_id, endpoint).with_netbox_token(netbox_token)
+            },
+        }
+    }
+}
+
+impl From<TenantMapping> for StoredMapping {
+    fn from(mapping: TenantMapping) -> Self {
+        match mapping {
+            TenantMapping { netbox_tenant_id, endpoint: None, netbox_token: None, disabled: None } => {
+                StoredMapping::Default(netbox_tenant_id)
+            }
+            TenantMapping { netbox_tenant_id, endpoint, netbox_token, disabled } => {
+                StoredMapping::Routed { netbox_tenant_id, endpoint, netbox_token, disabled }
+            }
+        }
+    }
+}
+
+/// Why a tenant mapping change was refused
+#[derive(Debug, thiserror::Error)]
+pub enum MappingError {
+    #[error("Tenant '{tenant_id}' is mapped to unknown NetBox endpoint '{endpoint}'")]
+    UnknownEndpoint { tenant_id: TenantId, endpoint: String },
+
+    #[error("Failed to persist tenant mappings: {0}")]
+    Store(#[from] MappingStoreError),
+}
+
+/// Why stored tenant mappings couldn't be read or written
+#[derive(Debug, thiserror::Error)]
+pub enum MappingStoreError {
+    #[error("Tenant mapping store I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Tenant mapping store holds invalid mappings: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[cfg(feature = "postgres")]
+    #[error("Tenant mapping store query failed: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Where tenant mappings managed through the API are kept across restarts
+#[async_trait]
+pub trait TenantMappingStore: Send + Sync {
+    /// Every stored mapping
+    async fn load(&self) -> Result<HashMap<TenantId, TenantMapping>, MappingStoreError>;
+    /// Store the tenant's mapping, replacing the one it had
+    async fn put(&self, tenant_id: &TenantId, mapping: &TenantMapping) -> Result<(), MappingStoreError>;
+    /// Remove the tenant's mapping, if it has one
+    async fn delete(&self, tenant_id: &TenantId) -> Result<(), MappingStoreError>;
+}
+
+/// Mappings kept in a JSON file, rewritten whole on every change
+///
+/// A missing file holds no mappings and is created on the first change.
+#[derive(Debug, Clone)]
+pub struct JsonFileTenantMappingStore {
+    path: PathBuf,
+}
+
+impl JsonFileTenantMappingStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    async fn update(&self, change: impl FnOnce(&mut HashMap<TenantId, TenantMapping>)) -> Result<(), MappingStoreError> {
+        let mut mappings = self.load().await?;
+        change(&mut mappings);
+        // Write a sibling file and rename it over the store, so a crash never leaves it half-written
+        let tmp = self.path.with_extension("tmp");
+        tokio::fs::write(&tmp, serde_json::to_vec_pretty(&mappings)?).await?;
+        tokio::fs::rename(&tmp, &self.path).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TenantMappingStore for JsonFileTenantMappingStore {
+    async fn load(&self) -> Result<HashMap<TenantId, TenantMapping>, MappingStoreError> {
+        match tokio::fs::read(&self.path).await {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn put(&self, tenant_id: &TenantId, mapping: &TenantMapping) -> Result<(), MappingStoreError> {
+        self.update(|mappings| {
+            mappings.insert(tenant_id.clone(), mapping.clone());
+        })
+        .await
+    }
+
+    async fn delete(&self, tenant_id: &TenantId) -> Result<(), MappingStoreError> {
+        self.update(|mappings| {
+            mappings.remove(tenant_id);
+        })
+        .await
+    }
+}
+
 /// Tenant mapping service - maps application tenant IDs to NetBox tenant IDs
+/// and the NetBox endpoints they live on
 pub struct TenantMappingService {
-    // Map from application tenant ID (string) to NetBox tenant ID (i32)
-    mappings: RwLock<HashMap<TenantId, NetBoxTenantId>>,
+    // Map from application tenant ID (string) to NetBox tenant ID and endpoint
+    mappings: RwLock<HashMap<TenantId, TenantMapping>>,
+    /// Store every checked change is written through to
+    store: Option<Arc<dyn TenantMappingStore>>,
+    /// Held while a change is written to the store, so changes are stored in the order they're made
+    writes: tokio::sync::Mutex<()>,
+    /// Endpoint names mappings may use, besides the default
+    endpoints: HashSet<String>,
 }
 
 impl TenantMappingService {
     pub fn new() -> Self {
         Self {
             mappings: RwLock::new(HashMap::new()),
+            store: None,
+            writes: tokio::sync::Mutex::new(()),
+            endpoints: HashSet::new(),
+        }
+    }
+
+    /// Load mappings from `store` and write every later change back to it
+    pub async fn load(store: Arc<dyn TenantMappingStore>) -> Result<Self, MappingStoreError> {
+        let mappings = store.load().await?;
+        Ok(Self {
+            mappings: RwLock::new(mappings),
+            store: Some(store),
+            ..Self::new()
+        })
+    }
+
+    /// Allow mappings to name these NetBox endpoints, besides the default
+    pub fn with_endpoints(mut self, names: impl IntoIterator<Item = String>) -> Self {
+        self.endpoints.extend(names);
+        self
+    }
+
+    /// Refuse a mapping to an endpoint that isn't configured
+    pub fn check_endpoint(&self, tenant_id: &TenantId, mapping: &TenantMapping) -> Result<(), MappingError> {
+        match mapping.endpoint {
+            Some(ref endpoint) if endpoint != DEFAULT_NETBOX_ENDPOINT && !self.endpoints.contains(endpoint) => {
+                Err(MappingError::UnknownEndpoint { tenant_id: tenant_id.clone(), endpoint: endpoint.clone() })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check every mapping names a configured endpoint, e.g. after loading the store file
+    pub fn validate_endpoints(&self) -> Result<(), MappingError> {
+        self.all_mappings()
+            .iter()
+            .try_for_each(|(tenant_id, mapping)| self.check_endpoint(tenant_id, mapping))
+    }
+
+    /// Whether any tenant calls NetBox with its own token
+    pub fn has_tenant_tokens(&self) -> bool {
+        self.mappings.read().values().any(|mapping| mapping.netbox_token.is_some())
+    }
+
+    /// Write the tenant's mapping to the store, or remove it there when None
+    async fn persist(&self, tenant_id: &TenantId, mapping: Option<&TenantMapping>) -> Result<(), MappingStoreError> {
+        match (&self.store, mapping) {
+            (Some(store), Some(mapping)) => store.put(tenant_id, mapping).await,
+            (Some(store), None) => store.delete(tenant_id).await,
+            (None, _) => Ok(()),
         }
     }
 
     /// Register a mapping between application tenant ID and NetBox tenant ID
-    pub fn register_mapping(&self, tenant_id: TenantId, netbox_tenant_id: NetBoxTenantId) {
-        let mut mappings = self.mappings.write().unwrap();
-        mappings.insert(tenant_id, netbox_tenant_id);
+    ///
+    /// The mapping is kept in memory only and endpoints are not checked here;
+    /// see [`Self::insert_mapping`] and [`Self::validate_endpoints`].
+    pub fn register_mapping(&self, tenant_id: TenantId, mapping: impl Into<TenantMapping>) {
+        let mut mappings = self.mappings.write();
+        mappings.insert(tenant_id, mapping.into());
+    }
+
+    /// Add a mapping for a tenant that has none, returning false if it already has one
+    ///
+    /// Nothing changes if the mapping cannot be stored.
+    pub async fn insert_mapping(&self, tenant_id: TenantId, mapping: impl Into<TenantMapping>) -> Result<bool, MappingError> {
+        let mapping = mapping.into();
+        self.check_endpoint(&tenant_id, &mapping)?;
+        let _write = self.writes.lock().await;
+        if self.has_mapping(&tenant_id) {
+            return Ok(false);
+        }
+        self.persist(&tenant_id, Some(&mapping)).await?;
+        self.mappings.write().insert(tenant_id, mapping);
+        Ok(true)
+    }
+
+    /// Point an existing tenant at another NetBox tenant, returning false if it has no mapping
+    ///
+    /// A disabled tenant stays disabled. Nothing changes if the mapping cannot be stored.
+    pub async fn update_mapping(&self, tenant_id: &TenantId, mapping: impl Into<TenantMapping>) -> Result<bool, MappingError> {
+        let mut mapping = mapping.into();
+        self.check_endpoint(tenant_id, &mapping)?;
+        let _write = self.writes.lock().await;
+        let Some(previous) = self.get_mapping(tenant_id) else {
+            return Ok(false);
+        };
+        mapping.disabled = previous.disabled;
+        self.persist(tenant_id, Some(&mapping)).await?;
+        self.mappings.write().insert(tenant_id.clone(), mapping);
+        Ok(true)
+    }
+
+    /// Soft-delete a tenant, returning None if it has no mapping
+    ///
+    /// The mapping is kept so in-flight orders still reach the tenant's NetBox,
+    /// but the tenant can no longer use the API beyond reading its orders.
+    /// Disabling a disabled tenant keeps the original timestamp and actor.
+    /// Nothing changes if the mapping cannot be stored.
+    pub async fn disable_tenant(&self, tenant_id: &TenantId, actor: String) -> Result<Option<TenantDisabled>, MappingStoreError> {
+        let mapping = self
+            .set_disabled(tenant_id, |current| {
+                current.or(Some(TenantDisabled { at: chrono::Utc::now(), by: actor }))
+            })
+            .await?;
+        Ok(mapping.and_then(|mapping| mapping.disabled))
+    }
+
+    /// Undo a soft delete, returning the tenant's mapping or None if it has none
+    ///
+    /// Nothing changes if the mapping cannot be stored.
+    pub async fn restore_tenant(&self, tenant_id: &TenantId) -> Result<Option<TenantMapping>, MappingStoreError> {
+        self.set_disabled(tenant_id, |_| None).await
+    }
+
+    async fn set_disabled(
+        &self,
+        tenant_id: &TenantId,
+        update: impl FnOnce(Option<TenantDisabled>) -> Option<TenantDisabled>,
+    ) -> Result<Option<TenantMapping>, MappingStoreError> {
+        let _write = self.writes.lock().await;
+        let Some(mut mapping) = self.get_mapping(tenant_id) else {
+            return Ok(None);
+        };
+        let previous = mapping.disabled.clone();
+        mapping.disabled = update(previous.clone());
+        if mapping.disabled != previous {
+            self.persist(tenant_id, Some(&mapping)).await?;
+            self.mappings.write().insert(tenant_id.clone(), mapping.clone());
+        }
+        Ok(Some(mapping))
+    }
+
+    /// When and by whom the tenant was disabled, if it is
+    pub fn disabled(&self, tenant_id: &TenantId) -> Option<TenantDisabled> {
+        let mappings = self.mappings.read();
+        mappings.get(tenant_id).and_then(|mapping| mapping.disabled.clone())
+    }
+
+    /// Remove a tenant's mapping for good, returning false if it had none
+    ///
+    /// Nothing changes if the removal cannot be stored.
+    pub async fn delete_mapping(&self, tenant_id: &TenantId) -> Result<bool, MappingStoreError> {
+        let _write = self.writes.lock().await;
+        if !self.has_mapping(tenant_id) {
+            return Ok(false);
+        }
+        self.persist(tenant_id, None).await?;
+        self.mappings.write().remove(tenant_id);
+        Ok(true)
+    }
+
+    /// All mappings, sorted by tenant ID
+    pub fn all_mappings(&self) -> Vec<(TenantId, TenantMapping)> {
+        let mappings = self.mappings.read();
+        let mut all: Vec<_> = mappings.iter().map(|(t, mapping)| (t.clone(), mapping.clone())).collect();
+        all.sort_by(|a, b| a.0.cmp(&b.0));
+        all
+    }
+
+    /// Get a tenant's full mapping, including its endpoint
+    pub fn get_mapping(&self, tenant_id: &TenantId) -> Option<TenantMapping> {
+        let mappings = self.mappings.read();
+        mappings.get(tenant_id).cloned()
     }
 
     /// Get NetBox tenant ID for an application tenant ID
     pub fn get_netbox_tenant_id(&self, tenant_id: &TenantId) -> Option<NetBoxTenantId> {
-        let mappings = self.mappings.read().unwrap();
-        mappings.get(tenant_id).copied()
+        let mappings = self.mappings.read();
+        mappings.get(tenant_id).map(|mapping| mapping.netbox_tenant_id)
     }
 
     /// Check if a tenant mapping exists
     pub fn has_mapping(&self, tenant_id: &TenantId) -> bool {
-        let mappings = self.mappings.read().unwrap();
+        let mappings = self.mappings.read();
         mappings.contains_key(tenant_id)
     }
 
-    /// Remove a tenant mapping
+    /// Remove a tenant mapping from memory only; see [`Self::delete_mapping`]
     pub fn remove_mapping(&self, tenant_id: &TenantId) {
-        let mut mappings = self.mappings.write().unwrap();
+        let mut mappings = self.mappings.write();
         mappings.remove(tenant_id);
     }
 
     /// Get all registered tenant IDs
     pub fn get_all_tenant_ids(&self) -> Vec<TenantId> {
-        let mappings = self.mappings.read().unwrap();
+        let mappings = self.mappings.read();
         mappings.keys().cloned().collect()
     }
 }
 
+impl<M: Into<TenantMapping>> From<HashMap<TenantId, M>> for TenantMappingService {
+    fn from(mappings: HashMap<TenantId, M>) -> Self {
+        Self {
+            mappings: RwLock::new(mappings.into_iter().map(|(tenant, mapping)| (tenant, mapping.into())).collect()),
+            ..Self::new()
+        }
+    }
+}
+
+/// Parse `tenant-a=10,tenant-b=20@eu` into tenant mappings, skipping malformed entries
+///
+/// `@name` routes the tenant to a named NetBox endpoint.
+pub fn parse_tenant_mappings(spec: &str) -> HashMap<TenantId, TenantMapping> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let parsed = entry
+                .split_once('=')
+                .and_then(|(tenant, target)| {
+                    let (id, endpoint) = match target.split_once('@') {
+                        Some((id, endpoint)) => (id, Some(endpoint.trim().to_string()).filter(|e| !e.is_empty())),
+                        None => (target, None),
+                    };
+                    Some((tenant.trim().to_string(), TenantMapping::new(id.trim().parse().ok()?, endpoint)))
+                })
+                .filter(|(tenant, _)| !tenant.is_empty());
+            if parsed.is_none() {
+                tracing::warn!("Ignoring malformed tenant mapping '{}'", entry);
+            }
+            parsed
+        })
+        .collect()
+}
+
 impl Default for TenantMappingService {
     fn default() -> Self {
         Self::new()
@@ -71,6 +447,11 @@ impl TenantAccessControl {
         }
     }
 
+    /// Use a mapping service shared with other components, e.g. the tenant admin API
+    pub fn from_shared(mapping_service: Arc<TenantMappingService>) -> Self {
+        Self { mapping_service }
+    }
+
     /// Get a reference to the underlying mapping service
     pub fn mapping_service(&self) -> &std::sync::Arc<TenantMappingService> {
         &self.mapping_service
@@ -212,6 +593,114 @@ mod tests {
     use super::*;
     use crate::netbox::models::{SiteStatus, DeviceStatus};
 
+    fn file_store(path: &std::path::Path) -> Arc<dyn TenantMappingStore> {
+        Arc::new(JsonFileTenantMappingStore::new(path))
+    }
+
+    /// Changes made through the service are in `store` after a restart
+    async fn check_mappings_are_written_through(store: Arc<dyn TenantMappingStore>) {
+        let service = TenantMappingService::load(store.clone()).await.unwrap().with_endpoints(["eu".to_string()]);
+        assert!(service.all_mappings().is_empty());
+        assert!(service.insert_mapping("tenant-a".to_string(), 10).await.unwrap());
+        assert!(!service.insert_mapping("tenant-a".to_string(), 11).await.unwrap());
+        assert!(service.insert_mapping("tenant-b".to_string(), 20).await.unwrap());
+        assert!(service.update_mapping(&"tenant-b".to_string(), 21).await.unwrap());
+        assert!(!service.update_mapping(&"tenant-c".to_string(), 30).await.unwrap());
+        assert!(service.delete_mapping(&"tenant-a".to_string()).await.unwrap());
+        assert!(!service.delete_mapping(&"tenant-a".to_string()).await.unwrap());
+        let routed = TenantMapping::new(30, Some("eu".to_string())).with_netbox_token(Some("sealed".to_string()));
+        assert!(service.insert_mapping("tenant-c".to_string(), routed.clone()).await.unwrap());
+        let disabled = service.disable_tenant(&"tenant-c".to_string(), "ops".to_string()).await.unwrap().unwrap();
+
+        let reloaded = TenantMappingService::load(store).await.unwrap();
+        let mut all = reloaded.all_mappings();
+        // Timestamps may come back rounded to the store's precision
+        for (_, mapping) in &mut all {
+            if let Some(ref mut stored) = mapping.disabled {
+                assert_eq!(stored.at.timestamp_micros(), disabled.at.timestamp_micros());
+                stored.at = disabled.at;
+            }
+        }
+        let routed = TenantMapping { disabled: Some(disabled), ..routed };
+        assert_eq!(all, vec![("tenant-b".to_string(), 21.into()), ("tenant-c".to_string(), routed)]);
+    }
+
+    #[tokio::test]
+    async fn test_mappings_are_written_through_to_store_file() {
+        let path = std::env::temp_dir().join(format!("netgate-mappings-{}.json", uuid::Uuid::new_v4()));
+        check_mappings_are_written_through(file_store(&path)).await;
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "postgres")]
+    #[tokio::test]
+    async fn test_mappings_are_written_through_to_postgres() {
+        let Some(database) = crate::database::test_database().await else {
+            return;
+        };
+        check_mappings_are_written_through(Arc::new(crate::database::PostgresTenantMappingStore::new(&database))).await;
+    }
+
+    #[tokio::test]
+    async fn test_mapping_change_is_undone_when_store_is_unwritable() {
+        let service = TenantMappingService::load(file_store("/nonexistent-dir/mappings.json".as_ref())).await.unwrap();
+
+        assert!(service.insert_mapping("tenant-a".to_string(), 10).await.is_err());
+        assert!(!service.has_mapping(&"tenant-a".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_endpoint_is_refused_at_registration() {
+        let service = TenantMappingService::new().with_endpoints(["eu".to_string()]);
+
+        assert!(service.insert_mapping("tenant-a".to_string(), TenantMapping::new(10, Some("eu".to_string()))).await.unwrap());
+        let err = service
+            .insert_mapping("tenant-b".to_string(), TenantMapping::new(20, Some("us".to_string())))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, MappingError::UnknownEndpoint { ref endpoint, .. } if endpoint == "us"));
+        assert!(!service.has_mapping(&"tenant-b".to_string()));
+        assert!(service
+            .update_mapping(&"tenant-a".to_string(), TenantMapping::new(10, Some("us".to_string())))
+            .await
+            .is_err());
+        assert_eq!(service.get_mapping(&"tenant-a".to_string()).unwrap().endpoint_name(), "eu");
+
+        // Mappings registered without a check, e.g. from a store file, are caught on validation
+        service.register_mapping("tenant-c".to_string(), TenantMapping::new(30, Some("apac".to_string())));
+        assert!(matches!(service.validate_endpoints(), Err(MappingError::UnknownEndpoint { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_store_file_keeps_endpoints_and_reads_bare_ids() {
+        let path = std::env::temp_dir().join(format!("netgate-mappings-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, r#"{"tenant-a": 10}"#).unwrap();
+
+        let service = TenantMappingService::load(file_store(&path)).await.unwrap().with_endpoints(["us".to_string()]);
+        assert_eq!(service.get_mapping(&"tenant-a".to_string()), Some(10.into()));
+        service
+            .insert_mapping("tenant-b".to_string(), TenantMapping::new(20, Some("us".to_string())))
+            .await
+            .unwrap();
+
+        let stored: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(stored["tenant-a"], 10);
+        assert_eq!(stored["tenant-b"], serde_json::json!({ "netbox_tenant_id": 20, "endpoint": "us" }));
+    }
+
+    #[test]
+    fn test_parse_tenant_mappings() {
+        let mappings = parse_tenant_mappings("tenant-a=10, tenant-b = 20@eu,broken,=5,tenant-c=x,");
+
+        assert_eq!(mappings.len(), 2);
+        assert_eq!(mappings["tenant-a"], 10.into());
+        assert_eq!(mappings["tenant-b"], TenantMapping::new(20, Some("eu".to_string())));
+
+        let service = TenantMappingService::from(mappings);
+        assert_eq!(service.get_netbox_tenant_id(&"tenant-b".to_string()), Some(20));
+    }
+
     fn create_test_site(id: i32, tenant_id: Option<i32>) -> NetBoxSite {
         NetBoxSite {
             id: Some(id),
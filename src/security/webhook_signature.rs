@@ -0,0 +1,260 @@
+use chrono::{DateTime, Utc};
+use hmac::digest::KeyInit;
+use hmac::{Hmac, Mac};
+use poem::http::HeaderMap;
+use sha2::Sha256;
+use std::time::Duration;
+
+/// Header carrying `sha256=<hex>` signatures of a delivery, one per active secret
+pub const SIGNATURE_HEADER: &str = "X-Netgate-Signature";
+
+/// Header carrying the Unix time a delivery was signed at, in seconds
+pub const TIMESTAMP_HEADER: &str = "X-Netgate-Timestamp";
+
+/// Largest difference between a delivery's timestamp and the receiver's clock
+/// that verification accepts; older deliveries may be replays
+pub const MAX_TIMESTAMP_SKEW: Duration = Duration::from_secs(300);
+
+/// Prefix of each signature in [`SIGNATURE_HEADER`]
+const SIGNATURE_SCHEME: &str = "sha256=";
+
+/// Why a webhook delivery failed verification
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum WebhookSignatureError {
+    #[error("Missing {0} header")]
+    MissingHeader(&'static str),
+
+    #[error("{TIMESTAMP_HEADER} is not a Unix timestamp")]
+    InvalidTimestamp,
+
+    /// The delivery was signed too long ago, or too far ahead, of the receiver's clock
+    #[error("Timestamp is {skew_secs}s off, more than the {}s allowed", MAX_TIMESTAMP_SKEW.as_secs())]
+    TimestampSkew { skew_secs: i64 },
+
+    #[error("No signature matches the secret")]
+    Mismatch,
+}
+
+/// Lowercase hex of the HMAC of `parts`, concatenated, under `secret`
+fn hmac_hex<M: Mac + KeyInit>(secret: &str, parts: &[&[u8]]) -> String {
+    let mut mac = <M as Mac>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Whether `signature` is the hex HMAC of `parts`, concatenated, under `secret`
+///
+/// Compared in constant time, so response timing tells nothing about the
+/// expected signature.
+pub(crate) fn hmac_matches<M: Mac + KeyInit>(secret: &str, parts: &[&[u8]], signature: &str) -> bool {
+    let signature = signature.trim();
+    if !signature.len().is_multiple_of(2) || !signature.is_ascii() {
+        return false;
+    }
+    let Ok(expected) = (0..signature.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&signature[i..i + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+    else {
+        return false;
+    };
+    let mut mac = <M as Mac>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// Hex HMAC-SHA256 of `<timestamp>.<body>` under `secret`
+///
+/// Signing the timestamp with the body keeps a captured delivery from being
+/// replayed later with a fresh timestamp.
+pub fn sign_webhook(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    hmac_hex::<Hmac<Sha256>>(secret, &[timestamp.to_string().as_bytes(), b".", body])
+}
+
+/// Value of [`SIGNATURE_HEADER`]: `sha256=<hex>` for each of `secrets`, comma-separated
+///
+/// While a secret is being rotated both are active, so receivers verify
+/// with whichever one they have.
+pub fn signature_header<'a>(secrets: impl IntoIterator<Item = &'a str>, timestamp: i64, body: &[u8]) -> String {
+    secrets
+        .into_iter()
+        .map(|secret| format!("{}{}", SIGNATURE_SCHEME, sign_webhook(secret, timestamp, body)))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Verify a webhook delivery from netgate against `secret`
+///
+/// Checks that one of the signatures in `X-Netgate-Signature` is the
+/// HMAC-SHA256 of `X-Netgate-Timestamp`, a dot and the raw `body`, and that
+/// the timestamp is within five minutes of the local clock. Pass the body
+/// exactly as received, before any JSON parsing.
+pub fn verify_webhook_signature(secret: &str, headers: &HeaderMap, body: &[u8]) -> Result<(), WebhookSignatureError> {
+    verify_webhook_signature_at(&[secret], headers, body, Utc::now())
+}
+
+/// Like [`verify_webhook_signature`], accepting a signature under any of `secrets`
+///
+/// For receivers rotating their secret: keep the old one here until the
+/// registration only signs with the new one.
+pub fn verify_webhook_signature_any(
+    secrets: &[&str],
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<(), WebhookSignatureError> {
+    verify_webhook_signature_at(secrets, headers, body, Utc::now())
+}
+
+fn verify_webhook_signature_at(
+    secrets: &[&str],
+    headers: &HeaderMap,
+    body: &[u8],
+    now: DateTime<Utc>,
+) -> Result<(), WebhookSignatureError> {
+    let header = |name: &'static str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .ok_or(WebhookSignatureError::MissingHeader(name))
+    };
+    let signatures = header(SIGNATURE_HEADER)?;
+    let timestamp: i64 = header(TIMESTAMP_HEADER)?
+        .trim()
+        .parse()
+        .map_err(|_| WebhookSignatureError::InvalidTimestamp)?;
+
+    let skew_secs = now.timestamp().saturating_sub(timestamp);
+    if skew_secs.unsigned_abs() > MAX_TIMESTAMP_SKEW.as_secs() {
+        return Err(WebhookSignatureError::TimestampSkew { skew_secs });
+    }
+
+    let timestamp = timestamp.to_string();
+    let signed: [&[u8]; 3] = [timestamp.as_bytes(), b".", body];
+    let matches = signatures
+        .split(',')
+        .filter_map(|signature| signature.trim().strip_prefix(SIGNATURE_SCHEME))
+        .any(|signature| {
+            secrets
+                .iter()
+                .any(|secret| hmac_matches::<Hmac<Sha256>>(secret, &signed, signature))
+        });
+    match matches {
+        true => Ok(()),
+        false => Err(WebhookSignatureError::Mismatch),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &[u8] = br#"{"event":"order_completed","order_id":"abc"}"#;
+
+    fn signed_headers(secrets: &[&str], timestamp: i64) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(SIGNATURE_HEADER, signature_header(secrets.iter().copied(), timestamp, BODY).parse().unwrap());
+        headers.insert(TIMESTAMP_HEADER, timestamp.to_string().parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_hmac_known_vector() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            hmac_hex::<Hmac<Sha256>>("Jefe", &[b"what do ya want ", b"for nothing?"]),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_signature_round_trip() {
+        let now = Utc::now();
+        let headers = signed_headers(&["s3cret"], now.timestamp());
+
+        assert_eq!(verify_webhook_signature_at(&["s3cret"], &headers, BODY, now), Ok(()));
+        assert_eq!(verify_webhook_signature("s3cret", &headers, BODY), Ok(()));
+        assert_eq!(
+            verify_webhook_signature_at(&["other"], &headers, BODY, now),
+            Err(WebhookSignatureError::Mismatch)
+        );
+        assert_eq!(
+            verify_webhook_signature_at(&["s3cret"], &headers, b"{}", now),
+            Err(WebhookSignatureError::Mismatch)
+        );
+    }
+
+    #[test]
+    fn test_timestamp_is_signed_with_the_body() {
+        let now = Utc::now();
+        let mut headers = signed_headers(&["s3cret"], now.timestamp() - 60);
+        headers.insert(TIMESTAMP_HEADER, now.timestamp().to_string().parse().unwrap());
+
+        assert_eq!(
+            verify_webhook_signature_at(&["s3cret"], &headers, BODY, now),
+            Err(WebhookSignatureError::Mismatch)
+        );
+    }
+
+    #[test]
+    fn test_skew_beyond_five_minutes_is_rejected() {
+        let now = Utc::now();
+        for offset in [-300, 300] {
+            let headers = signed_headers(&["s3cret"], now.timestamp() + offset);
+            assert_eq!(verify_webhook_signature_at(&["s3cret"], &headers, BODY, now), Ok(()));
+        }
+
+        let replayed = signed_headers(&["s3cret"], now.timestamp() - 301);
+        assert_eq!(
+            verify_webhook_signature_at(&["s3cret"], &replayed, BODY, now),
+            Err(WebhookSignatureError::TimestampSkew { skew_secs: 301 })
+        );
+        let ahead = signed_headers(&["s3cret"], now.timestamp() + 3600);
+        assert_eq!(
+            verify_webhook_signature_at(&["s3cret"], &ahead, BODY, now),
+            Err(WebhookSignatureError::TimestampSkew { skew_secs: -3600 })
+        );
+    }
+
+    #[test]
+    fn test_either_secret_validates_during_rotation() {
+        let now = Utc::now();
+        let headers = signed_headers(&["new-secret", "old-secret"], now.timestamp());
+
+        // Receivers that have either secret accept the delivery
+        assert_eq!(verify_webhook_signature_at(&["old-secret"], &headers, BODY, now), Ok(()));
+        assert_eq!(verify_webhook_signature_at(&["new-secret"], &headers, BODY, now), Ok(()));
+        assert_eq!(
+            verify_webhook_signature_at(&["retired-secret"], &headers, BODY, now),
+            Err(WebhookSignatureError::Mismatch)
+        );
+
+        // A receiver keeping both accepts deliveries signed with just one
+        let rotated = signed_headers(&["new-secret"], now.timestamp());
+        assert_eq!(verify_webhook_signature_at(&["old-secret", "new-secret"], &rotated, BODY, now), Ok(()));
+    }
+
+    #[test]
+    fn test_missing_or_malformed_headers() {
+        let now = Utc::now();
+        let mut headers = signed_headers(&["s3cret"], now.timestamp());
+        headers.insert(TIMESTAMP_HEADER, "yesterday".parse().unwrap());
+        assert_eq!(
+            verify_webhook_signature_at(&["s3cret"], &headers, BODY, now),
+            Err(WebhookSignatureError::InvalidTimestamp)
+        );
+
+        headers.remove(TIMESTAMP_HEADER);
+        assert_eq!(
+            verify_webhook_signature_at(&["s3cret"], &headers, BODY, now),
+            Err(WebhookSignatureError::MissingHeader(TIMESTAMP_HEADER))
+        );
+        assert_eq!(
+            verify_webhook_signature_at(&["s3cret"], &HeaderMap::new(), BODY, now),
+            Err(WebhookSignatureError::MissingHeader(SIGNATURE_HEADER))
+        );
+    }
+}
@@ -0,0 +1,333 @@
+use std::future::Future;
+
+use poem::endpoint::make_sync;
+use poem::listener::{Acceptor, Listener, TcpListener};
+use poem::web::Html;
+use poem::{EndpointExt, Response, Route};
+
+use crate::app::AppState;
+use crate::business::save_workflows;
+use crate::observability::{ConditionalGetMiddleware, CorsMiddleware, DeprecationMiddleware, SecurityHeadersMiddleware};
+use crate::security::TenantStatusMiddleware;
+use crate::shutdown;
+
+/// Path the current API version is served under, below [`ApiConfig::prefix`]
+pub const API_VERSION_PATH: &str = "/v1";
+
+/// How the API presents itself: OpenAPI metadata, where it is mounted and
+/// whether this version is deprecated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiConfig {
+    /// Title of the OpenAPI document
+    pub title: String,
+    /// Version of the OpenAPI document
+    pub version: String,
+    /// Base URL clients reach NetGate at, e.g. the gateway's; the spec's server
+    /// is this plus [`Self::mount_path`]
+    pub server_url: String,
+    /// Path the versioned API is mounted below, e.g. `/api/netgate`; empty for the root
+    pub prefix: String,
+    /// Send `Deprecation` on every response and mark every operation deprecated in the spec
+    pub deprecated: bool,
+    /// When the version is retired, as an HTTP date sent in `Sunset` while deprecated
+    pub sunset: Option<String>,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            title: "NetGate API".to_string(),
+            version: "1.0".to_string(),
+            server_url: "http://localhost:8080".to_string(),
+            prefix: String::new(),
+            deprecated: false,
+            sunset: None,
+        }
+    }
+}
+
+impl ApiConfig {
+    /// Load from API_TITLE, API_VERSION, API_SERVER_URL, API_PREFIX, API_DEPRECATED and API_SUNSET
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let text = |name: &str| std::env::var(name).ok().filter(|value| !value.trim().is_empty());
+        Self {
+            title: text("API_TITLE").unwrap_or(defaults.title),
+            version: text("API_VERSION").unwrap_or(defaults.version),
+            server_url: text("API_SERVER_URL").unwrap_or(defaults.server_url),
+            prefix: text("API_PREFIX")
+                .map(|prefix| normalize_prefix(&prefix))
+                .unwrap_or(defaults.prefix),
+            deprecated: text("API_DEPRECATED")
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(defaults.deprecated),
+            sunset: text("API_SUNSET"),
+        }
+    }
+
+    /// Where the API, `/docs` and `/spec` are served, e.g. `/api/netgate/v1`
+    pub fn mount_path(&self) -> String {
+        format!("{}{}", normalize_prefix(&self.prefix), API_VERSION_PATH)
+    }
+
+    /// The server listed in the spec, which requests from the docs are sent to
+    pub fn server(&self) -> String {
+        format!("{}{}", self.server_url.trim_end_matches('/'), self.mount_path())
+    }
+}
+
+/// `prefix` with one leading slash and no trailing one; empty stays empty
+fn normalize_prefix(prefix: &str) -> String {
+    let prefix = prefix.trim().trim_matches('/');
+    if prefix.is_empty() {
+        String::new()
+    } else {
+        format!("/{}", prefix)
+    }
+}
+
+/// `spec` with every operation flagged deprecated
+fn deprecate_operations(spec: &str) -> String {
+    let Ok(mut document) = serde_json::from_str::<serde_json::Value>(spec) else {
+        return spec.to_string();
+    };
+    let operations = document
+        .get_mut("paths")
+        .and_then(|paths| paths.as_object_mut())
+        .into_iter()
+        .flat_map(|paths| paths.values_mut())
+        .filter_map(|path| path.as_object_mut())
+        .flat_map(|path| path.values_mut())
+        .filter_map(|operation| operation.as_object_mut());
+    for operation in operations {
+        operation.insert("deprecated".to_string(), serde_json::Value::Bool(true));
+    }
+    serde_json::to_string_pretty(&document).unwrap_or_else(|_| spec.to_string())
+}
+
+/// The full NetGate app: the APIs, Swagger UI at `/docs` and the spec at `/spec`,
+/// all under [`ApiConfig::mount_path`], behind the disabled tenant, conditional
+/// GET, security headers and (when configured) CORS and deprecation middleware
+///
+/// Background tasks are not started; use [`run`] for a complete server, or hand
+/// the route to `poem::test::TestClient` to exercise it in-process.
+pub fn build_app(state: AppState) -> Route {
+    let api = &state.config.api;
+    let api_service = state.api_service();
+    let mut spec = api_service.spec();
+    let mut ui = api_service.swagger_ui_html();
+    if api.deprecated {
+        // Swagger UI embeds the spec, so it gets the flagged one too
+        let deprecated = deprecate_operations(&spec);
+        ui = ui.replace(&spec, &deprecated);
+        spec = deprecated;
+    }
+    let ui = make_sync(move |_| Html(ui.clone()));
+    let spec = make_sync(move |_| Response::builder().content_type("application/json").body(spec.clone()));
+
+    let app = Route::new()
+        .nest("/", api_service)
+        .at("/docs", ui)
+        .at("/spec", spec)
+        .with(TenantStatusMiddleware::new(state.tenant_mappings.clone()))
+        .with(ConditionalGetMiddleware::new(state.config.http_cache.clone()))
+        .with(SecurityHeadersMiddleware)
+        .with_if(api.deprecated, DeprecationMiddleware::new(api.sunset.clone()))
+        .with_if(state.config.cors.is_enabled(), CorsMiddleware::new(state.config.cors.clone()));
+    Route::new().nest(api.mount_path(), app)
+}
+
+/// Serve NetGate on `addr` until ctrl-c/SIGTERM
+pub async fn run(addr: impl Into<String>, state: AppState) -> std::io::Result<()> {
+    let addr = addr.into();
+    tracing::info!("Starting NetGate server on {}", addr);
+    let acceptor = TcpListener::bind(addr).into_acceptor().await?;
+    run_with_acceptor(acceptor, state, shutdown::shutdown_signal()).await
+}
+
+/// Serve NetGate on an already bound acceptor until `signal` completes
+///
+/// Binding `127.0.0.1:0` first lets callers learn the port before serving.
+/// Stored workflows are restored and background tasks started here; a store
+/// that can't be read stops startup. In-flight requests (including order
+/// processing) get the configured grace period to finish, and orders still
+/// processing afterwards are flagged as interrupted and stored, so the next
+/// start can reconcile them.
+pub async fn run_with_acceptor(
+    acceptor: impl Acceptor + 'static,
+    state: AppState,
+    signal: impl Future<Output = ()>,
+) -> std::io::Result<()> {
+    state
+        .restore_workflows()
+        .await
+        .map_err(|e| std::io::Error::other(format!("Failed to restore order workflows: {}", e)))?;
+    state.spawn_background_tasks();
+    let workflow_manager = state.workflow_manager.clone();
+    let workflow_store = state.workflow_store.clone();
+    let grace_period = state.config.shutdown_grace_period;
+    let hot_sites = state
+        .config
+        .warmup
+        .hot_sites_file
+        .clone()
+        .filter(|_| state.config.warmup.enabled)
+        .map(|path| (state.hot_sites.clone(), path));
+
+    poem::Server::new_with_acceptor(acceptor)
+        .run_with_graceful_shutdown(build_app(state), signal, Some(grace_period))
+        .await?;
+
+    shutdown::reconcile_interrupted_orders(&workflow_manager);
+    if let Some(store) = workflow_store {
+        if let Err(e) = save_workflows(&workflow_manager, store.as_ref()).await {
+            tracing::warn!("Failed to save order workflows: {}", e);
+        }
+    }
+    if let Some((hot_sites, path)) = hot_sites {
+        if let Err(e) = hot_sites.save(&path) {
+            tracing::warn!("Failed to save hot sites to {}: {}", path.display(), e);
+        }
+    }
+    tracing::info!("NetGate server stopped");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::bootstrap;
+    use crate::config::Config;
+    use poem::http::StatusCode;
+    use poem::test::TestClient;
+
+    #[tokio::test]
+    async fn test_build_app_serves_docs_and_security_headers() {
+        let state = bootstrap(Config::default()).await.unwrap();
+        let client = TestClient::new(build_app(state));
+
+        let response = client.get("/v1/spec").send().await;
+        response.assert_status_is_ok();
+        response.assert_header_exist("X-Content-Type-Options");
+
+        client.get("/v1/no-such-path").send().await.assert_status(StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_api_answers_under_the_configured_prefix_and_server() {
+        let state = bootstrap(Config {
+            api: ApiConfig {
+                title: "Gateway NetGate".to_string(),
+                version: "1.4".to_string(),
+                server_url: "https://gateway.example.com/".to_string(),
+                prefix: "api/netgate/".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let client = TestClient::new(build_app(state));
+
+        let response = client.get("/api/netgate/v1/spec").send().await;
+        response.assert_status_is_ok();
+        let spec: serde_json::Value =
+            serde_json::from_str(&response.0.into_body().into_string().await.unwrap()).unwrap();
+        assert_eq!(spec["servers"][0]["url"], "https://gateway.example.com/api/netgate/v1");
+        assert_eq!(spec["info"]["title"], "Gateway NetGate");
+        assert_eq!(spec["info"]["version"], "1.4");
+        assert!(spec["paths"]["/health"]["get"].get("deprecated").is_none());
+
+        let health = client.get("/api/netgate/v1/health").send().await;
+        health.assert_status_is_ok();
+        assert!(health.0.header("Deprecation").is_none());
+        client.get("/api/netgate/v1/docs").send().await.assert_status_is_ok();
+        client.get("/health").send().await.assert_status(StatusCode::NOT_FOUND);
+        client.get("/v1/health").send().await.assert_status(StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_deprecated_version_is_flagged_in_headers_and_spec() {
+        let state = bootstrap(Config {
+            api: ApiConfig {
+                deprecated: true,
+                sunset: Some("Wed, 31 Mar 2027 00:00:00 GMT".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let client = TestClient::new(build_app(state));
+
+        let health = client.get("/v1/health").send().await;
+        health.assert_header("Deprecation", "true");
+        health.assert_header("Sunset", "Wed, 31 Mar 2027 00:00:00 GMT");
+
+        let spec: serde_json::Value =
+            serde_json::from_str(&client.get("/v1/spec").send().await.0.into_body().into_string().await.unwrap())
+                .unwrap();
+        assert_eq!(spec["paths"]["/health"]["get"]["deprecated"], true);
+        assert_eq!(spec["paths"]["/orders/site"]["post"]["deprecated"], true);
+        assert!(spec["info"]["description"].as_str().unwrap().contains("retired on Wed, 31 Mar 2027"));
+
+        let docs = client.get("/v1/docs").send().await.0.into_body().into_string().await.unwrap();
+        assert!(docs.contains("\"deprecated\": true"));
+    }
+
+    #[tokio::test]
+    async fn test_orders_list_supports_conditional_get() {
+        let state = bootstrap(Config::default()).await.unwrap();
+        state.workflow_manager.create_order("tenant1".to_string());
+        let client = TestClient::new(build_app(state));
+
+        let first = client.get("/v1/orders").header("X-Tenant-Id", "tenant1").send().await;
+        first.assert_status_is_ok();
+        first.assert_header("Cache-Control", "private, max-age=5");
+        let etag = first.0.header("ETag").unwrap().to_string();
+
+        let second = client
+            .get("/v1/orders")
+            .header("X-Tenant-Id", "tenant1")
+            .header("If-None-Match", etag)
+            .send()
+            .await;
+        second.assert_status(StatusCode::NOT_MODIFIED);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_tenant_can_only_read_its_orders() {
+        let state = bootstrap(Config::default()).await.unwrap();
+        state.tenant_mappings.register_mapping("acme".to_string(), 10);
+        state.workflow_manager.create_order("acme".to_string());
+        state.tenant_mappings.disable_tenant(&"acme".to_string(), "ops".to_string()).await.unwrap();
+        let client = TestClient::new(build_app(state));
+
+        let order = client
+            .post("/v1/orders/site")
+            .header("X-Tenant-Id", "acme")
+            .body_json(&serde_json::json!({"name": "Site", "tags": []}))
+            .send()
+            .await;
+        order.assert_status(StatusCode::FORBIDDEN);
+        assert!(order.0.into_body().into_string().await.unwrap().contains("Tenant 'acme' was disabled by ops"));
+        client.get("/v1/orders").header("X-Tenant-Id", "acme").send().await.assert_status_is_ok();
+
+        // Admins can still restore it, after which it is served again
+        client
+            .post("/v1/tenants/acme/restore")
+            .header("X-Tenant-Id", "acme")
+            .header("X-User-Id", "ops")
+            .header("X-Roles", "admin")
+            .send()
+            .await
+            .assert_status_is_ok();
+        let order = client
+            .post("/v1/orders/site")
+            .header("X-Tenant-Id", "acme")
+            .body_json(&serde_json::json!({"name": "Site", "tags": []}))
+            .send()
+            .await;
+        assert_ne!(order.0.status(), StatusCode::FORBIDDEN);
+    }
+}
@@ -0,0 +1,46 @@
+use crate::business::WorkflowManager;
+use tracing::{info, warn};
+
+/// Resolve when the process receives ctrl-c or SIGTERM
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for ctrl-c: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("Received ctrl-c, shutting down"),
+        _ = terminate => info!("Received SIGTERM, shutting down"),
+    }
+}
+
+/// Flag orders left in Processing so they can be reconciled
+///
+/// Used both after the server has drained at shutdown and at startup, for
+/// orders a previous process left behind in the workflow store without
+/// stopping cleanly.
+pub fn reconcile_interrupted_orders(workflow_manager: &WorkflowManager) -> usize {
+    let marked = workflow_manager.mark_interrupted_processing_orders();
+    if marked > 0 {
+        warn!("Marked {} processing order(s) as interrupted", marked);
+    }
+    marked
+}
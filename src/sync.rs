@@ -0,0 +1,955 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::business::site_report::all_pages;
+use crate::error::AppError;
+use crate::netbox::models::{NetBoxDevice, NetBoxSite, NetBoxWebhookEvent};
+use crate::netbox::tenant_client::{SearchFilter, TenantAwareNetBoxClient, TenantScopedPage};
+use crate::security::tenant::{TenantId, TenantMappingService};
+
+/// Page size used when reading a tenant's sites and devices
+const SYNC_PAGE_SIZE: u32 = 1000;
+
+/// Mirroring of tenants' sites and devices into a local read model, off by default
+#[derive(Debug, Clone)]
+pub struct SyncConfig {
+    pub enabled: bool,
+    /// Time between sync runs
+    pub interval: Duration,
+    /// File the read model is kept in across restarts
+    pub store_file: Option<PathBuf>,
+    /// Re-read everything every this many runs, dropping objects deleted in NetBox;
+    /// 0 does so on the first run only
+    pub full_every: u32,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: Duration::from_secs(300),
+            store_file: None,
+            full_every: 12,
+        }
+    }
+}
+
+impl SyncConfig {
+    /// Load from SYNC_ENABLED, SYNC_INTERVAL_SECS, SYNC_STORE_FILE and SYNC_FULL_EVERY
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let number = |name: &str| std::env::var(name).ok().and_then(|value| value.parse::<u64>().ok());
+        Self {
+            enabled: std::env::var("SYNC_ENABLED")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(defaults.enabled),
+            interval: number("SYNC_INTERVAL_SECS")
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.interval),
+            store_file: std::env::var("SYNC_STORE_FILE")
+                .ok()
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from),
+            full_every: number("SYNC_FULL_EVERY")
+                .map(|runs| runs as u32)
+                .unwrap_or(defaults.full_every),
+        }
+    }
+}
+
+/// A NetBox object as mirrored, with NetBox's JSON for it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MirroredObject {
+    pub netbox_id: i32,
+    pub tenant: TenantId,
+    pub name: Option<String>,
+    pub status: Option<String>,
+    pub last_updated: Option<DateTime<Utc>>,
+    pub raw: serde_json::Value,
+}
+
+impl MirroredObject {
+    fn new<T: Serialize>(tenant: &TenantId, object: &T) -> Option<Self> {
+        Self::from_raw(tenant, serde_json::to_value(object).ok()?)
+    }
+
+    /// The object `raw` holds, which must have an id
+    fn from_raw(tenant: &TenantId, raw: serde_json::Value) -> Option<Self> {
+        let text = |key: &str| raw.get(key).and_then(|value| value.as_str()).map(str::to_string);
+        Some(Self {
+            netbox_id: raw.get("id")?.as_i64()? as i32,
+            tenant: tenant.clone(),
+            name: text("name"),
+            status: text("status"),
+            last_updated: text("last_updated").as_deref().and_then(parse_time),
+            raw,
+        })
+    }
+
+    fn matches(&self, filter: &SearchFilter) -> bool {
+        let q = filter.q.as_deref().map(str::to_lowercase);
+        let tags = self.raw.get("tags").and_then(|tags| tags.as_array());
+        q.is_none_or(|q| self.name.as_deref().is_some_and(|name| name.to_lowercase().contains(&q)))
+            && filter.status.as_ref().is_none_or(|status| self.status.as_ref() == Some(status))
+            && filter.tag.as_deref().is_none_or(|tag| {
+                tags.is_some_and(|tags| tags.iter().any(|value| value.as_str() == Some(tag)))
+            })
+            && filter.updated_since.is_none_or(|since| self.last_updated.is_some_and(|at| at >= since))
+    }
+}
+
+/// Kinds of NetBox objects the read model mirrors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectKind {
+    Site,
+    Device,
+}
+
+impl ObjectKind {
+    /// The kind a NetBox webhook's `model` names, if it is mirrored
+    pub fn from_model(model: &str) -> Option<Self> {
+        match model {
+            "site" => Some(Self::Site),
+            "device" => Some(Self::Device),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Site => "site",
+            Self::Device => "device",
+        }
+    }
+
+    /// NetBox's JSON for an object, as NetGate models it
+    fn normalize(&self, data: &serde_json::Value) -> Result<serde_json::Value, serde_json::Error> {
+        match self {
+            Self::Site => serde_json::to_value(serde_json::from_value::<NetBoxSite>(data.clone())?),
+            Self::Device => serde_json::to_value(serde_json::from_value::<NetBoxDevice>(data.clone())?),
+        }
+    }
+}
+
+/// Newest `last_updated` seen per object kind, where the next incremental run starts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncCursor {
+    pub sites: Option<DateTime<Utc>>,
+    pub devices: Option<DateTime<Utc>>,
+}
+
+/// When an object was deleted, and whose it was
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Deletion {
+    tenant: TenantId,
+    at: DateTime<Utc>,
+}
+
+/// The mirrored objects of one kind
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct MirroredSet {
+    objects: HashMap<i32, MirroredObject>,
+    /// Newest `last_updated` mirrored per tenant
+    cursors: HashMap<TenantId, DateTime<Utc>>,
+    /// When each tenant's objects were last read in full; earlier events are reflected in that read
+    read_at: HashMap<TenantId, DateTime<Utc>>,
+    /// Objects deleted since their tenant's last full read, so late events can't bring them back
+    deleted: HashMap<i32, Deletion>,
+}
+
+impl MirroredSet {
+    /// Store objects read for a tenant
+    ///
+    /// A full read, started at `full_read_at`, replaces the tenant's objects so
+    /// ones deleted in NetBox go away; otherwise the objects are upserted.
+    fn store(&mut self, tenant_id: &TenantId, fetched: Vec<MirroredObject>, full_read_at: Option<DateTime<Utc>>) {
+        if let Some(read_at) = full_read_at {
+            self.objects.retain(|_, object| &object.tenant != tenant_id);
+            self.cursors.remove(tenant_id);
+            self.read_at.insert(tenant_id.clone(), read_at);
+            // Deletions the read already reflects; later ones still hold
+            self.deleted.retain(|_, deletion| &deletion.tenant != tenant_id || deletion.at > read_at);
+        }
+        for object in fetched {
+            if !self.deleted.contains_key(&object.netbox_id) {
+                self.upsert(object);
+            }
+        }
+    }
+
+    fn upsert(&mut self, object: MirroredObject) {
+        if let Some(at) = object.last_updated {
+            let cursor = self.cursors.entry(object.tenant.clone()).or_insert(at);
+            *cursor = at.max(*cursor);
+        }
+        self.objects.insert(object.netbox_id, object);
+    }
+
+    fn retain_tenants(&mut self, tenants: &HashSet<TenantId>) {
+        self.objects.retain(|_, object| tenants.contains(&object.tenant));
+        self.cursors.retain(|tenant_id, _| tenants.contains(tenant_id));
+        self.read_at.retain(|tenant_id, _| tenants.contains(tenant_id));
+        self.deleted.retain(|_, deletion| tenants.contains(&deletion.tenant));
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Mirror {
+    sites: MirroredSet,
+    devices: MirroredSet,
+}
+
+impl Mirror {
+    fn set_mut(&mut self, kind: ObjectKind) -> &mut MirroredSet {
+        match kind {
+            ObjectKind::Site => &mut self.sites,
+            ObjectKind::Device => &mut self.devices,
+        }
+    }
+}
+
+/// Local copy of the tenants' NetBox sites and devices
+#[derive(Debug, Default)]
+pub struct ReadModel {
+    path: Option<PathBuf>,
+    mirror: RwLock<Mirror>,
+}
+
+impl ReadModel {
+    /// A read model held in memory only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read a model saved by [`Self::save`], which later saves go to; a missing file is an empty model
+    pub fn load(path: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let path = path.into();
+        let mirror = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Mirror::default(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self {
+            path: Some(path),
+            mirror: RwLock::new(mirror),
+        })
+    }
+
+    /// Write the model to its file, replacing it in one rename; a model without a file isn't saved
+    pub fn save(&self) -> std::io::Result<()> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        let bytes = serde_json::to_vec(&*self.mirror.read())?;
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(&tmp, path)
+    }
+
+    pub fn cursor(&self, tenant_id: &TenantId) -> SyncCursor {
+        let mirror = self.mirror.read();
+        SyncCursor {
+            sites: mirror.sites.cursors.get(tenant_id).copied(),
+            devices: mirror.devices.cursors.get(tenant_id).copied(),
+        }
+    }
+
+    /// Mirrored sites and devices, over all tenants
+    pub fn counts(&self) -> (usize, usize) {
+        let mirror = self.mirror.read();
+        (mirror.sites.objects.len(), mirror.devices.objects.len())
+    }
+
+    /// Drop everything of tenants not in `tenants`
+    fn retain_tenants(&self, tenants: &HashSet<TenantId>) {
+        let mut mirror = self.mirror.write();
+        mirror.sites.retain_tenants(tenants);
+        mirror.devices.retain_tenants(tenants);
+    }
+
+    /// Search a tenant's mirrored sites the way NetBox would be searched
+    ///
+    /// `q` matches names only, case-insensitively.
+    pub fn search_sites(&self, tenant_id: &TenantId, filter: &SearchFilter) -> TenantScopedPage<NetBoxSite> {
+        page(&self.mirror.read().sites.objects, tenant_id, filter, |_| true)
+    }
+
+    /// Search a tenant's mirrored devices, optionally within one site
+    pub fn search_devices(
+        &self,
+        tenant_id: &TenantId,
+        site_id: Option<i32>,
+        filter: &SearchFilter,
+    ) -> TenantScopedPage<NetBoxDevice> {
+        page(&self.mirror.read().devices.objects, tenant_id, filter, |object| {
+            site_id.is_none_or(|site_id| {
+                object.raw.get("site").and_then(|site| site.as_i64()) == Some(i64::from(site_id))
+            })
+        })
+    }
+}
+
+fn parse_time(at: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(at).ok().map(|at| at.with_timezone(&Utc))
+}
+
+/// One page of a tenant's objects that match `filter`, in id order
+fn page<T: serde::de::DeserializeOwned>(
+    objects: &HashMap<i32, MirroredObject>,
+    tenant_id: &TenantId,
+    filter: &SearchFilter,
+    keep: impl Fn(&MirroredObject) -> bool,
+) -> TenantScopedPage<T> {
+    let mut matching: Vec<&MirroredObject> = objects
+        .values()
+        .filter(|object| &object.tenant == tenant_id && keep(object) && object.matches(filter))
+        .collect();
+    matching.sort_by_key(|object| object.netbox_id);
+    let total = matching.len();
+    let offset = filter.offset.unwrap_or(0) as usize;
+    let limit = filter.limit.map_or(total, |limit| limit as usize);
+    let results: Vec<T> = matching
+        .into_iter()
+        .skip(offset)
+        .take(limit)
+        .filter_map(|object| serde_json::from_value(object.raw.clone()).ok())
+        .collect();
+    TenantScopedPage {
+        has_more: offset + results.len() < total,
+        results,
+        total: Some(total as i32),
+        filtered_out: 0,
+        degraded: false,
+    }
+}
+
+/// Outcome of the sync runs so far
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Object)]
+pub struct SyncStatus {
+    pub runs: u64,
+    pub last_started_at: Option<String>,
+    pub last_finished_at: Option<String>,
+    pub last_duration_ms: Option<u64>,
+    /// Whether the last run re-read everything instead of only what changed
+    pub last_full: bool,
+    /// Sites and devices the last run read from NetBox
+    pub last_fetched: u64,
+    /// Sites and devices mirrored, over all tenants
+    pub sites: u64,
+    pub devices: u64,
+    /// What went wrong in the last run, one entry per failed tenant
+    pub errors: Vec<String>,
+    /// NetBox webhooks applied to the read model, and ignored as stale or not about a mapped tenant
+    pub events_applied: u64,
+    pub events_ignored: u64,
+    /// Webhooks showing earlier ones were missed, each followed by a re-read of the object kind
+    pub gaps_detected: u64,
+}
+
+/// What a NetBox webhook did to the read model
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventOutcome {
+    Applied,
+    /// Older than what is mirrored, or about no mapped tenant's object
+    Ignored,
+    /// Applied, but the tenant's earlier events about objects of `kind` were missed;
+    /// see [`SyncService::resync`]
+    Gap { tenant: TenantId, kind: ObjectKind },
+}
+
+/// Mirrors every mapped tenant's NetBox sites and devices into a [`ReadModel`]
+///
+/// Runs after the first only ask NetBox for objects updated since the newest
+/// one already mirrored; every `full_every`th run re-reads everything so
+/// deletions, and changes whose webhooks were lost, show up. In between,
+/// NetBox webhooks passed to [`Self::apply_event`] keep the model current.
+pub struct SyncService {
+    client: Arc<TenantAwareNetBoxClient>,
+    mappings: Arc<TenantMappingService>,
+    model: Arc<ReadModel>,
+    full_every: u32,
+    status: RwLock<SyncStatus>,
+}
+
+impl SyncService {
+    pub fn new(
+        client: Arc<TenantAwareNetBoxClient>,
+        mappings: Arc<TenantMappingService>,
+        model: Arc<ReadModel>,
+    ) -> Self {
+        Self {
+            client,
+            mappings,
+            model,
+            full_every: SyncConfig::default().full_every,
+            status: RwLock::new(SyncStatus::default()),
+        }
+    }
+
+    pub fn with_full_every(mut self, runs: u32) -> Self {
+        self.full_every = runs;
+        self
+    }
+
+    pub fn read_model(&self) -> &ReadModel {
+        &self.model
+    }
+
+    /// Status of the runs, with the objects mirrored right now
+    pub fn status(&self) -> SyncStatus {
+        let mut status = self.status.read().clone();
+        let (sites, devices) = self.model.counts();
+        status.sites = sites as u64;
+        status.devices = devices as u64;
+        status
+    }
+
+    /// Search a tenant's sites in the read model; unmapped tenants are `Unauthorized`
+    pub fn search_sites(
+        &self,
+        tenant_id: &TenantId,
+        filter: &SearchFilter,
+    ) -> Result<TenantScopedPage<NetBoxSite>, AppError> {
+        self.ensure_mapped(tenant_id)?;
+        Ok(self.model.search_sites(tenant_id, filter))
+    }
+
+    /// Search a tenant's devices in the read model; unmapped tenants are `Unauthorized`
+    pub fn search_devices(
+        &self,
+        tenant_id: &TenantId,
+        site_id: Option<i32>,
+        filter: &SearchFilter,
+    ) -> Result<TenantScopedPage<NetBoxDevice>, AppError> {
+        self.ensure_mapped(tenant_id)?;
+        Ok(self.model.search_devices(tenant_id, site_id, filter))
+    }
+
+    fn ensure_mapped(&self, tenant_id: &TenantId) -> Result<(), AppError> {
+        if self.mappings.has_mapping(tenant_id) {
+            Ok(())
+        } else {
+            Err(AppError::Unauthorized)
+        }
+    }
+
+    /// Sync every enabled tenant once and save the model
+    ///
+    /// A tenant that fails keeps what was mirrored before and is listed in the
+    /// status errors; the other tenants are still synced.
+    pub async fn run(&self) -> SyncStatus {
+        let runs = self.status.read().runs;
+        let full = runs == 0 || (self.full_every > 0 && runs.is_multiple_of(u64::from(self.full_every)));
+        let started_at = Utc::now();
+        let timer = Instant::now();
+
+        let mut fetched = 0;
+        let mut errors = Vec::new();
+        let mut tenants = HashSet::new();
+        for (tenant_id, mapping) in self.mappings.all_mappings() {
+            if mapping.disabled.is_some() {
+                continue;
+            }
+            match self.sync_tenant(&tenant_id, full).await {
+                Ok(count) => fetched += count,
+                Err(e) => errors.push(format!("Tenant '{}': {}", tenant_id, e)),
+            }
+            tenants.insert(tenant_id);
+        }
+        // Tenants unmapped or disabled since the last full run are no longer served
+        if full {
+            self.model.retain_tenants(&tenants);
+        }
+        if let Err(e) = self.model.save() {
+            errors.push(format!("Saving the read model failed: {}", e));
+        }
+
+        for error in &errors {
+            warn!("Sync: {}", error);
+        }
+        {
+            let mut status = self.status.write();
+            status.runs = runs + 1;
+            status.last_started_at = Some(started_at.to_rfc3339());
+            status.last_finished_at = Some(Utc::now().to_rfc3339());
+            status.last_duration_ms = Some(timer.elapsed().as_millis() as u64);
+            status.last_full = full;
+            status.last_fetched = fetched as u64;
+            status.errors = errors;
+        }
+        let status = self.status();
+        info!(
+            "Sync run {} read {} object(s), {} error(s)",
+            status.runs,
+            status.last_fetched,
+            status.errors.len()
+        );
+        status
+    }
+
+    /// Read the tenant's sites and devices changed since its cursor, or all of them on a full run
+    async fn sync_tenant(&self, tenant_id: &TenantId, full: bool) -> Result<usize, AppError> {
+        let read_at = Utc::now();
+        let cursor = if full { SyncCursor::default() } else { self.model.cursor(tenant_id) };
+        let sites = self.read(tenant_id, ObjectKind::Site, cursor.sites).await?;
+        let devices = self.read(tenant_id, ObjectKind::Device, cursor.devices).await?;
+
+        let fetched = sites.len() + devices.len();
+        let full_read_at = full.then_some(read_at);
+        let mut mirror = self.model.mirror.write();
+        mirror.sites.store(tenant_id, sites, full_read_at);
+        mirror.devices.store(tenant_id, devices, full_read_at);
+        Ok(fetched)
+    }
+
+    /// Re-read all the tenant's objects of `kind`, e.g. after webhooks were missed
+    pub async fn resync(&self, tenant_id: &TenantId, kind: ObjectKind) -> Result<usize, AppError> {
+        let read_at = Utc::now();
+        let objects = self.read(tenant_id, kind, None).await?;
+        let fetched = objects.len();
+        self.model.mirror.write().set_mut(kind).store(tenant_id, objects, Some(read_at));
+        Ok(fetched)
+    }
+
+    /// The tenant's objects of `kind`, only those updated since `since` if given
+    async fn read(
+        &self,
+        tenant_id: &TenantId,
+        kind: ObjectKind,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<MirroredObject>, AppError> {
+        let filter = |offset| SearchFilter {
+            updated_since: since,
+            limit: Some(SYNC_PAGE_SIZE),
+            offset: Some(offset),
+            ..Default::default()
+        };
+        Ok(match kind {
+            ObjectKind::Site => all_pages(|offset| {
+                let filter = filter(offset);
+                async move { self.client.search_sites(tenant_id, &filter).await }
+            })
+            .await?
+            .iter()
+            .filter_map(|site| MirroredObject::new(tenant_id, site))
+            .collect(),
+            ObjectKind::Device => all_pages(|offset| {
+                let filter = filter(offset);
+                async move { self.client.search_devices(tenant_id, None, &filter).await }
+            })
+            .await?
+            .iter()
+            .filter_map(|device| MirroredObject::new(tenant_id, device))
+            .collect(),
+        })
+    }
+
+    /// Apply a NetBox webhook to the read model
+    ///
+    /// `endpoint` names the NetBox that sent it, whose tenant mappings say
+    /// whose object it is. Webhooks may arrive late and in any order: one about
+    /// an object older than the mirrored copy, or fired before the tenant's
+    /// last full read (by NetBox's clock against ours), is ignored, and deleted
+    /// objects stay deleted.
+    ///
+    /// An update of an object that isn't mirrored, or whose state before the
+    /// change is newer than the mirrored copy, means webhooks were missed; it
+    /// is applied and reported as a [`EventOutcome::Gap`].
+    pub fn apply_event(&self, endpoint: &str, event: &NetBoxWebhookEvent) -> Result<EventOutcome, AppError> {
+        let outcome = self.apply(endpoint, event)?;
+        let mut status = self.status.write();
+        match outcome {
+            EventOutcome::Applied => status.events_applied += 1,
+            EventOutcome::Ignored => status.events_ignored += 1,
+            EventOutcome::Gap { .. } => {
+                status.events_applied += 1;
+                status.gaps_detected += 1;
+            }
+        }
+        Ok(outcome)
+    }
+
+    fn apply(&self, endpoint: &str, event: &NetBoxWebhookEvent) -> Result<EventOutcome, AppError> {
+        let Some(kind) = ObjectKind::from_model(&event.model) else {
+            return Ok(EventOutcome::Ignored);
+        };
+        let invalid = |e: &dyn std::fmt::Display| {
+            AppError::ValidationError(format!("Webhook {} data is not a NetBox {}: {}", event.event, kind.as_str(), e))
+        };
+        let raw = kind.normalize(&event.data).map_err(|e| invalid(&e))?;
+        let id = raw
+            .get("id")
+            .and_then(|id| id.as_i64())
+            .ok_or_else(|| invalid(&"it has no id"))? as i32;
+        let tenant = raw
+            .get("tenant")
+            .and_then(|tenant| tenant.as_i64())
+            .and_then(|netbox_tenant_id| self.tenant_for(endpoint, netbox_tenant_id as i32));
+
+        let mut mirror = self.model.mirror.write();
+        let set = mirror.set_mut(kind);
+        let existing = set.objects.get(&id).cloned();
+        // Neither mirrored nor any mapped tenant's
+        let Some(owner) = existing.as_ref().map(|object| object.tenant.clone()).or_else(|| tenant.clone()) else {
+            return Ok(EventOutcome::Ignored);
+        };
+        let read_before = set.read_at.get(&owner).is_some_and(|read_at| event.timestamp <= *read_at);
+        // NetBox never reuses ids, so a deleted object is gone for good
+        if read_before || set.deleted.contains_key(&id) {
+            return Ok(EventOutcome::Ignored);
+        }
+
+        if event.event == "deleted" {
+            set.objects.remove(&id);
+            set.deleted.insert(id, Deletion { tenant: owner, at: event.timestamp });
+            return Ok(EventOutcome::Applied);
+        }
+        if event.event != "created" && event.event != "updated" {
+            return Ok(EventOutcome::Ignored);
+        }
+
+        let incoming = MirroredObject::from_raw(&owner, raw).ok_or_else(|| invalid(&"it has no id"))?;
+        if existing.as_ref().is_some_and(|existing| existing.last_updated > incoming.last_updated) {
+            return Ok(EventOutcome::Ignored);
+        }
+        let Some(tenant) = tenant else {
+            // Moved to a NetBox tenant no one is mapped to
+            set.objects.remove(&id);
+            return Ok(EventOutcome::Applied);
+        };
+
+        let before = event
+            .snapshots
+            .as_ref()
+            .and_then(|snapshots| snapshots.prechange.as_ref())
+            .and_then(|prechange| prechange.get("last_updated"))
+            .and_then(|at| at.as_str())
+            .and_then(parse_time);
+        let missed = match existing {
+            None => event.event == "updated",
+            Some(ref existing) => before.is_some_and(|before| Some(before) > existing.last_updated),
+        };
+        set.upsert(MirroredObject { tenant: tenant.clone(), ..incoming });
+        Ok(if missed { EventOutcome::Gap { tenant, kind } } else { EventOutcome::Applied })
+    }
+
+    /// The enabled tenant mapped to `netbox_tenant_id` on `endpoint`
+    fn tenant_for(&self, endpoint: &str, netbox_tenant_id: i32) -> Option<TenantId> {
+        self.mappings
+            .all_mappings()
+            .into_iter()
+            .find(|(_, mapping)| {
+                mapping.disabled.is_none()
+                    && mapping.netbox_tenant_id == netbox_tenant_id
+                    && mapping.endpoint_name() == endpoint
+            })
+            .map(|(tenant_id, _)| tenant_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::netbox::NetBoxClient;
+    use crate::security::tenant::{TenantAccessControl, DEFAULT_NETBOX_ENDPOINT};
+    use serde_json::json;
+    use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+    fn service(uri: String, model: Arc<ReadModel>) -> SyncService {
+        let config = Config {
+            netbox_url: uri,
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let client = Arc::new(NetBoxClient::new(config).unwrap());
+        let mappings = Arc::new(TenantMappingService::from(HashMap::from([("acme".to_string(), 10)])));
+        let access_control = Arc::new(TenantAccessControl::from_shared(mappings.clone()));
+        SyncService::new(Arc::new(TenantAwareNetBoxClient::new(client, access_control)), mappings, model)
+    }
+
+    fn listing(results: serde_json::Value) -> ResponseTemplate {
+        let count = results.as_array().unwrap().len();
+        ResponseTemplate::new(200).set_body_json(json!({ "count": count, "next": null, "results": results }))
+    }
+
+    #[tokio::test]
+    async fn test_incremental_run_only_fetches_changed_objects() {
+        let mock_server = MockServer::start().await;
+        let dir = std::env::temp_dir().join(format!("netgate-sync-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = dir.join("read-model.json");
+        let model = Arc::new(ReadModel::load(&store).unwrap());
+        let sync = service(mock_server.uri(), model.clone());
+
+        // The first run reads everything
+        Mock::given(method("GET"))
+            .and(path_regex("^/api/dcim/sites/$"))
+            .and(query_param("tenant_id", "10"))
+            .respond_with(listing(json!([
+                {"id": 1, "name": "Berlin", "status": "active", "tenant": 10, "last_updated": "2024-05-01T10:00:00Z"},
+                {"id": 2, "name": "Paris", "status": "planned", "tenant": 10, "last_updated": "2024-05-02T10:00:00Z"}
+            ])))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex("^/api/dcim/devices/$"))
+            .respond_with(listing(json!([
+                {"id": 7, "name": "sw1", "site": 1, "tenant": 10, "last_updated": "2024-05-03T08:00:00Z"}
+            ])))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let first = sync.run().await;
+        assert!(first.last_full);
+        assert_eq!(first.last_fetched, 3);
+        assert_eq!((first.sites, first.devices), (2, 1));
+        assert!(first.errors.is_empty());
+
+        // The second asks only for what changed since the newest object seen
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .and(query_param("last_updated__gte", "2024-05-02T10:00:00.000000Z"))
+            .respond_with(listing(json!([
+                {"id": 2, "name": "Paris", "status": "active", "tenant": 10, "last_updated": "2024-05-04T09:00:00Z"}
+            ])))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/devices/"))
+            .and(query_param("last_updated__gte", "2024-05-03T08:00:00.000000Z"))
+            .respond_with(listing(json!([])))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let second = sync.run().await;
+        assert!(!second.last_full);
+        assert_eq!(second.last_fetched, 1);
+        assert_eq!((second.sites, second.devices), (2, 1));
+        assert_eq!(
+            model.cursor(&"acme".to_string()).sites,
+            Some("2024-05-04T09:00:00Z".parse().unwrap())
+        );
+
+        let active = SearchFilter { status: Some("active".to_string()), ..Default::default() };
+        let names: Vec<_> = model
+            .search_sites(&"acme".to_string(), &active)
+            .results
+            .into_iter()
+            .map(|site| site.name)
+            .collect();
+        assert_eq!(names, vec!["Berlin", "Paris"]);
+
+        // The model survives a restart, cursors included
+        let reloaded = ReadModel::load(&store).unwrap();
+        assert_eq!(reloaded.counts(), (2, 1));
+        assert_eq!(reloaded.cursor(&"acme".to_string()), model.cursor(&"acme".to_string()));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_full_run_drops_deleted_objects_and_failures_keep_the_mirror() {
+        let mock_server = MockServer::start().await;
+        let model = Arc::new(ReadModel::new());
+        let sync = service(mock_server.uri(), model.clone()).with_full_every(1);
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(listing(json!([
+                {"id": 1, "name": "Berlin", "tenant": 10},
+                {"id": 2, "name": "Paris", "tenant": 10}
+            ])))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/devices/"))
+            .respond_with(listing(json!([])))
+            .mount(&mock_server)
+            .await;
+        sync.run().await;
+        assert_eq!(model.counts(), (2, 0));
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(listing(json!([{"id": 1, "name": "Berlin", "tenant": 10}])))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        let status = sync.run().await;
+        assert!(status.last_full);
+        assert_eq!(model.counts(), (1, 0));
+
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({"detail": "bad filter"})))
+            .mount(&mock_server)
+            .await;
+        let status = sync.run().await;
+        assert_eq!(status.errors.len(), 1);
+        assert!(status.errors[0].starts_with("Tenant 'acme'"));
+        assert_eq!(model.counts(), (1, 0));
+
+        assert!(matches!(
+            sync.search_sites(&"stranger".to_string(), &SearchFilter::default()),
+            Err(AppError::Unauthorized)
+        ));
+    }
+
+    fn event(kind: &str, model: &str, at: &str, data: serde_json::Value, prechange: Option<&str>) -> NetBoxWebhookEvent {
+        serde_json::from_value(json!({
+            "event": kind,
+            "timestamp": at,
+            "model": model,
+            "username": "admin",
+            "request_id": null,
+            "data": data,
+            "snapshots": {"prechange": prechange.map(|at| json!({"last_updated": at})), "postchange": {}}
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_scrambled_webhooks_converge_to_the_in_order_state() {
+        let site = |id: i32, name: &str, status: &str, at: &str| {
+            json!({"id": id, "name": name, "status": status, "tenant": 10, "last_updated": at})
+        };
+        let device = |at: &str| json!({"id": 7, "name": "sw1", "site": 1, "tenant": 10, "last_updated": at});
+        let in_order = vec![
+            event("created", "site", "2024-05-01T10:00:00Z", site(1, "Berlin", "planned", "2024-05-01T10:00:00Z"), None),
+            event("created", "site", "2024-05-01T11:00:00Z", site(2, "Paris", "active", "2024-05-01T11:00:00Z"), None),
+            event("created", "device", "2024-05-01T11:30:00Z", device("2024-05-01T11:30:00Z"), None),
+            event(
+                "updated",
+                "site",
+                "2024-05-01T12:00:00Z",
+                site(1, "Berlin", "active", "2024-05-01T12:00:00Z"),
+                Some("2024-05-01T10:00:00Z"),
+            ),
+            event("deleted", "site", "2024-05-01T13:00:00Z", site(2, "Paris", "active", "2024-05-01T11:00:00Z"), None),
+            event("created", "site", "2024-05-01T14:00:00Z", site(3, "Rome", "planned", "2024-05-01T14:00:00Z"), None),
+            event(
+                "updated",
+                "site",
+                "2024-05-01T15:00:00Z",
+                site(3, "Roma", "planned", "2024-05-01T15:00:00Z"),
+                Some("2024-05-01T14:00:00Z"),
+            ),
+            event("updated", "device", "2024-05-01T16:00:00Z", device("2024-05-01T16:00:00Z"), Some("2024-05-01T11:30:00Z")),
+        ];
+
+        let mock_server = MockServer::start().await;
+        let expected = service(mock_server.uri(), Arc::new(ReadModel::new()));
+        for event in &in_order {
+            assert_eq!(expected.apply_event(DEFAULT_NETBOX_ENDPOINT, event).unwrap(), EventOutcome::Applied);
+        }
+
+        // Delivered late, retried and out of order
+        let model = Arc::new(ReadModel::new());
+        let sync = service(mock_server.uri(), model.clone());
+        let mut gaps = Vec::new();
+        for i in [3, 4, 0, 1, 6, 5, 7, 2, 4, 0] {
+            if let EventOutcome::Gap { kind, .. } = sync.apply_event(DEFAULT_NETBOX_ENDPOINT, &in_order[i]).unwrap() {
+                gaps.push(kind);
+            }
+        }
+        // Updates of objects never seen showed webhooks were missed
+        assert_eq!(gaps, vec![ObjectKind::Site, ObjectKind::Site, ObjectKind::Device]);
+
+        let acme = "acme".to_string();
+        let all = SearchFilter::default();
+        let contents = |model: &ReadModel| {
+            (
+                serde_json::to_value(model.search_sites(&acme, &all).results).unwrap(),
+                serde_json::to_value(model.search_devices(&acme, None, &all).results).unwrap(),
+            )
+        };
+        assert_eq!(contents(&model), contents(expected.read_model()));
+        assert_eq!(model.counts(), (2, 1));
+
+        // The re-read after the gap agrees, and clears the deleted site's tombstone
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .and(query_param("tenant_id", "10"))
+            .respond_with(listing(json!([
+                site(1, "Berlin", "active", "2024-05-01T12:00:00Z"),
+                site(3, "Roma", "planned", "2024-05-01T15:00:00Z")
+            ])))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        assert_eq!(sync.resync(&acme, ObjectKind::Site).await.unwrap(), 2);
+        assert_eq!(contents(&model), contents(expected.read_model()));
+        assert!(model.mirror.read().sites.deleted.is_empty());
+
+        // Webhooks from before the re-read change nothing
+        assert_eq!(sync.apply_event(DEFAULT_NETBOX_ENDPOINT, &in_order[1]).unwrap(), EventOutcome::Ignored);
+        assert_eq!(contents(&model), contents(expected.read_model()));
+
+        let status = sync.status();
+        assert_eq!((status.events_applied, status.events_ignored, status.gaps_detected), (4, 7, 3));
+    }
+
+    #[tokio::test]
+    async fn test_webhooks_about_other_tenants_or_stale_copies_are_ignored() {
+        let mock_server = MockServer::start().await;
+        let model = Arc::new(ReadModel::new());
+        let sync = service(mock_server.uri(), model.clone());
+        let site = |tenant: i32, at: &str| json!({"id": 5, "name": "Oslo", "tenant": tenant, "last_updated": at});
+        let apply = |event: &NetBoxWebhookEvent| sync.apply_event(DEFAULT_NETBOX_ENDPOINT, event).unwrap();
+
+        let unmapped = event("created", "site", "2024-05-01T10:00:00Z", site(99, "2024-05-01T10:00:00Z"), None);
+        assert_eq!(apply(&unmapped), EventOutcome::Ignored);
+        let circuit = event("created", "circuit", "2024-05-01T10:00:00Z", json!({"id": 1}), None);
+        assert_eq!(apply(&circuit), EventOutcome::Ignored);
+        // Another NetBox's tenant 10 is someone else
+        let created = event("created", "site", "2024-05-01T10:00:00Z", site(10, "2024-05-01T10:00:00Z"), None);
+        assert_eq!(sync.apply_event("eu", &created).unwrap(), EventOutcome::Ignored);
+        assert_eq!(model.counts(), (0, 0));
+
+        assert_eq!(apply(&created), EventOutcome::Applied);
+        let stale = event("updated", "site", "2024-05-01T09:00:00Z", site(10, "2024-05-01T09:00:00Z"), None);
+        assert_eq!(apply(&stale), EventOutcome::Ignored);
+
+        // Changed at 11:00 by an update whose webhook never came
+        let skipped = event(
+            "updated",
+            "site",
+            "2024-05-01T12:00:00Z",
+            site(10, "2024-05-01T12:00:00Z"),
+            Some("2024-05-01T11:00:00Z"),
+        );
+        assert_eq!(
+            apply(&skipped),
+            EventOutcome::Gap { tenant: "acme".to_string(), kind: ObjectKind::Site }
+        );
+
+        // Moved to a tenant no one is mapped to
+        let moved = event("updated", "site", "2024-05-01T13:00:00Z", site(99, "2024-05-01T13:00:00Z"), None);
+        assert_eq!(apply(&moved), EventOutcome::Applied);
+        assert_eq!(model.counts(), (0, 0));
+
+        let invalid = event("updated", "device", "2024-05-01T13:00:00Z", json!({"name": "sw1"}), None);
+        assert!(matches!(
+            sync.apply_event(DEFAULT_NETBOX_ENDPOINT, &invalid),
+            Err(AppError::ValidationError(_))
+        ));
+        assert!(mock_server.received_requests().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,170 @@
+use ipnet::IpNet;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Parse a network CIDR such as `10.0.0.0/16` or `2001:db8::/48`
+///
+/// Host bits must be zero, so `10.0.0.1/24` is rejected rather than silently truncated.
+pub fn parse_cidr(cidr: &str) -> Result<IpNet, String> {
+    let net: IpNet = cidr
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid CIDR", cidr))?;
+    if net != net.trunc() {
+        return Err(format!("'{}' has host bits set; did you mean {}?", cidr, net.trunc()));
+    }
+    Ok(net)
+}
+
+/// Whether two networks share any address; networks of different families never do
+pub fn overlaps(a: &IpNet, b: &IpNet) -> bool {
+    match (range(a), range(b)) {
+        (Some((a_start, a_end)), Some((b_start, b_end))) if is_ipv4(a) == is_ipv4(b) => {
+            a_start <= b_end && b_start <= a_end
+        }
+        _ => false,
+    }
+}
+
+/// The first `prefix_len` subnet of `parent` that overlaps none of `allocated`
+///
+/// Returns None when `prefix_len` doesn't fit inside `parent` or the parent is exhausted.
+pub fn next_free_subnet(parent: &IpNet, prefix_len: u8, allocated: &[IpNet]) -> Option<IpNet> {
+    if prefix_len < parent.prefix_len() || prefix_len > parent.max_prefix_len() {
+        return None;
+    }
+    let (parent_start, parent_end) = range(parent)?;
+    // Subnet size minus one, so a /0 IPv6 subnet doesn't overflow
+    let span = host_mask(parent.max_prefix_len() - prefix_len);
+
+    let mut start = parent_start;
+    loop {
+        let end = start + span;
+        let candidate = subnet_at(parent, start, prefix_len)?;
+        let blocking_end = allocated
+            .iter()
+            .filter(|net| overlaps(net, &candidate))
+            .filter_map(range)
+            .map(|(_, end)| end)
+            .max();
+
+        let Some(blocking_end) = blocking_end else {
+            return Some(candidate);
+        };
+        // Skip to the first aligned subnet after the blocking allocation
+        let next = blocking_end.max(end).checked_add(1)?;
+        let size = span + 1;
+        let offset = (next - parent_start).checked_add(span)? / size;
+        start = offset.checked_mul(size).and_then(|o| parent_start.checked_add(o))?;
+        if start > parent_end {
+            return None;
+        }
+    }
+}
+
+fn is_ipv4(net: &IpNet) -> bool {
+    matches!(net, IpNet::V4(_))
+}
+
+/// First and last address of a network, as integers
+fn range(net: &IpNet) -> Option<(u128, u128)> {
+    let start = match net.network() {
+        IpAddr::V4(addr) => u32::from(addr) as u128,
+        IpAddr::V6(addr) => u128::from(addr),
+    };
+    let host_bits = net.max_prefix_len() - net.prefix_len();
+    Some((start, start.checked_add(host_mask(host_bits))?))
+}
+
+/// A value with the low `bits` bits set
+fn host_mask(bits: u8) -> u128 {
+    match bits {
+        0 => 0,
+        128.. => u128::MAX,
+        _ => (1u128 << bits) - 1,
+    }
+}
+
+fn subnet_at(parent: &IpNet, start: u128, prefix_len: u8) -> Option<IpNet> {
+    let addr = match parent {
+        IpNet::V4(_) => IpAddr::V4(Ipv4Addr::from(u32::try_from(start).ok()?)),
+        IpNet::V6(_) => IpAddr::V6(Ipv6Addr::from(start)),
+    };
+    IpNet::new(addr, prefix_len).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn net(cidr: &str) -> IpNet {
+        parse_cidr(cidr).unwrap()
+    }
+
+    #[test]
+    fn test_parse_cidr() {
+        assert_eq!(net("10.0.0.0/16").to_string(), "10.0.0.0/16");
+        assert_eq!(net(" 2001:db8::/48 ").to_string(), "2001:db8::/48");
+        assert!(parse_cidr("10.0.0.0").is_err());
+        assert!(parse_cidr("10.0.0.0/33").is_err());
+        assert!(parse_cidr("not-a-cidr").is_err());
+        assert!(parse_cidr("10.0.0.1/24").unwrap_err().contains("10.0.0.0/24"));
+    }
+
+    #[test]
+    fn test_overlap_edge_cases() {
+        // Identical
+        assert!(overlaps(&net("10.0.0.0/24"), &net("10.0.0.0/24")));
+        // Containing and contained
+        assert!(overlaps(&net("10.0.0.0/16"), &net("10.0.5.0/24")));
+        assert!(overlaps(&net("10.0.5.0/24"), &net("10.0.0.0/16")));
+        // Adjacent
+        assert!(!overlaps(&net("10.0.0.0/24"), &net("10.0.1.0/24")));
+        assert!(!overlaps(&net("10.0.1.0/24"), &net("10.0.0.0/24")));
+        // Different families
+        assert!(!overlaps(&net("0.0.0.0/0"), &net("::/0")));
+    }
+
+    #[test]
+    fn test_ipv6_overlap() {
+        assert!(overlaps(&net("2001:db8::/32"), &net("2001:db8:1::/48")));
+        assert!(overlaps(&net("::/0"), &net("2001:db8::/128")));
+        assert!(!overlaps(&net("2001:db8::/48"), &net("2001:db8:1::/48")));
+    }
+
+    #[test]
+    fn test_next_free_subnet() {
+        let parent = net("10.0.0.0/22");
+        assert_eq!(next_free_subnet(&parent, 24, &[]), Some(net("10.0.0.0/24")));
+
+        let allocated = [net("10.0.0.0/24"), net("10.0.2.0/23")];
+        assert_eq!(next_free_subnet(&parent, 24, &allocated), Some(net("10.0.1.0/24")));
+        // A /23 must skip past the first allocation's aligned block
+        assert_eq!(next_free_subnet(&parent, 23, &[net("10.0.0.0/24")]), Some(net("10.0.2.0/23")));
+    }
+
+    #[test]
+    fn test_next_free_subnet_exhaustion() {
+        let parent = net("10.0.0.0/23");
+        let allocated = [net("10.0.0.0/24"), net("10.0.1.0/24")];
+        assert_eq!(next_free_subnet(&parent, 24, &allocated), None);
+        assert_eq!(next_free_subnet(&parent, 25, &[parent]), None);
+        // Larger than the parent
+        assert_eq!(next_free_subnet(&parent, 22, &[]), None);
+    }
+
+    #[test]
+    fn test_next_free_subnet_ipv6() {
+        let parent = net("2001:db8::/48");
+        let allocated = [net("2001:db8::/64")];
+        assert_eq!(next_free_subnet(&parent, 64, &allocated), Some(net("2001:db8:0:1::/64")));
+
+        // Skipping a large allocation doesn't walk every small subnet in it
+        let allocated = [net("2001:db8::/49")];
+        assert_eq!(next_free_subnet(&parent, 120, &allocated), Some(net("2001:db8:0:8000::/120")));
+
+        let whole = net("::/0");
+        assert_eq!(next_free_subnet(&whole, 0, &[]), Some(whole));
+        assert_eq!(next_free_subnet(&whole, 0, &[whole]), None);
+        assert_eq!(next_free_subnet(&whole, 1, &[net("::/1")]), Some(net("8000::/1")));
+    }
+}
@@ -1,6 +1,6 @@
 use crate::r#virtual::models::VirtualResourceType;
 use std::collections::HashMap;
-use std::sync::RwLock;
+use parking_lot::RwLock;
 
 /// Mapping between virtual and physical resources
 #[derive(Debug, Clone)]
@@ -55,6 +55,10 @@ impl MappingManager {
     }
 
     /// Create a mapping between virtual and physical resources
+    ///
+    /// Fails with [`MappingError::InvalidMapping`] when the mapping already exists, when either
+    /// resource already has mappings of another type, or when `mapping_type`'s cardinality would
+    /// be broken (e.g. a second mapping for a OneToOne virtual resource).
     pub fn create_mapping(
         &self,
         virtual_id: String,
@@ -63,7 +67,18 @@ impl MappingManager {
         physical_type: VirtualResourceType,
         tenant_id: String,
         mapping_type: MappingType,
-    ) -> ResourceMapping {
+    ) -> Result<ResourceMapping, MappingError> {
+        // Validate under the write locks so concurrent creates can't both pass
+        let mut vtp = self.virtual_to_physical.write();
+        let mut ptv = self.physical_to_virtual.write();
+        Self::validate_mapping(
+            &virtual_id,
+            physical_id,
+            mapping_type,
+            vtp.get(&virtual_id).map(Vec::as_slice).unwrap_or_default(),
+            ptv.get(&physical_id).map(Vec::as_slice).unwrap_or_default(),
+        )?;
+
         let mapping = ResourceMapping {
             virtual_id: virtual_id.clone(),
             virtual_type,
@@ -76,59 +91,108 @@ impl MappingManager {
         };
 
         // Add to virtual -> physical mapping
-        let mut vtp = self.virtual_to_physical.write().unwrap();
         vtp.entry(virtual_id.clone())
             .or_insert_with(Vec::new)
             .push(mapping.clone());
 
         // Add to physical -> virtual mapping
-        let mut ptv = self.physical_to_virtual.write().unwrap();
         ptv.entry(physical_id)
             .or_insert_with(Vec::new)
             .push(mapping.clone());
 
         // Add to tenant mappings
-        let mut tm = self.tenant_mappings.write().unwrap();
+        let mut tm = self.tenant_mappings.write();
         tm.entry(tenant_id)
             .or_insert_with(Vec::new)
             .push(mapping.clone());
 
-        mapping
+        Ok(mapping)
+    }
+
+    /// Check a new mapping against the existing mappings of its virtual and physical resource
+    fn validate_mapping(
+        virtual_id: &str,
+        physical_id: i32,
+        mapping_type: MappingType,
+        by_virtual: &[ResourceMapping],
+        by_physical: &[ResourceMapping],
+    ) -> Result<(), MappingError> {
+        if by_virtual.iter().any(|m| m.physical_id == physical_id) {
+            return Err(MappingError::InvalidMapping(format!(
+                "{} is already mapped to {}",
+                virtual_id, physical_id
+            )));
+        }
+        if let Some(existing) = by_virtual.iter().find(|m| m.mapping_type != mapping_type) {
+            return Err(MappingError::InvalidMapping(format!(
+                "{} already has {:?} mappings, not {:?}",
+                virtual_id, existing.mapping_type, mapping_type
+            )));
+        }
+        if let Some(existing) = by_physical.iter().find(|m| m.mapping_type != mapping_type) {
+            return Err(MappingError::InvalidMapping(format!(
+                "Physical resource {} already has {:?} mappings, not {:?}",
+                physical_id, existing.mapping_type, mapping_type
+            )));
+        }
+
+        let (one_physical, one_virtual) = match mapping_type {
+            MappingType::OneToOne => (true, true),
+            MappingType::OneToMany => (false, true),
+            MappingType::ManyToOne => (true, false),
+            MappingType::ManyToMany => (false, false),
+        };
+        if let Some(existing) = by_virtual.first().filter(|_| one_physical) {
+            return Err(MappingError::InvalidMapping(format!(
+                "{} is {:?} and already mapped to {}",
+                virtual_id, mapping_type, existing.physical_id
+            )));
+        }
+        if let Some(existing) = by_physical.first().filter(|_| one_virtual) {
+            return Err(MappingError::InvalidMapping(format!(
+                "Physical resource {} is {:?} and already mapped to {}",
+                physical_id, mapping_type, existing.virtual_id
+            )));
+        }
+        Ok(())
     }
 
     /// Get all physical resources mapped to a virtual resource
     pub fn get_physical_resources(&self, virtual_id: &str) -> Vec<ResourceMapping> {
-        let vtp = self.virtual_to_physical.read().unwrap();
+        let vtp = self.virtual_to_physical.read();
         vtp.get(virtual_id).cloned().unwrap_or_default()
     }
 
     /// Get all virtual resources mapped to a physical resource
     pub fn get_virtual_resources(&self, physical_id: i32) -> Vec<ResourceMapping> {
-        let ptv = self.physical_to_virtual.read().unwrap();
+        let ptv = self.physical_to_virtual.read();
         ptv.get(&physical_id).cloned().unwrap_or_default()
     }
 
     /// Get all mappings for a tenant
     pub fn get_tenant_mappings(&self, tenant_id: &str) -> Vec<ResourceMapping> {
-        let tm = self.tenant_mappings.read().unwrap();
+        let tm = self.tenant_mappings.read();
         tm.get(tenant_id).cloned().unwrap_or_default()
     }
 
-    /// Remove a mapping
+    /// Remove a mapping from all indexes
     pub fn remove_mapping(
         &self,
         virtual_id: &str,
         physical_id: i32,
-    ) -> Result<(), MappingError> {
-        let mut vtp = self.virtual_to_physical.write().unwrap();
-        if let Some(mappings) = vtp.get_mut(virtual_id) {
-            mappings.retain(|m| m.physical_id != physical_id);
-            if mappings.is_empty() {
-                vtp.remove(virtual_id);
-            }
+    ) -> Result<ResourceMapping, MappingError> {
+        let mut vtp = self.virtual_to_physical.write();
+        let mappings = vtp.get_mut(virtual_id).ok_or(MappingError::MappingNotFound)?;
+        let position = mappings
+            .iter()
+            .position(|m| m.physical_id == physical_id)
+            .ok_or(MappingError::MappingNotFound)?;
+        let removed = mappings.remove(position);
+        if mappings.is_empty() {
+            vtp.remove(virtual_id);
         }
 
-        let mut ptv = self.physical_to_virtual.write().unwrap();
+        let mut ptv = self.physical_to_virtual.write();
         if let Some(mappings) = ptv.get_mut(&physical_id) {
             mappings.retain(|m| m.virtual_id != virtual_id);
             if mappings.is_empty() {
@@ -136,24 +200,87 @@ impl MappingManager {
             }
         }
 
-        Ok(())
+        let mut tm = self.tenant_mappings.write();
+        Self::remove_from_tenant_index(&mut tm, &removed);
+
+        Ok(removed)
+    }
+
+    /// Remove every mapping of a virtual resource from all indexes, returning the removed mappings
+    pub fn remove_all_for_virtual(&self, virtual_id: &str) -> Vec<ResourceMapping> {
+        let removed = self.virtual_to_physical.write().remove(virtual_id).unwrap_or_default();
+        if removed.is_empty() {
+            return removed;
+        }
+
+        let mut ptv = self.physical_to_virtual.write();
+        for mapping in &removed {
+            if let Some(mappings) = ptv.get_mut(&mapping.physical_id) {
+                mappings.retain(|m| m.virtual_id != virtual_id);
+                if mappings.is_empty() {
+                    ptv.remove(&mapping.physical_id);
+                }
+            }
+        }
+
+        let mut tm = self.tenant_mappings.write();
+        for mapping in &removed {
+            Self::remove_from_tenant_index(&mut tm, mapping);
+        }
+
+        removed
+    }
+
+    /// Remove every mapping to a physical resource from all indexes, returning the removed mappings
+    pub fn remove_all_for_physical(&self, physical_id: i32) -> Vec<ResourceMapping> {
+        let mut vtp = self.virtual_to_physical.write();
+        let removed = self.physical_to_virtual.write().remove(&physical_id).unwrap_or_default();
+        if removed.is_empty() {
+            return removed;
+        }
+
+        for mapping in &removed {
+            if let Some(mappings) = vtp.get_mut(&mapping.virtual_id) {
+                mappings.retain(|m| m.physical_id != physical_id);
+                if mappings.is_empty() {
+                    vtp.remove(&mapping.virtual_id);
+                }
+            }
+        }
+
+        let mut tm = self.tenant_mappings.write();
+        for mapping in &removed {
+            Self::remove_from_tenant_index(&mut tm, mapping);
+        }
+
+        removed
+    }
+
+    /// Drop `mapping` from the tenant index, and the tenant key once it has no mappings left
+    fn remove_from_tenant_index(tm: &mut HashMap<String, Vec<ResourceMapping>>, mapping: &ResourceMapping) {
+        if let Some(mappings) = tm.get_mut(&mapping.tenant_id) {
+            mappings.retain(|m| !(m.virtual_id == mapping.virtual_id && m.physical_id == mapping.physical_id));
+            if mappings.is_empty() {
+                tm.remove(&mapping.tenant_id);
+            }
+        }
     }
 
     /// Check if a virtual resource has any physical mappings
     pub fn has_physical_mapping(&self, virtual_id: &str) -> bool {
-        let vtp = self.virtual_to_physical.read().unwrap();
+        let vtp = self.virtual_to_physical.read();
         vtp.contains_key(virtual_id) && !vtp[virtual_id].is_empty()
     }
 
     /// Check if a physical resource has any virtual mappings
     pub fn has_virtual_mapping(&self, physical_id: i32) -> bool {
-        let ptv = self.physical_to_virtual.read().unwrap();
+        let ptv = self.physical_to_virtual.read();
         ptv.contains_key(&physical_id) && !ptv[&physical_id].is_empty()
     }
 
     /// Get mapping count for a virtual resource
     pub fn get_mapping_count(&self, virtual_id: &str) -> usize {
-        let vtp = self.virtual_to_physical.read().unwrap();
+        let vtp = self.virtual_to_physical.read();
         vtp.get(virtual_id).map(|v| v.len()).unwrap_or(0)
     }
 }
@@ -162,14 +289,15 @@ impl MappingManager {
 #[derive(Debug, Clone, PartialEq)]
 pub enum MappingError {
     MappingNotFound,
-    InvalidMapping,
+    /// The mapping would duplicate an existing one or break the declared cardinality
+    InvalidMapping(String),
 }
 
 impl std::fmt::Display for MappingError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             MappingError::MappingNotFound => write!(f, "Mapping not found"),
-            MappingError::InvalidMapping => write!(f, "Invalid mapping"),
+            MappingError::InvalidMapping(reason) => write!(f, "Invalid mapping: {}", reason),
         }
     }
 }
@@ -188,7 +316,7 @@ mod tests {
             VirtualResourceType::Site,
             "tenant-1".to_string(),
             MappingType::OneToOne,
-        );
+        ).unwrap();
 
         assert_eq!(mapping.virtual_id, "vs-1");
         assert_eq!(mapping.physical_id, 123);
@@ -205,7 +333,7 @@ mod tests {
             VirtualResourceType::Site,
             "tenant-1".to_string(),
             MappingType::OneToOne,
-        );
+        ).unwrap();
 
         let physical = manager.get_physical_resources("vs-1");
         assert_eq!(physical.len(), 1);
@@ -222,7 +350,7 @@ mod tests {
             VirtualResourceType::Site,
             "tenant-1".to_string(),
             MappingType::OneToOne,
-        );
+        ).unwrap();
 
         let virtual_resources = manager.get_virtual_resources(123);
         assert_eq!(virtual_resources.len(), 1);
@@ -239,7 +367,7 @@ mod tests {
             VirtualResourceType::Site,
             "tenant-1".to_string(),
             MappingType::OneToMany,
-        );
+        ).unwrap();
         manager.create_mapping(
             "vs-1".to_string(),
             VirtualResourceType::Site,
@@ -247,7 +375,7 @@ mod tests {
             VirtualResourceType::Site,
             "tenant-1".to_string(),
             MappingType::OneToMany,
-        );
+        ).unwrap();
 
         let physical = manager.get_physical_resources("vs-1");
         assert_eq!(physical.len(), 2);
@@ -265,7 +393,7 @@ mod tests {
             VirtualResourceType::Site,
             "tenant-1".to_string(),
             MappingType::ManyToOne,
-        );
+        ).unwrap();
         manager.create_mapping(
             "vs-2".to_string(),
             VirtualResourceType::Site,
@@ -273,7 +401,7 @@ mod tests {
             VirtualResourceType::Site,
             "tenant-1".to_string(),
             MappingType::ManyToOne,
-        );
+        ).unwrap();
 
         let virtual_resources = manager.get_virtual_resources(123);
         assert_eq!(virtual_resources.len(), 2);
@@ -291,7 +419,7 @@ mod tests {
             VirtualResourceType::Site,
             "tenant-1".to_string(),
             MappingType::OneToOne,
-        );
+        ).unwrap();
         manager.create_mapping(
             "vs-2".to_string(),
             VirtualResourceType::Site,
@@ -299,7 +427,7 @@ mod tests {
             VirtualResourceType::Site,
             "tenant-1".to_string(),
             MappingType::OneToOne,
-        );
+        ).unwrap();
         manager.create_mapping(
             "vs-3".to_string(),
             VirtualResourceType::Site,
@@ -307,7 +435,7 @@ mod tests {
             VirtualResourceType::Site,
             "tenant-2".to_string(),
             MappingType::OneToOne,
-        );
+        ).unwrap();
 
         let tenant1_mappings = manager.get_tenant_mappings("tenant-1");
         assert_eq!(tenant1_mappings.len(), 2);
@@ -326,7 +454,7 @@ mod tests {
             VirtualResourceType::Site,
             "tenant-1".to_string(),
             MappingType::OneToOne,
-        );
+        ).unwrap();
 
         assert!(manager.has_physical_mapping("vs-1"));
         assert!(manager.has_virtual_mapping(123));
@@ -349,7 +477,7 @@ mod tests {
             VirtualResourceType::Site,
             "tenant-1".to_string(),
             MappingType::OneToOne,
-        );
+        ).unwrap();
 
         assert!(manager.has_physical_mapping("vs-1"));
     }
@@ -366,7 +494,7 @@ mod tests {
             VirtualResourceType::Site,
             "tenant-1".to_string(),
             MappingType::OneToMany,
-        );
+        ).unwrap();
         manager.create_mapping(
             "vs-1".to_string(),
             VirtualResourceType::Site,
@@ -374,7 +502,7 @@ mod tests {
             VirtualResourceType::Site,
             "tenant-1".to_string(),
             MappingType::OneToMany,
-        );
+        ).unwrap();
 
         assert_eq!(manager.get_mapping_count("vs-1"), 2);
     }
@@ -409,8 +537,79 @@ mod tests {
     #[test]
     fn test_remove_mapping_nonexistent() {
         let manager = MappingManager::new();
-        // Should not panic, just return Ok
-        assert!(manager.remove_mapping("nonexistent", 999).is_ok());
+        assert_eq!(
+            manager.remove_mapping("nonexistent", 999).unwrap_err(),
+            MappingError::MappingNotFound
+        );
+    }
+
+    #[test]
+    fn test_remove_mapping_unknown_physical_id() {
+        let manager = MappingManager::new();
+        manager.create_mapping(
+            "vs-1".to_string(),
+            VirtualResourceType::Site,
+            123,
+            VirtualResourceType::Site,
+            "tenant-1".to_string(),
+            MappingType::OneToOne,
+        ).unwrap();
+
+        assert_eq!(manager.remove_mapping("vs-1", 456).unwrap_err(), MappingError::MappingNotFound);
+        assert_eq!(manager.get_mapping_count("vs-1"), 1);
+    }
+
+    #[test]
+    fn test_remove_mapping_updates_tenant_mappings() {
+        let manager = MappingManager::new();
+        for (virtual_id, physical_id) in [("vs-1", 123), ("vs-2", 456)] {
+            manager.create_mapping(
+                virtual_id.to_string(),
+                VirtualResourceType::Site,
+                physical_id,
+                VirtualResourceType::Site,
+                "tenant-1".to_string(),
+                MappingType::OneToOne,
+            ).unwrap();
+        }
+
+        let removed = manager.remove_mapping("vs-1", 123).unwrap();
+        assert_eq!(removed.virtual_id, "vs-1");
+        let remaining = manager.get_tenant_mappings("tenant-1");
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].virtual_id, "vs-2");
+
+        manager.remove_mapping("vs-2", 456).unwrap();
+        assert!(manager.get_tenant_mappings("tenant-1").is_empty());
+        assert!(!manager.tenant_mappings.read().contains_key("tenant-1"));
+    }
+
+    #[test]
+    fn test_remove_all_for_physical() {
+        let manager = MappingManager::new();
+        for (virtual_id, physical_id, tenant_id) in
+            [("vs-1", 123, "tenant-1"), ("vs-2", 123, "tenant-2"), ("vs-2", 456, "tenant-2")]
+        {
+            manager.create_mapping(
+                virtual_id.to_string(),
+                VirtualResourceType::Site,
+                physical_id,
+                VirtualResourceType::Site,
+                tenant_id.to_string(),
+                MappingType::ManyToMany,
+            ).unwrap();
+        }
+
+        let removed = manager.remove_all_for_physical(123);
+        assert_eq!(removed.len(), 2);
+        assert!(!manager.has_virtual_mapping(123));
+        assert!(!manager.has_physical_mapping("vs-1"));
+        assert_eq!(manager.get_mapping_count("vs-2"), 1);
+        assert!(manager.get_tenant_mappings("tenant-1").is_empty());
+        let tenant2 = manager.get_tenant_mappings("tenant-2");
+        assert_eq!(tenant2.len(), 1);
+        assert_eq!(tenant2[0].physical_id, 456);
+        assert!(manager.remove_all_for_physical(123).is_empty());
     }
 
     #[test]
@@ -423,7 +622,7 @@ mod tests {
             VirtualResourceType::Site,
             "tenant-1".to_string(),
             MappingType::OneToMany,
-        );
+        ).unwrap();
         manager.create_mapping(
             "vs-1".to_string(),
             VirtualResourceType::Site,
@@ -431,7 +630,7 @@ mod tests {
             VirtualResourceType::Site,
             "tenant-1".to_string(),
             MappingType::OneToMany,
-        );
+        ).unwrap();
 
         assert_eq!(manager.get_mapping_count("vs-1"), 2);
         manager.remove_mapping("vs-1", 123).unwrap();
@@ -449,7 +648,7 @@ mod tests {
             VirtualResourceType::Site,
             "tenant-1".to_string(),
             MappingType::ManyToMany,
-        );
+        ).unwrap();
         manager.create_mapping(
             "vs-2".to_string(),
             VirtualResourceType::Site,
@@ -457,7 +656,7 @@ mod tests {
             VirtualResourceType::Site,
             "tenant-1".to_string(),
             MappingType::ManyToMany,
-        );
+        ).unwrap();
         manager.create_mapping(
             "vs-1".to_string(),
             VirtualResourceType::Site,
@@ -465,7 +664,7 @@ mod tests {
             VirtualResourceType::Site,
             "tenant-1".to_string(),
             MappingType::ManyToMany,
-        );
+        ).unwrap();
 
         let physical = manager.get_physical_resources("vs-1");
         assert_eq!(physical.len(), 2);
@@ -479,8 +678,8 @@ mod tests {
         let error1 = MappingError::MappingNotFound;
         assert_eq!(error1.to_string(), "Mapping not found");
 
-        let error2 = MappingError::InvalidMapping;
-        assert_eq!(error2.to_string(), "Invalid mapping");
+        let error2 = MappingError::InvalidMapping("vs-1 is already mapped to 123".to_string());
+        assert_eq!(error2.to_string(), "Invalid mapping: vs-1 is already mapped to 123");
     }
 
     #[test]
@@ -515,6 +714,168 @@ mod tests {
         assert_eq!(manager.get_mapping_count("vs-1"), 0);
     }
 
+    #[test]
+    fn test_remove_all_for_virtual() {
+        let manager = MappingManager::new();
+        for physical_id in [123, 456] {
+            manager.create_mapping(
+                "vs-1".to_string(),
+                VirtualResourceType::Site,
+                physical_id,
+                VirtualResourceType::Site,
+                "tenant-1".to_string(),
+                MappingType::ManyToMany,
+            ).unwrap();
+        }
+        manager.create_mapping(
+            "vs-2".to_string(),
+            VirtualResourceType::Site,
+            123,
+            VirtualResourceType::Site,
+            "tenant-1".to_string(),
+            MappingType::ManyToMany,
+        ).unwrap();
+
+        let removed = manager.remove_all_for_virtual("vs-1");
+        assert_eq!(removed.len(), 2);
+        assert!(!manager.has_physical_mapping("vs-1"));
+        assert!(!manager.has_virtual_mapping(456));
+        // vs-2 still maps to 123
+        let remaining = manager.get_virtual_resources(123);
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].virtual_id, "vs-2");
+        assert!(manager.remove_all_for_virtual("vs-1").is_empty());
+    }
+
+    #[test]
+    fn test_remove_all_for_virtual_cleans_tenant_index() {
+        let manager = MappingManager::new();
+        manager.create_mapping(
+            "vs-1".to_string(),
+            VirtualResourceType::Site,
+            123,
+            VirtualResourceType::Site,
+            "tenant-1".to_string(),
+            MappingType::OneToOne,
+        ).unwrap();
+        manager.create_mapping(
+            "vs-2".to_string(),
+            VirtualResourceType::Site,
+            456,
+            VirtualResourceType::Site,
+            "tenant-2".to_string(),
+            MappingType::OneToOne,
+        ).unwrap();
+
+        manager.remove_all_for_virtual("vs-1");
+        assert!(manager.get_tenant_mappings("tenant-1").is_empty());
+        assert!(!manager.tenant_mappings.read().contains_key("tenant-1"));
+        assert_eq!(manager.get_tenant_mappings("tenant-2").len(), 1);
+    }
+
+    fn try_map(
+        manager: &MappingManager,
+        virtual_id: &str,
+        physical_id: i32,
+        mapping_type: MappingType,
+    ) -> Result<ResourceMapping, MappingError> {
+        manager.create_mapping(
+            virtual_id.to_string(),
+            VirtualResourceType::Site,
+            physical_id,
+            VirtualResourceType::Site,
+            "tenant-1".to_string(),
+            mapping_type,
+        )
+    }
+
+    fn assert_invalid(result: Result<ResourceMapping, MappingError>, reason: &str) {
+        match result {
+            Err(MappingError::InvalidMapping(message)) => {
+                assert!(message.contains(reason), "unexpected reason: {}", message)
+            }
+            other => panic!("Expected InvalidMapping, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_duplicate_mapping_rejected() {
+        let manager = MappingManager::new();
+        try_map(&manager, "vs-1", 123, MappingType::ManyToMany).unwrap();
+
+        assert_invalid(try_map(&manager, "vs-1", 123, MappingType::ManyToMany), "already mapped to 123");
+        assert_eq!(manager.get_tenant_mappings("tenant-1").len(), 1);
+    }
+
+    #[test]
+    fn test_one_to_one_rejects_second_physical() {
+        let manager = MappingManager::new();
+        try_map(&manager, "vs-1", 123, MappingType::OneToOne).unwrap();
+
+        assert_invalid(try_map(&manager, "vs-1", 456, MappingType::OneToOne), "vs-1 is OneToOne");
+        assert_eq!(manager.get_mapping_count("vs-1"), 1);
+    }
+
+    #[test]
+    fn test_one_to_one_rejects_second_virtual() {
+        let manager = MappingManager::new();
+        try_map(&manager, "vs-1", 123, MappingType::OneToOne).unwrap();
+
+        assert_invalid(
+            try_map(&manager, "vs-2", 123, MappingType::OneToOne),
+            "Physical resource 123 is OneToOne",
+        );
+        assert!(!manager.has_physical_mapping("vs-2"));
+    }
+
+    #[test]
+    fn test_one_to_many_rejects_shared_physical() {
+        let manager = MappingManager::new();
+        try_map(&manager, "vs-1", 123, MappingType::OneToMany).unwrap();
+        try_map(&manager, "vs-1", 456, MappingType::OneToMany).unwrap();
+
+        assert_invalid(
+            try_map(&manager, "vs-2", 123, MappingType::OneToMany),
+            "Physical resource 123 is OneToMany and already mapped to vs-1",
+        );
+    }
+
+    #[test]
+    fn test_many_to_one_rejects_second_physical() {
+        let manager = MappingManager::new();
+        try_map(&manager, "vs-1", 123, MappingType::ManyToOne).unwrap();
+        try_map(&manager, "vs-2", 123, MappingType::ManyToOne).unwrap();
+
+        assert_invalid(try_map(&manager, "vs-1", 456, MappingType::ManyToOne), "vs-1 is ManyToOne");
+    }
+
+    #[test]
+    fn test_mismatched_mapping_type_rejected() {
+        let manager = MappingManager::new();
+        try_map(&manager, "vs-1", 123, MappingType::OneToMany).unwrap();
+        try_map(&manager, "vs-2", 456, MappingType::ManyToMany).unwrap();
+
+        // vs-1 was declared OneToMany
+        assert_invalid(try_map(&manager, "vs-1", 789, MappingType::ManyToMany), "already has OneToMany mappings");
+        // 456 is in a ManyToMany mapping
+        assert_invalid(
+            try_map(&manager, "vs-3", 456, MappingType::ManyToOne),
+            "Physical resource 456 already has ManyToMany mappings",
+        );
+    }
+
+    #[test]
+    fn test_many_to_many_stacking_allowed() {
+        let manager = MappingManager::new();
+        for (virtual_id, physical_id) in [("vs-1", 123), ("vs-1", 456), ("vs-2", 123), ("vs-2", 456), ("vs-3", 123)] {
+            try_map(&manager, virtual_id, physical_id, MappingType::ManyToMany).unwrap();
+        }
+
+        assert_eq!(manager.get_virtual_resources(123).len(), 3);
+        assert_eq!(manager.get_physical_resources("vs-2").len(), 2);
+        assert_eq!(manager.get_tenant_mappings("tenant-1").len(), 5);
+    }
+
     #[test]
     fn test_has_physical_mapping_empty_list() {
         let manager = MappingManager::new();
@@ -526,7 +887,7 @@ mod tests {
             VirtualResourceType::Site,
             "tenant-1".to_string(),
             MappingType::OneToOne,
-        );
+        ).unwrap();
         manager.remove_mapping("vs-1", 123).unwrap();
         assert!(!manager.has_physical_mapping("vs-1"));
     }
@@ -1,8 +1,11 @@
+pub mod cidr;
 pub mod mapping;
 pub mod models;
 pub mod service;
+pub mod status;
 
 pub use mapping::*;
 pub use models::*;
 pub use service::*;
+pub use status::*;
 
@@ -9,6 +9,8 @@ pub enum VirtualResourceType {
     Device,
     Network,
     Service,
+    #[serde(rename = "virtual_machine")]
+    VirtualMachine,
 }
 
 /// Virtual site - a logical grouping that may map to multiple NetBox sites
@@ -42,7 +44,7 @@ impl VirtualSite {
     }
 }
 
-/// Virtual device - a logical device that may map to multiple NetBox devices
+/// Virtual device - a logical device that may map to multiple NetBox devices or virtual machines
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VirtualDevice {
     pub id: String,
@@ -50,6 +52,9 @@ pub struct VirtualDevice {
     pub description: Option<String>,
     pub tenant_id: String,
     pub virtual_type: VirtualResourceType,
+    /// What it maps to: `Device` or `VirtualMachine`
+    #[serde(default = "default_device_physical_type")]
+    pub physical_type: VirtualResourceType,
     pub metadata: HashMap<String, String>,
     pub tags: Vec<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
@@ -65,12 +70,23 @@ impl VirtualDevice {
             description: None,
             tenant_id,
             virtual_type: VirtualResourceType::Device,
+            physical_type: VirtualResourceType::Device,
             metadata: HashMap::new(),
             tags: Vec::new(),
             created_at: now,
             updated_at: now,
         }
     }
+
+    /// Map to NetBox virtual machines instead of devices
+    pub fn with_virtual_machines(mut self) -> Self {
+        self.physical_type = VirtualResourceType::VirtualMachine;
+        self
+    }
+}
+
+fn default_device_physical_type() -> VirtualResourceType {
+    VirtualResourceType::Device
 }
 
 /// Virtual network - a logical network abstraction
@@ -106,6 +122,90 @@ impl VirtualNetwork {
     }
 }
 
+/// Changes to a virtual resource; `None` fields are left as they are
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VirtualResourceUpdate {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// Replaces the metadata map
+    pub metadata: Option<HashMap<String, String>>,
+    /// Replaces the tag list
+    pub tags: Option<Vec<String>>,
+    /// Network CIDR; ignored for sites and devices
+    pub cidr: Option<String>,
+}
+
+/// Apply an update to the fields shared by every virtual resource type
+macro_rules! apply_update {
+    ($resource:expr, $update:expr) => {{
+        let resource = $resource;
+        let update = $update;
+        if let Some(name) = update.name {
+            resource.name = name;
+        }
+        if let Some(description) = update.description {
+            resource.description = Some(description);
+        }
+        if let Some(metadata) = update.metadata {
+            resource.metadata = metadata;
+        }
+        if let Some(tags) = update.tags {
+            resource.tags = tags;
+        }
+        resource.updated_at = chrono::Utc::now();
+    }};
+}
+
+impl VirtualSite {
+    /// Apply `update`, bumping `updated_at`
+    pub fn apply(&mut self, update: VirtualResourceUpdate) {
+        apply_update!(self, update);
+    }
+}
+
+impl VirtualDevice {
+    /// Apply `update`, bumping `updated_at`
+    pub fn apply(&mut self, update: VirtualResourceUpdate) {
+        apply_update!(self, update);
+    }
+}
+
+impl VirtualNetwork {
+    /// Apply `update`, bumping `updated_at`
+    pub fn apply(&mut self, update: VirtualResourceUpdate) {
+        apply_update!(self, update);
+    }
+}
+
+/// A virtual resource of any type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum VirtualResource {
+    Site(VirtualSite),
+    Device(VirtualDevice),
+    Network(VirtualNetwork),
+}
+
+impl VirtualResource {
+    /// The wrapped resource, through the common trait
+    pub fn as_resource(&self) -> &dyn Resource {
+        match self {
+            VirtualResource::Site(site) => site,
+            VirtualResource::Device(device) => device,
+            VirtualResource::Network(network) => network,
+        }
+    }
+
+    /// NetBox object type this resource maps to: sites for virtual sites and networks, devices or
+    /// virtual machines for devices
+    pub fn physical_type(&self) -> VirtualResourceType {
+        match self {
+            VirtualResource::Device(device) => device.physical_type,
+            VirtualResource::Site(_) | VirtualResource::Network(_) => VirtualResourceType::Site,
+        }
+    }
+}
+
 /// Abstraction trait for resources (both virtual and physical)
 pub trait Resource {
     fn id(&self) -> &str;
@@ -295,6 +395,23 @@ mod tests {
         assert!(device.is_virtual());
     }
 
+    #[test]
+    fn test_virtual_device_physical_type() {
+        let device = VirtualDevice::new("vd-1".to_string(), "Device".to_string(), "tenant-1".to_string());
+        assert_eq!(VirtualResource::Device(device.clone()).physical_type(), VirtualResourceType::Device);
+
+        let vm_backed = device.with_virtual_machines();
+        assert_eq!(vm_backed.resource_type(), VirtualResourceType::Device);
+        assert_eq!(VirtualResource::Device(vm_backed.clone()).physical_type(), VirtualResourceType::VirtualMachine);
+
+        // Devices saved before VMs were supported map to NetBox devices
+        let mut saved = serde_json::to_value(&vm_backed).unwrap();
+        assert_eq!(saved["physical_type"], "virtual_machine");
+        saved.as_object_mut().unwrap().remove("physical_type");
+        let restored: VirtualDevice = serde_json::from_value(saved).unwrap();
+        assert_eq!(restored.physical_type, VirtualResourceType::Device);
+    }
+
     #[test]
     fn test_virtual_network_creation() {
         let network = VirtualNetwork::new(
@@ -1,17 +1,123 @@
+use crate::cache::Cache;
 use crate::netbox::models::{NetBoxDevice, NetBoxSite};
-use crate::r#virtual::mapping::{MappingManager, MappingType};
+use crate::netbox::tenant_client::TenantAwareNetBoxClient;
+use crate::r#virtual::mapping::{MappingError, MappingManager, MappingType, ResourceMapping};
 use crate::r#virtual::models::{
-    NetBoxDeviceAdapter, NetBoxSiteAdapter, Resource, VirtualDevice, VirtualNetwork, VirtualSite,
-    VirtualResourceType,
+    NetBoxDeviceAdapter, NetBoxSiteAdapter, Resource, VirtualDevice, VirtualNetwork, VirtualResource,
+    VirtualResourceUpdate, VirtualSite, VirtualResourceType,
 };
+use crate::r#virtual::cidr::{next_free_subnet, overlaps, parse_cidr};
+use crate::r#virtual::status::{PhysicalStatus, VirtualResourceStatus};
+use ipnet::IpNet;
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use parking_lot::RwLock;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How long a computed aggregate status is reused
+pub const DEFAULT_STATUS_TTL: Duration = Duration::from_secs(30);
+
+/// Metadata key of a virtual network's allocated child subnets (comma-separated CIDRs)
+pub const ALLOCATED_SUBNETS_KEY: &str = "allocated_subnets";
 
 /// Virtual resource store
 pub struct VirtualResourceStore {
     sites: RwLock<HashMap<String, VirtualSite>>,
     devices: RwLock<HashMap<String, VirtualDevice>>,
     networks: RwLock<HashMap<String, VirtualNetwork>>,
+    // Mappings of stored resources, removed along with them
+    mapping_manager: Arc<MappingManager>,
+    // Refuse networks whose CIDR overlaps another network of the tenant
+    reject_cidr_overlap: bool,
+}
+
+/// Virtual resource errors
+#[derive(Debug, Clone, PartialEq)]
+pub enum VirtualResourceError {
+    /// No virtual resource of the requested type has this ID
+    NotFound(String),
+    /// A network CIDR or subnet request is malformed
+    InvalidCidr(String),
+    /// The CIDR overlaps these networks of the same tenant
+    CidrOverlap { cidr: String, networks: Vec<String> },
+    /// The parent network has no free subnet of the requested size
+    SubnetExhausted { network_id: String, prefix_len: u8 },
+    /// A mapping of the resource was rejected
+    Mapping(MappingError),
+}
+
+impl std::fmt::Display for VirtualResourceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VirtualResourceError::NotFound(id) => write!(f, "Virtual resource {} not found", id),
+            VirtualResourceError::InvalidCidr(reason) => write!(f, "Invalid CIDR: {}", reason),
+            VirtualResourceError::CidrOverlap { cidr, networks } => {
+                write!(f, "{} overlaps virtual networks {}", cidr, networks.join(", "))
+            }
+            VirtualResourceError::SubnetExhausted { network_id, prefix_len } => {
+                write!(f, "Virtual network {} has no free /{} subnet", network_id, prefix_len)
+            }
+            VirtualResourceError::Mapping(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<MappingError> for VirtualResourceError {
+    fn from(e: MappingError) -> Self {
+        VirtualResourceError::Mapping(e)
+    }
+}
+
+/// Subnets allocated from a network, as recorded in its metadata
+fn allocated_subnets(network: &VirtualNetwork) -> Vec<IpNet> {
+    network
+        .metadata
+        .get(ALLOCATED_SUBNETS_KEY)
+        .map(|subnets| subnets.split(',').filter_map(|cidr| parse_cidr(cidr).ok()).collect())
+        .unwrap_or_default()
+}
+
+/// Validate and normalize a CIDR, then check it against the tenant's other networks
+///
+/// A network carved out of another with [`VirtualResourceStore::allocate_subnet`] doesn't
+/// conflict with its parent.
+fn check_network_cidr(
+    networks: &HashMap<String, VirtualNetwork>,
+    network_id: &str,
+    tenant_id: &str,
+    cidr: &str,
+    reject_overlap: bool,
+) -> Result<String, VirtualResourceError> {
+    let net = parse_cidr(cidr).map_err(VirtualResourceError::InvalidCidr)?;
+    if reject_overlap {
+        let mut conflicts: Vec<String> = overlapping_networks(networks, tenant_id, &net)
+            .filter(|other| other.id != network_id)
+            .filter(|other| !allocated_subnets(other).iter().any(|subnet| subnet.contains(&net)))
+            .map(|other| other.id.clone())
+            .collect();
+        if !conflicts.is_empty() {
+            conflicts.sort();
+            return Err(VirtualResourceError::CidrOverlap {
+                cidr: net.to_string(),
+                networks: conflicts,
+            });
+        }
+    }
+    Ok(net.to_string())
+}
+
+fn overlapping_networks<'a>(
+    networks: &'a HashMap<String, VirtualNetwork>,
+    tenant_id: &'a str,
+    net: &'a IpNet,
+) -> impl Iterator<Item = &'a VirtualNetwork> {
+    networks.values().filter(move |n| {
+        n.tenant_id == tenant_id
+            && n.cidr
+                .as_deref()
+                .and_then(|cidr| parse_cidr(cidr).ok())
+                .is_some_and(|other| overlaps(&other, net))
+    })
 }
 
 impl Default for VirtualResourceStore {
@@ -22,27 +128,40 @@ impl Default for VirtualResourceStore {
 
 impl VirtualResourceStore {
     pub fn new() -> Self {
+        Self::with_mapping_manager(Arc::new(MappingManager::new()))
+    }
+
+    /// Create a store whose deletions also clean up `mapping_manager`
+    pub fn with_mapping_manager(mapping_manager: Arc<MappingManager>) -> Self {
         Self {
             sites: RwLock::new(HashMap::new()),
             devices: RwLock::new(HashMap::new()),
             networks: RwLock::new(HashMap::new()),
+            mapping_manager,
+            reject_cidr_overlap: false,
         }
     }
 
+    /// Refuse to save a network whose CIDR overlaps another network of the same tenant
+    pub fn with_cidr_overlap_rejection(mut self, reject: bool) -> Self {
+        self.reject_cidr_overlap = reject;
+        self
+    }
+
     pub fn create_virtual_site(&self, id: String, name: String, tenant_id: String) -> VirtualSite {
         let site = VirtualSite::new(id.clone(), name, tenant_id.clone());
-        let mut sites = self.sites.write().unwrap();
+        let mut sites = self.sites.write();
         sites.insert(id, site.clone());
         site
     }
 
     pub fn get_virtual_site(&self, id: &str) -> Option<VirtualSite> {
-        let sites = self.sites.read().unwrap();
+        let sites = self.sites.read();
         sites.get(id).cloned()
     }
 
     pub fn get_tenant_virtual_sites(&self, tenant_id: &str) -> Vec<VirtualSite> {
-        let sites = self.sites.read().unwrap();
+        let sites = self.sites.read();
         sites
             .values()
             .filter(|s| s.tenant_id == tenant_id)
@@ -52,18 +171,18 @@ impl VirtualResourceStore {
 
     pub fn create_virtual_device(&self, id: String, name: String, tenant_id: String) -> VirtualDevice {
         let device = VirtualDevice::new(id.clone(), name, tenant_id.clone());
-        let mut devices = self.devices.write().unwrap();
+        let mut devices = self.devices.write();
         devices.insert(id, device.clone());
         device
     }
 
     pub fn get_virtual_device(&self, id: &str) -> Option<VirtualDevice> {
-        let devices = self.devices.read().unwrap();
+        let devices = self.devices.read();
         devices.get(id).cloned()
     }
 
     pub fn get_tenant_virtual_devices(&self, tenant_id: &str) -> Vec<VirtualDevice> {
-        let devices = self.devices.read().unwrap();
+        let devices = self.devices.read();
         devices
             .values()
             .filter(|d| d.tenant_id == tenant_id)
@@ -73,67 +192,255 @@ impl VirtualResourceStore {
 
     pub fn create_virtual_network(&self, id: String, name: String, tenant_id: String) -> VirtualNetwork {
         let network = VirtualNetwork::new(id.clone(), name, tenant_id.clone());
-        let mut networks = self.networks.write().unwrap();
+        let mut networks = self.networks.write();
         networks.insert(id, network.clone());
         network
     }
 
     pub fn get_virtual_network(&self, id: &str) -> Option<VirtualNetwork> {
-        let networks = self.networks.read().unwrap();
+        let networks = self.networks.read();
         networks.get(id).cloned()
     }
 
     pub fn get_tenant_virtual_networks(&self, tenant_id: &str) -> Vec<VirtualNetwork> {
-        let networks = self.networks.read().unwrap();
+        let networks = self.networks.read();
         networks
             .values()
             .filter(|n| n.tenant_id == tenant_id)
             .cloned()
             .collect()
     }
+
+    pub fn update_virtual_site(&self, id: &str, update: VirtualResourceUpdate) -> Result<VirtualSite, VirtualResourceError> {
+        let mut sites = self.sites.write();
+        let site = sites
+            .get_mut(id)
+            .ok_or_else(|| VirtualResourceError::NotFound(id.to_string()))?;
+        site.apply(update);
+        Ok(site.clone())
+    }
+
+    pub fn update_virtual_device(
+        &self,
+        id: &str,
+        update: VirtualResourceUpdate,
+    ) -> Result<VirtualDevice, VirtualResourceError> {
+        let mut devices = self.devices.write();
+        let device = devices
+            .get_mut(id)
+            .ok_or_else(|| VirtualResourceError::NotFound(id.to_string()))?;
+        device.apply(update);
+        Ok(device.clone())
+    }
+
+    pub fn update_virtual_network(
+        &self,
+        id: &str,
+        mut update: VirtualResourceUpdate,
+    ) -> Result<VirtualNetwork, VirtualResourceError> {
+        let mut networks = self.networks.write();
+        let tenant_id = networks
+            .get(id)
+            .map(|network| network.tenant_id.clone())
+            .ok_or_else(|| VirtualResourceError::NotFound(id.to_string()))?;
+        let cidr = match update.cidr.take() {
+            Some(cidr) => Some(check_network_cidr(&networks, id, &tenant_id, &cidr, self.reject_cidr_overlap)?),
+            None => None,
+        };
+
+        let network = networks.get_mut(id).expect("network checked above");
+        network.apply(update);
+        if cidr.is_some() {
+            network.cidr = cidr;
+        }
+        Ok(network.clone())
+    }
+
+    /// The tenant's networks whose CIDR overlaps `cidr`
+    pub fn find_overlapping_networks(&self, tenant_id: &str, cidr: &str) -> Result<Vec<VirtualNetwork>, VirtualResourceError> {
+        let net = parse_cidr(cidr).map_err(VirtualResourceError::InvalidCidr)?;
+        let networks = self.networks.read();
+        let mut overlapping: Vec<VirtualNetwork> = overlapping_networks(&networks, tenant_id, &net).cloned().collect();
+        overlapping.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(overlapping)
+    }
+
+    /// Carve the next free `/prefix_len` subnet out of a network, recording it in the network's metadata
+    pub fn allocate_subnet(&self, parent_network_id: &str, prefix_len: u8) -> Result<String, VirtualResourceError> {
+        let mut networks = self.networks.write();
+        let parent = networks
+            .get_mut(parent_network_id)
+            .ok_or_else(|| VirtualResourceError::NotFound(parent_network_id.to_string()))?;
+        let parent_net = parent
+            .cidr
+            .as_deref()
+            .ok_or_else(|| VirtualResourceError::InvalidCidr(format!("Virtual network {} has no CIDR", parent_network_id)))
+            .and_then(|cidr| parse_cidr(cidr).map_err(VirtualResourceError::InvalidCidr))?;
+        if prefix_len < parent_net.prefix_len() || prefix_len > parent_net.max_prefix_len() {
+            return Err(VirtualResourceError::InvalidCidr(format!(
+                "/{} subnets don't fit in {}",
+                prefix_len, parent_net
+            )));
+        }
+
+        let mut allocated = allocated_subnets(parent);
+        let subnet = next_free_subnet(&parent_net, prefix_len, &allocated).ok_or_else(|| {
+            VirtualResourceError::SubnetExhausted {
+                network_id: parent_network_id.to_string(),
+                prefix_len,
+            }
+        })?;
+        allocated.push(subnet);
+        parent.metadata.insert(
+            ALLOCATED_SUBNETS_KEY.to_string(),
+            allocated.iter().map(IpNet::to_string).collect::<Vec<_>>().join(","),
+        );
+        parent.updated_at = chrono::Utc::now();
+        Ok(subnet.to_string())
+    }
+
+    /// Delete a virtual site and all its mappings
+    pub fn delete_virtual_site(&self, id: &str) -> Result<VirtualSite, VirtualResourceError> {
+        let site = self
+            .sites
+            .write()
+            .remove(id)
+            .ok_or_else(|| VirtualResourceError::NotFound(id.to_string()))?;
+        self.mapping_manager.remove_all_for_virtual(id);
+        Ok(site)
+    }
+
+    /// Delete a virtual device and all its mappings
+    pub fn delete_virtual_device(&self, id: &str) -> Result<VirtualDevice, VirtualResourceError> {
+        let device = self
+            .devices
+            .write()
+            .remove(id)
+            .ok_or_else(|| VirtualResourceError::NotFound(id.to_string()))?;
+        self.mapping_manager.remove_all_for_virtual(id);
+        Ok(device)
+    }
+
+    /// Delete a virtual network and all its mappings
+    pub fn delete_virtual_network(&self, id: &str) -> Result<VirtualNetwork, VirtualResourceError> {
+        let network = self
+            .networks
+            .write()
+            .remove(id)
+            .ok_or_else(|| VirtualResourceError::NotFound(id.to_string()))?;
+        self.mapping_manager.remove_all_for_virtual(id);
+        Ok(network)
+    }
+
+    /// Save a resource, replacing any with the same ID
+    ///
+    /// A network's CIDR is validated and normalized first.
+    pub fn insert_virtual_resource(&self, resource: VirtualResource) -> Result<(), VirtualResourceError> {
+        match resource {
+            VirtualResource::Site(site) => {
+                self.sites.write().insert(site.id.clone(), site);
+            }
+            VirtualResource::Device(device) => {
+                self.devices.write().insert(device.id.clone(), device);
+            }
+            VirtualResource::Network(mut network) => {
+                let mut networks = self.networks.write();
+                if let Some(ref cidr) = network.cidr {
+                    network.cidr = Some(check_network_cidr(
+                        &networks,
+                        &network.id,
+                        &network.tenant_id,
+                        cidr,
+                        self.reject_cidr_overlap,
+                    )?);
+                }
+                networks.insert(network.id.clone(), network);
+            }
+        }
+        Ok(())
+    }
+
+    /// Get a resource of any type by ID
+    pub fn get_virtual_resource(&self, id: &str) -> Option<VirtualResource> {
+        self.get_virtual_site(id)
+            .map(VirtualResource::Site)
+            .or_else(|| self.get_virtual_device(id).map(VirtualResource::Device))
+            .or_else(|| self.get_virtual_network(id).map(VirtualResource::Network))
+    }
+
+    /// Delete a resource of any type and all its mappings
+    pub fn delete_virtual_resource(&self, id: &str) -> Result<VirtualResource, VirtualResourceError> {
+        match self.get_virtual_resource(id) {
+            Some(VirtualResource::Site(_)) => self.delete_virtual_site(id).map(VirtualResource::Site),
+            Some(VirtualResource::Device(_)) => self.delete_virtual_device(id).map(VirtualResource::Device),
+            Some(VirtualResource::Network(_)) => self.delete_virtual_network(id).map(VirtualResource::Network),
+            None => Err(VirtualResourceError::NotFound(id.to_string())),
+        }
+    }
 }
 
 /// Virtual resource service - abstraction layer over virtual and physical resources
 pub struct VirtualResourceService {
     store: Arc<VirtualResourceStore>,
     mapping_manager: Arc<MappingManager>,
+    // Keyed by virtual ID and mapped physical IDs, so mapping changes miss the cache
+    status_cache: Cache<(String, Vec<i32>), VirtualResourceStatus>,
 }
 
 impl VirtualResourceService {
     pub fn new() -> Self {
+        let mapping_manager = Arc::new(MappingManager::new());
         Self {
-            store: Arc::new(VirtualResourceStore::new()),
-            mapping_manager: Arc::new(MappingManager::new()),
+            store: Arc::new(VirtualResourceStore::with_mapping_manager(mapping_manager.clone())),
+            mapping_manager,
+            status_cache: Cache::new(DEFAULT_STATUS_TTL),
         }
     }
 
+    /// Refuse virtual networks whose CIDR overlaps another network of the same tenant
+    pub fn with_cidr_overlap_rejection(mut self, reject: bool) -> Self {
+        self.store = Arc::new(
+            VirtualResourceStore::with_mapping_manager(self.mapping_manager.clone()).with_cidr_overlap_rejection(reject),
+        );
+        self
+    }
+
+    /// Set how long computed aggregate statuses are reused
+    pub fn with_status_ttl(mut self, ttl: Duration) -> Self {
+        self.status_cache = Cache::new(ttl);
+        self
+    }
+
     /// Create a virtual site and optionally map it to physical NetBox sites
+    ///
+    /// If any mapping is rejected the site is not kept.
     pub fn create_virtual_site(
         &self,
         name: String,
         tenant_id: String,
         physical_site_ids: Vec<i32>,
-    ) -> VirtualSite {
+        mapping_type: MappingType,
+    ) -> Result<VirtualSite, MappingError> {
         let id = uuid::Uuid::new_v4().to_string();
         let virtual_site = self.store.create_virtual_site(id.clone(), name, tenant_id.clone());
 
         // Create mappings to physical sites
         for physical_id in physical_site_ids.iter() {
-            self.mapping_manager.create_mapping(
+            let mapped = self.mapping_manager.create_mapping(
                 id.clone(),
                 VirtualResourceType::Site,
                 *physical_id,
                 VirtualResourceType::Site,
                 tenant_id.clone(),
-                if physical_site_ids.is_empty() || physical_site_ids.len() == 1 {
-                    MappingType::OneToOne
-                } else {
-                    MappingType::OneToMany
-                },
+                mapping_type,
             );
+            if let Err(e) = mapped {
+                let _ = self.store.delete_virtual_site(&id);
+                return Err(e);
+            }
         }
 
-        virtual_site
+        Ok(virtual_site)
     }
 
     /// Get virtual site with its physical mappings
@@ -168,7 +475,7 @@ impl VirtualResourceService {
         virtual_id: &str,
         physical_id: i32,
         tenant_id: &str,
-    ) {
+    ) -> Result<ResourceMapping, MappingError> {
         self.mapping_manager.create_mapping(
             virtual_id.to_string(),
             VirtualResourceType::Site,
@@ -176,36 +483,178 @@ impl VirtualResourceService {
             VirtualResourceType::Site,
             tenant_id.to_string(),
             MappingType::OneToMany,
-        );
+        )
     }
 
     /// Create a virtual device and optionally map it to physical NetBox devices
+    ///
+    /// If any mapping is rejected the device is not kept.
     pub fn create_virtual_device(
         &self,
         name: String,
         tenant_id: String,
         physical_device_ids: Vec<i32>,
-    ) -> VirtualDevice {
+        mapping_type: MappingType,
+    ) -> Result<VirtualDevice, MappingError> {
         let id = uuid::Uuid::new_v4().to_string();
         let virtual_device = self.store.create_virtual_device(id.clone(), name, tenant_id.clone());
 
         // Create mappings to physical devices
         for physical_id in physical_device_ids.iter() {
-            self.mapping_manager.create_mapping(
+            let mapped = self.mapping_manager.create_mapping(
                 id.clone(),
                 VirtualResourceType::Device,
                 *physical_id,
                 VirtualResourceType::Device,
                 tenant_id.clone(),
-                if physical_device_ids.is_empty() || physical_device_ids.len() == 1 {
-                    MappingType::OneToOne
-                } else {
-                    MappingType::OneToMany
-                },
+                mapping_type,
             );
+            if let Err(e) = mapped {
+                let _ = self.store.delete_virtual_device(&id);
+                return Err(e);
+            }
         }
 
-        virtual_device
+        Ok(virtual_device)
+    }
+
+    /// Save a new virtual resource and map it to the given physical resources
+    ///
+    /// The caller is responsible for checking the tenant may use those physical resources.
+    /// If any mapping is rejected the resource is not kept.
+    pub fn add_virtual_resource(&self, resource: VirtualResource, physical_ids: &[i32]) -> Result<(), VirtualResourceError> {
+        let virtual_id = resource.as_resource().id().to_string();
+        self.store.insert_virtual_resource(resource.clone())?;
+        for physical_id in physical_ids {
+            if let Err(e) = self.map_virtual_to_physical(&resource, *physical_id) {
+                let _ = self.store.delete_virtual_resource(&virtual_id);
+                return Err(e.into());
+            }
+        }
+        tracing::debug!("Created virtual resource {} with {} mappings", virtual_id, physical_ids.len());
+        Ok(())
+    }
+
+    /// Get a virtual resource of any type by ID
+    pub fn get_virtual_resource(&self, virtual_id: &str) -> Option<VirtualResource> {
+        self.store.get_virtual_resource(virtual_id)
+    }
+
+    /// All of a tenant's virtual sites, devices and networks
+    pub fn get_tenant_virtual_resources(&self, tenant_id: &str) -> Vec<VirtualResource> {
+        let mut resources: Vec<VirtualResource> = Vec::new();
+        resources.extend(self.store.get_tenant_virtual_sites(tenant_id).into_iter().map(VirtualResource::Site));
+        resources.extend(self.store.get_tenant_virtual_devices(tenant_id).into_iter().map(VirtualResource::Device));
+        resources.extend(self.store.get_tenant_virtual_networks(tenant_id).into_iter().map(VirtualResource::Network));
+        resources
+    }
+
+    /// Map a virtual resource to a physical NetBox object of its [`VirtualResource::physical_type`]
+    ///
+    /// These are ManyToMany mappings, so several virtual resources may share a NetBox object.
+    pub fn map_virtual_to_physical(
+        &self,
+        resource: &VirtualResource,
+        physical_id: i32,
+    ) -> Result<ResourceMapping, MappingError> {
+        let resource_ref = resource.as_resource();
+        self.mapping_manager.create_mapping(
+            resource_ref.id().to_string(),
+            resource_ref.resource_type(),
+            physical_id,
+            resource.physical_type(),
+            resource_ref.tenant_id().to_string(),
+            MappingType::ManyToMany,
+        )
+    }
+
+    /// Remove one mapping, returning whether it existed
+    pub fn unmap_virtual_from_physical(&self, virtual_id: &str, physical_id: i32) -> bool {
+        self.mapping_manager.remove_mapping(virtual_id, physical_id).is_ok()
+    }
+
+    /// Delete a virtual resource and its mappings
+    pub fn delete_virtual_resource(&self, virtual_id: &str) -> Result<VirtualResource, VirtualResourceError> {
+        self.store.delete_virtual_resource(virtual_id)
+    }
+
+    /// Aggregate status of a virtual site from the status of its NetBox sites
+    pub async fn compute_virtual_site_status(
+        &self,
+        virtual_id: &str,
+        client: &TenantAwareNetBoxClient,
+    ) -> Result<VirtualResourceStatus, VirtualResourceError> {
+        let resource = self
+            .store
+            .get_virtual_site(virtual_id)
+            .map(VirtualResource::Site)
+            .ok_or_else(|| VirtualResourceError::NotFound(virtual_id.to_string()))?;
+        Ok(self.compute_status(&resource, client).await)
+    }
+
+    /// Aggregate status of a virtual device from the status of its NetBox devices
+    pub async fn compute_virtual_device_status(
+        &self,
+        virtual_id: &str,
+        client: &TenantAwareNetBoxClient,
+    ) -> Result<VirtualResourceStatus, VirtualResourceError> {
+        let resource = self
+            .store
+            .get_virtual_device(virtual_id)
+            .map(VirtualResource::Device)
+            .ok_or_else(|| VirtualResourceError::NotFound(virtual_id.to_string()))?;
+        Ok(self.compute_status(&resource, client).await)
+    }
+
+    /// Aggregate status of any virtual resource, checking the NetBox objects it maps to
+    ///
+    /// Objects NetBox can't return, or that the tenant can't access, count as Unknown.
+    pub async fn compute_status(
+        &self,
+        resource: &VirtualResource,
+        client: &TenantAwareNetBoxClient,
+    ) -> VirtualResourceStatus {
+        let resource_ref = resource.as_resource();
+        let virtual_id = resource_ref.id().to_string();
+        let tenant_id = resource_ref.tenant_id().to_string();
+        let mut physical_ids: Vec<i32> = self
+            .mapping_manager
+            .get_physical_resources(&virtual_id)
+            .iter()
+            .map(|m| m.physical_id)
+            .collect();
+        physical_ids.sort_unstable();
+
+        let cache_key = (virtual_id.clone(), physical_ids.clone());
+        if let Some(status) = self.status_cache.get(&cache_key).await {
+            return status;
+        }
+
+        let mut physical = Vec::with_capacity(physical_ids.len());
+        for physical_id in physical_ids {
+            let status = match resource.physical_type() {
+                VirtualResourceType::Device => client
+                    .get_device(&tenant_id, physical_id)
+                    .await
+                    .map(|device| PhysicalStatus::from_device_status(physical_id, device.status.as_ref())),
+                VirtualResourceType::VirtualMachine => client
+                    .get_virtual_machine(&tenant_id, physical_id)
+                    .await
+                    .map(|vm| PhysicalStatus::from_virtual_machine_status(physical_id, vm.status.as_ref())),
+                _ => client
+                    .get_site(&tenant_id, physical_id)
+                    .await
+                    .map(|site| PhysicalStatus::from_site_status(physical_id, site.status.as_ref())),
+            };
+            physical.push(status.unwrap_or_else(|e| {
+                tracing::warn!("Could not check physical resource {} of {}: {}", physical_id, virtual_id, e);
+                PhysicalStatus::unreachable(physical_id, e.to_string())
+            }));
+        }
+
+        let status = VirtualResourceStatus::new(virtual_id, physical);
+        self.status_cache.put(cache_key, status.clone()).await;
+        status
     }
 
     /// Get all resources (virtual and physical) for a tenant using the Resource trait
@@ -240,6 +689,11 @@ impl VirtualResourceService {
         Box::new(NetBoxDeviceAdapter::new(device, tenant_id))
     }
 
+    /// Get virtual resource store reference
+    pub fn store(&self) -> &Arc<VirtualResourceStore> {
+        &self.store
+    }
+
     /// Get mapping manager reference
     pub fn mapping_manager(&self) -> &Arc<MappingManager> {
         &self.mapping_manager
@@ -255,6 +709,7 @@ impl Default for VirtualResourceService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::r#virtual::status::AggregateStatus;
 
     #[test]
     fn test_create_virtual_site() {
@@ -263,7 +718,8 @@ mod tests {
             "Test Site".to_string(),
             "tenant-1".to_string(),
             vec![123, 456],
-        );
+            MappingType::OneToMany,
+        ).unwrap();
 
         assert_eq!(virtual_site.name, "Test Site");
         assert_eq!(virtual_site.tenant_id, "tenant-1");
@@ -277,7 +733,8 @@ mod tests {
             "Test Site".to_string(),
             "tenant-1".to_string(),
             vec![123, 456],
-        );
+            MappingType::OneToMany,
+        ).unwrap();
 
         let (site, physical_ids) = service
             .get_virtual_site_with_mappings(&virtual_site.id)
@@ -296,7 +753,8 @@ mod tests {
             "Test Site".to_string(),
             "tenant-1".to_string(),
             vec![123],
-        );
+            MappingType::OneToMany,
+        ).unwrap();
 
         let physical_ids = service.get_physical_sites_for_virtual(&virtual_site.id);
         assert_eq!(physical_ids, vec![123]);
@@ -309,9 +767,10 @@ mod tests {
             "Test Site".to_string(),
             "tenant-1".to_string(),
             vec![],
-        );
+            MappingType::OneToMany,
+        ).unwrap();
 
-        service.map_virtual_to_physical_site(&virtual_site.id, 789, "tenant-1");
+        service.map_virtual_to_physical_site(&virtual_site.id, 789, "tenant-1").unwrap();
 
         let physical_ids = service.get_physical_sites_for_virtual(&virtual_site.id);
         assert_eq!(physical_ids, vec![789]);
@@ -324,7 +783,8 @@ mod tests {
             "Test Device".to_string(),
             "tenant-1".to_string(),
             vec![100],
-        );
+            MappingType::OneToMany,
+        ).unwrap();
 
         assert_eq!(virtual_device.name, "Test Device");
         assert_eq!(virtual_device.tenant_id, "tenant-1");
@@ -351,9 +811,24 @@ mod tests {
     #[test]
     fn test_get_all_resources_for_tenant() {
         let service = VirtualResourceService::new();
-        service.create_virtual_site("Site 1".to_string(), "tenant-1".to_string(), vec![]);
-        service.create_virtual_device("Device 1".to_string(), "tenant-1".to_string(), vec![]);
-        service.create_virtual_site("Site 2".to_string(), "tenant-2".to_string(), vec![]);
+        service.create_virtual_site(
+            "Site 1".to_string(),
+            "tenant-1".to_string(),
+            vec![],
+            MappingType::OneToMany,
+        ).unwrap();
+        service.create_virtual_device(
+            "Device 1".to_string(),
+            "tenant-1".to_string(),
+            vec![],
+            MappingType::OneToMany,
+        ).unwrap();
+        service.create_virtual_site(
+            "Site 2".to_string(),
+            "tenant-2".to_string(),
+            vec![],
+            MappingType::OneToMany,
+        ).unwrap();
 
         let resources = service.get_all_resources_for_tenant("tenant-1");
         assert_eq!(resources.len(), 2);
@@ -418,7 +893,8 @@ mod tests {
             "Test Site".to_string(),
             "tenant-1".to_string(),
             vec![],
-        );
+            MappingType::OneToMany,
+        ).unwrap();
 
         assert_eq!(virtual_site.name, "Test Site");
         let physical_ids = service.get_physical_sites_for_virtual(&virtual_site.id);
@@ -432,7 +908,8 @@ mod tests {
             "Test Device".to_string(),
             "tenant-1".to_string(),
             vec![],
-        );
+            MappingType::OneToMany,
+        ).unwrap();
 
         assert_eq!(virtual_device.name, "Test Device");
     }
@@ -451,12 +928,14 @@ mod tests {
             "Site 1".to_string(),
             "tenant-1".to_string(),
             vec![123],
-        );
+            MappingType::ManyToMany,
+        ).unwrap();
         let virtual_site2 = service.create_virtual_site(
             "Site 2".to_string(),
             "tenant-1".to_string(),
             vec![123],
-        );
+            MappingType::ManyToMany,
+        ).unwrap();
 
         let virtual_ids = service.get_virtual_sites_for_physical(123);
         assert_eq!(virtual_ids.len(), 2);
@@ -478,7 +957,8 @@ mod tests {
             "Test Site".to_string(),
             "tenant-1".to_string(),
             vec![123],
-        );
+            MappingType::OneToMany,
+        ).unwrap();
 
         let physical_ids = service.get_physical_sites_for_virtual(&virtual_site.id);
         assert_eq!(physical_ids.len(), 1);
@@ -492,7 +972,8 @@ mod tests {
             "Test Device".to_string(),
             "tenant-1".to_string(),
             vec![100],
-        );
+            MappingType::OneToMany,
+        ).unwrap();
 
         assert_eq!(virtual_device.name, "Test Device");
     }
@@ -504,11 +985,37 @@ mod tests {
             "Test Device".to_string(),
             "tenant-1".to_string(),
             vec![100, 200, 300],
-        );
+            MappingType::OneToMany,
+        ).unwrap();
 
         assert_eq!(virtual_device.name, "Test Device");
     }
 
+    #[test]
+    fn test_virtual_resource_lifecycle() {
+        let service = VirtualResourceService::new();
+        let network = VirtualNetwork::new("vn-1".to_string(), "Backbone".to_string(), "tenant-1".to_string());
+        service.add_virtual_resource(VirtualResource::Network(network), &[10, 11]).unwrap();
+
+        let resource = service.get_virtual_resource("vn-1").unwrap();
+        assert!(matches!(resource, VirtualResource::Network(_)));
+        assert_eq!(service.get_tenant_virtual_resources("tenant-1").len(), 1);
+        assert!(service.get_tenant_virtual_resources("tenant-2").is_empty());
+        let mappings = service.mapping_manager().get_physical_resources("vn-1");
+        assert_eq!(mappings.len(), 2);
+        assert_eq!(mappings[0].physical_type, VirtualResourceType::Site);
+
+        assert!(service.unmap_virtual_from_physical("vn-1", 10));
+        assert!(!service.unmap_virtual_from_physical("vn-1", 10));
+        assert!(service.delete_virtual_resource("vn-1").is_ok());
+        assert!(service.get_virtual_resource("vn-1").is_none());
+        assert!(!service.mapping_manager().has_virtual_mapping(11));
+        assert_eq!(
+            service.delete_virtual_resource("vn-1").unwrap_err(),
+            VirtualResourceError::NotFound("vn-1".to_string())
+        );
+    }
+
     #[test]
     fn test_get_all_resources_includes_networks() {
         let service = VirtualResourceService::new();
@@ -550,7 +1057,7 @@ mod tests {
             VirtualResourceType::Site,
             "tenant-1".to_string(),
             MappingType::OneToOne,
-        );
+        ).unwrap();
 
         assert!(manager.has_physical_mapping("vs-1"));
     }
@@ -587,15 +1094,359 @@ mod tests {
             "Test Site".to_string(),
             "tenant-1".to_string(),
             vec![],
-        );
+            MappingType::OneToMany,
+        ).unwrap();
 
-        service.map_virtual_to_physical_site(&virtual_site.id, 100, "tenant-1");
-        service.map_virtual_to_physical_site(&virtual_site.id, 200, "tenant-1");
+        service.map_virtual_to_physical_site(&virtual_site.id, 100, "tenant-1").unwrap();
+        service.map_virtual_to_physical_site(&virtual_site.id, 200, "tenant-1").unwrap();
 
         let physical_ids = service.get_physical_sites_for_virtual(&virtual_site.id);
         assert_eq!(physical_ids.len(), 2);
         assert!(physical_ids.contains(&100));
         assert!(physical_ids.contains(&200));
     }
-}
 
+    #[test]
+    fn test_update_virtual_site() {
+        let store = VirtualResourceStore::new();
+        let site = store.create_virtual_site("vs-1".to_string(), "Campus".to_string(), "tenant-1".to_string());
+
+        let updated = store
+            .update_virtual_site(
+                "vs-1",
+                VirtualResourceUpdate {
+                    name: Some("Campus West".to_string()),
+                    tags: Some(vec!["prod".to_string()]),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(updated.name, "Campus West");
+        assert_eq!(updated.tags, vec!["prod".to_string()]);
+        assert_eq!(updated.description, None);
+        assert!(updated.updated_at >= site.updated_at);
+        assert_eq!(store.get_virtual_site("vs-1").unwrap().name, "Campus West");
+    }
+
+    #[test]
+    fn test_update_missing_resource_is_not_found() {
+        let store = VirtualResourceStore::new();
+        store.create_virtual_site("vs-1".to_string(), "Campus".to_string(), "tenant-1".to_string());
+
+        // A site ID is not a device or network ID
+        assert!(matches!(
+            store.update_virtual_device("vs-1", VirtualResourceUpdate::default()),
+            Err(VirtualResourceError::NotFound(_))
+        ));
+        assert!(matches!(
+            store.update_virtual_network("vn-1", VirtualResourceUpdate::default()),
+            Err(VirtualResourceError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_delete_virtual_device_removes_mappings() {
+        let service = VirtualResourceService::new();
+        let device = service.create_virtual_device(
+            "Router".to_string(),
+            "tenant-1".to_string(),
+            vec![7, 8],
+            MappingType::OneToMany,
+        ).unwrap();
+        assert_eq!(service.mapping_manager().get_tenant_mappings("tenant-1").len(), 2);
+
+        service.store.delete_virtual_device(&device.id).unwrap();
+
+        assert!(service.store.get_virtual_device(&device.id).is_none());
+        assert!(!service.mapping_manager().has_physical_mapping(&device.id));
+        assert!(!service.mapping_manager().has_virtual_mapping(7));
+        assert!(service.mapping_manager().get_tenant_mappings("tenant-1").is_empty());
+    }
+
+    #[test]
+    fn test_delete_missing_resource_is_not_found() {
+        let store = VirtualResourceStore::new();
+        assert_eq!(
+            store.delete_virtual_site("vs-1").unwrap_err(),
+            VirtualResourceError::NotFound("vs-1".to_string())
+        );
+        assert!(store.delete_virtual_device("vd-1").is_err());
+        assert!(store.delete_virtual_network("vn-1").is_err());
+        assert_eq!(VirtualResourceError::NotFound("vn-1".to_string()).to_string(), "Virtual resource vn-1 not found");
+    }
+
+    fn tenant_client(mock_server: &wiremock::MockServer) -> TenantAwareNetBoxClient {
+        use crate::config::Config;
+        use crate::netbox::NetBoxClient;
+        use crate::security::tenant::{TenantAccessControl, TenantMappingService};
+
+        let config = Config {
+            netbox_url: mock_server.uri(),
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let client = Arc::new(NetBoxClient::new(config).unwrap());
+        let mappings = HashMap::from([("tenant-1".to_string(), 10)]);
+        let access_control = Arc::new(TenantAccessControl::new(TenantMappingService::from(mappings)));
+        TenantAwareNetBoxClient::new(client, access_control)
+    }
+
+    async fn mount_physical(mock_server: &wiremock::MockServer, kind: &str, id: i32, tenant: i32, status: &str) {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, ResponseTemplate};
+
+        Mock::given(method("GET"))
+            .and(path(format!("/api/dcim/{}/{}/", kind, id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": id,
+                "name": format!("{} {}", kind, id),
+                "slug": format!("{}-{}", kind, id),
+                "tenant": tenant,
+                "status": status
+            })))
+            .mount(mock_server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_compute_virtual_site_status() {
+        let mock_server = wiremock::MockServer::start().await;
+        mount_physical(&mock_server, "sites", 1, 10, "active").await;
+        mount_physical(&mock_server, "sites", 2, 10, "active").await;
+        mount_physical(&mock_server, "sites", 3, 10, "retired").await;
+        let client = tenant_client(&mock_server);
+        let service = VirtualResourceService::new();
+
+        let healthy = service.create_virtual_site(
+            "Campus".to_string(),
+            "tenant-1".to_string(),
+            vec![1, 2],
+            MappingType::ManyToMany,
+        ).unwrap();
+        let status = service.compute_virtual_site_status(&healthy.id, &client).await.unwrap();
+        assert_eq!(status.status, AggregateStatus::Healthy);
+        assert_eq!(status.physical.len(), 2);
+
+        let degraded = service.create_virtual_site(
+            "Metro".to_string(),
+            "tenant-1".to_string(),
+            vec![1, 3],
+            MappingType::ManyToMany,
+        ).unwrap();
+        let status = service.compute_virtual_site_status(&degraded.id, &client).await.unwrap();
+        assert_eq!(status.status, AggregateStatus::Degraded);
+        let retired = status.physical.iter().find(|p| p.physical_id == 3).unwrap();
+        assert_eq!(retired.netbox_status.as_deref(), Some("retired"));
+        assert_eq!(retired.health, AggregateStatus::Down);
+
+        // Not a virtual device
+        assert!(service.compute_virtual_device_status(&healthy.id, &client).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_compute_status_counts_unreachable_physical_as_unknown() {
+        let mock_server = wiremock::MockServer::start().await;
+        mount_physical(&mock_server, "devices", 1, 10, "active").await;
+        // Device 2 belongs to another tenant, device 3 doesn't exist
+        mount_physical(&mock_server, "devices", 2, 20, "active").await;
+        let client = tenant_client(&mock_server);
+        let service = VirtualResourceService::new();
+
+        let device = service.create_virtual_device(
+            "Router".to_string(),
+            "tenant-1".to_string(),
+            vec![1, 2, 3],
+            MappingType::OneToMany,
+        ).unwrap();
+        let status = service.compute_virtual_device_status(&device.id, &client).await.unwrap();
+
+        assert_eq!(status.status, AggregateStatus::Degraded);
+        let unknown: Vec<_> = status
+            .physical
+            .iter()
+            .filter(|p| p.health == AggregateStatus::Unknown)
+            .collect();
+        assert_eq!(unknown.len(), 2);
+        assert!(unknown.iter().all(|p| p.error.is_some()));
+    }
+
+    #[tokio::test]
+    async fn test_compute_status_is_cached_until_mappings_change() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, ResponseTemplate};
+
+        let mock_server = wiremock::MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/1/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": 1, "name": "Site 1", "slug": "site-1", "tenant": 10, "status": "active"
+            })))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        mount_physical(&mock_server, "sites", 2, 10, "planned").await;
+        let client = tenant_client(&mock_server);
+        let service = VirtualResourceService::new();
+        let site = service.create_virtual_site(
+            "Campus".to_string(),
+            "tenant-1".to_string(),
+            vec![1],
+            MappingType::OneToMany,
+        ).unwrap();
+
+        let first = service.compute_virtual_site_status(&site.id, &client).await.unwrap();
+        let second = service.compute_virtual_site_status(&site.id, &client).await.unwrap();
+        assert_eq!(first, second);
+
+        service.map_virtual_to_physical_site(&site.id, 2, "tenant-1").unwrap();
+        let remapped = service.compute_virtual_site_status(&site.id, &client).await.unwrap();
+        assert_eq!(remapped.status, AggregateStatus::Degraded);
+    }
+
+    #[test]
+    fn test_create_virtual_site_rejected_mapping_is_not_kept() {
+        let service = VirtualResourceService::new();
+
+        let result = service.create_virtual_site(
+            "Campus".to_string(),
+            "tenant-1".to_string(),
+            vec![1, 2],
+            MappingType::OneToOne,
+        );
+
+        assert!(matches!(result, Err(MappingError::InvalidMapping(_))));
+        assert!(service.store.get_tenant_virtual_sites("tenant-1").is_empty());
+        assert!(!service.mapping_manager().has_virtual_mapping(1));
+        assert!(service.mapping_manager().get_tenant_mappings("tenant-1").is_empty());
+    }
+
+    fn insert_network(
+        store: &VirtualResourceStore,
+        id: &str,
+        tenant_id: &str,
+        cidr: &str,
+    ) -> Result<(), VirtualResourceError> {
+        let mut network = VirtualNetwork::new(id.to_string(), id.to_string(), tenant_id.to_string());
+        network.cidr = Some(cidr.to_string());
+        store.insert_virtual_resource(VirtualResource::Network(network))
+    }
+
+    #[test]
+    fn test_find_overlapping_networks() {
+        let store = VirtualResourceStore::new();
+        insert_network(&store, "vn-1", "tenant-1", "10.0.0.0/16").unwrap();
+        insert_network(&store, "vn-2", "tenant-1", "10.1.0.0/24").unwrap();
+        insert_network(&store, "vn-3", "tenant-2", "10.0.0.0/16").unwrap();
+        insert_network(&store, "vn-4", "tenant-1", "2001:db8::/48").unwrap();
+
+        let ids = |cidr: &str| -> Vec<String> {
+            store
+                .find_overlapping_networks("tenant-1", cidr)
+                .unwrap()
+                .into_iter()
+                .map(|n| n.id)
+                .collect()
+        };
+        assert_eq!(ids("10.0.0.0/16"), vec!["vn-1"]);
+        assert_eq!(ids("10.0.3.0/24"), vec!["vn-1"]);
+        assert_eq!(ids("10.0.0.0/8"), vec!["vn-1", "vn-2"]);
+        assert!(ids("10.1.1.0/24").is_empty());
+        assert_eq!(ids("2001:db8:0:1::/64"), vec!["vn-4"]);
+        assert!(matches!(
+            store.find_overlapping_networks("tenant-1", "10.0.0.0/40"),
+            Err(VirtualResourceError::InvalidCidr(_))
+        ));
+    }
+
+    #[test]
+    fn test_network_cidr_is_validated() {
+        let store = VirtualResourceStore::new();
+        assert!(matches!(
+            insert_network(&store, "vn-1", "tenant-1", "10.0.0.1/24"),
+            Err(VirtualResourceError::InvalidCidr(_))
+        ));
+        assert!(store.get_virtual_network("vn-1").is_none());
+
+        insert_network(&store, "vn-1", "tenant-1", " 10.0.0.0/24").unwrap();
+        assert_eq!(store.get_virtual_network("vn-1").unwrap().cidr.as_deref(), Some("10.0.0.0/24"));
+        // Overlaps are allowed unless rejection is enabled
+        insert_network(&store, "vn-2", "tenant-1", "10.0.0.0/24").unwrap();
+
+        let update = |cidr: &str| VirtualResourceUpdate {
+            cidr: Some(cidr.to_string()),
+            ..Default::default()
+        };
+        assert!(store.update_virtual_network("vn-1", update("bogus")).is_err());
+        let updated = store.update_virtual_network("vn-1", update("2001:db8::/64")).unwrap();
+        assert_eq!(updated.cidr.as_deref(), Some("2001:db8::/64"));
+    }
+
+    #[test]
+    fn test_cidr_overlap_rejection() {
+        let store = VirtualResourceStore::new().with_cidr_overlap_rejection(true);
+        insert_network(&store, "vn-1", "tenant-1", "10.0.0.0/16").unwrap();
+
+        match insert_network(&store, "vn-2", "tenant-1", "10.0.128.0/17") {
+            Err(VirtualResourceError::CidrOverlap { cidr, networks }) => {
+                assert_eq!(cidr, "10.0.128.0/17");
+                assert_eq!(networks, vec!["vn-1".to_string()]);
+            }
+            other => panic!("Expected CidrOverlap, got {:?}", other),
+        }
+        // Adjacent networks and other tenants don't conflict
+        insert_network(&store, "vn-2", "tenant-1", "10.1.0.0/16").unwrap();
+        insert_network(&store, "vn-3", "tenant-2", "10.0.0.0/16").unwrap();
+        // Nor does a network updating its own CIDR
+        let update = VirtualResourceUpdate {
+            cidr: Some("10.0.0.0/15".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            store.update_virtual_network("vn-1", update),
+            Err(VirtualResourceError::CidrOverlap { .. })
+        ));
+        let update = VirtualResourceUpdate {
+            cidr: Some("10.0.0.0/17".to_string()),
+            ..Default::default()
+        };
+        store.update_virtual_network("vn-1", update).unwrap();
+    }
+
+    #[test]
+    fn test_allocate_subnet() {
+        let store = VirtualResourceStore::new().with_cidr_overlap_rejection(true);
+        insert_network(&store, "vn-1", "tenant-1", "10.0.0.0/23").unwrap();
+
+        assert_eq!(store.allocate_subnet("vn-1", 24).unwrap(), "10.0.0.0/24");
+        assert_eq!(store.allocate_subnet("vn-1", 25).unwrap(), "10.0.1.0/25");
+        assert_eq!(
+            store.get_virtual_network("vn-1").unwrap().metadata[ALLOCATED_SUBNETS_KEY],
+            "10.0.0.0/24,10.0.1.0/25"
+        );
+        // An allocated subnet can become its own network despite overlap rejection
+        insert_network(&store, "vn-2", "tenant-1", "10.0.0.0/24").unwrap();
+
+        assert_eq!(store.allocate_subnet("vn-1", 25).unwrap(), "10.0.1.128/25");
+        assert_eq!(
+            store.allocate_subnet("vn-1", 25).unwrap_err(),
+            VirtualResourceError::SubnetExhausted {
+                network_id: "vn-1".to_string(),
+                prefix_len: 25
+            }
+        );
+        assert!(matches!(store.allocate_subnet("vn-1", 22), Err(VirtualResourceError::InvalidCidr(_))));
+        assert!(matches!(store.allocate_subnet("vn-9", 24), Err(VirtualResourceError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_allocate_ipv6_subnet() {
+        let store = VirtualResourceStore::new();
+        insert_network(&store, "vn-1", "tenant-1", "2001:db8::/48").unwrap();
+        store.create_virtual_network("vn-2".to_string(), "No CIDR".to_string(), "tenant-1".to_string());
+
+        assert_eq!(store.allocate_subnet("vn-1", 64).unwrap(), "2001:db8::/64");
+        assert_eq!(store.allocate_subnet("vn-1", 64).unwrap(), "2001:db8:0:1::/64");
+        assert!(matches!(store.allocate_subnet("vn-2", 64), Err(VirtualResourceError::InvalidCidr(_))));
+    }
+}
@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+
+use crate::netbox::models::{DeviceStatus, SiteStatus, VirtualMachineStatus};
+
+/// Health of a virtual resource derived from the NetBox objects it maps to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AggregateStatus {
+    /// Every physical resource is active
+    Healthy,
+    /// Some physical resources are not in service, or could not be checked
+    Degraded,
+    /// No physical resource is in service
+    Down,
+    /// No physical resources, or none could be checked
+    Unknown,
+}
+
+impl AggregateStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AggregateStatus::Healthy => "healthy",
+            AggregateStatus::Degraded => "degraded",
+            AggregateStatus::Down => "down",
+            AggregateStatus::Unknown => "unknown",
+        }
+    }
+
+    /// Combine the health of each physical resource into one status
+    pub fn combine(statuses: impl IntoIterator<Item = AggregateStatus>) -> Self {
+        let statuses: Vec<_> = statuses.into_iter().collect();
+        let all = |status: AggregateStatus| statuses.iter().all(|s| *s == status);
+
+        if statuses.is_empty() || all(AggregateStatus::Unknown) {
+            AggregateStatus::Unknown
+        } else if all(AggregateStatus::Healthy) {
+            AggregateStatus::Healthy
+        } else if all(AggregateStatus::Down) {
+            AggregateStatus::Down
+        } else {
+            AggregateStatus::Degraded
+        }
+    }
+}
+
+impl From<Option<&SiteStatus>> for AggregateStatus {
+    fn from(status: Option<&SiteStatus>) -> Self {
+        match status {
+            Some(SiteStatus::Active) => AggregateStatus::Healthy,
+            Some(SiteStatus::Planned) | Some(SiteStatus::Staging) => AggregateStatus::Degraded,
+            Some(SiteStatus::Retired) => AggregateStatus::Down,
+            None => AggregateStatus::Unknown,
+        }
+    }
+}
+
+impl From<Option<&DeviceStatus>> for AggregateStatus {
+    fn from(status: Option<&DeviceStatus>) -> Self {
+        match status {
+            Some(DeviceStatus::Active) => AggregateStatus::Healthy,
+            Some(DeviceStatus::Planned)
+            | Some(DeviceStatus::Staged)
+            | Some(DeviceStatus::Inventory)
+            | Some(DeviceStatus::Decommissioning) => AggregateStatus::Degraded,
+            Some(DeviceStatus::Offline) | Some(DeviceStatus::Failed) => AggregateStatus::Down,
+            None => AggregateStatus::Unknown,
+        }
+    }
+}
+
+impl From<Option<&VirtualMachineStatus>> for AggregateStatus {
+    fn from(status: Option<&VirtualMachineStatus>) -> Self {
+        match status {
+            Some(VirtualMachineStatus::Active) => AggregateStatus::Healthy,
+            Some(VirtualMachineStatus::Planned)
+            | Some(VirtualMachineStatus::Staged)
+            | Some(VirtualMachineStatus::Decommissioning) => AggregateStatus::Degraded,
+            Some(VirtualMachineStatus::Offline) | Some(VirtualMachineStatus::Failed) => AggregateStatus::Down,
+            None => AggregateStatus::Unknown,
+        }
+    }
+}
+
+/// Status of one mapped NetBox object
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhysicalStatus {
+    pub physical_id: i32,
+    /// NetBox status value, e.g. `active`; None when unset or unreachable
+    pub netbox_status: Option<String>,
+    pub health: AggregateStatus,
+    /// Why the object couldn't be checked
+    pub error: Option<String>,
+}
+
+impl PhysicalStatus {
+    pub fn from_site_status(physical_id: i32, status: Option<&SiteStatus>) -> Self {
+        Self {
+            physical_id,
+            netbox_status: status.map(status_value),
+            health: status.into(),
+            error: None,
+        }
+    }
+
+    pub fn from_device_status(physical_id: i32, status: Option<&DeviceStatus>) -> Self {
+        Self {
+            physical_id,
+            netbox_status: status.map(status_value),
+            health: status.into(),
+            error: None,
+        }
+    }
+
+    pub fn from_virtual_machine_status(physical_id: i32, status: Option<&VirtualMachineStatus>) -> Self {
+        Self {
+            physical_id,
+            netbox_status: status.map(status_value),
+            health: status.into(),
+            error: None,
+        }
+    }
+
+    /// A physical resource NetBox couldn't return
+    pub fn unreachable(physical_id: i32, error: String) -> Self {
+        Self {
+            physical_id,
+            netbox_status: None,
+            health: AggregateStatus::Unknown,
+            error: Some(error),
+        }
+    }
+}
+
+/// Aggregate status of a virtual resource with per-physical detail
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VirtualResourceStatus {
+    pub virtual_id: String,
+    pub status: AggregateStatus,
+    pub physical: Vec<PhysicalStatus>,
+    pub computed_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl VirtualResourceStatus {
+    pub fn new(virtual_id: String, physical: Vec<PhysicalStatus>) -> Self {
+        Self {
+            virtual_id,
+            status: AggregateStatus::combine(physical.iter().map(|p| p.health)),
+            physical,
+            computed_at: chrono::Utc::now(),
+        }
+    }
+}
+
+/// The serialized (lowercase) form of a NetBox status enum
+fn status_value<T: Serialize>(status: &T) -> String {
+    serde_json::to_value(status)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combine_statuses() {
+        use AggregateStatus::*;
+
+        assert_eq!(AggregateStatus::combine([]), Unknown);
+        assert_eq!(AggregateStatus::combine([Healthy, Healthy, Healthy]), Healthy);
+        assert_eq!(AggregateStatus::combine([Healthy, Down, Healthy]), Degraded);
+        assert_eq!(AggregateStatus::combine([Healthy, Unknown]), Degraded);
+        assert_eq!(AggregateStatus::combine([Down, Down]), Down);
+        assert_eq!(AggregateStatus::combine([Unknown, Unknown]), Unknown);
+    }
+
+    #[test]
+    fn test_physical_status_from_netbox_status() {
+        let site = PhysicalStatus::from_site_status(1, Some(&SiteStatus::Retired));
+        assert_eq!(site.netbox_status.as_deref(), Some("retired"));
+        assert_eq!(site.health, AggregateStatus::Down);
+
+        let device = PhysicalStatus::from_device_status(2, Some(&DeviceStatus::Staged));
+        assert_eq!(device.netbox_status.as_deref(), Some("staged"));
+        assert_eq!(device.health, AggregateStatus::Degraded);
+
+        let vm = PhysicalStatus::from_virtual_machine_status(4, Some(&VirtualMachineStatus::Failed));
+        assert_eq!(vm.netbox_status.as_deref(), Some("failed"));
+        assert_eq!(vm.health, AggregateStatus::Down);
+
+        let unset = PhysicalStatus::from_device_status(3, None);
+        assert_eq!(unset.health, AggregateStatus::Unknown);
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::RwLock;
+use tracing::{info, warn};
+
+use crate::netbox::NetBoxRouter;
+use crate::security::tenant::{TenantId, TenantMappingService};
+
+/// Warm-up run before a replica reports ready, off by default
+#[derive(Debug, Clone)]
+pub struct WarmupConfig {
+    pub enabled: bool,
+    /// Report ready, with a warning, once this passes whether or not warm-up finished
+    pub deadline: Duration,
+    /// Most recently used sites remembered, and prefetched, per tenant
+    pub sites_per_tenant: usize,
+    /// File the recently used sites are kept in across restarts
+    pub hot_sites_file: Option<PathBuf>,
+}
+
+impl Default for WarmupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            deadline: Duration::from_secs(30),
+            sites_per_tenant: 20,
+            hot_sites_file: None,
+        }
+    }
+}
+
+impl WarmupConfig {
+    /// Load from WARMUP_ENABLED, WARMUP_DEADLINE_SECS, WARMUP_SITES_PER_TENANT and WARMUP_HOT_SITES_FILE
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let number = |name: &str| std::env::var(name).ok().and_then(|value| value.parse::<u64>().ok());
+        Self {
+            enabled: std::env::var("WARMUP_ENABLED")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(defaults.enabled),
+            deadline: number("WARMUP_DEADLINE_SECS").map(Duration::from_secs).unwrap_or(defaults.deadline),
+            sites_per_tenant: number("WARMUP_SITES_PER_TENANT")
+                .map(|count| count as usize)
+                .unwrap_or(defaults.sites_per_tenant),
+            hot_sites_file: std::env::var("WARMUP_HOT_SITES_FILE")
+                .ok()
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from),
+        }
+    }
+}
+
+/// The sites each tenant read most recently, newest first
+#[derive(Debug)]
+pub struct HotSites {
+    per_tenant: usize,
+    sites: RwLock<HashMap<TenantId, VecDeque<i32>>>,
+}
+
+impl HotSites {
+    pub fn new(per_tenant: usize) -> Self {
+        Self {
+            per_tenant,
+            sites: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Read a list saved by [`Self::save`]; a missing file is an empty list
+    pub fn load(path: &Path, per_tenant: usize) -> std::io::Result<Self> {
+        let hot = Self::new(per_tenant);
+        let saved: HashMap<TenantId, Vec<i32>> = match std::fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(hot),
+            Err(e) => return Err(e),
+        };
+        *hot.sites.write() = saved
+            .into_iter()
+            .map(|(tenant_id, mut ids)| {
+                ids.truncate(per_tenant);
+                (tenant_id, ids.into())
+            })
+            .collect();
+        Ok(hot)
+    }
+
+    /// Note that `tenant_id` just read `site_id`
+    pub fn record(&self, tenant_id: &TenantId, site_id: i32) {
+        if self.per_tenant == 0 {
+            return;
+        }
+        let mut sites = self.sites.write();
+        let recent = sites.entry(tenant_id.clone()).or_default();
+        recent.retain(|id| *id != site_id);
+        recent.push_front(site_id);
+        recent.truncate(self.per_tenant);
+    }
+
+    /// The tenant's recently read sites, newest first
+    pub fn recent(&self, tenant_id: &TenantId) -> Vec<i32> {
+        self.sites
+            .read()
+            .get(tenant_id)
+            .map(|recent| recent.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Write the list to `path`, replacing it in one rename
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let sites = self.sites.read();
+        let saved: HashMap<&TenantId, Vec<i32>> = sites
+            .iter()
+            .map(|(tenant_id, recent)| (tenant_id, recent.iter().copied().collect()))
+            .collect();
+        let bytes = serde_json::to_vec(&saved)?;
+        drop(sites);
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(&tmp, path)
+    }
+}
+
+/// Whether the replica is ready to take traffic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Readiness {
+    Warming,
+    /// Ready; `warnings` lists what warm-up couldn't do
+    Ready { warnings: Vec<String> },
+}
+
+/// Readiness shared between warm-up and the readiness endpoint
+#[derive(Debug)]
+pub struct ReadinessGate {
+    state: RwLock<Readiness>,
+}
+
+impl ReadinessGate {
+    /// A gate that is open from the start, for replicas without warm-up
+    pub fn ready() -> Self {
+        Self {
+            state: RwLock::new(Readiness::Ready { warnings: Vec::new() }),
+        }
+    }
+
+    /// A gate that stays closed until [`Self::open`]
+    pub fn warming() -> Self {
+        Self {
+            state: RwLock::new(Readiness::Warming),
+        }
+    }
+
+    pub fn state(&self) -> Readiness {
+        self.state.read().clone()
+    }
+
+    pub fn open(&self, warnings: Vec<String>) {
+        *self.state.write() = Readiness::Ready { warnings };
+    }
+}
+
+/// Primes a cold replica's caches before it reports ready
+///
+/// For each enabled tenant mapping it resolves the tenant's NetBox endpoint,
+/// loads the tenant's site list (priming the degradation cache), then
+/// prefetches the tenant's recently used sites into the response cache.
+pub struct Warmup {
+    router: Arc<NetBoxRouter>,
+    mappings: Arc<TenantMappingService>,
+    hot_sites: Arc<HotSites>,
+    deadline: Duration,
+}
+
+impl Warmup {
+    pub fn new(
+        router: Arc<NetBoxRouter>,
+        mappings: Arc<TenantMappingService>,
+        hot_sites: Arc<HotSites>,
+        deadline: Duration,
+    ) -> Self {
+        Self { router, mappings, hot_sites, deadline }
+    }
+
+    /// Warm up and open `gate`, at the latest once the deadline passes
+    ///
+    /// Failures never keep the gate closed; they are reported as warnings.
+    pub async fn run(&self, gate: &ReadinessGate) {
+        let mut warnings = Vec::new();
+        if tokio::time::timeout(self.deadline, self.warm(&mut warnings)).await.is_err() {
+            warnings.push(format!("Warm-up deadline of {:?} passed before it finished", self.deadline));
+        }
+        for warning in &warnings {
+            warn!("{}", warning);
+        }
+        info!("Warm-up finished with {} warning(s)", warnings.len());
+        gate.open(warnings);
+    }
+
+    async fn warm(&self, warnings: &mut Vec<String>) {
+        for (tenant_id, mapping) in self.mappings.all_mappings() {
+            if mapping.disabled.is_some() {
+                continue;
+            }
+            let endpoint = match self.router.endpoint_for(&tenant_id) {
+                Ok(endpoint) => endpoint,
+                Err(e) => {
+                    warnings.push(format!("Tenant '{}': {}", tenant_id, e));
+                    continue;
+                }
+            };
+            if let Err(e) = endpoint.client.list_sites(Some(mapping.netbox_tenant_id), None, None).await {
+                warnings.push(format!("Tenant '{}': listing sites failed: {}", tenant_id, e));
+                continue;
+            }
+            let recent = self.hot_sites.recent(&tenant_id);
+            if recent.is_empty() {
+                continue;
+            }
+            if let Err(e) = endpoint.cached_client.get_sites_by_ids(&recent).await {
+                warnings.push(format!("Tenant '{}': prefetching sites failed: {}", tenant_id, e));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::netbox::{NetBoxClient, NetBoxEndpoint};
+    use serde_json::json;
+    use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+    fn router(uri: String, mappings: Arc<TenantMappingService>) -> Arc<NetBoxRouter> {
+        let config = Config {
+            netbox_url: uri,
+            netbox_token: "test-token".to_string(),
+            ..Default::default()
+        };
+        let client = Arc::new(NetBoxClient::new(config.clone()).unwrap());
+        Arc::new(NetBoxRouter::new(NetBoxEndpoint::new("default", &config, client), mappings))
+    }
+
+    #[test]
+    fn test_hot_sites_keep_most_recent_and_survive_restart() {
+        let hot = HotSites::new(2);
+        let tenant = "acme".to_string();
+        for id in [1, 2, 1, 3] {
+            hot.record(&tenant, id);
+        }
+        assert_eq!(hot.recent(&tenant), vec![3, 1]);
+
+        let path = std::env::temp_dir().join(format!("netgate-hot-sites-{}.json", uuid::Uuid::new_v4()));
+        hot.save(&path).unwrap();
+        let loaded = HotSites::load(&path, 1).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.recent(&tenant), vec![3]);
+        assert!(HotSites::load(&path, 1).unwrap().recent(&tenant).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_warmup_prefetches_hot_sites() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .and(query_param("tenant_id", "10"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"count": 0, "results": []})))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .and(query_param("id__in", "7"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 1,
+                "results": [{"id": 7, "name": "Hot", "tenant": 10}]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mappings = Arc::new(TenantMappingService::new());
+        mappings.register_mapping("acme".to_string(), 10);
+        let router = router(mock_server.uri(), mappings.clone());
+        let hot = Arc::new(HotSites::new(5));
+        hot.record(&"acme".to_string(), 7);
+
+        let gate = ReadinessGate::warming();
+        Warmup::new(router.clone(), mappings, hot, Duration::from_secs(5)).run(&gate).await;
+        assert_eq!(gate.state(), Readiness::Ready { warnings: Vec::new() });
+        assert_eq!(router.default_endpoint().cached_client.cache_metrics().puts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_warmup_opens_gate_with_warning_at_deadline() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/dcim/sites/"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({"count": 0, "results": []}))
+                    .set_delay(Duration::from_secs(10)),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let mappings = Arc::new(TenantMappingService::new());
+        mappings.register_mapping("acme".to_string(), 10);
+        let warmup = Warmup::new(
+            router(mock_server.uri(), mappings.clone()),
+            mappings,
+            Arc::new(HotSites::new(5)),
+            Duration::from_millis(100),
+        );
+
+        let gate = ReadinessGate::warming();
+        let started = std::time::Instant::now();
+        warmup.run(&gate).await;
+        assert!(started.elapsed() < Duration::from_secs(5));
+        let Readiness::Ready { warnings } = gate.state() else {
+            panic!("Expected the gate to be open");
+        };
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("deadline"));
+    }
+}
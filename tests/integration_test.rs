@@ -1,66 +1,130 @@
-// Integration tests using reqwest to make actual HTTP calls
-// Note: These tests require the server to be running on port 8080
-// For a more complete setup, you could use a test server that starts/stops automatically
-
+// Integration tests making real HTTP calls to an in-process server
+// Each test binds NetGate to a random port, backed by a wiremock NetBox
+
+use netgate::app::{bootstrap, AppState};
+use netgate::config::{Config, NetBoxEndpointConfig};
+use netgate::domain::{CreateSiteOrder, Site};
+use netgate::security::tenant::parse_tenant_mappings;
+use netgate::server::run_with_acceptor;
+use poem::listener::{Acceptor, Listener, TcpListener};
 use serde_json::json;
+use wiremock::matchers::{method, path};
+use wiremock::http::Method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// NetBox stand-in answering the status probe and site creation
+async fn mock_netbox() -> MockServer {
+    let netbox = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/status/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "netbox-version": "4.0.0" })))
+        .mount(&netbox)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/dcim/sites/"))
+        .respond_with(|req: &wiremock::Request| {
+            let body: serde_json::Value = serde_json::from_slice(&req.body).unwrap();
+            ResponseTemplate::new(201).set_body_json(json!({
+                "id": 123,
+                "name": body["name"],
+                "slug": body["slug"],
+            }))
+        })
+        .mount(&netbox)
+        .await;
+    netbox
+}
+
+async fn state_for(netbox: &MockServer) -> AppState {
+    bootstrap(Config {
+        netbox_url: netbox.uri(),
+        netbox_token: "test-token".to_string(),
+        ..Default::default()
+    })
+    .await
+    .unwrap()
+}
+
+/// Serve `state` on a random local port, returning the API's base URL
+async fn start_server(state: AppState) -> String {
+    let acceptor = TcpListener::bind("127.0.0.1:0").into_acceptor().await.unwrap();
+    let addr = *acceptor.local_addr()[0].as_socket_addr().unwrap();
+    tokio::spawn(run_with_acceptor(acceptor, state, std::future::pending()));
+    format!("http://{}/v1", addr)
+}
+
+async fn start_with_netbox() -> (String, MockServer) {
+    let netbox = mock_netbox().await;
+    let base_url = start_server(state_for(&netbox).await).await;
+    (base_url, netbox)
+}
 
-const BASE_URL: &str = "http://localhost:8080";
+fn seed_site(state: &AppState, tenant_id: &str, name: &str) {
+    let order = CreateSiteOrder {
+        name: name.to_string(),
+        description: None,
+        address: None,
+        region: None,
+        facility: None,
+        tags: Vec::new(),
+        custom_fields: Default::default(),
+        priority: Default::default(),
+    };
+    state
+        .tenant_store
+        .add_site(tenant_id.to_string(), Site::from_order(order, tenant_id.to_string()));
+}
 
 #[tokio::test]
-#[ignore] // Ignore by default - run with: cargo test -- --ignored
 async fn test_health_endpoint() {
+    let (base_url, _netbox) = start_with_netbox().await;
     let client = reqwest::Client::new();
-    let resp = client.get(format!("{}/health", BASE_URL)).send().await.unwrap();
-    
+    let resp = client.get(format!("{}/health", base_url)).send().await.unwrap();
+
     // Health endpoint should return 200 or 503 depending on NetBox connectivity
     assert!(resp.status() == 200 || resp.status() == 503);
-    
+
     let body: serde_json::Value = resp.json().await.unwrap();
     assert_eq!(body["service"], "NetGate");
     assert_eq!(body["version"], "1.0.0");
     assert!(body["status"].is_string());
     assert!(body["timestamp"].is_string());
-    
-    // NetBox health should be present (may be connected or disconnected)
-    if let Some(netbox) = body.get("netbox") {
-        assert!(netbox["connected"].is_boolean());
-    }
-    
-    // Circuit breaker health should be present
-    if let Some(cb) = body.get("circuit_breaker") {
-        assert!(cb["state"].is_string());
-        assert!(cb["failure_count"].is_number());
-    }
+
+    // NetBox health is reported since NetBox is configured
+    let netbox = body.get("netbox").expect("netbox health");
+    assert!(netbox["connected"].is_boolean());
+
+    let cb = body.get("circuit_breaker").expect("circuit breaker health");
+    assert!(cb["state"].is_string());
+    assert!(cb["failure_count"].is_number());
 }
 
 #[tokio::test]
-#[ignore]
 async fn test_metrics_endpoint() {
+    let (base_url, _netbox) = start_with_netbox().await;
     let client = reqwest::Client::new();
-    let resp = client.get(format!("{}/metrics", BASE_URL)).send().await.unwrap();
-    
+    let resp = client.get(format!("{}/metrics", base_url)).send().await.unwrap();
+
     assert_eq!(resp.status(), 200);
     let body: serde_json::Value = resp.json().await.unwrap();
-    
+
     assert!(body["timestamp"].is_string());
-    
-    // NetBox metrics should be present
-    if let Some(netbox) = body.get("netbox") {
-        assert!(netbox["total_requests"].is_number());
-        assert!(netbox["successful_requests"].is_number());
-        assert!(netbox["failed_requests"].is_number());
-        assert!(netbox["success_rate"].is_number());
-        assert!(netbox["failure_rate"].is_number());
-        assert!(netbox["average_response_time_ms"].is_number());
-        assert!(netbox["total_retries"].is_number());
-        assert!(netbox["circuit_breaker_rejections"].is_number());
-        assert!(netbox["circuit_breaker_state"].is_string());
-    }
+
+    let netbox = body.get("netbox").expect("netbox metrics");
+    assert!(netbox["total_requests"].is_number());
+    assert!(netbox["successful_requests"].is_number());
+    assert!(netbox["failed_requests"].is_number());
+    assert!(netbox["success_rate"].is_number());
+    assert!(netbox["failure_rate"].is_number());
+    assert!(netbox["average_response_time_ms"].is_number());
+    assert!(netbox["total_retries"].is_number());
+    assert!(netbox["circuit_breaker_rejections"].is_number());
+    assert!(netbox["circuit_breaker_state"].is_string());
 }
 
 #[tokio::test]
-#[ignore]
 async fn test_create_site_success() {
+    let (base_url, _netbox) = start_with_netbox().await;
     let client = reqwest::Client::new();
     let order = json!({
         "name": "Test Site",
@@ -69,7 +133,7 @@ async fn test_create_site_success() {
     });
 
     let resp = client
-        .post(format!("{}/orders/site", BASE_URL))
+        .post(format!("{}/orders/site", base_url))
         .header("X-Tenant-Id", "tenant1")
         .json(&order)
         .send()
@@ -80,19 +144,20 @@ async fn test_create_site_success() {
     let body: serde_json::Value = resp.json().await.unwrap();
     assert_eq!(body["site_name"], "Test Site");
     assert_eq!(body["tenant_id"], "tenant1");
+    assert_eq!(body["netbox_site_id"], 123);
     assert!(body["order_id"].is_string());
 }
 
 #[tokio::test]
-#[ignore]
 async fn test_create_site_missing_header() {
+    let (base_url, _netbox) = start_with_netbox().await;
     let client = reqwest::Client::new();
     let order = json!({
         "name": "Test Site"
     });
 
     let resp = client
-        .post(format!("{}/orders/site", BASE_URL))
+        .post(format!("{}/orders/site", base_url))
         .json(&order)
         .send()
         .await
@@ -102,18 +167,35 @@ async fn test_create_site_missing_header() {
 }
 
 #[tokio::test]
-#[ignore]
-async fn test_get_sites_success() {
+async fn test_create_site_without_netbox_is_unavailable() {
+    let base_url = start_server(bootstrap(Config::default()).await.unwrap()).await;
     let client = reqwest::Client::new();
-    let tenant_id = "tenant2";
 
-    // Note: The new implementation creates sites in NetBox, not in-memory store
-    // These tests may need to be updated based on actual behavior
-    // For now, we'll test the order creation endpoint
+    let resp = client
+        .post(format!("{}/orders/site", base_url))
+        .header("X-Tenant-Id", "tenant1")
+        .json(&json!({ "name": "Test Site" }))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 503);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["error"], "NetBox integration not configured");
+}
+
+#[tokio::test]
+async fn test_get_sites_success() {
+    let netbox = mock_netbox().await;
+    let state = state_for(&netbox).await;
+    let tenant_id = "tenant2";
+    seed_site(&state, tenant_id, "Site 1");
+    seed_site(&state, tenant_id, "Site 2");
+    let base_url = start_server(state).await;
+    let client = reqwest::Client::new();
 
-    // Get sites
     let resp = client
-        .get(format!("{}/tenants/{}/sites", BASE_URL, tenant_id))
+        .get(format!("{}/tenants/{}/sites", base_url, tenant_id))
         .header("X-Tenant-Id", tenant_id)
         .send()
         .await
@@ -129,12 +211,12 @@ async fn test_get_sites_success() {
 }
 
 #[tokio::test]
-#[ignore]
 async fn test_get_sites_missing_header() {
+    let (base_url, _netbox) = start_with_netbox().await;
     let client = reqwest::Client::new();
 
     let resp = client
-        .get(format!("{}/tenants/tenant1/sites", BASE_URL))
+        .get(format!("{}/tenants/tenant1/sites", base_url))
         .send()
         .await
         .unwrap();
@@ -143,12 +225,12 @@ async fn test_get_sites_missing_header() {
 }
 
 #[tokio::test]
-#[ignore]
 async fn test_get_sites_header_mismatch() {
+    let (base_url, _netbox) = start_with_netbox().await;
     let client = reqwest::Client::new();
 
     let resp = client
-        .get(format!("{}/tenants/tenant1/sites", BASE_URL))
+        .get(format!("{}/tenants/tenant1/sites", base_url))
         .header("X-Tenant-Id", "tenant2")
         .send()
         .await
@@ -158,33 +240,17 @@ async fn test_get_sites_header_mismatch() {
 }
 
 #[tokio::test]
-#[ignore]
 async fn test_tenant_isolation() {
+    let netbox = mock_netbox().await;
+    let state = state_for(&netbox).await;
+    seed_site(&state, "tenant1", "Tenant1 Site");
+    seed_site(&state, "tenant2", "Tenant2 Site");
+    let base_url = start_server(state).await;
     let client = reqwest::Client::new();
 
-    // Create site for tenant1
-    let order1 = json!({"name": "Tenant1 Site"});
-    client
-        .post(format!("{}/orders/site", BASE_URL))
-        .header("X-Tenant-Id", "tenant1")
-        .json(&order1)
-        .send()
-        .await
-        .unwrap();
-
-    // Create site for tenant2
-    let order2 = json!({"name": "Tenant2 Site"});
-    client
-        .post(format!("{}/orders/site", BASE_URL))
-        .header("X-Tenant-Id", "tenant2")
-        .json(&order2)
-        .send()
-        .await
-        .unwrap();
-
     // Get sites for tenant1 - should only see tenant1's site
     let resp1 = client
-        .get(format!("{}/tenants/tenant1/sites", BASE_URL))
+        .get(format!("{}/tenants/tenant1/sites", base_url))
         .header("X-Tenant-Id", "tenant1")
         .send()
         .await
@@ -199,7 +265,7 @@ async fn test_tenant_isolation() {
 
     // Get sites for tenant2 - should only see tenant2's site
     let resp2 = client
-        .get(format!("{}/tenants/tenant2/sites", BASE_URL))
+        .get(format!("{}/tenants/tenant2/sites", base_url))
         .header("X-Tenant-Id", "tenant2")
         .send()
         .await
@@ -214,13 +280,13 @@ async fn test_tenant_isolation() {
 }
 
 #[tokio::test]
-#[ignore]
 async fn test_get_sites_empty() {
+    let (base_url, _netbox) = start_with_netbox().await;
     let client = reqwest::Client::new();
     let tenant_id = "empty_tenant";
 
     let resp = client
-        .get(format!("{}/tenants/{}/sites", BASE_URL, tenant_id))
+        .get(format!("{}/tenants/{}/sites", base_url, tenant_id))
         .header("X-Tenant-Id", tenant_id)
         .send()
         .await
@@ -234,8 +300,8 @@ async fn test_get_sites_empty() {
 }
 
 #[tokio::test]
-#[ignore]
 async fn test_create_site_order_end_to_end() {
+    let (base_url, netbox) = start_with_netbox().await;
     let client = reqwest::Client::new();
     let order = json!({
         "name": "End-to-End Test Site",
@@ -244,106 +310,214 @@ async fn test_create_site_order_end_to_end() {
     });
 
     let resp = client
-        .post(format!("{}/orders/site", BASE_URL))
+        .post(format!("{}/orders/site", base_url))
         .header("X-Tenant-Id", "e2e-tenant")
         .json(&order)
         .send()
         .await
         .unwrap();
 
-    // Should either succeed (if NetBox is available) or fail gracefully
-    assert!(resp.status() == 201 || resp.status() == 500);
-    
-    if resp.status() == 201 {
-        let body: serde_json::Value = resp.json().await.unwrap();
-        assert!(body["order_id"].is_string());
-        assert!(body["tenant_id"] == "e2e-tenant");
-        assert!(body["site_name"] == "End-to-End Test Site");
-        
-        // Test order status endpoint
-        let order_id = body["order_id"].as_str().unwrap();
-        let status_resp = client
-            .get(format!("{}/orders/{}/status", BASE_URL, order_id))
-            .header("X-Tenant-Id", "e2e-tenant")
-            .send()
-            .await
-            .unwrap();
-        
-        assert_eq!(status_resp.status(), 200);
-        let status_body: serde_json::Value = status_resp.json().await.unwrap();
-        assert_eq!(status_body["order_id"], order_id);
-        assert!(status_body["state"].is_string());
-    }
+    assert_eq!(resp.status(), 201);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert!(body["order_id"].is_string());
+    assert_eq!(body["tenant_id"], "e2e-tenant");
+    assert_eq!(body["site_name"], "End-to-End Test Site");
+
+    // The site was created in NetBox
+    let created = netbox
+        .received_requests()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|req| req.method == wiremock::http::Method::Post && req.url.path() == "/api/dcim/sites/")
+        .count();
+    assert_eq!(created, 1);
+
+    // Test order status endpoint
+    let order_id = body["order_id"].as_str().unwrap();
+    let status_resp = client
+        .get(format!("{}/orders/{}/status", base_url, order_id))
+        .header("X-Tenant-Id", "e2e-tenant")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(status_resp.status(), 200);
+    let status_body: serde_json::Value = status_resp.json().await.unwrap();
+    assert_eq!(status_body["order_id"], order_id);
+    assert_eq!(status_body["state"], "Completed");
+    assert_eq!(status_body["netbox_site_id"], 123);
 }
 
 #[tokio::test]
-#[ignore]
 async fn test_order_status_not_found() {
+    let (base_url, _netbox) = start_with_netbox().await;
     let client = reqwest::Client::new();
-    
+
     let resp = client
-        .get(format!("{}/orders/nonexistent-order-id/status", BASE_URL))
+        .get(format!("{}/orders/nonexistent-order-id/status", base_url))
         .header("X-Tenant-Id", "tenant1")
         .send()
         .await
         .unwrap();
-    
+
     assert_eq!(resp.status(), 404);
 }
 
 #[tokio::test]
-#[ignore]
 async fn test_order_status_unauthorized() {
+    let (base_url, _netbox) = start_with_netbox().await;
     let client = reqwest::Client::new();
-    
+
     // First create an order for tenant1
     let order = json!({"name": "Test Site"});
     let create_resp = client
-        .post(format!("{}/orders/site", BASE_URL))
+        .post(format!("{}/orders/site", base_url))
         .header("X-Tenant-Id", "tenant1")
         .json(&order)
         .send()
         .await
         .unwrap();
-    
-    if create_resp.status() == 201 {
-        let body: serde_json::Value = create_resp.json().await.unwrap();
-        let order_id = body["order_id"].as_str().unwrap();
-        
-        // Try to access with tenant2
-        let status_resp = client
-            .get(format!("{}/orders/{}/status", BASE_URL, order_id))
-            .header("X-Tenant-Id", "tenant2")
-            .send()
-            .await
-            .unwrap();
-        
-        assert_eq!(status_resp.status(), 401);
-    }
+
+    assert_eq!(create_resp.status(), 201);
+    let body: serde_json::Value = create_resp.json().await.unwrap();
+    let order_id = body["order_id"].as_str().unwrap();
+
+    // Try to access with tenant2
+    let status_resp = client
+        .get(format!("{}/orders/{}/status", base_url, order_id))
+        .header("X-Tenant-Id", "tenant2")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(status_resp.status(), 401);
 }
 
 #[tokio::test]
-#[ignore]
 async fn test_create_site_validation_error() {
+    let (base_url, _netbox) = start_with_netbox().await;
     let client = reqwest::Client::new();
-    
+
     // Create order with invalid data (empty name)
     let invalid_order = json!({
         "name": "",
         "description": "Invalid order"
     });
-    
+
     let resp = client
-        .post(format!("{}/orders/site", BASE_URL))
+        .post(format!("{}/orders/site", base_url))
         .header("X-Tenant-Id", "tenant1")
         .json(&invalid_order)
         .send()
         .await
         .unwrap();
-    
+
     // Should return validation error
     assert_eq!(resp.status(), 400);
     let body: serde_json::Value = resp.json().await.unwrap();
     assert_eq!(body["error"], "Validation failed");
 }
 
+/// Requests a mock NetBox received for one method and path
+async fn received(netbox: &MockServer, http_method: Method, url_path: &str) -> usize {
+    netbox
+        .received_requests()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|req| req.method == http_method && req.url.path() == url_path)
+        .count()
+}
+
+#[tokio::test]
+async fn test_tenants_are_routed_to_their_netbox_endpoint() {
+    let us = mock_netbox().await;
+    let eu = mock_netbox().await;
+    Mock::given(method("GET"))
+        .and(path("/api/dcim/sites/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "count": 1,
+            "results": [{ "id": 7, "name": "Paris 1", "slug": "paris-1", "tenant": 10 }]
+        })))
+        .mount(&eu)
+        .await;
+
+    let state = bootstrap(Config {
+        netbox_url: us.uri(),
+        netbox_token: "us-token".to_string(),
+        netbox_endpoints: vec![NetBoxEndpointConfig {
+            name: "eu".to_string(),
+            url: eu.uri(),
+            token: "eu-token".to_string(),
+        }],
+        tenant_mappings: parse_tenant_mappings("acme=10@eu,globex=20"),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    let base_url = start_server(state).await;
+    let client = reqwest::Client::new();
+
+    for tenant in ["acme", "globex"] {
+        let resp = client
+            .post(format!("{}/orders/site", base_url))
+            .header("X-Tenant-Id", tenant)
+            .json(&json!({ "name": format!("{} site", tenant) }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 201);
+    }
+    assert_eq!(received(&eu, Method::Post, "/api/dcim/sites/").await, 1);
+    assert_eq!(received(&us, Method::Post, "/api/dcim/sites/").await, 1);
+
+    // Tenant-scoped reads go to the tenant's endpoint as well
+    let resp = client
+        .get(format!("{}/sites", base_url))
+        .header("X-Tenant-Id", "acme")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["results"][0]["name"], "Paris 1");
+    assert_eq!(received(&us, Method::Get, "/api/dcim/sites/").await, 0);
+
+    // Health reports the named endpoint separately
+    let health: serde_json::Value = client
+        .get(format!("{}/health", base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(health["netbox_endpoints"][0]["name"], "eu");
+    assert_eq!(health["netbox_endpoints"][0]["url"], eu.uri());
+    assert_eq!(health["netbox_endpoints"][0]["netbox"]["connected"], true);
+
+    let metrics: serde_json::Value = client
+        .get(format!("{}/metrics", base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(metrics["netbox_endpoints"][0]["name"], "eu");
+    assert!(metrics["netbox_endpoints"][0]["netbox"]["total_requests"].as_u64().unwrap() >= 2);
+}
+
+#[tokio::test]
+async fn test_bootstrap_refuses_tenant_on_unknown_endpoint() {
+    let netbox = mock_netbox().await;
+    let result = bootstrap(Config {
+        netbox_url: netbox.uri(),
+        netbox_token: "test-token".to_string(),
+        tenant_mappings: parse_tenant_mappings("acme=10@eu"),
+        ..Default::default()
+    })
+    .await;
+    assert!(result.is_err());
+}
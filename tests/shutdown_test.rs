@@ -0,0 +1,99 @@
+// Startup/shutdown reconciliation of orders left in Processing
+
+use netgate::app::bootstrap;
+use netgate::business::{save_workflows, JsonFileWorkflowStore, OrderState, WorkflowManager, WorkflowStore};
+use netgate::config::Config;
+use netgate::server::run_with_acceptor;
+use netgate::shutdown::reconcile_interrupted_orders;
+use poem::listener::{Listener, TcpListener};
+use std::path::PathBuf;
+
+fn processing_order(manager: &WorkflowManager, tenant_id: &str) -> String {
+    let order_id = manager.create_order(tenant_id.to_string());
+    manager.update_order_state(&order_id, OrderState::Validated).unwrap();
+    manager.update_order_state(&order_id, OrderState::Processing).unwrap();
+    order_id
+}
+
+#[test]
+fn test_shutdown_reconciliation_flags_processing_orders() {
+    let manager = WorkflowManager::new();
+    let interrupted = processing_order(&manager, "tenant-1");
+    let completed = processing_order(&manager, "tenant-2");
+    manager.mark_order_completed(&completed, 42).unwrap();
+    let pending = manager.create_order("tenant-1".to_string());
+
+    assert_eq!(reconcile_interrupted_orders(&manager), 1);
+
+    let workflow = manager.get_order(&interrupted).unwrap();
+    assert_eq!(workflow.state, OrderState::Processing);
+    assert!(workflow.interrupted_at.is_some());
+    assert!(manager.get_order(&completed).unwrap().interrupted_at.is_none());
+    assert!(manager.get_order(&pending).unwrap().interrupted_at.is_none());
+}
+
+#[test]
+fn test_reconciliation_is_idempotent() {
+    let manager = WorkflowManager::new();
+    let order_id = processing_order(&manager, "tenant-1");
+
+    // The first run flags the order, a second finds nothing new
+    assert_eq!(reconcile_interrupted_orders(&manager), 1);
+    let first_mark = manager.get_order(&order_id).unwrap().interrupted_at;
+    assert_eq!(reconcile_interrupted_orders(&manager), 0);
+
+    assert_eq!(manager.get_order(&order_id).unwrap().interrupted_at, first_mark);
+}
+
+fn store_path() -> PathBuf {
+    std::env::temp_dir().join(format!("netgate-workflows-{}.json", uuid::Uuid::new_v4()))
+}
+
+#[tokio::test]
+async fn test_startup_reconciliation_flags_processing_orders_left_in_the_store() {
+    // What a crash leaves behind: workflows last saved while an order was processing
+    let path = store_path();
+    let previous = WorkflowManager::new();
+    let interrupted = processing_order(&previous, "tenant-1");
+    let pending = previous.create_order("tenant-2".to_string());
+    save_workflows(&previous, &JsonFileWorkflowStore::new(&path)).await.unwrap();
+
+    let state = bootstrap(Config {
+        workflow_store_file: Some(path.clone()),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    let manager = state.workflow_manager.clone();
+    let acceptor = TcpListener::bind("127.0.0.1:0").into_acceptor().await.unwrap();
+    // Start up, then shut down straight away
+    run_with_acceptor(acceptor, state, async {}).await.unwrap();
+
+    let workflow = manager.get_order(&interrupted).unwrap();
+    assert_eq!(workflow.state, OrderState::Processing);
+    let flagged_at = workflow.interrupted_at.expect("flagged at startup");
+    assert!(manager.get_order(&pending).unwrap().interrupted_at.is_none());
+
+    // Shutdown stored the flag for the next start
+    let stored = JsonFileWorkflowStore::new(&path).load().await.unwrap();
+    let stored = stored.iter().find(|workflow| workflow.order_id == interrupted).unwrap();
+    assert_eq!(stored.interrupted_at, Some(flagged_at));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn test_startup_fails_on_an_unreadable_workflow_store() {
+    let path = store_path();
+    std::fs::write(&path, b"{ not workflows").unwrap();
+    let state = bootstrap(Config {
+        workflow_store_file: Some(path.clone()),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    let acceptor = TcpListener::bind("127.0.0.1:0").into_acceptor().await.unwrap();
+
+    let error = run_with_acceptor(acceptor, state, async {}).await.unwrap_err();
+    assert!(error.to_string().contains("Failed to restore order workflows"), "{}", error);
+    let _ = std::fs::remove_file(&path);
+}